@@ -0,0 +1,422 @@
+use crate::{MvtFeature, MvtGeometry, MvtLayer, MvtTile, MvtValue, Point};
+use galileo_types::cartesian::{CartesianClosedContour, CartesianPoint2d, Winding};
+use galileo_types::contour::Contour as _;
+use galileo_types::impls::ClosedContour;
+use geozero::mvt::tile::GeomType;
+use geozero::mvt::Message as GeozeroMessage;
+use geozero::mvt::Tile;
+use std::collections::HashMap;
+
+const MOVE_TO: u32 = 1;
+const LINE_TO: u32 = 2;
+const CLOSE_PATH: u32 = 7;
+
+impl MvtTile {
+    /// Encodes the tile into the protobuf representation of the MVT format.
+    ///
+    /// Coordinates of the features are quantized to the integer grid of the layer `size`. Parts of the geometries
+    /// that become degenerate after quantization (lines with less than 2 points, polygon contours with zero area)
+    /// are skipped, as well as the features left without geometry.
+    pub fn encode(&self) -> Vec<u8> {
+        Tile {
+            layers: self.layers.iter().map(MvtLayer::encode).collect(),
+        }
+        .encode_to_vec()
+    }
+}
+
+impl MvtLayer {
+    fn encode(&self) -> geozero::mvt::tile::Layer {
+        let mut keys = KeyIndex::default();
+        let mut values = ValueIndex::default();
+        let features = self
+            .features
+            .iter()
+            .filter_map(|feature| feature.encode(self.size, &mut keys, &mut values))
+            .collect();
+
+        geozero::mvt::tile::Layer {
+            version: 2,
+            name: self.name.clone(),
+            features,
+            keys: keys.keys,
+            values: values.values,
+            extent: Some(self.size),
+        }
+    }
+}
+
+impl MvtFeature {
+    fn encode(
+        &self,
+        extent: u32,
+        keys: &mut KeyIndex,
+        values: &mut ValueIndex,
+    ) -> Option<geozero::mvt::tile::Feature> {
+        let mut encoder = GeometryEncoder::new(extent);
+        let geom_type = match &self.geometry {
+            MvtGeometry::Point(points) => {
+                encoder.encode_points(points);
+                GeomType::Point
+            }
+            MvtGeometry::LineString(lines) => {
+                for line in lines {
+                    encoder.encode_line(line.iter_points());
+                }
+                GeomType::Linestring
+            }
+            MvtGeometry::Polygon(polygons) => {
+                for polygon in polygons {
+                    // Polygons without outer contour cannot have holes.
+                    if encoder.encode_ring(&polygon.outer_contour, Winding::CounterClockwise) {
+                        for inner in &polygon.inner_contours {
+                            encoder.encode_ring(inner, Winding::Clockwise);
+                        }
+                    }
+                }
+                GeomType::Polygon
+            }
+        };
+
+        if encoder.commands.is_empty() {
+            return None;
+        }
+
+        // Properties are sorted to make the output independent of the hash map order.
+        let mut properties: Vec<_> = self.properties.iter().collect();
+        properties.sort_by_key(|(key, _)| *key);
+
+        let tags = properties
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let value_index = values.index(value)?;
+                Some([keys.index(key), value_index])
+            })
+            .flatten()
+            .collect();
+
+        Some(geozero::mvt::tile::Feature {
+            id: self.id,
+            tags,
+            r#type: Some(geom_type as i32),
+            geometry: encoder.commands,
+        })
+    }
+}
+
+#[derive(Default)]
+struct KeyIndex {
+    keys: Vec<String>,
+    indices: HashMap<String, u32>,
+}
+
+impl KeyIndex {
+    fn index(&mut self, key: &str) -> u32 {
+        if let Some(index) = self.indices.get(key) {
+            return *index;
+        }
+
+        let index = self.keys.len() as u32;
+        self.keys.push(key.to_string());
+        self.indices.insert(key.to_string(), index);
+        index
+    }
+}
+
+/// Hashable representation of [`MvtValue`] used to deduplicate values of a layer.
+#[derive(PartialEq, Eq, Hash)]
+enum ValueKey {
+    String(String),
+    Float(u32),
+    Double(u64),
+    Int64(i64),
+    Uint64(u64),
+    Bool(bool),
+}
+
+#[derive(Default)]
+struct ValueIndex {
+    values: Vec<geozero::mvt::tile::Value>,
+    indices: HashMap<ValueKey, u32>,
+}
+
+impl ValueIndex {
+    /// Returns the index of the value in the layer, or `None` if the value cannot be encoded.
+    fn index(&mut self, value: &MvtValue) -> Option<u32> {
+        let mut pb_value = geozero::mvt::tile::Value::default();
+        let key = match value {
+            MvtValue::String(v) => {
+                pb_value.string_value = Some(v.clone());
+                ValueKey::String(v.clone())
+            }
+            MvtValue::Float(v) => {
+                pb_value.float_value = Some(*v);
+                ValueKey::Float(v.to_bits())
+            }
+            MvtValue::Double(v) => {
+                pb_value.double_value = Some(*v);
+                ValueKey::Double(v.to_bits())
+            }
+            MvtValue::Int64(v) => {
+                // Signed values are encoded with zigzag encoding, which is compact for negative numbers too.
+                pb_value.sint_value = Some(*v);
+                ValueKey::Int64(*v)
+            }
+            MvtValue::Uint64(v) => {
+                pb_value.uint_value = Some(*v);
+                ValueKey::Uint64(*v)
+            }
+            MvtValue::Bool(v) => {
+                pb_value.bool_value = Some(*v);
+                ValueKey::Bool(*v)
+            }
+            MvtValue::Unknown => return None,
+        };
+
+        if let Some(index) = self.indices.get(&key) {
+            return Some(*index);
+        }
+
+        let index = self.values.len() as u32;
+        self.values.push(pb_value);
+        self.indices.insert(key, index);
+        Some(index)
+    }
+}
+
+struct GeometryEncoder {
+    extent: f32,
+    cursor: (i32, i32),
+    commands: Vec<u32>,
+}
+
+impl GeometryEncoder {
+    fn new(extent: u32) -> Self {
+        Self {
+            extent: extent as f32,
+            cursor: (0, 0),
+            commands: vec![],
+        }
+    }
+
+    fn quantize(&self, point: &Point) -> (i32, i32) {
+        (
+            (point.x() * self.extent).round() as i32,
+            (point.y() * self.extent).round() as i32,
+        )
+    }
+
+    fn encode_points(&mut self, points: &[Point]) {
+        if points.is_empty() {
+            return;
+        }
+
+        self.commands.push(command(MOVE_TO, points.len()));
+        for point in points {
+            let point = self.quantize(point);
+            self.push_point(point);
+        }
+    }
+
+    fn encode_line<'a>(&mut self, points: impl Iterator<Item = &'a Point>) {
+        let points = self.quantize_path(points);
+        if points.len() < 2 {
+            return;
+        }
+
+        self.push_path(&points);
+    }
+
+    /// Encodes the closed contour with the given winding (in tile coordinates with Y axis pointing down). Returns
+    /// false if the contour is degenerate and was skipped.
+    fn encode_ring(&mut self, contour: &ClosedContour<Point>, winding: Winding) -> bool {
+        let mut points = self.quantize_path(contour.points.iter());
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        let quantized = ClosedContour::new(
+            points
+                .iter()
+                .map(|&(x, y)| Point::new(x as f32, y as f32))
+                .collect(),
+        );
+        match quantized.winding() {
+            Winding::Degenerate => return false,
+            actual if actual != winding => points.reverse(),
+            _ => {}
+        }
+
+        self.push_path(&points);
+        self.commands.push(command(CLOSE_PATH, 1));
+        true
+    }
+
+    /// Quantizes the points, removing the consecutive duplicates.
+    fn quantize_path<'a>(&self, points: impl Iterator<Item = &'a Point>) -> Vec<(i32, i32)> {
+        let mut quantized: Vec<(i32, i32)> = vec![];
+        for point in points {
+            let point = self.quantize(point);
+            if quantized.last() != Some(&point) {
+                quantized.push(point);
+            }
+        }
+
+        quantized
+    }
+
+    fn push_path(&mut self, points: &[(i32, i32)]) {
+        self.commands.push(command(MOVE_TO, 1));
+        self.push_point(points[0]);
+        self.commands.push(command(LINE_TO, points.len() - 1));
+        for point in &points[1..] {
+            self.push_point(*point);
+        }
+    }
+
+    fn push_point(&mut self, (x, y): (i32, i32)) {
+        self.commands.push(int_to_sint(x - self.cursor.0));
+        self.commands.push(int_to_sint(y - self.cursor.1));
+        self.cursor = (x, y);
+    }
+}
+
+fn command(id: u32, count: usize) -> u32 {
+    (id & 0x7) | ((count as u32) << 3)
+}
+
+fn int_to_sint(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sint_to_int;
+    use galileo_types::impls::{Contour, Polygon};
+    use std::io::Cursor;
+
+    fn point(x: f32, y: f32) -> Point {
+        Point::new(x / 4096.0, y / 4096.0)
+    }
+
+    fn layer(features: Vec<MvtFeature>) -> MvtTile {
+        MvtTile {
+            layers: vec![MvtLayer {
+                name: "layer".into(),
+                features,
+                properties: vec![],
+                size: 4096,
+            }],
+        }
+    }
+
+    #[test]
+    fn int_to_sint_is_inverse_of_sint_to_int() {
+        for value in [0, 1, -1, 2, -2, 4096, -4096, i32::MAX, i32::MIN] {
+            assert_eq!(sint_to_int(int_to_sint(value)), value);
+        }
+    }
+
+    #[test]
+    fn encode_geometry_commands() {
+        // Example from the MVT specification: a line (2, 2), (2, 10), (10, 10).
+        let mut encoder = GeometryEncoder::new(4096);
+        encoder.encode_line([point(2.0, 2.0), point(2.0, 10.0), point(10.0, 10.0)].iter());
+        assert_eq!(encoder.commands, [9, 4, 4, 18, 0, 16, 16, 0]);
+
+        // Polygon from the specification: (3, 6), (8, 12), (20, 34). The reversed contour gets the same encoding.
+        for points in [
+            [point(3.0, 6.0), point(8.0, 12.0), point(20.0, 34.0)],
+            [point(20.0, 34.0), point(8.0, 12.0), point(3.0, 6.0)],
+        ] {
+            let mut encoder = GeometryEncoder::new(4096);
+            assert!(encoder.encode_ring(
+                &ClosedContour::new(points.to_vec()),
+                Winding::CounterClockwise
+            ));
+            assert_eq!(encoder.commands, [9, 6, 12, 18, 10, 12, 24, 44, 15]);
+        }
+
+        let mut encoder = GeometryEncoder::new(4096);
+
+        let degenerate =
+            ClosedContour::new(vec![point(0.0, 0.0), point(0.1, 0.1), point(0.2, 0.0)]);
+        assert!(!encoder.encode_ring(&degenerate, Winding::CounterClockwise));
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let square = |min: f32, max: f32| {
+            ClosedContour::new(vec![
+                point(min, min),
+                point(max, min),
+                point(max, max),
+                point(min, max),
+            ])
+        };
+        let tile = layer(vec![
+            MvtFeature {
+                id: Some(1),
+                properties: HashMap::from([
+                    ("name".to_string(), MvtValue::String("a".into())),
+                    ("value".to_string(), MvtValue::Int64(-5)),
+                ]),
+                geometry: MvtGeometry::Point(vec![point(10.0, 20.0), point(30.0, 40.0)]),
+            },
+            MvtFeature {
+                id: Some(2),
+                properties: HashMap::from([
+                    ("name".to_string(), MvtValue::String("a".into())),
+                    ("kind".to_string(), MvtValue::Unknown),
+                ]),
+                geometry: MvtGeometry::LineString(vec![Contour::open(vec![
+                    point(0.0, 0.0),
+                    point(0.0, 0.0),
+                    point(100.0, 50.0),
+                ])]),
+            },
+            MvtFeature {
+                id: None,
+                properties: HashMap::new(),
+                geometry: MvtGeometry::Polygon(vec![Polygon::new(
+                    square(0.0, 100.0),
+                    vec![square(10.0, 20.0)],
+                )]),
+            },
+            MvtFeature {
+                id: Some(4),
+                properties: HashMap::new(),
+                geometry: MvtGeometry::LineString(vec![Contour::open(vec![point(1.0, 1.0)])]),
+            },
+        ]);
+
+        let encoded = tile.encode();
+        let decoded = MvtTile::decode(&mut Cursor::new(&encoded), false).unwrap();
+
+        let layer = &decoded.layers[0];
+        assert_eq!(layer.name, "layer");
+        assert_eq!(layer.properties, ["name", "value"]);
+        assert_eq!(layer.features.len(), 3);
+
+        let feature = &layer.features[0];
+        assert_eq!(feature.id, Some(1));
+        assert!(matches!(feature.properties["value"], MvtValue::Int64(-5)));
+        let MvtGeometry::Point(points) = &feature.geometry else {
+            panic!("invalid geometry type");
+        };
+        assert_eq!(points, &[point(10.0, 20.0), point(30.0, 40.0)]);
+
+        assert!(!layer.features[1].properties.contains_key("kind"));
+        let MvtGeometry::LineString(lines) = &layer.features[1].geometry else {
+            panic!("invalid geometry type");
+        };
+        assert_eq!(lines[0].iter_points().count(), 2);
+
+        let MvtGeometry::Polygon(polygons) = &layer.features[2].geometry else {
+            panic!("invalid geometry type");
+        };
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].inner_contours.len(), 1);
+    }
+}
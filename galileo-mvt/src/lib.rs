@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
+mod encode;
 pub mod error;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -260,7 +261,7 @@ impl MvtFeature {
         values: &[MvtValue],
     ) -> Result<HashMap<String, MvtValue>, GalileoMvtError> {
         let mut properties = HashMap::new();
-        if tags.len() % 2 != 0 {
+        if !tags.len().is_multiple_of(2) {
             return Err(GalileoMvtError::Generic(
                 "Invalid number of tags in feature".into(),
             ));
@@ -419,32 +420,29 @@ impl MvtFeature {
 
                     // Since tile vectors have y axis pointing down, clockwiseness is also reversed
                     // here.
-                    if let Some(mut polygon) = curr_polygon.take() {
-                        match curr.winding() {
-                            Winding::CounterClockwise => {
-                                curr_polygon = Some(Polygon {
-                                    outer_contour: curr,
-                                    inner_contours: vec![],
-                                });
+                    match curr.winding() {
+                        // Contours with zero area do not cover anything, so they are skipped.
+                        Winding::Degenerate => continue,
+                        Winding::CounterClockwise => {
+                            if let Some(polygon) = curr_polygon.take() {
                                 polygons.push(polygon);
                             }
-                            Winding::Clockwise => {
-                                polygon.inner_contours.push(curr);
-                                curr_polygon = Some(polygon);
-                            }
+
+                            curr_polygon = Some(Polygon {
+                                outer_contour: curr,
+                                inner_contours: vec![],
+                            });
                         }
-                    } else {
-                        if curr.winding() == Winding::Clockwise {
-                            return Err(GalileoMvtError::Generic(
-                                "Outer contour of polygon cannot have counterclockwise winding"
-                                    .into(),
-                            ));
+                        Winding::Clockwise => {
+                            let Some(polygon) = curr_polygon.as_mut() else {
+                                return Err(GalileoMvtError::Generic(
+                                    "Outer contour of polygon cannot have counterclockwise winding"
+                                        .into(),
+                                ));
+                            };
+
+                            polygon.inner_contours.push(curr);
                         }
-
-                        curr_polygon = Some(Polygon {
-                            outer_contour: curr,
-                            inner_contours: vec![],
-                        });
                     }
                 }
             }
@@ -613,6 +611,19 @@ mod tests {
         assert_eq!(sint_to_int(0xffffffff), i32::MIN);
     }
 
+    #[test]
+    fn decode_polygon_skips_degenerate_contours() {
+        // Collinear contour: (0, 0), (1, 1), (2, 2)
+        let degenerate = [9, 0, 0, 18, 2, 2, 2, 2, 15];
+        // Square: (0, 0), (10, 0), (10, 10), (0, 10)
+        let square = [9, 3, 3, 26, 20, 0, 0, 20, 19, 0, 15];
+        let commands = [&degenerate[..], &square[..]].concat();
+        let polygons = MvtFeature::decode_polygon(commands, 4096).unwrap();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].outer_contour.points.len(), 4);
+        assert!(polygons[0].inner_contours.is_empty());
+    }
+
     #[test]
     fn test_protobuf() {
         let vt = include_bytes!("../test-data/vt.mvt");
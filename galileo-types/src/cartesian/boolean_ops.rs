@@ -0,0 +1,678 @@
+use crate::cartesian::NewCartesianPoint2d;
+use crate::contour::Contour as _;
+use crate::impls::{ClosedContour, MultiPolygon, Polygon};
+use std::collections::{HashMap, HashSet};
+
+/// Boolean operations (union, intersection, difference and symmetric difference) on polygonal geometries.
+///
+/// The operations are implemented for [`Polygon`], [`MultiPolygon`] and [`ClosedContour`], and can be applied to
+/// any combination of them. The result is always a [`MultiPolygon`], which is empty if the resulting area is empty.
+/// Outer contours of the resulting polygons are oriented counterclockwise and the holes are oriented clockwise.
+///
+/// The operands are interpreted with even-odd rule: a point is inside of the geometry if it is inside of an odd number
+/// of its contours. So the contours of the operands must not intersect each other (but may touch), while their
+/// orientation does not matter.
+///
+/// The algorithm splits the sides of both operands at their intersection points, selects the parts forming the
+/// boundary of the result and links them into contours. Intersection points are calculated with floating point
+/// arithmetic, so the sides that are almost (but not exactly) collinear can produce thin slivers in the result.
+/// Intersection points are searched with a sweep along *x* axis, but the worst case complexity is still *O(n²)*.
+///
+/// ```
+/// use galileo_types::cartesian::{BooleanOps, CartesianClosedContour, Point2d, Rect};
+/// use galileo_types::impls::{ClosedContour, Polygon};
+///
+/// let polygon = Polygon::new(
+///     ClosedContour::new(vec![
+///         Point2d::new(0.0, 0.0),
+///         Point2d::new(4.0, 0.0),
+///         Point2d::new(4.0, 4.0),
+///         Point2d::new(0.0, 4.0),
+///     ]),
+///     vec![],
+/// );
+/// let view = Rect::new(2.0, 2.0, 10.0, 10.0).into_contour();
+///
+/// let clipped = polygon.intersection(&view);
+/// assert_eq!(clipped.parts().len(), 1);
+/// assert_eq!(clipped.parts()[0].outer_contour.area_signed(), 4.0);
+/// ```
+pub trait BooleanOps<P: NewCartesianPoint2d + Clone> {
+    /// Iterates over all the contours of the geometry.
+    fn rings<'a>(&'a self) -> impl Iterator<Item = &'a ClosedContour<P>>
+    where
+        P: 'a;
+
+    /// Returns the area covered by any of the geometries.
+    fn union(&self, other: &impl BooleanOps<P>) -> MultiPolygon<P> {
+        boolean_operation(self, other, Operation::Union)
+    }
+
+    /// Returns the area covered by both geometries.
+    fn intersection(&self, other: &impl BooleanOps<P>) -> MultiPolygon<P> {
+        boolean_operation(self, other, Operation::Intersection)
+    }
+
+    /// Returns the area covered by this geometry but not by the `other` one.
+    fn difference(&self, other: &impl BooleanOps<P>) -> MultiPolygon<P> {
+        boolean_operation(self, other, Operation::Difference)
+    }
+
+    /// Returns the area covered by exactly one of the geometries.
+    fn xor(&self, other: &impl BooleanOps<P>) -> MultiPolygon<P> {
+        boolean_operation(self, other, Operation::Xor)
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> BooleanOps<P> for ClosedContour<P> {
+    fn rings<'a>(&'a self) -> impl Iterator<Item = &'a ClosedContour<P>>
+    where
+        P: 'a,
+    {
+        std::iter::once(self)
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> BooleanOps<P> for Polygon<P> {
+    fn rings<'a>(&'a self) -> impl Iterator<Item = &'a ClosedContour<P>>
+    where
+        P: 'a,
+    {
+        std::iter::once(&self.outer_contour).chain(self.inner_contours.iter())
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> BooleanOps<P> for MultiPolygon<P> {
+    fn rings<'a>(&'a self) -> impl Iterator<Item = &'a ClosedContour<P>>
+    where
+        P: 'a,
+    {
+        self.parts().iter().flat_map(|polygon| polygon.rings())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+    Xor,
+}
+
+type Vec2 = (f64, f64);
+
+fn sub(a: Vec2, b: Vec2) -> Vec2 {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn cross(a: Vec2, b: Vec2) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot(a: Vec2, b: Vec2) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+/// Key of a point for hash maps. Points are only merged if they are exactly equal.
+fn key(point: Vec2) -> (u64, u64) {
+    // Adding 0.0 turns negative zero into positive zero.
+    ((point.0 + 0.0).to_bits(), (point.1 + 0.0).to_bits())
+}
+
+/// Parameters of the intersection point closer to the ends of the segment than this are snapped to the ends.
+const PARAMETER_EPSILON: f64 = 1e-10;
+
+#[derive(Debug, Copy, Clone)]
+struct Edge {
+    start: Vec2,
+    end: Vec2,
+}
+
+impl Edge {
+    fn reversed(self) -> Self {
+        Self {
+            start: self.end,
+            end: self.start,
+        }
+    }
+
+    fn middle(&self) -> Vec2 {
+        (
+            (self.start.0 + self.end.0) / 2.0,
+            (self.start.1 + self.end.1) / 2.0,
+        )
+    }
+}
+
+/// Contours of an operand as lists of distinct points.
+fn operand_rings<P: NewCartesianPoint2d + Clone>(
+    operand: &(impl BooleanOps<P> + ?Sized),
+) -> Vec<Vec<Vec2>> {
+    operand
+        .rings()
+        .filter_map(|ring| {
+            let mut points: Vec<Vec2> = ring.iter_points().map(|p| (p.x(), p.y())).collect();
+            points.dedup();
+            if points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            (points.len() >= 3).then_some(points)
+        })
+        .collect()
+}
+
+/// Returns true if the point is inside of an odd number of the rings.
+fn is_inside(rings: &[Vec<Vec2>], point: Vec2) -> bool {
+    let mut inside = false;
+    for ring in rings {
+        for (i, &a) in ring.iter().enumerate() {
+            let b = ring[(i + 1) % ring.len()];
+            if (a.1 > point.1) != (b.1 > point.1)
+                && point.0 < a.0 + (point.1 - a.1) * (b.0 - a.0) / (b.1 - a.1)
+            {
+                inside = !inside;
+            }
+        }
+    }
+
+    inside
+}
+
+fn signed_area(ring: &[Vec2]) -> f64 {
+    ring.iter()
+        .enumerate()
+        .map(|(i, &a)| cross(a, ring[(i + 1) % ring.len()]))
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Returns the sides of the rings oriented so that the interior of the operand is on the left of every side.
+///
+/// Orientation of a ring is determined by its nesting level: rings inside of an even number of other rings must be
+/// counterclockwise, and the others must be clockwise.
+fn oriented_edges(rings: &[Vec<Vec2>]) -> Vec<Edge> {
+    let mut edges = vec![];
+    for (index, ring) in rings.iter().enumerate() {
+        // Middle of a side is used, as the rings may touch each other at the vertices.
+        let test_point = Edge {
+            start: ring[0],
+            end: ring[1],
+        }
+        .middle();
+        let depth = rings
+            .iter()
+            .enumerate()
+            .filter(|(other_index, other)| {
+                *other_index != index && is_inside(std::slice::from_ref(other), test_point)
+            })
+            .count();
+        let is_counterclockwise = signed_area(ring) > 0.0;
+        let reverse = is_counterclockwise != (depth % 2 == 0);
+        for (i, &start) in ring.iter().enumerate() {
+            let edge = Edge {
+                start,
+                end: ring[(i + 1) % ring.len()],
+            };
+            edges.push(if reverse { edge.reversed() } else { edge });
+        }
+    }
+
+    edges
+}
+
+/// Splits the edges at the points where they intersect each other. Returns the parts of the edges together with the
+/// indices of the edges they belong to.
+fn split_edges(edges: &[Edge]) -> Vec<(usize, Edge)> {
+    let mut splits: Vec<Vec<(f64, Vec2)>> = vec![vec![]; edges.len()];
+    let min_x = |edge: &Edge| edge.start.0.min(edge.end.0);
+    let max_x = |edge: &Edge| edge.start.0.max(edge.end.0);
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by(|a, b| min_x(&edges[*a]).total_cmp(&min_x(&edges[*b])));
+
+    for (position, &i) in order.iter().enumerate() {
+        let e = edges[i];
+        for &j in &order[position + 1..] {
+            let f = edges[j];
+            if min_x(&f) > max_x(&e) {
+                break;
+            }
+            if e.start.1.max(e.end.1) < f.start.1.min(f.end.1)
+                || f.start.1.max(f.end.1) < e.start.1.min(e.end.1)
+            {
+                continue;
+            }
+
+            for (index, t, point) in intersections(&e, &f) {
+                let edge = if index == 0 { i } else { j };
+                splits[edge].push((t, point));
+            }
+        }
+    }
+
+    let mut result = Vec::with_capacity(edges.len());
+    for (index, (edge, mut points)) in edges.iter().zip(splits).enumerate() {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut start = edge.start;
+        for (_, point) in points.into_iter().chain(std::iter::once((1.0, edge.end))) {
+            if point != start {
+                result.push((index, Edge { start, end: point }));
+                start = point;
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns the points, at which one of the edges should be split, as `(edge index (0 or 1), parameter, point)`.
+fn intersections(e: &Edge, f: &Edge) -> Vec<(usize, f64, Vec2)> {
+    let r = sub(e.end, e.start);
+    let s = sub(f.end, f.start);
+    let qp = sub(f.start, e.start);
+    let denominator = cross(r, s);
+    let is_inner = |t: f64| t > PARAMETER_EPSILON && t < 1.0 - PARAMETER_EPSILON;
+
+    if denominator == 0.0 {
+        if cross(qp, r) != 0.0 {
+            return vec![];
+        }
+
+        // Collinear edges: each edge is split at the ends of the other one lying on it.
+        let mut result = vec![];
+        for (index, edge, direction, self_edge) in [(0, e, r, f), (1, f, s, e)] {
+            let length_sq = dot(direction, direction);
+            for point in [self_edge.start, self_edge.end] {
+                let t = dot(sub(point, edge.start), direction) / length_sq;
+                if is_inner(t) {
+                    result.push((index, t, point));
+                }
+            }
+        }
+        return result;
+    }
+
+    let t = cross(qp, s) / denominator;
+    let u = cross(qp, r) / denominator;
+    let range = -PARAMETER_EPSILON..=1.0 + PARAMETER_EPSILON;
+    if !range.contains(&t) || !range.contains(&u) {
+        return vec![];
+    }
+
+    // Ends of the edges are used as is, so that the split edges are connected exactly.
+    let point = if u <= PARAMETER_EPSILON {
+        f.start
+    } else if u >= 1.0 - PARAMETER_EPSILON {
+        f.end
+    } else if t <= PARAMETER_EPSILON {
+        e.start
+    } else if t >= 1.0 - PARAMETER_EPSILON {
+        e.end
+    } else {
+        (e.start.0 + t * r.0, e.start.1 + t * r.1)
+    };
+
+    let mut result = vec![];
+    if is_inner(t) {
+        result.push((0, t, point));
+    }
+    if is_inner(u) {
+        result.push((1, u, point));
+    }
+    result
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum EdgeState {
+    Inside,
+    Outside,
+    /// The other operand has the same edge with the same direction.
+    SharedSame,
+    /// The other operand has the same edge with the opposite direction.
+    SharedOpposite,
+}
+
+fn classify(edges: &[Edge], other_edges: &[Edge], other_rings: &[Vec<Vec2>]) -> Vec<EdgeState> {
+    let other: HashSet<_> = other_edges
+        .iter()
+        .map(|edge| (key(edge.start), key(edge.end)))
+        .collect();
+    edges
+        .iter()
+        .map(|edge| {
+            if other.contains(&(key(edge.start), key(edge.end))) {
+                EdgeState::SharedSame
+            } else if other.contains(&(key(edge.end), key(edge.start))) {
+                EdgeState::SharedOpposite
+            } else if is_inside(other_rings, edge.middle()) {
+                EdgeState::Inside
+            } else {
+                EdgeState::Outside
+            }
+        })
+        .collect()
+}
+
+fn boolean_operation<P: NewCartesianPoint2d + Clone>(
+    a: &(impl BooleanOps<P> + ?Sized),
+    b: &impl BooleanOps<P>,
+    operation: Operation,
+) -> MultiPolygon<P> {
+    let a_rings = operand_rings(a);
+    let b_rings = operand_rings(b);
+    let a_count: usize = a_rings.iter().map(Vec::len).sum();
+
+    // Edges of both operands are split together, so that they are split both at the intersections with the other
+    // operand and at the points where the contours of the same operand touch each other.
+    let edges: Vec<Edge> = oriented_edges(&a_rings)
+        .into_iter()
+        .chain(oriented_edges(&b_rings))
+        .collect();
+    let (a_edges, b_edges): (Vec<_>, Vec<_>) = split_edges(&edges)
+        .into_iter()
+        .partition(|(index, _)| *index < a_count);
+    let a_edges: Vec<Edge> = a_edges.into_iter().map(|(_, edge)| edge).collect();
+    let b_edges: Vec<Edge> = b_edges.into_iter().map(|(_, edge)| edge).collect();
+
+    use EdgeState::*;
+    use Operation::*;
+
+    let mut selected = vec![];
+    for (edge, state) in a_edges.iter().zip(classify(&a_edges, &b_edges, &b_rings)) {
+        match (operation, state) {
+            (Union | Difference | Xor, Outside)
+            | (Intersection, Inside)
+            | (Union | Intersection, SharedSame)
+            | (Difference, SharedOpposite) => selected.push(*edge),
+            (Xor, Inside) => selected.push(edge.reversed()),
+            _ => {}
+        }
+    }
+    for (edge, state) in b_edges.iter().zip(classify(&b_edges, &a_edges, &a_rings)) {
+        match (operation, state) {
+            (Union | Xor, Outside) | (Intersection, Inside) => selected.push(*edge),
+            (Difference | Xor, Inside) => selected.push(edge.reversed()),
+            _ => {}
+        }
+    }
+
+    let polygons = assemble_polygons(link_edges(selected))
+        .into_iter()
+        .map(|(outer, holes)| {
+            let contour = |ring: Vec<Vec2>| {
+                ClosedContour::new(ring.into_iter().map(|(x, y)| P::new(x, y)).collect())
+            };
+            Polygon::new(contour(outer), holes.into_iter().map(contour).collect())
+        })
+        .collect::<Vec<_>>();
+
+    MultiPolygon::from(polygons)
+}
+
+/// Links the edges into closed rings.
+fn link_edges(edges: Vec<Edge>) -> Vec<Vec<Vec2>> {
+    // Duplicate edges are merged, and pairs of opposite edges are removed, as they are inside of the result.
+    let mut unique = HashMap::new();
+    for edge in edges {
+        unique
+            .entry((key(edge.start), key(edge.end)))
+            .or_insert(edge);
+    }
+    let edges: Vec<Edge> = unique
+        .iter()
+        .filter(|((start, end), _)| !unique.contains_key(&(*end, *start)))
+        .map(|(_, edge)| *edge)
+        .collect();
+
+    let mut outgoing: HashMap<_, Vec<usize>> = HashMap::new();
+    for (index, edge) in edges.iter().enumerate() {
+        outgoing.entry(key(edge.start)).or_default().push(index);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = vec![];
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+
+        let ring_start = key(edges[first].start);
+        let mut ring = vec![];
+        let mut current = first;
+        loop {
+            used[current] = true;
+            let edge = edges[current];
+            ring.push(edge.start);
+            if key(edge.end) == ring_start {
+                break;
+            }
+
+            // When several edges start at the same point, the one turning left the most is taken. This separates
+            // the contours touching each other at this point.
+            let direction = sub(edge.end, edge.start);
+            let next = outgoing.get(&key(edge.end)).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .copied()
+                    .filter(|candidate| !used[*candidate])
+                    .max_by(|a, b| {
+                        turn(direction, &edges[*a]).total_cmp(&turn(direction, &edges[*b]))
+                    })
+            });
+            match next {
+                Some(next) => current = next,
+                None => {
+                    // Broken ring, can happen only because of floating point errors.
+                    ring.clear();
+                    break;
+                }
+            }
+        }
+
+        let ring = remove_collinear_points(ring);
+        if ring.len() >= 3 {
+            rings.push(ring);
+        }
+    }
+
+    rings
+}
+
+/// Angle of turn from the `direction` to the `edge` direction. Positive for left turns.
+fn turn(direction: Vec2, edge: &Edge) -> f64 {
+    let edge_direction = sub(edge.end, edge.start);
+    let cross = cross(direction, edge_direction);
+    let dot = dot(direction, edge_direction);
+    if cross == 0.0 && dot < 0.0 {
+        // Turning back is the last option.
+        return -std::f64::consts::PI;
+    }
+
+    cross.atan2(dot)
+}
+
+fn remove_collinear_points(mut ring: Vec<Vec2>) -> Vec<Vec2> {
+    let mut index = 0;
+    while ring.len() >= 3 && index < ring.len() {
+        let previous = ring[(index + ring.len() - 1) % ring.len()];
+        let next = ring[(index + 1) % ring.len()];
+        if cross(sub(ring[index], previous), sub(next, ring[index])) == 0.0 {
+            ring.remove(index);
+            index = index.saturating_sub(1);
+        } else {
+            index += 1;
+        }
+    }
+
+    ring
+}
+
+/// Groups the rings into polygons. Counterclockwise rings are the outer contours, and each clockwise ring becomes a
+/// hole of the smallest outer contour containing it.
+fn assemble_polygons(rings: Vec<Vec<Vec2>>) -> Vec<(Vec<Vec2>, Vec<Vec<Vec2>>)> {
+    let mut polygons = vec![];
+    let mut areas = vec![];
+    let mut holes = vec![];
+    for ring in rings {
+        let area = signed_area(&ring);
+        if area > 0.0 {
+            polygons.push((ring, vec![]));
+            areas.push(area);
+        } else if area < 0.0 {
+            holes.push(ring);
+        }
+    }
+
+    for hole in holes {
+        let point = Edge {
+            start: hole[0],
+            end: hole[1],
+        }
+        .middle();
+        let outer = polygons
+            .iter()
+            .enumerate()
+            .filter(|(_, (outer, _))| is_inside(std::slice::from_ref(outer), point))
+            .min_by(|(a, _), (b, _)| areas[*a].total_cmp(&areas[*b]))
+            .map(|(index, _)| index);
+        if let Some(outer) = outer {
+            polygons[outer].1.push(hole);
+        }
+    }
+
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::{CartesianClosedContour, Point2d, Winding};
+
+    fn square(x: f64, y: f64, size: f64) -> ClosedContour<Point2d> {
+        ClosedContour::new(vec![
+            Point2d::new(x, y),
+            Point2d::new(x + size, y),
+            Point2d::new(x + size, y + size),
+            Point2d::new(x, y + size),
+        ])
+    }
+
+    fn area(geometry: &MultiPolygon<Point2d>) -> f64 {
+        geometry
+            .parts()
+            .iter()
+            .map(|polygon| {
+                polygon.outer_contour.area_signed()
+                    + polygon
+                        .inner_contours
+                        .iter()
+                        .map(|hole| hole.area_signed())
+                        .sum::<f64>()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let a = square(0.0, 0.0, 2.0);
+        let b = square(1.0, 1.0, 2.0);
+
+        let union = a.union(&b);
+        assert_eq!(union.parts().len(), 1);
+        assert_eq!(union.parts()[0].outer_contour.points.len(), 8);
+        assert_eq!(area(&union), 7.0);
+
+        let intersection = a.intersection(&b);
+        assert_eq!(intersection.parts().len(), 1);
+        assert_eq!(intersection.parts()[0].outer_contour.points.len(), 4);
+        assert_eq!(area(&intersection), 1.0);
+
+        let difference = a.difference(&b);
+        assert_eq!(difference.parts().len(), 1);
+        assert_eq!(area(&difference), 3.0);
+
+        let xor = a.xor(&b);
+        assert_eq!(xor.parts().len(), 2);
+        assert_eq!(area(&xor), 6.0);
+    }
+
+    #[test]
+    fn orientation_of_operands_does_not_matter() {
+        let a = square(0.0, 0.0, 2.0);
+        let mut b = square(1.0, 1.0, 2.0);
+        b.points.reverse();
+
+        let union = a.union(&b);
+        assert_eq!(area(&union), 7.0);
+        assert_eq!(
+            union.parts()[0].outer_contour.winding(),
+            Winding::CounterClockwise
+        );
+    }
+
+    #[test]
+    fn difference_creates_hole() {
+        let result = square(0.0, 0.0, 10.0).difference(&square(2.0, 2.0, 2.0));
+        assert_eq!(result.parts().len(), 1);
+        let polygon = &result.parts()[0];
+        assert_eq!(polygon.inner_contours.len(), 1);
+        assert_eq!(polygon.inner_contours[0].winding(), Winding::Clockwise);
+        assert_eq!(area(&result), 96.0);
+
+        // Intersection with the polygon with the hole
+        let intersection = polygon.intersection(&square(3.0, 0.0, 10.0));
+        assert_eq!(area(&intersection), 70.0 - 2.0);
+    }
+
+    #[test]
+    fn shared_edges() {
+        let a = square(0.0, 0.0, 1.0);
+        let b = square(1.0, 0.0, 1.0);
+
+        let union = a.union(&b);
+        assert_eq!(union.parts().len(), 1);
+        assert_eq!(union.parts()[0].outer_contour.points.len(), 4);
+        assert_eq!(area(&union), 2.0);
+
+        assert!(a.intersection(&b).parts().is_empty());
+        assert_eq!(area(&a.difference(&b)), 1.0);
+
+        let same = a.union(&a);
+        assert_eq!(area(&same), 1.0);
+        assert!(a.difference(&a).parts().is_empty());
+        assert_eq!(area(&a.intersection(&a)), 1.0);
+    }
+
+    #[test]
+    fn disjoint_and_touching_geometries() {
+        let a = square(0.0, 0.0, 1.0);
+        let b = square(5.0, 5.0, 1.0);
+        assert_eq!(a.union(&b).parts().len(), 2);
+        assert!(a.intersection(&b).parts().is_empty());
+        assert_eq!(area(&a.difference(&b)), 1.0);
+
+        // Touching by a corner.
+        let c = square(1.0, 1.0, 1.0);
+        let union = a.union(&c);
+        assert_eq!(union.parts().len(), 2);
+        assert_eq!(area(&union), 2.0);
+    }
+
+    #[test]
+    fn multi_polygons() {
+        let a = MultiPolygon::from(vec![
+            Polygon::new(square(0.0, 0.0, 2.0), vec![]),
+            Polygon::new(square(4.0, 0.0, 2.0), vec![]),
+        ]);
+        let b = Polygon::new(square(1.0, 0.0, 4.0), vec![]);
+
+        let union = a.union(&b);
+        assert_eq!(union.parts().len(), 1);
+        assert_eq!(area(&union), 6.0 * 2.0 + 4.0 * 2.0);
+
+        let intersection = a.intersection(&b);
+        assert_eq!(intersection.parts().len(), 2);
+        assert_eq!(area(&intersection), 4.0);
+
+        assert_eq!(area(&b.difference(&a)), 12.0);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::cartesian::{convex_hull, CartesianPoint2d, NewCartesianPoint2d, Point2d};
+use crate::impls::ClosedContour;
+
+/// Circle in 2d cartesian coordinates. See [`minimum_bounding_circle`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingCircle {
+    /// Center of the circle.
+    pub center: Point2d,
+    /// Radius of the circle.
+    pub radius: f64,
+}
+
+impl BoundingCircle {
+    /// Returns `true` if the point is inside the circle or on its border.
+    pub fn contains(&self, point: &impl CartesianPoint2d<Num = f64>) -> bool {
+        self.center.distance_sq(point) <= self.radius * self.radius
+    }
+}
+
+/// Calculates the [minimum bounding rectangle](https://en.wikipedia.org/wiki/Minimum_bounding_rectangle) of the
+/// given set of points, that is the rectangle with the smallest area enclosing all the points. Unlike
+/// [`Rect::from_points`](crate::cartesian::Rect::from_points), the sides of the rectangle are not necessarily
+/// parallel to the coordinate axes.
+///
+/// One of the sides of the minimum rectangle always lies on a side of the [`convex_hull`] of the points, so all
+/// the sides of the hull are checked. The complexity of the algorithm is *O(n log n + h²)*, where *h* is the number
+/// of points in the hull.
+///
+/// The points of the returned contour go in counterclockwise direction. Returns `None` if `points` is empty. If
+/// all the points are collinear, the returned rectangle has zero width.
+///
+/// ```
+/// use galileo_types::cartesian::{minimum_bounding_rectangle, CartesianClosedContour, Point2d};
+///
+/// let points = [
+///     Point2d::new(0.0, 0.0),
+///     Point2d::new(1.0, 1.0),
+///     Point2d::new(3.0, 3.0),
+///     Point2d::new(2.0, 4.0),
+/// ];
+///
+/// let rect = minimum_bounding_rectangle(&points).unwrap();
+/// assert!((rect.area_signed() - 6.0).abs() < 1e-9);
+/// ```
+pub fn minimum_bounding_rectangle<P>(points: &[P]) -> Option<ClosedContour<P>>
+where
+    P: NewCartesianPoint2d + Clone,
+{
+    let hull = convex_hull(points).points;
+    let first = hull.first()?;
+    if hull.len() == 1 {
+        return Some(ClosedContour::new(vec![first.clone(); 4]));
+    }
+
+    let mut best: Option<(f64, [(f64, f64); 4])> = None;
+    for index in 0..hull.len() {
+        let a = &hull[index];
+        let b = &hull[(index + 1) % hull.len()];
+        let (dx, dy) = (b.x() - a.x(), b.y() - a.y());
+        let length = (dx * dx + dy * dy).sqrt();
+        // Unit vector along the side and the normal to it.
+        let (ux, uy) = (dx / length, dy / length);
+        let (vx, vy) = (-uy, ux);
+
+        let (mut u_min, mut u_max, mut v_min, mut v_max) = (0.0f64, 0.0f64, 0.0f64, 0.0f64);
+        for point in &hull {
+            let (px, py) = (point.x() - a.x(), point.y() - a.y());
+            let u = px * ux + py * uy;
+            let v = px * vx + py * vy;
+            u_min = u_min.min(u);
+            u_max = u_max.max(u);
+            v_min = v_min.min(v);
+            v_max = v_max.max(v);
+        }
+
+        let area = (u_max - u_min) * (v_max - v_min);
+        if best.is_none_or(|(best_area, _)| area < best_area) {
+            let corner = |u: f64, v: f64| (a.x() + u * ux + v * vx, a.y() + u * uy + v * vy);
+            best = Some((
+                area,
+                [
+                    corner(u_min, v_min),
+                    corner(u_max, v_min),
+                    corner(u_max, v_max),
+                    corner(u_min, v_max),
+                ],
+            ));
+        }
+    }
+
+    let (_, corners) = best?;
+    Some(ClosedContour::new(
+        corners.iter().map(|&(x, y)| P::new(x, y)).collect(),
+    ))
+}
+
+/// Calculates the [smallest circle](https://en.wikipedia.org/wiki/Smallest-circle_problem) enclosing all the given
+/// points.
+///
+/// The circle is calculated with Welzl's algorithm over the points of the [`convex_hull`] of the set. Returns `None`
+/// if `points` is empty. For a single point the circle has zero radius.
+///
+/// ```
+/// use galileo_types::cartesian::{minimum_bounding_circle, Point2d};
+///
+/// let points = [
+///     Point2d::new(0.0, 0.0),
+///     Point2d::new(2.0, 0.0),
+///     Point2d::new(1.0, 0.5),
+/// ];
+///
+/// let circle = minimum_bounding_circle(&points).unwrap();
+/// assert_eq!(circle.center, Point2d::new(1.0, 0.0));
+/// assert_eq!(circle.radius, 1.0);
+/// ```
+pub fn minimum_bounding_circle<P>(points: &[P]) -> Option<BoundingCircle>
+where
+    P: CartesianPoint2d<Num = f64> + Clone,
+{
+    let hull: Vec<Point2d> = convex_hull(points)
+        .points
+        .iter()
+        .map(|p| Point2d::new(p.x(), p.y()))
+        .collect();
+
+    let mut circle = BoundingCircle {
+        center: *hull.first()?,
+        radius: 0.0,
+    };
+
+    for i in 1..hull.len() {
+        if contains_with_tolerance(&circle, &hull[i]) {
+            continue;
+        }
+
+        // The point i must be on the border of the circle of the first i + 1 points.
+        circle = circle_from_two(&hull[0], &hull[i]);
+        for j in 1..i {
+            if contains_with_tolerance(&circle, &hull[j]) {
+                continue;
+            }
+
+            // Both points i and j are on the border.
+            circle = circle_from_two(&hull[i], &hull[j]);
+            for k in 0..j {
+                if !contains_with_tolerance(&circle, &hull[k]) {
+                    circle = circle_from_three(&hull[i], &hull[j], &hull[k]);
+                }
+            }
+        }
+    }
+
+    Some(circle)
+}
+
+/// Checks if the point is inside the circle allowing for rounding errors of the circle calculation.
+fn contains_with_tolerance(circle: &BoundingCircle, point: &Point2d) -> bool {
+    circle.center.distance_sq(point).sqrt() <= circle.radius * (1.0 + 1e-12) + f64::EPSILON
+}
+
+fn circle_from_two(a: &Point2d, b: &Point2d) -> BoundingCircle {
+    let center = Point2d::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+    BoundingCircle {
+        center,
+        radius: center.distance_sq(a).sqrt(),
+    }
+}
+
+fn circle_from_three(a: &Point2d, b: &Point2d, c: &Point2d) -> BoundingCircle {
+    let (bx, by) = (b.x - a.x, b.y - a.y);
+    let (cx, cy) = (c.x - a.x, c.y - a.y);
+    let d = 2.0 * (bx * cy - by * cx);
+    if d == 0.0 {
+        // Collinear points, the circle is defined by the two most distant of them.
+        return [
+            circle_from_two(a, b),
+            circle_from_two(a, c),
+            circle_from_two(b, c),
+        ]
+        .into_iter()
+        .max_by(|x, y| x.radius.total_cmp(&y.radius))
+        .expect("array is not empty");
+    }
+
+    let b_sq = bx * bx + by * by;
+    let c_sq = cx * cx + cy * cy;
+    let ux = (cy * b_sq - by * c_sq) / d;
+    let uy = (bx * c_sq - cx * b_sq) / d;
+    BoundingCircle {
+        center: Point2d::new(a.x + ux, a.y + uy),
+        radius: (ux * ux + uy * uy).sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::{CartesianClosedContour, CartesianPolygon, Winding};
+    use crate::impls::Polygon;
+
+    #[test]
+    fn bounding_rectangle_of_rotated_square() {
+        let points = [
+            Point2d::new(1.0, 0.0),
+            Point2d::new(2.0, 1.0),
+            Point2d::new(1.0, 2.0),
+            Point2d::new(0.0, 1.0),
+            Point2d::new(1.0, 1.0),
+        ];
+
+        let rect = minimum_bounding_rectangle(&points).unwrap();
+        assert!((rect.area_signed() - 2.0).abs() < 1e-9);
+        assert_eq!(rect.winding(), Winding::CounterClockwise);
+
+        let polygon = Polygon::from(ClosedContour::new(
+            rect.points
+                .iter()
+                .map(|p| Point2d::new(p.x.round(), p.y.round()))
+                .collect(),
+        ));
+        assert!(points.iter().all(|p| polygon.contains_point(p)));
+    }
+
+    #[test]
+    fn bounding_rectangle_of_collinear_points() {
+        let points = [
+            Point2d::new(0.0, 0.0),
+            Point2d::new(1.0, 1.0),
+            Point2d::new(3.0, 3.0),
+        ];
+
+        let rect = minimum_bounding_rectangle(&points).unwrap();
+        assert_eq!(rect.area_signed(), 0.0);
+        assert!(minimum_bounding_rectangle::<Point2d>(&[]).is_none());
+    }
+
+    #[test]
+    fn bounding_circle_contains_all_points() {
+        let points: Vec<Point2d> = (0..50)
+            .map(|i| {
+                let angle = i as f64 * 2.4;
+                let distance = (i % 7) as f64;
+                Point2d::new(distance * angle.cos() + 3.0, distance * angle.sin() - 1.0)
+            })
+            .collect();
+
+        let circle = minimum_bounding_circle(&points).unwrap();
+        assert!(points.iter().all(|p| contains_with_tolerance(&circle, p)));
+        assert!(circle.radius <= 6.0 + 1e-9);
+        assert!(circle.radius > 5.5);
+    }
+
+    #[test]
+    fn bounding_circle_of_triangle() {
+        // Circumcircle of a right triangle has the hypotenuse as a diameter.
+        let points = [
+            Point2d::new(0.0, 0.0),
+            Point2d::new(4.0, 0.0),
+            Point2d::new(0.0, 3.0),
+        ];
+
+        let circle = minimum_bounding_circle(&points).unwrap();
+        assert!((circle.center.x - 2.0).abs() < 1e-12);
+        assert!((circle.center.y - 1.5).abs() < 1e-12);
+        assert!((circle.radius - 2.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bounding_circle_of_single_point() {
+        let circle = minimum_bounding_circle(&[Point2d::new(1.0, 2.0)]).unwrap();
+        assert_eq!(circle.center, Point2d::new(1.0, 2.0));
+        assert_eq!(circle.radius, 0.0);
+        assert!(minimum_bounding_circle::<Point2d>(&[]).is_none());
+    }
+}
@@ -0,0 +1,393 @@
+use crate::cartesian::{BooleanOps, NewCartesianPoint2d, Point2d};
+use crate::contour::Contour as _;
+use crate::geometry::Geom;
+use crate::impls::buffer::{buffer_closed, buffer_open, buffer_point, Style, DEFAULT_MITER_LIMIT};
+use crate::impls::{ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use crate::multi_contour::MultiContour as _;
+use crate::multi_point::MultiPoint as _;
+
+/// Shape of the buffer at the vertices where the line turns outwards. See [`BufferOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum JoinStyle {
+    /// Offset segments are extended until they meet. If the meeting point is further from the original vertex than
+    /// `limit` times the buffer distance, the join is beveled instead.
+    Miter {
+        /// Maximum distance of the miter vertex from the original vertex, as a multiple of the buffer distance.
+        limit: f64,
+    },
+    /// Ends of the offset segments are connected with a straight line.
+    Bevel,
+    /// Ends of the offset segments are connected with a circular arc around the original vertex.
+    #[default]
+    Round,
+}
+
+/// Shape of the buffer at the ends of open lines and around points. See [`BufferOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CapStyle {
+    /// The buffer ends exactly at the end of the line. Points produce no buffer.
+    Flat,
+    /// The buffer is extended beyond the end of the line by the buffer distance. Points produce squares.
+    Square,
+    /// The end of the line is closed by a half circle. Points produce circles.
+    #[default]
+    Round,
+}
+
+/// Parameters of the [`Buffer`] operation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BufferOptions {
+    /// Shape of the vertices where the line turns outwards.
+    pub join: JoinStyle,
+    /// Shape of the ends of lines and of buffered points.
+    pub cap: CapStyle,
+    /// Number of segments used to approximate a quarter of a circle in round joins and caps.
+    pub quadrant_segments: usize,
+}
+
+impl Default for BufferOptions {
+    fn default() -> Self {
+        Self {
+            join: JoinStyle::default(),
+            cap: CapStyle::default(),
+            quadrant_segments: 8,
+        }
+    }
+}
+
+impl BufferOptions {
+    /// Miter joins with the default limit of `2` and flat caps, same as used by
+    /// [`Contour::buffer`](crate::impls::Contour::buffer).
+    pub fn mitered() -> Self {
+        Self {
+            join: JoinStyle::Miter {
+                limit: DEFAULT_MITER_LIMIT,
+            },
+            cap: CapStyle::Flat,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the join style.
+    pub fn with_join(mut self, join: JoinStyle) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Sets the cap style.
+    pub fn with_cap(mut self, cap: CapStyle) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Sets the number of segments per quarter of a circle.
+    pub fn with_quadrant_segments(mut self, quadrant_segments: usize) -> Self {
+        self.quadrant_segments = quadrant_segments;
+        self
+    }
+}
+
+/// Calculation of the area within the given distance from a geometry.
+///
+/// Points are buffered into circles (or squares, depending on the [`CapStyle`]), open contours into corridors along
+/// both sides of the line, and polygons are extended outwards for positive distance or shrunk for negative. The
+/// result is always a [`MultiPolygon`], which is empty if the buffer has no area. Buffers of the parts of
+/// multi-geometries are merged together with [`BooleanOps::union`].
+///
+/// Self-intersections of the buffer of a single line or contour (e.g. when a narrow bay of a concave polygon closes
+/// up) are not resolved.
+///
+/// ```
+/// use galileo_types::cartesian::{Buffer, BufferOptions, CartesianClosedContour, Point2d};
+///
+/// let search_area = Point2d::new(10.0, 20.0).buffer_with_options(5.0, &BufferOptions::default());
+///
+/// let area = search_area.parts()[0].outer_contour.area_signed();
+/// assert!((area - std::f64::consts::PI * 25.0).abs() < 1.0);
+/// ```
+pub trait Buffer {
+    /// Type of the points of the resulting polygons.
+    type Point;
+
+    /// Returns the area within `distance` from the geometry, shaped according to the `options`.
+    fn buffer_with_options(
+        &self,
+        distance: f64,
+        options: &BufferOptions,
+    ) -> MultiPolygon<Self::Point>;
+}
+
+impl Buffer for Point2d {
+    type Point = Point2d;
+
+    fn buffer_with_options(&self, distance: f64, options: &BufferOptions) -> MultiPolygon<Point2d> {
+        point_buffer(self, distance, options)
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> Buffer for MultiPoint<P> {
+    type Point = P;
+
+    fn buffer_with_options(&self, distance: f64, options: &BufferOptions) -> MultiPolygon<P> {
+        union_all(
+            self.iter_points()
+                .map(|point| point_buffer(point, distance, options)),
+        )
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> Buffer for Contour<P> {
+    type Point = P;
+
+    fn buffer_with_options(&self, distance: f64, options: &BufferOptions) -> MultiPolygon<P> {
+        let points: Vec<P> = self.iter_points().cloned().collect();
+        if self.is_closed() {
+            return Polygon::from(points).buffer_with_options(distance, options);
+        }
+
+        buffer_open(&points, distance, &Style::new(options))
+            .into_iter()
+            .map(Polygon::from)
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> Buffer for ClosedContour<P> {
+    type Point = P;
+
+    fn buffer_with_options(&self, distance: f64, options: &BufferOptions) -> MultiPolygon<P> {
+        Polygon::from(self.clone()).buffer_with_options(distance, options)
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> Buffer for Polygon<P> {
+    type Point = P;
+
+    fn buffer_with_options(&self, distance: f64, options: &BufferOptions) -> MultiPolygon<P> {
+        if distance == 0.0 {
+            return vec![self.clone()].into();
+        }
+
+        let style = Style::new(options);
+        let Some(outer) = buffer_closed(&self.outer_contour, distance, &style)
+            .into_iter()
+            .next()
+        else {
+            return vec![].into();
+        };
+
+        // Holes are offset in the opposite direction: they shrink when the polygon grows.
+        let holes = self
+            .inner_contours
+            .iter()
+            .flat_map(|hole| buffer_closed(hole, -distance, &style));
+
+        if distance > 0.0 {
+            return vec![Polygon::new(outer, holes.collect())].into();
+        }
+
+        // Grown holes can cross the shrunk outer contour or each other, so they are cut out one by one.
+        let mut result: MultiPolygon<P> = vec![Polygon::from(outer)].into();
+        for hole in holes {
+            result = result.difference(&hole);
+        }
+
+        result
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> Buffer for MultiContour<P> {
+    type Point = P;
+
+    fn buffer_with_options(&self, distance: f64, options: &BufferOptions) -> MultiPolygon<P> {
+        union_all(
+            self.contours()
+                .map(|contour| contour.buffer_with_options(distance, options)),
+        )
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> Buffer for MultiPolygon<P> {
+    type Point = P;
+
+    fn buffer_with_options(&self, distance: f64, options: &BufferOptions) -> MultiPolygon<P> {
+        union_all(
+            self.parts()
+                .iter()
+                .map(|polygon| polygon.buffer_with_options(distance, options)),
+        )
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> Buffer for Geom<P> {
+    type Point = P;
+
+    fn buffer_with_options(&self, distance: f64, options: &BufferOptions) -> MultiPolygon<P> {
+        match self {
+            Geom::Point(point) => point_buffer(point, distance, options),
+            Geom::MultiPoint(points) => points.buffer_with_options(distance, options),
+            Geom::Contour(contour) => contour.buffer_with_options(distance, options),
+            Geom::MultiContour(contours) => contours.buffer_with_options(distance, options),
+            Geom::Polygon(polygon) => polygon.buffer_with_options(distance, options),
+            Geom::MultiPolygon(polygons) => polygons.buffer_with_options(distance, options),
+        }
+    }
+}
+
+fn point_buffer<P: NewCartesianPoint2d>(
+    point: &P,
+    distance: f64,
+    options: &BufferOptions,
+) -> MultiPolygon<P> {
+    buffer_point(point, distance, &Style::new(options))
+        .into_iter()
+        .map(Polygon::from)
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Merges the buffers of the parts of a multi-geometry.
+fn union_all<P: NewCartesianPoint2d + Clone>(
+    buffers: impl Iterator<Item = MultiPolygon<P>>,
+) -> MultiPolygon<P> {
+    let mut buffers = buffers.filter(|buffer| !buffer.parts().is_empty());
+    let Some(first) = buffers.next() else {
+        return vec![].into();
+    };
+
+    buffers.fold(first, |result, buffer| result.union(&buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::{CartesianClosedContour, CartesianPolygon, Winding};
+    use std::f64::consts::PI;
+
+    fn square(min: f64, max: f64) -> ClosedContour<Point2d> {
+        ClosedContour::new(vec![
+            Point2d::new(min, min),
+            Point2d::new(max, min),
+            Point2d::new(max, max),
+            Point2d::new(min, max),
+        ])
+    }
+
+    fn area(polygons: &MultiPolygon<Point2d>) -> f64 {
+        polygons
+            .parts()
+            .iter()
+            .map(|polygon| {
+                polygon.outer_contour.area_signed().abs()
+                    - polygon
+                        .inner_contours
+                        .iter()
+                        .map(|hole| hole.area_signed().abs())
+                        .sum::<f64>()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn point_buffer_styles() {
+        let point = Point2d::new(1.0, 1.0);
+
+        let circle = point.buffer_with_options(2.0, &BufferOptions::default());
+        assert_eq!(circle.parts()[0].outer_contour.points.len(), 32);
+        assert_eq!(
+            circle.parts()[0].outer_contour.winding(),
+            Winding::CounterClockwise
+        );
+        assert!((area(&circle) - PI * 4.0).abs() < 0.1);
+
+        let options = BufferOptions::default().with_cap(CapStyle::Square);
+        assert_eq!(area(&point.buffer_with_options(2.0, &options)), 16.0);
+
+        let options = BufferOptions::default().with_cap(CapStyle::Flat);
+        assert!(point.buffer_with_options(2.0, &options).parts().is_empty());
+        assert!(point
+            .buffer_with_options(-1.0, &BufferOptions::default())
+            .parts()
+            .is_empty());
+    }
+
+    #[test]
+    fn line_buffer_caps() {
+        let line = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(10.0, 0.0)]);
+
+        let flat = line.buffer_with_options(1.0, &BufferOptions::mitered());
+        assert_eq!(area(&flat), 20.0);
+
+        let square =
+            line.buffer_with_options(1.0, &BufferOptions::default().with_cap(CapStyle::Square));
+        assert_eq!(area(&square), 24.0);
+
+        let round = line.buffer_with_options(1.0, &BufferOptions::default());
+        assert!((area(&round) - (20.0 + PI)).abs() < 0.05);
+        assert!(round.parts()[0].contains_point(&Point2d::new(10.9, 0.0)));
+        assert!(!round.parts()[0].contains_point(&Point2d::new(10.8, 0.8)));
+    }
+
+    #[test]
+    fn contour_buffer_joins() {
+        let outer = square(0.0, 2.0);
+
+        let miter = outer.buffer_with_options(1.0, &BufferOptions::mitered());
+        assert_eq!(area(&miter), 16.0);
+
+        let bevel =
+            outer.buffer_with_options(1.0, &BufferOptions::default().with_join(JoinStyle::Bevel));
+        assert_eq!(area(&bevel), 14.0);
+        assert_eq!(bevel.parts()[0].outer_contour.points.len(), 8);
+
+        let round = outer.buffer_with_options(1.0, &BufferOptions::default());
+        assert!((area(&round) - (12.0 + PI)).abs() < 0.05);
+    }
+
+    #[test]
+    fn polygon_buffer_offsets_holes() {
+        let polygon = Polygon::new(square(0.0, 10.0), vec![square(4.0, 6.0)]);
+        let options = BufferOptions::mitered();
+
+        let grown = polygon.buffer_with_options(1.0, &options);
+        assert_eq!(grown.parts().len(), 1);
+        assert!(grown.parts()[0].inner_contours.is_empty());
+        assert_eq!(area(&grown), 144.0);
+
+        let slightly_grown = polygon.buffer_with_options(0.5, &options);
+        assert_eq!(
+            slightly_grown.parts()[0].inner_contours,
+            vec![square(4.5, 5.5)]
+        );
+
+        let shrunk = polygon.buffer_with_options(-1.0, &options);
+        assert_eq!(shrunk.parts().len(), 1);
+        assert!((area(&shrunk) - (64.0 - 16.0)).abs() < 1e-9);
+
+        assert!(polygon
+            .buffer_with_options(-3.5, &options)
+            .parts()
+            .is_empty());
+    }
+
+    #[test]
+    fn multi_geometry_buffers_are_merged() {
+        let points = MultiPoint::from(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(1.0, 0.0),
+            Point2d::new(10.0, 0.0),
+        ]);
+        let options = BufferOptions::default().with_cap(CapStyle::Square);
+
+        let buffer = points.buffer_with_options(1.0, &options);
+        assert_eq!(buffer.parts().len(), 2);
+        assert!((area(&buffer) - 10.0).abs() < 1e-9);
+
+        let geom = Geom::MultiPoint(points);
+        assert_eq!(
+            area(&geom.buffer_with_options(1.0, &options)),
+            area(&buffer)
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::cartesian::{CartesianPoint2d, Orientation};
+use crate::impls::ClosedContour;
+use std::cmp::Ordering;
+
+/// Calculates the [convex hull](https://en.wikipedia.org/wiki/Convex_hull) of the given set of points.
+///
+/// The hull is calculated using the monotone chain (Andrew's) algorithm with *O(n log n)* complexity. Coincident
+/// points are merged into one, and points lying on the sides of the hull are not included into the result.
+///
+/// The points of the returned contour are always ordered in [`Winding::CounterClockwise`](crate::cartesian::Winding)
+/// direction, starting with the point with the minimum *x* (and minimum *y* among the points with the same *x*).
+///
+/// If there are less than 3 distinct points in the input, these points are returned as-is. If all the points are
+/// collinear, the result contains only two extreme points of the set.
+///
+/// ```
+/// use galileo_types::cartesian::{convex_hull, CartesianClosedContour, Point2d, Winding};
+///
+/// let points = [
+///     Point2d::new(0.0, 0.0),
+///     Point2d::new(1.0, 1.0),
+///     Point2d::new(2.0, 0.0),
+///     Point2d::new(0.0, 2.0),
+///     Point2d::new(2.0, 2.0),
+/// ];
+///
+/// let hull = convex_hull(&points);
+/// assert_eq!(hull.points.len(), 4);
+/// assert_eq!(hull.winding(), Winding::CounterClockwise);
+/// ```
+pub fn convex_hull<P>(points: &[P]) -> ClosedContour<P>
+where
+    P: CartesianPoint2d + Clone,
+{
+    let mut sorted: Vec<&P> = points.iter().collect();
+    sorted.sort_by(|a, b| {
+        a.x()
+            .partial_cmp(&b.x())
+            .unwrap_or(Ordering::Equal)
+            .then(a.y().partial_cmp(&b.y()).unwrap_or(Ordering::Equal))
+    });
+    sorted.dedup_by(|a, b| a.equal(b));
+
+    if sorted.len() < 3 {
+        return ClosedContour::new(sorted.into_iter().cloned().collect());
+    }
+
+    let mut hull: Vec<&P> = Vec::with_capacity(sorted.len() * 2);
+
+    // Lower part of the hull.
+    for &p in &sorted {
+        push_hull_point(&mut hull, p, 2);
+    }
+
+    // Upper part of the hull.
+    let lower_len = hull.len() + 1;
+    for &p in sorted.iter().rev().skip(1) {
+        push_hull_point(&mut hull, p, lower_len);
+    }
+
+    // The last point is the same as the first one.
+    hull.pop();
+
+    ClosedContour::new(hull.into_iter().cloned().collect())
+}
+
+fn push_hull_point<'a, P: CartesianPoint2d>(hull: &mut Vec<&'a P>, p: &'a P, min_len: usize) {
+    while hull.len() >= min_len
+        && Orientation::triplet(hull[hull.len() - 2], hull[hull.len() - 1], p)
+            != Orientation::Counterclockwise
+    {
+        hull.pop();
+    }
+
+    hull.push(p);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::{CartesianClosedContour, Point2d, Winding};
+
+    #[test]
+    fn convex_hull_point_cloud() {
+        let points = [
+            Point2d::new(1.0, 1.0),
+            Point2d::new(0.0, 0.0),
+            Point2d::new(4.0, 0.0),
+            Point2d::new(2.0, 1.0),
+            Point2d::new(4.0, 4.0),
+            Point2d::new(3.0, 2.0),
+            Point2d::new(0.0, 4.0),
+            Point2d::new(2.0, 4.0),
+            Point2d::new(1.0, 3.0),
+            Point2d::new(4.0, 4.0),
+        ];
+
+        let hull = convex_hull(&points);
+        assert_eq!(
+            hull.points,
+            vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(4.0, 0.0),
+                Point2d::new(4.0, 4.0),
+                Point2d::new(0.0, 4.0),
+            ]
+        );
+        assert_eq!(hull.winding(), Winding::CounterClockwise);
+    }
+
+    #[test]
+    fn convex_hull_collinear() {
+        let points = [
+            Point2d::new(2.0, 2.0),
+            Point2d::new(0.0, 0.0),
+            Point2d::new(3.0, 3.0),
+            Point2d::new(1.0, 1.0),
+        ];
+
+        let hull = convex_hull(&points);
+        assert_eq!(
+            hull.points,
+            vec![Point2d::new(0.0, 0.0), Point2d::new(3.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn convex_hull_few_points() {
+        assert!(convex_hull::<Point2d>(&[]).points.is_empty());
+
+        let hull = convex_hull(&[Point2d::new(1.0, 1.0), Point2d::new(1.0, 1.0)]);
+        assert_eq!(hull.points, vec![Point2d::new(1.0, 1.0)]);
+
+        let hull = convex_hull(&[Point2d::new(1.0, 1.0), Point2d::new(0.0, 0.0)]);
+        assert_eq!(
+            hull.points,
+            vec![Point2d::new(0.0, 0.0), Point2d::new(1.0, 1.0)]
+        );
+    }
+}
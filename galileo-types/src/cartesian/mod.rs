@@ -1,13 +1,25 @@
 //! Types and functions on geometries in cartesian coordinates.
 
+mod boolean_ops;
+mod bounding;
+mod buffer;
+mod convex_hull;
 mod impls;
 mod orient;
 mod rect;
+pub(crate) mod simplify;
 mod size;
 mod traits;
+mod validate;
 
+pub use boolean_ops::BooleanOps;
+pub use bounding::{minimum_bounding_circle, minimum_bounding_rectangle, BoundingCircle};
+pub use buffer::{Buffer, BufferOptions, CapStyle, JoinStyle};
+pub use convex_hull::convex_hull;
 pub use impls::{Point2, Point2d, Point3, Point3d};
 pub use orient::Orientation;
 pub use rect::Rect;
+pub use simplify::{SimplificationAlgorithm, Simplify};
 pub use size::Size;
 pub use traits::*;
+pub use validate::{Validate, ValidationIssue, ValidationIssueKind};
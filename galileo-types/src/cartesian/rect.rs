@@ -183,6 +183,19 @@ impl<N: Num + Copy + PartialOrd + Scalar + FromPrimitive> Rect<N> {
             && self.y_max >= point.y()
     }
 
+    /// Returns `true` if the `other` rectangle is inside (or on the sides) of this rectangle.
+    pub fn contains_rect(&self, other: &Rect<N>) -> bool {
+        self.x_min <= other.x_min
+            && self.x_max >= other.x_max
+            && self.y_min <= other.y_min
+            && self.y_max >= other.y_max
+    }
+
+    /// Returns `true` if this rectangle is inside (or on the sides) of the `other` rectangle.
+    pub fn within(&self, other: &Rect<N>) -> bool {
+        other.contains_rect(self)
+    }
+
     /// Changes the width and height of the rectangle by the factor of `factor`, keeping the center of the rectangle
     /// at the same place.
     pub fn magnify(&self, factor: N) -> Self {
@@ -250,12 +263,31 @@ impl<N: Num + Copy + PartialOrd + Scalar + FromPrimitive> Rect<N> {
     }
 
     /// Returns true if two rectangle have at least one common point.
-    pub fn intersects(&self, other: Rect<N>) -> bool {
+    ///
+    /// Rectangles that only touch each other by an edge or a corner are considered intersecting.
+    pub fn intersects(&self, other: &Rect<N>) -> bool {
         self.x_max >= other.x_min
             && self.x_min <= other.x_max
-            && self.y_max >= other.y_max
+            && self.y_max >= other.y_min
             && self.y_min <= other.y_max
     }
+
+    /// Returns the rectangle that is covered by both this and the `other` rectangles.
+    ///
+    /// Returns `None` if the rectangles are disjoint. If the rectangles only touch each other by an edge or a corner,
+    /// a zero-area rectangle (with zero width and/or height) along the common boundary is returned.
+    pub fn intersection(&self, other: &Rect<N>) -> Option<Rect<N>> {
+        if self.intersects(other) {
+            Some(self.limit(*other))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the minimum rectangle that contains both this and the `other` rectangles.
+    pub fn union(&self, other: &Rect<N>) -> Rect<N> {
+        self.merge(*other)
+    }
 }
 
 impl<N: Num + Copy + PartialOrd + Scalar + FromPrimitive> FromIterator<Rect<N>>
@@ -271,3 +303,69 @@ impl<N: Num + Copy + PartialOrd + Scalar + FromPrimitive> FromIterator<Rect<N>>
         Some(prev)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersects() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert!(rect.intersects(&Rect::new(5.0, 5.0, 15.0, 15.0)));
+        assert!(rect.intersects(&Rect::new(2.0, 2.0, 3.0, 3.0)));
+        assert!(rect.intersects(&Rect::new(10.0, 0.0, 20.0, 10.0)));
+        assert!(rect.intersects(&Rect::new(10.0, 10.0, 20.0, 20.0)));
+        assert!(!rect.intersects(&Rect::new(11.0, 0.0, 20.0, 10.0)));
+        assert!(!rect.intersects(&Rect::new(0.0, 11.0, 10.0, 20.0)));
+        assert!(!rect.intersects(&Rect::new(0.0, -20.0, 10.0, -11.0)));
+    }
+
+    #[test]
+    fn intersection() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            rect.intersection(&Rect::new(5.0, -5.0, 15.0, 5.0)),
+            Some(Rect::new(5.0, 0.0, 10.0, 5.0))
+        );
+        assert_eq!(
+            rect.intersection(&Rect::new(2.0, 2.0, 3.0, 3.0)),
+            Some(Rect::new(2.0, 2.0, 3.0, 3.0))
+        );
+        assert_eq!(rect.intersection(&Rect::new(11.0, 0.0, 20.0, 10.0)), None);
+    }
+
+    #[test]
+    fn intersection_touching() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let edge = rect
+            .intersection(&Rect::new(10.0, 5.0, 20.0, 15.0))
+            .expect("touching rectangles intersect");
+        assert_eq!(edge, Rect::new(10.0, 5.0, 10.0, 10.0));
+        assert_eq!(edge.width(), 0.0);
+
+        let corner = rect
+            .intersection(&Rect::new(10.0, 10.0, 20.0, 20.0))
+            .expect("touching rectangles intersect");
+        assert_eq!(corner, Rect::new(10.0, 10.0, 10.0, 10.0));
+    }
+
+    #[test]
+    fn union() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(
+            rect.union(&Rect::new(20.0, -5.0, 30.0, 5.0)),
+            Rect::new(0.0, -5.0, 30.0, 10.0)
+        );
+        assert_eq!(rect.union(&Rect::new(2.0, 2.0, 3.0, 3.0)), rect);
+    }
+
+    #[test]
+    fn contains_rect() {
+        let outer = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let inner = Rect::new(0.0, 2.0, 5.0, 10.0);
+        assert!(outer.contains_rect(&inner));
+        assert!(inner.within(&outer));
+        assert!(!inner.contains_rect(&outer));
+        assert!(!Rect::new(5.0, 5.0, 11.0, 6.0).within(&outer));
+    }
+}
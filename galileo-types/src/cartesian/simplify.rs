@@ -0,0 +1,385 @@
+use crate::cartesian::CartesianPoint2d;
+use crate::geometry::Geom;
+use crate::impls::{MultiContour, MultiPolygon, Polygon};
+use crate::segment::Segment;
+use num_traits::Zero;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Algorithm used to simplify geometries with the [`Simplify`] trait.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimplificationAlgorithm {
+    /// [Ramer–Douglas–Peucker](https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm)
+    /// algorithm. Points that are closer than the tolerance to the simplified line are removed.
+    #[default]
+    DouglasPeucker,
+    /// [Visvalingam–Whyatt](https://en.wikipedia.org/wiki/Visvalingam%E2%80%93Whyatt_algorithm) algorithm. Points
+    /// with the smallest effective area (the area of the triangle formed with their neighbours) are removed one by one
+    /// while the area is less than square of the tolerance.
+    ///
+    /// This algorithm tends to produce smoother shapes than Douglas–Peucker for natural features like coastlines.
+    Visvalingam,
+}
+
+/// Geometries that can be simplified by removing the points that do not change their shape significantly.
+///
+/// The points of the simplified geometry are a subset of the original points. The first and the last points of open
+/// contours are always retained. Simplified closed contours keep at least 3 points if the original had as many,
+/// except for Douglas–Peucker that can collapse a ring smaller than the tolerance into a segment. Holes of
+/// polygons that collapse in this way are removed.
+///
+/// ```
+/// use galileo_types::cartesian::{Point2d, SimplificationAlgorithm, Simplify};
+/// use galileo_types::impls::Contour;
+/// use galileo_types::Contour as _;
+///
+/// let contour = Contour::open(vec![
+///     Point2d::new(0.0, 0.0),
+///     Point2d::new(1.0, 0.1),
+///     Point2d::new(2.0, 0.0),
+/// ]);
+///
+/// let simplified = contour.simplify_with(SimplificationAlgorithm::Visvalingam, 0.5);
+/// assert_eq!(simplified.iter_points().count(), 2);
+/// ```
+pub trait Simplify: Sized {
+    /// Numeric type of the tolerance.
+    type Num;
+
+    /// Returns a simplified copy of the geometry using the given algorithm.
+    ///
+    /// The `tolerance` is a distance in the units of the geometry coordinates.
+    fn simplify_with(&self, algorithm: SimplificationAlgorithm, tolerance: Self::Num) -> Self;
+}
+
+impl<P: CartesianPoint2d + Clone> Simplify for Polygon<P> {
+    type Num = P::Num;
+
+    fn simplify_with(&self, algorithm: SimplificationAlgorithm, tolerance: P::Num) -> Self {
+        Polygon::new(
+            self.outer_contour.simplify_with(algorithm, tolerance),
+            self.inner_contours
+                .iter()
+                .map(|contour| contour.simplify_with(algorithm, tolerance))
+                .filter(|contour| contour.points.len() >= 3)
+                .collect(),
+        )
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Simplify for MultiContour<P> {
+    type Num = P::Num;
+
+    fn simplify_with(&self, algorithm: SimplificationAlgorithm, tolerance: P::Num) -> Self {
+        use crate::MultiContour as _;
+
+        self.contours()
+            .map(|contour| contour.simplify_with(algorithm, tolerance))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Simplify for MultiPolygon<P> {
+    type Num = P::Num;
+
+    fn simplify_with(&self, algorithm: SimplificationAlgorithm, tolerance: P::Num) -> Self {
+        self.parts
+            .iter()
+            .map(|polygon| polygon.simplify_with(algorithm, tolerance))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Simplify for Geom<P> {
+    type Num = P::Num;
+
+    /// Simplifies contours and polygons. Point geometries are returned unchanged.
+    fn simplify_with(&self, algorithm: SimplificationAlgorithm, tolerance: P::Num) -> Self {
+        match self {
+            Geom::Point(_) | Geom::MultiPoint(_) => self.clone(),
+            Geom::Contour(contour) => Geom::Contour(contour.simplify_with(algorithm, tolerance)),
+            Geom::MultiContour(contour) => {
+                Geom::MultiContour(contour.simplify_with(algorithm, tolerance))
+            }
+            Geom::Polygon(polygon) => Geom::Polygon(polygon.simplify_with(algorithm, tolerance)),
+            Geom::MultiPolygon(polygon) => {
+                Geom::MultiPolygon(polygon.simplify_with(algorithm, tolerance))
+            }
+        }
+    }
+}
+
+/// Simplifies a contour with the given algorithm.
+pub(crate) fn simplify_points<P: CartesianPoint2d + Clone>(
+    points: &[P],
+    is_closed: bool,
+    algorithm: SimplificationAlgorithm,
+    tolerance: P::Num,
+) -> Vec<P> {
+    match (algorithm, is_closed) {
+        (SimplificationAlgorithm::DouglasPeucker, false) => douglas_peucker(points, tolerance),
+        (SimplificationAlgorithm::DouglasPeucker, true) => {
+            let Some(first) = points.first() else {
+                return vec![];
+            };
+
+            // Closing point is added to take the last segment into account, and then removed from the result.
+            let mut closing = points.to_vec();
+            closing.push(first.clone());
+
+            let mut simplified = douglas_peucker(&closing, tolerance);
+            simplified.pop();
+            simplified
+        }
+        (SimplificationAlgorithm::Visvalingam, is_closed) => {
+            visvalingam(points, is_closed, tolerance)
+        }
+    }
+}
+
+fn douglas_peucker<P: CartesianPoint2d + Clone>(points: &[P], tolerance: P::Num) -> Vec<P> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let tolerance_sq = tolerance * tolerance;
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        let segment = Segment(&points[start], &points[end]);
+        let mut max_distance = tolerance_sq;
+        let mut max_index = None;
+        for (index, point) in points.iter().enumerate().take(end).skip(start + 1) {
+            let distance = segment.distance_to_point_sq(point);
+            if distance > max_distance {
+                max_distance = distance;
+                max_index = Some(index);
+            }
+        }
+
+        if let Some(index) = max_index {
+            keep[index] = true;
+            ranges.push((start, index));
+            ranges.push((index, end));
+        }
+    }
+
+    retained(points, &keep)
+}
+
+fn visvalingam<P: CartesianPoint2d + Clone>(
+    points: &[P],
+    is_closed: bool,
+    tolerance: P::Num,
+) -> Vec<P> {
+    let count = points.len();
+    let min_count = if is_closed { 3 } else { 2 };
+    if count <= min_count {
+        return points.to_vec();
+    }
+
+    // Areas are compared doubled to avoid division.
+    let threshold = tolerance * tolerance + tolerance * tolerance;
+    let double_area = |prev: usize, index: usize, next: usize| {
+        let a = points[index].sub(&points[prev]);
+        let b = points[next].sub(&points[prev]);
+        let cross = a.x * b.y - a.y * b.x;
+        if cross < P::Num::zero() {
+            P::Num::zero() - cross
+        } else {
+            cross
+        }
+    };
+
+    let mut prev: Vec<usize> = (0..count).map(|i| (i + count - 1) % count).collect();
+    let mut next: Vec<usize> = (0..count).map(|i| (i + 1) % count).collect();
+    let mut keep = vec![true; count];
+    let mut versions = vec![0u32; count];
+
+    // The first point is always retained, and for open contours so is the last one.
+    let removable = |index: usize| index != 0 && (is_closed || index != count - 1);
+
+    let mut heap = BinaryHeap::new();
+    for index in (1..count).filter(|&index| removable(index)) {
+        heap.push(EffectiveArea {
+            area: double_area(prev[index], index, next[index]),
+            index,
+            version: 0,
+        });
+    }
+
+    let mut remaining = count;
+    while let Some(EffectiveArea {
+        area,
+        index,
+        version,
+    }) = heap.pop()
+    {
+        if !keep[index] || versions[index] != version {
+            continue;
+        }
+
+        if area >= threshold || remaining <= min_count {
+            break;
+        }
+
+        keep[index] = false;
+        remaining -= 1;
+
+        let (prev_index, next_index) = (prev[index], next[index]);
+        next[prev_index] = next_index;
+        prev[next_index] = prev_index;
+
+        for neighbour in [prev_index, next_index] {
+            if !removable(neighbour) {
+                continue;
+            }
+
+            // Effective area of the neighbours cannot be less than the area of the removed point, otherwise the
+            // points would be removed in the wrong order.
+            let mut neighbour_area = double_area(prev[neighbour], neighbour, next[neighbour]);
+            if neighbour_area < area {
+                neighbour_area = area;
+            }
+
+            versions[neighbour] += 1;
+            heap.push(EffectiveArea {
+                area: neighbour_area,
+                index: neighbour,
+                version: versions[neighbour],
+            });
+        }
+    }
+
+    retained(points, &keep)
+}
+
+fn retained<P: Clone>(points: &[P], keep: &[bool]) -> Vec<P> {
+    points
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| **keep)
+        .map(|(p, _)| p.clone())
+        .collect()
+}
+
+/// Entry of the min-heap of the effective areas of the points in Visvalingam algorithm.
+struct EffectiveArea<N> {
+    area: N,
+    index: usize,
+    version: u32,
+}
+
+impl<N: PartialOrd> PartialEq for EffectiveArea<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N: PartialOrd> Eq for EffectiveArea<N> {}
+
+impl<N: PartialOrd> PartialOrd for EffectiveArea<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N: PartialOrd> Ord for EffectiveArea<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed to make the smallest area the top of the heap.
+        other
+            .area
+            .partial_cmp(&self.area)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::Point2d;
+    use crate::impls::{ClosedContour, Contour};
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point2d> {
+        coords.iter().map(|&(x, y)| Point2d::new(x, y)).collect()
+    }
+
+    #[test]
+    fn visvalingam_removes_small_bumps() {
+        let contour = Contour::open(points(&[
+            (0.0, 0.0),
+            (1.0, 0.1),
+            (2.0, 0.0),
+            (3.0, 5.0),
+            (4.0, 0.0),
+        ]));
+
+        let simplified = contour.simplify_with(SimplificationAlgorithm::Visvalingam, 1.0);
+        assert_eq!(
+            simplified,
+            Contour::open(points(&[(0.0, 0.0), (2.0, 0.0), (3.0, 5.0), (4.0, 0.0)]))
+        );
+    }
+
+    #[test]
+    fn visvalingam_keeps_three_points_of_closed_contour() {
+        let contour = ClosedContour::new(points(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]));
+
+        let simplified = contour.simplify_with(SimplificationAlgorithm::Visvalingam, 100.0);
+        assert_eq!(simplified.points.len(), 3);
+        assert_eq!(simplified.points[0], Point2d::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn douglas_peucker_matches_inherent_simplify() {
+        let contour = Contour::open(points(&[
+            (0.0, 0.0),
+            (1.0, 0.4),
+            (2.0, -0.3),
+            (3.0, 2.0),
+            (4.0, 0.0),
+        ]));
+
+        assert_eq!(
+            contour.simplify_with(SimplificationAlgorithm::DouglasPeucker, 0.5),
+            contour.simplify(0.5)
+        );
+    }
+
+    #[test]
+    fn polygon_drops_collapsed_holes() {
+        let polygon = Polygon::new(
+            ClosedContour::new(points(&[
+                (0.0, 0.0),
+                (10.0, 0.0),
+                (10.0, 10.0),
+                (0.0, 10.0),
+            ])),
+            vec![ClosedContour::new(points(&[
+                (5.0, 5.0),
+                (5.1, 5.0),
+                (5.1, 5.1),
+                (5.0, 5.1),
+            ]))],
+        );
+
+        let simplified = polygon.simplify_with(SimplificationAlgorithm::DouglasPeucker, 1.0);
+        assert_eq!(simplified.outer_contour, polygon.outer_contour);
+        assert!(simplified.inner_contours.is_empty());
+    }
+
+    #[test]
+    fn points_are_not_simplified() {
+        let geom = Geom::Point(Point2d::new(1.0, 2.0));
+        assert_eq!(
+            geom.simplify_with(SimplificationAlgorithm::Visvalingam, 10.0),
+            geom
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use crate::cartesian::traits::cartesian_point::CartesianPoint2d;
+use crate::cartesian::traits::cartesian_point::{CartesianPoint2d, NewCartesianPoint2d};
+use crate::cartesian::traits::polygon::CartesianPolygon;
+use crate::cartesian::Rect;
 use crate::contour::{ClosedContour, Contour};
+use crate::segment::{Segment, SegmentIntersection};
 use num_traits::{One, Zero};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -17,9 +20,20 @@ pub trait CartesianClosedContour {
         Self: Sized;
 
     /// Winding direction of the contour.
+    ///
+    /// Contours with zero signed area (e.g. all points on one line, or less than 3 points) have no winding direction,
+    /// and [`Winding::Degenerate`] is returned for them.
     fn winding(&self) -> Winding
     where
         Self: Sized;
+
+    /// Returns `true` if the contour does not intersect itself.
+    ///
+    /// Adjacent segments of the contour are allowed to have a common endpoint, but not to overlap. Contours with less
+    /// than 3 points are not simple.
+    fn is_simple(&self) -> bool
+    where
+        Self: Sized;
 }
 
 impl<P, T> CartesianClosedContour for T
@@ -55,12 +69,44 @@ where
     where
         Self: Sized,
     {
-        if self.area_signed() <= P::Num::zero() {
+        let area = self.area_signed();
+        if area < P::Num::zero() {
             Winding::Clockwise
-        } else {
+        } else if area > P::Num::zero() {
             Winding::CounterClockwise
+        } else {
+            Winding::Degenerate
+        }
+    }
+
+    fn is_simple(&self) -> bool
+    where
+        Self: Sized,
+    {
+        let segments: Vec<_> = self.iter_segments().collect();
+        segments.len() >= 3 && !has_self_intersections(&segments, true)
+    }
+}
+
+/// Checks every pair of the segments of a contour for intersections, *O(n²)*. Consecutive segments may touch by
+/// their common point, as well as the last and the first segments of a closed contour.
+pub(crate) fn has_self_intersections<P: CartesianPoint2d>(
+    segments: &[Segment<P>],
+    is_closed: bool,
+) -> bool {
+    let count = segments.len();
+    for i in 0..count {
+        for j in (i + 1)..count {
+            let is_adjacent = j == i + 1 || (is_closed && i == 0 && j == count - 1);
+            match segments[i].intersection(&segments[j]) {
+                None => {}
+                Some(SegmentIntersection::Touching(_)) if is_adjacent => {}
+                Some(_) => return true,
+            }
         }
     }
+
+    false
 }
 
 /// [Winding](https://en.wikipedia.org/wiki/Winding_number) direction of the contour.
@@ -70,6 +116,9 @@ pub enum Winding {
     Clockwise,
     /// Negative winding.
     CounterClockwise,
+    /// The contour has zero signed area, so its winding direction cannot be determined. This is the case for
+    /// contours with all the points on a single line, and contours with less than 3 points.
+    Degenerate,
 }
 
 /// Methods for contours in 2d cartesian space. This trait is auto-implemented if applicable.
@@ -84,10 +133,236 @@ pub trait CartesianContour<P: CartesianPoint2d>: Contour<Point = P> {
             .map(|v| v.distance_to_point_sq(point))
             .min_by(move |a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
     }
+
+    /// Returns the point of the contour closest to the given `point` and the squared distance between them.
+    ///
+    /// The returned point is the projection of the `point` onto the nearest segment of the contour, or a vertex of the
+    /// contour if the projection falls outside of the segments. For closed contours the closing segment is also
+    /// considered. Returns `None` if the contour has no segments.
+    ///
+    /// ```
+    /// use galileo_types::cartesian::{CartesianContour, Point2d};
+    /// use galileo_types::impls::Contour;
+    ///
+    /// let route = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(4.0, 0.0), Point2d::new(4.0, 4.0)]);
+    /// let (snapped, distance_sq) = route.closest_point_on(&Point2d::new(3.0, 1.0)).unwrap();
+    /// assert_eq!(snapped, Point2d::new(3.0, 0.0));
+    /// assert_eq!(distance_sq, 1.0);
+    /// ```
+    fn closest_point_on<Point>(&self, point: &Point) -> Option<(P, f64)>
+    where
+        Self: Sized,
+        P: NewCartesianPoint2d + Clone,
+        Point: CartesianPoint2d<Num = f64>,
+    {
+        self.iter_segments()
+            .map(|v| v.closest_point(point))
+            .min_by(move |a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+    }
+
+    /// Total length of the contour. For closed contours this includes the segment between the last and the first
+    /// points.
+    fn length(&self) -> f64
+    where
+        Self: Sized,
+        P: CartesianPoint2d<Num = f64>,
+    {
+        self.iter_segments()
+            .map(|segment| segment.0.distance_sq(segment.1).sqrt())
+            .sum()
+    }
+
+    /// Returns the point at the given `distance` from the start of the contour, measured along the contour.
+    ///
+    /// The `distance` is clamped to the length of the contour, so negative values return the first point, and values
+    /// larger than the length return the last point (the first point for closed contours). Returns `None` if the
+    /// contour has no points.
+    ///
+    /// ```
+    /// use galileo_types::cartesian::{CartesianContour, Point2d};
+    /// use galileo_types::impls::Contour;
+    ///
+    /// let route = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(4.0, 0.0), Point2d::new(4.0, 4.0)]);
+    /// assert_eq!(route.point_at_distance(6.0), Some(Point2d::new(4.0, 2.0)));
+    /// assert_eq!(route.point_at_fraction(0.25), Some(Point2d::new(2.0, 0.0)));
+    /// ```
+    fn point_at_distance(&self, distance: f64) -> Option<P>
+    where
+        Self: Sized,
+        P: NewCartesianPoint2d + Clone,
+    {
+        let mut last = self.iter_points().next()?;
+        let mut passed = 0.0;
+        for Segment(from, to) in self.iter_segments() {
+            let length = from.distance_sq(to).sqrt();
+            if passed + length >= distance && length > 0.0 {
+                let k = ((distance - passed) / length).max(0.0);
+                return Some(P::new(
+                    from.x() + (to.x() - from.x()) * k,
+                    from.y() + (to.y() - from.y()) * k,
+                ));
+            }
+
+            passed += length;
+            last = to;
+        }
+
+        Some(last.clone())
+    }
+
+    /// Returns the point at the given `fraction` (from `0.0` at the start to `1.0` at the end) of the contour length.
+    /// See [`CartesianContour::point_at_distance`].
+    fn point_at_fraction(&self, fraction: f64) -> Option<P>
+    where
+        Self: Sized,
+        P: NewCartesianPoint2d + Clone,
+    {
+        self.point_at_distance(self.length() * fraction)
+    }
+
+    /// Returns the fraction of the contour length (from `0.0` at the start to `1.0` at the end) at which the point of
+    /// the contour closest to the given `point` is located. This is the inverse operation of
+    /// [`CartesianContour::point_at_fraction`] for the points lying on the contour.
+    ///
+    /// Returns `None` if the contour has no segments.
+    fn locate_point<Point>(&self, point: &Point) -> Option<f64>
+    where
+        Self: Sized,
+        P: NewCartesianPoint2d + Clone,
+        Point: CartesianPoint2d<Num = f64>,
+    {
+        let mut passed = 0.0;
+        let mut closest: Option<(f64, f64)> = None;
+        for segment in self.iter_segments() {
+            let (projected, distance_sq) = segment.closest_point(point);
+            if closest.is_none_or(|(_, closest_sq)| distance_sq < closest_sq) {
+                closest = Some((
+                    passed + segment.0.distance_sq(&projected).sqrt(),
+                    distance_sq,
+                ));
+            }
+
+            passed += segment.0.distance_sq(segment.1).sqrt();
+        }
+
+        let (position, _) = closest?;
+        Some(if passed > 0.0 { position / passed } else { 0.0 })
+    }
+
+    /// Returns the points of the contour placed at every `spacing` distance along the contour, starting with the first
+    /// point. The last point of the contour is only included if the length of the contour is a multiple of
+    /// `spacing`.
+    ///
+    /// This can be used to place markers along a route. If `spacing` is not positive, only the first point is
+    /// returned.
+    ///
+    /// ```
+    /// use galileo_types::cartesian::{CartesianContour, Point2d};
+    /// use galileo_types::impls::Contour;
+    ///
+    /// let route = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(5.0, 0.0)]);
+    /// assert_eq!(
+    ///     route.resample(2.0),
+    ///     vec![Point2d::new(0.0, 0.0), Point2d::new(2.0, 0.0), Point2d::new(4.0, 0.0)]
+    /// );
+    /// ```
+    fn resample(&self, spacing: f64) -> Vec<P>
+    where
+        Self: Sized,
+        P: NewCartesianPoint2d + Clone,
+    {
+        let Some(first) = self.iter_points().next() else {
+            return vec![];
+        };
+        if spacing <= 0.0 || spacing.is_nan() {
+            return vec![first.clone()];
+        }
+
+        let mut result = vec![];
+        let mut passed = 0.0;
+        let mut next = 0.0;
+        for Segment(from, to) in self.iter_segments() {
+            let length = from.distance_sq(to).sqrt();
+            while next <= passed + length && length > 0.0 {
+                let k = (next - passed) / length;
+                result.push(P::new(
+                    from.x() + (to.x() - from.x()) * k,
+                    from.y() + (to.y() - from.y()) * k,
+                ));
+                next += spacing;
+            }
+
+            passed += length;
+        }
+
+        if result.is_empty() {
+            result.push(first.clone());
+        }
+
+        result
+    }
+
+    /// Returns true if this and the `other` contours have at least one common point.
+    fn intersects_contour<C>(&self, other: &C) -> bool
+    where
+        Self: Sized,
+        C: Contour,
+        C::Point: CartesianPoint2d<Num = P::Num>,
+    {
+        let other_segments = segments_or_point(other);
+        segments_or_point(self)
+            .iter()
+            .any(|segment| other_segments.iter().any(|other| segment.intersects(other)))
+    }
+
+    /// Returns true if the contour has at least one common point with the `rect`, including its inner area.
+    fn intersects_rect(&self, rect: &Rect<P::Num>) -> bool
+    where
+        Self: Sized,
+    {
+        self.iter_points().any(|point| rect.contains(point))
+            || self.iter_segments().any(|segment| {
+                rect.into_contour()
+                    .iter_segments()
+                    .any(|side| side.intersects(&segment))
+            })
+    }
+
+    /// Returns true if all the points of the contour are inside the `rect` or on its sides.
+    fn within_rect(&self, rect: &Rect<P::Num>) -> bool
+    where
+        Self: Sized,
+    {
+        self.iter_points().all(|point| rect.contains(point))
+    }
+
+    /// Returns true if all the points of the contour are inside the `polygon` or on its boundary. See
+    /// [`CartesianPolygon::contains_contour`].
+    fn within_polygon(&self, polygon: &impl CartesianPolygon<Point = P>) -> bool
+    where
+        Self: Sized,
+    {
+        polygon.contains_contour(self)
+    }
 }
 
 impl<T: Contour<Point = P>, P: CartesianPoint2d> CartesianContour<P> for T {}
 
+/// Segments of the contour, or a zero-length segment if the contour consists of only one point.
+fn segments_or_point<C: Contour>(contour: &C) -> Vec<Segment<'_, C::Point>> {
+    let segments: Vec<_> = contour.iter_segments().collect();
+    if !segments.is_empty() {
+        return segments;
+    }
+
+    contour
+        .iter_points()
+        .next()
+        .map(|point| Segment(point, point))
+        .into_iter()
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,6 +371,48 @@ mod tests {
     use crate::impls::ClosedContour;
     use crate::segment::Segment;
 
+    #[test]
+    fn is_simple() {
+        let square = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(0.0, 1.0),
+            Point2d::new(1.0, 1.0),
+            Point2d::new(1.0, 0.0),
+        ]);
+        assert!(square.is_simple());
+
+        let bowtie = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(1.0, 1.0),
+            Point2d::new(1.0, 0.0),
+            Point2d::new(0.0, 1.0),
+        ]);
+        assert!(!bowtie.is_simple());
+
+        let touching_vertex = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(0.0, 2.0),
+            Point2d::new(2.0, 2.0),
+            Point2d::new(1.0, 0.0),
+            Point2d::new(0.0, 2.0),
+            Point2d::new(-1.0, 0.0),
+        ]);
+        assert!(!touching_vertex.is_simple());
+
+        let spike = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(0.0, 1.0),
+            Point2d::new(1.0, 1.0),
+            Point2d::new(2.0, 1.0),
+            Point2d::new(1.0, 1.0),
+            Point2d::new(1.0, 0.0),
+        ]);
+        assert!(!spike.is_simple());
+
+        let line = ClosedContour::new(vec![Point2d::new(0.0, 0.0), Point2d::new(1.0, 1.0)]);
+        assert!(!line.is_simple());
+    }
+
     #[test]
     fn iter_points_closing() {
         let contour =
@@ -175,6 +492,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn closest_point_on() {
+        let contour = crate::impls::Contour::open(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(4.0, 0.0),
+            Point2d::new(4.0, 4.0),
+        ]);
+
+        // Mid-segment
+        assert_eq!(
+            contour.closest_point_on(&Point2d::new(1.0, -2.0)),
+            Some((Point2d::new(1.0, 0.0), 4.0))
+        );
+        assert_eq!(
+            contour.closest_point_on(&Point2d::new(5.0, 3.0)),
+            Some((Point2d::new(4.0, 3.0), 1.0))
+        );
+
+        // Vertices
+        assert_eq!(
+            contour.closest_point_on(&Point2d::new(6.0, -1.0)),
+            Some((Point2d::new(4.0, 0.0), 5.0))
+        );
+        assert_eq!(
+            contour.closest_point_on(&Point2d::new(-3.0, 4.0)),
+            Some((Point2d::new(0.0, 0.0), 25.0))
+        );
+        assert_eq!(
+            contour.closest_point_on(&Point2d::new(4.0, 4.0)),
+            Some((Point2d::new(4.0, 4.0), 0.0))
+        );
+
+        // The closing segment of a closed contour
+        let closed = ClosedContour::new(contour.iter_points().copied().collect());
+        assert_eq!(
+            closed.closest_point_on(&Point2d::new(1.0, 3.0)),
+            Some((Point2d::new(2.0, 2.0), 2.0))
+        );
+
+        let single = crate::impls::Contour::open(vec![Point2d::new(1.0, 1.0)]);
+        assert_eq!(single.closest_point_on(&Point2d::new(0.0, 0.0)), None);
+    }
+
     #[test]
     fn area() {
         let contour = ClosedContour::new(vec![
@@ -212,4 +572,97 @@ mod tests {
 
         assert_eq!(contour.winding(), Winding::CounterClockwise);
     }
+
+    #[test]
+    fn winding_degenerate() {
+        let collinear = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(1.0, 1.0),
+            Point2d::new(2.0, 2.0),
+        ]);
+        assert_eq!(collinear.winding(), Winding::Degenerate);
+
+        let two_points = ClosedContour::new(vec![Point2d::new(0.0, 0.0), Point2d::new(1.0, 0.0)]);
+        assert_eq!(two_points.winding(), Winding::Degenerate);
+
+        let empty = ClosedContour::<Point2d>::new(vec![]);
+        assert_eq!(empty.winding(), Winding::Degenerate);
+    }
+
+    #[test]
+    fn contour_intersections() {
+        let line =
+            crate::impls::Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(2.0, 2.0)]);
+        let crossing =
+            crate::impls::Contour::open(vec![Point2d::new(0.0, 2.0), Point2d::new(2.0, 0.0)]);
+        let parallel =
+            crate::impls::Contour::open(vec![Point2d::new(0.0, 1.0), Point2d::new(1.0, 2.0)]);
+        let point = crate::impls::Contour::open(vec![Point2d::new(1.0, 1.0)]);
+
+        assert!(line.intersects_contour(&crossing));
+        assert!(!line.intersects_contour(&parallel));
+        assert!(line.intersects_contour(&point));
+        assert!(point.intersects_contour(&line));
+    }
+
+    #[test]
+    fn contour_and_rect() {
+        let rect = Rect::new(0.0, 0.0, 1.0, 1.0);
+        let crossing =
+            crate::impls::Contour::open(vec![Point2d::new(-1.0, 0.5), Point2d::new(2.0, 0.5)]);
+        let outside =
+            crate::impls::Contour::open(vec![Point2d::new(-1.0, 2.0), Point2d::new(2.0, 2.0)]);
+        let inside =
+            crate::impls::Contour::open(vec![Point2d::new(0.5, 0.5), Point2d::new(1.0, 1.0)]);
+
+        assert!(crossing.intersects_rect(&rect));
+        assert!(!crossing.within_rect(&rect));
+        assert!(!outside.intersects_rect(&rect));
+        assert!(inside.intersects_rect(&rect));
+        assert!(inside.within_rect(&rect));
+    }
+
+    #[test]
+    fn linear_referencing() {
+        let route = crate::impls::Contour::open(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(3.0, 0.0),
+            Point2d::new(3.0, 4.0),
+        ]);
+
+        assert_eq!(route.length(), 7.0);
+        assert_eq!(route.point_at_distance(-1.0), Some(Point2d::new(0.0, 0.0)));
+        assert_eq!(route.point_at_distance(3.0), Some(Point2d::new(3.0, 0.0)));
+        assert_eq!(route.point_at_distance(5.0), Some(Point2d::new(3.0, 2.0)));
+        assert_eq!(route.point_at_distance(100.0), Some(Point2d::new(3.0, 4.0)));
+        assert_eq!(route.locate_point(&Point2d::new(5.0, 2.0)), Some(5.0 / 7.0));
+        assert_eq!(route.locate_point(&Point2d::new(-1.0, -1.0)), Some(0.0));
+
+        let closed = crate::impls::Contour::closed(route.iter_points().copied().collect());
+        assert_eq!(closed.length(), 12.0);
+        assert_eq!(closed.point_at_fraction(1.0), Some(Point2d::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn resample_contour() {
+        let route = crate::impls::Contour::open(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(3.0, 0.0),
+            Point2d::new(3.0, 3.0),
+        ]);
+
+        assert_eq!(
+            route.resample(2.0),
+            vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(2.0, 0.0),
+                Point2d::new(3.0, 1.0),
+                Point2d::new(3.0, 3.0),
+            ]
+        );
+        assert_eq!(route.resample(0.0), vec![Point2d::new(0.0, 0.0)]);
+
+        let single = crate::impls::Contour::open(vec![Point2d::new(1.0, 1.0)]);
+        assert_eq!(single.resample(1.0), vec![Point2d::new(1.0, 1.0)]);
+    }
 }
@@ -1,5 +1,5 @@
 mod cartesian_point;
-mod contour;
+pub(crate) mod contour;
 mod polygon;
 
 pub use cartesian_point::{
@@ -1,18 +1,53 @@
 use crate::cartesian::traits::cartesian_point::CartesianPoint2d;
-use crate::contour::ClosedContour;
+use crate::cartesian::traits::contour::CartesianContour;
+use crate::cartesian::Rect;
+use crate::contour::{ClosedContour, Contour};
 use crate::polygon::Polygon;
-use crate::segment::Segment;
+use crate::segment::{Segment, SegmentIntersection};
 use nalgebra::Point2;
+use num_traits::{One, Zero};
+use std::cmp::Ordering;
 
 /// Polygon in 2d cartesian coordinates. This trait is auto-implemented for all illegible types.
+///
+/// All the predicates consider the boundary of the polygon (including the boundaries of its holes) to be a part of
+/// the polygon. Points on the boundary are determined exactly, without rounding errors.
 pub trait CartesianPolygon {
     /// Type of the points of the polygon.
     type Point: CartesianPoint2d;
 
     /// Returns true if the `point` lies inside or on one of the polygon's sides.
+    ///
+    /// The points inside holes of the polygon are not contained by it. Overlapping holes and contours are treated by
+    /// the even-odd rule.
     fn contains_point<P>(&self, point: &P) -> bool
     where
         P: CartesianPoint2d<Num = <Self::Point as CartesianPoint2d>::Num>;
+
+    /// Returns true if all the points of the `contour` are inside the polygon or on its boundary.
+    fn contains_contour<C>(&self, contour: &C) -> bool
+    where
+        C: Contour,
+        C::Point: CartesianPoint2d<Num = <Self::Point as CartesianPoint2d>::Num>;
+
+    /// Returns true if the polygon and the `contour` have at least one common point.
+    fn intersects_contour<C>(&self, contour: &C) -> bool
+    where
+        C: Contour,
+        C::Point: CartesianPoint2d<Num = <Self::Point as CartesianPoint2d>::Num>;
+
+    /// Returns true if this and the `other` polygons have at least one common point.
+    fn intersects_polygon<T>(&self, other: &T) -> bool
+    where
+        T: Polygon,
+        <T::Contour as Contour>::Point:
+            CartesianPoint2d<Num = <Self::Point as CartesianPoint2d>::Num>;
+
+    /// Returns true if the polygon and the `rect` have at least one common point.
+    fn intersects_rect(&self, rect: &Rect<<Self::Point as CartesianPoint2d>::Num>) -> bool;
+
+    /// Returns true if the polygon is completely inside the `rect` (touching the sides of the `rect` is allowed).
+    fn within_rect(&self, rect: &Rect<<Self::Point as CartesianPoint2d>::Num>) -> bool;
 }
 
 impl<P, C, T> CartesianPolygon for T
@@ -24,39 +59,133 @@ where
     type Point = P;
 
     fn contains_point<Point: CartesianPoint2d<Num = P::Num>>(&self, point: &Point) -> bool {
-        let mut wn = 0i64;
-        let x = point.x();
-        let y = point.y();
+        polygon_contains_point(self, point)
+    }
+
+    fn contains_contour<Cont>(&self, contour: &Cont) -> bool
+    where
+        Cont: Contour,
+        Cont::Point: CartesianPoint2d<Num = P::Num>,
+    {
+        let zero = P::Num::zero();
+        let one = P::Num::one();
+        let two = one + one;
 
-        for segment in self.iter_segments() {
-            if segment.0.x() < x && segment.1.x() < x {
-                continue;
+        contour.iter_segments().all(|segment| {
+            if !self.contains_point(segment.0) || !self.contains_point(segment.1) {
+                return false;
             }
 
-            let is_to_right = segment.0.x() > x && segment.1.x() > x || {
-                let x_max = if segment.0.x() > segment.1.x() {
-                    segment.0.x()
-                } else {
-                    segment.1.x()
-                };
-                let ray_p1 = Point2::new(x, y);
-                let ray_p2 = Point2::new(x_max, y);
-                let ray = Segment(&ray_p1, &ray_p2);
-
-                segment.intersects(&ray)
+            // The segment can leave the polygon only through the points where it intersects the boundary, so the
+            // middle points of the parts between these points are checked.
+            let start = Point2::new(segment.0.x(), segment.0.y());
+            let direction = Point2::new(segment.1.x() - start.x, segment.1.y() - start.y);
+            let length_sq = direction.x * direction.x + direction.y * direction.y;
+            if length_sq == zero {
+                return true;
+            }
+
+            let position = |point: &Point2<P::Num>| {
+                ((point.x - start.x) * direction.x + (point.y - start.y) * direction.y) / length_sq
             };
 
-            if is_to_right {
-                if segment.0.y() < y && segment.1.y() >= y {
-                    wn += 1;
-                } else if segment.0.y() > y && segment.1.y() <= y {
-                    wn -= 1;
+            let mut positions = vec![zero, one];
+            for boundary in self.iter_segments() {
+                match boundary.intersection(&segment) {
+                    Some(SegmentIntersection::Crossing(point))
+                    | Some(SegmentIntersection::Touching(point)) => {
+                        positions.push(position(&point))
+                    }
+                    Some(SegmentIntersection::Overlap(a, b)) => {
+                        positions.push(position(&a));
+                        positions.push(position(&b));
+                    }
+                    None => {}
                 }
             }
-        }
 
-        wn != 0
+            positions.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            positions.windows(2).all(|pair| {
+                if pair[0] == pair[1] {
+                    return true;
+                }
+
+                let k = (pair[0] + pair[1]) / two;
+                let middle = Point2::new(start.x + direction.x * k, start.y + direction.y * k);
+                self.contains_point(&middle)
+            })
+        })
+    }
+
+    fn intersects_contour<Cont>(&self, contour: &Cont) -> bool
+    where
+        Cont: Contour,
+        Cont::Point: CartesianPoint2d<Num = P::Num>,
+    {
+        contour
+            .iter_points()
+            .next()
+            .is_some_and(|point| self.contains_point(point))
+            || self
+                .iter_contours()
+                .any(|boundary| boundary.intersects_contour(contour))
+    }
+
+    fn intersects_polygon<Other>(&self, other: &Other) -> bool
+    where
+        Other: Polygon,
+        <Other::Contour as Contour>::Point: CartesianPoint2d<Num = P::Num>,
+    {
+        other
+            .iter_contours()
+            .any(|contour| self.intersects_contour(contour))
+            || self
+                .outer_contour()
+                .iter_points()
+                .next()
+                .is_some_and(|point| polygon_contains_point(other, point))
+    }
+
+    fn intersects_rect(&self, rect: &Rect<P::Num>) -> bool {
+        let Some(bbox) = Rect::from_points(self.outer_contour().iter_points()) else {
+            return false;
+        };
+        bbox.intersects(rect)
+            && self.intersects_polygon(&crate::impls::Polygon::from(rect.into_contour()))
+    }
+
+    fn within_rect(&self, rect: &Rect<P::Num>) -> bool {
+        self.outer_contour().within_rect(rect)
+    }
+}
+
+fn polygon_contains_point<T, N>(polygon: &T, point: &impl CartesianPoint2d<Num = N>) -> bool
+where
+    T: Polygon,
+    <T::Contour as Contour>::Point: CartesianPoint2d<Num = N>,
+    N: num_traits::Num + Copy + PartialOrd + nalgebra::Scalar,
+{
+    let degenerate = Segment(point, point);
+    if polygon
+        .iter_segments()
+        .any(|segment| segment.intersects(&degenerate))
+    {
+        return true;
+    }
+
+    let x = point.x();
+    let y = point.y();
+    let mut is_inside = false;
+    for Segment(a, b) in polygon.iter_segments() {
+        if (a.y() > y) != (b.y() > y) {
+            let x_cross = a.x() + (y - a.y()) * (b.x() - a.x()) / (b.y() - a.y());
+            if x < x_cross {
+                is_inside = !is_inside;
+            }
+        }
     }
+
+    is_inside
 }
 
 #[cfg(test)]
@@ -85,4 +214,95 @@ mod tests {
         assert!(!polygon.contains_point(&Point2d::new(0.2, -0.3)));
         assert!(!polygon.contains_point(&Point2d::new(1.1, 0.0)));
     }
+
+    fn square(x_min: f64, y_min: f64, size: f64) -> crate::impls::ClosedContour<Point2d> {
+        crate::impls::ClosedContour::new(vec![
+            Point2d::new(x_min, y_min),
+            Point2d::new(x_min + size, y_min),
+            Point2d::new(x_min + size, y_min + size),
+            Point2d::new(x_min, y_min + size),
+        ])
+    }
+
+    fn polygon_with_hole() -> crate::impls::Polygon<Point2d> {
+        crate::impls::Polygon::new(square(0.0, 0.0, 10.0), vec![square(4.0, 4.0, 2.0)])
+    }
+
+    #[test]
+    fn contains_point_with_holes() {
+        let polygon = polygon_with_hole();
+        assert!(polygon.contains_point(&Point2d::new(1.0, 1.0)));
+        assert!(!polygon.contains_point(&Point2d::new(5.0, 5.0)));
+        // Points on the boundary of the hole belong to the polygon.
+        assert!(polygon.contains_point(&Point2d::new(4.0, 5.0)));
+        assert!(polygon.contains_point(&Point2d::new(6.0, 6.0)));
+        assert!(polygon.contains_point(&Point2d::new(10.0, 3.0)));
+        assert!(!polygon.contains_point(&Point2d::new(10.0 + 1e-9, 3.0)));
+    }
+
+    #[test]
+    fn contains_point_hole_winding_does_not_matter() {
+        let mut hole = square(4.0, 4.0, 2.0);
+        hole.points.reverse();
+        let polygon = crate::impls::Polygon::new(square(0.0, 0.0, 10.0), vec![hole]);
+        assert!(!polygon.contains_point(&Point2d::new(5.0, 5.0)));
+    }
+
+    #[test]
+    fn contains_contour() {
+        let polygon = polygon_with_hole();
+        let inside =
+            crate::impls::Contour::open(vec![Point2d::new(1.0, 1.0), Point2d::new(3.0, 9.0)]);
+        let through_hole =
+            crate::impls::Contour::open(vec![Point2d::new(1.0, 5.0), Point2d::new(9.0, 5.0)]);
+        let along_boundary =
+            crate::impls::Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(10.0, 0.0)]);
+
+        assert!(polygon.contains_contour(&inside));
+        assert!(!polygon.contains_contour(&through_hole));
+        assert!(polygon.contains_contour(&along_boundary));
+        assert!(inside.within_polygon(&polygon));
+    }
+
+    #[test]
+    fn contains_contour_through_reflex_vertex() {
+        // U-shaped polygon, the segment connects the ends of the U through the air between them.
+        let polygon = crate::impls::Polygon::new(
+            crate::impls::ClosedContour::new(vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(3.0, 0.0),
+                Point2d::new(3.0, 3.0),
+                Point2d::new(2.0, 3.0),
+                Point2d::new(2.0, 1.0),
+                Point2d::new(1.0, 1.0),
+                Point2d::new(1.0, 3.0),
+                Point2d::new(0.0, 3.0),
+            ]),
+            vec![],
+        );
+        let contour =
+            crate::impls::Contour::open(vec![Point2d::new(0.5, 3.0), Point2d::new(2.5, 3.0)]);
+        assert!(!polygon.contains_contour(&contour));
+        assert!(polygon.intersects_contour(&contour));
+    }
+
+    #[test]
+    fn intersects() {
+        let polygon = polygon_with_hole();
+        let in_hole = crate::impls::Polygon::from(square(4.5, 4.5, 1.0));
+        let around = crate::impls::Polygon::from(square(-1.0, -1.0, 20.0));
+        let touching = crate::impls::Polygon::from(square(10.0, 10.0, 1.0));
+        let outside = crate::impls::Polygon::from(square(11.0, 0.0, 1.0));
+
+        assert!(!polygon.intersects_polygon(&in_hole));
+        assert!(polygon.intersects_polygon(&around));
+        assert!(around.intersects_polygon(&polygon));
+        assert!(polygon.intersects_polygon(&touching));
+        assert!(!polygon.intersects_polygon(&outside));
+
+        assert!(polygon.intersects_rect(&Rect::new(-5.0, -5.0, 1.0, 1.0)));
+        assert!(!polygon.intersects_rect(&Rect::new(4.5, 4.5, 5.5, 5.5)));
+        assert!(polygon.within_rect(&Rect::new(0.0, 0.0, 10.0, 10.0)));
+        assert!(!polygon.within_rect(&Rect::new(0.0, 0.0, 9.0, 10.0)));
+    }
 }
@@ -0,0 +1,660 @@
+//! Validation and repair of geometries.
+//!
+//! Geometries are not checked when they are created, but polygons with self-intersections, repeated points or
+//! holes wound the same way as the outer contour cannot be tessellated correctly. [`Validate`] lists such problems,
+//! and fixes the ones that can be fixed without changing the shape of the geometry:
+//!
+//! ```
+//! use galileo_types::cartesian::{Point2d, Validate, ValidationIssue, ValidationIssueKind};
+//! use galileo_types::impls::{ClosedContour, Polygon};
+//!
+//! // Clockwise square with the first point repeated at the end.
+//! let polygon = Polygon::new(
+//!     ClosedContour::new(vec![
+//!         Point2d::new(0.0, 0.0),
+//!         Point2d::new(0.0, 1.0),
+//!         Point2d::new(1.0, 1.0),
+//!         Point2d::new(1.0, 0.0),
+//!         Point2d::new(0.0, 0.0),
+//!     ]),
+//!     vec![],
+//! );
+//!
+//! let issue = |kind| ValidationIssue { part: 0, contour: 0, kind };
+//! assert_eq!(
+//!     polygon.validate(),
+//!     vec![
+//!         issue(ValidationIssueKind::DuplicatePoint(4)),
+//!         issue(ValidationIssueKind::WrongWinding),
+//!     ]
+//! );
+//!
+//! let fixed = polygon.fix();
+//! assert!(fixed.is_valid());
+//! assert_eq!(fixed.outer_contour.points.len(), 4);
+//! ```
+
+use crate::cartesian::traits::contour::has_self_intersections;
+use crate::cartesian::{CartesianClosedContour, CartesianPoint2d, Orientation, Winding};
+use crate::geometry::Geom;
+use crate::impls::{ClosedContour, Contour, MultiContour, MultiPolygon, Polygon};
+use crate::segment::{Segment, SegmentIntersection};
+use std::fmt::{Display, Formatter};
+
+/// Problem found in a geometry by [`Validate::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ValidationIssue {
+    /// Index of the part of a multi-geometry with the problem. Always `0` for other geometries.
+    pub part: usize,
+    /// Index of the contour with the problem in a polygon: `0` for the outer contour and `1..` for the holes. Always
+    /// `0` for other geometries.
+    pub contour: usize,
+    /// What is wrong with the contour.
+    pub kind: ValidationIssueKind,
+}
+
+/// Kind of [`ValidationIssue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationIssueKind {
+    /// The contour has less distinct points than needed: 3 for closed contours and 2 for open ones.
+    TooFewPoints,
+    /// The point with the given index is the same as the next point. For closed contours this includes the last
+    /// point repeating the first one, since the closing segment of a closed contour is implied.
+    DuplicatePoint(usize),
+    /// The point with the given index lies on the line through its neighbours. The point either does not change the
+    /// shape of the contour, or is the tip of a zero-width spike.
+    CollinearPoint(usize),
+    /// Two non-adjacent segments of the contour intersect.
+    SelfIntersection,
+    /// The contour crosses or overlaps the contour of the same polygon with the given index. Contours touching each
+    /// other at single points are allowed.
+    ContourIntersection(usize),
+    /// The outer contour of a polygon is not counterclockwise, or a hole of a polygon is not clockwise.
+    WrongWinding,
+    /// An open contour ends at its first point, so it is a ring that is not marked as closed.
+    UnclosedRing,
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "part {}, contour {}: ", self.part, self.contour)?;
+        match self.kind {
+            ValidationIssueKind::TooFewPoints => write!(f, "too few points"),
+            ValidationIssueKind::DuplicatePoint(index) => {
+                write!(f, "point {index} is the same as the next point")
+            }
+            ValidationIssueKind::CollinearPoint(index) => {
+                write!(f, "point {index} is collinear with its neighbours")
+            }
+            ValidationIssueKind::SelfIntersection => write!(f, "self-intersection"),
+            ValidationIssueKind::ContourIntersection(other) => {
+                write!(f, "intersects contour {other}")
+            }
+            ValidationIssueKind::WrongWinding => write!(f, "wrong winding"),
+            ValidationIssueKind::UnclosedRing => write!(f, "ring is not closed"),
+        }
+    }
+}
+
+/// Geometries that can be checked for problems that break their rendering and processing.
+///
+/// Outer contours of polygons are expected to be counterclockwise and holes clockwise, as produced by
+/// [`BooleanOps`](crate::cartesian::BooleanOps). Checking for intersections takes *O(n²)* time for a contour with *n*
+/// points.
+pub trait Validate: Sized {
+    /// Returns all the problems found in the geometry. The geometry is valid if the list is empty.
+    fn validate(&self) -> Vec<ValidationIssue>;
+
+    /// Returns `true` if no problems are found in the geometry.
+    fn is_valid(&self) -> bool {
+        self.validate().is_empty()
+    }
+
+    /// Returns a copy of the geometry with the problems fixed as far as possible without changing its shape:
+    /// duplicate and collinear points are removed, contours of polygons are reordered to the expected winding, and
+    /// open contours ending at their first point are closed. Holes of polygons and parts of multipolygons that have
+    /// less than 3 points left are removed.
+    ///
+    /// Intersections are not fixed, so the result must be validated again if it is required to be valid.
+    fn fix(&self) -> Self;
+}
+
+impl<P: CartesianPoint2d + Clone> Validate for Contour<P> {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        use crate::Contour as _;
+
+        let points: Vec<P> = self.iter_points().cloned().collect();
+        let mut issues = vec![];
+        let (points, is_closed) = match unclosed_ring(&points, self.is_closed()) {
+            Some(ring) => {
+                issues.push(ValidationIssueKind::UnclosedRing);
+                (ring, true)
+            }
+            None => (&points[..], self.is_closed()),
+        };
+
+        issues.extend(contour_issues(points, is_closed));
+        in_part(0, 0, issues)
+    }
+
+    fn fix(&self) -> Self {
+        use crate::Contour as _;
+
+        let points: Vec<P> = self.iter_points().cloned().collect();
+        match unclosed_ring(&points, self.is_closed()) {
+            Some(ring) => Contour::closed(fix_points(ring, true)),
+            None => Contour::new(fix_points(&points, self.is_closed()), self.is_closed()),
+        }
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Validate for ClosedContour<P> {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        in_part(0, 0, contour_issues(&self.points, true))
+    }
+
+    fn fix(&self) -> Self {
+        ClosedContour::new(fix_points(&self.points, true))
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Validate for Polygon<P> {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        polygon_issues(self, 0)
+    }
+
+    fn fix(&self) -> Self {
+        Polygon::new(
+            fix_ring(&self.outer_contour, Winding::CounterClockwise),
+            self.inner_contours
+                .iter()
+                .map(|contour| fix_ring(contour, Winding::Clockwise))
+                .filter(|contour| contour.points.len() >= 3)
+                .collect(),
+        )
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Validate for MultiContour<P> {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        use crate::MultiContour as _;
+
+        self.contours()
+            .enumerate()
+            .flat_map(|(part, contour)| {
+                contour
+                    .validate()
+                    .into_iter()
+                    .map(move |issue| ValidationIssue { part, ..issue })
+            })
+            .collect()
+    }
+
+    fn fix(&self) -> Self {
+        use crate::MultiContour as _;
+
+        self.contours()
+            .map(Validate::fix)
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Validate for MultiPolygon<P> {
+    fn validate(&self) -> Vec<ValidationIssue> {
+        self.parts
+            .iter()
+            .enumerate()
+            .flat_map(|(part, polygon)| polygon_issues(polygon, part))
+            .collect()
+    }
+
+    fn fix(&self) -> Self {
+        self.parts
+            .iter()
+            .map(Validate::fix)
+            .filter(|polygon| polygon.outer_contour.points.len() >= 3)
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Validate for Geom<P> {
+    /// Validates contours and polygons. Point geometries are always valid.
+    fn validate(&self) -> Vec<ValidationIssue> {
+        match self {
+            Geom::Point(_) | Geom::MultiPoint(_) => vec![],
+            Geom::Contour(contour) => contour.validate(),
+            Geom::MultiContour(contour) => contour.validate(),
+            Geom::Polygon(polygon) => polygon.validate(),
+            Geom::MultiPolygon(polygon) => polygon.validate(),
+        }
+    }
+
+    /// Fixes contours and polygons. Point geometries are returned unchanged.
+    fn fix(&self) -> Self {
+        match self {
+            Geom::Point(_) | Geom::MultiPoint(_) => self.clone(),
+            Geom::Contour(contour) => Geom::Contour(contour.fix()),
+            Geom::MultiContour(contour) => Geom::MultiContour(contour.fix()),
+            Geom::Polygon(polygon) => Geom::Polygon(polygon.fix()),
+            Geom::MultiPolygon(polygon) => Geom::MultiPolygon(polygon.fix()),
+        }
+    }
+}
+
+fn in_part(part: usize, contour: usize, kinds: Vec<ValidationIssueKind>) -> Vec<ValidationIssue> {
+    kinds
+        .into_iter()
+        .map(|kind| ValidationIssue {
+            part,
+            contour,
+            kind,
+        })
+        .collect()
+}
+
+fn polygon_issues<P: CartesianPoint2d>(polygon: &Polygon<P>, part: usize) -> Vec<ValidationIssue> {
+    let contours = std::iter::once(&polygon.outer_contour).chain(&polygon.inner_contours);
+
+    let mut issues = vec![];
+    let mut rings = vec![];
+    for (index, contour) in contours.enumerate() {
+        issues.extend(in_part(part, index, contour_issues(&contour.points, true)));
+
+        let distinct = distinct_points(&contour.points, true);
+        if distinct.len() < 3 {
+            continue;
+        }
+
+        let expected = if index == 0 {
+            Winding::CounterClockwise
+        } else {
+            Winding::Clockwise
+        };
+        let winding = contour.winding();
+        if winding != expected && winding != Winding::Degenerate {
+            issues.extend(in_part(
+                part,
+                index,
+                vec![ValidationIssueKind::WrongWinding],
+            ));
+        }
+
+        rings.push((index, segments(&distinct, true)));
+    }
+
+    for (i, (index, ring)) in rings.iter().enumerate() {
+        for (other_index, other) in &rings[i + 1..] {
+            if rings_intersect(ring, other) {
+                issues.extend(in_part(
+                    part,
+                    *index,
+                    vec![ValidationIssueKind::ContourIntersection(*other_index)],
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Problems of a single contour, that do not depend on other contours of the geometry.
+fn contour_issues<P: CartesianPoint2d>(points: &[P], is_closed: bool) -> Vec<ValidationIssueKind> {
+    let mut issues = vec![];
+
+    let count = points.len();
+    for index in 0..count {
+        let next = match index + 1 {
+            next if next < count => next,
+            _ if is_closed && count > 1 => 0,
+            _ => continue,
+        };
+
+        if is_same(&points[index], &points[next]) {
+            issues.push(ValidationIssueKind::DuplicatePoint(index));
+        }
+    }
+
+    let distinct = distinct_points(points, is_closed);
+    if distinct.len() < min_points(is_closed) {
+        issues.push(ValidationIssueKind::TooFewPoints);
+        return issues;
+    }
+
+    issues.extend(
+        collinear(&distinct, is_closed)
+            .into_iter()
+            .map(ValidationIssueKind::CollinearPoint),
+    );
+
+    if has_self_intersections(&segments(&distinct, is_closed), is_closed) {
+        issues.push(ValidationIssueKind::SelfIntersection);
+    }
+
+    issues
+}
+
+/// If the open contour ends at its first point, returns its points without the last one.
+fn unclosed_ring<P: CartesianPoint2d>(points: &[P], is_closed: bool) -> Option<&[P]> {
+    match points {
+        [first, .., last] if !is_closed && points.len() > 2 && is_same(first, last) => {
+            Some(&points[..points.len() - 1])
+        }
+        _ => None,
+    }
+}
+
+fn min_points(is_closed: bool) -> usize {
+    if is_closed {
+        3
+    } else {
+        2
+    }
+}
+
+fn is_same<P: CartesianPoint2d>(a: &P, b: &P) -> bool {
+    a.x() == b.x() && a.y() == b.y()
+}
+
+fn is_collinear<P: CartesianPoint2d>(prev: &P, point: &P, next: &P) -> bool {
+    Orientation::triplet(prev, point, next) == Orientation::Collinear
+}
+
+/// Points of the contour without the repeated points, with their indices in the contour.
+fn distinct_points<P: CartesianPoint2d>(points: &[P], is_closed: bool) -> Vec<(usize, &P)> {
+    let mut distinct: Vec<(usize, &P)> = Vec::with_capacity(points.len());
+    for (index, point) in points.iter().enumerate() {
+        if distinct
+            .last()
+            .is_none_or(|(_, last)| !is_same(*last, point))
+        {
+            distinct.push((index, point));
+        }
+    }
+
+    if is_closed {
+        while distinct.len() > 1 && is_same(distinct[0].1, distinct[distinct.len() - 1].1) {
+            distinct.pop();
+        }
+    }
+
+    distinct
+}
+
+/// Indices of the distinct points that are collinear with their neighbours. The first and the last points of open
+/// contours have only one neighbour and are never returned.
+fn collinear<P: CartesianPoint2d>(distinct: &[(usize, &P)], is_closed: bool) -> Vec<usize> {
+    let count = distinct.len();
+    (0..count)
+        .filter_map(|index| {
+            let (prev, next) = if is_closed {
+                ((index + count - 1) % count, (index + 1) % count)
+            } else if index > 0 && index + 1 < count {
+                (index - 1, index + 1)
+            } else {
+                return None;
+            };
+
+            is_collinear(distinct[prev].1, distinct[index].1, distinct[next].1)
+                .then_some(distinct[index].0)
+        })
+        .collect()
+}
+
+fn segments<'a, P>(distinct: &[(usize, &'a P)], is_closed: bool) -> Vec<Segment<'a, P>> {
+    let mut segments: Vec<_> = distinct
+        .windows(2)
+        .map(|pair| Segment(pair[0].1, pair[1].1))
+        .collect();
+    if let (true, Some(first), Some(last)) = (is_closed, distinct.first(), distinct.last()) {
+        segments.push(Segment(last.1, first.1));
+    }
+
+    segments
+}
+
+fn rings_intersect<P: CartesianPoint2d>(a: &[Segment<P>], b: &[Segment<P>]) -> bool {
+    a.iter().any(|a| {
+        b.iter().any(|b| {
+            matches!(
+                a.intersection(b),
+                Some(SegmentIntersection::Crossing(_) | SegmentIntersection::Overlap(..))
+            )
+        })
+    })
+}
+
+/// Removes duplicate and collinear points of the contour.
+fn fix_points<P: CartesianPoint2d + Clone>(points: &[P], is_closed: bool) -> Vec<P> {
+    let mut fixed: Vec<&P> = Vec::with_capacity(points.len());
+    for point in points {
+        if fixed.last().is_some_and(|last| is_same(*last, point)) {
+            continue;
+        }
+
+        fixed.push(point);
+        // Removing the tip of a spike makes its base a duplicate point.
+        while let [.., prev, middle, next] = fixed[..] {
+            if is_same(prev, next) {
+                fixed.truncate(fixed.len() - 2);
+            } else if is_collinear(prev, middle, next) {
+                fixed.remove(fixed.len() - 2);
+            } else {
+                break;
+            }
+        }
+    }
+
+    if is_closed {
+        // Points around the implied closing segment.
+        while fixed.len() >= 3 {
+            let count = fixed.len();
+            if is_same(fixed[0], fixed[count - 1])
+                || is_collinear(fixed[count - 2], fixed[count - 1], fixed[0])
+            {
+                fixed.pop();
+            } else if is_collinear(fixed[count - 1], fixed[0], fixed[1]) {
+                fixed.remove(0);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fixed.into_iter().cloned().collect()
+}
+
+fn fix_ring<P: CartesianPoint2d + Clone>(
+    contour: &ClosedContour<P>,
+    winding: Winding,
+) -> ClosedContour<P> {
+    let mut fixed = contour.fix();
+    if fixed.winding() != winding && fixed.winding() != Winding::Degenerate {
+        fixed.points.reverse();
+    }
+
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::Point2d;
+    use crate::Contour as _;
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point2d> {
+        coords.iter().map(|&(x, y)| Point2d::new(x, y)).collect()
+    }
+
+    fn kinds(issues: Vec<ValidationIssue>) -> Vec<ValidationIssueKind> {
+        issues.into_iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn valid_polygon() {
+        let polygon = Polygon::new(
+            ClosedContour::new(points(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)])),
+            vec![ClosedContour::new(points(&[
+                (1.0, 1.0),
+                (1.0, 2.0),
+                (2.0, 2.0),
+                (2.0, 1.0),
+            ]))],
+        );
+
+        assert!(polygon.is_valid());
+        assert_eq!(polygon.fix(), polygon);
+    }
+
+    #[test]
+    fn duplicate_and_collinear_points() {
+        let contour = ClosedContour::new(points(&[
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 2.0),
+        ]));
+        assert_eq!(
+            kinds(contour.validate()),
+            vec![
+                ValidationIssueKind::DuplicatePoint(2),
+                ValidationIssueKind::CollinearPoint(1),
+            ]
+        );
+        assert_eq!(
+            contour.fix().points,
+            points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0)])
+        );
+
+        // Zero-width spike of an open contour collapses into a single point.
+        let contour = Contour::open(points(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (1.0, 0.0)]));
+        assert_eq!(
+            kinds(contour.validate()),
+            vec![
+                ValidationIssueKind::CollinearPoint(2),
+                ValidationIssueKind::SelfIntersection,
+            ]
+        );
+        let fixed = contour.fix();
+        assert_eq!(
+            fixed.iter_points().cloned().collect::<Vec<_>>(),
+            points(&[(0.0, 0.0), (1.0, 0.0)])
+        );
+        assert!(fixed.is_valid());
+    }
+
+    #[test]
+    fn too_few_points() {
+        let contour = ClosedContour::new(points(&[(0.0, 0.0), (1.0, 1.0), (0.0, 0.0)]));
+        assert_eq!(
+            kinds(contour.validate()),
+            vec![
+                ValidationIssueKind::DuplicatePoint(2),
+                ValidationIssueKind::TooFewPoints,
+            ]
+        );
+
+        let contour = Contour::open(points(&[(1.0, 1.0)]));
+        assert_eq!(
+            kinds(contour.validate()),
+            vec![ValidationIssueKind::TooFewPoints]
+        );
+    }
+
+    #[test]
+    fn wrong_winding_and_collapsed_holes() {
+        let polygon = Polygon::new(
+            ClosedContour::new(points(&[(0.0, 0.0), (0.0, 4.0), (4.0, 4.0), (4.0, 0.0)])),
+            vec![
+                ClosedContour::new(points(&[(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0)])),
+                ClosedContour::new(points(&[(3.0, 3.0), (3.5, 3.0), (3.0, 3.0)])),
+            ],
+        );
+
+        let issues = polygon.validate();
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue {
+                    part: 0,
+                    contour: 0,
+                    kind: ValidationIssueKind::WrongWinding
+                },
+                ValidationIssue {
+                    part: 0,
+                    contour: 1,
+                    kind: ValidationIssueKind::WrongWinding
+                },
+                ValidationIssue {
+                    part: 0,
+                    contour: 2,
+                    kind: ValidationIssueKind::DuplicatePoint(2)
+                },
+                ValidationIssue {
+                    part: 0,
+                    contour: 2,
+                    kind: ValidationIssueKind::TooFewPoints
+                },
+            ]
+        );
+        assert_eq!(issues[1].to_string(), "part 0, contour 1: wrong winding");
+
+        let fixed = polygon.fix();
+        assert!(fixed.is_valid());
+        assert_eq!(fixed.outer_contour.winding(), Winding::CounterClockwise);
+        assert_eq!(fixed.inner_contours.len(), 1);
+        assert_eq!(fixed.inner_contours[0].winding(), Winding::Clockwise);
+    }
+
+    #[test]
+    fn intersections_are_reported_but_not_fixed() {
+        let bow_tie = Polygon::new(
+            ClosedContour::new(points(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)])),
+            vec![],
+        );
+        assert_eq!(
+            kinds(bow_tie.validate()),
+            vec![ValidationIssueKind::SelfIntersection]
+        );
+        assert_eq!(bow_tie.fix(), bow_tie);
+
+        let hole_outside = MultiPolygon::from(vec![
+            bow_tie.fix(),
+            Polygon::new(
+                ClosedContour::new(points(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)])),
+                vec![ClosedContour::new(points(&[
+                    (3.0, 1.0),
+                    (3.0, 2.0),
+                    (5.0, 2.0),
+                    (5.0, 1.0),
+                ]))],
+            ),
+        ]);
+        let issues = hole_outside.validate();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[1],
+            ValidationIssue {
+                part: 1,
+                contour: 0,
+                kind: ValidationIssueKind::ContourIntersection(1),
+            }
+        );
+    }
+
+    #[test]
+    fn unclosed_ring() {
+        let contour = Contour::open(points(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]));
+        assert_eq!(
+            kinds(contour.validate()),
+            vec![ValidationIssueKind::UnclosedRing]
+        );
+
+        let fixed = contour.fix();
+        assert!(fixed.is_closed());
+        assert_eq!(fixed.iter_points().count(), 3);
+        assert!(fixed.is_valid());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::geo::{Datum, GeoPoint};
+use num_traits::{Float, FloatConst};
+
+/// Signed area of a closed ring of points on the surface of the `datum` ellipsoid. Positive for counterclockwise
+/// rings.
+///
+/// The points are mapped to the sphere of equal surface area using authalic latitudes, and the area is calculated as
+/// the spherical excess of the ring on that sphere. The mapping preserves areas exactly, so the only error comes from
+/// the difference between the edges of the rings on the sphere and geodesics on the ellipsoid. This difference is
+/// negligible for rings with edges shorter than a few hundred kilometers.
+pub(crate) fn ring_area_signed<'a, N, P>(points: impl Iterator<Item = &'a P>, datum: &Datum) -> N
+where
+    N: Float + FloatConst,
+    P: GeoPoint<Num = N> + 'a,
+{
+    let authalic = Authalic::new(datum);
+    let two = N::one() + N::one();
+
+    let mut points = points.map(|p| (authalic.latitude(p.lat_rad()), p.lon_rad()));
+    let Some(first) = points.next() else {
+        return N::zero();
+    };
+
+    let mut excess = N::zero();
+    let mut prev = first;
+    for point in points.chain(std::iter::once(first)) {
+        excess = excess + edge_excess(prev, point, two);
+        prev = point;
+    }
+
+    let radius = N::from(authalic.radius).expect("f64 value must be representable");
+    // Edge excesses are positive for the edges going east in the northern hemisphere, which is the clockwise
+    // direction for the rings, so the sign is inverted.
+    -excess * radius * radius
+}
+
+/// Signed spherical excess of the area between the edge and the equator.
+fn edge_excess<N: Float + FloatConst>(from: (N, N), to: (N, N), two: N) -> N {
+    let mut d_lon = to.1 - from.1;
+    // The edge must go the short way around, also for the edges crossing the antimeridian.
+    if d_lon > N::PI() {
+        d_lon = d_lon - two * N::PI();
+    } else if d_lon < -N::PI() {
+        d_lon = d_lon + two * N::PI();
+    }
+
+    let t1 = (from.0 / two).tan();
+    let t2 = (to.0 / two).tan();
+    two * ((d_lon / two).tan() * (t1 + t2)).atan2(N::one() + t1 * t2)
+}
+
+/// Parameters of the mapping from the ellipsoid to the sphere of the same surface area.
+struct Authalic {
+    eccentricity: f64,
+    q_pole: f64,
+    radius: f64,
+}
+
+impl Authalic {
+    fn new(datum: &Datum) -> Self {
+        let flattening = 1.0 / datum.inv_flattening();
+        let eccentricity = (flattening * (2.0 - flattening)).sqrt();
+        let q_pole = Self::q(eccentricity, 1.0);
+        Self {
+            eccentricity,
+            q_pole,
+            radius: datum.semimajor() * (q_pole / 2.0).sqrt(),
+        }
+    }
+
+    fn q(eccentricity: f64, sin_lat: f64) -> f64 {
+        let e_sin = eccentricity * sin_lat;
+        (1.0 - eccentricity * eccentricity)
+            * (sin_lat / (1.0 - e_sin * e_sin)
+                - ((1.0 - e_sin) / (1.0 + e_sin)).ln() / (2.0 * eccentricity))
+    }
+
+    fn latitude<N: Float>(&self, lat: N) -> N {
+        let lat = lat.to_f64().expect("latitude must be representable as f64");
+        let authalic = (Self::q(self.eccentricity, lat.sin()) / self.q_pole)
+            .clamp(-1.0, 1.0)
+            .asin();
+        N::from(authalic).expect("f64 value must be representable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::impls::GeoPoint2d;
+    use crate::geo::{GeoClosedContour, GeoPolygon, NewGeoPoint};
+    use crate::impls::{ClosedContour, Polygon};
+
+    fn ring(points: &[(f64, f64)]) -> ClosedContour<GeoPoint2d> {
+        ClosedContour::new(
+            points
+                .iter()
+                .map(|&(lat, lon)| GeoPoint2d::latlon(lat, lon))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn one_degree_cell_area() {
+        // Reference value for the polygon with geodesic edges, calculated with GeographicLib.
+        let contour = ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        let area = contour.geodesic_area_signed(&Datum::WGS84);
+        assert!((area / 12_308_778_361.469 - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn area_sign_depends_on_winding() {
+        let ccw = ring(&[(50.0, 10.0), (50.0, 11.0), (51.0, 11.0), (51.0, 10.0)]);
+        let cw = ring(&[(51.0, 10.0), (51.0, 11.0), (50.0, 11.0), (50.0, 10.0)]);
+        let ccw_area = ccw.geodesic_area_signed(&Datum::WGS84);
+        assert!(ccw_area > 0.0);
+        assert!((ccw_area + cw.geodesic_area_signed(&Datum::WGS84)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn area_across_antimeridian() {
+        let crossing = ring(&[
+            (-20.0, 179.5),
+            (-20.0, -179.5),
+            (-19.0, -179.5),
+            (-19.0, 179.5),
+        ]);
+        let shifted = ring(&[(-20.0, -0.5), (-20.0, 0.5), (-19.0, 0.5), (-19.0, -0.5)]);
+        let crossing_area = crossing.geodesic_area_signed(&Datum::WGS84);
+        let shifted_area = shifted.geodesic_area_signed(&Datum::WGS84);
+        assert!((crossing_area - shifted_area).abs() < 1.0);
+        assert!(crossing_area > 1e10);
+    }
+
+    #[test]
+    fn polygon_area_excludes_holes() {
+        let outer = ring(&[(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0)]);
+        let hole = ring(&[(0.5, 0.5), (1.5, 0.5), (1.5, 1.5), (0.5, 1.5)]);
+        let expected = outer.geodesic_area_signed(&Datum::WGS84)
+            - hole.geodesic_area_signed(&Datum::WGS84).abs();
+
+        let polygon = Polygon::new(outer, vec![hole]);
+        let area = polygon.geodesic_area(&Datum::WGS84);
+        assert!((area - expected).abs() < 1e-3);
+        assert!(area < polygon.outer_contour.geodesic_area_signed(&Datum::WGS84));
+    }
+}
@@ -1,9 +1,14 @@
-use crate::cartesian::NewCartesianPoint2d;
+use crate::cartesian::{NewCartesianPoint2d, Point2d};
 use crate::geo::datum::Datum;
-use crate::geo::impls::projection::{GeodesyProjection, WebMercator};
+use crate::geo::impls::projection::{
+    CrsProjection, CrsTransform, GeoToProjected, GeodesyProjection, WebMercator,
+};
+use crate::geo::impls::GeoPoint2d;
 use crate::geo::traits::point::NewGeoPoint;
 use crate::geo::traits::projection::Projection;
+use crate::geometry_type::GeoSpace2d;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Coordinate reference system.
 #[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
@@ -47,6 +52,43 @@ impl Crs {
         }
     }
 
+    /// Creates a CRS from a PROJ definition string, e.g. `+proj=tmerc +lat_0=49 +lon_0=-2 +k=0.9996012717
+    /// +x_0=400000 +y_0=-100000 +ellps=airy` for the British National Grid (EPSG:27700).
+    ///
+    /// The definition is converted into a `geodesy` operator, so only the projections supported by the `geodesy`
+    /// crate can be used. Datum shift parameters (`towgs84`, `nadgrids`) are not applied.
+    ///
+    /// Returns `None` if the definition cannot be parsed or uses an unsupported projection.
+    #[cfg(feature = "geodesy")]
+    pub fn from_proj4(definition: &str) -> Option<Self> {
+        let definition = geodesy::authoring::parse_proj(definition).ok()?;
+        GeodesyProjection::<GeoPoint2d, Point2d>::new(&definition)?;
+
+        Some(Self::new(Datum::WGS84, ProjectionType::Other(definition)))
+    }
+
+    /// Creates a CRS for the given zone of the Universal Transverse Mercator projection on the WGS84 ellipsoid, same
+    /// as EPSG:326xx (northern hemisphere) and EPSG:327xx (southern hemisphere).
+    ///
+    /// Returns `None` if the `zone` is not in the range `1..=60`.
+    #[cfg(feature = "geodesy")]
+    pub fn utm(zone: u8, north: bool) -> Option<Self> {
+        if !(1..=60).contains(&zone) {
+            return None;
+        }
+
+        let hemisphere = if north { "" } else { " south" };
+        Some(Self::new(
+            Datum::WGS84,
+            ProjectionType::Other(format!("utm zone={zone}{hemisphere} ellps=WGS84")),
+        ))
+    }
+
+    /// Returns true if the coordinates of the CRS are *latitude* and *longitude*, like in [`Crs::WGS84`].
+    pub fn is_geographic(&self) -> bool {
+        self.projection_type == ProjectionType::None
+    }
+
     /// Returns a projection that converts geographic coordinates into the coordinates of this CRS.
     ///
     /// Returns `None` if the CRS coordinates cannot be projected from geographic coordinates.
@@ -65,4 +107,107 @@ impl Crs {
             _ => None,
         }
     }
+
+    /// Returns a reusable projection that converts coordinates of this CRS into the coordinates of the `other` CRS.
+    ///
+    /// This should be preferred to [`Crs::get_projection`] when many points must be projected, as the projection
+    /// is set up only once. Note that datum shift between the CRSs is not applied.
+    ///
+    /// The returned projection works with [`Point2d`], use [`CrsProjection::with_points`] for other point types.
+    ///
+    /// Returns `None` if either of the CRSs cannot be converted from or into geographic coordinates.
+    pub fn projection_to(&self, other: &Crs) -> Option<CrsProjection> {
+        Some(CrsProjection::new(
+            self.transform()?,
+            other.transform()?,
+            self == other,
+        ))
+    }
+
+    /// Returns a reusable projection that converts geographic points into the coordinates of this CRS.
+    ///
+    /// Unlike the projection returned by [`Crs::get_projection`], it is set up only once, can be cloned cheaply and
+    /// projects into Web Mercator without dynamic dispatch, so it should be used to project many points.
+    ///
+    /// Returns `None` if the CRS coordinates cannot be projected from geographic coordinates.
+    pub fn projection_from_geo<In, Out>(&self) -> Option<CrsProjection<In, Out, GeoSpace2d>>
+    where
+        In: NewGeoPoint,
+        Out: NewCartesianPoint2d,
+    {
+        Some(CrsProjection::new(
+            CrsTransform::Geographic,
+            self.transform()?,
+            self.is_geographic(),
+        ))
+    }
+
+    /// Conversion between geographic coordinates and the coordinates of this CRS.
+    fn transform(&self) -> Option<CrsTransform> {
+        let transform = match &self.projection_type {
+            ProjectionType::None => CrsTransform::Geographic,
+            ProjectionType::WebMercator => CrsTransform::WebMercator(WebMercator::new(self.datum)),
+            ProjectionType::Other(definition) => {
+                let projection: GeoToProjected =
+                    Arc::new(GeodesyProjection::<GeoPoint2d, Point2d>::new(definition)?);
+                CrsTransform::Other(projection)
+            }
+            ProjectionType::Unknown => return None,
+        };
+
+        Some(transform)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "geodesy")]
+mod tests {
+    use super::*;
+    use crate::cartesian::CartesianPoint2d;
+    use crate::geo::traits::point::GeoPoint;
+
+    fn project(crs: &Crs, lat: f64, lon: f64) -> Point2d {
+        crs.get_projection::<GeoPoint2d, Point2d>()
+            .unwrap()
+            .project(&GeoPoint2d::latlon(lat, lon))
+            .unwrap()
+    }
+
+    #[test]
+    fn proj4_definition() {
+        let crs = Crs::from_proj4(
+            "+proj=tmerc +lat_0=49 +lon_0=-2 +k=0.9996012717 +x_0=400000 +y_0=-100000 +ellps=airy +units=m +no_defs",
+        )
+        .unwrap();
+
+        let origin = project(&crs, 49.0, -2.0);
+        assert!((origin.x() - 400_000.0).abs() < 1e-6);
+        assert!((origin.y() + 100_000.0).abs() < 1e-6);
+
+        let projection = crs.get_projection::<GeoPoint2d, Point2d>().unwrap();
+        let london = GeoPoint2d::latlon(51.5, -0.12);
+        let unprojected = projection
+            .unproject(&projection.project(&london).unwrap())
+            .unwrap();
+        assert!((unprojected.lat() - london.lat()).abs() < 1e-9);
+        assert!((unprojected.lon() - london.lon()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid_proj4_definition() {
+        assert!(Crs::from_proj4("+proj=unknown +lat_0=49").is_none());
+    }
+
+    #[test]
+    fn utm_zones() {
+        let north = project(&Crs::utm(32, true).unwrap(), 0.0, 9.0);
+        assert!((north.x() - 500_000.0).abs() < 1e-6);
+        assert!(north.y().abs() < 1e-6);
+
+        let south = project(&Crs::utm(32, false).unwrap(), 0.0, 9.0);
+        assert!((south.y() - 10_000_000.0).abs() < 1e-6);
+
+        assert!(Crs::utm(0, true).is_none());
+        assert!(Crs::utm(61, true).is_none());
+    }
 }
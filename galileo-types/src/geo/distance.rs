@@ -0,0 +1,440 @@
+use crate::geo::{Datum, GeoPoint};
+use num_traits::{Float, FloatConst};
+
+/// Mean radius of the Earth in meters, as defined by IUGG.
+pub const EARTH_MEAN_RADIUS: f64 = 6_371_008.8;
+
+const VINCENTY_MAX_ITERATIONS: usize = 200;
+const VINCENTY_TOLERANCE: f64 = 1e-12;
+
+/// Great-circle distance between two geographic points on a sphere with the given `radius`.
+///
+/// The returned value has the same units as the `radius`. Use [`EARTH_MEAN_RADIUS`] to get the distance in meters
+/// on the Earth surface. The formula is numerically stable for close points, antipodal points and for points on
+/// different sides of the antimeridian.
+///
+/// ```
+/// use galileo_types::geo::{haversine_distance, EARTH_MEAN_RADIUS};
+/// use galileo_types::latlon;
+///
+/// let distance = haversine_distance(&latlon!(0.0, 179.0), &latlon!(0.0, -179.0), EARTH_MEAN_RADIUS);
+/// assert!((distance - 222_390.0).abs() < 1.0);
+/// ```
+pub fn haversine_distance<N: Float>(
+    a: &impl GeoPoint<Num = N>,
+    b: &impl GeoPoint<Num = N>,
+    radius: N,
+) -> N {
+    let two = N::one() + N::one();
+    let half_d_lat = (b.lat_rad() - a.lat_rad()) / two;
+    let half_sum_lat = (b.lat_rad() + a.lat_rad()) / two;
+    let half_d_lon = (b.lon_rad() - a.lon_rad()) / two;
+
+    let h =
+        half_d_lat.sin().powi(2) + a.lat_rad().cos() * b.lat_rad().cos() * half_d_lon.sin().powi(2);
+    // `1 - h` expanded into a sum of non-negative terms. Subtracting `h` from one directly loses precision for
+    // nearly antipodal points.
+    let h_complement = (half_d_lat.cos() * half_d_lon.cos()).powi(2)
+        + (half_sum_lat.sin() * half_d_lon.sin()).powi(2);
+
+    two * radius * h.sqrt().atan2(h_complement.sqrt())
+}
+
+/// Geodesic distance between two geographic points on the surface of the `datum` ellipsoid, calculated using
+/// Vincenty's inverse formula.
+///
+/// The distance is returned in the units of the datum semimajor axis (meters for [`Datum::WGS84`]). The result is
+/// accurate to fractions of a millimeter, but the iterative method does not converge for nearly antipodal points.
+/// In this case `None` is returned, and [`haversine_distance`] can be used as a fallback.
+///
+/// ```
+/// use galileo_types::geo::{vincenty_distance, Datum};
+/// use galileo_types::latlon;
+///
+/// let distance = vincenty_distance(&latlon!(0.0, 0.0), &latlon!(0.0, 1.0), &Datum::WGS84).unwrap();
+/// assert!((distance - 111_319.491).abs() < 0.001);
+/// ```
+pub fn vincenty_distance<N: Float + FloatConst>(
+    a: &impl GeoPoint<Num = N>,
+    b: &impl GeoPoint<Num = N>,
+    datum: &Datum,
+) -> Option<N> {
+    vincenty_inverse(a, b, datum).map(|(distance, _)| distance)
+}
+
+/// Geodesic distance on the `datum` ellipsoid, falling back to the great-circle distance on the sphere with the mean
+/// radius of the ellipsoid when Vincenty's formula does not converge.
+pub(crate) fn geodesic_distance<N: Float + FloatConst>(
+    a: &impl GeoPoint<Num = N>,
+    b: &impl GeoPoint<Num = N>,
+    datum: &Datum,
+) -> N {
+    match vincenty_inverse(a, b, datum) {
+        Some((distance, _)) => distance,
+        None => haversine_distance(a, b, num(mean_radius(datum))),
+    }
+}
+
+/// Initial azimuth of the geodesic from `a` to `b` in degrees clockwise from the north in the range `[0, 360)`.
+/// Falls back to the great-circle bearing when Vincenty's formula does not converge.
+pub(crate) fn geodesic_azimuth<N: Float + FloatConst>(
+    a: &impl GeoPoint<Num = N>,
+    b: &impl GeoPoint<Num = N>,
+    datum: &Datum,
+) -> N {
+    let azimuth = match vincenty_inverse(a, b, datum) {
+        Some((_, azimuth)) => azimuth,
+        None => {
+            let d_lon = b.lon_rad() - a.lon_rad();
+            let y = d_lon.sin() * b.lat_rad().cos();
+            let x = a.lat_rad().cos() * b.lat_rad().sin()
+                - a.lat_rad().sin() * b.lat_rad().cos() * d_lon.cos();
+            y.atan2(x)
+        }
+    };
+
+    let degrees = azimuth.to_degrees();
+    if degrees < N::zero() {
+        degrees + num(360.0)
+    } else {
+        degrees
+    }
+}
+
+/// Point at the given `distance` from the `start` point along the geodesic with the initial `azimuth` (in degrees
+/// clockwise from the north) on the `datum` ellipsoid, calculated using Vincenty's direct formula. Returns latitude
+/// and longitude of the point in degrees.
+pub(crate) fn geodesic_destination<N: Float>(
+    start: &impl GeoPoint<Num = N>,
+    azimuth: N,
+    distance: N,
+    datum: &Datum,
+) -> (N, N) {
+    let to_f64 = |v: N| v.to_f64().expect("value must be representable as f64");
+    let semimajor = datum.semimajor();
+    let flattening = 1.0 / datum.inv_flattening();
+    let semiminor = semimajor * (1.0 - flattening);
+
+    let (sin_alpha1, cos_alpha1) = to_f64(azimuth).to_radians().sin_cos();
+    let tan_u1 = (1.0 - flattening) * to_f64(start.lat_rad()).tan();
+    let cos_u1 = 1.0 / (1.0 + tan_u1 * tan_u1).sqrt();
+    let sin_u1 = tan_u1 * cos_u1;
+
+    let sigma1 = tan_u1.atan2(cos_alpha1);
+    let sin_alpha = cos_u1 * sin_alpha1;
+    let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+    let u_sq =
+        cos_sq_alpha * (semimajor * semimajor - semiminor * semiminor) / (semiminor * semiminor);
+    let big_a = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+    let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+
+    let base_sigma = to_f64(distance) / (semiminor * big_a);
+    let mut sigma = base_sigma;
+    let (mut sin_sigma, mut cos_sigma, mut cos_2sigma_m);
+    let mut iterations = 0;
+    loop {
+        cos_2sigma_m = (2.0 * sigma1 + sigma).cos();
+        (sin_sigma, cos_sigma) = sigma.sin_cos();
+        let delta_sigma = big_b
+            * sin_sigma
+            * (cos_2sigma_m
+                + big_b / 4.0
+                    * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)
+                        - big_b / 6.0
+                            * cos_2sigma_m
+                            * (-3.0 + 4.0 * sin_sigma * sin_sigma)
+                            * (-3.0 + 4.0 * cos_2sigma_m * cos_2sigma_m)));
+        let prev_sigma = sigma;
+        sigma = base_sigma + delta_sigma;
+        iterations += 1;
+        if (sigma - prev_sigma).abs() < VINCENTY_TOLERANCE || iterations >= VINCENTY_MAX_ITERATIONS
+        {
+            break;
+        }
+    }
+
+    let tmp = sin_u1 * sin_sigma - cos_u1 * cos_sigma * cos_alpha1;
+    let lat = (sin_u1 * cos_sigma + cos_u1 * sin_sigma * cos_alpha1)
+        .atan2((1.0 - flattening) * (sin_alpha * sin_alpha + tmp * tmp).sqrt());
+    let lambda =
+        (sin_sigma * sin_alpha1).atan2(cos_u1 * cos_sigma - sin_u1 * sin_sigma * cos_alpha1);
+    let c = flattening / 16.0 * cos_sq_alpha * (4.0 + flattening * (4.0 - 3.0 * cos_sq_alpha));
+    let lon_diff = lambda
+        - (1.0 - c)
+            * flattening
+            * sin_alpha
+            * (sigma
+                + c * sin_sigma
+                    * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)));
+
+    let lon = (to_f64(start.lon()) + lon_diff.to_degrees() + 540.0).rem_euclid(360.0) - 180.0;
+    (num(lat.to_degrees()), num(lon))
+}
+
+/// Mean radius of the ellipsoid `(2a + b) / 3`.
+fn mean_radius(datum: &Datum) -> f64 {
+    let semiminor = datum.semimajor() * (1.0 - 1.0 / datum.inv_flattening());
+    (2.0 * datum.semimajor() + semiminor) / 3.0
+}
+
+fn num<N: Float>(v: f64) -> N {
+    N::from(v).expect("f64 value must be representable")
+}
+
+/// Vincenty's inverse formula. Returns the distance and the initial azimuth in radians, or `None` if the method does
+/// not converge.
+fn vincenty_inverse<N: Float + FloatConst>(
+    a: &impl GeoPoint<Num = N>,
+    b: &impl GeoPoint<Num = N>,
+    datum: &Datum,
+) -> Option<(N, N)> {
+    let num = |v: f64| num::<N>(v);
+    let semimajor = num(datum.semimajor());
+    let flattening = N::one() / num(datum.inv_flattening());
+    let semiminor = semimajor * (N::one() - flattening);
+
+    let reduced_lat = |lat: N| ((N::one() - flattening) * lat.tan()).atan();
+    let (sin_u1, cos_u1) = reduced_lat(a.lat_rad()).sin_cos();
+    let (sin_u2, cos_u2) = reduced_lat(b.lat_rad()).sin_cos();
+
+    let lon_diff = b.lon_rad() - a.lon_rad();
+    let mut lambda = lon_diff;
+
+    for _ in 0..VINCENTY_MAX_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+
+        if sin_sigma == N::zero() {
+            // Either the points coincide, or they are exactly antipodal and the geodesic is not unique.
+            return (cos_sigma > N::zero()).then(|| (N::zero(), N::zero()));
+        }
+
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = N::one() - sin_alpha.powi(2);
+        let cos_2sigma_m = if cos_sq_alpha != N::zero() {
+            cos_sigma - num(2.0) * sin_u1 * sin_u2 / cos_sq_alpha
+        } else {
+            // Both points are on the equator.
+            N::zero()
+        };
+
+        let c = flattening / num(16.0)
+            * cos_sq_alpha
+            * (num(4.0) + flattening * (num(4.0) - num(3.0) * cos_sq_alpha));
+        let prev_lambda = lambda;
+        lambda = lon_diff
+            + (N::one() - c)
+                * flattening
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m
+                            + c * cos_sigma * (num(-1.0) + num(2.0) * cos_2sigma_m.powi(2))));
+
+        if lambda.abs() > num(2.0) * N::PI() {
+            return None;
+        }
+
+        if (lambda - prev_lambda).abs() < num(VINCENTY_TOLERANCE) {
+            let u_sq = cos_sq_alpha * (semimajor.powi(2) - semiminor.powi(2)) / semiminor.powi(2);
+            let big_a = N::one()
+                + u_sq / num(16384.0)
+                    * (num(4096.0)
+                        + u_sq * (num(-768.0) + u_sq * (num(320.0) - num(175.0) * u_sq)));
+            let big_b = u_sq / num(1024.0)
+                * (num(256.0) + u_sq * (num(-128.0) + u_sq * (num(74.0) - num(47.0) * u_sq)));
+            let delta_sigma = big_b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + big_b / num(4.0)
+                        * (cos_sigma * (num(-1.0) + num(2.0) * cos_2sigma_m.powi(2))
+                            - big_b / num(6.0)
+                                * cos_2sigma_m
+                                * (num(-3.0) + num(4.0) * sin_sigma.powi(2))
+                                * (num(-3.0) + num(4.0) * cos_2sigma_m.powi(2))));
+
+            let azimuth =
+                (cos_u2 * sin_lambda).atan2(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+            return Some((semiminor * big_a * (sigma - delta_sigma), azimuth));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::impls::GeoPoint2d;
+    use crate::geo::NewGeoPoint;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn haversine_quarter_of_equator() {
+        let distance = haversine_distance(
+            &GeoPoint2d::latlon(0.0, 0.0),
+            &GeoPoint2d::latlon(0.0, 90.0),
+            1.0,
+        );
+        assert!((distance - PI / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn haversine_same_point() {
+        let point = GeoPoint2d::latlon(55.75, 37.62);
+        assert_eq!(haversine_distance(&point, &point, EARTH_MEAN_RADIUS), 0.0);
+    }
+
+    #[test]
+    fn haversine_antipodal_points() {
+        let distance = haversine_distance(
+            &GeoPoint2d::latlon(10.0, 20.0),
+            &GeoPoint2d::latlon(-10.0, -160.0),
+            1.0,
+        );
+        assert!((distance - PI).abs() < 1e-9);
+
+        let distance = haversine_distance(
+            &GeoPoint2d::latlon(90.0, 0.0),
+            &GeoPoint2d::latlon(-90.0, 0.0),
+            1.0,
+        );
+        assert!((distance - PI).abs() < 1e-9);
+    }
+
+    #[test]
+    fn haversine_crosses_antimeridian() {
+        let direct = haversine_distance(
+            &GeoPoint2d::latlon(30.0, 179.5),
+            &GeoPoint2d::latlon(31.0, -179.5),
+            1.0,
+        );
+        let shifted = haversine_distance(
+            &GeoPoint2d::latlon(30.0, -0.5),
+            &GeoPoint2d::latlon(31.0, 0.5),
+            1.0,
+        );
+        assert!((direct - shifted).abs() < 1e-12);
+        assert!(direct < 2f64.to_radians());
+    }
+
+    #[test]
+    fn haversine_is_symmetric() {
+        let a = GeoPoint2d::latlon(51.5074, -0.1278);
+        let b = GeoPoint2d::latlon(48.8566, 2.3522);
+        assert_eq!(
+            haversine_distance(&a, &b, EARTH_MEAN_RADIUS),
+            haversine_distance(&b, &a, EARTH_MEAN_RADIUS)
+        );
+        assert!((haversine_distance(&a, &b, EARTH_MEAN_RADIUS) - 343_560.0).abs() < 100.0);
+    }
+
+    #[test]
+    fn vincenty_reference_distance() {
+        // Flinders Peak to Buninyong, the example from the original Vincenty's paper test set.
+        let a = GeoPoint2d::latlon(-37.951_033_416_666_67, 144.424_867_888_888_9);
+        let b = GeoPoint2d::latlon(-37.652_821_138_888_89, 143.926_495_527_777_8);
+        let distance = vincenty_distance(&a, &b, &Datum::WGS84).unwrap();
+        assert!((distance - 54_972.271).abs() < 0.001);
+    }
+
+    #[test]
+    fn vincenty_same_point() {
+        let point = GeoPoint2d::latlon(-33.0, 151.0);
+        assert_eq!(vincenty_distance(&point, &point, &Datum::WGS84), Some(0.0));
+    }
+
+    #[test]
+    fn vincenty_crosses_antimeridian() {
+        let distance = vincenty_distance(
+            &GeoPoint2d::latlon(0.0, 179.0),
+            &GeoPoint2d::latlon(0.0, -179.0),
+            &Datum::WGS84,
+        )
+        .unwrap();
+        let expected = Datum::WGS84.semimajor() * 2f64.to_radians();
+        assert!((distance - expected).abs() < 0.001);
+    }
+
+    #[test]
+    fn vincenty_meridian_arc() {
+        // Length of the WGS84 meridian from the equator to the pole.
+        let distance = vincenty_distance(
+            &GeoPoint2d::latlon(0.0, 0.0),
+            &GeoPoint2d::latlon(90.0, 0.0),
+            &Datum::WGS84,
+        )
+        .unwrap();
+        assert!((distance - 10_001_965.729).abs() < 0.01);
+    }
+
+    #[test]
+    fn vincenty_antipodal_points() {
+        assert_eq!(
+            vincenty_distance(
+                &GeoPoint2d::latlon(0.0, 0.0),
+                &GeoPoint2d::latlon(0.0, 180.0),
+                &Datum::WGS84
+            ),
+            None
+        );
+        assert_eq!(
+            vincenty_distance(
+                &GeoPoint2d::latlon(0.5, 0.0),
+                &GeoPoint2d::latlon(-0.5, 179.7),
+                &Datum::WGS84
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn geodesic_azimuth_cardinal_directions() {
+        let origin = GeoPoint2d::latlon(0.0, 0.0);
+        let azimuth =
+            |lat, lon| origin.geodesic_azimuth(&GeoPoint2d::latlon(lat, lon), &Datum::WGS84);
+        assert!(azimuth(1.0, 0.0).abs() < 1e-9);
+        assert!((azimuth(0.0, 1.0) - 90.0).abs() < 1e-9);
+        assert!((azimuth(-1.0, 0.0) - 180.0).abs() < 1e-9);
+        assert!((azimuth(0.0, -1.0) - 270.0).abs() < 1e-9);
+        assert_eq!(origin.geodesic_azimuth(&origin, &Datum::WGS84), 0.0);
+    }
+
+    #[test]
+    fn geodesic_azimuth_reference() {
+        // Flinders Peak to Buninyong, see `vincenty_reference_distance`.
+        let a = GeoPoint2d::latlon(-37.951_033_416_666_67, 144.424_867_888_888_9);
+        let b = GeoPoint2d::latlon(-37.652_821_138_888_89, 143.926_495_527_777_8);
+        let azimuth = a.geodesic_azimuth(&b, &Datum::WGS84);
+        // 306°52'05.37"
+        assert!((azimuth - 306.868_158).abs() < 1e-5);
+    }
+
+    #[test]
+    fn geodesic_distance_falls_back_for_antipodal_points() {
+        let distance = GeoPoint2d::latlon(0.0, 0.0)
+            .geodesic_distance(&GeoPoint2d::latlon(0.0, 180.0), &Datum::WGS84);
+        assert!((distance - PI * mean_radius(&Datum::WGS84)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn geodesic_destination_reference() {
+        // Flinders Peak to Buninyong, see `vincenty_reference_distance`.
+        let a = GeoPoint2d::latlon(-37.951_033_416_666_67, 144.424_867_888_888_9);
+        let (lat, lon) = geodesic_destination(&a, 306.868_158_8, 54_972.271, &Datum::WGS84);
+        assert!((lat + 37.652_821_138_888_89).abs() < 1e-7);
+        assert!((lon - 143.926_495_527_777_8).abs() < 1e-7);
+    }
+
+    #[test]
+    fn geodesic_destination_crosses_antimeridian() {
+        let start = GeoPoint2d::latlon(0.0, 179.5);
+        let (lat, lon) = geodesic_destination(&start, 90.0, 111_319.491, &Datum::WGS84);
+        assert!(lat.abs() < 1e-9);
+        assert!((lon + 179.5).abs() < 1e-6);
+    }
+}
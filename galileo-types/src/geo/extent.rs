@@ -0,0 +1,293 @@
+use crate::cartesian::Rect;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::{GeoPoint, NewGeoPoint};
+use crate::geometry::Geom;
+use crate::{Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use serde::{Deserialize, Serialize};
+
+/// Rectangular area in geographic coordinates that can cross the antimeridian.
+///
+/// The extent spans from its western longitude eastwards to its eastern longitude. If the western longitude is
+/// greater than the eastern one, the extent crosses the ±180° meridian. This allows representing areas like Fiji or
+/// the Bering strait with a narrow extent instead of one that goes the long way around the globe.
+///
+/// ```
+/// use galileo_types::geo::GeoExtent;
+/// use galileo_types::latlon;
+///
+/// let extent = GeoExtent::from_points(&[latlon!(-16.0, 178.0), latlon!(-18.0, -179.0)]).unwrap();
+/// assert!(extent.crosses_antimeridian());
+/// assert_eq!(extent.lon_west(), 178.0);
+/// assert_eq!(extent.lon_east(), -179.0);
+/// assert_eq!(extent.lon_span(), 3.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoExtent {
+    lat_min: f64,
+    lat_max: f64,
+    lon_west: f64,
+    lon_east: f64,
+}
+
+impl GeoExtent {
+    /// Creates a new extent. Longitudes are normalized into `[-180, 180]` range.
+    ///
+    /// If `lon_west` is greater than `lon_east` after normalization, the extent crosses the antimeridian. If the
+    /// longitudes are 360° or more apart, the extent covers all longitudes.
+    pub fn new(lat_min: f64, lat_max: f64, lon_west: f64, lon_east: f64) -> Self {
+        let (lon_west, lon_east) = if lon_east - lon_west >= 360.0 {
+            (-180.0, 180.0)
+        } else {
+            let west = normalize_lon(lon_west);
+            let east = normalize_lon(lon_east);
+            // Keep the extent `[x, 180]` from turning into `[x, -180]`.
+            let east = if east == -180.0 && west != -180.0 {
+                180.0
+            } else {
+                east
+            };
+            (west, east)
+        };
+
+        Self {
+            lat_min: lat_min.min(lat_max),
+            lat_max: lat_max.max(lat_min),
+            lon_west,
+            lon_east,
+        }
+    }
+
+    /// Returns the smallest extent containing all the given points.
+    ///
+    /// Of the two ways to go around the globe between the westernmost and easternmost points, the one with the
+    /// smaller longitude span is chosen, so points on both sides of the antimeridian produce an extent crossing it.
+    ///
+    /// Returns `None` if the iterator is empty.
+    pub fn from_points<'a, P: GeoPoint<Num = f64> + 'a>(
+        points: impl IntoIterator<Item = &'a P>,
+    ) -> Option<Self> {
+        let mut lat_min = f64::INFINITY;
+        let mut lat_max = f64::NEG_INFINITY;
+        let mut lons = vec![];
+
+        for point in points {
+            lat_min = lat_min.min(point.lat());
+            lat_max = lat_max.max(point.lat());
+            lons.push(normalize_lon(point.lon()));
+        }
+
+        lons.sort_by(f64::total_cmp);
+        let first = *lons.first()?;
+        let last = *lons.last()?;
+
+        // The extent is the complement of the largest longitude gap between the points. By default it is the gap
+        // going over the antimeridian, which gives a non-crossing extent.
+        let mut lon_west = first;
+        let mut lon_east = last;
+        let mut max_gap = first + 360.0 - last;
+        for pair in lons.windows(2) {
+            let gap = pair[1] - pair[0];
+            if gap > max_gap {
+                max_gap = gap;
+                lon_west = pair[1];
+                lon_east = pair[0];
+            }
+        }
+
+        Some(Self {
+            lat_min,
+            lat_max,
+            lon_west,
+            lon_east,
+        })
+    }
+
+    /// Returns the smallest extent containing all points of the given geometries. See [`GeoExtent::from_points`].
+    pub fn from_geometries<'a, P: GeoPoint<Num = f64> + 'a>(
+        geometries: impl IntoIterator<Item = &'a Geom<P>>,
+    ) -> Option<Self> {
+        let mut points = vec![];
+        for geometry in geometries {
+            collect_points(geometry, &mut points);
+        }
+
+        Self::from_points(&points)
+    }
+
+    /// Minimum latitude.
+    pub fn lat_min(&self) -> f64 {
+        self.lat_min
+    }
+
+    /// Maximum latitude.
+    pub fn lat_max(&self) -> f64 {
+        self.lat_max
+    }
+
+    /// Longitude of the western boundary.
+    pub fn lon_west(&self) -> f64 {
+        self.lon_west
+    }
+
+    /// Longitude of the eastern boundary.
+    pub fn lon_east(&self) -> f64 {
+        self.lon_east
+    }
+
+    /// Returns true if the extent crosses the ±180° meridian.
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.lon_west > self.lon_east
+    }
+
+    /// Width of the extent in degrees of longitude.
+    pub fn lon_span(&self) -> f64 {
+        if self.crosses_antimeridian() {
+            self.lon_east + 360.0 - self.lon_west
+        } else {
+            self.lon_east - self.lon_west
+        }
+    }
+
+    /// Center point of the extent. Its longitude is normalized into `[-180, 180)` range.
+    pub fn center(&self) -> GeoPoint2d {
+        GeoPoint2d::latlon(
+            (self.lat_min + self.lat_max) / 2.0,
+            normalize_lon(self.lon_west + self.lon_span() / 2.0),
+        )
+    }
+
+    /// Returns true if the point is inside the extent or on its boundary.
+    pub fn contains(&self, point: &impl GeoPoint<Num = f64>) -> bool {
+        if point.lat() < self.lat_min || point.lat() > self.lat_max {
+            return false;
+        }
+
+        let offset = normalize_lon(point.lon()) - self.lon_west;
+        offset.rem_euclid(360.0) <= self.lon_span()
+    }
+
+    /// Returns the extent as a rectangle with longitude as `x` and latitude as `y`.
+    ///
+    /// For extents crossing the antimeridian the eastern boundary of the rectangle is greater than 180°, so that the
+    /// rectangle is continuous.
+    pub fn unwrapped_rect(&self) -> Rect {
+        Rect::new(
+            self.lon_west,
+            self.lat_min,
+            self.lon_west + self.lon_span(),
+            self.lat_max,
+        )
+    }
+}
+
+fn normalize_lon(lon: f64) -> f64 {
+    (lon + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn collect_points<P: GeoPoint<Num = f64>>(geometry: &Geom<P>, points: &mut Vec<GeoPoint2d>) {
+    let mut push_contour = |contour: &crate::impls::Contour<P>| {
+        points.extend(contour.iter_points().map(GeoPoint2d::from));
+    };
+
+    match geometry {
+        Geom::Point(point) => points.push(GeoPoint2d::from(point)),
+        Geom::MultiPoint(multi_point) => {
+            points.extend(multi_point.iter_points().map(GeoPoint2d::from))
+        }
+        Geom::Contour(contour) => push_contour(contour),
+        Geom::MultiContour(multi_contour) => multi_contour.contours().for_each(push_contour),
+        // Inner contours are inside the outer one, so they cannot extend the extent.
+        Geom::Polygon(polygon) => {
+            points.extend(polygon.outer_contour().iter_points().map(GeoPoint2d::from))
+        }
+        Geom::MultiPolygon(multi_polygon) => {
+            for polygon in multi_polygon.polygons() {
+                points.extend(polygon.outer_contour().iter_points().map(GeoPoint2d::from));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::impls;
+
+    fn point(lat: f64, lon: f64) -> GeoPoint2d {
+        GeoPoint2d::latlon(lat, lon)
+    }
+
+    #[test]
+    fn from_points_regular() {
+        let extent =
+            GeoExtent::from_points(&[point(10.0, 20.0), point(-5.0, 40.0), point(0.0, 30.0)])
+                .unwrap();
+        assert!(!extent.crosses_antimeridian());
+        assert_eq!(extent, GeoExtent::new(-5.0, 10.0, 20.0, 40.0));
+        assert_eq!(extent.lon_span(), 20.0);
+    }
+
+    #[test]
+    fn from_points_across_antimeridian() {
+        let extent = GeoExtent::from_points(&[
+            point(-16.0, 177.0),
+            point(-18.0, -178.0),
+            point(-17.0, 179.5),
+        ])
+        .unwrap();
+        assert!(extent.crosses_antimeridian());
+        assert_eq!(extent.lon_west(), 177.0);
+        assert_eq!(extent.lon_east(), -178.0);
+        assert_eq!(extent.lon_span(), 5.0);
+        assert_eq!(extent.center(), point(-17.0, 179.5));
+    }
+
+    #[test]
+    fn from_points_empty() {
+        assert_eq!(GeoExtent::from_points::<GeoPoint2d>(&[]), None);
+    }
+
+    #[test]
+    fn from_geometries_polygon_across_antimeridian() {
+        let polygon = impls::Polygon::new(
+            impls::ClosedContour::new(vec![
+                point(-15.0, 175.0),
+                point(-15.0, -175.0),
+                point(-20.0, -175.0),
+                point(-20.0, 175.0),
+            ]),
+            vec![],
+        );
+        let extent = GeoExtent::from_geometries(&[Geom::Polygon(polygon)]).unwrap();
+        assert!(extent.crosses_antimeridian());
+        assert_eq!(extent.lon_span(), 10.0);
+        assert_eq!(
+            extent.unwrapped_rect(),
+            Rect::new(175.0, -20.0, 185.0, -15.0)
+        );
+    }
+
+    #[test]
+    fn contains() {
+        let extent = GeoExtent::new(-20.0, -15.0, 175.0, -175.0);
+        assert!(extent.contains(&point(-16.0, 179.0)));
+        assert!(extent.contains(&point(-16.0, -179.0)));
+        assert!(extent.contains(&point(-16.0, 180.0)));
+        assert!(!extent.contains(&point(-16.0, 0.0)));
+        assert!(!extent.contains(&point(-10.0, 179.0)));
+    }
+
+    #[test]
+    fn new_normalizes_longitudes() {
+        let extent = GeoExtent::new(0.0, 1.0, 190.0, 200.0);
+        assert_eq!(extent.lon_west(), -170.0);
+        assert_eq!(extent.lon_east(), -160.0);
+
+        let extent = GeoExtent::new(0.0, 1.0, 0.0, 180.0);
+        assert!(!extent.crosses_antimeridian());
+        assert_eq!(extent.lon_span(), 180.0);
+
+        let extent = GeoExtent::new(0.0, 1.0, -180.0, 180.0);
+        assert_eq!(extent.lon_span(), 360.0);
+    }
+}
@@ -1,6 +1,5 @@
 use crate::geo::traits::point::{GeoPoint, NewGeoPoint};
-use crate::geo::traits::projection::Projection;
-use crate::geometry::{Geom, Geometry};
+use crate::geometry_type::{GeoSpace2d, GeometryType, PointGeometryType};
 use serde::{Deserialize, Serialize};
 
 /// 2d point on the surface of a celestial body.
@@ -41,15 +40,9 @@ impl GeoPoint2d {
     }
 }
 
-impl Geometry for GeoPoint2d {
-    type Point = Self;
-
-    fn project<P: Projection<InPoint = Self::Point> + ?Sized>(
-        &self,
-        projection: &P,
-    ) -> Option<Geom<P::OutPoint>> {
-        Some(Geom::Point(projection.project(self)?))
-    }
+impl GeometryType for GeoPoint2d {
+    type Type = PointGeometryType;
+    type Space = GeoSpace2d;
 }
 
 /// Creates a new GeoPoint2d from latitude and longitude values (in degrees).
@@ -0,0 +1,287 @@
+use crate::cartesian::{NewCartesianPoint2d, Point2d};
+use crate::geo::impls::projection::WebMercator;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::traits::point::{GeoPoint, NewGeoPoint};
+use crate::geo::traits::projection::Projection;
+use crate::geometry_type::{CartesianSpace2d, GeoSpace2d};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Projection from geographic coordinates into the coordinates of a projected CRS.
+pub(crate) type GeoToProjected =
+    Arc<dyn Projection<InPoint = GeoPoint2d, OutPoint = Point2d> + Send + Sync>;
+
+/// Conversion between geographic coordinates and the coordinates of one CRS.
+#[derive(Clone)]
+pub(crate) enum CrsTransform {
+    /// The CRS is geographic: `x` is the longitude and `y` is the latitude.
+    Geographic,
+    /// Web Mercator is used by most of the maps, so it is computed without dynamic dispatch.
+    WebMercator(WebMercator<GeoPoint2d, Point2d>),
+    Other(GeoToProjected),
+}
+
+impl CrsTransform {
+    fn project_geo(&self, lon: f64, lat: f64) -> Option<Point2d> {
+        match self {
+            Self::Geographic => Some(Point2d::new(lon, lat)),
+            Self::WebMercator(projection) => projection.project(&GeoPoint2d::latlon(lat, lon)),
+            Self::Other(projection) => projection.project(&GeoPoint2d::latlon(lat, lon)),
+        }
+    }
+
+    fn unproject_geo(&self, point: &Point2d) -> Option<GeoPoint2d> {
+        match self {
+            Self::Geographic => Some(GeoPoint2d::latlon(point.y, point.x)),
+            Self::WebMercator(projection) => projection.unproject(point),
+            Self::Other(projection) => projection.unproject(point),
+        }
+    }
+}
+
+/// Reusable projection between two coordinate reference systems, created by
+/// [`Crs::projection_to`](crate::geo::Crs::projection_to) or
+/// [`Crs::projection_from_geo`](crate::geo::Crs::projection_from_geo).
+///
+/// Parameters of the transformation are computed once when the projection is created, so it should be preferred
+/// over creating a new projection for every point. Cloning the projection is cheap.
+///
+/// `In` and `Out` are the types of the points in the source and target CRSs. Points of a geographic target CRS
+/// (like [`Crs::WGS84`](crate::geo::Crs::WGS84)) are cartesian points with the longitude as `x` and the latitude as
+/// `y` in degrees. The `Space` of the input points is [`CartesianSpace2d`] for the projections created by
+/// [`Crs::projection_to`](crate::geo::Crs::projection_to), which read the source coordinates the same way, and
+/// [`GeoSpace2d`] for the projections of geographic points created by
+/// [`Crs::projection_from_geo`](crate::geo::Crs::projection_from_geo).
+///
+/// ```
+/// use galileo_types::cartesian::Point2d;
+/// use galileo_types::geo::Crs;
+///
+/// let projection = Crs::WGS84.projection_to(&Crs::EPSG3857).unwrap();
+/// let projected = projection.project_points(&[Point2d::new(0.0, 0.0), Point2d::new(180.0, 0.0)]).unwrap();
+/// assert!(projected[0].y.abs() < 1e-6);
+/// assert!((projected[1].x - 20_037_508.34).abs() < 0.01);
+/// ```
+pub struct CrsProjection<In = Point2d, Out = Point2d, Space = CartesianSpace2d> {
+    source: CrsTransform,
+    target: CrsTransform,
+    is_identity: bool,
+    phantom: PhantomData<fn(&In, &Space) -> Out>,
+}
+
+impl<In, Out, Space> Clone for CrsProjection<In, Out, Space> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            target: self.target.clone(),
+            is_identity: self.is_identity,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<In, Out, Space> CrsProjection<In, Out, Space> {
+    /// Creates a new projection. If `is_identity` is set, the source and target CRSs are the same.
+    pub(crate) fn new(source: CrsTransform, target: CrsTransform, is_identity: bool) -> Self {
+        Self {
+            source,
+            target,
+            is_identity,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the same projection for other types of the source and target points.
+    pub fn with_points<NewIn, NewOut>(self) -> CrsProjection<NewIn, NewOut, Space> {
+        CrsProjection::new(self.source, self.target, self.is_identity)
+    }
+}
+
+impl<In, Out, Space> CrsProjection<In, Out, Space>
+where
+    Self: Projection<InPoint = In, OutPoint = Out>,
+{
+    /// Projects the point from the source CRS into the target CRS.
+    ///
+    /// Returns `None` if the point cannot be projected.
+    pub fn project_point(&self, point: &In) -> Option<Out> {
+        self.project(point)
+    }
+
+    /// Projects the point from the target CRS back into the source CRS.
+    ///
+    /// Returns `None` if the point cannot be projected.
+    pub fn unproject_point(&self, point: &Out) -> Option<In> {
+        self.unproject(point)
+    }
+
+    /// Projects all the points from the source CRS into the target CRS.
+    ///
+    /// Returns `None` if at least one of the points cannot be projected.
+    pub fn project_points(&self, points: &[In]) -> Option<Vec<Out>> {
+        points.iter().map(|p| self.project(p)).collect()
+    }
+
+    /// Projects all the points from the target CRS back into the source CRS.
+    ///
+    /// Returns `None` if at least one of the points cannot be projected.
+    pub fn unproject_points(&self, points: &[Out]) -> Option<Vec<In>> {
+        points.iter().map(|p| self.unproject(p)).collect()
+    }
+}
+
+impl<In, Out> Projection for CrsProjection<In, Out, CartesianSpace2d>
+where
+    In: NewCartesianPoint2d<f64>,
+    Out: NewCartesianPoint2d<f64>,
+{
+    type InPoint = In;
+    type OutPoint = Out;
+
+    fn project(&self, input: &Self::InPoint) -> Option<Self::OutPoint> {
+        if self.is_identity {
+            return Some(Out::new(input.x(), input.y()));
+        }
+
+        let geo = self
+            .source
+            .unproject_geo(&Point2d::new(input.x(), input.y()))?;
+        let projected = self.target.project_geo(geo.lon(), geo.lat())?;
+        Some(Out::new(projected.x, projected.y))
+    }
+
+    fn unproject(&self, input: &Self::OutPoint) -> Option<Self::InPoint> {
+        if self.is_identity {
+            return Some(In::new(input.x(), input.y()));
+        }
+
+        let geo = self
+            .target
+            .unproject_geo(&Point2d::new(input.x(), input.y()))?;
+        let unprojected = self.source.project_geo(geo.lon(), geo.lat())?;
+        Some(In::new(unprojected.x, unprojected.y))
+    }
+}
+
+impl<In, Out> Projection for CrsProjection<In, Out, GeoSpace2d>
+where
+    In: NewGeoPoint<f64>,
+    Out: NewCartesianPoint2d<f64>,
+{
+    type InPoint = In;
+    type OutPoint = Out;
+
+    fn project(&self, input: &Self::InPoint) -> Option<Self::OutPoint> {
+        let projected = self.target.project_geo(input.lon(), input.lat())?;
+        Some(Out::new(projected.x, projected.y))
+    }
+
+    fn unproject(&self, input: &Self::OutPoint) -> Option<Self::InPoint> {
+        let geo = self
+            .target
+            .unproject_geo(&Point2d::new(input.x(), input.y()))?;
+        Some(In::latlon(geo.lat(), geo.lon()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cartesian::Point2d;
+    use crate::geo::impls::GeoPoint2d;
+    use crate::geo::{Crs, Datum, GeoPoint, NewGeoPoint, ProjectionType};
+
+    #[test]
+    fn same_as_crs_projection() {
+        let reusable = Crs::WGS84.projection_to(&Crs::EPSG3857).unwrap();
+        let direct = Crs::EPSG3857.get_projection::<_, Point2d>().unwrap();
+
+        for (lon, lat) in [(0.0, 0.0), (37.6, 55.7), (-122.4, 37.8), (179.9, -85.0)] {
+            let expected = direct.project(&GeoPoint2d::latlon(lat, lon)).unwrap();
+            assert_eq!(
+                reusable.project_point(&Point2d::new(lon, lat)),
+                Some(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn geo_points_same_as_crs_projection() {
+        let reusable = Crs::EPSG3857
+            .projection_from_geo::<GeoPoint2d, Point2d>()
+            .unwrap();
+        let direct = Crs::EPSG3857.get_projection::<_, Point2d>().unwrap();
+
+        for (lon, lat) in [(0.0, 0.0), (37.6, 55.7), (-122.4, 37.8), (179.9, -85.0)] {
+            let point = GeoPoint2d::latlon(lat, lon);
+            let projected = reusable.project_point(&point).unwrap();
+            assert_eq!(Some(projected), direct.project(&point));
+
+            let unprojected = reusable.unproject_point(&projected).unwrap();
+            assert!((unprojected.lat() - lat).abs() < 1e-9);
+            assert!((unprojected.lon() - lon).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "geo-types")]
+    fn other_point_types() {
+        let projection = Crs::WGS84
+            .projection_to(&Crs::EPSG3857)
+            .unwrap()
+            .with_points::<geo_types::Coord, Point2d>();
+
+        let projected = projection
+            .project_point(&geo_types::coord! { x: 180.0, y: 0.0 })
+            .unwrap();
+        assert!((projected.x - 20_037_508.34).abs() < 0.01);
+        assert!(projection.unproject_point(&projected).is_some());
+    }
+
+    #[test]
+    fn round_trip() {
+        let projection = Crs::WGS84.projection_to(&Crs::EPSG3857).unwrap();
+        let points = [Point2d::new(30.0, 60.0), Point2d::new(-45.0, -10.0)];
+
+        let projected = projection.project_points(&points).unwrap();
+        let unprojected = projection.unproject_points(&projected).unwrap();
+        for (expected, actual) in points.iter().zip(unprojected) {
+            assert!((expected - actual).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn between_projected_crs() {
+        let laea = Crs::new(
+            Datum::WGS84,
+            ProjectionType::Other("laea lon_0=10 lat_0=52 x_0=4321000 y_0=3210000".to_string()),
+        );
+        let projection = Crs::EPSG3857.projection_to(&laea).unwrap();
+
+        let mercator = Crs::WGS84.projection_to(&Crs::EPSG3857).unwrap();
+        let center = mercator.project_point(&Point2d::new(10.0, 52.0)).unwrap();
+        let projected = projection.project_point(&center).unwrap();
+        assert!((projected - Point2d::new(4_321_000.0, 3_210_000.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn identity_for_geographic() {
+        let projection = Crs::WGS84.projection_to(&Crs::WGS84).unwrap();
+        let point = Point2d::new(12.0, 34.0);
+        assert_eq!(projection.project_point(&point), Some(point));
+    }
+
+    #[test]
+    fn unsupported_crs() {
+        let unknown = Crs::new(Datum::WGS84, ProjectionType::Unknown);
+        assert!(Crs::WGS84.projection_to(&unknown).is_none());
+        assert!(unknown.projection_to(&Crs::EPSG3857).is_none());
+    }
+
+    #[test]
+    fn failed_point_fails_batch() {
+        let projection = Crs::WGS84.projection_to(&Crs::EPSG3857).unwrap();
+        assert!(projection
+            .project_points(&[Point2d::new(0.0, 0.0), Point2d::new(0.0, f64::NAN)])
+            .is_none());
+    }
+}
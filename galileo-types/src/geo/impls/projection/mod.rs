@@ -1,8 +1,11 @@
 //! Implementations for some of the common projections.
+mod crs;
 mod dimensions;
 mod identity;
 mod web_mercator;
 
+pub use crs::CrsProjection;
+pub(crate) use crs::{CrsTransform, GeoToProjected};
 pub use dimensions::AddDimensionProjection;
 pub use identity::IdentityProjection;
 pub use web_mercator::WebMercator;
@@ -1,12 +1,19 @@
 //! Geometries in geographic coordinates (latitude and longitude) (see [`GeoPoint`]) and conversion between different geographic
 //! coordinate systems (see [`Projection`]).
 
+mod area;
 mod crs;
 mod datum;
+mod distance;
+mod extent;
 pub mod impls;
 mod traits;
 
 pub use crs::{Crs, ProjectionType};
 pub use datum::Datum;
+pub use distance::{haversine_distance, vincenty_distance, EARTH_MEAN_RADIUS};
+pub use extent::GeoExtent;
+pub use traits::contour::{GeoClosedContour, GeoContour};
 pub use traits::point::{GeoPoint, NewGeoPoint};
+pub use traits::polygon::GeoPolygon;
 pub use traits::projection::{ChainProjection, InvertedProjection, Projection};
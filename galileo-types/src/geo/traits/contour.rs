@@ -0,0 +1,234 @@
+use crate::contour::{ClosedContour, Contour};
+use crate::geo::{Datum, GeoPoint, NewGeoPoint};
+use crate::segment::Segment;
+use num_traits::{Float, FloatConst, Zero};
+
+/// Measurements of contours on the surface of an ellipsoid. This trait is auto-implemented for all types implementing
+/// [`Contour`] trait and consist of [`GeoPoint`].
+pub trait GeoContour<P: GeoPoint>: Contour<Point = P>
+where
+    P::Num: FloatConst,
+{
+    /// Total geodesic length of the contour segments on the `datum` ellipsoid in the units of the datum semimajor axis
+    /// (meters for [`Datum::WGS84`]). For closed contours this includes the segment between the last and the first
+    /// points.
+    ///
+    /// See [`GeoPoint::geodesic_distance`] for details.
+    fn geodesic_length(&self, datum: &Datum) -> P::Num
+    where
+        Self: Sized,
+    {
+        self.iter_segments()
+            .fold(P::Num::zero(), |length, segment| {
+                length + segment.0.geodesic_distance(segment.1, datum)
+            })
+    }
+
+    /// Returns the point at the given geodesic `distance` from the start of the contour, measured along the contour on
+    /// the `datum` ellipsoid.
+    ///
+    /// The `distance` is clamped to the length of the contour. Returns `None` if the contour has no points. See
+    /// [`CartesianContour::point_at_distance`](crate::cartesian::CartesianContour::point_at_distance).
+    ///
+    /// ```
+    /// use galileo_types::geo::{Datum, GeoContour, GeoPoint};
+    /// use galileo_types::impls::Contour;
+    /// use galileo_types::latlon;
+    ///
+    /// let route = Contour::open(vec![latlon!(0.0, 0.0), latlon!(0.0, 2.0)]);
+    /// let middle = route.geodesic_point_at_distance(111_319.491, &Datum::WGS84).unwrap();
+    /// assert!((middle.lon() - 1.0).abs() < 1e-6);
+    /// ```
+    fn geodesic_point_at_distance<N>(&self, distance: N, datum: &Datum) -> Option<P>
+    where
+        Self: Sized,
+        N: Float + FloatConst,
+        P: NewGeoPoint<N> + Clone,
+    {
+        let mut last = self.iter_points().next()?;
+        let mut passed = N::zero();
+        for Segment(from, to) in self.iter_segments() {
+            let length = from.geodesic_distance(to, datum);
+            if passed + length >= distance && length > N::zero() {
+                return Some(point_along(
+                    from,
+                    to,
+                    (distance - passed).max(N::zero()),
+                    datum,
+                ));
+            }
+
+            passed = passed + length;
+            last = to;
+        }
+
+        Some(last.clone())
+    }
+
+    /// Returns the point at the given `fraction` (from `0.0` at the start to `1.0` at the end) of the geodesic length
+    /// of the contour. See [`GeoContour::geodesic_point_at_distance`].
+    fn geodesic_point_at_fraction<N>(&self, fraction: N, datum: &Datum) -> Option<P>
+    where
+        Self: Sized,
+        N: Float + FloatConst,
+        P: NewGeoPoint<N> + Clone,
+    {
+        self.geodesic_point_at_distance(self.geodesic_length(datum) * fraction, datum)
+    }
+
+    /// Returns the points of the contour placed at every `spacing` geodesic distance along the contour, starting with
+    /// the first point. See [`CartesianContour::resample`](crate::cartesian::CartesianContour::resample).
+    fn geodesic_resample<N>(&self, spacing: N, datum: &Datum) -> Vec<P>
+    where
+        Self: Sized,
+        N: Float + FloatConst,
+        P: NewGeoPoint<N> + Clone,
+    {
+        let Some(first) = self.iter_points().next() else {
+            return vec![];
+        };
+        if spacing <= N::zero() || spacing.is_nan() {
+            return vec![first.clone()];
+        }
+
+        let mut result = vec![];
+        let mut passed = N::zero();
+        let mut next = N::zero();
+        for Segment(from, to) in self.iter_segments() {
+            let length = from.geodesic_distance(to, datum);
+            while next <= passed + length && length > N::zero() {
+                result.push(point_along(from, to, next - passed, datum));
+                next = next + spacing;
+            }
+
+            passed = passed + length;
+        }
+
+        if result.is_empty() {
+            result.push(first.clone());
+        }
+
+        result
+    }
+}
+
+/// Point at the `distance` from `from` along the geodesic to `to`.
+fn point_along<N, P>(from: &P, to: &P, distance: N, datum: &Datum) -> P
+where
+    N: Float + FloatConst,
+    P: NewGeoPoint<N>,
+{
+    let azimuth = from.geodesic_azimuth(to, datum);
+    let (lat, lon) = crate::geo::distance::geodesic_destination(from, azimuth, distance, datum);
+    P::latlon(lat, lon)
+}
+
+impl<P, T> GeoContour<P> for T
+where
+    P: GeoPoint,
+    P::Num: FloatConst,
+    T: Contour<Point = P>,
+{
+}
+
+/// Area of closed contours on the surface of an ellipsoid. This trait is auto-implemented for all types implementing
+/// [`ClosedContour`] trait and consist of [`GeoPoint`].
+pub trait GeoClosedContour {
+    /// Type of the contour points.
+    type Point: GeoPoint;
+
+    /// Signed area of the contour on the `datum` ellipsoid in square units of the datum semimajor axis (square meters
+    /// for [`Datum::WGS84`]). The area is positive if the points of the contour go counterclockwise (with longitude as
+    /// the first coordinate), and negative otherwise.
+    ///
+    /// The contour segments are considered to be the shortest lines between the points, so a contour cannot span
+    /// more than half of the globe. The area is calculated on the sphere of the same surface area as the ellipsoid,
+    /// which for contours with segments shorter than a few hundred kilometers gives the same result as calculating
+    /// the area bounded by geodesics on the ellipsoid itself.
+    ///
+    /// ```
+    /// use galileo_types::geo::{Datum, GeoClosedContour};
+    /// use galileo_types::impls::ClosedContour;
+    /// use galileo_types::latlon;
+    ///
+    /// let contour = ClosedContour::new(vec![
+    ///     latlon!(0.0, 0.0),
+    ///     latlon!(0.0, 1.0),
+    ///     latlon!(1.0, 1.0),
+    ///     latlon!(1.0, 0.0),
+    /// ]);
+    ///
+    /// let area = contour.geodesic_area_signed(&Datum::WGS84);
+    /// assert!((area - 12_308_778_361.0).abs() < 1e6);
+    /// ```
+    fn geodesic_area_signed(&self, datum: &Datum) -> <Self::Point as GeoPoint>::Num
+    where
+        Self: Sized;
+}
+
+impl<P, T> GeoClosedContour for T
+where
+    P: GeoPoint,
+    P::Num: FloatConst,
+    T: ClosedContour<Point = P>,
+{
+    type Point = P;
+
+    fn geodesic_area_signed(&self, datum: &Datum) -> P::Num
+    where
+        Self: Sized,
+    {
+        crate::geo::area::ring_area_signed(self.iter_points(), datum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::impls::GeoPoint2d;
+    use crate::geo::NewGeoPoint;
+    use crate::impls;
+
+    #[test]
+    fn closed_contour_length_includes_closing_segment() {
+        let points = vec![
+            GeoPoint2d::latlon(0.0, 0.0),
+            GeoPoint2d::latlon(0.0, 1.0),
+            GeoPoint2d::latlon(1.0, 1.0),
+        ];
+        let open = impls::Contour::open(points.clone());
+        let closed = impls::Contour::closed(points.clone());
+
+        let closing = points[2].geodesic_distance(&points[0], &Datum::WGS84);
+        let open_length = open.geodesic_length(&Datum::WGS84);
+        assert!((closed.geodesic_length(&Datum::WGS84) - open_length - closing).abs() < 1e-6);
+        assert!((open_length - 111_319.491 - 110_574.389).abs() < 1.0);
+    }
+
+    #[test]
+    fn geodesic_linear_referencing() {
+        let route = impls::Contour::open(vec![
+            GeoPoint2d::latlon(0.0, 0.0),
+            GeoPoint2d::latlon(0.0, 1.0),
+            GeoPoint2d::latlon(1.0, 1.0),
+        ]);
+        let datum = Datum::WGS84;
+
+        let end = route.geodesic_point_at_fraction(1.0, &datum).unwrap();
+        assert!((end.lat() - 1.0).abs() < 1e-9 && (end.lon() - 1.0).abs() < 1e-9);
+
+        let corner = route
+            .geodesic_point_at_distance(111_319.491, &datum)
+            .unwrap();
+        assert!(corner.lat().abs() < 1e-7 && (corner.lon() - 1.0).abs() < 1e-7);
+
+        let points = route.geodesic_resample(50_000.0, &datum);
+        assert_eq!(points.len(), 5);
+        for pair in points.windows(2) {
+            let distance = pair[0].geodesic_distance(&pair[1], &datum);
+            // The points around the corner are closer than the spacing in a straight line.
+            assert!(distance <= 50_000.0 + 1e-3);
+            assert!(distance > 40_000.0);
+        }
+    }
+}
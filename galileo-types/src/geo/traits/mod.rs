@@ -1,2 +1,4 @@
+pub mod contour;
 pub mod point;
+pub mod polygon;
 pub mod projection;
@@ -1,7 +1,8 @@
 use crate::geo::traits::projection::Projection;
+use crate::geo::Datum;
 use crate::geometry::{Geom, GeometrySpecialization};
 use crate::geometry_type::{GeoSpace2d, GeometryType, PointGeometryType};
-use num_traits::Float;
+use num_traits::{Float, FloatConst};
 
 /// 2d point on the surface of a celestial body.
 pub trait GeoPoint {
@@ -22,6 +23,49 @@ pub trait GeoPoint {
     fn lon_rad(&self) -> Self::Num {
         self.lon().to_radians()
     }
+
+    /// Geodesic distance to the `other` point on the surface of the `datum` ellipsoid in the units of the datum
+    /// semimajor axis (meters for [`Datum::WGS84`]).
+    ///
+    /// The distance is calculated with [Vincenty's formula](crate::geo::vincenty_distance). For nearly antipodal
+    /// points, for which the formula does not converge, the great-circle distance on the sphere with the mean radius
+    /// of the ellipsoid is returned instead.
+    ///
+    /// ```
+    /// use galileo_types::geo::{Datum, GeoPoint};
+    /// use galileo_types::latlon;
+    ///
+    /// let distance = latlon!(0.0, 0.0).geodesic_distance(&latlon!(0.0, 1.0), &Datum::WGS84);
+    /// assert!((distance - 111_319.491).abs() < 0.001);
+    /// ```
+    fn geodesic_distance(&self, other: &impl GeoPoint<Num = Self::Num>, datum: &Datum) -> Self::Num
+    where
+        Self: Sized,
+        Self::Num: FloatConst,
+    {
+        crate::geo::distance::geodesic_distance(self, other, datum)
+    }
+
+    /// Initial azimuth (bearing) of the geodesic from this point to the `other` point on the `datum` ellipsoid, in
+    /// degrees clockwise from the north in the range `[0, 360)`. Zero is returned if the points coincide.
+    ///
+    /// For nearly antipodal points the great-circle bearing is returned instead, see
+    /// [`GeoPoint::geodesic_distance`].
+    ///
+    /// ```
+    /// use galileo_types::geo::{Datum, GeoPoint};
+    /// use galileo_types::latlon;
+    ///
+    /// let azimuth = latlon!(0.0, 0.0).geodesic_azimuth(&latlon!(-1.0, 0.0), &Datum::WGS84);
+    /// assert!((azimuth - 180.0).abs() < 1e-9);
+    /// ```
+    fn geodesic_azimuth(&self, other: &impl GeoPoint<Num = Self::Num>, datum: &Datum) -> Self::Num
+    where
+        Self: Sized,
+        Self::Num: FloatConst,
+    {
+        crate::geo::distance::geodesic_azimuth(self, other, datum)
+    }
 }
 
 /// Trait for points that can be constructed by only coordinates.
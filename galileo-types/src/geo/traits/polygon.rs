@@ -0,0 +1,35 @@
+use crate::contour::ClosedContour;
+use crate::geo::traits::contour::GeoClosedContour;
+use crate::geo::{Datum, GeoPoint};
+use crate::polygon::Polygon;
+use num_traits::{Float, FloatConst};
+
+/// Measurements of polygons on the surface of an ellipsoid. This trait is auto-implemented for all illegible types.
+pub trait GeoPolygon {
+    /// Type of the points of the polygon.
+    type Point: GeoPoint;
+
+    /// Area of the polygon on the `datum` ellipsoid in square units of the datum semimajor axis (square meters for
+    /// [`Datum::WGS84`]): the area of the outer contour minus the areas of the inner contours, independently of
+    /// their winding.
+    ///
+    /// See [`GeoClosedContour::geodesic_area_signed`] for details.
+    fn geodesic_area(&self, datum: &Datum) -> <Self::Point as GeoPoint>::Num;
+}
+
+impl<P, C, T> GeoPolygon for T
+where
+    P: GeoPoint,
+    P::Num: FloatConst,
+    C: ClosedContour<Point = P>,
+    T: Polygon<Contour = C>,
+{
+    type Point = P;
+
+    fn geodesic_area(&self, datum: &Datum) -> P::Num {
+        self.inner_contours().fold(
+            self.outer_contour().geodesic_area_signed(datum).abs(),
+            |area, contour| area - contour.geodesic_area_signed(datum).abs(),
+        )
+    }
+}
@@ -0,0 +1,210 @@
+//! Encoding and decoding of [geohash](https://en.wikipedia.org/wiki/Geohash) cells.
+//!
+//! A geohash is a string naming a rectangular cell of latitudes and longitudes. Every next character of the hash
+//! splits the cell into 32 smaller cells, so a hash of 5 characters names a cell of about 5 km across, and a hash of
+//! 12 characters a cell of a few centimetres.
+//!
+//! Decoded cells are returned as [`GeoExtent`]s, or as polygons with [`geohash_polygon`] to be displayed on a map:
+//!
+//! ```
+//! use galileo_types::geo::impls::GeoPoint2d;
+//! use galileo_types::geo::NewGeoPoint;
+//! use galileo_types::geohash::{decode_geohash, encode_geohash};
+//!
+//! let hash = encode_geohash(&GeoPoint2d::latlon(57.64911, 10.40744), 11).unwrap();
+//! assert_eq!(hash, "u4pruydqqvj");
+//!
+//! let cell = decode_geohash("u4pruydqqvj").unwrap();
+//! assert!(cell.contains(&GeoPoint2d::latlon(57.64911, 10.40744)));
+//! ```
+
+use crate::error::GalileoTypesError;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::{GeoExtent, GeoPoint, NewGeoPoint};
+use crate::impls::{ClosedContour, Polygon};
+
+const ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const BITS_PER_CHAR: usize = 5;
+
+/// Largest length of a geohash produced by [`encode_geohash`]. Longer hashes are more precise than `f64`
+/// coordinates.
+pub const MAX_GEOHASH_LENGTH: usize = 12;
+
+/// Returns the geohash of the given `length` of the cell containing the point. Longitude of the point is normalized
+/// into `[-180, 180]` range.
+///
+/// Returns an error if the length is `0` or greater than [`MAX_GEOHASH_LENGTH`], or if the latitude of the point is
+/// outside of `[-90, 90]` range.
+pub fn encode_geohash(
+    point: &impl GeoPoint<Num = f64>,
+    length: usize,
+) -> Result<String, GalileoTypesError> {
+    if length == 0 || length > MAX_GEOHASH_LENGTH {
+        return Err(geohash_error(format!(
+            "length must be between 1 and {MAX_GEOHASH_LENGTH}, got {length}"
+        )));
+    }
+
+    let (lat, lon) = (point.lat(), point.lon());
+    if !(-90.0..=90.0).contains(&lat) || !lon.is_finite() {
+        return Err(geohash_error(format!("invalid point ({lat}, {lon})")));
+    }
+    let lon = if (-180.0..=180.0).contains(&lon) {
+        lon
+    } else {
+        (lon + 180.0).rem_euclid(360.0) - 180.0
+    };
+
+    let mut lat_range = (-90.0, 90.0);
+    let mut lon_range = (-180.0, 180.0);
+    let mut hash = String::with_capacity(length);
+    let mut is_lon = true;
+    for _ in 0..length {
+        let mut index = 0;
+        for _ in 0..BITS_PER_CHAR {
+            let (value, range) = if is_lon {
+                (lon, &mut lon_range)
+            } else {
+                (lat, &mut lat_range)
+            };
+
+            let mid = (range.0 + range.1) / 2.0;
+            index <<= 1;
+            if value >= mid {
+                index |= 1;
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            is_lon = !is_lon;
+        }
+
+        hash.push(ALPHABET[index] as char);
+    }
+
+    Ok(hash)
+}
+
+/// Returns the cell of the geohash. The hash is case-insensitive.
+pub fn decode_geohash(hash: &str) -> Result<GeoExtent, GalileoTypesError> {
+    if hash.is_empty() {
+        return Err(geohash_error("empty hash"));
+    }
+
+    let mut lat_range = (-90.0, 90.0);
+    let mut lon_range = (-180.0, 180.0);
+    let mut is_lon = true;
+    for char in hash.bytes() {
+        let index = ALPHABET
+            .iter()
+            .position(|c| *c == char.to_ascii_lowercase())
+            .ok_or_else(|| geohash_error(format!("unexpected character {:?}", char as char)))?;
+
+        for bit in (0..BITS_PER_CHAR).rev() {
+            let range = if is_lon {
+                &mut lon_range
+            } else {
+                &mut lat_range
+            };
+
+            let mid = (range.0 + range.1) / 2.0;
+            if index & (1 << bit) != 0 {
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            is_lon = !is_lon;
+        }
+    }
+
+    Ok(GeoExtent::new(
+        lat_range.0,
+        lat_range.1,
+        lon_range.0,
+        lon_range.1,
+    ))
+}
+
+/// Returns the cell of the geohash as a polygon with the corners of the cell in counterclockwise order.
+pub fn geohash_polygon(hash: &str) -> Result<Polygon<GeoPoint2d>, GalileoTypesError> {
+    let cell = decode_geohash(hash)?;
+    let (south, north) = (cell.lat_min(), cell.lat_max());
+    let (west, east) = (cell.lon_west(), cell.lon_east());
+
+    Ok(Polygon::new(
+        ClosedContour::new(vec![
+            GeoPoint2d::latlon(south, west),
+            GeoPoint2d::latlon(south, east),
+            GeoPoint2d::latlon(north, east),
+            GeoPoint2d::latlon(north, west),
+        ]),
+        vec![],
+    ))
+}
+
+fn geohash_error(message: impl std::fmt::Display) -> GalileoTypesError {
+    GalileoTypesError::Conversion(format!("invalid geohash: {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Contour as _, Polygon as _};
+
+    #[test]
+    fn encodes_and_decodes_cells() {
+        let point = GeoPoint2d::latlon(-25.382708, -49.265506);
+        assert_eq!(encode_geohash(&point, 5).unwrap(), "6gkzw");
+        assert_eq!(encode_geohash(&point, 12).unwrap(), "6gkzwgjzn820");
+
+        for length in 1..=MAX_GEOHASH_LENGTH {
+            let hash = encode_geohash(&point, length).unwrap();
+            assert_eq!(hash.len(), length);
+            let cell = decode_geohash(&hash).unwrap();
+            assert!(cell.contains(&point), "{hash}");
+        }
+
+        let cell = decode_geohash("EZS42").unwrap();
+        assert_eq!(cell.lat_min(), 42.5830078125);
+        assert_eq!(cell.lat_max(), 42.626953125);
+        assert_eq!(cell.lon_west(), -5.625);
+        assert_eq!(cell.lon_east(), -5.5810546875);
+
+        // Points on the antimeridian and out of the normal range of longitudes.
+        assert_eq!(
+            encode_geohash(&GeoPoint2d::latlon(0.0, 180.0), 2).unwrap(),
+            "xb"
+        );
+        assert_eq!(
+            encode_geohash(&GeoPoint2d::latlon(0.0, 190.0), 4).unwrap(),
+            encode_geohash(&GeoPoint2d::latlon(0.0, -170.0), 4).unwrap()
+        );
+    }
+
+    #[test]
+    fn cell_polygon() {
+        let polygon = geohash_polygon("s").unwrap();
+        let points: Vec<_> = polygon.outer_contour().iter_points().copied().collect();
+        assert_eq!(
+            points,
+            vec![
+                GeoPoint2d::latlon(0.0, 0.0),
+                GeoPoint2d::latlon(0.0, 45.0),
+                GeoPoint2d::latlon(45.0, 45.0),
+                GeoPoint2d::latlon(45.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_input() {
+        let point = GeoPoint2d::latlon(10.0, 10.0);
+        assert!(encode_geohash(&point, 0).is_err());
+        assert!(encode_geohash(&point, MAX_GEOHASH_LENGTH + 1).is_err());
+        assert!(encode_geohash(&GeoPoint2d::latlon(91.0, 0.0), 5).is_err());
+        assert!(encode_geohash(&GeoPoint2d::latlon(0.0, f64::NAN), 5).is_err());
+
+        assert!(decode_geohash("").is_err());
+        assert!(decode_geohash("u4pa").is_err());
+    }
+}
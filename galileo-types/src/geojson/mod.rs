@@ -1,4 +1,4 @@
-use crate::geo::Projection;
+use crate::geo::{GeoPoint, Projection};
 use crate::geojson::point::GeoJsonPoint;
 use crate::geometry::{Geom, Geometry};
 use crate::impls::Contour;
@@ -6,6 +6,7 @@ use crate::impls::MultiContour;
 use crate::impls::MultiPoint;
 use crate::impls::MultiPolygon;
 use crate::impls::Polygon;
+use crate::{MultiContour as _, MultiPoint as _, MultiPolygon as _, Polygon as _};
 use geojson::{LineStringType, PolygonType, Position, Value};
 
 mod point;
@@ -73,3 +74,92 @@ fn convert_multi_polygon(mp: &[PolygonType]) -> Option<MultiPolygon<GeoJsonPoint
         mp.iter().map(convert_polygon).collect::<Option<Vec<_>>>()?,
     ))
 }
+
+/// Converts the geometry into a GeoJSON geometry value. This is the inverse of the [`Geometry`] implementation for
+/// [`geojson::Geometry`], which can also be created from the geometry with [`From`] through this value.
+///
+/// Only longitude and latitude of the points are written. Closed contours are written with the first point repeated
+/// at the end, as required by the GeoJSON specification.
+impl<P: GeoPoint<Num = f64>> From<&Geom<P>> for Value {
+    fn from(geometry: &Geom<P>) -> Self {
+        match geometry {
+            Geom::Point(p) => Value::Point(to_position(p)),
+            Geom::MultiPoint(points) => {
+                Value::MultiPoint(points.iter_points().map(to_position).collect())
+            }
+            Geom::Contour(contour) => Value::LineString(to_line_string(contour)),
+            Geom::MultiContour(lines) => {
+                Value::MultiLineString(lines.contours().map(to_line_string).collect())
+            }
+            Geom::Polygon(polygon) => Value::Polygon(to_polygon(polygon)),
+            Geom::MultiPolygon(mp) => Value::MultiPolygon(mp.polygons().map(to_polygon).collect()),
+        }
+    }
+}
+
+fn to_position(point: &impl GeoPoint<Num = f64>) -> Position {
+    vec![point.lon(), point.lat()]
+}
+
+fn to_line_string<C>(contour: &C) -> LineStringType
+where
+    C: crate::Contour,
+    C::Point: GeoPoint<Num = f64>,
+{
+    let mut line_string: LineStringType = contour.iter_points().map(to_position).collect();
+    if contour.is_closed() && line_string.first() != line_string.last() {
+        line_string.push(line_string[0].clone());
+    }
+
+    line_string
+}
+
+fn to_polygon<P: GeoPoint<Num = f64>>(polygon: &Polygon<P>) -> PolygonType {
+    polygon.iter_contours().map(to_line_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::impls::GeoPoint2d;
+    use crate::geo::NewGeoPoint;
+    use crate::impls::ClosedContour;
+
+    #[test]
+    fn polygon_round_trip() {
+        let json = r#"{"type": "Polygon", "coordinates": [[[10.5, 1.0], [11.0, 1.0], [11.0, 2.0], [10.5, 1.0]]]}"#;
+        let geometry: geojson::Geometry = json.parse().unwrap();
+        let projection = crate::geo::impls::projection::IdentityProjection::<
+            GeoJsonPoint,
+            GeoPoint2d,
+            crate::geometry_type::GeoSpace2d,
+        >::new();
+        let geom = geometry.project(&projection).unwrap();
+
+        assert_eq!(geojson::Geometry::from(&geom), geometry);
+    }
+
+    #[test]
+    fn closed_contour_is_closed_in_geojson() {
+        let polygon = Polygon::new(
+            ClosedContour::new(vec![
+                GeoPoint2d::latlon(0.0, 0.0),
+                GeoPoint2d::latlon(0.0, 1.0),
+                GeoPoint2d::latlon(1.0, 1.0),
+            ]),
+            vec![],
+        );
+        let Value::Polygon(rings) = Value::from(&Geom::Polygon(polygon)) else {
+            panic!("invalid geometry type");
+        };
+        assert_eq!(
+            rings,
+            vec![vec![
+                vec![0.0, 0.0],
+                vec![1.0, 0.0],
+                vec![1.0, 1.0],
+                vec![0.0, 0.0]
+            ]]
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! Offsetting of contours used by [`Contour::buffer`](super::Contour::buffer),
+//! [`ClosedContour::buffer`](super::ClosedContour::buffer) and the [`Buffer`](crate::cartesian::Buffer) trait.
+
+use crate::cartesian::{BufferOptions, CapStyle, JoinStyle, NewCartesianPoint2d};
+use crate::contour::Contour as _;
+use crate::impls::ClosedContour;
+use crate::segment::Segment;
+use num_traits::{Float, FloatConst};
+
+/// Miter limit used by the `buffer` methods of the contours.
+pub(crate) const DEFAULT_MITER_LIMIT: f64 = 2.0;
+
+/// Join and cap styles with the values converted to the number type of the points.
+pub(crate) struct Style<N> {
+    join: JoinStyle,
+    miter_limit: N,
+    cap: CapStyle,
+    arc_step: N,
+}
+
+impl<N: Float + FloatConst> Style<N> {
+    pub(crate) fn new(options: &BufferOptions) -> Self {
+        let miter_limit = match options.join {
+            JoinStyle::Miter { limit } => limit,
+            _ => DEFAULT_MITER_LIMIT,
+        };
+        let segments =
+            N::from(options.quadrant_segments.max(1)).expect("segment count must be representable");
+        Self {
+            join: options.join,
+            miter_limit: N::from(miter_limit).expect("f64 value must be representable"),
+            cap: options.cap,
+            arc_step: N::FRAC_PI_2() / segments,
+        }
+    }
+
+    /// Miter joins and flat caps used by the `buffer` methods of the contours.
+    pub(super) fn miter(miter_limit: N) -> Self {
+        Self {
+            join: JoinStyle::Miter {
+                limit: DEFAULT_MITER_LIMIT,
+            },
+            miter_limit,
+            cap: CapStyle::Flat,
+            arc_step: N::FRAC_PI_2(),
+        }
+    }
+}
+
+type Vec2<N> = (N, N);
+
+fn sub<N: Float>(a: Vec2<N>, b: Vec2<N>) -> Vec2<N> {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn add_scaled<N: Float>(a: Vec2<N>, b: Vec2<N>, k: N) -> Vec2<N> {
+    (a.0 + b.0 * k, a.1 + b.1 * k)
+}
+
+fn cross<N: Float>(a: Vec2<N>, b: Vec2<N>) -> N {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn dot<N: Float>(a: Vec2<N>, b: Vec2<N>) -> N {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn length<N: Float>(a: Vec2<N>) -> N {
+    dot(a, a).sqrt()
+}
+
+/// A segment of the source contour moved along its normal.
+#[derive(Debug, Clone, Copy)]
+struct OffsetLine<N> {
+    start: Vec2<N>,
+    end: Vec2<N>,
+    direction: Vec2<N>,
+    source_end: Vec2<N>,
+}
+
+impl<N: Float> OffsetLine<N> {
+    /// Offsets the segment to the right side by `offset`. Negative value offsets to the left. Returns `None` for
+    /// zero-length segments.
+    fn new<P: NewCartesianPoint2d<N>>(segment: Segment<'_, P>, offset: N) -> Option<Self> {
+        let start = (segment.0.x(), segment.0.y());
+        let end = (segment.1.x(), segment.1.y());
+        let direction = sub(end, start);
+        let len = length(direction);
+        if len == N::zero() {
+            return None;
+        }
+
+        let normal = (direction.1 / len, -direction.0 / len);
+        Some(Self {
+            start: add_scaled(start, normal, offset),
+            end: add_scaled(end, normal, offset),
+            direction,
+            source_end: end,
+        })
+    }
+
+    fn intersection(&self, other: &Self) -> Vec2<N> {
+        let t = cross(sub(other.start, self.start), other.direction)
+            / cross(self.direction, other.direction);
+        add_scaled(self.start, self.direction, t)
+    }
+}
+
+/// Points replacing the source vertex between two consecutive offset lines.
+fn join<N: Float + FloatConst>(
+    prev: &OffsetLine<N>,
+    next: &OffsetLine<N>,
+    offset: N,
+    style: &Style<N>,
+) -> Vec<Vec2<N>> {
+    let turn = cross(prev.direction, next.direction);
+    let scale = length(prev.direction) * length(next.direction);
+    let is_parallel = turn.abs() <= scale * N::epsilon();
+
+    if is_parallel {
+        if dot(prev.direction, next.direction) > N::zero() {
+            return vec![prev.end];
+        }
+
+        // The contour turns back, the gap between the lines is closed with a cap.
+        return match style.cap {
+            CapStyle::Flat => vec![prev.end, next.start],
+            CapStyle::Square => {
+                let extension = offset.abs() / length(prev.direction);
+                vec![
+                    add_scaled(prev.end, prev.direction, extension),
+                    add_scaled(next.start, prev.direction, extension),
+                ]
+            }
+            CapStyle::Round => arc(
+                prev.source_end,
+                prev.end,
+                next.start,
+                offset,
+                style.arc_step,
+            ),
+        };
+    }
+
+    let corner = prev.intersection(next);
+    let is_outer = turn * offset > N::zero();
+    if !is_outer {
+        return vec![corner];
+    }
+
+    match style.join {
+        JoinStyle::Miter { .. }
+            if length(sub(corner, prev.source_end)) <= style.miter_limit * offset.abs() =>
+        {
+            vec![corner]
+        }
+        JoinStyle::Miter { .. } | JoinStyle::Bevel => vec![prev.end, next.start],
+        JoinStyle::Round => arc(
+            prev.source_end,
+            prev.end,
+            next.start,
+            offset,
+            style.arc_step,
+        ),
+    }
+}
+
+/// Points of the circular arc around `center` from `from` to `to`, both included. The arc goes counterclockwise for
+/// positive `offset` (the right side of the contour) and clockwise for negative.
+fn arc<N: Float + FloatConst>(
+    center: Vec2<N>,
+    from: Vec2<N>,
+    to: Vec2<N>,
+    offset: N,
+    step: N,
+) -> Vec<Vec2<N>> {
+    let radius = offset.abs();
+    let start_angle = (from.1 - center.1).atan2(from.0 - center.0);
+    let end_angle = (to.1 - center.1).atan2(to.0 - center.0);
+
+    let mut sweep = end_angle - start_angle;
+    if offset > N::zero() && sweep <= N::zero() {
+        sweep = sweep + N::TAU();
+    } else if offset < N::zero() && sweep >= N::zero() {
+        sweep = sweep - N::TAU();
+    }
+
+    let steps = (sweep.abs() / step).ceil().to_usize().unwrap_or(1).max(1);
+    let mut points = Vec::with_capacity(steps + 1);
+    points.push(from);
+    for i in 1..steps {
+        let angle = start_angle
+            + sweep * N::from(i).expect("step index must be representable")
+                / N::from(steps).expect("step count must be representable");
+        points.push((
+            center.0 + radius * angle.cos(),
+            center.1 + radius * angle.sin(),
+        ));
+    }
+    points.push(to);
+
+    points
+}
+
+/// Offsets a cyclic sequence of lines. Lines that turn into the opposite direction after offsetting (collapse) are
+/// removed until no such lines remain or the shape degenerates.
+fn offset_lines<N: Float + FloatConst>(
+    mut lines: Vec<OffsetLine<N>>,
+    offset: N,
+    style: &Style<N>,
+    min_lines: usize,
+) -> Option<Vec<Vec2<N>>> {
+    loop {
+        if lines.len() < min_lines {
+            return None;
+        }
+
+        let count = lines.len();
+        let joins: Vec<_> = (0..count)
+            .map(|i| join(&lines[(i + count - 1) % count], &lines[i], offset, style))
+            .collect();
+
+        let collapsed = (0..count).find(|&i| {
+            let start = *joins[i].last().expect("join is never empty");
+            let end = joins[(i + 1) % count][0];
+            dot(sub(end, start), lines[i].direction) < N::zero()
+        });
+
+        match collapsed {
+            Some(index) => {
+                lines.remove(index);
+            }
+            None => return Some(joins.into_iter().flatten().collect()),
+        }
+    }
+}
+
+fn signed_area<N: Float>(points: &[Vec2<N>]) -> N {
+    let mut area = N::zero();
+    for (i, p) in points.iter().enumerate() {
+        let next = points[(i + 1) % points.len()];
+        area = area + cross(*p, next);
+    }
+
+    area / (N::one() + N::one())
+}
+
+fn to_contour<N: Float, P: NewCartesianPoint2d<N>>(points: Vec<Vec2<N>>) -> ClosedContour<P> {
+    ClosedContour::new(points.into_iter().map(|(x, y)| P::new(x, y)).collect())
+}
+
+pub(crate) fn buffer_closed<N, P>(
+    contour: &ClosedContour<P>,
+    distance: N,
+    style: &Style<N>,
+) -> Vec<ClosedContour<P>>
+where
+    N: Float + FloatConst,
+    P: NewCartesianPoint2d<N>,
+{
+    let source: Vec<_> = contour.points.iter().map(|p| (p.x(), p.y())).collect();
+    let source_area = signed_area(&source);
+    if source_area == N::zero() {
+        return vec![];
+    }
+
+    // For counterclockwise contours the outer side is on the right.
+    let offset = if source_area > N::zero() {
+        distance
+    } else {
+        -distance
+    };
+
+    let lines: Vec<_> = contour
+        .iter_segments()
+        .filter_map(|segment| OffsetLine::new(segment, offset))
+        .collect();
+    let Some(points) = offset_lines(lines, offset, style, 3) else {
+        return vec![];
+    };
+
+    // A shrunk contour that turned inside out has disappeared completely.
+    let area = signed_area(&points);
+    if area == N::zero()
+        || area.signum() != source_area.signum()
+        || area.abs() > source_area.abs() && distance < N::zero()
+    {
+        return vec![];
+    }
+
+    vec![to_contour(points)]
+}
+
+pub(crate) fn buffer_open<N, P>(
+    points: &[P],
+    distance: N,
+    style: &Style<N>,
+) -> Vec<ClosedContour<P>>
+where
+    N: Float + FloatConst,
+    P: NewCartesianPoint2d<N>,
+{
+    if distance <= N::zero() {
+        return vec![];
+    }
+
+    // The line is traversed forward and then backward, and the right side of both passes gives a closed contour
+    // around the line. The turns at the ends are closed by caps.
+    let forward = points.windows(2).map(|pair| Segment(&pair[0], &pair[1]));
+    let backward = points
+        .windows(2)
+        .rev()
+        .map(|pair| Segment(&pair[1], &pair[0]));
+    let lines: Vec<_> = forward
+        .chain(backward)
+        .filter_map(|segment| OffsetLine::new(segment, distance))
+        .collect();
+
+    match offset_lines(lines, distance, style, 2) {
+        Some(points) => vec![to_contour(points)],
+        None => vec![],
+    }
+}
+
+/// Returns the area within `distance` from the point: a circle for round caps, a square for square caps and nothing
+/// for flat caps.
+pub(crate) fn buffer_point<N, P>(
+    point: &P,
+    distance: N,
+    style: &Style<N>,
+) -> Option<ClosedContour<P>>
+where
+    N: Float + FloatConst,
+    P: NewCartesianPoint2d<N>,
+{
+    if distance <= N::zero() {
+        return None;
+    }
+
+    let center = (point.x(), point.y());
+    let points = match style.cap {
+        CapStyle::Flat => return None,
+        CapStyle::Square => vec![
+            (center.0 - distance, center.1 - distance),
+            (center.0 + distance, center.1 - distance),
+            (center.0 + distance, center.1 + distance),
+            (center.0 - distance, center.1 + distance),
+        ],
+        CapStyle::Round => {
+            let steps = (N::TAU() / style.arc_step).round().to_usize().unwrap_or(4);
+            (0..steps)
+                .map(|i| {
+                    let angle = N::TAU() * N::from(i).expect("step index must be representable")
+                        / N::from(steps).expect("step count must be representable");
+                    (
+                        center.0 + distance * angle.cos(),
+                        center.1 + distance * angle.sin(),
+                    )
+                })
+                .collect()
+        }
+    };
+
+    Some(to_contour(points))
+}
@@ -1,5 +1,10 @@
+use crate::cartesian::simplify::simplify_points;
+use crate::cartesian::{
+    CartesianPoint2d, CartesianPoint2dFloat, NewCartesianPoint2d, SimplificationAlgorithm, Simplify,
+};
 use crate::geo::Projection;
 use crate::geometry_type::{ContourGeometryType, GeometryType};
+use crate::impls::buffer::{buffer_closed, buffer_open, Style, DEFAULT_MITER_LIMIT};
 use serde::{Deserialize, Serialize};
 
 /// Simple [`crate::Contour`] implementation.
@@ -87,6 +92,202 @@ impl<Point> ClosedContour<Point> {
     }
 }
 
+impl<P: CartesianPoint2d + Clone> Contour<P> {
+    /// Simplifies the contour using the
+    /// [Ramer–Douglas–Peucker](https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm)
+    /// algorithm.
+    ///
+    /// Points that are closer than `tolerance` to the simplified line are removed. The first and the last points of
+    /// the contour are always retained. For closed contours the closing segment is taken into account, so the result
+    /// is still a valid closed contour starting with the same point.
+    ///
+    /// With zero `tolerance` only the points lying exactly on the line between their neighbours are removed, so the
+    /// result is geometrically equivalent to the original contour.
+    pub fn simplify(&self, tolerance: P::Num) -> Self {
+        self.simplify_with(SimplificationAlgorithm::DouglasPeucker, tolerance)
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Simplify for Contour<P> {
+    type Num = P::Num;
+
+    fn simplify_with(&self, algorithm: SimplificationAlgorithm, tolerance: P::Num) -> Self {
+        Self {
+            points: simplify_points(&self.points, self.is_closed, algorithm, tolerance),
+            is_closed: self.is_closed,
+        }
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> ClosedContour<P> {
+    /// Simplifies the contour using the
+    /// [Ramer–Douglas–Peucker](https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm)
+    /// algorithm.
+    ///
+    /// See [`Contour::simplify`] for details.
+    pub fn simplify(&self, tolerance: P::Num) -> Self {
+        self.simplify_with(SimplificationAlgorithm::DouglasPeucker, tolerance)
+    }
+}
+
+impl<P: CartesianPoint2d + Clone> Simplify for ClosedContour<P> {
+    type Num = P::Num;
+
+    fn simplify_with(&self, algorithm: SimplificationAlgorithm, tolerance: P::Num) -> Self {
+        Self {
+            points: simplify_points(&self.points, true, algorithm, tolerance),
+        }
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> Contour<P> {
+    /// Inserts intermediate points into the segments of the contour, so that no segment is longer than
+    /// `max_segment_length`.
+    ///
+    /// Long segments are split into equal parts, and the original vertices are retained exactly. For closed contours
+    /// the closing segment is densified too. This is useful before projecting the contour into another CRS, so that
+    /// straight segments follow the curvature of the projection.
+    ///
+    /// At most [`MAX_DENSIFY_POINTS_PER_SEGMENT`] points are inserted into a single segment, so segments that would
+    /// need more than that remain longer than `max_segment_length`. If `max_segment_length` is not a positive finite
+    /// number, the contour is returned unchanged.
+    ///
+    /// ```
+    /// use galileo_types::cartesian::Point2d;
+    /// use galileo_types::impls::Contour;
+    /// use galileo_types::Contour as _;
+    ///
+    /// let line = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(3.0, 0.0)]);
+    /// assert_eq!(line.densify(1.0).iter_points().count(), 4);
+    /// ```
+    pub fn densify(&self, max_segment_length: f64) -> Self {
+        Self {
+            points: densify_points(&self.points, self.is_closed, max_segment_length),
+            is_closed: self.is_closed,
+        }
+    }
+
+    /// Returns the area within `distance` from the contour.
+    ///
+    /// For closed contours this is the same as [`ClosedContour::buffer`]. Open contours are buffered on both sides,
+    /// with flat caps at the ends of the line. Zero or negative `distance` for an open contour returns an empty
+    /// vector.
+    pub fn buffer(&self, distance: P::Num) -> Vec<ClosedContour<P>> {
+        self.buffer_with_miter_limit(distance, DEFAULT_MITER_LIMIT)
+    }
+
+    /// Same as [`Contour::buffer`] but with a custom miter limit. See [`ClosedContour::buffer_with_miter_limit`].
+    pub fn buffer_with_miter_limit(
+        &self,
+        distance: P::Num,
+        miter_limit: P::Num,
+    ) -> Vec<ClosedContour<P>> {
+        if self.is_closed {
+            ClosedContour::new(self.points.clone()).buffer_with_miter_limit(distance, miter_limit)
+        } else {
+            buffer_open(&self.points, distance, &Style::miter(miter_limit))
+        }
+    }
+}
+
+impl<P: NewCartesianPoint2d + Clone> ClosedContour<P> {
+    /// Inserts intermediate points into the segments of the contour, including the closing one. See
+    /// [`Contour::densify`].
+    pub fn densify(&self, max_segment_length: f64) -> Self {
+        Self {
+            points: densify_points(&self.points, true, max_segment_length),
+        }
+    }
+
+    /// Offsets the contour outwards by `distance`, or inwards if the `distance` is negative.
+    ///
+    /// Every segment of the contour is moved along its normal, and the moved segments are joined with miter joins. A
+    /// join is replaced with a bevel if the miter vertex is further than `2 * distance` from the original vertex,
+    /// which happens for corners sharper than 60°. Use [`ClosedContour::buffer_with_miter_limit`] to change this
+    /// limit.
+    ///
+    /// When the contour is shrunk, segments that become shorter than zero are removed. If the whole contour
+    /// collapses this way, an empty vector is returned. The returned contours have the same winding as the
+    /// original one. Self-intersections of the offset contour (e.g. when a narrow bay of a concave contour closes
+    /// up) are not resolved, so the result contains at most one contour.
+    ///
+    /// ```
+    /// use galileo_types::cartesian::Point2d;
+    /// use galileo_types::impls::ClosedContour;
+    ///
+    /// let square = ClosedContour::new(vec![
+    ///     Point2d::new(0.0, 0.0),
+    ///     Point2d::new(4.0, 0.0),
+    ///     Point2d::new(4.0, 4.0),
+    ///     Point2d::new(0.0, 4.0),
+    /// ]);
+    ///
+    /// let shrunk = square.buffer(-1.0);
+    /// assert_eq!(shrunk[0].points[0], Point2d::new(1.0, 1.0));
+    /// assert!(square.buffer(-2.5).is_empty());
+    /// ```
+    pub fn buffer(&self, distance: P::Num) -> Vec<ClosedContour<P>> {
+        self.buffer_with_miter_limit(distance, DEFAULT_MITER_LIMIT)
+    }
+
+    /// Same as [`ClosedContour::buffer`] but with a custom miter limit.
+    ///
+    /// The limit is the maximum distance between an original vertex and its offset, as a multiple of the buffer
+    /// `distance`. The joins exceeding the limit are beveled. The limit of `1` bevels all convex corners, and
+    /// infinity never bevels.
+    pub fn buffer_with_miter_limit(
+        &self,
+        distance: P::Num,
+        miter_limit: P::Num,
+    ) -> Vec<ClosedContour<P>> {
+        if distance == 0.0 {
+            return vec![self.clone()];
+        }
+
+        buffer_closed(self, distance, &Style::miter(miter_limit))
+    }
+}
+
+/// Maximum number of points inserted into a single segment by [`Contour::densify`] and [`ClosedContour::densify`].
+pub const MAX_DENSIFY_POINTS_PER_SEGMENT: usize = 10_000;
+
+fn densify_points<P: NewCartesianPoint2d + Clone>(
+    points: &[P],
+    is_closed: bool,
+    max_segment_length: f64,
+) -> Vec<P> {
+    if !max_segment_length.is_finite() || max_segment_length <= 0.0 || points.len() < 2 {
+        return points.to_vec();
+    }
+
+    let mut result = Vec::with_capacity(points.len());
+    for (index, point) in points.iter().enumerate() {
+        result.push(point.clone());
+
+        let next = match points.get(index + 1) {
+            Some(next) => next,
+            None if is_closed => &points[0],
+            None => break,
+        };
+
+        let count = (point.distance(next) / max_segment_length).ceil();
+        if !count.is_finite() {
+            continue;
+        }
+        let count = count.min((MAX_DENSIFY_POINTS_PER_SEGMENT + 1) as f64);
+
+        for step in 1..count as usize {
+            let k = step as f64 / count;
+            result.push(P::new(
+                point.x() + (next.x() - point.x()) * k,
+                point.y() + (next.y() - point.y()) * k,
+            ));
+        }
+    }
+
+    result
+}
+
 impl<P> From<ClosedContour<P>> for Contour<P> {
     fn from(value: ClosedContour<P>) -> Self {
         Self {
@@ -125,3 +326,263 @@ impl<P: GeometryType> GeometryType for ClosedContour<P> {
     type Type = ContourGeometryType;
     type Space = P::Space;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::{CartesianClosedContour, Point2d, Winding};
+
+    #[test]
+    fn simplify_collinear() {
+        let contour = Contour::open((0..10).map(|i| Point2d::new(i as f64, i as f64)).collect());
+        let simplified = contour.simplify(0.1);
+        assert_eq!(
+            simplified,
+            Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(9.0, 9.0)])
+        );
+    }
+
+    #[test]
+    fn simplify_keeps_significant_points() {
+        let contour = Contour::open(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(1.0, 0.05),
+            Point2d::new(2.0, 0.0),
+            Point2d::new(3.0, 5.0),
+            Point2d::new(4.0, 0.0),
+        ]);
+        let simplified = contour.simplify(0.1);
+        assert_eq!(
+            simplified,
+            Contour::open(vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(2.0, 0.0),
+                Point2d::new(3.0, 5.0),
+                Point2d::new(4.0, 0.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn simplify_zero_tolerance() {
+        let contour = Contour::open(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(1.0, 0.05),
+            Point2d::new(2.0, 0.0),
+        ]);
+        assert_eq!(contour.simplify(0.0), contour);
+    }
+
+    #[test]
+    fn simplify_closed() {
+        let contour = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(0.0, 5.0),
+            Point2d::new(0.0, 10.0),
+            Point2d::new(5.0, 10.0),
+            Point2d::new(10.0, 10.0),
+            Point2d::new(10.0, 0.0),
+            Point2d::new(5.0, 0.0),
+        ]);
+        let simplified = contour.simplify(0.1);
+        assert_eq!(
+            simplified,
+            ClosedContour::new(vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(0.0, 10.0),
+                Point2d::new(10.0, 10.0),
+                Point2d::new(10.0, 0.0),
+            ])
+        );
+
+        let contour = Contour::from(contour);
+        assert_eq!(contour.simplify(0.1), Contour::from(simplified));
+    }
+
+    fn square(size: f64) -> ClosedContour<Point2d> {
+        ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(size, 0.0),
+            Point2d::new(size, size),
+            Point2d::new(0.0, size),
+        ])
+    }
+
+    #[test]
+    fn buffer_square_outwards() {
+        let buffered = square(1.0).buffer(1.0);
+        assert_eq!(
+            buffered,
+            vec![ClosedContour::new(vec![
+                Point2d::new(-1.0, -1.0),
+                Point2d::new(2.0, -1.0),
+                Point2d::new(2.0, 2.0),
+                Point2d::new(-1.0, 2.0),
+            ])]
+        );
+
+        let mut clockwise = square(1.0);
+        clockwise.points.reverse();
+        let buffered = clockwise.buffer(1.0);
+        assert_eq!(buffered.len(), 1);
+        assert_eq!(buffered[0].winding(), Winding::Clockwise);
+        assert_eq!(buffered[0].area_signed(), -9.0);
+    }
+
+    #[test]
+    fn buffer_square_inwards() {
+        assert_eq!(square(1.0).buffer(-1.0), vec![]);
+        assert_eq!(
+            square(4.0).buffer(-1.0),
+            vec![ClosedContour::new(vec![
+                Point2d::new(1.0, 1.0),
+                Point2d::new(3.0, 1.0),
+                Point2d::new(3.0, 3.0),
+                Point2d::new(1.0, 3.0),
+            ])]
+        );
+
+        let strip = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(10.0, 0.0),
+            Point2d::new(10.0, 1.0),
+            Point2d::new(0.0, 1.0),
+        ]);
+        assert_eq!(strip.buffer(-0.6), vec![]);
+    }
+
+    #[test]
+    fn buffer_concave_contour() {
+        let l_shape = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(4.0, 0.0),
+            Point2d::new(4.0, 2.0),
+            Point2d::new(2.0, 2.0),
+            Point2d::new(2.0, 4.0),
+            Point2d::new(0.0, 4.0),
+        ]);
+        let buffered = l_shape.buffer(1.0);
+        assert_eq!(buffered.len(), 1);
+        assert_eq!(buffered[0].points.len(), 6);
+        assert_eq!(buffered[0].points[3], Point2d::new(3.0, 3.0));
+    }
+
+    #[test]
+    fn buffer_sharp_corner_is_beveled() {
+        let triangle = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(10.0, 0.0),
+            Point2d::new(0.0, 1.0),
+        ]);
+        assert_eq!(triangle.buffer(0.1)[0].points.len(), 4);
+        assert_eq!(
+            triangle.buffer_with_miter_limit(0.1, f64::INFINITY)[0]
+                .points
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn buffer_open_contour() {
+        let line = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(2.0, 0.0)]);
+        assert_eq!(
+            line.buffer(1.0),
+            vec![ClosedContour::new(vec![
+                Point2d::new(0.0, 1.0),
+                Point2d::new(0.0, -1.0),
+                Point2d::new(2.0, -1.0),
+                Point2d::new(2.0, 1.0),
+            ])]
+        );
+        assert_eq!(line.buffer(-1.0), vec![]);
+
+        let corner = Contour::open(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(2.0, 0.0),
+            Point2d::new(2.0, 2.0),
+        ]);
+        let buffered = corner.buffer(1.0);
+        assert_eq!(buffered.len(), 1);
+        assert!(buffered[0].points.contains(&Point2d::new(3.0, -1.0)));
+        assert!(buffered[0].points.contains(&Point2d::new(1.0, 1.0)));
+        assert_eq!(buffered[0].area_signed(), 8.0);
+    }
+
+    #[test]
+    fn densify_open() {
+        let contour = Contour::open(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(1.0, 0.0),
+            Point2d::new(1.0, 2.5),
+        ]);
+        let densified = contour.densify(1.0);
+        assert_eq!(densified.points.len(), 5);
+        assert_eq!(densified.points[1], Point2d::new(1.0, 0.0));
+        assert_eq!(densified.points[4], Point2d::new(1.0, 2.5));
+        assert!((densified.points[2].y() - 2.5 / 3.0).abs() < 1e-12);
+        assert!((densified.points[3].y() - 5.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn densify_closed() {
+        let contour = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(2.0, 0.0),
+            Point2d::new(2.0, 2.0),
+            Point2d::new(0.0, 2.0),
+        ]);
+        let densified = contour.densify(1.5);
+        assert_eq!(
+            densified,
+            ClosedContour::new(vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(1.0, 0.0),
+                Point2d::new(2.0, 0.0),
+                Point2d::new(2.0, 1.0),
+                Point2d::new(2.0, 2.0),
+                Point2d::new(1.0, 2.0),
+                Point2d::new(0.0, 2.0),
+                Point2d::new(0.0, 1.0),
+            ])
+        );
+        assert_eq!(
+            Contour::from(contour).densify(1.0),
+            Contour::from(densified)
+        );
+    }
+
+    #[test]
+    fn densify_keeps_short_segments() {
+        let contour = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(0.5, 0.5)]);
+        assert_eq!(contour.densify(1.0), contour);
+        assert_eq!(contour.densify(0.0), contour);
+        assert_eq!(contour.densify(f64::NAN), contour);
+    }
+
+    #[test]
+    fn densify_ignores_invalid_segment_length() {
+        let contour = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(10.0, 0.0)]);
+        assert_eq!(contour.densify(-1.0), contour);
+        assert_eq!(contour.densify(f64::INFINITY), contour);
+        assert_eq!(contour.densify(f64::NEG_INFINITY), contour);
+    }
+
+    #[test]
+    fn densify_limits_points_per_segment() {
+        let contour = Contour::open(vec![Point2d::new(0.0, 0.0), Point2d::new(1.0, 0.0)]);
+        let densified = contour.densify(f64::MIN_POSITIVE);
+        assert_eq!(densified.points.len(), MAX_DENSIFY_POINTS_PER_SEGMENT + 2);
+        assert_eq!(densified.points.last(), Some(&Point2d::new(1.0, 0.0)));
+
+        let closed = ClosedContour::new(vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(1.0, 0.0),
+            Point2d::new(0.0, 1.0),
+        ]);
+        assert_eq!(
+            closed.densify(1e-300).points.len(),
+            3 * (MAX_DENSIFY_POINTS_PER_SEGMENT + 1)
+        );
+    }
+}
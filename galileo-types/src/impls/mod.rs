@@ -1,12 +1,13 @@
 //! Implementations of geometry traits.
 
+pub(crate) mod buffer;
 mod contour;
 mod multi_contour;
 mod multi_point;
 mod multi_polygon;
 mod polygon;
 
-pub use contour::{ClosedContour, Contour};
+pub use contour::{ClosedContour, Contour, MAX_DENSIFY_POINTS_PER_SEGMENT};
 pub use multi_contour::MultiContour;
 pub use multi_point::MultiPoint;
 pub use multi_polygon::MultiPolygon;
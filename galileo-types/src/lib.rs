@@ -55,7 +55,7 @@
 //!
 //! A subset of OGC geometry types are supported at the moment:
 //! * [`GeoPoint`](geo::GeoPoint), [`CartesianPoint2d`](cartesian::CartesianPoint2d), [`CartesianPoint3d`](cartesian::CartesianPoint2d)
-//!    (correspond to OGC *Point* geometry)
+//!   (correspond to OGC *Point* geometry)
 //! * [`MultiPoint`]
 //! * [`Contour`] (corresponds to OGC *LineString* geometry with slight difference, check the trait's documentation)
 //! * [`MultiContour`] (corresponds to OGC *MultiLineString* geometry)
@@ -79,6 +79,16 @@
 //! `galileo-types` provides geometry traits implementation for these crates:
 //! * `geo-types` - enabled by `geo-types` feature
 //! * `geojson` - enabled by `geojson` feature
+//!
+//! # WKT and WKB
+//!
+//! Geometries from databases like PostGIS can be read and written directly in [WKT](wkt) and [WKB](wkb)
+//! formats, without converting them to `geo-types` first.
+//!
+//! # Encoded polylines and geohashes
+//!
+//! Route geometries returned by routing services as [encoded polylines](polyline) and [geohash](geohash) cells can be
+//! decoded into geographic geometries of the crate.
 
 #![warn(clippy::unwrap_used)]
 #![warn(missing_docs)]
@@ -88,6 +98,7 @@ pub mod contour;
 mod disambig;
 pub mod error;
 pub mod geo;
+pub mod geohash;
 pub mod geometry;
 pub mod geometry_type;
 pub mod impls;
@@ -95,7 +106,10 @@ mod multi_contour;
 mod multi_point;
 mod multi_polygon;
 mod polygon;
-mod segment;
+pub mod polyline;
+pub mod segment;
+pub mod wkb;
+pub mod wkt;
 
 #[cfg(feature = "geo-types")]
 mod geo_types;
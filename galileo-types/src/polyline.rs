@@ -0,0 +1,182 @@
+//! Encoding and decoding of geographic lines in the
+//! [encoded polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm) format.
+//!
+//! The format is used by many routing services to return the geometry of a route in a compact string. The
+//! coordinates are rounded to the given number of decimal digits: Google uses precision `5`, while OSRM, Valhalla and
+//! some other services use precision `6`.
+//!
+//! ```
+//! use galileo_types::geo::impls::GeoPoint2d;
+//! use galileo_types::geo::NewGeoPoint;
+//! use galileo_types::polyline::{decode_polyline, encode_polyline};
+//! use galileo_types::Contour;
+//!
+//! let line = decode_polyline("_p~iF~ps|U_ulLnnqC_mqNvxq`@", 5).unwrap();
+//! assert_eq!(line.iter_points().count(), 3);
+//! assert_eq!(line.iter_points().next(), Some(&GeoPoint2d::latlon(38.5, -120.2)));
+//!
+//! assert_eq!(encode_polyline(line.iter_points(), 5), "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+//! ```
+
+use crate::error::GalileoTypesError;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::{GeoPoint, NewGeoPoint};
+use crate::impls::Contour;
+
+/// Offset added to every encoded 5-bit chunk to make it a printable character.
+const CHAR_OFFSET: u8 = 63;
+/// Flag of a chunk that is followed by more chunks of the same value.
+const CONTINUATION_BIT: u8 = 0x20;
+const CHUNK_MASK: u8 = 0x1f;
+/// Largest supported precision. With more digits the coordinates would not fit into 53 bits of `f64`.
+const MAX_PRECISION: u32 = 10;
+
+/// Encodes the points as a polyline, rounding the coordinates to `precision` decimal digits.
+///
+/// # Panics
+///
+/// Panics if `precision` is greater than `10`.
+pub fn encode_polyline<'a, P: GeoPoint<Num = f64> + 'a>(
+    points: impl IntoIterator<Item = &'a P>,
+    precision: u32,
+) -> String {
+    let factor = precision_factor(precision);
+
+    let mut encoded = String::new();
+    let mut previous = (0, 0);
+    for point in points {
+        let current = (
+            (point.lat() * factor).round() as i64,
+            (point.lon() * factor).round() as i64,
+        );
+        encode_value(current.0 - previous.0, &mut encoded);
+        encode_value(current.1 - previous.1, &mut encoded);
+        previous = current;
+    }
+
+    encoded
+}
+
+/// Decodes a polyline, coordinates of which were rounded to `precision` decimal digits, into an open contour.
+///
+/// # Panics
+///
+/// Panics if `precision` is greater than `10`.
+pub fn decode_polyline(
+    encoded: &str,
+    precision: u32,
+) -> Result<Contour<GeoPoint2d>, GalileoTypesError> {
+    let factor = precision_factor(precision);
+
+    let mut bytes = encoded.bytes();
+    let mut points = vec![];
+    let (mut lat, mut lon) = (0i64, 0i64);
+    while let Some(lat_delta) = decode_value(&mut bytes)? {
+        let lon_delta = decode_value(&mut bytes)?
+            .ok_or_else(|| polyline_error("missing longitude of the last point"))?;
+        let too_large = || polyline_error("coordinates are too large");
+        lat = lat.checked_add(lat_delta).ok_or_else(too_large)?;
+        lon = lon.checked_add(lon_delta).ok_or_else(too_large)?;
+        points.push(GeoPoint2d::latlon(lat as f64 / factor, lon as f64 / factor));
+    }
+
+    Ok(Contour::open(points))
+}
+
+fn precision_factor(precision: u32) -> f64 {
+    assert!(
+        precision <= MAX_PRECISION,
+        "polyline precision must not be greater than {MAX_PRECISION}"
+    );
+    10f64.powi(precision as i32)
+}
+
+fn polyline_error(message: impl std::fmt::Display) -> GalileoTypesError {
+    GalileoTypesError::Conversion(format!("invalid encoded polyline: {message}"))
+}
+
+fn encode_value(value: i64, out: &mut String) {
+    // Zigzag encoding puts the sign into the lowest bit.
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    while value >= CONTINUATION_BIT as u64 {
+        let chunk = (value as u8 & CHUNK_MASK) | CONTINUATION_BIT;
+        out.push((chunk + CHAR_OFFSET) as char);
+        value >>= 5;
+    }
+    out.push((value as u8 + CHAR_OFFSET) as char);
+}
+
+/// Reads the next value. Returns `None` if there are no more values.
+fn decode_value(bytes: &mut impl Iterator<Item = u8>) -> Result<Option<i64>, GalileoTypesError> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let Some(byte) = bytes.next() else {
+            return if shift == 0 {
+                Ok(None)
+            } else {
+                Err(polyline_error("unexpected end of the string"))
+            };
+        };
+
+        let chunk = byte
+            .checked_sub(CHAR_OFFSET)
+            .filter(|chunk| *chunk <= CHUNK_MASK | CONTINUATION_BIT)
+            .ok_or_else(|| polyline_error(format!("unexpected character {:?}", byte as char)))?;
+        if shift > 60 {
+            return Err(polyline_error("value is too large"));
+        }
+
+        value |= ((chunk & CHUNK_MASK) as u64) << shift;
+        shift += 5;
+        if chunk & CONTINUATION_BIT == 0 {
+            let value = (value >> 1) as i64 ^ -((value & 1) as i64);
+            return Ok(Some(value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Contour as _;
+
+    #[test]
+    fn round_trip_with_both_precisions() {
+        let points = vec![
+            GeoPoint2d::latlon(52.520008, 13.404954),
+            GeoPoint2d::latlon(52.519271, 13.406354),
+            GeoPoint2d::latlon(-33.868820, 151.209296),
+            GeoPoint2d::latlon(0.0, -179.999999),
+        ];
+
+        for precision in [5, 6] {
+            let encoded = encode_polyline(&points, precision);
+            let decoded = decode_polyline(&encoded, precision).unwrap();
+            let tolerance = 0.5 / 10f64.powi(precision as i32) + 1e-12;
+            assert_eq!(decoded.iter_points().count(), points.len());
+            for (decoded, original) in decoded.iter_points().zip(&points) {
+                assert!((decoded.lat() - original.lat()).abs() <= tolerance);
+                assert!((decoded.lon() - original.lon()).abs() <= tolerance);
+            }
+        }
+
+        assert_eq!(
+            encode_polyline(&[GeoPoint2d::latlon(38.5, -120.2)], 6),
+            "_izlhA~rlgdF"
+        );
+        assert_eq!(encode_polyline(&Vec::<GeoPoint2d>::new(), 5), "");
+        assert_eq!(decode_polyline("", 5).unwrap().iter_points().count(), 0);
+    }
+
+    #[test]
+    fn invalid_polylines() {
+        // Latitude without longitude.
+        assert!(decode_polyline("_p~iF", 5).is_err());
+        // Value is not terminated.
+        assert!(decode_polyline("_p~iF~ps|", 5).is_err());
+        // Characters outside of the encoding range.
+        assert!(decode_polyline("_p~iF ps|U", 5).is_err());
+        assert!(decode_polyline("~~~~~~~~~~~~~~~", 5).is_err());
+    }
+}
@@ -1,10 +1,38 @@
-use crate::cartesian::{CartesianPoint2d, Orientation};
+//! [`Segment`] type and functions to work with line segments.
+
+use crate::cartesian::{CartesianPoint2d, NewCartesianPoint2d, Orientation};
+use nalgebra::{Point2, Scalar};
 use num_traits::{One, Zero};
+use std::cmp::Ordering;
+
+/// Common part of two segments. See [`Segment::intersection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SegmentIntersection<N: Scalar> {
+    /// The segments cross each other at a single point, that is not an endpoint of any of the segments.
+    Crossing(Point2<N>),
+    /// The segments have a single common point, that is an endpoint of at least one of the segments. This includes
+    /// segments touching each other by their ends, T-junctions and collinear segments that touch by their ends.
+    Touching(Point2<N>),
+    /// The segments are collinear and overlap. The common part of the segments is the segment between the given
+    /// points.
+    Overlap(Point2<N>, Point2<N>),
+}
 
 /// A strait line segment between two points.
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct Segment<'a, Point>(pub &'a Point, pub &'a Point);
 
+/// Position of the closest to a point location on a segment. See [`Segment::project`].
+enum Projection<N> {
+    /// The closest location is the start point of the segment.
+    Start,
+    /// The closest location is the end point of the segment.
+    End,
+    /// The closest location is the end of the normal from the point to the segment. `ratio` is the position of the
+    /// location along the segment in `(0, 1)` range.
+    Inner { ratio: N, distance_sq: N },
+}
+
 impl<'a, P: CartesianPoint2d> Segment<'a, P> {
     /// Shortest euclidian distance (squared) between a point and the segment:
     ///
@@ -16,8 +44,43 @@ impl<'a, P: CartesianPoint2d> Segment<'a, P> {
         &self,
         point: &Point,
     ) -> P::Num {
+        match self.project(point) {
+            Projection::Start => self.0.distance_sq(point),
+            Projection::End => self.1.distance_sq(point),
+            Projection::Inner { distance_sq, .. } => distance_sq,
+        }
+    }
+
+    /// Returns the point of the segment closest to the given `point` and the squared distance between them. The
+    /// distance is the same as returned by [`Segment::distance_to_point_sq`].
+    ///
+    /// ```
+    /// use galileo_types::cartesian::Point2d;
+    /// use galileo_types::Segment;
+    ///
+    /// let segment = Segment(&Point2d::new(0.0, 0.0), &Point2d::new(4.0, 0.0));
+    /// assert_eq!(segment.closest_point(&Point2d::new(1.0, 2.0)), (Point2d::new(1.0, 0.0), 4.0));
+    /// assert_eq!(segment.closest_point(&Point2d::new(5.0, 0.0)), (Point2d::new(4.0, 0.0), 1.0));
+    /// ```
+    pub fn closest_point<Point: CartesianPoint2d<Num = f64>>(&self, point: &Point) -> (P, f64)
+    where
+        P: NewCartesianPoint2d + Clone,
+    {
+        match self.project(point) {
+            Projection::Start => (self.0.clone(), self.0.distance_sq(point)),
+            Projection::End => (self.1.clone(), self.1.distance_sq(point)),
+            Projection::Inner { ratio, distance_sq } => {
+                let ds = self.1.sub(self.0);
+                let projected = P::new(self.0.x() + ds.x * ratio, self.0.y() + ds.y * ratio);
+                (projected, distance_sq)
+            }
+        }
+    }
+
+    /// Finds the location on the segment closest to the given point.
+    fn project<Point: CartesianPoint2d<Num = P::Num>>(&self, point: &Point) -> Projection<P::Num> {
         if self.0.equal(self.1) {
-            return self.0.distance_sq(point);
+            return Projection::Start;
         }
 
         let ds = self.1.sub(self.0);
@@ -26,12 +89,15 @@ impl<'a, P: CartesianPoint2d> Segment<'a, P> {
 
         let r = (dp.x * ds.x + dp.y * ds.y) / ds_len;
         if r <= P::Num::zero() {
-            self.0.distance_sq(point)
+            Projection::Start
         } else if r >= P::Num::one() {
-            self.1.distance_sq(point)
+            Projection::End
         } else {
             let s = (dp.y * ds.x - dp.x * ds.y) / ds_len;
-            (s * s) * ds_len
+            Projection::Inner {
+                ratio: r,
+                distance_sq: (s * s) * ds_len,
+            }
         }
     }
 
@@ -47,8 +113,8 @@ impl<'a, P: CartesianPoint2d> Segment<'a, P> {
         ) -> bool {
             let x_max = if p.x() >= r.x() { p.x() } else { r.x() };
             let x_min = if p.x() <= r.x() { p.x() } else { r.x() };
-            let y_max = if p.y() >= r.y() { p.x() } else { r.x() };
-            let y_min = if p.y() <= r.y() { p.x() } else { r.x() };
+            let y_max = if p.y() >= r.y() { p.y() } else { r.y() };
+            let y_min = if p.y() <= r.y() { p.y() } else { r.y() };
 
             q.x() <= x_max && q.x() >= x_min && q.y() <= y_max && q.y() >= y_min
         }
@@ -77,4 +143,247 @@ impl<'a, P: CartesianPoint2d> Segment<'a, P> {
 
         false
     }
+
+    /// Returns the common part of this and the `other` segments, or `None` if the segments do not intersect.
+    ///
+    /// If the segments are collinear, they can overlap over a part of their length, in which case
+    /// [`SegmentIntersection::Overlap`] is returned. If the segments have a single common point, it is returned as
+    /// [`SegmentIntersection::Touching`] when it is an endpoint of any of the segments, and as
+    /// [`SegmentIntersection::Crossing`] otherwise.
+    ///
+    /// ```
+    /// use galileo_types::cartesian::Point2d;
+    /// use galileo_types::segment::SegmentIntersection;
+    /// use galileo_types::Segment;
+    ///
+    /// let a = Segment(&Point2d::new(0.0, 0.0), &Point2d::new(2.0, 2.0));
+    /// let b = Segment(&Point2d::new(0.0, 2.0), &Point2d::new(2.0, 0.0));
+    /// assert_eq!(a.intersection(&b), Some(SegmentIntersection::Crossing(Point2d::new(1.0, 1.0))));
+    /// ```
+    pub fn intersection<Point: CartesianPoint2d<Num = P::Num>>(
+        &self,
+        other: &Segment<Point>,
+    ) -> Option<SegmentIntersection<P::Num>> {
+        let zero = P::Num::zero();
+
+        let p0 = to_point(self.0);
+        let p1 = to_point(self.1);
+        let q0 = to_point(other.0);
+        let q1 = to_point(other.1);
+
+        if p0 == p1 {
+            return point_on_segment(&p0, &q0, &q1).then_some(SegmentIntersection::Touching(p0));
+        }
+        if q0 == q1 {
+            return point_on_segment(&q0, &p0, &p1).then_some(SegmentIntersection::Touching(q0));
+        }
+
+        let r = p1.sub(&p0);
+        let s = q1.sub(&q0);
+        let qp = q0.sub(&p0);
+
+        let denominator = cross(r.x, r.y, s.x, s.y);
+        if denominator == zero {
+            if cross(qp.x, qp.y, r.x, r.y) != zero {
+                // Parallel segments.
+                return None;
+            }
+
+            // Collinear segments. All four points lie on the same line, so they can be ordered
+            // lexicographically along it.
+            let (a_min, a_max) = ordered(p0, p1);
+            let (b_min, b_max) = ordered(q0, q1);
+            let start = if compare(&a_min, &b_min) == Ordering::Less {
+                b_min
+            } else {
+                a_min
+            };
+            let end = if compare(&a_max, &b_max) == Ordering::Less {
+                a_max
+            } else {
+                b_max
+            };
+
+            return match compare(&start, &end) {
+                Ordering::Less => Some(SegmentIntersection::Overlap(start, end)),
+                Ordering::Equal => Some(SegmentIntersection::Touching(start)),
+                Ordering::Greater => None,
+            };
+        }
+
+        // Parameters of the intersection point along the segments are `t / denominator` and `u / denominator`.
+        let mut t = cross(qp.x, qp.y, s.x, s.y);
+        let mut u = cross(qp.x, qp.y, r.x, r.y);
+        let mut denominator = denominator;
+        if denominator < zero {
+            t = zero - t;
+            u = zero - u;
+            denominator = zero - denominator;
+        }
+
+        if t < zero || t > denominator || u < zero || u > denominator {
+            return None;
+        }
+
+        // Endpoints are returned as is to avoid rounding errors.
+        let touching = if t == zero {
+            Some(p0)
+        } else if t == denominator {
+            Some(p1)
+        } else if u == zero {
+            Some(q0)
+        } else if u == denominator {
+            Some(q1)
+        } else {
+            None
+        };
+
+        match touching {
+            Some(point) => Some(SegmentIntersection::Touching(point)),
+            None => Some(SegmentIntersection::Crossing(Point2::new(
+                p0.x + r.x * t / denominator,
+                p0.y + r.y * t / denominator,
+            ))),
+        }
+    }
+}
+
+fn to_point<N: Scalar + Copy>(p: &impl CartesianPoint2d<Num = N>) -> Point2<N> {
+    Point2::new(p.x(), p.y())
+}
+
+fn cross<N: num_traits::Num + Copy>(ax: N, ay: N, bx: N, by: N) -> N {
+    ax * by - ay * bx
+}
+
+fn compare<N: PartialOrd + Scalar>(a: &Point2<N>, b: &Point2<N>) -> Ordering {
+    a.x.partial_cmp(&b.x)
+        .unwrap_or(Ordering::Equal)
+        .then(a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal))
+}
+
+fn ordered<N: PartialOrd + Scalar>(a: Point2<N>, b: Point2<N>) -> (Point2<N>, Point2<N>) {
+    if compare(&a, &b) == Ordering::Greater {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
+
+fn point_on_segment<N: num_traits::Num + PartialOrd + Scalar + Copy>(
+    p: &Point2<N>,
+    a: &Point2<N>,
+    b: &Point2<N>,
+) -> bool {
+    if a == b {
+        return p == a;
+    }
+
+    let (min, max) = ordered(*a, *b);
+    cross(p.x - a.x, p.y - a.y, b.x - a.x, b.y - a.y) == N::zero()
+        && compare(&min, p) != Ordering::Greater
+        && compare(p, &max) != Ordering::Greater
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::Point2d;
+
+    fn intersection(a: [f64; 4], b: [f64; 4]) -> Option<SegmentIntersection<f64>> {
+        let a = [Point2d::new(a[0], a[1]), Point2d::new(a[2], a[3])];
+        let b = [Point2d::new(b[0], b[1]), Point2d::new(b[2], b[3])];
+        let result = Segment(&a[0], &a[1]).intersection(&Segment(&b[0], &b[1]));
+        let reverse = Segment(&b[1], &b[0]).intersection(&Segment(&a[1], &a[0]));
+        assert_eq!(result, reverse);
+        assert_eq!(
+            result.is_some(),
+            Segment(&a[0], &a[1]).intersects(&Segment(&b[0], &b[1]))
+        );
+        result
+    }
+
+    #[test]
+    fn intersection_crossing() {
+        assert_eq!(
+            intersection([0.0, 0.0, 4.0, 4.0], [0.0, 4.0, 4.0, 0.0]),
+            Some(SegmentIntersection::Crossing(Point2d::new(2.0, 2.0)))
+        );
+        assert_eq!(
+            intersection([0.0, 1.0, 4.0, 1.0], [1.0, 0.0, 1.0, 4.0]),
+            Some(SegmentIntersection::Crossing(Point2d::new(1.0, 1.0)))
+        );
+        assert_eq!(
+            intersection([0.0, 0.0, 1.0, 1.0], [0.0, 4.0, 4.0, 0.0]),
+            None
+        );
+    }
+
+    #[test]
+    fn intersection_parallel() {
+        assert_eq!(
+            intersection([0.0, 0.0, 4.0, 0.0], [0.0, 1.0, 4.0, 1.0]),
+            None
+        );
+        assert_eq!(
+            intersection([0.0, 0.0, 1.0, 1.0], [1.0, 0.0, 2.0, 1.0]),
+            None
+        );
+    }
+
+    #[test]
+    fn intersection_collinear() {
+        assert_eq!(
+            intersection([0.0, 0.0, 4.0, 0.0], [2.0, 0.0, 6.0, 0.0]),
+            Some(SegmentIntersection::Overlap(
+                Point2d::new(2.0, 0.0),
+                Point2d::new(4.0, 0.0)
+            ))
+        );
+        assert_eq!(
+            intersection([0.0, 0.0, 0.0, 4.0], [0.0, 3.0, 0.0, 1.0]),
+            Some(SegmentIntersection::Overlap(
+                Point2d::new(0.0, 1.0),
+                Point2d::new(0.0, 3.0)
+            ))
+        );
+        assert_eq!(
+            intersection([0.0, 0.0, 2.0, 2.0], [2.0, 2.0, 3.0, 3.0]),
+            Some(SegmentIntersection::Touching(Point2d::new(2.0, 2.0)))
+        );
+        assert_eq!(
+            intersection([0.0, 0.0, 1.0, 1.0], [2.0, 2.0, 3.0, 3.0]),
+            None
+        );
+    }
+
+    #[test]
+    fn intersection_touching() {
+        // T-junction
+        assert_eq!(
+            intersection([0.0, 0.0, 4.0, 0.0], [2.0, 0.0, 2.0, 3.0]),
+            Some(SegmentIntersection::Touching(Point2d::new(2.0, 0.0)))
+        );
+        // Common endpoint
+        assert_eq!(
+            intersection([0.0, 0.0, 4.0, 0.0], [4.0, 0.0, 4.0, 3.0]),
+            Some(SegmentIntersection::Touching(Point2d::new(4.0, 0.0)))
+        );
+        // Degenerate segment
+        assert_eq!(
+            intersection([1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 2.0, 2.0]),
+            Some(SegmentIntersection::Touching(Point2d::new(1.0, 1.0)))
+        );
+        assert_eq!(
+            intersection([1.0, 0.0, 1.0, 0.0], [0.0, 0.0, 2.0, 2.0]),
+            None
+        );
+    }
+
+    #[test]
+    fn intersects_collinear_vertical() {
+        let a = [Point2d::new(0.0, 0.0), Point2d::new(0.0, 2.0)];
+        let b = [Point2d::new(0.0, 3.0), Point2d::new(0.0, 5.0)];
+        assert!(!Segment(&a[0], &a[1]).intersects(&Segment(&b[0], &b[1])));
+    }
 }
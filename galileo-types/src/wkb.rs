@@ -0,0 +1,427 @@
+//! Reading and writing geometries in the [well-known binary](https://en.wikipedia.org/wiki/Well-known_text_representation_of_geometry#Well-known_binary)
+//! (WKB) format.
+//!
+//! Geometries are written with [`ToWkb`] in the ISO WKB format with little-endian byte order. [`FromWkb`] reads
+//! both byte orders, ISO WKB and the extended WKB format used by PostGIS (with optional SRID, which is skipped).
+//! Point types and handling of `Z` and `M` coordinates are the same as for [WKT](crate::wkt):
+//!
+//! ```
+//! use galileo_types::cartesian::Point2d;
+//! use galileo_types::geometry::Geom;
+//! use galileo_types::wkb::{FromWkb, ToWkb};
+//!
+//! let geometry = Geom::Point(Point2d::new(1.0, 2.0));
+//! let wkb = geometry.to_wkb();
+//! assert_eq!(Geom::<Point2d>::from_wkb(&wkb).unwrap(), geometry);
+//! ```
+
+use crate::error::GalileoTypesError;
+use crate::geometry::Geom;
+use crate::impls::{ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use crate::wkt::{ring, WktPoint};
+use crate::{Contour as _, MultiContour as _, MultiPoint as _, MultiPolygon as _, Polygon as _};
+
+const LITTLE_ENDIAN: u8 = 1;
+const EWKB_Z_FLAG: u32 = 0x8000_0000;
+const EWKB_M_FLAG: u32 = 0x4000_0000;
+const EWKB_SRID_FLAG: u32 = 0x2000_0000;
+
+const POINT: u32 = 1;
+const LINESTRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTIPOINT: u32 = 4;
+const MULTILINESTRING: u32 = 5;
+const MULTIPOLYGON: u32 = 6;
+
+/// Geometry that can be written in WKB format.
+pub trait ToWkb {
+    /// Returns the WKB representation of the geometry.
+    fn to_wkb(&self) -> Vec<u8>;
+}
+
+/// Geometry that can be read from WKB format.
+pub trait FromWkb: Sized {
+    /// Parses the geometry from its WKB representation.
+    fn from_wkb(wkb: &[u8]) -> Result<Self, GalileoTypesError>;
+}
+
+fn wkb_error(message: impl std::fmt::Display) -> GalileoTypesError {
+    GalileoTypesError::Conversion(format!("invalid WKB: {message}"))
+}
+
+struct WkbWriter<P> {
+    bytes: Vec<u8>,
+    _phantom: std::marker::PhantomData<P>,
+}
+
+impl<P: WktPoint> WkbWriter<P> {
+    fn new() -> Self {
+        Self {
+            bytes: vec![],
+            _phantom: Default::default(),
+        }
+    }
+
+    fn header(&mut self, geometry_type: u32) {
+        self.bytes.push(LITTLE_ENDIAN);
+        let geometry_type = if P::DIMENSIONS == 3 {
+            geometry_type + 1000
+        } else {
+            geometry_type
+        };
+        self.count(geometry_type as usize);
+    }
+
+    fn count(&mut self, count: usize) {
+        self.bytes.extend_from_slice(&(count as u32).to_le_bytes());
+    }
+
+    fn point(&mut self, point: &P) {
+        for value in &point.coordinates()[..P::DIMENSIONS] {
+            self.bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn points<'a>(&mut self, points: impl Iterator<Item = &'a P>, close: bool)
+    where
+        P: 'a,
+    {
+        let points: Vec<_> = points.collect();
+        let closing = points.first().filter(|_| close).copied();
+        self.count(points.len() + closing.iter().count());
+        for point in points.into_iter().chain(closing) {
+            self.point(point);
+        }
+    }
+
+    fn contour(&mut self, contour: &Contour<P>) {
+        self.header(LINESTRING);
+        self.points(contour.iter_points(), contour.is_closed());
+    }
+
+    fn polygon(&mut self, polygon: &Polygon<P>) {
+        self.header(POLYGON);
+        self.count(polygon.iter_contours().count());
+        for contour in polygon.iter_contours() {
+            self.points(contour.iter_points(), true);
+        }
+    }
+}
+
+macro_rules! impl_to_wkb {
+    ($geom:ident, |$writer:ident, $value:ident| $body:expr) => {
+        impl<P: WktPoint> ToWkb for $geom<P> {
+            fn to_wkb(&self) -> Vec<u8> {
+                let mut $writer = WkbWriter::<P>::new();
+                let $value = self;
+                $body;
+                $writer.bytes
+            }
+        }
+    };
+}
+
+impl_to_wkb!(Contour, |writer, contour| writer.contour(contour));
+impl_to_wkb!(ClosedContour, |writer, contour| {
+    writer.header(LINESTRING);
+    writer.points(contour.iter_points(), true);
+});
+impl_to_wkb!(Polygon, |writer, polygon| writer.polygon(polygon));
+impl_to_wkb!(MultiPoint, |writer, points| {
+    writer.header(MULTIPOINT);
+    writer.count(points.iter_points().count());
+    for point in points.iter_points() {
+        writer.header(POINT);
+        writer.point(point);
+    }
+});
+impl_to_wkb!(MultiContour, |writer, contours| {
+    writer.header(MULTILINESTRING);
+    writer.count(contours.contours().count());
+    for contour in contours.contours() {
+        writer.contour(contour);
+    }
+});
+impl_to_wkb!(MultiPolygon, |writer, polygons| {
+    writer.header(MULTIPOLYGON);
+    writer.count(polygons.polygons().count());
+    for polygon in polygons.polygons() {
+        writer.polygon(polygon);
+    }
+});
+
+impl<P: WktPoint> ToWkb for Geom<P> {
+    fn to_wkb(&self) -> Vec<u8> {
+        match self {
+            Geom::Point(point) => {
+                let mut writer = WkbWriter::<P>::new();
+                writer.header(POINT);
+                writer.point(point);
+                writer.bytes
+            }
+            Geom::MultiPoint(points) => points.to_wkb(),
+            Geom::Contour(contour) => contour.to_wkb(),
+            Geom::MultiContour(contours) => contours.to_wkb(),
+            Geom::Polygon(polygon) => polygon.to_wkb(),
+            Geom::MultiPolygon(polygons) => polygons.to_wkb(),
+        }
+    }
+}
+
+impl<P: WktPoint> FromWkb for Geom<P> {
+    fn from_wkb(wkb: &[u8]) -> Result<Self, GalileoTypesError> {
+        let mut reader = WkbReader {
+            bytes: wkb,
+            offset: 0,
+            is_little_endian: true,
+        };
+        let geometry = reader.geometry(None)?;
+        if reader.offset != wkb.len() {
+            return Err(wkb_error("unexpected bytes after the geometry"));
+        }
+
+        Ok(geometry)
+    }
+}
+
+macro_rules! impl_from_wkb {
+    ($geom:ident, $variant:ident, $name:literal) => {
+        impl<P: WktPoint> FromWkb for $geom<P> {
+            fn from_wkb(wkb: &[u8]) -> Result<Self, GalileoTypesError> {
+                match Geom::from_wkb(wkb)? {
+                    Geom::$variant(geometry) => Ok(geometry),
+                    _ => Err(wkb_error(concat!("expected ", $name))),
+                }
+            }
+        }
+    };
+}
+
+impl_from_wkb!(Contour, Contour, "LineString");
+impl_from_wkb!(Polygon, Polygon, "Polygon");
+impl_from_wkb!(MultiPoint, MultiPoint, "MultiPoint");
+impl_from_wkb!(MultiContour, MultiContour, "MultiLineString");
+impl_from_wkb!(MultiPolygon, MultiPolygon, "MultiPolygon");
+
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    is_little_endian: bool,
+}
+
+impl WkbReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], GalileoTypesError> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + N)
+            .ok_or_else(|| wkb_error("unexpected end of data"))?;
+        self.offset += N;
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        if !self.is_little_endian {
+            array.reverse();
+        }
+        Ok(array)
+    }
+
+    fn u32(&mut self) -> Result<u32, GalileoTypesError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn count(&mut self) -> Result<usize, GalileoTypesError> {
+        let count = self.u32()? as usize;
+        // Every item takes at least 8 bytes, so a larger count cannot be valid. This prevents huge allocations for
+        // corrupted data.
+        if count > (self.bytes.len() - self.offset) / 8 {
+            return Err(wkb_error("invalid number of items"));
+        }
+        Ok(count)
+    }
+
+    fn f64(&mut self) -> Result<f64, GalileoTypesError> {
+        Ok(f64::from_le_bytes(self.take()?))
+    }
+
+    /// Reads a geometry with its header. If `expected_type` is set, the geometry must be of that type.
+    fn geometry<P: WktPoint>(
+        &mut self,
+        expected_type: Option<u32>,
+    ) -> Result<Geom<P>, GalileoTypesError> {
+        let [byte_order] = self.take::<1>()?;
+        self.is_little_endian = byte_order == LITTLE_ENDIAN;
+
+        let raw_type = self.u32()?;
+        if raw_type & EWKB_SRID_FLAG != 0 {
+            let _srid = self.u32()?;
+        }
+        let mut has_z = raw_type & EWKB_Z_FLAG != 0;
+        let mut has_m = raw_type & EWKB_M_FLAG != 0;
+        let iso_type = raw_type & 0x0fff_ffff;
+        match iso_type / 1000 {
+            0 => {}
+            1 => has_z = true,
+            2 => has_m = true,
+            3 => {
+                has_z = true;
+                has_m = true;
+            }
+            _ => return Err(wkb_error(format!("unknown geometry type {raw_type}"))),
+        }
+        let geometry_type = iso_type % 1000;
+        if expected_type.is_some_and(|expected| expected != geometry_type) {
+            return Err(wkb_error(format!(
+                "unexpected geometry type {geometry_type} in a multi geometry"
+            )));
+        }
+
+        let point = |reader: &mut Self| -> Result<P, GalileoTypesError> {
+            let x = reader.f64()?;
+            let y = reader.f64()?;
+            let z = if has_z { Some(reader.f64()?) } else { None };
+            if has_m {
+                let _m = reader.f64()?;
+            }
+            Ok(P::from_coordinates(x, y, z))
+        };
+        let points = |reader: &mut Self| -> Result<Vec<P>, GalileoTypesError> {
+            (0..reader.count()?).map(|_| point(reader)).collect()
+        };
+        let polygon = |reader: &mut Self| -> Result<Polygon<P>, GalileoTypesError> {
+            let mut rings = (0..reader.count()?)
+                .map(|_| points(reader).map(ring))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter();
+            let outer = rings.next().unwrap_or_else(|| ClosedContour::new(vec![]));
+            Ok(Polygon::new(outer, rings.collect()))
+        };
+
+        Ok(match geometry_type {
+            POINT => {
+                let point = point(self)?;
+                if point.coordinates()[..2].iter().all(|value| value.is_nan()) {
+                    return Err(wkb_error("empty points are not supported"));
+                }
+                Geom::Point(point)
+            }
+            LINESTRING => Geom::Contour(Contour::open(points(self)?)),
+            POLYGON => Geom::Polygon(polygon(self)?),
+            MULTIPOINT | MULTILINESTRING | MULTIPOLYGON => {
+                let item_type = geometry_type - 3;
+                let items = (0..self.count()?)
+                    .map(|_| self.geometry::<P>(Some(item_type)))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter();
+                match geometry_type {
+                    MULTIPOINT => Geom::MultiPoint(MultiPoint::from(
+                        items
+                            .filter_map(|item| match item {
+                                Geom::Point(point) => Some(point),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>(),
+                    )),
+                    MULTILINESTRING => Geom::MultiContour(MultiContour::from(
+                        items
+                            .filter_map(|item| match item {
+                                Geom::Contour(contour) => Some(contour),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>(),
+                    )),
+                    _ => Geom::MultiPolygon(MultiPolygon::from(
+                        items
+                            .filter_map(|item| match item {
+                                Geom::Polygon(polygon) => Some(polygon),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>(),
+                    )),
+                }
+            }
+            _ => {
+                return Err(wkb_error(format!(
+                    "unsupported geometry type {geometry_type}"
+                )))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::{Point2d, Point3d};
+    use crate::geo::impls::GeoPoint2d;
+    use crate::geo::NewGeoPoint;
+    use crate::wkt::{FromWkt, ToWkt};
+
+    fn hex(bytes: &str) -> Vec<u8> {
+        (0..bytes.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&bytes[index..index + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn writes_point() {
+        let wkb = Geom::Point(Point2d::new(1.0, 2.0)).to_wkb();
+        assert_eq!(wkb, hex("0101000000000000000000f03f0000000000000040"));
+    }
+
+    #[test]
+    fn reads_big_endian_point() {
+        let wkb = hex("00000000013ff00000000000004000000000000000");
+        assert_eq!(
+            Geom::<Point2d>::from_wkb(&wkb).unwrap(),
+            Geom::Point(Point2d::new(1.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn reads_postgis_ewkb_with_srid() {
+        // SRID=4326;POINT(20 10) as returned by PostGIS.
+        let wkb = hex("0101000020E610000000000000000034400000000000002440");
+        assert_eq!(
+            Geom::<GeoPoint2d>::from_wkb(&wkb).unwrap(),
+            Geom::Point(GeoPoint2d::latlon(10.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        for wkt in [
+            "LINESTRING (1 2, 4 5)",
+            "POLYGON ((0 0, 10 0, 10 10, 0 0), (1 1, 2 1, 2 2, 1 1))",
+            "MULTIPOINT ((1 2), (3 4))",
+            "MULTILINESTRING ((1 2, 3 4), (5 6, 7 8))",
+            "MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), ((5 5, 6 5, 6 6, 5 5)))",
+        ] {
+            let geometry = Geom::<Point2d>::from_wkt(wkt).unwrap();
+            let wkb = geometry.to_wkb();
+            assert_eq!(Geom::<Point2d>::from_wkb(&wkb).unwrap().to_wkt(), wkt);
+        }
+
+        let geometry =
+            Geom::<Point3d>::from_wkt("POLYGON Z ((0 0 1, 1 0 2, 1 1 3, 0 0 1))").unwrap();
+        let wkb = geometry.to_wkb();
+        assert_eq!(u32::from_le_bytes([wkb[1], wkb[2], wkb[3], wkb[4]]), 1003);
+        assert_eq!(Geom::<Point3d>::from_wkb(&wkb).unwrap(), geometry);
+
+        // 3d geometry read as 2d.
+        let polygon = Polygon::<Point2d>::from_wkb(&wkb).unwrap();
+        assert_eq!(polygon.to_wkt(), "POLYGON ((0 0, 1 0, 1 1, 0 0))");
+    }
+
+    #[test]
+    fn invalid_wkb_is_an_error() {
+        let wkb = Geom::Point(Point2d::new(1.0, 2.0)).to_wkb();
+        assert!(Geom::<Point2d>::from_wkb(&wkb[..wkb.len() - 1]).is_err());
+        assert!(Geom::<Point2d>::from_wkb(&[wkb.clone(), vec![0]].concat()).is_err());
+        assert!(Contour::<Point2d>::from_wkb(&wkb).is_err());
+
+        // Line string with 2^32 - 1 points.
+        assert!(Geom::<Point2d>::from_wkb(&hex("0102000000ffffffff")).is_err());
+        // Geometry collection.
+        assert!(Geom::<Point2d>::from_wkb(&hex("010700000000000000")).is_err());
+    }
+}
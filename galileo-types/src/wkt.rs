@@ -0,0 +1,540 @@
+//! Reading and writing geometries in the [well-known text](https://en.wikipedia.org/wiki/Well-known_text_representation_of_geometry)
+//! (WKT) format.
+//!
+//! [`Geom`] and the geometry types of the [`impls`](crate::impls) module can be written with [`ToWkt`] and read
+//! with [`FromWkt`], for any point type implementing [`WktPoint`]:
+//!
+//! ```
+//! use galileo_types::cartesian::Point2d;
+//! use galileo_types::geometry::Geom;
+//! use galileo_types::wkt::{FromWkt, ToWkt};
+//!
+//! let geometry = Geom::<Point2d>::from_wkt("LINESTRING (30 10, 10 30, 40 40)").unwrap();
+//! assert_eq!(geometry.to_wkt(), "LINESTRING (30 10, 10 30, 40 40)");
+//! ```
+//!
+//! Geometries with `Z` coordinates are written as `POINT Z (1 2 3)`. When a geometry is read, `M` coordinates are
+//! ignored, missing `Z` coordinates are set to `0`, and `Z` coordinates are dropped for 2d point types. The
+//! `SRID=...;` prefix of the extended WKT format is skipped. Empty points and geometry collections are not supported.
+
+use crate::cartesian::{NewCartesianPoint2d, NewCartesianPoint3d, Point2d, Point3d};
+use crate::error::GalileoTypesError;
+use crate::geo::impls::GeoPoint2d;
+use crate::geo::{GeoPoint, NewGeoPoint};
+use crate::geometry::Geom;
+use crate::impls::{ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use crate::{Contour as _, MultiContour as _, MultiPoint as _, MultiPolygon as _, Polygon as _};
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A point that can be written into and read from WKT and [WKB](crate::wkb).
+pub trait WktPoint: Sized {
+    /// Number of the coordinates of the point: `2` or `3`.
+    const DIMENSIONS: usize;
+
+    /// Coordinates of the point in the order they are written: `x`, `y` and `z`, or `longitude` and `latitude`.
+    /// Values after the first [`WktPoint::DIMENSIONS`] are ignored.
+    fn coordinates(&self) -> [f64; 3];
+
+    /// Creates a point from its coordinates. `z` is `None` if the source geometry is 2d.
+    fn from_coordinates(x: f64, y: f64, z: Option<f64>) -> Self;
+}
+
+impl WktPoint for Point2d {
+    const DIMENSIONS: usize = 2;
+
+    fn coordinates(&self) -> [f64; 3] {
+        [self.x, self.y, 0.0]
+    }
+
+    fn from_coordinates(x: f64, y: f64, _z: Option<f64>) -> Self {
+        <Point2d as NewCartesianPoint2d>::new(x, y)
+    }
+}
+
+impl WktPoint for Point3d {
+    const DIMENSIONS: usize = 3;
+
+    fn coordinates(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    fn from_coordinates(x: f64, y: f64, z: Option<f64>) -> Self {
+        <Point3d as NewCartesianPoint3d>::new(x, y, z.unwrap_or_default())
+    }
+}
+
+impl WktPoint for GeoPoint2d {
+    const DIMENSIONS: usize = 2;
+
+    fn coordinates(&self) -> [f64; 3] {
+        [self.lon(), self.lat(), 0.0]
+    }
+
+    fn from_coordinates(x: f64, y: f64, _z: Option<f64>) -> Self {
+        GeoPoint2d::lonlat(x, y)
+    }
+}
+
+/// Geometry that can be written in WKT format.
+pub trait ToWkt {
+    /// Returns the WKT representation of the geometry.
+    fn to_wkt(&self) -> String;
+}
+
+/// Geometry that can be read from WKT format.
+pub trait FromWkt: Sized {
+    /// Parses the geometry from its WKT representation.
+    fn from_wkt(wkt: &str) -> Result<Self, GalileoTypesError>;
+}
+
+fn wkt_error(message: impl std::fmt::Display) -> GalileoTypesError {
+    GalileoTypesError::Conversion(format!("invalid WKT: {message}"))
+}
+
+fn dimension_tag<P: WktPoint>() -> &'static str {
+    if P::DIMENSIONS == 3 {
+        " Z"
+    } else {
+        ""
+    }
+}
+
+fn write_point<P: WktPoint>(out: &mut String, point: &P) {
+    for (index, value) in point.coordinates()[..P::DIMENSIONS].iter().enumerate() {
+        if index > 0 {
+            out.push(' ');
+        }
+        let _ = write!(out, "{value}");
+    }
+}
+
+/// Writes a list of points in parentheses. If `close` is true, the first point is repeated at the end.
+fn write_points<'a, P: WktPoint + 'a>(
+    out: &mut String,
+    points: impl Iterator<Item = &'a P>,
+    close: bool,
+) {
+    let points: Vec<_> = points.collect();
+    if points.is_empty() {
+        out.push_str("EMPTY");
+        return;
+    }
+
+    out.push('(');
+    let first = points[0];
+    let closing = close.then_some(first);
+    for (index, point) in points.into_iter().chain(closing).enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        write_point(out, point);
+    }
+    out.push(')');
+}
+
+fn write_polygon_body<P: WktPoint>(out: &mut String, polygon: &Polygon<P>) {
+    out.push('(');
+    for (index, contour) in polygon.iter_contours().enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        write_points(out, contour.iter_points(), true);
+    }
+    out.push(')');
+}
+
+fn write_list<T>(
+    out: &mut String,
+    items: impl Iterator<Item = T>,
+    mut write: impl FnMut(&mut String, T),
+) {
+    let mut is_empty = true;
+    for (index, item) in items.enumerate() {
+        out.push_str(if index == 0 { "(" } else { ", " });
+        write(out, item);
+        is_empty = false;
+    }
+    out.push_str(if is_empty { "EMPTY" } else { ")" });
+}
+
+fn tagged<P: WktPoint>(tag: &str, write: impl FnOnce(&mut String)) -> String {
+    let mut out = format!("{tag}{} ", dimension_tag::<P>());
+    write(&mut out);
+    out
+}
+
+impl<P: WktPoint> ToWkt for Contour<P> {
+    fn to_wkt(&self) -> String {
+        tagged::<P>("LINESTRING", |out| {
+            write_points(out, self.iter_points(), self.is_closed())
+        })
+    }
+}
+
+impl<P: WktPoint> ToWkt for ClosedContour<P> {
+    fn to_wkt(&self) -> String {
+        tagged::<P>("LINESTRING", |out| {
+            write_points(out, self.iter_points(), true)
+        })
+    }
+}
+
+impl<P: WktPoint> ToWkt for Polygon<P> {
+    fn to_wkt(&self) -> String {
+        tagged::<P>("POLYGON", |out| write_polygon_body(out, self))
+    }
+}
+
+impl<P: WktPoint> ToWkt for MultiPoint<P> {
+    fn to_wkt(&self) -> String {
+        tagged::<P>("MULTIPOINT", |out| {
+            write_list(out, self.iter_points(), |out, point| {
+                out.push('(');
+                write_point(out, point);
+                out.push(')');
+            })
+        })
+    }
+}
+
+impl<P: WktPoint> ToWkt for MultiContour<P> {
+    fn to_wkt(&self) -> String {
+        tagged::<P>("MULTILINESTRING", |out| {
+            write_list(out, self.contours(), |out, contour| {
+                write_points(out, contour.iter_points(), contour.is_closed())
+            })
+        })
+    }
+}
+
+impl<P: WktPoint> ToWkt for MultiPolygon<P> {
+    fn to_wkt(&self) -> String {
+        tagged::<P>("MULTIPOLYGON", |out| {
+            write_list(out, self.polygons(), write_polygon_body)
+        })
+    }
+}
+
+impl<P: WktPoint> ToWkt for Geom<P> {
+    fn to_wkt(&self) -> String {
+        match self {
+            Geom::Point(point) => tagged::<P>("POINT", |out| {
+                out.push('(');
+                write_point(out, point);
+                out.push(')');
+            }),
+            Geom::MultiPoint(points) => points.to_wkt(),
+            Geom::Contour(contour) => contour.to_wkt(),
+            Geom::MultiContour(contours) => contours.to_wkt(),
+            Geom::Polygon(polygon) => polygon.to_wkt(),
+            Geom::MultiPolygon(polygons) => polygons.to_wkt(),
+        }
+    }
+}
+
+impl<P: WktPoint> FromWkt for Geom<P> {
+    fn from_wkt(wkt: &str) -> Result<Self, GalileoTypesError> {
+        let wkt = wkt.trim();
+        let wkt = match wkt.get(..5) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("SRID=") => wkt
+                .split_once(';')
+                .map(|(_, wkt)| wkt)
+                .ok_or_else(|| wkt_error("SRID prefix without geometry"))?,
+            _ => wkt,
+        };
+
+        let mut parser = WktParser {
+            chars: wkt.chars().peekable(),
+            has_z: false,
+            has_m: false,
+        };
+        let geometry = parser.geometry()?;
+        parser.skip_whitespace();
+        if parser.chars.next().is_some() {
+            return Err(wkt_error("unexpected characters after the geometry"));
+        }
+
+        Ok(geometry)
+    }
+}
+
+macro_rules! impl_from_wkt {
+    ($geom:ident, $variant:ident, $name:literal) => {
+        impl<P: WktPoint> FromWkt for $geom<P> {
+            fn from_wkt(wkt: &str) -> Result<Self, GalileoTypesError> {
+                match Geom::from_wkt(wkt)? {
+                    Geom::$variant(geometry) => Ok(geometry),
+                    _ => Err(wkt_error(concat!("expected ", $name))),
+                }
+            }
+        }
+    };
+}
+
+impl_from_wkt!(Contour, Contour, "LINESTRING");
+impl_from_wkt!(Polygon, Polygon, "POLYGON");
+impl_from_wkt!(MultiPoint, MultiPoint, "MULTIPOINT");
+impl_from_wkt!(MultiContour, MultiContour, "MULTILINESTRING");
+impl_from_wkt!(MultiPolygon, MultiPolygon, "MULTIPOLYGON");
+
+/// Removes the last point of a ring if it repeats the first one.
+pub(crate) fn ring<P: WktPoint>(mut points: Vec<P>) -> ClosedContour<P> {
+    if points.len() > 1
+        && points.first().map(WktPoint::coordinates) == points.last().map(WktPoint::coordinates)
+    {
+        points.pop();
+    }
+    ClosedContour::new(points)
+}
+
+struct WktParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    has_z: bool,
+    has_m: bool,
+}
+
+impl WktParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+    }
+
+    fn word(&mut self) -> String {
+        self.skip_whitespace();
+        let mut word = String::new();
+        while let Some(ch) = self.chars.next_if(|ch| ch.is_ascii_alphabetic()) {
+            word.push(ch.to_ascii_uppercase());
+        }
+        word
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), GalileoTypesError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(ch) if ch == expected => Ok(()),
+            Some(ch) => Err(wkt_error(format!("expected '{expected}', found '{ch}'"))),
+            None => Err(wkt_error(format!("expected '{expected}'"))),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().copied()
+    }
+
+    fn geometry<P: WktPoint>(&mut self) -> Result<Geom<P>, GalileoTypesError> {
+        let name = self.word();
+
+        // Dimension modifiers are `Z`, `M` and `ZM`, so a word starting with `E` can only be `EMPTY`, which is
+        // handled when the coordinates are read.
+        if self
+            .peek()
+            .is_some_and(|ch| ch.is_ascii_alphabetic() && ch != 'E')
+        {
+            match self.word().as_str() {
+                "Z" => self.has_z = true,
+                "M" => self.has_m = true,
+                "ZM" => {
+                    self.has_z = true;
+                    self.has_m = true;
+                }
+                modifier => return Err(wkt_error(format!("unknown modifier {modifier}"))),
+            }
+        }
+
+        Ok(match name.as_str() {
+            "POINT" => {
+                if self.peek() == Some('E') {
+                    return Err(wkt_error("empty points are not supported"));
+                }
+                self.expect('(')?;
+                let point = self.point()?;
+                self.expect(')')?;
+                Geom::Point(point)
+            }
+            "LINESTRING" => Geom::Contour(Contour::open(self.list(Self::point)?)),
+            "POLYGON" => Geom::Polygon(self.polygon()?),
+            "MULTIPOINT" => Geom::MultiPoint(MultiPoint::from(self.list(|parser| {
+                if parser.peek() == Some('(') {
+                    parser.expect('(')?;
+                    let point = parser.point()?;
+                    parser.expect(')')?;
+                    Ok(point)
+                } else {
+                    parser.point()
+                }
+            })?)),
+            "MULTILINESTRING" => Geom::MultiContour(MultiContour::from(
+                self.list(|parser| Ok(Contour::open(parser.list(Self::point)?)))?,
+            )),
+            "MULTIPOLYGON" => Geom::MultiPolygon(MultiPolygon::from(self.list(Self::polygon)?)),
+            "" => return Err(wkt_error("expected geometry type")),
+            _ => return Err(wkt_error(format!("unsupported geometry type {name}"))),
+        })
+    }
+
+    /// Reads a comma separated list in parentheses, or `EMPTY`.
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, GalileoTypesError>,
+    ) -> Result<Vec<T>, GalileoTypesError> {
+        if self.peek() == Some('E') {
+            return match self.word().as_str() {
+                "EMPTY" => Ok(vec![]),
+                word => Err(wkt_error(format!("unexpected {word}"))),
+            };
+        }
+
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        while self.peek() == Some(',') {
+            self.chars.next();
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Ok(items)
+    }
+
+    fn polygon<P: WktPoint>(&mut self) -> Result<Polygon<P>, GalileoTypesError> {
+        let mut rings = self
+            .list(|parser| parser.list(Self::point))?
+            .into_iter()
+            .map(ring);
+        let outer = rings.next().unwrap_or_else(|| ClosedContour::new(vec![]));
+        Ok(Polygon::new(outer, rings.collect()))
+    }
+
+    fn point<P: WktPoint>(&mut self) -> Result<P, GalileoTypesError> {
+        let mut values = vec![];
+        while let Some(ch) = self.peek() {
+            if !(ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E')) {
+                break;
+            }
+            let mut number = String::new();
+            while let Some(ch) = self
+                .chars
+                .next_if(|ch| ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E'))
+            {
+                number.push(ch);
+            }
+            values.push(
+                number
+                    .parse::<f64>()
+                    .map_err(|_| wkt_error(format!("invalid number {number}")))?,
+            );
+        }
+
+        // Without a modifier, the number of values defines the dimensions.
+        let has_z = self.has_z || (!self.has_m && values.len() > 2);
+        match values[..] {
+            [x, y] if !has_z => Ok(P::from_coordinates(x, y, None)),
+            [x, y, z] if has_z && !self.has_m => Ok(P::from_coordinates(x, y, Some(z))),
+            [x, y, _m] if !has_z && self.has_m => Ok(P::from_coordinates(x, y, None)),
+            [x, y, z, _m] if has_z => Ok(P::from_coordinates(x, y, Some(z))),
+            _ => Err(wkt_error(format!(
+                "invalid number of coordinates: {}",
+                values.len()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_geometries() {
+        let point = Geom::Point(Point2d::new(1.0, 2.5));
+        assert_eq!(point.to_wkt(), "POINT (1 2.5)");
+
+        let point = Geom::Point(Point3d::new(1.0, 2.0, 3.0));
+        assert_eq!(point.to_wkt(), "POINT Z (1 2 3)");
+
+        let polygon = Polygon::new(
+            ClosedContour::new(vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(1.0, 0.0),
+                Point2d::new(1.0, 1.0),
+            ]),
+            vec![],
+        );
+        assert_eq!(polygon.to_wkt(), "POLYGON ((0 0, 1 0, 1 1, 0 0))");
+
+        let points = MultiPoint::from(vec![GeoPoint2d::latlon(10.0, 20.0)]);
+        assert_eq!(points.to_wkt(), "MULTIPOINT ((20 10))");
+
+        let contours = MultiContour::<Point2d>::from(vec![]);
+        assert_eq!(contours.to_wkt(), "MULTILINESTRING EMPTY");
+    }
+
+    #[test]
+    fn reads_geometries() {
+        let polygon = Polygon::<Point2d>::from_wkt(
+            "polygon((0 0, 10 0, 10 10, 0 10, 0 0), (1 1, 2 1, 2 2, 1 1))",
+        )
+        .unwrap();
+        assert_eq!(polygon.outer_contour.points.len(), 4);
+        assert_eq!(polygon.inner_contours.len(), 1);
+
+        let points = Geom::<Point2d>::from_wkt("MULTIPOINT (1 2, 3 4)").unwrap();
+        assert_eq!(
+            points,
+            Geom::<Point2d>::from_wkt("MULTIPOINT ((1 2), (3 4))").unwrap()
+        );
+
+        let polygons = MultiPolygon::<Point2d>::from_wkt(
+            "SRID=3857;MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), ((5 5, 6 5, 6 6, 5 5)))",
+        )
+        .unwrap();
+        assert_eq!(polygons.parts().len(), 2);
+
+        let point = Geom::<GeoPoint2d>::from_wkt("POINT (20 10)").unwrap();
+        assert_eq!(point, Geom::Point(GeoPoint2d::latlon(10.0, 20.0)));
+
+        assert_eq!(
+            Contour::<Point2d>::from_wkt("LINESTRING EMPTY").unwrap(),
+            Contour::open(vec![])
+        );
+    }
+
+    #[test]
+    fn reads_z_and_m_coordinates() {
+        let point = Geom::<Point3d>::from_wkt("POINT Z (1 2 3)").unwrap();
+        assert_eq!(point, Geom::Point(Point3d::new(1.0, 2.0, 3.0)));
+
+        let point = Geom::<Point3d>::from_wkt("POINT (1 2 3)").unwrap();
+        assert_eq!(point, Geom::Point(Point3d::new(1.0, 2.0, 3.0)));
+
+        let point = Geom::<Point3d>::from_wkt("POINT M (1 2 3)").unwrap();
+        assert_eq!(point, Geom::Point(Point3d::new(1.0, 2.0, 0.0)));
+
+        let point = Geom::<Point2d>::from_wkt("POINT ZM (1 2 3 4)").unwrap();
+        assert_eq!(point, Geom::Point(Point2d::new(1.0, 2.0)));
+    }
+
+    #[test]
+    fn roundtrip() {
+        for wkt in [
+            "LINESTRING (1 2, 4 5)",
+            "MULTILINESTRING ((1 2, 3 4), (5 6, 7 8))",
+            "MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0), (0.2 0.2, 0.4 0.2, 0.4 0.4, 0.2 0.2)))",
+        ] {
+            assert_eq!(Geom::<Point2d>::from_wkt(wkt).unwrap().to_wkt(), wkt);
+        }
+
+        let wkt = "LINESTRING Z (1 2 3, 4 5 -6.5)";
+        assert_eq!(Geom::<Point3d>::from_wkt(wkt).unwrap().to_wkt(), wkt);
+    }
+
+    #[test]
+    fn invalid_wkt_is_an_error() {
+        assert!(Geom::<Point2d>::from_wkt("POINT (1)").is_err());
+        assert!(Geom::<Point2d>::from_wkt("POINT EMPTY").is_err());
+        assert!(Geom::<Point2d>::from_wkt("POINT (1 2").is_err());
+        assert!(Geom::<Point2d>::from_wkt("POINT (1 2) extra").is_err());
+        assert!(Geom::<Point2d>::from_wkt("GEOMETRYCOLLECTION (POINT (1 2))").is_err());
+        assert!(Contour::<Point2d>::from_wkt("POINT (1 2)").is_err());
+    }
+}
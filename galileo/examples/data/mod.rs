@@ -1,8 +1,7 @@
 use galileo::layer::feature_layer::Feature;
 use galileo::Color;
 use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
-use galileo_types::geo::impls::GeoPoint2d;
-use galileo_types::geo::{GeoPoint, NewGeoPoint, Projection};
+use galileo_types::geo::Projection;
 use galileo_types::geometry::{CartesianGeometry2d, Geom, Geometry};
 use galileo_types::impls::{MultiPolygon, Polygon};
 use serde::{Deserialize, Deserializer, Serialize};
@@ -63,42 +62,3 @@ impl CartesianGeometry2d<Point2d> for Country {
         Some(self.bbox)
     }
 }
-
-#[derive(Debug, Deserialize)]
-pub struct City {
-    lat: f64,
-    lng: f64,
-    pub capital: String,
-    pub population: f64,
-}
-
-impl Feature for City {
-    type Geom = Self;
-
-    fn geometry(&self) -> &Self::Geom {
-        self
-    }
-}
-
-impl GeoPoint for City {
-    type Num = f64;
-
-    fn lat(&self) -> Self::Num {
-        self.lat
-    }
-
-    fn lon(&self) -> Self::Num {
-        self.lng
-    }
-}
-
-impl Geometry for City {
-    type Point = GeoPoint2d;
-
-    fn project<P: Projection<InPoint = Self::Point> + ?Sized>(
-        &self,
-        projection: &P,
-    ) -> Option<Geom<P::OutPoint>> {
-        GeoPoint2d::latlon(self.lat, self.lng).project(projection)
-    }
-}
@@ -1,15 +1,17 @@
-use data::{City, Country};
+use data::Country;
 use galileo::control::{EventPropagation, MouseButton, UserEvent};
 use galileo::layer::feature_layer::symbol::{SimplePolygonSymbol, Symbol};
-use galileo::layer::feature_layer::FeatureLayer;
+use galileo::layer::feature_layer::{Feature, FeatureLayer};
 use galileo::render::point_paint::PointPaint;
 use galileo::render::render_bundle::RenderPrimitive;
 use galileo::{Color, MapBuilder};
-use galileo_types::cartesian::CartesianPoint3d;
-use galileo_types::geo::Crs;
-use galileo_types::geometry::Geom;
+use galileo_types::cartesian::{CartesianPoint3d, NewCartesianPoint3d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, GeoPoint, NewGeoPoint, Projection};
+use galileo_types::geometry::{Geom, Geometry};
 use galileo_types::impls::{Contour, Polygon};
-use num_traits::AsPrimitive;
+use num_traits::{AsPrimitive, Float};
+use serde::Deserialize;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 
@@ -53,7 +55,7 @@ pub async fn run(builder: MapBuilder) {
         countries,
         CountrySymbol {},
         Crs::EPSG3857,
-        &vec![8000.0, 1000.0, 1.0],
+        &[8000.0, 1000.0, 1.0],
     );
     let feature_layer = Arc::new(RwLock::new(feature_layer));
 
@@ -154,8 +156,8 @@ impl Symbol<Country> for CountrySymbol {
         min_resolution: f64,
     ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
     where
-        N: AsPrimitive<f32>,
-        P: CartesianPoint3d<Num = N> + Clone,
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
     {
         self.get_polygon_symbol(feature)
             .render(&(), geometry, min_resolution)
@@ -181,7 +183,7 @@ impl Symbol<City> for CitySymbol {
             return primitives;
         };
 
-        let _ = match &feature.capital[..] {
+        match &feature.capital[..] {
             "primary" => {
                 primitives.push(RenderPrimitive::new_point_ref(
                     point,
@@ -233,3 +235,42 @@ impl Symbol<City> for CitySymbol {
         primitives
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct City {
+    lat: f64,
+    lng: f64,
+    capital: String,
+    population: f64,
+}
+
+impl Feature for City {
+    type Geom = Self;
+
+    fn geometry(&self) -> &Self::Geom {
+        self
+    }
+}
+
+impl GeoPoint for City {
+    type Num = f64;
+
+    fn lat(&self) -> Self::Num {
+        self.lat
+    }
+
+    fn lon(&self) -> Self::Num {
+        self.lng
+    }
+}
+
+impl Geometry for City {
+    type Point = GeoPoint2d;
+
+    fn project<P: Projection<InPoint = Self::Point> + ?Sized>(
+        &self,
+        projection: &P,
+    ) -> Option<Geom<P::OutPoint>> {
+        GeoPoint2d::latlon(self.lat, self.lng).project(projection)
+    }
+}
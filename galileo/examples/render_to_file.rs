@@ -1,11 +1,14 @@
 //! This example shows how to render a map to an image file without creating a window.
 //!
-//! Run this example with one argument - path to a `.geojson` file to plot. Running it will create
-//! a file `output_map.png` with the plotted GEOJSON with OSM background.
+//! Run this example with one argument - path to a `.geojson` file to plot. The file can also be
+//! compressed with gzip or zip. Running it will create a file `output_map.png` with the plotted
+//! GEOJSON with OSM background.
 //!
 //! ```shell
-//! cargo run --example render_to_file --features geojson -- "./galileo/examples/data/Museums 2021.geojson"
+//! cargo run --example render_to_file --features geojson,blocking -- "./galileo/examples/data/Museums 2021.geojson"
 //! ```
+//!
+//! The example uses blocking versions of the async methods, so it does not need an async runtime.
 
 use anyhow::{anyhow, Result};
 use galileo::layer::data_provider::{FileCacheController, UrlImageProvider};
@@ -16,14 +19,12 @@ use galileo::tile_scheme::TileIndex;
 use galileo::{DummyMessenger, Map, MapView, Messenger, TileSchema};
 use galileo_types::cartesian::Size;
 use galileo_types::geo::Crs;
-use geojson::{FeatureCollection, GeoJson};
 use image::{ImageBuffer, Rgba};
 use std::sync::Arc;
 use std::time::Duration;
 
 #[cfg(not(target_arch = "wasm32"))]
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
     if std::env::args().count() != 2 {
@@ -32,10 +33,8 @@ async fn main() -> Result<()> {
         ));
     }
 
-    let file_name = std::env::args().skip(1).next().unwrap();
-    let json = &std::fs::read_to_string(file_name)?;
-    let geojson = json.parse::<GeoJson>()?;
-    let collection = FeatureCollection::try_from(geojson)?;
+    let file_name = std::env::args().nth(1).unwrap();
+    let collection = galileo::io::read_geojson(file_name)?;
 
     // We can give GEOJSON features directly to a feature layer, as `geo-json` feature provides
     // implementation of `Feature` trait for GEOJSON features and of `Geometry` trait for
@@ -57,8 +56,9 @@ async fn main() -> Result<()> {
 
     let width_resolution = extent.width() / image_size.width() as f64;
     let height_resolution = extent.height() / image_size.height() as f64;
-    let resolution = (width_resolution.max(height_resolution) * 1.1)
-        .max(TileSchema::web(18).lod_resolution(17).unwrap());
+    let resolution = TileSchema::web(18)
+        .clamp_resolution(width_resolution.max(height_resolution) * 1.1)
+        .unwrap();
 
     // Create OSM layer for background
     let cache_controller = FileCacheController::new(".tile_cache");
@@ -84,7 +84,7 @@ async fn main() -> Result<()> {
     let map_view = MapView::new_projected(&center, resolution).with_size(image_size.cast());
 
     // Load all tiles required for the given view before we request rendering.
-    osm.load_tiles(&map_view).await;
+    osm.load_tiles_blocking(&map_view);
 
     let map = Map::new(
         map_view,
@@ -95,10 +95,10 @@ async fn main() -> Result<()> {
     // We create a renderer without window, so it will use internal texture to render to.
     // Every time the `render` method is callled, the image is updated and can be retrieved
     // by the `get_image` method.
-    let renderer = WgpuRenderer::new_with_texture_rt(image_size).await.unwrap();
+    let renderer = WgpuRenderer::new_with_texture_rt_blocking(image_size).unwrap();
     renderer.render(&map).unwrap();
 
-    let bitmap = renderer.get_image().await.unwrap();
+    let bitmap = renderer.get_image_blocking().unwrap();
     let buffer =
         ImageBuffer::<Rgba<u8>, _>::from_raw(image_size.width(), image_size.height(), bitmap)
             .unwrap();
@@ -101,5 +101,6 @@ pub fn tile_scheme() -> TileSchema {
         tile_height: 1024,
         y_direction: VerticalDirection::TopToBottom,
         crs: Crs::EPSG3857,
+        matrices: vec![],
     }
 }
@@ -0,0 +1,351 @@
+//! Animations of feature geometries and styles.
+//!
+//! An [`Animation`] is added to the map with [`Map::add_animation`](crate::Map::add_animation) and is updated by
+//! [`Map::animate`](crate::Map::animate) before every frame, together with the animations of the map view. The value
+//! of an animation changes over time according to a [`Tween`], and a [`FeatureTween`] applies the value to a feature of
+//! a [`FeatureLayer`], e.g. to make a marker pulse or to move a vehicle along its track.
+//!
+//! ```
+//! use galileo::animation::{FeatureTween, Tween, TweenRepeat};
+//! use galileo::layer::FeatureLayer;
+//! use galileo::symbol::CirclePointSymbol;
+//! use galileo::{Color, Easing, Map, MapView};
+//! use galileo_types::geo::Crs;
+//! use galileo_types::latlon;
+//! use std::sync::{Arc, RwLock};
+//! use std::time::Duration;
+//!
+//! let layer = Arc::new(RwLock::new(FeatureLayer::new(
+//!     vec![latlon!(52.52, 13.40)],
+//!     CirclePointSymbol::new(Color::RED, 6.0),
+//!     Crs::WGS84,
+//! )));
+//! let mut map = Map::new(MapView::new(&latlon!(52.52, 13.40), 10.0), vec![], None::<galileo::DummyMessenger>);
+//! map.add_layer(layer.clone());
+//!
+//! // Move the point back and forth forever.
+//! let tween = Tween::new(latlon!(52.52, 13.40), latlon!(52.53, 13.42), Duration::from_secs(2))
+//!     .with_easing(Easing::EaseInOut)
+//!     .with_repeat(TweenRepeat::PingPong);
+//! map.add_animation(FeatureTween::geometry(layer, 0, tween, |point, position| *point = *position));
+//! ```
+
+use crate::layer::feature_layer::symbol::{Interpolate, Symbol};
+use crate::layer::feature_layer::Feature;
+use crate::layer::FeatureLayer;
+use crate::map::Easing;
+use galileo_types::geo::NewGeoPoint;
+use galileo_types::geometry::Geometry;
+use galileo_types::geometry_type::GeoSpace2d;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::sync::{Arc, RwLock};
+use web_time::{Duration, SystemTime};
+
+/// Animation updated by the map before every frame.
+pub trait Animation: MaybeSend + MaybeSync {
+    /// Updates the animated objects to their state at the `time`. Returns `false` when the animation has finished
+    /// and can be removed from the map.
+    fn update(&mut self, time: SystemTime) -> bool;
+}
+
+impl<T> Animation for T
+where
+    T: FnMut(SystemTime) -> bool + MaybeSend + MaybeSync,
+{
+    fn update(&mut self, time: SystemTime) -> bool {
+        self(time)
+    }
+}
+
+/// Id of an animation returned by [`Map::add_animation`](crate::Map::add_animation).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct AnimationId(pub(crate) u64);
+
+/// What a [`Tween`] does after it reaches the end value.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TweenRepeat {
+    /// The tween stops at the end value.
+    #[default]
+    Once,
+    /// The tween starts again from the start value.
+    Loop,
+    /// The tween goes back to the start value, and then forward again.
+    PingPong,
+}
+
+/// Change of a value from one value to another during some time.
+///
+/// The tween starts when it is created, or at the time set with [`Tween::with_start_time`]. Before the start it has
+/// the start value.
+#[derive(Debug, Clone)]
+pub struct Tween<T> {
+    from: T,
+    to: T,
+    start_time: SystemTime,
+    duration: Duration,
+    easing: Easing,
+    repeat: TweenRepeat,
+}
+
+impl<T: Interpolate> Tween<T> {
+    /// Creates a new linear tween from `from` to `to`, that starts now and runs once during the `duration`.
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            start_time: SystemTime::now(),
+            duration,
+            easing: Easing::Linear,
+            repeat: TweenRepeat::Once,
+        }
+    }
+
+    /// Sets the easing curve of the tween. With [`TweenRepeat::PingPong`] the curve is applied in both directions.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Sets what the tween does after it reaches the end value.
+    pub fn with_repeat(mut self, repeat: TweenRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Sets the time the tween starts at.
+    pub fn with_start_time(mut self, start_time: SystemTime) -> Self {
+        self.start_time = start_time;
+        self
+    }
+
+    /// Start value.
+    pub fn from(&self) -> &T {
+        &self.from
+    }
+
+    /// End value.
+    pub fn to(&self) -> &T {
+        &self.to
+    }
+
+    /// Duration of one run of the tween.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns `true` if the tween has reached the end value at the `time` and will not change anymore.
+    pub fn is_finished(&self, time: SystemTime) -> bool {
+        self.repeat == TweenRepeat::Once && self.elapsed(time) >= self.duration.as_secs_f64()
+    }
+
+    /// Value of the tween at the `time`.
+    pub fn value(&self, time: SystemTime) -> T {
+        let duration = self.duration.as_secs_f64();
+        if duration <= 0.0 {
+            return self.to.clone();
+        }
+
+        let runs = self.elapsed(time) / duration;
+        let t = match self.repeat {
+            TweenRepeat::Once => runs.min(1.0),
+            TweenRepeat::Loop => runs.fract(),
+            TweenRepeat::PingPong => {
+                let t = runs % 2.0;
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+        };
+
+        self.from.interpolate(&self.to, self.easing.apply(t))
+    }
+
+    fn elapsed(&self, time: SystemTime) -> f64 {
+        time.duration_since(self.start_time)
+            .unwrap_or_default()
+            .as_secs_f64()
+    }
+}
+
+/// Animation that applies the value of a [`Tween`] to a feature of a [`FeatureLayer`].
+///
+/// The value is applied either to the geometry of the feature ([`FeatureTween::geometry`]) or only to the properties
+/// that change the style of the feature ([`FeatureTween::style`]), e.g. the size or the color the symbol of the layer
+/// draws the feature with. Style changes are cheaper, since the geometry of the feature is not processed again.
+///
+/// The animation finishes when the tween does, or when the feature no longer exists.
+pub struct FeatureTween<P, F, S, T>
+where
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    layer: Arc<RwLock<FeatureLayer<P, F, S, GeoSpace2d>>>,
+    index: usize,
+    tween: Tween<T>,
+    apply: fn(&mut F, &T),
+    is_style: bool,
+}
+
+impl<P, F, S, T> FeatureTween<P, F, S, T>
+where
+    P: NewGeoPoint + 'static,
+    F: Feature + MaybeSend + MaybeSync + 'static,
+    F::Geom: Geometry<Point = P>,
+    S: Symbol<F> + MaybeSend + MaybeSync + 'static,
+    T: Interpolate + MaybeSend + MaybeSync,
+{
+    /// Creates an animation changing the geometry of the feature with the `index` in the `layer` by calling `apply`
+    /// with the feature and the current value of the tween.
+    pub fn geometry(
+        layer: Arc<RwLock<FeatureLayer<P, F, S, GeoSpace2d>>>,
+        index: usize,
+        tween: Tween<T>,
+        apply: fn(&mut F, &T),
+    ) -> Self {
+        Self {
+            layer,
+            index,
+            tween,
+            apply,
+            is_style: false,
+        }
+    }
+
+    /// Creates an animation changing the style of the feature with the `index` in the `layer` by calling `apply` with
+    /// the feature and the current value of the tween. The `apply` function must not change the geometry of the
+    /// feature.
+    pub fn style(
+        layer: Arc<RwLock<FeatureLayer<P, F, S, GeoSpace2d>>>,
+        index: usize,
+        tween: Tween<T>,
+        apply: fn(&mut F, &T),
+    ) -> Self {
+        Self {
+            is_style: true,
+            ..Self::geometry(layer, index, tween, apply)
+        }
+    }
+
+    /// The tween of the animation.
+    pub fn tween(&self) -> &Tween<T> {
+        &self.tween
+    }
+}
+
+impl<P, F, S, T> Animation for FeatureTween<P, F, S, T>
+where
+    P: NewGeoPoint + 'static,
+    F: Feature + MaybeSend + MaybeSync + 'static,
+    F::Geom: Geometry<Point = P>,
+    S: Symbol<F> + MaybeSend + MaybeSync + 'static,
+    T: Interpolate + MaybeSend + MaybeSync,
+{
+    fn update(&mut self, time: SystemTime) -> bool {
+        let value = self.tween.value(time);
+        let mut layer = self.layer.write().expect("lock is poisoned");
+        let exists = if self.is_style {
+            match layer.features_mut().get_mut(self.index) {
+                Some(feature) => {
+                    (self.apply)(feature.edit_style(), &value);
+                    true
+                }
+                None => false,
+            }
+        } else {
+            layer.update_feature(self.index, |feature| (self.apply)(feature, &value))
+        };
+
+        exists && !self.tween.is_finished(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::CirclePointSymbol;
+    use crate::Color;
+    use galileo_types::geo::impls::GeoPoint2d;
+    use galileo_types::geo::Crs;
+    use galileo_types::latlon;
+
+    fn at(tween: &Tween<f64>, seconds: f64) -> f64 {
+        tween.value(tween.start_time + Duration::from_secs_f64(seconds))
+    }
+
+    #[test]
+    fn tween_values() {
+        let tween = Tween::new(0.0, 10.0, Duration::from_secs(2));
+        assert_eq!(at(&tween, 0.0), 0.0);
+        assert_eq!(at(&tween, 1.0), 5.0);
+        assert_eq!(at(&tween, 3.0), 10.0);
+        assert!(tween.is_finished(tween.start_time + Duration::from_secs(2)));
+        assert_eq!(tween.value(tween.start_time - Duration::from_secs(1)), 0.0);
+
+        let looped = tween.clone().with_repeat(TweenRepeat::Loop);
+        assert_eq!(at(&looped, 2.5), 2.5);
+        assert!(!looped.is_finished(looped.start_time + Duration::from_secs(100)));
+
+        let ping_pong = tween
+            .with_repeat(TweenRepeat::PingPong)
+            .with_easing(Easing::EaseIn);
+        assert_eq!(at(&ping_pong, 1.0), 1.25);
+        assert_eq!(at(&ping_pong, 3.0), 1.25);
+        assert_eq!(at(&ping_pong, 4.0), 0.0);
+    }
+
+    struct Marker {
+        position: GeoPoint2d,
+        size: f64,
+    }
+
+    impl Feature for Marker {
+        type Geom = GeoPoint2d;
+
+        fn geometry(&self) -> &Self::Geom {
+            &self.position
+        }
+    }
+
+    #[test]
+    fn feature_tweens_update_features() {
+        let layer = Arc::new(RwLock::new(FeatureLayer::new(
+            vec![Marker {
+                position: latlon!(0.0, 0.0),
+                size: 1.0,
+            }],
+            CirclePointSymbol::new(Color::RED, 4.0),
+            Crs::WGS84,
+        )));
+        let start = SystemTime::now();
+
+        let size = Tween::new(1.0, 3.0, Duration::from_secs(1)).with_start_time(start);
+        let mut pulse = FeatureTween::style(layer.clone(), 0, size, |marker: &mut Marker, size| {
+            marker.size = *size
+        });
+        let position = Tween::new(
+            latlon!(0.0, 0.0),
+            latlon!(10.0, 20.0),
+            Duration::from_secs(2),
+        )
+        .with_start_time(start);
+        let mut movement =
+            FeatureTween::geometry(layer.clone(), 0, position, |marker, position| {
+                marker.position = *position
+            });
+
+        assert!(pulse.update(start + Duration::from_millis(500)));
+        assert!(movement.update(start + Duration::from_millis(500)));
+        {
+            let layer = layer.read().unwrap();
+            let marker = layer.features().get(0).unwrap();
+            assert_eq!(marker.size, 2.0);
+            assert_eq!(marker.position, latlon!(2.5, 5.0));
+        }
+
+        assert!(!pulse.update(start + Duration::from_secs(1)));
+        layer.write().unwrap().remove_feature(0);
+        assert!(!movement.update(start + Duration::from_millis(1500)));
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(not(target_arch = "wasm32"))]
 use maybe_sync::MaybeSend;
 use std::future::Future;
+use web_time::Duration;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub fn spawn<T>(future: T)
@@ -21,3 +22,35 @@ where
         future.await;
     });
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: Duration) {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let global = js_sys::global();
+        let set_timeout = js_sys::Reflect::get(&global, &"setTimeout".into())
+            .ok()
+            .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+
+        let result = match set_timeout {
+            Some(set_timeout) => {
+                set_timeout.call2(&global, &resolve, &(duration.as_millis() as f64).into())
+            }
+            None => resolve.call0(&JsValue::NULL),
+        };
+
+        if let Err(err) = result {
+            log::warn!("Failed to set timeout: {err:?}");
+        }
+    });
+
+    if let Err(err) = wasm_bindgen_futures::JsFuture::from(promise).await {
+        log::warn!("Timeout promise failed: {err:?}");
+    }
+}
@@ -1,10 +1,13 @@
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// Color representation.
+///
+/// With `serde` feature the color is serialized as a HEX8 string (`#RRGGBBAA`) and can be deserialized from both HEX6
+/// (`#RRGGBB`) and HEX8 strings. Deserialization of an invalid string fails.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(into = "String"))]
 pub struct Color {
     r: u8,
     g: u8,
@@ -18,6 +21,18 @@ impl From<String> for Color {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Self::try_from_hex(&hex).ok_or_else(|| {
+            serde::de::Error::custom(format!(
+                "invalid color '{hex}', expected #RRGGBB or #RRGGBBAA hex string"
+            ))
+        })
+    }
+}
+
 impl From<Color> for String {
     fn from(val: Color) -> Self {
         val.to_hex()
@@ -65,7 +80,10 @@ impl Color {
 
     /// Parses a color from the hex string. Hex string can be either HEX6 (`#RRGGBB`) or HEX8 (`#RRGGBBAA`).
     pub fn try_from_hex(hex_string: &str) -> Option<Self> {
-        if hex_string.len() != 7 && hex_string.len() != 9 || hex_string.chars().next()? != '#' {
+        if hex_string.len() != 7 && hex_string.len() != 9
+            || !hex_string.starts_with('#')
+            || !hex_string[1..].bytes().all(|b| b.is_ascii_hexdigit())
+        {
             return None;
         }
 
@@ -142,6 +160,32 @@ mod tests {
         let color = Color::try_from_hex(hex).unwrap();
         assert_eq!(&color.to_hex(), hex);
 
-        assert_eq!(Color::from_hex(&hex), color);
+        assert_eq!(Color::from_hex(hex), color);
+    }
+
+    #[test]
+    fn try_from_hex_rejects_invalid_strings() {
+        assert_eq!(
+            Color::try_from_hex("#ff1000"),
+            Some(Color::rgba(255, 16, 0, 255))
+        );
+        assert_eq!(Color::try_from_hex("ff1000"), None);
+        assert_eq!(Color::try_from_hex("#ff10"), None);
+        assert_eq!(Color::try_from_hex("#+f1000"), None);
+        assert_eq!(Color::try_from_hex("#ééé"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn color_deserialization() {
+        let color: Color = serde_json::from_str("\"#ff1000\"").unwrap();
+        assert_eq!(color, Color::rgba(255, 16, 0, 255));
+        assert_eq!(serde_json::to_string(&color).unwrap(), "\"#FF1000FF\"");
+
+        let color: Color = serde_json::from_str("\"#FF1000AA\"").unwrap();
+        assert_eq!(color, Color::rgba(255, 16, 0, 170));
+
+        let err = serde_json::from_str::<Color>("\"red\"").unwrap_err();
+        assert!(err.to_string().contains("invalid color 'red'"));
     }
 }
@@ -0,0 +1,972 @@
+//! [`GeometryEditor`] lets the user draw new geometries on the map and edit existing ones.
+
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::error::GalileoError;
+use crate::layer::Layer;
+use crate::map::Map;
+use crate::messenger::Messenger;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::{
+    Canvas, LineCap, LineJoin, LinePaint, PackedBundle, PolygonFill, PolygonPaint, RenderOptions,
+};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Point3d};
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use galileo_types::Contour as _;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::any::Any;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+mod snapping;
+
+pub use snapping::{Snap, SnapTarget, SnappingProvider};
+
+/// Default distance in pixels from a vertex handle at which the handle can be grabbed.
+const DEFAULT_TOLERANCE: f64 = 8.0;
+
+/// Kind of geometry drawn or edited by a [`GeometryEditor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryKind {
+    /// A single point.
+    Point,
+    /// An open line of at least two vertices.
+    Line,
+    /// A polygon with the outer ring and optional holes, every ring having at least three vertices.
+    Polygon,
+}
+
+impl GeometryKind {
+    fn min_vertices(self) -> usize {
+        match self {
+            GeometryKind::Point => 1,
+            GeometryKind::Line => 2,
+            GeometryKind::Polygon => 3,
+        }
+    }
+}
+
+/// Change of the geometry made by the user in a [`GeometryEditor`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditEvent {
+    /// Drawing of a new geometry was finished. The editor starts editing the created geometry.
+    Created(Geom<Point2d>),
+    /// A vertex of the edited geometry was moved, inserted or deleted.
+    Changed(Geom<Point2d>),
+}
+
+/// Style of the geometries drawn by a [`GeometryEditor`].
+#[derive(Debug, Clone, Copy)]
+pub struct EditorStyle {
+    /// Paint of the lines and outlines of polygons.
+    pub line: LinePaint,
+    /// Fill color of polygons.
+    pub fill: Color,
+    /// Fill color of the vertex handles.
+    pub vertex_color: Color,
+    /// Diameter of the vertex handles in pixels.
+    pub vertex_size: f32,
+    /// Fill color of the handles in the middle of segments. Dragging such a handle inserts a new vertex.
+    pub midpoint_color: Color,
+}
+
+impl Default for EditorStyle {
+    fn default() -> Self {
+        Self {
+            line: LinePaint {
+                color: Color::rgba(0, 120, 255, 255),
+                width: 2.0,
+                offset: 0.0,
+                line_cap: LineCap::Round,
+                line_join: LineJoin::Round,
+                dash: None,
+                arrows: None,
+            },
+            fill: Color::rgba(0, 120, 255, 60),
+            vertex_color: Color::WHITE,
+            vertex_size: 10.0,
+            midpoint_color: Color::rgba(255, 255, 255, 150),
+        }
+    }
+}
+
+/// Interactive editor of geometries, that lets the user digitize points, lines and polygons on the map, and modify
+/// their vertices.
+///
+/// The editor is both a [`Layer`] that draws the edited geometry with its vertex handles, and a
+/// [`UserEventHandler`] that changes the geometry in response to user input. Clones of an editor share the same
+/// state, so the same editor is added to the map as a layer and to the
+/// [`EventProcessor`](super::EventProcessor) as a handler. The handler should be added before the
+/// [`MapController`](super::MapController), so that dragging a vertex does not pan the map.
+///
+/// ```no_run
+/// use galileo::control::editor::{EditEvent, GeometryEditor, GeometryKind};
+/// use galileo::control::{EventProcessor, MapController};
+/// # use galileo::Map;
+/// # fn setup(map: &mut Map, event_processor: &mut EventProcessor) {
+///
+/// let editor = GeometryEditor::new().with_event_handler(|event: &EditEvent| match event {
+///     EditEvent::Created(geometry) => println!("created {geometry:?}"),
+///     EditEvent::Changed(geometry) => println!("changed {geometry:?}"),
+/// });
+///
+/// map.add_layer(editor.clone());
+/// event_processor.add_handler(editor.clone());
+/// event_processor.add_handler(MapController::default());
+///
+/// editor.draw(GeometryKind::Polygon);
+/// # }
+/// ```
+///
+/// # Drawing
+///
+/// After [`GeometryEditor::draw`] is called, every left click adds a vertex to the new geometry and a right click
+/// removes the last added vertex. A double click (or a call to [`GeometryEditor::finish_drawing`]) finishes the
+/// line or polygon, and a point is finished with the first click. When the geometry is finished, the
+/// [`EditEvent::Created`] event is emitted, and the editor starts editing the new geometry.
+///
+/// # Editing
+///
+/// While a geometry is edited (after drawing is finished or after [`GeometryEditor::edit`] is called), the user can
+/// drag its vertices, insert new vertices by dragging the handles in the middle of the segments, and delete vertices
+/// with a double click. The [`EditEvent::Changed`] event is emitted after every such change.
+///
+/// # Snapping
+///
+/// If a [`SnappingProvider`] is set with [`GeometryEditor::with_snapping`], new and dragged vertices are snapped to
+/// the vertices and edges of the geometries of the provider within the [tolerance](GeometryEditor::with_tolerance).
+///
+/// All coordinates are in the CRS of the map view.
+#[derive(Clone, Default)]
+pub struct GeometryEditor {
+    state: Arc<Mutex<EditorState>>,
+}
+
+type EventHandler = Arc<dyn Fn(&EditEvent) + MaybeSend + MaybeSync>;
+
+/// Index of the part (ring of a polygon) and index of the vertex in the part.
+type VertexId = (usize, usize);
+
+struct EditorState {
+    sketch: Sketch,
+    style: EditorStyle,
+    tolerance: f64,
+    event_handlers: Vec<EventHandler>,
+    snapping: Option<SnappingProvider>,
+    messenger: Option<Box<dyn Messenger>>,
+    /// Bundle with the drawn sketch and the resolution it was packed for.
+    packed: Option<(f64, Box<dyn PackedBundle>)>,
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        Self {
+            sketch: Sketch::Idle,
+            style: EditorStyle::default(),
+            tolerance: DEFAULT_TOLERANCE,
+            event_handlers: vec![],
+            snapping: None,
+            messenger: None,
+            packed: None,
+        }
+    }
+}
+
+enum Sketch {
+    Idle,
+    Drawing {
+        kind: GeometryKind,
+        vertices: Vec<Point2d>,
+        cursor: Option<Point2d>,
+    },
+    Editing {
+        kind: GeometryKind,
+        parts: Vec<Vec<Point2d>>,
+        drag: Option<VertexId>,
+    },
+}
+
+/// Handle of the edited geometry under the pointer.
+enum Handle {
+    Vertex(VertexId),
+    /// Middle of the segment starting at the given vertex.
+    Midpoint(VertexId),
+}
+
+impl GeometryEditor {
+    /// Creates a new editor that does not draw or edit anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the style of the edited geometries.
+    pub fn with_style(self, style: EditorStyle) -> Self {
+        let mut state = self.lock();
+        state.style = style;
+        state.invalidate();
+        drop(state);
+        self
+    }
+
+    /// Sets the distance in pixels from a vertex within which the vertex can be grabbed by the pointer. Default
+    /// value is 8 pixels.
+    pub fn with_tolerance(self, tolerance: f64) -> Self {
+        self.lock().tolerance = tolerance;
+        self
+    }
+
+    /// Sets the provider to snap new and dragged vertices to.
+    pub fn with_snapping(self, snapping: SnappingProvider) -> Self {
+        self.lock().snapping = Some(snapping);
+        self
+    }
+
+    /// Adds a handler that is called every time the user changes the geometry.
+    pub fn with_event_handler(
+        self,
+        handler: impl Fn(&EditEvent) + MaybeSend + MaybeSync + 'static,
+    ) -> Self {
+        self.lock().event_handlers.push(Arc::new(handler));
+        self
+    }
+
+    /// Starts drawing a new geometry of the given `kind`, discarding the currently drawn or edited geometry.
+    pub fn draw(&self, kind: GeometryKind) {
+        let mut state = self.lock();
+        state.sketch = Sketch::Drawing {
+            kind,
+            vertices: vec![],
+            cursor: None,
+        };
+        state.invalidate();
+    }
+
+    /// Starts editing the given `geometry`, discarding the currently drawn or edited geometry.
+    ///
+    /// Only points, open contours and polygons can be edited. An error is returned for other geometries.
+    pub fn edit(&self, geometry: Geom<Point2d>) -> Result<(), GalileoError> {
+        let (kind, parts) = match geometry {
+            Geom::Point(point) => (GeometryKind::Point, vec![vec![point]]),
+            Geom::Contour(contour) if !contour.is_closed() => (
+                GeometryKind::Line,
+                vec![contour.iter_points().copied().collect()],
+            ),
+            Geom::Polygon(polygon) => (
+                GeometryKind::Polygon,
+                std::iter::once(polygon.outer_contour)
+                    .chain(polygon.inner_contours)
+                    .map(|ring| ring_vertices(ring.points))
+                    .collect(),
+            ),
+            _ => {
+                return Err(GalileoError::Generic(
+                    "only points, open contours and polygons can be edited".into(),
+                ))
+            }
+        };
+
+        let mut state = self.lock();
+        state.sketch = Sketch::Editing {
+            kind,
+            parts,
+            drag: None,
+        };
+        state.invalidate();
+
+        Ok(())
+    }
+
+    /// Finishes drawing of the current geometry, as if the user double clicked.
+    ///
+    /// Returns false if the editor is not drawing, or if the drawn geometry does not have enough vertices yet.
+    pub fn finish_drawing(&self) -> bool {
+        let mut state = self.lock();
+        let Some(event) = state.sketch.finish_drawing() else {
+            return false;
+        };
+        state.invalidate();
+        drop(state);
+
+        self.emit(event);
+        true
+    }
+
+    /// Stops drawing or editing and clears the editor.
+    ///
+    /// Returns the edited geometry, or `None` if the editor was drawing a geometry that was not finished yet.
+    pub fn stop(&self) -> Option<Geom<Point2d>> {
+        let mut state = self.lock();
+        let geometry = state.sketch.geometry();
+        state.sketch = Sketch::Idle;
+        state.invalidate();
+
+        geometry
+    }
+
+    /// Geometry that is currently edited. Returns `None` if no geometry is edited, or if a new geometry is being
+    /// drawn.
+    pub fn geometry(&self) -> Option<Geom<Point2d>> {
+        self.lock().sketch.geometry()
+    }
+
+    /// Returns true if the editor is drawing a new geometry or editing an existing one.
+    pub fn is_active(&self) -> bool {
+        !matches!(self.lock().sketch, Sketch::Idle)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, EditorState> {
+        self.state.lock().expect("mutex is poisoned")
+    }
+
+    fn emit(&self, event: EditEvent) {
+        // Handlers are called without the lock, so that they can use the editor.
+        let handlers = self.lock().event_handlers.clone();
+        for handler in handlers {
+            handler(&event);
+        }
+    }
+}
+
+impl EditorState {
+    fn invalidate(&mut self) {
+        self.packed = None;
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+
+    fn handle(
+        &mut self,
+        event: &UserEvent,
+        view: &MapView,
+    ) -> (EventPropagation, Option<EditEvent>) {
+        let tolerance = self.tolerance;
+        let snapping = self.snapping.as_ref();
+        let position = |screen_position| snap(snapping, view, screen_position, tolerance);
+        let (propagation, is_changed, edit_event) = match &mut self.sketch {
+            Sketch::Idle => (EventPropagation::Propagate, false, None),
+            Sketch::Drawing {
+                kind,
+                vertices,
+                cursor,
+            } => match event {
+                UserEvent::PointerMoved(e) => {
+                    *cursor = position(e.screen_pointer_position);
+                    (EventPropagation::Propagate, true, None)
+                }
+                UserEvent::Click(MouseButton::Left, e) => {
+                    let Some(position) = position(e.screen_pointer_position) else {
+                        return (EventPropagation::Stop, None);
+                    };
+                    vertices.push(position);
+                    let edit_event = match kind {
+                        GeometryKind::Point => self.sketch.finish_drawing(),
+                        _ => None,
+                    };
+                    (EventPropagation::Stop, true, edit_event)
+                }
+                UserEvent::Click(MouseButton::Right, _) => {
+                    vertices.pop();
+                    (EventPropagation::Stop, true, None)
+                }
+                UserEvent::DoubleClick(MouseButton::Left, _) => {
+                    // Both clicks of a double click have already added vertices at the same position.
+                    if vertices.len() > 1 {
+                        vertices.pop();
+                    }
+                    let edit_event = self.sketch.finish_drawing();
+                    (EventPropagation::Stop, true, edit_event)
+                }
+                _ => (EventPropagation::Propagate, false, None),
+            },
+            Sketch::Editing { kind, parts, drag } => match event {
+                UserEvent::DragStarted(MouseButton::Left | MouseButton::Other, e) => {
+                    match find_handle(*kind, parts, view, &e.screen_pointer_position, tolerance) {
+                        Some(Handle::Vertex(id)) => {
+                            *drag = Some(id);
+                            (EventPropagation::Consume, false, None)
+                        }
+                        Some(Handle::Midpoint((part, index))) => {
+                            let (start, end) = segment(&parts[part], index);
+                            parts[part].insert(index + 1, midpoint(&start, &end));
+                            *drag = Some((part, index + 1));
+                            (EventPropagation::Consume, true, None)
+                        }
+                        None => (EventPropagation::Propagate, false, None),
+                    }
+                }
+                UserEvent::Drag(_, _, e) if drag.is_some() => {
+                    let (part, index) = drag.expect("checked above");
+                    match position(e.screen_pointer_position) {
+                        Some(position) => {
+                            parts[part][index] = position;
+                            (EventPropagation::Stop, true, None)
+                        }
+                        None => (EventPropagation::Stop, false, None),
+                    }
+                }
+                UserEvent::DragEnded(..) if drag.is_some() => {
+                    *drag = None;
+                    let geometry = make_geometry(*kind, parts);
+                    (
+                        EventPropagation::Stop,
+                        false,
+                        Some(EditEvent::Changed(geometry)),
+                    )
+                }
+                UserEvent::DoubleClick(MouseButton::Left, e) => {
+                    let handle =
+                        find_handle(*kind, parts, view, &e.screen_pointer_position, tolerance);
+                    match handle {
+                        Some(Handle::Vertex((part, index)))
+                            if parts[part].len() > kind.min_vertices() =>
+                        {
+                            parts[part].remove(index);
+                            let geometry = make_geometry(*kind, parts);
+                            (
+                                EventPropagation::Stop,
+                                true,
+                                Some(EditEvent::Changed(geometry)),
+                            )
+                        }
+                        _ => (EventPropagation::Propagate, false, None),
+                    }
+                }
+                _ => (EventPropagation::Propagate, false, None),
+            },
+        };
+
+        if is_changed {
+            self.invalidate();
+        }
+
+        (propagation, edit_event)
+    }
+
+    fn render_sketch(&self, bundle: &mut RenderBundle, resolution: f64) {
+        let style = &self.style;
+        let mut add =
+            |primitive: RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>| {
+                bundle.add(primitive, resolution);
+            };
+
+        match &self.sketch {
+            Sketch::Idle => {}
+            Sketch::Drawing {
+                kind,
+                vertices,
+                cursor,
+            } => {
+                let points: Vec<Point3d> = vertices.iter().chain(cursor).map(to_3d).collect();
+                match kind {
+                    GeometryKind::Point => {}
+                    GeometryKind::Line if points.len() > 1 => {
+                        add(RenderPrimitive::new_contour(
+                            Contour::open(points),
+                            style.line,
+                        ));
+                    }
+                    GeometryKind::Polygon if points.len() > 2 => {
+                        add(RenderPrimitive::new_polygon(
+                            Polygon::new(ClosedContour::new(points.clone()), vec![]),
+                            PolygonPaint {
+                                color: style.fill,
+                                fill: PolygonFill::Solid,
+                            },
+                        ));
+                        add(RenderPrimitive::new_contour(
+                            Contour::closed(points),
+                            style.line,
+                        ));
+                    }
+                    GeometryKind::Polygon if points.len() > 1 => {
+                        add(RenderPrimitive::new_contour(
+                            Contour::open(points),
+                            style.line,
+                        ));
+                    }
+                    _ => {}
+                }
+
+                for vertex in vertices {
+                    add(vertex_handle(vertex, style, style.vertex_color));
+                }
+            }
+            Sketch::Editing { kind, parts, .. } => {
+                match kind {
+                    GeometryKind::Point => {}
+                    GeometryKind::Line => {
+                        let points = parts[0].iter().map(to_3d).collect();
+                        add(RenderPrimitive::new_contour(
+                            Contour::open(points),
+                            style.line,
+                        ));
+                    }
+                    GeometryKind::Polygon => {
+                        let mut rings = parts
+                            .iter()
+                            .map(|part| ClosedContour::new(part.iter().map(to_3d).collect()));
+                        if let Some(outer) = rings.next() {
+                            add(RenderPrimitive::new_polygon(
+                                Polygon::new(outer, rings.collect()),
+                                PolygonPaint {
+                                    color: style.fill,
+                                    fill: PolygonFill::Solid,
+                                },
+                            ));
+                        }
+                        for part in parts {
+                            let points = part.iter().map(to_3d).collect();
+                            add(RenderPrimitive::new_contour(
+                                Contour::closed(points),
+                                style.line,
+                            ));
+                        }
+                    }
+                }
+
+                for part in parts {
+                    for index in 0..segment_count(*kind, part) {
+                        let (start, end) = segment(part, index);
+                        add(vertex_handle(
+                            &midpoint(&start, &end),
+                            style,
+                            style.midpoint_color,
+                        ));
+                    }
+                }
+
+                for vertex in parts.iter().flatten() {
+                    add(vertex_handle(vertex, style, style.vertex_color));
+                }
+            }
+        }
+    }
+}
+
+impl Sketch {
+    /// Switches from drawing to editing the drawn geometry, if it has enough vertices.
+    fn finish_drawing(&mut self) -> Option<EditEvent> {
+        let Sketch::Drawing { kind, vertices, .. } = self else {
+            return None;
+        };
+        if vertices.len() < kind.min_vertices() {
+            return None;
+        }
+
+        let kind = *kind;
+        let parts = vec![std::mem::take(vertices)];
+        let geometry = make_geometry(kind, &parts);
+        *self = Sketch::Editing {
+            kind,
+            parts,
+            drag: None,
+        };
+
+        Some(EditEvent::Created(geometry))
+    }
+
+    fn geometry(&self) -> Option<Geom<Point2d>> {
+        match self {
+            Sketch::Editing { kind, parts, .. } => Some(make_geometry(*kind, parts)),
+            _ => None,
+        }
+    }
+}
+
+impl UserEventHandler for GeometryEditor {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        let (propagation, edit_event) = self.lock().handle(event, map.view());
+        if let Some(edit_event) = edit_event {
+            self.emit(edit_event);
+        }
+
+        propagation
+    }
+}
+
+impl Layer for GeometryEditor {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let mut state = self.lock();
+        if matches!(state.sketch, Sketch::Idle) {
+            return;
+        }
+
+        let resolution = view.resolution();
+        if canvas.is_transient() {
+            let mut bundle = canvas.create_bundle();
+            state.render_sketch(&mut bundle, resolution);
+            let packed = canvas.pack_bundle(&bundle);
+            canvas.draw_bundles(&[&*packed], RenderOptions::default());
+            return;
+        }
+
+        if state
+            .packed
+            .as_ref()
+            .map(|(packed_resolution, _)| *packed_resolution)
+            != Some(resolution)
+        {
+            let mut bundle = canvas.create_bundle();
+            state.render_sketch(&mut bundle, resolution);
+            state.packed = Some((resolution, canvas.pack_bundle(&bundle)));
+        }
+
+        if let Some((_, packed)) = &state.packed {
+            canvas.draw_bundles(&[&**packed], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // do nothing
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.lock().messenger = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Map position of the `screen_position`, snapped with the `snapping` provider if possible.
+fn snap(
+    snapping: Option<&SnappingProvider>,
+    view: &MapView,
+    screen_position: Point2d,
+    tolerance: f64,
+) -> Option<Point2d> {
+    snapping
+        .and_then(|snapping| snapping.snap(screen_position, view, tolerance))
+        .map(|snap| snap.position)
+        .or_else(|| view.screen_to_map(screen_position))
+}
+
+fn make_geometry(kind: GeometryKind, parts: &[Vec<Point2d>]) -> Geom<Point2d> {
+    match kind {
+        GeometryKind::Point => Geom::Point(parts[0][0]),
+        GeometryKind::Line => Geom::Contour(Contour::open(parts[0].clone())),
+        GeometryKind::Polygon => Geom::Polygon(Polygon::new(
+            ClosedContour::new(parts[0].clone()),
+            parts[1..]
+                .iter()
+                .map(|ring| ClosedContour::new(ring.clone()))
+                .collect(),
+        )),
+    }
+}
+
+/// Vertices of a polygon ring without the closing point repeating the first one.
+fn ring_vertices(mut points: Vec<Point2d>) -> Vec<Point2d> {
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
+
+fn segment_count(kind: GeometryKind, part: &[Point2d]) -> usize {
+    match kind {
+        GeometryKind::Point => 0,
+        GeometryKind::Line => part.len().saturating_sub(1),
+        GeometryKind::Polygon => part.len(),
+    }
+}
+
+/// Segment of the part starting at the vertex with the given `index`. The segment after the last vertex of a
+/// polygon ring ends at its first vertex.
+fn segment(part: &[Point2d], index: usize) -> (Point2d, Point2d) {
+    (part[index], part[(index + 1) % part.len()])
+}
+
+fn midpoint(start: &Point2d, end: &Point2d) -> Point2d {
+    Point2d::new((start.x() + end.x()) / 2.0, (start.y() + end.y()) / 2.0)
+}
+
+/// Finds the vertex handle or, if there is none, the midpoint handle closest to the `screen_point` within
+/// `tolerance` pixels.
+fn find_handle(
+    kind: GeometryKind,
+    parts: &[Vec<Point2d>],
+    view: &MapView,
+    screen_point: &Point2d,
+    tolerance: f64,
+) -> Option<Handle> {
+    let distance_sq = |point: &Point2d| {
+        view.map_to_screen(&to_3d(point))
+            .map(|position| position.distance_sq(screen_point))
+            .filter(|distance_sq| *distance_sq <= tolerance * tolerance)
+    };
+    let closest = |handles: Vec<(f64, VertexId)>| {
+        handles
+            .into_iter()
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, id)| id)
+    };
+
+    let vertices = parts
+        .iter()
+        .enumerate()
+        .flat_map(|(part_index, part)| {
+            part.iter()
+                .enumerate()
+                .map(move |(index, vertex)| ((part_index, index), vertex))
+        })
+        .filter_map(|(id, vertex)| Some((distance_sq(vertex)?, id)))
+        .collect();
+    if let Some(id) = closest(vertices) {
+        return Some(Handle::Vertex(id));
+    }
+
+    let midpoints = parts
+        .iter()
+        .enumerate()
+        .flat_map(|(part_index, part)| {
+            (0..segment_count(kind, part)).map(move |index| {
+                let (start, end) = segment(part, index);
+                ((part_index, index), midpoint(&start, &end))
+            })
+        })
+        .filter_map(|(id, midpoint)| Some((distance_sq(&midpoint)?, id)))
+        .collect();
+
+    closest(midpoints).map(Handle::Midpoint)
+}
+
+fn vertex_handle<'a>(
+    position: &Point2d,
+    style: &EditorStyle,
+    color: Color,
+) -> RenderPrimitive<'a, f64, Point3d, Contour<Point3d>, Polygon<Point3d>> {
+    RenderPrimitive::new_point(
+        to_3d(position),
+        PointPaint::circle(color, style.vertex_size).with_outline(style.line.color, 1.5),
+    )
+}
+
+fn to_3d(point: &Point2d) -> Point3d {
+    Point3d::new(point.x(), point.y(), 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{MouseButtonsState, MouseEvent};
+    use crate::messenger::DummyMessenger;
+    use galileo_types::cartesian::Size;
+
+    fn test_map() -> Map {
+        // Map point (0, 0) is at the screen point (50, 50), one map unit per pixel.
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        Map::new(view, vec![], None::<DummyMessenger>)
+    }
+
+    fn mouse(x: f64, y: f64) -> MouseEvent {
+        MouseEvent {
+            screen_pointer_position: Point2d::new(x, y),
+            buttons: MouseButtonsState::default(),
+        }
+    }
+
+    fn recording_editor() -> (GeometryEditor, Arc<Mutex<Vec<EditEvent>>>) {
+        let events = Arc::new(Mutex::new(vec![]));
+        let recorded = events.clone();
+        let editor = GeometryEditor::new().with_event_handler(move |event: &EditEvent| {
+            recorded.lock().unwrap().push(event.clone())
+        });
+        (editor, events)
+    }
+
+    fn click(editor: &GeometryEditor, map: &mut Map, x: f64, y: f64) {
+        editor.handle(&UserEvent::Click(MouseButton::Left, mouse(x, y)), map);
+    }
+
+    fn drag(editor: &GeometryEditor, map: &mut Map, from: (f64, f64), to: (f64, f64)) {
+        let started = UserEvent::DragStarted(MouseButton::Left, mouse(from.0, from.1));
+        assert!(matches!(
+            editor.handle(&started, map),
+            EventPropagation::Consume
+        ));
+        let delta = nalgebra::Vector2::new(to.0 - from.0, to.1 - from.1);
+        editor.handle(
+            &UserEvent::Drag(MouseButton::Left, delta, mouse(to.0, to.1)),
+            map,
+        );
+        editor.handle(
+            &UserEvent::DragEnded(MouseButton::Left, mouse(to.0, to.1)),
+            map,
+        );
+    }
+
+    fn line(points: &[(f64, f64)]) -> Geom<Point2d> {
+        Geom::Contour(Contour::open(
+            points.iter().map(|&(x, y)| Point2d::new(x, y)).collect(),
+        ))
+    }
+
+    #[test]
+    fn draws_line() {
+        let mut map = test_map();
+        let (editor, events) = recording_editor();
+        editor.draw(GeometryKind::Line);
+
+        click(&editor, &mut map, 50.0, 50.0);
+        click(&editor, &mut map, 60.0, 40.0);
+        click(&editor, &mut map, 60.0, 40.0);
+        editor.handle(
+            &UserEvent::DoubleClick(MouseButton::Left, mouse(60.0, 40.0)),
+            &mut map,
+        );
+
+        let expected = line(&[(0.0, 0.0), (10.0, 10.0)]);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![EditEvent::Created(expected.clone())]
+        );
+        assert_eq!(editor.geometry(), Some(expected));
+    }
+
+    #[test]
+    fn draws_point_with_single_click() {
+        let mut map = test_map();
+        let (editor, events) = recording_editor();
+        editor.draw(GeometryKind::Point);
+
+        click(&editor, &mut map, 40.0, 50.0);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![EditEvent::Created(Geom::Point(Point2d::new(-10.0, 0.0)))]
+        );
+    }
+
+    #[test]
+    fn polygon_requires_three_vertices() {
+        let mut map = test_map();
+        let editor = GeometryEditor::new();
+        editor.draw(GeometryKind::Polygon);
+
+        click(&editor, &mut map, 50.0, 50.0);
+        click(&editor, &mut map, 60.0, 50.0);
+        assert!(!editor.finish_drawing());
+
+        click(&editor, &mut map, 60.0, 40.0);
+        editor.handle(
+            &UserEvent::Click(MouseButton::Right, mouse(0.0, 0.0)),
+            &mut map,
+        );
+        assert!(!editor.finish_drawing());
+
+        click(&editor, &mut map, 50.0, 40.0);
+        assert!(editor.finish_drawing());
+        assert!(matches!(editor.geometry(), Some(Geom::Polygon(_))));
+    }
+
+    #[test]
+    fn drags_vertex() {
+        let mut map = test_map();
+        let (editor, events) = recording_editor();
+        editor.edit(line(&[(0.0, 0.0), (20.0, 0.0)])).unwrap();
+
+        drag(&editor, &mut map, (52.0, 51.0), (50.0, 30.0));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![EditEvent::Changed(line(&[(0.0, 20.0), (20.0, 0.0)]))]
+        );
+    }
+
+    #[test]
+    fn inserts_vertex_at_midpoint() {
+        let mut map = test_map();
+        let editor = GeometryEditor::new();
+        editor.edit(line(&[(0.0, 0.0), (20.0, 0.0)])).unwrap();
+
+        drag(&editor, &mut map, (60.0, 50.0), (60.0, 40.0));
+        assert_eq!(
+            editor.geometry(),
+            Some(line(&[(0.0, 0.0), (10.0, 10.0), (20.0, 0.0)]))
+        );
+    }
+
+    #[test]
+    fn deletes_vertex_with_double_click() {
+        let mut map = test_map();
+        let editor = GeometryEditor::new();
+        editor
+            .edit(line(&[(0.0, 0.0), (10.0, 10.0), (20.0, 0.0)]))
+            .unwrap();
+
+        let double_click = |x, y, map: &mut Map| {
+            editor.handle(&UserEvent::DoubleClick(MouseButton::Left, mouse(x, y)), map)
+        };
+        assert!(matches!(
+            double_click(60.0, 40.0, &mut map),
+            EventPropagation::Stop
+        ));
+        assert_eq!(editor.geometry(), Some(line(&[(0.0, 0.0), (20.0, 0.0)])));
+
+        // A line cannot have less than two vertices.
+        assert!(matches!(
+            double_click(50.0, 50.0, &mut map),
+            EventPropagation::Propagate
+        ));
+    }
+
+    #[test]
+    fn edits_polygon_without_closing_point() {
+        let ring = |points: &[(f64, f64)]| {
+            ClosedContour::new(points.iter().map(|&(x, y)| Point2d::new(x, y)).collect())
+        };
+        let polygon = |points: &[(f64, f64)]| Geom::Polygon(Polygon::new(ring(points), vec![]));
+
+        let editor = GeometryEditor::new();
+        editor
+            .edit(polygon(&[
+                (0.0, 0.0),
+                (10.0, 0.0),
+                (10.0, 10.0),
+                (0.0, 0.0),
+            ]))
+            .unwrap();
+        assert_eq!(
+            editor.stop(),
+            Some(polygon(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]))
+        );
+        assert!(!editor.is_active());
+    }
+
+    #[test]
+    fn snaps_new_vertices() {
+        let mut map = test_map();
+        let snapping = SnappingProvider::new();
+        snapping.add_geometry(&Geom::Point(Point2d::new(10.0, 10.0)));
+        let editor = GeometryEditor::new().with_snapping(snapping);
+        editor.draw(GeometryKind::Point);
+
+        click(&editor, &mut map, 63.0, 42.0);
+        assert_eq!(
+            editor.geometry(),
+            Some(Geom::Point(Point2d::new(10.0, 10.0)))
+        );
+    }
+
+    #[test]
+    fn ignores_events_when_idle() {
+        let mut map = test_map();
+        let editor = GeometryEditor::new();
+        let propagation = editor.handle(
+            &UserEvent::Click(MouseButton::Left, mouse(50.0, 50.0)),
+            &mut map,
+        );
+        assert!(matches!(propagation, EventPropagation::Propagate));
+        assert!(editor
+            .edit(Geom::Contour(Contour::closed(vec![Point2d::new(0.0, 0.0)])))
+            .is_err());
+    }
+}
@@ -0,0 +1,309 @@
+use crate::layer::feature_layer::{Feature, FeatureLayer, Symbol};
+use crate::view::MapView;
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Point3d};
+use galileo_types::geo::{Crs, NewGeoPoint};
+use galileo_types::geometry::{Geom, Geometry};
+use galileo_types::geometry_type::GeoSpace2d;
+use galileo_types::{Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use rstar::primitives::Line;
+use rstar::RTree;
+use std::sync::{Arc, RwLock};
+
+/// Position a pointer was snapped to by a [`SnappingProvider`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    /// Snapped position in the map CRS.
+    pub position: Point2d,
+    /// Part of the geometry the position was snapped to.
+    pub target: SnapTarget,
+}
+
+/// Part of a geometry a position can be snapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapTarget {
+    /// A vertex of the geometry.
+    Vertex,
+    /// The closest point on an edge of the geometry.
+    Edge,
+}
+
+/// Snaps pointer positions to vertices and edges of geometries that are closer than a tolerance in pixels.
+///
+/// The provider keeps an R-tree index of the vertices and edges of the added geometries. The geometries are added
+/// in the CRS of the map view, either one by one with [`SnappingProvider::add_geometry`], or as all visible features
+/// of a layer with [`SnappingProvider::add_layer`]. The index is not updated automatically when the geometries
+/// change, so it should be [cleared](SnappingProvider::clear) and filled again in this case.
+///
+/// Vertices have priority over edges: a position is snapped to an edge only if there are no vertices within the
+/// tolerance. Clones of a provider share the same index, so the same provider can be given to a
+/// [`GeometryEditor`](super::GeometryEditor) and used by the application at the same time.
+#[derive(Debug, Clone)]
+pub struct SnappingProvider {
+    index: Arc<RwLock<SnappingIndex>>,
+}
+
+#[derive(Debug)]
+struct SnappingIndex {
+    vertices: RTree<[f64; 2]>,
+    edges: RTree<Line<[f64; 2]>>,
+    snap_to_vertices: bool,
+    snap_to_edges: bool,
+}
+
+impl Default for SnappingProvider {
+    fn default() -> Self {
+        Self {
+            index: Arc::new(RwLock::new(SnappingIndex {
+                vertices: RTree::new(),
+                edges: RTree::new(),
+                snap_to_vertices: true,
+                snap_to_edges: true,
+            })),
+        }
+    }
+}
+
+impl SnappingProvider {
+    /// Creates a new provider without any geometries, that snaps to both vertices and edges.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether positions are snapped to vertices of the geometries.
+    pub fn with_snap_to_vertices(self, snap_to_vertices: bool) -> Self {
+        self.index
+            .write()
+            .expect("lock is poisoned")
+            .snap_to_vertices = snap_to_vertices;
+        self
+    }
+
+    /// Sets whether positions are snapped to edges of the geometries.
+    pub fn with_snap_to_edges(self, snap_to_edges: bool) -> Self {
+        self.index.write().expect("lock is poisoned").snap_to_edges = snap_to_edges;
+        self
+    }
+
+    /// Adds vertices and edges of the `geometry` to the index. The geometry must be in the CRS of the map view.
+    pub fn add_geometry(&self, geometry: &Geom<Point2d>) {
+        self.index
+            .write()
+            .expect("lock is poisoned")
+            .add_geometry(geometry);
+    }
+
+    /// Adds geometries of all visible features of the `layer`, projected into the `crs` of the map view.
+    ///
+    /// Features that cannot be projected into the `crs` are skipped.
+    pub fn add_layer<P, F, S>(&self, layer: &FeatureLayer<P, F, S, GeoSpace2d>, crs: &Crs)
+    where
+        P: NewGeoPoint + 'static,
+        F: Feature,
+        F::Geom: Geometry<Point = P>,
+        S: Symbol<F>,
+    {
+        let Some(projection) = crs.get_projection::<P, Point2d>() else {
+            return;
+        };
+
+        let mut index = self.index.write().expect("lock is poisoned");
+        for feature in layer.features().iter() {
+            if feature.is_hidden() {
+                continue;
+            }
+
+            if let Some(projected) = feature.as_ref().geometry().project(&*projection) {
+                index.add_geometry(&projected);
+            }
+        }
+    }
+
+    /// Removes all geometries from the index.
+    pub fn clear(&self) {
+        let mut index = self.index.write().expect("lock is poisoned");
+        index.vertices = RTree::new();
+        index.edges = RTree::new();
+    }
+
+    /// Snaps the `screen_position` of the pointer to the closest vertex or edge of the indexed geometries, that is
+    /// not farther than `tolerance` pixels from it on the screen.
+    ///
+    /// Returns `None` if there are no such vertices or edges.
+    pub fn snap(&self, screen_position: Point2d, view: &MapView, tolerance: f64) -> Option<Snap> {
+        let index = self.index.read().expect("lock is poisoned");
+        let position = view.screen_to_map(screen_position)?;
+        let position = [position.x(), position.y()];
+        let is_within_tolerance = |point: &[f64; 2]| {
+            view.map_to_screen(&Point3d::new(point[0], point[1], 0.0))
+                .is_some_and(|snapped| {
+                    snapped.distance_sq(&screen_position) <= tolerance * tolerance
+                })
+        };
+
+        if index.snap_to_vertices {
+            if let Some(vertex) = index.vertices.nearest_neighbor(&position) {
+                if is_within_tolerance(vertex) {
+                    return Some(Snap {
+                        position: Point2d::new(vertex[0], vertex[1]),
+                        target: SnapTarget::Vertex,
+                    });
+                }
+            }
+        }
+
+        if index.snap_to_edges {
+            if let Some(edge) = index.edges.nearest_neighbor(&position) {
+                let point = edge.nearest_point(&position);
+                if is_within_tolerance(&point) {
+                    return Some(Snap {
+                        position: Point2d::new(point[0], point[1]),
+                        target: SnapTarget::Edge,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl SnappingIndex {
+    fn add_geometry(&mut self, geometry: &Geom<Point2d>) {
+        match geometry {
+            Geom::Point(point) => self.add_vertex(point),
+            Geom::MultiPoint(points) => {
+                for point in points.iter_points() {
+                    self.add_vertex(point);
+                }
+            }
+            Geom::Contour(contour) => self.add_contour(contour),
+            Geom::MultiContour(contours) => {
+                for contour in contours.contours() {
+                    self.add_contour(contour);
+                }
+            }
+            Geom::Polygon(polygon) => self.add_polygon(polygon),
+            Geom::MultiPolygon(polygons) => {
+                for polygon in polygons.polygons() {
+                    self.add_polygon(polygon);
+                }
+            }
+        }
+    }
+
+    fn add_vertex(&mut self, point: &Point2d) {
+        self.vertices.insert([point.x(), point.y()]);
+    }
+
+    fn add_contour(&mut self, contour: &impl Contour<Point = Point2d>) {
+        for point in contour.iter_points() {
+            self.add_vertex(point);
+        }
+        for segment in contour.iter_segments() {
+            self.edges.insert(Line::new(
+                [segment.0.x(), segment.0.y()],
+                [segment.1.x(), segment.1.y()],
+            ));
+        }
+    }
+
+    fn add_polygon<Poly>(&mut self, polygon: &Poly)
+    where
+        Poly: Polygon,
+        Poly::Contour: Contour<Point = Point2d>,
+    {
+        for contour in polygon.iter_contours() {
+            self.add_contour(contour);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::ArbitraryGeometrySymbol;
+    use galileo_types::cartesian::Size;
+    use galileo_types::impls::{ClosedContour, Polygon};
+    use galileo_types::latlon;
+
+    fn view() -> MapView {
+        // Map point (0, 0) is at the screen point (50, 50), one map unit per pixel.
+        MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0))
+    }
+
+    fn square() -> Geom<Point2d> {
+        Geom::Polygon(Polygon::new(
+            ClosedContour::new(vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(20.0, 0.0),
+                Point2d::new(20.0, 20.0),
+                Point2d::new(0.0, 20.0),
+            ]),
+            vec![],
+        ))
+    }
+
+    #[test]
+    fn snaps_to_vertex_before_edge() {
+        let provider = SnappingProvider::new();
+        provider.add_geometry(&square());
+
+        let snap = provider.snap(Point2d::new(68.0, 49.0), &view(), 5.0);
+        assert_eq!(
+            snap,
+            Some(Snap {
+                position: Point2d::new(20.0, 0.0),
+                target: SnapTarget::Vertex,
+            })
+        );
+    }
+
+    #[test]
+    fn snaps_to_closing_edge_of_polygon() {
+        let provider = SnappingProvider::new();
+        provider.add_geometry(&square());
+
+        // Point (-2, 10) is next to the edge between the last and the first vertices.
+        let snap = provider.snap(Point2d::new(48.0, 40.0), &view(), 5.0);
+        assert_eq!(
+            snap,
+            Some(Snap {
+                position: Point2d::new(0.0, 10.0),
+                target: SnapTarget::Edge,
+            })
+        );
+
+        let provider = provider.with_snap_to_edges(false);
+        assert_eq!(provider.snap(Point2d::new(48.0, 40.0), &view(), 5.0), None);
+    }
+
+    #[test]
+    fn does_not_snap_outside_of_tolerance() {
+        let provider = SnappingProvider::new();
+        provider.add_geometry(&square());
+        assert_eq!(provider.snap(Point2d::new(60.0, 40.0), &view(), 5.0), None);
+
+        provider.clear();
+        assert_eq!(provider.snap(Point2d::new(50.0, 50.0), &view(), 5.0), None);
+    }
+
+    #[test]
+    fn indexes_visible_features_of_layer() {
+        let mut layer = FeatureLayer::new(
+            vec![latlon!(0.0, 0.0), latlon!(0.0, 0.0001)],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+        layer.features_mut().get_mut(1).unwrap().hide();
+
+        let provider = SnappingProvider::new();
+        provider.add_layer(&layer, &Crs::EPSG3857);
+
+        assert!(provider
+            .snap(Point2d::new(52.0, 50.0), &view(), 5.0)
+            .is_some());
+        assert!(provider
+            .snap(Point2d::new(61.0, 50.0), &view(), 5.0)
+            .is_none());
+    }
+}
@@ -1,6 +1,7 @@
+use crate::control::map_events::MapEventDispatcher;
 use crate::control::{
-    EventPropagation, MouseButton, MouseButtonsState, MouseEvent, RawUserEvent, TouchId, UserEvent,
-    UserEventHandler,
+    EventPropagation, KeyModifiers, MapEvent, MouseButton, MouseButtonsState, MouseEvent,
+    RawUserEvent, SubscriptionId, TouchGestures, TouchId, UserEvent, UserEventHandler,
 };
 use crate::map::Map;
 use galileo_types::cartesian::{CartesianPoint2d, Point2d};
@@ -15,6 +16,20 @@ struct TouchInfo {
     start_position: Point2d,
     _start_time: SystemTime,
     prev_position: Point2d,
+    /// Position of the touch when the current two-finger gesture started.
+    gesture_start_position: Point2d,
+}
+
+/// Kind of the current two-finger gesture.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum TwoFingerGesture {
+    /// The touches have not moved far enough yet to decide the kind of the gesture.
+    Undecided,
+    Pinch {
+        is_zooming: bool,
+        is_rotating: bool,
+    },
+    Tilt,
 }
 
 /// Stores input state, converts [`RawUserEvent`] into [`UserEvent`] and manages a list of event handlers.
@@ -26,13 +41,18 @@ pub struct EventProcessor {
     pointer_position: Point2d,
     pointer_pressed_position: Point2d,
     touches: Vec<TouchInfo>,
+    touch_gestures: TouchGestures,
+    two_finger_gesture: TwoFingerGesture,
 
     buttons_state: MouseButtonsState,
+    key_modifiers: KeyModifiers,
 
     last_pressed_time: SystemTime,
     last_click_time: SystemTime,
 
     drag_target: Option<usize>,
+
+    map_events: MapEventDispatcher,
 }
 
 impl Default for EventProcessor {
@@ -42,10 +62,14 @@ impl Default for EventProcessor {
             pointer_position: Default::default(),
             pointer_pressed_position: Default::default(),
             touches: Vec::new(),
+            touch_gestures: TouchGestures::default(),
+            two_finger_gesture: TwoFingerGesture::Undecided,
             buttons_state: Default::default(),
+            key_modifiers: Default::default(),
             last_pressed_time: SystemTime::UNIX_EPOCH,
             last_click_time: SystemTime::UNIX_EPOCH,
             drag_target: None,
+            map_events: MapEventDispatcher::default(),
         }
     }
 }
@@ -56,6 +80,47 @@ impl EventProcessor {
         self.handlers.push(Box::new(handler));
     }
 
+    /// Subscribes the `handler` to the [`MapEvent`]s and returns the id of the subscription, that can be used to
+    /// [`unsubscribe`](EventProcessor::unsubscribe) it.
+    ///
+    /// Map events are emitted after the user event that produced them is given to the [`UserEventHandler`]s, whether
+    /// it was consumed by one of them or not. The changes of the view and layer loading state are detected in
+    /// [`EventProcessor::handle`] and [`EventProcessor::update`].
+    pub fn subscribe(
+        &mut self,
+        handler: impl FnMut(&MapEvent, &mut Map) + 'static,
+    ) -> SubscriptionId {
+        self.map_events.subscribe(handler)
+    }
+
+    /// Removes the subscription. Returns `false` if there is no subscription with the given id.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.map_events.unsubscribe(id)
+    }
+
+    /// Sets the distance in pixels from the pointer, within which the features are considered to be under the
+    /// pointer for the [`MapEvent`]s. Default value is 3 pixels.
+    pub fn set_pick_tolerance(&mut self, tolerance: f64) {
+        self.map_events.set_pick_tolerance(tolerance);
+    }
+
+    /// Emits [`MapEvent::ViewChanged`] and [`MapEvent::LayerLoaded`] events if the view of the map has changed or a
+    /// layer finished loading since the last call. This method should be called after every frame, as the view also
+    /// changes with animations and the layers are loaded in the background.
+    pub fn update(&mut self, map: &mut Map) {
+        self.map_events.update(map);
+    }
+
+    /// Touch gestures recognized by the processor.
+    pub fn touch_gestures(&self) -> TouchGestures {
+        self.touch_gestures
+    }
+
+    /// Sets which touch gestures are recognized by the processor and their thresholds. See [`TouchGestures`].
+    pub fn set_touch_gestures(&mut self, gestures: TouchGestures) {
+        self.touch_gestures = gestures;
+    }
+
     /// Handles the event.
     pub fn handle(&mut self, event: RawUserEvent, map: &mut Map) {
         if let Some(user_events) = self.process(event) {
@@ -105,7 +170,11 @@ impl EventProcessor {
                 if matches!(user_event, UserEvent::DragEnded(..)) {
                     self.drag_target = None;
                 }
+
+                self.map_events.handle_user_event(&user_event, map);
             }
+
+            self.map_events.update(map);
         }
     }
 
@@ -138,10 +207,10 @@ impl EventProcessor {
                     }
 
                     self.last_click_time = now;
+                }
 
-                    if self.drag_target.take().is_some() {
-                        events.push(UserEvent::DragEnded(button, self.get_mouse_event()));
-                    }
+                if self.drag_target.take().is_some() {
+                    events.push(UserEvent::DragEnded(button, self.get_mouse_event()));
                 }
 
                 Some(events)
@@ -193,7 +262,9 @@ impl EventProcessor {
                     start_position: touch.position,
                     _start_time: now,
                     prev_position: touch.position,
+                    gesture_start_position: touch.position,
                 });
+                self.start_touch_gesture();
 
                 None
             }
@@ -203,7 +274,7 @@ impl EventProcessor {
 
                 let mut events = vec![];
 
-                if self.touches.len() == 1 {
+                if self.touches.len() == 1 && self.touch_gestures.pan {
                     let mut is_dragging = self.drag_target.is_some();
                     if self.drag_target.is_none()
                         && position.taxicab_distance(&touch_info.start_position) > DRAG_THRESHOLD
@@ -230,12 +301,42 @@ impl EventProcessor {
                         return None;
                     };
 
-                    let distance = (other_touch.prev_position - position).magnitude();
-                    let prev_distance =
-                        (other_touch.prev_position - touch_info.prev_position).magnitude();
-                    let zoom = prev_distance / distance;
+                    self.two_finger_gesture =
+                        self.next_two_finger_gesture(touch_info, other_touch, position);
+                    let center = other_touch.prev_position;
+                    // Screen Y axis goes down, so the angles are measured clockwise.
+                    let angle = |point: Point2d| {
+                        let delta = point - center;
+                        delta.y.atan2(delta.x)
+                    };
+
+                    match self.two_finger_gesture {
+                        TwoFingerGesture::Undecided => {}
+                        TwoFingerGesture::Pinch {
+                            is_zooming,
+                            is_rotating,
+                        } => {
+                            if is_zooming {
+                                let distance = (center - position).magnitude();
+                                let prev_distance = (center - touch_info.prev_position).magnitude();
+                                events.push(UserEvent::Zoom(prev_distance / distance, center));
+                            }
 
-                    events.push(UserEvent::Zoom(zoom, other_touch.prev_position))
+                            let rotation =
+                                normalize_angle(angle(touch_info.prev_position) - angle(position));
+                            if is_rotating && rotation != 0.0 {
+                                events.push(UserEvent::Rotate(rotation, center));
+                            }
+                        }
+                        TwoFingerGesture::Tilt => {
+                            // Only one of the touches moves in an event, so the movement of both touches is the
+                            // average of their movements.
+                            let delta = (position.y - touch_info.prev_position.y) / 2.0;
+                            if delta != 0.0 {
+                                events.push(UserEvent::Tilt(delta));
+                            }
+                        }
+                    }
                 }
 
                 for touch_info in &mut self.touches {
@@ -246,6 +347,14 @@ impl EventProcessor {
 
                 Some(events)
             }
+            RawUserEvent::KeyPressed(key) => {
+                self.key_modifiers.set_pressed(key, true);
+                Some(vec![UserEvent::KeyPressed(key, self.key_modifiers)])
+            }
+            RawUserEvent::KeyReleased(key) => {
+                self.key_modifiers.set_pressed(key, false);
+                Some(vec![UserEvent::KeyReleased(key, self.key_modifiers)])
+            }
             RawUserEvent::TouchEnd(touch) => {
                 for i in 0..self.touches.len() {
                     if self.touches[i].id == touch.touch_id {
@@ -254,6 +363,13 @@ impl EventProcessor {
                     }
                 }
 
+                // The remaining touches start a new gesture from their current positions, so that lifting one finger
+                // after a pinch does not make the map jump.
+                for touch_info in &mut self.touches {
+                    touch_info.start_position = touch_info.prev_position;
+                }
+                self.start_touch_gesture();
+
                 let mut events = vec![];
 
                 if self.drag_target.is_some() && self.touches.is_empty() {
@@ -269,6 +385,59 @@ impl EventProcessor {
         }
     }
 
+    fn start_touch_gesture(&mut self) {
+        self.two_finger_gesture = TwoFingerGesture::Undecided;
+        for touch_info in &mut self.touches {
+            touch_info.gesture_start_position = touch_info.prev_position;
+        }
+    }
+
+    /// Decides the kind of the two-finger gesture after the `touch` moved to the `position`.
+    fn next_two_finger_gesture(
+        &self,
+        touch: &TouchInfo,
+        other_touch: &TouchInfo,
+        position: Point2d,
+    ) -> TwoFingerGesture {
+        let gestures = &self.touch_gestures;
+        let (mut is_zooming, mut is_rotating) = match self.two_finger_gesture {
+            TwoFingerGesture::Undecided => (false, false),
+            TwoFingerGesture::Pinch {
+                is_zooming,
+                is_rotating,
+            } => (is_zooming, is_rotating),
+            TwoFingerGesture::Tilt => return TwoFingerGesture::Tilt,
+        };
+
+        let start_vector = touch.gesture_start_position - other_touch.gesture_start_position;
+        let vector = position - other_touch.prev_position;
+
+        let distance_change = (vector.magnitude() - start_vector.magnitude()).abs();
+        is_zooming |= gestures.pinch_zoom && distance_change > gestures.zoom_threshold;
+
+        let rotation =
+            normalize_angle(vector.y.atan2(vector.x) - start_vector.y.atan2(start_vector.x));
+        is_rotating |= gestures.rotate && rotation.abs() > gestures.rotation_threshold;
+
+        if is_zooming || is_rotating {
+            return TwoFingerGesture::Pinch {
+                is_zooming,
+                is_rotating,
+            };
+        }
+
+        let dy = position.y - touch.gesture_start_position.y;
+        let other_dy = other_touch.prev_position.y - other_touch.gesture_start_position.y;
+        if gestures.tilt
+            && dy.signum() == other_dy.signum()
+            && dy.abs().min(other_dy.abs()) > gestures.tilt_threshold
+        {
+            return TwoFingerGesture::Tilt;
+        }
+
+        TwoFingerGesture::Undecided
+    }
+
     fn get_mouse_event(&self) -> MouseEvent {
         self.get_mouse_event_pos(self.pointer_position)
     }
@@ -280,3 +449,94 @@ impl EventProcessor {
         }
     }
 }
+
+/// Brings the angle into `[-PI, PI]` range.
+fn normalize_angle(angle: f64) -> f64 {
+    use std::f64::consts::{PI, TAU};
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::TouchEvent;
+
+    fn touch(
+        processor: &mut EventProcessor,
+        event: fn(TouchEvent) -> RawUserEvent,
+        id: TouchId,
+        x: f64,
+        y: f64,
+    ) -> Vec<UserEvent> {
+        processor
+            .process(event(TouchEvent {
+                touch_id: id,
+                position: Point2d::new(x, y),
+            }))
+            .unwrap_or_default()
+    }
+
+    fn two_touches(processor: &mut EventProcessor) {
+        touch(processor, RawUserEvent::TouchStart, 1, 0.0, 100.0);
+        touch(processor, RawUserEvent::TouchStart, 2, 100.0, 100.0);
+    }
+
+    #[test]
+    fn pinch_starts_after_threshold() {
+        let mut processor = EventProcessor::default();
+        two_touches(&mut processor);
+
+        assert!(touch(&mut processor, RawUserEvent::TouchMove, 2, 105.0, 100.0).is_empty());
+        let events = touch(&mut processor, RawUserEvent::TouchMove, 2, 120.0, 100.0);
+        assert!(matches!(&events[..], [UserEvent::Zoom(zoom, _)] if *zoom < 1.0));
+    }
+
+    #[test]
+    fn two_finger_rotation() {
+        let mut processor = EventProcessor::default();
+        two_touches(&mut processor);
+
+        let angle = 20f64.to_radians();
+        let events = touch(
+            &mut processor,
+            RawUserEvent::TouchMove,
+            2,
+            100.0 * angle.cos(),
+            100.0 - 100.0 * angle.sin(),
+        );
+        assert!(
+            matches!(&events[..], [UserEvent::Rotate(rotation, _)] if (rotation - angle).abs() < 1e-9)
+        );
+    }
+
+    #[test]
+    fn two_finger_vertical_drag_tilts() {
+        let mut processor = EventProcessor::default();
+        two_touches(&mut processor);
+
+        assert!(touch(&mut processor, RawUserEvent::TouchMove, 2, 100.0, 85.0).is_empty());
+        let events = touch(&mut processor, RawUserEvent::TouchMove, 1, 0.0, 85.0);
+        assert!(matches!(&events[..], [UserEvent::Tilt(delta)] if *delta == -7.5));
+
+        // Tilt is not combined with zoom.
+        let events = touch(&mut processor, RawUserEvent::TouchMove, 2, 150.0, 75.0);
+        assert!(matches!(&events[..], [UserEvent::Tilt(delta)] if *delta == -5.0));
+    }
+
+    #[test]
+    fn disabled_gestures_are_not_recognized() {
+        let mut processor = EventProcessor::default();
+        processor.set_touch_gestures(TouchGestures {
+            pan: false,
+            tilt: false,
+            ..Default::default()
+        });
+        two_touches(&mut processor);
+
+        touch(&mut processor, RawUserEvent::TouchMove, 2, 100.0, 85.0);
+        assert!(touch(&mut processor, RawUserEvent::TouchMove, 1, 0.0, 85.0).is_empty());
+
+        touch(&mut processor, RawUserEvent::TouchEnd, 2, 100.0, 85.0);
+        assert!(touch(&mut processor, RawUserEvent::TouchMove, 1, 50.0, 85.0).is_empty());
+    }
+}
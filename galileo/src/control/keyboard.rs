@@ -0,0 +1,190 @@
+use crate::control::map::constrained_zoom;
+use crate::control::{EventPropagation, Key, KeyModifiers, UserEvent, UserEventHandler};
+use crate::map::{Easing, Map};
+use crate::view::MapView;
+use galileo_types::cartesian::Point2d;
+use nalgebra::Vector2;
+use std::time::Duration;
+
+/// Event handler navigating the map with the keyboard.
+///
+/// * Arrow keys pan the map.
+/// * `+` (or `=`) and `-` zoom the map in and out around the center of the screen.
+/// * `Shift` + left and right arrows rotate the map, `Shift` + up and down arrows tilt it.
+///
+/// The handler is not added by default. Add it to the [`EventProcessor`](crate::control::EventProcessor) together
+/// with the [`MapController`](crate::control::MapController) to make the map accessible without a mouse:
+///
+/// ```
+/// use galileo::control::{EventProcessor, KeyboardController, KeyboardControlsOptions, MapController};
+///
+/// let mut event_processor = EventProcessor::default();
+/// event_processor.add_handler(KeyboardController::new(KeyboardControlsOptions {
+///     pan_step: 200.0,
+///     ..Default::default()
+/// }));
+/// event_processor.add_handler(MapController::default());
+/// ```
+#[derive(Debug, Default)]
+pub struct KeyboardController {
+    options: KeyboardControlsOptions,
+}
+
+/// Options of a [`KeyboardController`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyboardControlsOptions {
+    /// Distance in pixels the map is moved by one press of an arrow key.
+    pub pan_step: f64,
+    /// Change of the resolution by one press of a zoom key. For example, with `2.0` zooming in makes the resolution
+    /// two times smaller.
+    pub zoom_step: f64,
+    /// Angle in radians the map is rotated or tilted by one key press.
+    pub rotation_step: f64,
+    /// The maximum tilt ([`MapView::rotation_x`]) of the map in radians.
+    pub max_rotation_x: f64,
+    /// Duration of the animation of one step.
+    pub animation_duration: Duration,
+}
+
+impl Default for KeyboardControlsOptions {
+    fn default() -> Self {
+        Self {
+            pan_step: 100.0,
+            zoom_step: 2.0,
+            rotation_step: 15f64.to_radians(),
+            max_rotation_x: 80f64.to_radians(),
+            animation_duration: Duration::from_millis(150),
+        }
+    }
+}
+
+impl KeyboardController {
+    /// Creates a new controller with the given options.
+    pub fn new(options: KeyboardControlsOptions) -> Self {
+        Self { options }
+    }
+
+    /// Options of the controller.
+    pub fn options(&self) -> &KeyboardControlsOptions {
+        &self.options
+    }
+
+    /// Sets the options of the controller.
+    pub fn set_options(&mut self, options: KeyboardControlsOptions) {
+        self.options = options;
+    }
+
+    /// Returns the view after the key press, or `None` if the key is not used by the controller.
+    ///
+    /// Steps are applied to the target view of the current animation, so that pressing a key several times quickly
+    /// moves the map by several steps.
+    fn target_view(&self, map: &Map, key: Key, modifiers: KeyModifiers) -> Option<MapView> {
+        if modifiers.control || modifiers.alt {
+            return None;
+        }
+
+        let view = map.target_view();
+        let size = view.size();
+        let center = Point2d::new(size.half_width(), size.half_height());
+        let step = self.options.pan_step;
+        let rotation = self.options.rotation_step;
+
+        let target = match (key, modifiers.shift) {
+            (Key::ArrowLeft, false) => view.translate_by_pixels(center, center + pan(step, 0.0)),
+            (Key::ArrowRight, false) => view.translate_by_pixels(center, center + pan(-step, 0.0)),
+            (Key::ArrowUp, false) => view.translate_by_pixels(center, center + pan(0.0, step)),
+            (Key::ArrowDown, false) => view.translate_by_pixels(center, center + pan(0.0, -step)),
+            (Key::ArrowLeft, true) => view.rotate(rotation, center),
+            (Key::ArrowRight, true) => view.rotate(-rotation, center),
+            (Key::ArrowUp, true) => view.with_rotation_x(self.clamp_rotation_x(view, rotation)),
+            (Key::ArrowDown, true) => view.with_rotation_x(self.clamp_rotation_x(view, -rotation)),
+            (Key::Character('+' | '='), _) => view.zoom(
+                constrained_zoom(map, view, 1.0 / self.options.zoom_step),
+                center,
+            ),
+            (Key::Character('-' | '_'), _) => {
+                view.zoom(constrained_zoom(map, view, self.options.zoom_step), center)
+            }
+            _ => return None,
+        };
+
+        Some(target)
+    }
+
+    fn clamp_rotation_x(&self, view: &MapView, delta: f64) -> f64 {
+        (view.rotation_x() + delta).clamp(0.0, self.options.max_rotation_x)
+    }
+}
+
+fn pan(dx: f64, dy: f64) -> Vector2<f64> {
+    Vector2::new(dx, dy)
+}
+
+impl UserEventHandler for KeyboardController {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        let UserEvent::KeyPressed(key, modifiers) = event else {
+            return EventPropagation::Propagate;
+        };
+
+        match self.target_view(map, *key, *modifiers) {
+            Some(target) => {
+                map.animate_to(target, self.options.animation_duration, Easing::EaseOut);
+                EventPropagation::Stop
+            }
+            None => EventPropagation::Propagate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messenger::DummyMessenger;
+    use galileo_types::cartesian::Size;
+
+    fn press(controller: &KeyboardController, key: Key, shift: bool) -> Map {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view, vec![], None::<DummyMessenger>);
+        let modifiers = KeyModifiers {
+            shift,
+            ..Default::default()
+        };
+        controller.handle(&UserEvent::KeyPressed(key, modifiers), &mut map);
+        map
+    }
+
+    fn center(map: &Map) -> Point2d {
+        map.target_view()
+            .screen_to_map(Point2d::new(50.0, 50.0))
+            .unwrap()
+    }
+
+    #[test]
+    fn keys_move_the_map() {
+        let controller = KeyboardController::new(KeyboardControlsOptions {
+            pan_step: 10.0,
+            ..Default::default()
+        });
+
+        let left = center(&press(&controller, Key::ArrowLeft, false));
+        assert!((left.x + 10.0).abs() < 1e-6 && left.y.abs() < 1e-6);
+        let up = center(&press(&controller, Key::ArrowUp, false));
+        assert!(up.x.abs() < 1e-6 && (up.y - 10.0).abs() < 1e-6);
+
+        let map = press(&controller, Key::Character('+'), false);
+        assert_eq!(map.target_view().resolution(), 0.5);
+        let map = press(&controller, Key::Character('-'), false);
+        assert_eq!(map.target_view().resolution(), 2.0);
+
+        let map = press(&controller, Key::ArrowLeft, true);
+        assert!((map.target_view().rotation_z() - 15f64.to_radians()).abs() < 1e-9);
+        let map = press(&controller, Key::ArrowUp, true);
+        assert!((map.target_view().rotation_x() - 15f64.to_radians()).abs() < 1e-9);
+        let map = press(&controller, Key::ArrowDown, true);
+        assert_eq!(map.target_view().rotation_x(), 0.0);
+
+        let map = press(&controller, Key::Character('a'), false);
+        assert_eq!(center(&map), Point2d::new(0.0, 0.0));
+    }
+}
@@ -1,48 +1,131 @@
 use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
-use crate::map::Map;
+use crate::map::{Easing, Map};
 use crate::view::MapView;
+use galileo_types::cartesian::Point2d;
 use nalgebra::Vector2;
+use std::sync::Mutex;
 use std::time::Duration;
+use web_time::SystemTime;
 
 const DEFAULT_ZOOM_DURATION: Duration = Duration::from_millis(50);
+const DOUBLE_CLICK_ZOOM_DURATION: Duration = Duration::from_millis(250);
 
-/// Event handler of a map, providing panning, zooming and tilting capabilities.
+/// Only the drag movements done during this time before the release are used to calculate the speed of the map.
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+/// Kinetic panning is not started if the pointer was not moving for this time before the release.
+const MAX_RELEASE_DELAY: Duration = Duration::from_millis(50);
+/// Kinetic panning is not started if the pointer moved slower than this, in pixels per second.
+const MIN_KINETIC_SPEED: f64 = 50.0;
+
+/// Event handler of a map, providing panning, zooming, rotating and tilting capabilities.
+///
+/// The map is rotated and tilted by dragging it with the right mouse button. With touches, the map is rotated by
+/// turning two fingers around each other and tilted by moving two fingers up or down together. The touch gestures
+/// can be configured with [`EventProcessor::set_touch_gestures`](crate::control::EventProcessor::set_touch_gestures).
+///
+/// The feel of the interaction, e.g. the zoom speed or kinetic panning, is configured with [`MapControlsOptions`]:
+///
+/// ```
+/// use galileo::control::{DoubleClickBehavior, MapController, MapControlsOptions};
+///
+/// let controller = MapController::new(MapControlsOptions {
+///     zoom_about_cursor: false,
+///     double_click: DoubleClickBehavior::None,
+///     kinetic_panning: false,
+///     ..Default::default()
+/// });
+/// ```
 #[derive(Default)]
 pub struct MapController {
-    parameters: MapControllerParameters,
+    options: MapControlsOptions,
+    drag: Mutex<DragState>,
 }
 
-pub struct MapControllerParameters {
-    zoom_duration: Duration,
-    zoom_speed: f64,
-    min_resolution: f64,
-    max_resolution: f64,
-
-    rotation_speed: f64,
-    max_rotation_x: f64,
+/// Options of a [`MapController`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapControlsOptions {
+    /// If `true`, scroll zoom keeps the point under the cursor in place. Otherwise, the map is zoomed about the center
+    /// of the screen.
+    pub zoom_about_cursor: bool,
+    /// Change of the resolution for one scroll line. For example, with `0.2` one line up makes the resolution `1.2`
+    /// times smaller.
+    pub scroll_zoom_speed: f64,
+    /// Duration of the scroll zoom animation.
+    pub zoom_duration: Duration,
+    /// The minimum resolution the map can be zoomed in to.
+    pub min_resolution: f64,
+    /// The maximum resolution the map can be zoomed out to.
+    pub max_resolution: f64,
+    /// What is done on a double click.
+    pub double_click: DoubleClickBehavior,
+    /// Rotation in radians for the drag of one pixel with the right mouse button or the tilt gesture.
+    pub rotation_speed: f64,
+    /// The maximum tilt ([`MapView::rotation_x`]) of the map in radians.
+    pub max_rotation_x: f64,
+    /// If `true`, the map keeps moving and gradually slows down after it is released while being dragged.
+    pub kinetic_panning: bool,
+    /// How fast the kinetic panning slows down. The speed of the map decreases `e` times during `1 / friction` seconds,
+    /// so larger values stop the map sooner.
+    pub kinetic_friction: f64,
 }
 
-impl Default for MapControllerParameters {
+impl Default for MapControlsOptions {
     fn default() -> Self {
         Self {
+            zoom_about_cursor: true,
+            scroll_zoom_speed: 0.2,
             zoom_duration: DEFAULT_ZOOM_DURATION,
-            zoom_speed: 0.2,
             max_resolution: 156543.03392800014 / 8.0,
             min_resolution: 156543.03392800014 / 8.0 / 2.0f64.powi(16),
+            double_click: DoubleClickBehavior::default(),
             rotation_speed: 0.005,
             max_rotation_x: 80f64.to_radians(),
+            kinetic_panning: true,
+            kinetic_friction: 4.0,
         }
     }
 }
 
+/// Action of the [`MapController`] on a double click.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DoubleClickBehavior {
+    /// Zoom in twice, keeping the point under the cursor in place if
+    /// [`MapControlsOptions::zoom_about_cursor`] is set.
+    #[default]
+    ZoomIn,
+    /// Double click is ignored.
+    None,
+}
+
+/// Recent movements of the current drag, used for kinetic panning.
+#[derive(Default)]
+struct DragState {
+    movements: Vec<(SystemTime, Vector2<f64>)>,
+    is_kinetic: bool,
+}
+
 impl UserEventHandler for MapController {
     fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
         match event {
+            UserEvent::ButtonPressed(..) => {
+                let mut drag = self.drag.lock().expect("mutex is poisoned");
+                if drag.is_kinetic {
+                    // Stop the map that is still moving after the previous drag.
+                    drag.is_kinetic = false;
+                    map.set_view(map.view().clone());
+                }
+
+                EventPropagation::Propagate
+            }
             UserEvent::DragStarted(button, _)
                 if *button == MouseButton::Left
                     || *button == MouseButton::Right
                     || *button == MouseButton::Other =>
             {
+                let mut drag = self.drag.lock().expect("mutex is poisoned");
+                drag.movements.clear();
+                drag.is_kinetic = false;
+
                 EventPropagation::Consume
             }
             UserEvent::Drag(button, delta, e) => match button {
@@ -54,6 +137,14 @@ impl UserEventHandler for MapController {
                         map.view()
                             .translate_by_pixels(prev_position, current_position),
                     );
+
+                    let mut drag = self.drag.lock().expect("mutex is poisoned");
+                    let now = SystemTime::now();
+                    drag.movements.push((now, *delta));
+                    drag.movements.retain(|(time, _)| {
+                        now.duration_since(*time).unwrap_or_default() <= VELOCITY_WINDOW
+                    });
+
                     EventPropagation::Stop
                 }
                 MouseButton::Right => {
@@ -62,51 +153,275 @@ impl UserEventHandler for MapController {
                 }
                 _ => EventPropagation::Propagate,
             },
+            UserEvent::DragEnded(button, e)
+                if *button == MouseButton::Left || *button == MouseButton::Other =>
+            {
+                let mut drag = self.drag.lock().expect("mutex is poisoned");
+                let movements = std::mem::take(&mut drag.movements);
+                if !self.options.kinetic_panning {
+                    return EventPropagation::Propagate;
+                }
+
+                if let Some(velocity) = release_velocity(&movements, SystemTime::now()) {
+                    let (target, duration) =
+                        self.kinetic_target(map.view(), e.screen_pointer_position, velocity);
+                    map.animate_to(target, duration, Easing::EaseOut);
+                    drag.is_kinetic = true;
+                }
+
+                EventPropagation::Propagate
+            }
             UserEvent::Scroll(delta, mouse_event) => {
                 let zoom = self.get_zoom(*delta, map.view().resolution());
-                let target = map
-                    .target_view()
-                    .zoom(zoom, mouse_event.screen_pointer_position);
-                map.animate_to(target, self.parameters.zoom_duration);
+                let target = map.target_view();
+                let zoom = constrained_zoom(map, target, zoom);
+                let center = self.zoom_center(target, mouse_event.screen_pointer_position);
+                let target = target.zoom(zoom, center);
+                map.animate_to(target, self.options.zoom_duration, Easing::Linear);
 
                 EventPropagation::Stop
             }
+            UserEvent::DoubleClick(MouseButton::Left, mouse_event) => {
+                match self.options.double_click {
+                    DoubleClickBehavior::ZoomIn => {
+                        let target = map.target_view();
+                        let zoom = constrained_zoom(map, target, 0.5);
+                        let center = self.zoom_center(target, mouse_event.screen_pointer_position);
+                        let target = target.zoom(zoom, center);
+                        map.animate_to(target, DOUBLE_CLICK_ZOOM_DURATION, Easing::EaseOut);
+
+                        EventPropagation::Stop
+                    }
+                    DoubleClickBehavior::None => EventPropagation::Propagate,
+                }
+            }
             UserEvent::Zoom(zoom, center) => {
-                let target = map.view().zoom(*zoom, *center);
+                let zoom = constrained_zoom(map, map.view(), *zoom);
+                let target = map.view().zoom(zoom, *center);
                 map.set_view(target);
 
                 EventPropagation::Stop
             }
+            UserEvent::Rotate(angle, center) => {
+                let target = map.view().rotate(*angle, *center);
+                map.set_view(target);
+
+                EventPropagation::Stop
+            }
+            UserEvent::Tilt(delta) => {
+                let view = map.view();
+                let rotation_x =
+                    self.clamp_rotation_x(view.rotation_x() - delta * self.options.rotation_speed);
+                map.set_view(view.with_rotation_x(rotation_x));
+
+                EventPropagation::Stop
+            }
             _ => EventPropagation::Propagate,
         }
     }
 }
 
+/// Zoom factor limited so that zooming the `view` does not go beyond the resolution range of the map, so the point
+/// under the cursor stays in place when the limit is reached.
+pub(super) fn constrained_zoom(map: &Map, view: &MapView, zoom: f64) -> f64 {
+    let resolution = view.resolution();
+    map.view_constraints().clamp_resolution(resolution * zoom) / resolution
+}
+
+/// Speed of the pointer in pixels per second at the moment of the release, or `None` if the map should not continue
+/// moving.
+fn release_velocity(
+    movements: &[(SystemTime, Vector2<f64>)],
+    now: SystemTime,
+) -> Option<Vector2<f64>> {
+    let (first_time, _) = movements.first()?;
+    let (last_time, _) = movements.last()?;
+    if now.duration_since(*last_time).unwrap_or_default() > MAX_RELEASE_DELAY {
+        return None;
+    }
+
+    // The first movement is done during the time before its event, which is not known.
+    let duration = now.duration_since(*first_time).unwrap_or_default();
+    let distance: Vector2<f64> = movements[1..].iter().map(|(_, delta)| delta).sum();
+    if duration.is_zero() {
+        return None;
+    }
+
+    let velocity = distance / duration.as_secs_f64();
+    (velocity.magnitude() >= MIN_KINETIC_SPEED).then_some(velocity)
+}
+
 impl MapController {
+    /// Creates a new controller with the given options.
+    pub fn new(options: MapControlsOptions) -> Self {
+        Self {
+            options,
+            drag: Default::default(),
+        }
+    }
+
+    /// Options of the controller.
+    pub fn options(&self) -> &MapControlsOptions {
+        &self.options
+    }
+
+    /// Sets the options of the controller.
+    pub fn set_options(&mut self, options: MapControlsOptions) {
+        self.options = options;
+    }
+
     fn get_zoom(&self, delta: f64, current_resolution: f64) -> f64 {
-        let zoom = (self.parameters.zoom_speed + 1.0).powf(-delta);
+        let zoom = (self.options.scroll_zoom_speed + 1.0).powf(-delta);
         let target_resolution = current_resolution * zoom;
-        if target_resolution > self.parameters.max_resolution {
-            self.parameters.max_resolution / current_resolution
-        } else if target_resolution < self.parameters.min_resolution {
-            self.parameters.min_resolution / current_resolution
+        if target_resolution > self.options.max_resolution {
+            self.options.max_resolution / current_resolution
+        } else if target_resolution < self.options.min_resolution {
+            self.options.min_resolution / current_resolution
         } else {
             zoom
         }
     }
 
+    fn zoom_center(&self, view: &MapView, cursor_position: Point2d) -> Point2d {
+        if self.options.zoom_about_cursor {
+            cursor_position
+        } else {
+            let size = view.size();
+            Point2d::new(size.half_width(), size.half_height())
+        }
+    }
+
+    /// Target of the kinetic panning animation and its duration.
+    ///
+    /// With the speed decreasing exponentially, the map moves `v / friction` pixels. The ease out curve has the initial
+    /// speed of `3 * distance / duration`, so the duration is chosen for the animation to start with the speed of the
+    /// pointer.
+    fn kinetic_target(
+        &self,
+        view: &MapView,
+        release_position: Point2d,
+        velocity: Vector2<f64>,
+    ) -> (MapView, Duration) {
+        let friction = self.options.kinetic_friction.max(f64::EPSILON);
+        let target_position = release_position + velocity / friction;
+        let duration = Duration::from_secs_f64(3.0 / friction);
+
+        (
+            view.translate_by_pixels(release_position, target_position),
+            duration,
+        )
+    }
+
     fn get_rotation(&self, curr_view: &MapView, px_delta: Vector2<f64>) -> MapView {
-        let dz = px_delta.x * self.parameters.rotation_speed;
+        let dz = px_delta.x * self.options.rotation_speed;
 
         let rotation_z = curr_view.rotation_z() + dz;
-        let mut rotation_x = curr_view.rotation_x() - px_delta.y * self.parameters.rotation_speed;
-
-        if rotation_x < 0.0 {
-            rotation_x = 0.0;
-        } else if rotation_x > self.parameters.max_rotation_x {
-            rotation_x = self.parameters.max_rotation_x;
-        };
+        let rotation_x = self
+            .clamp_rotation_x(curr_view.rotation_x() - px_delta.y * self.options.rotation_speed);
 
         curr_view.with_rotation(rotation_x, rotation_z)
     }
+
+    fn clamp_rotation_x(&self, rotation_x: f64) -> f64 {
+        rotation_x.clamp(0.0, self.options.max_rotation_x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{MouseButtonsState, MouseEvent};
+    use crate::messenger::DummyMessenger;
+    use galileo_types::cartesian::Size;
+
+    fn test_map() -> Map {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        Map::new(view, vec![], None::<DummyMessenger>)
+    }
+
+    fn mouse(x: f64, y: f64) -> MouseEvent {
+        MouseEvent {
+            screen_pointer_position: Point2d::new(x, y),
+            buttons: MouseButtonsState::default(),
+        }
+    }
+
+    #[test]
+    fn release_velocity_uses_recent_movements() {
+        let now = SystemTime::now();
+        let ms = |ms: u64| now - Duration::from_millis(ms);
+        let movements = [
+            (ms(80), Vector2::new(100.0, 0.0)),
+            (ms(40), Vector2::new(4.0, 0.0)),
+            (ms(0), Vector2::new(4.0, 0.0)),
+        ];
+        let velocity = release_velocity(&movements, now).unwrap();
+        assert!((velocity.x - 100.0).abs() < 1e-6);
+        assert_eq!(velocity.y, 0.0);
+
+        // The pointer stopped before the release.
+        assert_eq!(
+            release_velocity(&movements, now + MAX_RELEASE_DELAY * 2),
+            None
+        );
+        // Slow movements.
+        let slow = [
+            (ms(80), Vector2::new(1.0, 0.0)),
+            (ms(0), Vector2::new(1.0, 0.0)),
+        ];
+        assert_eq!(release_velocity(&slow, now), None);
+        assert_eq!(release_velocity(&[], now), None);
+    }
+
+    #[test]
+    fn kinetic_target_moves_with_pointer() {
+        let controller = MapController::default();
+        let view = test_map().view().clone();
+        let (target, duration) =
+            controller.kinetic_target(&view, Point2d::new(50.0, 50.0), Vector2::new(400.0, 0.0));
+
+        // The map moves right, so the view center moves left by `400 / 4` pixels.
+        let position = target.screen_to_map(Point2d::new(50.0, 50.0)).unwrap();
+        assert!((position.x + 100.0).abs() < 1e-6);
+        assert!(position.y.abs() < 1e-6);
+        assert_eq!(duration, Duration::from_millis(750));
+    }
+
+    #[test]
+    fn zoom_center_and_double_click() {
+        let mut map = test_map();
+        let controller = MapController::default();
+        controller.handle(
+            &UserEvent::DoubleClick(MouseButton::Left, mouse(100.0, 50.0)),
+            &mut map,
+        );
+        let target = map.target_view();
+        assert_eq!(target.resolution(), 0.5);
+        // The point under the cursor stays in place.
+        let position = target.screen_to_map(Point2d::new(100.0, 50.0)).unwrap();
+        assert!((position.x - 50.0).abs() < 1e-6);
+
+        let mut map = test_map();
+        let controller = MapController::new(MapControlsOptions {
+            zoom_about_cursor: false,
+            ..Default::default()
+        });
+        controller.handle(&UserEvent::Scroll(1.0, mouse(100.0, 50.0)), &mut map);
+        let target = map.target_view();
+        assert!(target.resolution() < 1.0);
+        let center = target.screen_to_map(Point2d::new(50.0, 50.0)).unwrap();
+        assert!(center.x.abs() < 1e-6);
+
+        let mut map = test_map();
+        let controller = MapController::new(MapControlsOptions {
+            double_click: DoubleClickBehavior::None,
+            ..Default::default()
+        });
+        controller.handle(
+            &UserEvent::DoubleClick(MouseButton::Left, mouse(100.0, 50.0)),
+            &mut map,
+        );
+        assert_eq!(map.target_view().resolution(), 1.0);
+    }
 }
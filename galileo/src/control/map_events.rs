@@ -0,0 +1,333 @@
+use crate::control::{MouseButton, UserEvent};
+use crate::layer::feature_layer::FeatureId;
+use crate::map::{LayerId, Map};
+use crate::view::MapView;
+use galileo_types::cartesian::Point2d;
+use galileo_types::geo::impls::GeoPoint2d;
+use std::collections::HashSet;
+
+const DEFAULT_PICK_TOLERANCE: f64 = 3.0;
+
+type MapEventHandler = dyn FnMut(&MapEvent, &mut Map);
+
+/// High level event of the map, given to the handlers subscribed with [`EventProcessor::subscribe`].
+///
+/// [`EventProcessor::subscribe`]: crate::control::EventProcessor::subscribe
+#[derive(Debug, Clone)]
+pub enum MapEvent {
+    /// A mouse button was clicked.
+    Click(MouseButton, PointerEvent),
+    /// A double click was done.
+    DoubleClick(MouseButton, PointerEvent),
+    /// The pointer moved over a feature. The feature of the event is always set.
+    HoverEnter(PointerEvent),
+    /// The pointer moved out of the feature, that was hovered before. The feature of the event is always set.
+    HoverLeave(PointerEvent),
+    /// The view of the map has changed, either by the user interaction or by the application (for example, with an
+    /// animation). The value is the new view of the map.
+    ViewChanged(MapView),
+    /// A visible layer of the map has finished loading the data needed to draw the current view.
+    /// See [`Layer::is_loaded`](crate::layer::Layer::is_loaded).
+    LayerLoaded(LayerId),
+}
+
+/// Position of the pointer at the moment of a [`MapEvent`] and the feature under it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerEvent {
+    /// Pointer position on the screen in pixels from the top-left corner.
+    pub screen_position: Point2d,
+    /// Geographic coordinates of the pointer. `None` if the pointer is outside of the map surface, or the CRS of the
+    /// map cannot be projected into geographic coordinates.
+    pub position: Option<GeoPoint2d>,
+    /// The topmost feature under the pointer. See [`Layer::feature_at`](crate::layer::Layer::feature_at).
+    pub feature: Option<FeatureHit>,
+}
+
+/// Feature of a map layer found under the pointer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FeatureHit {
+    /// Id of the layer the feature belongs to.
+    pub layer: LayerId,
+    /// Id of the feature in the layer, e.g. the id in the
+    /// [`FeatureStore`](crate::layer::feature_layer::FeatureStore) of a feature layer.
+    pub feature: FeatureId,
+}
+
+/// Id of a subscription returned by [`EventProcessor::subscribe`](crate::control::EventProcessor::subscribe).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Keeps the list of map event subscriptions and the state needed to produce map events.
+pub(crate) struct MapEventDispatcher {
+    subscriptions: Vec<(SubscriptionId, Box<MapEventHandler>)>,
+    next_id: u64,
+    pick_tolerance: f64,
+    hovered: Option<FeatureHit>,
+    last_view: Option<MapView>,
+    loading_layers: HashSet<LayerId>,
+}
+
+impl Default for MapEventDispatcher {
+    fn default() -> Self {
+        Self {
+            subscriptions: vec![],
+            next_id: 0,
+            pick_tolerance: DEFAULT_PICK_TOLERANCE,
+            hovered: None,
+            last_view: None,
+            loading_layers: HashSet::new(),
+        }
+    }
+}
+
+impl MapEventDispatcher {
+    pub(crate) fn subscribe(
+        &mut self,
+        handler: impl FnMut(&MapEvent, &mut Map) + 'static,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscriptions.push((id, Box::new(handler)));
+        id
+    }
+
+    pub(crate) fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.subscriptions.len();
+        self.subscriptions
+            .retain(|(subscription_id, _)| *subscription_id != id);
+        self.subscriptions.len() != len
+    }
+
+    pub(crate) fn set_pick_tolerance(&mut self, tolerance: f64) {
+        self.pick_tolerance = tolerance;
+    }
+
+    /// Emits the map events produced by the user event.
+    pub(crate) fn handle_user_event(&mut self, event: &UserEvent, map: &mut Map) {
+        if self.subscriptions.is_empty() {
+            return;
+        }
+
+        match event {
+            UserEvent::Click(button, mouse_event) => {
+                let event = self.pointer_event(map, mouse_event.screen_pointer_position);
+                self.dispatch(&MapEvent::Click(*button, event), map);
+            }
+            UserEvent::DoubleClick(button, mouse_event) => {
+                let event = self.pointer_event(map, mouse_event.screen_pointer_position);
+                self.dispatch(&MapEvent::DoubleClick(*button, event), map);
+            }
+            UserEvent::PointerMoved(mouse_event) => {
+                let event = self.pointer_event(map, mouse_event.screen_pointer_position);
+                if event.feature == self.hovered {
+                    return;
+                }
+
+                if let Some(hovered) = self.hovered.take() {
+                    let leave = PointerEvent {
+                        feature: Some(hovered),
+                        ..event.clone()
+                    };
+                    self.dispatch(&MapEvent::HoverLeave(leave), map);
+                }
+
+                if event.feature.is_some() {
+                    self.hovered = event.feature;
+                    self.dispatch(&MapEvent::HoverEnter(event), map);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Emits the events of the changes of the map state: the view change and the layer loading.
+    pub(crate) fn update(&mut self, map: &mut Map) {
+        if self.subscriptions.is_empty() {
+            return;
+        }
+
+        if self.last_view.as_ref() != Some(map.view()) {
+            let is_first = self.last_view.is_none();
+            self.last_view = Some(map.view().clone());
+            if !is_first {
+                self.dispatch(&MapEvent::ViewChanged(map.view().clone()), map);
+            }
+        }
+
+        let mut loaded = vec![];
+        let layers = map.layers();
+        for index in 0..layers.len() {
+            let id = layers.id(index);
+            let is_loaded = !layers.is_visible(index)
+                || layers
+                    .get(index)
+                    .is_some_and(|layer| layer.is_loaded(map.view()));
+            if !is_loaded {
+                self.loading_layers.insert(id);
+            } else if self.loading_layers.remove(&id) {
+                loaded.push(id);
+            }
+        }
+        // Removed layers are not loading anymore.
+        self.loading_layers
+            .retain(|id| layers.index_of(*id).is_some());
+
+        for id in loaded {
+            self.dispatch(&MapEvent::LayerLoaded(id), map);
+        }
+    }
+
+    fn pointer_event(&self, map: &Map, screen_position: Point2d) -> PointerEvent {
+        PointerEvent {
+            screen_position,
+            position: map.view().screen_to_map_geo(screen_position),
+            feature: self.pick(map, screen_position),
+        }
+    }
+
+    fn pick(&self, map: &Map, screen_position: Point2d) -> Option<FeatureHit> {
+        let layers = map.layers();
+        let world_views = map.world_views(map.view());
+        (0..layers.len())
+            .rev()
+            .filter(|index| layers.is_visible(*index))
+            .find_map(|index| {
+                let layer = layers.get(index)?;
+                let feature = world_views.iter().find_map(|view| {
+                    layer.feature_at(view, screen_position, self.pick_tolerance)
+                })?;
+                Some(FeatureHit {
+                    layer: layers.id(index),
+                    feature,
+                })
+            })
+    }
+
+    fn dispatch(&mut self, event: &MapEvent, map: &mut Map) {
+        for (_, handler) in &mut self.subscriptions {
+            handler(event, map);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{EventProcessor, RawUserEvent};
+    use crate::layer::Layer;
+    use crate::messenger::{DummyMessenger, Messenger};
+    use crate::render::Canvas;
+    use galileo_types::cartesian::Size;
+    use std::any::Any;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// Layer with a single feature covering the screen square from (40, 40) to (60, 60).
+    struct SquareLayer(Arc<AtomicBool>);
+
+    impl Layer for SquareLayer {
+        fn render(&self, _view: &MapView, _canvas: &mut dyn Canvas) {}
+
+        fn prepare(&self, _view: &MapView) {}
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn is_loaded(&self, _view: &MapView) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
+
+        fn feature_at(&self, _view: &MapView, point: Point2d, tolerance: f64) -> Option<FeatureId> {
+            let range = (40.0 - tolerance)..=(60.0 + tolerance);
+            (range.contains(&point.x) && range.contains(&point.y)).then_some(FeatureId::new(7))
+        }
+    }
+
+    #[test]
+    fn map_events_are_emitted() {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let mut map = Map::new(view.clone(), vec![], None::<DummyMessenger>);
+        let loaded = Arc::new(AtomicBool::new(false));
+        let layer_id = map.add_layer(SquareLayer(loaded.clone()));
+
+        let events = Rc::new(RefCell::new(vec![]));
+        let recorded = events.clone();
+        let mut processor = EventProcessor::default();
+        let subscription = processor.subscribe(move |event: &MapEvent, _map: &mut Map| {
+            recorded.borrow_mut().push(event.clone())
+        });
+        let take_events = || std::mem::take(&mut *events.borrow_mut());
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(10.0, 10.0)),
+            &mut map,
+        );
+        assert!(take_events().is_empty());
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(50.0, 50.0)),
+            &mut map,
+        );
+        let hit = FeatureHit {
+            layer: layer_id,
+            feature: FeatureId::new(7),
+        };
+        match &take_events()[..] {
+            [MapEvent::HoverEnter(event)] => assert_eq!(event.feature, Some(hit)),
+            other => panic!("unexpected events: {other:?}"),
+        }
+
+        processor.handle(RawUserEvent::ButtonPressed(MouseButton::Left), &mut map);
+        processor.handle(RawUserEvent::ButtonReleased(MouseButton::Left), &mut map);
+        match &take_events()[..] {
+            [MapEvent::Click(MouseButton::Left, event)] => {
+                assert_eq!(event.screen_position, Point2d::new(50.0, 50.0));
+                assert_eq!(
+                    event.position,
+                    view.screen_to_map_geo(Point2d::new(50.0, 50.0))
+                );
+                assert_eq!(event.feature, Some(hit));
+            }
+            other => panic!("unexpected events: {other:?}"),
+        }
+
+        processor.handle(
+            RawUserEvent::PointerMoved(Point2d::new(90.0, 90.0)),
+            &mut map,
+        );
+        match &take_events()[..] {
+            [MapEvent::HoverLeave(event)] => {
+                assert_eq!(event.screen_position, Point2d::new(90.0, 90.0));
+                assert_eq!(event.feature, Some(hit));
+            }
+            other => panic!("unexpected events: {other:?}"),
+        }
+
+        loaded.store(true, Ordering::Relaxed);
+        processor.update(&mut map);
+        assert!(matches!(&take_events()[..], [MapEvent::LayerLoaded(id)] if *id == layer_id));
+
+        map.set_view(view.with_resolution(2.0));
+        processor.update(&mut map);
+        processor.update(&mut map);
+        assert!(
+            matches!(&take_events()[..], [MapEvent::ViewChanged(view)] if view.resolution() == 2.0)
+        );
+
+        assert!(processor.unsubscribe(subscription));
+        assert!(!processor.unsubscribe(subscription));
+        map.set_view(view);
+        processor.update(&mut map);
+        assert!(take_events().is_empty());
+    }
+}
@@ -2,25 +2,37 @@
 //!
 //! User interaction handling is done in several steps:
 //! 1. OS event is converted to a common [`RawUserEvent`] enum. For example, apps that use `winit` can use
-//! [`WinitInputHandler`](crate::winit::WinitInputHandler) to convert [winit::event::WindowEvent] into `RawUserEvent`.
+//!    [`WinitInputHandler`](crate::winit::WinitInputHandler) to convert [winit::event::WindowEvent] into `RawUserEvent`.
 //! 2. `RawUserEvent` is given to the [`EventProcessor`], that converts it into a [`UserEvent`]. `EventProcessor`
-//! keeps track of input state (which keys, modifiers and mouse buttons) are pressed, and provides a more convenient
-//! way to handle user interactions for the application.
+//!    keeps track of input state (which keys, modifiers and mouse buttons) are pressed, and provides a more convenient
+//!    way to handle user interactions for the application.
 //! 3. `EventProcessor` has a list of [`UserEventHandler`]s, which change the state of application based on the events.
 //!
 //! To write a user interaction logic, the app must provide an implementation of [`UserEventHandler`] trait and add it
 //! to the `EventProcessor` handler list.
+//!
+//! For the common interactions it is often enough to [subscribe](EventProcessor::subscribe) to [`MapEvent`]s
+//! instead. These events are given in geographic coordinates and contain the feature under the pointer.
+//!
+//! Drawing and editing of geometries by the user is provided by the [`editor::GeometryEditor`].
 
 use crate::map::Map;
 use galileo_types::cartesian::Point2d;
 use maybe_sync::{MaybeSend, MaybeSync};
 use nalgebra::Vector2;
 
+pub mod editor;
 mod event_processor;
+mod keyboard;
 mod map;
+mod map_events;
+mod swipe;
 
 pub use event_processor::EventProcessor;
-pub use map::MapController;
+pub use keyboard::{KeyboardController, KeyboardControlsOptions};
+pub use map::{DoubleClickBehavior, MapController, MapControlsOptions};
+pub use map_events::{FeatureHit, MapEvent, PointerEvent, SubscriptionId};
+pub use swipe::SwipeController;
 
 /// User input handler.
 pub trait UserEventHandler {
@@ -57,6 +69,10 @@ pub enum RawUserEvent {
     TouchMove(TouchEvent),
     /// Existing touch was released.
     TouchEnd(TouchEvent),
+    /// A keyboard key was pressed. This event is also sent repeatedly while the key is held.
+    KeyPressed(Key),
+    /// A keyboard key was released.
+    KeyReleased(Key),
 }
 
 /// User interaction event. This is the main type that the application would use through [`UserEventHandler`]s.
@@ -93,6 +109,19 @@ pub enum UserEvent {
     /// Zoom is called around a point. This is different from [`UserEvent::Scroll`], as it is not produced by a mouse
     /// but rather by multi-tough gestures. The first parameter is zoom delta value.
     Zoom(f64, Point2d),
+
+    /// Rotation is called around a point by a two-finger touch gesture. The first parameter is the rotation angle in
+    /// radians, counterclockwise (same as [`MapView::rotation_z`](crate::MapView::rotation_z)).
+    Rotate(f64, Point2d),
+
+    /// Tilt is called by a two-finger touch gesture, when both fingers move up or down together. The parameter is the
+    /// vertical movement of the fingers in pixels, negative when moving up.
+    Tilt(f64),
+
+    /// A keyboard key was pressed, or is held.
+    KeyPressed(Key, KeyModifiers),
+    /// A keyboard key was released.
+    KeyReleased(Key, KeyModifiers),
 }
 
 /// Value returned by an [`UserEventHandler`] to indicate the status of the event.
@@ -129,6 +158,52 @@ pub struct MouseEvent {
     pub buttons: MouseButtonsState,
 }
 
+/// Keyboard key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// Left arrow.
+    ArrowLeft,
+    /// Right arrow.
+    ArrowRight,
+    /// Up arrow.
+    ArrowUp,
+    /// Down arrow.
+    ArrowDown,
+    /// Shift modifier key (left or right).
+    Shift,
+    /// Control modifier key (left or right).
+    Control,
+    /// Alt modifier key (left or right).
+    Alt,
+    /// A key producing the given character, taking the keyboard layout and the modifiers into account. For example,
+    /// `Shift` + `=` is `Character('+')` on a US keyboard.
+    Character(char),
+    /// Any other key.
+    Other,
+}
+
+/// State of the keyboard modifier keys at the moment of a keyboard event.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct KeyModifiers {
+    /// Shift key is pressed.
+    pub shift: bool,
+    /// Control key is pressed.
+    pub control: bool,
+    /// Alt key is pressed.
+    pub alt: bool,
+}
+
+impl KeyModifiers {
+    pub(crate) fn set_pressed(&mut self, key: Key, is_pressed: bool) {
+        match key {
+            Key::Shift => self.shift = is_pressed,
+            Key::Control => self.control = is_pressed,
+            Key::Alt => self.alt = is_pressed,
+            _ => {}
+        }
+    }
+}
+
 /// Id of the current touch.
 pub type TouchId = u64;
 
@@ -142,6 +217,46 @@ pub struct TouchEvent {
     pub position: Point2d,
 }
 
+/// Touch gestures recognized by the [`EventProcessor`] and their thresholds. Set with
+/// [`EventProcessor::set_touch_gestures`].
+///
+/// The kind of a two-finger gesture is decided when the touches move far enough from the positions where the
+/// second touch started: changing the distance between the touches starts zooming, turning them around each other
+/// starts rotation, and moving them up or down together starts tilting. Once the gesture is a zoom or rotation, the
+/// other of the two can start when its threshold is reached, but tilt cannot be combined with them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TouchGestures {
+    /// Moving a single touch produces [`UserEvent::Drag`] events.
+    pub pan: bool,
+    /// Changing the distance between two touches produces [`UserEvent::Zoom`] events.
+    pub pinch_zoom: bool,
+    /// Turning two touches around each other produces [`UserEvent::Rotate`] events.
+    pub rotate: bool,
+    /// Moving two touches up or down together produces [`UserEvent::Tilt`] events.
+    pub tilt: bool,
+    /// Change of the distance between the touches in pixels needed to start zooming.
+    pub zoom_threshold: f64,
+    /// Angle in radians the touches must turn to start rotation.
+    pub rotation_threshold: f64,
+    /// Distance in pixels both touches must move vertically to start tilting.
+    pub tilt_threshold: f64,
+}
+
+impl Default for TouchGestures {
+    /// All gestures are enabled.
+    fn default() -> Self {
+        Self {
+            pan: true,
+            pinch_zoom: true,
+            rotate: true,
+            tilt: true,
+            zoom_threshold: 10.0,
+            rotation_threshold: 10f64.to_radians(),
+            tilt_threshold: 10.0,
+        }
+    }
+}
+
 /// State of a mouse button.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum MouseButtonState {
@@ -0,0 +1,142 @@
+use crate::control::{EventPropagation, MouseButton, UserEvent, UserEventHandler};
+use crate::map::{Map, SwipeOrientation};
+use galileo_types::cartesian::Point2d;
+
+/// Event handler that moves the divider of the [swipe comparison mode](crate::Swipe) of the map when the user drags
+/// it with the left mouse button.
+///
+/// The handler must be added to the [`EventProcessor`](crate::control::EventProcessor) before the
+/// [`MapController`](crate::control::MapController), otherwise dragging the divider moves the map instead. Drags that
+/// start away from the divider are given to the next handlers, so the map can still be panned.
+///
+/// ```
+/// use galileo::control::{EventProcessor, MapController, SwipeController};
+///
+/// let mut event_processor = EventProcessor::default();
+/// event_processor.add_handler(SwipeController::new(8.0));
+/// event_processor.add_handler(MapController::default());
+/// ```
+#[derive(Debug)]
+pub struct SwipeController {
+    tolerance: f64,
+}
+
+impl Default for SwipeController {
+    fn default() -> Self {
+        Self::new(6.0)
+    }
+}
+
+impl SwipeController {
+    /// Creates a new controller. A drag is started on the divider if the pointer is not farther than `tolerance`
+    /// pixels from it.
+    pub fn new(tolerance: f64) -> Self {
+        Self { tolerance }
+    }
+
+    /// Distance in pixels from the divider at which the divider can be grabbed.
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    fn is_on_divider(&self, map: &Map, position: Point2d) -> bool {
+        let Some(swipe) = map.swipe() else {
+            return false;
+        };
+
+        let offset = swipe.divider_offset(map.view());
+        let distance = match swipe.orientation() {
+            SwipeOrientation::Vertical => (position.x - offset).abs(),
+            SwipeOrientation::Horizontal => (position.y - offset).abs(),
+        };
+
+        distance <= self.tolerance + swipe.divider_width() / 2.0
+    }
+}
+
+impl UserEventHandler for SwipeController {
+    fn handle(&self, event: &UserEvent, map: &mut Map) -> EventPropagation {
+        match event {
+            UserEvent::DragStarted(MouseButton::Left, e)
+                if self.is_on_divider(map, e.screen_pointer_position) =>
+            {
+                EventPropagation::Consume
+            }
+            UserEvent::Drag(MouseButton::Left, _, e) => {
+                let size = map.view().size();
+                let position = e.screen_pointer_position;
+                if let Some(swipe) = map.swipe_mut() {
+                    let share = match swipe.orientation() {
+                        SwipeOrientation::Vertical => position.x / size.width(),
+                        SwipeOrientation::Horizontal => position.y / size.height(),
+                    };
+                    if share.is_finite() {
+                        swipe.set_position(share);
+                    }
+                }
+
+                EventPropagation::Stop
+            }
+            UserEvent::DragEnded(MouseButton::Left, _) => EventPropagation::Stop,
+            _ => EventPropagation::Propagate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{MouseButtonState, MouseButtonsState, MouseEvent};
+    use crate::map::Swipe;
+    use crate::messenger::DummyMessenger;
+    use crate::view::MapView;
+    use galileo_types::cartesian::Size;
+    use nalgebra::Vector2;
+
+    fn mouse_event(x: f64) -> MouseEvent {
+        MouseEvent {
+            screen_pointer_position: Point2d::new(x, 50.0),
+            buttons: MouseButtonsState {
+                left: MouseButtonState::Pressed,
+                middle: MouseButtonState::Released,
+                right: MouseButtonState::Released,
+            },
+        }
+    }
+
+    #[test]
+    fn drags_divider() {
+        let view =
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(200.0, 100.0));
+        let mut map = Map::new(view, vec![], None::<DummyMessenger>);
+        let controller = SwipeController::default();
+
+        let start = UserEvent::DragStarted(MouseButton::Left, mouse_event(103.0));
+        assert!(matches!(
+            controller.handle(&start, &mut map),
+            EventPropagation::Propagate
+        ));
+
+        map.set_swipe(Some(Swipe::new(vec![], vec![])));
+        let far = UserEvent::DragStarted(MouseButton::Left, mouse_event(150.0));
+        assert!(matches!(
+            controller.handle(&far, &mut map),
+            EventPropagation::Propagate
+        ));
+        assert!(matches!(
+            controller.handle(&start, &mut map),
+            EventPropagation::Consume
+        ));
+
+        let drag = UserEvent::Drag(
+            MouseButton::Left,
+            Vector2::new(-53.0, 0.0),
+            mouse_event(50.0),
+        );
+        assert!(matches!(
+            controller.handle(&drag, &mut map),
+            EventPropagation::Stop
+        ));
+        assert_eq!(map.swipe().unwrap().position(), 0.25);
+    }
+}
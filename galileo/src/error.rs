@@ -1,6 +1,7 @@
 //! Error types used by the crate.
 
 use galileo_mvt::error::GalileoMvtError;
+use std::time::Duration;
 use thiserror::Error;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -21,6 +22,14 @@ pub enum GalileoError {
     /// Item not found.
     #[error("item not found")]
     NotFound,
+    /// The server replied with an unsuccessful HTTP status other than `404 Not Found`.
+    #[error("server replied with status {status}")]
+    Http {
+        /// HTTP status code of the reply.
+        status: u16,
+        /// Delay before the next request requested by the server with the `Retry-After` header.
+        retry_after: Option<Duration>,
+    },
     /// Image decoding error.
     #[cfg(not(target_arch = "wasm32"))]
     #[error("image decode error: {0:?}")]
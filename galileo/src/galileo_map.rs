@@ -1,9 +1,9 @@
-use crate::control::{EventProcessor, EventPropagation, MapController, UserEvent};
+use crate::control::{EventProcessor, EventPropagation, MapController, MapEvent, UserEvent};
 use crate::layer::data_provider::UrlSource;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::layer::Layer;
 use crate::map::Map;
-use crate::render::WgpuRenderer;
+use crate::render::{WgpuRenderer, WgpuRendererOptions};
 use crate::tile_scheme::{TileIndex, TileSchema};
 use crate::view::MapView;
 use crate::winit::{WinitInputHandler, WinitMessenger};
@@ -29,6 +29,7 @@ pub struct GalileoMap {
     window: Arc<Window>,
     map: Arc<RwLock<Map>>,
     backend: Arc<RwLock<Option<WgpuRenderer>>>,
+    renderer_options: WgpuRendererOptions,
     event_processor: EventProcessor,
     input_handler: WinitInputHandler,
     event_loop: EventLoop<()>,
@@ -42,6 +43,7 @@ impl GalileoMap {
             window,
             map,
             backend,
+            renderer_options,
             mut event_processor,
             mut input_handler,
             event_loop,
@@ -60,9 +62,10 @@ impl GalileoMap {
                         crate::async_runtime::spawn(async move {
                             let size = window.inner_size();
 
-                            let mut renderer = WgpuRenderer::new_with_window(
+                            let mut renderer = WgpuRenderer::new_with_window_and_options(
                                 window.clone(),
                                 Size::new(size.width, size.height),
+                                renderer_options,
                             )
                             .await
                             .expect("failed to init renderer");
@@ -130,7 +133,9 @@ impl GalileoMap {
                         }
                     }
                     Event::AboutToWait => {
-                        map.write().expect("lock is poisoned").animate();
+                        let mut map = map.write().expect("lock is poisoned");
+                        map.animate();
+                        event_processor.update(&mut map);
                     }
                     _ => (),
                 }
@@ -144,6 +149,8 @@ type EventHandler = dyn (Fn(&UserEvent, &mut Map) -> EventPropagation);
 #[cfg(not(target_arch = "wasm32"))]
 type EventHandler = dyn (Fn(&UserEvent, &mut Map) -> EventPropagation) + MaybeSend + MaybeSync;
 
+type MapEventHandler = dyn FnMut(&MapEvent, &mut Map);
+
 /// Builder for a [`GalileoMap`].
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub struct MapBuilder {
@@ -152,8 +159,10 @@ pub struct MapBuilder {
     pub(crate) view: Option<MapView>,
     pub(crate) layers: Vec<Box<dyn Layer>>,
     pub(crate) event_handlers: Vec<Box<EventHandler>>,
+    pub(crate) map_event_handlers: Vec<Box<MapEventHandler>>,
     pub(crate) window: Option<Window>,
     pub(crate) event_loop: Option<EventLoop<()>>,
+    pub(crate) renderer_options: WgpuRendererOptions,
 }
 
 impl Default for MapBuilder {
@@ -185,6 +194,7 @@ impl MapBuilder {
         let window = Arc::new(window);
         let messenger = WinitMessenger::new(window.clone());
         let backend = Arc::new(RwLock::new(None));
+        let renderer_options = self.renderer_options;
 
         let input_handler = WinitInputHandler::default();
 
@@ -193,11 +203,15 @@ impl MapBuilder {
             event_processor.add_handler(handler);
         }
         event_processor.add_handler(MapController::default());
+        for handler in self.map_event_handlers.drain(..) {
+            event_processor.subscribe(handler);
+        }
 
         GalileoMap {
             window,
             map: self.build_map(messenger),
             backend,
+            renderer_options,
             event_processor,
             input_handler,
             event_loop,
@@ -210,6 +224,12 @@ impl MapBuilder {
         self
     }
 
+    /// Sets the options of the renderer, e.g. the number of samples for multisample antialiasing.
+    pub fn with_renderer_options(mut self, options: WgpuRendererOptions) -> Self {
+        self.renderer_options = options;
+        self
+    }
+
     /// Use the given event loop instead of creating a default one.
     pub fn with_event_loop(mut self, event_loop: EventLoop<()>) -> Self {
         self.event_loop = Some(event_loop);
@@ -264,6 +284,15 @@ impl MapBuilder {
         self
     }
 
+    /// Subscribe a handler to the map events. See [`EventProcessor::subscribe`].
+    pub fn with_map_event_handler(
+        mut self,
+        handler: impl FnMut(&MapEvent, &mut Map) + 'static,
+    ) -> Self {
+        self.map_event_handlers.push(Box::new(handler));
+        self
+    }
+
     fn build_map(mut self, messenger: WinitMessenger) -> Arc<RwLock<Map>> {
         for layer in self.layers.iter_mut() {
             layer.set_messenger(Box::new(messenger.clone()))
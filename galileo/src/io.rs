@@ -0,0 +1,280 @@
+//! Helpers for reading data files from the file system.
+
+use crate::error::GalileoError;
+use flate2::read::MultiGzDecoder;
+use geojson::{FeatureCollection, GeoJson};
+#[cfg(feature = "geojson-archive")]
+use std::io::Cursor;
+use std::io::Read;
+use std::path::Path;
+#[cfg(feature = "geojson-archive")]
+use zip::ZipArchive;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    None,
+    Gzip,
+    Zip,
+}
+
+impl Compression {
+    fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Self::Gzip
+        } else if bytes.starts_with(ZIP_MAGIC) {
+            Self::Zip
+        } else {
+            Self::None
+        }
+    }
+
+    fn from_extension(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => Self::Gzip,
+            Some(ext) if ext.eq_ignore_ascii_case("zip") => Self::Zip,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Reads a GeoJSON feature collection from a file.
+///
+/// Gzip and zip compressed files are decompressed transparently. Compression is detected by the signature at the
+/// start of the file, so a compressed file is read correctly regardless of its name. A file with a `.gz` or `.zip`
+/// extension that has no corresponding signature is rejected as a corrupt archive. A zip archive must contain a single
+/// file, or a single file with `.geojson` or `.json` extension. Zip archives can be read only with the
+/// `geojson-archive` feature.
+///
+/// A GeoJSON file containing a single feature is read as a collection with that feature.
+///
+/// ```no_run
+/// let collection = galileo::io::read_geojson("data/countries.geojson.gz").unwrap();
+/// println!("Read {} features", collection.features.len());
+/// ```
+pub fn read_geojson(path: impl AsRef<Path>) -> Result<FeatureCollection, GalileoError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+
+    let compression = Compression::detect(&bytes);
+    let expected = Compression::from_extension(path);
+    if expected != Compression::None && expected != compression {
+        return Err(GalileoError::Generic(format!(
+            "{} is not a valid {} archive",
+            path.display(),
+            if expected == Compression::Gzip {
+                "gzip"
+            } else {
+                "zip"
+            }
+        )));
+    }
+
+    let contents = match compression {
+        Compression::None => String::from_utf8(bytes).map_err(|err| {
+            GalileoError::Generic(format!("{} is not a UTF-8 file: {err}", path.display()))
+        })?,
+        Compression::Gzip => {
+            let mut contents = String::new();
+            MultiGzDecoder::new(&bytes[..])
+                .read_to_string(&mut contents)
+                .map_err(|err| {
+                    GalileoError::Generic(format!(
+                        "failed to decompress gzip archive {}: {err}",
+                        path.display()
+                    ))
+                })?;
+            contents
+        }
+        #[cfg(feature = "geojson-archive")]
+        Compression::Zip => read_zip_entry(&bytes).map_err(|err| {
+            GalileoError::Generic(format!(
+                "failed to decompress zip archive {}: {err}",
+                path.display()
+            ))
+        })?,
+        #[cfg(not(feature = "geojson-archive"))]
+        Compression::Zip => {
+            return Err(GalileoError::Generic(format!(
+                "{} is a zip archive, which requires the `geojson-archive` feature",
+                path.display()
+            )))
+        }
+    };
+
+    let geojson: GeoJson = contents.parse().map_err(|err| {
+        GalileoError::Generic(format!(
+            "failed to parse GeoJSON from {}: {err}",
+            path.display()
+        ))
+    })?;
+
+    match geojson {
+        GeoJson::FeatureCollection(collection) => Ok(collection),
+        GeoJson::Feature(feature) => Ok(FeatureCollection {
+            bbox: None,
+            features: vec![feature],
+            foreign_members: None,
+        }),
+        GeoJson::Geometry(_) => Err(GalileoError::Generic(format!(
+            "{} contains a geometry instead of features",
+            path.display()
+        ))),
+    }
+}
+
+#[cfg(feature = "geojson-archive")]
+fn read_zip_entry(bytes: &[u8]) -> Result<String, String> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|err| err.to_string())?;
+
+    let files: Vec<String> = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(String::from)
+        .collect();
+    let geojson_files: Vec<String> = files
+        .iter()
+        .filter(|name| {
+            let name = name.to_ascii_lowercase();
+            name.ends_with(".geojson") || name.ends_with(".json")
+        })
+        .cloned()
+        .collect();
+
+    let name = match (&files[..], &geojson_files[..]) {
+        ([single], _) | (_, [single]) => single.clone(),
+        ([], _) => return Err("archive is empty".into()),
+        (_, []) => {
+            return Err("archive contains several files, none of which is a GeoJSON file".into())
+        }
+        _ => return Err("archive contains more than one GeoJSON file".into()),
+    };
+
+    let mut contents = String::new();
+    archive
+        .by_name(&name)
+        .map_err(|err| err.to_string())?
+        .read_to_string(&mut contents)
+        .map_err(|err| err.to_string())?;
+
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    const COLLECTION: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {"type": "Feature", "geometry": {"type": "Point", "coordinates": [1.0, 2.0]}, "properties": {"a": 1}},
+            {"type": "Feature", "geometry": {"type": "Point", "coordinates": [3.0, 4.0]}, "properties": null}
+        ]
+    }"#;
+
+    fn write_temp(name: &str, bytes: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("galileo_io_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[cfg(feature = "geojson-archive")]
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(vec![]));
+        for (name, contents) in files {
+            writer
+                .start_file(*name, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_plain_file() {
+        let path = write_temp("plain.geojson", COLLECTION.as_bytes());
+        assert_eq!(read_geojson(path).unwrap().features.len(), 2);
+    }
+
+    #[test]
+    fn reads_gzip_by_signature() {
+        let compressed = gzip(COLLECTION.as_bytes());
+
+        let path = write_temp("collection.geojson.gz", &compressed);
+        assert_eq!(read_geojson(path).unwrap().features.len(), 2);
+
+        let path = write_temp("misnamed_gzip.geojson", &compressed);
+        assert_eq!(read_geojson(path).unwrap().features.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "geojson-archive")]
+    fn reads_zip() {
+        let path = write_temp(
+            "collection.zip",
+            &zip(&[("readme.txt", "data"), ("data.geojson", COLLECTION)]),
+        );
+        assert_eq!(read_geojson(path).unwrap().features.len(), 2);
+
+        let path = write_temp(
+            "two_files.zip",
+            &zip(&[("a.geojson", COLLECTION), ("b.geojson", COLLECTION)]),
+        );
+        let Err(GalileoError::Generic(message)) = read_geojson(&path) else {
+            panic!("archive with two GeoJSON files must not be read");
+        };
+        assert!(message.contains("more than one GeoJSON file"));
+
+        let path = write_temp(
+            "no_geojson.zip",
+            &zip(&[("readme.txt", "data"), ("data.csv", "a,b")]),
+        );
+        let Err(GalileoError::Generic(message)) = read_geojson(&path) else {
+            panic!("archive without GeoJSON files must not be read");
+        };
+        assert!(message.contains("none of which is a GeoJSON file"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "geojson-archive"))]
+    fn zip_requires_feature() {
+        let path = write_temp("collection.zip", b"PK\x03\x04");
+        let Err(GalileoError::Generic(message)) = read_geojson(&path) else {
+            panic!("zip archives must not be read without the feature");
+        };
+        assert!(message.contains("geojson-archive"));
+    }
+
+    #[test]
+    fn truncated_gzip_is_an_error() {
+        let compressed = gzip(COLLECTION.as_bytes());
+        let path = write_temp("truncated.geojson.gz", &compressed[..compressed.len() / 2]);
+
+        let Err(GalileoError::Generic(message)) = read_geojson(&path) else {
+            panic!("truncated archive must not be read");
+        };
+        assert!(message.contains("failed to decompress gzip archive"));
+    }
+
+    #[test]
+    fn extension_without_signature_is_an_error() {
+        let path = write_temp("not_gzip.geojson.gz", COLLECTION.as_bytes());
+        let Err(GalileoError::Generic(message)) = read_geojson(&path) else {
+            panic!("file is not an archive");
+        };
+        assert!(message.contains("is not a valid gzip archive"));
+    }
+}
@@ -0,0 +1,313 @@
+//! [`CogLayer`] draws Cloud-Optimized GeoTIFF images.
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::{CogSource, CogTile, DataProvider};
+use crate::layer::{ColorRamp, Layer, RasterTileLayer, TileProgress};
+use crate::messenger::Messenger;
+use crate::render::{Canvas, ColorAdjustment};
+use crate::tile_scheme::{PrefetchPolicy, TileIndex};
+use crate::view::MapView;
+use crate::Color;
+use bytes::Bytes;
+use galileo_types::cartesian::Rect;
+use maybe_sync::MaybeSend;
+use std::any::Any;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use web_time::Duration;
+
+/// COG layer draws a [Cloud-Optimized GeoTIFF](https://cogeo.org/) image read from a [`CogSource`].
+///
+/// The full resolution image and every overview of the file become z-levels of the tile schema of the layer, so only
+/// the tiles of the overview closest to the resolution of the map are read, with separate range requests. The
+/// samples of the tiles are converted into colors as set by the [`CogRendering`] of the layer.
+///
+/// ```no_run
+/// use galileo::layer::data_provider::CogSource;
+/// use galileo::layer::{CogLayer, CogOptions, CogRendering, ColorRamp};
+/// use galileo::Color;
+///
+/// # async fn run() -> Result<(), galileo::error::GalileoError> {
+/// let source = CogSource::open_url("https://example.com/elevation.tif").await?;
+/// let layer = CogLayer::new(source, None)?.with_options(CogOptions {
+///     rendering: CogRendering::ColorRamp {
+///         band: 0,
+///         color_ramp: ColorRamp::new([(0.0, Color::BLUE), (1000.0, Color::RED)]),
+///     },
+///     nodata: Some(-9999.0),
+/// });
+/// # Ok(())
+/// # }
+/// ```
+pub struct CogLayer {
+    tiles: RasterTileLayer<CogProvider>,
+    options: Arc<RwLock<CogOptions>>,
+    bounds: Rect,
+}
+
+/// The way the samples of a COG image are converted into colors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CogRendering {
+    /// The first three bands are red, green and blue channels, and the fourth band (if present) is the alpha
+    /// channel. Images with one or two bands are drawn in grayscale, with the second band as the alpha channel.
+    ///
+    /// Sample values are expected to be in the range from `0` to `255`, larger values are clamped.
+    Rgb,
+    /// Values of a single band are converted into colors with a color ramp. The stops of the ramp are set in the
+    /// units of the sample values.
+    ColorRamp {
+        /// Index of the band, starting from `0`.
+        band: usize,
+        /// Colors of the sample values.
+        color_ramp: ColorRamp,
+    },
+}
+
+/// Configuration of a [`CogLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CogOptions {
+    /// Conversion of the samples into colors.
+    pub rendering: CogRendering,
+    /// Pixels with this value are not drawn. With [`CogRendering::Rgb`] all color bands of a pixel must have this
+    /// value.
+    pub nodata: Option<f64>,
+}
+
+impl Default for CogOptions {
+    /// RGB rendering without nodata value.
+    fn default() -> Self {
+        Self {
+            rendering: CogRendering::Rgb,
+            nodata: None,
+        }
+    }
+}
+
+impl CogLayer {
+    /// Creates a new layer drawing the `source` image with RGB rendering and the nodata value of the image.
+    ///
+    /// Returns an error if the CRS of the image is not known. In this case set it with [`CogSource::with_crs`].
+    pub fn new(
+        source: CogSource,
+        messenger: Option<Arc<dyn Messenger>>,
+    ) -> Result<Self, GalileoError> {
+        let tile_schema = source.tile_schema()?;
+        let bounds = source.bounds();
+        let options = Arc::new(RwLock::new(CogOptions {
+            nodata: source.nodata(),
+            ..Default::default()
+        }));
+        let provider = CogProvider {
+            source,
+            options: options.clone(),
+        };
+
+        Ok(Self {
+            tiles: RasterTileLayer::new(tile_schema, provider, messenger),
+            options,
+            bounds,
+        })
+    }
+
+    /// Sets the options of the layer. The options are applied to the tiles loaded after the call.
+    pub fn with_options(self, options: CogOptions) -> Self {
+        *self.options.write().expect("lock is poisoned") = options;
+        self
+    }
+
+    /// Options of the layer.
+    pub fn options(&self) -> CogOptions {
+        self.options.read().expect("lock is poisoned").clone()
+    }
+
+    /// Area covered by the image in its CRS.
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Sets fade in duration for newly loaded tiles.
+    pub fn set_fade_in_duration(&mut self, duration: Duration) {
+        self.tiles.set_fade_in_duration(duration);
+    }
+
+    /// Sets which tiles outside of the visible area are loaded in advance. See [`RasterTileLayer::set_prefetch`].
+    pub fn set_prefetch(&mut self, policy: PrefetchPolicy) {
+        self.tiles.set_prefetch(policy);
+    }
+
+    /// Sets the limits of the in-memory tile cache. See [`RasterTileLayer::set_memory_cache_limits`].
+    pub fn set_memory_cache_limits(&mut self, max_entries: usize, max_bytes: u64) {
+        self.tiles.set_memory_cache_limits(max_entries, max_bytes);
+    }
+
+    /// Sets the color adjustment of the image. See [`RasterTileLayer::set_color_adjustment`].
+    pub fn set_color_adjustment(&mut self, color_adjustment: ColorAdjustment) {
+        self.tiles.set_color_adjustment(color_adjustment);
+    }
+
+    /// Loads all the tiles needed to draw the `view`. See [`RasterTileLayer::load_tiles`].
+    pub async fn load_tiles(&self, view: &MapView) -> usize {
+        self.tiles.load_tiles(view).await
+    }
+
+    /// Same as [`CogLayer::load_tiles`], but reports the progress of loading to the `on_progress` callback.
+    pub async fn load_tiles_with_progress(
+        &self,
+        view: &MapView,
+        on_progress: impl FnMut(TileProgress),
+    ) -> TileProgress {
+        self.tiles.load_tiles_with_progress(view, on_progress).await
+    }
+}
+
+impl Layer for CogLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        self.tiles.render(view, canvas);
+    }
+
+    fn prepare(&self, view: &MapView) {
+        self.tiles.prepare(view);
+    }
+
+    fn is_loaded(&self, view: &MapView) -> bool {
+        self.tiles.is_loaded(view)
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.tiles.set_messenger(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Reads the tiles of a COG and converts them into images.
+struct CogProvider {
+    source: CogSource,
+    options: Arc<RwLock<CogOptions>>,
+}
+
+impl DataProvider<TileIndex, DecodedImage, ()> for CogProvider {
+    async fn load_raw(&self, _key: &TileIndex) -> Result<Bytes, GalileoError> {
+        Err(GalileoError::Generic(
+            "tiles of a GeoTIFF image can only be loaded decoded".into(),
+        ))
+    }
+
+    fn decode(&self, _bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+        // Compressed tiles cannot be decoded without the parameters of the image, which are not in the tile data.
+        Err(GalileoError::Generic(
+            "tiles of a GeoTIFF image can only be loaded with a tile index".into(),
+        ))
+    }
+
+    fn load(
+        &self,
+        key: &TileIndex,
+        _context: (),
+    ) -> impl Future<Output = Result<DecodedImage, GalileoError>> + MaybeSend {
+        let key = *key;
+        async move {
+            let tile = self.source.read_tile(key.x, key.y, key.z).await?;
+            let options = self.options.read().expect("lock is poisoned").clone();
+            Ok(render_tile(&tile, &options))
+        }
+    }
+}
+
+/// Converts the samples of the tile into colors. Pixels outside of the image are transparent.
+fn render_tile(tile: &CogTile, options: &CogOptions) -> DecodedImage {
+    let is_nodata = |value: f64| options.nodata == Some(value) || value.is_nan();
+    let channel = |value: f64| value.clamp(0.0, 255.0) as u8;
+
+    let mut bytes = Vec::with_capacity(tile.width as usize * tile.height as usize * 4);
+    for (i, pixel) in tile.values.chunks_exact(tile.bands).enumerate() {
+        let x = i as u32 % tile.width;
+        let y = i as u32 / tile.width;
+        if x >= tile.valid_size.0 || y >= tile.valid_size.1 {
+            bytes.extend_from_slice(&Color::TRANSPARENT.to_u8_array());
+            continue;
+        }
+
+        let color = match &options.rendering {
+            CogRendering::Rgb => {
+                let (colors, alpha) = match pixel.len() {
+                    1 | 2 => (&pixel[..1], pixel.get(1)),
+                    _ => (&pixel[..3], pixel.get(3)),
+                };
+                if colors.iter().all(|value| is_nodata(*value)) {
+                    Color::TRANSPARENT
+                } else {
+                    let [r, g, b] = match colors {
+                        [gray] => [*gray; 3],
+                        _ => [colors[0], colors[1], colors[2]],
+                    };
+                    Color::rgba(
+                        channel(r),
+                        channel(g),
+                        channel(b),
+                        alpha.map_or(255, |alpha| channel(*alpha)),
+                    )
+                }
+            }
+            CogRendering::ColorRamp { band, color_ramp } => match pixel.get(*band) {
+                Some(value) if !is_nodata(*value) => color_ramp.color_at(*value as f32),
+                _ => Color::TRANSPARENT,
+            },
+        };
+        bytes.extend_from_slice(&color.to_u8_array());
+    }
+
+    DecodedImage {
+        bytes,
+        dimensions: (tile.width, tile.height),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_are_colored_by_rendering() {
+        let tile = CogTile {
+            width: 2,
+            height: 2,
+            bands: 1,
+            values: vec![0.0, 100.0, -1.0, 50.0],
+            valid_size: (2, 1),
+        };
+        let colors = |options: &CogOptions| {
+            render_tile(&tile, options)
+                .bytes
+                .chunks_exact(4)
+                .map(|pixel| Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3]))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            colors(&CogOptions::default()),
+            vec![
+                Color::BLACK,
+                Color::rgba(100, 100, 100, 255),
+                Color::TRANSPARENT,
+                Color::TRANSPARENT
+            ]
+        );
+
+        let options = CogOptions {
+            rendering: CogRendering::ColorRamp {
+                band: 0,
+                color_ramp: ColorRamp::new([(0.0, Color::BLACK), (100.0, Color::WHITE)]),
+            },
+            nodata: Some(0.0),
+        };
+        assert_eq!(colors(&options)[..2], [Color::TRANSPARENT, Color::WHITE]);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::{Canvas, WgpuFrame};
+use crate::view::MapView;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::any::Any;
+
+/// Contents of a [`CustomLayer`], drawn with the application's own wgpu pipelines.
+///
+/// This can be used for effects that cannot be expressed with symbols, like particle animations or wind fields. The
+/// layer gets the device, the queue and the render target of the [`WgpuRenderer`](crate::render::WgpuRenderer),
+/// together with the view transform of the map, through [`WgpuFrame`]. The pipelines should be created with the
+/// layouts and formats given by the frame, and they can be cached between frames, since the device of a renderer
+/// does not change.
+///
+/// Use the [`wgpu`](crate::wgpu) crate re-exported by galileo, so that the versions of the types match.
+pub trait CustomRenderLayer: MaybeSend + MaybeSync {
+    /// Draws the layer to the frame.
+    fn render(&self, view: &MapView, frame: &mut WgpuFrame);
+
+    /// Prepares the layer for rendering with the given `view`. Does nothing by default.
+    fn prepare(&self, _view: &MapView) {}
+}
+
+/// Layer that draws a [`CustomRenderLayer`].
+///
+/// The layer is drawn only by the renderers giving access to wgpu, and is skipped by other canvases, e.g. by
+/// [`SvgRenderer`](crate::render::SvgRenderer).
+///
+/// Animated layers can request redraws of the map with [`CustomLayer::request_redraw`].
+pub struct CustomLayer<T> {
+    inner: T,
+    messenger: Option<Box<dyn Messenger>>,
+}
+
+impl<T: CustomRenderLayer> CustomLayer<T> {
+    /// Creates a new layer.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            messenger: None,
+        }
+    }
+
+    /// Returns the contents of the layer.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the contents of the layer. Call [`CustomLayer::request_redraw`] after
+    /// modifying it to update the map.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Notifies the application that the map should be redrawn.
+    pub fn request_redraw(&self) {
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+}
+
+impl<T: CustomRenderLayer + 'static> Layer for CustomLayer<T> {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if let Some(mut frame) = canvas.wgpu_frame() {
+            self.inner.render(view, &mut frame);
+        }
+    }
+
+    fn prepare(&self, view: &MapView) {
+        self.inner.prepare(view);
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.messenger = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{SvgRenderer, WgpuRenderer};
+    use crate::Map;
+    use galileo_types::cartesian::{Point2d, Size};
+    use std::sync::Mutex;
+
+    /// Fills the target with red color, remembering the parameters of the frame.
+    #[derive(Default)]
+    struct FillLayer {
+        frame: Mutex<Option<(Size<u32>, u32)>>,
+    }
+
+    impl CustomRenderLayer for FillLayer {
+        fn render(&self, _view: &MapView, frame: &mut WgpuFrame) {
+            *self.frame.lock().expect("mutex is poisoned") =
+                Some((frame.target_size(), frame.sample_count()));
+
+            let mut encoder = frame.device().create_command_encoder(&Default::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.target_view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::RED),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            frame.queue().submit(Some(encoder.finish()));
+        }
+    }
+
+    fn test_map() -> Map {
+        let mut map = Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(40.0, 30.0)),
+            vec![Box::new(CustomLayer::new(FillLayer::default()))],
+            None::<crate::messenger::DummyMessenger>,
+        );
+        map.layers_mut().set_opacity(0, 0.5);
+        map
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn custom_layer_draws_to_wgpu_target() {
+        let renderer = tokio_test::block_on(WgpuRenderer::new_with_texture_rt(Size::new(40, 30)))
+            .expect("no graphics adapter is available");
+
+        let map = test_map();
+        renderer.render(&map).unwrap();
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+        // The layer draws opaque red, and the renderer composites it over the white background with half opacity.
+        assert!(image
+            .chunks(4)
+            .all(|p| p[0] == 255 && (150..230).contains(&p[1]) && p[1] == p[2]));
+
+        let layer = map.layers()[0]
+            .as_any()
+            .downcast_ref::<CustomLayer<FillLayer>>()
+            .unwrap();
+        let frame = *layer.inner().frame.lock().expect("mutex is poisoned");
+        assert_eq!(frame, Some((Size::new(40, 30), renderer.sample_count())));
+    }
+
+    #[test]
+    fn custom_layer_is_skipped_by_other_canvases() {
+        let map = test_map();
+        let svg = SvgRenderer::new().render(&map);
+        assert!(svg.contains(r#"<g opacity="0.5"></g>"#));
+
+        let layer = map.layers()[0]
+            .as_any()
+            .downcast_ref::<CustomLayer<FillLayer>>()
+            .unwrap();
+        assert!(layer
+            .inner()
+            .frame
+            .lock()
+            .expect("mutex is poisoned")
+            .is_none());
+    }
+}
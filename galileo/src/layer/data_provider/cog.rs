@@ -0,0 +1,1295 @@
+//! Reading tiles of [Cloud-Optimized GeoTIFF](https://cogeo.org/) images.
+
+use crate::error::GalileoError;
+use crate::layer::data_provider::range_reader::RangeReader;
+use crate::tile_scheme::{TileMatrix, TileSchema, VerticalDirection};
+use bytes::Bytes;
+use flate2::read::ZlibDecoder;
+use galileo_types::cartesian::{Point2d, Rect};
+use galileo_types::geo::Crs;
+use std::io::Read;
+use std::path::Path;
+
+/// Size of the first request to the file. Writers of COGs place all the IFDs at the start of the file, so in most
+/// cases the whole structure of the image is read with it.
+const HEADER_CHUNK: u64 = 64 * 1024;
+/// Limit for the number of IFDs, protecting against loops in corrupted files.
+const MAX_IFDS: usize = 64;
+/// Limit for the number of entries in one IFD.
+const MAX_IFD_ENTRIES: u64 = 4096;
+/// Limit for the width and height of a tile.
+const MAX_TILE_SIZE: u32 = 4096;
+/// Limit for the size of a tile, both as stored in the file and decompressed, and of a value of an IFD entry.
+const MAX_DATA_LENGTH: u64 = 256 * 1024 * 1024;
+
+const TAG_NEW_SUBFILE_TYPE: u16 = 254;
+const TAG_IMAGE_WIDTH: u16 = 256;
+const TAG_IMAGE_LENGTH: u16 = 257;
+const TAG_BITS_PER_SAMPLE: u16 = 258;
+const TAG_COMPRESSION: u16 = 259;
+const TAG_SAMPLES_PER_PIXEL: u16 = 277;
+const TAG_PLANAR_CONFIGURATION: u16 = 284;
+const TAG_PREDICTOR: u16 = 317;
+const TAG_TILE_WIDTH: u16 = 322;
+const TAG_TILE_LENGTH: u16 = 323;
+const TAG_TILE_OFFSETS: u16 = 324;
+const TAG_TILE_BYTE_COUNTS: u16 = 325;
+const TAG_SAMPLE_FORMAT: u16 = 339;
+const TAG_JPEG_TABLES: u16 = 347;
+const TAG_MODEL_PIXEL_SCALE: u16 = 33550;
+const TAG_MODEL_TIEPOINT: u16 = 33922;
+const TAG_MODEL_TRANSFORMATION: u16 = 34264;
+const TAG_GEO_KEY_DIRECTORY: u16 = 34735;
+const TAG_GDAL_NODATA: u16 = 42113;
+
+const GEO_KEY_GEOGRAPHIC_TYPE: u16 = 2048;
+const GEO_KEY_PROJECTED_CS_TYPE: u16 = 3072;
+
+/// Compression of the tiles of a GeoTIFF image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CogCompression {
+    /// Tiles are not compressed.
+    None,
+    /// LZW compression.
+    Lzw,
+    /// Deflate (zlib) compression.
+    Deflate,
+    /// JPEG compression.
+    Jpeg,
+}
+
+impl CogCompression {
+    fn from_tag(value: u64) -> Result<Self, GalileoError> {
+        match value {
+            1 => Ok(Self::None),
+            5 => Ok(Self::Lzw),
+            7 => Ok(Self::Jpeg),
+            8 | 32946 => Ok(Self::Deflate),
+            _ => Err(tiff_error(format!("unsupported compression {value}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SampleFormat {
+    Unsigned,
+    Signed,
+    Float,
+}
+
+/// Parameters of the tile data of a level of the image.
+#[derive(Debug, Clone, PartialEq)]
+struct DataFormat {
+    byte_order: ByteOrder,
+    compression: CogCompression,
+    predictor: u16,
+    bands: usize,
+    bits_per_sample: u16,
+    sample_format: SampleFormat,
+    jpeg_tables: Option<Bytes>,
+}
+
+impl DataFormat {
+    fn from_ifd(ifd: &Ifd, byte_order: ByteOrder) -> Result<Self, GalileoError> {
+        let format = Self {
+            byte_order,
+            compression: CogCompression::from_tag(ifd.number(TAG_COMPRESSION).unwrap_or(1))?,
+            predictor: ifd.number(TAG_PREDICTOR).unwrap_or(1) as u16,
+            bands: ifd.number(TAG_SAMPLES_PER_PIXEL).unwrap_or(1) as usize,
+            bits_per_sample: ifd.number(TAG_BITS_PER_SAMPLE).unwrap_or(1) as u16,
+            sample_format: match ifd.number(TAG_SAMPLE_FORMAT).unwrap_or(1) {
+                1 => SampleFormat::Unsigned,
+                2 => SampleFormat::Signed,
+                3 => SampleFormat::Float,
+                value => return Err(tiff_error(format!("unsupported sample format {value}"))),
+            },
+            jpeg_tables: ifd.bytes(TAG_JPEG_TABLES),
+        };
+
+        if ifd.number(TAG_PLANAR_CONFIGURATION).unwrap_or(1) != 1 {
+            return Err(tiff_error("only chunky planar configuration is supported"));
+        }
+        if !matches!(format.bits_per_sample, 8 | 16 | 32 | 64) || format.bands == 0 {
+            return Err(tiff_error(format!(
+                "unsupported {} bits per sample",
+                format.bits_per_sample
+            )));
+        }
+        if !matches!(format.predictor, 1..=3) {
+            return Err(tiff_error(format!(
+                "unsupported predictor {}",
+                format.predictor
+            )));
+        }
+
+        Ok(format)
+    }
+
+    /// Size in bytes of a decompressed tile, if it is not over the [`MAX_DATA_LENGTH`].
+    fn tile_len(&self, tile_width: u32, tile_height: u32) -> Option<usize> {
+        let len = (tile_width as usize)
+            .checked_mul(tile_height as usize)?
+            .checked_mul(self.bands)?
+            .checked_mul(self.bits_per_sample as usize / 8)?;
+        (len as u64 <= MAX_DATA_LENGTH).then_some(len)
+    }
+}
+
+/// Full resolution image or one of its overviews.
+#[derive(Debug, Clone)]
+struct CogLevel {
+    format: DataFormat,
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    tile_offsets: Vec<u64>,
+    tile_byte_counts: Vec<u64>,
+}
+
+impl CogLevel {
+    fn tiles_across(&self) -> u32 {
+        self.width.div_ceil(self.tile_width)
+    }
+
+    fn tiles_down(&self) -> u32 {
+        self.height.div_ceil(self.tile_height)
+    }
+}
+
+/// Decoded samples of a tile of a COG, with the pixels outside of the image marked as missing.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CogTile {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) bands: usize,
+    /// Sample values of the pixels row by row, `bands` values for each pixel.
+    pub(crate) values: Vec<f64>,
+    /// Number of columns and rows of the tile that are inside the image.
+    pub(crate) valid_size: (u32, u32),
+}
+
+/// A Cloud-Optimized GeoTIFF image stored in a local file or on an HTTP server supporting range requests.
+///
+/// Only the header of the image is read when it is opened. Tiles of the full resolution image and its overviews are
+/// read with separate range requests when they are needed, so large images can be displayed without downloading
+/// them. The image is displayed with a [`CogLayer`](crate::layer::CogLayer).
+///
+/// Supported are tiled images with chunky (pixel interleaved) samples of 8, 16, 32 or 64 bits, compressed with
+/// deflate, LZW or JPEG, or not compressed. The image must be georeferenced with a pixel scale and a tie point or a
+/// transformation without rotation.
+pub struct CogSource {
+    backend: RangeReader,
+    /// Levels from the full resolution image to the smallest overview.
+    levels: Vec<CogLevel>,
+    origin: Point2d,
+    pixel_size: f64,
+    crs: Option<Crs>,
+    nodata: Option<f64>,
+}
+
+impl CogSource {
+    /// Opens an image stored in a local file.
+    pub async fn open_file(path: impl AsRef<Path>) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::file(path)?).await
+    }
+
+    /// Opens an image at the given URL. The server must support HTTP range requests.
+    pub async fn open_url(url: impl Into<String>) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::url(url, None)).await
+    }
+
+    /// Opens an image at the given URL, making all the requests with the given HTTP client.
+    pub async fn open_url_with_http_client(
+        url: impl Into<String>,
+        client: reqwest::Client,
+    ) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::url(url, Some(client))).await
+    }
+
+    async fn open(backend: RangeReader) -> Result<Self, GalileoError> {
+        let reader = TiffReader::new(backend).await?;
+        let mut ifds = vec![];
+        let mut next = reader.first_ifd;
+        while next != 0 && ifds.len() < MAX_IFDS {
+            let (ifd, next_ifd) = reader.read_ifd(next).await?;
+            ifds.push(ifd);
+            next = next_ifd;
+        }
+
+        let full = ifds
+            .first()
+            .ok_or_else(|| tiff_error("the file has no images"))?;
+        let (origin, pixel_size) = georeference(full)?;
+        let crs = full
+            .geo_key_directory()
+            .and_then(|keys| crs_from_geo_keys(&keys));
+        let nodata = full
+            .ascii(TAG_GDAL_NODATA)
+            .and_then(|value| value.trim().parse().ok());
+
+        let mut levels = vec![];
+        for (i, ifd) in ifds.iter().enumerate() {
+            let subfile_type = ifd.number(TAG_NEW_SUBFILE_TYPE).unwrap_or(0);
+            let is_mask = subfile_type & 4 != 0;
+            if is_mask || (i > 0 && subfile_type & 1 == 0) {
+                continue;
+            }
+
+            // Overviews in an unsupported format are not used, but the full resolution image is required.
+            let format = match DataFormat::from_ifd(ifd, reader.byte_order) {
+                Ok(format) => format,
+                Err(err) if i == 0 => return Err(err),
+                Err(_) => continue,
+            };
+            if levels
+                .first()
+                .is_some_and(|full: &CogLevel| full.format.bands != format.bands)
+            {
+                continue;
+            }
+
+            let level = CogLevel {
+                format,
+                width: ifd.required(TAG_IMAGE_WIDTH)? as u32,
+                height: ifd.required(TAG_IMAGE_LENGTH)? as u32,
+                tile_width: ifd
+                    .number(TAG_TILE_WIDTH)
+                    .ok_or_else(|| tiff_error("the image is not tiled"))?
+                    as u32,
+                tile_height: ifd.required(TAG_TILE_LENGTH)? as u32,
+                tile_offsets: ifd.numbers(TAG_TILE_OFFSETS).unwrap_or_default(),
+                tile_byte_counts: ifd.numbers(TAG_TILE_BYTE_COUNTS).unwrap_or_default(),
+            };
+            if level.width == 0
+                || level.height == 0
+                || level.tile_width == 0
+                || level.tile_height == 0
+            {
+                return Err(tiff_error("invalid tile structure"));
+            }
+            if level.tile_width > MAX_TILE_SIZE
+                || level.tile_height > MAX_TILE_SIZE
+                || level
+                    .format
+                    .tile_len(level.tile_width, level.tile_height)
+                    .is_none()
+            {
+                return Err(tiff_error(format!(
+                    "tiles of {}x{} pixels are too large",
+                    level.tile_width, level.tile_height
+                )));
+            }
+            let tile_count =
+                (level.tiles_across() as usize).checked_mul(level.tiles_down() as usize);
+            if tile_count.is_none_or(|count| {
+                level.tile_offsets.len() < count || level.tile_byte_counts.len() < count
+            }) {
+                return Err(tiff_error("invalid tile structure"));
+            }
+            levels.push(level);
+        }
+        levels.sort_by_key(|level| std::cmp::Reverse(level.width));
+
+        Ok(Self {
+            backend: reader.backend,
+            levels,
+            origin,
+            pixel_size,
+            crs,
+            nodata,
+        })
+    }
+
+    /// Sets the CRS of the image, replacing the one read from its GeoTIFF keys. Use it for images in CRSs that
+    /// are not recognized automatically (only Web Mercator, WGS84 and WGS84 UTM zones are).
+    pub fn with_crs(mut self, crs: Crs) -> Self {
+        self.crs = Some(crs);
+        self
+    }
+
+    /// CRS of the image, if it is known.
+    pub fn crs(&self) -> Option<&Crs> {
+        self.crs.as_ref()
+    }
+
+    /// Width and height of the full resolution image in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.levels[0].width, self.levels[0].height)
+    }
+
+    /// Number of the bands (samples of every pixel) of the image.
+    pub fn band_count(&self) -> usize {
+        self.levels[0].format.bands
+    }
+
+    /// Number of the overviews of the image, not counting the full resolution image.
+    pub fn overview_count(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Compression of the tiles.
+    pub fn compression(&self) -> CogCompression {
+        self.levels[0].format.compression
+    }
+
+    /// Value of the pixels without data, as set by the `GDAL_NODATA` tag.
+    pub fn nodata(&self) -> Option<f64> {
+        self.nodata
+    }
+
+    /// Area covered by the image in its CRS.
+    pub fn bounds(&self) -> Rect {
+        let (width, height) = self.size();
+        Rect::new(
+            self.origin.x,
+            self.origin.y - height as f64 * self.pixel_size,
+            self.origin.x + width as f64 * self.pixel_size,
+            self.origin.y,
+        )
+    }
+
+    /// Tile schema with a z-level for every overview and the full resolution image, the latter having the highest
+    /// z-level.
+    ///
+    /// Returns an error if the CRS of the image is not known.
+    pub fn tile_schema(&self) -> Result<TileSchema, GalileoError> {
+        let crs = self
+            .crs
+            .clone()
+            .ok_or_else(|| GalileoError::Generic("CRS of the GeoTIFF image is not known".into()))?;
+        let full_width = self.levels[0].width as f64;
+        let max_z = self.levels.len() as u32 - 1;
+        let matrices = self.levels.iter().enumerate().map(|(i, level)| TileMatrix {
+            z: max_z - i as u32,
+            resolution: self.pixel_size * full_width / level.width as f64,
+            origin: self.origin,
+            tile_width: level.tile_width,
+            tile_height: level.tile_height,
+            matrix_width: level.tiles_across(),
+            matrix_height: level.tiles_down(),
+        });
+
+        TileSchema::from_tile_matrices(crs, VerticalDirection::TopToBottom, matrices)
+            .ok_or_else(|| tiff_error("invalid levels of the image"))
+    }
+
+    /// Reads and decodes the tile `(x, y)` of the z-level `z` of the [tile schema](CogSource::tile_schema).
+    pub(crate) async fn read_tile(&self, x: i32, y: i32, z: u32) -> Result<CogTile, GalileoError> {
+        let level = (self.levels.len() as u32)
+            .checked_sub(z + 1)
+            .and_then(|i| self.levels.get(i as usize))
+            .ok_or(GalileoError::NotFound)?;
+        if x < 0 || y < 0 || x as u32 >= level.tiles_across() || y as u32 >= level.tiles_down() {
+            return Err(GalileoError::NotFound);
+        }
+
+        let index = y as usize * level.tiles_across() as usize + x as usize;
+        let (offset, length) = (level.tile_offsets[index], level.tile_byte_counts[index]);
+        if length == 0 {
+            // Sparse files do not store tiles without data.
+            return Err(GalileoError::NotFound);
+        }
+        if length > MAX_DATA_LENGTH {
+            return Err(tiff_error(format!("tile of {length} bytes is too large")));
+        }
+
+        let data = self.backend.read(offset, length).await?;
+        let mut tile = decode_tile(&level.format, data, level.tile_width, level.tile_height)?;
+        tile.valid_size = (
+            (level.width - x as u32 * level.tile_width).min(level.tile_width),
+            (level.height - y as u32 * level.tile_height).min(level.tile_height),
+        );
+        Ok(tile)
+    }
+}
+
+/// Byte order of a TIFF file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteOrder {
+    LittleEndian,
+    BigEndian,
+}
+
+impl ByteOrder {
+    fn read<const N: usize>(&self, bytes: &[u8]) -> [u8; N] {
+        let mut value: [u8; N] = bytes[..N].try_into().expect("slice length is checked");
+        if let ByteOrder::BigEndian = self {
+            value.reverse();
+        }
+        value
+    }
+
+    fn u16(&self, bytes: &[u8]) -> u16 {
+        u16::from_le_bytes(self.read(bytes))
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        u32::from_le_bytes(self.read(bytes))
+    }
+
+    fn u64(&self, bytes: &[u8]) -> u64 {
+        u64::from_le_bytes(self.read(bytes))
+    }
+}
+
+/// Reads the structure of a TIFF file, serving the reads from the first chunk of the file when possible.
+struct TiffReader {
+    backend: RangeReader,
+    head: Bytes,
+    byte_order: ByteOrder,
+    big_tiff: bool,
+    first_ifd: u64,
+}
+
+impl TiffReader {
+    async fn new(backend: RangeReader) -> Result<Self, GalileoError> {
+        // A file shorter than the chunk cannot be read with a single request, so fallback to the header size.
+        let head = match backend.read(0, HEADER_CHUNK).await {
+            Ok(head) => head,
+            Err(_) => backend.read(0, 16).await?,
+        };
+        if head.len() < 8 {
+            return Err(tiff_error("the file is too short"));
+        }
+
+        let byte_order = match &head[0..2] {
+            b"II" => ByteOrder::LittleEndian,
+            b"MM" => ByteOrder::BigEndian,
+            _ => return Err(tiff_error("invalid byte order mark")),
+        };
+        let (big_tiff, first_ifd) = match byte_order.u16(&head[2..]) {
+            42 => (false, byte_order.u32(&head[4..]) as u64),
+            43 if head.len() >= 16 => (true, byte_order.u64(&head[8..])),
+            _ => return Err(tiff_error("not a TIFF file")),
+        };
+
+        Ok(Self {
+            backend,
+            head,
+            byte_order,
+            big_tiff,
+            first_ifd,
+        })
+    }
+
+    async fn read(&self, offset: u64, length: u64) -> Result<Bytes, GalileoError> {
+        let end = offset
+            .checked_add(length)
+            .ok_or_else(|| tiff_error("data offset is out of range"))?;
+        if end <= self.head.len() as u64 {
+            return Ok(self.head.slice(offset as usize..end as usize));
+        }
+        if length > MAX_DATA_LENGTH {
+            return Err(tiff_error(format!("value of {length} bytes is too large")));
+        }
+
+        let bytes = self.backend.read(offset, length).await?;
+        if (bytes.len() as u64) < length {
+            return Err(tiff_error("unexpected end of file"));
+        }
+        Ok(bytes)
+    }
+
+    /// Reads the IFD at the `offset` and returns it with the offset of the next IFD.
+    async fn read_ifd(&self, offset: u64) -> Result<(Ifd, u64), GalileoError> {
+        let order = self.byte_order;
+        let (count_size, entry_size, offset_size) = if self.big_tiff {
+            (8, 20, 8)
+        } else {
+            (2, 12, 4)
+        };
+
+        let count_bytes = self.read(offset, count_size).await?;
+        let count = if self.big_tiff {
+            order.u64(&count_bytes)
+        } else {
+            order.u16(&count_bytes) as u64
+        };
+        if count > MAX_IFD_ENTRIES {
+            return Err(tiff_error(format!("IFD has too many entries ({count})")));
+        }
+        let entries_offset = offset
+            .checked_add(count_size)
+            .ok_or_else(|| tiff_error("IFD offset is out of range"))?;
+        let entries = self
+            .read(entries_offset, count * entry_size + offset_size)
+            .await?;
+
+        let mut ifd = Ifd {
+            byte_order: order,
+            entries: vec![],
+        };
+        for entry in entries
+            .chunks_exact(entry_size as usize)
+            .take(count as usize)
+        {
+            let tag = order.u16(entry);
+            let field_type = order.u16(&entry[2..]);
+            let (value_count, value) = if self.big_tiff {
+                (order.u64(&entry[4..]), &entry[12..20])
+            } else {
+                (order.u32(&entry[4..]) as u64, &entry[8..12])
+            };
+
+            let Some(type_size) = type_size(field_type) else {
+                continue;
+            };
+            let length = type_size
+                .checked_mul(value_count)
+                .ok_or_else(|| tiff_error(format!("invalid value count of tag {tag}")))?;
+            let data = if length <= offset_size {
+                Bytes::copy_from_slice(&value[..length as usize])
+            } else {
+                let value_offset = if self.big_tiff {
+                    order.u64(value)
+                } else {
+                    order.u32(value) as u64
+                };
+                self.read(value_offset, length).await?
+            };
+
+            ifd.entries.push(IfdEntry {
+                tag,
+                field_type,
+                data,
+            });
+        }
+
+        let next = &entries[(count * entry_size) as usize..];
+        let next = if self.big_tiff {
+            order.u64(next)
+        } else {
+            order.u32(next) as u64
+        };
+
+        Ok((ifd, next))
+    }
+}
+
+fn type_size(field_type: u16) -> Option<u64> {
+    match field_type {
+        1 | 2 | 6 | 7 => Some(1),
+        3 | 8 => Some(2),
+        4 | 9 | 11 => Some(4),
+        5 | 10 | 12 | 16 | 17 | 18 => Some(8),
+        _ => None,
+    }
+}
+
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    data: Bytes,
+}
+
+/// Image file directory: tags of a single image of the file.
+struct Ifd {
+    byte_order: ByteOrder,
+    entries: Vec<IfdEntry>,
+}
+
+impl Ifd {
+    fn entry(&self, tag: u16) -> Option<&IfdEntry> {
+        self.entries.iter().find(|entry| entry.tag == tag)
+    }
+
+    /// Values of a numeric tag.
+    fn values(&self, tag: u16) -> Option<Vec<f64>> {
+        let entry = self.entry(tag)?;
+        let order = self.byte_order;
+        let size = type_size(entry.field_type)? as usize;
+        let values = entry
+            .data
+            .chunks_exact(size)
+            .map(|value| match entry.field_type {
+                1 | 7 => value[0] as f64,
+                6 => value[0] as i8 as f64,
+                3 => order.u16(value) as f64,
+                8 => order.u16(value) as i16 as f64,
+                4 => order.u32(value) as f64,
+                9 => order.u32(value) as i32 as f64,
+                11 => f32::from_bits(order.u32(value)) as f64,
+                12 => f64::from_bits(order.u64(value)),
+                16 | 18 => order.u64(value) as f64,
+                17 => order.u64(value) as i64 as f64,
+                5 => order.u32(value) as f64 / order.u32(&value[4..]) as f64,
+                10 => order.u32(value) as i32 as f64 / order.u32(&value[4..]) as i32 as f64,
+                _ => f64::NAN,
+            });
+
+        Some(values.collect())
+    }
+
+    /// Values of an integer tag.
+    fn numbers(&self, tag: u16) -> Option<Vec<u64>> {
+        let entry = self.entry(tag)?;
+        let order = self.byte_order;
+        let size = type_size(entry.field_type)? as usize;
+        entry
+            .data
+            .chunks_exact(size)
+            .map(|value| match entry.field_type {
+                1 | 7 => Some(value[0] as u64),
+                3 => Some(order.u16(value) as u64),
+                4 => Some(order.u32(value) as u64),
+                16 | 18 => Some(order.u64(value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn number(&self, tag: u16) -> Option<u64> {
+        self.numbers(tag)?.first().copied()
+    }
+
+    fn required(&self, tag: u16) -> Result<u64, GalileoError> {
+        self.number(tag)
+            .ok_or_else(|| tiff_error(format!("missing tag {tag}")))
+    }
+
+    fn bytes(&self, tag: u16) -> Option<Bytes> {
+        Some(self.entry(tag)?.data.clone())
+    }
+
+    fn ascii(&self, tag: u16) -> Option<String> {
+        let data = self.bytes(tag)?;
+        let text = data.split(|byte| *byte == 0).next().unwrap_or_default();
+        Some(String::from_utf8_lossy(text).into_owned())
+    }
+
+    /// Pairs of GeoTIFF keys and their values. Only keys with values stored directly in the directory are returned.
+    fn geo_key_directory(&self) -> Option<Vec<(u16, u16)>> {
+        let values = self.numbers(TAG_GEO_KEY_DIRECTORY)?;
+        let count = *values.get(3)? as usize;
+        Some(
+            values[4..]
+                .chunks_exact(4)
+                .take(count)
+                .filter(|key| key[1] == 0)
+                .map(|key| (key[0] as u16, key[3] as u16))
+                .collect(),
+        )
+    }
+}
+
+/// Returns the top left corner of the image and the size of its pixels.
+fn georeference(ifd: &Ifd) -> Result<(Point2d, f64), GalileoError> {
+    let (origin, size_x, size_y) = if let Some(matrix) = ifd.values(TAG_MODEL_TRANSFORMATION) {
+        if matrix.len() < 8 || matrix[1] != 0.0 || matrix[4] != 0.0 {
+            return Err(tiff_error("rotated images are not supported"));
+        }
+        (Point2d::new(matrix[3], matrix[7]), matrix[0], -matrix[5])
+    } else {
+        let scale = ifd
+            .values(TAG_MODEL_PIXEL_SCALE)
+            .filter(|scale| scale.len() >= 2)
+            .ok_or_else(|| tiff_error("the image is not georeferenced"))?;
+        let tiepoint = ifd
+            .values(TAG_MODEL_TIEPOINT)
+            .filter(|tiepoint| tiepoint.len() >= 6)
+            .ok_or_else(|| tiff_error("the image is not georeferenced"))?;
+        (
+            Point2d::new(
+                tiepoint[3] - tiepoint[0] * scale[0],
+                tiepoint[4] + tiepoint[1] * scale[1],
+            ),
+            scale[0],
+            scale[1],
+        )
+    };
+
+    if size_x <= 0.0 || size_x.is_nan() || (size_x - size_y).abs() > size_x * 1e-3 {
+        return Err(tiff_error("only square pixels are supported"));
+    }
+
+    Ok((origin, size_x))
+}
+
+fn crs_from_geo_keys(keys: &[(u16, u16)]) -> Option<Crs> {
+    let code = keys
+        .iter()
+        .find(|(key, _)| *key == GEO_KEY_PROJECTED_CS_TYPE)
+        .or_else(|| keys.iter().find(|(key, _)| *key == GEO_KEY_GEOGRAPHIC_TYPE))?
+        .1;
+    match code {
+        3857 | 3785 => Some(Crs::EPSG3857),
+        4326 => Some(Crs::WGS84),
+        32601..=32660 => Crs::utm((code - 32600) as u8, true),
+        32701..=32760 => Crs::utm((code - 32700) as u8, false),
+        _ => None,
+    }
+}
+
+fn decode_tile(
+    format: &DataFormat,
+    data: Bytes,
+    tile_width: u32,
+    tile_height: u32,
+) -> Result<CogTile, GalileoError> {
+    let pixel_count = tile_width as usize * tile_height as usize;
+    if format.compression == CogCompression::Jpeg {
+        return decode_jpeg_tile(format, &data, tile_width, tile_height);
+    }
+
+    // Data past the size of the tile is not used, so it is not decompressed.
+    let tile_len = format
+        .tile_len(tile_width, tile_height)
+        .ok_or_else(|| tiff_error("tile is too large"))?;
+    let mut bytes = match format.compression {
+        CogCompression::None => data[..data.len().min(tile_len)].to_vec(),
+        CogCompression::Deflate => {
+            let mut decompressed = vec![];
+            ZlibDecoder::new(&data[..])
+                .take(tile_len as u64)
+                .read_to_end(&mut decompressed)
+                .map_err(|err| tiff_error(format!("invalid deflate data: {err}")))?;
+            decompressed
+        }
+        CogCompression::Lzw => lzw_decode(&data, tile_len)?,
+        CogCompression::Jpeg => unreachable!("JPEG tiles are decoded separately"),
+    };
+
+    let sample_size = format.bits_per_sample as usize / 8;
+    let row_len = tile_width as usize * format.bands * sample_size;
+    bytes.resize(tile_len, 0);
+
+    // The floating point predictor stores the bytes of the samples from the most significant ones, so they are in
+    // big-endian order after it is undone.
+    let byte_order = match format.predictor {
+        2 => {
+            undo_horizontal_predictor(&mut bytes, row_len, format);
+            format.byte_order
+        }
+        3 => {
+            undo_float_predictor(&mut bytes, row_len, sample_size);
+            ByteOrder::BigEndian
+        }
+        _ => format.byte_order,
+    };
+
+    let values = bytes
+        .chunks_exact(sample_size)
+        .take(pixel_count * format.bands)
+        .map(|sample| sample_value(sample, format, byte_order))
+        .collect();
+
+    Ok(CogTile {
+        width: tile_width,
+        height: tile_height,
+        bands: format.bands,
+        values,
+        valid_size: (tile_width, tile_height),
+    })
+}
+
+fn sample_value(sample: &[u8], format: &DataFormat, order: ByteOrder) -> f64 {
+    match (format.sample_format, sample.len()) {
+        (SampleFormat::Unsigned, 1) => sample[0] as f64,
+        (SampleFormat::Signed, 1) => sample[0] as i8 as f64,
+        (SampleFormat::Unsigned, 2) => order.u16(sample) as f64,
+        (SampleFormat::Signed, 2) => order.u16(sample) as i16 as f64,
+        (SampleFormat::Unsigned, 4) => order.u32(sample) as f64,
+        (SampleFormat::Signed, 4) => order.u32(sample) as i32 as f64,
+        (SampleFormat::Float, 4) => f32::from_bits(order.u32(sample)) as f64,
+        (SampleFormat::Unsigned, _) => order.u64(sample) as f64,
+        (SampleFormat::Signed, _) => order.u64(sample) as i64 as f64,
+        (SampleFormat::Float, _) => f64::from_bits(order.u64(sample)),
+    }
+}
+
+/// Undoes the horizontal differencing of integer samples: every sample is stored as the difference from the same
+/// sample of the previous pixel.
+fn undo_horizontal_predictor(bytes: &mut [u8], row_len: usize, format: &DataFormat) {
+    let order = format.byte_order;
+    let bands = format.bands;
+    for row in bytes.chunks_exact_mut(row_len) {
+        match format.bits_per_sample {
+            8 => {
+                for i in bands..row.len() {
+                    row[i] = row[i].wrapping_add(row[i - bands]);
+                }
+            }
+            16 => {
+                for i in (bands * 2..row.len()).step_by(2) {
+                    let value = order
+                        .u16(&row[i..])
+                        .wrapping_add(order.u16(&row[i - bands * 2..]));
+                    row[i..i + 2].copy_from_slice(&order.read::<2>(&value.to_le_bytes()));
+                }
+            }
+            32 => {
+                for i in (bands * 4..row.len()).step_by(4) {
+                    let value = order
+                        .u32(&row[i..])
+                        .wrapping_add(order.u32(&row[i - bands * 4..]));
+                    row[i..i + 4].copy_from_slice(&order.read::<4>(&value.to_le_bytes()));
+                }
+            }
+            _ => {
+                for i in (bands * 8..row.len()).step_by(8) {
+                    let value = order
+                        .u64(&row[i..])
+                        .wrapping_add(order.u64(&row[i - bands * 8..]));
+                    row[i..i + 8].copy_from_slice(&order.read::<8>(&value.to_le_bytes()));
+                }
+            }
+        }
+    }
+}
+
+/// Undoes the floating point predictor: bytes of a row are differenced one by one, having first the most
+/// significant bytes of all samples, then the next bytes and so on.
+fn undo_float_predictor(bytes: &mut [u8], row_len: usize, sample_size: usize) {
+    let sample_count = row_len / sample_size;
+    let mut reordered = vec![0; row_len];
+    for row in bytes.chunks_exact_mut(row_len) {
+        for i in 1..row.len() {
+            row[i] = row[i].wrapping_add(row[i - 1]);
+        }
+        for (sample, value) in reordered.chunks_exact_mut(sample_size).enumerate() {
+            for (byte, target) in value.iter_mut().enumerate() {
+                *target = row[byte * sample_count + sample];
+            }
+        }
+        row.copy_from_slice(&reordered);
+    }
+}
+
+/// Decodes TIFF flavour of LZW compressed data: codes are read from the most significant bit and their width is
+/// increased one code earlier than in other LZW implementations. Decoding stops after `limit` bytes.
+fn lzw_decode(data: &[u8], limit: usize) -> Result<Vec<u8>, GalileoError> {
+    const CLEAR_CODE: usize = 256;
+    const END_OF_INFORMATION: usize = 257;
+    const MAX_WIDTH: u32 = 12;
+
+    let mut output = vec![];
+    // Dictionary entries are stored as the code of the prefix and the last byte of the entry.
+    let mut table: Vec<(Option<usize>, u8)> = (0..=255u8).map(|b| (None, b)).collect();
+    table.push((None, 0));
+    table.push((None, 0));
+    let mut width = 9;
+    let mut previous: Option<usize> = None;
+
+    let mut bit_buffer = 0u32;
+    let mut bit_count = 0;
+    let mut bytes = data.iter();
+
+    let mut entry = vec![];
+    loop {
+        while bit_count < width {
+            let Some(byte) = bytes.next() else {
+                return Ok(output);
+            };
+            bit_buffer = (bit_buffer << 8) | *byte as u32;
+            bit_count += 8;
+        }
+        let code = ((bit_buffer >> (bit_count - width)) & ((1 << width) - 1)) as usize;
+        bit_count -= width;
+
+        if code == CLEAR_CODE {
+            table.truncate(END_OF_INFORMATION + 1);
+            width = 9;
+            previous = None;
+            continue;
+        }
+        if code == END_OF_INFORMATION {
+            return Ok(output);
+        }
+
+        let first_byte = |code: usize, table: &[(Option<usize>, u8)]| {
+            let mut current = code;
+            while let Some(prefix) = table[current].0 {
+                current = prefix;
+            }
+            table[current].1
+        };
+
+        let entry_code = match previous {
+            None => {
+                if code >= CLEAR_CODE {
+                    return Err(tiff_error("invalid LZW data"));
+                }
+                code
+            }
+            Some(previous) => {
+                let last_byte = if code < table.len() {
+                    first_byte(code, &table)
+                } else if code == table.len() {
+                    first_byte(previous, &table)
+                } else {
+                    return Err(tiff_error("invalid LZW data"));
+                };
+                if table.len() < 1 << MAX_WIDTH {
+                    table.push((Some(previous), last_byte));
+                }
+                code
+            }
+        };
+
+        entry.clear();
+        let mut current = Some(entry_code);
+        while let Some(code) = current {
+            entry.push(table[code].1);
+            current = table[code].0;
+        }
+        output.extend(entry.iter().rev());
+        if output.len() >= limit {
+            output.truncate(limit);
+            return Ok(output);
+        }
+
+        previous = Some(code);
+        if table.len() + 1 >= 1 << width && width < MAX_WIDTH {
+            width += 1;
+        }
+    }
+}
+
+fn decode_jpeg_tile(
+    format: &DataFormat,
+    data: &[u8],
+    tile_width: u32,
+    tile_height: u32,
+) -> Result<CogTile, GalileoError> {
+    // Quantization and Huffman tables can be stored once for all tiles. They are inserted into the stream of the
+    // tile after its start of image marker, dropping the markers around the tables.
+    let stream = match &format.jpeg_tables {
+        Some(tables) if tables.len() > 4 && data.len() > 2 => {
+            let mut stream = Vec::with_capacity(tables.len() + data.len());
+            stream.extend_from_slice(&tables[..tables.len() - 2]);
+            stream.extend_from_slice(&data[2..]);
+            stream
+        }
+        _ => data.to_vec(),
+    };
+
+    let image = image::load_from_memory_with_format(&stream, image::ImageFormat::Jpeg)?;
+    let values: Vec<f64> = match format.bands {
+        1 => image.into_luma8().into_raw(),
+        _ => image.into_rgb8().into_raw(),
+    }
+    .into_iter()
+    .map(f64::from)
+    .collect();
+    let bands = format.bands.min(3);
+    if values.len() != tile_width as usize * tile_height as usize * bands {
+        return Err(tiff_error("unexpected size of a JPEG tile"));
+    }
+
+    Ok(CogTile {
+        width: tile_width,
+        height: tile_height,
+        bands,
+        values,
+        valid_size: (tile_width, tile_height),
+    })
+}
+
+fn tiff_error(message: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid GeoTIFF: {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile_scheme::TileIndex;
+    use assert_matches::assert_matches;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    enum Value {
+        Short(Vec<u16>),
+        Long(Vec<u32>),
+        Double(Vec<f64>),
+        Ascii(&'static str),
+    }
+
+    impl Value {
+        fn encode(&self) -> (u16, u32, Vec<u8>) {
+            match self {
+                Value::Short(v) => (
+                    3,
+                    v.len() as u32,
+                    v.iter().flat_map(|v| v.to_le_bytes()).collect(),
+                ),
+                Value::Long(v) => (
+                    4,
+                    v.len() as u32,
+                    v.iter().flat_map(|v| v.to_le_bytes()).collect(),
+                ),
+                Value::Double(v) => (
+                    12,
+                    v.len() as u32,
+                    v.iter().flat_map(|v| v.to_le_bytes()).collect(),
+                ),
+                Value::Ascii(v) => (2, v.len() as u32 + 1, [v.as_bytes(), &[0]].concat()),
+            }
+        }
+    }
+
+    /// Tags and tiles of an image of a TIFF file.
+    type Image = (Vec<(u16, Value)>, Vec<Vec<u8>>);
+
+    /// Writes a little-endian TIFF file with the given images.
+    fn tiff(images: Vec<Image>) -> Vec<u8> {
+        let mut buf = b"II\x2a\x00\x00\x00\x00\x00".to_vec();
+        let mut next_position = 4;
+        for (mut tags, tiles) in images {
+            let mut offsets = vec![];
+            for tile in &tiles {
+                offsets.push(buf.len() as u32);
+                buf.extend_from_slice(tile);
+            }
+            tags.push((TAG_TILE_OFFSETS, Value::Long(offsets)));
+            tags.push((
+                TAG_TILE_BYTE_COUNTS,
+                Value::Long(tiles.iter().map(|tile| tile.len() as u32).collect()),
+            ));
+            tags.sort_by_key(|(tag, _)| *tag);
+
+            let mut entries = vec![];
+            for (tag, value) in &tags {
+                let (field_type, count, mut data) = value.encode();
+                if data.len() > 4 {
+                    let offset = buf.len() as u32;
+                    buf.extend_from_slice(&data);
+                    data = offset.to_le_bytes().to_vec();
+                }
+                data.resize(4, 0);
+                entries.extend_from_slice(&tag.to_le_bytes());
+                entries.extend_from_slice(&field_type.to_le_bytes());
+                entries.extend_from_slice(&count.to_le_bytes());
+                entries.extend_from_slice(&data);
+            }
+
+            let ifd_position = buf.len() as u32;
+            buf[next_position..next_position + 4].copy_from_slice(&ifd_position.to_le_bytes());
+            buf.extend_from_slice(&(tags.len() as u16).to_le_bytes());
+            buf.extend_from_slice(&entries);
+            next_position = buf.len();
+            buf.extend_from_slice(&[0; 4]);
+        }
+
+        buf
+    }
+
+    fn image_tags(width: u16, height: u16, compression: u16) -> Vec<(u16, Value)> {
+        vec![
+            (TAG_IMAGE_WIDTH, Value::Short(vec![width])),
+            (TAG_IMAGE_LENGTH, Value::Short(vec![height])),
+            (TAG_BITS_PER_SAMPLE, Value::Short(vec![8])),
+            (TAG_COMPRESSION, Value::Short(vec![compression])),
+            (TAG_TILE_WIDTH, Value::Short(vec![2])),
+            (TAG_TILE_LENGTH, Value::Short(vec![2])),
+        ]
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn write_file(name: &str, data: Vec<u8>) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("galileo_cog_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    /// 4x3 image with a 2x2 overview, both split into 2x2 tiles. Every tile of the full image is filled with the
+    /// values `tile * 10 + pixel`, stored with the horizontal predictor.
+    fn write_cog() -> std::path::PathBuf {
+        let mut full = image_tags(4, 3, 8);
+        full.extend([
+            (TAG_PREDICTOR, Value::Short(vec![2])),
+            (TAG_MODEL_PIXEL_SCALE, Value::Double(vec![10.0, 10.0, 0.0])),
+            (
+                TAG_MODEL_TIEPOINT,
+                Value::Double(vec![0.0, 0.0, 0.0, 1000.0, 2000.0, 0.0]),
+            ),
+            (
+                TAG_GEO_KEY_DIRECTORY,
+                Value::Short(vec![1, 1, 0, 1, GEO_KEY_PROJECTED_CS_TYPE, 0, 1, 3857]),
+            ),
+            (TAG_GDAL_NODATA, Value::Ascii("255")),
+        ]);
+        let full_tiles = (0..4u8)
+            .map(|tile| {
+                let values = [tile * 10, tile * 10 + 1, tile * 10 + 2, tile * 10 + 3];
+                deflate(&[values[0], 1, values[2], 1])
+            })
+            .collect();
+
+        let mut overview = image_tags(2, 2, 1);
+        overview.push((TAG_NEW_SUBFILE_TYPE, Value::Long(vec![1])));
+        let mut mask = image_tags(4, 3, 1);
+        mask.push((TAG_NEW_SUBFILE_TYPE, Value::Long(vec![4])));
+
+        write_file(
+            "image.tif",
+            tiff(vec![
+                (full, full_tiles),
+                (mask, vec![vec![255; 4]; 4]),
+                (overview, vec![vec![5, 6, 7, 8]]),
+            ]),
+        )
+    }
+
+    #[test]
+    fn reads_tiles_and_overviews() {
+        let source = tokio_test::block_on(CogSource::open_file(write_cog())).unwrap();
+        assert_eq!(source.size(), (4, 3));
+        assert_eq!(source.band_count(), 1);
+        assert_eq!(source.overview_count(), 1);
+        assert_eq!(source.compression(), CogCompression::Deflate);
+        assert_eq!(source.crs(), Some(&Crs::EPSG3857));
+        assert_eq!(source.nodata(), Some(255.0));
+        assert_eq!(source.bounds(), Rect::new(1000.0, 1970.0, 1040.0, 2000.0));
+
+        let schema = source.tile_schema().unwrap();
+        assert_eq!(schema.lod_resolution(1), Some(10.0));
+        assert_eq!(schema.lod_resolution(0), Some(20.0));
+        let index = TileIndex {
+            z: 1,
+            x: 1,
+            y: 1,
+            display_x: 1,
+        };
+        assert_eq!(
+            schema.tile_bbox(index),
+            Some(Rect::new(1020.0, 1960.0, 1040.0, 1980.0))
+        );
+
+        let tile = tokio_test::block_on(source.read_tile(1, 1, 1)).unwrap();
+        assert_eq!(tile.values, vec![30.0, 31.0, 32.0, 33.0]);
+        assert_eq!(tile.valid_size, (2, 1));
+
+        let tile = tokio_test::block_on(source.read_tile(0, 0, 0)).unwrap();
+        assert_eq!(tile.values, vec![5.0, 6.0, 7.0, 8.0]);
+        assert_eq!(tile.valid_size, (2, 2));
+
+        assert_matches!(
+            tokio_test::block_on(source.read_tile(2, 0, 1)),
+            Err(GalileoError::NotFound)
+        );
+        assert_matches!(
+            tokio_test::block_on(source.read_tile(0, 0, 2)),
+            Err(GalileoError::NotFound)
+        );
+    }
+
+    #[test]
+    fn lzw_decoding() {
+        let pack = |codes: &[u64]| {
+            let mut bits = 0u64;
+            for code in codes {
+                bits = (bits << 9) | code;
+            }
+            bits <<= 64 - 9 * codes.len();
+            bits.to_be_bytes()[..(codes.len() * 9).div_ceil(8)].to_vec()
+        };
+
+        // Clear code, `A`, `B`, `AB`, `ABA` and end of information.
+        assert_eq!(
+            lzw_decode(&pack(&[256, 65, 66, 258, 260, 257]), 100).unwrap(),
+            b"ABABABA"
+        );
+        assert_eq!(
+            lzw_decode(&pack(&[256, 65, 66, 258, 260, 257]), 4).unwrap(),
+            b"ABAB"
+        );
+        assert!(lzw_decode(&pack(&[256, 65, 300]), 100).is_err());
+    }
+
+    #[test]
+    fn float_predictor() {
+        let values = [1.5f32, -2.0, 100.25, 0.0];
+        // Bytes of the samples from the most significant ones, differenced.
+        let planes: Vec<u8> = (0..4)
+            .flat_map(|byte| values.iter().map(move |value| value.to_be_bytes()[byte]))
+            .collect();
+        let mut encoded = planes.clone();
+        for i in 1..planes.len() {
+            encoded[i] = planes[i].wrapping_sub(planes[i - 1]);
+        }
+
+        let format = DataFormat {
+            byte_order: ByteOrder::LittleEndian,
+            compression: CogCompression::None,
+            predictor: 3,
+            bands: 1,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+            jpeg_tables: None,
+        };
+        let tile = decode_tile(&format, encoded.into(), 4, 1).unwrap();
+        assert_eq!(tile.values, vec![1.5, -2.0, 100.25, 0.0]);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        let path = write_file("invalid.tif", vec![0; 100]);
+        assert!(tokio_test::block_on(CogSource::open_file(path)).is_err());
+
+        let path = write_file(
+            "striped.tif",
+            tiff(vec![(
+                vec![
+                    (TAG_IMAGE_WIDTH, Value::Short(vec![2])),
+                    (TAG_IMAGE_LENGTH, Value::Short(vec![2])),
+                ],
+                vec![],
+            )]),
+        );
+        assert!(tokio_test::block_on(CogSource::open_file(path)).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_structures() {
+        let mut huge_tiles = image_tags(2, 2, 1);
+        huge_tiles.retain(|(tag, _)| *tag != TAG_TILE_WIDTH && *tag != TAG_TILE_LENGTH);
+        huge_tiles.extend([
+            (TAG_TILE_WIDTH, Value::Long(vec![1 << 20])),
+            (TAG_TILE_LENGTH, Value::Long(vec![1 << 20])),
+        ]);
+        let path = write_file("huge_tiles.tif", tiff(vec![(huge_tiles, vec![vec![0; 4]])]));
+        assert!(tokio_test::block_on(CogSource::open_file(path)).is_err());
+
+        // The IFD claims more entries than the limit and than the file holds.
+        let mut data = tiff(vec![(image_tags(2, 2, 1), vec![vec![0; 4]])]);
+        let ifd_offset = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+        data[ifd_offset..ifd_offset + 2].copy_from_slice(&u16::MAX.to_le_bytes());
+        let path = write_file("huge_ifd.tif", data);
+        assert!(tokio_test::block_on(CogSource::open_file(path)).is_err());
+
+        // The value of an entry is said to be stored at the end of the address space.
+        let mut data = tiff(vec![(image_tags(2, 2, 1), vec![vec![0; 4]])]);
+        let ifd_offset = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+        let offsets_entry = (ifd_offset + 2..)
+            .step_by(12)
+            .find(|&entry| u16::from_le_bytes([data[entry], data[entry + 1]]) == TAG_TILE_OFFSETS)
+            .unwrap();
+        data[offsets_entry + 4..offsets_entry + 12]
+            .copy_from_slice(&[2, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]);
+        let path = write_file("huge_offset.tif", data);
+        assert!(tokio_test::block_on(CogSource::open_file(path)).is_err());
+    }
+
+    #[test]
+    fn decompressed_tile_is_limited_to_tile_size() {
+        let format = DataFormat {
+            byte_order: ByteOrder::LittleEndian,
+            compression: CogCompression::Deflate,
+            predictor: 1,
+            bands: 1,
+            bits_per_sample: 8,
+            sample_format: SampleFormat::Unsigned,
+            jpeg_tables: None,
+        };
+        let tile = decode_tile(&format, deflate(&[7; 1 << 20]).into(), 2, 2).unwrap();
+        assert_eq!(tile.values, vec![7.0; 4]);
+    }
+}
@@ -1,17 +1,51 @@
 use crate::error::GalileoError;
-use crate::layer::data_provider::PersistentCacheController;
+use crate::layer::data_provider::{CacheMetadata, CacheUsage, PersistentCacheController};
 use bytes::Bytes;
 use log::debug;
+use std::fs::File;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
 
 const CACHE_FOLDER: &str = ".tile_cache";
 
+/// Suffix of the files storing the [HTTP caching metadata](CacheMetadata) next to the cached files.
+const METADATA_SUFFIX: &str = ".galileo-meta";
+
+/// Number of entries saved into the cache between the checks of the [cache limits](FileCacheLimits).
+const LIMITS_CHECK_INTERVAL: usize = 100;
+
+/// Limits of the size of a [`FileCacheController`] cache. When a limit is exceeded, least recently used entries
+/// are removed from the cache.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct FileCacheLimits {
+    /// Maximum total size of the cached files in bytes.
+    pub max_bytes: Option<u64>,
+    /// Maximum number of the cached files.
+    pub max_entries: Option<usize>,
+    /// Entries that were not used for longer than this duration are considered expired. Expired entries are not
+    /// returned from the cache and are removed when the limits are checked.
+    pub max_age: Option<Duration>,
+}
+
 /// Stores the cached data as a set of files in the specified folder. It generates file names from the given urls.
 ///
-/// Currently, there is no eviction mechanism.
+/// By default, the cache is not cleaned up automatically. Set [limits](FileCacheController::with_limits) to remove
+/// least recently used entries when the cache grows too large, or use [`FileCacheController::prune_cache`] to limit
+/// the size of the cache folder manually. The limits are checked when the limits are set and then after every
+/// 100 saved entries, so the cache can temporarily exceed them.
+///
+/// The [HTTP caching metadata](CacheMetadata) of an entry is stored in a separate file next to the cached file, with
+/// the same name and the `.galileo-meta` suffix.
 #[derive(Debug, Clone)]
 pub struct FileCacheController {
     folder_path: PathBuf,
+    // Prevents the files from being removed from the cache while they are being read.
+    lock: Arc<RwLock<()>>,
+    limits: FileCacheLimits,
+    inserts_since_check: Arc<AtomicUsize>,
 }
 
 impl Default for FileCacheController {
@@ -23,7 +57,25 @@ impl Default for FileCacheController {
 impl PersistentCacheController<str, Bytes> for FileCacheController {
     fn get(&self, key: &str) -> Option<Bytes> {
         let file_path = self.get_file_path(key);
-        if let Ok(bytes) = std::fs::read(file_path) {
+        let _lock = self.lock.read();
+        if self.is_expired(&file_path) {
+            debug!("Cache file {file_path:?} is expired");
+            return None;
+        }
+
+        if let Ok(bytes) = std::fs::read(&file_path) {
+            // Modification time is used to find least recently used entries when pruning the cache, as access time
+            // is not updated on many systems.
+            if let Err(err) = File::options()
+                .write(true)
+                .open(&file_path)
+                .and_then(|file| file.set_modified(SystemTime::now()))
+            {
+                debug!(
+                    "Failed to update modification time of the cache file {file_path:?}: {err:?}"
+                );
+            }
+
             Some(bytes.into())
         } else {
             None
@@ -37,7 +89,20 @@ impl PersistentCacheController<str, Bytes> for FileCacheController {
                 Ok(()) => {
                     debug!("Saving entry {key} to the cache file {file_path:?}");
                     std::fs::write(&file_path, data)?;
+                    // Metadata of the previous data is not valid for the new one.
+                    remove_cache_file(&metadata_path(&file_path))?;
                     debug!("Entry {key} saved to cache file {file_path:?}");
+
+                    if self.limits != FileCacheLimits::default()
+                        && self.inserts_since_check.fetch_add(1, Ordering::Relaxed) + 1
+                            >= LIMITS_CHECK_INTERVAL
+                    {
+                        self.inserts_since_check.store(0, Ordering::Relaxed);
+                        if let Err(err) = self.apply_limits() {
+                            debug!("Failed to apply the cache limits: {err:?}");
+                        }
+                    }
+
                     Ok(())
                 }
                 Err(err) => {
@@ -53,6 +118,41 @@ impl PersistentCacheController<str, Bytes> for FileCacheController {
             }
         }
     }
+
+    fn get_with_metadata(&self, key: &str) -> Option<(Bytes, CacheMetadata)> {
+        let data = self.get(key)?;
+        let metadata = std::fs::read_to_string(metadata_path(&self.get_file_path(key)))
+            .map(|contents| parse_metadata(&contents))
+            .unwrap_or_default();
+
+        Some((data, metadata))
+    }
+
+    fn insert_with_metadata(
+        &self,
+        key: &str,
+        data: &Bytes,
+        metadata: &CacheMetadata,
+    ) -> Result<(), GalileoError> {
+        self.insert(key, data)?;
+        self.update_metadata(key, metadata)
+    }
+
+    fn update_metadata(&self, key: &str, metadata: &CacheMetadata) -> Result<(), GalileoError> {
+        let file_path = self.get_file_path(key);
+        if !file_path.is_file() {
+            return Ok(());
+        }
+
+        let path = metadata_path(&file_path);
+        if *metadata == CacheMetadata::default() {
+            remove_cache_file(&path)?;
+        } else {
+            std::fs::write(path, format_metadata(metadata))?;
+        }
+
+        Ok(())
+    }
 }
 
 impl FileCacheController {
@@ -62,7 +162,145 @@ impl FileCacheController {
         ensure_folder_exists(path.as_ref()).expect("Failed to initialize file cache controller.");
         Self {
             folder_path: path.as_ref().into(),
+            lock: Arc::new(RwLock::new(())),
+            limits: FileCacheLimits::default(),
+            inserts_since_check: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Sets the limits of the cache size and removes the entries exceeding them.
+    pub fn with_limits(mut self, limits: FileCacheLimits) -> Self {
+        self.limits = limits;
+        if let Err(err) = self.apply_limits() {
+            debug!("Failed to apply the cache limits: {err:?}");
+        }
+
+        self
+    }
+
+    /// Limits of the cache size.
+    pub fn limits(&self) -> FileCacheLimits {
+        self.limits
+    }
+
+    /// Returns the total size of all the files stored in the cache.
+    pub fn cache_size_bytes(&self) -> io::Result<u64> {
+        Ok(self.cache_files()?.iter().map(|entry| entry.size).sum())
+    }
+
+    /// Returns the number and the total size of the files stored in the cache.
+    pub fn usage(&self) -> io::Result<CacheUsage> {
+        let files = self.cache_files()?;
+        Ok(CacheUsage {
+            entries: files.len(),
+            bytes: files.iter().map(|entry| entry.size).sum(),
+        })
+    }
+
+    /// Removes all entries from the cache.
+    pub fn clear(&self) -> io::Result<()> {
+        let _lock = self.lock.write();
+        for entry in self.cache_files()? {
+            remove_cache_file(&entry.path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes expired and least recently used entries from the cache until it satisfies the
+    /// [limits](FileCacheController::with_limits).
+    pub fn apply_limits(&self) -> io::Result<()> {
+        let _lock = self.lock.write();
+
+        let mut files = self.cache_files()?;
+        if let Some(max_age) = self.limits.max_age {
+            let now = SystemTime::now();
+            let mut fresh = Vec::with_capacity(files.len());
+            for entry in files {
+                if is_older_than(entry.last_used, now, max_age) {
+                    remove_cache_file(&entry.path)?;
+                } else {
+                    fresh.push(entry);
+                }
+            }
+            files = fresh;
         }
+
+        Self::remove_least_recently_used(files, self.limits.max_bytes, self.limits.max_entries)
+    }
+
+    /// Removes least recently used entries from the cache until the total size of the cache is not larger than
+    /// `max_bytes`.
+    ///
+    /// Entries are ordered by the time they were last loaded from or saved into the cache. Entries with equal times
+    /// are removed in the order of their file paths.
+    pub fn prune_cache(&self, max_bytes: u64) -> io::Result<()> {
+        let _lock = self.lock.write();
+        Self::remove_least_recently_used(self.cache_files()?, Some(max_bytes), None)
+    }
+
+    fn remove_least_recently_used(
+        mut files: Vec<CacheFile>,
+        max_bytes: Option<u64>,
+        max_entries: Option<usize>,
+    ) -> io::Result<()> {
+        let max_bytes = max_bytes.unwrap_or(u64::MAX);
+        let max_entries = max_entries.unwrap_or(usize::MAX);
+
+        let mut total_size: u64 = files.iter().map(|entry| entry.size).sum();
+        let mut count = files.len();
+        if total_size <= max_bytes && count <= max_entries {
+            return Ok(());
+        }
+
+        files.sort_by(|a, b| a.last_used.cmp(&b.last_used).then(a.path.cmp(&b.path)));
+
+        for entry in files {
+            if total_size <= max_bytes && count <= max_entries {
+                break;
+            }
+
+            remove_cache_file(&entry.path)?;
+            total_size = total_size.saturating_sub(entry.size);
+            count -= 1;
+        }
+
+        Ok(())
+    }
+
+    fn is_expired(&self, file_path: &Path) -> bool {
+        let Some(max_age) = self.limits.max_age else {
+            return false;
+        };
+
+        std::fs::metadata(file_path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| is_older_than(modified, SystemTime::now(), max_age))
+    }
+
+    fn cache_files(&self) -> io::Result<Vec<CacheFile>> {
+        let mut files = vec![];
+        let mut folders = vec![self.folder_path.clone()];
+        while let Some(folder) = folders.pop() {
+            for entry in std::fs::read_dir(folder)? {
+                let entry = entry?;
+                let metadata = entry.metadata()?;
+                if metadata.is_dir() {
+                    folders.push(entry.path());
+                } else if metadata.is_file() && !is_metadata_file(&entry.path()) {
+                    files.push(CacheFile {
+                        path: entry.path(),
+                        size: metadata.len(),
+                        last_used: metadata
+                            .modified()
+                            .or_else(|_| metadata.accessed())
+                            .unwrap_or(SystemTime::UNIX_EPOCH),
+                    });
+                }
+            }
+        }
+
+        Ok(files)
     }
 
     fn get_file_path(&self, url: &str) -> PathBuf {
@@ -78,6 +316,247 @@ impl FileCacheController {
     }
 }
 
+struct CacheFile {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+fn is_older_than(time: SystemTime, now: SystemTime, max_age: Duration) -> bool {
+    now.duration_since(time).is_ok_and(|age| age > max_age)
+}
+
+fn metadata_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_owned();
+    path.push(METADATA_SUFFIX);
+    path.into()
+}
+
+fn is_metadata_file(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.ends_with(METADATA_SUFFIX))
+}
+
+fn format_metadata(metadata: &CacheMetadata) -> String {
+    let mut contents = String::new();
+    if let Some(etag) = &metadata.etag {
+        contents.push_str(&format!("etag: {etag}\n"));
+    }
+    if let Some(last_modified) = &metadata.last_modified {
+        contents.push_str(&format!("last-modified: {last_modified}\n"));
+    }
+    if let Some(expires) = metadata.expires {
+        let seconds = expires
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        contents.push_str(&format!("expires: {seconds}\n"));
+    }
+
+    contents
+}
+
+fn parse_metadata(contents: &str) -> CacheMetadata {
+    let mut metadata = CacheMetadata::default();
+    for line in contents.lines() {
+        match line.split_once(": ") {
+            Some(("etag", value)) => metadata.etag = Some(value.to_string()),
+            Some(("last-modified", value)) => metadata.last_modified = Some(value.to_string()),
+            Some(("expires", value)) => {
+                metadata.expires = value
+                    .parse()
+                    .ok()
+                    .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds));
+            }
+            _ => {}
+        }
+    }
+
+    metadata
+}
+
+/// Removes the cache file with its metadata. A file that was already removed is not considered an error.
+fn remove_cache_file(path: &Path) -> io::Result<()> {
+    if !is_metadata_file(path) {
+        remove_cache_file(&metadata_path(path))?;
+    }
+
+    match std::fs::remove_file(path) {
+        Ok(()) => {
+            debug!("Removed cache file {path:?}");
+            Ok(())
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
 fn ensure_folder_exists(folder_path: &Path) -> std::io::Result<()> {
     std::fs::create_dir_all(folder_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_cache(name: &str) -> (FileCacheController, PathBuf) {
+        let path =
+            std::env::temp_dir().join(format!("galileo_file_cache_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        (FileCacheController::new(&path), path)
+    }
+
+    fn set_modified(cache: &FileCacheController, key: &str, secs: u64) {
+        File::options()
+            .write(true)
+            .open(cache.get_file_path(key))
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn cache_size() {
+        let (cache, path) = test_cache("size");
+        assert_eq!(cache.cache_size_bytes().unwrap(), 0);
+
+        cache
+            .insert("http://a/1.png", &Bytes::from(vec![0; 10]))
+            .unwrap();
+        cache
+            .insert("http://a/b/2.png", &Bytes::from(vec![0; 20]))
+            .unwrap();
+        assert_eq!(cache.cache_size_bytes().unwrap(), 30);
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn prune_removes_least_recently_used() {
+        let (cache, path) = test_cache("prune");
+
+        for key in ["1", "2", "3"] {
+            cache.insert(key, &Bytes::from(vec![0; 10])).unwrap();
+        }
+        set_modified(&cache, "1", 1000);
+        set_modified(&cache, "2", 3000);
+        set_modified(&cache, "3", 2000);
+
+        cache.prune_cache(30).unwrap();
+        assert_eq!(cache.cache_size_bytes().unwrap(), 30);
+
+        cache.prune_cache(15).unwrap();
+        assert_eq!(cache.cache_size_bytes().unwrap(), 10);
+        assert!(cache.get("1").is_none());
+        assert!(cache.get("3").is_none());
+        assert!(cache.get("2").is_some());
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn get_marks_entry_as_used() {
+        let (cache, path) = test_cache("get");
+
+        for key in ["1", "2"] {
+            cache.insert(key, &Bytes::from(vec![0; 10])).unwrap();
+            set_modified(&cache, key, 1000);
+        }
+
+        assert!(cache.get("1").is_some());
+        cache.prune_cache(10).unwrap();
+        assert!(cache.get("1").is_some());
+        assert!(cache.get("2").is_none());
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn limits_remove_least_recently_used() {
+        let (cache, path) = test_cache("limits");
+
+        for (key, secs) in [("1", 1000), ("2", 3000), ("3", 2000)] {
+            cache.insert(key, &Bytes::from(vec![0; 10])).unwrap();
+            set_modified(&cache, key, secs);
+        }
+
+        let cache = cache.with_limits(FileCacheLimits {
+            max_entries: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(
+            cache.usage().unwrap(),
+            CacheUsage {
+                entries: 2,
+                bytes: 20
+            }
+        );
+        assert!(cache.get("1").is_none());
+
+        cache.clear().unwrap();
+        assert_eq!(cache.usage().unwrap(), CacheUsage::default());
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn expired_entries_are_not_returned() {
+        let (cache, path) = test_cache("max_age");
+        let cache = cache.with_limits(FileCacheLimits {
+            max_age: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        });
+
+        cache.insert("old", &Bytes::from(vec![0; 10])).unwrap();
+        cache.insert("new", &Bytes::from(vec![0; 10])).unwrap();
+        set_modified(&cache, "old", 1000);
+
+        assert!(cache.get("old").is_none());
+        assert!(cache.get("new").is_some());
+
+        cache.apply_limits().unwrap();
+        assert_eq!(cache.usage().unwrap().entries, 1);
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn metadata_is_stored_next_to_entry() {
+        let (cache, path) = test_cache("metadata");
+        let metadata = CacheMetadata {
+            etag: Some("\"v1\"".into()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".into()),
+            expires: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(2000)),
+        };
+
+        cache
+            .insert_with_metadata("http://a/1.png", &Bytes::from(vec![0; 10]), &metadata)
+            .unwrap();
+        assert_eq!(
+            cache.get_with_metadata("http://a/1.png").unwrap().1,
+            metadata
+        );
+        assert_eq!(
+            cache.usage().unwrap(),
+            CacheUsage {
+                entries: 1,
+                bytes: 10
+            }
+        );
+
+        // New data without metadata replaces the old metadata.
+        cache
+            .insert("http://a/1.png", &Bytes::from(vec![0; 10]))
+            .unwrap();
+        assert_eq!(
+            cache.get_with_metadata("http://a/1.png").unwrap().1,
+            CacheMetadata::default()
+        );
+
+        cache.update_metadata("http://a/1.png", &metadata).unwrap();
+        cache.clear().unwrap();
+        assert!(std::fs::read_dir(path.join("a")).unwrap().next().is_none());
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+}
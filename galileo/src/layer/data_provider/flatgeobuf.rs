@@ -0,0 +1,1235 @@
+//! Reading features from [FlatGeobuf](https://flatgeobuf.org) files.
+
+use crate::error::GalileoError;
+use crate::layer::data_provider::range_reader::RangeReader;
+use crate::layer::feature_layer::{cast_geom, AttributeValue, Feature, FeatureAttributes};
+use bytes::Bytes;
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, NewGeoPoint};
+use galileo_types::geometry::{CartesianGeometry2d, Geom};
+use galileo_types::geometry_type::GeometryType;
+use galileo_types::impls::{
+    ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon,
+};
+use quick_cache::sync::Cache;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+const MAGIC: &[u8] = &[0x66, 0x67, 0x62, 0x03, 0x66, 0x67, 0x62];
+const MAGIC_LEN: u64 = 8;
+const MAX_HEADER_LEN: u32 = 10 * 1024 * 1024;
+const DEFAULT_INDEX_NODE_SIZE: u16 = 16;
+const NODE_ITEM_LEN: u64 = 40;
+const NODE_CACHE_SIZE: usize = 1024;
+
+/// Features closer to each other than this are loaded with a single request.
+const MAX_REQUEST_GAP: u64 = 64 * 1024;
+
+// Field ids of the `Header` table.
+const HEADER_ENVELOPE: usize = 1;
+const HEADER_GEOMETRY_TYPE: usize = 2;
+const HEADER_COLUMNS: usize = 7;
+const HEADER_FEATURES_COUNT: usize = 8;
+const HEADER_INDEX_NODE_SIZE: usize = 9;
+const HEADER_CRS: usize = 10;
+
+// Field ids of the `Column` and `Crs` tables.
+const COLUMN_NAME: usize = 0;
+const COLUMN_TYPE: usize = 1;
+const CRS_CODE: usize = 1;
+
+// Field ids of the `Feature` and `Geometry` tables.
+const FEATURE_GEOMETRY: usize = 0;
+const FEATURE_PROPERTIES: usize = 1;
+const FEATURE_COLUMNS: usize = 2;
+const GEOMETRY_ENDS: usize = 0;
+const GEOMETRY_XY: usize = 1;
+const GEOMETRY_TYPE: usize = 6;
+const GEOMETRY_PARTS: usize = 7;
+
+const TYPE_UNKNOWN: u8 = 0;
+const TYPE_POINT: u8 = 1;
+const TYPE_LINESTRING: u8 = 2;
+const TYPE_POLYGON: u8 = 3;
+const TYPE_MULTIPOINT: u8 = 4;
+const TYPE_MULTILINESTRING: u8 = 5;
+const TYPE_MULTIPOLYGON: u8 = 6;
+
+fn fgb_error(message: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid FlatGeobuf file: {message}"))
+}
+
+fn take(bytes: &[u8], position: usize, length: usize) -> Result<&[u8], GalileoError> {
+    bytes
+        .get(position..position.saturating_add(length))
+        .ok_or_else(|| fgb_error("unexpected end of data"))
+}
+
+fn read_u32(bytes: &[u8], position: usize) -> Result<u32, GalileoError> {
+    Ok(u32::from_le_bytes(
+        take(bytes, position, 4)?
+            .try_into()
+            .expect("slice has 4 bytes"),
+    ))
+}
+
+fn read_u64(bytes: &[u8], position: usize) -> Result<u64, GalileoError> {
+    Ok(u64::from_le_bytes(
+        take(bytes, position, 8)?
+            .try_into()
+            .expect("slice has 8 bytes"),
+    ))
+}
+
+fn read_f64(bytes: &[u8], position: usize) -> Result<f64, GalileoError> {
+    Ok(f64::from_bits(read_u64(bytes, position)?))
+}
+
+/// A table of a FlatBuffers buffer.
+#[derive(Clone, Copy)]
+struct Table<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Table<'a> {
+    fn root(bytes: &'a [u8]) -> Result<Self, GalileoError> {
+        Self::at_offset(bytes, 0)
+    }
+
+    /// Table referenced by the offset stored at `position`.
+    fn at_offset(bytes: &'a [u8], position: usize) -> Result<Self, GalileoError> {
+        Ok(Self {
+            bytes,
+            position: position + read_u32(bytes, position)? as usize,
+        })
+    }
+
+    /// Position of the field value in the buffer, or `None` if the field is not set.
+    fn field(&self, id: usize) -> Result<Option<usize>, GalileoError> {
+        let vtable_offset = read_u32(self.bytes, self.position)? as i32;
+        let vtable = (self.position as i64 - vtable_offset as i64)
+            .try_into()
+            .map_err(|_| fgb_error("invalid vtable offset"))?;
+        let vtable_len = u16::from_le_bytes(
+            take(self.bytes, vtable, 2)?
+                .try_into()
+                .expect("slice has 2 bytes"),
+        );
+        let entry = 4 + 2 * id;
+        if entry + 2 > vtable_len as usize {
+            return Ok(None);
+        }
+
+        let offset = u16::from_le_bytes(
+            take(self.bytes, vtable + entry, 2)?
+                .try_into()
+                .expect("slice has 2 bytes"),
+        );
+        Ok((offset != 0).then_some(self.position + offset as usize))
+    }
+
+    fn u8(&self, id: usize, default: u8) -> Result<u8, GalileoError> {
+        match self.field(id)? {
+            Some(position) => Ok(take(self.bytes, position, 1)?[0]),
+            None => Ok(default),
+        }
+    }
+
+    fn u16(&self, id: usize, default: u16) -> Result<u16, GalileoError> {
+        match self.field(id)? {
+            Some(position) => Ok(u16::from_le_bytes(
+                take(self.bytes, position, 2)?
+                    .try_into()
+                    .expect("slice has 2 bytes"),
+            )),
+            None => Ok(default),
+        }
+    }
+
+    fn i32(&self, id: usize, default: i32) -> Result<i32, GalileoError> {
+        match self.field(id)? {
+            Some(position) => Ok(read_u32(self.bytes, position)? as i32),
+            None => Ok(default),
+        }
+    }
+
+    fn u64(&self, id: usize, default: u64) -> Result<u64, GalileoError> {
+        match self.field(id)? {
+            Some(position) => read_u64(self.bytes, position),
+            None => Ok(default),
+        }
+    }
+
+    fn table(&self, id: usize) -> Result<Option<Table<'a>>, GalileoError> {
+        self.field(id)?
+            .map(|position| Self::at_offset(self.bytes, position))
+            .transpose()
+    }
+
+    /// Returns the bytes of a vector field with elements of `element_len` bytes.
+    fn vector(&self, id: usize, element_len: usize) -> Result<&'a [u8], GalileoError> {
+        let Some(position) = self.field(id)? else {
+            return Ok(&[]);
+        };
+        let start = position + read_u32(self.bytes, position)? as usize;
+        let len = read_u32(self.bytes, start)? as usize;
+        take(self.bytes, start + 4, len.saturating_mul(element_len))
+    }
+
+    fn string(&self, id: usize) -> Result<&'a str, GalileoError> {
+        std::str::from_utf8(self.vector(id, 1)?).map_err(|_| fgb_error("invalid string"))
+    }
+
+    fn f64_vector(&self, id: usize) -> Result<Vec<f64>, GalileoError> {
+        Ok(self
+            .vector(id, 8)?
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().expect("chunk has 8 bytes")))
+            .collect())
+    }
+
+    fn u32_vector(&self, id: usize) -> Result<Vec<u32>, GalileoError> {
+        Ok(self
+            .vector(id, 4)?
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().expect("chunk has 4 bytes")))
+            .collect())
+    }
+
+    fn tables(&self, id: usize) -> Result<Vec<Table<'a>>, GalileoError> {
+        let Some(position) = self.field(id)? else {
+            return Ok(vec![]);
+        };
+        let start = position + read_u32(self.bytes, position)? as usize;
+        let len = read_u32(self.bytes, start)? as usize;
+        take(self.bytes, start + 4, len.saturating_mul(4))?;
+        (0..len)
+            .map(|i| Self::at_offset(self.bytes, start + 4 + 4 * i))
+            .collect()
+    }
+}
+
+/// Value of a property of a [`FlatGeobufFeature`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlatGeobufValue {
+    /// Boolean value.
+    Bool(bool),
+    /// Signed or unsigned integer of up to 32 bits, or signed 64-bit integer.
+    Integer(i64),
+    /// Unsigned 64-bit integer.
+    UnsignedInteger(u64),
+    /// Floating point number.
+    Double(f64),
+    /// Text. JSON values and date-times (in ISO 8601 format) are also stored as text.
+    String(String),
+    /// Binary data.
+    Binary(Vec<u8>),
+}
+
+impl FlatGeobufValue {
+    /// Returns the text value, if this is a text value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a number, if this is a numeric value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Integer(value) => Some(*value as f64),
+            Self::UnsignedInteger(value) => Some(*value as f64),
+            Self::Double(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a signed integer, if this is an integer value that fits into `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            Self::UnsignedInteger(value) => i64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean, if this is a boolean value.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// A feature read from a FlatGeobuf file.
+///
+/// Coordinates of the geometry are in the CRS of the file (see [`FlatGeobufSource::crs`]). Only `x` and `y`
+/// coordinates are read. Features of files in geographic coordinates can be converted into features with
+/// [`GeoPoint2d`] geometries with [`FlatGeobufFeature::into_geo`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatGeobufFeature<P = Point2d> {
+    /// Index of the feature in the file.
+    pub index: usize,
+    /// Geometry of the feature.
+    pub geometry: Geom<P>,
+    /// Properties of the feature by the column names.
+    pub properties: HashMap<String, FlatGeobufValue>,
+}
+
+impl<P> FlatGeobufFeature<P> {
+    /// Returns the value of the property with the given column name.
+    pub fn property(&self, name: &str) -> Option<&FlatGeobufValue> {
+        self.properties.get(name)
+    }
+}
+
+impl FlatGeobufFeature {
+    /// Converts a feature in geographic coordinates into a feature with [`GeoPoint2d`] geometry. `x` coordinates are
+    /// used as longitudes and `y` coordinates as latitudes.
+    pub fn into_geo(self) -> FlatGeobufFeature<GeoPoint2d> {
+        FlatGeobufFeature {
+            index: self.index,
+            geometry: cast_geom(&self.geometry, |point| {
+                GeoPoint2d::latlon(point.y(), point.x())
+            }),
+            properties: self.properties,
+        }
+    }
+}
+
+impl<P> FeatureAttributes for FlatGeobufFeature<P> {
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        match self.property(name)? {
+            FlatGeobufValue::Bool(value) => Some(AttributeValue::Bool(*value)),
+            FlatGeobufValue::String(value) => Some(AttributeValue::String(value.clone())),
+            FlatGeobufValue::Binary(_) => None,
+            value => value.as_f64().map(AttributeValue::Number),
+        }
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        let mut attributes: Vec<_> = self
+            .properties
+            .keys()
+            .filter_map(|name| Some((name.clone(), self.attribute(name)?)))
+            .collect();
+        attributes.sort_by(|a, b| a.0.cmp(&b.0));
+        attributes
+    }
+}
+
+impl<P: GeometryType> Feature for FlatGeobufFeature<P> {
+    type Geom = Geom<P>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    name: String,
+    column_type: u8,
+}
+
+impl Column {
+    /// Parses the columns stored in the vector field with the given id.
+    fn parse_all(table: &Table, id: usize) -> Result<Vec<Self>, GalileoError> {
+        table
+            .tables(id)?
+            .iter()
+            .map(|column| {
+                Ok(Self {
+                    name: column.string(COLUMN_NAME)?.to_string(),
+                    column_type: column.u8(COLUMN_TYPE, 0)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Header {
+    geometry_type: u8,
+    columns: Vec<Column>,
+    features_count: u64,
+    index_node_size: u16,
+    extent: Option<Rect>,
+    crs: Option<Crs>,
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> Result<Self, GalileoError> {
+        let table = Table::root(bytes)?;
+        let extent = match table.f64_vector(HEADER_ENVELOPE)?[..] {
+            [x_min, y_min, x_max, y_max, ..] => Some(Rect::new(x_min, y_min, x_max, y_max)),
+            _ => None,
+        };
+        let crs = match table.table(HEADER_CRS)? {
+            Some(crs) => crs_from_epsg(crs.i32(CRS_CODE, 0)?),
+            None => None,
+        };
+
+        Ok(Self {
+            geometry_type: table.u8(HEADER_GEOMETRY_TYPE, TYPE_UNKNOWN)?,
+            columns: Column::parse_all(&table, HEADER_COLUMNS)?,
+            features_count: table.u64(HEADER_FEATURES_COUNT, 0)?,
+            index_node_size: table.u16(HEADER_INDEX_NODE_SIZE, DEFAULT_INDEX_NODE_SIZE)?,
+            extent,
+            crs,
+        })
+    }
+}
+
+fn crs_from_epsg(code: i32) -> Option<Crs> {
+    match code {
+        4326 => Some(Crs::WGS84),
+        3857 | 900913 => Some(Crs::EPSG3857),
+        32601..=32660 => Crs::utm((code - 32600) as u8, true),
+        32701..=32760 => Crs::utm((code - 32700) as u8, false),
+        _ => None,
+    }
+}
+
+/// Ranges of node indices of every level of a packed Hilbert R-tree, starting with the leaves.
+fn level_bounds(items_count: u64, node_size: u16) -> Vec<Range<u64>> {
+    let node_size = u64::from(node_size.max(2));
+    let mut level_sizes = vec![items_count];
+    let mut count = items_count;
+    loop {
+        count = count.div_ceil(node_size);
+        level_sizes.push(count);
+        if count <= 1 {
+            break;
+        }
+    }
+
+    // Root is stored first and the leaves are stored last.
+    let mut end: u64 = level_sizes.iter().sum();
+    level_sizes
+        .into_iter()
+        .map(|size| {
+            let start = end - size;
+            end = start;
+            start..start + size
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NodeItem {
+    extent: Rect,
+    /// Byte offset of the feature in the features section for leaves, and index of the first child node for other
+    /// nodes.
+    offset: u64,
+}
+
+fn parse_nodes(bytes: &[u8]) -> Result<Vec<NodeItem>, GalileoError> {
+    bytes
+        .chunks_exact(NODE_ITEM_LEN as usize)
+        .map(|chunk| {
+            Ok(NodeItem {
+                extent: Rect::new(
+                    read_f64(chunk, 0)?,
+                    read_f64(chunk, 8)?,
+                    read_f64(chunk, 16)?,
+                    read_f64(chunk, 24)?,
+                ),
+                offset: read_u64(chunk, 32)?,
+            })
+        })
+        .collect()
+}
+
+struct PackedRTree {
+    node_size: u64,
+    levels: Vec<Range<u64>>,
+    nodes: Cache<u64, Arc<Vec<NodeItem>>>,
+}
+
+/// Location of a feature in the features section of the file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FeatureLocation {
+    index: usize,
+    offset: u64,
+    /// Offset of the next feature, if known.
+    end: Option<u64>,
+}
+
+/// Reads features from a [FlatGeobuf](https://flatgeobuf.org) file, stored locally or on an HTTP server supporting
+/// range requests.
+///
+/// If the file has a spatial index, only the parts of the index and the features that are needed to get the features
+/// in the requested extent are read, so the source can be used with very large files without tiling them. Nodes of
+/// the index are cached after they are read. Files without spatial index are read completely for every request.
+///
+/// Features are usually displayed with [`FlatGeobufLayer`](crate::layer::FlatGeobufLayer), that loads the features
+/// in the current view as it changes.
+///
+/// ```no_run
+/// # async fn load() -> Result<(), galileo::error::GalileoError> {
+/// use galileo::layer::data_provider::FlatGeobufSource;
+/// use galileo_types::cartesian::Rect;
+///
+/// let source = FlatGeobufSource::open_url("https://example.com/buildings.fgb").await?;
+/// let features = source
+///     .features_in_extent(&Rect::new(13.3, 52.4, 13.5, 52.6))
+///     .await?;
+/// for feature in features {
+///     println!("{:?}", feature.property("name"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct FlatGeobufSource {
+    reader: RangeReader,
+    header: Header,
+    index: Option<PackedRTree>,
+    features_offset: u64,
+}
+
+impl FlatGeobufSource {
+    /// Opens a file stored locally.
+    pub async fn open_file(path: impl AsRef<Path>) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::file(path)?).await
+    }
+
+    /// Opens a file at the given URL. The server must support HTTP range requests.
+    pub async fn open_url(url: impl Into<String>) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::url(url, None)).await
+    }
+
+    /// Opens a file at the given URL, making all the requests with the given HTTP client.
+    pub async fn open_url_with_http_client(
+        url: impl Into<String>,
+        client: reqwest::Client,
+    ) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::url(url, Some(client))).await
+    }
+
+    async fn open(reader: RangeReader) -> Result<Self, GalileoError> {
+        let start = reader.read(0, MAGIC_LEN + 4).await?;
+        if !start.starts_with(MAGIC) {
+            return Err(GalileoError::Generic("not a FlatGeobuf file".into()));
+        }
+
+        let header_len = read_u32(&start, MAGIC_LEN as usize)?;
+        if header_len > MAX_HEADER_LEN {
+            return Err(fgb_error("header is too large"));
+        }
+        let header = Header::parse(&reader.read(MAGIC_LEN + 4, header_len as u64).await?)?;
+
+        let index_offset = MAGIC_LEN + 4 + header_len as u64;
+        let (index, index_len) = if header.index_node_size > 0 && header.features_count > 0 {
+            let levels = level_bounds(header.features_count, header.index_node_size);
+            let nodes_count = levels.first().map(|leaves| leaves.end).unwrap_or_default();
+            let index = PackedRTree {
+                node_size: u64::from(header.index_node_size.max(2)),
+                levels,
+                nodes: Cache::new(NODE_CACHE_SIZE),
+            };
+            (Some(index), nodes_count * NODE_ITEM_LEN)
+        } else {
+            (None, 0)
+        };
+
+        Ok(Self {
+            reader,
+            header,
+            index,
+            features_offset: index_offset + index_len,
+        })
+    }
+
+    /// Number of features in the file.
+    pub fn features_count(&self) -> u64 {
+        self.header.features_count
+    }
+
+    /// CRS of the feature coordinates. `None` if the file does not specify the CRS, or the CRS is not supported.
+    ///
+    /// WGS84 (`EPSG:4326`), Web Mercator (`EPSG:3857`) and WGS84 UTM zones are supported.
+    pub fn crs(&self) -> Option<&Crs> {
+        self.header.crs.as_ref()
+    }
+
+    /// Extent of all features in the file, if it is specified in the file.
+    pub fn extent(&self) -> Option<Rect> {
+        self.header.extent
+    }
+
+    /// Names of the property columns.
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        self.header
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+    }
+
+    /// Returns true if the file has a spatial index.
+    pub fn has_index(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Loads all the features, bounding rectangles of which intersect the `extent`. The `extent` is given in the CRS
+    /// of the file. Features are returned in the order they are stored in the file.
+    ///
+    /// Features with geometry types not supported by [`Geom`] (like geometry collections or curves) are skipped.
+    pub async fn features_in_extent(
+        &self,
+        extent: &Rect,
+    ) -> Result<Vec<FlatGeobufFeature>, GalileoError> {
+        let Some(index) = &self.index else {
+            return self.scan_features(extent).await;
+        };
+
+        let locations = self.search(index, extent).await?;
+        let mut features = Vec::with_capacity(locations.len());
+        let mut from = 0;
+        while from < locations.len() {
+            // Merge the requests for the features close to each other into one request.
+            let mut to = from + 1;
+            while to < locations.len()
+                && locations[to - 1]
+                    .end
+                    .is_some_and(|end| locations[to].offset <= end + MAX_REQUEST_GAP)
+            {
+                to += 1;
+            }
+
+            let group = &locations[from..to];
+            let start = group[0].offset;
+            let bytes = match group[group.len() - 1].end {
+                Some(end) => self.read_features(start, end - start).await?,
+                None => {
+                    let last = group[group.len() - 1].offset;
+                    let mut bytes = self.read_features(start, last - start).await?.to_vec();
+                    bytes.extend_from_slice(&self.read_feature_at(last).await?);
+                    bytes.into()
+                }
+            };
+
+            for location in group {
+                let position = (location.offset - start) as usize;
+                let len = read_u32(&bytes, position)? as usize;
+                if let Some(feature) =
+                    self.parse_feature(location.index, take(&bytes, position + 4, len)?)?
+                {
+                    features.push(feature);
+                }
+            }
+
+            from = to;
+        }
+
+        Ok(features)
+    }
+
+    async fn read_features(&self, offset: u64, length: u64) -> Result<Bytes, GalileoError> {
+        self.reader
+            .read(self.features_offset + offset, length)
+            .await
+    }
+
+    /// Reads the size prefixed feature at the offset.
+    async fn read_feature_at(&self, offset: u64) -> Result<Bytes, GalileoError> {
+        let len = read_u32(&self.read_features(offset, 4).await?, 0)?;
+        let body = self.read_features(offset + 4, len as u64).await?;
+        Ok([&len.to_le_bytes()[..], &body[..]].concat().into())
+    }
+
+    async fn scan_features(&self, extent: &Rect) -> Result<Vec<FlatGeobufFeature>, GalileoError> {
+        let mut features = vec![];
+        let mut offset = 0;
+        for index in 0..self.header.features_count as usize {
+            let bytes = self.read_feature_at(offset).await?;
+            offset += bytes.len() as u64;
+            let Some(feature) = self.parse_feature(index, &bytes[4..])? else {
+                continue;
+            };
+            if feature
+                .geometry
+                .bounding_rectangle()
+                .is_some_and(|bbox| bbox.intersects(extent))
+            {
+                features.push(feature);
+            }
+        }
+
+        Ok(features)
+    }
+
+    /// Returns the batch of nodes of the given `level` that contains the node with the given index, together with
+    /// the index of the first node of the batch.
+    async fn nodes(
+        &self,
+        index: &PackedRTree,
+        level: usize,
+        node_index: u64,
+    ) -> Result<(u64, Arc<Vec<NodeItem>>), GalileoError> {
+        let bounds = &index.levels[level];
+        if !bounds.contains(&node_index) {
+            return Err(fgb_error("invalid node index"));
+        }
+
+        let start = bounds.start + (node_index - bounds.start) / index.node_size * index.node_size;
+        if let Some(nodes) = index.nodes.get(&start) {
+            return Ok((start, nodes));
+        }
+
+        let end = (start + index.node_size).min(bounds.end);
+        let bytes = self
+            .reader
+            .read(
+                self.features_offset - index.levels[0].end * NODE_ITEM_LEN + start * NODE_ITEM_LEN,
+                (end - start) * NODE_ITEM_LEN,
+            )
+            .await?;
+        let nodes = Arc::new(parse_nodes(&bytes)?);
+        index.nodes.insert(start, nodes.clone());
+
+        Ok((start, nodes))
+    }
+
+    /// Finds the locations of the features with the bounding rectangles intersecting the `extent`, ordered by their
+    /// offsets.
+    async fn search(
+        &self,
+        index: &PackedRTree,
+        extent: &Rect,
+    ) -> Result<Vec<FeatureLocation>, GalileoError> {
+        let leaves = index.levels[0].clone();
+        let mut locations = vec![];
+        let mut stack = vec![(
+            index.levels.len() - 1,
+            index.levels[index.levels.len() - 1].start,
+        )];
+        while let Some((level, node_index)) = stack.pop() {
+            let (start, nodes) = self.nodes(index, level, node_index).await?;
+            for (i, node) in nodes.iter().enumerate() {
+                if !node.extent.intersects(extent) {
+                    continue;
+                }
+
+                if level == 0 {
+                    locations.push(FeatureLocation {
+                        index: (start + i as u64 - leaves.start) as usize,
+                        offset: node.offset,
+                        end: nodes.get(i + 1).map(|next| next.offset),
+                    });
+                } else {
+                    stack.push((level - 1, node.offset));
+                }
+            }
+        }
+
+        locations.sort_by_key(|location| location.index);
+        for location in &mut locations {
+            let next = leaves.start + location.index as u64 + 1;
+            if location.end.is_none() && next < leaves.end {
+                let (start, nodes) = self.nodes(index, 0, next).await?;
+                location.end = Some(nodes[(next - start) as usize].offset);
+            }
+        }
+
+        Ok(locations)
+    }
+
+    fn parse_feature(
+        &self,
+        index: usize,
+        bytes: &[u8],
+    ) -> Result<Option<FlatGeobufFeature>, GalileoError> {
+        let table = Table::root(bytes)?;
+        let Some(geometry) = table.table(FEATURE_GEOMETRY)? else {
+            return Ok(None);
+        };
+        let Some(geometry) = parse_geometry(&geometry, self.header.geometry_type)? else {
+            return Ok(None);
+        };
+
+        let feature_columns = Column::parse_all(&table, FEATURE_COLUMNS)?;
+        let columns = if feature_columns.is_empty() {
+            &self.header.columns
+        } else {
+            &feature_columns
+        };
+
+        Ok(Some(FlatGeobufFeature {
+            index,
+            geometry,
+            properties: parse_properties(table.vector(FEATURE_PROPERTIES, 1)?, columns)?,
+        }))
+    }
+}
+
+fn parse_properties(
+    bytes: &[u8],
+    columns: &[Column],
+) -> Result<HashMap<String, FlatGeobufValue>, GalileoError> {
+    let mut properties = HashMap::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        let column_index = u16::from_le_bytes(
+            take(bytes, position, 2)?
+                .try_into()
+                .expect("slice has 2 bytes"),
+        );
+        position += 2;
+        let column = columns
+            .get(column_index as usize)
+            .ok_or_else(|| fgb_error(format!("unknown column {column_index}")))?;
+
+        let mut next = |len: usize| -> Result<&[u8], GalileoError> {
+            let value = take(bytes, position, len)?;
+            position += len;
+            Ok(value)
+        };
+        let value = match column.column_type {
+            0 => FlatGeobufValue::Integer(next(1)?[0] as i8 as i64),
+            1 => FlatGeobufValue::Integer(next(1)?[0] as i64),
+            2 => FlatGeobufValue::Bool(next(1)?[0] != 0),
+            3 => FlatGeobufValue::Integer(
+                i16::from_le_bytes(next(2)?.try_into().expect("2 bytes")) as i64,
+            ),
+            4 => FlatGeobufValue::Integer(
+                u16::from_le_bytes(next(2)?.try_into().expect("2 bytes")) as i64,
+            ),
+            5 => FlatGeobufValue::Integer(
+                i32::from_le_bytes(next(4)?.try_into().expect("4 bytes")) as i64,
+            ),
+            6 => FlatGeobufValue::Integer(
+                u32::from_le_bytes(next(4)?.try_into().expect("4 bytes")) as i64,
+            ),
+            7 => {
+                FlatGeobufValue::Integer(i64::from_le_bytes(next(8)?.try_into().expect("8 bytes")))
+            }
+            8 => FlatGeobufValue::UnsignedInteger(u64::from_le_bytes(
+                next(8)?.try_into().expect("8 bytes"),
+            )),
+            9 => FlatGeobufValue::Double(
+                f32::from_le_bytes(next(4)?.try_into().expect("4 bytes")) as f64
+            ),
+            10 => {
+                FlatGeobufValue::Double(f64::from_le_bytes(next(8)?.try_into().expect("8 bytes")))
+            }
+            11..=14 => {
+                let len = u32::from_le_bytes(next(4)?.try_into().expect("4 bytes")) as usize;
+                let value = next(len)?;
+                if column.column_type == 14 {
+                    FlatGeobufValue::Binary(value.to_vec())
+                } else {
+                    FlatGeobufValue::String(String::from_utf8_lossy(value).into_owned())
+                }
+            }
+            column_type => return Err(fgb_error(format!("unknown column type {column_type}"))),
+        };
+        properties.insert(column.name.clone(), value);
+    }
+
+    Ok(properties)
+}
+
+fn parse_geometry(table: &Table, geometry_type: u8) -> Result<Option<Geom<Point2d>>, GalileoError> {
+    let geometry_type = match geometry_type {
+        TYPE_UNKNOWN => table.u8(GEOMETRY_TYPE, TYPE_UNKNOWN)?,
+        geometry_type => geometry_type,
+    };
+
+    let points: Vec<_> = table
+        .f64_vector(GEOMETRY_XY)?
+        .chunks_exact(2)
+        .map(|xy| Point2d::new(xy[0], xy[1]))
+        .collect();
+    let parts = |points: Vec<Point2d>| -> Result<Vec<Vec<Point2d>>, GalileoError> {
+        let ends = table.u32_vector(GEOMETRY_ENDS)?;
+        if ends.is_empty() {
+            return Ok(vec![points]);
+        }
+
+        let mut start = 0;
+        ends.into_iter()
+            .map(|end| {
+                let part = points
+                    .get(start..end as usize)
+                    .ok_or_else(|| fgb_error("invalid geometry part ends"))?
+                    .to_vec();
+                start = end as usize;
+                Ok(part)
+            })
+            .collect()
+    };
+    let polygon = |rings: Vec<Vec<Point2d>>| {
+        let mut rings = rings.into_iter().map(|mut ring| {
+            if ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
+            }
+            ClosedContour::new(ring)
+        });
+        let outer = rings.next()?;
+        Some(Polygon::new(outer, rings.collect()))
+    };
+
+    Ok(match geometry_type {
+        TYPE_POINT => points.first().map(|point| Geom::Point(*point)),
+        TYPE_MULTIPOINT => Some(Geom::MultiPoint(MultiPoint::from(points))),
+        TYPE_LINESTRING => Some(Geom::Contour(Contour::open(points))),
+        TYPE_MULTILINESTRING => Some(Geom::MultiContour(MultiContour::from(
+            parts(points)?
+                .into_iter()
+                .map(Contour::open)
+                .collect::<Vec<_>>(),
+        ))),
+        TYPE_POLYGON => polygon(parts(points)?).map(Geom::Polygon),
+        TYPE_MULTIPOLYGON => {
+            let mut polygons = vec![];
+            for part in table.tables(GEOMETRY_PARTS)? {
+                if let Some(Geom::Polygon(polygon)) = parse_geometry(&part, TYPE_POLYGON)? {
+                    polygons.push(polygon);
+                }
+            }
+            Some(Geom::MultiPolygon(MultiPolygon::from(polygons)))
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use galileo_types::MultiContour as _;
+    use std::path::PathBuf;
+
+    /// Field of a FlatBuffers table written by [`write_table`].
+    enum Field {
+        Inline(Vec<u8>),
+        Vector(Vec<u8>, usize),
+        Table(Vec<(usize, Field)>),
+        Tables(Vec<Vec<(usize, Field)>>),
+    }
+
+    fn string(value: &str) -> Field {
+        Field::Vector(value.as_bytes().to_vec(), value.len())
+    }
+
+    fn doubles(values: &[f64]) -> Field {
+        Field::Vector(
+            values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            values.len(),
+        )
+    }
+
+    /// Writes the table at the end of the buffer, with its vtable before it and the referenced objects after it.
+    /// Returns the position of the table.
+    fn write_table(buffer: &mut Vec<u8>, fields: &[(usize, Field)]) -> usize {
+        let max_id = fields.iter().map(|(id, _)| *id).max().unwrap_or(0);
+        let vtable_len = 4 + 2 * (max_id + 1);
+        let field_len = |field: &Field| match field {
+            Field::Inline(bytes) => bytes.len(),
+            _ => 4,
+        };
+        let table_len = 4 + fields.iter().map(|(_, f)| field_len(f)).sum::<usize>();
+
+        let vtable = buffer.len();
+        let mut offsets = vec![0u16; max_id + 1];
+        let mut field_offset = 4;
+        for (id, field) in fields {
+            offsets[*id] = field_offset as u16;
+            field_offset += field_len(field);
+        }
+        buffer.extend_from_slice(&(vtable_len as u16).to_le_bytes());
+        buffer.extend_from_slice(&(table_len as u16).to_le_bytes());
+        for offset in offsets {
+            buffer.extend_from_slice(&offset.to_le_bytes());
+        }
+
+        let table = buffer.len();
+        buffer.extend_from_slice(&((table - vtable) as i32).to_le_bytes());
+        let mut references = vec![];
+        for (_, field) in fields {
+            match field {
+                Field::Inline(bytes) => buffer.extend_from_slice(bytes),
+                _ => {
+                    references.push((buffer.len(), field));
+                    buffer.extend_from_slice(&[0; 4]);
+                }
+            }
+        }
+
+        for (position, field) in references {
+            let target = match field {
+                Field::Inline(_) => unreachable!(),
+                Field::Vector(bytes, len) => {
+                    let target = buffer.len();
+                    buffer.extend_from_slice(&(*len as u32).to_le_bytes());
+                    buffer.extend_from_slice(bytes);
+                    target
+                }
+                Field::Table(fields) => write_table(buffer, fields),
+                Field::Tables(tables) => {
+                    let target = buffer.len();
+                    buffer.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+                    let elements = buffer.len();
+                    buffer.extend_from_slice(&vec![0; 4 * tables.len()]);
+                    for (i, fields) in tables.iter().enumerate() {
+                        let table = write_table(buffer, fields);
+                        let element = elements + 4 * i;
+                        buffer[element..element + 4]
+                            .copy_from_slice(&((table - element) as u32).to_le_bytes());
+                    }
+                    target
+                }
+            };
+            buffer[position..position + 4]
+                .copy_from_slice(&((target - position) as u32).to_le_bytes());
+        }
+
+        table
+    }
+
+    /// Writes a size prefixed buffer with the root table.
+    fn write_buffer(fields: &[(usize, Field)]) -> Vec<u8> {
+        let mut buffer = vec![0; 4];
+        let table = write_table(&mut buffer, fields);
+        buffer[..4].copy_from_slice(&(table as u32).to_le_bytes());
+        [(buffer.len() as u32).to_le_bytes().to_vec(), buffer].concat()
+    }
+
+    fn point_feature(x: f64, y: f64, name: &str) -> Vec<u8> {
+        let mut properties = vec![0, 0];
+        properties.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        properties.extend_from_slice(name.as_bytes());
+        properties.extend_from_slice(&[1, 0]);
+        properties.extend_from_slice(&(x as i32).to_le_bytes());
+
+        write_buffer(&[
+            (
+                FEATURE_GEOMETRY,
+                Field::Table(vec![
+                    (GEOMETRY_XY, doubles(&[x, y])),
+                    (GEOMETRY_TYPE, Field::Inline(vec![TYPE_POINT])),
+                ]),
+            ),
+            (
+                FEATURE_PROPERTIES,
+                Field::Vector(properties.clone(), properties.len()),
+            ),
+        ])
+    }
+
+    /// Writes a file with points `(i, i)` for `i` in `0..count`. Each feature has `name` (string) and `value` (int)
+    /// properties.
+    pub(crate) fn write_file(name: &str, count: usize, node_size: u16) -> PathBuf {
+        let columns = vec![
+            vec![
+                (COLUMN_NAME, string("name")),
+                (COLUMN_TYPE, Field::Inline(vec![11])),
+            ],
+            vec![
+                (COLUMN_NAME, string("value")),
+                (COLUMN_TYPE, Field::Inline(vec![5])),
+            ],
+        ];
+        let header = write_buffer(&[
+            (
+                HEADER_ENVELOPE,
+                doubles(&[0.0, 0.0, count as f64 - 1.0, count as f64 - 1.0]),
+            ),
+            (HEADER_GEOMETRY_TYPE, Field::Inline(vec![TYPE_POINT])),
+            (HEADER_COLUMNS, Field::Tables(columns)),
+            (
+                HEADER_FEATURES_COUNT,
+                Field::Inline((count as u64).to_le_bytes().to_vec()),
+            ),
+            (
+                HEADER_INDEX_NODE_SIZE,
+                Field::Inline(node_size.to_le_bytes().to_vec()),
+            ),
+            (
+                HEADER_CRS,
+                Field::Table(vec![(
+                    CRS_CODE,
+                    Field::Inline(4326i32.to_le_bytes().to_vec()),
+                )]),
+            ),
+        ]);
+
+        let mut features = vec![];
+        let mut nodes = vec![];
+        for i in 0..count {
+            let x = i as f64;
+            nodes.push(NodeItem {
+                extent: Rect::new(x, x, x, x),
+                offset: features.len() as u64,
+            });
+            features.extend(point_feature(x, x, &format!("feature {i}")));
+        }
+
+        let mut index = vec![];
+        if node_size > 0 {
+            let levels = level_bounds(count as u64, node_size);
+            let mut tree = vec![nodes[0]; levels[0].end as usize];
+            tree[levels[0].start as usize..].copy_from_slice(&nodes);
+            for level in 1..levels.len() {
+                for parent in levels[level].clone() {
+                    let first =
+                        levels[level - 1].start + (parent - levels[level].start) * node_size as u64;
+                    let last = (first + node_size as u64).min(levels[level - 1].end);
+                    let extent = (first..last)
+                        .map(|child| tree[child as usize].extent)
+                        .reduce(|a, b| a.merge(b))
+                        .unwrap();
+                    tree[parent as usize] = NodeItem {
+                        extent,
+                        offset: first,
+                    };
+                }
+            }
+            for node in tree {
+                for value in [
+                    node.extent.x_min(),
+                    node.extent.y_min(),
+                    node.extent.x_max(),
+                    node.extent.y_max(),
+                ] {
+                    index.extend_from_slice(&value.to_le_bytes());
+                }
+                index.extend_from_slice(&node.offset.to_le_bytes());
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("galileo_fgb_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let magic = [MAGIC, &[0]].concat();
+        std::fs::write(&path, [magic, header, index, features].concat()).unwrap();
+        path
+    }
+
+    fn indices(features: &[FlatGeobufFeature]) -> Vec<usize> {
+        features.iter().map(|feature| feature.index).collect()
+    }
+
+    #[test]
+    fn level_bounds_of_tree() {
+        assert_eq!(level_bounds(1, 16), vec![1..2, 0..1]);
+        assert_eq!(level_bounds(5, 2), vec![6..11, 3..6, 1..3, 0..1]);
+    }
+
+    #[test]
+    fn reads_header() {
+        let path = write_file("header.fgb", 5, 2);
+        let source = tokio_test::block_on(FlatGeobufSource::open_file(&path)).unwrap();
+        assert_eq!(source.features_count(), 5);
+        assert_eq!(source.crs(), Some(&Crs::WGS84));
+        assert_eq!(source.extent(), Some(Rect::new(0.0, 0.0, 4.0, 4.0)));
+        assert_eq!(source.columns().collect::<Vec<_>>(), vec!["name", "value"]);
+        assert!(source.has_index());
+    }
+
+    #[test]
+    fn reads_features_in_extent_with_index() {
+        let path = write_file("indexed.fgb", 37, 4);
+        let source = tokio_test::block_on(FlatGeobufSource::open_file(&path)).unwrap();
+
+        let features =
+            tokio_test::block_on(source.features_in_extent(&Rect::new(9.5, 0.0, 20.0, 13.0)))
+                .unwrap();
+        assert_eq!(indices(&features), vec![10, 11, 12, 13]);
+        assert_eq!(features[0].geometry, Geom::Point(Point2d::new(10.0, 10.0)));
+        assert_eq!(
+            features[0].property("name"),
+            Some(&FlatGeobufValue::String("feature 10".into()))
+        );
+        assert_eq!(
+            features[0].property("value").and_then(|v| v.as_i64()),
+            Some(10)
+        );
+
+        let features =
+            tokio_test::block_on(source.features_in_extent(&Rect::new(35.0, 35.0, 50.0, 50.0)))
+                .unwrap();
+        assert_eq!(indices(&features), vec![35, 36]);
+
+        let features =
+            tokio_test::block_on(source.features_in_extent(&Rect::new(-10.0, -10.0, 100.0, 100.0)))
+                .unwrap();
+        assert_eq!(indices(&features), (0..37).collect::<Vec<_>>());
+
+        let features =
+            tokio_test::block_on(source.features_in_extent(&Rect::new(1.2, 1.2, 1.8, 1.8)))
+                .unwrap();
+        assert!(features.is_empty());
+    }
+
+    #[test]
+    fn reads_features_without_index() {
+        let path = write_file("no_index.fgb", 7, 0);
+        let source = tokio_test::block_on(FlatGeobufSource::open_file(&path)).unwrap();
+        assert!(!source.has_index());
+
+        let features =
+            tokio_test::block_on(source.features_in_extent(&Rect::new(2.0, 2.0, 4.0, 4.0)))
+                .unwrap();
+        assert_eq!(indices(&features), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn reads_geometries() {
+        let mut buffer = vec![0; 4];
+        let table = write_table(
+            &mut buffer,
+            &[
+                (
+                    GEOMETRY_ENDS,
+                    Field::Vector([4u32, 8].iter().flat_map(|v| v.to_le_bytes()).collect(), 2),
+                ),
+                (
+                    GEOMETRY_XY,
+                    doubles(&[
+                        0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 0.0, 1.0, 1.0, 2.0, 1.0, 2.0, 2.0,
+                        1.0, 1.0,
+                    ]),
+                ),
+                (GEOMETRY_TYPE, Field::Inline(vec![TYPE_POLYGON])),
+            ],
+        );
+        buffer[..4].copy_from_slice(&(table as u32).to_le_bytes());
+
+        let geometry = parse_geometry(&Table::root(&buffer).unwrap(), TYPE_UNKNOWN)
+            .unwrap()
+            .unwrap();
+        let Geom::Polygon(polygon) = geometry else {
+            panic!("expected polygon");
+        };
+        assert_eq!(polygon.outer_contour.points.len(), 3);
+        assert_eq!(polygon.inner_contours.len(), 1);
+
+        let geometry = parse_geometry(&Table::root(&buffer).unwrap(), TYPE_MULTILINESTRING)
+            .unwrap()
+            .unwrap();
+        assert_matches!(geometry, Geom::MultiContour(contours) if contours.contours().count() == 2);
+    }
+
+    #[test]
+    fn rejects_invalid_file() {
+        let dir = std::env::temp_dir().join(format!("galileo_fgb_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("invalid.fgb");
+        std::fs::write(&path, [0u8; 100]).unwrap();
+
+        let Err(GalileoError::Generic(message)) =
+            tokio_test::block_on(FlatGeobufSource::open_file(&path))
+        else {
+            panic!("invalid file must not be opened");
+        };
+        assert_eq!(message, "not a FlatGeobuf file");
+    }
+}
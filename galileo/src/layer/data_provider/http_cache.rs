@@ -0,0 +1,215 @@
+use web_time::{Duration, SystemTime};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::GalileoError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::layer::data_provider::{HttpSourceOptions, TileStore};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::platform::PlatformServiceImpl;
+#[cfg(not(target_arch = "wasm32"))]
+use bytes::Bytes;
+
+/// HTTP caching information of a cached response, used to decide when the cached data must be revalidated with the
+/// server.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheMetadata {
+    /// Value of the `ETag` header of the response.
+    pub etag: Option<String>,
+    /// Value of the `Last-Modified` header of the response.
+    pub last_modified: Option<String>,
+    /// Time after which the data must be revalidated, from the `Cache-Control: max-age` or `Expires` headers. If not
+    /// set, the data never expires.
+    pub expires: Option<SystemTime>,
+}
+
+impl CacheMetadata {
+    /// Creates the metadata from the values of the `Cache-Control`, `Expires`, `ETag` and `Last-Modified` headers of
+    /// a response received at `now`. Returns `None` if the response must not be stored (`Cache-Control: no-store`).
+    pub fn from_headers(
+        cache_control: Option<&str>,
+        expires: Option<&str>,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+        now: SystemTime,
+    ) -> Option<Self> {
+        let mut max_age = None;
+        for directive in cache_control.unwrap_or_default().split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            if directive == "no-store" {
+                return None;
+            } else if directive == "no-cache" {
+                max_age = Some(0);
+            } else if let Some(seconds) = directive.strip_prefix("max-age=") {
+                if let Ok(seconds) = seconds.trim_matches('"').parse::<u64>() {
+                    max_age = Some(max_age.map_or(seconds, |age: u64| age.min(seconds)));
+                }
+            }
+        }
+
+        let expires = match max_age {
+            Some(seconds) => Some(now + Duration::from_secs(seconds)),
+            None => expires.map(|value| parse_http_date(value).unwrap_or(now)),
+        };
+
+        Some(Self {
+            etag: etag.map(String::from),
+            last_modified: last_modified.map(String::from),
+            expires,
+        })
+    }
+
+    /// Returns `true` if the data can be used without revalidation at the `time`.
+    pub fn is_fresh(&self, time: SystemTime) -> bool {
+        self.expires.is_none_or(|expires| time < expires)
+    }
+}
+
+/// Parses the value of the `Retry-After` header of a response received at `now`, given either as a number of seconds
+/// or as an HTTP date.
+pub(crate) fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => Some(
+            parse_http_date(value)?
+                .duration_since(now)
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    httpdate::parse_http_date(value.trim()).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn parse_http_date(_value: &str) -> Option<SystemTime> {
+    None
+}
+
+/// Response to a request made with the validators of the cached data.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) enum HttpResponse {
+    /// New data and its metadata, or `None` if the data must not be stored.
+    Data(Bytes, Option<CacheMetadata>),
+    /// The cached data is still valid, with the updated metadata.
+    NotModified(CacheMetadata),
+}
+
+/// Loads the data from the `url`, using the `cache` according to the HTTP caching headers of the server.
+///
+/// Fresh cached data is returned without a request. Expired data is revalidated with a conditional request, and is
+/// also returned if the server cannot be reached or replies with an error. In `offline` mode only the cache is used.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn load_cached<Cache>(
+    platform_service: &PlatformServiceImpl,
+    options: &HttpSourceOptions,
+    url: &str,
+    cache: Option<&Cache>,
+    offline: bool,
+) -> Result<Bytes, GalileoError>
+where
+    Cache: TileStore,
+{
+    let cache_key = options.resolve_url(url);
+    let cached = match cache {
+        Some(cache) => cache.get_tile(&cache_key).await,
+        None => None,
+    };
+    if let Some((data, metadata)) = &cached {
+        if offline || metadata.is_fresh(SystemTime::now()) {
+            return Ok(data.clone());
+        }
+    }
+
+    if offline {
+        return Err(GalileoError::NotFound);
+    }
+
+    let request_url = options.request_url(&cache_key);
+    log::info!("Loading {cache_key}");
+    let response = platform_service
+        .load_with_validation(
+            &request_url,
+            options.headers(),
+            cached.as_ref().map(|(_, metadata)| metadata),
+        )
+        .await;
+
+    match response {
+        Ok(HttpResponse::NotModified(metadata)) => {
+            let (Some((data, _)), Some(cache)) = (cached, cache) else {
+                return Err(GalileoError::IO);
+            };
+            if let Err(error) = cache.update_tile_metadata(&cache_key, metadata).await {
+                log::warn!("Failed to write persistent cache entry: {:?}", error);
+            }
+
+            Ok(data)
+        }
+        Ok(HttpResponse::Data(data, metadata)) => {
+            if let (Some(cache), Some(metadata)) = (cache, metadata) {
+                if let Err(error) = cache.put_tile(&cache_key, data.clone(), metadata).await {
+                    log::warn!("Failed to write persistent cache entry: {:?}", error);
+                }
+            }
+
+            Ok(data)
+        }
+        Err(GalileoError::IO | GalileoError::Http { .. }) if cached.is_some() => {
+            log::debug!("Failed to revalidate {cache_key}, using the cached data");
+            cached.map(|(data, _)| data).ok_or(GalileoError::IO)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_values() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        assert_eq!(
+            parse_retry_after(" 120 ", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Mon, 12 Jan 1970 13:46:50 GMT", now),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            parse_retry_after("Thu, 01 Jan 1970 00:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn metadata_from_headers() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let metadata = CacheMetadata::from_headers(
+            Some("public, max-age=3600"),
+            Some("Thu, 01 Jan 1970 00:00:00 GMT"),
+            Some("\"abc\""),
+            None,
+            now,
+        )
+        .unwrap();
+        assert_eq!(metadata.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(metadata.expires, Some(now + Duration::from_secs(3600)));
+        assert!(metadata.is_fresh(now + Duration::from_secs(3599)));
+        assert!(!metadata.is_fresh(now + Duration::from_secs(3600)));
+
+        let no_cache = CacheMetadata::from_headers(Some("no-cache"), None, None, None, now);
+        assert!(!no_cache.unwrap().is_fresh(now));
+        assert_eq!(
+            CacheMetadata::from_headers(Some("No-Store"), None, None, None, now),
+            None
+        );
+
+        let without_headers = CacheMetadata::from_headers(None, None, None, None, now).unwrap();
+        assert!(without_headers.is_fresh(now + Duration::from_secs(1_000_000)));
+    }
+}
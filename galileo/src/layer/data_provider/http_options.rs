@@ -0,0 +1,223 @@
+/// Settings of the HTTP requests made by a url data provider, e.g. the credentials required by a commercial tile
+/// source.
+///
+/// * Headers are added to every request, e.g. an API key header or a bearer token.
+/// * Query parameters are appended to the url of every request, e.g. `?access_token=...`.
+/// * Subdomain placeholders in the url are replaced with one of the subdomains. A `{s}` placeholder is replaced with
+///   one of the [subdomains](HttpSourceOptions::with_subdomains) (`a`, `b` and `c` by default), and a range
+///   placeholder like `{a-c}` or `{1-4}` with one of the characters of the range. The same url always gets the same
+///   subdomain, so the requests are spread between the servers while the tiles are still cached by their url.
+///
+/// The url without the query parameters is used as the key of the persistent cache, so the credentials are not saved
+/// into the cache and changing them does not invalidate the cached data.
+///
+/// Headers cannot be set for images loaded by the browser on the web platform, so only the query parameters can be
+/// used to authenticate image tile sources there.
+///
+/// ```
+/// use galileo::layer::data_provider::{HttpSourceOptions, UrlImageProvider};
+/// use galileo::tile_scheme::TileIndex;
+///
+/// let mut provider = UrlImageProvider::new(|index: &TileIndex| {
+///     format!("https://{{a-c}}.tiles.example.com/{}/{}/{}.png", index.z, index.x, index.y)
+/// });
+/// provider.set_http_options(
+///     HttpSourceOptions::new()
+///         .with_bearer_token("secret-token")
+///         .with_query_param("app", "my-app"),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpSourceOptions {
+    headers: Vec<(String, String)>,
+    query_params: Vec<(String, String)>,
+    subdomains: Vec<String>,
+}
+
+impl Default for HttpSourceOptions {
+    fn default() -> Self {
+        Self {
+            headers: vec![],
+            query_params: vec![],
+            subdomains: ["a", "b", "c"].map(String::from).into(),
+        }
+    }
+}
+
+impl HttpSourceOptions {
+    /// Creates new options without any headers or query parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a header to every request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Adds the `Authorization: Bearer <token>` header to every request.
+    pub fn with_bearer_token(self, token: &str) -> Self {
+        self.with_header("Authorization", format!("Bearer {token}"))
+    }
+
+    /// Appends a query parameter to the url of every request. The name and the value are percent-encoded.
+    pub fn with_query_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query_params.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the subdomains a `{s}` placeholder in the url is replaced with. Default subdomains are `a`, `b` and `c`.
+    pub fn with_subdomains(
+        mut self,
+        subdomains: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.subdomains = subdomains.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Headers added to every request.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Query parameters appended to the url of every request.
+    pub fn query_params(&self) -> &[(String, String)] {
+        &self.query_params
+    }
+
+    /// Subdomains a `{s}` placeholder in the url is replaced with.
+    pub fn subdomains(&self) -> &[String] {
+        &self.subdomains
+    }
+
+    /// Replaces the subdomain placeholders in the `url`. The result is used as the key of the persistent cache.
+    pub(crate) fn resolve_url(&self, url: &str) -> String {
+        let hash = fnv_hash(url);
+        let mut resolved = String::with_capacity(url.len());
+        let mut rest = url;
+        while let Some(start) = rest.find('{') {
+            let Some(length) = rest[start..].find('}') else {
+                break;
+            };
+
+            resolved.push_str(&rest[..start]);
+            let placeholder = &rest[start + 1..start + length];
+            match self.subdomain_options(placeholder) {
+                Some(options) if !options.is_empty() => {
+                    resolved.push_str(&options[(hash % options.len() as u64) as usize]);
+                }
+                _ => resolved.push_str(&rest[start..=start + length]),
+            }
+            rest = &rest[start + length + 1..];
+        }
+        resolved.push_str(rest);
+
+        resolved
+    }
+
+    /// Appends the query parameters to the resolved url.
+    pub(crate) fn request_url(&self, resolved_url: &str) -> String {
+        if self.query_params.is_empty() {
+            return resolved_url.to_string();
+        }
+
+        let mut url = resolved_url.to_string();
+        let mut separator = if url.contains('?') { '&' } else { '?' };
+        for (name, value) in &self.query_params {
+            url.push(separator);
+            url.push_str(&percent_encode(name));
+            url.push('=');
+            url.push_str(&percent_encode(value));
+            separator = '&';
+        }
+
+        url
+    }
+
+    fn subdomain_options(&self, placeholder: &str) -> Option<Vec<String>> {
+        if placeholder == "s" {
+            return Some(self.subdomains.clone());
+        }
+
+        let mut chars = placeholder.chars();
+        let (Some(from), Some('-'), Some(to), None) =
+            (chars.next(), chars.next(), chars.next(), chars.next())
+        else {
+            return None;
+        };
+
+        let is_range = (from.is_ascii_lowercase() && to.is_ascii_lowercase())
+            || (from.is_ascii_uppercase() && to.is_ascii_uppercase())
+            || (from.is_ascii_digit() && to.is_ascii_digit());
+        (is_range && from <= to).then(|| (from..=to).map(String::from).collect())
+    }
+}
+
+/// Hash of the string that does not change between runs and platforms.
+fn fnv_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subdomains_are_stable_per_url() {
+        let options = HttpSourceOptions::new();
+        let resolved: Vec<_> = (0..12)
+            .map(|x| options.resolve_url(&format!("https://{{a-c}}.example.com/1/{x}/0.png")))
+            .collect();
+
+        for (x, url) in resolved.iter().enumerate() {
+            assert!(url.ends_with(&format!(".example.com/1/{x}/0.png")));
+            assert_eq!(
+                *url,
+                options.resolve_url(&format!("https://{{a-c}}.example.com/1/{x}/0.png"))
+            );
+        }
+        for subdomain in ["a", "b", "c"] {
+            assert!(resolved
+                .iter()
+                .any(|url| url.starts_with(&format!("https://{subdomain}."))));
+        }
+
+        let options = options.with_subdomains(["tiles1"]);
+        assert_eq!(
+            options.resolve_url("https://{s}.example.com/{z}/{1-1}.png"),
+            "https://tiles1.example.com/{z}/1.png"
+        );
+    }
+
+    #[test]
+    fn query_params_are_encoded() {
+        let options = HttpSourceOptions::new()
+            .with_query_param("key", "a b&c")
+            .with_query_param("app", "test");
+        assert_eq!(
+            options.request_url("https://example.com/tile.png"),
+            "https://example.com/tile.png?key=a%20b%26c&app=test"
+        );
+        assert_eq!(
+            options.request_url("https://example.com/tile.png?style=dark"),
+            "https://example.com/tile.png?style=dark&key=a%20b%26c&app=test"
+        );
+    }
+}
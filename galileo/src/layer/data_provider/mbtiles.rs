@@ -0,0 +1,356 @@
+//! Reading tiles from [MBTiles](https://github.com/mapbox/mbtiles-spec) archives.
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::{DataProcessor, DataProvider};
+use crate::layer::vector_tile_layer::tile_provider::{VectorTileDecodeContext, VtProcessor};
+use crate::render::render_bundle::RenderBundle;
+use crate::tile_scheme::TileIndex;
+use crate::TileSchema;
+use bytes::Bytes;
+use flate2::read::MultiGzDecoder;
+use galileo_mvt::MvtTile;
+use galileo_types::geo::GeoExtent;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Mutex;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// Loads tiles from an [MBTiles](https://github.com/mapbox/mbtiles-spec) archive (SQLite database).
+///
+/// The source can be used both by [`RasterTileLayer`](crate::layer::RasterTileLayer) and, through
+/// [`ThreadedProvider`](crate::layer::vector_tile_layer::tile_provider::ThreadedProvider), by
+/// [`VectorTileLayer`](crate::layer::VectorTileLayer). See
+/// [`MapBuilder::create_mbtiles_raster_layer`](crate::MapBuilder::create_mbtiles_raster_layer) and
+/// [`MapBuilder::create_mbtiles_vector_layer`](crate::MapBuilder::create_mbtiles_vector_layer) for the simplest way
+/// to create such layers.
+///
+/// Zoom range and bounds from the `metadata` table of the archive are respected: tiles outside of them are reported as
+/// [`GalileoError::NotFound`] without querying the database, and [`MbTilesSource::tile_schema`] contains only the
+/// z-levels stored in the archive. Gzip compressed tiles (usual for vector tiles) are decompressed transparently.
+pub struct MbTilesSource {
+    connection: Mutex<Connection>,
+    min_zoom: u32,
+    max_zoom: u32,
+    bounds: Option<GeoExtent>,
+    format: Option<String>,
+}
+
+fn sqlite_error(err: rusqlite::Error) -> GalileoError {
+    GalileoError::Generic(format!("failed to read MBTiles archive: {err}"))
+}
+
+impl MbTilesSource {
+    /// Opens an archive in read-only mode.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GalileoError> {
+        let connection = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(sqlite_error)?;
+
+        let metadata = Self::read_metadata(&connection)?;
+        let zoom = |key: &str| -> Result<Option<u32>, GalileoError> {
+            metadata
+                .get(key)
+                .map(|value| {
+                    value.trim().parse().map_err(|_| {
+                        GalileoError::Generic(format!("invalid MBTiles {key} value: {value}"))
+                    })
+                })
+                .transpose()
+        };
+        let min_zoom = zoom("minzoom")?;
+        let max_zoom = zoom("maxzoom")?;
+
+        // The zoom range is optional in the metadata, so the range of the stored tiles is used if it is missing.
+        let (min_zoom, max_zoom) = match (min_zoom, max_zoom) {
+            (Some(min), Some(max)) => (min, max),
+            (min, max) => {
+                let (stored_min, stored_max): (Option<u32>, Option<u32>) = connection
+                    .query_row(
+                        "SELECT MIN(zoom_level), MAX(zoom_level) FROM tiles",
+                        [],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .map_err(sqlite_error)?;
+                (
+                    min.or(stored_min).unwrap_or(0),
+                    max.or(stored_max).unwrap_or(0),
+                )
+            }
+        };
+
+        let bounds = metadata
+            .get("bounds")
+            .map(|value| Self::parse_bounds(value))
+            .transpose()?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            min_zoom,
+            max_zoom,
+            bounds,
+            format: metadata.get("format").cloned(),
+        })
+    }
+
+    fn read_metadata(connection: &Connection) -> Result<HashMap<String, String>, GalileoError> {
+        let mut statement = connection
+            .prepare("SELECT name, value FROM metadata")
+            .map_err(sqlite_error)?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(sqlite_error)?;
+        rows.collect::<Result<_, _>>().map_err(sqlite_error)
+    }
+
+    fn parse_bounds(value: &str) -> Result<GeoExtent, GalileoError> {
+        let values: Vec<f64> = value
+            .split(',')
+            .map(|v| v.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| GalileoError::Generic(format!("invalid MBTiles bounds: {value}")))?;
+        let [west, south, east, north] = values[..] else {
+            return Err(GalileoError::Generic(format!(
+                "invalid MBTiles bounds: {value}"
+            )));
+        };
+
+        Ok(GeoExtent::new(south, north, west, east))
+    }
+
+    /// Minimum z-level of the archive.
+    pub fn min_zoom(&self) -> u32 {
+        self.min_zoom
+    }
+
+    /// Maximum z-level of the archive.
+    pub fn max_zoom(&self) -> u32 {
+        self.max_zoom
+    }
+
+    /// Area covered by the archive, if specified in its metadata.
+    pub fn bounds(&self) -> Option<GeoExtent> {
+        self.bounds
+    }
+
+    /// Format of the tiles (for example, `png`, `jpg` or `pbf`), if specified in the archive metadata.
+    pub fn format(&self) -> Option<&str> {
+        self.format.as_deref()
+    }
+
+    /// Web Mercator tile schema with the z-levels of the archive.
+    pub fn tile_schema(&self) -> TileSchema {
+        let mut schema = TileSchema::web(self.max_zoom + 1);
+        schema.lods.retain(|lod| lod.z_index() >= self.min_zoom);
+        schema
+    }
+
+    fn contains(&self, index: &TileIndex) -> bool {
+        if index.z < self.min_zoom || index.z > self.max_zoom || index.z > 30 {
+            return false;
+        }
+
+        let count = 1i64 << index.z;
+        if !(0..count).contains(&(index.x as i64)) || !(0..count).contains(&(index.y as i64)) {
+            return false;
+        }
+
+        let Some(bounds) = self.bounds else {
+            return true;
+        };
+
+        let count = count as f64;
+        let lon = |x: i32| x as f64 / count * 360.0 - 180.0;
+        let lat = |y: i32| {
+            (PI * (1.0 - 2.0 * y as f64 / count))
+                .sinh()
+                .atan()
+                .to_degrees()
+        };
+        let (west, east) = (lon(index.x), lon(index.x + 1));
+        let (north, south) = (lat(index.y), lat(index.y + 1));
+
+        let lat_intersects = south < bounds.lat_max() && north > bounds.lat_min();
+        let lon_intersects = if bounds.crosses_antimeridian() {
+            east > bounds.lon_west() || west < bounds.lon_east()
+        } else {
+            west < bounds.lon_east() && east > bounds.lon_west()
+        };
+
+        lat_intersects && lon_intersects
+    }
+
+    fn load_tile(&self, index: &TileIndex) -> Result<Bytes, GalileoError> {
+        if !self.contains(index) {
+            return Err(GalileoError::NotFound);
+        }
+
+        // MBTiles use TMS scheme with Y axis directed from bottom to top.
+        let row = (1i64 << index.z) - 1 - index.y as i64;
+        let data: Vec<u8> = self
+            .connection
+            .lock()
+            .expect("connection mutex is poisoned")
+            .query_row(
+                "SELECT tile_data FROM tiles WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+                (index.z, index.x, row),
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .ok_or(GalileoError::NotFound)?;
+
+        if !data.starts_with(GZIP_MAGIC) {
+            return Ok(data.into());
+        }
+
+        let mut decompressed = vec![];
+        MultiGzDecoder::new(&data[..])
+            .read_to_end(&mut decompressed)
+            .map_err(|err| GalileoError::Generic(format!("failed to decompress tile: {err}")))?;
+        Ok(decompressed.into())
+    }
+}
+
+impl DataProvider<TileIndex, DecodedImage, ()> for MbTilesSource {
+    async fn load_raw(&self, key: &TileIndex) -> Result<Bytes, GalileoError> {
+        self.load_tile(key)
+    }
+
+    fn decode(&self, bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+        DecodedImage::new(&bytes)
+    }
+}
+
+impl DataProvider<TileIndex, (RenderBundle, MvtTile), VectorTileDecodeContext> for MbTilesSource {
+    async fn load_raw(&self, key: &TileIndex) -> Result<Bytes, GalileoError> {
+        self.load_tile(key)
+    }
+
+    fn decode(
+        &self,
+        bytes: Bytes,
+        context: VectorTileDecodeContext,
+    ) -> Result<(RenderBundle, MvtTile), GalileoError> {
+        VtProcessor {}.process(bytes, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    fn create_archive(name: &str, metadata: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("galileo_mbtiles_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = std::fs::remove_file(&path);
+
+        let connection = Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE metadata (name TEXT, value TEXT);
+                CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);",
+            )
+            .unwrap();
+        for (name, value) in metadata {
+            connection
+                .execute("INSERT INTO metadata VALUES (?1, ?2)", (name, value))
+                .unwrap();
+        }
+
+        let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(b"compressed").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        for (z, x, row, data) in [
+            (1, 0, 1, b"top left".to_vec()),
+            (1, 1, 0, b"bottom right".to_vec()),
+            (2, 2, 3, compressed),
+        ] {
+            connection
+                .execute(
+                    "INSERT INTO tiles VALUES (?1, ?2, ?3, ?4)",
+                    (z, x, row, data),
+                )
+                .unwrap();
+        }
+
+        path
+    }
+
+    fn index(z: u32, x: i32, y: i32) -> TileIndex {
+        TileIndex {
+            z,
+            x,
+            y,
+            display_x: x,
+        }
+    }
+
+    fn load(source: &MbTilesSource, index: TileIndex) -> Result<Bytes, GalileoError> {
+        tokio_test::block_on(<MbTilesSource as DataProvider<
+            TileIndex,
+            DecodedImage,
+            (),
+        >>::load_raw(source, &index))
+    }
+
+    #[test]
+    fn loads_tiles() {
+        let path = create_archive("tiles.mbtiles", &[("format", "png")]);
+        let source = MbTilesSource::open(&path).unwrap();
+        assert_eq!(source.format(), Some("png"));
+        assert_eq!(source.min_zoom(), 1);
+        assert_eq!(source.max_zoom(), 2);
+        assert_eq!(source.bounds(), None);
+
+        assert_eq!(load(&source, index(1, 0, 0)).unwrap(), "top left");
+        assert_eq!(load(&source, index(1, 1, 1)).unwrap(), "bottom right");
+        assert_eq!(load(&source, index(2, 2, 0)).unwrap(), "compressed");
+        assert_matches!(load(&source, index(1, 1, 0)), Err(GalileoError::NotFound));
+    }
+
+    #[test]
+    fn metadata_limits_tiles() {
+        let path = create_archive(
+            "limited.mbtiles",
+            &[
+                ("minzoom", "1"),
+                ("maxzoom", "1"),
+                ("bounds", "-180,0,0,85"),
+            ],
+        );
+        let source = MbTilesSource::open(&path).unwrap();
+        assert_eq!(
+            source.bounds(),
+            Some(GeoExtent::new(0.0, 85.0, -180.0, 0.0))
+        );
+
+        assert_eq!(load(&source, index(1, 0, 0)).unwrap(), "top left");
+        // Stored in the archive, but outside of the bounds.
+        assert_matches!(load(&source, index(1, 1, 1)), Err(GalileoError::NotFound));
+        // Stored in the archive, but outside of the zoom range.
+        assert_matches!(load(&source, index(2, 2, 0)), Err(GalileoError::NotFound));
+
+        let schema = source.tile_schema();
+        let levels: Vec<_> = schema.lods.iter().map(|lod| lod.z_index()).collect();
+        assert_eq!(levels, vec![1]);
+    }
+
+    #[test]
+    fn invalid_metadata_is_an_error() {
+        let path = create_archive("invalid.mbtiles", &[("bounds", "1,2,3")]);
+        assert!(MbTilesSource::open(&path).is_err());
+    }
+}
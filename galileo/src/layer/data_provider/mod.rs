@@ -1,8 +1,17 @@
 //! Data sources for layers.
 
+mod http_cache;
+mod http_options;
+mod tile_store;
 mod url_data_provider;
 mod url_image_provider;
 
+pub(crate) use http_cache::parse_retry_after;
+pub use http_cache::CacheMetadata;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use http_cache::{load_cached, HttpResponse};
+pub use http_options::HttpSourceOptions;
+pub use tile_store::{MemoryTileStore, TileStore};
 pub use url_data_provider::UrlDataProvider;
 pub use url_image_provider::UrlImageProvider;
 
@@ -10,13 +19,58 @@ pub use url_image_provider::UrlImageProvider;
 mod file_cache;
 
 #[cfg(not(target_arch = "wasm32"))]
-pub use file_cache::FileCacheController;
+pub use file_cache::{FileCacheController, FileCacheLimits};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod tile_downloader;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use tile_downloader::{DownloadProgress, TileDownloader};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod range_reader;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod flatgeobuf;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use flatgeobuf::{FlatGeobufFeature, FlatGeobufSource, FlatGeobufValue};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod cog;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use cog::{CogCompression, CogSource};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use cog::CogTile;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod pmtiles;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use pmtiles::{PmTilesSource, PmTilesTileType};
+
+#[cfg(all(feature = "mbtiles", not(target_arch = "wasm32")))]
+mod mbtiles;
+
+#[cfg(all(feature = "mbtiles", not(target_arch = "wasm32")))]
+pub use mbtiles::MbTilesSource;
 
 use crate::error::GalileoError;
 use bytes::Bytes;
 use maybe_sync::{MaybeSend, MaybeSync};
 use std::future::Future;
 
+/// Current usage of a tile cache.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CacheUsage {
+    /// Number of entries in the cache.
+    pub entries: usize,
+    /// Total size of the cached entries in bytes.
+    pub bytes: u64,
+}
+
 /// Data provider is a generic way to load and decode data for a layer.
 ///
 /// The purpose of data providers is to encapsulate the details of where the data for a layer comes from. Data providers
@@ -68,11 +122,39 @@ pub trait DataProcessor {
 }
 
 /// Persistent cache for a data of type `Data` with a key `Key`.
+///
+/// A cache of bytes with string keys is also a [`TileStore`] and can be used by the url data providers. Implement
+/// [`TileStore`] directly for storages that must be accessed asynchronously.
+///
+/// Url data providers save the [HTTP caching metadata](CacheMetadata) of the responses together with the data, and
+/// revalidate the data with the server when it expires. Caches that do not store the metadata (using the default
+/// implementations of the `*_metadata` methods) keep the data forever.
 pub trait PersistentCacheController<Key: ?Sized, Data> {
     /// Loads data item from the cache.
     fn get(&self, key: &Key) -> Option<Data>;
     /// Puts data item from the cache, replacing existing value if any.
     fn insert(&self, key: &Key, data: &Data) -> Result<(), GalileoError>;
+
+    /// Loads data item from the cache together with its HTTP caching metadata.
+    fn get_with_metadata(&self, key: &Key) -> Option<(Data, CacheMetadata)> {
+        self.get(key).map(|data| (data, CacheMetadata::default()))
+    }
+
+    /// Puts data item with its HTTP caching metadata into the cache, replacing existing value if any.
+    fn insert_with_metadata(
+        &self,
+        key: &Key,
+        data: &Data,
+        _metadata: &CacheMetadata,
+    ) -> Result<(), GalileoError> {
+        self.insert(key, data)
+    }
+
+    /// Replaces the HTTP caching metadata of the cached item, e.g. after the server confirmed that the item has not
+    /// changed.
+    fn update_metadata(&self, _key: &Key, _metadata: &CacheMetadata) -> Result<(), GalileoError> {
+        Ok(())
+    }
 }
 
 /// Method that constructs URL address to load a data item using the data key.
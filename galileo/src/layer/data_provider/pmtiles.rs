@@ -0,0 +1,592 @@
+//! Reading tiles from [PMTiles](https://github.com/protomaps/PMTiles) archives.
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::range_reader::RangeReader;
+use crate::layer::data_provider::{DataProcessor, DataProvider};
+use crate::layer::vector_tile_layer::tile_provider::{VectorTileDecodeContext, VtProcessor};
+use crate::render::render_bundle::RenderBundle;
+use crate::tile_scheme::TileIndex;
+use bytes::Bytes;
+use flate2::read::MultiGzDecoder;
+use galileo_mvt::MvtTile;
+use quick_cache::sync::Cache;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+const MAGIC: &[u8] = b"PMTiles";
+const VERSION: u8 = 3;
+const HEADER_LEN: u64 = 127;
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// The specification allows only a root directory and one level of leaf directories, but some writers produce
+/// deeper trees.
+const MAX_DIRECTORY_DEPTH: usize = 4;
+const LEAF_CACHE_SIZE: usize = 64;
+
+/// Type of the tiles stored in a PMTiles archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PmTilesTileType {
+    /// Type is not specified in the archive.
+    Unknown,
+    /// Mapbox vector tiles.
+    Mvt,
+    /// PNG images.
+    Png,
+    /// JPEG images.
+    Jpeg,
+    /// WebP images.
+    Webp,
+    /// AVIF images.
+    Avif,
+}
+
+impl PmTilesTileType {
+    fn from_byte(value: u8) -> Self {
+        match value {
+            1 => Self::Mvt,
+            2 => Self::Png,
+            3 => Self::Jpeg,
+            4 => Self::Webp,
+            5 => Self::Avif,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    Unknown,
+    None,
+    Gzip,
+    Brotli,
+    Zstd,
+}
+
+impl Compression {
+    fn from_byte(value: u8) -> Self {
+        match value {
+            1 => Self::None,
+            2 => Self::Gzip,
+            3 => Self::Brotli,
+            4 => Self::Zstd,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn decompress(self, bytes: Bytes) -> Result<Bytes, GalileoError> {
+        let is_gzip = match self {
+            Self::None => false,
+            Self::Gzip => true,
+            // Archives with unknown compression are written by old tools, that only used gzip, if any.
+            Self::Unknown => bytes.starts_with(GZIP_MAGIC),
+            Self::Brotli | Self::Zstd => {
+                return Err(GalileoError::Generic(format!(
+                    "{self:?} compression of PMTiles archives is not supported"
+                )))
+            }
+        };
+
+        if !is_gzip {
+            return Ok(bytes);
+        }
+
+        let mut decompressed = vec![];
+        MultiGzDecoder::new(&bytes[..])
+            .read_to_end(&mut decompressed)
+            .map_err(|err| {
+                GalileoError::Generic(format!("failed to decompress PMTiles data: {err}"))
+            })?;
+        Ok(decompressed.into())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Header {
+    root_directory_offset: u64,
+    root_directory_length: u64,
+    leaf_directories_offset: u64,
+    tile_data_offset: u64,
+    internal_compression: Compression,
+    tile_compression: Compression,
+    tile_type: PmTilesTileType,
+    min_zoom: u8,
+    max_zoom: u8,
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> Result<Self, GalileoError> {
+        if bytes.len() < HEADER_LEN as usize || !bytes.starts_with(MAGIC) {
+            return Err(GalileoError::Generic("not a PMTiles archive".into()));
+        }
+
+        if bytes[7] != VERSION {
+            return Err(GalileoError::Generic(format!(
+                "unsupported PMTiles version {}",
+                bytes[7]
+            )));
+        }
+
+        let read_u64 = |offset: usize| {
+            u64::from_le_bytes(
+                bytes[offset..offset + 8]
+                    .try_into()
+                    .expect("slice has 8 bytes"),
+            )
+        };
+
+        Ok(Self {
+            root_directory_offset: read_u64(8),
+            root_directory_length: read_u64(16),
+            leaf_directories_offset: read_u64(40),
+            tile_data_offset: read_u64(56),
+            internal_compression: Compression::from_byte(bytes[97]),
+            tile_compression: Compression::from_byte(bytes[98]),
+            tile_type: PmTilesTileType::from_byte(bytes[99]),
+            min_zoom: bytes[100],
+            max_zoom: bytes[101],
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Entry {
+    tile_id: u64,
+    offset: u64,
+    length: u64,
+    run_length: u64,
+}
+
+type Directory = Arc<Vec<Entry>>;
+
+fn read_varint(bytes: &[u8], position: &mut usize) -> Result<u64, GalileoError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let Some(&byte) = bytes.get(*position) else {
+            break;
+        };
+        *position += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(GalileoError::Generic(
+        "invalid varint in PMTiles directory".into(),
+    ))
+}
+
+fn parse_directory(bytes: &[u8]) -> Result<Vec<Entry>, GalileoError> {
+    let mut position = 0;
+    let count = read_varint(bytes, &mut position)? as usize;
+    // Every entry takes at least 4 bytes, so this check prevents huge allocations for corrupt data.
+    if count > bytes.len() {
+        return Err(GalileoError::Generic(
+            "invalid PMTiles directory entry count".into(),
+        ));
+    }
+
+    let mut entries = vec![Entry::default(); count];
+    let mut tile_id = 0u64;
+    for entry in &mut entries {
+        tile_id = tile_id.wrapping_add(read_varint(bytes, &mut position)?);
+        entry.tile_id = tile_id;
+    }
+    for entry in &mut entries {
+        entry.run_length = read_varint(bytes, &mut position)?;
+    }
+    for entry in &mut entries {
+        entry.length = read_varint(bytes, &mut position)?;
+    }
+    for i in 0..count {
+        let value = read_varint(bytes, &mut position)?;
+        // Zero offset means that the data directly follows the data of the previous entry.
+        entries[i].offset = match (value, i) {
+            (0, 0) => {
+                return Err(GalileoError::Generic(
+                    "invalid offset of the first PMTiles directory entry".into(),
+                ))
+            }
+            (0, _) => entries[i - 1].offset + entries[i - 1].length,
+            (value, _) => value - 1,
+        };
+    }
+
+    Ok(entries)
+}
+
+/// Returns the entry that contains the tile, or the leaf directory entry that may contain it.
+fn find_entry(entries: &[Entry], tile_id: u64) -> Option<Entry> {
+    let index = entries.partition_point(|entry| entry.tile_id <= tile_id);
+    let entry = *entries.get(index.checked_sub(1)?)?;
+    if entry.run_length == 0 || tile_id - entry.tile_id < entry.run_length {
+        Some(entry)
+    } else {
+        None
+    }
+}
+
+/// Position of the tile on the Hilbert curve, that is used by PMTiles to order tiles.
+fn tile_id(z: u32, x: i32, y: i32) -> Option<u64> {
+    if z > 31 {
+        return None;
+    }
+
+    let size = 1u64 << z;
+    let (mut x, mut y) = (u64::try_from(x).ok()?, u64::try_from(y).ok()?);
+    if x >= size || y >= size {
+        return None;
+    }
+
+    let lower_levels_count = ((1u64 << (2 * z)) - 1) / 3;
+    let mut distance = 0;
+    let mut s = size / 2;
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        distance += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - (x & (s - 1));
+                y = s - 1 - (y & (s - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+
+    Some(lower_levels_count + distance)
+}
+
+/// Loads tiles from a [PMTiles](https://github.com/protomaps/PMTiles) (version 3) archive, stored in a local file
+/// or on an HTTP server supporting range requests.
+///
+/// The source reads only the parts of the archive that are needed to get a requested tile, so it can be used with
+/// large archives without a tile server. It can be used both by [`RasterTileLayer`](crate::layer::RasterTileLayer)
+/// and, through [`ThreadedProvider`](crate::layer::vector_tile_layer::tile_provider::ThreadedProvider), by
+/// [`VectorTileLayer`](crate::layer::VectorTileLayer). Tiles in PMTiles archives are indexed with XYZ scheme, so
+/// the layers should use [`TileSchema::web`](crate::TileSchema::web) or any other schema with the same indices.
+///
+/// Gzip compressed archives and tiles are supported. A tile missing from the archive is reported as
+/// [`GalileoError::NotFound`].
+///
+/// ```no_run
+/// # async fn open() -> Result<(), galileo::error::GalileoError> {
+/// use galileo::layer::data_provider::PmTilesSource;
+/// use galileo::layer::RasterTileLayer;
+/// use galileo::TileSchema;
+///
+/// let source = PmTilesSource::open_file("data/tiles.pmtiles").await?;
+/// let layer = RasterTileLayer::new(TileSchema::web(source.max_zoom() as u32 + 1), source, None);
+/// # Ok(())
+/// # }
+/// ```
+pub struct PmTilesSource {
+    backend: RangeReader,
+    header: Header,
+    root_directory: Directory,
+    leaf_directories: Cache<(u64, u64), Directory>,
+}
+
+impl PmTilesSource {
+    /// Opens an archive stored in a local file.
+    pub async fn open_file(path: impl AsRef<Path>) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::file(path)?).await
+    }
+
+    /// Opens an archive at the given URL. The server must support HTTP range requests.
+    pub async fn open_url(url: impl Into<String>) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::url(url, None)).await
+    }
+
+    /// Opens an archive at the given URL, making all the requests with the given HTTP client.
+    pub async fn open_url_with_http_client(
+        url: impl Into<String>,
+        client: reqwest::Client,
+    ) -> Result<Self, GalileoError> {
+        Self::open(RangeReader::url(url, Some(client))).await
+    }
+
+    async fn open(backend: RangeReader) -> Result<Self, GalileoError> {
+        let header = Header::parse(&backend.read(0, HEADER_LEN).await?)?;
+        let root_directory = backend
+            .read(header.root_directory_offset, header.root_directory_length)
+            .await?;
+        let root_directory =
+            parse_directory(&header.internal_compression.decompress(root_directory)?)?;
+
+        Ok(Self {
+            backend,
+            header,
+            root_directory: Arc::new(root_directory),
+            leaf_directories: Cache::new(LEAF_CACHE_SIZE),
+        })
+    }
+
+    /// Type of the tiles in the archive.
+    pub fn tile_type(&self) -> PmTilesTileType {
+        self.header.tile_type
+    }
+
+    /// Minimum z-level of the tiles in the archive.
+    pub fn min_zoom(&self) -> u8 {
+        self.header.min_zoom
+    }
+
+    /// Maximum z-level of the tiles in the archive.
+    pub fn max_zoom(&self) -> u8 {
+        self.header.max_zoom
+    }
+
+    async fn leaf_directory(&self, offset: u64, length: u64) -> Result<Directory, GalileoError> {
+        let key = (offset, length);
+        if let Some(directory) = self.leaf_directories.get(&key) {
+            return Ok(directory);
+        }
+
+        let bytes = self
+            .backend
+            .read(self.header.leaf_directories_offset + offset, length)
+            .await?;
+        let directory = Arc::new(parse_directory(
+            &self.header.internal_compression.decompress(bytes)?,
+        )?);
+        self.leaf_directories.insert(key, directory.clone());
+
+        Ok(directory)
+    }
+
+    async fn load_tile(&self, index: &TileIndex) -> Result<Bytes, GalileoError> {
+        let tile_id = tile_id(index.z, index.x, index.y).ok_or(GalileoError::NotFound)?;
+
+        let mut directory = self.root_directory.clone();
+        for _ in 0..MAX_DIRECTORY_DEPTH {
+            let entry = find_entry(&directory, tile_id).ok_or(GalileoError::NotFound)?;
+            if entry.run_length > 0 {
+                let bytes = self
+                    .backend
+                    .read(self.header.tile_data_offset + entry.offset, entry.length)
+                    .await?;
+                return self.header.tile_compression.decompress(bytes);
+            }
+
+            directory = self.leaf_directory(entry.offset, entry.length).await?;
+        }
+
+        Err(GalileoError::Generic(
+            "PMTiles directories are nested too deep".into(),
+        ))
+    }
+}
+
+impl DataProvider<TileIndex, DecodedImage, ()> for PmTilesSource {
+    async fn load_raw(&self, key: &TileIndex) -> Result<Bytes, GalileoError> {
+        self.load_tile(key).await
+    }
+
+    fn decode(&self, bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+        DecodedImage::new(&bytes)
+    }
+}
+
+impl DataProvider<TileIndex, (RenderBundle, MvtTile), VectorTileDecodeContext> for PmTilesSource {
+    async fn load_raw(&self, key: &TileIndex) -> Result<Bytes, GalileoError> {
+        self.load_tile(key).await
+    }
+
+    fn decode(
+        &self,
+        bytes: Bytes,
+        context: VectorTileDecodeContext,
+    ) -> Result<(RenderBundle, MvtTile), GalileoError> {
+        VtProcessor {}.process(bytes, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            buffer.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        buffer.push(value as u8);
+    }
+
+    fn directory(entries: &[Entry]) -> Vec<u8> {
+        let mut buffer = vec![];
+        write_varint(&mut buffer, entries.len() as u64);
+        let mut last_id = 0;
+        for entry in entries {
+            write_varint(&mut buffer, entry.tile_id - last_id);
+            last_id = entry.tile_id;
+        }
+        for entry in entries {
+            write_varint(&mut buffer, entry.run_length);
+        }
+        for entry in entries {
+            write_varint(&mut buffer, entry.length);
+        }
+        for (i, entry) in entries.iter().enumerate() {
+            let is_contiguous =
+                i > 0 && entries[i - 1].offset + entries[i - 1].length == entry.offset;
+            write_varint(
+                &mut buffer,
+                if is_contiguous { 0 } else { entry.offset + 1 },
+            );
+        }
+        buffer
+    }
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn entry(tile_id: u64, offset: u64, length: u64, run_length: u64) -> Entry {
+        Entry {
+            tile_id,
+            offset,
+            length,
+            run_length,
+        }
+    }
+
+    /// Writes an archive with gzip compressed directories and tiles:
+    /// * tiles 0 and 1 share the same data,
+    /// * tile 2 has its own data,
+    /// * tile 5 is stored in a leaf directory.
+    fn write_archive(name: &str) -> PathBuf {
+        let tiles = [gzip(b"first"), gzip(b"second"), gzip(b"leaf")];
+        let mut tile_data = vec![];
+        let mut tile_entries = vec![];
+        for tile in &tiles {
+            tile_entries.push((tile_data.len() as u64, tile.len() as u64));
+            tile_data.extend_from_slice(tile);
+        }
+
+        let leaf = gzip(&directory(&[entry(
+            5,
+            tile_entries[2].0,
+            tile_entries[2].1,
+            1,
+        )]));
+        let root = gzip(&directory(&[
+            entry(0, tile_entries[0].0, tile_entries[0].1, 2),
+            entry(2, tile_entries[1].0, tile_entries[1].1, 1),
+            entry(5, 0, leaf.len() as u64, 0),
+        ]));
+
+        let root_offset = HEADER_LEN;
+        let leaf_offset = root_offset + root.len() as u64;
+        let tile_data_offset = leaf_offset + leaf.len() as u64;
+
+        let mut header = vec![0u8; HEADER_LEN as usize];
+        header[..7].copy_from_slice(MAGIC);
+        header[7] = VERSION;
+        for (position, value) in [
+            (8, root_offset),
+            (16, root.len() as u64),
+            (40, leaf_offset),
+            (48, leaf.len() as u64),
+            (56, tile_data_offset),
+            (64, tile_data.len() as u64),
+        ] {
+            header[position..position + 8].copy_from_slice(&value.to_le_bytes());
+        }
+        header[97] = 2;
+        header[98] = 2;
+        header[99] = 2;
+        header[100] = 0;
+        header[101] = 2;
+
+        let dir = std::env::temp_dir().join(format!("galileo_pmtiles_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, [header, root, leaf, tile_data].concat()).unwrap();
+        path
+    }
+
+    fn index(z: u32, x: i32, y: i32) -> TileIndex {
+        TileIndex {
+            z,
+            x,
+            y,
+            display_x: x,
+        }
+    }
+
+    fn load(source: &PmTilesSource, index: TileIndex) -> Result<Bytes, GalileoError> {
+        tokio_test::block_on(<PmTilesSource as DataProvider<
+            TileIndex,
+            DecodedImage,
+            (),
+        >>::load_raw(source, &index))
+    }
+
+    #[test]
+    fn tile_ids() {
+        assert_eq!(tile_id(0, 0, 0), Some(0));
+        assert_eq!(tile_id(1, 0, 0), Some(1));
+        assert_eq!(tile_id(1, 0, 1), Some(2));
+        assert_eq!(tile_id(1, 1, 1), Some(3));
+        assert_eq!(tile_id(1, 1, 0), Some(4));
+        assert_eq!(tile_id(2, 0, 0), Some(5));
+        assert_eq!(tile_id(2, 3, 0), Some(20));
+        assert_eq!(tile_id(1, 2, 0), None);
+        assert_eq!(tile_id(1, -1, 0), None);
+    }
+
+    #[test]
+    fn directory_round_trip() {
+        let entries = vec![
+            entry(0, 0, 10, 1),
+            entry(1, 10, 20, 3),
+            entry(300, 1000, 5, 0),
+        ];
+        assert_eq!(parse_directory(&directory(&entries)).unwrap(), entries);
+        assert!(parse_directory(&[3, 0]).is_err());
+    }
+
+    #[test]
+    fn loads_tiles() {
+        let path = write_archive("tiles.pmtiles");
+        let source = tokio_test::block_on(PmTilesSource::open_file(&path)).unwrap();
+        assert_eq!(source.tile_type(), PmTilesTileType::Png);
+        assert_eq!(source.min_zoom(), 0);
+        assert_eq!(source.max_zoom(), 2);
+
+        assert_eq!(load(&source, index(0, 0, 0)).unwrap(), "first");
+        assert_eq!(load(&source, index(1, 0, 0)).unwrap(), "first");
+        assert_eq!(load(&source, index(1, 0, 1)).unwrap(), "second");
+        assert_eq!(load(&source, index(2, 0, 0)).unwrap(), "leaf");
+
+        assert_matches!(load(&source, index(1, 1, 1)), Err(GalileoError::NotFound));
+        assert_matches!(load(&source, index(2, 3, 0)), Err(GalileoError::NotFound));
+        assert_matches!(load(&source, index(1, 5, 0)), Err(GalileoError::NotFound));
+    }
+
+    #[test]
+    fn rejects_invalid_archive() {
+        let dir = std::env::temp_dir().join(format!("galileo_pmtiles_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("invalid.pmtiles");
+        std::fs::write(&path, [0u8; 200]).unwrap();
+
+        let Err(GalileoError::Generic(message)) =
+            tokio_test::block_on(PmTilesSource::open_file(&path))
+        else {
+            panic!("invalid archive must not be opened");
+        };
+        assert_eq!(message, "not a PMTiles archive");
+    }
+}
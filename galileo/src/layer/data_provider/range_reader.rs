@@ -0,0 +1,63 @@
+use crate::error::GalileoError;
+use crate::platform::{PlatformService, PlatformServiceImpl};
+use bytes::Bytes;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Mutex;
+
+/// Reads byte ranges of a resource stored in a local file or on an HTTP server supporting range requests.
+pub(crate) enum RangeReader {
+    File(Mutex<File>),
+    Http {
+        url: String,
+        platform_service: PlatformServiceImpl,
+    },
+}
+
+impl RangeReader {
+    /// Opens a local file.
+    pub(crate) fn file(path: impl AsRef<std::path::Path>) -> Result<Self, GalileoError> {
+        Ok(Self::File(Mutex::new(File::open(path)?)))
+    }
+
+    /// Creates a reader for the resource at the given URL. If `http_client` is set, all the requests are made with
+    /// that client.
+    pub(crate) fn url(url: impl Into<String>, http_client: Option<reqwest::Client>) -> Self {
+        Self::Http {
+            url: url.into(),
+            platform_service: match http_client {
+                Some(client) => PlatformServiceImpl::with_http_client(client),
+                None => PlatformServiceImpl::new(),
+            },
+        }
+    }
+
+    /// Reads `length` bytes starting at `offset`.
+    pub(crate) async fn read(&self, offset: u64, length: u64) -> Result<Bytes, GalileoError> {
+        match self {
+            Self::File(file) => {
+                let mut file = file.lock().expect("file mutex is poisoned");
+                // The buffer is allocated only for the bytes the file has, so a corrupted length cannot exhaust
+                // the memory.
+                let file_len = file.metadata()?.len();
+                if offset.checked_add(length).is_none_or(|end| end > file_len) {
+                    return Err(GalileoError::Generic(format!(
+                        "range {offset}+{length} is outside of the file of {file_len} bytes"
+                    )));
+                }
+                file.seek(SeekFrom::Start(offset))?;
+                let mut buffer = vec![0; length as usize];
+                file.read_exact(&mut buffer)?;
+                Ok(buffer.into())
+            }
+            Self::Http {
+                url,
+                platform_service,
+            } => {
+                platform_service
+                    .load_range_from_url(url, offset, length)
+                    .await
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::error::GalileoError;
+use crate::layer::data_provider::{HttpResponse, HttpSourceOptions, TileStore, UrlSource};
+use crate::platform::{PlatformService, PlatformServiceImpl};
+use crate::tile_scheme::{TileIndex, TileSchema};
+use futures::StreamExt;
+use galileo_types::cartesian::Rect;
+use std::ops::RangeInclusive;
+
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 6;
+
+/// Downloads all the tiles of an area into a persistent cache, so that they can be used later without network
+/// access.
+///
+/// Tiles are saved to the cache with their urls as keys. A [`UrlImageProvider`](super::UrlImageProvider) created
+/// with the same url source and cache then finds them in the cache, including in
+/// [offline mode](super::UrlImageProvider::set_offline_mode). Any [`TileStore`] can be used as the output, e.g.
+/// [`FileCacheController`](super::FileCacheController).
+///
+/// Tiles that are already in the cache are not downloaded again, so an interrupted download can be resumed by
+/// starting it again with the same parameters.
+///
+/// ```no_run
+/// use galileo::layer::data_provider::{FileCacheController, TileDownloader};
+/// use galileo::tile_scheme::TileIndex;
+/// use galileo::TileSchema;
+/// use galileo_types::cartesian::Rect;
+///
+/// # async fn download() {
+/// let downloader = TileDownloader::new(
+///     |index: &TileIndex| {
+///         format!("https://tile.openstreetmap.org/{}/{}/{}.png", index.z, index.x, index.y)
+///     },
+///     FileCacheController::new(".tile_cache"),
+/// );
+///
+/// let progress = downloader
+///     .download(
+///         &TileSchema::web(18),
+///         Rect::new(-10_000.0, -10_000.0, 10_000.0, 10_000.0),
+///         10..=14,
+///         |progress| println!("{} of {} tiles", progress.processed(), progress.total),
+///     )
+///     .await;
+/// assert_eq!(progress.failed, 0);
+/// # }
+/// ```
+pub struct TileDownloader<Cache> {
+    url_source: Box<dyn UrlSource<TileIndex>>,
+    cache: Cache,
+    platform_service: PlatformServiceImpl,
+    http_options: HttpSourceOptions,
+    max_concurrent_requests: usize,
+}
+
+/// Progress of downloading tiles by [`TileDownloader::download`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Number of tiles downloaded and saved into the cache.
+    pub downloaded: usize,
+    /// Number of tiles that were already in the cache.
+    pub skipped: usize,
+    /// Number of tiles that could not be downloaded or saved.
+    pub failed: usize,
+    /// Total number of tiles to download.
+    pub total: usize,
+}
+
+impl DownloadProgress {
+    /// Number of tiles that are downloaded, skipped or failed.
+    pub fn processed(&self) -> usize {
+        self.downloaded + self.skipped + self.failed
+    }
+
+    /// Returns true if all the tiles are processed.
+    pub fn is_complete(&self) -> bool {
+        self.processed() >= self.total
+    }
+}
+
+enum TileResult {
+    Downloaded,
+    Skipped,
+    Failed,
+}
+
+impl<Cache> TileDownloader<Cache>
+where
+    Cache: TileStore,
+{
+    /// Creates a new downloader that loads tiles from the urls given by the `url_source` and saves them into the
+    /// `cache`.
+    pub fn new(url_source: impl UrlSource<TileIndex> + 'static, cache: Cache) -> Self {
+        Self {
+            url_source: Box::new(url_source),
+            cache,
+            platform_service: PlatformServiceImpl::new(),
+            http_options: HttpSourceOptions::default(),
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+        }
+    }
+
+    /// Sets the maximum number of tiles downloaded simultaneously. Default value is `6`. Value of `0` is treated
+    /// as `1`.
+    pub fn with_max_concurrent_requests(mut self, max_requests: usize) -> Self {
+        self.max_concurrent_requests = max_requests.max(1);
+        self
+    }
+
+    /// Sets the HTTP client used to download the tiles.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.platform_service = PlatformServiceImpl::with_http_client(client);
+        self
+    }
+
+    /// Sets the headers, query parameters and subdomains of the requests. Use the same options as for the
+    /// [`UrlImageProvider`](super::UrlImageProvider) reading the tiles, so that it finds them in the cache.
+    pub fn with_http_options(mut self, options: HttpSourceOptions) -> Self {
+        self.http_options = options;
+        self
+    }
+
+    /// Cache the tiles are saved to.
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    /// Returns indices of all the tiles of the `schema` intersecting the `extent` for the z-levels in the
+    /// `z_levels` range. The `extent` is given in the CRS of the schema.
+    pub fn tiles(
+        schema: &TileSchema,
+        extent: Rect,
+        z_levels: RangeInclusive<u32>,
+    ) -> Vec<TileIndex> {
+        z_levels
+            .filter_map(|z| schema.iter_tiles_in_bbox(z, extent))
+            .flatten()
+            .collect()
+    }
+
+    /// Downloads all the tiles of the `schema` intersecting the `extent` for the z-levels in the `z_levels` range.
+    ///
+    /// The callback is called once before any tile is downloaded with the total number of tiles, and then after every
+    /// processed tile. The returned future resolves when all the tiles are processed, and returns the same progress
+    /// as the last call of the callback.
+    pub async fn download(
+        &self,
+        schema: &TileSchema,
+        extent: Rect,
+        z_levels: RangeInclusive<u32>,
+        mut on_progress: impl FnMut(DownloadProgress),
+    ) -> DownloadProgress {
+        let tiles = Self::tiles(schema, extent, z_levels);
+        let mut progress = DownloadProgress {
+            total: tiles.len(),
+            ..Default::default()
+        };
+        on_progress(progress);
+
+        let mut results = futures::stream::iter(tiles)
+            .map(|index| self.download_tile(index))
+            .buffer_unordered(self.max_concurrent_requests);
+        while let Some(result) = results.next().await {
+            match result {
+                TileResult::Downloaded => progress.downloaded += 1,
+                TileResult::Skipped => progress.skipped += 1,
+                TileResult::Failed => progress.failed += 1,
+            }
+
+            on_progress(progress);
+        }
+
+        progress
+    }
+
+    async fn download_tile(&self, index: TileIndex) -> TileResult {
+        let url = self.http_options.resolve_url(&(self.url_source)(&index));
+        if self.cache.get_tile(&url).await.is_some() {
+            return TileResult::Skipped;
+        }
+
+        let result: Result<(), GalileoError> = async {
+            let response = self
+                .platform_service
+                .load_with_validation(
+                    &self.http_options.request_url(&url),
+                    self.http_options.headers(),
+                    None,
+                )
+                .await?;
+            match response {
+                // The tiles are downloaded to be used offline, so they are saved even if the server does not allow
+                // storing them.
+                HttpResponse::Data(data, metadata) => {
+                    self.cache
+                        .put_tile(&url, data, metadata.unwrap_or_default())
+                        .await
+                }
+                HttpResponse::NotModified(_) => Err(GalileoError::IO),
+            }
+        }
+        .await;
+
+        match result {
+            Ok(()) => TileResult::Downloaded,
+            Err(err) => {
+                log::debug!("Failed to download tile {index:?} from {url}: {err}");
+                TileResult::Failed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::data_provider::{FileCacheController, PersistentCacheController};
+    use bytes::Bytes;
+
+    fn test_cache(name: &str) -> (FileCacheController, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "galileo_tile_downloader_{name}_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        (FileCacheController::new(&path), path)
+    }
+
+    fn url(index: &TileIndex) -> String {
+        // Nothing listens on this port, so all the requests fail.
+        format!("http://127.0.0.1:1/{}/{}/{}.png", index.z, index.x, index.y)
+    }
+
+    #[test]
+    fn tiles_for_zoom_range() {
+        let schema = TileSchema::web(18);
+        let extent = Rect::new(-1.0, -1.0, 1.0, 1.0);
+        let tiles = TileDownloader::<FileCacheController>::tiles(&schema, extent, 1..=3);
+        assert_eq!(tiles.len(), 12);
+        assert!(tiles.iter().all(|index| (1..=3).contains(&index.z)));
+    }
+
+    #[tokio::test]
+    async fn download_skips_cached_tiles() {
+        let (cache, path) = test_cache("resume");
+        let schema = TileSchema::web(18);
+        let extent = Rect::new(-1.0, -1.0, 1.0, 1.0);
+
+        let tiles = TileDownloader::<FileCacheController>::tiles(&schema, extent, 0..=1);
+        for index in &tiles[..3] {
+            cache
+                .insert(&url(index), &Bytes::from_static(b"tile"))
+                .unwrap();
+        }
+
+        let downloader = TileDownloader::new(url, cache).with_max_concurrent_requests(2);
+        let mut calls = vec![];
+        let progress = downloader
+            .download(&schema, extent, 0..=1, |progress| calls.push(progress))
+            .await;
+
+        assert_eq!(
+            progress,
+            DownloadProgress {
+                downloaded: 0,
+                skipped: 3,
+                failed: 2,
+                total: 5,
+            }
+        );
+        assert!(progress.is_complete());
+        assert_eq!(calls.len(), 6);
+        assert_eq!(calls.last(), Some(&progress));
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+}
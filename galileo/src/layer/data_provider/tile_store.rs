@@ -0,0 +1,248 @@
+use crate::error::GalileoError;
+use crate::layer::data_provider::{CacheMetadata, PersistentCacheController};
+use bytes::Bytes;
+use maybe_sync::{MaybeSend, MaybeSync};
+use quick_cache::sync::Cache;
+use quick_cache::Weighter;
+use std::future::Future;
+
+/// Persistent storage of the tiles downloaded by the url data providers.
+///
+/// Implement this trait to keep the tiles in an object storage (e.g. S3 or GCS) or in a database of the application.
+/// The methods are asynchronous, so the implementation can make network requests without blocking the layer.
+///
+/// Every [`PersistentCacheController`] of bytes is also a tile store, so the
+/// [`FileCacheController`](super::FileCacheController) can be used to store the tiles on the file system, and the
+/// [`MemoryTileStore`] to store them in memory.
+///
+/// ```
+/// use bytes::Bytes;
+/// use galileo::error::GalileoError;
+/// use galileo::layer::data_provider::{CacheMetadata, TileStore, UrlImageProvider};
+/// use galileo::tile_scheme::TileIndex;
+///
+/// struct ObjectStore {
+///     bucket: String,
+/// }
+///
+/// impl TileStore for ObjectStore {
+///     async fn get_tile(&self, key: &str) -> Option<(Bytes, CacheMetadata)> {
+///         // Request the object from the bucket.
+///         None
+///     }
+///
+///     async fn put_tile(
+///         &self,
+///         key: &str,
+///         data: Bytes,
+///         metadata: CacheMetadata,
+///     ) -> Result<(), GalileoError> {
+///         // Upload the object into the bucket.
+///         Ok(())
+///     }
+/// }
+///
+/// let provider = UrlImageProvider::new_cached(
+///     |index: &TileIndex| format!("https://tiles.example.com/{}/{}/{}.png", index.z, index.x, index.y),
+///     ObjectStore { bucket: "tiles".into() },
+/// );
+/// ```
+pub trait TileStore: MaybeSend + MaybeSync {
+    /// Loads the tile data with its HTTP caching metadata. Returns `None` if the store does not contain the tile.
+    fn get_tile(
+        &self,
+        key: &str,
+    ) -> impl Future<Output = Option<(Bytes, CacheMetadata)>> + MaybeSend;
+
+    /// Saves the tile data with its HTTP caching metadata, replacing the existing value if any.
+    fn put_tile(
+        &self,
+        key: &str,
+        data: Bytes,
+        metadata: CacheMetadata,
+    ) -> impl Future<Output = Result<(), GalileoError>> + MaybeSend;
+
+    /// Replaces the HTTP caching metadata of the stored tile, e.g. after the server confirmed that the tile has not
+    /// changed. Does nothing if the store does not contain the tile.
+    ///
+    /// The default implementation loads the tile and saves it again with the new metadata.
+    fn update_tile_metadata(
+        &self,
+        key: &str,
+        metadata: CacheMetadata,
+    ) -> impl Future<Output = Result<(), GalileoError>> + MaybeSend {
+        async move {
+            match self.get_tile(key).await {
+                Some((data, _)) => self.put_tile(key, data, metadata).await,
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+impl<T> TileStore for T
+where
+    T: PersistentCacheController<str, Bytes> + MaybeSend + MaybeSync,
+{
+    fn get_tile(
+        &self,
+        key: &str,
+    ) -> impl Future<Output = Option<(Bytes, CacheMetadata)>> + MaybeSend {
+        std::future::ready(self.get_with_metadata(key))
+    }
+
+    fn put_tile(
+        &self,
+        key: &str,
+        data: Bytes,
+        metadata: CacheMetadata,
+    ) -> impl Future<Output = Result<(), GalileoError>> + MaybeSend {
+        std::future::ready(self.insert_with_metadata(key, &data, &metadata))
+    }
+
+    fn update_tile_metadata(
+        &self,
+        key: &str,
+        metadata: CacheMetadata,
+    ) -> impl Future<Output = Result<(), GalileoError>> + MaybeSend {
+        std::future::ready(self.update_metadata(key, &metadata))
+    }
+}
+
+/// Tile store keeping the tiles in memory, up to the given total size. When the size is exceeded, the least recently
+/// used tiles are removed.
+///
+/// The tiles are lost when the application is closed, but unlike the in-memory cache of a layer, the store keeps the
+/// encoded tile data, which is usually much smaller than the decoded images.
+pub struct MemoryTileStore {
+    tiles: Cache<String, (Bytes, CacheMetadata), TileSizeWeighter>,
+}
+
+#[derive(Clone)]
+struct TileSizeWeighter;
+
+impl Weighter<String, (Bytes, CacheMetadata)> for TileSizeWeighter {
+    fn weight(&self, key: &String, (data, _): &(Bytes, CacheMetadata)) -> u32 {
+        u32::try_from(key.len() + data.len())
+            .unwrap_or(u32::MAX)
+            .max(1)
+    }
+}
+
+impl MemoryTileStore {
+    /// Approximate size of a tile used to estimate the number of tiles in the store.
+    const ESTIMATED_TILE_SIZE: u64 = 32 * 1024;
+
+    /// Creates a new store keeping up to `max_bytes` of the tile data.
+    pub fn new(max_bytes: u64) -> Self {
+        let max_bytes = max_bytes.max(1);
+        let estimated_tiles = (max_bytes / Self::ESTIMATED_TILE_SIZE).max(1) as usize;
+        Self {
+            tiles: Cache::with_weighter(estimated_tiles, max_bytes, TileSizeWeighter),
+        }
+    }
+
+    /// Number of tiles in the store.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns true if the store contains no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Total size of the stored tiles in bytes.
+    pub fn size_bytes(&self) -> u64 {
+        self.tiles.weight()
+    }
+
+    /// Removes all the tiles from the store.
+    pub fn clear(&self) {
+        self.tiles.clear();
+    }
+}
+
+impl PersistentCacheController<str, Bytes> for MemoryTileStore {
+    fn get(&self, key: &str) -> Option<Bytes> {
+        self.get_with_metadata(key).map(|(data, _)| data)
+    }
+
+    fn insert(&self, key: &str, data: &Bytes) -> Result<(), GalileoError> {
+        self.insert_with_metadata(key, data, &CacheMetadata::default())
+    }
+
+    fn get_with_metadata(&self, key: &str) -> Option<(Bytes, CacheMetadata)> {
+        self.tiles.get(key)
+    }
+
+    fn insert_with_metadata(
+        &self,
+        key: &str,
+        data: &Bytes,
+        metadata: &CacheMetadata,
+    ) -> Result<(), GalileoError> {
+        self.tiles
+            .insert(key.to_string(), (data.clone(), metadata.clone()));
+        Ok(())
+    }
+
+    fn update_metadata(&self, key: &str, metadata: &CacheMetadata) -> Result<(), GalileoError> {
+        if let Some(data) = self.get(key) {
+            self.insert_with_metadata(key, &data, metadata)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web_time::SystemTime;
+
+    #[test]
+    fn memory_store_keeps_data_with_metadata() {
+        let store = MemoryTileStore::new(1024);
+        let metadata = CacheMetadata {
+            etag: Some("\"v1\"".into()),
+            last_modified: None,
+            expires: Some(SystemTime::UNIX_EPOCH),
+        };
+
+        tokio_test::block_on(async {
+            assert_eq!(store.get_tile("tile").await, None);
+            store
+                .put_tile("tile", Bytes::from_static(b"data"), metadata.clone())
+                .await
+                .unwrap();
+            assert_eq!(
+                store.get_tile("tile").await,
+                Some((Bytes::from_static(b"data"), metadata))
+            );
+
+            store
+                .update_tile_metadata("tile", CacheMetadata::default())
+                .await
+                .unwrap();
+            assert_eq!(
+                store.get_tile("tile").await,
+                Some((Bytes::from_static(b"data"), CacheMetadata::default()))
+            );
+        });
+    }
+
+    #[test]
+    fn memory_store_is_limited_by_size() {
+        let store = MemoryTileStore::new(1000);
+        for i in 0..100 {
+            store
+                .insert(&format!("tile{i:03}"), &Bytes::from(vec![0; 93]))
+                .unwrap();
+        }
+
+        assert!(store.size_bytes() <= 1000);
+        assert!(store.len() < 100);
+        assert!(!store.is_empty());
+    }
+}
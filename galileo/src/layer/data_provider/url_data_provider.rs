@@ -1,25 +1,26 @@
 use crate::error::GalileoError;
 use crate::layer::data_provider::dummy::DummyCacheController;
 use crate::layer::data_provider::{
-    DataProcessor, DataProvider, PersistentCacheController, UrlSource,
+    DataProcessor, DataProvider, HttpSourceOptions, TileStore, UrlSource,
 };
 use crate::platform::{PlatformService, PlatformServiceImpl};
 use bytes::Bytes;
 use maybe_sync::{MaybeSend, MaybeSync};
 use std::marker::PhantomData;
 
-/// Loads data from Internet and uses `Cache` persistent cache to save data locally.
+/// Loads data from Internet and uses `Cache` [tile store](TileStore) to save data locally.
 pub struct UrlDataProvider<Key, Decoder, Cache = DummyCacheController>
 where
     Key: ?Sized,
     Decoder: DataProcessor<Input = Bytes>,
-    Cache: PersistentCacheController<str, Bytes>,
+    Cache: TileStore,
 {
     url_source: Box<dyn UrlSource<Key>>,
     decoder: Decoder,
     cache: Option<Cache>,
     offline_mode: bool,
     platform_service: PlatformServiceImpl,
+    http_options: HttpSourceOptions,
     _phantom_key: PhantomData<Key>,
 }
 
@@ -36,6 +37,7 @@ where
             cache: None,
             offline_mode: false,
             platform_service: PlatformServiceImpl::new(),
+            http_options: HttpSourceOptions::default(),
             _phantom_key: Default::default(),
         }
     }
@@ -46,7 +48,7 @@ where
     Key: ?Sized + MaybeSend + MaybeSync,
     Decoder: DataProcessor<Input = Bytes> + MaybeSend + MaybeSync,
     Decoder::Context: MaybeSend + MaybeSync,
-    Cache: PersistentCacheController<str, Bytes> + MaybeSend + MaybeSync,
+    Cache: TileStore,
 {
     /// Creates a new instance with persistent cache.
     pub fn new_cached(
@@ -60,6 +62,7 @@ where
             cache: Some(cache),
             offline_mode: false,
             platform_service: PlatformServiceImpl::new(),
+            http_options: HttpSourceOptions::default(),
             _phantom_key: Default::default(),
         }
     }
@@ -76,6 +79,28 @@ where
         self.offline_mode = enabled;
     }
 
+    /// Sets the HTTP client used to download the data.
+    ///
+    /// By default every provider creates its own client. Sharing one client between several providers allows them to
+    /// reuse connections and TLS sessions. All requests of the provider are made with this client, so its timeout,
+    /// proxy and default headers settings apply.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_http_client(&mut self, client: reqwest::Client) {
+        self.platform_service = PlatformServiceImpl::with_http_client(client);
+    }
+
+    /// Sets the headers, query parameters and subdomains of the requests, e.g. to authenticate with a commercial data
+    /// source. See [`HttpSourceOptions`].
+    pub fn set_http_options(&mut self, options: HttpSourceOptions) {
+        self.http_options = options;
+    }
+
+    /// Settings of the requests made by the provider.
+    pub fn http_options(&self) -> &HttpSourceOptions {
+        &self.http_options
+    }
+
+    #[cfg(target_arch = "wasm32")]
     fn check_offline_mode(&self) -> Result<(), GalileoError> {
         if self.offline_mode {
             Err(GalileoError::NotFound)
@@ -91,22 +116,42 @@ where
     Key: ?Sized + MaybeSend + MaybeSync,
     Decoder: DataProcessor<Input = Bytes> + MaybeSend + MaybeSync,
     Decoder::Context: MaybeSend + MaybeSync,
-    Cache: PersistentCacheController<str, Bytes> + MaybeSend + MaybeSync,
+    Cache: TileStore,
 {
+    #[cfg(not(target_arch = "wasm32"))]
     async fn load_raw(&self, key: &Key) -> Result<Bytes, GalileoError> {
         let url = (self.url_source)(key);
+        crate::layer::data_provider::load_cached(
+            &self.platform_service,
+            &self.http_options,
+            &url,
+            self.cache.as_ref(),
+            self.offline_mode,
+        )
+        .await
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn load_raw(&self, key: &Key) -> Result<Bytes, GalileoError> {
+        let url = self.http_options.resolve_url(&(self.url_source)(key));
         if let Some(cache) = &self.cache {
-            if let Some(data) = cache.get(&url) {
+            if let Some((data, _)) = cache.get_tile(&url).await {
                 return Ok(data);
             }
         }
 
         self.check_offline_mode()?;
 
-        let data = self.platform_service.load_bytes_from_url(&url).await?;
+        let data = self
+            .platform_service
+            .load_bytes_with_headers(
+                &self.http_options.request_url(&url),
+                self.http_options.headers(),
+            )
+            .await?;
 
         if let Some(cache) = &self.cache {
-            if let Err(error) = cache.insert(&url, &data) {
+            if let Err(error) = cache.put_tile(&url, data.clone(), Default::default()).await {
                 log::warn!("Failed to write persistent cache entry: {:?}", error);
             }
         }
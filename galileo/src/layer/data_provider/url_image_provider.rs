@@ -1,21 +1,24 @@
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
 use crate::layer::data_provider::dummy::DummyCacheController;
-use crate::layer::data_provider::{DataProvider, PersistentCacheController, UrlSource};
+use crate::layer::data_provider::{DataProvider, HttpSourceOptions, TileStore, UrlSource};
 use crate::platform::{PlatformService, PlatformServiceImpl};
 use bytes::Bytes;
 use maybe_sync::{MaybeSend, MaybeSync};
 use std::marker::PhantomData;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::layer::data_provider::load_cached;
 #[cfg(target_arch = "wasm32")]
 use std::future::Future;
 
-/// Loads an image from Internet and uses `Cache` persistent cache controller to save it locally.
+/// Loads an image from Internet and uses `Cache` [tile store](TileStore) to save it locally.
 #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
 pub struct UrlImageProvider<Key, Cache = DummyCacheController> {
     url_source: Box<dyn UrlSource<Key>>,
     cache: Option<Cache>,
     platform_service: PlatformServiceImpl,
+    http_options: HttpSourceOptions,
     offline_mode: bool,
     _phantom_key: PhantomData<Key>,
 }
@@ -27,6 +30,7 @@ impl<Key> UrlImageProvider<Key, DummyCacheController> {
             url_source: Box::new(url_source),
             cache: None,
             platform_service: PlatformServiceImpl::new(),
+            http_options: HttpSourceOptions::default(),
             offline_mode: false,
             _phantom_key: Default::default(),
         }
@@ -40,6 +44,7 @@ impl<Key, Cache> UrlImageProvider<Key, Cache> {
             url_source: Box::new(url_source),
             cache: Some(cache),
             platform_service: PlatformServiceImpl::new(),
+            http_options: HttpSourceOptions::default(),
             offline_mode: false,
             _phantom_key: Default::default(),
         }
@@ -57,13 +62,25 @@ impl<Key, Cache> UrlImageProvider<Key, Cache> {
         self.offline_mode = enabled;
     }
 
+    /// Sets the HTTP client used to download the data.
+    ///
+    /// By default every provider creates its own client. Sharing one client between several providers allows them to
+    /// reuse connections and TLS sessions. All requests of the provider are made with this client, so its timeout,
+    /// proxy and default headers settings apply.
     #[cfg(not(target_arch = "wasm32"))]
-    fn check_offline_mode(&self) -> Result<(), GalileoError> {
-        if self.offline_mode {
-            Err(GalileoError::NotFound)
-        } else {
-            Ok(())
-        }
+    pub fn set_http_client(&mut self, client: reqwest::Client) {
+        self.platform_service = PlatformServiceImpl::with_http_client(client);
+    }
+
+    /// Sets the headers, query parameters and subdomains of the requests, e.g. to authenticate with a commercial tile
+    /// source. See [`HttpSourceOptions`].
+    pub fn set_http_options(&mut self, options: HttpSourceOptions) {
+        self.http_options = options;
+    }
+
+    /// Settings of the requests made by the provider.
+    pub fn http_options(&self) -> &HttpSourceOptions {
+        &self.http_options
     }
 }
 
@@ -71,29 +88,18 @@ impl<Key, Cache> UrlImageProvider<Key, Cache> {
 impl<Key, Cache> DataProvider<Key, DecodedImage, ()> for UrlImageProvider<Key, Cache>
 where
     Key: MaybeSend + MaybeSync,
-    Cache: PersistentCacheController<str, Bytes> + MaybeSend + MaybeSync,
+    Cache: TileStore,
 {
     async fn load_raw(&self, key: &Key) -> Result<Bytes, GalileoError> {
         let url = (self.url_source)(key);
-
-        if let Some(cache) = &self.cache {
-            if let Some(data) = cache.get(&url) {
-                return Ok(data);
-            }
-        }
-
-        self.check_offline_mode()?;
-
-        log::info!("Loading {url}");
-        let data = self.platform_service.load_bytes_from_url(&url).await?;
-
-        if let Some(cache) = &self.cache {
-            if let Err(error) = cache.insert(&url, &data) {
-                log::warn!("Failed to write persistent cache entry: {:?}", error);
-            }
-        }
-
-        Ok(data)
+        load_cached(
+            &self.platform_service,
+            &self.http_options,
+            &url,
+            self.cache.as_ref(),
+            self.offline_mode,
+        )
+        .await
     }
 
     fn decode(&self, bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
@@ -105,7 +111,7 @@ where
 impl<Key, Cache> DataProvider<Key, DecodedImage, ()> for UrlImageProvider<Key, Cache>
 where
     Key: MaybeSend + MaybeSync,
-    Cache: PersistentCacheController<str, Bytes> + MaybeSend + MaybeSync,
+    Cache: TileStore,
 {
     fn load_raw(
         &self,
@@ -120,6 +126,155 @@ where
 
     async fn load(&self, key: &Key, _context: ()) -> Result<DecodedImage, GalileoError> {
         let url = (self.url_source)(key);
+        let url = self
+            .http_options
+            .request_url(&self.http_options.resolve_url(&url));
         self.platform_service.load_image_url(&url).await
     }
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::layer::data_provider::{
+        FileCacheController, HttpSourceOptions, PersistentCacheController,
+    };
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn offline_mode_uses_only_cache() {
+        let cache_path =
+            std::env::temp_dir().join(format!("galileo_offline_mode_test_{}", std::process::id()));
+        let cache = FileCacheController::new(&cache_path);
+        cache
+            .insert("http://localhost:0/cached", &Bytes::from_static(b"tile"))
+            .unwrap();
+
+        let mut provider =
+            UrlImageProvider::new_cached(|key: &String| format!("http://localhost:0/{key}"), cache);
+        provider.set_offline_mode(true);
+
+        let cached = tokio_test::block_on(provider.load_raw(&"cached".to_string()));
+        assert_eq!(cached.unwrap(), Bytes::from_static(b"tile"));
+
+        let missing = tokio_test::block_on(provider.load_raw(&"missing".to_string()));
+        assert_matches!(missing, Err(GalileoError::NotFound));
+
+        let _ = std::fs::remove_dir_all(cache_path);
+    }
+
+    fn local_server(respond: bool) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![0; 4096];
+            let len = stream.read(&mut request).unwrap();
+            let request = String::from_utf8_lossy(&request[..len]).to_string();
+
+            if respond {
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-length: 4\r\nconnection: close\r\n\r\ntile",
+                    )
+                    .unwrap();
+            } else {
+                std::thread::sleep(std::time::Duration::from_secs(2));
+            }
+
+            request
+        });
+
+        (address, handle)
+    }
+
+    #[test]
+    fn injected_http_client_is_used() {
+        let (address, server) = local_server(true);
+        let client = reqwest::Client::builder()
+            .user_agent("custom-agent")
+            .build()
+            .unwrap();
+
+        let mut provider = UrlImageProvider::new(move |key: &String| format!("{address}/{key}"));
+        provider.set_http_client(client);
+
+        let data = tokio_test::block_on(provider.load_raw(&"tile".to_string()));
+        assert_eq!(data.unwrap(), Bytes::from_static(b"tile"));
+        assert!(server
+            .join()
+            .unwrap()
+            .to_lowercase()
+            .contains("user-agent: custom-agent"));
+    }
+
+    #[test]
+    fn injected_http_client_timeout_is_respected() {
+        let (address, _server) = local_server(false);
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let mut provider = UrlImageProvider::new(move |key: &String| format!("{address}/{key}"));
+        provider.set_http_client(client);
+
+        let started = std::time::Instant::now();
+        let data = tokio_test::block_on(provider.load_raw(&"tile".to_string()));
+        assert_matches!(data, Err(GalileoError::IO));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn expired_data_is_revalidated() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let responses: [&[u8]; 2] = [
+                b"HTTP/1.1 200 OK\r\ncontent-length: 4\r\netag: \"v1\"\r\ncache-control: no-cache\r\nconnection: close\r\n\r\ntile",
+                b"HTTP/1.1 304 Not Modified\r\ncache-control: max-age=60\r\nconnection: close\r\n\r\n",
+            ];
+            responses
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = vec![0; 4096];
+                    let len = stream.read(&mut request).unwrap();
+                    stream.write_all(response).unwrap();
+                    String::from_utf8_lossy(&request[..len]).to_lowercase()
+                })
+                .to_vec()
+        });
+
+        let cache_path =
+            std::env::temp_dir().join(format!("galileo_revalidation_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&cache_path);
+        let mut provider = UrlImageProvider::new_cached(
+            move |key: &String| format!("{address}/{key}"),
+            FileCacheController::new(&cache_path),
+        );
+        provider.set_http_options(
+            HttpSourceOptions::new()
+                .with_bearer_token("secret")
+                .with_query_param("key", "abc"),
+        );
+
+        let key = "tile".to_string();
+        for _ in 0..3 {
+            let data = tokio_test::block_on(provider.load_raw(&key));
+            assert_eq!(data.unwrap(), Bytes::from_static(b"tile"));
+        }
+
+        // The third load uses the revalidated data without a request.
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("get /tile?key=abc "));
+        assert!(requests[0].contains("authorization: bearer secret"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+
+        let _ = std::fs::remove_dir_all(cache_path);
+    }
+}
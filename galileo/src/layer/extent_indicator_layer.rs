@@ -0,0 +1,142 @@
+//! [`ExtentIndicatorLayer`] draws the footprint of a map view.
+
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::{Canvas, RenderOptions};
+use crate::symbol::SimplePolygonSymbol;
+use crate::view::MapView;
+use galileo_types::cartesian::{Point2d, Point3d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{ClosedContour, Polygon};
+use std::any::Any;
+
+/// Number of points taken along every edge of the screen to build the footprint of a view.
+const EDGE_SAMPLES: usize = 8;
+
+/// Layer that draws the area shown by another map view as a polygon, e.g. the footprint of the main map in an
+/// [`OverviewMap`](crate::OverviewMap).
+///
+/// The footprint is stored in geographic coordinates, so the layer can be shown on a map with any CRS. Since the
+/// footprint is built from the points along the edges of the screen, it follows the shape of the view even if the
+/// view is rotated or tilted. Parts of the screen that do not show the map surface (the sky of a tilted view) are
+/// left out.
+pub struct ExtentIndicatorLayer {
+    symbol: SimplePolygonSymbol,
+    footprint: Vec<GeoPoint2d>,
+    messenger: Option<Box<dyn Messenger>>,
+}
+
+impl ExtentIndicatorLayer {
+    /// Creates a new layer without a footprint, that draws it with the given symbol once it is set.
+    pub fn new(symbol: SimplePolygonSymbol) -> Self {
+        Self {
+            symbol,
+            footprint: vec![],
+            messenger: None,
+        }
+    }
+
+    /// Symbol of the footprint.
+    pub fn symbol(&self) -> &SimplePolygonSymbol {
+        &self.symbol
+    }
+
+    /// Changes the symbol of the footprint.
+    pub fn set_symbol(&mut self, symbol: SimplePolygonSymbol) {
+        self.symbol = symbol;
+        self.request_redraw();
+    }
+
+    /// Points of the footprint in geographic coordinates.
+    pub fn footprint(&self) -> &[GeoPoint2d] {
+        &self.footprint
+    }
+
+    /// Sets the footprint to the area shown by the `view`.
+    pub fn set_view(&mut self, view: &MapView) {
+        let footprint = Self::view_footprint(view);
+        if footprint != self.footprint {
+            self.footprint = footprint;
+            self.request_redraw();
+        }
+    }
+
+    /// Returns the footprint of the area shown by the `view` in geographic coordinates going clockwise from the top
+    /// left corner of the screen.
+    pub fn view_footprint(view: &MapView) -> Vec<GeoPoint2d> {
+        let size = view.size();
+        let (width, height) = (size.width(), size.height());
+        let edges = [
+            (Point2d::new(0.0, 0.0), Point2d::new(width, 0.0)),
+            (Point2d::new(width, 0.0), Point2d::new(width, height)),
+            (Point2d::new(width, height), Point2d::new(0.0, height)),
+            (Point2d::new(0.0, height), Point2d::new(0.0, 0.0)),
+        ];
+
+        edges
+            .into_iter()
+            .flat_map(|(from, to)| {
+                (0..EDGE_SAMPLES).map(move |i| {
+                    let t = i as f64 / EDGE_SAMPLES as f64;
+                    Point2d::new(from.x + (to.x - from.x) * t, from.y + (to.y - from.y) * t)
+                })
+            })
+            .filter_map(|point| view.screen_to_map_geo(point))
+            .collect()
+    }
+
+    fn request_redraw(&self) {
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+}
+
+impl Layer for ExtentIndicatorLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if self.footprint.len() < 3 {
+            return;
+        }
+        let Some(projection) = view.crs().get_projection::<GeoPoint2d, Point2d>() else {
+            return;
+        };
+        let Some(points) = self
+            .footprint
+            .iter()
+            .map(|point| {
+                let projected = projection.project(point)?;
+                Some(Point3d::new(projected.x, projected.y, 0.0))
+            })
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+
+        let geometry = Geom::Polygon(Polygon::new(ClosedContour::new(points), vec![]));
+        let mut bundle = canvas.create_bundle();
+        for primitive in Symbol::<()>::render(&self.symbol, &(), &geometry, view.resolution()) {
+            bundle.add(primitive, view.resolution());
+        }
+
+        let packed = canvas.pack_bundle(&bundle);
+        canvas.draw_bundles(&[&*packed], RenderOptions::default());
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // do nothing
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.messenger = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,123 @@
+use galileo_types::cartesian::Point3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{ClosedContour, Contour, MultiContour, MultiPolygon, Polygon};
+use galileo_types::{Contour as _, MultiContour as _};
+
+/// Moves the points of a geometry projected into a horizontally repeated CRS by whole world widths, so that no segment
+/// of the geometry is longer than half of the world. Geometries crossing the antimeridian then continue into the next
+/// copy of the world instead of being drawn across the whole map.
+///
+/// All the parts of the geometry are unwrapped relative to its first point.
+pub(super) fn unwrap_projected(geometry: Geom<Point3d>, world_width: f64) -> Geom<Point3d> {
+    let Some(mut reference) = first_point(&geometry) else {
+        return geometry;
+    };
+
+    match geometry {
+        Geom::Point(_) | Geom::MultiPoint(_) => geometry,
+        Geom::Contour(contour) => {
+            Geom::Contour(unwrap_contour(&contour, &mut reference, world_width))
+        }
+        Geom::MultiContour(contour) => Geom::MultiContour(MultiContour::from(
+            contour
+                .contours()
+                .map(|contour| unwrap_contour(contour, &mut reference.clone(), world_width))
+                .collect::<Vec<_>>(),
+        )),
+        Geom::Polygon(polygon) => Geom::Polygon(unwrap_polygon(&polygon, reference, world_width)),
+        Geom::MultiPolygon(polygon) => Geom::MultiPolygon(MultiPolygon::from(
+            polygon
+                .parts()
+                .iter()
+                .map(|polygon| unwrap_polygon(polygon, reference, world_width))
+                .collect::<Vec<_>>(),
+        )),
+    }
+}
+
+fn first_point(geometry: &Geom<Point3d>) -> Option<Point3d> {
+    match geometry {
+        Geom::Point(_) | Geom::MultiPoint(_) => None,
+        Geom::Contour(contour) => contour.iter_points().next().copied(),
+        Geom::MultiContour(contour) => contour
+            .contours()
+            .next()
+            .and_then(|contour| contour.iter_points().next().copied()),
+        Geom::Polygon(polygon) => polygon.outer_contour.iter_points().next().copied(),
+        Geom::MultiPolygon(polygon) => polygon
+            .parts()
+            .first()
+            .and_then(|polygon| polygon.outer_contour.iter_points().next().copied()),
+    }
+}
+
+fn unwrap_contour(
+    contour: &Contour<Point3d>,
+    previous: &mut Point3d,
+    world_width: f64,
+) -> Contour<Point3d> {
+    Contour::new(
+        unwrap_points(contour.iter_points(), previous, world_width),
+        contour.is_closed(),
+    )
+}
+
+fn unwrap_polygon(
+    polygon: &Polygon<Point3d>,
+    reference: Point3d,
+    world_width: f64,
+) -> Polygon<Point3d> {
+    let unwrap_closed = |contour: &ClosedContour<Point3d>| {
+        ClosedContour::new(unwrap_points(
+            contour.iter_points(),
+            &mut reference.clone(),
+            world_width,
+        ))
+    };
+
+    Polygon::new(
+        unwrap_closed(&polygon.outer_contour),
+        polygon.inner_contours.iter().map(unwrap_closed).collect(),
+    )
+}
+
+fn unwrap_points<'a>(
+    points: impl Iterator<Item = &'a Point3d>,
+    previous: &mut Point3d,
+    world_width: f64,
+) -> Vec<Point3d> {
+    points
+        .map(|point| {
+            let copies = ((previous.x - point.x) / world_width).round();
+            let unwrapped = Point3d::new(point.x + copies * world_width, point.y, point.z);
+            *previous = unwrapped;
+            unwrapped
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contour_continues_across_antimeridian() {
+        let contour = Contour::open(vec![
+            Point3d::new(170.0, 0.0, 1.0),
+            Point3d::new(-170.0, 10.0, 2.0),
+            Point3d::new(-150.0, 20.0, 3.0),
+            Point3d::new(175.0, 30.0, 4.0),
+        ]);
+
+        let unwrapped = unwrap_projected(Geom::Contour(contour), 360.0);
+        assert_eq!(
+            unwrapped,
+            Geom::Contour(Contour::open(vec![
+                Point3d::new(170.0, 0.0, 1.0),
+                Point3d::new(190.0, 10.0, 2.0),
+                Point3d::new(210.0, 20.0, 3.0),
+                Point3d::new(175.0, 30.0, 4.0),
+            ]))
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use crate::layer::feature_layer::symbol::ClusterSymbol;
+use crate::render::PackedBundle;
+use galileo_types::cartesian::{CartesianPoint3d, Point3d};
+use galileo_types::geo::Crs;
+use galileo_types::geometry::Geom;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Clustering configuration and cached clusters of a feature layer.
+pub(super) struct Clustering<F> {
+    pub radius: f64,
+    pub symbol: Box<dyn ClusterSymbol<F>>,
+    pub state: Mutex<ClusterState>,
+}
+
+impl<F> Clustering<F> {
+    pub fn new(radius: f64, symbol: Box<dyn ClusterSymbol<F>>) -> Self {
+        Self {
+            radius,
+            symbol,
+            state: Mutex::new(ClusterState::default()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(super) struct ClusterState {
+    /// CRS the geometries are projected to.
+    pub crs: Option<Crs>,
+    /// Projected geometries of the visible features with their indices in the feature store.
+    pub geometries: Vec<(usize, Geom<Point3d>)>,
+    /// Resolution of the packed bundle.
+    pub resolution: Option<f64>,
+    pub bundle: Option<Box<dyn PackedBundle>>,
+}
+
+impl ClusterState {
+    /// Drops the projected geometries and the rendered clusters.
+    pub fn invalidate(&mut self) {
+        self.crs = None;
+        self.geometries.clear();
+        self.resolution = None;
+        self.bundle = None;
+    }
+}
+
+/// Groups the points that are closer than `distance` to the first point of a group. Returns the groups as indices
+/// of the points in the given slice. Every point is included in exactly one group, and the groups are ordered by the
+/// first point.
+pub(super) fn cluster_points(points: &[Point3d], distance: f64) -> Vec<Vec<usize>> {
+    if distance <= 0.0 || !distance.is_finite() {
+        return (0..points.len()).map(|index| vec![index]).collect();
+    }
+
+    let cell = |point: &Point3d| {
+        (
+            (point.x() / distance).floor() as i64,
+            (point.y() / distance).floor() as i64,
+        )
+    };
+
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (index, point) in points.iter().enumerate() {
+        grid.entry(cell(point)).or_default().push(index);
+    }
+
+    let distance_sq = distance * distance;
+    let mut is_clustered = vec![false; points.len()];
+    let mut clusters = vec![];
+    for (index, point) in points.iter().enumerate() {
+        if is_clustered[index] {
+            continue;
+        }
+
+        let (cell_x, cell_y) = cell(point);
+        let mut cluster = vec![];
+        for x in cell_x - 1..=cell_x + 1 {
+            for y in cell_y - 1..=cell_y + 1 {
+                for &other in grid.get(&(x, y)).into_iter().flatten() {
+                    let dx = points[other].x() - point.x();
+                    let dy = points[other].y() - point.y();
+                    if !is_clustered[other] && dx * dx + dy * dy <= distance_sq {
+                        is_clustered[other] = true;
+                        cluster.push(other);
+                    }
+                }
+            }
+        }
+
+        cluster.sort_unstable();
+        clusters.push(cluster);
+    }
+
+    clusters
+}
+
+/// Center of the given points.
+pub(super) fn cluster_center(points: &[Point3d], cluster: &[usize]) -> Point3d {
+    let count = cluster.len().max(1) as f64;
+    let (x, y, z) = cluster.iter().fold((0.0, 0.0, 0.0), |(x, y, z), &index| {
+        let point = &points[index];
+        (x + point.x(), y + point.y(), z + point.z())
+    });
+
+    Point3d::new(x / count, y / count, z / count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(f64, f64)]) -> Vec<Point3d> {
+        coords
+            .iter()
+            .map(|&(x, y)| Point3d::new(x, y, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn groups_close_points() {
+        let points = points(&[
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (3.0, 4.0),
+            (104.0, 3.0),
+            (50.0, 50.0),
+        ]);
+        assert_eq!(
+            cluster_points(&points, 5.0),
+            vec![vec![0, 2], vec![1, 3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn groups_points_across_cells() {
+        let points = points(&[(9.5, 9.5), (10.5, 10.5), (-0.5, 0.0), (0.5, 0.0)]);
+        assert_eq!(cluster_points(&points, 10.0), vec![vec![0, 1], vec![2, 3]]);
+    }
+
+    #[test]
+    fn zero_distance_keeps_points_separate() {
+        let points = points(&[(0.0, 0.0), (0.0, 0.0)]);
+        assert_eq!(cluster_points(&points, 0.0), vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn center_is_average() {
+        let points = points(&[(0.0, 0.0), (4.0, 2.0), (100.0, 100.0)]);
+        let center = cluster_center(&points, &[0, 1]);
+        assert_eq!((center.x(), center.y()), (2.0, 1.0));
+    }
+}
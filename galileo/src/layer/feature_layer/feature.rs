@@ -1,9 +1,11 @@
 use galileo_types::cartesian::{Point2d, Point3d};
 use galileo_types::geo::impls::GeoPoint2d;
-use galileo_types::geometry::Geometry;
+use galileo_types::geometry::{Geom, Geometry};
 use galileo_types::geometry_type::GeometryType;
-use galileo_types::impls::{Contour, MultiContour, MultiPolygon, Polygon};
-use galileo_types::Disambig;
+use galileo_types::impls::{Contour, MultiContour, MultiPoint, MultiPolygon, Polygon};
+use galileo_types::{Contour as _, Disambig, MultiContour as _, MultiPoint as _};
+use std::ops::RangeInclusive;
+use web_time::SystemTime;
 
 /// A feature is an arbitrary geographic object.
 pub trait Feature {
@@ -11,6 +13,77 @@ pub trait Feature {
     type Geom: Geometry;
     /// Returns the geometry of the feature.
     fn geometry(&self) -> &Self::Geom;
+    /// Time range of the feature, e.g. the time of a measurement or the period during which the object existed.
+    ///
+    /// When the map view has a [time](crate::view::MapView::time), a [`FeatureLayer`](super::FeatureLayer) shows only
+    /// the features with time ranges containing that time. Features without time range (default) are always shown.
+    fn time_range(&self) -> Option<RangeInclusive<SystemTime>> {
+        None
+    }
+}
+
+/// Value of a feature attribute, as returned by [`FeatureAttributes::attribute`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum AttributeValue {
+    /// Boolean value.
+    Bool(bool),
+    /// Number.
+    Number(f64),
+    /// Text.
+    String(String),
+}
+
+impl AttributeValue {
+    /// Returns the number, if this is a numeric value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for AttributeValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+/// Named attributes of a feature.
+///
+/// Features implementing this trait can be styled by their attributes with
+/// [`Expression`](super::symbol::Expression)s and selected with a [`FeatureFilter`](super::FeatureFilter).
+pub trait FeatureAttributes {
+    /// Returns the value of the attribute with the given name, or `None` if the feature has no such attribute or its
+    /// value is empty.
+    fn attribute(&self, name: &str) -> Option<AttributeValue>;
+
+    /// Returns all the non-empty attributes of the feature sorted by name.
+    ///
+    /// The default implementation returns no attributes, for features that can only look up attributes by name.
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        Vec::new()
+    }
 }
 
 macro_rules! impl_feature {
@@ -52,5 +125,52 @@ where
     }
 }
 
+/// Converts the points of the geometry with the `cast` function, keeping its structure.
+pub(crate) fn cast_geom<P, Q>(geom: &Geom<P>, cast: impl Fn(&P) -> Q + Copy) -> Geom<Q> {
+    let cast_contour = |contour: &Contour<P>| {
+        Contour::new(
+            contour.iter_points().map(cast).collect(),
+            contour.is_closed(),
+        )
+    };
+    match geom {
+        Geom::Point(point) => Geom::Point(cast(point)),
+        Geom::MultiPoint(points) => Geom::MultiPoint(MultiPoint::from(
+            points.iter_points().map(cast).collect::<Vec<_>>(),
+        )),
+        Geom::Contour(contour) => Geom::Contour(cast_contour(contour)),
+        Geom::MultiContour(contours) => Geom::MultiContour(MultiContour::from(
+            contours.contours().map(cast_contour).collect::<Vec<_>>(),
+        )),
+        Geom::Polygon(polygon) => Geom::Polygon(polygon.cast_points(cast)),
+        Geom::MultiPolygon(polygons) => Geom::MultiPolygon(MultiPolygon::from(
+            polygons
+                .parts()
+                .iter()
+                .map(|polygon| polygon.cast_points(cast))
+                .collect::<Vec<_>>(),
+        )),
+    }
+}
+
 #[cfg(feature = "geojson")]
 mod geojson;
+#[cfg(feature = "geojson")]
+pub use self::geojson::{features_to_geojson, features_to_geojson_reprojected, GeoJsonProperties};
+
+#[cfg(feature = "gpx")]
+mod gpx;
+#[cfg(feature = "gpx")]
+pub use self::gpx::{parse_gpx, GpxFeature, GpxFeatureKind};
+
+#[cfg(feature = "kml")]
+mod kml;
+#[cfg(all(feature = "kml", not(target_arch = "wasm32")))]
+pub use self::kml::parse_kmz;
+#[cfg(feature = "kml")]
+pub use self::kml::{parse_kml, KmlDocument, KmlFeature, KmlStyle};
+
+#[cfg(feature = "shapefile")]
+mod shapefile;
+#[cfg(feature = "shapefile")]
+pub use self::shapefile::{DbfValue, Shapefile, ShapefileFeature};
@@ -1,4 +1,13 @@
-use crate::layer::feature_layer::feature::Feature;
+use crate::layer::feature_layer::feature::{AttributeValue, Feature, FeatureAttributes};
+use galileo_types::cartesian::{NewCartesianPoint2d, Point2d};
+use galileo_types::geo::impls::projection::IdentityProjection;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, InvertedProjection, NewGeoPoint, Projection};
+use galileo_types::geometry::Geometry;
+use galileo_types::geometry_type::GeoSpace2d;
+use galileo_types::impls::{Contour, MultiContour, MultiPolygon, Polygon};
+use geojson::feature::Id;
+use geojson::{FeatureCollection, JsonObject};
 
 impl Feature for geojson::Feature {
     type Geom = geojson::Geometry;
@@ -9,3 +18,276 @@ impl Feature for geojson::Feature {
             .expect("GeoJSON Feature has no geometry")
     }
 }
+
+impl FeatureAttributes for geojson::Feature {
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        match self.property(name)? {
+            geojson::JsonValue::Bool(value) => Some(AttributeValue::Bool(*value)),
+            geojson::JsonValue::Number(value) => value.as_f64().map(AttributeValue::Number),
+            geojson::JsonValue::String(value) => Some(AttributeValue::String(value.clone())),
+            _ => None,
+        }
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        let mut attributes: Vec<_> = self
+            .properties_iter()
+            .filter_map(|(name, _)| Some((name.clone(), self.attribute(name)?)))
+            .collect();
+        attributes.sort_by(|a, b| a.0.cmp(&b.0));
+        attributes
+    }
+}
+
+/// Attributes of a feature that are written into GeoJSON by [`features_to_geojson`].
+///
+/// Both methods return `None` by default, so an empty `impl` block is enough for a feature without attributes.
+pub trait GeoJsonProperties {
+    /// Properties of the GeoJSON feature.
+    fn geojson_properties(&self) -> Option<JsonObject> {
+        None
+    }
+
+    /// Identifier of the GeoJSON feature.
+    fn geojson_id(&self) -> Option<Id> {
+        None
+    }
+}
+
+impl GeoJsonProperties for geojson::Feature {
+    fn geojson_properties(&self) -> Option<JsonObject> {
+        self.properties.clone()
+    }
+
+    fn geojson_id(&self) -> Option<Id> {
+        self.id.clone()
+    }
+}
+
+impl GeoJsonProperties for GeoPoint2d {}
+impl GeoJsonProperties for Point2d {}
+impl<P> GeoJsonProperties for Contour<P> {}
+impl<P> GeoJsonProperties for MultiContour<P> {}
+impl<P> GeoJsonProperties for Polygon<P> {}
+impl<P> GeoJsonProperties for MultiPolygon<P> {}
+
+/// Converts the features into a GeoJSON feature collection.
+///
+/// Geometries are converted into GeoJSON geometries with longitude and latitude of the points. Features are expected
+/// to be in WGS84 coordinates, as required by GeoJSON. Properties and ids are taken from [`GeoJsonProperties`].
+///
+/// Returns `None` if geometry of at least one of the features cannot be converted.
+pub fn features_to_geojson<'a, F, P>(
+    features: impl IntoIterator<Item = &'a F>,
+) -> Option<FeatureCollection>
+where
+    F: Feature + GeoJsonProperties + 'a,
+    F::Geom: Geometry<Point = P>,
+    P: NewGeoPoint,
+{
+    let projection = IdentityProjection::<P, GeoPoint2d, GeoSpace2d>::new();
+    convert_features(features, &projection)
+}
+
+/// Converts the features with cartesian coordinates in the `crs` into a GeoJSON feature collection, reprojecting
+/// their geometries into WGS84. See [`features_to_geojson`] for details.
+///
+/// Returns `None` if the `crs` does not support converting its coordinates into geographic ones, or geometry of at
+/// least one of the features cannot be reprojected.
+pub fn features_to_geojson_reprojected<'a, F, P>(
+    features: impl IntoIterator<Item = &'a F>,
+    crs: &Crs,
+) -> Option<FeatureCollection>
+where
+    F: Feature + GeoJsonProperties + 'a,
+    F::Geom: Geometry<Point = P>,
+    P: NewCartesianPoint2d + 'static,
+{
+    let projection = InvertedProjection::new(crs.get_projection::<GeoPoint2d, P>()?);
+    convert_features(features, &projection)
+}
+
+fn convert_features<'a, F, P>(
+    features: impl IntoIterator<Item = &'a F>,
+    projection: &impl Projection<InPoint = P, OutPoint = GeoPoint2d>,
+) -> Option<FeatureCollection>
+where
+    F: Feature + GeoJsonProperties + 'a,
+    F::Geom: Geometry<Point = P>,
+{
+    let features = features
+        .into_iter()
+        .map(|feature| {
+            let geometry = feature.geometry().project(projection)?;
+            Some(geojson::Feature {
+                bbox: None,
+                geometry: Some((&geometry).into()),
+                id: feature.geojson_id(),
+                properties: feature.geojson_properties(),
+                foreign_members: None,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::symbol::ArbitraryGeometrySymbol;
+    use crate::layer::FeatureLayer;
+    use galileo_types::cartesian::Point2d;
+    use geojson::GeoJson;
+
+    const COLLECTION: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "id": "point",
+                "geometry": {"type": "Point", "coordinates": [37.617, 55.755]},
+                "properties": {"name": "Moscow", "population": 13010112, "tags": ["capital", null]}
+            },
+            {
+                "type": "Feature",
+                "geometry": {"type": "LineString", "coordinates": [[0.1, 0.2], [1.3, 1.4], [2.5, 0.6]]},
+                "properties": {}
+            },
+            {
+                "type": "Feature",
+                "id": 7,
+                "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [[
+                        [[178.0, -16.0], [-179.0, -16.0], [-179.0, -19.0], [178.0, -19.0], [178.0, -16.0]],
+                        [[178.5, -17.0], [179.0, -17.0], [179.0, -18.0], [178.5, -17.0]]
+                    ]]
+                },
+                "properties": null
+            }
+        ]
+    }"#;
+
+    fn read(json: &str) -> FeatureCollection {
+        match json.parse::<GeoJson>().unwrap() {
+            GeoJson::FeatureCollection(collection) => collection,
+            _ => panic!("not a feature collection"),
+        }
+    }
+
+    #[test]
+    fn read_write_read_round_trip() {
+        let collection = read(COLLECTION);
+        let layer = FeatureLayer::new(
+            collection.features.clone(),
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+
+        let written = layer.to_geojson().unwrap();
+        let reread = read(&written.to_string());
+
+        assert_eq!(reread.features.len(), collection.features.len());
+        for (original, reread) in collection.features.iter().zip(&reread.features) {
+            assert_eq!(original.properties, reread.properties);
+            assert_eq!(original.id, reread.id);
+            assert_eq!(original.geometry, reread.geometry);
+        }
+    }
+
+    struct City {
+        location: GeoPoint2d,
+        name: &'static str,
+    }
+
+    impl Feature for City {
+        type Geom = GeoPoint2d;
+
+        fn geometry(&self) -> &Self::Geom {
+            &self.location
+        }
+    }
+
+    impl GeoJsonProperties for City {
+        fn geojson_properties(&self) -> Option<JsonObject> {
+            let mut properties = JsonObject::new();
+            properties.insert("name".into(), self.name.into());
+            Some(properties)
+        }
+    }
+
+    #[test]
+    fn custom_features() {
+        let cities = [City {
+            location: GeoPoint2d::latlon(-33.87, 151.21),
+            name: "Sydney",
+        }];
+
+        let collection = features_to_geojson(&cities).unwrap();
+        assert_eq!(collection.features.len(), 1);
+        assert_eq!(collection.features[0].id, None);
+        assert_eq!(
+            collection.features[0].property("name"),
+            Some(&"Sydney".into())
+        );
+        assert_eq!(
+            collection.features[0].geometry,
+            Some(geojson::Geometry::new(geojson::Value::Point(vec![
+                151.21, -33.87
+            ])))
+        );
+    }
+
+    #[test]
+    fn reprojects_cartesian_features() {
+        let points = [Point2d::new(0.0, 0.0), Point2d::new(20037508.34, 0.0)];
+        let layer = FeatureLayer::new(
+            points.to_vec(),
+            ArbitraryGeometrySymbol::default(),
+            Crs::EPSG3857,
+        );
+
+        let collection = layer.to_geojson().unwrap();
+        let coordinates: Vec<_> = collection
+            .features
+            .iter()
+            .map(|feature| match &feature.geometry.as_ref().unwrap().value {
+                geojson::Value::Point(position) => position.clone(),
+                _ => panic!("expected point"),
+            })
+            .collect();
+        assert_eq!(coordinates[0], vec![0.0, 0.0]);
+        assert!((coordinates[1][0] - 180.0).abs() < 1e-6);
+
+        let local = Crs::new(
+            galileo_types::geo::Datum::WGS84,
+            galileo_types::geo::ProjectionType::Unknown,
+        );
+        assert!(features_to_geojson_reprojected(&points, &local).is_none());
+    }
+
+    #[test]
+    fn attributes() {
+        let mut feature = geojson::Feature::default();
+        feature.set_property("name", "Sydney");
+        feature.set_property("population", 5_312_163);
+        feature.set_property("tags", vec!["harbour"]);
+
+        assert_eq!(feature.attribute("name"), Some("Sydney".into()));
+        assert_eq!(feature.attribute("population"), Some(5_312_163.0.into()));
+        assert_eq!(feature.attribute("tags"), None);
+        assert_eq!(feature.attribute("area"), None);
+        assert_eq!(
+            feature.attributes(),
+            vec![
+                ("name".to_string(), "Sydney".into()),
+                ("population".to_string(), 5_312_163.0.into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use crate::error::GalileoError;
+use crate::layer::feature_layer::feature::Feature;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::NewGeoPoint;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, MultiContour};
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+
+/// Type of the GPX element a [`GpxFeature`] is read from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GpxFeatureKind {
+    /// Waypoint (`wpt` element).
+    Waypoint,
+    /// Route (`rte` element).
+    Route,
+    /// Track (`trk` element).
+    Track,
+}
+
+/// A waypoint, route or track read from a GPX file with [`parse_gpx`].
+///
+/// The geometry of a waypoint is a point, the geometry of a route is a contour, and the geometry of a track is a
+/// contour if it has a single segment, or a multi contour otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpxFeature {
+    /// Type of the element.
+    pub kind: GpxFeatureKind,
+    /// Name of the element (`name`).
+    pub name: Option<String>,
+    /// Description of the element (`desc`).
+    pub description: Option<String>,
+    /// Geometry of the element.
+    pub geometry: Geom<GeoPoint2d>,
+    /// Elevations (`ele`) of the points of the geometry in meters, in the same order as the points.
+    pub elevations: Vec<Option<f64>>,
+}
+
+impl Feature for GpxFeature {
+    type Geom = Geom<GeoPoint2d>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+}
+
+/// Reads waypoints, routes and tracks from a GPX document.
+///
+/// Waypoints are returned first, then routes, then tracks, each in the order of the document. Routes and tracks
+/// without points are skipped. Extensions and metadata of the document are ignored.
+///
+/// ```
+/// use galileo::layer::feature_layer::{parse_gpx, GpxFeatureKind};
+///
+/// let gpx = r#"<gpx version="1.1" creator="example">
+///     <trk><name>Morning run</name><trkseg>
+///         <trkpt lat="52.01" lon="4.35"><ele>1.5</ele></trkpt>
+///         <trkpt lat="52.02" lon="4.36"/>
+///     </trkseg></trk>
+/// </gpx>"#;
+///
+/// let features = parse_gpx(gpx).unwrap();
+/// assert_eq!(features[0].kind, GpxFeatureKind::Track);
+/// assert_eq!(features[0].name.as_deref(), Some("Morning run"));
+/// assert_eq!(features[0].elevations, [Some(1.5), None]);
+/// ```
+pub fn parse_gpx(xml: &str) -> Result<Vec<GpxFeature>, GalileoError> {
+    let mut reader = Reader::from_str(xml);
+    let mut parser = GpxParser::default();
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) => parser.start(&element)?,
+            Event::Empty(element) => {
+                parser.start(&element)?;
+                parser.end(element.local_name().as_ref());
+            }
+            Event::End(element) => parser.end(element.local_name().as_ref()),
+            Event::Text(text) => parser.text.push_str(&text.decode().map_err(xml_error)?),
+            Event::CData(text) => parser.text.push_str(&text.decode().map_err(xml_error)?),
+            Event::GeneralRef(reference) => {
+                let name = reference.decode().map_err(xml_error)?;
+                if let Some(ch) = reference.resolve_char_ref().map_err(xml_error)? {
+                    parser.text.push(ch);
+                } else if let Some(value) = resolve_predefined_entity(&name) {
+                    parser.text.push_str(value);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let mut features = parser.waypoints;
+    features.append(&mut parser.routes);
+    features.append(&mut parser.tracks);
+    Ok(features)
+}
+
+fn xml_error(err: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid GPX document: {err}"))
+}
+
+#[derive(Default)]
+struct GpxParser {
+    /// Local names of the open elements.
+    path: Vec<String>,
+    text: String,
+    point: Option<(GeoPoint2d, Option<f64>)>,
+    name: Option<String>,
+    description: Option<String>,
+    segments: Vec<Vec<(GeoPoint2d, Option<f64>)>>,
+    waypoints: Vec<GpxFeature>,
+    routes: Vec<GpxFeature>,
+    tracks: Vec<GpxFeature>,
+}
+
+impl GpxParser {
+    fn start(&mut self, element: &BytesStart) -> Result<(), GalileoError> {
+        let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+        self.text.clear();
+
+        match name.as_str() {
+            "wpt" | "rtept" | "trkpt" => {
+                let coordinate = |key: &str| -> Result<f64, GalileoError> {
+                    let value = element
+                        .try_get_attribute(key)
+                        .map_err(xml_error)?
+                        .ok_or_else(|| xml_error(format!("{name} element without {key}")))?
+                        .normalized_value(XmlVersion::default())
+                        .map_err(xml_error)?;
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| xml_error(format!("invalid {key} value {value}")))
+                };
+                self.point = Some((
+                    GeoPoint2d::latlon(coordinate("lat")?, coordinate("lon")?),
+                    None,
+                ));
+            }
+            "rte" | "trk" => {
+                self.name = None;
+                self.description = None;
+                self.segments = vec![vec![]];
+            }
+            "trkseg"
+                if self
+                    .segments
+                    .last()
+                    .is_some_and(|segment| !segment.is_empty()) =>
+            {
+                self.segments.push(vec![]);
+            }
+            _ => {}
+        }
+
+        self.path.push(name);
+        Ok(())
+    }
+
+    fn end(&mut self, name: &[u8]) {
+        self.path.pop();
+        let parent = self.path.last().map(String::as_str);
+        let text = std::mem::take(&mut self.text);
+        match (name, parent) {
+            (b"ele", Some("wpt" | "rtept" | "trkpt")) => {
+                if let Some(point) = &mut self.point {
+                    point.1 = text.trim().parse().ok();
+                }
+            }
+            (b"name", Some("wpt" | "rte" | "trk")) => self.name = Some(text.trim().to_string()),
+            (b"desc", Some("wpt" | "rte" | "trk")) => {
+                self.description = Some(text.trim().to_string())
+            }
+            (b"wpt", _) => {
+                if let Some((point, elevation)) = self.point.take() {
+                    self.waypoints.push(GpxFeature {
+                        kind: GpxFeatureKind::Waypoint,
+                        name: self.name.take(),
+                        description: self.description.take(),
+                        geometry: Geom::Point(point),
+                        elevations: vec![elevation],
+                    });
+                }
+            }
+            (b"rtept" | b"trkpt", _) => {
+                if let (Some(point), Some(segment)) = (self.point.take(), self.segments.last_mut())
+                {
+                    segment.push(point);
+                }
+            }
+            (b"rte", _) => {
+                if let Some(feature) = self.take_line(GpxFeatureKind::Route) {
+                    self.routes.push(feature);
+                }
+            }
+            (b"trk", _) => {
+                if let Some(feature) = self.take_line(GpxFeatureKind::Track) {
+                    self.tracks.push(feature);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn take_line(&mut self, kind: GpxFeatureKind) -> Option<GpxFeature> {
+        let segments: Vec<_> = std::mem::take(&mut self.segments)
+            .into_iter()
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let name = self.name.take();
+        let description = self.description.take();
+
+        let elevations = segments
+            .iter()
+            .flatten()
+            .map(|(_, elevation)| *elevation)
+            .collect();
+        let mut contours: Vec<_> = segments
+            .into_iter()
+            .map(|segment| Contour::open(segment.into_iter().map(|(point, _)| point).collect()))
+            .collect();
+        let geometry = match contours.len() {
+            0 => return None,
+            1 => Geom::Contour(contours.remove(0)),
+            _ => Geom::MultiContour(MultiContour::from(contours)),
+        };
+
+        Some(GpxFeature {
+            kind,
+            name,
+            description,
+            geometry,
+            elevations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::geo::GeoPoint;
+    use galileo_types::{Contour as _, MultiContour as _};
+
+    const GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+  <metadata><name>Document name</name></metadata>
+  <trk>
+    <name>Track</name>
+    <trkseg>
+      <trkpt lat="1.0" lon="2.0"><ele>10</ele></trkpt>
+      <trkpt lat="1.5" lon="2.5"></trkpt>
+    </trkseg>
+    <trkseg>
+      <trkpt lat="3.0" lon="4.0"/>
+      <trkpt lat="3.5" lon="4.5"/>
+    </trkseg>
+  </trk>
+  <rte>
+    <name>Route &amp; more</name>
+    <rtept lat="5" lon="6"/>
+    <rtept lat="7" lon="8"/>
+  </rte>
+  <wpt lat="-33.9" lon="18.4">
+    <ele>12.5</ele>
+    <name>Cape Town</name>
+    <desc><![CDATA[Mother City]]></desc>
+  </wpt>
+</gpx>"#;
+
+    #[test]
+    fn parses_waypoints_routes_and_tracks() {
+        let features = parse_gpx(GPX).unwrap();
+        assert_eq!(features.len(), 3);
+
+        let waypoint = &features[0];
+        assert_eq!(waypoint.kind, GpxFeatureKind::Waypoint);
+        assert_eq!(waypoint.name.as_deref(), Some("Cape Town"));
+        assert_eq!(waypoint.description.as_deref(), Some("Mother City"));
+        assert_eq!(
+            waypoint.geometry,
+            Geom::Point(GeoPoint2d::latlon(-33.9, 18.4))
+        );
+        assert_eq!(waypoint.elevations, [Some(12.5)]);
+
+        let route = &features[1];
+        assert_eq!(route.kind, GpxFeatureKind::Route);
+        assert_eq!(route.name.as_deref(), Some("Route & more"));
+        assert!(
+            matches!(&route.geometry, Geom::Contour(contour) if contour.iter_points().count() == 2)
+        );
+
+        let track = &features[2];
+        assert_eq!(track.kind, GpxFeatureKind::Track);
+        assert_eq!(track.name.as_deref(), Some("Track"));
+        assert_eq!(track.elevations, [Some(10.0), None, None, None]);
+        let Geom::MultiContour(contours) = &track.geometry else {
+            panic!("expected multi contour");
+        };
+        let segments: Vec<_> = contours.contours().collect();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(
+            segments[1].iter_points().next().map(|point| point.lat()),
+            Some(3.0)
+        );
+    }
+
+    #[test]
+    fn invalid_coordinates_are_errors() {
+        assert!(parse_gpx(r#"<gpx><wpt lat="abc" lon="1"/></gpx>"#).is_err());
+        assert!(parse_gpx(r#"<gpx><wpt lon="1"/></gpx>"#).is_err());
+        assert!(parse_gpx(r#"<gpx><trk><trkseg></trkseg></trk></gpx>"#)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,650 @@
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::feature_layer::feature::Feature;
+use crate::layer::feature_layer::symbol::KmlSymbol;
+use crate::Color;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::NewGeoPoint;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{
+    ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon,
+};
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Style of a KML placemark.
+///
+/// All the values are optional, since KML styles can set only some of them. The values missing from the style of a
+/// placemark are taken from the shared style it references with `styleUrl`. [`KmlSymbol`] uses the KML defaults for
+/// the values missing from both.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KmlStyle {
+    /// Link to the icon image of points (`IconStyle/Icon/href`).
+    pub icon_href: Option<String>,
+    /// Color of points (`IconStyle/color`).
+    pub icon_color: Option<Color>,
+    /// Scale of the icon of points (`IconStyle/scale`).
+    pub icon_scale: Option<f64>,
+    /// Color of lines and polygon outlines (`LineStyle/color`).
+    pub line_color: Option<Color>,
+    /// Width of lines and polygon outlines in pixels (`LineStyle/width`).
+    pub line_width: Option<f64>,
+    /// Fill color of polygons (`PolyStyle/color`).
+    pub poly_color: Option<Color>,
+    /// Whether polygons are filled (`PolyStyle/fill`).
+    pub fill: Option<bool>,
+    /// Whether polygons are outlined (`PolyStyle/outline`).
+    pub outline: Option<bool>,
+}
+
+impl KmlStyle {
+    /// Returns the style with the values set in `other` replacing the values of this style.
+    pub fn merge(&self, other: &KmlStyle) -> KmlStyle {
+        KmlStyle {
+            icon_href: other.icon_href.clone().or_else(|| self.icon_href.clone()),
+            icon_color: other.icon_color.or(self.icon_color),
+            icon_scale: other.icon_scale.or(self.icon_scale),
+            line_color: other.line_color.or(self.line_color),
+            line_width: other.line_width.or(self.line_width),
+            poly_color: other.poly_color.or(self.poly_color),
+            fill: other.fill.or(self.fill),
+            outline: other.outline.or(self.outline),
+        }
+    }
+}
+
+/// A placemark read from a KML document.
+///
+/// A `MultiGeometry` of a placemark is converted into a multi point, multi contour or multi polygon. If it contains
+/// geometries of different types, the placemark is split into several features, one for each type, with the same
+/// name and style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KmlFeature {
+    /// Name of the placemark (`name`).
+    pub name: Option<String>,
+    /// Description of the placemark (`description`).
+    pub description: Option<String>,
+    /// Geometry of the placemark.
+    pub geometry: Geom<GeoPoint2d>,
+    /// Style of the placemark, with the shared style it references already applied.
+    pub style: KmlStyle,
+}
+
+impl Feature for KmlFeature {
+    type Geom = Geom<GeoPoint2d>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+}
+
+/// Placemarks and icons read from a KML or KMZ file.
+#[derive(Debug, Clone, Default)]
+pub struct KmlDocument {
+    /// Placemarks of the document in the order of the file.
+    pub features: Vec<KmlFeature>,
+    /// Icon images packed into a KMZ archive by their `href`.
+    pub icons: HashMap<String, Arc<DecodedImage>>,
+}
+
+impl KmlDocument {
+    /// Creates a symbol that draws the features of the document with their KML styles and the icons of the document.
+    pub fn symbol(&self) -> KmlSymbol {
+        self.icons
+            .iter()
+            .fold(KmlSymbol::new(), |symbol, (href, image)| {
+                symbol.with_icon(href.clone(), image.clone())
+            })
+    }
+}
+
+/// Reads placemarks with their styles from a KML document.
+///
+/// Points, line strings, linear rings and polygons are read, including the ones inside `MultiGeometry` elements.
+/// Placemarks without geometry, ground overlays, network links and extensions are ignored. Styles are resolved from
+/// the `Style` and `StyleMap` elements of the same document (the `normal` style of a style map is used).
+///
+/// Icons are not loaded, since KML files usually reference them by URL. Use [`parse_kmz`] to get the icons packed
+/// into a KMZ archive.
+///
+/// ```
+/// use galileo::layer::feature_layer::parse_kml;
+/// use galileo::Color;
+///
+/// let kml = r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Document>
+///     <Style id="red"><LineStyle><color>ff0000ff</color><width>3</width></LineStyle></Style>
+///     <Placemark>
+///         <name>Road</name>
+///         <styleUrl>#red</styleUrl>
+///         <LineString><coordinates>4.35,52.01 4.36,52.02</coordinates></LineString>
+///     </Placemark>
+/// </Document></kml>"#;
+///
+/// let document = parse_kml(kml).unwrap();
+/// assert_eq!(document.features[0].name.as_deref(), Some("Road"));
+/// assert_eq!(document.features[0].style.line_color, Some(Color::RED));
+/// ```
+pub fn parse_kml(xml: &str) -> Result<KmlDocument, GalileoError> {
+    let mut reader = Reader::from_str(xml);
+    let mut parser = KmlParser::default();
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(element) => parser.start(&element)?,
+            Event::Empty(element) => {
+                parser.start(&element)?;
+                parser.end(element.local_name().as_ref())?;
+            }
+            Event::End(element) => parser.end(element.local_name().as_ref())?,
+            Event::Text(text) => parser.text.push_str(&text.decode().map_err(xml_error)?),
+            Event::CData(text) => parser.text.push_str(&text.decode().map_err(xml_error)?),
+            Event::GeneralRef(reference) => {
+                let name = reference.decode().map_err(xml_error)?;
+                if let Some(ch) = reference.resolve_char_ref().map_err(xml_error)? {
+                    parser.text.push(ch);
+                } else if let Some(value) = resolve_predefined_entity(&name) {
+                    parser.text.push_str(value);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(KmlDocument {
+        features: parser.into_features(),
+        icons: HashMap::new(),
+    })
+}
+
+/// Reads placemarks with their styles and icons from a KMZ archive.
+///
+/// The archive must contain a `doc.kml` file or at least one `.kml` file, which is read with [`parse_kml`]. Icons
+/// of the placemarks that are packed into the archive are decoded and added to [`KmlDocument::icons`]. Icons that
+/// cannot be decoded are skipped.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn parse_kmz(bytes: &[u8]) -> Result<KmlDocument, GalileoError> {
+    use std::io::Read;
+
+    let kmz_error = |err: zip::result::ZipError| {
+        GalileoError::Generic(format!("failed to read KMZ archive: {err}"))
+    };
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).map_err(kmz_error)?;
+
+    let kml_file = archive
+        .file_names()
+        .find(|name| name.eq_ignore_ascii_case("doc.kml"))
+        .or_else(|| {
+            archive
+                .file_names()
+                .find(|name| name.to_ascii_lowercase().ends_with(".kml"))
+        })
+        .map(String::from)
+        .ok_or_else(|| GalileoError::Generic("KMZ archive contains no KML file".into()))?;
+
+    let mut contents = String::new();
+    archive
+        .by_name(&kml_file)
+        .map_err(kmz_error)?
+        .read_to_string(&mut contents)?;
+    let mut document = parse_kml(&contents)?;
+
+    for feature in &document.features {
+        let Some(href) = &feature.style.icon_href else {
+            continue;
+        };
+        if document.icons.contains_key(href) {
+            continue;
+        }
+
+        let Ok(mut file) = archive.by_name(href.trim_start_matches("./")) else {
+            continue;
+        };
+        let mut image = vec![];
+        if file.read_to_end(&mut image).is_err() {
+            continue;
+        }
+        match DecodedImage::new(&image) {
+            Ok(image) => {
+                document.icons.insert(href.clone(), Arc::new(image));
+            }
+            Err(err) => log::warn!("Failed to decode KMZ icon {href}: {err}"),
+        }
+    }
+
+    Ok(document)
+}
+
+fn xml_error(err: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid KML document: {err}"))
+}
+
+/// Parses a KML color, which is written as `aabbggrr` hex string.
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().trim_start_matches('#');
+    if value.len() != 8 {
+        return None;
+    }
+
+    let channel = |index: usize| u8::from_str_radix(value.get(index..index + 2)?, 16).ok();
+    Some(Color::rgba(
+        channel(6)?,
+        channel(4)?,
+        channel(2)?,
+        channel(0)?,
+    ))
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim() {
+        "1" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_coordinates(value: &str) -> Result<Vec<GeoPoint2d>, GalileoError> {
+    value
+        .split_whitespace()
+        .map(|tuple| {
+            let mut parts = tuple.split(',').map(|part| part.trim().parse::<f64>());
+            match (parts.next(), parts.next()) {
+                (Some(Ok(lon)), Some(Ok(lat))) => Ok(GeoPoint2d::latlon(lat, lon)),
+                _ => Err(xml_error(format!("invalid coordinates {tuple}"))),
+            }
+        })
+        .collect()
+}
+
+fn closed_contour(mut points: Vec<GeoPoint2d>) -> ClosedContour<GeoPoint2d> {
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    ClosedContour::new(points)
+}
+
+#[derive(Default)]
+struct Placemark {
+    name: Option<String>,
+    description: Option<String>,
+    style_url: Option<String>,
+    style: KmlStyle,
+    geometries: Vec<Geom<GeoPoint2d>>,
+}
+
+#[derive(Default)]
+struct PolygonRings {
+    outer: Option<ClosedContour<GeoPoint2d>>,
+    inner: Vec<ClosedContour<GeoPoint2d>>,
+}
+
+#[derive(Default)]
+struct KmlParser {
+    /// Local names of the open elements.
+    path: Vec<String>,
+    text: String,
+    styles: HashMap<String, KmlStyle>,
+    /// Urls of the `normal` styles of the style maps.
+    style_maps: HashMap<String, String>,
+    style: Option<(Option<String>, KmlStyle)>,
+    style_map: Option<(Option<String>, Option<String>, Option<String>)>,
+    placemark: Option<Placemark>,
+    polygon: Option<PolygonRings>,
+    placemarks: Vec<Placemark>,
+}
+
+impl KmlParser {
+    fn start(&mut self, element: &BytesStart) -> Result<(), GalileoError> {
+        let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+        self.text.clear();
+
+        let id = || -> Result<Option<String>, GalileoError> {
+            element
+                .try_get_attribute("id")
+                .map_err(xml_error)?
+                .map(|attr| {
+                    attr.normalized_value(XmlVersion::default())
+                        .map(|value| value.into_owned())
+                        .map_err(xml_error)
+                })
+                .transpose()
+        };
+
+        match name.as_str() {
+            "Style" => self.style = Some((id()?, KmlStyle::default())),
+            "StyleMap" => self.style_map = Some((id()?, None, None)),
+            "Pair" => {
+                if let Some(style_map) = &mut self.style_map {
+                    style_map.1 = None;
+                    style_map.2 = None;
+                }
+            }
+            "Placemark" => self.placemark = Some(Placemark::default()),
+            "Polygon" => self.polygon = Some(PolygonRings::default()),
+            _ => {}
+        }
+
+        self.path.push(name);
+        Ok(())
+    }
+
+    fn end(&mut self, name: &[u8]) -> Result<(), GalileoError> {
+        self.path.pop();
+        let parent = self.path.last().map(String::as_str);
+        let text = std::mem::take(&mut self.text);
+        let value = text.trim();
+
+        if let Some((_, style)) = &mut self.style {
+            match (name, parent) {
+                (b"href", Some("Icon")) => style.icon_href = Some(value.to_string()),
+                (b"color", Some("IconStyle")) => style.icon_color = parse_color(value),
+                (b"scale", Some("IconStyle")) => style.icon_scale = value.parse().ok(),
+                (b"color", Some("LineStyle")) => style.line_color = parse_color(value),
+                (b"width", Some("LineStyle")) => style.line_width = value.parse().ok(),
+                (b"color", Some("PolyStyle")) => style.poly_color = parse_color(value),
+                (b"fill", Some("PolyStyle")) => style.fill = parse_bool(value),
+                (b"outline", Some("PolyStyle")) => style.outline = parse_bool(value),
+                _ => {}
+            }
+        }
+
+        match (name, parent) {
+            (b"Style", _) => {
+                if let Some((id, style)) = self.style.take() {
+                    match (&mut self.placemark, id) {
+                        (Some(placemark), _) => placemark.style = style,
+                        (None, Some(id)) => {
+                            self.styles.insert(id, style);
+                        }
+                        (None, None) => {}
+                    }
+                }
+            }
+            (b"key", Some("Pair")) => {
+                if let Some(style_map) = &mut self.style_map {
+                    style_map.1 = Some(value.to_string());
+                }
+            }
+            (b"styleUrl", Some("Pair")) => {
+                if let Some(style_map) = &mut self.style_map {
+                    style_map.2 = Some(value.to_string());
+                }
+            }
+            (b"Pair", _) => {
+                if let Some((Some(id), Some(key), Some(url))) = &self.style_map {
+                    if key == "normal" {
+                        self.style_maps.insert(id.clone(), url.clone());
+                    }
+                }
+            }
+            (b"StyleMap", _) => self.style_map = None,
+            (b"name", Some("Placemark")) => {
+                if let Some(placemark) = &mut self.placemark {
+                    placemark.name = Some(value.to_string());
+                }
+            }
+            (b"description", Some("Placemark")) => {
+                if let Some(placemark) = &mut self.placemark {
+                    placemark.description = Some(value.to_string());
+                }
+            }
+            (b"styleUrl", Some("Placemark")) => {
+                if let Some(placemark) = &mut self.placemark {
+                    placemark.style_url = Some(value.to_string());
+                }
+            }
+            (b"coordinates", Some(geometry)) => {
+                let is_in_boundary = |boundary: &str| {
+                    self.path
+                        .iter()
+                        .rev()
+                        .nth(1)
+                        .is_some_and(|name| name == boundary)
+                };
+                let points = parse_coordinates(value)?;
+                match geometry {
+                    "Point" => {
+                        if let Some(point) = points.first() {
+                            self.push_geometry(Geom::Point(*point));
+                        }
+                    }
+                    "LineString" if !points.is_empty() => {
+                        self.push_geometry(Geom::Contour(Contour::open(points)));
+                    }
+                    "LinearRing" if !points.is_empty() => {
+                        let ring = closed_contour(points);
+                        match &mut self.polygon {
+                            Some(polygon) if is_in_boundary("outerBoundaryIs") => {
+                                polygon.outer = Some(ring)
+                            }
+                            Some(polygon) if is_in_boundary("innerBoundaryIs") => {
+                                polygon.inner.push(ring)
+                            }
+                            _ => self.push_geometry(Geom::Contour(ring.into())),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            (b"Polygon", _) => {
+                if let Some(PolygonRings {
+                    outer: Some(outer),
+                    inner,
+                }) = self.polygon.take()
+                {
+                    self.push_geometry(Geom::Polygon(Polygon::new(outer, inner)));
+                }
+            }
+            (b"Placemark", _) => {
+                if let Some(placemark) = self.placemark.take() {
+                    if !placemark.geometries.is_empty() {
+                        self.placemarks.push(placemark);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn push_geometry(&mut self, geometry: Geom<GeoPoint2d>) {
+        if let Some(placemark) = &mut self.placemark {
+            placemark.geometries.push(geometry);
+        }
+    }
+
+    fn shared_style(&self, url: &str) -> Option<&KmlStyle> {
+        let id = url.strip_prefix('#')?;
+        match self.style_maps.get(id) {
+            Some(url) => self.styles.get(url.strip_prefix('#')?),
+            None => self.styles.get(id),
+        }
+    }
+
+    fn into_features(mut self) -> Vec<KmlFeature> {
+        let placemarks = std::mem::take(&mut self.placemarks);
+        let mut features = vec![];
+        for placemark in placemarks {
+            let style = match placemark
+                .style_url
+                .as_deref()
+                .and_then(|url| self.shared_style(url))
+            {
+                Some(shared) => shared.merge(&placemark.style),
+                None => placemark.style,
+            };
+
+            let mut points = vec![];
+            let mut contours = vec![];
+            let mut polygons = vec![];
+            for geometry in placemark.geometries {
+                match geometry {
+                    Geom::Point(point) => points.push(point),
+                    Geom::Contour(contour) => contours.push(contour),
+                    Geom::Polygon(polygon) => polygons.push(polygon),
+                    _ => {}
+                }
+            }
+
+            let geometries = [
+                match points.len() {
+                    0 => None,
+                    1 => Some(Geom::Point(points.remove(0))),
+                    _ => Some(Geom::MultiPoint(MultiPoint::from(points))),
+                },
+                match contours.len() {
+                    0 => None,
+                    1 => Some(Geom::Contour(contours.remove(0))),
+                    _ => Some(Geom::MultiContour(MultiContour::from(contours))),
+                },
+                match polygons.len() {
+                    0 => None,
+                    1 => Some(Geom::Polygon(polygons.remove(0))),
+                    _ => Some(Geom::MultiPolygon(MultiPolygon::from(polygons))),
+                },
+            ];
+
+            for geometry in geometries.into_iter().flatten() {
+                features.push(KmlFeature {
+                    name: placemark.name.clone(),
+                    description: placemark.description.clone(),
+                    geometry,
+                    style: style.clone(),
+                });
+            }
+        }
+
+        features
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::{Contour as _, MultiPoint as _, Polygon as _};
+
+    const KML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+  <Document>
+    <Style id="river">
+      <LineStyle><color>7fff0000</color><width>4</width></LineStyle>
+    </Style>
+    <Style id="park-normal">
+      <IconStyle><scale>1.5</scale><Icon><href>files/tree.png</href></Icon></IconStyle>
+      <PolyStyle><color>ff00ff00</color><outline>0</outline></PolyStyle>
+    </Style>
+    <StyleMap id="park">
+      <Pair><key>normal</key><styleUrl>#park-normal</styleUrl></Pair>
+      <Pair><key>highlight</key><styleUrl>#river</styleUrl></Pair>
+    </StyleMap>
+    <Folder>
+      <Placemark>
+        <name>River</name>
+        <styleUrl>#river</styleUrl>
+        <LineString><coordinates>
+          1,2,0 3,4,0
+        </coordinates></LineString>
+      </Placemark>
+      <Placemark>
+        <name>Park</name>
+        <description><![CDATA[<b>Open</b> daily]]></description>
+        <styleUrl>#park</styleUrl>
+        <Style><PolyStyle><fill>0</fill></PolyStyle></Style>
+        <MultiGeometry>
+          <Point><coordinates>10,20</coordinates></Point>
+          <Point><coordinates>11,21</coordinates></Point>
+          <Polygon>
+            <outerBoundaryIs><LinearRing><coordinates>0,0 1,0 1,1 0,1 0,0</coordinates></LinearRing></outerBoundaryIs>
+            <innerBoundaryIs><LinearRing><coordinates>0.2,0.2 0.4,0.2 0.4,0.4 0.2,0.2</coordinates></LinearRing></innerBoundaryIs>
+          </Polygon>
+        </MultiGeometry>
+      </Placemark>
+      <Placemark><name>No geometry</name></Placemark>
+    </Folder>
+  </Document>
+</kml>"#;
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse_color("ff0000ff"), Some(Color::RED));
+        assert_eq!(
+            parse_color(" 7f332211 "),
+            Some(Color::rgba(0x11, 0x22, 0x33, 0x7f))
+        );
+        assert_eq!(parse_color("ff0000"), None);
+        assert_eq!(parse_color("zz0000ff"), None);
+    }
+
+    #[test]
+    fn parses_placemarks_with_styles() {
+        let features = parse_kml(KML).unwrap().features;
+        assert_eq!(features.len(), 3);
+
+        let river = &features[0];
+        assert_eq!(river.name.as_deref(), Some("River"));
+        assert_eq!(river.style.line_color, Some(Color::rgba(0, 0, 255, 0x7f)));
+        assert_eq!(river.style.line_width, Some(4.0));
+        assert!(
+            matches!(&river.geometry, Geom::Contour(contour) if contour.iter_points().count() == 2)
+        );
+
+        let Geom::MultiPoint(points) = &features[1].geometry else {
+            panic!("expected multi point");
+        };
+        assert_eq!(points.iter_points().count(), 2);
+
+        let park = &features[2];
+        assert_eq!(park.description.as_deref(), Some("<b>Open</b> daily"));
+        assert_eq!(park.style.icon_href.as_deref(), Some("files/tree.png"));
+        assert_eq!(park.style.icon_scale, Some(1.5));
+        assert_eq!(park.style.poly_color, Some(Color::GREEN));
+        assert_eq!(park.style.fill, Some(false));
+        assert_eq!(park.style.outline, Some(false));
+        let Geom::Polygon(polygon) = &park.geometry else {
+            panic!("expected polygon");
+        };
+        assert_eq!(polygon.outer_contour().iter_points().count(), 4);
+        assert_eq!(polygon.inner_contours().count(), 1);
+    }
+
+    #[test]
+    fn invalid_coordinates_are_errors() {
+        let kml = "<kml><Placemark><Point><coordinates>a,b</coordinates></Point></Placemark></kml>";
+        assert!(parse_kml(kml).is_err());
+    }
+
+    #[test]
+    fn reads_kmz_with_icons() {
+        use std::io::Write;
+
+        let mut icon = vec![];
+        image::DynamicImage::new_rgba8(2, 2)
+            .write_to(
+                &mut std::io::Cursor::new(&mut icon),
+                image::ImageOutputFormat::Png,
+            )
+            .unwrap();
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+        writer
+            .start_file("doc.kml", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(KML.as_bytes()).unwrap();
+        writer
+            .start_file("files/tree.png", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(&icon).unwrap();
+        let kmz = writer.finish().unwrap().into_inner();
+
+        let document = parse_kmz(&kmz).unwrap();
+        assert_eq!(document.features.len(), 3);
+        assert_eq!(document.icons.len(), 1);
+        assert_eq!(document.icons["files/tree.png"].dimensions, (2, 2));
+
+        assert!(parse_kmz(b"not an archive").is_err());
+    }
+}
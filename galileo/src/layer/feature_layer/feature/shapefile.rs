@@ -0,0 +1,838 @@
+use crate::error::GalileoError;
+use crate::layer::feature_layer::feature::{cast_geom, AttributeValue, Feature, FeatureAttributes};
+use galileo_types::cartesian::{CartesianPoint2d, Point2d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, NewGeoPoint};
+use galileo_types::geometry::Geom;
+use galileo_types::geometry_type::GeometryType;
+use galileo_types::impls::{
+    ClosedContour, Contour, MultiContour, MultiPoint, MultiPolygon, Polygon,
+};
+use std::collections::HashMap;
+
+const SHP_FILE_CODE: i32 = 9994;
+const SHP_HEADER_LENGTH: usize = 100;
+const DBF_HEADER_LENGTH: usize = 32;
+const DBF_FIELD_LENGTH: usize = 32;
+const DBF_HEADER_TERMINATOR: u8 = 0x0d;
+const DBF_DELETED_RECORD: u8 = b'*';
+
+/// Value of an attribute of a [`ShapefileFeature`], read from the `.dbf` file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DbfValue {
+    /// Text (`C` field).
+    Character(String),
+    /// Number (`N` and `F` fields).
+    Numeric(f64),
+    /// Boolean (`L` field).
+    Logical(bool),
+    /// Date in `YYYYMMDD` format (`D` field).
+    Date(String),
+    /// Value is not set.
+    Null,
+}
+
+impl DbfValue {
+    /// Returns the text value, if this is a character value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Character(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the number, if this is a numeric value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Numeric(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean, if this is a logical value.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Logical(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// A shape read from a shapefile together with its attributes.
+///
+/// Point type `P` is [`Point2d`] with the coordinates of the shapefile CRS, or [`GeoPoint2d`] for the shapefiles
+/// converted with [`Shapefile::into_geo`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShapefileFeature<P = Point2d> {
+    /// Geometry of the shape.
+    pub geometry: Geom<P>,
+    /// Attributes of the shape by the field names. Empty if the shapefile has no `.dbf` file.
+    pub attributes: HashMap<String, DbfValue>,
+}
+
+impl<P> ShapefileFeature<P> {
+    /// Returns the value of the attribute with the given field name.
+    pub fn attribute(&self, name: &str) -> Option<&DbfValue> {
+        self.attributes.get(name)
+    }
+}
+
+impl<P: GeometryType> Feature for ShapefileFeature<P> {
+    type Geom = Geom<P>;
+
+    fn geometry(&self) -> &Self::Geom {
+        &self.geometry
+    }
+}
+
+impl<P> FeatureAttributes for ShapefileFeature<P> {
+    fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        match self.attributes.get(name)? {
+            DbfValue::Character(value) | DbfValue::Date(value) => {
+                Some(AttributeValue::String(value.clone()))
+            }
+            DbfValue::Numeric(value) => Some(AttributeValue::Number(*value)),
+            DbfValue::Logical(value) => Some(AttributeValue::Bool(*value)),
+            DbfValue::Null => None,
+        }
+    }
+
+    fn attributes(&self) -> Vec<(String, AttributeValue)> {
+        let mut attributes: Vec<_> = self
+            .attributes
+            .keys()
+            .filter_map(|name| Some((name.clone(), FeatureAttributes::attribute(self, name)?)))
+            .collect();
+        attributes.sort_by(|a, b| a.0.cmp(&b.0));
+        attributes
+    }
+}
+
+/// Shapes of a shapefile with their attributes and CRS.
+///
+/// Points, multipoints, polylines and polygons are read, including their `Z` and `M` variants (only `x` and `y`
+/// coordinates are used). Null shapes and deleted records are skipped.
+///
+/// The CRS is read from the `.prj` file. Geographic coordinate systems, Web Mercator, and transverse Mercator
+/// (including UTM), Lambert conformal conic and Mercator projections are supported. Datum shifts are not applied,
+/// so coordinates in datums other than WGS84 are treated as if they were WGS84.
+///
+/// ```no_run
+/// use galileo::layer::feature_layer::{FeatureLayer, Shapefile};
+/// use galileo_types::geometry_type::CartesianSpace2d;
+/// use galileo::symbol::SimplePolygonSymbol;
+/// use galileo::Color;
+/// use galileo_types::geo::Crs;
+///
+/// let shapefile = Shapefile::read("data/parcels.shp").unwrap();
+/// let crs = shapefile.crs.clone().unwrap_or(Crs::EPSG3857);
+/// for feature in &shapefile.features {
+///     println!("{:?}", feature.attribute("OWNER"));
+/// }
+///
+/// let layer: FeatureLayer<_, _, _, CartesianSpace2d> = FeatureLayer::new(
+///     shapefile.features,
+///     SimplePolygonSymbol::new(Color::BLUE),
+///     crs,
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shapefile<P = Point2d> {
+    /// Shapes of the file in the order of records.
+    pub features: Vec<ShapefileFeature<P>>,
+    /// Names of the attribute fields in the order of the `.dbf` file.
+    pub fields: Vec<String>,
+    /// CRS of the coordinates, or `None` if there is no `.prj` file or its CRS is not supported.
+    pub crs: Option<Crs>,
+}
+
+impl Shapefile {
+    /// Reads the shapefile from the `.shp` file at the `path`, and the `.dbf` and `.prj` files with the same name
+    /// next to it, if they exist.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read(path: impl AsRef<std::path::Path>) -> Result<Self, GalileoError> {
+        let path = path.as_ref();
+        let sibling = |extension: &str| {
+            [extension.to_string(), extension.to_ascii_uppercase()]
+                .into_iter()
+                .map(|extension| path.with_extension(extension))
+                .find(|path| path.exists())
+        };
+
+        let shp = std::fs::read(path)?;
+        let dbf = sibling("dbf").map(std::fs::read).transpose()?;
+        let prj = sibling("prj").map(std::fs::read_to_string).transpose()?;
+
+        Self::parse(&shp, dbf.as_deref(), prj.as_deref())
+    }
+
+    /// Parses the contents of the `.shp` file, and optionally of the `.dbf` and `.prj` files of a shapefile.
+    ///
+    /// Text attributes are decoded as UTF-8, with invalid sequences replaced.
+    pub fn parse(shp: &[u8], dbf: Option<&[u8]>, prj: Option<&str>) -> Result<Self, GalileoError> {
+        let shapes = read_shapes(shp)?;
+        let (fields, records) = match dbf {
+            Some(dbf) => read_records(dbf)?,
+            None => (vec![], vec![]),
+        };
+
+        if dbf.is_some() && records.len() != shapes.len() {
+            return Err(shapefile_error(format!(
+                "{} shapes and {} attribute records",
+                shapes.len(),
+                records.len()
+            )));
+        }
+
+        let mut records = records.into_iter();
+        let features = shapes
+            .into_iter()
+            .filter_map(|shape| {
+                let record = records.next().flatten();
+                let geometry = shape?;
+                if dbf.is_some() && record.is_none() {
+                    // Deleted record.
+                    return None;
+                }
+
+                Some(ShapefileFeature {
+                    geometry,
+                    attributes: record
+                        .map(|values| fields.iter().cloned().zip(values).collect())
+                        .unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            features,
+            fields,
+            crs: prj.and_then(crs_from_prj),
+        })
+    }
+
+    /// Converts a shapefile in geographic coordinates into features with [`GeoPoint2d`] geometries, so they can be
+    /// added to a [`FeatureLayer`](crate::layer::FeatureLayer) with geographic coordinates. `x` coordinates are used
+    /// as longitudes and `y` coordinates as latitudes.
+    pub fn into_geo(self) -> Shapefile<GeoPoint2d> {
+        Shapefile {
+            features: self
+                .features
+                .into_iter()
+                .map(|feature| ShapefileFeature {
+                    geometry: cast_geom(&feature.geometry, |point| {
+                        GeoPoint2d::latlon(point.y(), point.x())
+                    }),
+                    attributes: feature.attributes,
+                })
+                .collect(),
+            fields: self.fields,
+            crs: self.crs,
+        }
+    }
+}
+
+fn shapefile_error(message: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid shapefile: {message}"))
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], GalileoError> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + N)
+            .ok_or_else(|| shapefile_error("unexpected end of file"))?;
+        self.offset += N;
+        Ok(bytes.try_into().expect("slice has correct length"))
+    }
+
+    fn i32_be(&mut self) -> Result<i32, GalileoError> {
+        Ok(i32::from_be_bytes(self.take()?))
+    }
+
+    fn i32_le(&mut self) -> Result<i32, GalileoError> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    fn f64_le(&mut self) -> Result<f64, GalileoError> {
+        Ok(f64::from_le_bytes(self.take()?))
+    }
+
+    fn count(&mut self) -> Result<usize, GalileoError> {
+        usize::try_from(self.i32_le()?).map_err(|_| shapefile_error("negative count"))
+    }
+
+    fn points(&mut self, count: usize) -> Result<Vec<Point2d>, GalileoError> {
+        (0..count)
+            .map(|_| Ok(Point2d::new(self.f64_le()?, self.f64_le()?)))
+            .collect()
+    }
+
+    fn skip(&mut self, count: usize) {
+        self.offset += count;
+    }
+}
+
+/// Reads the shapes of a `.shp` file. Null shapes are returned as `None`.
+fn read_shapes(shp: &[u8]) -> Result<Vec<Option<Geom<Point2d>>>, GalileoError> {
+    let mut header = ByteReader::new(shp);
+    if header.i32_be()? != SHP_FILE_CODE {
+        return Err(shapefile_error("not a .shp file"));
+    }
+
+    let mut shapes = vec![];
+    let mut offset = SHP_HEADER_LENGTH;
+    while offset + 8 <= shp.len() {
+        let mut record_header = ByteReader::new(&shp[offset..]);
+        let _record_number = record_header.i32_be()?;
+        let length = usize::try_from(record_header.i32_be()?)
+            .map_err(|_| shapefile_error("negative record length"))?
+            * 2;
+        let content = shp
+            .get(offset + 8..offset + 8 + length)
+            .ok_or_else(|| shapefile_error("unexpected end of file"))?;
+        shapes.push(read_shape(content)?);
+        offset += 8 + length;
+    }
+
+    Ok(shapes)
+}
+
+fn read_shape(content: &[u8]) -> Result<Option<Geom<Point2d>>, GalileoError> {
+    let mut reader = ByteReader::new(content);
+    let shape_type = reader.i32_le()?;
+
+    // Z (1x) and M (2x) variants store x and y coordinates the same way as the plain types, followed by the
+    // additional values.
+    let geometry = match shape_type {
+        0 => None,
+        1 | 11 | 21 => Some(Geom::Point(reader.points(1)?.remove(0))),
+        8 | 18 | 28 => {
+            reader.skip(32);
+            let count = reader.count()?;
+            Some(Geom::MultiPoint(MultiPoint::from(reader.points(count)?)))
+        }
+        3 | 13 | 23 | 5 | 15 | 25 => {
+            reader.skip(32);
+            let part_count = reader.count()?;
+            let point_count = reader.count()?;
+            let mut starts = (0..part_count)
+                .map(|_| reader.count())
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut points = reader.points(point_count)?;
+
+            starts.push(point_count);
+            let mut parts = vec![];
+            for range in starts.windows(2).rev() {
+                if range[0] > range[1] || range[1] > points.len() {
+                    return Err(shapefile_error("invalid part index"));
+                }
+                parts.push(points.split_off(range[0]));
+            }
+            parts.reverse();
+
+            if shape_type % 10 == 3 {
+                line_geometry(parts)
+            } else {
+                polygon_geometry(parts)
+            }
+        }
+        _ => {
+            log::warn!("Unsupported shapefile shape type {shape_type} is skipped");
+            None
+        }
+    };
+
+    Ok(geometry)
+}
+
+fn line_geometry(parts: Vec<Vec<Point2d>>) -> Option<Geom<Point2d>> {
+    let mut contours: Vec<_> = parts
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .map(Contour::open)
+        .collect();
+    match contours.len() {
+        0 => None,
+        1 => Some(Geom::Contour(contours.remove(0))),
+        _ => Some(Geom::MultiContour(MultiContour::from(contours))),
+    }
+}
+
+/// Builds polygons from the rings of a shape. Outer rings are clockwise, and the counterclockwise rings following
+/// an outer ring are its holes.
+fn polygon_geometry(parts: Vec<Vec<Point2d>>) -> Option<Geom<Point2d>> {
+    let mut polygons: Vec<(ClosedContour<Point2d>, Vec<ClosedContour<Point2d>>)> = vec![];
+    for mut ring in parts {
+        if ring.len() > 1 && ring.first() == ring.last() {
+            ring.pop();
+        }
+        if ring.len() < 3 {
+            continue;
+        }
+
+        let is_clockwise = signed_area(&ring) < 0.0;
+        let ring = ClosedContour::new(ring);
+        match polygons.last_mut() {
+            Some((_, holes)) if !is_clockwise => holes.push(ring),
+            _ => polygons.push((ring, vec![])),
+        }
+    }
+
+    let mut polygons: Vec<_> = polygons
+        .into_iter()
+        .map(|(outer, holes)| Polygon::new(outer, holes))
+        .collect();
+    match polygons.len() {
+        0 => None,
+        1 => Some(Geom::Polygon(polygons.remove(0))),
+        _ => Some(Geom::MultiPolygon(MultiPolygon::from(polygons))),
+    }
+}
+
+/// Doubled signed area of the ring. Positive for counterclockwise rings.
+fn signed_area(ring: &[Point2d]) -> f64 {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(from, to)| from.x() * to.y() - to.x() * from.y())
+        .sum()
+}
+
+/// Reads field names and records of a `.dbf` file. Deleted records are returned as `None`.
+#[allow(clippy::type_complexity)]
+fn read_records(dbf: &[u8]) -> Result<(Vec<String>, Vec<Option<Vec<DbfValue>>>), GalileoError> {
+    let header = dbf
+        .get(..DBF_HEADER_LENGTH)
+        .ok_or_else(|| shapefile_error("unexpected end of .dbf file"))?;
+    let record_count = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let header_length = u16::from_le_bytes([header[8], header[9]]) as usize;
+    let record_length = u16::from_le_bytes([header[10], header[11]]) as usize;
+
+    // Every record starts with the deletion flag, and all of them must fit in the file. This prevents huge allocations
+    // for corrupted headers.
+    if record_length == 0 {
+        return Err(shapefile_error("invalid .dbf record length"));
+    }
+    if record_count
+        .checked_mul(record_length)
+        .and_then(|length| length.checked_add(header_length))
+        .is_none_or(|end| end > dbf.len())
+    {
+        return Err(shapefile_error("invalid number of .dbf records"));
+    }
+
+    let mut fields = vec![];
+    let mut offset = DBF_HEADER_LENGTH;
+    while dbf
+        .get(offset)
+        .is_some_and(|byte| *byte != DBF_HEADER_TERMINATOR)
+    {
+        let descriptor = dbf
+            .get(offset..offset + DBF_FIELD_LENGTH)
+            .ok_or_else(|| shapefile_error("unexpected end of .dbf file"))?;
+        let name_length = descriptor[..11]
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(11);
+        let name = String::from_utf8_lossy(&descriptor[..name_length]).into_owned();
+        fields.push((name, descriptor[11], descriptor[16] as usize));
+        offset += DBF_FIELD_LENGTH;
+    }
+
+    let mut records = Vec::with_capacity(record_count);
+    for index in 0..record_count {
+        let start = header_length + index * record_length;
+        let record = dbf
+            .get(start..start + record_length)
+            .ok_or_else(|| shapefile_error("unexpected end of .dbf file"))?;
+        if record[0] == DBF_DELETED_RECORD {
+            records.push(None);
+            continue;
+        }
+
+        let mut values = Vec::with_capacity(fields.len());
+        let mut position = 1;
+        for (_, field_type, length) in &fields {
+            let raw = record
+                .get(position..position + length)
+                .ok_or_else(|| shapefile_error("field is longer than the record"))?;
+            values.push(parse_value(*field_type, raw));
+            position += length;
+        }
+        records.push(Some(values));
+    }
+
+    Ok((fields.into_iter().map(|(name, ..)| name).collect(), records))
+}
+
+fn parse_value(field_type: u8, raw: &[u8]) -> DbfValue {
+    let text = String::from_utf8_lossy(raw);
+    let trimmed = text.trim();
+    match field_type {
+        b'C' => DbfValue::Character(text.trim_end().to_string()),
+        b'N' | b'F' => trimmed
+            .parse()
+            .map(DbfValue::Numeric)
+            .unwrap_or(DbfValue::Null),
+        b'L' => match trimmed {
+            "T" | "t" | "Y" | "y" => DbfValue::Logical(true),
+            "F" | "f" | "N" | "n" => DbfValue::Logical(false),
+            _ => DbfValue::Null,
+        },
+        b'D' if !trimmed.is_empty() => DbfValue::Date(trimmed.to_string()),
+        b'D' => DbfValue::Null,
+        _ => DbfValue::Character(trimmed.to_string()),
+    }
+}
+
+/// A node of a well-known text CRS definition, e.g. `PARAMETER["false_easting",500000.0]`.
+#[derive(Debug)]
+struct WktNode {
+    keyword: String,
+    texts: Vec<String>,
+    numbers: Vec<f64>,
+    children: Vec<WktNode>,
+}
+
+impl WktNode {
+    fn parse(wkt: &str) -> Option<Self> {
+        let mut chars = wkt.trim().chars().peekable();
+        let node = Self::parse_node(&mut chars)?;
+        chars.all(char::is_whitespace).then_some(node)
+    }
+
+    fn parse_node(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Self> {
+        let mut keyword = String::new();
+        while let Some(ch) = chars.next_if(|ch| ch.is_ascii_alphanumeric() || *ch == '_') {
+            keyword.push(ch);
+        }
+        if keyword.is_empty() || !matches!(chars.next()?, '[' | '(') {
+            return None;
+        }
+
+        let mut node = WktNode {
+            keyword: keyword.to_ascii_uppercase(),
+            texts: vec![],
+            numbers: vec![],
+            children: vec![],
+        };
+        loop {
+            while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+            match *chars.peek()? {
+                '"' => {
+                    chars.next();
+                    node.texts
+                        .push(chars.by_ref().take_while(|ch| *ch != '"').collect());
+                }
+                ch if ch.is_ascii_alphabetic() => node.children.push(Self::parse_node(chars)?),
+                _ => {
+                    let mut number = String::new();
+                    while let Some(ch) = chars.next_if(|ch| !matches!(ch, ',' | ']' | ')')) {
+                        number.push(ch);
+                    }
+                    node.numbers.push(number.trim().parse().ok()?);
+                }
+            }
+
+            while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+            match chars.next()? {
+                ',' => {}
+                ']' | ')' => return Some(node),
+                _ => return None,
+            }
+        }
+    }
+
+    fn child(&self, keyword: &str) -> Option<&WktNode> {
+        self.children.iter().find(|child| child.keyword == keyword)
+    }
+
+    fn name(&self) -> &str {
+        self.texts.first().map(String::as_str).unwrap_or_default()
+    }
+}
+
+/// Converts the well-known text CRS definition of a `.prj` file into a [`Crs`].
+fn crs_from_prj(wkt: &str) -> Option<Crs> {
+    let root = WktNode::parse(wkt)?;
+    match root.keyword.as_str() {
+        "GEOGCS" => Some(Crs::WGS84),
+        "PROJCS" => {
+            let projection = root.child("PROJECTION")?.name().to_ascii_lowercase();
+            let name = root.name().to_ascii_lowercase();
+            if projection.contains("pseudo_mercator")
+                || projection.contains("auxiliary_sphere")
+                || name.contains("pseudo-mercator")
+                || name.contains("web_mercator")
+            {
+                return Some(Crs::EPSG3857);
+            }
+
+            let is_metric = root
+                .children
+                .iter()
+                .rfind(|child| child.keyword == "UNIT")
+                .and_then(|unit| unit.numbers.first())
+                .is_none_or(|factor| (factor - 1.0).abs() < 1e-9);
+            if !is_metric {
+                return None;
+            }
+
+            let proj = match projection.as_str() {
+                "transverse_mercator" => "tmerc",
+                "lambert_conformal_conic"
+                | "lambert_conformal_conic_1sp"
+                | "lambert_conformal_conic_2sp" => "lcc",
+                "mercator" | "mercator_1sp" | "mercator_2sp" => "merc",
+                _ => return None,
+            };
+
+            let mut definition = format!("+proj={proj}");
+            for parameter in root
+                .children
+                .iter()
+                .filter(|child| child.keyword == "PARAMETER")
+            {
+                let key = match parameter.name().to_ascii_lowercase().as_str() {
+                    "latitude_of_origin" | "latitude_of_center" => "lat_0",
+                    "central_meridian" | "longitude_of_center" => "lon_0",
+                    "scale_factor" => "k_0",
+                    "false_easting" => "x_0",
+                    "false_northing" => "y_0",
+                    "standard_parallel_1" => "lat_1",
+                    "standard_parallel_2" => "lat_2",
+                    _ => continue,
+                };
+                definition.push_str(&format!(" +{key}={}", parameter.numbers.first()?));
+            }
+
+            let spheroid = root.child("GEOGCS")?.child("DATUM")?.child("SPHEROID")?;
+            if let [semimajor, inv_flattening, ..] = spheroid.numbers[..] {
+                definition.push_str(&format!(" +a={semimajor} +rf={inv_flattening}"));
+            }
+
+            Crs::from_proj4(&definition)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::geo::GeoPoint;
+    use galileo_types::{Contour as _, MultiContour as _, Polygon as _};
+
+    fn shp(records: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = vec![0; SHP_HEADER_LENGTH];
+        bytes[..4].copy_from_slice(&SHP_FILE_CODE.to_be_bytes());
+        for (index, content) in records.iter().enumerate() {
+            bytes.extend_from_slice(&(index as i32 + 1).to_be_bytes());
+            bytes.extend_from_slice(&(content.len() as i32 / 2).to_be_bytes());
+            bytes.extend_from_slice(content);
+        }
+        bytes
+    }
+
+    fn point_record(x: f64, y: f64) -> Vec<u8> {
+        let mut bytes = 1i32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&x.to_le_bytes());
+        bytes.extend_from_slice(&y.to_le_bytes());
+        bytes
+    }
+
+    fn poly_record(shape_type: i32, parts: &[&[(f64, f64)]]) -> Vec<u8> {
+        let mut bytes = shape_type.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&[0; 32]);
+        bytes.extend_from_slice(&(parts.len() as i32).to_le_bytes());
+        let point_count: usize = parts.iter().map(|part| part.len()).sum();
+        bytes.extend_from_slice(&(point_count as i32).to_le_bytes());
+        let mut start = 0;
+        for part in parts {
+            bytes.extend_from_slice(&(start as i32).to_le_bytes());
+            start += part.len();
+        }
+        for (x, y) in parts.iter().flat_map(|part| part.iter()) {
+            bytes.extend_from_slice(&x.to_le_bytes());
+            bytes.extend_from_slice(&y.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn dbf(fields: &[(&str, u8, u8)], records: &[(bool, Vec<&str>)]) -> Vec<u8> {
+        let record_length = 1 + fields.iter().map(|field| field.2 as usize).sum::<usize>();
+        let header_length = DBF_HEADER_LENGTH + fields.len() * DBF_FIELD_LENGTH + 1;
+
+        let mut bytes = vec![0; DBF_HEADER_LENGTH];
+        bytes[0] = 3;
+        bytes[4..8].copy_from_slice(&(records.len() as u32).to_le_bytes());
+        bytes[8..10].copy_from_slice(&(header_length as u16).to_le_bytes());
+        bytes[10..12].copy_from_slice(&(record_length as u16).to_le_bytes());
+        for (name, field_type, length) in fields {
+            let mut descriptor = [0; DBF_FIELD_LENGTH];
+            descriptor[..name.len()].copy_from_slice(name.as_bytes());
+            descriptor[11] = *field_type;
+            descriptor[16] = *length;
+            bytes.extend_from_slice(&descriptor);
+        }
+        bytes.push(DBF_HEADER_TERMINATOR);
+
+        for (is_deleted, values) in records {
+            bytes.push(if *is_deleted {
+                DBF_DELETED_RECORD
+            } else {
+                b' '
+            });
+            for ((_, _, length), value) in fields.iter().zip(values) {
+                bytes.extend_from_slice(
+                    format!("{value:<width$}", width = *length as usize).as_bytes(),
+                );
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn reads_shapes_with_attributes() {
+        let square: &[(f64, f64)] = &[
+            (0.0, 0.0),
+            (0.0, 10.0),
+            (10.0, 10.0),
+            (10.0, 0.0),
+            (0.0, 0.0),
+        ];
+        let hole: &[(f64, f64)] = &[(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 2.0)];
+        let shp = shp(&[
+            point_record(1.0, 2.0),
+            poly_record(3, &[&[(0.0, 0.0), (1.0, 1.0)], &[(2.0, 2.0), (3.0, 3.0)]]),
+            poly_record(5, &[square, hole]),
+            point_record(5.0, 5.0),
+        ]);
+        let dbf = dbf(
+            &[("NAME", b'C', 10), ("AREA", b'N', 8), ("OPEN", b'L', 1)],
+            &[
+                (false, vec!["Well", "1.5", "T"]),
+                (false, vec!["Road", "", "?"]),
+                (false, vec!["Park", "100", "F"]),
+                (true, vec!["Deleted", "0", "F"]),
+            ],
+        );
+
+        let shapefile = Shapefile::parse(&shp, Some(&dbf), None).unwrap();
+        assert_eq!(shapefile.fields, ["NAME", "AREA", "OPEN"]);
+        assert_eq!(shapefile.features.len(), 3);
+        assert_eq!(shapefile.crs, None);
+
+        let well = &shapefile.features[0];
+        assert_eq!(well.geometry, Geom::Point(Point2d::new(1.0, 2.0)));
+        assert_eq!(
+            well.attribute("NAME").and_then(DbfValue::as_str),
+            Some("Well")
+        );
+        assert_eq!(well.attribute("AREA").and_then(DbfValue::as_f64), Some(1.5));
+        assert_eq!(
+            well.attribute("OPEN").and_then(DbfValue::as_bool),
+            Some(true)
+        );
+
+        let road = &shapefile.features[1];
+        assert!(
+            matches!(&road.geometry, Geom::MultiContour(contours) if contours.contours().count() == 2)
+        );
+        assert_eq!(road.attribute("AREA"), Some(&DbfValue::Null));
+        assert_eq!(road.attribute("OPEN"), Some(&DbfValue::Null));
+
+        let Geom::Polygon(park) = &shapefile.features[2].geometry else {
+            panic!("expected polygon");
+        };
+        assert_eq!(park.outer_contour().iter_points().count(), 4);
+        assert_eq!(park.inner_contours().count(), 1);
+    }
+
+    #[test]
+    fn separate_outer_rings_are_multi_polygon() {
+        let first: &[(f64, f64)] = &[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)];
+        let second: &[(f64, f64)] = &[(5.0, 5.0), (5.0, 6.0), (6.0, 6.0), (6.0, 5.0)];
+        let shapefile =
+            Shapefile::parse(&shp(&[poly_record(15, &[first, second])]), None, None).unwrap();
+        assert!(matches!(
+            &shapefile.features[0].geometry,
+            Geom::MultiPolygon(polygons) if polygons.parts().len() == 2
+        ));
+        assert!(shapefile.features[0].attributes.is_empty());
+    }
+
+    #[test]
+    fn invalid_files_are_errors() {
+        assert!(Shapefile::parse(b"not a shapefile", None, None).is_err());
+
+        let mut truncated = shp(&[point_record(1.0, 2.0)]);
+        truncated.truncate(truncated.len() - 4);
+        assert!(Shapefile::parse(&truncated, None, None).is_err());
+
+        let records_mismatch = dbf(&[("NAME", b'C', 4)], &[]);
+        assert!(Shapefile::parse(
+            &shp(&[point_record(1.0, 2.0)]),
+            Some(&records_mismatch),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn invalid_dbf_headers_are_errors() {
+        let shp = shp(&[point_record(1.0, 2.0)]);
+        let valid = dbf(&[("NAME", b'C', 4)], &[(false, vec!["well"])]);
+
+        let mut huge_count = valid.clone();
+        huge_count[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Shapefile::parse(&shp, Some(&huge_count), None).is_err());
+
+        let mut empty_records = valid;
+        empty_records[10..12].copy_from_slice(&0u16.to_le_bytes());
+        assert!(Shapefile::parse(&shp, Some(&empty_records), None).is_err());
+    }
+
+    #[test]
+    fn converts_to_geographic_coordinates() {
+        let shapefile = Shapefile::parse(&shp(&[point_record(30.0, 60.0)]), None, None)
+            .unwrap()
+            .into_geo();
+        let Geom::Point(point) = &shapefile.features[0].geometry else {
+            panic!("expected point");
+        };
+        assert_eq!((point.lat(), point.lon()), (60.0, 30.0));
+    }
+
+    #[test]
+    fn reads_crs_from_prj() {
+        let wgs84 = r#"GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]]"#;
+        assert_eq!(crs_from_prj(wgs84), Some(Crs::WGS84));
+
+        let web_mercator = r#"PROJCS["WGS_1984_Web_Mercator_Auxiliary_Sphere",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]],PROJECTION["Mercator_Auxiliary_Sphere"],PARAMETER["False_Easting",0.0],UNIT["Meter",1.0]]"#;
+        assert_eq!(crs_from_prj(web_mercator), Some(Crs::EPSG3857));
+
+        let utm = r#"PROJCS["WGS_1984_UTM_Zone_33N",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]],PROJECTION["Transverse_Mercator"],PARAMETER["False_Easting",500000.0],PARAMETER["False_Northing",0.0],PARAMETER["Central_Meridian",15.0],PARAMETER["Scale_Factor",0.9996],PARAMETER["Latitude_Of_Origin",0.0],UNIT["Meter",1.0]]"#;
+        let crs = crs_from_prj(utm).unwrap();
+        let projection = crs.get_projection::<GeoPoint2d, Point2d>().unwrap();
+        let projected = projection.project(&GeoPoint2d::latlon(0.0, 15.0)).unwrap();
+        assert!((projected.x() - 500_000.0).abs() < 1e-3);
+        assert!(projected.y().abs() < 1e-3);
+
+        let feet = utm.replace(
+            r#"UNIT["Meter",1.0]]"#,
+            r#"UNIT["Foot_US",0.3048006096012192]]"#,
+        );
+        assert_eq!(crs_from_prj(&feet), None);
+        assert_eq!(crs_from_prj("not wkt"), None);
+    }
+}
@@ -1,6 +1,8 @@
+use crate::layer::feature_layer::label_placer::{LabelBox, LabelCandidate, Placement};
+use crate::render::point_paint::PointShape;
 use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
-use crate::render::{Canvas, PackedBundle, PrimitiveId};
-use galileo_types::cartesian::Point3d;
+use crate::render::{Canvas, ImagePaint, PackedBundle, PrimitiveId, RenderOptions};
+use galileo_types::cartesian::{CartesianPoint3d, Point3d, Rect};
 use galileo_types::impls::{Contour, Polygon};
 use std::collections::{HashMap, HashSet};
 
@@ -13,11 +15,78 @@ pub(super) struct FeatureRenderStore {
     buffer_size_limit: usize,
     bundle_indices_to_pack: HashSet<usize>,
     next_index: usize,
+    /// Render indices of the features that are being tessellated in background.
+    pending_renders: HashSet<usize>,
+    /// Area, all the features in which were rendered by the streaming layer.
+    streamed_area: Option<Rect>,
+    labels_changed: bool,
+    placement_generation: Option<u64>,
+}
+
+/// Primitives of a feature to be tessellated in background.
+pub(super) type OwnedPrimitive =
+    RenderPrimitive<'static, f64, Point3d, Contour<Point3d>, Polygon<Point3d>>;
+
+/// Features of a render store tessellated into a separate bundle in background.
+pub(super) struct TessellatedBatch {
+    store_id: usize,
+    bundle: RenderBundle,
+    renders: Vec<TessellatedRender>,
+}
+
+struct TessellatedRender {
+    render_index: usize,
+    primitive_ids: Vec<PrimitiveId>,
+    labels: Vec<(Vec<usize>, LabelCandidate)>,
+}
+
+impl TessellatedBatch {
+    /// Tessellates the primitives of the features with the given render indices into the empty `bundle`.
+    pub fn tessellate(
+        store_id: usize,
+        min_resolution: f64,
+        mut bundle: RenderBundle,
+        features: Vec<(usize, Vec<OwnedPrimitive>)>,
+    ) -> Self {
+        let renders = features
+            .into_iter()
+            .map(|(render_index, primitives)| {
+                let labels = label_candidates(render_index, &primitives);
+                let primitive_ids = primitives
+                    .into_iter()
+                    .map(|primitive| bundle.add(primitive, min_resolution))
+                    .collect();
+                TessellatedRender {
+                    render_index,
+                    primitive_ids,
+                    labels,
+                }
+            })
+            .collect();
+
+        Self {
+            store_id,
+            bundle,
+            renders,
+        }
+    }
+
+    /// Id of the render store the batch belongs to.
+    pub fn store_id(&self) -> usize {
+        self.store_id
+    }
 }
 
 struct RenderMapEntry {
     bundle_index: usize,
     primitive_ids: Vec<PrimitiveId>,
+    labels: Vec<StoredLabel>,
+}
+
+struct StoredLabel {
+    primitive_ids: Vec<PrimitiveId>,
+    candidate: LabelCandidate,
+    is_hidden: bool,
 }
 
 impl FeatureRenderStore {
@@ -31,6 +100,10 @@ impl FeatureRenderStore {
             feature_render_map: HashMap::new(),
             bundle_indices_to_pack: HashSet::new(),
             next_index: 0,
+            pending_renders: HashSet::new(),
+            streamed_area: None,
+            labels_changed: false,
+            placement_generation: None,
         }
     }
 
@@ -46,6 +119,14 @@ impl FeatureRenderStore {
         self.buffer_size_limit = limit;
     }
 
+    pub fn streamed_area(&self) -> Option<Rect> {
+        self.streamed_area
+    }
+
+    pub fn set_streamed_area(&mut self, area: Option<Rect>) {
+        self.streamed_area = area;
+    }
+
     pub fn init_bundle(&mut self, f: impl Fn() -> RenderBundle) {
         if !self.has_not_full_bundles() {
             self.render_bundles.push(f());
@@ -60,11 +141,18 @@ impl FeatureRenderStore {
     }
 
     pub fn remove_render(&mut self, render_index: usize) {
+        if self.pending_renders.remove(&render_index) {
+            return;
+        }
+
         if let Some(RenderMapEntry {
             bundle_index,
             primitive_ids,
+            labels,
         }) = self.feature_render_map.remove(&render_index)
         {
+            self.labels_changed |= !labels.is_empty();
+
             for id in primitive_ids {
                 if let Err(err) = self.render_bundles[bundle_index].remove(id) {
                     log::warn!("Error while removing render primitive: {err:?}.")
@@ -84,21 +172,25 @@ impl FeatureRenderStore {
         primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>>,
     ) -> usize {
         let curr_bundle_index = self.curr_bundle_index();
-        let ids = primitives
+        let next_index = self.next_index;
+        self.next_index += 1;
+
+        let candidates = label_candidates(next_index, &primitives);
+        let ids: Vec<_> = primitives
             .into_iter()
             .map(|primitive| {
                 self.render_bundles[curr_bundle_index].add(primitive, self.min_resolution)
             })
             .collect();
-
-        let next_index = self.next_index;
-        self.next_index += 1;
+        let labels = stored_labels(&ids, candidates);
+        self.labels_changed |= !labels.is_empty();
 
         self.feature_render_map.insert(
             next_index,
             RenderMapEntry {
                 bundle_index: curr_bundle_index,
                 primitive_ids: ids,
+                labels,
             },
         );
 
@@ -107,6 +199,92 @@ impl FeatureRenderStore {
         next_index
     }
 
+    /// Reserves a render index for a feature that is tessellated in background. The feature is drawn after its
+    /// [batch](TessellatedBatch) is added with [`FeatureRenderStore::add_batch`], unless the render is removed before
+    /// that.
+    pub fn reserve_render(&mut self) -> usize {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.pending_renders.insert(index);
+
+        index
+    }
+
+    /// Returns true if the feature with the given render index is being tessellated in background.
+    pub fn is_pending(&self, render_index: usize) -> bool {
+        self.pending_renders.contains(&render_index)
+    }
+
+    /// Returns true if any features of the store are being tessellated in background.
+    pub fn has_pending(&self) -> bool {
+        !self.pending_renders.is_empty()
+    }
+
+    /// Adds the features tessellated in background. Features removed since their render indices were reserved are
+    /// removed from the bundle of the batch.
+    ///
+    /// The batch is merged into the last bundle of the store if it is not full, so the features are drawn in the
+    /// same order as if they were added with [`FeatureRenderStore::add_primitives`], and the number of bundles does
+    /// not grow with the number of batches.
+    pub fn add_batch(&mut self, batch: TessellatedBatch) {
+        let TessellatedBatch {
+            mut bundle,
+            renders,
+            ..
+        } = batch;
+        let mut entries = vec![];
+        for render in renders {
+            if self.pending_renders.remove(&render.render_index) {
+                entries.push(render);
+                continue;
+            }
+
+            for id in render.primitive_ids {
+                if let Err(err) = bundle.remove(id) {
+                    log::warn!("Error while removing render primitive: {err:?}.")
+                }
+            }
+        }
+
+        if entries.is_empty() {
+            return;
+        }
+
+        let bundle_index = match self.render_bundles.last_mut() {
+            Some(last) if last.approx_buffer_size() < self.buffer_size_limit => {
+                let new_ids = last.append(bundle);
+                for render in &mut entries {
+                    render.primitive_ids = render
+                        .primitive_ids
+                        .iter()
+                        .filter_map(|id| new_ids.get(id).copied())
+                        .collect();
+                }
+                self.render_bundles.len() - 1
+            }
+            _ => {
+                self.render_bundles.push(bundle);
+                self.packed_bundles.push(None);
+                self.render_bundles.len() - 1
+            }
+        };
+
+        for render in entries {
+            let labels = stored_labels(&render.primitive_ids, render.labels);
+            self.labels_changed |= !labels.is_empty();
+            self.feature_render_map.insert(
+                render.render_index,
+                RenderMapEntry {
+                    bundle_index,
+                    primitive_ids: render.primitive_ids,
+                    labels,
+                },
+            );
+        }
+
+        self.bundle_indices_to_pack.insert(bundle_index);
+    }
+
     fn curr_bundle_index(&self) -> usize {
         self.render_bundles.len() - 1
     }
@@ -116,33 +294,250 @@ impl FeatureRenderStore {
         render_index: usize,
         primitives: Vec<RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>>,
     ) {
-        let RenderMapEntry {
+        let Some(RenderMapEntry {
             bundle_index,
             primitive_ids,
-        } = &self.feature_render_map[&render_index];
+            labels,
+        }) = self.feature_render_map.get_mut(&render_index)
+        else {
+            log::error!(
+                "Tried to update render index {render_index} that was not present in the map."
+            );
+            return;
+        };
         if primitive_ids.len() != primitives.len() {
             log::error!("Cannot update feature style. The number of primitives is not equal to what it was.")
         }
 
-        for (id, primitive) in primitive_ids.iter().zip(primitives.into_iter()) {
+        let candidates = label_candidates(render_index, &primitives);
+        for (id, primitive) in primitive_ids.iter().zip(primitives) {
             if let Err(err) = self.render_bundles[*bundle_index].update(*id, primitive) {
                 log::warn!("Failed to update feature style: {err:?}");
             }
         }
 
+        self.labels_changed |= !labels.is_empty() || !candidates.is_empty();
+        *labels = stored_labels(primitive_ids, candidates);
+        self.placement_generation = None;
         self.bundle_indices_to_pack.insert(*bundle_index);
     }
 
+    /// Returns all labels of the store if they were changed since the last call.
+    pub fn take_changed_labels(&mut self) -> Option<Vec<LabelCandidate>> {
+        if !self.labels_changed {
+            return None;
+        }
+
+        self.labels_changed = false;
+        Some(self.labels())
+    }
+
+    pub fn labels(&self) -> Vec<LabelCandidate> {
+        self.feature_render_map
+            .values()
+            .flat_map(|entry| entry.labels.iter().map(|label| label.candidate.clone()))
+            .collect()
+    }
+
+    /// Shows and hides the labels according to the `placement`. The bundles must be packed after that.
+    pub fn apply_placement(&mut self, placement: &Placement) {
+        if self.placement_generation == Some(placement.generation) {
+            return;
+        }
+
+        self.placement_generation = Some(placement.generation);
+        for entry in self.feature_render_map.values_mut() {
+            for label in &mut entry.labels {
+                let is_hidden = placement.hidden.contains(&label.candidate.id);
+                if is_hidden == label.is_hidden {
+                    continue;
+                }
+
+                let opacity = if is_hidden { 0 } else { 255 };
+                for primitive_id in &label.primitive_ids {
+                    if let Err(err) = self.render_bundles[entry.bundle_index]
+                        .modify_image(*primitive_id, ImagePaint { opacity })
+                    {
+                        log::warn!("Failed to change label visibility: {err:?}");
+                    }
+                }
+
+                label.is_hidden = is_hidden;
+                self.bundle_indices_to_pack.insert(entry.bundle_index);
+            }
+        }
+    }
+
+    /// Packs the changed bundles. Bundles are not packed for transient canvases, they are packed on draw instead.
     pub fn pack(&mut self, canvas: &dyn Canvas) {
+        if canvas.is_transient() {
+            return;
+        }
+
         for index in self.bundle_indices_to_pack.drain() {
             self.packed_bundles[index] = Some(canvas.pack_bundle(&self.render_bundles[index]));
         }
     }
 
-    pub fn bundles(&self) -> Vec<&dyn PackedBundle> {
+    /// Draws all bundles of the store. Bundles that were changed while rendering to a transient canvas are packed
+    /// before drawing.
+    pub fn draw(&mut self, canvas: &mut dyn Canvas, options: RenderOptions) {
+        if canvas.is_transient() {
+            let packed: Vec<_> = self
+                .render_bundles
+                .iter()
+                .map(|bundle| canvas.pack_bundle(bundle))
+                .collect();
+            canvas.draw_bundles(&packed.iter().map(|v| &**v).collect::<Vec<_>>(), options);
+        } else {
+            self.pack(canvas);
+            canvas.draw_bundles(&self.bundles(), options);
+        }
+    }
+
+    fn bundles(&self) -> Vec<&dyn PackedBundle> {
         self.packed_bundles
             .iter()
             .filter_map(|v| v.as_ref().map(|bundle| &**bundle))
             .collect()
     }
 }
+
+/// Candidates for label placement for the label primitives of a feature, paired with indices of the primitives
+/// that make up every label.
+fn label_candidates(
+    render_index: usize,
+    primitives: &[RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>],
+) -> Vec<(Vec<usize>, LabelCandidate)> {
+    let mut candidates: Vec<(Vec<usize>, LabelCandidate)> = vec![];
+    for (primitive_index, primitive) in primitives.iter().enumerate() {
+        let RenderPrimitive::Point(point, paint) = primitive else {
+            continue;
+        };
+        let (Some(placement), PointShape::Image { width, height, .. }) =
+            (paint.placement, &paint.shape)
+        else {
+            continue;
+        };
+
+        let label_box = LabelBox {
+            position: Point3d::new(point.x(), point.y(), point.z()),
+            width: *width,
+            height: *height,
+            offset: paint.offset,
+        };
+        if placement.continues_previous {
+            if let Some((indices, candidate)) = candidates.last_mut() {
+                indices.push(primitive_index);
+                candidate.boxes.push(label_box);
+                continue;
+            }
+        }
+
+        let candidate = LabelCandidate {
+            id: (render_index, candidates.len()),
+            boxes: vec![label_box],
+            priority: placement.priority,
+            allow_overlap: placement.allow_overlap,
+        };
+        candidates.push((vec![primitive_index], candidate));
+    }
+
+    candidates
+}
+
+fn stored_labels(
+    primitive_ids: &[PrimitiveId],
+    candidates: Vec<(Vec<usize>, LabelCandidate)>,
+) -> Vec<StoredLabel> {
+    candidates
+        .into_iter()
+        .filter_map(|(primitive_indices, candidate)| {
+            Some(StoredLabel {
+                primitive_ids: primitive_indices
+                    .into_iter()
+                    .map(|index| primitive_ids.get(index).copied())
+                    .collect::<Option<_>>()?,
+                candidate,
+                is_hidden: false,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layer::feature_layer::{FeatureLayer, FeatureLayerOptions};
+    use crate::render::SvgRenderer;
+    use crate::symbol::ArbitraryGeometrySymbol;
+    use crate::{Map, MapView};
+    use galileo_types::cartesian::Size;
+    use galileo_types::geo::impls::GeoPoint2d;
+    use galileo_types::geo::Crs;
+    use galileo_types::geometry_type::GeoSpace2d;
+    use galileo_types::latlon;
+
+    type PointLayer = FeatureLayer<GeoPoint2d, GeoPoint2d, ArbitraryGeometrySymbol, GeoSpace2d>;
+
+    fn point_layer(map: &mut Map) -> &mut PointLayer {
+        map.layers_mut()[0]
+            .as_any_mut()
+            .downcast_mut::<PointLayer>()
+            .unwrap()
+    }
+
+    fn render_until_tessellated(map: &mut Map) {
+        for _ in 0..1000 {
+            SvgRenderer::new().render(map);
+            if !point_layer(map).is_tessellating() {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        panic!("features were not tessellated");
+    }
+
+    /// Number of bundles and of rendered features of the layer.
+    fn store_state(map: &mut Map) -> (usize, usize) {
+        let store = point_layer(map).lods[0].contents.lock().unwrap();
+        assert!(!store.has_pending());
+        (store.render_bundles.len(), store.feature_render_map.len())
+    }
+
+    #[test]
+    fn background_batches_are_merged_into_one_bundle() {
+        let points = (0..20).map(|i| latlon!(0.0, i as f64 * 0.001)).collect();
+        let layer = PointLayer::new(points, ArbitraryGeometrySymbol::default(), Crs::WGS84)
+            .with_options(FeatureLayerOptions {
+                background_tessellation: true,
+                ..Default::default()
+            });
+        let mut map = Map::new(
+            MapView::new(&latlon!(0.0, 0.0), 1000.0).with_size(Size::new(100.0, 100.0)),
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        );
+
+        render_until_tessellated(&mut map);
+        assert_eq!(store_state(&mut map), (1, 20));
+
+        for round in 0..5 {
+            let features = point_layer(&mut map).features_mut();
+            for _ in 0..5 {
+                features.remove(0);
+            }
+            for i in 0..4 {
+                features.insert(latlon!(1.0, (round * 4 + i) as f64 * 0.001));
+            }
+
+            // The last feature is removed while it is being tessellated.
+            SvgRenderer::new().render(&map);
+            let features = point_layer(&mut map).features_mut();
+            features.remove(features.len() - 1);
+
+            render_until_tessellated(&mut map);
+            assert_eq!(store_state(&mut map), (1, 18 - round * 2), "{round}");
+        }
+    }
+}
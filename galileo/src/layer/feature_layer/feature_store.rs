@@ -11,6 +11,46 @@ use std::sync::{Arc, Mutex};
 pub struct FeatureStore<F> {
     features: Vec<FeatureEntry<F>>,
     pending_updates: Arc<Mutex<Vec<FeatureUpdate>>>,
+    next_id: u64,
+}
+
+/// Id of a feature in a [FeatureStore].
+///
+/// Unlike the index of the feature, the id does not change when other features are removed from the store. Ids are
+/// given to the features in the order they are added, and an id of a removed feature is never reused by the same
+/// store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FeatureId(u64);
+
+impl FeatureId {
+    /// Creates an id from its numeric value, e.g. for layers that keep their own feature ids.
+    pub fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Numeric value of the id.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+/// Render state of a feature in a [FeatureLayer](super::FeatureLayer).
+///
+/// The state of a feature is given to the [`Symbol::render_with_state`](super::Symbol::render_with_state) of the
+/// layer, so the symbol can, for example, draw hovered and selected features with different colors. Changing the
+/// state between `Normal`, `Hovered` and `Selected` only updates the style of the rendered feature without
+/// tessellating its geometry again.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FeatureState {
+    /// The feature is drawn as usual.
+    #[default]
+    Normal,
+    /// The pointer is over the feature.
+    Hovered,
+    /// The feature is selected by the user.
+    Selected,
+    /// The feature is not drawn. Same as [hiding](FeatureContainerMut::hide) the feature.
+    Hidden,
 }
 
 /// Immutable container for a feature in a [FeatureLayer](super::FeatureLayer).
@@ -18,7 +58,10 @@ pub struct FeatureStore<F> {
 /// Reference to the container can be converted into a reference to the feature using [AsRef] trait.
 pub struct FeatureContainer<'a, F> {
     feature: &'a F,
+    feature_id: FeatureId,
     feature_index: usize,
+    is_hidden: bool,
+    state: FeatureState,
 }
 
 impl<'a, F> FeatureContainer<'a, F> {
@@ -26,6 +69,24 @@ impl<'a, F> FeatureContainer<'a, F> {
     pub fn index(&self) -> usize {
         self.feature_index
     }
+
+    /// Id of the feature in the layer. See [`FeatureId`].
+    pub fn id(&self) -> FeatureId {
+        self.feature_id
+    }
+
+    /// Returns true if the feature is hidden.
+    pub fn is_hidden(&self) -> bool {
+        self.is_hidden
+    }
+
+    /// Render state of the feature.
+    pub fn state(&self) -> FeatureState {
+        match self.is_hidden {
+            true => FeatureState::Hidden,
+            false => self.state,
+        }
+    }
 }
 
 impl<'a, F> AsRef<F> for FeatureContainer<'a, F> {
@@ -50,6 +111,11 @@ impl<'a, F> FeatureContainerMut<'a, F> {
         self.feature_index
     }
 
+    /// Id of the feature in the layer. See [`FeatureId`].
+    pub fn id(&self) -> FeatureId {
+        self.entry.id
+    }
+
     /// Returns true if the feature is hidden.
     ///
     /// Hidden features keep their place in the layer, but are not displayed on the map.
@@ -57,6 +123,35 @@ impl<'a, F> FeatureContainerMut<'a, F> {
         self.entry.is_hidden
     }
 
+    /// Render state of the feature.
+    pub fn state(&self) -> FeatureState {
+        self.entry.state()
+    }
+
+    /// Sets the render state of the feature. See [`FeatureState`].
+    ///
+    /// Setting [`FeatureState::Hidden`] hides the feature, and setting another state to a hidden feature shows it
+    /// again.
+    pub fn set_state(&mut self, state: FeatureState) {
+        if state == FeatureState::Hidden {
+            self.hide();
+            return;
+        }
+
+        let is_changed = self.entry.state != state;
+        self.entry.state = state;
+        if self.is_hidden() {
+            self.show();
+        } else if is_changed && !self.is_updated {
+            self.pending_updates
+                .lock()
+                .expect("poisoned mutex")
+                .push(FeatureUpdate::UpdateStyle {
+                    feature_index: self.feature_index,
+                });
+        }
+    }
+
     /// Notifies the layer that after the feature is modified, the geometry will not be changed and only the style
     /// is to be updated. If geometry might change, use [container.as_mut()](AsMut::as_mut) instead.
     pub fn edit_style(self) -> &'a mut F {
@@ -91,6 +186,7 @@ impl<'a, F> FeatureContainerMut<'a, F> {
             .expect("mutex is poisoned")
             .push(FeatureUpdate::Delete {
                 render_indices: to_store,
+                removed_index: None,
             });
 
         self.is_updated = true;
@@ -141,15 +237,26 @@ impl<'a, F> AsMut<F> for FeatureContainerMut<'a, F> {
 
 #[derive(Debug)]
 pub(super) enum FeatureUpdate {
-    Update { feature_index: usize },
-    UpdateStyle { feature_index: usize },
-    Delete { render_indices: Vec<Option<usize>> },
+    Update {
+        feature_index: usize,
+    },
+    UpdateStyle {
+        feature_index: usize,
+    },
+    Delete {
+        render_indices: Vec<Option<usize>>,
+        /// Index the feature had in the store if it was removed from the store, or `None` if it was hidden.
+        removed_index: Option<usize>,
+    },
 }
 
 impl<F> FeatureStore<F> {
     /// Creates a new store with the given feature set.
     pub fn new(features: impl Iterator<Item = F>) -> Self {
-        let features: Vec<_> = features.map(|f| FeatureEntry::new(f)).collect();
+        let features: Vec<_> = features
+            .enumerate()
+            .map(|(index, f)| FeatureEntry::new(FeatureId(index as u64), f))
+            .collect();
         let count = features.len();
         Self {
             features,
@@ -158,22 +265,46 @@ impl<F> FeatureStore<F> {
                     .map(|feature_index| FeatureUpdate::Update { feature_index })
                     .collect(),
             )),
+            next_id: count as u64,
         }
     }
 
-    /// Adds a new feature to the store.
-    pub fn insert(&mut self, feature: F) {
+    /// Adds a new feature to the store and returns its id.
+    pub fn insert(&mut self, feature: F) -> FeatureId {
         let feature_index = self.features.len();
-        self.features.push(FeatureEntry::new(feature));
+        let id = self.next_id();
+        self.features.push(FeatureEntry::new(id, feature));
         self.pending_updates
             .lock()
             .expect("poisoned mutex")
-            .push(FeatureUpdate::Update { feature_index })
+            .push(FeatureUpdate::Update { feature_index });
+        id
+    }
+
+    /// Adds a new hidden feature to the store at the end of the list and returns its id.
+    pub fn insert_hidden(&mut self, feature: F) -> FeatureId {
+        let id = self.next_id();
+        self.features.push(FeatureEntry::hidden(id, feature));
+        id
+    }
+
+    fn next_id(&mut self) -> FeatureId {
+        let id = FeatureId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Current index of the feature with the given `id`. Returns `None` if the feature was removed from the store.
+    pub fn index_of(&self, id: FeatureId) -> Option<usize> {
+        // Features are only added at the end of the list, so their ids are sorted.
+        self.features
+            .binary_search_by_key(&id, |entry| entry.id)
+            .ok()
     }
 
-    /// Adds a new hidden feature to the store at the end of the list.
-    pub fn insert_hidden(&mut self, feature: F) {
-        self.features.push(FeatureEntry::hidden(feature));
+    /// Id of the feature with the given `index`. Returns `None` if a feature with the given `index` does not exist.
+    pub fn id_of(&self, index: usize) -> Option<FeatureId> {
+        self.features.get(index).map(|entry| entry.id)
     }
 
     /// Returns a reference to the feature. Returns `None` if a feature with the given `index` does not exist.
@@ -181,8 +312,18 @@ impl<F> FeatureStore<F> {
         self.features.get(index).map(|f| &f.feature)
     }
 
+    pub(crate) fn get_container(&self, index: usize) -> Option<FeatureContainer<'_, F>> {
+        self.features.get(index).map(|f| FeatureContainer {
+            feature: &f.feature,
+            feature_id: f.id,
+            feature_index: index,
+            is_hidden: f.is_hidden,
+            state: f.state,
+        })
+    }
+
     /// Returns a mutable reference to the feature. Returns `None` if a feature with the given `index` does not exist.
-    pub fn get_mut(&mut self, index: usize) -> Option<FeatureContainerMut<F>> {
+    pub fn get_mut(&mut self, index: usize) -> Option<FeatureContainerMut<'_, F>> {
         self.features.get_mut(index).map(|f| FeatureContainerMut {
             entry: f,
             feature_index: index,
@@ -199,41 +340,80 @@ impl<F> FeatureStore<F> {
     pub fn remove(&mut self, index: usize) -> F {
         let FeatureEntry {
             feature,
-            is_hidden: _is_hidden,
             render_indices,
+            ..
         } = self.features.remove(index);
-        self.pending_updates
-            .lock()
-            .expect("mutex is poisoned")
-            .push(FeatureUpdate::Delete {
-                render_indices: render_indices.into_inner().expect("mutex is poisoned"),
-            });
+
+        let mut pending_updates = self.pending_updates.lock().expect("mutex is poisoned");
+        // Pending updates of the following features must point to their new positions in the store.
+        pending_updates.retain_mut(|update| match update {
+            FeatureUpdate::Update { feature_index }
+            | FeatureUpdate::UpdateStyle { feature_index } => {
+                if *feature_index == index {
+                    return false;
+                }
+                if *feature_index > index {
+                    *feature_index -= 1;
+                }
+                true
+            }
+            FeatureUpdate::Delete { .. } => true,
+        });
+        pending_updates.push(FeatureUpdate::Delete {
+            render_indices: render_indices.into_inner().expect("mutex is poisoned"),
+            removed_index: Some(index),
+        });
 
         feature
     }
 
+    /// Number of features in the store, including hidden ones.
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Returns true if the store contains no features.
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
     pub(super) fn get_entry(&self, index: usize) -> Option<&FeatureEntry<F>> {
         self.features.get(index)
     }
 
+    /// Marks all the visible features for re-rendering.
+    pub(super) fn update_all(&self) {
+        let mut updates = self.pending_updates.lock().expect("poisoned mutex");
+        updates.extend(
+            self.features
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| !entry.is_hidden)
+                .map(|(feature_index, _)| FeatureUpdate::Update { feature_index }),
+        );
+    }
+
     pub(super) fn drain_updates(&self) -> Vec<FeatureUpdate> {
         let mut updates = self.pending_updates.lock().expect("poisoned mutex");
         std::mem::take(&mut *updates)
     }
 
     /// Iterates over immutable containers of the features.
-    pub fn iter(&self) -> impl Iterator<Item = FeatureContainer<F>> {
+    pub fn iter(&self) -> impl Iterator<Item = FeatureContainer<'_, F>> {
         self.features
             .iter()
             .enumerate()
             .map(|(feature_index, f)| FeatureContainer {
                 feature: &f.feature,
+                feature_id: f.id,
                 feature_index,
+                is_hidden: f.is_hidden,
+                state: f.state,
             })
     }
 
     /// Iterates over mutable containers of the features.
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = FeatureContainerMut<F>> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = FeatureContainerMut<'_, F>> {
         self.features
             .iter_mut()
             .enumerate()
@@ -247,24 +427,31 @@ impl<F> FeatureStore<F> {
 }
 
 pub(super) struct FeatureEntry<F> {
+    id: FeatureId,
     feature: F,
     is_hidden: bool,
+    /// State of the feature when it is not hidden. Never [`FeatureState::Hidden`].
+    state: FeatureState,
     render_indices: Mutex<Vec<Option<usize>>>,
 }
 
 impl<F> FeatureEntry<F> {
-    fn new(feature: F) -> Self {
+    fn new(id: FeatureId, feature: F) -> Self {
         Self {
+            id,
             feature,
             is_hidden: false,
+            state: FeatureState::Normal,
             render_indices: Mutex::new(vec![]),
         }
     }
 
-    fn hidden(feature: F) -> Self {
+    fn hidden(id: FeatureId, feature: F) -> Self {
         Self {
+            id,
             feature,
             is_hidden: true,
+            state: FeatureState::Normal,
             render_indices: Mutex::new(vec![]),
         }
     }
@@ -273,6 +460,17 @@ impl<F> FeatureEntry<F> {
         &self.feature
     }
 
+    pub fn is_hidden(&self) -> bool {
+        self.is_hidden
+    }
+
+    pub fn state(&self) -> FeatureState {
+        match self.is_hidden {
+            true => FeatureState::Hidden,
+            false => self.state,
+        }
+    }
+
     pub fn render_index(&self, render_store_id: usize) -> Option<usize> {
         self.render_indices
             .lock()
@@ -291,6 +489,13 @@ impl<F> FeatureEntry<F> {
 
         render_indices[render_store_id] = Some(render_index)
     }
+
+    pub fn clear_render_index(&self, render_store_id: usize) {
+        let mut render_indices = self.render_indices.lock().expect("mutex is poisoned");
+        if let Some(entry) = render_indices.get_mut(render_store_id) {
+            *entry = None;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -322,4 +527,44 @@ mod tests {
 
         assert_eq!(store.get(0).expect("no feature"), &"F12".to_string());
     }
+    #[test]
+    fn removal_shifts_pending_updates() {
+        let mut store = FeatureStore::new(["F1", "F2", "F3"].into_iter());
+        store.remove(1);
+
+        let pending_updates = store.drain_updates();
+        assert_eq!(pending_updates.len(), 3);
+        assert_matches!(
+            pending_updates[0],
+            FeatureUpdate::Update { feature_index: 0 }
+        );
+        assert_matches!(
+            pending_updates[1],
+            FeatureUpdate::Update { feature_index: 1 }
+        );
+        assert_matches!(
+            pending_updates[2],
+            FeatureUpdate::Delete {
+                removed_index: Some(1),
+                ..
+            }
+        );
+        assert_eq!(store.get(1), Some(&"F3"));
+    }
+
+    #[test]
+    fn ids_do_not_change_on_removal() {
+        let mut store = FeatureStore::new(["F1", "F2", "F3"].into_iter());
+        let id = store.id_of(2).expect("no feature");
+        let removed = store.id_of(1).expect("no feature");
+
+        store.remove(1);
+        let inserted = store.insert("F4");
+
+        assert_eq!(store.index_of(id), Some(1));
+        assert_eq!(store.index_of(removed), None);
+        assert_ne!(inserted, removed);
+        assert_eq!(store.index_of(inserted), Some(2));
+        assert_eq!(store.get_container(1).map(|f| f.id()), Some(id));
+    }
 }
@@ -0,0 +1,456 @@
+use crate::error::GalileoError;
+use crate::layer::feature_layer::{AttributeValue, FeatureAttributes};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// Comparison of an attribute value in a [`FeatureFilter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ComparisonOperator {
+    /// `=`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+}
+
+impl ComparisonOperator {
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering == Ordering::Equal,
+            Self::Ne => ordering != Ordering::Equal,
+            Self::Lt => ordering == Ordering::Less,
+            Self::Le => ordering != Ordering::Greater,
+            Self::Gt => ordering == Ordering::Greater,
+            Self::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Condition on the [attributes](FeatureAttributes) of a feature, selecting the features a
+/// [`FeatureLayer`](super::FeatureLayer) shows. See [`FeatureLayer::with_filter`](super::FeatureLayer::with_filter).
+///
+/// Filters can be constructed from their parts or parsed from a string:
+///
+/// ```
+/// use galileo::layer::feature_layer::{AttributeValue, FeatureAttributes, FeatureFilter};
+///
+/// struct City {
+///     population: f64,
+///     capital: bool,
+/// }
+///
+/// impl FeatureAttributes for City {
+///     fn attribute(&self, name: &str) -> Option<AttributeValue> {
+///         match name {
+///             "population" => Some(self.population.into()),
+///             "capital" => Some(self.capital.into()),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let filter: FeatureFilter = "population > 10000 and not capital".parse().unwrap();
+/// assert_eq!(
+///     filter,
+///     FeatureFilter::gt("population", 10000.0).and(FeatureFilter::eq("capital", true).not())
+/// );
+/// assert!(filter.matches(&City { population: 2e5, capital: false }));
+/// assert!(!filter.matches(&City { population: 2e5, capital: true }));
+/// ```
+///
+/// The parsed syntax consists of comparisons `attribute <op> value` with operators `=` (or `==`), `!=`, `<`, `<=`,
+/// `>`, `>=`, combined with `and`, `or`, `not` and parentheses. Values are numbers, `true`, `false`, or strings in
+/// single or double quotes. An attribute name alone checks that the attribute is `true`.
+///
+/// A comparison is false if the feature does not have the attribute, or its value has a different type. Numbers and
+/// strings support all the operators, and booleans only `=` and `!=`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FeatureFilter {
+    /// Compares the value of the attribute with the given value.
+    Compare {
+        /// Name of the attribute.
+        attribute: String,
+        /// Comparison operator, with the attribute value on the left.
+        op: ComparisonOperator,
+        /// Value to compare the attribute with.
+        value: AttributeValue,
+    },
+    /// Feature has the attribute with the given name.
+    Has(String),
+    /// All the filters match the feature.
+    All(Vec<FeatureFilter>),
+    /// Any of the filters matches the feature.
+    Any(Vec<FeatureFilter>),
+    /// The filter does not match the feature.
+    Not(Box<FeatureFilter>),
+}
+
+impl FeatureFilter {
+    /// Creates a comparison of the attribute with the value.
+    pub fn compare(
+        attribute: impl Into<String>,
+        op: ComparisonOperator,
+        value: impl Into<AttributeValue>,
+    ) -> Self {
+        Self::Compare {
+            attribute: attribute.into(),
+            op,
+            value: value.into(),
+        }
+    }
+
+    /// Attribute is equal to the value.
+    pub fn eq(attribute: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        Self::compare(attribute, ComparisonOperator::Eq, value)
+    }
+
+    /// Attribute is not equal to the value.
+    pub fn ne(attribute: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        Self::compare(attribute, ComparisonOperator::Ne, value)
+    }
+
+    /// Attribute is less than the value.
+    pub fn lt(attribute: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        Self::compare(attribute, ComparisonOperator::Lt, value)
+    }
+
+    /// Attribute is less than or equal to the value.
+    pub fn le(attribute: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        Self::compare(attribute, ComparisonOperator::Le, value)
+    }
+
+    /// Attribute is greater than the value.
+    pub fn gt(attribute: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        Self::compare(attribute, ComparisonOperator::Gt, value)
+    }
+
+    /// Attribute is greater than or equal to the value.
+    pub fn ge(attribute: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        Self::compare(attribute, ComparisonOperator::Ge, value)
+    }
+
+    /// Both this and the `other` filter match.
+    pub fn and(self, other: FeatureFilter) -> Self {
+        match self {
+            Self::All(mut filters) => {
+                filters.push(other);
+                Self::All(filters)
+            }
+            filter => Self::All(vec![filter, other]),
+        }
+    }
+
+    /// This or the `other` filter matches.
+    pub fn or(self, other: FeatureFilter) -> Self {
+        match self {
+            Self::Any(mut filters) => {
+                filters.push(other);
+                Self::Any(filters)
+            }
+            filter => Self::Any(vec![filter, other]),
+        }
+    }
+
+    /// Negation of this filter.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+
+    /// Returns true if the feature satisfies the filter.
+    pub fn matches(&self, feature: &(impl FeatureAttributes + ?Sized)) -> bool {
+        match self {
+            Self::Compare {
+                attribute,
+                op,
+                value,
+            } => {
+                let Some(attribute) = feature.attribute(attribute) else {
+                    return false;
+                };
+                let ordering = match (&attribute, value) {
+                    (AttributeValue::Number(a), AttributeValue::Number(b)) => a.partial_cmp(b),
+                    (AttributeValue::String(a), AttributeValue::String(b)) => Some(a.cmp(b)),
+                    (AttributeValue::Bool(a), AttributeValue::Bool(b))
+                        if matches!(op, ComparisonOperator::Eq | ComparisonOperator::Ne) =>
+                    {
+                        Some(a.cmp(b))
+                    }
+                    _ => None,
+                };
+                ordering.is_some_and(|ordering| op.accepts(ordering))
+            }
+            Self::Has(attribute) => feature.attribute(attribute).is_some(),
+            Self::All(filters) => filters.iter().all(|filter| filter.matches(feature)),
+            Self::Any(filters) => filters.iter().any(|filter| filter.matches(feature)),
+            Self::Not(filter) => !filter.matches(feature),
+        }
+    }
+}
+
+impl FromStr for FeatureFilter {
+    type Err = GalileoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let filter = parser.or()?;
+        match parser.next() {
+            None => Ok(filter),
+            Some(token) => Err(filter_error(format!("unexpected {token:?}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Value(AttributeValue),
+    Op(ComparisonOperator),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, GalileoError> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let token = match c {
+            '(' | ')' => {
+                chars.next();
+                if c == '(' {
+                    Token::Open
+                } else {
+                    Token::Close
+                }
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => value.push(next),
+                        None => return Err(filter_error("unterminated string")),
+                    }
+                }
+                Token::Value(AttributeValue::String(value))
+            }
+            '=' | '!' | '<' | '>' | '&' | '|' => {
+                chars.next();
+                let followed_by = |chars: &mut std::iter::Peekable<std::str::Chars>, next| {
+                    chars.next_if_eq(&next).is_some()
+                };
+                match c {
+                    '=' => {
+                        followed_by(&mut chars, '=');
+                        Token::Op(ComparisonOperator::Eq)
+                    }
+                    '!' if followed_by(&mut chars, '=') => Token::Op(ComparisonOperator::Ne),
+                    '!' => Token::Not,
+                    '<' if followed_by(&mut chars, '=') => Token::Op(ComparisonOperator::Le),
+                    '<' => Token::Op(ComparisonOperator::Lt),
+                    '>' if followed_by(&mut chars, '=') => Token::Op(ComparisonOperator::Ge),
+                    '>' => Token::Op(ComparisonOperator::Gt),
+                    '&' if followed_by(&mut chars, '&') => Token::And,
+                    '|' if followed_by(&mut chars, '|') => Token::Or,
+                    _ => return Err(filter_error(format!("unexpected '{c}'"))),
+                }
+            }
+            _ if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut number = String::new();
+                while let Some(next) = chars
+                    .next_if(|next| next.is_ascii_alphanumeric() || matches!(next, '.' | '-' | '+'))
+                {
+                    number.push(next);
+                }
+                let value = number
+                    .parse()
+                    .map_err(|_| filter_error(format!("invalid number '{number}'")))?;
+                Token::Value(AttributeValue::Number(value))
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut word = String::new();
+                while let Some(next) =
+                    chars.next_if(|next| next.is_alphanumeric() || matches!(next, '_' | '.' | ':'))
+                {
+                    word.push(next);
+                }
+                match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "true" => Token::Value(AttributeValue::Bool(true)),
+                    "false" => Token::Value(AttributeValue::Bool(false)),
+                    _ => Token::Name(word),
+                }
+            }
+            _ => return Err(filter_error(format!("unexpected '{c}'"))),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser of filters. `or` has lower precedence than `and`, which has lower precedence than `not`.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn next_if(&mut self, expected: &Token) -> bool {
+        if self.tokens.get(self.position) == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<FeatureFilter, GalileoError> {
+        let mut filter = self.and()?;
+        while self.next_if(&Token::Or) {
+            filter = filter.or(self.and()?);
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<FeatureFilter, GalileoError> {
+        let mut filter = self.unary()?;
+        while self.next_if(&Token::And) {
+            filter = filter.and(self.unary()?);
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<FeatureFilter, GalileoError> {
+        match self.next().cloned() {
+            Some(Token::Not) => Ok(self.unary()?.not()),
+            Some(Token::Open) => {
+                let filter = self.or()?;
+                if !self.next_if(&Token::Close) {
+                    return Err(filter_error("missing ')'"));
+                }
+                Ok(filter)
+            }
+            Some(Token::Name(attribute)) => {
+                let Some(Token::Op(op)) = self.tokens.get(self.position).cloned() else {
+                    return Ok(FeatureFilter::eq(attribute, true));
+                };
+                self.position += 1;
+                match self.next() {
+                    Some(Token::Value(value)) => {
+                        Ok(FeatureFilter::compare(attribute, op, value.clone()))
+                    }
+                    _ => Err(filter_error(format!("missing value for '{attribute}'"))),
+                }
+            }
+            Some(token) => Err(filter_error(format!("unexpected {token:?}"))),
+            None => Err(filter_error("unexpected end of the filter")),
+        }
+    }
+}
+
+fn filter_error(message: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid feature filter: {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct Attributes(HashMap<&'static str, AttributeValue>);
+
+    impl FeatureAttributes for Attributes {
+        fn attribute(&self, name: &str) -> Option<AttributeValue> {
+            self.0.get(name).cloned()
+        }
+    }
+
+    fn feature(attributes: &[(&'static str, AttributeValue)]) -> Attributes {
+        Attributes(attributes.iter().cloned().collect())
+    }
+
+    #[test]
+    fn comparisons() {
+        let city = feature(&[
+            ("population", 15000.0.into()),
+            ("name", "Springfield".into()),
+            ("capital", false.into()),
+        ]);
+
+        assert!(FeatureFilter::gt("population", 10000.0).matches(&city));
+        assert!(FeatureFilter::le("population", 15000.0).matches(&city));
+        assert!(!FeatureFilter::lt("population", 15000.0).matches(&city));
+        assert!(FeatureFilter::eq("name", "Springfield").matches(&city));
+        assert!(FeatureFilter::lt("name", "Tokyo").matches(&city));
+        assert!(FeatureFilter::ne("capital", true).matches(&city));
+        assert!(!FeatureFilter::lt("capital", true).matches(&city));
+
+        // Missing attributes and values of other types do not match.
+        assert!(!FeatureFilter::ne("area", 1.0).matches(&city));
+        assert!(!FeatureFilter::ne("name", 1.0).matches(&city));
+        assert!(FeatureFilter::Has("name".into()).matches(&city));
+        assert!(FeatureFilter::ne("area", 1.0).not().matches(&city));
+    }
+
+    #[test]
+    fn parsing() {
+        let parse = |s: &str| s.parse::<FeatureFilter>();
+
+        assert_eq!(
+            parse("population > 10000").unwrap(),
+            FeatureFilter::gt("population", 10000.0)
+        );
+        assert_eq!(
+            parse("a = 1 or b != 'x y' and not (c <= -2.5e3 || d)").unwrap(),
+            FeatureFilter::eq("a", 1.0).or(FeatureFilter::ne("b", "x y").and(
+                FeatureFilter::le("c", -2500.0)
+                    .or(FeatureFilter::eq("d", true))
+                    .not()
+            ))
+        );
+        assert_eq!(
+            parse("kind == \"park\" && visible = false").unwrap(),
+            FeatureFilter::eq("kind", "park").and(FeatureFilter::eq("visible", false))
+        );
+
+        assert!(parse("").is_err());
+        assert!(parse("a >").is_err());
+        assert!(parse("(a > 1").is_err());
+        assert!(parse("a > 1 b").is_err());
+        assert!(parse("name = 'open").is_err());
+        assert!(parse("a > 1..2").is_err());
+    }
+}
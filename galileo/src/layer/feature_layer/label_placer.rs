@@ -0,0 +1,291 @@
+use crate::view::MapView;
+use galileo_types::cartesian::Point3d;
+use nalgebra::{Matrix4, Vector2};
+use rstar::primitives::Rectangle;
+use rstar::{Envelope, RTree, AABB};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Identifier of a label inside a layer: render index of the feature and index of the label among the labels of the
+/// feature.
+pub(crate) type LabelId = (usize, usize);
+
+/// Label to be placed on the screen.
+#[derive(Debug, Clone)]
+pub(crate) struct LabelCandidate {
+    pub id: LabelId,
+    /// Screen boxes of the label. Most labels have one box, but the labels placed along lines have a box per glyph.
+    pub boxes: Vec<LabelBox>,
+    pub priority: f32,
+    pub allow_overlap: bool,
+}
+
+/// Part of a label occupying a rectangle on the screen.
+#[derive(Debug, Clone)]
+pub(crate) struct LabelBox {
+    pub position: Point3d,
+    pub width: f32,
+    pub height: f32,
+    /// Anchor offset as a portion of the box size, starting from the top left corner.
+    pub offset: Vector2<f32>,
+}
+
+/// Result of label placement for one layer.
+#[derive(Debug, Clone)]
+pub(crate) struct Placement {
+    /// Incremented every time placement is recalculated.
+    pub generation: u64,
+    /// Labels of the layer that collide with labels of higher priority.
+    pub hidden: Arc<HashSet<LabelId>>,
+}
+
+/// Places text labels on the screen, hiding the labels that overlap labels of higher priority.
+///
+/// Every [`FeatureLayer`](super::FeatureLayer) has its own placer by default, so its labels are decluttered
+/// independently of other layers. To prevent labels of several layers from overlapping each other, give the layers
+/// the same placer with [`FeatureLayer::with_label_placer`](super::FeatureLayer::with_label_placer). Since layers
+/// are drawn one by one, a change in the labels of one layer is reflected in the layers drawn before it only on the
+/// next frame.
+///
+/// Placement is recalculated only when the map view or the set of labels changes. Labels with higher
+/// [priority](crate::render::text::TextStyle::priority) are placed first. Labels that
+/// [allow overlap](crate::render::text::TextStyle::allow_overlap) are always shown, but still hide the labels of lower
+/// priority under them.
+#[derive(Debug, Clone, Default)]
+pub struct LabelPlacer {
+    state: Arc<Mutex<PlacerState>>,
+}
+
+#[derive(Debug, Default)]
+struct PlacerState {
+    next_layer_id: usize,
+    layers: HashMap<usize, Vec<LabelCandidate>>,
+    view: Option<(Matrix4<f64>, [f64; 2])>,
+    is_dirty: bool,
+    generation: u64,
+    hidden: HashMap<usize, Arc<HashSet<LabelId>>>,
+}
+
+impl LabelPlacer {
+    /// Creates a new placer without any labels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn register(&self) -> usize {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let id = state.next_layer_id;
+        state.next_layer_id += 1;
+        id
+    }
+
+    pub(crate) fn unregister(&self, layer_id: usize) {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        if state.layers.remove(&layer_id).is_some() {
+            state.is_dirty = true;
+        }
+        state.hidden.remove(&layer_id);
+    }
+
+    /// Replaces all labels of the layer.
+    pub(crate) fn set_labels(&self, layer_id: usize, labels: Vec<LabelCandidate>) {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        state.layers.insert(layer_id, labels);
+        state.is_dirty = true;
+    }
+
+    /// Returns placement of the labels of the layer for the `view`, recalculating it if necessary.
+    pub(crate) fn place(&self, layer_id: usize, view: &MapView) -> Option<Placement> {
+        let mut state = self.state.lock().expect("mutex is poisoned");
+        let transform = view.map_to_scene_transform()?;
+        let view_key = (transform, [view.size().width(), view.size().height()]);
+        if state.is_dirty || state.view != Some(view_key) {
+            state.view = Some(view_key);
+            state.is_dirty = false;
+            state.generation += 1;
+            state.hidden = place_labels(&state.layers, &transform, view_key.1);
+        }
+
+        Some(Placement {
+            generation: state.generation,
+            hidden: state.hidden.get(&layer_id).cloned().unwrap_or_default(),
+        })
+    }
+}
+
+fn place_labels(
+    layers: &HashMap<usize, Vec<LabelCandidate>>,
+    transform: &Matrix4<f64>,
+    [width, height]: [f64; 2],
+) -> HashMap<usize, Arc<HashSet<LabelId>>> {
+    let mut candidates: Vec<(usize, &LabelCandidate)> = layers
+        .iter()
+        .flat_map(|(layer_id, labels)| labels.iter().map(|label| (*layer_id, label)))
+        .collect();
+    candidates.sort_by(|(layer_a, a), (layer_b, b)| {
+        b.priority
+            .total_cmp(&a.priority)
+            .then(layer_a.cmp(layer_b))
+            .then(a.id.cmp(&b.id))
+    });
+
+    let screen = AABB::from_corners([0.0, 0.0], [width, height]);
+    let mut placed: RTree<Rectangle<[f64; 2]>> = RTree::new();
+    let mut hidden: HashMap<usize, HashSet<LabelId>> = HashMap::new();
+    for (layer_id, label) in candidates {
+        let boxes: Vec<_> = label
+            .boxes
+            .iter()
+            .filter_map(|label_box| screen_box(label_box, transform, width, height))
+            .collect();
+
+        if !boxes.iter().any(|bbox| bbox.intersects(&screen)) {
+            continue;
+        }
+
+        let collides = boxes.iter().any(|bbox| {
+            placed
+                .locate_in_envelope_intersecting(bbox)
+                .next()
+                .is_some()
+        });
+        if collides && !label.allow_overlap {
+            hidden.entry(layer_id).or_default().insert(label.id);
+        } else {
+            for bbox in boxes {
+                placed.insert(Rectangle::from_aabb(bbox));
+            }
+        }
+    }
+
+    hidden
+        .into_iter()
+        .map(|(layer_id, labels)| (layer_id, Arc::new(labels)))
+        .collect()
+}
+
+/// Bounding box of the part of a label in screen pixels with Y axis going from top to bottom.
+fn screen_box(
+    label: &LabelBox,
+    transform: &Matrix4<f64>,
+    width: f64,
+    height: f64,
+) -> Option<AABB<[f64; 2]>> {
+    let scene = transform * label.position.to_homogeneous();
+    if scene.w <= 0.0 {
+        return None;
+    }
+
+    let x = (scene.x / scene.w + 1.0) / 2.0 * width;
+    let y = (1.0 - scene.y / scene.w) / 2.0 * height;
+    let left = x - (label.offset.x * label.width) as f64;
+    let top = y - (label.offset.y * label.height) as f64;
+
+    Some(AABB::from_corners(
+        [left, top],
+        [left + label.width as f64, top + label.height as f64],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::MapView;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    fn view() -> MapView {
+        MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(200.0, 200.0))
+    }
+
+    fn label_box(x: f64, width: f32) -> LabelBox {
+        LabelBox {
+            position: Point3d::new(x, 0.0, 0.0),
+            width,
+            height: 10.0,
+            offset: Vector2::new(0.5, 0.5),
+        }
+    }
+
+    fn label(id: usize, x: f64, priority: f32) -> LabelCandidate {
+        LabelCandidate {
+            id: (id, 0),
+            boxes: vec![label_box(x, 40.0)],
+            priority,
+            allow_overlap: false,
+        }
+    }
+
+    fn hidden(placer: &LabelPlacer, layer_id: usize) -> Vec<LabelId> {
+        let mut hidden: Vec<_> = placer
+            .place(layer_id, &view())
+            .unwrap()
+            .hidden
+            .iter()
+            .copied()
+            .collect();
+        hidden.sort();
+        hidden
+    }
+
+    #[test]
+    fn hides_overlapping_labels_with_lower_priority() {
+        let placer = LabelPlacer::new();
+        let layer = placer.register();
+        placer.set_labels(
+            layer,
+            vec![label(0, 0.0, 0.0), label(1, 20.0, 1.0), label(2, 70.0, 0.0)],
+        );
+
+        assert_eq!(hidden(&placer, layer), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn allow_overlap_keeps_label_visible() {
+        let placer = LabelPlacer::new();
+        let layer = placer.register();
+        let mut overlapping = label(0, 0.0, 0.0);
+        overlapping.allow_overlap = true;
+        placer.set_labels(layer, vec![overlapping, label(1, 20.0, 1.0)]);
+
+        assert!(hidden(&placer, layer).is_empty());
+    }
+
+    #[test]
+    fn label_parts_are_placed_together() {
+        let placer = LabelPlacer::new();
+        let layer = placer.register();
+        let mut line_label = label(0, 0.0, 0.0);
+        line_label.boxes = (0..5).map(|i| label_box(i as f64 * 10.0, 10.0)).collect();
+        placer.set_labels(layer, vec![line_label, label(1, 45.0, 1.0)]);
+
+        assert_eq!(hidden(&placer, layer), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn declutters_labels_across_layers() {
+        let placer = LabelPlacer::new();
+        let first = placer.register();
+        let second = placer.register();
+        placer.set_labels(first, vec![label(0, 0.0, 0.0)]);
+        placer.set_labels(second, vec![label(0, 10.0, 5.0)]);
+
+        assert_eq!(hidden(&placer, first), vec![(0, 0)]);
+        assert!(hidden(&placer, second).is_empty());
+
+        placer.unregister(second);
+        assert!(hidden(&placer, first).is_empty());
+    }
+
+    #[test]
+    fn recalculates_only_on_changes() {
+        let placer = LabelPlacer::new();
+        let layer = placer.register();
+        placer.set_labels(layer, vec![label(0, 0.0, 0.0)]);
+
+        let generation = placer.place(layer, &view()).unwrap().generation;
+        assert_eq!(placer.place(layer, &view()).unwrap().generation, generation);
+
+        let moved = view().translate_pixels(10.0, 0.0);
+        assert_ne!(placer.place(layer, &moved).unwrap().generation, generation);
+    }
+}
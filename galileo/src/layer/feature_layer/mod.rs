@@ -1,33 +1,67 @@
 //! [`FeatureLayer`] stores features in a [`FeatureStore`] and renders them with a [`Symbol`].
 
+use crate::layer::vector_tile_layer::VectorTileEncoder;
 use crate::layer::Layer;
 use crate::messenger::Messenger;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
 use crate::render::{Canvas, RenderOptions};
-use crate::view::MapView;
-use feature_render_store::FeatureRenderStore;
+use crate::tile_scheme::TileIndex;
+use crate::view::{self, MapView};
+use cluster::{cluster_center, cluster_points, Clustering};
+use feature_render_store::{FeatureRenderStore, OwnedPrimitive, TessellatedBatch};
+use galileo_mvt::{MvtLayer, MvtValue};
 use galileo_types::cartesian::{
     CartesianPoint2d, NewCartesianPoint2d, NewCartesianPoint3d, Point2d, Point3d, Rect,
+    SimplificationAlgorithm,
 };
 use galileo_types::geo::impls::projection::{AddDimensionProjection, IdentityProjection};
 use galileo_types::geo::impls::GeoPoint2d;
-use galileo_types::geo::{ChainProjection, Crs, InvertedProjection, NewGeoPoint, Projection};
+use galileo_types::geo::{ChainProjection, Crs, GeoExtent, NewGeoPoint, Projection};
 use galileo_types::geometry::{CartesianGeometry2d, Geom, Geometry};
 use galileo_types::geometry_type::{CartesianSpace2d, CartesianSpace3d, GeoSpace2d};
 use maybe_sync::{MaybeSend, MaybeSync};
 use num_traits::AsPrimitive;
+use spatial_index::SpatialIndex;
 use std::any::Any;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::ops::Deref;
-use std::sync::{Mutex, RwLock};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use streaming::StreamIndex;
+use web_time::SystemTime;
 
+mod antimeridian;
+mod cluster;
 mod feature;
 mod feature_render_store;
 mod feature_store;
+mod filter;
+mod label_placer;
+mod picking;
+mod simplification;
+mod spatial_index;
+mod streaming;
 pub mod symbol;
+#[cfg(not(target_arch = "wasm32"))]
+mod tessellation;
 
-pub use feature::Feature;
+pub(crate) use feature::cast_geom;
+#[cfg(all(feature = "kml", not(target_arch = "wasm32")))]
+pub use feature::parse_kmz;
+#[cfg(feature = "geojson")]
+pub use feature::{features_to_geojson, features_to_geojson_reprojected, GeoJsonProperties};
+#[cfg(feature = "gpx")]
+pub use feature::{parse_gpx, GpxFeature, GpxFeatureKind};
+#[cfg(feature = "kml")]
+pub use feature::{parse_kml, KmlDocument, KmlFeature, KmlStyle};
+pub use feature::{AttributeValue, Feature, FeatureAttributes};
+#[cfg(feature = "shapefile")]
+pub use feature::{DbfValue, Shapefile, ShapefileFeature};
 pub use feature_store::*;
-pub use symbol::Symbol;
+pub use filter::{ComparisonOperator, FeatureFilter};
+pub use label_placer::LabelPlacer;
+pub use symbol::{ClusterSymbol, Symbol};
 
 /// Feature layers render a set of [features](Feature) using [symbols](Symbol).
 ///
@@ -42,6 +76,91 @@ pub use symbol::Symbol;
 ///
 /// Feature layer can render features differently at different resolutions. See [`FeatureLayer::with_lods`] for
 /// details.
+///
+/// # Spatial index
+///
+/// Spatial queries of the layer (like [`FeatureLayer::features_in_extent`] and [`FeatureLayer::query_extent`]) can use an R-tree index of the
+/// features' bounding rectangles instead of checking every feature. The index is built for a specific CRS either
+/// explicitly with [`FeatureLayer::build_index`], or automatically on the first query if
+/// [`FeatureLayerOptions::use_spatial_index`] is set.
+///
+/// The index is dropped every time mutable access to the features is requested (e.g. with
+/// [`FeatureLayer::features_mut`]), as the layer cannot know whether geometries of the features were changed. After
+/// that the queries fall back to checking every feature until the index is built again (which happens automatically
+/// with `use_spatial_index` option).
+///
+/// Features added, modified or removed with [`FeatureLayer::add_feature`], [`FeatureLayer::update_feature`] and
+/// [`FeatureLayer::remove_feature`] keep the index up to date instead of dropping it. These methods, as well as
+/// [`FeatureStore`] editing, mark only the affected features for re-rendering, so the rest of the layer is not
+/// re-tessellated.
+///
+/// # Labels
+///
+/// Text labels drawn by the layer (e.g. with [`symbol::LabelSymbol`]) are placed so that they do not overlap each
+/// other: labels colliding on the screen with labels of higher priority are hidden. The placement is recalculated
+/// every time the map view changes. To declutter labels of several layers together, set the same [`LabelPlacer`] to
+/// all of them with [`FeatureLayer::with_label_placer`].
+///
+/// # Picking
+///
+/// Features under a point on the screen (e.g. under the mouse cursor) can be found with
+/// [`FeatureLayer::query_features`]. The check uses the primitives that the symbol of the layer draws at the current
+/// resolution: points use the size of their shapes or images (taking the image anchor into account), lines use their
+/// width, and polygons are checked with their holes. So a point feature drawn as an icon can be picked by clicking
+/// anywhere on the icon.
+///
+/// The found features are returned in the reverse order of drawing, so the topmost feature goes first. Hidden
+/// features are skipped. When the layer is drawn with clustering, the features are checked as if they were drawn
+/// without it.
+///
+/// [`Layer::feature_at`] returns the [`FeatureId`] of the topmost feature, which, unlike its index, stays the same
+/// when other features are removed from the layer. It stops at the first found feature, and if the spatial index is
+/// enabled, only checks the features with bounding rectangles near the point.
+///
+/// # Clustering
+///
+/// Layers with a large number of point features can group the points that are close to each other on the screen into
+/// clusters. See [`FeatureLayer::with_clustering`] for details.
+///
+/// # Time
+///
+/// If the map view has a [time](MapView::time), the layer shows only the features with
+/// [time ranges](Feature::time_range) containing that time, and does not return the others from
+/// [`FeatureLayer::query_features`]. Every time the map time changes, all the features with time ranges are
+/// re-rendered.
+///
+/// # Filtering
+///
+/// A layer with a [`FeatureFilter`] shows only the features with [attributes](FeatureAttributes) matching the filter,
+/// and does not return the others from [`FeatureLayer::query_features`]. The filter can be changed with
+/// [`FeatureLayer::set_filter`] without rebuilding the layer.
+///
+/// # Feature states
+///
+/// Every feature has a [`FeatureState`], that is given to the symbol of the layer with
+/// [`Symbol::render_with_state`]. Interactive applications can set the hovered and selected features with
+/// [`FeatureLayer::set_state`], and draw them differently with, for example, a
+/// [`FeatureStateSymbol`](crate::symbol::FeatureStateSymbol). Only the style of the feature is updated when its
+/// state changes, so it is cheap enough to be done on every pointer move.
+///
+/// # Background tessellation
+///
+/// Converting the primitives of the features into the vertices to draw (tessellation) can take seconds for layers with
+/// hundreds of thousands of polygons. With [`FeatureLayerOptions::background_tessellation`] the layer tessellates the
+/// features in a pool of worker threads shared by all the layers, and draws them as soon as they are ready, so the
+/// application keeps responding while the layer is loaded.
+///
+/// # Streaming
+///
+/// Layers with millions of features do not need to tessellate all of them to show a part of the map. With
+/// [`FeatureLayerOptions::streaming`] the layer renders only the features around the view, and only for the level of
+/// detail used by the view. The features are found with an R-tree of their bounding rectangles in the CRS of the map,
+/// which is built on the first render and then kept up to date with the changes of the features. The rendered
+/// features are kept, so panning back and forth does not tessellate them again.
+///
+/// Combined with [levels of detail](FeatureLayer::with_lods) and
+/// [simplification](FeatureLayerOptions::simplification), this lets the layer draw a generalized version of the whole
+/// dataset when the map is zoomed out, and the detailed geometries only of the features in view when zoomed in.
 pub struct FeatureLayer<P, F, S, Space>
 where
     F: Feature,
@@ -51,12 +170,54 @@ where
     symbol: S,
     crs: Crs,
     lods: Vec<Lod>,
-    messenger: RwLock<Option<Box<dyn Messenger>>>,
+    messenger: Arc<RwLock<Option<Box<dyn Messenger>>>>,
     options: FeatureLayerOptions,
+    spatial_index: RwLock<Option<SpatialIndex>>,
+    label_placer: LabelPlacer,
+    label_layer_id: usize,
+    labels_lod: Mutex<Option<usize>>,
+    clustering: Option<Clustering<F>>,
+    rendered_time: Mutex<Option<SystemTime>>,
+    world_width: Mutex<Option<f64>>,
+    filter: Option<LayerFilter<F>>,
+    tessellated: TessellatedBatches,
+    stream_index: Mutex<Option<StreamIndex>>,
 
     space: PhantomData<Space>,
 }
 
+/// Channel the features tessellated in background are sent back to the layer with.
+struct TessellatedBatches {
+    sender: Sender<TessellatedBatch>,
+    receiver: Mutex<Receiver<TessellatedBatch>>,
+}
+
+impl TessellatedBatches {
+    fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+/// Filter of a layer with the function evaluating it, so that features are filtered without requiring
+/// [`FeatureAttributes`] from all the layers.
+struct LayerFilter<F> {
+    filter: FeatureFilter,
+    matches: fn(&FeatureFilter, &F) -> bool,
+}
+
+impl<F: FeatureAttributes> LayerFilter<F> {
+    fn new(filter: FeatureFilter) -> Self {
+        Self {
+            filter,
+            matches: |filter, feature| filter.matches(feature),
+        }
+    }
+}
+
 /// Configuration of a [FeatureLayer].
 #[derive(Debug, Copy, Clone)]
 pub struct FeatureLayerOptions {
@@ -81,6 +242,39 @@ pub struct FeatureLayerOptions {
     /// If set to true, the layer will be rendered with anti-aliasing. It makes rendered lines look smoother but is a
     /// little less performant.
     pub use_antialiasing: bool,
+
+    /// If set to true, a spatial index of the features is built on the first spatial query to the layer and is used
+    /// for the subsequent queries. See [`FeatureLayer`] documentation for details.
+    pub use_spatial_index: bool,
+
+    /// If set, contours and polygons are simplified with the given algorithm before they are rendered. This reduces
+    /// the number of vertices to draw for detailed geometries at low resolutions.
+    ///
+    /// Geometries are simplified separately for each level of detail of the layer (see [`FeatureLayer::with_lods`]),
+    /// removing the details smaller than [`simplification_tolerance`](FeatureLayerOptions::simplification_tolerance)
+    /// pixels at the minimum resolution of the level.
+    pub simplification: Option<SimplificationAlgorithm>,
+
+    /// Tolerance of the geometry simplification in pixels. See
+    /// [`simplification`](FeatureLayerOptions::simplification).
+    pub simplification_tolerance: f64,
+
+    /// If set to true, features are tessellated in background threads instead of the render thread. A feature is not
+    /// drawn until it is tessellated, see [`FeatureLayer::is_tessellating`].
+    ///
+    /// Features are tessellated for every level of detail of the layer, starting with the one used by the current
+    /// view. The results are kept and reused in the following frames, so only the added and changed features are
+    /// tessellated again. Style changes (e.g. of the [feature state](FeatureLayer::set_state)) of the tessellated
+    /// features are applied immediately.
+    ///
+    /// This option is ignored on the web platform and for layers with clustering.
+    pub background_tessellation: bool,
+
+    /// If set to true, only the features around the view are rendered, for the level of detail used by the view.
+    /// See [`FeatureLayer`] documentation for details.
+    ///
+    /// This option is ignored for layers with clustering.
+    pub streaming: bool,
 }
 
 impl Default for FeatureLayerOptions {
@@ -89,6 +283,11 @@ impl Default for FeatureLayerOptions {
             sort_by_depth: false,
             buffer_size_limit: 10_000_000,
             use_antialiasing: true,
+            use_spatial_index: false,
+            simplification: None,
+            simplification_tolerance: 1.0,
+            background_tessellation: false,
+            streaming: false,
         }
     }
 }
@@ -120,13 +319,25 @@ where
     /// Creates a new layer with the given parameters.
     pub fn new(features: Vec<F>, style: S, crs: Crs) -> Self {
         let options = FeatureLayerOptions::default();
+        let label_placer = LabelPlacer::new();
+        let label_layer_id = label_placer.register();
         Self {
             features: FeatureStore::new(features.into_iter()),
             symbol: style,
             crs,
-            messenger: RwLock::new(None),
+            messenger: Arc::new(RwLock::new(None)),
             lods: vec![Lod::new(0, 1.0, options.buffer_size_limit)],
             options,
+            spatial_index: RwLock::new(None),
+            label_placer,
+            label_layer_id,
+            labels_lod: Mutex::new(None),
+            clustering: None,
+            rendered_time: Mutex::new(None),
+            world_width: Mutex::new(None),
+            filter: None,
+            tessellated: TessellatedBatches::new(),
+            stream_index: Mutex::new(None),
             space: Default::default(),
         }
     }
@@ -142,14 +353,26 @@ where
             .map(|(id, &min_resolution)| Lod::new(id, min_resolution, options.buffer_size_limit))
             .collect();
         lods.sort_by(|a, b| b.min_resolution.total_cmp(&a.min_resolution));
+        let label_placer = LabelPlacer::new();
+        let label_layer_id = label_placer.register();
 
         Self {
             features: FeatureStore::new(features.into_iter()),
             symbol: style,
             crs,
-            messenger: RwLock::new(None),
+            messenger: Arc::new(RwLock::new(None)),
             lods,
             options,
+            spatial_index: RwLock::new(None),
+            label_placer,
+            label_layer_id,
+            labels_lod: Mutex::new(None),
+            clustering: None,
+            rendered_time: Mutex::new(None),
+            world_width: Mutex::new(None),
+            filter: None,
+            tessellated: TessellatedBatches::new(),
+            stream_index: Mutex::new(None),
             space: Default::default(),
         }
     }
@@ -166,13 +389,53 @@ where
         self
     }
 
+    /// Sets the placer of the text labels of the layer. Layers with the same placer do not draw overlapping labels.
+    pub fn with_label_placer(mut self, label_placer: LabelPlacer) -> Self {
+        self.label_placer.unregister(self.label_layer_id);
+        self.label_layer_id = label_placer.register();
+        self.label_placer = label_placer;
+        *self.labels_lod.get_mut().expect("mutex is poisoned") = None;
+
+        self
+    }
+
+    /// Enables clustering of the point features of the layer.
+    ///
+    /// Points closer than `radius` pixels to each other are grouped into clusters, which are drawn with the given
+    /// cluster `symbol` at the center of the group. Points that do not have close neighbours, as well as features
+    /// with other geometry types, are drawn with the symbol of the layer as usual. The clusters are recalculated every
+    /// time the map resolution changes or the features of the layer are edited.
+    ///
+    /// Levels of detail of the layer are not used when clustering is enabled.
+    pub fn with_clustering(mut self, radius: f64, symbol: impl ClusterSymbol<F> + 'static) -> Self {
+        self.clustering = Some(Clustering::new(radius, Box::new(symbol)));
+        self
+    }
+
+    /// Sets the filter selecting the features that the layer shows. See [`FeatureLayer`] documentation for details.
+    pub fn with_filter(mut self, filter: FeatureFilter) -> Self
+    where
+        F: FeatureAttributes,
+    {
+        self.filter = Some(LayerFilter::new(filter));
+        self
+    }
+
+    /// Returns the placer of the text labels of the layer.
+    pub fn label_placer(&self) -> &LabelPlacer {
+        &self.label_placer
+    }
+
     /// Returns a reference to the feature store.
     pub fn features(&self) -> &FeatureStore<F> {
         &self.features
     }
 
     /// Returns a mutable reference to the feature store.
+    ///
+    /// This drops the spatial index of the layer, if it was built.
     pub fn features_mut(&mut self) -> &mut FeatureStore<F> {
+        *self.spatial_index.get_mut().expect("lock is poisoned") = None;
         &mut self.features
     }
 
@@ -180,6 +443,73 @@ where
     pub fn crs(&self) -> &Crs {
         &self.crs
     }
+
+    /// Returns true if some features of the layer are being tessellated in background and are not drawn yet. See
+    /// [`FeatureLayerOptions::background_tessellation`].
+    pub fn is_tessellating(&self) -> bool {
+        self.lods.iter().any(|lod| {
+            lod.contents
+                .lock()
+                .expect("mutex is poisoned")
+                .has_pending()
+        })
+    }
+
+    /// Returns the filter of the layer.
+    pub fn filter(&self) -> Option<&FeatureFilter> {
+        self.filter.as_ref().map(|filter| &filter.filter)
+    }
+
+    /// Replaces the filter of the layer, or removes it if `None` is given. All the features of the layer are
+    /// re-rendered on the next redraw.
+    pub fn set_filter(&mut self, filter: Option<FeatureFilter>)
+    where
+        F: FeatureAttributes,
+    {
+        self.filter = filter.map(LayerFilter::new);
+        self.features.update_all();
+        if let Some(messenger) = &*self.messenger.read().expect("lock is poisoned") {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Render state of the feature with the given index. Returns `None` if the feature does not exist.
+    pub fn state(&self, index: usize) -> Option<FeatureState> {
+        Some(self.features.get_entry(index)?.state())
+    }
+
+    /// Sets the render state of the feature with the given index and requests redraw. Returns `false` if the feature
+    /// does not exist.
+    ///
+    /// Changing the state between [`FeatureState::Normal`], [`FeatureState::Hovered`] and
+    /// [`FeatureState::Selected`] only updates the style of the rendered feature, which is much cheaper than updating
+    /// the feature itself. See [`Symbol::render_with_state`].
+    pub fn set_state(&mut self, index: usize, state: FeatureState) -> bool {
+        let Some(mut feature) = self.features.get_mut(index) else {
+            return false;
+        };
+
+        feature.set_state(state);
+        if let Some(messenger) = &*self.messenger.read().expect("lock is poisoned") {
+            messenger.request_redraw();
+        }
+
+        true
+    }
+
+    /// Returns true if the feature should be shown when the map has the given time.
+    fn is_shown(&self, feature: &F, time: Option<SystemTime>) -> bool {
+        let matches_time = match (time, feature.time_range()) {
+            (Some(time), Some(range)) => range.contains(&time),
+            _ => true,
+        };
+
+        matches_time
+            && self
+                .filter
+                .as_ref()
+                .is_none_or(|filter| (filter.matches)(&filter.filter, feature))
+    }
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, GeoSpace2d>
@@ -193,13 +523,319 @@ where
     /// If the layer doesn't contain any features, or if at least one of them cannot be projected into the given
     /// CRS, `None` will be returned.
     pub fn extent_projected(&self, crs: &Crs) -> Option<Rect> {
-        let projection = crs.get_projection::<P, Point2d>()?;
+        let projection = crs.projection_from_geo::<P, Point2d>()?;
         self.features
             .iter()
-            .filter_map(|f| f.as_ref().geometry().project(&*projection))
+            .filter_map(|f| f.as_ref().geometry().project(&projection))
             .filter_map(|g| g.bounding_rectangle())
             .collect()
     }
+
+    /// Converts all features of the layer (including hidden ones) into a GeoJSON feature collection. See
+    /// [`features_to_geojson`] for details.
+    #[cfg(feature = "geojson")]
+    pub fn to_geojson(&self) -> Option<geojson::FeatureCollection>
+    where
+        F: GeoJsonProperties,
+    {
+        features_to_geojson((0..).map_while(|index| self.features.get(index)))
+    }
+
+    /// Cuts the features of the layer into a vector tile layer with the given `name`, e.g. to serve or cache the
+    /// features as vector tiles. See [`VectorTileEncoder`] for details.
+    ///
+    /// Features are projected into the CRS of the tile schema of the encoder. Hidden features are skipped. Indices
+    /// of features in the layer are used as the ids of tile features, and the properties of tile features are
+    /// returned by the `properties` function.
+    ///
+    /// Returns `None` if the tile schema does not have the level of detail of the tile, or its CRS does not
+    /// support projecting geographic coordinates.
+    pub fn encode_tile_layer(
+        &self,
+        encoder: &VectorTileEncoder,
+        index: TileIndex,
+        name: &str,
+        properties: impl Fn(&F) -> HashMap<String, MvtValue>,
+    ) -> Option<MvtLayer> {
+        let crs = &encoder.tile_schema().crs;
+        let projection = crs.projection_from_geo::<P, Point2d>()?;
+        let bbox = encoder.tile_bbox(index)?;
+        let features = self
+            .features_in_extent(&bbox, crs)
+            .filter(|container| !container.is_hidden())
+            .filter_map(|container| {
+                let feature = container.as_ref();
+                let geometry = feature.geometry().project(&projection)?;
+                encoder.encode_feature(
+                    index,
+                    Some(container.index() as u64),
+                    &geometry,
+                    properties(feature),
+                )
+            })
+            .collect();
+
+        Some(encoder.layer(name, features))
+    }
+
+    /// Geographic extent of the layer that takes the antimeridian into account.
+    ///
+    /// Unlike [`FeatureLayer::extent_projected`], for features lying on both sides of the ±180° meridian the
+    /// returned extent crosses the meridian instead of spanning the whole globe the long way around. Use
+    /// [`MapView::fit_geo_extent`] to show this extent on the map.
+    ///
+    /// If the layer doesn't contain any features, `None` will be returned.
+    pub fn extent_geo_wrapped(&self) -> Option<GeoExtent> {
+        let projection = IdentityProjection::<P, GeoPoint2d, GeoSpace2d>::new();
+        let geometries: Vec<_> = self
+            .features
+            .iter()
+            .filter_map(|f| f.as_ref().geometry().project(&projection))
+            .collect();
+
+        GeoExtent::from_geometries(&geometries)
+    }
+
+    /// Adds the feature to the end of the layer and returns its index.
+    ///
+    /// Only the new feature is rendered on the next redraw. If the spatial index of the layer is built, the feature
+    /// is added to it.
+    pub fn add_feature(&mut self, feature: F) -> usize {
+        let feature_index = self.features.len();
+        if let Some(index) = self.spatial_index.get_mut().expect("lock is poisoned") {
+            if let Some(extent) = projected_extent(&feature, index.crs()) {
+                index.insert(feature_index, &extent);
+            }
+        }
+
+        self.features.insert(feature);
+        feature_index
+    }
+
+    /// Modifies the feature with the given index with the `update` function. Returns `false` if there is no such
+    /// feature.
+    ///
+    /// Only this feature is re-rendered on the next redraw, and its entry in the spatial index (if built) is updated.
+    /// If only the style of the feature changes, use [`FeatureContainerMut::edit_style`] instead, which is cheaper.
+    pub fn update_feature(&mut self, index: usize, update: impl FnOnce(&mut F)) -> bool {
+        let Some(mut container) = self.features.get_mut(index) else {
+            return false;
+        };
+
+        let spatial_index = self.spatial_index.get_mut().expect("lock is poisoned");
+        let old_extent = spatial_index
+            .as_ref()
+            .and_then(|spatial_index| projected_extent(container.as_ref(), spatial_index.crs()));
+
+        update(container.as_mut());
+
+        if let Some(spatial_index) = spatial_index {
+            if let Some(extent) = old_extent {
+                spatial_index.remove(index, &extent);
+            }
+            if let Some(extent) = projected_extent(container.as_ref(), spatial_index.crs()) {
+                spatial_index.insert(index, &extent);
+            }
+        }
+
+        true
+    }
+
+    /// Removes the feature with the given index from the layer and returns it. Returns `None` if there is no such
+    /// feature.
+    ///
+    /// Indices of the following features are decreased by one, while their [ids](FeatureId) stay the same. Only the
+    /// render of the removed feature is dropped on the next redraw.
+    pub fn remove_feature(&mut self, index: usize) -> Option<F> {
+        if index >= self.features.len() {
+            return None;
+        }
+
+        let feature = self.features.remove(index);
+        if let Some(spatial_index) = self.spatial_index.get_mut().expect("lock is poisoned") {
+            if let Some(extent) = projected_extent(&feature, spatial_index.crs()) {
+                spatial_index.remove(index, &extent);
+            }
+            spatial_index.shift_indices_after(index);
+        }
+
+        Some(feature)
+    }
+
+    /// Returns an iterator of features, bounding rectangles of which intersect the given `extent`. The `extent` is
+    /// expected to be set in the given `crs`, and the features are projected into that `crs` before checking.
+    ///
+    /// Features that cannot be projected into the `crs` are skipped. If the `crs` does not support projecting
+    /// geographic coordinates, no features are returned.
+    ///
+    /// If the spatial index is available for the `crs`, it is used for the query. Otherwise, every feature of the
+    /// layer is checked. See [`FeatureLayer`] documentation for details.
+    pub fn features_in_extent<'a>(
+        &'a self,
+        extent: &Rect,
+        crs: &Crs,
+    ) -> impl Iterator<Item = FeatureContainer<'a, F>> + 'a {
+        self.query_extent(extent, crs)
+            .into_iter()
+            .filter_map(|index| self.features.get_container(index))
+    }
+
+    /// Returns indices of the features in the [`FeatureStore`] of the layer, bounding rectangles of which intersect
+    /// the given `bbox`. The indices are sorted in ascending order. This is the same query as
+    /// [`FeatureLayer::features_in_extent`], but the result does not borrow the layer.
+    pub fn query_extent(&self, bbox: &Rect, crs: &Crs) -> Vec<usize> {
+        match self.with_index(crs, |index| index.locate_in_extent(bbox)) {
+            Some(indices) => indices,
+            None => self
+                .projected_extents(crs)
+                .filter(|(_, extent)| extent.intersects(bbox))
+                .map(|(index, _)| index)
+                .collect(),
+        }
+    }
+
+    /// Returns the feature, bounding rectangle of which is the closest to the `point`. The `point` is expected to be
+    /// set in the given `crs`, and the features are projected into that `crs` before checking.
+    ///
+    /// If the `point` is inside bounding rectangles of several features, any of them can be returned.
+    ///
+    /// If the spatial index is available for the `crs`, it is used for the query. Otherwise, every feature of the
+    /// layer is checked. See [`FeatureLayer`] documentation for details.
+    pub fn nearest_feature(
+        &self,
+        point: &impl CartesianPoint2d<Num = f64>,
+        crs: &Crs,
+    ) -> Option<FeatureContainer<'_, F>> {
+        let index = match self.with_index(crs, |index| index.nearest(point)) {
+            Some(index) => index,
+            None => self
+                .projected_extents(crs)
+                .map(|(index, bbox)| (index, distance_to_rect_sq(&bbox, point)))
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(index, _)| index),
+        }?;
+
+        self.features.get_container(index)
+    }
+
+    /// Builds the spatial index of the layer for the given `crs`, replacing the existing one.
+    ///
+    /// If the `crs` does not support projecting geographic coordinates, the index is not built.
+    pub fn build_index(&self, crs: &Crs) {
+        let index = crs
+            .projection_from_geo::<P, Point2d>()
+            .map(|_| SpatialIndex::new(crs.clone(), self.projected_extents(crs)));
+        *self.spatial_index.write().expect("lock is poisoned") = index;
+    }
+
+    /// Indices of the features that can be drawn at the `screen_point` of the `view`, found with the spatial index.
+    /// The indices are sorted in ascending order.
+    ///
+    /// Returns `None` if the spatial index is not available for the CRS of the view, or the area around the point is
+    /// not on the map surface, so every feature of the layer must be checked instead.
+    fn pick_candidates(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> Option<Vec<usize>> {
+        let margin = tolerance + PICK_MARGIN;
+        let area = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .into_iter()
+            .map(|(dx, dy)| {
+                let corner =
+                    Point2d::new(screen_point.x + dx * margin, screen_point.y + dy * margin);
+                let position = view.screen_to_map(corner)?;
+                Some(Rect::new(position.x, position.y, position.x, position.y))
+            })
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .reduce(|area, corner| area.merge(corner))?;
+
+        // Features crossing the antimeridian are drawn unwrapped outside of the main copy of the world, so the areas
+        // in the neighbouring copies are checked too.
+        let shifts = match view.world_width() {
+            Some(width) => vec![-width, 0.0, width],
+            None => vec![0.0],
+        };
+
+        self.with_index(view.crs(), |index| {
+            let mut indices: Vec<usize> = shifts
+                .into_iter()
+                .flat_map(|shift| {
+                    index.locate_in_extent(&Rect::new(
+                        area.x_min() + shift,
+                        area.y_min(),
+                        area.x_max() + shift,
+                        area.y_max(),
+                    ))
+                })
+                .collect();
+            indices.sort_unstable();
+            indices.dedup();
+            indices
+        })
+    }
+
+    fn with_index<T>(&self, crs: &Crs, f: impl FnOnce(&SpatialIndex) -> T) -> Option<T> {
+        let is_built = self
+            .spatial_index
+            .read()
+            .expect("lock is poisoned")
+            .as_ref()
+            .is_some_and(|index| index.crs() == crs);
+
+        if !is_built {
+            if !self.options.use_spatial_index {
+                return None;
+            }
+
+            self.build_index(crs);
+        }
+
+        self.spatial_index
+            .read()
+            .expect("lock is poisoned")
+            .as_ref()
+            .filter(|index| index.crs() == crs)
+            .map(f)
+    }
+
+    fn projected_extents<'a>(&'a self, crs: &Crs) -> impl Iterator<Item = (usize, Rect)> + 'a {
+        let projection = crs.projection_from_geo::<P, Point2d>();
+        self.features.iter().filter_map(move |f| {
+            let bbox = f
+                .as_ref()
+                .geometry()
+                .project(projection.as_ref()?)?
+                .bounding_rectangle()?;
+            Some((f.index(), bbox))
+        })
+    }
+}
+
+/// Bounding rectangle of the feature projected into the `crs`.
+fn projected_extent<P, F>(feature: &F, crs: &Crs) -> Option<Rect>
+where
+    P: NewGeoPoint + 'static,
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    let projection = crs.projection_from_geo::<P, Point2d>()?;
+    feature
+        .geometry()
+        .project(&projection)?
+        .bounding_rectangle()
+}
+
+fn distance_to_rect_sq(rect: &Rect, point: &impl CartesianPoint2d<Num = f64>) -> f64 {
+    let dx = (rect.x_min() - point.x())
+        .max(point.x() - rect.x_max())
+        .max(0.0);
+    let dy = (rect.y_min() - point.y())
+        .max(point.y() - rect.y_max())
+        .max(0.0);
+    dx * dx + dy * dy
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, CartesianSpace2d>
@@ -233,6 +869,8 @@ where
     /// At this moment this method just iterates over all features checking for each one if it is at the point. But
     /// in future it may be changed into using geo-index to make this more efficient. So this method should be preferred
     /// to manually checking every feature.
+    ///
+    /// This drops the spatial index of the layer, if it was built.
     pub fn get_features_at_mut<'a>(
         &'a mut self,
         point: &'a impl CartesianPoint2d<Num = P::Num>,
@@ -241,12 +879,35 @@ where
     where
         F::Geom: CartesianGeometry2d<P>,
     {
+        *self.spatial_index.get_mut().expect("lock is poisoned") = None;
         self.features
             .iter_mut()
             .filter(move |f| f.as_ref().geometry().is_point_inside(point, tolerance))
     }
+
+    /// Converts all features of the layer (including hidden ones) into a GeoJSON feature collection, reprojecting
+    /// them from the CRS of the layer into WGS84. See [`features_to_geojson_reprojected`] for details.
+    #[cfg(feature = "geojson")]
+    pub fn to_geojson(&self) -> Option<geojson::FeatureCollection>
+    where
+        P: NewCartesianPoint2d + 'static,
+        F: GeoJsonProperties,
+    {
+        features_to_geojson_reprojected(
+            (0..).map_while(|index| self.features.get(index)),
+            &self.crs,
+        )
+    }
 }
 
+/// Maximum number of features tessellated in background together.
+const TESSELLATION_BATCH_SIZE: usize = 1000;
+
+/// Distance in pixels, by which symbols are expected to draw features outside of the bounding rectangles of their
+/// geometries, e.g. with point markers or wide lines. When the features under the pointer are searched with the
+/// spatial index, features further than that from the pointer are not checked.
+const PICK_MARGIN: f64 = 128.0;
+
 impl<P, F, S, Space> FeatureLayer<P, F, S, Space>
 where
     F: Feature,
@@ -271,32 +932,189 @@ where
         canvas: &mut dyn Canvas,
         projection: impl Deref<Target = Proj>,
     ) {
-        let updates = self.features.drain_updates();
+        *self.world_width.lock().expect("mutex is poisoned") = view::world_width(view.crs());
+        let mut updates = self.features.drain_updates();
+        self.update_time(view.time(), &mut updates);
+        if let Some(clustering) = &self.clustering {
+            self.render_clusters(view, canvas, &*projection, clustering, !updates.is_empty());
+            return;
+        }
+
+        if self.options.streaming {
+            self.update_stream_index(view.crs(), &*projection, &updates);
+        }
         if !updates.is_empty() {
-            self.update_feature_renders(canvas, projection, &updates);
+            self.update_feature_renders(canvas, &*projection, &updates, view.resolution());
         }
+        if self.options.streaming {
+            self.stream_features(view, canvas, &*projection);
+        }
+        self.add_tessellated_batches();
 
-        let lod = self
+        let mut lod = self
             .select_lod(view.resolution())
             .lock()
             .expect("mutex is poisoned");
+        self.place_labels(view, canvas, &mut lod);
 
-        canvas.draw_bundles(
-            &lod.bundles(),
+        lod.draw(
+            canvas,
             RenderOptions {
                 antialias: self.options.use_antialiasing,
+                ..Default::default()
             },
         );
     }
 
+    fn render_clusters<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        view: &MapView,
+        canvas: &mut dyn Canvas,
+        projection: &Proj,
+        clustering: &Clustering<F>,
+        is_updated: bool,
+    ) {
+        let mut state = clustering.state.lock().expect("mutex is poisoned");
+        let time = view.time();
+        if is_updated || state.crs.as_ref() != Some(view.crs()) {
+            state.invalidate();
+            state.crs = Some(view.crs().clone());
+            state.geometries = (0..)
+                .map_while(|index| Some((index, self.features.get_entry(index)?)))
+                .filter(|(_, entry)| !entry.is_hidden() && self.is_shown(entry.feature(), time))
+                .filter_map(|(index, entry)| {
+                    Some((index, entry.feature().geometry().project(projection)?))
+                })
+                .collect();
+        }
+
+        let resolution = view.resolution();
+        let options = RenderOptions {
+            antialias: self.options.use_antialiasing,
+            ..Default::default()
+        };
+        if canvas.is_transient() {
+            let bundle = self.render_cluster_bundle(
+                canvas.create_bundle(),
+                &state.geometries,
+                clustering,
+                resolution,
+            );
+            let packed = canvas.pack_bundle(&bundle);
+            canvas.draw_bundles(&[&*packed], options);
+            return;
+        }
+
+        if state.resolution != Some(resolution) {
+            let bundle = self.render_cluster_bundle(
+                canvas.create_bundle(),
+                &state.geometries,
+                clustering,
+                resolution,
+            );
+            state.bundle = Some(canvas.pack_bundle(&bundle));
+            state.resolution = Some(resolution);
+        }
+
+        if let Some(bundle) = &state.bundle {
+            canvas.draw_bundles(&[&**bundle], options);
+        }
+    }
+
+    fn render_cluster_bundle(
+        &self,
+        mut bundle: RenderBundle,
+        geometries: &[(usize, Geom<Point3d>)],
+        clustering: &Clustering<F>,
+        resolution: f64,
+    ) -> RenderBundle {
+        let mut points = vec![];
+        let mut point_geometries = vec![];
+        for (feature_index, geometry) in geometries {
+            match geometry {
+                Geom::Point(point) => {
+                    points.push(*point);
+                    point_geometries.push((*feature_index, geometry));
+                }
+                _ => {
+                    let Some(entry) = self.features.get_entry(*feature_index) else {
+                        continue;
+                    };
+                    for primitive in self.symbol.render_with_state(
+                        entry.feature(),
+                        geometry,
+                        resolution,
+                        entry.state(),
+                    ) {
+                        bundle.add(primitive, resolution);
+                    }
+                }
+            }
+        }
+
+        for cluster in cluster_points(&points, clustering.radius * resolution) {
+            if let [index] = cluster[..] {
+                let (feature_index, geometry) = point_geometries[index];
+                let Some(entry) = self.features.get_entry(feature_index) else {
+                    continue;
+                };
+                for primitive in self.symbol.render_with_state(
+                    entry.feature(),
+                    geometry,
+                    resolution,
+                    entry.state(),
+                ) {
+                    bundle.add(primitive, resolution);
+                }
+            } else {
+                let center = cluster_center(&points, &cluster);
+                let features: Vec<&F> = cluster
+                    .iter()
+                    .filter_map(|&index| self.features.get(point_geometries[index].0))
+                    .collect();
+                for primitive in clustering.symbol.render(&features, &center, resolution) {
+                    bundle.add(primitive, resolution);
+                }
+            }
+        }
+
+        bundle
+    }
+
+    fn place_labels(&self, view: &MapView, canvas: &dyn Canvas, lod: &mut FeatureRenderStore) {
+        let mut labels_lod = self.labels_lod.lock().expect("mutex is poisoned");
+        let changed = lod.take_changed_labels();
+        let labels = match changed {
+            Some(labels) => Some(labels),
+            None if labels_lod.is_some_and(|id| id != lod.id()) => Some(lod.labels()),
+            None => None,
+        };
+
+        if let Some(labels) = labels {
+            self.label_placer.set_labels(self.label_layer_id, labels);
+            *labels_lod = Some(lod.id());
+        }
+
+        if labels_lod.is_none() {
+            // the layer has never had any labels
+            return;
+        }
+
+        if let Some(placement) = self.label_placer.place(self.label_layer_id, view) {
+            lod.apply_placement(&placement);
+            lod.pack(canvas);
+        }
+    }
+
     fn update_feature_renders<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
         canvas: &dyn Canvas,
         projection: impl Deref<Target = Proj>,
         updates: &[FeatureUpdate],
+        resolution: f64,
     ) {
         for update in updates {
-            if let FeatureUpdate::Delete { render_indices } = update {
+            if let FeatureUpdate::Delete { render_indices, .. } = update {
                 for (render_index, lod_index) in render_indices
                     .iter()
                     .enumerate()
@@ -311,11 +1129,32 @@ where
             }
         }
 
-        for lod in &self.lods {
-            let mut lod = lod.contents.lock().expect("mutex is poisoned");
+        let is_background = self.is_background_tessellation();
+        let is_streaming = self.options.streaming;
+        // Features of the level of detail used by the view are tessellated first.
+        let current = self.select_lod(resolution);
+        let lods = std::iter::once(current).chain(
+            self.lods
+                .iter()
+                .map(|lod| &lod.contents)
+                .filter(|contents| !std::ptr::eq(*contents, current)),
+        );
+        for lod in lods {
+            let mut lod = lod.lock().expect("mutex is poisoned");
+            let mut background = vec![];
+            if is_streaming
+                && updates
+                    .iter()
+                    .any(|update| matches!(update, FeatureUpdate::Update { .. }))
+            {
+                // Updated features are rendered again when they get into the streamed area.
+                lod.set_streamed_area(None);
+            }
 
             for update in updates {
-                lod.init_bundle(|| canvas.create_bundle());
+                if !is_background {
+                    lod.init_bundle(|| canvas.create_bundle());
+                }
 
                 match update {
                     FeatureUpdate::Update { feature_index } => {
@@ -326,9 +1165,23 @@ where
 
                         if let Some(render_index) = feature_entry.render_index(lod.id()) {
                             lod.remove_render(render_index);
+                            feature_entry.clear_render_index(lod.id());
+                        }
+
+                        if is_streaming {
+                            continue;
                         }
 
-                        self.render_feature(feature_entry, &*projection, &mut lod);
+                        if is_background {
+                            self.render_feature_in_background(
+                                feature_entry,
+                                &*projection,
+                                &mut lod,
+                                &mut background,
+                            );
+                        } else {
+                            self.render_feature(feature_entry, &*projection, &mut lod);
+                        }
                     }
                     FeatureUpdate::UpdateStyle { feature_index } => {
                         let Some(feature_entry) = self.features.get_entry(*feature_index) else {
@@ -336,9 +1189,20 @@ where
                             continue;
                         };
 
-                        if let Some(render_index) = feature_entry.render_index(lod.id()) {
-                            self.update_feature(
-                                feature_entry.feature(),
+                        let render_index = feature_entry.render_index(lod.id());
+                        if let Some(render_index) = render_index.filter(|&i| lod.is_pending(i)) {
+                            // The feature is being tessellated with the old style.
+                            lod.remove_render(render_index);
+                            feature_entry.clear_render_index(lod.id());
+                            self.render_feature_in_background(
+                                feature_entry,
+                                &*projection,
+                                &mut lod,
+                                &mut background,
+                            );
+                        } else if let Some(render_index) = render_index {
+                            self.restyle_feature(
+                                feature_entry,
                                 &*projection,
                                 render_index,
                                 &mut lod,
@@ -349,44 +1213,350 @@ where
                 }
             }
 
+            self.tessellate_in_background(canvas, &lod, background);
             lod.pack(canvas);
         }
     }
 
-    fn render_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+    /// Builds the index of the features used for streaming in the `crs` of the map, or applies the `updates` of the
+    /// features to the existing one.
+    fn update_stream_index<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
-        feature_entry: &FeatureEntry<F>,
+        crs: &Crs,
         projection: &Proj,
-        lod: &mut FeatureRenderStore,
+        updates: &[FeatureUpdate],
     ) {
-        let feature = feature_entry.feature();
-        let Some(projected): Option<Geom<Point3d>> = feature.geometry().project(projection) else {
-            return;
+        let extent = |feature_index| {
+            let entry = self.features.get_entry(feature_index)?;
+            streaming::planar_extent(&self.project_unwrapped(entry.feature(), projection)?)
         };
 
-        let primitives = self
-            .symbol
-            .render(feature, &projected, lod.min_resolution());
-        let index = lod.add_primitives(primitives);
-        feature_entry.set_render_index(index, lod.id());
+        let mut stream_index = self.stream_index.lock().expect("mutex is poisoned");
+        match &mut *stream_index {
+            Some(index) if index.crs() == crs => index.apply_updates(updates, extent),
+            _ => {
+                let extents = (0..self.features.len()).map(extent).collect();
+                *stream_index = Some(StreamIndex::new(crs.clone(), extents));
+            }
+        }
     }
 
-    fn update_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+    /// Renders the features around the view, that were not rendered yet for the level of detail used by the view.
+    fn stream_features<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
         &self,
-        feature: &F,
+        view: &MapView,
+        canvas: &dyn Canvas,
+        projection: &Proj,
+    ) {
+        let Some(bbox) = view.get_bbox() else {
+            return;
+        };
+
+        let world_width = *self.world_width.lock().expect("mutex is poisoned");
+        let area = streaming::streamed_area(&bbox, world_width);
+        let mut lod = self
+            .select_lod(view.resolution())
+            .lock()
+            .expect("mutex is poisoned");
+        if !streaming::needs_streaming(lod.streamed_area(), &area) {
+            return;
+        }
+
+        let indices = match &*self.stream_index.lock().expect("mutex is poisoned") {
+            Some(index) if index.crs() == view.crs() => index.locate(&area, world_width),
+            _ => return,
+        };
+
+        let is_background = self.is_background_tessellation();
+        let mut background = vec![];
+        for feature_index in indices {
+            let Some(feature_entry) = self.features.get_entry(feature_index) else {
+                continue;
+            };
+            if feature_entry.is_hidden() || feature_entry.render_index(lod.id()).is_some() {
+                continue;
+            }
+
+            if is_background {
+                self.render_feature_in_background(
+                    feature_entry,
+                    projection,
+                    &mut lod,
+                    &mut background,
+                );
+            } else {
+                lod.init_bundle(|| canvas.create_bundle());
+                self.render_feature(feature_entry, projection, &mut lod);
+            }
+        }
+
+        self.tessellate_in_background(canvas, &lod, background);
+        lod.set_streamed_area(Some(area));
+        lod.pack(canvas);
+    }
+
+    fn is_background_tessellation(&self) -> bool {
+        self.options.background_tessellation && !cfg!(target_arch = "wasm32")
+    }
+
+    /// Renders the feature into primitives to be tessellated in background with the other features of the `batch`.
+    fn render_feature_in_background<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        feature_entry: &FeatureEntry<F>,
         projection: &Proj,
-        render_index: usize,
         lod: &mut FeatureRenderStore,
+        batch: &mut Vec<(usize, Vec<OwnedPrimitive>)>,
     ) {
-        let Some(projected): Option<Geom<Point3d>> = feature.geometry().project(projection) else {
+        let feature = feature_entry.feature();
+        let time = *self.rendered_time.lock().expect("mutex is poisoned");
+        if !self.is_shown(feature, time) {
+            return;
+        }
+
+        let Some(projected) = self.project_feature(feature, projection, lod.min_resolution())
+        else {
             return;
         };
 
         let primitives = self
             .symbol
-            .render(feature, &projected, lod.min_resolution());
+            .render_with_state(
+                feature,
+                &projected,
+                lod.min_resolution(),
+                feature_entry.state(),
+            )
+            .into_iter()
+            .map(RenderPrimitive::into_owned)
+            .collect();
+        let index = lod.reserve_render();
+        feature_entry.set_render_index(index, lod.id());
+        batch.push((index, primitives));
+    }
+
+    /// Tessellates the rendered features in background, splitting them into batches.
+    fn tessellate_in_background(
+        &self,
+        canvas: &dyn Canvas,
+        lod: &FeatureRenderStore,
+        mut features: Vec<(usize, Vec<OwnedPrimitive>)>,
+    ) {
+        while !features.is_empty() {
+            let rest = features.split_off(features.len().min(TESSELLATION_BATCH_SIZE));
+            self.tessellate_batch_in_background(canvas, lod, features);
+            features = rest;
+        }
+    }
+
+    fn tessellate_batch_in_background(
+        &self,
+        canvas: &dyn Canvas,
+        lod: &FeatureRenderStore,
+        features: Vec<(usize, Vec<OwnedPrimitive>)>,
+    ) {
+        let bundle = canvas.create_bundle();
+        let store_id = lod.id();
+        let min_resolution = lod.min_resolution();
+        let sender = self.tessellated.sender.clone();
+        let messenger = self.messenger.clone();
+        let job = move || {
+            let batch = TessellatedBatch::tessellate(store_id, min_resolution, bundle, features);
+            if sender.send(batch).is_ok() {
+                if let Some(messenger) = &*messenger.read().expect("lock is poisoned") {
+                    messenger.request_redraw();
+                }
+            }
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        tessellation::TessellationPool::global().spawn(job);
+        #[cfg(target_arch = "wasm32")]
+        job();
+    }
+
+    /// Adds the features tessellated in background since the last render to their render stores.
+    fn add_tessellated_batches(&self) {
+        let batches: Vec<_> = self
+            .tessellated
+            .receiver
+            .lock()
+            .expect("mutex is poisoned")
+            .try_iter()
+            .collect();
+        for batch in batches {
+            for lod in &self.lods {
+                let mut contents = lod.contents.lock().expect("mutex is poisoned");
+                if contents.id() == batch.store_id() {
+                    contents.add_batch(batch);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Marks all the features with time ranges for re-rendering if the time of the view changed since the last
+    /// render.
+    fn update_time(&self, time: Option<SystemTime>, updates: &mut Vec<FeatureUpdate>) {
+        let mut rendered_time = self.rendered_time.lock().expect("mutex is poisoned");
+        if *rendered_time == time {
+            return;
+        }
+
+        *rendered_time = time;
+        updates.extend(
+            (0..)
+                .map_while(|index| Some((index, self.features.get_entry(index)?)))
+                .filter(|(_, entry)| !entry.is_hidden() && entry.feature().time_range().is_some())
+                .map(|(feature_index, _)| FeatureUpdate::Update { feature_index }),
+        );
+    }
+
+    fn render_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        feature_entry: &FeatureEntry<F>,
+        projection: &Proj,
+        lod: &mut FeatureRenderStore,
+    ) {
+        let feature = feature_entry.feature();
+        let time = *self.rendered_time.lock().expect("mutex is poisoned");
+        if !self.is_shown(feature, time) {
+            return;
+        }
+
+        let Some(projected) = self.project_feature(feature, projection, lod.min_resolution())
+        else {
+            return;
+        };
+
+        let primitives = self.symbol.render_with_state(
+            feature,
+            &projected,
+            lod.min_resolution(),
+            feature_entry.state(),
+        );
+        let index = lod.add_primitives(primitives);
+        feature_entry.set_render_index(index, lod.id());
+    }
+
+    fn restyle_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        feature_entry: &FeatureEntry<F>,
+        projection: &Proj,
+        render_index: usize,
+        lod: &mut FeatureRenderStore,
+    ) {
+        let feature = feature_entry.feature();
+        let Some(projected) = self.project_feature(feature, projection, lod.min_resolution())
+        else {
+            return;
+        };
+
+        let primitives = self.symbol.render_with_state(
+            feature,
+            &projected,
+            lod.min_resolution(),
+            feature_entry.state(),
+        );
         lod.update_renders(render_index, primitives);
     }
+
+    /// Projects the geometry of the feature for rendering at the given resolution, simplifying it if set up in the
+    /// layer options.
+    fn project_feature<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        feature: &F,
+        projection: &Proj,
+        resolution: f64,
+    ) -> Option<Geom<Point3d>> {
+        let projected = self.project_unwrapped(feature, projection)?;
+        Some(match self.options.simplification {
+            Some(algorithm) => simplification::simplify_projected(
+                projected,
+                algorithm,
+                self.options.simplification_tolerance * resolution,
+            ),
+            None => projected,
+        })
+    }
+
+    /// Projects the geometry of the feature, unwrapping it across the antimeridian if the world is repeated.
+    fn project_unwrapped<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        feature: &F,
+        projection: &Proj,
+    ) -> Option<Geom<Point3d>> {
+        let projected: Geom<Point3d> = feature.geometry().project(projection)?;
+        Some(match *self.world_width.lock().expect("mutex is poisoned") {
+            Some(world_width) => antimeridian::unwrap_projected(projected, world_width),
+            None => projected,
+        })
+    }
+
+    fn query_with_projection<Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &self,
+        view: &MapView,
+        screen_point: &Point2d,
+        tolerance: f64,
+        projection: &Proj,
+    ) -> Vec<usize> {
+        self.hits_with_projection(view, screen_point, tolerance, projection, None)
+            .collect()
+    }
+
+    /// Lazily finds indices of the features hit by the `screen_point`, topmost first. Only the features with the
+    /// `candidates` indices (sorted in ascending order) are checked, or every feature of the layer if `candidates` is
+    /// `None`.
+    fn hits_with_projection<'a, Proj: Projection<InPoint = P, OutPoint = Point3d> + ?Sized>(
+        &'a self,
+        view: &'a MapView,
+        screen_point: &'a Point2d,
+        tolerance: f64,
+        projection: &'a Proj,
+        candidates: Option<Vec<usize>>,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let resolution = view.resolution();
+        let world_width = view::world_width(view.crs());
+        // Features drawn last are on top.
+        let indices: Box<dyn Iterator<Item = usize>> = match candidates {
+            Some(candidates) => Box::new(candidates.into_iter().rev()),
+            None => Box::new((0..self.features.len()).rev()),
+        };
+
+        indices
+            .filter_map(|index| Some((index, self.features.get_entry(index)?)))
+            .filter(move |(_, entry)| {
+                !entry.is_hidden() && self.is_shown(entry.feature(), view.time())
+            })
+            .filter(move |(_, entry)| {
+                let feature = entry.feature();
+                let Some(mut projected): Option<Geom<Point3d>> =
+                    feature.geometry().project(projection)
+                else {
+                    return false;
+                };
+                if let Some(world_width) = world_width {
+                    projected = antimeridian::unwrap_projected(projected, world_width);
+                }
+
+                self.symbol
+                    .render_with_state(feature, &projected, resolution, entry.state())
+                    .iter()
+                    .any(|primitive| {
+                        picking::primitive_hit(primitive, view, screen_point, tolerance)
+                    })
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+impl<P, F, S, Space> Drop for FeatureLayer<P, F, S, Space>
+where
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    fn drop(&mut self) {
+        self.label_placer.unregister(self.label_layer_id);
+    }
 }
 
 impl<P, F, S> FeatureLayer<P, F, S, GeoSpace2d>
@@ -401,10 +1571,30 @@ where
         crs: &Crs,
     ) -> Option<impl Projection<InPoint = P, OutPoint = Point3d>> {
         Some(ChainProjection::new(
-            crs.get_projection::<P, Point2d>()?,
+            Box::new(crs.projection_from_geo::<P, Point2d>()?),
             Box::new(AddDimensionProjection::new(0.0)),
         ))
     }
+
+    /// Returns features, drawn geometries of which cover the `screen_point` of the `view`, or are closer to it than
+    /// `tolerance` pixels. The topmost feature goes first. See [`FeatureLayer`] documentation for details.
+    pub fn query_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> impl Iterator<Item = FeatureContainer<'_, F>> {
+        let indices = match self.get_projection(view.crs()) {
+            Some(projection) => {
+                self.query_with_projection(view, &screen_point, tolerance, &projection)
+            }
+            None => vec![],
+        };
+
+        indices
+            .into_iter()
+            .filter_map(|index| self.features.get_container(index))
+    }
 }
 
 impl<P, F, S> Layer for FeatureLayer<P, F, S, GeoSpace2d>
@@ -429,6 +1619,20 @@ where
         *self.messenger.write().expect("lock is poisoned") = Some(messenger);
     }
 
+    fn feature_at(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> Option<FeatureId> {
+        let projection = self.get_projection(view.crs())?;
+        let candidates = self.pick_candidates(view, screen_point, tolerance);
+        let index = self
+            .hits_with_projection(view, &screen_point, tolerance, &projection, candidates)
+            .next()?;
+        self.features.id_of(index)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -452,18 +1656,33 @@ where
         if crs == &self.crs {
             Some(Box::new(AddDimensionProjection::new(0.0)))
         } else {
-            let self_proj = self.crs.get_projection::<GeoPoint2d, P>()?;
-            let view_proj: Box<dyn Projection<InPoint = _, OutPoint = Point2d>> =
-                crs.get_projection()?;
+            let projection = self.crs.projection_to(crs)?.with_points::<P, Point2d>();
             Some(Box::new(ChainProjection::new(
-                Box::new(ChainProjection::new(
-                    Box::new(InvertedProjection::new(self_proj)),
-                    view_proj,
-                )),
+                Box::new(projection),
                 Box::new(AddDimensionProjection::new(0.0)),
             )))
         }
     }
+
+    /// Returns features, drawn geometries of which cover the `screen_point` of the `view`, or are closer to it than
+    /// `tolerance` pixels. The topmost feature goes first. See [`FeatureLayer`] documentation for details.
+    pub fn query_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> impl Iterator<Item = FeatureContainer<'_, F>> {
+        let indices = match self.get_projection(view.crs()) {
+            Some(projection) => {
+                self.query_with_projection(view, &screen_point, tolerance, &*projection)
+            }
+            None => vec![],
+        };
+
+        indices
+            .into_iter()
+            .filter_map(|index| self.features.get_container(index))
+    }
 }
 
 impl<P, F, S> Layer for FeatureLayer<P, F, S, CartesianSpace2d>
@@ -488,6 +1707,17 @@ where
         *self.messenger.write().expect("lock is poisoned") = Some(messenger);
     }
 
+    fn feature_at(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> Option<FeatureId> {
+        self.query_features(view, screen_point, tolerance)
+            .next()
+            .map(|feature| feature.id())
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -508,6 +1738,25 @@ where
     fn get_projection(&self) -> IdentityProjection<P, Point3d, CartesianSpace3d> {
         IdentityProjection::new()
     }
+
+    /// Returns features, drawn geometries of which cover the `screen_point` of the `view`, or are closer to it than
+    /// `tolerance` pixels. The topmost feature goes first. See [`FeatureLayer`] documentation for details.
+    pub fn query_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> impl Iterator<Item = FeatureContainer<'_, F>> {
+        let indices = if view.crs() == &self.crs {
+            self.query_with_projection(view, &screen_point, tolerance, &self.get_projection())
+        } else {
+            vec![]
+        };
+
+        indices
+            .into_iter()
+            .filter_map(|index| self.features.get_container(index))
+    }
 }
 
 impl<P, F, S> Layer for FeatureLayer<P, F, S, CartesianSpace3d>
@@ -536,6 +1785,17 @@ where
         *self.messenger.write().expect("lock is poisoned") = Some(messenger);
     }
 
+    fn feature_at(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> Option<FeatureId> {
+        self.query_features(view, screen_point, tolerance)
+            .next()
+            .map(|feature| feature.id())
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -544,3 +1804,618 @@ where
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::ArbitraryGeometrySymbol;
+    use galileo_types::cartesian::Size;
+    use galileo_types::geo::GeoPoint;
+    use galileo_types::latlon;
+
+    #[test]
+    fn query_features_at_screen_point() {
+        let mut layer = FeatureLayer::new(
+            vec![latlon!(0.0, 0.0), latlon!(0.0, 0.0001), latlon!(0.0, 0.0)],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+        let view = MapView::new(&latlon!(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let query =
+            |layer: &FeatureLayer<_, _, _, GeoSpace2d>, x: f64, tolerance: f64| -> Vec<usize> {
+                layer
+                    .query_features(&view, Point2d::new(x, 50.0), tolerance)
+                    .map(|f| f.index())
+                    .collect()
+            };
+
+        assert_eq!(query(&layer, 51.0, 0.0), vec![2, 0]);
+        assert_eq!(query(&layer, 61.0, 0.0), vec![1]);
+        assert!(query(&layer, 56.0, 0.0).is_empty());
+        assert_eq!(query(&layer, 56.0, 4.0), vec![2, 1, 0]);
+
+        layer.features_mut().get_mut(2).unwrap().hide();
+        assert_eq!(query(&layer, 51.0, 0.0), vec![0]);
+    }
+
+    #[test]
+    fn features_in_extent() {
+        let layer = FeatureLayer::new(
+            vec![latlon!(0.0, 0.0), latlon!(10.0, 10.0), latlon!(-10.0, 0.0)],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+
+        let extent = Rect::new(-1000.0, -1000.0, 1000.0, 1000.0);
+        let found: Vec<_> = layer
+            .features_in_extent(&extent, &Crs::EPSG3857)
+            .map(|f| f.index())
+            .collect();
+        assert_eq!(found, vec![0]);
+
+        let extent = Rect::new(-1000.0, -2_000_000.0, 2_000_000.0, 2_000_000.0);
+        let found: Vec<_> = layer
+            .features_in_extent(&extent, &Crs::EPSG3857)
+            .map(|f| f.index())
+            .collect();
+        assert_eq!(found, vec![0, 1, 2]);
+
+        let extent = Rect::new(5_000_000.0, 5_000_000.0, 6_000_000.0, 6_000_000.0);
+        assert_eq!(layer.features_in_extent(&extent, &Crs::EPSG3857).count(), 0);
+    }
+
+    #[test]
+    fn query_extent_returns_indices() {
+        let layer = FeatureLayer::new(
+            vec![latlon!(0.0, 0.0), latlon!(10.0, 10.0), latlon!(-10.0, 0.0)],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+
+        let extent = Rect::new(-1000.0, -2_000_000.0, 2_000_000.0, 2_000_000.0);
+        assert_eq!(layer.query_extent(&extent, &Crs::EPSG3857), vec![0, 1, 2]);
+
+        layer.build_index(&Crs::EPSG3857);
+        let extent = Rect::new(-1000.0, -2_000_000.0, 1000.0, 0.0);
+        assert_eq!(layer.query_extent(&extent, &Crs::EPSG3857), vec![0, 2]);
+
+        // Geographic CRS has coordinates in degrees.
+        let extent = Rect::new(5.0, 5.0, 15.0, 15.0);
+        assert_eq!(layer.query_extent(&extent, &Crs::WGS84), vec![1]);
+    }
+
+    #[test]
+    fn incremental_updates_keep_index() {
+        let mut layer = FeatureLayer::new(
+            vec![latlon!(0.0, 0.0), latlon!(10.0, 10.0), latlon!(-10.0, 0.0)],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+        layer.build_index(&Crs::EPSG3857);
+        // Initial rendering of the features is not of interest here.
+        layer.features.drain_updates();
+
+        let near_origin = Rect::new(-1000.0, -1000.0, 1000.0, 1000.0);
+        assert_eq!(layer.add_feature(latlon!(0.001, 0.001)), 3);
+        assert_eq!(layer.query_extent(&near_origin, &Crs::EPSG3857), vec![0, 3]);
+
+        assert!(layer.update_feature(0, |point| *point = latlon!(20.0, 20.0)));
+        assert!(!layer.update_feature(10, |_| {}));
+        assert_eq!(layer.query_extent(&near_origin, &Crs::EPSG3857), vec![3]);
+
+        assert_eq!(layer.remove_feature(1), Some(latlon!(10.0, 10.0)));
+        assert_eq!(layer.remove_feature(10), None);
+        assert_eq!(layer.query_extent(&near_origin, &Crs::EPSG3857), vec![2]);
+        assert!(layer.spatial_index.read().unwrap().is_some());
+
+        let updates = layer.features.drain_updates();
+        assert_eq!(updates.len(), 3);
+    }
+
+    struct TimedPoint(GeoPoint2d, std::ops::RangeInclusive<SystemTime>);
+
+    impl Feature for TimedPoint {
+        type Geom = GeoPoint2d;
+
+        fn geometry(&self) -> &Self::Geom {
+            &self.0
+        }
+
+        fn time_range(&self) -> Option<std::ops::RangeInclusive<SystemTime>> {
+            Some(self.1.clone())
+        }
+    }
+
+    #[test]
+    fn encode_features_into_tile_layer() {
+        let mut layer = FeatureLayer::new(
+            vec![
+                latlon!(10.0, 10.0),
+                latlon!(-10.0, 10.0),
+                latlon!(10.0, -10.0),
+            ],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+        layer.features_mut().get_mut(2).unwrap().hide();
+        let encoder = VectorTileEncoder::new(crate::tile_scheme::TileSchema::web(18));
+        let properties =
+            |_: &GeoPoint2d| HashMap::from([("kind".to_string(), MvtValue::Bool(true))]);
+
+        // Tile (1, 0, 1) is the north-eastern quarter of the world.
+        let tile_layer = layer
+            .encode_tile_layer(&encoder, TileIndex::new(1, 0, 1), "points", properties)
+            .unwrap();
+        assert_eq!(tile_layer.name, "points");
+        assert_eq!(tile_layer.properties, ["kind"]);
+        assert_eq!(tile_layer.features.len(), 1);
+        assert_eq!(tile_layer.features[0].id, Some(0));
+
+        assert!(layer
+            .encode_tile_layer(&encoder, TileIndex::new(0, 0, 30), "points", properties)
+            .is_none());
+    }
+
+    #[test]
+    fn features_are_filtered_by_view_time() {
+        let at = |seconds| SystemTime::UNIX_EPOCH + web_time::Duration::from_secs(seconds);
+        let layer = FeatureLayer::new(
+            vec![
+                TimedPoint(latlon!(0.0, 0.0), at(0)..=at(10)),
+                TimedPoint(latlon!(0.0, 0.0), at(10)..=at(20)),
+            ],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+        let view = MapView::new(&latlon!(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let query = |view: &MapView| -> Vec<usize> {
+            layer
+                .query_features(view, Point2d::new(50.0, 50.0), 1.0)
+                .map(|f| f.index())
+                .collect()
+        };
+
+        assert_eq!(query(&view), vec![1, 0]);
+        assert_eq!(query(&view.with_time(Some(at(5)))), vec![0]);
+        assert_eq!(query(&view.with_time(Some(at(10)))), vec![1, 0]);
+        assert!(query(&view.with_time(Some(at(30)))).is_empty());
+
+        let mut updates = vec![];
+        layer.update_time(Some(at(5)), &mut updates);
+        assert_eq!(updates.len(), 2);
+        layer.update_time(Some(at(5)), &mut updates);
+        assert_eq!(updates.len(), 2);
+    }
+
+    struct City(GeoPoint2d, f64);
+
+    impl Feature for City {
+        type Geom = GeoPoint2d;
+
+        fn geometry(&self) -> &Self::Geom {
+            &self.0
+        }
+    }
+
+    impl FeatureAttributes for City {
+        fn attribute(&self, name: &str) -> Option<AttributeValue> {
+            (name == "population").then_some(AttributeValue::Number(self.1))
+        }
+    }
+
+    #[test]
+    fn features_are_filtered_by_attributes() {
+        let mut layer = FeatureLayer::new(
+            vec![
+                City(latlon!(0.0, 0.0), 5000.0),
+                City(latlon!(0.0, 0.0), 50000.0),
+            ],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        )
+        .with_filter("population > 10000".parse().unwrap());
+        let view = MapView::new(&latlon!(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let query = |layer: &FeatureLayer<_, _, _, GeoSpace2d>| -> Vec<usize> {
+            layer
+                .query_features(&view, Point2d::new(50.0, 50.0), 1.0)
+                .map(|f| f.index())
+                .collect()
+        };
+
+        assert_eq!(query(&layer), vec![1]);
+        layer.features.drain_updates();
+
+        layer.set_filter(Some(FeatureFilter::lt("population", 10000.0)));
+        assert_eq!(query(&layer), vec![0]);
+        assert_eq!(layer.features.drain_updates().len(), 2);
+
+        layer.set_filter(None);
+        assert_eq!(layer.filter(), None);
+        assert_eq!(query(&layer), vec![1, 0]);
+    }
+
+    #[test]
+    fn feature_state_changes_update_style() {
+        let mut layer: FeatureLayer<_, _, _, GeoSpace2d> = FeatureLayer::new(
+            vec![latlon!(0.0, 0.0), latlon!(1.0, 1.0)],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+        layer.features.drain_updates();
+
+        assert!(layer.set_state(0, FeatureState::Selected));
+        assert!(layer.set_state(0, FeatureState::Selected));
+        assert_eq!(layer.state(0), Some(FeatureState::Selected));
+        assert_eq!(layer.state(1), Some(FeatureState::Normal));
+        assert!(matches!(
+            &layer.features.drain_updates()[..],
+            [FeatureUpdate::UpdateStyle { feature_index: 0 }]
+        ));
+
+        layer.set_state(0, FeatureState::Hidden);
+        assert_eq!(layer.state(0), Some(FeatureState::Hidden));
+        assert!(layer.features.get_container(0).unwrap().is_hidden());
+        assert!(matches!(
+            &layer.features.drain_updates()[..],
+            [FeatureUpdate::Delete { .. }]
+        ));
+
+        layer.set_state(0, FeatureState::Hovered);
+        assert_eq!(layer.state(0), Some(FeatureState::Hovered));
+        assert!(matches!(
+            &layer.features.drain_updates()[..],
+            [FeatureUpdate::Update { feature_index: 0 }]
+        ));
+
+        assert!(!layer.set_state(2, FeatureState::Selected));
+        assert_eq!(layer.state(2), None);
+    }
+
+    #[test]
+    fn feature_state_changes_point_shape() {
+        use crate::decoded_image::DecodedImage;
+        use crate::render::point_paint::PointPaint;
+        use crate::render::render_bundle::RenderPrimitive;
+        use crate::render::SvgRenderer;
+        use crate::Color;
+        use galileo_types::cartesian::NewCartesianPoint3d;
+        use galileo_types::geometry::Geom;
+        use galileo_types::impls::{Contour, Polygon};
+        use num_traits::{AsPrimitive, Float};
+        use std::sync::Arc;
+
+        /// Draws the points as a dot, a sector or an image depending on the state.
+        struct ShapeByState(Arc<DecodedImage>);
+
+        impl Symbol<Point2d> for ShapeByState {
+            fn render<'a, N, P>(
+                &self,
+                feature: &Point2d,
+                geometry: &'a Geom<P>,
+                min_resolution: f64,
+            ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+            where
+                N: AsPrimitive<f32> + Float,
+                P: NewCartesianPoint3d<N> + Clone,
+            {
+                self.render_with_state(feature, geometry, min_resolution, FeatureState::Normal)
+            }
+
+            fn render_with_state<'a, N, P>(
+                &self,
+                _feature: &Point2d,
+                geometry: &'a Geom<P>,
+                _min_resolution: f64,
+                state: FeatureState,
+            ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+            where
+                N: AsPrimitive<f32> + Float,
+                P: NewCartesianPoint3d<N> + Clone,
+            {
+                let Geom::Point(point) = geometry else {
+                    return vec![];
+                };
+                let paint = match state {
+                    FeatureState::Selected => {
+                        PointPaint::image(self.0.clone(), Default::default(), 1.0)
+                    }
+                    FeatureState::Hovered => PointPaint::sector(Color::RED, 10.0, 0.0, 3.0),
+                    _ => PointPaint::dot(Color::BLUE),
+                };
+                vec![RenderPrimitive::new_point_ref(point, paint)]
+            }
+        }
+
+        type PointLayer = FeatureLayer<Point2d, Point2d, ShapeByState, CartesianSpace2d>;
+
+        let image = DecodedImage::from_raw(vec![255; 4], 1, 1).unwrap();
+        let layer = PointLayer::new(
+            vec![Point2d::new(0.0, 0.0), Point2d::new(10.0, 0.0)],
+            ShapeByState(Arc::new(image)),
+            Crs::EPSG3857,
+        );
+        let mut map = crate::Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 60.0)),
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        );
+        SvgRenderer::new().render(&map);
+
+        for state in [
+            FeatureState::Selected,
+            FeatureState::Hovered,
+            FeatureState::Normal,
+            FeatureState::Selected,
+        ] {
+            let layer = map.layers_mut()[0]
+                .as_any_mut()
+                .downcast_mut::<PointLayer>()
+                .unwrap();
+            assert!(layer.set_state(0, state));
+
+            let svg = SvgRenderer::new().render(&map);
+            assert_eq!(
+                svg.contains("<image"),
+                state == FeatureState::Selected,
+                "{state:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn streaming_renders_features_around_view() {
+        use crate::render::SvgRenderer;
+        use crate::symbol::CirclePointSymbol;
+        use crate::Color;
+
+        type StreamingLayer = FeatureLayer<Point2d, Point2d, CirclePointSymbol, CartesianSpace2d>;
+
+        let layer = StreamingLayer::new(
+            vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(80.0, 0.0),
+                Point2d::new(1000.0, 0.0),
+            ],
+            CirclePointSymbol::new(Color::BLUE, 10.0),
+            Crs::EPSG3857,
+        )
+        .with_options(FeatureLayerOptions {
+            streaming: true,
+            ..Default::default()
+        });
+        let view_at = |x: f64| {
+            MapView::new_projected(&Point2d::new(x, 0.0), 1.0).with_size(Size::new(100.0, 60.0))
+        };
+        let mut map = crate::Map::new(
+            view_at(0.0),
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        );
+        let rendered = |map: &crate::Map| -> Vec<usize> {
+            let layer = map.layers()[0]
+                .as_any()
+                .downcast_ref::<StreamingLayer>()
+                .unwrap();
+            (0..3)
+                .filter(|&index| {
+                    let entry = layer.features.get_entry(index).unwrap();
+                    entry.render_index(0).is_some()
+                })
+                .collect()
+        };
+
+        SvgRenderer::new().render(&map);
+        assert_eq!(rendered(&map), vec![0, 1]);
+
+        map.set_view(view_at(1000.0));
+        SvgRenderer::new().render(&map);
+        assert_eq!(rendered(&map), vec![0, 1, 2]);
+
+        *map.layers_mut()[0]
+            .as_any_mut()
+            .downcast_mut::<StreamingLayer>()
+            .unwrap()
+            .features_mut()
+            .get_mut(0)
+            .unwrap()
+            .as_mut() = Point2d::new(10.0, 0.0);
+        SvgRenderer::new().render(&map);
+        assert_eq!(rendered(&map), vec![1, 2]);
+
+        map.set_view(view_at(0.0));
+        SvgRenderer::new().render(&map);
+        assert_eq!(rendered(&map), vec![0, 1, 2]);
+    }
+
+    fn antimeridian_layer(
+    ) -> FeatureLayer<GeoPoint2d, GeoPoint2d, ArbitraryGeometrySymbol, GeoSpace2d> {
+        // Corners of an area around Fiji, lying on both sides of the antimeridian.
+        let points = vec![
+            latlon!(-16.0, 177.0),
+            latlon!(-16.0, -179.0),
+            latlon!(-19.0, -179.0),
+            latlon!(-19.0, 177.0),
+        ];
+
+        FeatureLayer::new(points, ArbitraryGeometrySymbol::default(), Crs::WGS84)
+    }
+
+    #[test]
+    fn extent_geo_wrapped_across_antimeridian() {
+        let layer = antimeridian_layer();
+
+        // Naive projected extent spans 356 degrees around the globe.
+        let naive = layer.extent_projected(&Crs::EPSG3857).unwrap();
+        assert!(naive.width() > 39_000_000.0);
+
+        let extent = layer.extent_geo_wrapped().unwrap();
+        assert!(extent.crosses_antimeridian());
+        assert_eq!(extent.lon_west(), 177.0);
+        assert_eq!(extent.lon_east(), -179.0);
+        assert_eq!(extent.lon_span(), 4.0);
+    }
+
+    #[test]
+    fn extent_geo_wrapped_empty_layer() {
+        let layer =
+            FeatureLayer::<GeoPoint2d, GeoPoint2d, ArbitraryGeometrySymbol, GeoSpace2d>::new(
+                vec![],
+                ArbitraryGeometrySymbol::default(),
+                Crs::WGS84,
+            );
+        assert!(layer.extent_geo_wrapped().is_none());
+    }
+
+    #[test]
+    fn fit_view_to_wrapped_extent() {
+        let layer = antimeridian_layer();
+        let extent = layer.extent_geo_wrapped().unwrap();
+        let view = MapView::new(&latlon!(0.0, 0.0), 1.0)
+            .with_size(Size::new(100.0, 100.0))
+            .fit_geo_extent(&extent);
+
+        // 4 degrees of longitude at the equator is about 445 km, so 100 pixels are about 4.5 km each.
+        assert!(view.resolution() > 4000.0 && view.resolution() < 5000.0);
+        let position = view.position().unwrap();
+        assert!((position.lon().abs() - 179.0).abs() < 1e-6);
+        assert!(position.lat() < -16.0 && position.lat() > -19.0);
+    }
+
+    fn test_points(count: usize) -> Vec<GeoPoint2d> {
+        // Simple deterministic pseudo-random sequence.
+        let mut seed: u64 = 42;
+        let mut next = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        (0..count)
+            .map(|_| latlon!(next() * 160.0 - 80.0, next() * 360.0 - 180.0))
+            .collect()
+    }
+
+    #[test]
+    fn indexed_queries_match_linear() {
+        let points = test_points(5000);
+        let linear = FeatureLayer::new(
+            points.clone(),
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+        let indexed = FeatureLayer::new(points, ArbitraryGeometrySymbol::default(), Crs::WGS84);
+        indexed.build_index(&Crs::EPSG3857);
+
+        let mut queried = 0;
+        for (i, point) in test_points(200).iter().enumerate() {
+            let projection = Crs::EPSG3857
+                .get_projection::<GeoPoint2d, Point2d>()
+                .unwrap();
+            let center = projection.project(point).unwrap();
+            let half_size = 10_000.0 * (i % 50 + 1) as f64;
+            let extent = Rect::new(
+                center.x() - half_size,
+                center.y() - half_size,
+                center.x() + half_size,
+                center.y() + half_size,
+            );
+
+            let expected: Vec<_> = linear
+                .features_in_extent(&extent, &Crs::EPSG3857)
+                .map(|f| f.index())
+                .collect();
+            let actual: Vec<_> = indexed
+                .features_in_extent(&extent, &Crs::EPSG3857)
+                .map(|f| f.index())
+                .collect();
+            assert_eq!(expected, actual);
+            queried += actual.len();
+
+            let expected = linear.nearest_feature(&center, &Crs::EPSG3857).unwrap();
+            let actual = indexed.nearest_feature(&center, &Crs::EPSG3857).unwrap();
+            let distance = |f: &GeoPoint2d| projection.project(f).unwrap().distance_sq(&center);
+            assert_eq!(distance(expected.as_ref()), distance(actual.as_ref()));
+        }
+
+        assert!(queried > 0);
+    }
+
+    #[test]
+    fn index_is_invalidated_on_mutation() {
+        let mut layer = FeatureLayer::new(
+            vec![latlon!(0.0, 0.0)],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        );
+        layer.build_index(&Crs::EPSG3857);
+        assert!(layer.spatial_index.read().unwrap().is_some());
+
+        layer.features_mut().insert(latlon!(0.001, 0.001));
+        assert!(layer.spatial_index.read().unwrap().is_none());
+
+        let extent = Rect::new(-1000.0, -1000.0, 1000.0, 1000.0);
+        assert_eq!(layer.features_in_extent(&extent, &Crs::EPSG3857).count(), 2);
+        assert!(layer.spatial_index.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn index_is_built_lazily() {
+        let layer = FeatureLayer::new(
+            vec![latlon!(0.0, 0.0), latlon!(10.0, 10.0)],
+            ArbitraryGeometrySymbol::default(),
+            Crs::WGS84,
+        )
+        .with_options(FeatureLayerOptions {
+            use_spatial_index: true,
+            ..Default::default()
+        });
+        assert!(layer.spatial_index.read().unwrap().is_none());
+
+        let nearest = layer
+            .nearest_feature(&Point2d::new(1_000_000.0, 1_000_000.0), &Crs::EPSG3857)
+            .unwrap();
+        assert_eq!(nearest.index(), 1);
+        assert!(layer.spatial_index.read().unwrap().is_some());
+    }
+
+    #[test]
+    fn feature_at_returns_stable_id() {
+        for use_spatial_index in [false, true] {
+            let mut layer = FeatureLayer::new(
+                vec![latlon!(0.0, 0.0), latlon!(0.0, 0.0001), latlon!(0.0, 1.0)],
+                ArbitraryGeometrySymbol::default(),
+                Crs::WGS84,
+            )
+            .with_options(FeatureLayerOptions {
+                use_spatial_index,
+                ..Default::default()
+            });
+            let view = MapView::new(&latlon!(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+            let point = Point2d::new(61.0, 50.0);
+
+            let id = layer.feature_at(&view, point, 0.0).expect("no feature");
+            assert_eq!(layer.features().index_of(id), Some(1));
+
+            layer.remove_feature(0);
+            assert_eq!(layer.feature_at(&view, point, 0.0), Some(id));
+            assert_eq!(layer.features().index_of(id), Some(0));
+        }
+    }
+
+    #[test]
+    fn pick_candidates_are_found_with_spatial_index() {
+        let features = vec![latlon!(0.0, 0.0), latlon!(0.0, 0.0001), latlon!(0.0, 1.0)];
+        let view = MapView::new(&latlon!(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0));
+        let point = Point2d::new(50.0, 50.0);
+
+        let layer = FeatureLayer::new(features, ArbitraryGeometrySymbol::default(), Crs::WGS84);
+        assert_eq!(layer.pick_candidates(&view, point, 1.0), None);
+
+        let layer = layer.with_options(FeatureLayerOptions {
+            use_spatial_index: true,
+            ..Default::default()
+        });
+        assert_eq!(layer.pick_candidates(&view, point, 1.0), Some(vec![0, 1]));
+    }
+}
@@ -0,0 +1,260 @@
+use crate::render::point_paint::{PointPaint, PointShape};
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::LinePaint;
+use crate::view::MapView;
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Point3d};
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::{Contour as _, Polygon as _, Segment};
+
+/// Returns true if the `primitive` drawn with the `view` covers the `screen_point`, or is closer to it than
+/// `tolerance` pixels.
+pub(super) fn primitive_hit(
+    primitive: &RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>,
+    view: &MapView,
+    screen_point: &Point2d,
+    tolerance: f64,
+) -> bool {
+    match primitive {
+        RenderPrimitive::Point(point, paint) => {
+            let Some(position) = view.map_to_screen(&**point) else {
+                return false;
+            };
+            point_hit(paint, &position, screen_point, tolerance)
+        }
+        RenderPrimitive::Contour(contour, paint) => {
+            let Some(points) = to_screen(contour.iter_points_closing(), view) else {
+                return false;
+            };
+            let max_distance = paint.width / 2.0 + tolerance;
+            distance_to_line_sq(&points, screen_point) <= max_distance * max_distance
+        }
+        RenderPrimitive::Polygon(polygon, _) => {
+            let mut is_inside = false;
+            for contour in polygon.iter_contours() {
+                let Some(points) = to_screen(contour.iter_points_closing(), view) else {
+                    return false;
+                };
+                if distance_to_line_sq(&points, screen_point) <= tolerance * tolerance {
+                    return true;
+                }
+                if crosses_ray(&points, screen_point) {
+                    is_inside = !is_inside;
+                }
+            }
+
+            is_inside
+        }
+    }
+}
+
+fn point_hit(
+    paint: &PointPaint,
+    position: &Point2d,
+    screen_point: &Point2d,
+    tolerance: f64,
+) -> bool {
+    // Outlines of point shapes are drawn outside of the shape with their full width.
+    let outline_width =
+        |outline: &Option<LinePaint>| outline.map(|outline| outline.width).unwrap_or(0.0);
+
+    let (left, top, right, bottom) = match &paint.shape {
+        PointShape::Dot { .. } => (0.0, 0.0, 0.0, 0.0),
+        PointShape::Circle {
+            radius, outline, ..
+        } => {
+            let radius = *radius as f64 + outline_width(outline) + tolerance;
+            return position.distance_sq(screen_point) <= radius * radius;
+        }
+        PointShape::Sector(parameters) => {
+            let radius = parameters.radius as f64 + outline_width(&parameters.outline) + tolerance;
+            return position.distance_sq(screen_point) <= radius * radius;
+        }
+        PointShape::Square {
+            size,
+            outline,
+            rotation,
+            ..
+        } => {
+            // Rotate the point into the coordinates of the square with Y axis going up.
+            let (dx, dy) = (
+                screen_point.x() - position.x(),
+                position.y() - screen_point.y(),
+            );
+            let (sin, cos) = (*rotation as f64).sin_cos();
+            let (x, y) = (dx * cos + dy * sin, dy * cos - dx * sin);
+            let half = *size as f64 / 2.0 + outline_width(outline) + tolerance;
+            return x.abs() <= half && y.abs() <= half;
+        }
+        PointShape::FreeShape {
+            scale,
+            outline,
+            shape,
+            ..
+        } => {
+            let points = &shape.points;
+            if points.is_empty() {
+                return false;
+            }
+
+            let scale = *scale as f64;
+            let outline = outline_width(outline);
+            let (mut left, mut top, mut right, mut bottom) =
+                (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+            for point in points {
+                // Shape coordinates have Y axis going up, while screen Y axis goes down.
+                let (x, y) = (point.x as f64 * scale, -point.y as f64 * scale);
+                left = left.min(x);
+                top = top.min(y);
+                right = right.max(x);
+                bottom = bottom.max(y);
+            }
+
+            (
+                left - outline,
+                top - outline,
+                right + outline,
+                bottom + outline,
+            )
+        }
+        PointShape::Image { width, height, .. } => {
+            let left = -(paint.offset.x * width) as f64;
+            let top = -(paint.offset.y * height) as f64;
+            (left, top, left + *width as f64, top + *height as f64)
+        }
+    };
+
+    let dx = screen_point.x() - position.x();
+    let dy = screen_point.y() - position.y();
+    dx >= left - tolerance
+        && dx <= right + tolerance
+        && dy >= top - tolerance
+        && dy <= bottom + tolerance
+}
+
+fn to_screen<'a>(
+    points: impl Iterator<Item = &'a Point3d>,
+    view: &MapView,
+) -> Option<Vec<Point2d>> {
+    points.map(|point| view.map_to_screen(point)).collect()
+}
+
+fn distance_to_line_sq(points: &[Point2d], screen_point: &Point2d) -> f64 {
+    match points {
+        [] => f64::MAX,
+        [point] => point.distance_sq(screen_point),
+        _ => points
+            .windows(2)
+            .map(|pair| Segment(&pair[0], &pair[1]).distance_to_point_sq(screen_point))
+            .fold(f64::MAX, f64::min),
+    }
+}
+
+/// Returns true if a horizontal ray from the `point` to the right crosses the closed `ring` odd number of times.
+fn crosses_ray(ring: &[Point2d], point: &Point2d) -> bool {
+    let mut crosses = false;
+    for pair in ring.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        if (a.y() > point.y()) != (b.y() > point.y()) {
+            let x = a.x() + (point.y() - a.y()) / (b.y() - a.y()) * (b.x() - a.x());
+            if x > point.x() {
+                crosses = !crosses;
+            }
+        }
+    }
+
+    crosses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{LineCap, LineJoin, PolygonFill, PolygonPaint};
+    use crate::Color;
+    use galileo_types::cartesian::Size;
+    use galileo_types::impls::ClosedContour;
+
+    type Primitive<'a> = RenderPrimitive<'a, f64, Point3d, Contour<Point3d>, Polygon<Point3d>>;
+
+    fn view() -> MapView {
+        // Map point (0, 0) is at the screen point (50, 50), one map unit per pixel.
+        MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(Size::new(100.0, 100.0))
+    }
+
+    fn hit(primitive: &Primitive, x: f64, y: f64, tolerance: f64) -> bool {
+        primitive_hit(primitive, &view(), &Point2d::new(x, y), tolerance)
+    }
+
+    #[test]
+    fn circle_hit_includes_radius_and_tolerance() {
+        let circle = Primitive::new_point(
+            Point3d::new(0.0, 0.0, 0.0),
+            PointPaint::circle(Color::RED, 10.0),
+        );
+        assert!(hit(&circle, 54.0, 50.0, 0.0));
+        assert!(!hit(&circle, 57.0, 50.0, 0.0));
+        assert!(hit(&circle, 57.0, 50.0, 2.0));
+    }
+
+    #[test]
+    fn image_hit_uses_anchor_offset() {
+        let image = std::sync::Arc::new(
+            crate::decoded_image::DecodedImage::from_raw(vec![0; 20 * 10 * 4], 20, 10).unwrap(),
+        );
+        let marker = Primitive::new_point(
+            Point3d::new(0.0, 0.0, 0.0),
+            PointPaint::image(image, nalgebra::Vector2::new(0.5, 1.0), 1.0),
+        );
+
+        assert!(hit(&marker, 55.0, 45.0, 0.0));
+        assert!(!hit(&marker, 55.0, 55.0, 0.0));
+        assert!(!hit(&marker, 62.0, 45.0, 0.0));
+    }
+
+    #[test]
+    fn line_hit_includes_width() {
+        let line = Primitive::new_contour(
+            Contour::open(vec![
+                Point3d::new(-20.0, 0.0, 0.0),
+                Point3d::new(20.0, 0.0, 0.0),
+            ]),
+            LinePaint {
+                color: Color::RED,
+                width: 4.0,
+                offset: 0.0,
+                line_cap: LineCap::Butt,
+                line_join: LineJoin::Round,
+                dash: None,
+                arrows: None,
+            },
+        );
+
+        assert!(hit(&line, 60.0, 51.5, 0.0));
+        assert!(!hit(&line, 60.0, 53.0, 0.0));
+        assert!(hit(&line, 60.0, 53.0, 1.0));
+        assert!(!hit(&line, 80.0, 50.0, 0.0));
+    }
+
+    #[test]
+    fn polygon_hit_respects_holes() {
+        let square = |half: f64| {
+            ClosedContour::new(vec![
+                Point3d::new(-half, -half, 0.0),
+                Point3d::new(half, -half, 0.0),
+                Point3d::new(half, half, 0.0),
+                Point3d::new(-half, half, 0.0),
+            ])
+        };
+        let polygon = Primitive::new_polygon(
+            Polygon::new(square(20.0), vec![square(5.0)]),
+            PolygonPaint {
+                color: Color::RED,
+                fill: PolygonFill::Solid,
+            },
+        );
+
+        assert!(hit(&polygon, 60.0, 60.0, 0.0));
+        assert!(!hit(&polygon, 50.0, 50.0, 0.0));
+        assert!(!hit(&polygon, 75.0, 50.0, 0.0));
+        assert!(hit(&polygon, 75.0, 50.0, 5.0));
+    }
+}
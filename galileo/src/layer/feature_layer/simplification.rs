@@ -0,0 +1,102 @@
+use galileo_types::cartesian::{
+    CartesianPoint2d, CartesianPoint3d, Point3d, SimplificationAlgorithm, Simplify,
+};
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, MultiContour, MultiPolygon, Polygon};
+use galileo_types::{Contour as _, MultiContour as _};
+
+/// Projected point simplified by its horizontal coordinates.
+#[derive(Clone)]
+struct PlanarPoint(Point3d);
+
+impl CartesianPoint2d for PlanarPoint {
+    type Num = f64;
+
+    fn x(&self) -> f64 {
+        self.0.x()
+    }
+
+    fn y(&self) -> f64 {
+        self.0.y()
+    }
+}
+
+/// Simplifies a projected geometry using only `x` and `y` coordinates of its points.
+pub(super) fn simplify_projected(
+    geometry: Geom<Point3d>,
+    algorithm: SimplificationAlgorithm,
+    tolerance: f64,
+) -> Geom<Point3d> {
+    match geometry {
+        Geom::Point(_) | Geom::MultiPoint(_) => geometry,
+        Geom::Contour(contour) => Geom::Contour(simplify_contour(&contour, algorithm, tolerance)),
+        Geom::MultiContour(contour) => Geom::MultiContour(MultiContour::from(
+            contour
+                .contours()
+                .map(|contour| simplify_contour(contour, algorithm, tolerance))
+                .collect::<Vec<_>>(),
+        )),
+        Geom::Polygon(polygon) => Geom::Polygon(simplify_polygon(&polygon, algorithm, tolerance)),
+        Geom::MultiPolygon(polygon) => Geom::MultiPolygon(MultiPolygon::from(
+            polygon
+                .parts()
+                .iter()
+                .map(|polygon| simplify_polygon(polygon, algorithm, tolerance))
+                .collect::<Vec<_>>(),
+        )),
+    }
+}
+
+fn simplify_contour(
+    contour: &Contour<Point3d>,
+    algorithm: SimplificationAlgorithm,
+    tolerance: f64,
+) -> Contour<Point3d> {
+    let planar = Contour::new(
+        contour.iter_points().cloned().map(PlanarPoint).collect(),
+        contour.is_closed(),
+    );
+    let simplified = planar.simplify_with(algorithm, tolerance);
+    Contour::new(
+        simplified.iter_points().map(|p| p.0).collect(),
+        simplified.is_closed(),
+    )
+}
+
+fn simplify_polygon(
+    polygon: &Polygon<Point3d>,
+    algorithm: SimplificationAlgorithm,
+    tolerance: f64,
+) -> Polygon<Point3d> {
+    polygon
+        .cast_points(|p| PlanarPoint(*p))
+        .simplify_with(algorithm, tolerance)
+        .cast_points(|p| p.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simplification_keeps_z_coordinate() {
+        let contour = Contour::open(vec![
+            Point3d::new(0.0, 0.0, 1.0),
+            Point3d::new(1.0, 0.1, 2.0),
+            Point3d::new(2.0, 0.0, 3.0),
+        ]);
+
+        let simplified = simplify_projected(
+            Geom::Contour(contour),
+            SimplificationAlgorithm::DouglasPeucker,
+            0.5,
+        );
+        assert_eq!(
+            simplified,
+            Geom::Contour(Contour::open(vec![
+                Point3d::new(0.0, 0.0, 1.0),
+                Point3d::new(2.0, 0.0, 3.0),
+            ]))
+        );
+    }
+}
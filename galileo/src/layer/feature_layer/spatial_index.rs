@@ -0,0 +1,78 @@
+use galileo_types::cartesian::{CartesianPoint2d, Rect};
+use galileo_types::geo::Crs;
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+
+type IndexEntry = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+/// R-tree of bounding rectangles of features, projected into a specific CRS.
+pub(crate) struct SpatialIndex {
+    crs: Crs,
+    tree: RTree<IndexEntry>,
+}
+
+impl SpatialIndex {
+    /// Creates a new index from the iterator of `(feature_index, bounding_rectangle)` pairs in the `crs`.
+    pub fn new(crs: Crs, extents: impl Iterator<Item = (usize, Rect)>) -> Self {
+        let entries = extents.map(|(index, rect)| entry(index, &rect)).collect();
+
+        Self {
+            crs,
+            tree: RTree::bulk_load(entries),
+        }
+    }
+
+    /// CRS the index was built for.
+    pub fn crs(&self) -> &Crs {
+        &self.crs
+    }
+
+    /// Adds a feature with the given bounding rectangle to the index.
+    pub fn insert(&mut self, feature_index: usize, extent: &Rect) {
+        self.tree.insert(entry(feature_index, extent));
+    }
+
+    /// Removes the feature with the given bounding rectangle from the index. Returns `false` if there was no such
+    /// entry in the index.
+    pub fn remove(&mut self, feature_index: usize, extent: &Rect) -> bool {
+        self.tree.remove(&entry(feature_index, extent)).is_some()
+    }
+
+    /// Decrements indices of all features after the `feature_index`, following removal of a feature from the store.
+    pub fn shift_indices_after(&mut self, feature_index: usize) {
+        for entry in self.tree.iter_mut() {
+            if entry.data > feature_index {
+                entry.data -= 1;
+            }
+        }
+    }
+
+    /// Indices of the features, bounding rectangles of which intersect the `extent`. Returned indices are sorted.
+    pub fn locate_in_extent(&self, extent: &Rect) -> Vec<usize> {
+        let envelope = AABB::from_corners(
+            [extent.x_min(), extent.y_min()],
+            [extent.x_max(), extent.y_max()],
+        );
+        let mut indices: Vec<usize> = self
+            .tree
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|entry| entry.data)
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    /// Index of the feature, bounding rectangle of which is the closest to the `point`.
+    pub fn nearest(&self, point: &impl CartesianPoint2d<Num = f64>) -> Option<usize> {
+        self.tree
+            .nearest_neighbor(&[point.x(), point.y()])
+            .map(|entry| entry.data)
+    }
+}
+
+fn entry(feature_index: usize, rect: &Rect) -> IndexEntry {
+    GeomWithData::new(
+        Rectangle::from_corners([rect.x_min(), rect.y_min()], [rect.x_max(), rect.y_max()]),
+        feature_index,
+    )
+}
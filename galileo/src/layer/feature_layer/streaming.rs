@@ -0,0 +1,231 @@
+use crate::layer::feature_layer::spatial_index::SpatialIndex;
+use crate::layer::feature_layer::FeatureUpdate;
+use galileo_types::cartesian::{CartesianPoint3d, Point3d, Rect};
+use galileo_types::geo::Crs;
+use galileo_types::geometry::Geom;
+use galileo_types::{Contour as _, MultiContour as _, MultiPoint as _};
+
+/// Width and height of the area around the view, in which features are rendered when streaming, relative to the
+/// view. Features slightly outside the view are rendered in advance, so that they are ready when the view is panned.
+const STREAMED_AREA_FACTOR: f64 = 2.0;
+
+/// Index of the bounding rectangles of the features projected into the CRS of the map, used to find the features to
+/// render around the view when the layer streams its features.
+///
+/// Unlike the [`SpatialIndex`] of the layer, the index is kept up to date with the features by the updates of the
+/// feature store.
+pub(super) struct StreamIndex {
+    crs: Crs,
+    index: SpatialIndex,
+    /// Bounding rectangles of the features as they were added to the index, by the feature index.
+    extents: Vec<Option<Rect>>,
+}
+
+impl StreamIndex {
+    /// Creates a new index from the bounding rectangles of all the features of the store, by the feature index.
+    pub fn new(crs: Crs, extents: Vec<Option<Rect>>) -> Self {
+        let index = SpatialIndex::new(
+            crs.clone(),
+            extents
+                .iter()
+                .enumerate()
+                .filter_map(|(feature_index, extent)| Some((feature_index, (*extent)?))),
+        );
+
+        Self {
+            crs,
+            index,
+            extents,
+        }
+    }
+
+    /// CRS the index was built for.
+    pub fn crs(&self) -> &Crs {
+        &self.crs
+    }
+
+    /// Applies the updates of the feature store to the index. The `extent` function returns the bounding rectangle of
+    /// the feature with the given index in the feature store.
+    pub fn apply_updates(
+        &mut self,
+        updates: &[FeatureUpdate],
+        extent: impl Fn(usize) -> Option<Rect>,
+    ) {
+        // Indices of the updated features point to their positions after all the removals.
+        for update in updates {
+            if let FeatureUpdate::Delete {
+                removed_index: Some(feature_index),
+                ..
+            } = update
+            {
+                self.remove(*feature_index);
+            }
+        }
+
+        for update in updates {
+            if let FeatureUpdate::Update { feature_index } = update {
+                self.set_extent(*feature_index, extent(*feature_index));
+            }
+        }
+    }
+
+    /// Indices of the features, bounding rectangles of which intersect the `area`. If the world is repeated
+    /// horizontally with the given `world_width`, features in all copies of the world are returned. Returned indices
+    /// are sorted.
+    pub fn locate(&self, area: &Rect, world_width: Option<f64>) -> Vec<usize> {
+        let Some(width) = world_width else {
+            return self.index.locate_in_extent(area);
+        };
+
+        // Features crossing the antimeridian can be unwrapped outside of the main copy of the world, so both the
+        // neighbouring copies are checked.
+        let mut indices: Vec<usize> = [-width, 0.0, width]
+            .into_iter()
+            .flat_map(|shift| {
+                let shifted = Rect::new(
+                    area.x_min() + shift,
+                    area.y_min(),
+                    area.x_max() + shift,
+                    area.y_max(),
+                );
+                self.index.locate_in_extent(&shifted)
+            })
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    fn set_extent(&mut self, feature_index: usize, extent: Option<Rect>) {
+        if self.extents.len() <= feature_index {
+            self.extents.resize(feature_index + 1, None);
+        }
+
+        if let Some(old) = self.extents[feature_index].take() {
+            self.index.remove(feature_index, &old);
+        }
+
+        if let Some(extent) = extent {
+            self.index.insert(feature_index, &extent);
+        }
+        self.extents[feature_index] = extent;
+    }
+
+    fn remove(&mut self, feature_index: usize) {
+        if feature_index >= self.extents.len() {
+            return;
+        }
+
+        if let Some(old) = self.extents.remove(feature_index) {
+            self.index.remove(feature_index, &old);
+        }
+        self.index.shift_indices_after(feature_index);
+    }
+}
+
+/// Bounding rectangle of the projected geometry by the horizontal coordinates of its points.
+pub(super) fn planar_extent(geometry: &Geom<Point3d>) -> Option<Rect> {
+    let points: Box<dyn Iterator<Item = &Point3d>> = match geometry {
+        Geom::Point(point) => Box::new(std::iter::once(point)),
+        Geom::MultiPoint(points) => Box::new(points.iter_points()),
+        Geom::Contour(contour) => Box::new(contour.iter_points()),
+        Geom::MultiContour(contour) => {
+            Box::new(contour.contours().flat_map(|contour| contour.iter_points()))
+        }
+        // Holes are inside of the outer contour and do not change the extent.
+        Geom::Polygon(polygon) => Box::new(polygon.outer_contour.iter_points()),
+        Geom::MultiPolygon(polygon) => Box::new(
+            polygon
+                .parts()
+                .iter()
+                .flat_map(|polygon| polygon.outer_contour.iter_points()),
+        ),
+    };
+
+    points
+        .map(|point| Rect::new(point.x(), point.y(), point.x(), point.y()))
+        .reduce(|extent, point| extent.merge(point))
+}
+
+/// Returns true if the view, for which the `area` was calculated, is not inside the area that has already been
+/// streamed, so the features in the `area` must be rendered.
+pub(super) fn needs_streaming(streamed: Option<Rect>, area: &Rect) -> bool {
+    let view = area.magnify(1.0 / STREAMED_AREA_FACTOR);
+    streamed.is_none_or(|streamed| !streamed.contains_rect(&view))
+}
+
+/// Area around the view, in which features are rendered when streaming. If the world is repeated horizontally with
+/// the given `world_width`, the area is moved into the main copy of the world, so that all the copies of the view
+/// get the same area.
+pub(super) fn streamed_area(view_bbox: &Rect, world_width: Option<f64>) -> Rect {
+    let area = view_bbox.magnify(STREAMED_AREA_FACTOR);
+    match world_width {
+        Some(width) if area.width() < width => {
+            let center = (area.x_min() + area.x_max()) / 2.0;
+            let shift = (center / width).round() * width;
+            Rect::new(
+                area.x_min() - shift,
+                area.y_min(),
+                area.x_max() - shift,
+                area.y_max(),
+            )
+        }
+        _ => area,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f64, y: f64) -> Option<Rect> {
+        Some(Rect::new(x, y, x + 1.0, y + 1.0))
+    }
+
+    #[test]
+    fn updates_keep_index_in_sync() {
+        let mut index = StreamIndex::new(
+            Crs::EPSG3857,
+            vec![square(0.0, 0.0), square(10.0, 0.0), square(20.0, 0.0)],
+        );
+        let query =
+            |index: &StreamIndex, x: f64| index.locate(&Rect::new(x, 0.0, x + 1.0, 1.0), None);
+        assert_eq!(query(&index, 10.0), vec![1]);
+
+        // Feature 1 is removed, then feature 2 (now at index 1) is moved and a new feature is added at index 2.
+        let updates = [
+            FeatureUpdate::Delete {
+                render_indices: vec![],
+                removed_index: Some(1),
+            },
+            FeatureUpdate::Update { feature_index: 1 },
+            FeatureUpdate::Update { feature_index: 2 },
+        ];
+        index.apply_updates(&updates, |feature_index| match feature_index {
+            1 => square(30.0, 0.0),
+            2 => square(40.0, 0.0),
+            _ => None,
+        });
+
+        assert!(query(&index, 10.0).is_empty());
+        assert!(query(&index, 20.0).is_empty());
+        assert_eq!(query(&index, 0.0), vec![0]);
+        assert_eq!(query(&index, 30.0), vec![1]);
+        assert_eq!(query(&index, 40.0), vec![2]);
+    }
+
+    #[test]
+    fn wrapped_areas_find_features_in_other_copies() {
+        let width = 100.0;
+        let index = StreamIndex::new(Crs::EPSG3857, vec![square(45.0, 0.0), square(90.0, 0.0)]);
+
+        let view = Rect::new(140.0, 0.0, 150.0, 1.0);
+        let area = streamed_area(&view, Some(width));
+        assert_eq!(area, Rect::new(35.0, -0.5, 55.0, 1.5));
+        assert_eq!(index.locate(&area, Some(width)), vec![0]);
+
+        let across_antimeridian = streamed_area(&Rect::new(85.0, 0.0, 95.0, 1.0), Some(width));
+        assert_eq!(index.locate(&across_antimeridian, Some(width)), vec![1]);
+        assert!(index.locate(&across_antimeridian, None).is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::layer::feature_layer::Feature;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::symbol::{ArbitraryGeometrySymbol, Symbol};
+use galileo_types::cartesian::NewCartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use num_traits::{AsPrimitive, Float};
+use std::marker::PhantomData;
+
+/// Symbol that selects the style of every feature with a callback function. This allows styling the features
+/// based on their properties and geometries.
+///
+/// The callback is called with the feature and its geometry (as returned by [`Feature::geometry`]) every time the
+/// feature is rendered, and the returned [`ArbitraryGeometrySymbol`] is used to draw the geometry of the feature.
+///
+/// ```
+/// use galileo::layer::feature_layer::Feature;
+/// use galileo::symbol::{
+///     ArbitraryGeometrySymbol, CallbackSymbol, CirclePointSymbol, SimpleContourSymbol,
+///     SimplePolygonSymbol,
+/// };
+/// use galileo::Color;
+/// use galileo_types::cartesian::Point2d;
+///
+/// struct Museum {
+///     category: String,
+///     location: Point2d,
+/// }
+///
+/// impl Feature for Museum {
+///     type Geom = Point2d;
+///
+///     fn geometry(&self) -> &Self::Geom {
+///         &self.location
+///     }
+/// }
+///
+/// let symbol = CallbackSymbol::new(|museum: &Museum, _location: &Point2d| {
+///     let color = match &museum.category[..] {
+///         "art" => Color::RED,
+///         "history" => Color::BLUE,
+///         _ => Color::BLACK,
+///     };
+///
+///     ArbitraryGeometrySymbol::new(
+///         CirclePointSymbol::new(color, 10.0),
+///         SimpleContourSymbol::new(color, 2.0),
+///         SimplePolygonSymbol::new(color),
+///     )
+/// });
+/// ```
+pub struct CallbackSymbol<F, Callback>
+where
+    F: Feature,
+    Callback: Fn(&F, &F::Geom) -> ArbitraryGeometrySymbol,
+{
+    callback: Callback,
+    _phantom: PhantomData<fn(&F)>,
+}
+
+impl<F, Callback> CallbackSymbol<F, Callback>
+where
+    F: Feature,
+    Callback: Fn(&F, &F::Geom) -> ArbitraryGeometrySymbol,
+{
+    /// Creates a new instance.
+    pub fn new(callback: Callback) -> Self {
+        Self {
+            callback,
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<F, Callback> Symbol<F> for CallbackSymbol<F, Callback>
+where
+    F: Feature,
+    Callback: Fn(&F, &F::Geom) -> ArbitraryGeometrySymbol,
+{
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        (self.callback)(feature, feature.geometry()).render(feature, geometry, min_resolution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::{CirclePointSymbol, SimpleContourSymbol, SimplePolygonSymbol};
+    use crate::Color;
+    use galileo_types::cartesian::{Point2d, Point3d};
+    use galileo_types::Contour as _;
+
+    struct TestFeature {
+        is_red: bool,
+        geometry: Contour<Point2d>,
+    }
+
+    impl Feature for TestFeature {
+        type Geom = Contour<Point2d>;
+
+        fn geometry(&self) -> &Self::Geom {
+            &self.geometry
+        }
+    }
+
+    fn test_feature(is_red: bool, points: usize) -> TestFeature {
+        TestFeature {
+            is_red,
+            geometry: Contour::open((0..points).map(|i| Point2d::new(i as f64, 0.0)).collect()),
+        }
+    }
+
+    fn contour_color(symbol: &impl Symbol<TestFeature>, feature: &TestFeature) -> Color {
+        let geometry = Geom::Contour(Contour::open(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+        ]));
+
+        let primitives = symbol.render(feature, &geometry, 1.0);
+        assert_eq!(primitives.len(), 1);
+        let RenderPrimitive::Contour(_, paint) = &primitives[0] else {
+            panic!("expected contour primitive");
+        };
+        paint.color
+    }
+
+    fn symbol_of_color(color: Color) -> ArbitraryGeometrySymbol {
+        ArbitraryGeometrySymbol::new(
+            CirclePointSymbol::new(color, 1.0),
+            SimpleContourSymbol::new(color, 1.0),
+            SimplePolygonSymbol::new(color),
+        )
+    }
+
+    #[test]
+    fn callback_symbol_uses_feature() {
+        let symbol = CallbackSymbol::new(|feature: &TestFeature, _: &Contour<Point2d>| {
+            symbol_of_color(if feature.is_red {
+                Color::RED
+            } else {
+                Color::BLUE
+            })
+        });
+
+        for (is_red, expected) in [(true, Color::RED), (false, Color::BLUE)] {
+            assert_eq!(contour_color(&symbol, &test_feature(is_red, 2)), expected);
+        }
+    }
+
+    #[test]
+    fn callback_symbol_uses_geometry() {
+        let symbol = CallbackSymbol::new(|_: &TestFeature, geometry: &Contour<Point2d>| {
+            symbol_of_color(if geometry.iter_points().count() > 2 {
+                Color::RED
+            } else {
+                Color::BLUE
+            })
+        });
+
+        for (points, expected) in [(5, Color::RED), (2, Color::BLUE)] {
+            assert_eq!(
+                contour_color(&symbol, &test_feature(false, points)),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use crate::layer::feature_layer::FeatureAttributes;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::symbol::{Ramp, SimplePolygonSymbol, Symbol};
+use crate::Color;
+use galileo_types::cartesian::NewCartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use num_traits::{AsPrimitive, Float};
+
+/// Method of dividing a set of numeric values into classes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Classification {
+    /// Classes of equal width between the minimum and the maximum value.
+    EqualInterval,
+    /// Classes with (about) the same number of values.
+    Quantile,
+    /// Jenks natural breaks: classes minimizing the variance of the values inside them.
+    ///
+    /// The running time grows with the square of the number of values, so for large datasets consider classifying a
+    /// sample of the values.
+    Jenks,
+}
+
+impl Classification {
+    /// Returns the boundaries of the classes of the `values`: the minimum value followed by the upper bound of every
+    /// class. Upper bounds are inclusive.
+    ///
+    /// The result has fewer than `classes + 1` items if there are not enough distinct values to fill all classes, and
+    /// is empty if there are no finite values.
+    pub fn breaks(&self, values: impl IntoIterator<Item = f64>, classes: usize) -> Vec<f64> {
+        let mut values: Vec<f64> = values.into_iter().filter(|v| v.is_finite()).collect();
+        if values.is_empty() || classes == 0 {
+            return vec![];
+        }
+        values.sort_by(f64::total_cmp);
+
+        let mut breaks = match self {
+            Self::EqualInterval => {
+                let min = values[0];
+                let width = (values[values.len() - 1] - min) / classes as f64;
+                (0..=classes).map(|i| min + width * i as f64).collect()
+            }
+            Self::Quantile => std::iter::once(values[0])
+                .chain((1..=classes).map(|i| values[(i * values.len()).div_ceil(classes) - 1]))
+                .collect(),
+            Self::Jenks => jenks_breaks(&values, classes),
+        };
+        let mut upper_bounds = breaks.split_off(1);
+        upper_bounds.dedup();
+        breaks.extend(upper_bounds);
+
+        breaks
+    }
+}
+
+/// Fisher-Jenks optimization over the sorted values.
+fn jenks_breaks(values: &[f64], classes: usize) -> Vec<f64> {
+    let n = values.len();
+    let classes = classes.min(n);
+
+    // Both matrices are indexed by (number of first values, number of classes), starting from 1.
+    let mut lower_class_limits = vec![vec![0usize; classes + 1]; n + 1];
+    let mut variances = vec![vec![f64::INFINITY; classes + 1]; n + 1];
+    for class in 1..=classes {
+        lower_class_limits[1][class] = 1;
+        variances[1][class] = 0.0;
+    }
+
+    for last in 2..=n {
+        let mut sum = 0.0;
+        let mut sum_squares = 0.0;
+        let mut variance = 0.0;
+        for count in 1..=last {
+            let first = last - count + 1;
+            let value = values[first - 1];
+            sum += value;
+            sum_squares += value * value;
+            variance = sum_squares - sum * sum / count as f64;
+
+            if first > 1 {
+                for class in 2..=classes {
+                    let candidate = variance + variances[first - 1][class - 1];
+                    if variances[last][class] >= candidate {
+                        lower_class_limits[last][class] = first;
+                        variances[last][class] = candidate;
+                    }
+                }
+            }
+        }
+        lower_class_limits[last][1] = 1;
+        variances[last][1] = variance;
+    }
+
+    let mut breaks = vec![0.0; classes + 1];
+    breaks[0] = values[0];
+    breaks[classes] = values[n - 1];
+    let mut last = n;
+    for class in (2..=classes).rev() {
+        let first = lower_class_limits[last][class];
+        breaks[class - 1] = values[first - 2];
+        last = first - 1;
+    }
+
+    breaks
+}
+
+/// A class of a [`ChoroplethSymbol`], as shown in the map legend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChoroplethClass {
+    /// Lower bound of the class values. Values equal to it belong to the previous class, if there is one.
+    pub min: f64,
+    /// Upper bound of the class values (inclusive).
+    pub max: f64,
+    /// Fill color of the class.
+    pub color: Color,
+}
+
+/// Renders polygons filled with the color of the class their numeric attribute belongs to.
+///
+/// The classes are computed from a set of attribute values (usually the values of all features of the layer) with one
+/// of the [`Classification`] methods, and colored by sampling a color ramp evenly from `0.0` to `1.0`. The classes are
+/// available with [`ChoroplethSymbol::legend`] to draw a map legend.
+///
+/// ```
+/// use galileo::layer::feature_layer::{AttributeValue, FeatureAttributes};
+/// use galileo::symbol::{ChoroplethSymbol, Classification, Ramp};
+/// use galileo::Color;
+///
+/// struct Region {
+///     density: f64,
+/// }
+///
+/// impl FeatureAttributes for Region {
+///     fn attribute(&self, name: &str) -> Option<AttributeValue> {
+///         (name == "density").then_some(AttributeValue::Number(self.density))
+///     }
+/// }
+///
+/// let regions = [Region { density: 3.0 }, Region { density: 140.0 }, Region { density: 18.0 }];
+/// let symbol = ChoroplethSymbol::new(
+///     "density",
+///     regions.iter().map(|region| region.density),
+///     Classification::Quantile,
+///     3,
+///     &Ramp::blues(),
+/// )
+/// .with_stroke(Color::WHITE, 0.5);
+///
+/// assert_eq!(symbol.legend().len(), 3);
+/// ```
+///
+/// Non-polygon geometries are not rendered. Polygons without the numeric attribute are rendered with the
+/// [no data color](ChoroplethSymbol::with_no_data_color), if it is set.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChoroplethSymbol {
+    attribute: String,
+    classes: Vec<ChoroplethClass>,
+    stroke_color: Color,
+    stroke_width: f64,
+    no_data_color: Option<Color>,
+}
+
+impl ChoroplethSymbol {
+    /// Creates a new symbol dividing the `values` of the given attribute into the number of `classes`.
+    pub fn new(
+        attribute: impl Into<String>,
+        values: impl IntoIterator<Item = f64>,
+        classification: Classification,
+        classes: usize,
+        ramp: &Ramp<Color>,
+    ) -> Self {
+        Self::with_breaks(attribute, classification.breaks(values, classes), ramp)
+    }
+
+    /// Creates a new symbol with the given class boundaries: the lower bound of the first class followed by the
+    /// upper bound of every class, as returned by [`Classification::breaks`].
+    pub fn with_breaks(attribute: impl Into<String>, breaks: Vec<f64>, ramp: &Ramp<Color>) -> Self {
+        let count = breaks.len().saturating_sub(1);
+        let classes = breaks
+            .windows(2)
+            .enumerate()
+            .map(|(i, bounds)| {
+                let position = match count {
+                    1 => 0.5,
+                    _ => i as f64 / (count - 1) as f64,
+                };
+                ChoroplethClass {
+                    min: bounds[0],
+                    max: bounds[1],
+                    color: ramp.value_at(position).unwrap_or(Color::TRANSPARENT),
+                }
+            })
+            .collect();
+
+        Self {
+            attribute: attribute.into(),
+            classes,
+            stroke_color: Color::TRANSPARENT,
+            stroke_width: 0.0,
+            no_data_color: None,
+        }
+    }
+
+    /// Sets the outline of the polygons.
+    pub fn with_stroke(mut self, color: Color, width: f64) -> Self {
+        self.stroke_color = color;
+        self.stroke_width = width;
+        self
+    }
+
+    /// Sets the color of the polygons without the attribute. By default, such polygons are not rendered.
+    pub fn with_no_data_color(mut self, color: Color) -> Self {
+        self.no_data_color = Some(color);
+        self
+    }
+
+    /// Classes of the symbol from the lowest to the highest values.
+    pub fn legend(&self) -> &[ChoroplethClass] {
+        &self.classes
+    }
+
+    /// Returns the class of the value. Values outside the range of the classes belong to the first or last class.
+    pub fn class_of(&self, value: f64) -> Option<&ChoroplethClass> {
+        let last = self.classes.len().checked_sub(1)?;
+        let index = self.classes[..last].partition_point(|class| class.max < value);
+        Some(&self.classes[index])
+    }
+}
+
+impl<F: FeatureAttributes> Symbol<F> for ChoroplethSymbol {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let color = feature
+            .attribute(&self.attribute)
+            .and_then(|value| value.as_f64())
+            .and_then(|value| self.class_of(value))
+            .map(|class| class.color)
+            .or(self.no_data_color);
+        let Some(color) = color else {
+            return vec![];
+        };
+
+        SimplePolygonSymbol::new(color)
+            .with_stroke_color(self.stroke_color)
+            .with_stroke_width(self.stroke_width)
+            .render(feature, geometry, min_resolution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::AttributeValue;
+    use galileo_types::cartesian::Point3d;
+
+    #[test]
+    fn classifications() {
+        let values = [1.0, 2.0, 3.0, 10.0, 11.0, 12.0, 20.0, 21.0, 22.0];
+
+        assert_eq!(
+            Classification::EqualInterval.breaks(values, 3),
+            vec![1.0, 8.0, 15.0, 22.0]
+        );
+        assert_eq!(
+            Classification::Quantile.breaks((1..=10).map(f64::from), 2),
+            vec![1.0, 5.0, 10.0]
+        );
+        assert_eq!(
+            Classification::Jenks.breaks(values.into_iter().rev(), 3),
+            vec![1.0, 3.0, 12.0, 22.0]
+        );
+
+        assert_eq!(
+            Classification::Quantile.breaks([1.0, 1.0, 1.0, 5.0], 4),
+            vec![1.0, 1.0, 5.0]
+        );
+        assert_eq!(Classification::Jenks.breaks([4.0], 3), vec![4.0, 4.0]);
+        assert!(Classification::Jenks.breaks([f64::NAN], 3).is_empty());
+    }
+
+    struct Region(Option<f64>);
+
+    impl FeatureAttributes for Region {
+        fn attribute(&self, _name: &str) -> Option<AttributeValue> {
+            self.0.map(AttributeValue::Number)
+        }
+    }
+
+    #[test]
+    fn symbol_fills_polygons_by_class() {
+        let ramp = Ramp::linear([(0.0, Color::BLACK), (1.0, Color::WHITE)]);
+        let symbol = ChoroplethSymbol::with_breaks("value", vec![0.0, 10.0, 20.0, 30.0], &ramp);
+        let colors: Vec<_> = symbol.legend().iter().map(|class| class.color).collect();
+        assert_eq!(
+            colors,
+            vec![Color::BLACK, Color::rgba(128, 128, 128, 255), Color::WHITE]
+        );
+        assert_eq!(symbol.class_of(10.0).unwrap().min, 0.0);
+        assert_eq!(symbol.class_of(10.5).unwrap().min, 10.0);
+        assert_eq!(symbol.class_of(100.0).unwrap().min, 20.0);
+
+        let geometry = Geom::Polygon(Polygon::from(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+        ]));
+        let fill_color = |region: &Region, symbol: &ChoroplethSymbol| {
+            let primitives = symbol.render(region, &geometry, 1.0);
+            primitives.first().map(|primitive| {
+                let RenderPrimitive::Polygon(_, paint) = primitive else {
+                    panic!("expected polygon primitive");
+                };
+                paint.color
+            })
+        };
+
+        assert_eq!(fill_color(&Region(Some(25.0)), &symbol), Some(Color::WHITE));
+        assert_eq!(fill_color(&Region(None), &symbol), None);
+        let symbol = symbol.with_no_data_color(Color::RED);
+        assert_eq!(fill_color(&Region(None), &symbol), Some(Color::RED));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use galileo_types::cartesian::Point3d;
+use galileo_types::impls::{Contour, Polygon};
+use maybe_sync::{MaybeSend, MaybeSync};
+
+/// Cluster symbol is used to draw a group of point features that are close to each other on the screen, when the
+/// [`FeatureLayer`](super::super::FeatureLayer) is rendered with
+/// [clustering](super::super::FeatureLayer::with_clustering).
+///
+/// Any closure that receives the features of a cluster and returns a list of [`PointPaint`] can be used as a cluster
+/// symbol. The paints are drawn at the center of the cluster.
+///
+/// ```
+/// use galileo::render::point_paint::PointPaint;
+/// use galileo::Color;
+/// use galileo::symbol::ClusterSymbol;
+///
+/// struct Shop;
+///
+/// fn cluster_symbol() -> impl ClusterSymbol<Shop> {
+///     |shops: &[&Shop]| {
+///         let size = 10.0 + (shops.len() as f32).log2() * 4.0;
+///         vec![PointPaint::circle(Color::BLUE, size).with_outline(Color::WHITE, 2.0)]
+///     }
+/// }
+/// ```
+pub trait ClusterSymbol<F>: MaybeSend + MaybeSync {
+    /// Converts the given `features` of a cluster into set of primitives that should be rendered to the map.
+    /// `position` is the center of the cluster in the map CRS. There are always at least two features in a cluster.
+    ///
+    /// The `min_resolution` argument specifies the map resolution that the cluster was created for.
+    fn render<'a>(
+        &self,
+        features: &[&F],
+        position: &'a Point3d,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, f64, Point3d, Contour<Point3d>, Polygon<Point3d>>>;
+}
+
+impl<F, T> ClusterSymbol<F> for T
+where
+    T: Fn(&[&F]) -> Vec<PointPaint<'static>> + MaybeSend + MaybeSync,
+{
+    fn render<'a>(
+        &self,
+        features: &[&F],
+        position: &'a Point3d,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, f64, Point3d, Contour<Point3d>, Polygon<Point3d>>> {
+        self(features)
+            .into_iter()
+            .map(|paint| RenderPrimitive::new_point_ref(position, paint))
+            .collect()
+    }
+}
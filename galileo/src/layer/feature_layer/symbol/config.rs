@@ -0,0 +1,139 @@
+use crate::render::render_bundle::RenderPrimitive;
+use crate::symbol::{
+    ArbitraryGeometrySymbol, CirclePointSymbol, SimpleContourSymbol, SimplePolygonSymbol, Symbol,
+};
+use galileo_types::cartesian::NewCartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use num_traits::{AsPrimitive, Float};
+
+/// One of the built-in symbols, selected at runtime.
+///
+/// This type allows loading the style of a [`FeatureLayer`](crate::layer::FeatureLayer) from a configuration file.
+/// With `serde` feature the symbol is (de)serialized with the `type` field naming the variant and the fields of the
+/// inner symbol next to it:
+///
+/// ```json
+/// { "type": "circle_point", "color": "#FF0000FF", "size": 8.0, "outline_color": "#000000", "outline_width": 1.0 }
+/// ```
+///
+/// [`Symbol`] trait has a generic render method and so cannot be used as a trait object. Instead of converting into a
+/// `Box<dyn Symbol>`, the config implements [`Symbol`] itself by delegating to the inner symbol, so it can be given
+/// to a feature layer directly.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum SymbolConfig {
+    /// See [`CirclePointSymbol`].
+    CirclePoint(CirclePointSymbol),
+    /// See [`SimpleContourSymbol`].
+    SimpleContour(SimpleContourSymbol),
+    /// See [`SimplePolygonSymbol`].
+    SimplePolygon(SimplePolygonSymbol),
+    /// See [`ArbitraryGeometrySymbol`].
+    ArbitraryGeometry(ArbitraryGeometrySymbol),
+}
+
+impl From<CirclePointSymbol> for SymbolConfig {
+    fn from(value: CirclePointSymbol) -> Self {
+        Self::CirclePoint(value)
+    }
+}
+
+impl From<SimpleContourSymbol> for SymbolConfig {
+    fn from(value: SimpleContourSymbol) -> Self {
+        Self::SimpleContour(value)
+    }
+}
+
+impl From<SimplePolygonSymbol> for SymbolConfig {
+    fn from(value: SimplePolygonSymbol) -> Self {
+        Self::SimplePolygon(value)
+    }
+}
+
+impl From<ArbitraryGeometrySymbol> for SymbolConfig {
+    fn from(value: ArbitraryGeometrySymbol) -> Self {
+        Self::ArbitraryGeometry(value)
+    }
+}
+
+impl<F> Symbol<F> for SymbolConfig {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        match self {
+            Self::CirclePoint(symbol) => symbol.render(feature, geometry, min_resolution),
+            Self::SimpleContour(symbol) => symbol.render(feature, geometry, min_resolution),
+            Self::SimplePolygon(symbol) => symbol.render(feature, geometry, min_resolution),
+            Self::ArbitraryGeometry(symbol) => symbol.render(feature, geometry, min_resolution),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn round_trip() {
+        let configs = vec![
+            SymbolConfig::from(
+                CirclePointSymbol::new(Color::RED, 8.0)
+                    .with_outline_color(Color::BLACK)
+                    .with_outline_width(1.5),
+            ),
+            SimpleContourSymbol::new(Color::from_hex("#10203040"), 2.0).into(),
+            SimplePolygonSymbol::new(Color::BLUE)
+                .with_stroke_color(Color::GREEN)
+                .with_stroke_width(3.0)
+                .with_stroke_offset(-1.0)
+                .into(),
+            ArbitraryGeometrySymbol::default().into(),
+        ];
+
+        let json = serde_json::to_string(&configs).unwrap();
+        let restored: Vec<SymbolConfig> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, configs);
+    }
+
+    #[test]
+    fn deserialize_hand_written_config() {
+        let config: SymbolConfig = serde_json::from_str(
+            r##"{"type": "simple_polygon", "fill_color": "#ff000080", "stroke_color": "#000000", "stroke_width": 1.0}"##,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            SymbolConfig::SimplePolygon(
+                SimplePolygonSymbol::new(Color::rgba(255, 0, 0, 128))
+                    .with_stroke_color(Color::BLACK)
+                    .with_stroke_width(1.0)
+            )
+        );
+
+        let config: SymbolConfig =
+            serde_json::from_str(r##"{"type": "circle_point", "color": "#00ff00", "size": 5.0}"##)
+                .unwrap();
+        assert_eq!(
+            config,
+            SymbolConfig::CirclePoint(CirclePointSymbol::new(Color::GREEN, 5.0))
+        );
+    }
+
+    #[test]
+    fn invalid_color_is_an_error() {
+        let result = serde_json::from_str::<SymbolConfig>(
+            r##"{"type": "simple_contour", "color": "green", "width": 1.0}"##,
+        );
+        assert!(result.is_err());
+    }
+}
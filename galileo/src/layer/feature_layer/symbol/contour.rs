@@ -1,6 +1,6 @@
 use crate::layer::feature_layer::symbol::Symbol;
 use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{LineCap, LinePaint};
+use crate::render::{DashPattern, LineArrows, LineCap, LineJoin, LinePaint};
 use crate::Color;
 use galileo_types::cartesian::CartesianPoint3d;
 use galileo_types::geometry::Geom;
@@ -9,18 +9,66 @@ use galileo_types::MultiContour;
 use num_traits::AsPrimitive;
 
 /// Renders a contour as a line of fixed width.
-#[derive(Debug, Copy, Clone)]
+///
+/// The line can be dashed with a [`DashPattern`] and decorated with arrowheads showing its direction.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimpleContourSymbol {
     /// Color of the line.
     pub color: Color,
     /// Width of the line in pixels.
     pub width: f64,
+    /// Style of the line ends.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub line_cap: LineCap,
+    /// Style of the joins between line segments.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub line_join: LineJoin,
+    /// Dash pattern of the line. If not set, the line is solid.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dash: Option<DashPattern>,
+    /// Arrowheads drawn along the line.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub arrows: Option<LineArrows>,
 }
 
 impl SimpleContourSymbol {
     /// Creates a new instance.
     pub fn new(color: Color, width: f64) -> Self {
-        Self { color, width }
+        Self {
+            color,
+            width,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Round,
+            dash: None,
+            arrows: None,
+        }
+    }
+
+    /// Creates a new symbol from a copy of the current, but with the given line cap.
+    pub fn with_line_cap(&self, line_cap: LineCap) -> Self {
+        Self { line_cap, ..*self }
+    }
+
+    /// Creates a new symbol from a copy of the current, but with the given line join.
+    pub fn with_line_join(&self, line_join: LineJoin) -> Self {
+        Self { line_join, ..*self }
+    }
+
+    /// Creates a new symbol from a copy of the current, but with the given dash pattern.
+    pub fn with_dash(&self, dash: DashPattern) -> Self {
+        Self {
+            dash: Some(dash),
+            ..*self
+        }
+    }
+
+    /// Creates a new symbol from a copy of the current, but with the given arrowheads.
+    pub fn with_arrows(&self, arrows: LineArrows) -> Self {
+        Self {
+            arrows: Some(arrows),
+            ..*self
+        }
     }
 }
 
@@ -39,7 +87,10 @@ impl<F> Symbol<F> for SimpleContourSymbol {
             color: self.color,
             width: self.width,
             offset: 0.0,
-            line_cap: LineCap::Butt,
+            line_cap: self.line_cap,
+            line_join: self.line_join,
+            dash: self.dash,
+            arrows: self.arrows,
         };
 
         match geometry {
@@ -0,0 +1,503 @@
+use crate::layer::feature_layer::{AttributeValue, FeatureAttributes};
+use crate::render::render_bundle::RenderPrimitive;
+use crate::symbol::{CirclePointSymbol, SimpleContourSymbol, SimplePolygonSymbol, Symbol};
+use crate::Color;
+use galileo_types::cartesian::{NewCartesianPoint3d, Point2d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint};
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use num_traits::{AsPrimitive, Float};
+
+/// Value that can be interpolated between two [`Ramp`] stops.
+pub trait Interpolate: Clone {
+    /// Returns the value at `ratio` (from `0.0` to `1.0`) of the way from `self` to `other`.
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        self + (other - self) * ratio
+    }
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        self + (other - self) * ratio as f32
+    }
+}
+
+impl Interpolate for Point2d {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        Point2d::new(
+            self.x.interpolate(&other.x, ratio),
+            self.y.interpolate(&other.y, ratio),
+        )
+    }
+}
+
+/// Longitude is interpolated the short way, so the point moving over the antimeridian does not go around the globe.
+impl Interpolate for GeoPoint2d {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        let lon_delta = (other.lon() - self.lon() + 540.0).rem_euclid(360.0) - 180.0;
+        let lon = (self.lon() + lon_delta * ratio + 540.0).rem_euclid(360.0) - 180.0;
+        GeoPoint2d::latlon(self.lat().interpolate(&other.lat(), ratio), lon)
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, ratio: f64) -> Self {
+        let from = self.to_u8_array();
+        let to = other.to_u8_array();
+        let channel =
+            |i: usize| (from[i] as f64 + (to[i] as f64 - from[i] as f64) * ratio).round() as u8;
+        Color::rgba(channel(0), channel(1), channel(2), channel(3))
+    }
+}
+
+/// Booleans are not interpolated: the value of the lower stop is used up to the next stop.
+impl Interpolate for bool {
+    fn interpolate(&self, _other: &Self, _ratio: f64) -> Self {
+        *self
+    }
+}
+
+/// Mapping of numeric input values to output values, set by a list of `(input, output)` stops.
+///
+/// Inputs before the first stop and after the last stop get the outputs of the first and the last stop respectively.
+/// Between the stops the output is either interpolated linearly ([`Ramp::linear`]) or taken from the lower stop
+/// ([`Ramp::step`]).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ramp<T> {
+    stops: Vec<(f64, T)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    interpolate: bool,
+}
+
+impl<T: Interpolate> Ramp<T> {
+    /// Creates a ramp interpolating the outputs between the stops. The stops do not have to be sorted.
+    pub fn linear(stops: impl IntoIterator<Item = (f64, T)>) -> Self {
+        Self::new(stops, true)
+    }
+
+    /// Creates a ramp changing the output only at the stops. The stops do not have to be sorted.
+    pub fn step(stops: impl IntoIterator<Item = (f64, T)>) -> Self {
+        Self::new(stops, false)
+    }
+
+    fn new(stops: impl IntoIterator<Item = (f64, T)>, interpolate: bool) -> Self {
+        let mut stops: Vec<_> = stops.into_iter().collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops, interpolate }
+    }
+
+    /// Stops of the ramp sorted by the input value.
+    pub fn stops(&self) -> &[(f64, T)] {
+        &self.stops
+    }
+
+    /// Output for the given `input`. Returns `None` if the ramp has no stops.
+    pub fn value_at(&self, input: f64) -> Option<T> {
+        let (first_stop, first) = self.stops.first()?;
+        if input <= *first_stop {
+            return Some(first.clone());
+        }
+
+        for pair in self.stops.windows(2) {
+            let ((from_stop, from), (to_stop, to)) = (&pair[0], &pair[1]);
+            if input < *to_stop {
+                return Some(if self.interpolate {
+                    from.interpolate(to, (input - from_stop) / (to_stop - from_stop))
+                } else {
+                    from.clone()
+                });
+            }
+        }
+
+        self.stops.last().map(|(_, value)| value.clone())
+    }
+}
+
+/// Built-in color ramps. The colors are set for inputs from `0.0` to `1.0` and interpolated between the stops.
+impl Ramp<Color> {
+    /// Perceptually uniform ramp from dark purple through blue and green to yellow (matplotlib `viridis`).
+    pub fn viridis() -> Self {
+        Self::evenly_spaced(&[
+            Color::from_hex("#440154"),
+            Color::from_hex("#472d7b"),
+            Color::from_hex("#3b528b"),
+            Color::from_hex("#2c728e"),
+            Color::from_hex("#21918c"),
+            Color::from_hex("#28ae80"),
+            Color::from_hex("#5ec962"),
+            Color::from_hex("#addc30"),
+            Color::from_hex("#fde725"),
+        ])
+    }
+
+    /// Sequential ramp from almost white to dark blue (ColorBrewer `Blues`).
+    pub fn blues() -> Self {
+        Self::evenly_spaced(&[
+            Color::from_hex("#f7fbff"),
+            Color::from_hex("#deebf7"),
+            Color::from_hex("#c6dbef"),
+            Color::from_hex("#9ecae1"),
+            Color::from_hex("#6baed6"),
+            Color::from_hex("#4292c6"),
+            Color::from_hex("#2171b5"),
+            Color::from_hex("#08519c"),
+            Color::from_hex("#08306b"),
+        ])
+    }
+
+    /// Diverging ramp from dark red through light gray at `0.5` to dark blue (ColorBrewer `RdBu`).
+    pub fn diverging() -> Self {
+        Self::evenly_spaced(&[
+            Color::from_hex("#b2182b"),
+            Color::from_hex("#d6604d"),
+            Color::from_hex("#f4a582"),
+            Color::from_hex("#fddbc7"),
+            Color::from_hex("#f7f7f7"),
+            Color::from_hex("#d1e5f0"),
+            Color::from_hex("#92c5de"),
+            Color::from_hex("#4393c3"),
+            Color::from_hex("#2166ac"),
+        ])
+    }
+
+    fn evenly_spaced(colors: &[Color]) -> Self {
+        let step = 1.0 / (colors.len() - 1) as f64;
+        Self::linear(
+            colors
+                .iter()
+                .enumerate()
+                .map(|(i, color)| (i as f64 * step, *color)),
+        )
+    }
+}
+
+/// Value of a symbol property computed for every feature from its [attributes](FeatureAttributes) or from the
+/// resolution it is rendered at.
+///
+/// Constants convert into expressions, so a property that does not depend on the feature can be set by its value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Expression<T> {
+    /// Same value for all features.
+    Constant(T),
+    /// Value of the ramp for the numeric attribute with the given name.
+    ByAttribute(String, Ramp<T>),
+    /// Value of the first case equal to the attribute with the given name, or the default value if none is equal.
+    Match {
+        /// Name of the attribute.
+        attribute: String,
+        /// `(attribute value, output)` pairs.
+        cases: Vec<(AttributeValue, T)>,
+        /// Output for features without a matching case, including the ones that don't have the attribute.
+        default: T,
+    },
+    /// Value of the ramp for the map resolution the feature is rendered at.
+    ByResolution(Ramp<T>),
+}
+
+impl<T> From<T> for Expression<T> {
+    fn from(value: T) -> Self {
+        Self::Constant(value)
+    }
+}
+
+impl<T: Interpolate> Expression<T> {
+    /// Creates an expression mapping the numeric attribute with the given name through the ramp.
+    pub fn by_attribute(attribute: impl Into<String>, ramp: Ramp<T>) -> Self {
+        Self::ByAttribute(attribute.into(), ramp)
+    }
+
+    /// Creates an expression selecting the value by the attribute with the given name.
+    pub fn match_attribute(
+        attribute: impl Into<String>,
+        cases: impl IntoIterator<Item = (AttributeValue, T)>,
+        default: T,
+    ) -> Self {
+        Self::Match {
+            attribute: attribute.into(),
+            cases: cases.into_iter().collect(),
+            default,
+        }
+    }
+
+    /// Computes the value for the `feature` rendered at the given `resolution`.
+    ///
+    /// Returns `None` if the value cannot be computed, e.g. the attribute of [`Expression::ByAttribute`] is not set or
+    /// is not a number.
+    pub fn evaluate(&self, feature: &impl FeatureAttributes, resolution: f64) -> Option<T> {
+        match self {
+            Self::Constant(value) => Some(value.clone()),
+            Self::ByAttribute(attribute, ramp) => {
+                ramp.value_at(feature.attribute(attribute)?.as_f64()?)
+            }
+            Self::Match {
+                attribute,
+                cases,
+                default,
+            } => {
+                let value = feature.attribute(attribute);
+                let output = cases
+                    .iter()
+                    .find(|(case, _)| value.as_ref() == Some(case))
+                    .map_or(default, |(_, output)| output);
+                Some(output.clone())
+            }
+            Self::ByResolution(ramp) => ramp.value_at(resolution),
+        }
+    }
+}
+
+/// Renders any type of geometry with properties computed from the feature [attributes](FeatureAttributes) and the
+/// map resolution.
+///
+/// The symbol draws points as circles, contours as lines and polygons as filled areas, like
+/// [`ArbitraryGeometrySymbol`](crate::symbol::ArbitraryGeometrySymbol), but every property is an [`Expression`]:
+///
+/// ```
+/// use galileo::symbol::{Expression, ExpressionSymbol, Ramp};
+/// use galileo::Color;
+///
+/// let symbol = ExpressionSymbol::new(Expression::by_attribute(
+///     "population",
+///     Ramp::linear([(0.0, Color::rgba(255, 255, 0, 255)), (1e6, Color::RED)]),
+/// ))
+/// .with_size(Expression::by_attribute(
+///     "population",
+///     Ramp::step([(0.0, 4.0), (1e5, 8.0), (1e6, 12.0)]),
+/// ))
+/// .with_visible(Expression::ByResolution(Ramp::step([
+///     (0.0, true),
+///     (1000.0, false),
+/// ])));
+/// ```
+///
+/// A feature is not drawn if any of the properties used for its geometry type cannot be evaluated.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExpressionSymbol {
+    /// Color of points, lines and the inner area of polygons.
+    pub color: Expression<Color>,
+    /// Outline color of points and polygons. If not set, they are drawn without outline.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stroke_color: Option<Expression<Color>>,
+    /// Diameter of points in pixels.
+    pub size: Expression<f64>,
+    /// Width of lines and of the outlines in pixels.
+    pub width: Expression<f64>,
+    /// Whether the feature is drawn.
+    pub visible: Expression<bool>,
+}
+
+impl ExpressionSymbol {
+    /// Creates a new symbol with the given color, points of 5 pixels, lines of 1 pixel and no outlines.
+    pub fn new(color: impl Into<Expression<Color>>) -> Self {
+        Self {
+            color: color.into(),
+            stroke_color: None,
+            size: Expression::Constant(5.0),
+            width: Expression::Constant(1.0),
+            visible: Expression::Constant(true),
+        }
+    }
+
+    /// Sets the outline color of points and polygons.
+    pub fn with_stroke_color(mut self, stroke_color: impl Into<Expression<Color>>) -> Self {
+        self.stroke_color = Some(stroke_color.into());
+        self
+    }
+
+    /// Sets the diameter of points.
+    pub fn with_size(mut self, size: impl Into<Expression<f64>>) -> Self {
+        self.size = size.into();
+        self
+    }
+
+    /// Sets the width of lines and outlines.
+    pub fn with_width(mut self, width: impl Into<Expression<f64>>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the visibility of the features.
+    pub fn with_visible(mut self, visible: impl Into<Expression<bool>>) -> Self {
+        self.visible = visible.into();
+        self
+    }
+
+    fn outline(&self, feature: &impl FeatureAttributes, resolution: f64) -> Option<(Color, f64)> {
+        match &self.stroke_color {
+            Some(stroke_color) => Some((
+                stroke_color.evaluate(feature, resolution)?,
+                self.width.evaluate(feature, resolution)?,
+            )),
+            None => Some((Color::TRANSPARENT, 0.0)),
+        }
+    }
+}
+
+impl<F: FeatureAttributes> Symbol<F> for ExpressionSymbol {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        if self.visible.evaluate(feature, min_resolution) != Some(true) {
+            return vec![];
+        }
+
+        let Some(color) = self.color.evaluate(feature, min_resolution) else {
+            return vec![];
+        };
+
+        let primitives = match geometry {
+            Geom::Point(_) | Geom::MultiPoint(_) => self
+                .size
+                .evaluate(feature, min_resolution)
+                .zip(self.outline(feature, min_resolution))
+                .map(|(size, (outline_color, outline_width))| {
+                    CirclePointSymbol::new(color, size)
+                        .with_outline_color(outline_color)
+                        .with_outline_width(outline_width)
+                        .render(feature, geometry, min_resolution)
+                }),
+            Geom::Contour(_) | Geom::MultiContour(_) => {
+                self.width.evaluate(feature, min_resolution).map(|width| {
+                    SimpleContourSymbol::new(color, width).render(feature, geometry, min_resolution)
+                })
+            }
+            Geom::Polygon(_) | Geom::MultiPolygon(_) => {
+                self.outline(feature, min_resolution)
+                    .map(|(stroke_color, stroke_width)| {
+                        SimplePolygonSymbol::new(color)
+                            .with_stroke_color(stroke_color)
+                            .with_stroke_width(stroke_width)
+                            .render(feature, geometry, min_resolution)
+                    })
+            }
+        };
+
+        primitives.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::point_paint::PointShape;
+    use assert_matches::assert_matches;
+    use galileo_types::cartesian::Point3d;
+    use std::collections::HashMap;
+
+    struct City(HashMap<&'static str, AttributeValue>);
+
+    impl FeatureAttributes for City {
+        fn attribute(&self, name: &str) -> Option<AttributeValue> {
+            self.0.get(name).cloned()
+        }
+    }
+
+    fn city(population: f64, kind: &str) -> City {
+        City(HashMap::from([
+            ("population", population.into()),
+            ("kind", kind.into()),
+        ]))
+    }
+
+    #[test]
+    fn ramps() {
+        let linear = Ramp::linear([(10.0, 20.0), (0.0, 0.0)]);
+        assert_eq!(linear.value_at(-1.0), Some(0.0));
+        assert_eq!(linear.value_at(2.5), Some(5.0));
+        assert_eq!(linear.value_at(11.0), Some(20.0));
+
+        let step = Ramp::step([(0.0, Color::RED), (10.0, Color::BLUE)]);
+        assert_eq!(step.value_at(9.9), Some(Color::RED));
+        assert_eq!(step.value_at(10.0), Some(Color::BLUE));
+
+        assert_eq!(Ramp::<f64>::linear([]).value_at(1.0), None);
+
+        let viridis = Ramp::viridis();
+        assert_eq!(viridis.value_at(0.0), Some(Color::from_hex("#440154")));
+        assert_eq!(viridis.value_at(1.0), Some(Color::from_hex("#fde725")));
+        assert_eq!(
+            Ramp::diverging().value_at(0.5),
+            Some(Color::from_hex("#f7f7f7"))
+        );
+    }
+
+    #[test]
+    fn expressions() {
+        let by_population = Expression::by_attribute(
+            "population",
+            Ramp::linear([(0.0, Color::BLACK), (100.0, Color::rgba(200, 0, 0, 255))]),
+        );
+        assert_eq!(
+            by_population.evaluate(&city(25.0, "town"), 1.0),
+            Some(Color::rgba(50, 0, 0, 255))
+        );
+        assert_eq!(by_population.evaluate(&City(HashMap::new()), 1.0), None);
+        assert_eq!(
+            Expression::by_attribute("kind", Ramp::step([(0.0, 1.0)]))
+                .evaluate(&city(25.0, "town"), 1.0),
+            None
+        );
+
+        let by_kind = Expression::match_attribute("kind", [("capital".into(), 10.0)], 5.0);
+        assert_eq!(by_kind.evaluate(&city(1.0, "capital"), 1.0), Some(10.0));
+        assert_eq!(by_kind.evaluate(&city(1.0, "town"), 1.0), Some(5.0));
+
+        let by_resolution = Expression::ByResolution(Ramp::step([(0.0, true), (100.0, false)]));
+        assert_eq!(by_resolution.evaluate(&city(1.0, "town"), 50.0), Some(true));
+        assert_eq!(
+            by_resolution.evaluate(&city(1.0, "town"), 150.0),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn symbol_evaluates_properties_per_feature() {
+        let symbol = ExpressionSymbol::new(Expression::match_attribute(
+            "kind",
+            [("capital".into(), Color::RED)],
+            Color::BLUE,
+        ))
+        .with_size(Expression::by_attribute(
+            "population",
+            Ramp::linear([(0.0, 2.0), (100.0, 12.0)]),
+        ))
+        .with_visible(Expression::ByResolution(Ramp::step([
+            (0.0, true),
+            (10.0, false),
+        ])));
+        let geometry = Geom::Point(Point3d::new(0.0, 0.0, 0.0));
+
+        let primitives = symbol.render(&city(50.0, "capital"), &geometry, 1.0);
+        let [RenderPrimitive::Point(_, paint)] = &primitives[..] else {
+            panic!("expected point primitive");
+        };
+        assert_matches!(
+            &paint.shape,
+            PointShape::Circle { fill, radius, outline: None }
+                if fill.center_color == Color::RED && *radius == 3.5
+        );
+
+        assert!(symbol
+            .render(&city(50.0, "capital"), &geometry, 20.0)
+            .is_empty());
+        assert!(symbol
+            .render(&City(HashMap::new()), &geometry, 1.0)
+            .is_empty());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::{PolygonFill, PolygonPaint};
+use crate::Color;
+use galileo_types::cartesian::NewCartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use galileo_types::{Contour as _, MultiPolygon, Polygon as _};
+use num_traits::{AsPrimitive, Float};
+use std::marker::PhantomData;
+
+/// Renders a polygon as a 3D block: a roof raised to the height of the feature and walls connecting it to the ground.
+/// This can be used to draw building footprints as buildings when the map is tilted.
+///
+/// The height is returned by a callback in the map units, so it can be based on the feature attributes. If the
+/// callback returns `None` or a height that is not positive, the polygon is drawn flat with the roof color.
+///
+/// Walls are shaded depending on their direction, as if the light came from the north-west, so the sides of the
+/// blocks can be distinguished. Blocks are drawn after the flat lines and polygons of the layer and with the depth
+/// test, so the nearer blocks correctly cover the farther ones when the map is tilted.
+///
+/// ```
+/// use galileo::symbol::ExtrudedPolygonSymbol;
+/// use galileo::Color;
+///
+/// struct Building {
+///     levels: Option<u32>,
+/// }
+///
+/// let symbol = ExtrudedPolygonSymbol::new(Color::rgba(200, 190, 180, 255), |building: &Building| {
+///     building.levels.map(|levels| levels as f64 * 3.0)
+/// })
+/// .with_wall_color(Color::rgba(160, 150, 140, 255));
+/// ```
+pub struct ExtrudedPolygonSymbol<F, HeightFn>
+where
+    HeightFn: Fn(&F) -> Option<f64>,
+{
+    roof_color: Color,
+    wall_color: Color,
+    height: HeightFn,
+    _phantom: PhantomData<fn(&F)>,
+}
+
+impl<F, HeightFn> ExtrudedPolygonSymbol<F, HeightFn>
+where
+    HeightFn: Fn(&F) -> Option<f64>,
+{
+    /// Creates a new instance. Walls are drawn with the same color as the roof until
+    /// [`ExtrudedPolygonSymbol::with_wall_color`] is set.
+    pub fn new(roof_color: Color, height: HeightFn) -> Self {
+        Self {
+            roof_color,
+            wall_color: roof_color,
+            height,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Sets the base color of the walls. The walls are drawn darker or lighter than this color depending on their
+    /// direction.
+    pub fn with_wall_color(mut self, wall_color: Color) -> Self {
+        self.wall_color = wall_color;
+        self
+    }
+
+    fn render_poly<'a, N, P>(
+        &self,
+        polygon: &'a Polygon<P>,
+        height: Option<f64>,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let roof_paint = PolygonPaint {
+            color: self.roof_color,
+            fill: PolygonFill::Solid,
+        };
+        let Some(height) = height
+            .filter(|height| *height > 0.0)
+            .and_then(|height| N::from(height))
+        else {
+            return vec![RenderPrimitive::new_polygon_ref(polygon, roof_paint)];
+        };
+
+        let raise = |point: &P| P::new(point.x(), point.y(), point.z() + height);
+
+        let mut primitives = vec![];
+        for (index, contour) in polygon.iter_contours().enumerate() {
+            // Left side of the edges of a counterclockwise outer contour is inside the polygon, while for the holes
+            // the inside is on the right side.
+            let is_hole = index > 0;
+            let outward_sign = if (signed_area(contour) > 0.0) != is_hole {
+                1.0
+            } else {
+                -1.0
+            };
+
+            for segment in contour.iter_segments() {
+                let (from, to) = (segment.0, segment.1);
+                let dx = (to.x() - from.x()).as_() as f64;
+                let dy = (to.y() - from.y()).as_() as f64;
+                let length = dx.hypot(dy);
+                if length == 0.0 {
+                    continue;
+                }
+
+                let normal = (dy / length * outward_sign, -dx / length * outward_sign);
+                let wall =
+                    ClosedContour::new(vec![from.clone(), to.clone(), raise(to), raise(from)]);
+                primitives.push(RenderPrimitive::new_polygon(
+                    Polygon::new(wall, vec![]),
+                    PolygonPaint {
+                        color: shade(self.wall_color, normal),
+                        fill: PolygonFill::Solid,
+                    },
+                ));
+            }
+        }
+
+        let roof = Polygon::new(
+            ClosedContour::new(polygon.outer_contour().iter_points().map(raise).collect()),
+            polygon
+                .inner_contours()
+                .map(|contour| ClosedContour::new(contour.iter_points().map(raise).collect()))
+                .collect(),
+        );
+        primitives.push(RenderPrimitive::new_polygon(roof, roof_paint));
+
+        primitives
+    }
+}
+
+impl<F, HeightFn> Symbol<F> for ExtrudedPolygonSymbol<F, HeightFn>
+where
+    HeightFn: Fn(&F) -> Option<f64>,
+{
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let height = (self.height)(feature);
+        match geometry {
+            Geom::Polygon(polygon) => self.render_poly(polygon, height),
+            Geom::MultiPolygon(polygons) => polygons
+                .polygons()
+                .flat_map(|polygon| self.render_poly(polygon, height))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Doubled signed area of the contour projected to the ground. Positive for counterclockwise contours.
+fn signed_area<N, P>(contour: &ClosedContour<P>) -> f64
+where
+    N: AsPrimitive<f32> + Float,
+    P: NewCartesianPoint3d<N>,
+{
+    contour
+        .iter_segments()
+        .map(|segment| {
+            let (from, to) = (segment.0, segment.1);
+            (from.x().as_() as f64) * (to.y().as_() as f64)
+                - (to.x().as_() as f64) * (from.y().as_() as f64)
+        })
+        .sum()
+}
+
+/// Makes the wall color lighter if the wall faces the light coming from the north-west, and darker otherwise.
+fn shade(color: Color, normal: (f64, f64)) -> Color {
+    let light = std::f64::consts::FRAC_1_SQRT_2;
+    let factor = 0.8 + 0.2 * (-normal.0 * light + normal.1 * light);
+    let [r, g, b, a] = color.to_u8_array();
+    let channel = |value: u8| (value as f64 * factor).round().min(255.0) as u8;
+    Color::rgba(channel(r), channel(g), channel(b), a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::Point3d;
+
+    fn square() -> Geom<Point3d> {
+        Geom::Polygon(Polygon::new(
+            ClosedContour::new(vec![
+                Point3d::new(0.0, 0.0, 0.0),
+                Point3d::new(10.0, 0.0, 0.0),
+                Point3d::new(10.0, 10.0, 0.0),
+                Point3d::new(0.0, 10.0, 0.0),
+            ]),
+            vec![],
+        ))
+    }
+
+    fn polygons<'a>(
+        primitives: &'a [RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>],
+    ) -> Vec<&'a Polygon<Point3d>> {
+        primitives
+            .iter()
+            .map(|primitive| match primitive {
+                RenderPrimitive::Polygon(polygon, _) => &**polygon,
+                _ => panic!("expected polygon"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extrudes_walls_and_roof() {
+        let symbol = ExtrudedPolygonSymbol::new(Color::RED, |height: &f64| Some(*height));
+        let geometry = square();
+        let primitives = symbol.render(&15.0, &geometry, 1.0);
+        let polygons = polygons(&primitives);
+
+        assert_eq!(polygons.len(), 5);
+        for wall in &polygons[..4] {
+            let heights: Vec<_> = wall.outer_contour().iter_points().map(|p| p.z).collect();
+            assert_eq!(heights, [0.0, 0.0, 15.0, 15.0]);
+        }
+        assert!(polygons[4]
+            .outer_contour()
+            .iter_points()
+            .all(|point| point.z == 15.0));
+    }
+
+    #[test]
+    fn without_height_draws_flat_polygon() {
+        let symbol = ExtrudedPolygonSymbol::new(Color::RED, |height: &f64| Some(*height));
+        let geometry = square();
+        assert_eq!(symbol.render(&0.0, &geometry, 1.0).len(), 1);
+
+        let symbol = ExtrudedPolygonSymbol::new(Color::RED, |_: &()| None);
+        assert_eq!(symbol.render(&(), &geometry, 1.0).len(), 1);
+    }
+
+    #[test]
+    fn walls_facing_light_are_lighter() {
+        let color = Color::rgba(100, 100, 100, 255);
+        let north = shade(color, (0.0, 1.0)).to_u8_array()[0];
+        let west = shade(color, (-1.0, 0.0)).to_u8_array()[0];
+        let south = shade(color, (0.0, -1.0)).to_u8_array()[0];
+        assert_eq!(north, west);
+        assert!(north > south);
+    }
+}
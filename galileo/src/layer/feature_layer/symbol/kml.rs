@@ -0,0 +1,184 @@
+use crate::decoded_image::DecodedImage;
+use crate::layer::feature_layer::feature::KmlFeature;
+use crate::layer::feature_layer::symbol::{
+    CirclePointSymbol, SimpleContourSymbol, SimplePolygonSymbol, Symbol,
+};
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::Color;
+use galileo_types::cartesian::NewCartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::MultiPoint;
+use nalgebra::Vector2;
+use num_traits::{AsPrimitive, Float};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Diameter in pixels of the circle that is drawn for a point without an icon image, at icon scale `1`.
+const DEFAULT_POINT_SIZE: f64 = 10.0;
+
+/// Renders [`KmlFeature`]s with their KML styles.
+///
+/// * Points are drawn with the icon image of the style, if the symbol has an image for its `href`, centered on the
+///   point and scaled by the icon scale. Otherwise, a circle of the icon color is drawn.
+/// * Lines are drawn with the line color and width.
+/// * Polygons are filled with the poly color and outlined with the line color and width, unless the style disables
+///   filling or outlining.
+///
+/// Values not set in a style are replaced with the KML defaults: white color, line width of 1 pixel, scale of 1,
+/// and both fill and outline enabled.
+///
+/// A symbol with the icons of a KMZ archive is created by [`KmlDocument::symbol`](crate::layer::feature_layer::KmlDocument::symbol).
+#[derive(Debug, Clone, Default)]
+pub struct KmlSymbol {
+    icons: HashMap<String, Arc<DecodedImage>>,
+}
+
+impl KmlSymbol {
+    /// Creates a new instance without icon images.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an image that is used for the points with the given icon `href`.
+    pub fn with_icon(mut self, href: impl Into<String>, image: Arc<DecodedImage>) -> Self {
+        self.icons.insert(href.into(), image);
+        self
+    }
+}
+
+impl Symbol<KmlFeature> for KmlSymbol {
+    fn render<'a, N, P>(
+        &self,
+        feature: &KmlFeature,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let style = &feature.style;
+        let line_color = style.line_color.unwrap_or(Color::WHITE);
+        let line_width = style.line_width.unwrap_or(1.0);
+
+        match geometry {
+            Geom::Point(_) | Geom::MultiPoint(_) => {
+                let scale = style.icon_scale.unwrap_or(1.0);
+                let icon = style
+                    .icon_href
+                    .as_ref()
+                    .and_then(|href| self.icons.get(href));
+                let Some(icon) = icon else {
+                    return CirclePointSymbol::new(
+                        style.icon_color.unwrap_or(Color::WHITE),
+                        DEFAULT_POINT_SIZE * scale,
+                    )
+                    .with_outline_color(Color::BLACK)
+                    .with_outline_width(1.0)
+                    .render(feature, geometry, min_resolution);
+                };
+
+                let paint = PointPaint::image(icon.clone(), Vector2::new(0.5, 0.5), scale as f32);
+                match geometry {
+                    Geom::Point(point) => vec![RenderPrimitive::new_point_ref(point, paint)],
+                    Geom::MultiPoint(points) => points
+                        .iter_points()
+                        .map(|point| RenderPrimitive::new_point_ref(point, paint.clone()))
+                        .collect(),
+                    _ => vec![],
+                }
+            }
+            Geom::Contour(_) | Geom::MultiContour(_) => SimpleContourSymbol::new(
+                line_color, line_width,
+            )
+            .render(feature, geometry, min_resolution),
+            Geom::Polygon(_) | Geom::MultiPolygon(_) => {
+                let fill_color = match style.fill {
+                    Some(false) => Color::TRANSPARENT,
+                    _ => style.poly_color.unwrap_or(Color::WHITE),
+                };
+                let stroke_width = match style.outline {
+                    Some(false) => 0.0,
+                    _ => line_width,
+                };
+                SimplePolygonSymbol::new(fill_color)
+                    .with_stroke_color(line_color)
+                    .with_stroke_width(stroke_width)
+                    .render(feature, geometry, min_resolution)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::feature::KmlStyle;
+    use crate::render::point_paint::PointShape;
+    use assert_matches::assert_matches;
+    use galileo_types::cartesian::Point3d;
+    use galileo_types::geo::impls::GeoPoint2d;
+    use galileo_types::geo::NewGeoPoint;
+
+    fn feature(style: KmlStyle) -> KmlFeature {
+        KmlFeature {
+            name: None,
+            description: None,
+            geometry: Geom::Point(GeoPoint2d::latlon(0.0, 0.0)),
+            style,
+        }
+    }
+
+    #[test]
+    fn point_uses_icon_if_available() {
+        let style = KmlStyle {
+            icon_href: Some("icon.png".into()),
+            icon_scale: Some(2.0),
+            ..Default::default()
+        };
+        let geometry = Geom::Point(Point3d::new(0.0, 0.0, 0.0));
+
+        let primitives = KmlSymbol::new().render(&feature(style.clone()), &geometry, 1.0);
+        let [RenderPrimitive::Point(_, paint)] = &primitives[..] else {
+            panic!("expected point primitive");
+        };
+        assert_matches!(paint.shape, PointShape::Circle { radius, .. } if radius == 10.0);
+
+        let image = DecodedImage::from_raw(vec![0; 16], 2, 2).unwrap();
+        let symbol = KmlSymbol::new().with_icon("icon.png", Arc::new(image));
+        let primitives = symbol.render(&feature(style), &geometry, 1.0);
+        let [RenderPrimitive::Point(_, paint)] = &primitives[..] else {
+            panic!("expected point primitive");
+        };
+        assert_matches!(paint.shape, PointShape::Image { width, .. } if width == 4.0);
+    }
+
+    #[test]
+    fn polygon_without_fill_is_transparent() {
+        let style = KmlStyle {
+            poly_color: Some(Color::RED),
+            fill: Some(false),
+            ..Default::default()
+        };
+        let geometry = Geom::Polygon(Polygon::new(
+            galileo_types::impls::ClosedContour::new(vec![
+                Point3d::new(0.0, 0.0, 0.0),
+                Point3d::new(1.0, 0.0, 0.0),
+                Point3d::new(1.0, 1.0, 0.0),
+            ]),
+            vec![],
+        ));
+
+        let primitives = KmlSymbol::new().render(&feature(style), &geometry, 1.0);
+        let [RenderPrimitive::Polygon(_, paint), RenderPrimitive::Contour(_, line)] =
+            &primitives[..]
+        else {
+            panic!("expected polygon and outline primitives");
+        };
+        assert_eq!(paint.color, Color::TRANSPARENT);
+        assert_eq!(line.color, Color::WHITE);
+        assert_eq!(line.width, 1.0);
+    }
+}
@@ -0,0 +1,601 @@
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::text::TextStyle;
+use galileo_types::cartesian::NewCartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::{Contour as _, MultiContour, MultiPoint, MultiPolygon, Polygon as _};
+use num_traits::{AsPrimitive, Float};
+use std::f64::consts::PI;
+use std::marker::PhantomData;
+
+/// Renders a text label for every feature. The text is returned by a callback, so it can be based on the feature
+/// attributes. If the callback returns `None` or an empty string, the feature is not labeled.
+///
+/// Points are labeled at their position, lines at the middle of their length and polygons at the centroid of their
+/// outer contour. Every part of a multi-geometry gets its own label. The size of the labels is set in pixels and does
+/// not depend on the map resolution.
+///
+/// To draw both the feature geometry and its label, combine the label symbol with another symbol in a tuple.
+///
+/// With [`LabelSymbol::with_line_placement`] the labels of lines follow the line instead, like the names of streets
+/// and rivers.
+///
+/// Labels overlapping other labels on the screen are hidden based on their priority (see
+/// [`LabelPlacer`](crate::layer::feature_layer::LabelPlacer)). The priority is set by [`TextStyle::priority`] and can
+/// be set for every feature separately with [`LabelSymbol::with_priority`].
+///
+/// ```no_run
+/// use galileo::render::text::{Font, TextStyle};
+/// use galileo::symbol::{CirclePointSymbol, LabelSymbol};
+/// use galileo::Color;
+///
+/// struct City {
+///     name: String,
+/// }
+///
+/// let font = Font::from_path("fonts/NotoSans-Regular.ttf").unwrap();
+/// let style = TextStyle::new(font, 14.0).with_halo(Color::WHITE, 2.0);
+/// let symbol = (
+///     CirclePointSymbol::new(Color::RED, 6.0),
+///     LabelSymbol::new(style, |city: &City| Some(city.name.clone())),
+/// );
+/// ```
+pub struct LabelSymbol<F, TextFn, PriorityFn = fn(&F) -> f32>
+where
+    TextFn: Fn(&F) -> Option<String>,
+    PriorityFn: Fn(&F) -> f32,
+{
+    style: TextStyle,
+    text: TextFn,
+    priority: Option<PriorityFn>,
+    line_placement: Option<LinePlacement>,
+    _phantom: PhantomData<fn(&F)>,
+}
+
+/// Placement of labels along lines, see [`LabelSymbol::with_line_placement`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinePlacement {
+    /// Distance between the centers of the labels repeated along a line in pixels. If `None`, a line gets one label
+    /// in its middle.
+    pub repeat_distance: Option<f32>,
+    /// Maximum angle between adjacent glyphs in radians. Labels are not placed where the line turns more sharply.
+    pub max_angle: f32,
+}
+
+impl Default for LinePlacement {
+    /// One label per line, with glyphs turning by at most 45 degrees.
+    fn default() -> Self {
+        Self {
+            repeat_distance: None,
+            max_angle: std::f32::consts::FRAC_PI_4,
+        }
+    }
+}
+
+impl LinePlacement {
+    /// Sets the distance between the repeated labels in pixels.
+    pub fn with_repeat_distance(mut self, repeat_distance: f32) -> Self {
+        self.repeat_distance = Some(repeat_distance);
+        self
+    }
+
+    /// Sets the maximum angle between adjacent glyphs in radians.
+    pub fn with_max_angle(mut self, max_angle: f32) -> Self {
+        self.max_angle = max_angle;
+        self
+    }
+}
+
+impl<F, TextFn> LabelSymbol<F, TextFn>
+where
+    TextFn: Fn(&F) -> Option<String>,
+{
+    /// Creates a new instance.
+    pub fn new(style: TextStyle, text: TextFn) -> Self {
+        Self {
+            style,
+            text,
+            priority: None,
+            line_placement: None,
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<F, TextFn, PriorityFn> LabelSymbol<F, TextFn, PriorityFn>
+where
+    TextFn: Fn(&F) -> Option<String>,
+    PriorityFn: Fn(&F) -> f32,
+{
+    /// Sets a callback returning the placement priority of the label of a feature. It overrides the priority set in
+    /// the [`TextStyle`].
+    pub fn with_priority<Priority>(self, priority: Priority) -> LabelSymbol<F, TextFn, Priority>
+    where
+        Priority: Fn(&F) -> f32,
+    {
+        LabelSymbol {
+            style: self.style,
+            text: self.text,
+            priority: Some(priority),
+            line_placement: self.line_placement,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Places the labels of lines along the lines, following their curvature.
+    ///
+    /// Every glyph of the label is rotated to the direction of the line under it. Labels are flipped to be read from
+    /// left to right, so they are not shown upside down on a map that is not rotated. The label anchor and offset of
+    /// the [`TextStyle`] are not used: the labels are centered on the line. Lines shorter than the label are not
+    /// labeled.
+    ///
+    /// The glyphs are laid out for the resolution of the map that the layer renders the features with, so at other
+    /// resolutions they are a bit further apart or closer to each other.
+    pub fn with_line_placement(mut self, placement: LinePlacement) -> Self {
+        self.line_placement = Some(placement);
+        self
+    }
+
+    /// Style of the labels.
+    pub fn style(&self) -> &TextStyle {
+        &self.style
+    }
+}
+
+impl<F, TextFn, PriorityFn> Symbol<F> for LabelSymbol<F, TextFn, PriorityFn>
+where
+    TextFn: Fn(&F) -> Option<String>,
+    PriorityFn: Fn(&F) -> f32,
+{
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let Some(text) = (self.text)(feature).filter(|text| !text.is_empty()) else {
+            return vec![];
+        };
+
+        if let Some(placement) = &self.line_placement {
+            let contours: Vec<&Contour<P>> = match geometry {
+                Geom::Contour(contour) => vec![contour],
+                Geom::MultiContour(contours) => contours.contours().collect(),
+                _ => vec![],
+            };
+            if !contours.is_empty() {
+                let priority = self.priority.as_ref().map(|priority| priority(feature));
+                return self.line_labels(&text, &contours, placement, priority, min_resolution);
+            }
+        }
+
+        let mut paint = PointPaint::label(&text, &self.style);
+        if let (Some(priority), Some(placement)) = (&self.priority, &mut paint.placement) {
+            placement.priority = priority(feature);
+        }
+
+        let anchors: Vec<P> = match geometry {
+            Geom::Point(point) => return vec![RenderPrimitive::new_point_ref(point, paint)],
+            Geom::MultiPoint(points) => {
+                return points
+                    .iter_points()
+                    .map(|point| RenderPrimitive::new_point_ref(point, paint.clone()))
+                    .collect()
+            }
+            Geom::Contour(contour) => line_middle(contour).into_iter().collect(),
+            Geom::MultiContour(contours) => contours.contours().filter_map(line_middle).collect(),
+            Geom::Polygon(polygon) => polygon_center(polygon).into_iter().collect(),
+            Geom::MultiPolygon(polygons) => {
+                polygons.polygons().filter_map(polygon_center).collect()
+            }
+        };
+
+        anchors
+            .into_iter()
+            .map(|point| RenderPrimitive::new_point(point, paint.clone()))
+            .collect()
+    }
+}
+
+impl<F, TextFn, PriorityFn> LabelSymbol<F, TextFn, PriorityFn>
+where
+    TextFn: Fn(&F) -> Option<String>,
+    PriorityFn: Fn(&F) -> f32,
+{
+    /// Glyphs of the labels placed along the contours.
+    fn line_labels<'a, N, P>(
+        &self,
+        text: &str,
+        contours: &[&Contour<P>],
+        placement: &LinePlacement,
+        priority: Option<f32>,
+        resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let (glyphs, text_width) = self.style.rasterize_glyphs(text);
+        if glyphs.is_empty() {
+            return vec![];
+        }
+
+        let label_length = text_width as f64 * resolution;
+        let shifts: Vec<f64> = glyphs
+            .iter()
+            .map(|glyph| (glyph.position - text_width / 2.0) as f64 * resolution)
+            .collect();
+
+        let mut primitives = vec![];
+        for contour in contours {
+            let Some(line) = MeasuredLine::new(contour) else {
+                continue;
+            };
+            for center in line.label_centers(label_length, placement, resolution) {
+                let Some(placed) = line.place_glyphs(center, &shifts, label_length) else {
+                    continue;
+                };
+                if !turns_within(&placed, placement.max_angle as f64) {
+                    continue;
+                }
+
+                for (i, (glyph, ([x, y, z], angle))) in glyphs.iter().zip(placed).enumerate() {
+                    let (Some(x), Some(y), Some(z)) = (N::from(x), N::from(y), N::from(z)) else {
+                        continue;
+                    };
+                    let mut paint =
+                        PointPaint::label_glyph(glyph, angle as f32, &self.style, i > 0);
+                    if let (Some(priority), Some(label_placement)) =
+                        (priority, &mut paint.placement)
+                    {
+                        label_placement.priority = priority;
+                    }
+                    primitives.push(RenderPrimitive::new_point(P::new(x, y, z), paint));
+                }
+            }
+        }
+
+        primitives
+    }
+}
+
+/// Points of a line with the distances along the line from its start.
+struct MeasuredLine {
+    points: Vec<[f64; 3]>,
+    distances: Vec<f64>,
+}
+
+impl MeasuredLine {
+    fn new<N: Float, P: NewCartesianPoint3d<N>>(contour: &Contour<P>) -> Option<Self> {
+        let points: Vec<[f64; 3]> = contour
+            .iter_points_closing()
+            .map(|p| Some([p.x().to_f64()?, p.y().to_f64()?, p.z().to_f64()?]))
+            .collect::<Option<_>>()?;
+        let mut distances = Vec::with_capacity(points.len());
+        let mut distance = 0.0;
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                let prev = points[i - 1];
+                distance += (point[0] - prev[0]).hypot(point[1] - prev[1]);
+            }
+            distances.push(distance);
+        }
+
+        (distance > 0.0).then_some(Self { points, distances })
+    }
+
+    fn length(&self) -> f64 {
+        self.distances[self.distances.len() - 1]
+    }
+
+    /// Distances of the centers of the labels of the given length along the line. Labels are distributed evenly.
+    fn label_centers(
+        &self,
+        label_length: f64,
+        placement: &LinePlacement,
+        resolution: f64,
+    ) -> Vec<f64> {
+        let length = self.length();
+        if label_length > length {
+            return vec![];
+        }
+
+        let mut count = match placement.repeat_distance {
+            Some(distance) if distance > 0.0 => {
+                (length / (distance as f64 * resolution)).floor() as usize
+            }
+            _ => 1,
+        };
+        if label_length > 0.0 {
+            count = count.min((length / label_length).floor() as usize);
+        }
+        let count = count.max(1);
+
+        let step = length / count as f64;
+        (0..count).map(|i| (i as f64 + 0.5) * step).collect()
+    }
+
+    /// Position and direction angle of the line at the given distance from its start.
+    fn point_at(&self, distance: f64) -> Option<([f64; 3], f64)> {
+        let index = self
+            .distances
+            .partition_point(|d| *d < distance)
+            .clamp(1, self.points.len() - 1);
+        let (segment_start, segment_end) = (self.distances[index - 1], self.distances[index]);
+        let segment = segment_end - segment_start;
+        if !(segment_start..=segment_end).contains(&distance) || segment <= 0.0 {
+            return None;
+        }
+
+        let (a, b) = (self.points[index - 1], self.points[index]);
+        let ratio = (distance - segment_start) / segment;
+        let point = [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * ratio);
+        Some((point, (b[1] - a[1]).atan2(b[0] - a[0])))
+    }
+
+    /// Positions and rotation angles of the glyphs of the label with the given center, with the glyphs shifted from
+    /// the center along the line. If the label would be drawn upside down, it is flipped to the other direction of
+    /// the line.
+    fn place_glyphs(
+        &self,
+        center: f64,
+        shifts: &[f64],
+        label_length: f64,
+    ) -> Option<Vec<([f64; 3], f64)>> {
+        let (start, _) = self.point_at(center - label_length / 2.0)?;
+        let (end, _) = self.point_at(center + label_length / 2.0)?;
+        let flip = end[0] < start[0];
+
+        shifts
+            .iter()
+            .map(|shift| {
+                let distance = if flip { center - shift } else { center + shift };
+                let (point, angle) = self.point_at(distance)?;
+                Some((point, if flip { angle + PI } else { angle }))
+            })
+            .collect()
+    }
+}
+
+/// Checks that the direction changes by no more than `max_angle` between adjacent glyphs.
+fn turns_within(glyphs: &[([f64; 3], f64)], max_angle: f64) -> bool {
+    glyphs.windows(2).all(|pair| {
+        let turn = (pair[1].1 - pair[0].1).rem_euclid(2.0 * PI);
+        turn.min(2.0 * PI - turn) <= max_angle
+    })
+}
+
+fn interpolate<N: Float, P: NewCartesianPoint3d<N>>(a: &P, b: &P, ratio: N) -> P {
+    P::new(
+        a.x() + (b.x() - a.x()) * ratio,
+        a.y() + (b.y() - a.y()) * ratio,
+        a.z() + (b.z() - a.z()) * ratio,
+    )
+}
+
+/// Point at the half of the length of the line.
+fn line_middle<N: Float, P: NewCartesianPoint3d<N>>(contour: &Contour<P>) -> Option<P> {
+    let points: Vec<&P> = contour.iter_points_closing().collect();
+    let segment_length = |a: &P, b: &P| (b.x() - a.x()).hypot(b.y() - a.y());
+    let length = points.windows(2).fold(N::zero(), |acc, pair| {
+        acc + segment_length(pair[0], pair[1])
+    });
+
+    let mut remaining = length / (N::one() + N::one());
+    for pair in points.windows(2) {
+        let segment = segment_length(pair[0], pair[1]);
+        if remaining <= segment && segment > N::zero() {
+            return Some(interpolate(pair[0], pair[1], remaining / segment));
+        }
+        remaining = remaining - segment;
+    }
+
+    points.first().map(|p| P::new(p.x(), p.y(), p.z()))
+}
+
+/// Centroid of the outer contour of the polygon.
+fn polygon_center<N: Float, P: NewCartesianPoint3d<N>>(polygon: &Polygon<P>) -> Option<P> {
+    let points: Vec<&P> = polygon.outer_contour().iter_points().collect();
+    let count = N::from(points.len())?;
+    if points.is_empty() {
+        return None;
+    }
+
+    let (mut area, mut x, mut y, mut z) = (N::zero(), N::zero(), N::zero(), N::zero());
+    for (i, point) in points.iter().enumerate() {
+        let next = points[(i + 1) % points.len()];
+        let cross = point.x() * next.y() - next.x() * point.y();
+        area = area + cross;
+        x = x + (point.x() + next.x()) * cross;
+        y = y + (point.y() + next.y()) * cross;
+        z = z + point.z();
+    }
+
+    let z = z / count;
+    if area == N::zero() {
+        let x = points.iter().fold(N::zero(), |acc, p| acc + p.x()) / count;
+        let y = points.iter().fold(N::zero(), |acc, p| acc + p.y()) / count;
+        return Some(P::new(x, y, z));
+    }
+
+    let divider = area * N::from(3)?;
+    Some(P::new(x / divider, y / divider, z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::point_paint::PointShape;
+    use crate::render::text::tests::test_font;
+    use galileo_types::cartesian::{CartesianPoint3d, Point3d};
+    use galileo_types::impls::ClosedContour;
+
+    struct Named(Option<&'static str>);
+
+    fn symbol() -> LabelSymbol<Named, impl Fn(&Named) -> Option<String>> {
+        LabelSymbol::new(TextStyle::new(test_font(), 12.0), |feature: &Named| {
+            feature.0.map(String::from)
+        })
+    }
+
+    fn anchors(geometry: &Geom<Point3d>) -> Vec<Point3d> {
+        symbol()
+            .render(&Named(Some("name")), geometry, 1.0)
+            .into_iter()
+            .map(|primitive| {
+                let RenderPrimitive::Point(point, paint) = primitive else {
+                    panic!("expected point primitive");
+                };
+                assert!(matches!(paint.shape, PointShape::Image { .. }));
+                point.into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn labels_point() {
+        let point = Point3d::new(1.0, 2.0, 0.0);
+        assert_eq!(anchors(&Geom::Point(point)), vec![point]);
+    }
+
+    #[test]
+    fn labels_line_middle() {
+        let line = Geom::Contour(Contour::open(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(2.0, 0.0, 0.0),
+            Point3d::new(2.0, 6.0, 0.0),
+        ]));
+        assert_eq!(anchors(&line), vec![Point3d::new(2.0, 2.0, 0.0)]);
+    }
+
+    #[test]
+    fn labels_polygon_centroid() {
+        let polygon = Geom::Polygon(Polygon::new(
+            ClosedContour::new(vec![
+                Point3d::new(0.0, 0.0, 1.0),
+                Point3d::new(4.0, 0.0, 1.0),
+                Point3d::new(4.0, 2.0, 1.0),
+                Point3d::new(0.0, 2.0, 1.0),
+            ]),
+            vec![],
+        ));
+        let anchors = anchors(&polygon);
+        assert_eq!(anchors.len(), 1);
+        assert_eq!(
+            (anchors[0].x(), anchors[0].y(), anchors[0].z()),
+            (2.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn sets_priority_per_feature() {
+        let symbol =
+            symbol().with_priority(|feature: &Named| feature.0.map_or(0.0, |v| v.len() as f32));
+        let point = Geom::Point(Point3d::new(1.0, 2.0, 0.0));
+        let primitives = symbol.render(&Named(Some("name")), &point, 1.0);
+        let RenderPrimitive::Point(_, paint) = &primitives[0] else {
+            panic!("expected point primitive");
+        };
+        assert_eq!(paint.placement.map(|p| p.priority), Some(4.0));
+    }
+
+    #[test]
+    fn skips_features_without_text() {
+        let point = Geom::Point(Point3d::new(1.0, 2.0, 0.0));
+        assert!(symbol().render(&Named(None), &point, 1.0).is_empty());
+        assert!(symbol().render(&Named(Some("")), &point, 1.0).is_empty());
+    }
+
+    fn placed_glyphs(
+        symbol: &impl Symbol<Named>,
+        geometry: &Geom<Point3d>,
+    ) -> Vec<(Point3d, f32, bool)> {
+        symbol
+            .render(&Named(Some("abc")), geometry, 1.0)
+            .into_iter()
+            .map(|primitive| {
+                let RenderPrimitive::Point(point, paint) = primitive else {
+                    panic!("expected point primitive");
+                };
+                let PointShape::Image {
+                    map_rotation: Some(rotation),
+                    ..
+                } = paint.shape
+                else {
+                    panic!("expected rotated image");
+                };
+                let continues = paint.placement.unwrap().continues_previous;
+                (point.into_owned(), rotation, continues)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn places_glyphs_along_line() {
+        let symbol = symbol().with_line_placement(LinePlacement::default());
+        let line = |points: Vec<Point3d>| Geom::Contour(Contour::open(points));
+
+        let glyphs = placed_glyphs(
+            &symbol,
+            &line(vec![
+                Point3d::new(0.0, 0.0, 0.0),
+                Point3d::new(0.0, 100.0, 0.0),
+            ]),
+        );
+        assert_eq!(glyphs.len(), 3);
+        assert_eq!(
+            glyphs.iter().map(|g| g.2).collect::<Vec<_>>(),
+            vec![false, true, true]
+        );
+        assert!(glyphs[0].0.y() < 50.0 && glyphs[2].0.y() > 50.0);
+        assert!(glyphs.iter().all(|g| g.0.x() == 0.0));
+        assert!((glyphs[1].0.y() - 50.0).abs() < 0.5);
+        assert!(glyphs
+            .iter()
+            .all(|g| (g.1 - std::f32::consts::FRAC_PI_2).abs() < 1e-6));
+
+        // A line going to the west is labeled from west to east, so that the label is not upside down.
+        let glyphs = placed_glyphs(
+            &symbol,
+            &line(vec![
+                Point3d::new(100.0, 0.0, 0.0),
+                Point3d::new(0.0, 0.0, 0.0),
+            ]),
+        );
+        assert!(glyphs[0].0.x() < glyphs[2].0.x());
+        assert!(glyphs.iter().all(|g| g.1.cos() > 0.999));
+
+        // Labels do not fit on too short lines and are not placed over sharp turns.
+        let short = line(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(5.0, 0.0, 0.0),
+        ]);
+        assert!(placed_glyphs(&symbol, &short).is_empty());
+        let turn = line(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(20.0, 0.0, 0.0),
+            Point3d::new(20.0, -20.0, 0.0),
+        ]);
+        assert!(placed_glyphs(&symbol, &turn).is_empty());
+    }
+
+    #[test]
+    fn repeats_line_labels() {
+        let symbol =
+            symbol().with_line_placement(LinePlacement::default().with_repeat_distance(200.0));
+        let line = Geom::Contour(Contour::open(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1000.0, 0.0, 0.0),
+        ]));
+        let glyphs = placed_glyphs(&symbol, &line);
+        assert_eq!(glyphs.len(), 15);
+        assert_eq!(glyphs.iter().filter(|g| !g.2).count(), 5);
+        assert!((glyphs[1].0.x() - 100.0).abs() < 0.5);
+    }
+}
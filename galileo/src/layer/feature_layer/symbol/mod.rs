@@ -2,20 +2,45 @@
 //! [`Symbol`] trait is designed to be easy to implement, so an application may provide rendering logic for the
 //! features it uses. But a few simple implementations are provided for convenience.
 
-use num_traits::AsPrimitive;
+use num_traits::{AsPrimitive, Float};
 
 mod arbitrary;
+mod callback;
+mod choropleth;
+mod cluster;
+mod config;
 mod contour;
+mod expression;
+mod extruded;
+#[cfg(feature = "kml")]
+mod kml;
+mod label;
 mod point;
 mod polygon;
+mod state;
+#[cfg(feature = "svg")]
+mod svg;
 
 pub use arbitrary::ArbitraryGeometrySymbol;
+pub use callback::CallbackSymbol;
+pub use choropleth::{ChoroplethClass, ChoroplethSymbol, Classification};
+pub use cluster::ClusterSymbol;
+pub use config::SymbolConfig;
 pub use contour::SimpleContourSymbol;
+pub use expression::{Expression, ExpressionSymbol, Interpolate, Ramp};
+pub use extruded::ExtrudedPolygonSymbol;
+#[cfg(feature = "kml")]
+pub use kml::KmlSymbol;
+pub use label::{LabelSymbol, LinePlacement};
 pub use point::{CirclePointSymbol, ImagePointSymbol};
-pub use polygon::SimplePolygonSymbol;
+pub use polygon::{PatternPolygonSymbol, SimplePolygonSymbol};
+pub use state::FeatureStateSymbol;
+#[cfg(feature = "svg")]
+pub use svg::SvgMarkerSymbol;
 
+use crate::layer::feature_layer::FeatureState;
 use crate::render::render_bundle::RenderPrimitive;
-use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::cartesian::NewCartesianPoint3d;
 use galileo_types::geometry::Geom;
 use galileo_types::impls::{Contour, Polygon};
 
@@ -38,6 +63,67 @@ pub trait Symbol<F> {
         min_resolution: f64,
     ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
     where
-        N: AsPrimitive<f32>,
-        P: CartesianPoint3d<Num = N> + Clone;
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone;
+
+    /// Same as [`Symbol::render`], but for a feature in the given render `state`. Hidden features are not rendered,
+    /// so the `state` is never [`FeatureState::Hidden`].
+    ///
+    /// When the state of a feature changes, only the style of its primitives is updated, so this method must return
+    /// the same number and types of primitives for all states. Point primitives can change their shape between the
+    /// states though. The default implementation ignores the state.
+    fn render_with_state<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+        state: FeatureState,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let _ = state;
+        self.render(feature, geometry, min_resolution)
+    }
+}
+
+/// A pair of symbols renders a feature with both of them. The primitives of the second symbol are drawn on top of the
+/// first one, so a pair can be used, for example, to add a [`LabelSymbol`] to the geometry drawn by another symbol.
+impl<F, A: Symbol<F>, B: Symbol<F>> Symbol<F> for (A, B) {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let mut primitives = self.0.render(feature, geometry, min_resolution);
+        primitives.extend(self.1.render(feature, geometry, min_resolution));
+        primitives
+    }
+
+    fn render_with_state<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+        state: FeatureState,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        let mut primitives = self
+            .0
+            .render_with_state(feature, geometry, min_resolution, state);
+        primitives.extend(
+            self.1
+                .render_with_state(feature, geometry, min_resolution, state),
+        );
+        primitives
+    }
 }
@@ -16,19 +16,49 @@ use galileo_types::impls::{Contour, Polygon};
 #[cfg(not(target_arch = "wasm32"))]
 use std::ops::Deref;
 
-/// Renders a point as a circle of fixes size.
-#[derive(Debug, Copy, Clone)]
+/// Renders a point as a circle of fixes size with an optional outline.
+///
+/// The size of the circle is set in pixels and does not depend on the map resolution.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CirclePointSymbol {
     /// Color of the circle.
     pub color: Color,
-    /// Diameter of the circle.
+    /// Diameter of the circle in pixels.
     pub size: f64,
+    /// Color of the outline.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub outline_color: Color,
+    /// Width of the outline in pixels. If set to `0`, the outline is not drawn.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub outline_width: f64,
 }
 
 impl CirclePointSymbol {
     /// Create a new instance.
     pub fn new(color: Color, size: f64) -> Self {
-        Self { color, size }
+        Self {
+            color,
+            size,
+            outline_color: Default::default(),
+            outline_width: 0.0,
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given outline color.
+    pub fn with_outline_color(&self, outline_color: Color) -> Self {
+        Self {
+            outline_color,
+            ..*self
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with the given outline width.
+    pub fn with_outline_width(&self, outline_width: f64) -> Self {
+        Self {
+            outline_width,
+            ..*self
+        }
     }
 }
 
@@ -43,7 +73,11 @@ impl<F> Symbol<F> for CirclePointSymbol {
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N> + Clone,
     {
-        let paint = PointPaint::circle(self.color, self.size as f32);
+        let mut paint = PointPaint::circle(self.color, self.size as f32);
+        if self.outline_width > 0.0 {
+            paint = paint.with_outline(self.outline_color, self.outline_width as f32);
+        }
+
         match geometry {
             Geom::Point(point) => vec![RenderPrimitive::new_point_ref(point, paint)],
             Geom::MultiPoint(points) => points
@@ -109,6 +143,41 @@ impl<F> Symbol<F> for ImagePointSymbol {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::point_paint::PointShape;
+    use assert_matches::assert_matches;
+    use galileo_types::cartesian::Point3d;
+    use galileo_types::impls::MultiPoint;
+
+    #[test]
+    fn circle_symbol_outline() {
+        let point = Geom::Point(Point3d::new(1.0, 1.0, 0.0));
+
+        let symbol = CirclePointSymbol::new(Color::BLUE, 10.0);
+        let primitives = symbol.render(&(), &point, 1.0);
+        assert_eq!(primitives.len(), 1);
+        let RenderPrimitive::Point(_, paint) = &primitives[0] else {
+            panic!("expected point primitive");
+        };
+        assert_matches!(paint.shape, PointShape::Circle { radius, outline: None, .. } if radius == 5.0);
+
+        let symbol = symbol
+            .with_outline_color(Color::RED)
+            .with_outline_width(2.0);
+        let primitives = symbol.render(&(), &point, 1.0);
+        let RenderPrimitive::Point(_, paint) = &primitives[0] else {
+            panic!("expected point primitive");
+        };
+        assert_matches!(
+            paint.shape,
+            PointShape::Circle { outline: Some(outline), .. } if outline.color == Color::RED && outline.width == 2.0
+        );
+
+        let points = Geom::MultiPoint(MultiPoint::from(vec![
+            Point3d::new(1.0, 1.0, 0.0),
+            Point3d::new(2.0, 2.0, 0.0),
+        ]));
+        assert_eq!(symbol.render(&(), &points, 1.0).len(), 2);
+    }
 
     #[test]
     fn image_symbol_from_file() {
@@ -1,25 +1,35 @@
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
 use crate::layer::feature_layer::symbol::Symbol;
 use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{LineCap, LinePaint, PolygonPaint};
+use crate::render::{HatchFill, LineCap, LineJoin, LinePaint, PolygonFill, PolygonPaint};
 use crate::Color;
 use galileo_types::cartesian::CartesianPoint3d;
 use galileo_types::geometry::Geom;
 use galileo_types::impls::Contour;
 use galileo_types::{MultiPolygon, Polygon};
 use num_traits::AsPrimitive;
+use std::sync::Arc;
 
 /// Renders a polygon geometry as a filled polygon with an outline.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimplePolygonSymbol {
     /// Color of the inner area of the polygon.
     pub fill_color: Color,
     /// Color of the outline.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub stroke_color: Color,
     /// Width of the outline in pixels.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub stroke_width: f64,
     /// Offset of the outline in pixels. Positive offset will move outline outside of the polygon, negative offset
     /// will move the outline inside the polygon.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub stroke_offset: f64,
+    /// If set, the inner area is hatched with lines of the fill color instead of being filled solid.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fill_hatch: Option<HatchFill>,
 }
 
 impl SimplePolygonSymbol {
@@ -30,6 +40,7 @@ impl SimplePolygonSymbol {
             stroke_color: Default::default(),
             stroke_width: 0.0,
             stroke_offset: 0.0,
+            fill_hatch: None,
         }
     }
 
@@ -57,6 +68,14 @@ impl SimplePolygonSymbol {
         }
     }
 
+    /// Creates a new instance from a copy of the current, but with the inner area hatched.
+    pub fn with_fill_hatch(&self, fill_hatch: HatchFill) -> Self {
+        Self {
+            fill_hatch: Some(fill_hatch),
+            ..*self
+        }
+    }
+
     fn render_poly<'a, N, P>(
         &self,
         polygon: &'a galileo_types::impls::Polygon<P>,
@@ -70,6 +89,9 @@ impl SimplePolygonSymbol {
             polygon,
             PolygonPaint {
                 color: self.fill_color,
+                fill: self
+                    .fill_hatch
+                    .map_or(PolygonFill::Solid, PolygonFill::Hatch),
             },
         ));
 
@@ -78,6 +100,9 @@ impl SimplePolygonSymbol {
             width: self.stroke_width,
             offset: self.stroke_offset,
             line_cap: LineCap::Butt,
+            line_join: LineJoin::Round,
+            dash: None,
+            arrows: None,
         };
 
         for contour in polygon.iter_contours() {
@@ -112,3 +137,57 @@ impl<F> Symbol<F> for SimplePolygonSymbol {
         }
     }
 }
+
+/// Renders a polygon geometry filled with an image repeated in both directions.
+///
+/// The image is drawn one image pixel per screen pixel regardless of the map resolution.
+pub struct PatternPolygonSymbol {
+    image: Arc<DecodedImage>,
+}
+
+impl PatternPolygonSymbol {
+    /// Creates a new instance with the given pattern image.
+    pub fn new(image: Arc<DecodedImage>) -> Self {
+        Self { image }
+    }
+
+    /// Loads the pattern image from the file system path.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path(path: &str) -> Result<Self, GalileoError> {
+        use image::GenericImageView;
+        let image = image::io::Reader::open(path)?.decode()?;
+        let dimensions = image.dimensions();
+
+        Ok(Self::new(Arc::new(DecodedImage {
+            bytes: image.to_rgba8().into_raw(),
+            dimensions,
+        })))
+    }
+}
+
+impl<F> Symbol<F> for PatternPolygonSymbol {
+    fn render<'a, N, P>(
+        &self,
+        _feature: &F,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, galileo_types::impls::Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let paint = PolygonPaint {
+            color: Color::WHITE,
+            fill: PolygonFill::Pattern(self.image.clone()),
+        };
+
+        match geometry {
+            Geom::Polygon(polygon) => vec![RenderPrimitive::new_polygon_ref(polygon, paint)],
+            Geom::MultiPolygon(polygons) => polygons
+                .polygons()
+                .map(|polygon| RenderPrimitive::new_polygon_ref(polygon, paint.clone()))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::layer::feature_layer::FeatureState;
+use crate::render::render_bundle::RenderPrimitive;
+use galileo_types::cartesian::NewCartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use num_traits::{AsPrimitive, Float};
+
+/// Symbol that draws the features with different symbols depending on their [`FeatureState`].
+///
+/// Features without a symbol for their state are drawn with the normal symbol. Since changing the state of a feature
+/// only updates the style of its primitives, all the symbols must produce the same primitives for a feature, e.g. by
+/// only having different colors.
+///
+/// ```
+/// use galileo::layer::feature_layer::FeatureState;
+/// use galileo::symbol::{FeatureStateSymbol, SimplePolygonSymbol};
+/// use galileo::Color;
+///
+/// let normal = SimplePolygonSymbol::new(Color::BLUE)
+///     .with_stroke_color(Color::BLACK)
+///     .with_stroke_width(1.0);
+/// let symbol = FeatureStateSymbol::new(normal)
+///     .with_hovered(normal.with_stroke_color(Color::WHITE))
+///     .with_selected(normal.with_stroke_color(Color::RED));
+///
+/// assert_eq!(symbol.symbol(FeatureState::Selected).stroke_color, Color::RED);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureStateSymbol<S> {
+    normal: S,
+    hovered: Option<S>,
+    selected: Option<S>,
+}
+
+impl<S> FeatureStateSymbol<S> {
+    /// Creates a new symbol drawing the features in all states with the `normal` symbol.
+    pub fn new(normal: S) -> Self {
+        Self {
+            normal,
+            hovered: None,
+            selected: None,
+        }
+    }
+
+    /// Sets the symbol of the hovered features.
+    pub fn with_hovered(mut self, symbol: S) -> Self {
+        self.hovered = Some(symbol);
+        self
+    }
+
+    /// Sets the symbol of the selected features.
+    pub fn with_selected(mut self, symbol: S) -> Self {
+        self.selected = Some(symbol);
+        self
+    }
+
+    /// Returns the symbol used to draw the features in the given state.
+    pub fn symbol(&self, state: FeatureState) -> &S {
+        let symbol = match state {
+            FeatureState::Hovered => self.hovered.as_ref(),
+            FeatureState::Selected => self.selected.as_ref(),
+            FeatureState::Normal | FeatureState::Hidden => None,
+        };
+
+        symbol.unwrap_or(&self.normal)
+    }
+}
+
+impl<F, S: Symbol<F>> Symbol<F> for FeatureStateSymbol<S> {
+    fn render<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        self.normal.render(feature, geometry, min_resolution)
+    }
+
+    fn render_with_state<'a, N, P>(
+        &self,
+        feature: &F,
+        geometry: &'a Geom<P>,
+        min_resolution: f64,
+        state: FeatureState,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32> + Float,
+        P: NewCartesianPoint3d<N> + Clone,
+    {
+        self.symbol(state)
+            .render_with_state(feature, geometry, min_resolution, state)
+    }
+}
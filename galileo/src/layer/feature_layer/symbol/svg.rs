@@ -0,0 +1,1007 @@
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::Color;
+use galileo_types::cartesian::CartesianPoint3d;
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{Contour, Polygon};
+use galileo_types::MultiPoint;
+use lyon::geom::{point, vector, Angle, ArcFlags, SvgArc};
+use nalgebra::Vector2;
+use num_traits::AsPrimitive;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tiny_skia::{FillRule, LineCap, LineJoin, Paint, Path, PathBuilder, Pixmap, Stroke, Transform};
+
+/// Rasterized images of an icon by their width and height in pixels.
+type RasterCache = HashMap<(u32, u32), Arc<DecodedImage>>;
+
+/// Maximum width or height of a rasterized icon in pixels.
+const MAX_RASTER_SIZE: u32 = 2048;
+
+/// Symbol that renders a point with an SVG icon. The icon size is fixed on the screen and does not depend on map
+/// resolution.
+///
+/// The icon is rasterized exactly at the size it is displayed with, which is the size of the SVG document multiplied
+/// by the symbol scale and the pixel ratio of the screen, so it stays crisp on high DPI displays. Rasterized images
+/// are cached by their pixel size, and all the features rendered with the same size share one texture.
+///
+/// Clones of the symbol share the pixel ratio and the cache, so an application can keep a clone and call
+/// [`SvgMarkerSymbol::set_pixel_ratio`] when the window is moved to a screen with a different DPI. Features rendered
+/// after that use the icon rasterized for the new pixel ratio.
+///
+/// Only a subset of SVG is supported: `path`, `rect`, `circle`, `ellipse`, `line`, `polyline` and `polygon`
+/// elements, optionally grouped with `g`, painted with solid fill and stroke colors, opacity and transforms.
+/// Gradients, text, embedded images, clipping, masks and filters are ignored.
+#[derive(Clone)]
+pub struct SvgMarkerSymbol {
+    document: Arc<SvgDocument>,
+    width: f32,
+    height: f32,
+    offset: Vector2<f32>,
+    scale: f32,
+    pixel_ratio: Arc<AtomicU32>,
+    rasterized: Arc<Mutex<RasterCache>>,
+}
+
+impl SvgMarkerSymbol {
+    /// Parses the SVG document. The size of the icon is taken from the `width` and `height` attributes of the
+    /// document, or from its `viewBox` if they are not set.
+    pub fn new(svg: &[u8], offset: Vector2<f32>, scale: f32) -> Result<Self, GalileoError> {
+        let svg = std::str::from_utf8(svg)
+            .map_err(|err| GalileoError::Generic(format!("invalid SVG document: {err}")))?;
+        let document = SvgDocument::parse(svg)?;
+
+        Ok(Self {
+            width: document.width,
+            height: document.height,
+            document: Arc::new(document),
+            offset,
+            scale,
+            pixel_ratio: Arc::new(AtomicU32::new(1.0f32.to_bits())),
+            rasterized: Default::default(),
+        })
+    }
+
+    /// Loads the SVG document from the file system path.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path(path: &str, offset: Vector2<f32>, scale: f32) -> Result<Self, GalileoError> {
+        Self::new(&std::fs::read(path)?, offset, scale)
+    }
+
+    /// Sets the size of the icon in pixels before scaling, replacing the size of the SVG document.
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the ratio between physical and logical pixels of the screen.
+    pub fn with_pixel_ratio(self, pixel_ratio: f32) -> Self {
+        self.set_pixel_ratio(pixel_ratio);
+        self
+    }
+
+    /// Ratio between physical and logical pixels of the screen.
+    pub fn pixel_ratio(&self) -> f32 {
+        f32::from_bits(self.pixel_ratio.load(Ordering::Relaxed))
+    }
+
+    /// Sets the ratio between physical and logical pixels of the screen for this symbol and all its clones.
+    ///
+    /// Non-positive values are ignored.
+    pub fn set_pixel_ratio(&self, pixel_ratio: f32) {
+        if pixel_ratio > 0.0 && pixel_ratio.is_finite() {
+            self.pixel_ratio
+                .store(pixel_ratio.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Size of the icon on the screen in pixels for the current pixel ratio.
+    pub fn pixel_size(&self) -> (u32, u32) {
+        let factor = self.scale * self.pixel_ratio();
+        let dimension = |size: f32| ((size * factor).round() as u32).clamp(1, MAX_RASTER_SIZE);
+        (dimension(self.width), dimension(self.height))
+    }
+
+    /// Returns the icon rasterized for the current pixel ratio.
+    pub fn image(&self) -> Arc<DecodedImage> {
+        let (width, height) = self.pixel_size();
+        self.rasterized
+            .lock()
+            .expect("lock is poisoned")
+            .entry((width, height))
+            .or_insert_with(|| Arc::new(self.document.rasterize(width, height)))
+            .clone()
+    }
+}
+
+impl<F> Symbol<F> for SvgMarkerSymbol {
+    fn render<'a, N, P>(
+        &self,
+        _feature: &F,
+        geometry: &'a Geom<P>,
+        _min_resolution: f64,
+    ) -> Vec<RenderPrimitive<'a, N, P, Contour<P>, Polygon<P>>>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+    {
+        let paint = PointPaint::image(self.image(), self.offset, 1.0);
+
+        match geometry {
+            Geom::Point(point) => vec![RenderPrimitive::new_point_ref(point, paint)],
+            Geom::MultiPoint(points) => points
+                .iter_points()
+                .map(|point| RenderPrimitive::new_point_ref(point, paint.clone()))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Parsed SVG document, ready to be rasterized at any size.
+struct SvgDocument {
+    width: f32,
+    height: f32,
+    /// `x`, `y`, `width`, `height` of the view box.
+    view_box: [f32; 4],
+    shapes: Vec<SvgShape>,
+}
+
+struct SvgShape {
+    path: Path,
+    transform: Transform,
+    fill: Option<(Color, FillRule)>,
+    stroke: Option<(Color, Stroke)>,
+}
+
+/// Elements which content is not rendered directly.
+const NON_RENDERED_ELEMENTS: &[&[u8]] = &[
+    b"defs",
+    b"clipPath",
+    b"mask",
+    b"marker",
+    b"pattern",
+    b"symbol",
+    b"linearGradient",
+    b"radialGradient",
+    b"filter",
+    b"text",
+    b"style",
+    b"title",
+    b"desc",
+    b"metadata",
+];
+
+impl SvgDocument {
+    fn parse(svg: &str) -> Result<Self, GalileoError> {
+        let mut reader = Reader::from_str(svg);
+        let mut document: Option<SvgDocument> = None;
+        let mut styles: Vec<PaintStyle> = vec![];
+        let mut skipped_depth = 0;
+
+        loop {
+            let event = reader.read_event().map_err(svg_error)?;
+            let (element, is_empty) = match &event {
+                Event::Start(element) => (element, false),
+                Event::Empty(element) => (element, true),
+                Event::End(_) => {
+                    if skipped_depth > 0 {
+                        skipped_depth -= 1;
+                    } else {
+                        styles.pop();
+                    }
+                    continue;
+                }
+                Event::Eof => break,
+                _ => continue,
+            };
+
+            if skipped_depth > 0 {
+                skipped_depth += usize::from(!is_empty);
+                continue;
+            }
+
+            let name = element.local_name();
+            let attributes = Attributes::read(element)?;
+            let Some(document) = &mut document else {
+                if name.as_ref() != b"svg" {
+                    return Err(svg_error("root element is not svg"));
+                }
+
+                document = Some(Self::new(&attributes)?);
+                styles.push(PaintStyle::default().apply(&attributes));
+                continue;
+            };
+
+            let parent = styles.last().cloned().unwrap_or_default();
+            if NON_RENDERED_ELEMENTS.contains(&name.as_ref()) || attributes.is_hidden() {
+                skipped_depth += usize::from(!is_empty);
+                continue;
+            }
+
+            let style = parent.apply(&attributes);
+            if let Some(path) = shape_path(name.as_ref(), &attributes) {
+                document.shapes.extend(style.shape(path));
+            }
+
+            if !is_empty {
+                styles.push(style);
+            }
+        }
+
+        document.ok_or_else(|| svg_error("document has no svg element"))
+    }
+
+    fn new(attributes: &Attributes) -> Result<Self, GalileoError> {
+        let view_box = attributes
+            .get("viewBox")
+            .map(numbers)
+            .filter(|values| values.len() == 4 && values[2] > 0.0 && values[3] > 0.0)
+            .map(|values| [values[0], values[1], values[2], values[3]]);
+        let width = attributes.length("width").or(view_box.map(|v| v[2]));
+        let height = attributes.length("height").or(view_box.map(|v| v[3]));
+        let (Some(width), Some(height)) = (width, height) else {
+            return Err(svg_error("document has neither size nor viewBox"));
+        };
+
+        if width <= 0.0 || height <= 0.0 {
+            return Err(svg_error("document size must be positive"));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            view_box: view_box.unwrap_or([0.0, 0.0, width, height]),
+            shapes: vec![],
+        })
+    }
+
+    /// Renders the document into an RGBA image of the given size, keeping the aspect ratio of the view box and
+    /// centering it in the image.
+    fn rasterize(&self, width: u32, height: u32) -> DecodedImage {
+        let mut pixmap = Pixmap::new(width, height).expect("size is checked to be valid");
+
+        let [x, y, view_width, view_height] = self.view_box;
+        let scale = (width as f32 / view_width).min(height as f32 / view_height);
+        let dx = (width as f32 - view_width * scale) / 2.0 - x * scale;
+        let dy = (height as f32 - view_height * scale) / 2.0 - y * scale;
+        let view_transform = Transform::from_row(scale, 0.0, 0.0, scale, dx, dy);
+
+        let mut paint = Paint {
+            anti_alias: true,
+            ..Default::default()
+        };
+        for shape in &self.shapes {
+            let transform = view_transform.pre_concat(shape.transform);
+            if let Some((color, fill_rule)) = shape.fill {
+                let [r, g, b, a] = color.to_u8_array();
+                paint.set_color_rgba8(r, g, b, a);
+                pixmap.fill_path(&shape.path, &paint, fill_rule, transform, None);
+            }
+            if let Some((color, stroke)) = &shape.stroke {
+                let [r, g, b, a] = color.to_u8_array();
+                paint.set_color_rgba8(r, g, b, a);
+                pixmap.stroke_path(&shape.path, &paint, stroke, transform, None);
+            }
+        }
+
+        let bytes = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+
+        DecodedImage {
+            bytes,
+            dimensions: (width, height),
+        }
+    }
+}
+
+fn svg_error(err: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid SVG document: {err}"))
+}
+
+/// Attributes of an element, with the properties of the `style` attribute overriding presentation attributes.
+struct Attributes(Vec<(String, String)>);
+
+impl Attributes {
+    fn read(element: &BytesStart) -> Result<Self, GalileoError> {
+        let mut values = vec![];
+        let mut style = None;
+        for attribute in element.attributes() {
+            let attribute = attribute.map_err(svg_error)?;
+            let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+            let value = attribute
+                .normalized_value(XmlVersion::default())
+                .map_err(svg_error)?
+                .into_owned();
+            if key == "style" {
+                style = Some(value);
+            } else {
+                values.push((key, value));
+            }
+        }
+
+        for property in style.iter().flat_map(|style| style.split(';')) {
+            if let Some((key, value)) = property.split_once(':') {
+                values.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        Ok(Self(values))
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn length(&self, key: &str) -> Option<f32> {
+        let value = self.get(key)?.trim();
+        value
+            .strip_suffix("px")
+            .unwrap_or(value)
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    fn is_hidden(&self) -> bool {
+        self.get("display") == Some("none")
+    }
+}
+
+/// Paint properties inherited from the parent elements.
+#[derive(Clone)]
+struct PaintStyle {
+    fill: Option<Color>,
+    fill_rule: FillRule,
+    fill_opacity: f32,
+    stroke: Option<Color>,
+    stroke_width: f32,
+    stroke_opacity: f32,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: f32,
+    opacity: f32,
+    transform: Transform,
+}
+
+impl Default for PaintStyle {
+    fn default() -> Self {
+        Self {
+            fill: Some(Color::BLACK),
+            fill_rule: FillRule::Winding,
+            fill_opacity: 1.0,
+            stroke: None,
+            stroke_width: 1.0,
+            stroke_opacity: 1.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 4.0,
+            opacity: 1.0,
+            transform: Transform::identity(),
+        }
+    }
+}
+
+impl PaintStyle {
+    /// Returns the style of an element with the given attributes inside the element with this style.
+    ///
+    /// Opacity of a group is applied to each of its children separately, which differs from SVG only where the
+    /// children overlap.
+    fn apply(&self, attributes: &Attributes) -> Self {
+        let mut style = self.clone();
+        let opacity = |key: &str| {
+            attributes
+                .get(key)
+                .and_then(|value| value.trim().parse::<f32>().ok())
+                .map(|value| value.clamp(0.0, 1.0))
+        };
+
+        if let Some(fill) = attributes.get("fill").and_then(parse_paint) {
+            style.fill = fill;
+        }
+        if let Some(stroke) = attributes.get("stroke").and_then(parse_paint) {
+            style.stroke = stroke;
+        }
+        match attributes.get("fill-rule") {
+            Some("evenodd") => style.fill_rule = FillRule::EvenOdd,
+            Some("nonzero") => style.fill_rule = FillRule::Winding,
+            _ => {}
+        }
+        match attributes.get("stroke-linecap") {
+            Some("butt") => style.line_cap = LineCap::Butt,
+            Some("round") => style.line_cap = LineCap::Round,
+            Some("square") => style.line_cap = LineCap::Square,
+            _ => {}
+        }
+        match attributes.get("stroke-linejoin") {
+            Some("miter") => style.line_join = LineJoin::Miter,
+            Some("round") => style.line_join = LineJoin::Round,
+            Some("bevel") => style.line_join = LineJoin::Bevel,
+            _ => {}
+        }
+        if let Some(width) = attributes.length("stroke-width") {
+            style.stroke_width = width.max(0.0);
+        }
+        if let Some(limit) = attributes.length("stroke-miterlimit") {
+            style.miter_limit = limit.max(1.0);
+        }
+        if let Some(value) = opacity("fill-opacity") {
+            style.fill_opacity = value;
+        }
+        if let Some(value) = opacity("stroke-opacity") {
+            style.stroke_opacity = value;
+        }
+        if let Some(value) = opacity("opacity") {
+            style.opacity *= value;
+        }
+        if let Some(transform) = attributes.get("transform").and_then(parse_transform) {
+            style.transform = style.transform.pre_concat(transform);
+        }
+
+        style
+    }
+
+    fn shape(&self, path: Path) -> Option<SvgShape> {
+        let with_opacity = |color: Color, opacity: f32| {
+            let alpha = (color.to_u8_array()[3] as f32 * opacity * self.opacity).round() as u8;
+            (alpha > 0).then(|| color.with_alpha(alpha))
+        };
+
+        let fill = self
+            .fill
+            .and_then(|color| with_opacity(color, self.fill_opacity))
+            .map(|color| (color, self.fill_rule));
+        let stroke = self
+            .stroke
+            .filter(|_| self.stroke_width > 0.0)
+            .and_then(|color| with_opacity(color, self.stroke_opacity))
+            .map(|color| {
+                let stroke = Stroke {
+                    width: self.stroke_width,
+                    miter_limit: self.miter_limit,
+                    line_cap: self.line_cap,
+                    line_join: self.line_join,
+                    dash: None,
+                };
+                (color, stroke)
+            });
+
+        (fill.is_some() || stroke.is_some()).then_some(SvgShape {
+            path,
+            transform: self.transform,
+            fill,
+            stroke,
+        })
+    }
+}
+
+/// Parses a paint value. Returns `Some(None)` for `none`, and `None` for values that are not supported, in which
+/// case the inherited paint is used.
+fn parse_paint(value: &str) -> Option<Option<Color>> {
+    let value = value.trim();
+    match value {
+        "none" | "transparent" => return Some(None),
+        "currentColor" | "black" => return Some(Some(Color::BLACK)),
+        "white" => return Some(Some(Color::WHITE)),
+        "red" => return Some(Some(Color::RED)),
+        "lime" => return Some(Some(Color::GREEN)),
+        "blue" => return Some(Some(Color::BLUE)),
+        "green" => return Some(Some(Color::rgba(0, 128, 0, 255))),
+        "yellow" => return Some(Some(Color::rgba(255, 255, 0, 255))),
+        "orange" => return Some(Some(Color::rgba(255, 165, 0, 255))),
+        "gray" | "grey" => return Some(Some(Color::rgba(128, 128, 128, 255))),
+        _ => {}
+    }
+
+    if let Some(hex) = value.strip_prefix('#') {
+        let hex = match hex.len() {
+            3 | 4 => hex.chars().flat_map(|c| [c, c]).collect(),
+            _ => hex.to_string(),
+        };
+        return Color::try_from_hex(&format!("#{hex}")).map(Some);
+    }
+
+    let arguments = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))?
+        .strip_suffix(')')?;
+    let mut channels = arguments.split([',', ' ', '/']).filter(|s| !s.is_empty());
+    let mut channel = || -> Option<u8> {
+        let channel = channels.next()?.trim();
+        let value = match channel.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok()? * 2.55,
+            None => channel.parse().ok()?,
+        };
+        Some(value.round().clamp(0.0, 255.0) as u8)
+    };
+    let (r, g, b) = (channel()?, channel()?, channel()?);
+    let a = channels
+        .next()
+        .and_then(|alpha| match alpha.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok().map(|value| value / 100.0),
+            None => alpha.parse::<f32>().ok(),
+        })
+        .map_or(255, |alpha| (alpha.clamp(0.0, 1.0) * 255.0).round() as u8);
+
+    Some(Some(Color::rgba(r, g, b, a)))
+}
+
+/// Parses a list of transform functions, e.g. `translate(10 20) rotate(45)`.
+fn parse_transform(value: &str) -> Option<Transform> {
+    let mut transform = Transform::identity();
+    for function in value.split(')').filter(|s| !s.trim().is_empty()) {
+        let (name, arguments) = function.split_once('(')?;
+        let args = numbers(arguments);
+        let arg = |index: usize| args.get(index).copied();
+        let next = match (name.trim().trim_start_matches(','), args.len()) {
+            ("matrix", 6) => {
+                Transform::from_row(args[0], args[1], args[2], args[3], args[4], args[5])
+            }
+            ("translate", 1 | 2) => Transform::from_translate(args[0], arg(1).unwrap_or(0.0)),
+            ("scale", 1 | 2) => Transform::from_scale(args[0], arg(1).unwrap_or(args[0])),
+            ("rotate", 1) => Transform::from_rotate(args[0]),
+            ("rotate", 3) => Transform::from_rotate_at(args[0], args[1], args[2]),
+            ("skewX", 1) => Transform::from_skew(args[0].to_radians().tan(), 0.0),
+            ("skewY", 1) => Transform::from_skew(0.0, args[0].to_radians().tan()),
+            _ => return None,
+        };
+        transform = transform.pre_concat(next);
+    }
+
+    Some(transform)
+}
+
+/// Parses a list of numbers separated by whitespace or commas.
+fn numbers(value: &str) -> Vec<f32> {
+    let mut parser = PathDataParser::new(value);
+    std::iter::from_fn(|| parser.number()).collect()
+}
+
+/// Builds the path of a basic shape or a `path` element. Returns `None` for other elements and for shapes with
+/// invalid or empty geometry.
+fn shape_path(name: &[u8], attributes: &Attributes) -> Option<Path> {
+    let length = |key: &str| attributes.length(key).unwrap_or(0.0);
+    let points = |builder: &mut PathBuilder| {
+        let coordinates = numbers(attributes.get("points")?);
+        let mut pairs = coordinates.chunks_exact(2);
+        let first = pairs.next()?;
+        builder.move_to(first[0], first[1]);
+        for pair in pairs {
+            builder.line_to(pair[0], pair[1]);
+        }
+        Some(())
+    };
+
+    let mut builder = PathBuilder::new();
+    match name {
+        b"path" => return parse_path_data(attributes.get("d")?),
+        b"rect" => {
+            let (x, y, width, height) =
+                (length("x"), length("y"), length("width"), length("height"));
+            let rx = attributes
+                .length("rx")
+                .or(attributes.length("ry"))
+                .unwrap_or(0.0);
+            let ry = attributes.length("ry").unwrap_or(rx);
+            push_rounded_rect(&mut builder, x, y, width, height, rx, ry);
+        }
+        b"circle" => {
+            let r = length("r");
+            if r <= 0.0 {
+                return None;
+            }
+            builder.push_circle(length("cx"), length("cy"), r);
+        }
+        b"ellipse" => {
+            let (rx, ry) = (length("rx"), length("ry"));
+            let oval = tiny_skia::Rect::from_xywh(
+                length("cx") - rx,
+                length("cy") - ry,
+                rx * 2.0,
+                ry * 2.0,
+            )?;
+            builder.push_oval(oval);
+        }
+        b"line" => {
+            builder.move_to(length("x1"), length("y1"));
+            builder.line_to(length("x2"), length("y2"));
+        }
+        b"polyline" => points(&mut builder)?,
+        b"polygon" => {
+            points(&mut builder)?;
+            builder.close();
+        }
+        _ => return None,
+    }
+
+    builder.finish()
+}
+
+/// Adds a rectangle with corners rounded by elliptical arcs with the given radii.
+fn push_rounded_rect(
+    builder: &mut PathBuilder,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rx: f32,
+    ry: f32,
+) {
+    let rx = rx.clamp(0.0, width / 2.0);
+    let ry = ry.clamp(0.0, height / 2.0);
+    if rx == 0.0 || ry == 0.0 {
+        if let Some(rect) = tiny_skia::Rect::from_xywh(x, y, width, height) {
+            builder.push_rect(rect);
+        }
+        return;
+    }
+
+    // Distance of the control points of a cubic bezier approximating a quarter of an ellipse.
+    const KAPPA: f32 = 0.552_284_8;
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let (right, bottom) = (x + width, y + height);
+
+    builder.move_to(x + rx, y);
+    builder.line_to(right - rx, y);
+    builder.cubic_to(right - rx + kx, y, right, y + ry - ky, right, y + ry);
+    builder.line_to(right, bottom - ry);
+    builder.cubic_to(
+        right,
+        bottom - ry + ky,
+        right - rx + kx,
+        bottom,
+        right - rx,
+        bottom,
+    );
+    builder.line_to(x + rx, bottom);
+    builder.cubic_to(x + rx - kx, bottom, x, bottom - ry + ky, x, bottom - ry);
+    builder.line_to(x, y + ry);
+    builder.cubic_to(x, y + ry - ky, x + rx - kx, y, x + rx, y);
+    builder.close();
+}
+
+/// Builds a path from the SVG path data (`d` attribute). In case of an error, the path is built up to the invalid
+/// segment, as SVG renderers do.
+fn parse_path_data(data: &str) -> Option<Path> {
+    let mut parser = PathDataParser::new(data);
+    let mut builder = PathBuilder::new();
+    let mut current = (0.0f32, 0.0f32);
+    let mut start = current;
+    let mut command: Option<u8> = None;
+    // Last control point of a cubic or a quadratic curve, used by the smooth curve commands.
+    let mut last_cubic: Option<(f32, f32)> = None;
+    let mut last_quad: Option<(f32, f32)> = None;
+
+    while !parser.is_at_end() {
+        if let Some(next) = parser.command() {
+            command = Some(next);
+        }
+        let Some(cmd) = command else {
+            break;
+        };
+
+        let base = if cmd.is_ascii_lowercase() {
+            current
+        } else {
+            (0.0, 0.0)
+        };
+        let (mut cubic, mut quad) = (None, None);
+
+        match cmd.to_ascii_uppercase() {
+            b'M' => {
+                let Some(to) = parser.point(base) else { break };
+                builder.move_to(to.0, to.1);
+                (current, start) = (to, to);
+                command = Some(if cmd == b'm' { b'l' } else { b'L' });
+            }
+            b'L' => {
+                let Some(to) = parser.point(base) else { break };
+                builder.line_to(to.0, to.1);
+                current = to;
+            }
+            b'H' => {
+                let Some(x) = parser.number() else { break };
+                current.0 = x + base.0;
+                builder.line_to(current.0, current.1);
+            }
+            b'V' => {
+                let Some(y) = parser.number() else { break };
+                current.1 = y + base.1;
+                builder.line_to(current.0, current.1);
+            }
+            b'C' | b'S' => {
+                let ctrl1 = if cmd.eq_ignore_ascii_case(&b'C') {
+                    let Some(ctrl1) = parser.point(base) else {
+                        break;
+                    };
+                    ctrl1
+                } else {
+                    reflect(last_cubic, current)
+                };
+                let (Some(ctrl2), Some(to)) = (parser.point(base), parser.point(base)) else {
+                    break;
+                };
+                builder.cubic_to(ctrl1.0, ctrl1.1, ctrl2.0, ctrl2.1, to.0, to.1);
+                cubic = Some(ctrl2);
+                current = to;
+            }
+            b'Q' | b'T' => {
+                let ctrl = if cmd.eq_ignore_ascii_case(&b'Q') {
+                    let Some(ctrl) = parser.point(base) else {
+                        break;
+                    };
+                    ctrl
+                } else {
+                    reflect(last_quad, current)
+                };
+                let Some(to) = parser.point(base) else { break };
+                builder.quad_to(ctrl.0, ctrl.1, to.0, to.1);
+                quad = Some(ctrl);
+                current = to;
+            }
+            b'A' => {
+                let (Some(rx), Some(ry), Some(rotation)) =
+                    (parser.number(), parser.number(), parser.number())
+                else {
+                    break;
+                };
+                let (Some(large_arc), Some(sweep)) = (parser.flag(), parser.flag()) else {
+                    break;
+                };
+                let Some(to) = parser.point(base) else { break };
+
+                if rx == 0.0 || ry == 0.0 {
+                    builder.line_to(to.0, to.1);
+                } else {
+                    let arc = SvgArc {
+                        from: point(current.0, current.1),
+                        to: point(to.0, to.1),
+                        radii: vector(rx.abs(), ry.abs()),
+                        x_rotation: Angle::degrees(rotation),
+                        flags: ArcFlags { large_arc, sweep },
+                    };
+                    arc.for_each_cubic_bezier(&mut |segment| {
+                        builder.cubic_to(
+                            segment.ctrl1.x,
+                            segment.ctrl1.y,
+                            segment.ctrl2.x,
+                            segment.ctrl2.y,
+                            segment.to.x,
+                            segment.to.y,
+                        );
+                    });
+                }
+                current = to;
+            }
+            b'Z' => {
+                builder.close();
+                current = start;
+                // Close path command takes no arguments, so the next segment must start with a command.
+                command = None;
+            }
+            _ => break,
+        }
+
+        (last_cubic, last_quad) = (cubic, quad);
+    }
+
+    builder.finish()
+}
+
+/// Reflects the last control point about the current point, or returns the current point if there is no previous
+/// control point.
+fn reflect(control: Option<(f32, f32)>, current: (f32, f32)) -> (f32, f32) {
+    match control {
+        Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        None => current,
+    }
+}
+
+/// Tokenizer of the SVG path data and number lists.
+struct PathDataParser<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PathDataParser<'a> {
+    fn new(data: &'a str) -> Self {
+        Self {
+            data: data.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .data
+            .get(self.position)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b',')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn is_at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.position >= self.data.len()
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let c = *self.data.get(self.position)?;
+        (c.is_ascii_alphabetic() && c != b'e' && c != b'E').then(|| {
+            self.position += 1;
+            c
+        })
+    }
+
+    /// Reads a pair of coordinates, adding them to the `base` point.
+    fn point(&mut self, base: (f32, f32)) -> Option<(f32, f32)> {
+        Some((self.number()? + base.0, self.number()? + base.1))
+    }
+
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.data.get(self.position)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.position += 1;
+        Some(flag)
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            let from = parser.position;
+            while parser
+                .data
+                .get(parser.position)
+                .is_some_and(u8::is_ascii_digit)
+            {
+                parser.position += 1;
+            }
+            parser.position > from
+        };
+        let sign = |parser: &mut Self| {
+            if matches!(parser.data.get(parser.position), Some(b'+' | b'-')) {
+                parser.position += 1;
+            }
+        };
+
+        sign(self);
+        let mut has_digits = digits(self);
+        if self.data.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            has_digits |= digits(self);
+        }
+        if !has_digits {
+            self.position = start;
+            return None;
+        }
+
+        if matches!(self.data.get(self.position), Some(b'e' | b'E')) {
+            let mantissa_end = self.position;
+            self.position += 1;
+            sign(self);
+            if !digits(self) {
+                self.position = mantissa_end;
+            }
+        }
+
+        std::str::from_utf8(&self.data[start..self.position])
+            .ok()?
+            .parse()
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::point_paint::PointShape;
+    use galileo_types::cartesian::Point3d;
+
+    const PIN: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10" viewBox="0 0 20 20">
+        <defs><circle cx="10" cy="10" r="10" fill="blue"/></defs>
+        <g transform="translate(10 10)" style="fill: #f00">
+            <circle r="5"/>
+        </g>
+        <rect x="0" y="0" width="4" height="4" fill="none" stroke="rgb(0, 0, 255)" display="none"/>
+    </svg>"##;
+
+    fn pixel(image: &DecodedImage, x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * image.dimensions.0 + x) * 4) as usize;
+        image.bytes[index..index + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn rasterizes_at_pixel_ratio() {
+        let symbol = SvgMarkerSymbol::new(PIN.as_bytes(), Vector2::new(0.5, 0.5), 2.0).unwrap();
+        assert_eq!(symbol.document.shapes.len(), 1);
+
+        let image = symbol.image();
+        assert_eq!(image.dimensions, (20, 20));
+        assert_eq!(pixel(&image, 10, 10), [255, 0, 0, 255]);
+        assert_eq!(pixel(&image, 1, 1)[3], 0);
+        assert!(Arc::ptr_eq(&image, &symbol.image()));
+
+        let clone = symbol.clone();
+        clone.set_pixel_ratio(1.5);
+        assert_eq!(symbol.pixel_ratio(), 1.5);
+        let image = symbol.image();
+        assert_eq!(image.dimensions, (30, 30));
+        assert_eq!(pixel(&image, 15, 15), [255, 0, 0, 255]);
+
+        let point = Geom::Point(Point3d::new(1.0, 1.0, 0.0));
+        let primitives = symbol.render(&(), &point, 1.0);
+        let RenderPrimitive::Point(_, paint) = &primitives[0] else {
+            panic!("expected point primitive");
+        };
+        assert!(matches!(
+            paint.shape,
+            PointShape::Image { width, height, .. } if width == 30.0 && height == 30.0
+        ));
+    }
+
+    #[test]
+    fn path_data() {
+        let bounds = |data: &str| {
+            let bounds = parse_path_data(data).unwrap().bounds();
+            [bounds.left(), bounds.top(), bounds.right(), bounds.bottom()]
+        };
+
+        assert_eq!(bounds("M0 0L10 0 10 5z"), [0.0, 0.0, 10.0, 5.0]);
+        assert_eq!(bounds("m1.5.5h2v-1.5-1e1"), [1.5, -11.0, 3.5, 0.5]);
+        assert_eq!(bounds("M0,0 Q5,10 10,0 T20,0"), [0.0, -10.0, 20.0, 10.0]);
+        let [left, top, right, bottom] = bounds("M0 0 a5 5 0 105 5");
+        assert!(right - left > 5.0 || bottom - top > 5.0);
+        // Parsing stops at the invalid segment.
+        assert_eq!(bounds("M0 0 L4 4 L7"), [0.0, 0.0, 4.0, 4.0]);
+        assert!(parse_path_data("10 10").is_none());
+    }
+
+    #[test]
+    fn paint_values() {
+        assert_eq!(parse_paint("none"), Some(None));
+        assert_eq!(
+            parse_paint("#0f08"),
+            Some(Some(Color::rgba(0, 255, 0, 136)))
+        );
+        assert_eq!(
+            parse_paint("rgba(10, 20, 100%, 0.5)"),
+            Some(Some(Color::rgba(10, 20, 255, 128)))
+        );
+        assert_eq!(parse_paint("url(#gradient)"), None);
+    }
+
+    #[test]
+    fn invalid_documents() {
+        assert!(SvgMarkerSymbol::new(b"<html/>", Vector2::new(0.0, 0.0), 1.0).is_err());
+        assert!(SvgMarkerSymbol::new(
+            b"<svg><path d=\"M0 0\"/></svg>",
+            Vector2::new(0.0, 0.0),
+            1.0
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Pool of threads tessellating features of all the feature layers in background.
+pub(super) struct TessellationPool {
+    sender: Option<Mutex<Sender<Job>>>,
+}
+
+impl TessellationPool {
+    /// Pool shared by all the layers. It uses all the available cores but one, which is left for the render thread.
+    pub fn global() -> &'static Self {
+        static POOL: OnceLock<TessellationPool> = OnceLock::new();
+        POOL.get_or_init(|| {
+            let threads = std::thread::available_parallelism()
+                .map(|count| count.get().saturating_sub(1))
+                .unwrap_or(1)
+                .max(1);
+            Self::new(threads)
+        })
+    }
+
+    fn new(threads: usize) -> Self {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut started = 0;
+        for index in 0..threads {
+            let receiver = receiver.clone();
+            let result = std::thread::Builder::new()
+                .name(format!("galileo-tessellation-{index}"))
+                .spawn(move || run_worker(&receiver));
+            match result {
+                Ok(_) => started += 1,
+                Err(err) => log::warn!("Failed to start tessellation thread: {err}"),
+            }
+        }
+
+        Self {
+            sender: (started > 0).then(|| Mutex::new(sender)),
+        }
+    }
+
+    /// Runs the `job` in one of the threads of the pool. If no threads could be started, the job is run immediately in
+    /// the current thread.
+    pub fn spawn(&self, job: impl FnOnce() + Send + 'static) {
+        let Some(sender) = &self.sender else {
+            job();
+            return;
+        };
+
+        if let Err(err) = sender
+            .lock()
+            .expect("mutex is poisoned")
+            .send(Box::new(job))
+        {
+            (err.0)();
+        }
+    }
+}
+
+fn run_worker(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = receiver.lock().expect("mutex is poisoned").recv();
+        match job {
+            Ok(job) => job(),
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_run_in_pool_threads() {
+        let pool = TessellationPool::new(2);
+        let (sender, receiver) = channel();
+        for value in 0..10 {
+            let sender = sender.clone();
+            pool.spawn(move || {
+                let thread = std::thread::current().name().map(String::from);
+                sender.send((value, thread)).unwrap();
+            });
+        }
+
+        let mut results: Vec<_> = (0..10).map(|_| receiver.recv().unwrap()).collect();
+        results.sort();
+        assert_eq!(
+            results.iter().map(|(value, _)| *value).collect::<Vec<_>>(),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert!(results.iter().all(|(_, thread)| thread
+            .as_deref()
+            .is_some_and(|name| name.starts_with("galileo-tessellation-"))));
+    }
+}
@@ -0,0 +1,425 @@
+use crate::layer::data_provider::{FlatGeobufFeature, FlatGeobufSource};
+use crate::layer::feature_layer::symbol::Symbol;
+use crate::layer::feature_layer::FeatureId;
+use crate::layer::{FeatureLayer, Layer};
+use crate::messenger::Messenger;
+use crate::render::Canvas;
+use crate::view::MapView;
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::Crs;
+use galileo_types::geometry_type::{CartesianSpace2d, GeoSpace2d, GeometryType};
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::any::Any;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+
+type ProjectedFeatureLayer<S> = FeatureLayer<Point2d, FlatGeobufFeature, S, CartesianSpace2d>;
+type GeoFeatureLayer<S> = FeatureLayer<GeoPoint2d, FlatGeobufFeature<GeoPoint2d>, S, GeoSpace2d>;
+
+#[derive(Debug, Default)]
+struct LoadRequest {
+    extent: Option<Rect>,
+    generation: u64,
+}
+
+/// Feature layer the loaded features are added to. Sources in geographic coordinates are displayed by a layer in
+/// geographic coordinates, so that they can be projected into the CRS of the map.
+enum FeatureLayers<S> {
+    Projected(Arc<RwLock<ProjectedFeatureLayer<S>>>),
+    Geographic(Arc<RwLock<GeoFeatureLayer<S>>>),
+}
+
+impl<S> Clone for FeatureLayers<S> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Projected(layer) => Self::Projected(layer.clone()),
+            Self::Geographic(layer) => Self::Geographic(layer.clone()),
+        }
+    }
+}
+
+impl<S> FeatureLayers<S>
+where
+    S: Symbol<FlatGeobufFeature>
+        + Symbol<FlatGeobufFeature<GeoPoint2d>>
+        + MaybeSend
+        + MaybeSync
+        + 'static,
+{
+    fn with_layer<T>(&self, f: impl FnOnce(&dyn Layer) -> T) -> T {
+        match self {
+            Self::Projected(layer) => f(&*layer.read().expect("lock is poisoned")),
+            Self::Geographic(layer) => f(&*layer.read().expect("lock is poisoned")),
+        }
+    }
+
+    fn with_layer_mut<T>(&self, f: impl FnOnce(&mut dyn Layer) -> T) -> T {
+        match self {
+            Self::Projected(layer) => f(&mut *layer.write().expect("lock is poisoned")),
+            Self::Geographic(layer) => f(&mut *layer.write().expect("lock is poisoned")),
+        }
+    }
+
+    fn replace_features(&self, features: Vec<FlatGeobufFeature>) {
+        match self {
+            Self::Projected(layer) => replace_features(layer, features),
+            Self::Geographic(layer) => replace_features(
+                layer,
+                features
+                    .into_iter()
+                    .map(FlatGeobufFeature::into_geo)
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Layer that displays the features of a [`FlatGeobufSource`], loading only the features in the current view.
+///
+/// When the view moves out of the previously loaded area, the features in the view extent enlarged by the prefetch
+/// margin (see [`FlatGeobufLayer::with_prefetch_margin`]) are requested from the source in background. Once loaded,
+/// they replace the previously loaded features. With a spatially indexed file this allows displaying datasets too
+/// large to be loaded at once, without tiling them.
+///
+/// The features are rendered by a [`FeatureLayer`] with the given symbol. Features of a source in a projected CRS
+/// are kept in the CRS of the source (see [`FlatGeobufLayer::feature_layer`]). Features of a source in geographic
+/// coordinates, or with unknown CRS, are converted into [`GeoPoint2d`] geometries (see
+/// [`FlatGeobufLayer::geo_feature_layer`]), so the symbol must be able to draw both kinds of features. To avoid
+/// loading all the features of the file when the map is zoomed out, set the maximum resolution with
+/// [`FlatGeobufLayer::with_max_resolution`].
+///
+/// ```no_run
+/// # async fn create() -> Result<(), galileo::error::GalileoError> {
+/// use galileo::layer::data_provider::FlatGeobufSource;
+/// use galileo::layer::FlatGeobufLayer;
+/// use galileo::symbol::SimplePolygonSymbol;
+/// use galileo::Color;
+///
+/// let source = FlatGeobufSource::open_url("https://example.com/buildings.fgb").await?;
+/// let layer = FlatGeobufLayer::new(source, SimplePolygonSymbol::new(Color::BLUE))
+///     .with_max_resolution(10.0);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FlatGeobufLayer<S> {
+    source: Arc<FlatGeobufSource>,
+    crs: Crs,
+    features: FeatureLayers<S>,
+    request: Arc<Mutex<LoadRequest>>,
+    messenger: Option<Arc<dyn Messenger>>,
+    max_resolution: Option<f64>,
+    prefetch_margin: f64,
+}
+
+impl<S> FlatGeobufLayer<S>
+where
+    S: Symbol<FlatGeobufFeature>
+        + Symbol<FlatGeobufFeature<GeoPoint2d>>
+        + MaybeSend
+        + MaybeSync
+        + 'static,
+{
+    /// Creates a new layer displaying the features of the `source` with the given symbol.
+    pub fn new(source: FlatGeobufSource, style: S) -> Self {
+        let crs = source
+            .crs()
+            .filter(|crs| !crs.is_geographic())
+            .cloned()
+            .unwrap_or(Crs::WGS84);
+        let features = if crs.is_geographic() {
+            FeatureLayers::Geographic(Arc::new(RwLock::new(FeatureLayer::new(
+                vec![],
+                style,
+                crs.clone(),
+            ))))
+        } else {
+            FeatureLayers::Projected(Arc::new(RwLock::new(FeatureLayer::new(
+                vec![],
+                style,
+                crs.clone(),
+            ))))
+        };
+
+        Self {
+            source: Arc::new(source),
+            crs,
+            features,
+            request: Default::default(),
+            messenger: None,
+            max_resolution: None,
+            prefetch_margin: 0.5,
+        }
+    }
+
+    /// Sets the maximum resolution of the map at which the layer is displayed. At coarser resolutions the layer is not
+    /// rendered and no features are loaded.
+    pub fn with_max_resolution(mut self, resolution: f64) -> Self {
+        self.max_resolution = Some(resolution);
+        self
+    }
+
+    /// Sets the size of the area around the view, that is loaded together with the view, as a fraction of the view
+    /// size. Default value is `0.5`, so half of the view width is added on the left and on the right of the view.
+    pub fn with_prefetch_margin(mut self, margin: f64) -> Self {
+        self.prefetch_margin = margin.max(0.0);
+        self
+    }
+
+    /// Source of the features.
+    pub fn source(&self) -> &FlatGeobufSource {
+        &self.source
+    }
+
+    /// Feature layer with currently loaded features of a source in a projected CRS. It can be used to query the
+    /// features or modify their styles. Returns `None` for sources in geographic coordinates, see
+    /// [`FlatGeobufLayer::geo_feature_layer`].
+    pub fn feature_layer(&self) -> Option<&RwLock<ProjectedFeatureLayer<S>>> {
+        match &self.features {
+            FeatureLayers::Projected(layer) => Some(layer),
+            FeatureLayers::Geographic(_) => None,
+        }
+    }
+
+    /// Feature layer with currently loaded features of a source in geographic coordinates or with unknown CRS.
+    /// Returns `None` for sources in a projected CRS, see [`FlatGeobufLayer::feature_layer`].
+    pub fn geo_feature_layer(&self) -> Option<&RwLock<GeoFeatureLayer<S>>> {
+        match &self.features {
+            FeatureLayers::Geographic(layer) => Some(layer),
+            FeatureLayers::Projected(_) => None,
+        }
+    }
+
+    fn is_visible(&self, view: &MapView) -> bool {
+        self.max_resolution
+            .is_none_or(|max_resolution| view.resolution() <= max_resolution)
+    }
+
+    /// Extent of the view in the CRS of the source.
+    fn view_extent(&self, view: &MapView, crs: &Crs) -> Option<Rect> {
+        let bbox = view.get_bbox()?;
+        if view.crs() == crs {
+            return Some(bbox);
+        }
+
+        // Edges of the view are not straight lines in the source CRS, so their middle points are projected as well.
+        let projection = view.crs().projection_to(crs)?;
+        let center = bbox.center();
+        let points = [
+            Point2d::new(bbox.x_min(), bbox.y_min()),
+            Point2d::new(center.x(), bbox.y_min()),
+            Point2d::new(bbox.x_max(), bbox.y_min()),
+            Point2d::new(bbox.x_max(), center.y()),
+            Point2d::new(bbox.x_max(), bbox.y_max()),
+            Point2d::new(center.x(), bbox.y_max()),
+            Point2d::new(bbox.x_min(), bbox.y_max()),
+            Point2d::new(bbox.x_min(), center.y()),
+        ];
+        let projected = projection.project_points(&points)?;
+        Rect::from_points(projected.iter())
+    }
+}
+
+/// Replaces the features in the layer with the loaded ones, keeping the features that are already in the layer.
+fn replace_features<P, S, Space>(
+    layer: &RwLock<FeatureLayer<P, FlatGeobufFeature<P>, S, Space>>,
+    features: Vec<FlatGeobufFeature<P>>,
+) where
+    P: GeometryType,
+    S: Symbol<FlatGeobufFeature<P>>,
+{
+    let mut layer = layer.write().expect("lock is poisoned");
+    let store = layer.features_mut();
+
+    let loaded: HashSet<usize> = features.iter().map(|feature| feature.index).collect();
+    let existing: Vec<usize> = store.iter().map(|feature| feature.as_ref().index).collect();
+    let mut kept = HashSet::new();
+    for (store_index, feature_index) in existing.into_iter().enumerate().rev() {
+        if loaded.contains(&feature_index) {
+            kept.insert(feature_index);
+        } else {
+            store.remove(store_index);
+        }
+    }
+
+    for feature in features {
+        if !kept.contains(&feature.index) {
+            store.insert(feature);
+        }
+    }
+}
+
+impl<S> Layer for FlatGeobufLayer<S>
+where
+    S: Symbol<FlatGeobufFeature>
+        + Symbol<FlatGeobufFeature<GeoPoint2d>>
+        + MaybeSend
+        + MaybeSync
+        + 'static,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if self.is_visible(view) {
+            self.features.with_layer(|layer| layer.render(view, canvas));
+        }
+    }
+
+    fn prepare(&self, view: &MapView) {
+        if !self.is_visible(view) {
+            return;
+        }
+
+        let Some(view_extent) = self.view_extent(view, &self.crs) else {
+            return;
+        };
+
+        let (extent, generation) = {
+            let mut request = self.request.lock().expect("lock is poisoned");
+            let is_loaded = request.extent.is_some_and(|extent| {
+                extent.contains(&Point2d::new(view_extent.x_min(), view_extent.y_min()))
+                    && extent.contains(&Point2d::new(view_extent.x_max(), view_extent.y_max()))
+            });
+            if is_loaded {
+                return;
+            }
+
+            let extent = view_extent.magnify(1.0 + 2.0 * self.prefetch_margin);
+            request.extent = Some(extent);
+            request.generation += 1;
+            (extent, request.generation)
+        };
+
+        let source = self.source.clone();
+        let features = self.features.clone();
+        let request = self.request.clone();
+        let messenger = self.messenger.clone();
+        crate::async_runtime::spawn(async move {
+            let loaded = source.features_in_extent(&extent).await;
+            let mut request = request.lock().expect("lock is poisoned");
+            if request.generation != generation {
+                // The view has changed while the features were loading.
+                return;
+            }
+
+            match loaded {
+                Ok(loaded) => {
+                    features.replace_features(loaded);
+                    if let Some(messenger) = messenger {
+                        messenger.request_redraw();
+                    }
+                }
+                Err(err) => {
+                    log::warn!("Failed to load FlatGeobuf features: {err:?}");
+                    request.extent = None;
+                }
+            }
+        });
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = Arc::from(messenger);
+        self.features
+            .with_layer_mut(|layer| layer.set_messenger(Box::new(messenger.clone())));
+        self.messenger = Some(messenger);
+    }
+
+    fn feature_at(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> Option<FeatureId> {
+        if !self.is_visible(view) {
+            return None;
+        }
+
+        self.features
+            .with_layer(|layer| layer.feature_at(view, screen_point, tolerance))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::symbol::CirclePointSymbol;
+    use crate::Color;
+    use galileo_types::cartesian::Size;
+    use galileo_types::geometry::Geom;
+    use std::collections::HashMap;
+
+    fn feature(index: usize) -> FlatGeobufFeature {
+        FlatGeobufFeature {
+            index,
+            geometry: Geom::Point(Point2d::new(index as f64, 0.0)),
+            properties: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn replace_features_keeps_loaded_features() {
+        let layer = RwLock::new(ProjectedFeatureLayer::new(
+            vec![feature(1), feature(2), feature(3)],
+            CirclePointSymbol::new(Color::RED, 5.0),
+            Crs::WGS84,
+        ));
+
+        replace_features(&layer, vec![feature(2), feature(3), feature(4)]);
+
+        let layer = layer.read().unwrap();
+        let indices: Vec<_> = layer
+            .features()
+            .iter()
+            .map(|feature| feature.as_ref().index)
+            .collect();
+        assert_eq!(indices, vec![2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn renders_source_in_geographic_coordinates() {
+        use crate::layer::data_provider::flatgeobuf::tests::write_file;
+        use crate::render::SvgRenderer;
+        use galileo_types::latlon;
+
+        // Points at `(i, i)` degrees.
+        let path = write_file("geographic_layer.fgb", 3, 2);
+        let source = FlatGeobufSource::open_file(&path).await.unwrap();
+        let layer = FlatGeobufLayer::new(source, CirclePointSymbol::new(Color::RED, 5.0));
+        assert!(layer.feature_layer().is_none());
+
+        let view = MapView::new(&latlon!(1.0, 1.0), 1000.0).with_size(Size::new(100.0, 100.0));
+        layer.prepare(&view);
+        let loaded = |layer: &FlatGeobufLayer<CirclePointSymbol>| {
+            layer
+                .geo_feature_layer()
+                .unwrap()
+                .read()
+                .unwrap()
+                .features()
+                .iter()
+                .count()
+        };
+        for _ in 0..100 {
+            if loaded(&layer) > 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        // Only the point at the center of the view is in the loaded area.
+        assert_eq!(loaded(&layer), 1);
+
+        let map = crate::Map::new(
+            view,
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        );
+        let svg = SvgRenderer::new().render(&map);
+        assert!(svg.contains(r##"fill="#ff0000""##));
+    }
+}
@@ -0,0 +1,501 @@
+//! [`GraticuleLayer`] draws the lines of latitude and longitude over the map.
+
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::RenderPrimitive;
+use crate::render::text::{TextAnchor, TextStyle};
+use crate::render::{Canvas, LinePaint, PackedBundle, RenderOptions};
+use crate::symbol::SimpleContourSymbol;
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Point3d};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint, Projection};
+use galileo_types::impls::{Contour, Polygon};
+use std::any::Any;
+use std::sync::{Mutex, RwLock};
+
+/// Spacings of the grid lines in degrees, from the largest to the smallest.
+const SPACINGS: [f64; 20] = [
+    90.0, 45.0, 30.0, 20.0, 10.0, 5.0, 2.0, 1.0, 0.5, 0.2, 0.1, 0.05, 0.02, 0.01, 0.005, 0.002,
+    0.001, 0.0005, 0.0002, 0.0001,
+];
+
+/// Number of points along each side of the screen used to find the visible geographic extent.
+const EXTENT_SAMPLES: usize = 10;
+
+/// Graticule layer draws meridians and parallels over the map and labels them at the edges of the screen.
+///
+/// The spacing of the lines is selected from a list of round values (`90°`, `45°`, `30°`, `20°`, `10°`, `5°`, `2°`,
+/// `1°`, `0.5°` and so on) based on the resolution of the map, so that the lines are at least
+/// [`min_spacing`](GraticuleOptions::min_spacing) pixels apart at the center of the screen. The lines are densified
+/// before projecting, so they are curved correctly in any projection of the map. Parts of the lines that cannot be
+/// projected or are too far outside of the screen, like the poles of Web Mercator, are not drawn. As the meridians
+/// converge at the poles, only the meridians with longitude divisible by `90°` reach the poles, the others end at the
+/// last parallel before the pole.
+///
+/// If a [label style](GraticuleOptions::label_style) is set, meridians are labeled where they cross the bottom edge
+/// of the screen, and parallels where they cross the left edge.
+///
+/// ```no_run
+/// use galileo::layer::{GraticuleLayer, GraticuleOptions};
+/// use galileo::render::text::{Font, TextStyle};
+///
+/// let font = Font::from_path("fonts/NotoSans-Regular.ttf").expect("failed to load font");
+/// let layer = GraticuleLayer::new(GraticuleOptions {
+///     label_style: Some(TextStyle::new(font, 12.0).with_halo(galileo::Color::WHITE, 2.0)),
+///     ..Default::default()
+/// });
+/// ```
+pub struct GraticuleLayer {
+    options: GraticuleOptions,
+    packed: Mutex<Option<(MapView, Box<dyn PackedBundle>)>>,
+    messenger: RwLock<Option<Box<dyn Messenger>>>,
+}
+
+/// Configuration of a [`GraticuleLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraticuleOptions {
+    /// Symbol of the grid lines.
+    pub line: SimpleContourSymbol,
+    /// The minimum distance between grid lines in pixels.
+    pub min_spacing: f64,
+    /// Style of the labels. If `None`, the lines are not labeled.
+    pub label_style: Option<TextStyle>,
+}
+
+impl Default for GraticuleOptions {
+    /// Thin semi-transparent black lines at least 100 pixels apart without labels.
+    fn default() -> Self {
+        Self {
+            line: SimpleContourSymbol::new(Color::rgba(0, 0, 0, 100), 1.0),
+            min_spacing: 100.0,
+            label_style: None,
+        }
+    }
+}
+
+/// Meridian or parallel prepared for rendering.
+#[derive(Debug)]
+struct GridLine {
+    /// Parts of the line in map coordinates.
+    parts: Vec<Vec<Point2d>>,
+    /// Label of the line.
+    label: Option<GridLabel>,
+}
+
+/// Label of a grid line at the edge of the screen.
+#[derive(Debug)]
+struct GridLabel {
+    text: String,
+    /// Position in map coordinates.
+    position: Point2d,
+    anchor: TextAnchor,
+}
+
+/// Visible range of geographic coordinates in degrees.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct GeoRange {
+    lat_min: f64,
+    lat_max: f64,
+    lon_min: f64,
+    lon_max: f64,
+}
+
+impl GraticuleLayer {
+    /// Creates a new layer with the given options.
+    pub fn new(options: GraticuleOptions) -> Self {
+        Self {
+            options,
+            packed: Mutex::new(None),
+            messenger: RwLock::new(None),
+        }
+    }
+
+    /// Options of the layer.
+    pub fn options(&self) -> &GraticuleOptions {
+        &self.options
+    }
+
+    /// Changes the options of the layer.
+    pub fn set_options(&mut self, options: GraticuleOptions) {
+        self.options = options;
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+        if let Some(messenger) = &*self.messenger.read().expect("lock is poisoned") {
+            messenger.request_redraw();
+        }
+    }
+
+    /// Spacing of the grid lines in degrees drawn for the `view`.
+    ///
+    /// Returns `None` if the center of the view cannot be converted into geographic coordinates.
+    pub fn spacing(&self, view: &MapView) -> Option<f64> {
+        let size = view.size();
+        let center = Point2d::new(size.half_width(), size.half_height());
+        let at_center = view.screen_to_map_geo(center)?;
+
+        let mut degrees_per_pixel: f64 = 0.0;
+        for (dx, dy) in [(1.0, 0.0), (0.0, 1.0)] {
+            let point = view.screen_to_map_geo(Point2d::new(center.x + dx, center.y + dy))?;
+            degrees_per_pixel = degrees_per_pixel
+                .max((point.lat() - at_center.lat()).abs())
+                .max((point.lon() - at_center.lon()).abs());
+        }
+
+        let min_spacing = self.options.min_spacing * degrees_per_pixel;
+        Some(
+            SPACINGS
+                .iter()
+                .rev()
+                .copied()
+                .find(|spacing| *spacing >= min_spacing)
+                .unwrap_or(SPACINGS[0]),
+        )
+    }
+
+    fn grid_lines(&self, view: &MapView) -> Vec<GridLine> {
+        let Some(projection) = view.crs().get_projection::<GeoPoint2d, Point2d>() else {
+            return vec![];
+        };
+        let (Some(spacing), Some(range)) = (self.spacing(view), visible_range(view, &*projection))
+        else {
+            return vec![];
+        };
+
+        let step = (spacing / 10.0).min(1.0);
+        let size = view.size();
+        let mut lines = vec![];
+
+        for lon in multiples(spacing, range.lon_min, range.lon_max) {
+            let pole_limit = if is_multiple(lon, 90.0) {
+                90.0
+            } else {
+                90.0 - spacing
+            };
+            let (from, to) = (
+                range.lat_min.max(-pole_limit),
+                range.lat_max.min(pole_limit),
+            );
+            let points = (0..=((to - from) / step).ceil() as usize)
+                .map(|i| GeoPoint2d::latlon((from + i as f64 * step).min(to), lon));
+            let (parts, screen_parts) = project_line(view, &*projection, points);
+            let label =
+                find_edge_crossing(view, &screen_parts, |p| p.y - size.height()).map(|position| {
+                    GridLabel {
+                        text: format_degrees(normalize_lon(lon), spacing, 'E', 'W'),
+                        position,
+                        anchor: TextAnchor::Bottom,
+                    }
+                });
+            lines.push(GridLine { parts, label });
+        }
+
+        for lat in multiples(spacing, range.lat_min, range.lat_max) {
+            let points = (0..=((range.lon_max - range.lon_min) / step).ceil() as usize).map(|i| {
+                GeoPoint2d::latlon(lat, (range.lon_min + i as f64 * step).min(range.lon_max))
+            });
+            let (parts, screen_parts) = project_line(view, &*projection, points);
+            let label =
+                find_edge_crossing(view, &screen_parts, |p| p.x).map(|position| GridLabel {
+                    text: format_degrees(lat, spacing, 'N', 'S'),
+                    position,
+                    anchor: TextAnchor::Left,
+                });
+            lines.push(GridLine { parts, label });
+        }
+
+        lines
+    }
+
+    fn pack(&self, view: &MapView, canvas: &dyn Canvas) -> Box<dyn PackedBundle> {
+        let line = &self.options.line;
+        let paint = LinePaint {
+            color: line.color,
+            width: line.width,
+            offset: 0.0,
+            line_cap: line.line_cap,
+            line_join: line.line_join,
+            dash: line.dash,
+            arrows: line.arrows,
+        };
+
+        let mut bundle = canvas.create_bundle();
+        let lines = self.grid_lines(view);
+        for part in lines.iter().flat_map(|line| &line.parts) {
+            let contour = Contour::open(part.iter().map(|p| Point3d::new(p.x, p.y, 0.0)).collect());
+            bundle.add(
+                RenderPrimitive::<_, _, _, Polygon<Point3d>>::new_contour(contour, paint),
+                view.resolution(),
+            );
+        }
+
+        if let Some(style) = &self.options.label_style {
+            for label in lines.iter().filter_map(|line| line.label.as_ref()) {
+                let style = style.clone().with_anchor(label.anchor);
+                bundle.add(
+                    RenderPrimitive::<_, _, Contour<Point3d>, Polygon<Point3d>>::new_point(
+                        Point3d::new(label.position.x, label.position.y, 0.0),
+                        PointPaint::label(&label.text, &style),
+                    ),
+                    view.resolution(),
+                );
+            }
+        }
+
+        canvas.pack_bundle(&bundle)
+    }
+}
+
+impl Layer for GraticuleLayer {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if canvas.is_transient() {
+            let packed = self.pack(view, canvas);
+            canvas.draw_bundles(&[&*packed], RenderOptions::default());
+            return;
+        }
+
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        if packed.as_ref().map(|(packed_view, _)| packed_view) != Some(view) {
+            *packed = Some((view.clone(), self.pack(view, canvas)));
+        }
+        if let Some((_, bundle)) = &*packed {
+            canvas.draw_bundles(&[&**bundle], RenderOptions::default());
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // do nothing
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        *self.messenger.write().expect("lock is poisoned") = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Finds the range of geographic coordinates visible in the `view` by converting a grid of screen points. If a pole is
+/// on the screen, all longitudes are visible.
+fn visible_range(
+    view: &MapView,
+    projection: &dyn Projection<InPoint = GeoPoint2d, OutPoint = Point2d>,
+) -> Option<GeoRange> {
+    let size = view.size();
+    let mut range: Option<GeoRange> = None;
+    for i in 0..=EXTENT_SAMPLES {
+        for j in 0..=EXTENT_SAMPLES {
+            let screen = Point2d::new(
+                size.width() * i as f64 / EXTENT_SAMPLES as f64,
+                size.height() * j as f64 / EXTENT_SAMPLES as f64,
+            );
+            let Some(point) = view.screen_to_map_geo(screen) else {
+                continue;
+            };
+            let (lat, lon) = (point.lat(), point.lon());
+            range = Some(match range {
+                Some(range) => GeoRange {
+                    lat_min: range.lat_min.min(lat),
+                    lat_max: range.lat_max.max(lat),
+                    lon_min: range.lon_min.min(lon),
+                    lon_max: range.lon_max.max(lon),
+                },
+                None => GeoRange {
+                    lat_min: lat,
+                    lat_max: lat,
+                    lon_min: lon,
+                    lon_max: lon,
+                },
+            });
+        }
+    }
+
+    let mut range = range?;
+    for pole in [-90.0, 90.0] {
+        let is_visible = projection
+            .project(&GeoPoint2d::latlon(pole, 0.0))
+            .and_then(|point| view.map_to_screen(&Point3d::new(point.x, point.y, 0.0)))
+            .is_some_and(|screen| {
+                (0.0..=size.width()).contains(&screen.x)
+                    && (0.0..=size.height()).contains(&screen.y)
+            });
+        if is_visible {
+            range.lat_min = range.lat_min.min(pole);
+            range.lat_max = range.lat_max.max(pole);
+            range.lon_min = -180.0;
+            range.lon_max = 180.0;
+        }
+    }
+
+    range.lat_min = range.lat_min.max(-90.0);
+    range.lat_max = range.lat_max.min(90.0);
+    Some(range)
+}
+
+/// Multiples of the `spacing` between `from` and `to`.
+fn multiples(spacing: f64, from: f64, to: f64) -> impl Iterator<Item = f64> {
+    let first = (from / spacing).ceil() as i64;
+    let last = (to / spacing).floor() as i64;
+    (first..=last).map(move |i| i as f64 * spacing)
+}
+
+fn is_multiple(value: f64, of: f64) -> bool {
+    let ratio = value / of;
+    (ratio - ratio.round()).abs() < 1e-9
+}
+
+/// Projects the points of a line and splits it into parts that can be projected and are not too far outside the
+/// screen. Returns the parts in map and in screen coordinates.
+fn project_line(
+    view: &MapView,
+    projection: &dyn Projection<InPoint = GeoPoint2d, OutPoint = Point2d>,
+    points: impl Iterator<Item = GeoPoint2d>,
+) -> (Vec<Vec<Point2d>>, Vec<Vec<Point2d>>) {
+    let size = view.size();
+    let margin = size.width().max(size.height());
+    let is_near_screen = |screen: &Point2d| {
+        (-margin..=size.width() + margin).contains(&screen.x)
+            && (-margin..=size.height() + margin).contains(&screen.y)
+    };
+
+    let mut parts = vec![];
+    let mut screen_parts = vec![];
+    let mut part = vec![];
+    let mut screen_part = vec![];
+    for point in points {
+        let projected = projection.project(&point).and_then(|point| {
+            let screen = view.map_to_screen(&Point3d::new(point.x, point.y, 0.0))?;
+            is_near_screen(&screen).then_some((point, screen))
+        });
+        match projected {
+            Some((point, screen)) => {
+                part.push(point);
+                screen_part.push(screen);
+            }
+            None => {
+                if part.len() > 1 {
+                    parts.push(std::mem::take(&mut part));
+                    screen_parts.push(std::mem::take(&mut screen_part));
+                } else {
+                    part.clear();
+                    screen_part.clear();
+                }
+            }
+        }
+    }
+    if part.len() > 1 {
+        parts.push(part);
+        screen_parts.push(screen_part);
+    }
+
+    (parts, screen_parts)
+}
+
+/// Finds the first point where a line crosses the edge of the screen, given as the line where the `edge` function is
+/// zero, and returns it in map coordinates.
+fn find_edge_crossing(
+    view: &MapView,
+    screen_parts: &[Vec<Point2d>],
+    edge: impl Fn(&Point2d) -> f64,
+) -> Option<Point2d> {
+    let size = view.size();
+    screen_parts
+        .iter()
+        .flat_map(|part| part.windows(2))
+        .find_map(|segment| {
+            let (a, b) = (segment[0], segment[1]);
+            let (distance_a, distance_b) = (edge(&a), edge(&b));
+            if distance_a == distance_b || distance_a * distance_b > 0.0 {
+                return None;
+            }
+
+            let t = distance_a / (distance_a - distance_b);
+            let crossing = Point2d::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+            let is_on_screen = (-0.5..=size.width() + 0.5).contains(&crossing.x)
+                && (-0.5..=size.height() + 0.5).contains(&crossing.y);
+            is_on_screen.then(|| view.screen_to_map(crossing)).flatten()
+        })
+}
+
+/// Brings the longitude into the `[-180, 180]` range.
+fn normalize_lon(lon: f64) -> f64 {
+    let lon = (lon + 180.0).rem_euclid(360.0) - 180.0;
+    if lon == -180.0 {
+        180.0
+    } else {
+        lon
+    }
+}
+
+/// Formats the coordinate with as many decimal places as needed to show the multiples of the `spacing`.
+fn format_degrees(value: f64, spacing: f64, positive: char, negative: char) -> String {
+    let decimals = (-spacing.log10() - 1e-9).ceil().max(0.0) as usize;
+    let magnitude = format!("{:.*}", decimals, value.abs());
+    let is_zero = magnitude.trim_start_matches(['0', '.']).is_empty();
+    if is_zero || (value.abs() - 180.0).abs() < 1e-9 {
+        format!("{magnitude}°")
+    } else if value > 0.0 {
+        format!("{magnitude}°{positive}")
+    } else {
+        format!("{magnitude}°{negative}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::Size;
+
+    fn view(resolution: f64) -> MapView {
+        MapView::new(&GeoPoint2d::latlon(0.0, 0.0), resolution).with_size(Size::new(1000.0, 800.0))
+    }
+
+    #[test]
+    fn spacing_depends_on_resolution() {
+        let layer = GraticuleLayer::new(GraticuleOptions::default());
+        assert_eq!(layer.spacing(&view(40_000.0)), Some(45.0));
+        assert_eq!(layer.spacing(&view(1_000.0)), Some(1.0));
+        assert_eq!(layer.spacing(&view(10.0)), Some(0.01));
+    }
+
+    #[test]
+    fn grid_lines_are_labeled_at_edges() {
+        let layer = GraticuleLayer::new(GraticuleOptions::default());
+        let view = view(40_000.0);
+        let lines = layer.grid_lines(&view);
+        assert!(!lines.is_empty());
+
+        let labels: Vec<_> = lines
+            .iter()
+            .filter_map(|line| line.label.as_ref())
+            .map(|label| label.text.as_str())
+            .collect();
+        assert!(labels.contains(&"0°"));
+        assert!(labels.contains(&"90°E"));
+        assert!(labels.contains(&"45°S"));
+
+        // Web Mercator cannot show the poles, so the lines stay close to the screen.
+        for point in lines.iter().flat_map(|line| &line.parts).flatten() {
+            let screen = view
+                .map_to_screen(&Point3d::new(point.x, point.y, 0.0))
+                .unwrap();
+            assert!(screen.y.abs() < 2000.0);
+        }
+    }
+
+    #[test]
+    fn degrees_formatting() {
+        assert_eq!(format_degrees(45.0, 45.0, 'N', 'S'), "45°N");
+        assert_eq!(format_degrees(-12.5, 0.5, 'E', 'W'), "12.5°W");
+        assert_eq!(format_degrees(0.0, 0.05, 'E', 'W'), "0.00°");
+        assert_eq!(format_degrees(180.0, 10.0, 'E', 'W'), "180°");
+        assert_eq!(normalize_lon(190.0), -170.0);
+        assert_eq!(normalize_lon(-180.0), 180.0);
+    }
+}
@@ -0,0 +1,271 @@
+//! [`HeatmapLayer`] draws the density surface of a set of weighted points.
+
+use crate::layer::Layer;
+use crate::messenger::Messenger;
+use crate::render::{Canvas, HeatmapPaint, PackedBundle};
+use crate::symbol::Interpolate;
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::Point2d;
+use galileo_types::geo::{Crs, NewGeoPoint};
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::any::Any;
+use std::sync::{Mutex, RwLock};
+
+/// Number of colors the color ramp is sampled into for rendering.
+const RAMP_SAMPLES: usize = 256;
+
+/// Heatmap layer draws the density of weighted geographic points as a continuous colored surface.
+///
+/// Every point adds its weight to the density of the area around it within the [radius](HeatmapOptions::radius). The
+/// density is accumulated on the GPU every frame, so the surface is always drawn with the screen resolution. The
+/// resulting density, multiplied by the [intensity](HeatmapOptions::intensity), is converted into colors with the
+/// [color ramp](HeatmapOptions::color_ramp).
+///
+/// ```no_run
+/// use galileo::layer::{HeatmapLayer, HeatmapOptions};
+/// use galileo_types::geo::impls::GeoPoint2d;
+/// use galileo_types::geo::NewGeoPoint;
+///
+/// let layer = HeatmapLayer::new(vec![
+///     (GeoPoint2d::latlon(52.52, 13.40), 1.0),
+///     (GeoPoint2d::latlon(52.51, 13.38), 0.5),
+/// ])
+/// .with_options(HeatmapOptions {
+///     radius: 30.0,
+///     ..Default::default()
+/// });
+/// ```
+pub struct HeatmapLayer<P> {
+    points: Vec<(P, f32)>,
+    options: HeatmapOptions,
+    packed: Mutex<Option<(Crs, Box<dyn PackedBundle>)>>,
+    messenger: RwLock<Option<Box<dyn Messenger>>>,
+}
+
+/// Configuration of a [`HeatmapLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapOptions {
+    /// Radius of influence of every point in pixels.
+    pub radius: f32,
+    /// Multiplier for the density of the points. The density of a single point with weight `1.0` at its position is
+    /// equal to the intensity.
+    pub intensity: f32,
+    /// Colors of the density values.
+    pub color_ramp: ColorRamp,
+}
+
+impl Default for HeatmapOptions {
+    fn default() -> Self {
+        Self {
+            radius: 20.0,
+            intensity: 1.0,
+            color_ramp: ColorRamp::default(),
+        }
+    }
+}
+
+/// Gradient of colors used to draw density values between `0.0` and `1.0`.
+///
+/// The ramp is set by a list of stops, each of which specifies the color for a density value. Colors between the
+/// stops are interpolated linearly. Density values before the first stop and after the last stop are drawn with the
+/// colors of the first and the last stop respectively.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    stops: Vec<(f32, Color)>,
+}
+
+impl ColorRamp {
+    /// Creates a new ramp with the given `(density, color)` stops. The stops do not have to be sorted.
+    pub fn new(stops: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        let mut stops: Vec<_> = stops.into_iter().collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    /// Stops of the ramp sorted by density.
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.stops
+    }
+
+    /// Color for the given `density` value.
+    pub fn color_at(&self, density: f32) -> Color {
+        let Some(&(first_stop, first_color)) = self.stops.first() else {
+            return Color::TRANSPARENT;
+        };
+        if density <= first_stop {
+            return first_color;
+        }
+
+        for pair in self.stops.windows(2) {
+            let ((from_stop, from), (to_stop, to)) = (pair[0], pair[1]);
+            if density <= to_stop {
+                let ratio = (density - from_stop) / (to_stop - from_stop);
+                return from.interpolate(&to, ratio as f64);
+            }
+        }
+
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+impl Default for ColorRamp {
+    /// Transparent blue at zero density through cyan, green and yellow to red at the density of `1.0`.
+    fn default() -> Self {
+        Self::new([
+            (0.0, Color::rgba(0, 0, 255, 0)),
+            (0.2, Color::rgba(0, 0, 255, 255)),
+            (0.4, Color::rgba(0, 255, 255, 255)),
+            (0.6, Color::rgba(0, 255, 0, 255)),
+            (0.8, Color::rgba(255, 255, 0, 255)),
+            (1.0, Color::rgba(255, 0, 0, 255)),
+        ])
+    }
+}
+
+impl<P> HeatmapLayer<P> {
+    /// Creates a new layer with the given `(position, weight)` points and default options.
+    pub fn new(points: Vec<(P, f32)>) -> Self {
+        Self {
+            points,
+            options: HeatmapOptions::default(),
+            packed: Mutex::new(None),
+            messenger: RwLock::new(None),
+        }
+    }
+
+    /// Sets the options of the layer.
+    pub fn with_options(mut self, options: HeatmapOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Options of the layer.
+    pub fn options(&self) -> &HeatmapOptions {
+        &self.options
+    }
+
+    /// Changes the options of the layer.
+    pub fn set_options(&mut self, options: HeatmapOptions) {
+        self.options = options;
+        self.request_redraw();
+    }
+
+    /// Points of the layer with their weights.
+    pub fn points(&self) -> &[(P, f32)] {
+        &self.points
+    }
+
+    /// Replaces the points of the layer.
+    pub fn set_points(&mut self, points: Vec<(P, f32)>) {
+        self.points = points;
+        *self.packed.get_mut().expect("mutex is poisoned") = None;
+        self.request_redraw();
+    }
+
+    fn request_redraw(&self) {
+        if let Some(messenger) = &*self.messenger.read().expect("lock is poisoned") {
+            messenger.request_redraw();
+        }
+    }
+}
+
+impl<P: NewGeoPoint + 'static> HeatmapLayer<P> {
+    fn pack_points(&self, view: &MapView, canvas: &dyn Canvas) -> Option<Box<dyn PackedBundle>> {
+        let projection = view.crs().get_projection::<P, Point2d>()?;
+        let points: Vec<_> = self
+            .points
+            .iter()
+            .filter_map(|(point, weight)| Some((projection.project(point)?, *weight)))
+            .collect();
+        Some(canvas.pack_heatmap(&points))
+    }
+}
+
+impl<P> Layer for HeatmapLayer<P>
+where
+    P: NewGeoPoint + MaybeSend + MaybeSync + 'static,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let mut packed = self.packed.lock().expect("mutex is poisoned");
+        let transient;
+        let points = if canvas.is_transient() {
+            let Some(points) = self.pack_points(view, canvas) else {
+                return;
+            };
+            transient = points;
+            &*transient
+        } else {
+            if packed.as_ref().map(|(crs, _)| crs) != Some(view.crs()) {
+                *packed = self
+                    .pack_points(view, canvas)
+                    .map(|points| (view.crs().clone(), points));
+            }
+            let Some((_, points)) = &*packed else {
+                return;
+            };
+            &**points
+        };
+
+        let colors: Vec<_> = (0..RAMP_SAMPLES)
+            .map(|i| {
+                self.options
+                    .color_ramp
+                    .color_at(i as f32 / (RAMP_SAMPLES - 1) as f32)
+            })
+            .collect();
+        canvas.draw_heatmap(
+            points,
+            HeatmapPaint {
+                radius: self.options.radius,
+                intensity: self.options.intensity,
+                colors: &colors,
+            },
+        );
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // do nothing
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        *self.messenger.write().expect("lock is poisoned") = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_ramp_interpolates_stops() {
+        let ramp = ColorRamp::new([
+            (1.0, Color::rgba(0, 0, 200, 255)),
+            (0.0, Color::rgba(0, 0, 0, 0)),
+        ]);
+        assert_eq!(ramp.stops()[0].0, 0.0);
+        assert_eq!(ramp.color_at(-1.0), Color::rgba(0, 0, 0, 0));
+        assert_eq!(ramp.color_at(0.25), Color::rgba(0, 0, 50, 64));
+        assert_eq!(ramp.color_at(1.5), Color::rgba(0, 0, 200, 255));
+    }
+
+    #[test]
+    fn empty_ramp_is_transparent() {
+        assert_eq!(ColorRamp::new([]).color_at(0.5), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn default_ramp_goes_from_transparent_to_red() {
+        let ramp = ColorRamp::default();
+        assert!(ramp.color_at(0.0).is_transparent());
+        assert_eq!(ramp.color_at(1.0), Color::RED);
+    }
+}
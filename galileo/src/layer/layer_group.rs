@@ -0,0 +1,248 @@
+use crate::layer::{Attribution, Layer};
+use crate::messenger::Messenger;
+use crate::render::Canvas;
+use crate::view::MapView;
+use crate::LayerCollection;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Layer that combines several child layers, so that they can be shown, hidden and faded together.
+///
+/// A group is added to a map as a single layer, so hiding the group or changing its opacity or blend mode in the
+/// [`LayerCollection`] of the map applies to all the layers of the group at once. The child layers are drawn in
+/// their order in the group, and each of them keeps its own visibility, opacity and blend mode inside the group.
+///
+/// The group is drawn like a single layer: the child layers are first composed together, and the result is drawn
+/// over the layers below the group with the opacity and blend mode of the group. Blend modes of the child layers
+/// apply only to the layers of the same group below them.
+///
+/// A group shared between the application and the map as `Arc<RwLock<LayerGroup>>` is drawn the same way.
+///
+/// Attributions of the visible child layers are returned as the attributions of the group.
+///
+/// ```
+/// use galileo::layer::{LayerGroup, TestLayer};
+/// use galileo::LayerCollection;
+///
+/// let mut hydrography = LayerGroup::new(vec![TestLayer("Rivers"), TestLayer("Lakes")]);
+/// hydrography.layers_mut().hide(1);
+///
+/// let mut collection = LayerCollection::default();
+/// collection.push(TestLayer("Basemap"));
+/// collection.push(hydrography);
+///
+/// // Hides all the layers of the group.
+/// collection.hide(1);
+/// ```
+#[derive(Default)]
+pub struct LayerGroup {
+    layers: LayerCollection,
+    messenger: Option<Arc<dyn Messenger>>,
+}
+
+impl LayerGroup {
+    /// Creates a new group with the given layers.
+    pub fn new(layers: impl Into<LayerCollection>) -> Self {
+        Self {
+            layers: layers.into(),
+            messenger: None,
+        }
+    }
+
+    /// Returns the child layers of the group.
+    pub fn layers(&self) -> &LayerCollection {
+        &self.layers
+    }
+
+    /// Returns a mutable reference to the child layers of the group.
+    ///
+    /// Layers added through the returned reference do not get the messenger of the group, use
+    /// [`LayerGroup::add_layer`] to add a layer with the messenger set.
+    pub fn layers_mut(&mut self) -> &mut LayerCollection {
+        &mut self.layers
+    }
+
+    /// Adds the layer on top of the other layers of the group. If the group has a messenger, it is also set for the
+    /// layer.
+    pub fn add_layer(&mut self, mut layer: impl Layer + 'static) {
+        if let Some(messenger) = &self.messenger {
+            layer.set_messenger(Box::new(messenger.clone()));
+        }
+
+        self.layers.push(layer);
+    }
+}
+
+impl Layer for LayerGroup {
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        // Renderers compose the children of a group themselves, this is a fallback for the canvases rendering the
+        // group directly.
+        for layer in self.layers.iter_visible() {
+            layer.render(view, canvas);
+        }
+    }
+
+    fn prepare(&self, view: &MapView) {
+        for layer in self.layers.iter_visible() {
+            layer.prepare(view);
+        }
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = Arc::from(messenger);
+        for layer in self.layers.iter_mut() {
+            layer.set_messenger(Box::new(messenger.clone()));
+        }
+
+        self.messenger = Some(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn attributions(&self) -> Vec<Attribution> {
+        self.layers.attributions()
+    }
+
+    fn is_loaded(&self, view: &MapView) -> bool {
+        self.layers
+            .iter_visible()
+            .all(|layer| layer.is_loaded(view))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::RenderedItem;
+    use crate::render::BlendMode;
+    use std::sync::RwLock;
+
+    struct AttributedLayer(&'static str);
+
+    impl Layer for AttributedLayer {
+        fn render(&self, _view: &MapView, _canvas: &mut dyn Canvas) {}
+
+        fn prepare(&self, _view: &MapView) {}
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn attributions(&self) -> Vec<Attribution> {
+            vec![Attribution::new(self.0, None)]
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Rendered {
+        Layer(&'static str, f32, BlendMode),
+        Group(Vec<Rendered>, f32, BlendMode),
+    }
+
+    fn rendered(collection: &LayerCollection) -> Vec<Rendered> {
+        let mut items = vec![];
+        collection.for_each_rendered(|_, item, opacity, blend_mode| {
+            items.push(match item {
+                RenderedItem::Layer(layer) => Rendered::Layer(
+                    layer.as_any().downcast_ref::<AttributedLayer>().unwrap().0,
+                    opacity,
+                    blend_mode,
+                ),
+                RenderedItem::Group(layers) => {
+                    Rendered::Group(rendered(layers), opacity, blend_mode)
+                }
+            })
+        });
+
+        items
+    }
+
+    #[test]
+    fn group_is_rendered_as_one_item() {
+        let mut group = LayerGroup::new(vec![
+            AttributedLayer("Rivers"),
+            AttributedLayer("Lakes"),
+            AttributedLayer("Canals"),
+        ]);
+        group.layers_mut().set_opacity(0, 0.5);
+        group.layers_mut().set_blend_mode(1, BlendMode::Additive);
+        group.layers_mut().hide(2);
+
+        let mut collection = LayerCollection::default();
+        collection.push(AttributedLayer("Basemap"));
+        collection.push(group);
+        collection.set_opacity(1, 0.5);
+        collection.set_blend_mode(1, BlendMode::Multiply);
+
+        assert_eq!(
+            rendered(&collection),
+            vec![
+                Rendered::Layer("Basemap", 1.0, BlendMode::Normal),
+                Rendered::Group(
+                    vec![
+                        Rendered::Layer("Rivers", 0.5, BlendMode::Normal),
+                        Rendered::Layer("Lakes", 1.0, BlendMode::Additive),
+                    ],
+                    0.5,
+                    BlendMode::Multiply
+                ),
+            ]
+        );
+
+        collection.hide(1);
+        assert_eq!(
+            rendered(&collection),
+            vec![Rendered::Layer("Basemap", 1.0, BlendMode::Normal)]
+        );
+    }
+
+    #[test]
+    fn shared_group_is_rendered_as_group() {
+        let group = Arc::new(RwLock::new(LayerGroup::new(vec![AttributedLayer(
+            "Rivers",
+        )])));
+        let mut collection = LayerCollection::default();
+        collection.push(group.clone());
+        collection.set_opacity(0, 0.5);
+
+        let expected = |rivers_opacity| {
+            vec![Rendered::Group(
+                vec![Rendered::Layer("Rivers", rivers_opacity, BlendMode::Normal)],
+                0.5,
+                BlendMode::Normal,
+            )]
+        };
+        assert_eq!(rendered(&collection), expected(1.0));
+
+        group
+            .write()
+            .expect("lock is poisoned")
+            .layers_mut()
+            .set_opacity(0, 0.25);
+        assert_eq!(rendered(&collection), expected(0.25));
+    }
+
+    #[test]
+    fn group_attributions_of_visible_children() {
+        let mut group = LayerGroup::new(vec![
+            AttributedLayer("Rivers"),
+            AttributedLayer("Lakes"),
+            AttributedLayer("Rivers"),
+        ]);
+        group.layers_mut().hide(1);
+
+        assert_eq!(group.attributions(), vec![Attribution::new("Rivers", None)]);
+    }
+}
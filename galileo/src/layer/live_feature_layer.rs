@@ -0,0 +1,503 @@
+//! [`LiveFeatureLayer`] displays features that are updated in real time.
+
+use crate::layer::feature_layer::symbol::{Interpolate, Symbol};
+use crate::layer::feature_layer::{Feature, FeatureId};
+use crate::layer::{FeatureLayer, Layer};
+use crate::messenger::Messenger;
+use crate::render::Canvas;
+use crate::view::MapView;
+use futures::{Stream, StreamExt};
+use galileo_types::cartesian::Point2d;
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{Crs, NewGeoPoint};
+use galileo_types::geometry::Geometry;
+use galileo_types::geometry_type::GeoSpace2d;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::any::Any;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+use web_time::{Duration, Instant};
+
+type FeatureLayerImpl<P, F, S> = FeatureLayer<P, F, S, GeoSpace2d>;
+
+/// Change of a feature of a [`LiveFeatureLayer`], identified by its key of type `K`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveUpdate<K, F> {
+    /// Adds the feature, or replaces the feature with the same key.
+    Upsert(K, F),
+    /// Removes the feature with the key.
+    Delete(K),
+    /// Removes all features.
+    Clear,
+}
+
+/// Feature with a position that can be moved smoothly by a [`LiveFeatureLayer`] between the updates (see
+/// [`LiveFeatureLayer::with_interpolation`]).
+pub trait MovingFeature: Feature {
+    /// Current position of the feature. Features without position are not interpolated.
+    fn position(&self) -> Option<GeoPoint2d>;
+    /// Moves the feature to the position.
+    fn set_position(&mut self, position: GeoPoint2d);
+}
+
+impl MovingFeature for GeoPoint2d {
+    fn position(&self) -> Option<GeoPoint2d> {
+        Some(*self)
+    }
+
+    fn set_position(&mut self, position: GeoPoint2d) {
+        *self = position;
+    }
+}
+
+/// Layer that displays features updated in real time, e.g. positions of vehicles received over a WebSocket.
+///
+/// Every feature is identified by a key, and changes are given to the layer as [`LiveUpdate`]s, either directly
+/// with [`LiveFeatureLayer::apply`], or from an asynchronous stream with [`LiveFeatureLayer::subscribe`]. Only the
+/// changed features are re-rendered.
+///
+/// If the features implement [`MovingFeature`], the layer can move them smoothly from the old position to the new one
+/// when a feature is updated, see [`LiveFeatureLayer::with_interpolation`]. Without interpolation the features jump to
+/// the new positions.
+///
+/// The features are rendered by a [`FeatureLayer`] with the given symbol.
+///
+/// ```
+/// use galileo::layer::{LiveFeatureLayer, LiveUpdate};
+/// use galileo::symbol::CirclePointSymbol;
+/// use galileo::Color;
+/// use galileo_types::geo::Crs;
+/// use galileo_types::latlon;
+/// use std::time::Duration;
+///
+/// let layer = LiveFeatureLayer::new(CirclePointSymbol::new(Color::RED, 6.0), Crs::WGS84)
+///     .with_interpolation(Duration::from_secs(1));
+/// layer.apply(LiveUpdate::Upsert("bus 12", latlon!(52.52, 13.40)));
+/// layer.apply(LiveUpdate::Upsert("bus 7", latlon!(52.50, 13.37)));
+/// layer.apply(LiveUpdate::Delete("bus 12"));
+/// assert_eq!(layer.len(), 1);
+///
+/// // Positions from a WebSocket can be given to the layer as a stream of updates, e.g.:
+/// // layer.subscribe(socket.map(|message| parse_update(message)));
+/// ```
+pub struct LiveFeatureLayer<K, P, F, S>
+where
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    state: Arc<RwLock<LiveState<K, P, F, S>>>,
+}
+
+struct LiveState<K, P, F, S>
+where
+    F: Feature,
+    F::Geom: Geometry<Point = P>,
+{
+    layer: FeatureLayerImpl<P, F, S>,
+    indices: HashMap<K, usize>,
+    keys: Vec<K>,
+    interpolation: Option<Interpolation<F>>,
+    motions: HashMap<K, Motion>,
+    messenger: Option<Arc<dyn Messenger>>,
+}
+
+struct Interpolation<F> {
+    duration: Duration,
+    position: fn(&F) -> Option<GeoPoint2d>,
+    set_position: fn(&mut F, GeoPoint2d),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Motion {
+    from: GeoPoint2d,
+    to: GeoPoint2d,
+    started: Instant,
+}
+
+impl Motion {
+    /// Position of the motion at the `share` of its duration.
+    fn position(&self, share: f64) -> GeoPoint2d {
+        self.from.interpolate(&self.to, share)
+    }
+}
+
+impl<K, P, F, S> LiveFeatureLayer<K, P, F, S>
+where
+    K: Hash + Eq + Clone + MaybeSend + MaybeSync + 'static,
+    P: NewGeoPoint + 'static,
+    F: Feature + MaybeSend + MaybeSync + 'static,
+    F::Geom: Geometry<Point = P>,
+    S: Symbol<F> + MaybeSend + MaybeSync + 'static,
+{
+    /// Creates a new layer without features, that draws them with the given style once they are added.
+    pub fn new(style: S, crs: Crs) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(LiveState {
+                layer: FeatureLayer::new(vec![], style, crs),
+                indices: HashMap::new(),
+                keys: vec![],
+                interpolation: None,
+                motions: HashMap::new(),
+                messenger: None,
+            })),
+        }
+    }
+
+    /// Applies the update to the layer and requests redraw of the map.
+    pub fn apply(&self, update: LiveUpdate<K, F>) {
+        let mut state = self.state.write().expect("lock is poisoned");
+        state.apply(update, Instant::now());
+        state.request_redraw();
+    }
+
+    /// Applies all the updates from the `updates` stream as they come, until the stream ends or the layer is
+    /// dropped. The stream is polled by a background task, so this method returns immediately.
+    ///
+    /// Several streams can be subscribed to at the same time, e.g. one per data feed.
+    pub fn subscribe(&self, updates: impl Stream<Item = LiveUpdate<K, F>> + MaybeSend + 'static) {
+        // The task must not keep the layer alive after it has been removed from the map.
+        let state = Arc::downgrade(&self.state);
+        crate::async_runtime::spawn(async move {
+            let mut updates = std::pin::pin!(updates);
+            while let Some(update) = updates.next().await {
+                let Some(state) = state.upgrade() else {
+                    return;
+                };
+
+                let mut state = state.write().expect("lock is poisoned");
+                state.apply(update, Instant::now());
+                state.request_redraw();
+            }
+        });
+    }
+
+    /// Number of features in the layer.
+    pub fn len(&self) -> usize {
+        self.state.read().expect("lock is poisoned").keys.len()
+    }
+
+    /// Returns `true` if there are no features in the layer.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index of the feature with the key in the [feature layer](LiveFeatureLayer::access_feature_layer).
+    ///
+    /// Indices change when features are deleted, so they should be used right away.
+    pub fn feature_index(&self, key: &K) -> Option<usize> {
+        self.state
+            .read()
+            .expect("lock is poisoned")
+            .indices
+            .get(key)
+            .copied()
+    }
+
+    /// Key of the feature with the given index.
+    pub fn key(&self, index: usize) -> Option<K> {
+        self.state
+            .read()
+            .expect("lock is poisoned")
+            .keys
+            .get(index)
+            .cloned()
+    }
+
+    /// Key of the feature with the given id, e.g. of the feature returned by [`Layer::feature_at`].
+    pub fn key_of(&self, id: FeatureId) -> Option<K> {
+        let state = self.state.read().expect("lock is poisoned");
+        let index = state.layer.features().index_of(id)?;
+        state.keys.get(index).cloned()
+    }
+
+    /// Calls `f` with the feature layer that renders the features, e.g. to query them or modify their styles.
+    ///
+    /// The features must not be added or removed through the feature layer, since the layer would lose track of
+    /// their keys.
+    pub fn access_feature_layer<T>(
+        &self,
+        f: impl FnOnce(&mut FeatureLayerImpl<P, F, S>) -> T,
+    ) -> T {
+        f(&mut self.state.write().expect("lock is poisoned").layer)
+    }
+}
+
+impl<K, P, F, S> LiveFeatureLayer<K, P, F, S>
+where
+    K: Hash + Eq + Clone + MaybeSend + MaybeSync + 'static,
+    P: NewGeoPoint + 'static,
+    F: MovingFeature + MaybeSend + MaybeSync + 'static,
+    F::Geom: Geometry<Point = P>,
+    S: Symbol<F> + MaybeSend + MaybeSync + 'static,
+{
+    /// Enables smooth movement of the features. When a feature is updated, it is moved from its current position to
+    /// the new position during the `duration`. Usually the duration is set to the expected interval between updates,
+    /// so that the features move steadily.
+    pub fn with_interpolation(self, duration: Duration) -> Self {
+        self.state.write().expect("lock is poisoned").interpolation = Some(Interpolation {
+            duration,
+            position: F::position,
+            set_position: F::set_position,
+        });
+        self
+    }
+}
+
+impl<K, P, F, S> LiveState<K, P, F, S>
+where
+    K: Hash + Eq + Clone,
+    P: NewGeoPoint + 'static,
+    F: Feature + MaybeSend + MaybeSync + 'static,
+    F::Geom: Geometry<Point = P>,
+    S: Symbol<F> + MaybeSend + MaybeSync + 'static,
+{
+    fn apply(&mut self, update: LiveUpdate<K, F>, now: Instant) {
+        match update {
+            LiveUpdate::Upsert(key, feature) => self.upsert(key, feature, now),
+            LiveUpdate::Delete(key) => self.delete(&key),
+            LiveUpdate::Clear => {
+                for index in (0..self.keys.len()).rev() {
+                    self.layer.remove_feature(index);
+                }
+                self.keys.clear();
+                self.indices.clear();
+                self.motions.clear();
+            }
+        }
+    }
+
+    fn upsert(&mut self, key: K, mut feature: F, now: Instant) {
+        let Some(&index) = self.indices.get(&key) else {
+            let index = self.layer.add_feature(feature);
+            self.indices.insert(key.clone(), index);
+            self.keys.push(key);
+            return;
+        };
+
+        if let Some(interpolation) = &self.interpolation {
+            let current = self
+                .layer
+                .features()
+                .get(index)
+                .and_then(interpolation.position);
+            match (current, (interpolation.position)(&feature)) {
+                (Some(from), Some(to)) if !interpolation.duration.is_zero() => {
+                    (interpolation.set_position)(&mut feature, from);
+                    self.motions.insert(
+                        key,
+                        Motion {
+                            from,
+                            to,
+                            started: now,
+                        },
+                    );
+                }
+                _ => {
+                    self.motions.remove(&key);
+                }
+            }
+        }
+
+        self.layer.update_feature(index, |old| *old = feature);
+    }
+
+    fn delete(&mut self, key: &K) {
+        let Some(index) = self.indices.remove(key) else {
+            return;
+        };
+
+        self.layer.remove_feature(index);
+        self.keys.remove(index);
+        self.motions.remove(key);
+        for key in &self.keys[index..] {
+            if let Some(index) = self.indices.get_mut(key) {
+                *index -= 1;
+            }
+        }
+    }
+
+    /// Moves the features with running motions to their positions at the time `now`. Returns `true` if some motions
+    /// are not finished yet.
+    fn advance(&mut self, now: Instant) -> bool {
+        let Some(interpolation) = &self.interpolation else {
+            return false;
+        };
+        if self.motions.is_empty() {
+            return false;
+        }
+
+        let duration = interpolation.duration.as_secs_f64();
+        let set_position = interpolation.set_position;
+        let mut finished = vec![];
+        for (key, motion) in &self.motions {
+            let share = (now.duration_since(motion.started).as_secs_f64() / duration).min(1.0);
+            if share >= 1.0 {
+                finished.push(key.clone());
+            }
+
+            if let Some(&index) = self.indices.get(key) {
+                let position = motion.position(share);
+                self.layer
+                    .update_feature(index, |feature| set_position(feature, position));
+            }
+        }
+
+        for key in finished {
+            self.motions.remove(&key);
+        }
+
+        !self.motions.is_empty()
+    }
+
+    fn request_redraw(&self) {
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
+        }
+    }
+}
+
+impl<K, P, F, S> Layer for LiveFeatureLayer<K, P, F, S>
+where
+    K: Hash + Eq + Clone + MaybeSend + MaybeSync + 'static,
+    P: NewGeoPoint + 'static,
+    F: Feature + MaybeSend + MaybeSync + 'static,
+    F::Geom: Geometry<Point = P>,
+    S: Symbol<F> + MaybeSend + MaybeSync + 'static,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let is_moving = self
+            .state
+            .write()
+            .expect("lock is poisoned")
+            .advance(Instant::now());
+
+        let state = self.state.read().expect("lock is poisoned");
+        state.layer.render(view, canvas);
+        if is_moving {
+            state.request_redraw();
+        }
+    }
+
+    fn prepare(&self, _view: &MapView) {
+        // do nothing
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        let messenger: Arc<dyn Messenger> = Arc::from(messenger);
+        let mut state = self.state.write().expect("lock is poisoned");
+        state.layer.set_messenger(Box::new(messenger.clone()));
+        state.messenger = Some(messenger);
+    }
+
+    fn feature_at(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> Option<FeatureId> {
+        self.state
+            .read()
+            .expect("lock is poisoned")
+            .layer
+            .feature_at(view, screen_point, tolerance)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::symbol::CirclePointSymbol;
+    use crate::Color;
+    use galileo_types::geo::GeoPoint;
+    use galileo_types::latlon;
+
+    type TestLayer = LiveFeatureLayer<u32, GeoPoint2d, GeoPoint2d, CirclePointSymbol>;
+
+    fn test_layer() -> TestLayer {
+        LiveFeatureLayer::new(CirclePointSymbol::new(Color::RED, 4.0), Crs::WGS84)
+    }
+
+    fn position(layer: &TestLayer, key: u32) -> GeoPoint2d {
+        let index = layer.feature_index(&key).unwrap();
+        layer.access_feature_layer(|layer| *layer.features().get(index).unwrap())
+    }
+
+    #[test]
+    fn applies_updates_by_key() {
+        let layer = test_layer();
+        layer.apply(LiveUpdate::Upsert(1, latlon!(1.0, 1.0)));
+        layer.apply(LiveUpdate::Upsert(2, latlon!(2.0, 2.0)));
+        layer.apply(LiveUpdate::Upsert(3, latlon!(3.0, 3.0)));
+        layer.apply(LiveUpdate::Upsert(1, latlon!(1.5, 1.5)));
+        assert_eq!(layer.len(), 3);
+        assert_eq!(position(&layer, 1), latlon!(1.5, 1.5));
+
+        layer.apply(LiveUpdate::Delete(2));
+        layer.apply(LiveUpdate::Delete(4));
+        assert_eq!(layer.len(), 2);
+        assert_eq!(layer.feature_index(&3), Some(1));
+        assert_eq!(layer.key(1), Some(3));
+        assert_eq!(position(&layer, 3), latlon!(3.0, 3.0));
+
+        layer.apply(LiveUpdate::Clear);
+        assert!(layer.is_empty());
+        assert_eq!(
+            layer.access_feature_layer(|layer| layer.features().len()),
+            0
+        );
+    }
+
+    #[test]
+    fn interpolates_positions() {
+        let layer = test_layer().with_interpolation(Duration::from_secs(2));
+        let start = Instant::now();
+        {
+            let mut state = layer.state.write().unwrap();
+            state.apply(LiveUpdate::Upsert(1, latlon!(0.0, 179.0)), start);
+            state.apply(LiveUpdate::Upsert(1, latlon!(10.0, -179.0)), start);
+        }
+        assert_eq!(position(&layer, 1), latlon!(0.0, 179.0));
+
+        assert!(layer
+            .state
+            .write()
+            .unwrap()
+            .advance(start + Duration::from_secs(1)));
+        let halfway = position(&layer, 1);
+        assert!((halfway.lat() - 5.0).abs() < 1e-9);
+        assert!((halfway.lon().abs() - 180.0).abs() < 1e-9);
+
+        assert!(!layer
+            .state
+            .write()
+            .unwrap()
+            .advance(start + Duration::from_secs(3)));
+        assert_eq!(position(&layer, 1), latlon!(10.0, -179.0));
+    }
+
+    #[test]
+    fn subscribes_to_stream() {
+        let layer = test_layer();
+        let updates = futures::stream::iter([
+            LiveUpdate::Upsert(1, latlon!(1.0, 1.0)),
+            LiveUpdate::Upsert(2, latlon!(2.0, 2.0)),
+            LiveUpdate::Delete(1),
+        ]);
+
+        tokio_test::block_on(async {
+            layer.subscribe(updates);
+            while layer.len() != 1 || layer.key(0) != Some(2) {
+                tokio::task::yield_now().await;
+            }
+        });
+    }
+}
@@ -1,28 +1,77 @@
 //! [Layers](Layer) specify a data source and the way the data should be rendered to the map.
 
+use crate::layer::feature_layer::FeatureId;
 use crate::messenger::Messenger;
 use crate::render::Canvas;
 use crate::view::MapView;
+use galileo_types::cartesian::Point2d;
 use maybe_sync::{MaybeSend, MaybeSync};
 use std::any::Any;
 use std::sync::{Arc, RwLock};
 
+#[cfg(not(target_arch = "wasm32"))]
+mod cog_layer;
+#[cfg(feature = "wgpu")]
+mod custom_layer;
 pub mod data_provider;
+mod extent_indicator_layer;
 pub mod feature_layer;
+#[cfg(not(target_arch = "wasm32"))]
+mod flatgeobuf_layer;
+mod graticule_layer;
+mod heatmap_layer;
+mod layer_group;
+mod live_feature_layer;
 mod raster_tile_layer;
+mod terrain_layer;
+mod tile_request_queue;
 pub mod vector_tile_layer;
+mod wms_layer;
+#[cfg(feature = "wmts")]
+mod wmts_layer;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use cog_layer::{CogLayer, CogOptions, CogRendering};
+#[cfg(feature = "wgpu")]
+pub use custom_layer::{CustomLayer, CustomRenderLayer};
+pub use extent_indicator_layer::ExtentIndicatorLayer;
 pub use feature_layer::FeatureLayer;
-pub use raster_tile_layer::RasterTileLayer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use flatgeobuf_layer::FlatGeobufLayer;
+pub use graticule_layer::{GraticuleLayer, GraticuleOptions};
+pub use heatmap_layer::{ColorRamp, HeatmapLayer, HeatmapOptions};
+pub use layer_group::LayerGroup;
+pub use live_feature_layer::{LiveFeatureLayer, LiveUpdate, MovingFeature};
+pub use raster_tile_layer::{RasterTileLayer, TileProgress};
+pub use terrain_layer::{DemEncoding, HillshadeOptions, TerrainLayer};
 pub use vector_tile_layer::VectorTileLayer;
+pub use wms_layer::{WmsLayerBuilder, WmsVersion};
+#[cfg(feature = "wmts")]
+pub use wmts_layer::{
+    WmtsCapabilities, WmtsEncoding, WmtsLayerBuilder, WmtsLayerInfo, WmtsResourceUrl,
+    WmtsTileMatrix, WmtsTileMatrixSet,
+};
 
 /// Layers specify a data source and the way the data should be rendered to the map.
 ///
-/// There are currently 3 types of layers:
-/// * [`RasterTileLayer`] - downloads prerendered tiles from an Internet source and draws them as is.
+/// There are currently 10 types of layers:
+/// * [`RasterTileLayer`] - downloads prerendered tiles from an Internet source and draws them as is. A layer showing
+///   the images of a WMS server can be created with [`WmsLayerBuilder`], and a layer of a WMTS server with
+///   `WmtsCapabilities` (requires `wmts` feature).
 /// * [`VectorTileLayer`] - downloads vector tiles (in MVT format) from an Internet source and draws them using the
 ///   provided stylesheet.
 /// * [`FeatureLayer`] - draws custom set of geographic objects with the given [`feature_layer::Symbol`];
+/// * [`HeatmapLayer`] - draws the density surface of a set of weighted points.
+/// * [`LiveFeatureLayer`] - draws features updated in real time, e.g. from a WebSocket stream.
+/// * [`TerrainLayer`] - draws the hillshaded relief of the terrain from elevation tiles.
+/// * [`GraticuleLayer`] - draws the grid of meridians and parallels with their labels.
+/// * [`ExtentIndicatorLayer`] - draws the footprint of another map view, e.g. in an [`OverviewMap`](crate::OverviewMap).
+/// * `FlatGeobufLayer` - draws the features of a large FlatGeobuf file, loading only the features in the current view.
+/// * `CogLayer` - draws a Cloud-Optimized GeoTIFF image, reading only the tiles of the overview needed for the current
+///   resolution.
+///
+/// Several layers can be combined into a [`LayerGroup`] to be shown, hidden and faded together. Applications can also
+/// draw their own content with wgpu pipelines using a `CustomLayer`.
 pub trait Layer: MaybeSend + MaybeSync {
     /// Renders the layer to the given canvas.
     fn render(&self, view: &MapView, canvas: &mut dyn Canvas);
@@ -36,6 +85,46 @@ pub trait Layer: MaybeSend + MaybeSync {
     fn as_any(&self) -> &dyn Any;
     /// A map stores layers as trait objects. This method can be used to convert the trait object into the concrete type.
     fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Attributions of the data displayed by the layer, that the application should show together with the map.
+    /// Returns an empty list by default.
+    fn attributions(&self) -> Vec<Attribution> {
+        Vec::new()
+    }
+    /// Returns `false` while the layer is still loading the data needed to draw the `view`. Layers that do not load
+    /// their data asynchronously always return `true`, which is the default.
+    fn is_loaded(&self, _view: &MapView) -> bool {
+        true
+    }
+    /// Returns the id of the topmost feature of the layer drawn at the `screen_point` of the `view`, or closer to it
+    /// than `tolerance` pixels. Used by the [`EventProcessor`](crate::control::EventProcessor) to report the
+    /// features under the pointer in [`MapEvent`](crate::control::MapEvent)s. Returns `None` by default.
+    fn feature_at(
+        &self,
+        _view: &MapView,
+        _screen_point: Point2d,
+        _tolerance: f64,
+    ) -> Option<FeatureId> {
+        None
+    }
+}
+
+/// Attribution of the data displayed by a layer, e.g. the copyright notice of the tile provider.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Attribution {
+    /// Text of the attribution.
+    pub text: String,
+    /// Optional link to the data source or its license.
+    pub url: Option<String>,
+}
+
+impl Attribution {
+    /// Creates a new attribution.
+    pub fn new(text: impl Into<String>, url: Option<String>) -> Self {
+        Self {
+            text: text.into(),
+            url,
+        }
+    }
 }
 
 impl<T: Layer + 'static> Layer for Arc<RwLock<T>> {
@@ -62,6 +151,25 @@ impl<T: Layer + 'static> Layer for Arc<RwLock<T>> {
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn attributions(&self) -> Vec<Attribution> {
+        self.read().expect("lock is poisoned").attributions()
+    }
+
+    fn is_loaded(&self, view: &MapView) -> bool {
+        self.read().expect("lock is poisoned").is_loaded(view)
+    }
+
+    fn feature_at(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> Option<FeatureId> {
+        self.read()
+            .expect("lock is poisoned")
+            .feature_at(view, screen_point, tolerance)
+    }
 }
 
 /// Used for doc-tests
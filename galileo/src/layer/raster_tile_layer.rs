@@ -1,20 +1,48 @@
 use crate::decoded_image::DecodedImage;
-use crate::layer::data_provider::DataProvider;
+use crate::error::GalileoError;
+use crate::layer::data_provider::{CacheUsage, DataProvider};
 use crate::messenger::Messenger;
 use crate::render::render_bundle::RenderBundle;
-use crate::render::{Canvas, ImagePaint, PackedBundle, PrimitiveId, RenderOptions};
-use crate::tile_scheme::{TileIndex, TileSchema};
+use crate::render::{
+    Canvas, ColorAdjustment, ImagePaint, PackedBundle, PrimitiveId, RenderOptions,
+};
+use crate::tile_scheme::{clamp_latitude, rect_outline, REPROJECTION_SAMPLES};
+use crate::tile_scheme::{PrefetchPolicy, TileIndex, TileSchema};
 use crate::view::MapView;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use futures_intrusive::sync::ManualResetEvent;
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
+use galileo_types::geo::impls::projection::CrsProjection;
+use galileo_types::geo::Crs;
 use maybe_sync::{MaybeSend, MaybeSync, Mutex};
 use quick_cache::sync::Cache;
+use quick_cache::Weighter;
 use std::any::Any;
 use std::collections::HashSet;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use web_time::{Duration, SystemTime};
 
-use super::Layer;
+use super::tile_request_queue::TileRequestQueue;
+use super::{Attribution, Layer};
+
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 6;
+const DEFAULT_MEMORY_CACHE_ENTRIES: usize = 5000;
+const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const DEFAULT_MEMORY_CACHE_BYTES: u64 = 512 * 1024 * 1024;
+
+type TileCache = Cache<TileIndex, Arc<TileState>, TileWeighter>;
 
 /// Raster tile layers load prerender tile sets using [`Provider`](DataProvider) and render them to the map.
+///
+/// The CRS of the tile schema can be different from the CRS of the map. In this case the layer loads the tiles
+/// covering the visible area in the schema CRS and warps every tile image into the map CRS before drawing it. For
+/// example, tiles of a WMTS source in EPSG:4326 can be displayed on a map in EPSG:3857.
+///
+/// If a tile cannot be loaded, it is not drawn, and the area it covers is filled with the tiles of other levels
+/// that are already loaded (if any). This allows using the layer without network access: a provider in offline mode
+/// (see [`UrlImageProvider::set_offline_mode`](super::data_provider::UrlImageProvider::set_offline_mode)) reads the
+/// tiles only from its persistent cache, and the tiles missing from the cache are rendered as blank.
 pub struct RasterTileLayer<Provider>
 where
     Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
@@ -22,15 +50,69 @@ where
     tile_provider: Arc<Provider>,
     tile_scheme: TileSchema,
     fade_in_duration: Duration,
-    tiles: Arc<Cache<TileIndex, Arc<TileState>>>,
+    tiles: Arc<TileCache>,
     prev_drawn_tiles: Mutex<Vec<TileIndex>>,
+    rendered_crs: Mutex<Option<Crs>>,
     messenger: Option<Arc<dyn Messenger>>,
+    request_queue: Arc<TileRequestQueue>,
+    /// The last prepared view and the tiles it needs, including its world copies prepared after it.
+    needed_tiles: Mutex<Option<(MapView, Vec<TileIndex>)>>,
+    retry_policy: RetryPolicy,
+    prefetch: PrefetchPolicy,
+    attribution: Option<Attribution>,
+    color_adjustment: ColorAdjustment,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    fn should_retry(&self, attempt: u32, error: &GalileoError) -> bool {
+        // Missing tile, undecodable data or a client error will not change with the next attempt, unless the server
+        // asks to slow down.
+        attempt < self.max_attempts
+            && match error {
+                GalileoError::IO => true,
+                GalileoError::Http { status, .. } => *status == 429 || *status >= 500,
+                _ => false,
+            }
+    }
+
+    fn delay(&self, attempt: u32, error: &GalileoError) -> Duration {
+        if let GalileoError::Http {
+            retry_after: Some(retry_after),
+            ..
+        } = error
+        {
+            return (*retry_after).min(self.max_delay);
+        }
+
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+
+        // Jitter prevents all failed tiles from being requested again at the same moment.
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        delay
+            .mul_f64(0.5 + (nanos % 1000) as f64 / 2000.0)
+            .min(self.max_delay)
+    }
 }
 
 enum TileState {
-    Loading,
-    Loaded(Mutex<DecodedImage>),
-    Rendered(Box<Mutex<RenderedTile>>),
+    /// The tile is being loaded. The event is set when the loading is finished, whatever its result.
+    Loading(Arc<ManualResetEvent>),
+    /// Loaded image and its size in bytes. The image is taken out of the mutex when the tile is rendered.
+    Loaded(Mutex<DecodedImage>, usize),
+    /// Rendered tile and the size of its data in bytes.
+    Rendered(Box<Mutex<RenderedTile>>, usize),
     Error,
 }
 
@@ -56,9 +138,45 @@ where
             tile_provider: Arc::new(tile_provider),
             tile_scheme,
             prev_drawn_tiles: Mutex::new(vec![]),
+            rendered_crs: Mutex::new(None),
             fade_in_duration: Duration::from_millis(300),
-            tiles: Arc::new(Cache::new(5000)),
+            tiles: Arc::new(create_tile_cache(
+                DEFAULT_MEMORY_CACHE_ENTRIES,
+                DEFAULT_MEMORY_CACHE_BYTES,
+            )),
             messenger,
+            request_queue: Arc::new(TileRequestQueue::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            needed_tiles: Mutex::new(None),
+            retry_policy: RetryPolicy {
+                max_attempts: 1,
+                base_delay: Duration::ZERO,
+                max_delay: DEFAULT_MAX_RETRY_DELAY,
+            },
+            prefetch: PrefetchPolicy::default(),
+            attribution: None,
+            color_adjustment: ColorAdjustment::default(),
+        }
+    }
+
+    /// Sets the attribution of the tile source, returned by [`Layer::attributions`].
+    pub fn set_attribution(&mut self, attribution: Option<Attribution>) {
+        self.attribution = attribution;
+    }
+
+    /// Color adjustments applied to the tiles when they are drawn.
+    pub fn color_adjustment(&self) -> ColorAdjustment {
+        self.color_adjustment
+    }
+
+    /// Sets the color adjustments applied to the tiles when they are drawn, e.g.
+    /// [`ColorAdjustment::dark_mode`] to show a light basemap in dark colors.
+    ///
+    /// The adjustments are applied by the renderer, so changing them does not reload or re-render the tiles and
+    /// takes effect on the next redraw of the map, which is requested by this method.
+    pub fn set_color_adjustment(&mut self, adjustment: ColorAdjustment) {
+        self.color_adjustment = adjustment;
+        if let Some(messenger) = &self.messenger {
+            messenger.request_redraw();
         }
     }
 
@@ -67,9 +185,84 @@ where
         self.fade_in_duration = duration;
     }
 
+    /// Sets the maximum number of tiles that can be loaded by the layer simultaneously. Other tiles wait in queue
+    /// until one of the loading tiles is done.
+    ///
+    /// The tiles requested by [`Layer::prepare`] are loaded from the center of the view to its edges. When the view
+    /// changes, the requests for the tiles that are not needed for the new view anymore are cancelled, both waiting
+    /// in the queue and already being loaded, so that panning and zooming quickly does not delay the tiles of the
+    /// current view. The tiles requested by [`RasterTileLayer::load_tiles`] are never cancelled.
+    ///
+    /// Default value is `6`. Value of `0` is treated as `1`. Requests that are already being loaded are not
+    /// interrupted if the new limit is lower.
+    pub fn set_max_concurrent_requests(&mut self, max_requests: usize) {
+        self.request_queue.set_max_concurrent(max_requests);
+    }
+
+    /// Sets the number of attempts to load a tile in case of network errors.
+    ///
+    /// After a failed attempt the layer waits before trying again. The delay is `base_delay` after the first
+    /// attempt and is doubled after every next one, with a random jitter of up to a half of the delay. If the server
+    /// sets the `Retry-After` header, the layer waits for the requested time instead. The delay never exceeds the
+    /// value set by [`RasterTileLayer::set_max_retry_delay`].
+    ///
+    /// Tiles that do not exist in the source ([`GalileoError::NotFound`]) or cannot be decoded are not retried.
+    /// Client errors of the server ([`GalileoError::Http`] with `4xx` status) are not retried either, except for
+    /// `429 Too Many Requests`.
+    ///
+    /// By default, every tile is requested only once. Value of `0` is treated as `1`.
+    pub fn set_retry(&mut self, max_attempts: u32, base_delay: Duration) {
+        self.retry_policy = RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            ..self.retry_policy
+        };
+    }
+
+    /// Sets the longest delay between the attempts to load a tile, see [`RasterTileLayer::set_retry`]. Longer delays
+    /// requested by the server with the `Retry-After` header are shortened to this value.
+    ///
+    /// Default value is 60 seconds.
+    pub fn set_max_retry_delay(&mut self, max_delay: Duration) {
+        self.retry_policy.max_delay = max_delay;
+    }
+
+    /// Sets which tiles outside of the visible area are loaded in advance. See [`PrefetchPolicy`].
+    ///
+    /// By default, only the visible tiles are loaded.
+    pub fn set_prefetch(&mut self, policy: PrefetchPolicy) {
+        self.prefetch = policy;
+    }
+
+    /// Sets the limits of the in-memory cache of the loaded tiles. When a limit is exceeded, least recently used tiles
+    /// are removed from the cache.
+    ///
+    /// Every tile is counted as taking at least `max_bytes / max_entries` bytes, so the cache never holds more than
+    /// `max_entries` tiles. By default, the cache holds up to 5000 tiles or 512 MiB of tiles. Setting the limits
+    /// clears the cache.
+    pub fn set_memory_cache_limits(&mut self, max_entries: usize, max_bytes: u64) {
+        self.tiles = Arc::new(create_tile_cache(max_entries, max_bytes));
+        self.prev_drawn_tiles.lock().clear();
+    }
+
+    /// Returns the number of tiles in the in-memory cache and their size. Tiles that are still loading are counted
+    /// with the minimum size set by [`RasterTileLayer::set_memory_cache_limits`].
+    pub fn memory_cache_usage(&self) -> CacheUsage {
+        CacheUsage {
+            entries: self.tiles.len(),
+            bytes: self.tiles.weight(),
+        }
+    }
+
+    /// Removes all tiles from the in-memory cache. The tiles will be loaded again when they are needed.
+    pub fn clear_memory_cache(&self) {
+        self.tiles.clear();
+        self.prev_drawn_tiles.lock().clear();
+    }
+
     fn get_tiles_to_draw(&self, view: &MapView) -> Vec<(TileIndex, Arc<TileState>)> {
         let mut tiles = vec![];
-        let Some(tile_iter) = self.tile_scheme.iter_tiles(view) else {
+        let Some(tile_iter) = self.tile_scheme.iter_tiles_reprojected(view) else {
             return vec![];
         };
 
@@ -80,14 +273,14 @@ where
             match self.tiles.get(&index) {
                 None => to_substitute.push(index),
                 Some(tile_state) => match &*tile_state.clone() {
-                    TileState::Rendered(tile) => {
+                    TileState::Rendered(tile, _) => {
                         if !tile.lock().is_opaque {
                             to_substitute.push(index);
                         }
 
                         tiles.push((index, tile_state));
                     }
-                    TileState::Loaded(_) => {
+                    TileState::Loaded(..) => {
                         to_substitute.push(index);
                         tiles.push((index, tile_state));
                     }
@@ -110,14 +303,14 @@ where
                     next_level = substitute_index;
 
                     if let Some(tile) = self.tiles.get(&substitute_index) {
-                        if matches!(*tile, TileState::Rendered(_))
+                        if matches!(*tile, TileState::Rendered(..))
                             && !substitute_indices.contains(&substitute_index)
                         {
                             substitute_tiles.push((substitute_index, tile));
                             substitute_indices.insert(substitute_index);
                         }
 
-                        if let Some(TileState::Rendered(rendered)) = self
+                        if let Some(TileState::Rendered(rendered, _)) = self
                             .tiles
                             .get(&substitute_index)
                             .as_ref()
@@ -146,7 +339,7 @@ where
                     let Some(prev_bbox) = self.tile_scheme.tile_bbox(*prev) else {
                         continue;
                     };
-                    if !substitute_indices.contains(prev) && prev_bbox.intersects(required_bbox) {
+                    if !substitute_indices.contains(prev) && prev_bbox.intersects(&required_bbox) {
                         substitute_indices.insert(*prev);
                         let Some(tile) = self.tiles.get(prev) else {
                             continue;
@@ -157,19 +350,29 @@ where
             }
         }
 
-        substitute_tiles.sort_unstable_by(|(index_a, _), (index_b, _)| index_a.z.cmp(&index_b.z));
+        substitute_tiles.sort_unstable_by_key(|(index, _)| index.z);
         substitute_tiles.append(&mut tiles);
         substitute_tiles.dedup_by(|a, b| a.0 == b.0);
         substitute_tiles
     }
 
-    fn prepare_tile_renders(&self, tiles: &[(TileIndex, Arc<TileState>)], canvas: &mut dyn Canvas) {
+    fn prepare_tile_renders(
+        &self,
+        tiles: &[(TileIndex, Arc<TileState>)],
+        crs: &Crs,
+        canvas: &mut dyn Canvas,
+    ) {
         let mut requires_redraw = false;
+        let reprojection = if *crs != self.tile_scheme.crs {
+            self.tile_scheme.crs.projection_to(crs)
+        } else {
+            None
+        };
 
         let now = SystemTime::now();
         for (index, tile) in tiles {
             match &**tile {
-                TileState::Rendered(rendered) => {
+                TileState::Rendered(rendered, _) => {
                     let mut rendered = rendered.lock();
                     if rendered.is_opaque {
                         continue;
@@ -204,8 +407,7 @@ where
                     rendered.packed_bundle = packed;
                     rendered.is_opaque = is_opaque;
                 }
-                TileState::Loaded(decoded_image) => {
-                    let mut bundle = canvas.create_bundle();
+                TileState::Loaded(decoded_image, _) => {
                     let mut decoded_image = decoded_image.lock();
 
                     let owned = std::mem::replace(
@@ -222,26 +424,25 @@ where
                         0
                     };
 
-                    let Some(tile_bbox) = self.tile_scheme.tile_bbox(*index) else {
-                        log::warn!("Failed to get bbox for tile {index:?}");
+                    let Some((bundle, id)) =
+                        self.tile_bundle(*index, owned, opacity, reprojection.as_ref(), canvas)
+                    else {
                         continue;
                     };
-
-                    let id = bundle.add_image(
-                        owned,
-                        tile_bbox.into_quadrangle(),
-                        ImagePaint { opacity },
-                    );
                     let packed = canvas.pack_bundle(&bundle);
+                    let size = bundle.approx_buffer_size();
                     self.tiles.insert(
                         *index,
-                        Arc::new(TileState::Rendered(Box::new(Mutex::new(RenderedTile {
-                            render_bundle: bundle,
-                            packed_bundle: packed,
-                            first_drawn: now,
-                            is_opaque: false,
-                            primitive_id: id,
-                        })))),
+                        Arc::new(TileState::Rendered(
+                            Box::new(Mutex::new(RenderedTile {
+                                render_bundle: bundle,
+                                packed_bundle: packed,
+                                first_drawn: now,
+                                is_opaque: false,
+                                primitive_id: id,
+                            })),
+                            size,
+                        )),
                     );
 
                     requires_redraw = true;
@@ -257,51 +458,419 @@ where
         }
     }
 
+    /// Creates the bundle drawing the tile `image` with the given `opacity`, reprojecting it with the `reprojection`
+    /// if the CRS of the view is different from the CRS of the tile schema.
+    fn tile_bundle(
+        &self,
+        index: TileIndex,
+        image: DecodedImage,
+        opacity: u8,
+        reprojection: Option<&CrsProjection>,
+        canvas: &dyn Canvas,
+    ) -> Option<(RenderBundle, PrimitiveId)> {
+        let Some(tile_bbox) = self.tile_scheme.tile_bbox(index) else {
+            log::warn!("Failed to get bbox for tile {index:?}");
+            return None;
+        };
+
+        let (image, tile_bbox) = match reprojection {
+            Some(projection) => {
+                let Some(reprojected) =
+                    reproject_tile_image(&image, tile_bbox, &self.tile_scheme.crs, projection)
+                else {
+                    log::warn!("Failed to reproject tile {index:?}");
+                    return None;
+                };
+                reprojected
+            }
+            None => (image, tile_bbox),
+        };
+
+        let mut bundle = canvas.create_bundle();
+        let id = bundle.add_image(image, tile_bbox.into_quadrangle(), ImagePaint { opacity });
+        Some((bundle, id))
+    }
+
+    /// Packs the tiles for a transient canvas, without changing the state of the tiles in the cache. Bundles stored in
+    /// the cache are packed for the canvas the map is usually drawn to, so the transient canvas gets its own ones, and
+    /// the tiles are drawn without fading in.
+    fn pack_transient(
+        &self,
+        tiles: &[(TileIndex, Arc<TileState>)],
+        crs: &Crs,
+        canvas: &dyn Canvas,
+    ) -> Vec<Box<dyn PackedBundle>> {
+        let reprojection = if *crs != self.tile_scheme.crs {
+            self.tile_scheme.crs.projection_to(crs)
+        } else {
+            None
+        };
+
+        tiles
+            .iter()
+            .filter_map(|(index, tile)| match &**tile {
+                TileState::Rendered(rendered, _) => {
+                    let rendered = rendered.lock();
+                    let mut bundle = rendered.render_bundle.clone();
+                    if let Err(err) =
+                        bundle.modify_image(rendered.primitive_id, ImagePaint { opacity: 255 })
+                    {
+                        log::warn!("Failed to update image style: {err}");
+                    }
+                    Some(canvas.pack_bundle(&bundle))
+                }
+                TileState::Loaded(image, _) => {
+                    let image = image.lock().clone();
+                    let (bundle, _) =
+                        self.tile_bundle(*index, image, 255, reprojection.as_ref(), canvas)?;
+                    Some(canvas.pack_bundle(&bundle))
+                }
+                TileState::Loading(_) | TileState::Error => None,
+            })
+            .collect()
+    }
+
+    /// Loads the tile into the `tiles` cache. Returns `true` if the tile data is in the cache after the call.
+    ///
+    /// If the tile is already being loaded, waits until that loading is finished. Tiles that failed to load before are
+    /// loaded again if `reload_failed` is set, and are reported as failed otherwise.
+    ///
+    /// The cache is referenced weakly, so that if the layer is dropped while the tile is being loaded (e.g. when the
+    /// layer is removed from the map), the loading is stopped and the loaded data is discarded.
+    #[allow(clippy::too_many_arguments)]
     async fn load_tile(
         index: TileIndex,
         tile_provider: Arc<Provider>,
-        tiles: &Cache<TileIndex, Arc<TileState>>,
+        tiles: Weak<TileCache>,
         messenger: Option<Arc<dyn Messenger>>,
-    ) {
-        match tiles.get_value_or_guard_async(&index).await {
-            Ok(_) => {}
-            Err(guard) => {
-                let _ = guard.insert(Arc::new(TileState::Loading));
-                let load_result = tile_provider.load(&index, ()).await;
-
-                match load_result {
-                    Ok(decoded_image) => {
-                        if let Some(v) = tiles.get(&index) {
-                            if matches!(*v, TileState::Rendered(_)) {
-                                log::error!("This should not happen to {index:?}");
-                            }
-                        }
+        request_queue: Arc<TileRequestQueue>,
+        retry_policy: RetryPolicy,
+        cancellable: bool,
+        reload_failed: bool,
+    ) -> bool {
+        let _loading = loop {
+            let Some(cache) = tiles.upgrade() else {
+                return false;
+            };
 
-                        tiles.insert(
-                            index,
-                            Arc::new(TileState::Loaded(Mutex::new(decoded_image))),
-                        );
+            let tile = match cache.get_value_or_guard_async(&index).await {
+                Ok(tile) => tile,
+                Err(guard) => {
+                    let finished = Arc::new(ManualResetEvent::new(false));
+                    let _ = guard.insert(Arc::new(TileState::Loading(finished.clone())));
+                    break LoadingGuard(finished);
+                }
+            };
 
-                        if let Some(messenger) = messenger {
-                            messenger.request_redraw();
-                        }
+            match &*tile {
+                TileState::Loading(finished) => {
+                    let finished = finished.clone();
+                    drop(cache);
+                    finished.wait().await;
+                }
+                TileState::Error if reload_failed => {
+                    let finished = Arc::new(ManualResetEvent::new(false));
+                    cache.insert(index, Arc::new(TileState::Loading(finished.clone())));
+                    break LoadingGuard(finished);
+                }
+                TileState::Error => return false,
+                TileState::Loaded(..) | TileState::Rendered(..) => return true,
+            }
+        };
+
+        let mut attempt = 1;
+        let load_result = loop {
+            let request = async {
+                if tiles.strong_count() == 0 {
+                    return None;
+                }
+
+                Some(tile_provider.load(&index, ()).await)
+            };
+            let Some(result) = request_queue
+                .run(index, cancellable, request)
+                .await
+                .flatten()
+            else {
+                // The tile is no longer needed, so it is removed from the cache to be loaded again when it is.
+                if let Some(tiles) = tiles.upgrade() {
+                    tiles.remove(&index);
+                }
+                return false;
+            };
+
+            match result {
+                Err(err) if retry_policy.should_retry(attempt, &err) => {
+                    log::debug!("Failed to load tile {index:?} (attempt {attempt}): {err}");
+                    crate::async_runtime::sleep(retry_policy.delay(attempt, &err)).await;
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+
+        let Some(tiles) = tiles.upgrade() else {
+            log::debug!("Tile {index:?} is loaded for a dropped layer, ignoring it");
+            return false;
+        };
+
+        match load_result {
+            Ok(decoded_image) => {
+                if let Some(v) = tiles.get(&index) {
+                    if matches!(*v, TileState::Rendered(..)) {
+                        log::error!("This should not happen to {index:?}");
                     }
-                    Err(_) => tiles.insert(index, Arc::new(TileState::Error)),
                 }
+
+                let size = decoded_image.bytes.len();
+                tiles.insert(
+                    index,
+                    Arc::new(TileState::Loaded(Mutex::new(decoded_image), size)),
+                );
+
+                if let Some(messenger) = messenger {
+                    messenger.request_redraw();
+                }
+
+                true
+            }
+            Err(_) => {
+                tiles.insert(index, Arc::new(TileState::Error));
+                false
             }
         }
     }
 
     /// Preload tiles for the given `view`.
-    pub async fn load_tiles(&self, view: &MapView) {
-        if let Some(iter) = self.tile_scheme.iter_tiles(view) {
-            for index in iter {
-                let tile_provider = self.tile_provider.clone();
-                let tiles = self.tiles.clone();
-                let messenger = self.messenger.clone();
-                Self::load_tile(index, tile_provider, &tiles, messenger).await;
+    ///
+    /// The tiles are loaded in parallel, but no more than the number set by
+    /// [`RasterTileLayer::set_max_concurrent_requests`] at a time. The returned future resolves when all the tiles
+    /// are either loaded or failed to load. Tiles that are already being loaded (e.g. after [`Layer::prepare`]) are
+    /// waited for, and tiles that failed to load before are requested again.
+    ///
+    /// Returns the number of tiles that could not be loaded (including the retries set by
+    /// [`RasterTileLayer::set_retry`]).
+    pub async fn load_tiles(&self, view: &MapView) -> usize {
+        self.load_tiles_with_progress(view, |_| {}).await.failed
+    }
+
+    /// Blocking version of [`RasterTileLayer::load_tiles`]. Can be called outside of any async runtime.
+    ///
+    /// The tiles are loaded by a new single-threaded runtime, which exists only until the call returns.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn load_tiles_blocking(&self, view: &MapView) -> usize {
+        match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime.block_on(self.load_tiles(view)),
+            Err(err) => {
+                log::error!("Failed to create a runtime to load tiles: {err}");
+                self.tile_scheme
+                    .iter_tiles_reprojected(view)
+                    .map_or(0, |tiles| tiles.len())
+            }
+        }
+    }
+
+    /// Same as [`RasterTileLayer::load_tiles`], but reports the progress of loading to the `on_progress` callback.
+    ///
+    /// The callback is called once before any tile is loaded with the total number of tiles needed for the view, and
+    /// then every time the data of a tile arrives or the tile fails to load after all the retries. Tiles that are
+    /// already being loaded by the layer are counted only when that loading is finished.
+    ///
+    /// The callback is called from the future returned by this method, so it runs on the executor that drives the
+    /// future. The last call reports the same progress as the returned value.
+    pub async fn load_tiles_with_progress(
+        &self,
+        view: &MapView,
+        mut on_progress: impl FnMut(TileProgress),
+    ) -> TileProgress {
+        let mut loading: FuturesUnordered<_> = self
+            .tile_scheme
+            .iter_tiles_reprojected(view)
+            .into_iter()
+            .flatten()
+            .map(|index| {
+                Self::load_tile(
+                    index,
+                    self.tile_provider.clone(),
+                    Arc::downgrade(&self.tiles),
+                    self.messenger.clone(),
+                    self.request_queue.clone(),
+                    self.retry_policy,
+                    false,
+                    true,
+                )
+            })
+            .collect();
+
+        let mut progress = TileProgress {
+            loaded: 0,
+            failed: 0,
+            total: loading.len(),
+        };
+        on_progress(progress);
+
+        while let Some(is_loaded) = loading.next().await {
+            if is_loaded {
+                progress.loaded += 1;
+            } else {
+                progress.failed += 1;
             }
+
+            on_progress(progress);
         }
+
+        progress
+    }
+}
+
+/// Sets the event of a loading tile when the loading is finished or the loading future is dropped.
+struct LoadingGuard(Arc<ManualResetEvent>);
+
+impl Drop for LoadingGuard {
+    fn drop(&mut self) {
+        self.0.set();
+    }
+}
+
+/// Weighs the tiles in the cache by the size of their data, but not less than `min_weight`.
+#[derive(Debug, Clone)]
+struct TileWeighter {
+    min_weight: u32,
+}
+
+impl Weighter<TileIndex, Arc<TileState>> for TileWeighter {
+    fn weight(&self, _key: &TileIndex, val: &Arc<TileState>) -> u32 {
+        let size = match &**val {
+            TileState::Loaded(_, size) | TileState::Rendered(_, size) => *size,
+            TileState::Loading(_) | TileState::Error => 0,
+        };
+
+        u32::try_from(size).unwrap_or(u32::MAX).max(self.min_weight)
+    }
+}
+
+fn create_tile_cache(max_entries: usize, max_bytes: u64) -> TileCache {
+    let max_entries = max_entries.max(1);
+    let min_weight = (max_bytes / max_entries as u64).clamp(1, u32::MAX as u64) as u32;
+    Cache::with_weighter(max_entries, max_bytes.max(1), TileWeighter { min_weight })
+}
+
+/// Distance in pixels between the points of the tile image, for which the source position is projected exactly.
+/// Positions of the pixels between them are interpolated.
+const REPROJECTION_GRID_STEP: u32 = 16;
+
+/// Warps the tile `image` covering the `tile_bbox` in the `tile_crs` into the target CRS of the `projection`.
+///
+/// Returns the new image and the area it covers in the target CRS. The new image has the same size as the original
+/// one, and its pixels outside of the projected tile are transparent. Parts of geographic tiles beyond the latitude
+/// limit of Web Mercator are not drawn.
+fn reproject_tile_image(
+    image: &DecodedImage,
+    tile_bbox: Rect,
+    tile_crs: &Crs,
+    projection: &CrsProjection,
+) -> Option<(DecodedImage, Rect)> {
+    let target_bbox = Rect::from_points(
+        rect_outline(&clamp_latitude(tile_bbox, tile_crs)?, REPROJECTION_SAMPLES)
+            .iter()
+            .map(|point| projection.project_point(point))
+            .collect::<Option<Vec<_>>>()?
+            .iter(),
+    )?;
+
+    let (width, height) = image.dimensions;
+    if width == 0 || height == 0 || target_bbox.width() <= 0.0 || target_bbox.height() <= 0.0 {
+        return None;
+    }
+
+    // Positions of the grid nodes in the source image pixels.
+    let columns = width.div_ceil(REPROJECTION_GRID_STEP) + 1;
+    let rows = height.div_ceil(REPROJECTION_GRID_STEP) + 1;
+    let mut grid = Vec::with_capacity((columns * rows) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            let x = (column * REPROJECTION_GRID_STEP) as f64;
+            let y = (row * REPROJECTION_GRID_STEP) as f64;
+            let target = Point2d::new(
+                target_bbox.x_min() + x / width as f64 * target_bbox.width(),
+                target_bbox.y_max() - y / height as f64 * target_bbox.height(),
+            );
+            grid.push(projection.unproject_point(&target).map(|source| {
+                (
+                    (source.x() - tile_bbox.x_min()) / tile_bbox.width() * width as f64,
+                    (tile_bbox.y_max() - source.y()) / tile_bbox.height() * height as f64,
+                )
+            }));
+        }
+    }
+
+    let mut bytes = vec![0; image.bytes.len()];
+    for y in 0..height {
+        let row = y / REPROJECTION_GRID_STEP;
+        let ty = (y % REPROJECTION_GRID_STEP) as f64 / REPROJECTION_GRID_STEP as f64;
+        for x in 0..width {
+            let column = x / REPROJECTION_GRID_STEP;
+            let tx = (x % REPROJECTION_GRID_STEP) as f64 / REPROJECTION_GRID_STEP as f64;
+            let node = |c: u32, r: u32| grid[(r * columns + c) as usize];
+            let (Some(a), Some(b), Some(c), Some(d)) = (
+                node(column, row),
+                node(column + 1, row),
+                node(column, row + 1),
+                node(column + 1, row + 1),
+            ) else {
+                continue;
+            };
+
+            // Node positions are for the corners of the pixels, so the pixel center is shifted by a half pixel.
+            let tx = tx + 0.5 / REPROJECTION_GRID_STEP as f64;
+            let ty = ty + 0.5 / REPROJECTION_GRID_STEP as f64;
+            let source_x = lerp(lerp(a.0, b.0, tx), lerp(c.0, d.0, tx), ty);
+            let source_y = lerp(lerp(a.1, b.1, tx), lerp(c.1, d.1, tx), ty);
+            if source_x < 0.0
+                || source_y < 0.0
+                || source_x >= width as f64
+                || source_y >= height as f64
+            {
+                continue;
+            }
+
+            let source = (source_y as usize * width as usize + source_x as usize) * 4;
+            let target = (y as usize * width as usize + x as usize) * 4;
+            bytes[target..target + 4].copy_from_slice(&image.bytes[source..source + 4]);
+        }
+    }
+
+    Some((
+        DecodedImage {
+            bytes,
+            dimensions: (width, height),
+        },
+        target_bbox,
+    ))
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+/// Progress of loading tiles by [`RasterTileLayer::load_tiles_with_progress`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TileProgress {
+    /// Number of tiles that are loaded successfully.
+    pub loaded: usize,
+    /// Number of tiles that could not be loaded.
+    pub failed: usize,
+    /// Total number of tiles to load.
+    pub total: usize,
+}
+
+impl TileProgress {
+    /// Returns true if all the tiles are either loaded or failed.
+    pub fn is_complete(&self) -> bool {
+        self.loaded + self.failed >= self.total
     }
 }
 
@@ -310,8 +879,28 @@ where
     Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
 {
     fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        {
+            // Rendered tiles are reprojected into the CRS of the view, so they cannot be reused with another CRS.
+            let mut rendered_crs = self.rendered_crs.lock();
+            if rendered_crs.as_ref().is_some_and(|crs| crs != view.crs()) {
+                self.tiles.clear();
+                self.prev_drawn_tiles.lock().clear();
+            }
+            *rendered_crs = Some(view.crs().clone());
+        }
+
         let tiles = self.get_tiles_to_draw(view);
-        self.prepare_tile_renders(&tiles, canvas);
+        let options = RenderOptions {
+            color_adjustment: self.color_adjustment,
+            ..Default::default()
+        };
+        if canvas.is_transient() {
+            let packed = self.pack_transient(&tiles, view.crs(), canvas);
+            canvas.draw_bundles(&packed.iter().map(|v| &**v).collect::<Vec<_>>(), options);
+            return;
+        }
+
+        self.prepare_tile_renders(&tiles, view.crs(), canvas);
 
         let updated_tiles: Vec<_> = tiles
             .iter()
@@ -319,32 +908,71 @@ where
             .collect();
         let mut to_draw = Vec::new();
         for tile in &updated_tiles {
-            if let TileState::Rendered(rendered) = tile.as_ref() {
+            if let TileState::Rendered(rendered, _) = tile.as_ref() {
                 to_draw.push(rendered.lock());
             }
         }
 
-        canvas.draw_bundles(
-            &to_draw
-                .iter()
-                .map(|guard| &*guard.packed_bundle)
-                .collect::<Vec<_>>(),
-            RenderOptions::default(),
-        );
+        let packed: Vec<&dyn PackedBundle> =
+            to_draw.iter().map(|guard| &*guard.packed_bundle).collect();
+        canvas.draw_bundles(&packed, options);
         *self.prev_drawn_tiles.lock() = tiles.iter().map(|(index, _)| *index).collect();
     }
 
     fn prepare(&self, view: &MapView) {
-        if let Some(iter) = self.tile_scheme.iter_tiles(view) {
-            for index in iter {
-                let tile_provider = self.tile_provider.clone();
-                let tiles = self.tiles.clone();
-                let messenger = self.messenger.clone();
-                crate::async_runtime::spawn(async move {
-                    Self::load_tile(index, tile_provider, &tiles, messenger).await;
-                });
+        let Some(mut to_load) = self.tile_scheme.iter_tiles_reprojected(view) else {
+            return;
+        };
+        self.tile_scheme.sort_by_distance(&mut to_load, view);
+        to_load.extend(self.tile_scheme.prefetch_tiles(view, &self.prefetch));
+
+        {
+            // The map prepares the layer for every visible world copy, and the tiles of all of them are needed.
+            let mut needed_tiles = self.needed_tiles.lock();
+            match &mut *needed_tiles {
+                Some((prepared_view, needed)) if view.is_world_copy_of(prepared_view) => {
+                    needed.extend(to_load.iter().copied());
+                }
+                _ => *needed_tiles = Some((view.clone(), to_load.clone())),
+            }
+
+            if let Some((_, needed)) = &*needed_tiles {
+                self.request_queue.set_needed(needed.iter().copied());
             }
         }
+
+        for index in to_load {
+            let tile_provider = self.tile_provider.clone();
+            let tiles = Arc::downgrade(&self.tiles);
+            let messenger = self.messenger.clone();
+            let request_queue = self.request_queue.clone();
+            let retry_policy = self.retry_policy;
+            crate::async_runtime::spawn(async move {
+                Self::load_tile(
+                    index,
+                    tile_provider,
+                    tiles,
+                    messenger,
+                    request_queue,
+                    retry_policy,
+                    true,
+                    false,
+                )
+                .await;
+            });
+        }
+    }
+
+    fn is_loaded(&self, view: &MapView) -> bool {
+        let Some(tile_iter) = self.tile_scheme.iter_tiles_reprojected(view) else {
+            return true;
+        };
+
+        tile_iter.into_iter().all(|index| {
+            self.tiles
+                .get(&index)
+                .is_some_and(|tile| !matches!(*tile, TileState::Loading(_)))
+        })
     }
 
     fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
@@ -358,4 +986,647 @@ where
     fn as_any_mut(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn attributions(&self) -> Vec<Attribution> {
+        self.attribution.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lod::Lod;
+    use crate::tile_scheme::VerticalDirection;
+    use bytes::Bytes;
+    use futures::future::poll_fn;
+    use galileo_types::cartesian::{Point2d, Rect, Size};
+    use galileo_types::geo::Crs;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::Poll;
+
+    #[derive(Default)]
+    struct RequestCounter {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        loaded: AtomicUsize,
+    }
+
+    struct CountingProvider(Arc<RequestCounter>);
+
+    async fn yield_now() {
+        let mut yielded = false;
+        poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    impl DataProvider<TileIndex, DecodedImage, ()> for CountingProvider {
+        async fn load_raw(&self, _key: &TileIndex) -> Result<Bytes, GalileoError> {
+            let in_flight = self.0.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.0.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
+            for _ in 0..5 {
+                yield_now().await;
+            }
+
+            self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+            self.0.loaded.fetch_add(1, Ordering::SeqCst);
+            Ok(Bytes::new())
+        }
+
+        fn decode(&self, _bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+            Ok(DecodedImage {
+                bytes: vec![],
+                dimensions: (0, 0),
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct MissingProvider(AtomicUsize);
+
+    impl DataProvider<TileIndex, DecodedImage, ()> for MissingProvider {
+        async fn load_raw(&self, _key: &TileIndex) -> Result<Bytes, GalileoError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Err(GalileoError::NotFound)
+        }
+
+        fn decode(&self, _bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+            unreachable!()
+        }
+    }
+
+    /// Fails to load every tile `failures` times with the `error`, then succeeds.
+    struct FlakyProvider {
+        failures: usize,
+        error: fn() -> GalileoError,
+        attempts: Mutex<HashMap<TileIndex, usize>>,
+    }
+
+    impl DataProvider<TileIndex, DecodedImage, ()> for FlakyProvider {
+        async fn load_raw(&self, key: &TileIndex) -> Result<Bytes, GalileoError> {
+            let mut attempts = self.attempts.lock();
+            let attempt = attempts.entry(*key).or_default();
+            *attempt += 1;
+            if *attempt > self.failures {
+                Ok(Bytes::new())
+            } else {
+                Err((self.error)())
+            }
+        }
+
+        fn decode(&self, _bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+            Ok(DecodedImage {
+                bytes: vec![],
+                dimensions: (0, 0),
+            })
+        }
+    }
+
+    fn flaky_provider(failures: usize) -> FlakyProvider {
+        flaky_provider_with(failures, || GalileoError::IO)
+    }
+
+    fn flaky_provider_with(failures: usize, error: fn() -> GalileoError) -> FlakyProvider {
+        FlakyProvider {
+            failures,
+            error,
+            attempts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a red image of one pixel for every tile.
+    struct RedProvider;
+
+    impl DataProvider<TileIndex, DecodedImage, ()> for RedProvider {
+        async fn load_raw(&self, _key: &TileIndex) -> Result<Bytes, GalileoError> {
+            Ok(Bytes::new())
+        }
+
+        fn decode(&self, _bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+            DecodedImage::from_raw(vec![255, 0, 0, 255], 1, 1)
+        }
+    }
+
+    fn test_schema() -> TileSchema {
+        TileSchema {
+            origin: Point2d::default(),
+            bounds: Rect::new(0.0, 0.0, 2048.0, 2048.0),
+            lods: [Lod::new(8.0, 0).unwrap(), Lod::new(2.0, 1).unwrap()].into(),
+            tile_width: 256,
+            tile_height: 256,
+            y_direction: VerticalDirection::BottomToTop,
+            crs: Crs::EPSG3857,
+            matrices: vec![],
+        }
+    }
+
+    fn test_view() -> MapView {
+        MapView::new_projected(&Point2d::new(1024.0, 1024.0), 2.0)
+            .with_size(Size::new(1024.0, 1024.0))
+    }
+
+    #[test]
+    fn loaded_tiles_are_exported_to_svg() {
+        let layer = RasterTileLayer::new(test_schema(), RedProvider, None);
+        tokio_test::block_on(layer.load_tiles(&test_view()));
+        let map = crate::Map::new(
+            test_view(),
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        );
+
+        for _ in 0..2 {
+            let svg = crate::render::SvgRenderer::new().render(&map);
+            assert_eq!(svg.matches("<image").count(), 16);
+        }
+
+        // Tiles are kept for the canvas the map is normally drawn to.
+        let layer = map.layers()[0]
+            .as_any()
+            .downcast_ref::<RasterTileLayer<RedProvider>>()
+            .unwrap();
+        assert!(layer
+            .tile_scheme
+            .iter_tiles(&test_view())
+            .unwrap()
+            .all(|index| matches!(
+                layer.tiles.get(&index).as_deref(),
+                Some(TileState::Loaded(..))
+            )));
+    }
+
+    #[test]
+    fn load_tiles_default_concurrency() {
+        let counter = Arc::new(RequestCounter::default());
+        let layer = RasterTileLayer::new(test_schema(), CountingProvider(counter.clone()), None);
+
+        tokio_test::block_on(layer.load_tiles(&test_view()));
+
+        assert_eq!(counter.loaded.load(Ordering::SeqCst), 16);
+        assert_eq!(
+            counter.max_in_flight.load(Ordering::SeqCst),
+            DEFAULT_MAX_CONCURRENT_REQUESTS
+        );
+    }
+
+    #[test]
+    fn memory_cache_limits() {
+        let counter = Arc::new(RequestCounter::default());
+        let mut layer =
+            RasterTileLayer::new(test_schema(), CountingProvider(counter.clone()), None);
+        layer.set_memory_cache_limits(4, 4000);
+
+        tokio_test::block_on(layer.load_tiles(&test_view()));
+
+        assert_eq!(counter.loaded.load(Ordering::SeqCst), 16);
+        let usage = layer.memory_cache_usage();
+        assert!(usage.entries > 0 && usage.entries <= 4);
+        assert_eq!(usage.bytes, usage.entries as u64 * 1000);
+
+        layer.clear_memory_cache();
+        assert_eq!(layer.memory_cache_usage(), CacheUsage::default());
+    }
+
+    #[test]
+    fn load_tiles_serialized() {
+        let counter = Arc::new(RequestCounter::default());
+        let mut layer =
+            RasterTileLayer::new(test_schema(), CountingProvider(counter.clone()), None);
+        layer.set_max_concurrent_requests(1);
+
+        tokio_test::block_on(layer.load_tiles(&test_view()));
+
+        assert_eq!(counter.loaded.load(Ordering::SeqCst), 16);
+        assert_eq!(counter.max_in_flight.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn load_tiles_missing() {
+        let mut layer = RasterTileLayer::new(test_schema(), MissingProvider::default(), None);
+        layer.set_retry(3, Duration::from_millis(1));
+        let view = test_view();
+
+        let failed = tokio_test::block_on(layer.load_tiles(&view));
+
+        assert_eq!(failed, 16);
+        assert_eq!(layer.tile_provider.0.load(Ordering::SeqCst), 16);
+        assert!(layer.get_tiles_to_draw(&view).is_empty());
+        // Failed tiles are not loaded again, so the layer is considered to be loaded.
+        assert!(layer.is_loaded(&view));
+    }
+
+    #[test]
+    fn layer_is_loaded_when_all_visible_tiles_are_loaded() {
+        let counter = Arc::new(RequestCounter::default());
+        let layer = RasterTileLayer::new(test_schema(), CountingProvider(counter), None);
+        let view = test_view();
+        assert!(!layer.is_loaded(&view));
+
+        tokio_test::block_on(layer.load_tiles(&view));
+        assert!(layer.is_loaded(&view));
+    }
+
+    #[test]
+    fn loading_for_dropped_layer_is_ignored() {
+        let counter = Arc::new(RequestCounter::default());
+        let layer = RasterTileLayer::new(test_schema(), CountingProvider(counter.clone()), None);
+        let load = |index| {
+            RasterTileLayer::load_tile(
+                index,
+                layer.tile_provider.clone(),
+                Arc::downgrade(&layer.tiles),
+                None,
+                layer.request_queue.clone(),
+                layer.retry_policy,
+                false,
+                false,
+            )
+        };
+
+        let mut indices = test_schema().iter_tiles(&test_view()).unwrap();
+        let in_flight = load(indices.next().unwrap());
+        let not_started = load(indices.next().unwrap());
+        let tiles = Arc::downgrade(&layer.tiles);
+
+        tokio_test::block_on(async move {
+            let mut in_flight = Box::pin(in_flight);
+            assert!(futures::poll!(&mut in_flight).is_pending());
+
+            drop(layer);
+            assert!(tiles.upgrade().is_none());
+
+            assert!(!in_flight.await);
+            assert!(!not_started.await);
+        });
+
+        assert_eq!(counter.loaded.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn tiles_no_longer_needed_are_cancelled() {
+        let counter = Arc::new(RequestCounter::default());
+        let layer = RasterTileLayer::new(test_schema(), CountingProvider(counter.clone()), None);
+        let mut indices = test_schema().iter_tiles(&test_view()).unwrap();
+        let (first, second) = (indices.next().unwrap(), indices.next().unwrap());
+        layer.request_queue.set_needed([first, second]);
+        let load = |index| {
+            RasterTileLayer::load_tile(
+                index,
+                layer.tile_provider.clone(),
+                Arc::downgrade(&layer.tiles),
+                None,
+                layer.request_queue.clone(),
+                layer.retry_policy,
+                true,
+                false,
+            )
+        };
+
+        tokio_test::block_on(async {
+            let mut cancelled = Box::pin(load(first));
+            assert!(futures::poll!(&mut cancelled).is_pending());
+            assert!(layer.tiles.get(&first).is_some());
+
+            layer.request_queue.set_needed([second]);
+            assert!(!cancelled.await);
+            assert!(load(second).await);
+        });
+
+        assert!(layer.tiles.get(&first).is_none());
+        assert_eq!(counter.loaded.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn visible_tiles_are_sorted_from_center() {
+        let schema = test_schema();
+        let view = MapView::new_projected(&Point2d::new(900.0, 1100.0), 2.0)
+            .with_size(Size::new(1024.0, 1024.0));
+        let mut tiles: Vec<_> = schema.iter_tiles(&view).unwrap().collect();
+        schema.sort_by_distance(&mut tiles, &view);
+
+        // The view center is inside the tile spanning from 512 to 1024 by x and from 1024 to 1536 by y.
+        assert_eq!(tiles[0], TileIndex::new(1, 2, 1));
+        let distance = |index: &TileIndex| {
+            schema
+                .tile_bbox(*index)
+                .unwrap()
+                .center()
+                .distance_sq(&Point2d::new(900.0, 1100.0))
+        };
+        assert!(tiles.windows(2).all(|w| distance(&w[0]) <= distance(&w[1])));
+    }
+
+    #[test]
+    fn load_tiles_retries_network_errors() {
+        let mut layer = RasterTileLayer::new(test_schema(), flaky_provider(2), None);
+        layer.set_retry(3, Duration::from_millis(1));
+
+        let failed = tokio_test::block_on(layer.load_tiles(&test_view()));
+
+        assert_eq!(failed, 0);
+        assert!(layer
+            .tile_provider
+            .attempts
+            .lock()
+            .values()
+            .all(|attempts| *attempts == 3));
+    }
+
+    #[test]
+    fn load_tiles_reports_failed_tiles() {
+        let mut layer = RasterTileLayer::new(test_schema(), flaky_provider(3), None);
+        layer.set_retry(3, Duration::from_millis(1));
+
+        let failed = tokio_test::block_on(layer.load_tiles(&test_view()));
+        assert_eq!(failed, 16);
+
+        // Failed tiles are requested again by the next call.
+        let failed = tokio_test::block_on(layer.load_tiles(&test_view()));
+        assert_eq!(failed, 0);
+        assert!(layer
+            .tile_provider
+            .attempts
+            .lock()
+            .values()
+            .all(|attempts| *attempts == 4));
+    }
+
+    #[test]
+    fn load_tiles_waits_for_prepared_tiles() {
+        let counter = Arc::new(RequestCounter::default());
+        let layer = RasterTileLayer::new(test_schema(), CountingProvider(counter.clone()), None);
+        let view = test_view();
+
+        let failed = tokio_test::block_on(async {
+            layer.prepare(&view);
+            // Let the spawned loads mark the tiles as loading.
+            yield_now().await;
+            assert!(!layer.is_loaded(&view));
+
+            layer.load_tiles(&view).await
+        });
+
+        assert_eq!(failed, 0);
+        assert!(layer.is_loaded(&view));
+        assert_eq!(counter.loaded.load(Ordering::SeqCst), 16);
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let mut layer = RasterTileLayer::new(
+            test_schema(),
+            flaky_provider_with(1, || GalileoError::Http {
+                status: 403,
+                retry_after: None,
+            }),
+            None,
+        );
+        layer.set_retry(3, Duration::from_millis(1));
+
+        let failed = tokio_test::block_on(layer.load_tiles(&test_view()));
+
+        assert_eq!(failed, 16);
+        assert!(layer
+            .tile_provider
+            .attempts
+            .lock()
+            .values()
+            .all(|attempts| *attempts == 1));
+    }
+
+    #[test]
+    fn too_many_requests_are_retried_after_requested_delay() {
+        let mut layer = RasterTileLayer::new(
+            test_schema(),
+            flaky_provider_with(1, || GalileoError::Http {
+                status: 429,
+                retry_after: Some(Duration::from_millis(50)),
+            }),
+            None,
+        );
+        layer.set_retry(2, Duration::from_millis(1));
+
+        let started = web_time::Instant::now();
+        let failed = tokio_test::block_on(layer.load_tiles(&test_view()));
+
+        assert_eq!(failed, 0);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(layer
+            .tile_provider
+            .attempts
+            .lock()
+            .values()
+            .all(|attempts| *attempts == 2));
+    }
+
+    #[test]
+    fn requested_retry_delay_is_limited() {
+        let mut layer = RasterTileLayer::new(
+            test_schema(),
+            flaky_provider_with(1, || GalileoError::Http {
+                status: 503,
+                retry_after: Some(Duration::from_secs(86400)),
+            }),
+            None,
+        );
+        layer.set_retry(2, Duration::from_millis(1));
+        layer.set_max_retry_delay(Duration::from_millis(10));
+
+        let started = web_time::Instant::now();
+        let failed = tokio_test::block_on(layer.load_tiles(&test_view()));
+
+        assert_eq!(failed, 0);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn load_tiles_without_retry() {
+        let layer = RasterTileLayer::new(test_schema(), flaky_provider(1), None);
+
+        let failed = tokio_test::block_on(layer.load_tiles(&test_view()));
+
+        assert_eq!(failed, 16);
+        assert!(layer
+            .tile_provider
+            .attempts
+            .lock()
+            .values()
+            .all(|attempts| *attempts == 1));
+    }
+
+    #[test]
+    #[cfg(feature = "blocking")]
+    fn load_tiles_blocking_without_runtime() {
+        let mut layer = RasterTileLayer::new(test_schema(), flaky_provider(1), None);
+        layer.set_retry(2, Duration::from_millis(1));
+
+        assert_eq!(layer.load_tiles_blocking(&test_view()), 0);
+        assert!(layer.is_loaded(&test_view()));
+    }
+
+    #[test]
+    fn load_tiles_reports_progress() {
+        let counter = Arc::new(RequestCounter::default());
+        let layer = RasterTileLayer::new(test_schema(), CountingProvider(counter.clone()), None);
+
+        let mut events = vec![];
+        let result = tokio_test::block_on(
+            layer.load_tiles_with_progress(&test_view(), |progress| events.push(progress)),
+        );
+
+        assert_eq!(events.len(), 17);
+        assert_eq!(
+            events[0],
+            TileProgress {
+                loaded: 0,
+                failed: 0,
+                total: 16
+            }
+        );
+        assert!(events
+            .iter()
+            .enumerate()
+            .all(|(i, e)| e.loaded == i && e.failed == 0 && e.total == 16));
+        assert_eq!(events.last(), Some(&result));
+        assert!(result.is_complete());
+    }
+
+    #[test]
+    fn load_tiles_reports_progress_of_prepared_tiles() {
+        let counter = Arc::new(RequestCounter::default());
+        let layer = RasterTileLayer::new(test_schema(), CountingProvider(counter.clone()), None);
+        let view = test_view();
+        let loaded_tiles = || {
+            layer
+                .tile_scheme
+                .iter_tiles(&view)
+                .unwrap()
+                .filter(|index| {
+                    matches!(
+                        layer.tiles.get(index).as_deref(),
+                        Some(TileState::Loaded(..))
+                    )
+                })
+                .count()
+        };
+
+        let result = tokio_test::block_on(async {
+            layer.prepare(&view);
+            yield_now().await;
+
+            layer
+                .load_tiles_with_progress(&view, |progress| {
+                    assert!(progress.loaded <= loaded_tiles());
+                })
+                .await
+        });
+
+        assert!(result.is_complete());
+        assert_eq!(loaded_tiles(), 16);
+    }
+
+    #[test]
+    fn load_tiles_reports_failed_progress() {
+        let layer = RasterTileLayer::new(test_schema(), MissingProvider::default(), None);
+
+        let mut events = vec![];
+        let result = tokio_test::block_on(
+            layer.load_tiles_with_progress(&test_view(), |progress| events.push(progress)),
+        );
+
+        assert_eq!(events.len(), 17);
+        assert!(!events[15].is_complete());
+        assert_eq!(
+            result,
+            TileProgress {
+                loaded: 0,
+                failed: 16,
+                total: 16
+            }
+        );
+    }
+
+    #[test]
+    fn reproject_tile_image_into_another_crs() {
+        // Left half of the image is red and the right half is blue.
+        let mut bytes = vec![];
+        for _ in 0..32 {
+            for x in 0..32 {
+                bytes.extend(if x < 16 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 255, 255]
+                });
+            }
+        }
+        let image = DecodedImage::from_raw(bytes, 32, 32).unwrap();
+        let projection = Crs::WGS84.projection_to(&Crs::EPSG3857).unwrap();
+
+        let (reprojected, bbox) = reproject_tile_image(
+            &image,
+            Rect::new(0.0, 0.0, 10.0, 10.0),
+            &Crs::WGS84,
+            &projection,
+        )
+        .unwrap();
+        assert_eq!(reprojected.dimensions, (32, 32));
+        assert!(bbox.x_min().abs() < 1e-6);
+        assert!((bbox.x_max() - 1_113_194.9).abs() < 1.0);
+        assert!((bbox.y_max() - 1_118_890.0).abs() < 1.0);
+
+        let pixel = |x: usize, y: usize| &reprojected.bytes[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!(pixel(5, 16), [255, 0, 0, 255]);
+        assert_eq!(pixel(26, 16), [0, 0, 255, 255]);
+        assert_eq!(pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(31, 31), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn reproject_polar_tile_image() {
+        // Rows north of 82.5 degrees are red and the rest are blue.
+        let mut bytes = vec![];
+        for y in 0..32 {
+            for _ in 0..32 {
+                bytes.extend(if y < 24 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 255, 255]
+                });
+            }
+        }
+        let image = DecodedImage::from_raw(bytes, 32, 32).unwrap();
+        let projection = Crs::WGS84.projection_to(&Crs::EPSG3857).unwrap();
+
+        let (reprojected, bbox) = reproject_tile_image(
+            &image,
+            Rect::new(0.0, 80.0, 10.0, 90.0),
+            &Crs::WGS84,
+            &projection,
+        )
+        .unwrap();
+        // Web Mercator ends at its latitude limit, so the tile ends at the edge of the map.
+        assert!((bbox.y_max() - 20_037_508.3).abs() < 100.0);
+        assert!((bbox.y_min() - 15_538_711.1).abs() < 1.0);
+
+        let pixel = |x: usize, y: usize| &reprojected.bytes[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+        assert_eq!(pixel(16, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(16, 31), [0, 0, 255, 255]);
+
+        assert!(reproject_tile_image(
+            &image,
+            Rect::new(0.0, 86.0, 10.0, 90.0),
+            &Crs::WGS84,
+            &projection,
+        )
+        .is_none());
+    }
 }
@@ -0,0 +1,374 @@
+//! [`TerrainLayer`] draws the relief of the terrain from elevation tiles.
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::layer::data_provider::DataProvider;
+use crate::layer::{ColorRamp, Layer, RasterTileLayer, TileProgress};
+use crate::messenger::Messenger;
+use crate::render::Canvas;
+use crate::tile_scheme::{PrefetchPolicy, TileIndex, TileSchema};
+use crate::view::MapView;
+use crate::Color;
+use bytes::Bytes;
+use galileo_types::geo::Crs;
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::any::Any;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use web_time::Duration;
+
+/// Radius of the Earth used by the Web Mercator projection.
+const EARTH_RADIUS: f64 = 6378137.0;
+
+/// Terrain layer loads elevation (DEM) tiles and draws them as a hillshade: the relief of the terrain lit by the sun.
+///
+/// Elevation tiles are images with the elevation of every pixel encoded into its color channels, as specified by
+/// the [`DemEncoding`]. The tiles are loaded by the given provider the same way images are loaded for a
+/// [`RasterTileLayer`], and the shading is calculated for every tile when it is loaded. The shading is then converted
+/// into colors with the [color ramp](HillshadeOptions::color_ramp), which by default darkens the slopes turned away
+/// from the sun and slightly lightens the slopes facing it, so the layer can be drawn over a base map.
+///
+/// ```no_run
+/// use galileo::layer::data_provider::UrlImageProvider;
+/// use galileo::layer::{DemEncoding, HillshadeOptions, TerrainLayer};
+/// use galileo::tile_scheme::{TileIndex, TileSchema};
+///
+/// let provider = UrlImageProvider::new(|index: &TileIndex| {
+///     format!(
+///         "https://s3.amazonaws.com/elevation-tiles-prod/terrarium/{}/{}/{}.png",
+///         index.z, index.x, index.y
+///     )
+/// });
+/// let layer = TerrainLayer::new(TileSchema::web(16), provider, None).with_options(HillshadeOptions {
+///     encoding: DemEncoding::Terrarium,
+///     exaggeration: 2.0,
+///     ..Default::default()
+/// });
+/// ```
+pub struct TerrainLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    tiles: RasterTileLayer<HillshadeProvider<Provider>>,
+    options: Arc<RwLock<HillshadeOptions>>,
+}
+
+/// The way elevation values are encoded into the colors of DEM tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemEncoding {
+    /// Terrarium encoding: `elevation = R * 256 + G + B / 256 - 32768` meters.
+    Terrarium,
+    /// Mapbox Terrain-RGB encoding: `elevation = -10000 + (R * 256 * 256 + G * 256 + B) * 0.1` meters.
+    MapboxRgb,
+}
+
+impl DemEncoding {
+    /// Decodes the elevation in meters from the color channels of a pixel.
+    pub fn elevation(&self, r: u8, g: u8, b: u8) -> f64 {
+        let (r, g, b) = (r as f64, g as f64, b as f64);
+        match self {
+            DemEncoding::Terrarium => r * 256.0 + g + b / 256.0 - 32768.0,
+            DemEncoding::MapboxRgb => -10000.0 + (r * 256.0 * 256.0 + g * 256.0 + b) * 0.1,
+        }
+    }
+}
+
+/// Configuration of a [`TerrainLayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HillshadeOptions {
+    /// Encoding of the elevation tiles.
+    pub encoding: DemEncoding,
+    /// Direction to the sun in degrees clockwise from the north.
+    pub azimuth: f64,
+    /// Angle of the sun above the horizon in degrees.
+    pub altitude: f64,
+    /// Multiplier for the elevation values. Values larger than `1.0` make the relief more pronounced.
+    pub exaggeration: f64,
+    /// Colors of the illumination values from `0.0` (not lit) to `1.0` (lit by the sun directly).
+    pub color_ramp: ColorRamp,
+}
+
+impl Default for HillshadeOptions {
+    /// Terrarium encoding with the sun from the north-west at 45 degrees above the horizon.
+    fn default() -> Self {
+        Self {
+            encoding: DemEncoding::Terrarium,
+            azimuth: 315.0,
+            altitude: 45.0,
+            exaggeration: 1.0,
+            color_ramp: ColorRamp::new([
+                (0.0, Color::rgba(0, 0, 0, 180)),
+                (0.7, Color::rgba(0, 0, 0, 0)),
+                (1.0, Color::rgba(255, 255, 255, 80)),
+            ]),
+        }
+    }
+}
+
+impl<Provider> TerrainLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    /// Creates a new layer with default options, that loads elevation tiles of the `tile_scheme` with the
+    /// `tile_provider`.
+    pub fn new(
+        tile_scheme: TileSchema,
+        tile_provider: Provider,
+        messenger: Option<Arc<dyn Messenger>>,
+    ) -> Self {
+        let options = Arc::new(RwLock::new(HillshadeOptions::default()));
+        let provider = HillshadeProvider {
+            dem_provider: tile_provider,
+            tile_scheme: tile_scheme.clone(),
+            options: options.clone(),
+        };
+
+        Self {
+            tiles: RasterTileLayer::new(tile_scheme, provider, messenger),
+            options,
+        }
+    }
+
+    /// Sets the options of the layer. The options are applied to the tiles loaded after the call.
+    pub fn with_options(self, options: HillshadeOptions) -> Self {
+        *self.options.write().expect("lock is poisoned") = options;
+        self
+    }
+
+    /// Options of the layer.
+    pub fn options(&self) -> HillshadeOptions {
+        self.options.read().expect("lock is poisoned").clone()
+    }
+
+    /// Sets fade in duration for newly loaded tiles.
+    pub fn set_fade_in_duration(&mut self, duration: Duration) {
+        self.tiles.set_fade_in_duration(duration);
+    }
+
+    /// Sets which tiles outside of the visible area are loaded in advance. See [`RasterTileLayer::set_prefetch`].
+    pub fn set_prefetch(&mut self, policy: PrefetchPolicy) {
+        self.tiles.set_prefetch(policy);
+    }
+
+    /// Sets the limits of the in-memory tile cache. See [`RasterTileLayer::set_memory_cache_limits`].
+    pub fn set_memory_cache_limits(&mut self, max_entries: usize, max_bytes: u64) {
+        self.tiles.set_memory_cache_limits(max_entries, max_bytes);
+    }
+
+    /// Loads all the tiles needed to draw the `view`. See [`RasterTileLayer::load_tiles`].
+    pub async fn load_tiles(&self, view: &MapView) -> usize {
+        self.tiles.load_tiles(view).await
+    }
+
+    /// Same as [`TerrainLayer::load_tiles`], but reports the progress of loading to the `on_progress` callback.
+    pub async fn load_tiles_with_progress(
+        &self,
+        view: &MapView,
+        on_progress: impl FnMut(TileProgress),
+    ) -> TileProgress {
+        self.tiles.load_tiles_with_progress(view, on_progress).await
+    }
+}
+
+impl<Provider> Layer for TerrainLayer<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend + 'static,
+{
+    fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        self.tiles.render(view, canvas);
+    }
+
+    fn prepare(&self, view: &MapView) {
+        self.tiles.prepare(view);
+    }
+
+    fn is_loaded(&self, view: &MapView) -> bool {
+        self.tiles.is_loaded(view)
+    }
+
+    fn set_messenger(&mut self, messenger: Box<dyn Messenger>) {
+        self.tiles.set_messenger(messenger);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Loads elevation tiles and converts them into hillshade images.
+struct HillshadeProvider<Provider> {
+    dem_provider: Provider,
+    tile_scheme: TileSchema,
+    options: Arc<RwLock<HillshadeOptions>>,
+}
+
+impl<Provider> HillshadeProvider<Provider> {
+    /// Size of a pixel of the tile image in meters.
+    fn pixel_size(&self, index: TileIndex, image_width: u32) -> Option<f64> {
+        let resolution = self.tile_scheme.lod_resolution(index.z)?;
+        let pixel_size = resolution * self.tile_scheme.tile_width() as f64 / image_width as f64;
+
+        if self.tile_scheme.crs == Crs::EPSG3857 {
+            // Web Mercator stretches distances by `1 / cos(latitude)`.
+            let center = self.tile_scheme.tile_bbox(index)?.center();
+            let latitude = (center.y / EARTH_RADIUS).sinh().atan();
+            Some(pixel_size * latitude.cos())
+        } else {
+            Some(pixel_size)
+        }
+    }
+}
+
+impl<Provider> DataProvider<TileIndex, DecodedImage, ()> for HillshadeProvider<Provider>
+where
+    Provider: DataProvider<TileIndex, DecodedImage, ()> + MaybeSync + MaybeSend,
+{
+    fn load_raw(
+        &self,
+        key: &TileIndex,
+    ) -> impl Future<Output = Result<Bytes, GalileoError>> + MaybeSend {
+        self.dem_provider.load_raw(key)
+    }
+
+    fn decode(&self, _bytes: Bytes, _context: ()) -> Result<DecodedImage, GalileoError> {
+        // Shading depends on the size of the tile pixels, so it cannot be calculated without the tile index.
+        Err(GalileoError::Generic(
+            "elevation tiles can only be loaded with a tile index".into(),
+        ))
+    }
+
+    fn load(
+        &self,
+        key: &TileIndex,
+        context: (),
+    ) -> impl Future<Output = Result<DecodedImage, GalileoError>> + MaybeSend {
+        let key = *key;
+        async move {
+            let dem = self.dem_provider.load(&key, context).await?;
+            let pixel_size = self
+                .pixel_size(key, dem.dimensions.0)
+                .ok_or_else(|| GalileoError::Generic(format!("unknown z-level {}", key.z)))?;
+            let options = self.options.read().expect("lock is poisoned").clone();
+            Ok(hillshade(&dem, pixel_size, &options))
+        }
+    }
+}
+
+/// Calculates the hillshade image of the `dem` tile with the given size of a pixel in meters.
+///
+/// Slopes are calculated with the Horn's method, using the pixel itself for the missing neighbours at the borders.
+fn hillshade(dem: &DecodedImage, pixel_size: f64, options: &HillshadeOptions) -> DecodedImage {
+    let (width, height) = dem.dimensions;
+    let elevations: Vec<f64> = dem
+        .bytes
+        .chunks_exact(4)
+        .map(|pixel| {
+            options.encoding.elevation(pixel[0], pixel[1], pixel[2]) * options.exaggeration
+        })
+        .collect();
+    let elevation = |x: i64, y: i64| {
+        let x = x.clamp(0, width as i64 - 1);
+        let y = y.clamp(0, height as i64 - 1);
+        elevations[(y * width as i64 + x) as usize]
+    };
+
+    let zenith = (90.0 - options.altitude).to_radians();
+    let azimuth = (360.0 - options.azimuth + 90.0).to_radians();
+
+    let mut bytes = Vec::with_capacity(dem.bytes.len());
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            let [a, b, c] = [-1, 0, 1].map(|dx| elevation(x + dx, y - 1));
+            let [d, _, f] = [-1, 0, 1].map(|dx| elevation(x + dx, y));
+            let [g, h, i] = [-1, 0, 1].map(|dx| elevation(x + dx, y + 1));
+
+            let dz_dx = ((c + 2.0 * f + i) - (a + 2.0 * d + g)) / (8.0 * pixel_size);
+            let dz_dy = ((g + 2.0 * h + i) - (a + 2.0 * b + c)) / (8.0 * pixel_size);
+            let slope = dz_dx.hypot(dz_dy).atan();
+            let aspect = dz_dy.atan2(-dz_dx);
+
+            let illumination =
+                zenith.cos() * slope.cos() + zenith.sin() * slope.sin() * (azimuth - aspect).cos();
+            let color = options
+                .color_ramp
+                .color_at(illumination.clamp(0.0, 1.0) as f32);
+            bytes.extend_from_slice(&color.to_u8_array());
+        }
+    }
+
+    DecodedImage {
+        bytes,
+        dimensions: (width, height),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Terrarium tile with the elevation given by the function of pixel coordinates.
+    fn dem(elevation: impl Fn(u32, u32) -> f64) -> DecodedImage {
+        let mut bytes = vec![];
+        for y in 0..8 {
+            for x in 0..8 {
+                let value = elevation(x, y) + 32768.0;
+                let r = (value / 256.0).floor();
+                let g = (value - r * 256.0).floor();
+                let b = ((value - r * 256.0 - g) * 256.0).round();
+                bytes.extend_from_slice(&[r as u8, g as u8, b as u8, 255]);
+            }
+        }
+
+        DecodedImage {
+            bytes,
+            dimensions: (8, 8),
+        }
+    }
+
+    fn illumination_options(azimuth: f64) -> HillshadeOptions {
+        HillshadeOptions {
+            azimuth,
+            color_ramp: ColorRamp::new([(0.0, Color::BLACK), (1.0, Color::WHITE)]),
+            ..Default::default()
+        }
+    }
+
+    fn center_brightness(image: &DecodedImage) -> u8 {
+        image.bytes[(4 * 8 + 4) * 4]
+    }
+
+    #[test]
+    fn decodes_elevation() {
+        assert_eq!(DemEncoding::Terrarium.elevation(128, 0, 0), 0.0);
+        assert_eq!(DemEncoding::Terrarium.elevation(128, 100, 128), 100.5);
+        assert_eq!(DemEncoding::MapboxRgb.elevation(1, 134, 160), 0.0);
+        assert_eq!(DemEncoding::MapboxRgb.elevation(0, 0, 0), -10000.0);
+    }
+
+    #[test]
+    fn flat_terrain_is_lit_by_altitude_of_sun() {
+        let image = hillshade(&dem(|_, _| 500.0), 10.0, &illumination_options(315.0));
+        let expected = (45f64.to_radians().sin() * 255.0).round() as u8;
+        assert!(image.bytes.chunks(4).all(|pixel| pixel[0] == expected));
+    }
+
+    #[test]
+    fn slopes_facing_sun_are_brighter() {
+        // Elevation grows to the east, so the slope faces the west.
+        let slope = dem(|x, _| x as f64 * 10.0);
+        let flat = center_brightness(&hillshade(
+            &dem(|_, _| 0.0),
+            10.0,
+            &illumination_options(270.0),
+        ));
+        let lit = center_brightness(&hillshade(&slope, 10.0, &illumination_options(270.0)));
+        let shaded = center_brightness(&hillshade(&slope, 10.0, &illumination_options(90.0)));
+
+        assert!(lit > flat);
+        assert!(shaded < flat);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::tile_scheme::TileIndex;
+use maybe_sync::Mutex;
+use std::collections::HashMap;
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+use std::task::{Poll, Waker};
+
+/// Queue of the tile requests of a layer.
+///
+/// No more than `max_concurrent` requests are run at a time. Waiting requests are started in the order of the tile
+/// priorities set with [`TileRequestQueue::set_needed`], so the tiles in the center of the view are loaded first.
+/// Cancellable requests for tiles that are no longer needed are cancelled, both waiting and already running.
+pub(crate) struct TileRequestQueue {
+    state: Mutex<QueueState>,
+}
+
+struct QueueState {
+    max_concurrent: usize,
+    running: usize,
+    /// Priorities of the needed tiles, lower value first.
+    priorities: HashMap<TileIndex, usize>,
+    next_ticket: u64,
+    requests: HashMap<u64, Request>,
+}
+
+struct Request {
+    index: TileIndex,
+    cancellable: bool,
+    is_running: bool,
+    waker: Option<Waker>,
+}
+
+impl QueueState {
+    fn is_cancelled(&self, request: &Request) -> bool {
+        request.cancellable && !self.priorities.contains_key(&request.index)
+    }
+
+    /// Order of the request in the queue: needed tiles by their priority, then other tiles, in the order they were
+    /// requested.
+    fn order(&self, ticket: u64, request: &Request) -> (usize, u64) {
+        let priority = self
+            .priorities
+            .get(&request.index)
+            .copied()
+            .unwrap_or(usize::MAX);
+        (priority, ticket)
+    }
+
+    fn can_start(&self, ticket: u64) -> bool {
+        let Some(request) = self.requests.get(&ticket) else {
+            return false;
+        };
+
+        let order = self.order(ticket, request);
+        self.running < self.max_concurrent
+            && !self
+                .requests
+                .iter()
+                .filter(|(_, other)| !other.is_running && !self.is_cancelled(other))
+                .any(|(other_ticket, other)| self.order(*other_ticket, other) < order)
+    }
+
+    fn wake_all(&mut self) {
+        for request in self.requests.values_mut() {
+            if let Some(waker) = request.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Removes the request from the queue when its future is done or dropped.
+struct Ticket<'a> {
+    queue: &'a TileRequestQueue,
+    id: u64,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        let mut state = self.queue.state.lock();
+        if let Some(request) = state.requests.remove(&self.id) {
+            if request.is_running {
+                state.running -= 1;
+            }
+        }
+
+        state.wake_all();
+    }
+}
+
+impl TileRequestQueue {
+    /// Creates a new queue running up to `max_concurrent` requests at a time (at least one).
+    pub(crate) fn new(max_concurrent: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                max_concurrent: max_concurrent.max(1),
+                running: 0,
+                priorities: HashMap::new(),
+                next_ticket: 0,
+                requests: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Changes the maximum number of requests running at a time. Running requests are not interrupted.
+    pub(crate) fn set_max_concurrent(&self, max_concurrent: usize) {
+        let mut state = self.state.lock();
+        state.max_concurrent = max_concurrent.max(1);
+        state.wake_all();
+    }
+
+    /// Sets the tiles that are needed by the layer, in the order of their priority. Cancellable requests for all other
+    /// tiles are cancelled.
+    pub(crate) fn set_needed(&self, tiles: impl IntoIterator<Item = TileIndex>) {
+        let mut state = self.state.lock();
+        state.priorities.clear();
+        for (priority, index) in tiles.into_iter().enumerate() {
+            state.priorities.entry(index).or_insert(priority);
+        }
+
+        state.wake_all();
+    }
+
+    /// Runs the `request` for the tile `index` when its turn comes.
+    ///
+    /// If the request is `cancellable`, it is cancelled when the tile is no longer needed: the `request` future is
+    /// dropped and `None` is returned.
+    pub(crate) async fn run<T>(
+        &self,
+        index: TileIndex,
+        cancellable: bool,
+        request: impl Future<Output = T>,
+    ) -> Option<T> {
+        let ticket = {
+            let mut state = self.state.lock();
+            let id = state.next_ticket;
+            state.next_ticket += 1;
+            state.requests.insert(
+                id,
+                Request {
+                    index,
+                    cancellable,
+                    is_running: false,
+                    waker: None,
+                },
+            );
+
+            Ticket { queue: self, id }
+        };
+
+        let is_started = poll_fn(|cx| {
+            let mut state = self.state.lock();
+            let Some(request) = state.requests.get(&ticket.id) else {
+                return Poll::Ready(false);
+            };
+            if state.is_cancelled(request) {
+                return Poll::Ready(false);
+            }
+
+            if state.can_start(ticket.id) {
+                state.running += 1;
+                if let Some(request) = state.requests.get_mut(&ticket.id) {
+                    request.is_running = true;
+                }
+                return Poll::Ready(true);
+            }
+
+            if let Some(request) = state.requests.get_mut(&ticket.id) {
+                request.waker = Some(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+        .await;
+
+        if !is_started {
+            return None;
+        }
+
+        let mut request = pin!(request);
+        poll_fn(|cx| {
+            {
+                let mut state = self.state.lock();
+                let Some(queued) = state.requests.get(&ticket.id) else {
+                    return Poll::Ready(None);
+                };
+                if state.is_cancelled(queued) {
+                    return Poll::Ready(None);
+                }
+
+                if let Some(queued) = state.requests.get_mut(&ticket.id) {
+                    queued.waker = Some(cx.waker().clone());
+                }
+            }
+
+            request.as_mut().poll(cx).map(Some)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::oneshot;
+    use futures::FutureExt;
+
+    fn tile(x: i32) -> TileIndex {
+        TileIndex::new(x, 0, 1)
+    }
+
+    #[test]
+    fn requests_start_by_priority() {
+        let queue = TileRequestQueue::new(1);
+        queue.set_needed([tile(0), tile(2), tile(1)]);
+
+        let (first_sender, first_receiver) = oneshot::channel::<()>();
+        let mut first = Box::pin(queue.run(tile(0), true, first_receiver));
+        let mut later = Box::pin(queue.run(tile(1), true, async { 1 }));
+        let mut sooner = Box::pin(queue.run(tile(2), true, async { 2 }));
+        assert!(first.as_mut().now_or_never().is_none());
+        assert!(later.as_mut().now_or_never().is_none());
+        assert!(sooner.as_mut().now_or_never().is_none());
+
+        first_sender.send(()).unwrap();
+        assert_eq!(first.now_or_never(), Some(Some(Ok(()))));
+        assert!(later.as_mut().now_or_never().is_none());
+        assert_eq!(sooner.now_or_never(), Some(Some(2)));
+        assert_eq!(later.now_or_never(), Some(Some(1)));
+        assert!(queue.state.lock().requests.is_empty());
+    }
+
+    #[test]
+    fn requests_for_unneeded_tiles_are_cancelled() {
+        let queue = TileRequestQueue::new(1);
+        queue.set_needed([tile(0), tile(1)]);
+
+        let (_sender, receiver) = oneshot::channel::<()>();
+        let mut running = Box::pin(queue.run(tile(0), true, receiver));
+        let mut waiting = Box::pin(queue.run(tile(1), true, async {}));
+        let mut kept = Box::pin(queue.run(tile(2), false, async {}));
+        assert!(running.as_mut().now_or_never().is_none());
+        assert!(waiting.as_mut().now_or_never().is_none());
+
+        queue.set_needed([]);
+        assert_eq!(running.now_or_never(), Some(None));
+        assert_eq!(waiting.now_or_never(), Some(None));
+        assert_eq!(kept.as_mut().now_or_never(), Some(Some(())));
+    }
+}
@@ -0,0 +1,780 @@
+//! Loading of [`VectorTileStyle`]s from [MapLibre / Mapbox GL style](https://maplibre.org/maplibre-style-spec/)
+//! documents.
+
+use crate::error::GalileoError;
+use crate::layer::vector_tile_layer::style::{
+    CompareOp, FilterValue, StyleFilter, StyleRule, VectorTileLineSymbol, VectorTilePointSymbol,
+    VectorTilePolygonSymbol, VectorTileStyle, VectorTileSymbol,
+};
+use crate::Color;
+use serde_json::Value;
+
+/// Maximum zoom level, up to which zoom dependent properties are evaluated.
+const MAX_ZOOM: u32 = 24;
+
+/// Parsed GL style document.
+///
+/// Only the parts of the specification that can be drawn by a [`VectorTileLayer`](super::VectorTileLayer) are
+/// supported:
+/// * `background`, `fill`, `line` and `circle` layers. Other layer types (e.g. `symbol` or `raster`) are skipped.
+/// * Colors, widths and opacities of the layers, either constant or depending on zoom level with `stops` functions
+///   or `interpolate` and `step` expressions.
+/// * `minzoom`, `maxzoom` and `visibility` of the layers.
+/// * Filters in both the legacy syntax (`["==", "class", "river"]`) and the expression syntax
+///   (`["==", ["get", "class"], "river"]`) with comparison, `in`, `has`, `all`, `any`, `none` and `!` operators.
+///
+/// Layers with unsupported filters are skipped with a warning in the log.
+///
+/// Every feature of a tile is drawn with one symbol only, so if a feature passes the filters of several GL layers,
+/// it is drawn with the topmost of them.
+///
+/// ```
+/// use galileo::layer::vector_tile_layer::gl_style::GlStyle;
+///
+/// let style = GlStyle::parse(r##"{
+///     "version": 8,
+///     "sources": { "openmaptiles": { "type": "vector", "url": "https://example.com/tiles.json" } },
+///     "layers": [
+///         { "id": "water", "type": "fill", "source": "openmaptiles", "source-layer": "water",
+///           "paint": { "fill-color": "#aad3df" } }
+///     ]
+/// }"##).unwrap();
+///
+/// assert_eq!(style.sources()[0].name, "openmaptiles");
+/// let vt_style = style.vector_tile_style("openmaptiles");
+/// assert_eq!(vt_style.rules.len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GlStyle {
+    sources: Vec<GlSource>,
+    layers: Vec<GlLayer>,
+    background: Option<Color>,
+}
+
+/// Vector tile source of a [`GlStyle`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlSource {
+    /// Name of the source, referenced by the layers of the style.
+    pub name: String,
+    /// URL of the TileJSON document describing the source.
+    pub url: Option<String>,
+    /// URL templates of the tiles, e.g. `https://example.com/{z}/{x}/{y}.pbf`.
+    pub tiles: Vec<String>,
+    /// Minimum zoom level of the tiles.
+    pub min_zoom: u32,
+    /// Maximum zoom level of the tiles.
+    pub max_zoom: u32,
+}
+
+#[derive(Debug, Clone)]
+struct GlLayer {
+    source: String,
+    source_layer: String,
+    filter: Option<StyleFilter>,
+    min_zoom: u32,
+    max_zoom: u32,
+    paint: GlPaint,
+}
+
+#[derive(Debug, Clone)]
+enum GlPaint {
+    Fill {
+        color: ZoomValue<Color>,
+        opacity: ZoomValue<f64>,
+    },
+    Line {
+        color: ZoomValue<Color>,
+        width: ZoomValue<f64>,
+        opacity: ZoomValue<f64>,
+    },
+    Circle {
+        color: ZoomValue<Color>,
+        radius: ZoomValue<f64>,
+        opacity: ZoomValue<f64>,
+    },
+}
+
+/// Value of a layer property, that can depend on the zoom level.
+#[derive(Debug, Clone)]
+enum ZoomValue<T> {
+    Constant(T),
+    Interpolate { base: f64, stops: Vec<(f64, T)> },
+    Step { first: T, stops: Vec<(f64, T)> },
+}
+
+trait Interpolate: Clone {
+    fn interpolate(&self, other: &Self, k: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, k: f64) -> Self {
+        self + (other - self) * k
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, k: f64) -> Self {
+        let from = self.to_u8_array();
+        let to = other.to_u8_array();
+        let channel = |i: usize| (from[i] as f64).interpolate(&(to[i] as f64), k).round() as u8;
+        Color::rgba(channel(0), channel(1), channel(2), channel(3))
+    }
+}
+
+impl<T: Interpolate> ZoomValue<T> {
+    fn is_constant(&self) -> bool {
+        matches!(self, ZoomValue::Constant(_))
+    }
+
+    fn evaluate(&self, zoom: f64) -> T {
+        match self {
+            ZoomValue::Constant(value) => value.clone(),
+            ZoomValue::Step { first, stops } => stops
+                .iter()
+                .take_while(|(stop, _)| *stop <= zoom)
+                .last()
+                .map_or(first, |(_, value)| value)
+                .clone(),
+            ZoomValue::Interpolate { base, stops } => {
+                let next = stops.iter().position(|(stop, _)| *stop > zoom);
+                match next {
+                    Some(0) => stops[0].1.clone(),
+                    None => stops[stops.len() - 1].1.clone(),
+                    Some(index) => {
+                        let (z0, v0) = &stops[index - 1];
+                        let (z1, v1) = &stops[index];
+                        let k = if (base - 1.0).abs() < f64::EPSILON {
+                            (zoom - z0) / (z1 - z0)
+                        } else {
+                            (base.powf(zoom - z0) - 1.0) / (base.powf(z1 - z0) - 1.0)
+                        };
+                        v0.interpolate(v1, k)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl GlStyle {
+    /// Parses a GL style document.
+    ///
+    /// Returns an error if the document is not a valid JSON object. Unsupported parts of the style are skipped.
+    pub fn parse(json: &str) -> Result<Self, GalileoError> {
+        let document: Value = serde_json::from_str(json)
+            .map_err(|err| GalileoError::Generic(format!("invalid GL style: {err}")))?;
+        let Some(document) = document.as_object() else {
+            return Err(GalileoError::Generic(
+                "GL style must be a JSON object".into(),
+            ));
+        };
+
+        let sources = document
+            .get("sources")
+            .and_then(Value::as_object)
+            .map(|sources| {
+                sources
+                    .iter()
+                    .filter(|(_, source)| source.get("type") == Some(&Value::from("vector")))
+                    .map(|(name, source)| parse_source(name, source))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut layers = vec![];
+        let mut background = None;
+        for layer in document
+            .get("layers")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let id = layer.get("id").and_then(Value::as_str).unwrap_or_default();
+            let is_visible =
+                layer.pointer("/layout/visibility").and_then(Value::as_str) != Some("none");
+            if !is_visible {
+                continue;
+            }
+
+            if layer.get("type").and_then(Value::as_str) == Some("background") {
+                if let Some(color) = layer
+                    .pointer("/paint/background-color")
+                    .and_then(parse_color)
+                {
+                    let opacity = layer
+                        .pointer("/paint/background-opacity")
+                        .and_then(Value::as_f64)
+                        .unwrap_or(1.0);
+                    background = Some(with_opacity(color, opacity));
+                }
+                continue;
+            }
+
+            match parse_layer(layer) {
+                Ok(Some(layer)) => layers.push(layer),
+                Ok(None) => {}
+                Err(err) => log::warn!("GL style layer '{id}' is skipped: {err}"),
+            }
+        }
+
+        Ok(Self {
+            sources,
+            layers,
+            background,
+        })
+    }
+
+    /// Vector tile sources of the style.
+    pub fn sources(&self) -> &[GlSource] {
+        &self.sources
+    }
+
+    /// Creates a style for a [`VectorTileLayer`](super::VectorTileLayer) showing the tiles of the given `source`.
+    ///
+    /// Layers with constant properties are converted into a single [`StyleRule`] each. For layers with zoom
+    /// dependent properties a rule is created for every zoom level.
+    pub fn vector_tile_style(&self, source: &str) -> VectorTileStyle {
+        let mut rules = vec![];
+        // The first rule matching a feature is used, so the topmost layers must go first.
+        for layer in self.layers.iter().rev().filter(|l| l.source == source) {
+            if layer.paint.is_constant() {
+                rules.push(layer.rule(layer.min_zoom as f64, layer.min_zoom, layer.max_zoom));
+            } else {
+                for zoom in layer.min_zoom..layer.max_zoom.min(MAX_ZOOM) {
+                    rules.push(layer.rule(zoom as f64, zoom, zoom + 1));
+                }
+            }
+        }
+
+        VectorTileStyle {
+            rules,
+            default_symbol: VectorTileSymbol::default(),
+            background: self.background.unwrap_or(Color::TRANSPARENT),
+        }
+    }
+}
+
+impl GlPaint {
+    fn is_constant(&self) -> bool {
+        match self {
+            GlPaint::Fill { color, opacity } => color.is_constant() && opacity.is_constant(),
+            GlPaint::Line {
+                color,
+                width,
+                opacity,
+            } => color.is_constant() && width.is_constant() && opacity.is_constant(),
+            GlPaint::Circle {
+                color,
+                radius,
+                opacity,
+            } => color.is_constant() && radius.is_constant() && opacity.is_constant(),
+        }
+    }
+
+    fn symbol(&self, zoom: f64) -> VectorTileSymbol {
+        match self {
+            GlPaint::Fill { color, opacity } => VectorTileSymbol {
+                polygon: Some(VectorTilePolygonSymbol {
+                    fill_color: with_opacity(color.evaluate(zoom), opacity.evaluate(zoom)),
+                }),
+                ..Default::default()
+            },
+            GlPaint::Line {
+                color,
+                width,
+                opacity,
+            } => VectorTileSymbol {
+                line: Some(VectorTileLineSymbol {
+                    width: width.evaluate(zoom),
+                    stroke_color: with_opacity(color.evaluate(zoom), opacity.evaluate(zoom)),
+                }),
+                ..Default::default()
+            },
+            GlPaint::Circle {
+                color,
+                radius,
+                opacity,
+            } => VectorTileSymbol {
+                point: Some(VectorTilePointSymbol {
+                    size: radius.evaluate(zoom) * 2.0,
+                    color: with_opacity(color.evaluate(zoom), opacity.evaluate(zoom)),
+                }),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl GlLayer {
+    fn rule(&self, zoom: f64, min_zoom: u32, max_zoom: u32) -> StyleRule {
+        StyleRule {
+            layer_name: Some(self.source_layer.clone()),
+            properties: Default::default(),
+            filter: self.filter.clone(),
+            min_zoom: (min_zoom > 0).then_some(min_zoom),
+            max_zoom: (max_zoom < MAX_ZOOM).then_some(max_zoom),
+            symbol: self.paint.symbol(zoom),
+        }
+    }
+}
+
+fn parse_source(name: &str, source: &Value) -> GlSource {
+    GlSource {
+        name: name.to_string(),
+        url: source
+            .get("url")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        tiles: source
+            .get("tiles")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|tile| tile.as_str().map(str::to_string))
+            .collect(),
+        min_zoom: source.get("minzoom").and_then(Value::as_u64).unwrap_or(0) as u32,
+        max_zoom: source.get("maxzoom").and_then(Value::as_u64).unwrap_or(22) as u32,
+    }
+}
+
+fn parse_layer(layer: &Value) -> Result<Option<GlLayer>, String> {
+    let (Some(source), Some(source_layer)) = (
+        layer.get("source").and_then(Value::as_str),
+        layer.get("source-layer").and_then(Value::as_str),
+    ) else {
+        return Ok(None);
+    };
+
+    let paint = layer.get("paint").unwrap_or(&Value::Null);
+    let color = |name: &str, default: Color| match paint.get(name) {
+        Some(value) => parse_zoom_value(value, &parse_color),
+        None => Ok(ZoomValue::Constant(default)),
+    };
+    let number = |name: &str, default: f64| match paint.get(name) {
+        Some(value) => parse_zoom_value(value, &Value::as_f64),
+        None => Ok(ZoomValue::Constant(default)),
+    };
+
+    let paint = match layer.get("type").and_then(Value::as_str) {
+        Some("fill") => GlPaint::Fill {
+            color: color("fill-color", Color::BLACK)?,
+            opacity: number("fill-opacity", 1.0)?,
+        },
+        Some("line") => GlPaint::Line {
+            color: color("line-color", Color::BLACK)?,
+            width: number("line-width", 1.0)?,
+            opacity: number("line-opacity", 1.0)?,
+        },
+        Some("circle") => GlPaint::Circle {
+            color: color("circle-color", Color::BLACK)?,
+            radius: number("circle-radius", 5.0)?,
+            opacity: number("circle-opacity", 1.0)?,
+        },
+        _ => return Ok(None),
+    };
+
+    let filter = layer.get("filter").map(parse_filter).transpose()?;
+
+    Ok(Some(GlLayer {
+        source: source.to_string(),
+        source_layer: source_layer.to_string(),
+        filter,
+        min_zoom: layer.get("minzoom").and_then(Value::as_f64).unwrap_or(0.0) as u32,
+        max_zoom: layer
+            .get("maxzoom")
+            .and_then(Value::as_f64)
+            .map_or(MAX_ZOOM, |zoom| zoom.ceil() as u32),
+        paint,
+    }))
+}
+
+fn parse_zoom_value<T>(
+    value: &Value,
+    parse: &impl Fn(&Value) -> Option<T>,
+) -> Result<ZoomValue<T>, String> {
+    if let Some(constant) = parse(value) {
+        return Ok(ZoomValue::Constant(constant));
+    }
+
+    let stops = |values: &[Value]| -> Result<Vec<(f64, T)>, String> {
+        values
+            .chunks(2)
+            .map(|pair| match pair {
+                [zoom, value] => Ok((
+                    zoom.as_f64().ok_or("zoom stop must be a number")?,
+                    parse(value).ok_or(format!("unsupported value {value}"))?,
+                )),
+                _ => Err("stops must go in pairs".to_string()),
+            })
+            .collect()
+    };
+
+    // Legacy zoom function: {"base": 1.2, "stops": [[10, 1], [15, 4]]}
+    if let Some(function) = value.as_object() {
+        let values: Vec<Value> = function
+            .get("stops")
+            .and_then(Value::as_array)
+            .ok_or("function without stops")?
+            .iter()
+            .filter_map(Value::as_array)
+            .flatten()
+            .cloned()
+            .collect();
+        let base = function.get("base").and_then(Value::as_f64).unwrap_or(1.0);
+        return non_empty(ZoomValue::Interpolate {
+            base,
+            stops: stops(&values)?,
+        });
+    }
+
+    let expression = value
+        .as_array()
+        .ok_or(format!("unsupported value {value}"))?;
+    match expression.as_slice() {
+        [op, interpolation, input, rest @ ..] if op == "interpolate" && is_zoom(input) => {
+            let base = match interpolation.as_array().map(Vec::as_slice) {
+                Some([kind]) if kind == "linear" => 1.0,
+                Some([kind, base]) if kind == "exponential" => {
+                    base.as_f64().ok_or("exponential base must be a number")?
+                }
+                _ => return Err(format!("unsupported interpolation {interpolation}")),
+            };
+            non_empty(ZoomValue::Interpolate {
+                base,
+                stops: stops(rest)?,
+            })
+        }
+        [op, input, first, rest @ ..] if op == "step" && is_zoom(input) => Ok(ZoomValue::Step {
+            first: parse(first).ok_or(format!("unsupported value {first}"))?,
+            stops: stops(rest)?,
+        }),
+        _ => Err(format!("unsupported expression {value}")),
+    }
+}
+
+fn non_empty<T>(value: ZoomValue<T>) -> Result<ZoomValue<T>, String> {
+    match &value {
+        ZoomValue::Interpolate { stops, .. } if stops.is_empty() => {
+            Err("zoom function has no stops".into())
+        }
+        _ => Ok(value),
+    }
+}
+
+fn is_zoom(value: &Value) -> bool {
+    value.as_array().map(Vec::as_slice) == Some(&[Value::from("zoom")])
+}
+
+fn parse_filter(filter: &Value) -> Result<StyleFilter, String> {
+    let Some([op, args @ ..]) = filter.as_array().map(Vec::as_slice) else {
+        return Err(format!("unsupported filter {filter}"));
+    };
+    let op = op.as_str().ok_or(format!("unsupported filter {filter}"))?;
+    let filters = || args.iter().map(parse_filter).collect::<Result<Vec<_>, _>>();
+
+    Ok(match (op, args) {
+        ("all", _) => StyleFilter::All(filters()?),
+        ("any", _) => StyleFilter::Any(filters()?),
+        ("none", _) => StyleFilter::Not(Box::new(StyleFilter::Any(filters()?))),
+        ("!", [inner]) => StyleFilter::Not(Box::new(parse_filter(inner)?)),
+        ("has", [key]) => StyleFilter::Has(parse_key(key)?),
+        ("!has", [key]) => StyleFilter::Not(Box::new(StyleFilter::Has(parse_key(key)?))),
+        ("in", [key, values @ ..]) => StyleFilter::In(parse_key(key)?, parse_values(values)?),
+        ("!in", [key, values @ ..]) => StyleFilter::Not(Box::new(StyleFilter::In(
+            parse_key(key)?,
+            parse_values(values)?,
+        ))),
+        (_, [key, value]) => {
+            let op = match op {
+                "==" => CompareOp::Eq,
+                "!=" => CompareOp::NotEq,
+                "<" => CompareOp::Less,
+                "<=" => CompareOp::LessOrEqual,
+                ">" => CompareOp::Greater,
+                ">=" => CompareOp::GreaterOrEqual,
+                _ => return Err(format!("unsupported filter {filter}")),
+            };
+            StyleFilter::Compare(parse_key(key)?, op, parse_value(value)?)
+        }
+        _ => return Err(format!("unsupported filter {filter}")),
+    })
+}
+
+/// Parses the attribute name of a filter, either as a string in legacy filters, or as `["get", name]`,
+/// `["geometry-type"]` or `["id"]` expression.
+fn parse_key(key: &Value) -> Result<String, String> {
+    if let Some(key) = key.as_str() {
+        return Ok(key.to_string());
+    }
+
+    match key.as_array().map(Vec::as_slice) {
+        Some([op, name]) if op == "get" => name
+            .as_str()
+            .map(str::to_string)
+            .ok_or(format!("unsupported attribute {key}")),
+        Some([op]) if op == "geometry-type" => Ok("$type".into()),
+        Some([op]) if op == "id" => Ok("$id".into()),
+        _ => Err(format!("unsupported attribute {key}")),
+    }
+}
+
+fn parse_values(values: &[Value]) -> Result<Vec<FilterValue>, String> {
+    // Expression syntax puts the values into a literal array: ["in", ["get", "class"], ["literal", ["a", "b"]]]
+    if let [value] = values {
+        if let Some([op, Value::Array(literals)]) = value.as_array().map(Vec::as_slice) {
+            if op == "literal" {
+                return literals.iter().map(parse_value).collect();
+            }
+        }
+    }
+
+    values.iter().map(parse_value).collect()
+}
+
+fn parse_value(value: &Value) -> Result<FilterValue, String> {
+    match value {
+        Value::String(v) => Ok(FilterValue::String(v.clone())),
+        Value::Number(v) => Ok(FilterValue::Number(v.as_f64().unwrap_or_default())),
+        Value::Bool(v) => Ok(FilterValue::Bool(*v)),
+        _ => Err(format!("unsupported value {value}")),
+    }
+}
+
+fn with_opacity(color: Color, opacity: f64) -> Color {
+    let alpha = color.to_u8_array()[3] as f64 * opacity.clamp(0.0, 1.0);
+    color.with_alpha(alpha.round() as u8)
+}
+
+/// Parses a CSS color: hex (`#rgb`, `#rrggbb` and with alpha), `rgb()`, `rgba()`, `hsl()`, `hsla()` or one of
+/// the basic color names.
+fn parse_color(value: &Value) -> Option<Color> {
+    let value = value.as_str()?.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#') {
+        let full: String = match hex.len() {
+            3 | 4 => hex.chars().flat_map(|c| [c, c]).collect(),
+            6 | 8 => hex.to_string(),
+            _ => return None,
+        };
+        return Color::try_from_hex(&format!("#{full}"));
+    }
+
+    if let Some((function, args)) = value
+        .strip_suffix(')')
+        .and_then(|value| value.split_once('('))
+    {
+        let args: Vec<&str> = args.split(',').map(str::trim).collect();
+        let alpha = match args.get(3) {
+            Some(alpha) => alpha.parse::<f64>().ok()?,
+            None => 1.0,
+        };
+        let alpha = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let (r, g, b) = match (function.trim(), args.len()) {
+            ("rgb" | "rgba", 3 | 4) => {
+                let channel = |s: &str| s.parse::<f64>().ok().map(|v| v.clamp(0.0, 255.0));
+                (channel(args[0])?, channel(args[1])?, channel(args[2])?)
+            }
+            ("hsl" | "hsla", 3 | 4) => {
+                let percent = |s: &str| {
+                    s.strip_suffix('%')?
+                        .parse::<f64>()
+                        .ok()
+                        .map(|v| v.clamp(0.0, 100.0) / 100.0)
+                };
+                hsl_to_rgb(
+                    args[0].parse::<f64>().ok()?,
+                    percent(args[1])?,
+                    percent(args[2])?,
+                )
+            }
+            _ => return None,
+        };
+
+        return Some(Color::rgba(
+            r.round() as u8,
+            g.round() as u8,
+            b.round() as u8,
+            alpha,
+        ));
+    }
+
+    match value.as_str() {
+        "transparent" => Some(Color::TRANSPARENT),
+        "black" => Some(Color::BLACK),
+        "white" => Some(Color::WHITE),
+        "red" => Some(Color::RED),
+        "lime" => Some(Color::GREEN),
+        "blue" => Some(Color::BLUE),
+        "green" => Some(Color::rgba(0, 128, 0, 255)),
+        "gray" | "grey" => Some(Color::rgba(128, 128, 128, 255)),
+        "yellow" => Some(Color::rgba(255, 255, 0, 255)),
+        _ => None,
+    }
+}
+
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (f64, f64, f64) {
+    let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = lightness - c / 2.0;
+    ((r + m) * 255.0, (g + m) * 255.0, (b + m) * 255.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_mvt::{MvtFeature, MvtGeometry, MvtValue};
+    use std::collections::HashMap;
+
+    fn feature(properties: &[(&str, MvtValue)]) -> MvtFeature {
+        MvtFeature {
+            id: Some(1),
+            properties: properties
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect::<HashMap<_, _>>(),
+            geometry: MvtGeometry::LineString(vec![]),
+        }
+    }
+
+    #[test]
+    fn parse_colors() {
+        let color = |s: &str| parse_color(&Value::from(s));
+        assert_eq!(color("#f00"), Some(Color::RED));
+        assert_eq!(color("#0000FF80"), Some(Color::rgba(0, 0, 255, 128)));
+        assert_eq!(
+            color("rgba(255, 255, 255, 0.5)"),
+            Some(Color::rgba(255, 255, 255, 128))
+        );
+        assert_eq!(color("hsl(120, 100%, 50%)"), Some(Color::GREEN));
+        assert_eq!(color("hsla(0, 0%, 0%, 0)"), Some(Color::TRANSPARENT));
+        assert_eq!(color("white"), Some(Color::WHITE));
+        assert_eq!(color("#12345"), None);
+        assert_eq!(color("chartreuse-ish"), None);
+    }
+
+    #[test]
+    fn zoom_values() {
+        let parse = |json: &str| {
+            parse_zoom_value(&serde_json::from_str(json).unwrap(), &Value::as_f64).unwrap()
+        };
+
+        assert_eq!(parse("2").evaluate(5.0), 2.0);
+
+        let legacy = parse(r#"{"stops": [[10, 1], [14, 5]]}"#);
+        assert_eq!(legacy.evaluate(5.0), 1.0);
+        assert_eq!(legacy.evaluate(12.0), 3.0);
+        assert_eq!(legacy.evaluate(20.0), 5.0);
+
+        let exponential = parse(r#"["interpolate", ["exponential", 2], ["zoom"], 0, 0, 2, 3]"#);
+        assert_eq!(exponential.evaluate(1.0), 1.0);
+
+        let step = parse(r#"["step", ["zoom"], 1, 10, 2, 15, 3]"#);
+        assert_eq!(step.evaluate(9.0), 1.0);
+        assert_eq!(step.evaluate(10.0), 2.0);
+        assert_eq!(step.evaluate(16.0), 3.0);
+
+        assert!(parse_zoom_value(
+            &serde_json::from_str(r#"["get", "width"]"#).unwrap(),
+            &Value::as_f64
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn legacy_and_expression_filters() {
+        let parse = |json: &str| parse_filter(&serde_json::from_str(json).unwrap()).unwrap();
+        let river = feature(&[
+            ("class", MvtValue::String("river".into())),
+            ("admin_level", MvtValue::Int64(4)),
+        ]);
+        let lake = feature(&[("class", MvtValue::String("lake".into()))]);
+
+        let legacy =
+            parse(r#"["all", ["==", "$type", "LineString"], ["in", "class", "river", "canal"]]"#);
+        assert!(legacy.matches(&river));
+        assert!(!legacy.matches(&lake));
+
+        let expression = parse(r#"["in", ["get", "class"], ["literal", ["lake", "pond"]]]"#);
+        assert!(!expression.matches(&river));
+        assert!(expression.matches(&lake));
+
+        let compare = parse(r#"["<=", ["get", "admin_level"], 4]"#);
+        assert!(compare.matches(&river));
+        assert!(!compare.matches(&lake));
+
+        assert!(parse(r#"["!has", "admin_level"]"#).matches(&lake));
+        assert!(parse(r#"["!=", "class", "river"]"#).matches(&lake));
+        assert!(parse(r#"["none", ["==", "class", "river"]]"#).matches(&lake));
+        assert!(parse_filter(&serde_json::from_str(r#"["within", {}]"#).unwrap()).is_err());
+    }
+
+    #[test]
+    fn style_conversion() {
+        let style = GlStyle::parse(
+            r##"{
+                "version": 8,
+                "sources": {
+                    "tiles": { "type": "vector", "tiles": ["https://example.com/{z}/{x}/{y}.pbf"], "maxzoom": 14 },
+                    "satellite": { "type": "raster", "url": "https://example.com/raster.json" }
+                },
+                "layers": [
+                    { "id": "background", "type": "background", "paint": { "background-color": "#eeeeee" } },
+                    { "id": "water", "type": "fill", "source": "tiles", "source-layer": "water",
+                      "paint": { "fill-color": "#0000ff", "fill-opacity": 0.5 } },
+                    { "id": "roads", "type": "line", "source": "tiles", "source-layer": "transportation",
+                      "minzoom": 10, "maxzoom": 12, "filter": ["==", "class", "primary"],
+                      "paint": { "line-color": "#ff0000", "line-width": { "stops": [[10, 1], [12, 3]] } } },
+                    { "id": "hidden", "type": "fill", "source": "tiles", "source-layer": "park",
+                      "layout": { "visibility": "none" } },
+                    { "id": "labels", "type": "symbol", "source": "tiles", "source-layer": "place" },
+                    { "id": "unsupported", "type": "fill", "source": "tiles", "source-layer": "landuse",
+                      "filter": ["within", {}] }
+                ]
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            style.sources(),
+            &[GlSource {
+                name: "tiles".into(),
+                url: None,
+                tiles: vec!["https://example.com/{z}/{x}/{y}.pbf".into()],
+                min_zoom: 0,
+                max_zoom: 14,
+            }]
+        );
+
+        let vt_style = style.vector_tile_style("tiles");
+        assert_eq!(vt_style.background, Color::from_hex("#EEEEEE"));
+        assert_eq!(vt_style.rules.len(), 3);
+
+        let road = feature(&[("class", MvtValue::String("primary".into()))]);
+        assert!(vt_style
+            .get_style_rule_at_zoom("transportation", &road, 9)
+            .is_none());
+        let width = |zoom| {
+            vt_style
+                .get_style_rule_at_zoom("transportation", &road, zoom)
+                .and_then(|rule| rule.symbol.line.as_ref())
+                .map(|line| line.width)
+        };
+        assert_eq!(width(10), Some(1.0));
+        assert_eq!(width(11), Some(2.0));
+        assert_eq!(width(12), None);
+
+        let water = vt_style
+            .get_style_rule_at_zoom("water", &feature(&[]), 5)
+            .and_then(|rule| rule.symbol.polygon.as_ref())
+            .unwrap();
+        assert_eq!(water.fill_color, Color::rgba(0, 0, 255, 128));
+    }
+}
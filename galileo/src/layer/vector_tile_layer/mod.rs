@@ -4,34 +4,74 @@
 use crate::layer::Layer;
 use crate::messenger::Messenger;
 use crate::render::{Canvas, PackedBundle, RenderOptions};
-use crate::tile_scheme::TileSchema;
+use crate::tile_scheme::{PrefetchPolicy, TileIndex, TileSchema};
 use crate::view::MapView;
 use nalgebra::Point2;
 use std::any::Any;
 use std::collections::HashSet;
 
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
-use crate::layer::vector_tile_layer::tile_provider::{LockedTileStore, VectorTileProvider};
-use galileo_mvt::{MvtFeature, MvtGeometry};
-use galileo_types::cartesian::CartesianPoint2d;
+use crate::layer::vector_tile_layer::tile_provider::{
+    LockedTileStore, VectorTileProvider, VtProcessor,
+};
+use galileo_mvt::{MvtFeature, MvtGeometry, MvtValue};
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
 use galileo_types::geometry::CartesianGeometry2d;
+use galileo_types::{Contour, Polygon};
 
+#[cfg(feature = "gl-style")]
+pub mod gl_style;
 pub mod style;
+mod tile_encoder;
 pub mod tile_provider;
 mod vector_tile;
 
+pub use tile_encoder::VectorTileEncoder;
 pub use vector_tile::VectorTile;
 
 /// Vector tile layers use [`Providers`](VectorTileProvider) to load prepared vector tiles, and then render them using
 /// specified [styles](VectorTileStyle).
+///
+/// Features drawn by the layer can be found with [`VectorTileLayer::query_features`] and
+/// [`VectorTileLayer::query_features_in_rect`], e.g. to show a popup with the attributes of the clicked feature.
 pub struct VectorTileLayer<Provider: VectorTileProvider> {
     tile_provider: Provider,
     tile_scheme: TileSchema,
     style: VectorTileStyle,
+    prefetch: PrefetchPolicy,
+}
+
+/// Feature of a vector tile found by [`VectorTileLayer::query_features`].
+#[derive(Debug, Clone)]
+pub struct VectorTileFeature {
+    /// Name of the MVT layer the feature belongs to.
+    pub layer_name: String,
+    /// Index of the tile the feature was found in.
+    pub tile_index: TileIndex,
+    /// Decoded feature. Its geometry is in the coordinates of the tile.
+    pub feature: MvtFeature,
+}
+
+impl VectorTileFeature {
+    /// Returns the value of the attribute of the feature.
+    pub fn property(&self, name: &str) -> Option<&MvtValue> {
+        self.feature.properties.get(name)
+    }
+
+    fn is_same(&self, other: &VectorTileFeature) -> bool {
+        self.feature.id.is_some()
+            && self.feature.id == other.feature.id
+            && self.layer_name == other.layer_name
+    }
 }
 
 impl<Provider: VectorTileProvider + 'static> Layer for VectorTileLayer<Provider> {
     fn render(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        if canvas.is_transient() {
+            self.render_transient(view, canvas);
+            return;
+        }
+
         let mut tiles_store = self.tile_provider.read();
         let tiles = self.get_tiles_to_draw(view, &mut tiles_store, canvas);
         let to_render: Vec<&dyn PackedBundle> = tiles.iter().map(|v| &*v.bundle).collect();
@@ -41,7 +81,8 @@ impl<Provider: VectorTileProvider + 'static> Layer for VectorTileLayer<Provider>
 
     fn prepare(&self, view: &MapView) {
         if let Some(iter) = self.tile_scheme.iter_tiles(view) {
-            for index in iter {
+            let prefetched = self.tile_scheme.prefetch_tiles(view, &self.prefetch);
+            for index in iter.chain(prefetched) {
                 self.tile_provider.load_tile(index, &self.style);
             }
         }
@@ -76,31 +117,91 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
             tile_provider,
             tile_scheme,
             style,
+            prefetch: PrefetchPolicy::default(),
         }
     }
 
+    /// Sets which tiles outside of the visible area are loaded in advance. See [`PrefetchPolicy`].
+    ///
+    /// By default, only the visible tiles are loaded.
+    pub fn set_prefetch(&mut self, policy: PrefetchPolicy) {
+        self.prefetch = policy;
+    }
+
     fn get_tiles_to_draw<'a>(
         &self,
         view: &MapView,
         tiles_store: &'a mut LockedTileStore,
         canvas: &dyn Canvas,
     ) -> Vec<&'a VectorTile> {
-        let mut tiles = vec![];
         let Some(tile_iter) = self.tile_scheme.iter_tiles(view) else {
             return vec![];
         };
 
         let indices: Vec<_> = tile_iter.collect();
-
         for index in &indices {
             tiles_store.pack(*index, canvas);
         }
 
+        let tiles_store = &*tiles_store;
+        self.select_tiles(indices, |index| tiles_store.get_tile(index).is_some())
+            .into_iter()
+            .filter_map(|index| tiles_store.get_tile(index))
+            .collect()
+    }
+
+    /// Draws the loaded tiles to a transient canvas.
+    ///
+    /// Tiles are packed for the canvas the map is usually drawn to, and the tessellated bundles are not kept after
+    /// packing, so the tiles are tessellated again for the transient canvas.
+    fn render_transient(&self, view: &MapView, canvas: &mut dyn Canvas) {
+        let Some(tile_iter) = self.tile_scheme.iter_tiles(view) else {
+            return;
+        };
+
+        let tiles_store = self.tile_provider.read();
+        let indices = self.select_tiles(tile_iter.collect(), |index| {
+            tiles_store.get_mvt_tile(index).is_some()
+        });
+        let packed: Vec<_> = indices
+            .into_iter()
+            .filter_map(|index| {
+                let mvt_tile = tiles_store.get_mvt_tile(index)?;
+                let mut bundle = canvas.create_bundle();
+                if let Err(err) = VtProcessor::prepare(
+                    mvt_tile,
+                    &mut bundle,
+                    index,
+                    &self.style,
+                    &self.tile_scheme,
+                ) {
+                    log::warn!("Failed to render tile {index:?}: {err}");
+                    return None;
+                }
+                Some(canvas.pack_bundle(&bundle))
+            })
+            .collect();
+
+        canvas.draw_bundles(
+            &packed.iter().map(|v| &**v).collect::<Vec<_>>(),
+            RenderOptions::default(),
+        );
+    }
+
+    /// Selects the tiles to draw among the `indices` of the visible tiles. Tiles that are not available are
+    /// substituted with the available tiles of other z-levels covering them. The tiles are sorted by their z-level.
+    fn select_tiles(
+        &self,
+        indices: Vec<TileIndex>,
+        is_available: impl Fn(TileIndex) -> bool,
+    ) -> Vec<TileIndex> {
+        let mut tiles = vec![];
         let mut to_substitute = vec![];
-        for index in &indices {
-            match tiles_store.get_tile(*index) {
-                None => to_substitute.push(*index),
-                Some(v) => tiles.push((*index, v)),
+        for index in indices {
+            if is_available(index) {
+                tiles.push(index);
+            } else {
+                to_substitute.push(index);
             }
         }
 
@@ -113,9 +214,9 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
                     None => break,
                 };
 
-                if let Some(tile) = tiles_store.get_tile(substitute_index) {
+                if is_available(substitute_index) {
                     if !substitute_indices.contains(&substitute_index) {
-                        tiles.push((substitute_index, tile));
+                        tiles.push(substitute_index);
                         substitute_indices.insert(substitute_index);
                     }
 
@@ -124,8 +225,8 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
             }
         }
 
-        tiles.sort_unstable_by(|(index_a, _), (index_b, _)| index_a.z.cmp(&index_b.z));
-        tiles.into_iter().map(|(_, tile)| tile).collect()
+        tiles.sort_unstable_by_key(|index| index.z);
+        tiles
     }
 
     /// Change style of the layer and redraw it.
@@ -134,6 +235,189 @@ impl<Provider: VectorTileProvider> VectorTileLayer<Provider> {
         self.tile_provider.update_style();
     }
 
+    /// Returns features drawn by the layer, that cover the `screen_point` of the `view` or are closer to it than
+    /// `tolerance` pixels. The features drawn last (that are on top) go first.
+    ///
+    /// Only the features that are drawn with the current style are checked, and the lines are checked taking their
+    /// width into account. The features are searched in the tiles of the current view that are already loaded. A
+    /// feature with an id, that is split between several tiles, is returned only once.
+    pub fn query_features(
+        &self,
+        view: &MapView,
+        screen_point: Point2d,
+        tolerance: f64,
+    ) -> Vec<VectorTileFeature> {
+        let Some(point) = view.screen_to_map(screen_point) else {
+            return vec![];
+        };
+
+        self.find_features(
+            view,
+            |tile_index, tile_bbox, tile_resolution, layer_name, feature| {
+                let tile_point = Point2::new(
+                    ((point.x() - tile_bbox.x_min()) / tile_resolution) as f32,
+                    ((tile_bbox.y_max() - point.y()) / tile_resolution) as f32,
+                );
+                let pixel_size = view.resolution() / tile_resolution;
+
+                match &feature.geometry {
+                    MvtGeometry::Point(_) => false,
+                    MvtGeometry::LineString(contours) => {
+                        let Some(paint) = VtProcessor::get_line_symbol(
+                            &self.style,
+                            layer_name,
+                            feature,
+                            tile_index.z,
+                        ) else {
+                            return false;
+                        };
+                        let tolerance = ((tolerance + paint.width / 2.0) * pixel_size) as f32;
+                        contours
+                            .iter()
+                            .any(|c| c.is_point_inside(&tile_point, tolerance))
+                    }
+                    MvtGeometry::Polygon(polygons) => {
+                        if VtProcessor::get_polygon_symbol(
+                            &self.style,
+                            layer_name,
+                            feature,
+                            tile_index.z,
+                        )
+                        .is_none()
+                        {
+                            return false;
+                        }
+                        let tolerance = (tolerance * pixel_size) as f32;
+                        polygons
+                            .iter()
+                            .any(|p| p.is_point_inside(&tile_point, tolerance))
+                    }
+                }
+            },
+        )
+    }
+
+    /// Returns features drawn by the layer, bounding rectangles of which intersect the `screen_rect` (in pixels) of the
+    /// `view`. The features drawn last go first. See [`VectorTileLayer::query_features`] for details.
+    ///
+    /// For rotated or tilted views, the bounding rectangle of the area covered by the `screen_rect` on the map is
+    /// used.
+    pub fn query_features_in_rect(
+        &self,
+        view: &MapView,
+        screen_rect: Rect,
+    ) -> Vec<VectorTileFeature> {
+        let corners: Option<Vec<Point2d>> = screen_rect
+            .into_quadrangle()
+            .into_iter()
+            .map(|corner| view.screen_to_map(Point2d::new(corner.x, corner.y)))
+            .collect();
+        let Some(map_rect) = corners.and_then(|corners| Rect::from_points(corners.iter())) else {
+            return vec![];
+        };
+
+        self.find_features(
+            view,
+            |tile_index, tile_bbox, tile_resolution, layer_name, feature| {
+                let tile_rect = Rect::new(
+                    (map_rect.x_min() - tile_bbox.x_min()) / tile_resolution,
+                    (tile_bbox.y_max() - map_rect.y_max()) / tile_resolution,
+                    (map_rect.x_max() - tile_bbox.x_min()) / tile_resolution,
+                    (tile_bbox.y_max() - map_rect.y_min()) / tile_resolution,
+                );
+
+                let points: Vec<_> = match &feature.geometry {
+                    MvtGeometry::Point(_) => return false,
+                    MvtGeometry::LineString(contours) => {
+                        if VtProcessor::get_line_symbol(
+                            &self.style,
+                            layer_name,
+                            feature,
+                            tile_index.z,
+                        )
+                        .is_none()
+                        {
+                            return false;
+                        }
+                        contours.iter().flat_map(|c| c.iter_points()).collect()
+                    }
+                    MvtGeometry::Polygon(polygons) => {
+                        if VtProcessor::get_polygon_symbol(
+                            &self.style,
+                            layer_name,
+                            feature,
+                            tile_index.z,
+                        )
+                        .is_none()
+                        {
+                            return false;
+                        }
+                        polygons
+                            .iter()
+                            .flat_map(|p| p.outer_contour().iter_points())
+                            .collect()
+                    }
+                };
+
+                let points: Vec<_> = points
+                    .iter()
+                    .map(|p| Point2d::new(p.x as f64, p.y as f64))
+                    .collect();
+                Rect::from_points(points.iter()).is_some_and(|bbox| bbox.intersects(&tile_rect))
+            },
+        )
+    }
+
+    /// Checks all features of the loaded tiles of the view with the `predicate`, which gets the index and the bbox of
+    /// the tile, the size of the tile in map units, the name of the MVT layer and the feature.
+    fn find_features(
+        &self,
+        view: &MapView,
+        mut predicate: impl FnMut(TileIndex, &Rect, f64, &str, &MvtFeature) -> bool,
+    ) -> Vec<VectorTileFeature> {
+        let tile_store = self.tile_provider.read();
+        let mut found = vec![];
+        let Some(iter) = self.tile_scheme.iter_tiles(view) else {
+            return found;
+        };
+
+        for index in iter {
+            let Some(tile_bbox) = self.tile_scheme.tile_bbox(index) else {
+                continue;
+            };
+            let Some(lod_resolution) = self.tile_scheme.lod_resolution(index.z) else {
+                continue;
+            };
+            let Some(mvt_tile) = tile_store.get_mvt_tile(index) else {
+                continue;
+            };
+
+            let tile_resolution = lod_resolution * self.tile_scheme.tile_width() as f64;
+            for layer in &mvt_tile.layers {
+                for feature in &layer.features {
+                    if !predicate(index, &tile_bbox, tile_resolution, &layer.name, feature) {
+                        continue;
+                    }
+
+                    let feature = VectorTileFeature {
+                        layer_name: layer.name.clone(),
+                        tile_index: index,
+                        feature: feature.clone(),
+                    };
+                    if !found
+                        .iter()
+                        .any(|f: &VectorTileFeature| f.is_same(&feature))
+                    {
+                        found.push(feature);
+                    }
+                }
+            }
+        }
+
+        found.reverse();
+        found
+    }
+
     /// Returns features, visible in the layer at the given point with the given map view.
     pub fn get_features_at(
         &self,
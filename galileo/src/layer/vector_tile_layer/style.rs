@@ -1,7 +1,7 @@
 //! See [`VectorTileStyle`].
 
 use crate::Color;
-use galileo_mvt::MvtFeature;
+use galileo_mvt::{MvtFeature, MvtGeometry, MvtValue};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -22,19 +22,25 @@ pub struct VectorTileStyle {
 }
 
 impl VectorTileStyle {
-    /// Get a rule for the given feature.
+    /// Get a rule for the given feature. Zoom limits of the rules are not checked, see
+    /// [`VectorTileStyle::get_style_rule_at_zoom`].
     pub fn get_style_rule(&self, layer_name: &str, feature: &MvtFeature) -> Option<&StyleRule> {
+        self.rules
+            .iter()
+            .find(|&rule| rule.matches(layer_name, feature))
+    }
+
+    /// Get a rule for the given feature of a tile with the zoom level `zoom`.
+    pub fn get_style_rule_at_zoom(
+        &self,
+        layer_name: &str,
+        feature: &MvtFeature,
+        zoom: u32,
+    ) -> Option<&StyleRule> {
         self.rules.iter().find(|&rule| {
-            let layer_name_check_passed = match &rule.layer_name {
-                Some(name) => name == layer_name,
-                None => true,
-            };
-            layer_name_check_passed
-                && (rule.properties.is_empty()
-                    || rule.properties.iter().all(|(key, value)| {
-                        feature.properties.get(key).map(|v| v.to_string())
-                            == Some(value.to_string())
-                    }))
+            rule.min_zoom.is_none_or(|min_zoom| zoom >= min_zoom)
+                && rule.max_zoom.is_none_or(|max_zoom| zoom < max_zoom)
+                && rule.matches(layer_name, feature)
         })
     }
 }
@@ -47,10 +53,152 @@ pub struct StyleRule {
     /// Specifies a set of attibutes of a feature that must have the given values for this rule to be applied.
     #[serde(default)]
     pub properties: HashMap<String, String>,
+    /// If set, a feature must pass the filter for this rule to be applied.
+    #[serde(default)]
+    pub filter: Option<StyleFilter>,
+    /// If set, the rule is applied only to the tiles with zoom level not less than this value.
+    #[serde(default)]
+    pub min_zoom: Option<u32>,
+    /// If set, the rule is applied only to the tiles with zoom level less than this value.
+    #[serde(default)]
+    pub max_zoom: Option<u32>,
     /// Symbol to draw a feature with.
     pub symbol: VectorTileSymbol,
 }
 
+impl StyleRule {
+    fn matches(&self, layer_name: &str, feature: &MvtFeature) -> bool {
+        let layer_name_check_passed = match &self.layer_name {
+            Some(name) => name == layer_name,
+            None => true,
+        };
+        layer_name_check_passed
+            && (self.properties.is_empty()
+                || self.properties.iter().all(|(key, value)| {
+                    feature.properties.get(key).map(|v| v.to_string()) == Some(value.to_string())
+                }))
+            && self
+                .filter
+                .as_ref()
+                .is_none_or(|filter| filter.matches(feature))
+    }
+}
+
+/// Condition on the attributes of a feature, see [`StyleRule::filter`].
+///
+/// Besides the attributes of the feature, two special keys can be used: `$type` for the geometry type of the feature
+/// (`"Point"`, `"LineString"` or `"Polygon"`) and `$id` for the id of the feature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StyleFilter {
+    /// Passes if all the filters pass.
+    All(Vec<StyleFilter>),
+    /// Passes if any of the filters passes.
+    Any(Vec<StyleFilter>),
+    /// Passes if the filter does not pass.
+    Not(Box<StyleFilter>),
+    /// Passes if the feature has the attribute.
+    Has(String),
+    /// Passes if the value of the attribute is equal to one of the values.
+    In(String, Vec<FilterValue>),
+    /// Passes if the value of the attribute compares to the value with the operator. Values of different types are
+    /// never equal, and features without the attribute pass only the [`CompareOp::NotEq`] comparison.
+    Compare(String, CompareOp, FilterValue),
+}
+
+/// Comparison operator of a [`StyleFilter::Compare`] filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareOp {
+    /// `==`
+    Eq,
+    /// `!=`
+    NotEq,
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+}
+
+/// Value to compare feature attributes with in a [`StyleFilter`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FilterValue {
+    /// String value.
+    String(String),
+    /// Numeric value. All numeric attribute types are compared as numbers.
+    Number(f64),
+    /// Boolean value.
+    Bool(bool),
+}
+
+impl FilterValue {
+    fn from_attribute(key: &str, feature: &MvtFeature) -> Option<Self> {
+        match key {
+            "$type" => Some(Self::String(
+                match feature.geometry {
+                    MvtGeometry::Point(_) => "Point",
+                    MvtGeometry::LineString(_) => "LineString",
+                    MvtGeometry::Polygon(_) => "Polygon",
+                }
+                .to_string(),
+            )),
+            "$id" => feature.id.map(|id| Self::Number(id as f64)),
+            _ => match feature.properties.get(key)? {
+                MvtValue::String(v) => Some(Self::String(v.clone())),
+                MvtValue::Float(v) => Some(Self::Number(*v as f64)),
+                MvtValue::Double(v) => Some(Self::Number(*v)),
+                MvtValue::Int64(v) => Some(Self::Number(*v as f64)),
+                MvtValue::Uint64(v) => Some(Self::Number(*v as f64)),
+                MvtValue::Bool(v) => Some(Self::Bool(*v)),
+                MvtValue::Unknown => None,
+            },
+        }
+    }
+
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => a.partial_cmp(b),
+            (Self::Number(a), Self::Number(b)) => a.partial_cmp(b),
+            (Self::Bool(a), Self::Bool(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
+impl StyleFilter {
+    /// Returns true if the feature passes the filter.
+    pub fn matches(&self, feature: &MvtFeature) -> bool {
+        use std::cmp::Ordering;
+
+        match self {
+            Self::All(filters) => filters.iter().all(|f| f.matches(feature)),
+            Self::Any(filters) => filters.iter().any(|f| f.matches(feature)),
+            Self::Not(filter) => !filter.matches(feature),
+            Self::Has(key) => FilterValue::from_attribute(key, feature).is_some(),
+            Self::In(key, values) => FilterValue::from_attribute(key, feature)
+                .is_some_and(|value| values.contains(&value)),
+            Self::Compare(key, op, value) => {
+                let ordering = FilterValue::from_attribute(key, feature)
+                    .and_then(|attribute| attribute.partial_cmp(value));
+                match op {
+                    CompareOp::Eq => ordering == Some(Ordering::Equal),
+                    CompareOp::NotEq => ordering != Some(Ordering::Equal),
+                    CompareOp::Less => ordering == Some(Ordering::Less),
+                    CompareOp::LessOrEqual => {
+                        matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+                    }
+                    CompareOp::Greater => ordering == Some(Ordering::Greater),
+                    CompareOp::GreaterOrEqual => {
+                        matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Symbol to draw a vector tile feature.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct VectorTileSymbol {
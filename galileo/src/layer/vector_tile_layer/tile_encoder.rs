@@ -0,0 +1,475 @@
+use crate::tile_scheme::{TileIndex, TileSchema};
+use galileo_mvt::{MvtFeature, MvtGeometry, MvtLayer, MvtTile, MvtValue};
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
+use galileo_types::geometry::Geom;
+use galileo_types::impls::{ClosedContour, Contour, Polygon};
+use galileo_types::{Contour as _, MultiContour as _, MultiPoint as _};
+use std::collections::HashMap;
+
+type TilePoint = galileo_mvt::Point;
+
+/// Cuts geometries into vector tiles of a [`TileSchema`].
+///
+/// Geometries are expected to be in the CRS of the tile schema. The parts of the geometries outside the tile (plus
+/// the buffer around it) are clipped, and the coordinates are converted into the tile coordinate system, that is
+/// quantized to the grid of `extent × extent` cells when the tile is [encoded](MvtTile::encode).
+///
+/// ```
+/// use galileo::layer::vector_tile_layer::VectorTileEncoder;
+/// use galileo::tile_scheme::{TileIndex, TileSchema};
+/// use galileo_types::cartesian::Point2d;
+/// use galileo_types::geometry::Geom;
+/// use std::collections::HashMap;
+///
+/// let encoder = VectorTileEncoder::new(TileSchema::web(18));
+/// let index = TileIndex::new(0, 0, 0);
+/// let feature = encoder
+///     .encode_feature(index, Some(1), &Geom::Point(Point2d::new(0.0, 0.0)), HashMap::new())
+///     .unwrap();
+/// let layer = encoder.layer("points", vec![feature]);
+/// let bytes = encoder.tile(vec![layer]).encode();
+/// assert!(!bytes.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct VectorTileEncoder {
+    tile_schema: TileSchema,
+    extent: u32,
+    buffer: u32,
+}
+
+impl VectorTileEncoder {
+    /// Default size of the tile grid.
+    pub const DEFAULT_EXTENT: u32 = 4096;
+    /// Default size of the buffer around the tile, kept by clipping, in the tile grid units.
+    pub const DEFAULT_BUFFER: u32 = 64;
+
+    /// Creates a new encoder for the tiles of the given schema.
+    pub fn new(tile_schema: TileSchema) -> Self {
+        Self {
+            tile_schema,
+            extent: Self::DEFAULT_EXTENT,
+            buffer: Self::DEFAULT_BUFFER,
+        }
+    }
+
+    /// Sets the size of the tile grid, coordinates of the features are quantized to.
+    pub fn with_extent(mut self, extent: u32) -> Self {
+        self.extent = extent.max(1);
+        self
+    }
+
+    /// Sets the size of the buffer around the tile in the tile grid units. Geometries are clipped by the tile
+    /// boundaries extended by the buffer, so that lines and polygon outlines do not have visible cuts at the tile
+    /// edges.
+    pub fn with_buffer(mut self, buffer: u32) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    /// Tile schema of the encoder.
+    pub fn tile_schema(&self) -> &TileSchema {
+        &self.tile_schema
+    }
+
+    /// Area covered by the tile including the buffer, in the CRS of the tile schema. Only geometries intersecting
+    /// this area produce features in the tile.
+    ///
+    /// Returns `None` if the tile schema does not have the level of detail of the tile.
+    pub fn tile_bbox(&self, index: TileIndex) -> Option<Rect> {
+        let bbox = self.tile_schema.tile_bbox(index)?;
+        let buffer = bbox.width() * self.buffer as f64 / self.extent as f64;
+        Some(Rect::new(
+            bbox.x_min() - buffer,
+            bbox.y_min() - buffer,
+            bbox.x_max() + buffer,
+            bbox.y_max() + buffer,
+        ))
+    }
+
+    /// Clips the geometry by the tile and converts it into the tile coordinates.
+    ///
+    /// Returns `None` if no part of the geometry is inside the tile, or if the tile schema does not have the level
+    /// of detail of the tile.
+    pub fn encode_geometry(
+        &self,
+        index: TileIndex,
+        geometry: &Geom<Point2d>,
+    ) -> Option<MvtGeometry> {
+        let bbox = self.tile_schema.tile_bbox(index)?;
+        let clipper = Clipper {
+            bbox,
+            buffer: self.buffer as f64 / self.extent as f64,
+        };
+
+        let geometry = match geometry {
+            Geom::Point(point) => MvtGeometry::Point(clipper.clip_points([point])),
+            Geom::MultiPoint(points) => {
+                MvtGeometry::Point(clipper.clip_points(points.iter_points()))
+            }
+            Geom::Contour(contour) => MvtGeometry::LineString(clipper.clip_contour(contour)),
+            Geom::MultiContour(contours) => MvtGeometry::LineString(
+                contours
+                    .contours()
+                    .flat_map(|contour| clipper.clip_contour(contour))
+                    .collect(),
+            ),
+            Geom::Polygon(polygon) => {
+                MvtGeometry::Polygon(clipper.clip_polygon(polygon).into_iter().collect())
+            }
+            Geom::MultiPolygon(polygons) => MvtGeometry::Polygon(
+                polygons
+                    .parts()
+                    .iter()
+                    .filter_map(|polygon| clipper.clip_polygon(polygon))
+                    .collect(),
+            ),
+        };
+
+        let is_empty = match &geometry {
+            MvtGeometry::Point(points) => points.is_empty(),
+            MvtGeometry::LineString(lines) => lines.is_empty(),
+            MvtGeometry::Polygon(polygons) => polygons.is_empty(),
+        };
+
+        (!is_empty).then_some(geometry)
+    }
+
+    /// Creates a tile feature with the given geometry clipped by the tile. See [`VectorTileEncoder::encode_geometry`].
+    pub fn encode_feature(
+        &self,
+        index: TileIndex,
+        id: Option<u64>,
+        geometry: &Geom<Point2d>,
+        properties: HashMap<String, MvtValue>,
+    ) -> Option<MvtFeature> {
+        Some(MvtFeature {
+            id,
+            properties,
+            geometry: self.encode_geometry(index, geometry)?,
+        })
+    }
+
+    /// Creates a tile layer with the given features.
+    pub fn layer(&self, name: impl Into<String>, features: Vec<MvtFeature>) -> MvtLayer {
+        let mut properties: Vec<String> = features
+            .iter()
+            .flat_map(|feature| feature.properties.keys().cloned())
+            .collect();
+        properties.sort();
+        properties.dedup();
+
+        MvtLayer {
+            name: name.into(),
+            features,
+            properties,
+            size: self.extent,
+        }
+    }
+
+    /// Creates a tile with the given layers. Layers without features are skipped.
+    pub fn tile(&self, layers: Vec<MvtLayer>) -> MvtTile {
+        MvtTile {
+            layers: layers
+                .into_iter()
+                .filter(|layer| !layer.features.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// Clips geometries by the tile bbox and converts them into the tile coordinates, where the tile occupies
+/// `[0, 1] × [0, 1]` square with the Y axis pointing down.
+struct Clipper {
+    bbox: Rect,
+    buffer: f64,
+}
+
+impl Clipper {
+    fn min(&self) -> f64 {
+        -self.buffer
+    }
+
+    fn max(&self) -> f64 {
+        1.0 + self.buffer
+    }
+
+    fn to_tile(&self, point: &impl CartesianPoint2d<Num = f64>) -> (f64, f64) {
+        (
+            (point.x() - self.bbox.x_min()) / self.bbox.width(),
+            (self.bbox.y_max() - point.y()) / self.bbox.height(),
+        )
+    }
+
+    fn is_inside(&self, (x, y): (f64, f64)) -> bool {
+        x >= self.min() && x <= self.max() && y >= self.min() && y <= self.max()
+    }
+
+    fn clip_points<'a>(&self, points: impl IntoIterator<Item = &'a Point2d>) -> Vec<TilePoint> {
+        points
+            .into_iter()
+            .map(|p| self.to_tile(p))
+            .filter(|&p| self.is_inside(p))
+            .map(tile_point)
+            .collect()
+    }
+
+    /// Clips every segment of the line with Liang-Barsky algorithm. The line is split into several parts if it
+    /// leaves the tile and then comes back.
+    fn clip_contour(&self, contour: &Contour<Point2d>) -> Vec<Contour<TilePoint>> {
+        let mut points: Vec<(f64, f64)> = contour.iter_points().map(|p| self.to_tile(p)).collect();
+        if contour.is_closed() {
+            if let Some(first) = points.first().copied() {
+                points.push(first);
+            }
+        }
+
+        let mut parts = vec![];
+        let mut current: Vec<(f64, f64)> = vec![];
+        for segment in points.windows(2) {
+            match self.clip_segment(segment[0], segment[1]) {
+                Some((from, to)) => {
+                    if current.last() != Some(&from) {
+                        push_part(&mut parts, std::mem::take(&mut current));
+                        current.push(from);
+                    }
+                    current.push(to);
+                }
+                None => push_part(&mut parts, std::mem::take(&mut current)),
+            }
+        }
+        push_part(&mut parts, current);
+
+        parts
+    }
+
+    fn clip_segment(&self, from: (f64, f64), to: (f64, f64)) -> Option<((f64, f64), (f64, f64))> {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let (mut t0, mut t1) = (0.0f64, 1.0f64);
+        for (p, q) in [
+            (-dx, from.0 - self.min()),
+            (dx, self.max() - from.0),
+            (-dy, from.1 - self.min()),
+            (dy, self.max() - from.1),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+
+        let at = |t: f64| {
+            if t == 0.0 {
+                from
+            } else if t == 1.0 {
+                to
+            } else {
+                (from.0 + dx * t, from.1 + dy * t)
+            }
+        };
+        Some((at(t0), at(t1)))
+    }
+
+    fn clip_polygon(&self, polygon: &Polygon<Point2d>) -> Option<Polygon<TilePoint>> {
+        let outer_contour = self.clip_ring(&polygon.outer_contour)?;
+        let inner_contours = polygon
+            .inner_contours
+            .iter()
+            .filter_map(|contour| self.clip_ring(contour))
+            .collect();
+
+        Some(Polygon::new(outer_contour, inner_contours))
+    }
+
+    /// Clips the closed contour with Sutherland-Hodgman algorithm. Rings going around the tile become the tile
+    /// boundary with the buffer.
+    fn clip_ring(&self, contour: &ClosedContour<Point2d>) -> Option<ClosedContour<TilePoint>> {
+        let mut points: Vec<(f64, f64)> = contour.points.iter().map(|p| self.to_tile(p)).collect();
+        let (min, max) = (self.min(), self.max());
+        // Edges of the clip rectangle: coordinate axis, its limit, and whether the limit is the lower one.
+        let edges = [
+            (Axis::X, min, true),
+            (Axis::X, max, false),
+            (Axis::Y, min, true),
+            (Axis::Y, max, false),
+        ];
+
+        for (axis, limit, is_lower) in edges {
+            let coordinate = |p: (f64, f64)| match axis {
+                Axis::X => p.0,
+                Axis::Y => p.1,
+            };
+            let inside = |p: (f64, f64)| {
+                if is_lower {
+                    coordinate(p) >= limit
+                } else {
+                    coordinate(p) <= limit
+                }
+            };
+
+            let mut clipped = Vec::with_capacity(points.len());
+            for (index, &point) in points.iter().enumerate() {
+                let prev = points[(index + points.len() - 1) % points.len()];
+                if inside(point) != inside(prev) {
+                    let t = (limit - coordinate(prev)) / (coordinate(point) - coordinate(prev));
+                    clipped.push((
+                        prev.0 + (point.0 - prev.0) * t,
+                        prev.1 + (point.1 - prev.1) * t,
+                    ));
+                }
+                if inside(point) {
+                    clipped.push(point);
+                }
+            }
+
+            points = clipped;
+            if points.is_empty() {
+                return None;
+            }
+        }
+
+        points.dedup();
+        (points.len() >= 3)
+            .then(|| ClosedContour::new(points.into_iter().map(tile_point).collect()))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+
+fn push_part(parts: &mut Vec<Contour<TilePoint>>, mut points: Vec<(f64, f64)>) {
+    points.dedup();
+    if points.len() >= 2 {
+        parts.push(Contour::open(points.into_iter().map(tile_point).collect()));
+    }
+}
+
+fn tile_point((x, y): (f64, f64)) -> TilePoint {
+    TilePoint::new(x as f32, y as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use galileo_types::cartesian::CartesianClosedContour;
+    use std::io::Cursor;
+
+    fn encoder() -> VectorTileEncoder {
+        VectorTileEncoder::new(TileSchema::web(18)).with_buffer(0)
+    }
+
+    fn tile_size() -> f64 {
+        TileSchema::web(18)
+            .tile_bbox(TileIndex::new(0, 0, 0))
+            .unwrap()
+            .width()
+    }
+
+    #[test]
+    fn points_outside_tile_are_skipped() {
+        let half = tile_size() / 2.0;
+        let geometry = Geom::MultiPoint(
+            vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(-half / 2.0, half / 2.0),
+                Point2d::new(half * 2.0, 0.0),
+            ]
+            .into(),
+        );
+
+        let Some(MvtGeometry::Point(points)) =
+            encoder().encode_geometry(TileIndex::new(0, 0, 0), &geometry)
+        else {
+            panic!("invalid geometry");
+        };
+        assert_eq!(
+            points,
+            [TilePoint::new(0.5, 0.5), TilePoint::new(0.25, 0.25)]
+        );
+
+        let outside = Geom::Point(Point2d::new(-half / 2.0, half / 2.0));
+        assert!(encoder()
+            .encode_geometry(TileIndex::new(1, 1, 1), &outside)
+            .is_none());
+    }
+
+    #[test]
+    fn line_is_split_at_tile_boundary() {
+        let half = tile_size() / 2.0;
+        // Tile (0, 0, 1) is the top left quarter of the world.
+        let geometry = Geom::Contour(Contour::open(vec![
+            Point2d::new(-half * 1.5, half / 2.0),
+            Point2d::new(half / 2.0, half / 2.0),
+            Point2d::new(half / 2.0, half * 0.75),
+            Point2d::new(-half / 2.0, half * 0.75),
+        ]));
+
+        let Some(MvtGeometry::LineString(lines)) =
+            encoder().encode_geometry(TileIndex::new(0, 0, 1), &geometry)
+        else {
+            panic!("invalid geometry");
+        };
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0].iter_points().copied().collect::<Vec<_>>(),
+            [TilePoint::new(0.0, 0.5), TilePoint::new(1.0, 0.5)]
+        );
+        assert_eq!(
+            lines[1].iter_points().copied().collect::<Vec<_>>(),
+            [TilePoint::new(1.0, 0.25), TilePoint::new(0.5, 0.25)]
+        );
+    }
+
+    #[test]
+    fn polygon_is_clipped_and_encoded() {
+        let size = tile_size();
+        let square = |half: f64| {
+            ClosedContour::new(vec![
+                Point2d::new(-half, -half),
+                Point2d::new(half, -half),
+                Point2d::new(half, half),
+                Point2d::new(-half, half),
+            ])
+        };
+        // Polygon covering the center of the world with a hole, clipped by the top left quarter of it.
+        let geometry = Geom::Polygon(Polygon::new(square(size / 4.0), vec![square(size / 8.0)]));
+        let encoder = encoder();
+        let index = TileIndex::new(0, 0, 1);
+        let feature = encoder
+            .encode_feature(index, Some(7), &geometry, HashMap::new())
+            .unwrap();
+
+        let MvtGeometry::Polygon(polygons) = &feature.geometry else {
+            panic!("invalid geometry");
+        };
+        assert!((polygons[0].outer_contour.area_signed().abs() - 0.25).abs() < 1e-6);
+        assert!((polygons[0].inner_contours[0].area_signed().abs() - 0.0625).abs() < 1e-6);
+
+        let tile = encoder.tile(vec![
+            encoder.layer("polygons", vec![feature]),
+            encoder.layer("empty", vec![]),
+        ]);
+        let decoded = MvtTile::decode(&mut Cursor::new(tile.encode()), false).unwrap();
+        assert_eq!(decoded.layers.len(), 1);
+        assert_eq!(decoded.layers[0].features[0].id, Some(7));
+        let MvtGeometry::Polygon(polygons) = &decoded.layers[0].features[0].geometry else {
+            panic!("invalid geometry");
+        };
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].inner_contours.len(), 1);
+    }
+}
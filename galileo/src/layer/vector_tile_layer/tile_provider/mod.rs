@@ -31,7 +31,7 @@ pub trait VectorTileProvider: MaybeSend + MaybeSync {
     /// Update the style of the loaded tiles.
     fn update_style(&self);
     /// Returns a lock of the tile store.
-    fn read(&self) -> LockedTileStore;
+    fn read(&self) -> LockedTileStore<'_>;
     /// Set a messenger to notify the application when a new tile is loaded.
     fn set_messenger(&self, messenger: Box<dyn Messenger>);
 }
@@ -85,7 +85,7 @@ where
         }
     }
 
-    fn read(&self) -> LockedTileStore {
+    fn read(&self) -> LockedTileStore<'_> {
         LockedTileStore {
             guard: self.tiles.lock().expect("tile store mutex is poisoned"),
         }
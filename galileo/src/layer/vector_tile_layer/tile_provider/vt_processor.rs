@@ -2,7 +2,7 @@ use crate::error::GalileoError;
 use crate::layer::data_provider::DataProcessor;
 use crate::layer::vector_tile_layer::style::VectorTileStyle;
 use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
-use crate::render::{LineCap, LinePaint, PolygonPaint};
+use crate::render::{LineCap, LineJoin, LinePaint, PolygonFill, PolygonPaint};
 use crate::tile_scheme::TileIndex;
 use crate::TileSchema;
 use bytes::Bytes;
@@ -51,7 +51,8 @@ impl DataProcessor for VtProcessor {
 }
 
 impl VtProcessor {
-    fn prepare(
+    /// Renders the features of the `mvt_tile` with the `style` into the `bundle`.
+    pub(crate) fn prepare(
         mvt_tile: &MvtTile,
         bundle: &mut RenderBundle,
         index: TileIndex,
@@ -82,6 +83,7 @@ impl VtProcessor {
                 &bounds,
                 PolygonPaint {
                     color: style.background,
+                    fill: PolygonFill::Solid,
                 },
             ),
             lod_resolution,
@@ -95,7 +97,9 @@ impl VtProcessor {
                         continue;
                     }
                     MvtGeometry::LineString(contours) => {
-                        if let Some(paint) = Self::get_line_symbol(style, &layer.name, feature) {
+                        if let Some(paint) =
+                            Self::get_line_symbol(style, &layer.name, feature, index.z)
+                        {
                             for contour in contours {
                                 bundle.add(
                                     RenderPrimitive::<_, _, _, Polygon<_>>::new_contour_ref(
@@ -116,14 +120,16 @@ impl VtProcessor {
                         }
                     }
                     MvtGeometry::Polygon(polygons) => {
-                        if let Some(paint) = Self::get_polygon_symbol(style, &layer.name, feature) {
+                        if let Some(paint) =
+                            Self::get_polygon_symbol(style, &layer.name, feature, index.z)
+                        {
                             for polygon in polygons {
                                 bundle.add(
                                     RenderPrimitive::<_, _, galileo_types::impls::Contour<_>, _>::new_polygon_ref(
                                         &polygon.cast_points(|p| {
                                             Self::transform_point(p, bbox, tile_resolution)
                                         }),
-                                        paint,
+                                        paint.clone(),
                                     ),
                                     lod_resolution,
                                 );
@@ -137,18 +143,22 @@ impl VtProcessor {
         Ok(())
     }
 
-    fn get_line_symbol(
+    pub(crate) fn get_line_symbol(
         style: &VectorTileStyle,
         layer_name: &str,
         feature: &MvtFeature,
+        zoom: u32,
     ) -> Option<LinePaint> {
-        let Some(rule) = style.get_style_rule(layer_name, feature) else {
+        let Some(rule) = style.get_style_rule_at_zoom(layer_name, feature, zoom) else {
             let symbol = style.default_symbol.line.as_ref()?;
             return Some(LinePaint {
                 width: symbol.width,
                 color: symbol.stroke_color,
                 offset: 0.0,
                 line_cap: LineCap::Butt,
+                line_join: LineJoin::Round,
+                dash: None,
+                arrows: None,
             });
         };
 
@@ -159,22 +169,28 @@ impl VtProcessor {
             color: symbol.stroke_color,
             offset: 0.0,
             line_cap: LineCap::Butt,
+            line_join: LineJoin::Round,
+            dash: None,
+            arrows: None,
         })
     }
 
-    fn get_polygon_symbol(
+    pub(crate) fn get_polygon_symbol(
         style: &VectorTileStyle,
         layer_name: &str,
         feature: &MvtFeature,
+        zoom: u32,
     ) -> Option<PolygonPaint> {
-        let Some(rule) = style.get_style_rule(layer_name, feature) else {
+        let Some(rule) = style.get_style_rule_at_zoom(layer_name, feature, zoom) else {
             return Some(PolygonPaint {
                 color: style.default_symbol.polygon.as_ref()?.fill_color,
+                fill: PolygonFill::Solid,
             });
         };
 
         Some(PolygonPaint {
             color: rule.symbol.polygon.as_ref()?.fill_color,
+            fill: PolygonFill::Solid,
         })
     }
 
@@ -0,0 +1,471 @@
+//! Layers showing the images of a [WMS](https://www.ogc.org/standard/wms/) server.
+
+use crate::error::GalileoError;
+use crate::layer::data_provider::UrlImageProvider;
+use crate::layer::RasterTileLayer;
+use crate::map::time::format_iso8601;
+use crate::tile_scheme::{TileIndex, TileSchema};
+use crate::view::MapView;
+use galileo_types::cartesian::Rect;
+use std::fmt::Write;
+use web_time::SystemTime;
+
+/// Version of the WMS protocol used for the requests.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum WmsVersion {
+    /// WMS 1.1.1. The CRS is set with the `SRS` parameter and the bbox is always given in *x, y* order.
+    V1_1_1,
+    /// WMS 1.3.0. The CRS is set with the `CRS` parameter and the bbox follows the axis order of the CRS, so for
+    /// `EPSG:4326` it is given in *latitude, longitude* order.
+    #[default]
+    V1_3_0,
+}
+
+impl WmsVersion {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WmsVersion::V1_1_1 => "1.1.1",
+            WmsVersion::V1_3_0 => "1.3.0",
+        }
+    }
+}
+
+/// Creates a [`RasterTileLayer`] that loads its images with WMS `GetMap` requests.
+///
+/// The map area is split into tiles according to the tile schema of the layer, and one `GetMap` request is sent for
+/// every tile with the bbox of the tile and the tile size in pixels. This way the WMS images are loaded, cached and
+/// drawn exactly as raster tiles.
+///
+/// By default the layer uses the standard Web Mercator tile schema with `EPSG:3857` CRS, WMS version 1.3.0 and
+/// transparent PNG images. If another CRS is used, the tile schema must be set to the one in that CRS too.
+///
+/// ```
+/// use galileo::layer::WmsLayerBuilder;
+///
+/// let layer = WmsLayerBuilder::new("https://example.com/wms")
+///     .with_layers(["roads", "buildings"])
+///     .with_styles(["", "dark"])
+///     .build()
+///     .expect("at least one layer is set");
+/// ```
+#[derive(Debug, Clone)]
+pub struct WmsLayerBuilder {
+    base_url: String,
+    layers: Vec<String>,
+    styles: Vec<String>,
+    crs: String,
+    format: String,
+    transparent: bool,
+    version: WmsVersion,
+    tile_schema: TileSchema,
+    params: Vec<(String, String)>,
+    time: Option<SystemTime>,
+}
+
+impl WmsLayerBuilder {
+    /// Creates a new builder for the WMS server at `base_url`. The URL may contain its own query parameters, the
+    /// `GetMap` parameters are appended to them.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            layers: vec![],
+            styles: vec![],
+            crs: "EPSG:3857".into(),
+            format: "image/png".into(),
+            transparent: true,
+            version: WmsVersion::default(),
+            tile_schema: TileSchema::web(18),
+            params: vec![],
+            time: None,
+        }
+    }
+
+    /// Sets the names of the WMS layers to request. The layers are drawn by the server in the given order, the first
+    /// one at the bottom.
+    pub fn with_layers(mut self, layers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.layers = layers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the styles of the layers, one for each of the layers in the same order. Empty string means the default
+    /// style of the layer. If no styles are set, default styles are used for all layers.
+    pub fn with_styles(mut self, styles: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.styles = styles.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the code of the CRS of the requested images, e.g. `EPSG:3857`.
+    ///
+    /// The CRS must match the CRS of the tile schema set with [`WmsLayerBuilder::with_tile_schema`].
+    pub fn with_crs(mut self, crs: impl Into<String>) -> Self {
+        self.crs = crs.into();
+        self
+    }
+
+    /// Sets the MIME type of the requested images. Default is `image/png`.
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Sets whether the images should have transparent background. Default is `true`.
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// Sets the version of the WMS protocol. Default is [`WmsVersion::V1_3_0`].
+    pub fn with_version(mut self, version: WmsVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the tile schema used to split the map into requested images. The tile size of the schema is used as the
+    /// size of the images.
+    pub fn with_tile_schema(mut self, tile_schema: TileSchema) -> Self {
+        self.tile_schema = tile_schema;
+        self
+    }
+
+    /// Adds a custom parameter to every request, e.g. a vendor specific parameter or an access key.
+    pub fn with_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets the value of the `TIME` parameter of the requests for the layers with time dimension. The time is sent
+    /// in ISO 8601 format in UTC.
+    ///
+    /// The tiles of the built layer are cached by their index, so to show another time the layer must be built again.
+    pub fn with_time(mut self, time: SystemTime) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Returns the URL of the `GetMap` request for the area `bbox` (in the coordinates of the layer CRS) and the image
+    /// of `width` x `height` pixels.
+    pub fn get_map_url(&self, bbox: &Rect, width: u32, height: u32) -> String {
+        self.get_map_url_at(bbox, width, height, self.time)
+    }
+
+    fn get_map_url_at(
+        &self,
+        bbox: &Rect,
+        width: u32,
+        height: u32,
+        time: Option<SystemTime>,
+    ) -> String {
+        let mut url = self.base_url.clone();
+        if !url.contains('?') {
+            url.push('?');
+        } else if !url.ends_with('?') && !url.ends_with('&') {
+            url.push('&');
+        }
+
+        let (crs_param, bbox) = match self.version {
+            WmsVersion::V1_1_1 => (
+                "SRS",
+                [bbox.x_min(), bbox.y_min(), bbox.x_max(), bbox.y_max()],
+            ),
+            WmsVersion::V1_3_0 if has_lat_lon_axis_order(&self.crs) => (
+                "CRS",
+                [bbox.y_min(), bbox.x_min(), bbox.y_max(), bbox.x_max()],
+            ),
+            WmsVersion::V1_3_0 => (
+                "CRS",
+                [bbox.x_min(), bbox.y_min(), bbox.x_max(), bbox.y_max()],
+            ),
+        };
+
+        let styles = if self.styles.is_empty() {
+            vec![String::new(); self.layers.len()]
+        } else {
+            self.styles.clone()
+        };
+
+        let params = [
+            ("SERVICE", "WMS".to_string()),
+            ("VERSION", self.version.as_str().to_string()),
+            ("REQUEST", "GetMap".to_string()),
+            ("LAYERS", encode_list(&self.layers)),
+            ("STYLES", encode_list(&styles)),
+            (crs_param, encode(&self.crs)),
+            (
+                "BBOX",
+                format!("{},{},{},{}", bbox[0], bbox[1], bbox[2], bbox[3]),
+            ),
+            ("WIDTH", width.to_string()),
+            ("HEIGHT", height.to_string()),
+            ("FORMAT", encode(&self.format)),
+            (
+                "TRANSPARENT",
+                if self.transparent { "TRUE" } else { "FALSE" }.to_string(),
+            ),
+        ];
+
+        let time = time.map(|time| ("TIME".to_string(), encode(&format_iso8601(time))));
+        let custom = self
+            .params
+            .iter()
+            .map(|(key, value)| (encode(key), encode(value)));
+        let mut is_first = true;
+        for (key, value) in params
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .chain(time)
+            .chain(custom)
+        {
+            if !is_first {
+                url.push('&');
+            }
+            is_first = false;
+            let _ = write!(url, "{key}={value}");
+        }
+
+        url
+    }
+
+    /// Returns the URL of the `GetMap` request for a single image covering the whole `view`, with the size of the
+    /// view. If the view has a [time](MapView::time), it is used for the `TIME` parameter instead of the time set
+    /// with [`WmsLayerBuilder::with_time`].
+    ///
+    /// Returns `None` if the view has no size, or its bbox cannot be calculated.
+    pub fn get_map_url_for_view(&self, view: &MapView) -> Option<String> {
+        let size = view.size();
+        let width = size.width().round() as u32;
+        let height = size.height().round() as u32;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        Some(self.get_map_url_at(&view.get_bbox()?, width, height, view.time().or(self.time)))
+    }
+
+    fn tile_url(&self, index: &TileIndex) -> String {
+        let bbox = self
+            .tile_schema
+            .tile_bbox(*index)
+            .unwrap_or_else(|| Rect::new(0.0, 0.0, 0.0, 0.0));
+        self.get_map_url(
+            &bbox,
+            self.tile_schema.tile_width(),
+            self.tile_schema.tile_height(),
+        )
+    }
+
+    /// Creates the layer.
+    ///
+    /// Returns an error if no WMS layers are set, or if the number of styles does not match the number of layers.
+    pub fn build(self) -> Result<RasterTileLayer<UrlImageProvider<TileIndex>>, GalileoError> {
+        if self.layers.is_empty() {
+            return Err(GalileoError::Generic(
+                "at least one WMS layer must be set".into(),
+            ));
+        }
+
+        if !self.styles.is_empty() && self.styles.len() != self.layers.len() {
+            return Err(GalileoError::Generic(format!(
+                "number of WMS styles ({}) does not match the number of layers ({})",
+                self.styles.len(),
+                self.layers.len()
+            )));
+        }
+
+        let tile_schema = self.tile_schema.clone();
+        let provider = UrlImageProvider::new(move |index: &TileIndex| self.tile_url(index));
+        Ok(RasterTileLayer::new(tile_schema, provider, None))
+    }
+}
+
+/// WMS 1.3.0 requires the bbox to follow the axis order defined by the CRS, which for the geographic WGS84 CRS is
+/// latitude first.
+fn has_lat_lon_axis_order(crs: &str) -> bool {
+    crs.eq_ignore_ascii_case("EPSG:4326")
+}
+
+fn encode_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| encode(value))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub(super) fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b':' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => {
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    fn params(url: &str) -> Vec<(String, String)> {
+        let (_, query) = url.split_once('?').unwrap();
+        query
+            .split('&')
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap();
+                (key.to_string(), value.to_string())
+            })
+            .collect()
+    }
+
+    fn param(url: &str, key: &str) -> Option<String> {
+        params(url)
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn bbox(url: &str) -> Vec<f64> {
+        param(url, "BBOX")
+            .unwrap()
+            .split(',')
+            .map(|v| v.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn get_map_url() {
+        let builder = WmsLayerBuilder::new("https://example.com/wms")
+            .with_layers(["roads", "land use"])
+            .with_styles(["", "dark"]);
+        let url = builder.get_map_url(&Rect::new(-10.0, -20.5, 30.0, 40.0), 256, 128);
+
+        assert!(url.starts_with("https://example.com/wms?"));
+        assert_eq!(param(&url, "SERVICE").unwrap(), "WMS");
+        assert_eq!(param(&url, "VERSION").unwrap(), "1.3.0");
+        assert_eq!(param(&url, "REQUEST").unwrap(), "GetMap");
+        assert_eq!(param(&url, "LAYERS").unwrap(), "roads,land%20use");
+        assert_eq!(param(&url, "STYLES").unwrap(), ",dark");
+        assert_eq!(param(&url, "CRS").unwrap(), "EPSG:3857");
+        assert_eq!(param(&url, "BBOX").unwrap(), "-10,-20.5,30,40");
+        assert_eq!(param(&url, "WIDTH").unwrap(), "256");
+        assert_eq!(param(&url, "HEIGHT").unwrap(), "128");
+        assert_eq!(param(&url, "FORMAT").unwrap(), "image/png");
+        assert_eq!(param(&url, "TRANSPARENT").unwrap(), "TRUE");
+        assert_eq!(param(&url, "SRS"), None);
+    }
+
+    #[test]
+    fn get_map_url_versions_and_axis_order() {
+        let bbox = Rect::new(10.0, 50.0, 11.0, 51.0);
+        let builder = WmsLayerBuilder::new("https://example.com/wms")
+            .with_layers(["a"])
+            .with_crs("EPSG:4326");
+
+        let url = builder.get_map_url(&bbox, 1, 1);
+        assert_eq!(param(&url, "CRS").unwrap(), "EPSG:4326");
+        assert_eq!(param(&url, "BBOX").unwrap(), "50,10,51,11");
+
+        let url = builder
+            .with_version(WmsVersion::V1_1_1)
+            .get_map_url(&bbox, 1, 1);
+        assert_eq!(param(&url, "VERSION").unwrap(), "1.1.1");
+        assert_eq!(param(&url, "SRS").unwrap(), "EPSG:4326");
+        assert_eq!(param(&url, "CRS"), None);
+        assert_eq!(param(&url, "BBOX").unwrap(), "10,50,11,51");
+    }
+
+    #[test]
+    fn get_map_url_with_base_query_and_params() {
+        let builder = WmsLayerBuilder::new("https://example.com/cgi?map=world")
+            .with_layers(["a"])
+            .with_format("image/jpeg")
+            .with_transparent(false)
+            .with_param("key", "a&b");
+        let url = builder.get_map_url(&Rect::new(0.0, 0.0, 1.0, 1.0), 1, 1);
+
+        assert!(url.starts_with("https://example.com/cgi?map=world&SERVICE=WMS&"));
+        assert_eq!(param(&url, "FORMAT").unwrap(), "image/jpeg");
+        assert_eq!(param(&url, "TRANSPARENT").unwrap(), "FALSE");
+        assert_eq!(param(&url, "key").unwrap(), "a%26b");
+        assert_eq!(param(&url, "STYLES").unwrap(), "");
+    }
+
+    #[test]
+    fn get_map_url_for_view() {
+        let builder = WmsLayerBuilder::new("https://example.com/wms").with_layers(["a"]);
+        let view = MapView::new_projected(&Point2d::new(100.0, 200.0), 2.0);
+        assert_eq!(builder.get_map_url_for_view(&view), None);
+
+        let url = builder
+            .get_map_url_for_view(&view.with_size(Size::new(300.0, 100.0)))
+            .unwrap();
+        for (value, expected) in bbox(&url).into_iter().zip([-200.0, 100.0, 400.0, 300.0]) {
+            assert_abs_diff_eq!(value, expected, epsilon = 1e-6);
+        }
+        assert_eq!(param(&url, "WIDTH").unwrap(), "300");
+        assert_eq!(param(&url, "HEIGHT").unwrap(), "100");
+    }
+
+    #[test]
+    fn get_map_url_with_time() {
+        let time = SystemTime::UNIX_EPOCH + web_time::Duration::from_secs(1_715_949_000);
+        let builder = WmsLayerBuilder::new("https://example.com/wms").with_layers(["a"]);
+        let bbox = Rect::new(0.0, 0.0, 1.0, 1.0);
+        assert_eq!(param(&builder.get_map_url(&bbox, 1, 1), "TIME"), None);
+
+        let builder = builder.with_time(time);
+        let url = builder.get_map_url(&bbox, 1, 1);
+        assert_eq!(param(&url, "TIME").unwrap(), "2024-05-17T12:30:00Z");
+
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+            .with_size(Size::new(10.0, 10.0))
+            .with_time(Some(SystemTime::UNIX_EPOCH));
+        let url = builder.get_map_url_for_view(&view).unwrap();
+        assert_eq!(param(&url, "TIME").unwrap(), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn tile_url() {
+        let builder = WmsLayerBuilder::new("https://example.com/wms").with_layers(["a"]);
+        let index = TileIndex {
+            z: 1,
+            x: 1,
+            y: 0,
+            display_x: 1,
+        };
+        let url = builder.tile_url(&index);
+
+        let half_world = 20037508.342787;
+        for (value, expected) in bbox(&url)
+            .into_iter()
+            .zip([0.0, 0.0, half_world, half_world])
+        {
+            assert_abs_diff_eq!(value, expected, epsilon = 0.001);
+        }
+        assert_eq!(param(&url, "WIDTH").unwrap(), "256");
+        assert_eq!(param(&url, "HEIGHT").unwrap(), "256");
+    }
+
+    #[test]
+    fn build_validates_layers_and_styles() {
+        assert!(WmsLayerBuilder::new("https://example.com/wms")
+            .build()
+            .is_err());
+        assert!(WmsLayerBuilder::new("https://example.com/wms")
+            .with_layers(["a", "b"])
+            .with_styles(["x"])
+            .build()
+            .is_err());
+        assert!(WmsLayerBuilder::new("https://example.com/wms")
+            .with_layers(["a", "b"])
+            .build()
+            .is_ok());
+    }
+}
@@ -0,0 +1,917 @@
+//! Layers showing the tiles of a [WMTS](https://www.ogc.org/standard/wmts/) server, configured from its capabilities
+//! document.
+
+use crate::error::GalileoError;
+use crate::layer::data_provider::UrlImageProvider;
+use crate::layer::wms_layer::encode;
+use crate::layer::RasterTileLayer;
+use crate::platform::{PlatformService, PlatformServiceImpl};
+use crate::tile_scheme::{TileIndex, TileMatrix, TileSchema, VerticalDirection};
+use galileo_types::cartesian::Point2d;
+use galileo_types::geo::Crs;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use std::fmt::Write;
+
+/// Length of a degree of the WGS84 equator in meters, used by WMTS to convert scale denominators of geographic tile
+/// matrix sets.
+const METERS_PER_DEGREE: f64 = 6378137.0 * 2.0 * std::f64::consts::PI / 360.0;
+
+/// Contents of the `GetCapabilities` response of a WMTS server: the layers and tile matrix sets it provides.
+///
+/// A configured [`RasterTileLayer`] for one of the layers is created with [`WmtsCapabilities::layer_builder`]:
+///
+/// ```no_run
+/// use galileo::layer::WmtsCapabilities;
+///
+/// # async fn run() -> Result<(), galileo::error::GalileoError> {
+/// let capabilities = WmtsCapabilities::fetch("https://example.com/wmts/1.0.0/WMTSCapabilities.xml").await?;
+/// for layer in &capabilities.layers {
+///     println!("{}: {:?}", layer.identifier, layer.title);
+/// }
+///
+/// let layer = capabilities
+///     .layer_builder("orthophoto")?
+///     .with_tile_matrix_set("GoogleMapsCompatible")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WmtsCapabilities {
+    /// Layers of the server.
+    pub layers: Vec<WmtsLayerInfo>,
+    /// Tile matrix sets the layers are available in.
+    pub tile_matrix_sets: Vec<WmtsTileMatrixSet>,
+    /// URL of the `GetTile` operation with key-value-pair encoding, if the server supports it.
+    pub get_tile_url: Option<String>,
+}
+
+/// A layer of a WMTS server as described in its capabilities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WmtsLayerInfo {
+    /// Identifier of the layer used in the requests.
+    pub identifier: String,
+    /// Human-readable title of the layer.
+    pub title: Option<String>,
+    /// Identifiers of the styles of the layer.
+    pub styles: Vec<String>,
+    /// Identifier of the default style, if any style is marked as default.
+    pub default_style: Option<String>,
+    /// MIME types of the tile images.
+    pub formats: Vec<String>,
+    /// Identifiers of the tile matrix sets the layer is available in.
+    pub tile_matrix_sets: Vec<String>,
+    /// Dimensions of the layer (e.g. `Time`) with their default values.
+    pub dimensions: Vec<(String, String)>,
+    /// URL templates of the tiles for the RESTful encoding.
+    pub resource_urls: Vec<WmtsResourceUrl>,
+}
+
+/// URL template of the tiles of a layer in one format, used by the RESTful encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WmtsResourceUrl {
+    /// MIME type of the tiles.
+    pub format: String,
+    /// Template of the URLs with `{TileMatrixSet}`, `{TileMatrix}`, `{TileRow}`, `{TileCol}`, `{Style}` and dimension
+    /// placeholders.
+    pub template: String,
+}
+
+/// A tile matrix set of a WMTS server: the CRS and the grids of tiles for every level of detail.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WmtsTileMatrixSet {
+    /// Identifier of the set used in the requests.
+    pub identifier: String,
+    /// Code of the CRS as given in the capabilities, e.g. `urn:ogc:def:crs:EPSG::3857`.
+    pub crs: String,
+    /// Tile matrices from the lowest level of detail to the highest one.
+    pub matrices: Vec<WmtsTileMatrix>,
+}
+
+/// A single level of detail of a [`WmtsTileMatrixSet`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WmtsTileMatrix {
+    /// Identifier of the matrix used in the requests.
+    pub identifier: String,
+    /// Scale denominator of the level, assuming pixels of 0.28 mm.
+    pub scale_denominator: f64,
+    /// Top left corner of the matrix in the axis order of the CRS.
+    pub top_left_corner: [f64; 2],
+    /// Width of a tile in pixels.
+    pub tile_width: u32,
+    /// Height of a tile in pixels.
+    pub tile_height: u32,
+    /// Number of tile columns.
+    pub matrix_width: u32,
+    /// Number of tile rows.
+    pub matrix_height: u32,
+}
+
+/// The way the tiles are requested from a WMTS server.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WmtsEncoding {
+    /// `GetTile` requests with the parameters in the query string.
+    Kvp,
+    /// Requests to the URL templates of the layer resources.
+    Rest,
+}
+
+impl WmtsCapabilities {
+    /// Loads and parses the capabilities document from the `url`.
+    ///
+    /// If the document does not specify the URL of key-value-pair `GetTile` requests, the `url` without its query is
+    /// used for them.
+    pub async fn fetch(url: &str) -> Result<Self, GalileoError> {
+        let data = PlatformServiceImpl::new().load_bytes_from_url(url).await?;
+        let xml = std::str::from_utf8(&data).map_err(xml_error)?;
+        let mut capabilities = Self::parse(xml)?;
+        if capabilities.get_tile_url.is_none() {
+            let base = url.split_once('?').map_or(url, |(base, _)| base);
+            capabilities.get_tile_url = Some(base.to_string());
+        }
+
+        Ok(capabilities)
+    }
+
+    /// Parses the capabilities document.
+    pub fn parse(xml: &str) -> Result<Self, GalileoError> {
+        let root = XmlElement::parse(xml)?;
+        if root.name != "Capabilities" {
+            return Err(xml_error(format!("unexpected root element {}", root.name)));
+        }
+
+        let contents = root.child("Contents");
+        let layers = contents
+            .into_iter()
+            .flat_map(|contents| contents.children("Layer"))
+            .map(WmtsLayerInfo::from_xml)
+            .collect::<Result<_, _>>()?;
+        let tile_matrix_sets = contents
+            .into_iter()
+            .flat_map(|contents| contents.children("TileMatrixSet"))
+            .map(WmtsTileMatrixSet::from_xml)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            layers,
+            tile_matrix_sets,
+            get_tile_url: get_tile_kvp_url(&root),
+        })
+    }
+
+    /// Returns the layer with the given identifier.
+    pub fn layer(&self, identifier: &str) -> Option<&WmtsLayerInfo> {
+        self.layers
+            .iter()
+            .find(|layer| layer.identifier == identifier)
+    }
+
+    /// Returns the tile matrix set with the given identifier.
+    pub fn tile_matrix_set(&self, identifier: &str) -> Option<&WmtsTileMatrixSet> {
+        self.tile_matrix_sets
+            .iter()
+            .find(|set| set.identifier == identifier)
+    }
+
+    /// Creates a builder of a [`RasterTileLayer`] showing the layer with the given identifier.
+    ///
+    /// By default, the builder uses the default style of the layer, its first format, the first of its tile matrix
+    /// sets in a CRS supported by [`WmtsTileMatrixSet::tile_schema`], the default values of its dimensions, and the
+    /// RESTful encoding if the layer has a URL template for the format.
+    ///
+    /// Returns an error if there is no such layer.
+    pub fn layer_builder(&self, identifier: &str) -> Result<WmtsLayerBuilder, GalileoError> {
+        let layer = self.layer(identifier).ok_or_else(|| {
+            GalileoError::Generic(format!("WMTS layer {identifier} is not found"))
+        })?;
+
+        let tile_matrix_set = layer
+            .tile_matrix_sets
+            .iter()
+            .find(|id| {
+                self.tile_matrix_set(id)
+                    .is_some_and(|set| set.tile_schema().is_ok())
+            })
+            .or(layer.tile_matrix_sets.first())
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(WmtsLayerBuilder {
+            capabilities: self.clone(),
+            layer: layer.clone(),
+            style: layer
+                .default_style
+                .clone()
+                .or_else(|| layer.styles.first().cloned())
+                .unwrap_or_default(),
+            format: layer.formats.first().cloned().unwrap_or_default(),
+            tile_matrix_set,
+            encoding: None,
+            dimensions: layer.dimensions.clone(),
+            params: vec![],
+        })
+    }
+}
+
+impl WmtsLayerInfo {
+    fn from_xml(element: &XmlElement) -> Result<Self, GalileoError> {
+        let identifier = element
+            .child_text("Identifier")
+            .ok_or_else(|| xml_error("layer without identifier"))?;
+
+        let styles = element.children("Style");
+        let default_style = styles
+            .clone()
+            .find(|style| style.attribute("isDefault") == Some("true"))
+            .and_then(|style| style.child_text("Identifier"));
+
+        Ok(Self {
+            identifier,
+            title: element.child_text("Title"),
+            styles: styles
+                .filter_map(|style| style.child_text("Identifier"))
+                .collect(),
+            default_style,
+            formats: element
+                .children("Format")
+                .map(|format| format.text.trim().to_string())
+                .collect(),
+            tile_matrix_sets: element
+                .children("TileMatrixSetLink")
+                .filter_map(|link| link.child_text("TileMatrixSet"))
+                .collect(),
+            dimensions: element
+                .children("Dimension")
+                .filter_map(|dimension| {
+                    let identifier = dimension.child_text("Identifier")?;
+                    let default = dimension
+                        .child_text("Default")
+                        .or_else(|| dimension.child_text("Value"))
+                        .unwrap_or_default();
+                    Some((identifier, default))
+                })
+                .collect(),
+            resource_urls: element
+                .children("ResourceURL")
+                .filter(|url| url.attribute("resourceType").unwrap_or("tile") == "tile")
+                .filter_map(|url| {
+                    Some(WmtsResourceUrl {
+                        format: url.attribute("format")?.to_string(),
+                        template: url.attribute("template")?.to_string(),
+                    })
+                })
+                .collect(),
+        })
+    }
+}
+
+impl WmtsTileMatrixSet {
+    fn from_xml(element: &XmlElement) -> Result<Self, GalileoError> {
+        let identifier = element
+            .child_text("Identifier")
+            .ok_or_else(|| xml_error("tile matrix set without identifier"))?;
+        let matrices = element
+            .children("TileMatrix")
+            .map(WmtsTileMatrix::from_xml)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            identifier,
+            crs: element.child_text("SupportedCRS").unwrap_or_default(),
+            matrices,
+        })
+    }
+
+    /// Creates the tile schema of the set. Z-levels of the schema are the indices of the matrices ordered by their
+    /// scale from the smallest one.
+    ///
+    /// Only Web Mercator (`EPSG:3857` and its aliases) and WGS84 (`EPSG:4326` and `CRS:84`) sets are supported, use
+    /// [`WmtsTileMatrixSet::tile_schema_with_crs`] for other CRSs.
+    pub fn tile_schema(&self) -> Result<TileSchema, GalileoError> {
+        let code = self.crs.rsplit(':').next().unwrap_or_default();
+        let (crs, meters_per_unit, lat_lon) = match code {
+            "3857" | "900913" | "102100" | "102113" => (Crs::EPSG3857, 1.0, false),
+            "4326" => (Crs::WGS84, METERS_PER_DEGREE, true),
+            "CRS84" | "84" => (Crs::WGS84, METERS_PER_DEGREE, false),
+            _ => {
+                return Err(GalileoError::Generic(format!(
+                    "CRS {} of WMTS tile matrix set {} is not supported",
+                    self.crs, self.identifier
+                )))
+            }
+        };
+
+        self.create_schema(crs, meters_per_unit, lat_lon)
+    }
+
+    /// Creates the tile schema of the set in the given CRS. `meters_per_unit` is the size of a unit of the CRS in
+    /// meters, used to convert the scale denominators of the matrices into resolutions. The top left corners of
+    /// the matrices are expected to be in *x, y* order.
+    pub fn tile_schema_with_crs(
+        &self,
+        crs: Crs,
+        meters_per_unit: f64,
+    ) -> Result<TileSchema, GalileoError> {
+        self.create_schema(crs, meters_per_unit, false)
+    }
+
+    fn create_schema(
+        &self,
+        crs: Crs,
+        meters_per_unit: f64,
+        lat_lon: bool,
+    ) -> Result<TileSchema, GalileoError> {
+        let matrices = self.sorted_matrices().enumerate().map(|(z, matrix)| {
+            let [a, b] = matrix.top_left_corner;
+            TileMatrix {
+                z: z as u32,
+                resolution: TileMatrix::resolution_from_scale_denominator(
+                    matrix.scale_denominator,
+                    meters_per_unit,
+                ),
+                origin: if lat_lon {
+                    Point2d::new(b, a)
+                } else {
+                    Point2d::new(a, b)
+                },
+                tile_width: matrix.tile_width,
+                tile_height: matrix.tile_height,
+                matrix_width: matrix.matrix_width,
+                matrix_height: matrix.matrix_height,
+            }
+        });
+
+        TileSchema::from_tile_matrices(crs, VerticalDirection::TopToBottom, matrices).ok_or_else(
+            || {
+                GalileoError::Generic(format!(
+                    "WMTS tile matrix set {} has no valid tile matrices",
+                    self.identifier
+                ))
+            },
+        )
+    }
+
+    /// Matrices in the order of the z-levels of the tile schema.
+    fn sorted_matrices(&self) -> impl Iterator<Item = &WmtsTileMatrix> {
+        let mut matrices: Vec<_> = self.matrices.iter().collect();
+        matrices.sort_by(|a, b| b.scale_denominator.total_cmp(&a.scale_denominator));
+        matrices.into_iter()
+    }
+}
+
+impl WmtsTileMatrix {
+    fn from_xml(element: &XmlElement) -> Result<Self, GalileoError> {
+        let text = |name: &str| {
+            element
+                .child_text(name)
+                .ok_or_else(|| xml_error(format!("tile matrix without {name}")))
+        };
+        let number = |name: &str| -> Result<f64, GalileoError> {
+            let value = text(name)?;
+            value
+                .parse()
+                .map_err(|_| xml_error(format!("invalid {name} value {value}")))
+        };
+        let size = |name: &str| -> Result<u32, GalileoError> {
+            let value = text(name)?;
+            value
+                .parse()
+                .map_err(|_| xml_error(format!("invalid {name} value {value}")))
+        };
+
+        let corner = text("TopLeftCorner")?;
+        let top_left_corner = match corner
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<f64>, _>>()
+            .as_deref()
+        {
+            Ok([a, b]) => [*a, *b],
+            _ => return Err(xml_error(format!("invalid TopLeftCorner value {corner}"))),
+        };
+
+        Ok(Self {
+            identifier: text("Identifier")?,
+            scale_denominator: number("ScaleDenominator")?,
+            top_left_corner,
+            tile_width: size("TileWidth")?,
+            tile_height: size("TileHeight")?,
+            matrix_width: size("MatrixWidth")?,
+            matrix_height: size("MatrixHeight")?,
+        })
+    }
+}
+
+/// Creates a [`RasterTileLayer`] showing a layer of a WMTS server. Created with
+/// [`WmtsCapabilities::layer_builder`].
+#[derive(Debug, Clone)]
+pub struct WmtsLayerBuilder {
+    capabilities: WmtsCapabilities,
+    layer: WmtsLayerInfo,
+    style: String,
+    format: String,
+    tile_matrix_set: String,
+    encoding: Option<WmtsEncoding>,
+    dimensions: Vec<(String, String)>,
+    params: Vec<(String, String)>,
+}
+
+impl WmtsLayerBuilder {
+    /// Sets the style of the layer.
+    pub fn with_style(mut self, style: impl Into<String>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// Sets the MIME type of the tiles.
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Sets the identifier of the tile matrix set to load the tiles in.
+    pub fn with_tile_matrix_set(mut self, tile_matrix_set: impl Into<String>) -> Self {
+        self.tile_matrix_set = tile_matrix_set.into();
+        self
+    }
+
+    /// Sets the way the tiles are requested. By default, the RESTful encoding is used if the layer has a URL
+    /// template for the format, and the key-value-pair encoding otherwise.
+    pub fn with_encoding(mut self, encoding: WmtsEncoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Sets the value of a dimension of the layer, e.g. `Time`. Dimensions that are not set use their default values.
+    pub fn with_dimension(
+        mut self,
+        identifier: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        let identifier = identifier.into();
+        let value = value.into();
+        match self
+            .dimensions
+            .iter_mut()
+            .find(|(id, _)| id.eq_ignore_ascii_case(&identifier))
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.dimensions.push((identifier, value)),
+        }
+        self
+    }
+
+    /// Adds a custom parameter to every request, e.g. an access key.
+    pub fn with_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Returns the tile schema of the selected tile matrix set.
+    pub fn tile_schema(&self) -> Result<TileSchema, GalileoError> {
+        self.matrix_set()?.tile_schema()
+    }
+
+    /// Returns the URL of the tile with the given index of the tile schema, or `None` if the selected tile matrix set
+    /// does not have the z-level of the index.
+    pub fn tile_url(&self, index: &TileIndex) -> Option<String> {
+        let set = self.matrix_set().ok()?;
+        let matrix = set.sorted_matrices().nth(index.z as usize)?;
+        let url = match self.encoding() {
+            WmtsEncoding::Rest => self.rest_url(&set.identifier, matrix, index),
+            WmtsEncoding::Kvp => self.kvp_url(&set.identifier, matrix, index),
+        }?;
+
+        if self.params.is_empty() || self.encoding() == WmtsEncoding::Kvp {
+            return Some(url);
+        }
+        Some(append_params(url, self.params.iter().cloned()))
+    }
+
+    /// Creates the layer.
+    ///
+    /// Returns an error if the layer is not available in the selected tile matrix set, the tile schema cannot be
+    /// created for the set, or the tiles cannot be requested with the selected encoding.
+    pub fn build(self) -> Result<RasterTileLayer<UrlImageProvider<TileIndex>>, GalileoError> {
+        if !self.layer.tile_matrix_sets.contains(&self.tile_matrix_set) {
+            return Err(GalileoError::Generic(format!(
+                "WMTS layer {} is not available in tile matrix set {}",
+                self.layer.identifier, self.tile_matrix_set
+            )));
+        }
+
+        match self.encoding() {
+            WmtsEncoding::Rest if self.resource_template().is_none() => {
+                return Err(GalileoError::Generic(format!(
+                    "WMTS layer {} has no URL template for format {}",
+                    self.layer.identifier, self.format
+                )))
+            }
+            WmtsEncoding::Kvp if self.capabilities.get_tile_url.is_none() => {
+                return Err(GalileoError::Generic(
+                    "WMTS server does not provide GetTile URL".into(),
+                ))
+            }
+            _ => {}
+        }
+
+        let tile_schema = self.tile_schema()?;
+        let provider = UrlImageProvider::new(move |index: &TileIndex| {
+            self.tile_url(index).unwrap_or_default()
+        });
+        Ok(RasterTileLayer::new(tile_schema, provider, None))
+    }
+
+    fn matrix_set(&self) -> Result<&WmtsTileMatrixSet, GalileoError> {
+        self.capabilities
+            .tile_matrix_set(&self.tile_matrix_set)
+            .ok_or_else(|| {
+                GalileoError::Generic(format!(
+                    "WMTS tile matrix set {} is not found",
+                    self.tile_matrix_set
+                ))
+            })
+    }
+
+    fn encoding(&self) -> WmtsEncoding {
+        self.encoding.unwrap_or(match self.resource_template() {
+            Some(_) => WmtsEncoding::Rest,
+            None => WmtsEncoding::Kvp,
+        })
+    }
+
+    fn resource_template(&self) -> Option<&str> {
+        self.layer
+            .resource_urls
+            .iter()
+            .find(|url| url.format == self.format)
+            .map(|url| url.template.as_str())
+    }
+
+    fn rest_url(&self, set: &str, matrix: &WmtsTileMatrix, index: &TileIndex) -> Option<String> {
+        let mut url = self
+            .resource_template()?
+            .replace("{TileMatrixSet}", set)
+            .replace("{TileMatrix}", &matrix.identifier)
+            .replace("{TileRow}", &index.y.to_string())
+            .replace("{TileCol}", &index.x.to_string())
+            .replace("{Style}", &self.style);
+        for (identifier, value) in &self.dimensions {
+            url = url.replace(&format!("{{{identifier}}}"), value);
+        }
+
+        Some(url)
+    }
+
+    fn kvp_url(&self, set: &str, matrix: &WmtsTileMatrix, index: &TileIndex) -> Option<String> {
+        let params = [
+            ("SERVICE", "WMTS".to_string()),
+            ("REQUEST", "GetTile".to_string()),
+            ("VERSION", "1.0.0".to_string()),
+            ("LAYER", self.layer.identifier.clone()),
+            ("STYLE", self.style.clone()),
+            ("FORMAT", self.format.clone()),
+            ("TILEMATRIXSET", set.to_string()),
+            ("TILEMATRIX", matrix.identifier.clone()),
+            ("TILEROW", index.y.to_string()),
+            ("TILECOL", index.x.to_string()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .chain(self.dimensions.iter().cloned())
+        .chain(self.params.iter().cloned());
+
+        Some(append_params(
+            self.capabilities.get_tile_url.clone()?,
+            params,
+        ))
+    }
+}
+
+fn append_params(mut url: String, params: impl Iterator<Item = (String, String)>) -> String {
+    let mut separator = if !url.contains('?') {
+        "?"
+    } else if !url.ends_with('?') && !url.ends_with('&') {
+        "&"
+    } else {
+        ""
+    };
+    for (key, value) in params {
+        let _ = write!(url, "{separator}{}={}", encode(&key), encode(&value));
+        separator = "&";
+    }
+
+    url
+}
+
+/// Finds the URL of the `GetTile` operation accepting key-value-pair requests.
+fn get_tile_kvp_url(root: &XmlElement) -> Option<String> {
+    let operation = root
+        .child("OperationsMetadata")?
+        .children("Operation")
+        .find(|operation| operation.attribute("name") == Some("GetTile"))?;
+    operation
+        .children("DCP")
+        .flat_map(|dcp| dcp.children("HTTP"))
+        .flat_map(|http| http.children("Get"))
+        .find(|get| {
+            // Without the constraint, any encoding is allowed.
+            let encodings: Vec<_> = get
+                .children("Constraint")
+                .filter(|constraint| constraint.attribute("name") == Some("GetEncoding"))
+                .flat_map(|constraint| constraint.children("AllowedValues"))
+                .flat_map(|values| values.children("Value"))
+                .map(|value| value.text.trim())
+                .collect();
+            encodings.is_empty() || encodings.contains(&"KVP")
+        })?
+        .attribute("href")
+        .map(str::to_string)
+}
+
+fn xml_error(err: impl std::fmt::Display) -> GalileoError {
+    GalileoError::Generic(format!("invalid WMTS capabilities document: {err}"))
+}
+
+/// Element of an XML document with namespaces removed from the names.
+#[derive(Debug, Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+    /// Parses the document and returns its root element.
+    fn parse(xml: &str) -> Result<Self, GalileoError> {
+        let mut reader = Reader::from_str(xml);
+        let mut stack: Vec<XmlElement> = vec![];
+        let mut root = None;
+
+        loop {
+            match reader.read_event().map_err(xml_error)? {
+                Event::Start(element) => stack.push(Self::from_start(&element)?),
+                Event::Empty(element) => {
+                    let element = Self::from_start(&element)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => root = Some(element),
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().ok_or_else(|| xml_error("unexpected end tag"))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => root = Some(element),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text.decode().map_err(xml_error)?);
+                    }
+                }
+                Event::CData(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text.decode().map_err(xml_error)?);
+                    }
+                }
+                Event::GeneralRef(reference) => {
+                    let name = reference.decode().map_err(xml_error)?;
+                    if let Some(element) = stack.last_mut() {
+                        if let Some(ch) = reference.resolve_char_ref().map_err(xml_error)? {
+                            element.text.push(ch);
+                        } else if let Some(value) = resolve_predefined_entity(&name) {
+                            element.text.push_str(value);
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        root.ok_or_else(|| xml_error("empty document"))
+    }
+
+    fn from_start(element: &BytesStart) -> Result<Self, GalileoError> {
+        let attributes = element
+            .attributes()
+            .map(|attribute| {
+                let attribute = attribute.map_err(xml_error)?;
+                let value = attribute
+                    .normalized_value(XmlVersion::default())
+                    .map_err(xml_error)?;
+                Ok((
+                    String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(),
+                    value.into_owned(),
+                ))
+            })
+            .collect::<Result<_, GalileoError>>()?;
+
+        Ok(Self {
+            name: String::from_utf8_lossy(element.local_name().as_ref()).into_owned(),
+            attributes,
+            ..Default::default()
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> + Clone {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Trimmed text of the first child with the given name.
+    fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name).map(|child| child.text.trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    const CAPABILITIES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Capabilities xmlns="http://www.opengis.net/wmts/1.0" xmlns:ows="http://www.opengis.net/ows/1.1"
+    xmlns:xlink="http://www.w3.org/1999/xlink" version="1.0.0">
+  <ows:OperationsMetadata>
+    <ows:Operation name="GetCapabilities">
+      <ows:DCP><ows:HTTP><ows:Get xlink:href="https://example.com/wmts?"/></ows:HTTP></ows:DCP>
+    </ows:Operation>
+    <ows:Operation name="GetTile">
+      <ows:DCP><ows:HTTP>
+        <ows:Get xlink:href="https://example.com/rest/">
+          <ows:Constraint name="GetEncoding"><ows:AllowedValues><ows:Value>RESTful</ows:Value></ows:AllowedValues></ows:Constraint>
+        </ows:Get>
+        <ows:Get xlink:href="https://example.com/wmts?key=secret&amp;">
+          <ows:Constraint name="GetEncoding"><ows:AllowedValues><ows:Value>KVP</ows:Value></ows:AllowedValues></ows:Constraint>
+        </ows:Get>
+      </ows:HTTP></ows:DCP>
+    </ows:Operation>
+  </ows:OperationsMetadata>
+  <Contents>
+    <Layer>
+      <ows:Title>Aerial photos</ows:Title>
+      <ows:Identifier>aerial</ows:Identifier>
+      <Style><ows:Identifier>light</ows:Identifier></Style>
+      <Style isDefault="true"><ows:Identifier>default</ows:Identifier></Style>
+      <Format>image/jpeg</Format>
+      <Format>image/png</Format>
+      <Dimension><ows:Identifier>Time</ows:Identifier><Default>2024</Default><Value>2023</Value><Value>2024</Value></Dimension>
+      <TileMatrixSetLink><TileMatrixSet>utm</TileMatrixSet></TileMatrixSetLink>
+      <TileMatrixSetLink><TileMatrixSet>wgs84</TileMatrixSet></TileMatrixSetLink>
+      <ResourceURL format="image/jpeg" resourceType="tile"
+          template="https://example.com/rest/aerial/{Style}/{Time}/{TileMatrixSet}/{TileMatrix}/{TileRow}/{TileCol}.jpg"/>
+    </Layer>
+    <TileMatrixSet>
+      <ows:Identifier>utm</ows:Identifier>
+      <ows:SupportedCRS>urn:ogc:def:crs:EPSG::25832</ows:SupportedCRS>
+      <TileMatrix>
+        <ows:Identifier>0</ows:Identifier>
+        <ScaleDenominator>1000000</ScaleDenominator>
+        <TopLeftCorner>0 6000000</TopLeftCorner>
+        <TileWidth>256</TileWidth><TileHeight>256</TileHeight>
+        <MatrixWidth>4</MatrixWidth><MatrixHeight>4</MatrixHeight>
+      </TileMatrix>
+    </TileMatrixSet>
+    <TileMatrixSet>
+      <ows:Identifier>wgs84</ows:Identifier>
+      <ows:SupportedCRS>urn:ogc:def:crs:EPSG::4326</ows:SupportedCRS>
+      <TileMatrix>
+        <ows:Identifier>wgs84:1</ows:Identifier>
+        <ScaleDenominator>139770566.00717944</ScaleDenominator>
+        <TopLeftCorner>90 -180</TopLeftCorner>
+        <TileWidth>256</TileWidth><TileHeight>256</TileHeight>
+        <MatrixWidth>4</MatrixWidth><MatrixHeight>2</MatrixHeight>
+      </TileMatrix>
+      <TileMatrix>
+        <ows:Identifier>wgs84:0</ows:Identifier>
+        <ScaleDenominator>279541132.01435887</ScaleDenominator>
+        <TopLeftCorner>90 -180</TopLeftCorner>
+        <TileWidth>256</TileWidth><TileHeight>256</TileHeight>
+        <MatrixWidth>2</MatrixWidth><MatrixHeight>1</MatrixHeight>
+      </TileMatrix>
+    </TileMatrixSet>
+  </Contents>
+</Capabilities>"#;
+
+    #[test]
+    fn parse_capabilities() {
+        let capabilities = WmtsCapabilities::parse(CAPABILITIES).unwrap();
+        assert_eq!(
+            capabilities.get_tile_url.as_deref(),
+            Some("https://example.com/wmts?key=secret&")
+        );
+
+        let layer = capabilities.layer("aerial").unwrap();
+        assert_eq!(layer.title.as_deref(), Some("Aerial photos"));
+        assert_eq!(layer.styles, ["light", "default"]);
+        assert_eq!(layer.default_style.as_deref(), Some("default"));
+        assert_eq!(layer.formats, ["image/jpeg", "image/png"]);
+        assert_eq!(layer.tile_matrix_sets, ["utm", "wgs84"]);
+        assert_eq!(layer.dimensions, [("Time".to_string(), "2024".to_string())]);
+        assert_eq!(layer.resource_urls.len(), 1);
+
+        let set = capabilities.tile_matrix_set("wgs84").unwrap();
+        assert_eq!(set.crs, "urn:ogc:def:crs:EPSG::4326");
+        assert_eq!(set.matrices[0].top_left_corner, [90.0, -180.0]);
+        assert_eq!(set.matrices[0].matrix_width, 4);
+
+        assert!(WmtsCapabilities::parse("<WMS_Capabilities/>").is_err());
+    }
+
+    #[test]
+    fn tile_schema_of_matrix_set() {
+        let capabilities = WmtsCapabilities::parse(CAPABILITIES).unwrap();
+        let schema = capabilities
+            .tile_matrix_set("wgs84")
+            .unwrap()
+            .tile_schema()
+            .unwrap();
+
+        assert_eq!(schema.crs, Crs::WGS84);
+        assert_eq!(schema.lods.len(), 2);
+        // Matrices are ordered by scale and the corner is converted into longitude, latitude order.
+        assert_abs_diff_eq!(
+            schema.lod_resolution(0).unwrap(),
+            180.0 / 256.0,
+            epsilon = 1e-9
+        );
+        assert_eq!(
+            schema.tile_matrix(1).unwrap().origin,
+            Point2d::new(-180.0, 90.0)
+        );
+        assert_abs_diff_eq!(schema.bounds.x_max(), 180.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(schema.bounds.y_min(), -90.0, epsilon = 1e-6);
+
+        let utm = capabilities.tile_matrix_set("utm").unwrap();
+        assert!(utm.tile_schema().is_err());
+        let schema = utm.tile_schema_with_crs(Crs::EPSG3857, 1.0).unwrap();
+        assert_abs_diff_eq!(schema.lod_resolution(0).unwrap(), 280.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn rest_tile_urls() {
+        let capabilities = WmtsCapabilities::parse(CAPABILITIES).unwrap();
+        let builder = capabilities.layer_builder("aerial").unwrap();
+
+        // The UTM set is skipped as its CRS is not supported.
+        assert_eq!(
+            builder.tile_url(&TileIndex::new(3, 1, 1)).as_deref(),
+            Some("https://example.com/rest/aerial/default/2024/wgs84/wgs84:1/1/3.jpg")
+        );
+        assert_eq!(builder.tile_url(&TileIndex::new(0, 0, 2)), None);
+
+        let builder = builder.with_dimension("time", "2023").with_style("light");
+        assert_eq!(
+            builder.tile_url(&TileIndex::new(0, 0, 0)).as_deref(),
+            Some("https://example.com/rest/aerial/light/2023/wgs84/wgs84:0/0/0.jpg")
+        );
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn kvp_tile_urls() {
+        let capabilities = WmtsCapabilities::parse(CAPABILITIES).unwrap();
+        let builder = capabilities
+            .layer_builder("aerial")
+            .unwrap()
+            .with_format("image/png")
+            .with_param("token", "a b");
+
+        assert_eq!(
+            builder.tile_url(&TileIndex::new(3, 1, 1)).as_deref(),
+            Some(concat!(
+                "https://example.com/wmts?key=secret&SERVICE=WMTS&REQUEST=GetTile&VERSION=1.0.0&LAYER=aerial",
+                "&STYLE=default&FORMAT=image/png&TILEMATRIXSET=wgs84&TILEMATRIX=wgs84:1&TILEROW=1&TILECOL=3",
+                "&Time=2024&token=a%20b"
+            ))
+        );
+
+        assert!(builder
+            .clone()
+            .with_encoding(WmtsEncoding::Rest)
+            .build()
+            .is_err());
+        assert!(builder
+            .clone()
+            .with_tile_matrix_set("unknown")
+            .build()
+            .is_err());
+        assert!(builder.build().is_ok());
+        assert!(capabilities.layer_builder("roads").is_err());
+    }
+}
@@ -68,15 +68,19 @@
 #![warn(clippy::unwrap_used)]
 #![warn(missing_docs)]
 
+pub mod animation;
 pub(crate) mod async_runtime;
 mod color;
 pub mod control;
 pub mod decoded_image;
 pub mod error;
+#[cfg(all(feature = "geojson", not(target_arch = "wasm32")))]
+pub mod io;
 pub mod layer;
 mod lod;
 mod map;
 mod messenger;
+pub mod overlay;
 pub mod platform;
 pub mod render;
 pub mod tile_scheme;
@@ -93,10 +97,16 @@ pub use galileo_map::{GalileoMap, MapBuilder};
 pub use color::Color;
 pub use layer::feature_layer::symbol;
 pub use lod::Lod;
-pub use map::{LayerCollection, Map};
+pub use map::{
+    Easing, LayerCollection, LayerId, LayerSource, LayerState, Map, MapState, OverviewMap, Swipe,
+    SwipeOrientation, SwipeSide, TimeDimension, ViewLink, ViewState,
+};
 pub use messenger::{DummyMessenger, Messenger};
 pub use tile_scheme::TileSchema;
-pub use view::MapView;
+pub use view::{MapView, ViewConstraints};
 
 // Reexport galileo_types
 pub use galileo_types;
+// Reexport wgpu for custom render layers
+#[cfg(feature = "wgpu")]
+pub use wgpu;
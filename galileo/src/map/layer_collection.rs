@@ -1,17 +1,26 @@
-use crate::layer::Layer;
+use crate::layer::{Attribution, Layer, LayerGroup};
+use crate::map::LayerSource;
+use crate::render::BlendMode;
 use std::ops::{Index, IndexMut, RangeBounds};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 
 /// Collection of layers with some meta-information.
 ///
 /// When a map is rendered, it draws all visible layers in the order they are stored in the
 /// collection. Any layer can be temporary hidden with the [`LayerCollection::hide`] or
 /// [`LayerCollection::show_by`] methods. These layers will be ignored by the renderer, but
-/// retain their place in the collection.
+/// retain their place in the collection. Layers can also be drawn semi-transparent with
+/// [`LayerCollection::set_opacity`], and composed with the underlying layers in different ways with
+/// [`LayerCollection::set_blend_mode`].
 ///
 /// Since a map should be able to render anything implementing the [`Layer`] trait, this
 /// collection stores layers as trait objects. You can use downcasting through `Any` trait
 /// to obtain a concrete layer type you work with.
 ///
+/// Every layer added to the collection gets a [`LayerId`], which stays the same when other layers are inserted,
+/// removed or reordered. Use [`LayerCollection::index_of`] to find the current position of a layer by its id.
+///
 /// ```no_run
 /// use galileo::layer::{RasterTileLayer, VectorTileLayer};
 /// use galileo::layer::data_provider::FileCacheController;
@@ -34,9 +43,27 @@ use std::ops::{Index, IndexMut, RangeBounds};
 #[derive(Default)]
 pub struct LayerCollection(Vec<LayerEntry>);
 
+/// Stable identifier of a layer in a [`LayerCollection`].
+///
+/// Unlike the index of the layer, the id does not change when the layers are reordered. Ids are unique for the
+/// lifetime of the program, so an id of a removed layer is never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LayerId(u64);
+
+impl LayerId {
+    fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
 struct LayerEntry {
+    id: LayerId,
     layer: Box<dyn Layer>,
     is_hidden: bool,
+    opacity: f32,
+    blend_mode: BlendMode,
+    source: Option<LayerSource>,
 }
 
 impl LayerCollection {
@@ -214,6 +241,11 @@ impl LayerCollection {
         self.0.push(layer.into())
     }
 
+    /// Same as [`LayerCollection::push`] for a layer that is already boxed.
+    pub(crate) fn push_boxed(&mut self, layer: Box<dyn Layer>) {
+        self.0.push(layer.into())
+    }
+
     /// Removes the last layer from the collection and returns it. Returns `None` if the collection
     /// is empty.
     ///
@@ -377,6 +409,36 @@ impl LayerCollection {
         self.0.swap(a, b)
     }
 
+    /// Moves the layer from the index `from` to the index `to`, shifting the layers between them. The visibility of
+    /// the layer is preserved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` are out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    ///
+    /// let mut collection = LayerCollection::from(vec![
+    ///     TestLayer("Layer A"),
+    ///     TestLayer("Layer B"),
+    ///     TestLayer("Layer C"),
+    /// ]);
+    ///
+    /// collection.move_layer(0, 2);
+    ///
+    /// assert_eq!(collection[0].as_any().downcast_ref(), Some(&TestLayer("Layer B")));
+    /// assert_eq!(collection[1].as_any().downcast_ref(), Some(&TestLayer("Layer C")));
+    /// assert_eq!(collection[2].as_any().downcast_ref(), Some(&TestLayer("Layer A")));
+    /// ```
+    pub fn move_layer(&mut self, from: usize, to: usize) {
+        let entry = self.0.remove(from);
+        self.0.insert(to, entry);
+    }
+
     /// Iterates over all layers in the collection.
     ///
     /// ```
@@ -471,6 +533,139 @@ impl LayerCollection {
         self.0[index].is_hidden = false;
     }
 
+    /// Sets the layer at `index` as visible or hidden. See [`LayerCollection::show`] and
+    /// [`LayerCollection::hide`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    ///
+    /// let mut collection = LayerCollection::from(vec![
+    ///     TestLayer("Layer A"),
+    ///     TestLayer("Layer B"),
+    /// ]);
+    ///
+    /// collection.set_visible(1, false);
+    /// assert!(!collection.is_visible(1));
+    /// collection.set_visible(1, true);
+    /// assert!(collection.is_visible(1));
+    /// ```
+    pub fn set_visible(&mut self, index: usize, is_visible: bool) {
+        self.0[index].is_hidden = !is_visible;
+    }
+
+    /// Sets the opacity of the layer at `index`. The value is clamped into `[0.0, 1.0]` range, where `0.0` is fully
+    /// transparent and `1.0` is fully opaque (default).
+    ///
+    /// The layer is faded as a whole: it is first drawn on its own, and the result is drawn over the layers below
+    /// with the given opacity, so overlapping features, tiles and labels of the layer do not show through each
+    /// other. Layers with zero opacity are skipped by the renderer the same way as hidden layers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    ///
+    /// let mut collection = LayerCollection::from(vec![
+    ///     TestLayer("Layer A"),
+    ///     TestLayer("Layer B"),
+    /// ]);
+    ///
+    /// assert_eq!(collection.opacity(1), 1.0);
+    /// collection.set_opacity(1, 0.5);
+    /// assert_eq!(collection.opacity(1), 0.5);
+    /// collection.set_opacity(1, 2.0);
+    /// assert_eq!(collection.opacity(1), 1.0);
+    /// ```
+    pub fn set_opacity(&mut self, index: usize, opacity: f32) {
+        self.0[index].opacity = if opacity.is_nan() {
+            1.0
+        } else {
+            opacity.clamp(0.0, 1.0)
+        };
+    }
+
+    /// Returns the opacity of the layer at `index`. See [`LayerCollection::set_opacity`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn opacity(&self, index: usize) -> f32 {
+        self.0[index].opacity
+    }
+
+    /// Sets the way the layer at `index` is combined with the layers drawn before it. The default mode is
+    /// [`BlendMode::Normal`].
+    ///
+    /// As with the [opacity](LayerCollection::set_opacity), the layer is first drawn on its own, and then the result
+    /// is combined with the layers below, so the features of the layer are not blended with each other.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    /// use galileo::render::BlendMode;
+    ///
+    /// let mut collection = LayerCollection::from(vec![
+    ///     TestLayer("Basemap"),
+    ///     TestLayer("Radar"),
+    /// ]);
+    ///
+    /// collection.set_blend_mode(1, BlendMode::Multiply);
+    /// assert_eq!(collection.blend_mode(1), BlendMode::Multiply);
+    /// assert_eq!(collection.blend_mode(0), BlendMode::Normal);
+    /// ```
+    pub fn set_blend_mode(&mut self, index: usize, blend_mode: BlendMode) {
+        self.0[index].blend_mode = blend_mode;
+    }
+
+    /// Returns the blend mode of the layer at `index`. See [`LayerCollection::set_blend_mode`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn blend_mode(&self, index: usize) -> BlendMode {
+        self.0[index].blend_mode
+    }
+
+    /// Sets the source the layer at `index` was created from. The source is saved in the
+    /// [state](crate::Map::state) of the map, so that the layer can be created again when the state is restored.
+    ///
+    /// The collection does not check that the source matches the layer. If a parameter of the layer saved in the
+    /// source is changed (for example, the style of a vector tile layer), the source must be updated too.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_source(&mut self, index: usize, source: Option<LayerSource>) {
+        self.0[index].source = source;
+    }
+
+    /// Returns the source of the layer at `index`. See [`LayerCollection::set_source`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn source(&self, index: usize) -> Option<&LayerSource> {
+        self.0[index].source.as_ref()
+    }
+
     /// Sets all layers for which the predicate returns true as visible. The rest of layers are set
     /// as hidden.
     ///
@@ -529,6 +724,59 @@ impl LayerCollection {
         !self.0[index].is_hidden
     }
 
+    /// Returns the id of the layer at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn id(&self, index: usize) -> LayerId {
+        self.0[index].id
+    }
+
+    /// Returns the current index of the layer with the given id, or `None` if the collection does not contain it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    ///
+    /// let mut collection = LayerCollection::from(vec![
+    ///     TestLayer("Layer A"),
+    ///     TestLayer("Layer B"),
+    /// ]);
+    ///
+    /// let id = collection.id(0);
+    /// collection.move_layer(0, 1);
+    /// assert_eq!(collection.index_of(id), Some(1));
+    ///
+    /// collection.remove(1);
+    /// assert_eq!(collection.index_of(id), None);
+    /// ```
+    pub fn index_of(&self, id: LayerId) -> Option<usize> {
+        self.0.iter().position(|entry| entry.id == id)
+    }
+
+    /// Iterates over all layers in the collection together with their ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    ///
+    /// let collection = LayerCollection::from(vec![
+    ///     TestLayer("Layer A"),
+    ///     TestLayer("Layer B"),
+    /// ]);
+    ///
+    /// let ids: Vec<_> = collection.iter_with_ids().map(|(id, _)| id).collect();
+    /// assert_eq!(ids, vec![collection.id(0), collection.id(1)]);
+    /// ```
+    pub fn iter_with_ids(&self) -> impl Iterator<Item = (LayerId, &dyn Layer)> + '_ {
+        self.0.iter().map(|entry| (entry.id, &*entry.layer))
+    }
+
     /// Iterates over all visible layers in the collection.
     ///
     /// # Examples
@@ -556,6 +804,80 @@ impl LayerCollection {
             .filter(|entry| !entry.is_hidden)
             .map(|entry| &*entry.layer)
     }
+
+    /// Returns the attributions of all visible layers without duplicates. See [`Layer::attributions`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use galileo::LayerCollection;
+    /// use galileo::layer::TestLayer;
+    ///
+    /// let collection = LayerCollection::from(vec![TestLayer("Layer A")]);
+    /// assert!(collection.attributions().is_empty());
+    /// ```
+    pub fn attributions(&self) -> Vec<Attribution> {
+        let mut attributions: Vec<Attribution> = vec![];
+        for layer in self.iter_visible() {
+            for attribution in layer.attributions() {
+                if !attributions.contains(&attribution) {
+                    attributions.push(attribution);
+                }
+            }
+        }
+
+        attributions
+    }
+
+    /// Calls `f` for every layer that should be rendered, in the rendering order, together with the id, opacity and
+    /// blend mode of the layer.
+    ///
+    /// [`LayerGroup`]s (including the groups shared as `Arc<RwLock<LayerGroup>>`) are given as
+    /// [`RenderedItem::Group`], so that the renderer can compose their child layers together before drawing the
+    /// group with its opacity and blend mode.
+    pub(crate) fn for_each_rendered(
+        &self,
+        mut f: impl FnMut(LayerId, RenderedItem<'_>, f32, BlendMode),
+    ) {
+        for entry in self
+            .0
+            .iter()
+            .filter(|entry| !entry.is_hidden && entry.opacity > 0.0)
+        {
+            let layer = entry.layer.as_any();
+            if let Some(group) = layer.downcast_ref::<LayerGroup>() {
+                f(
+                    entry.id,
+                    RenderedItem::Group(group.layers()),
+                    entry.opacity,
+                    entry.blend_mode,
+                );
+            } else if let Some(group) = layer.downcast_ref::<Arc<RwLock<LayerGroup>>>() {
+                let group = group.read().expect("lock is poisoned");
+                f(
+                    entry.id,
+                    RenderedItem::Group(group.layers()),
+                    entry.opacity,
+                    entry.blend_mode,
+                );
+            } else {
+                f(
+                    entry.id,
+                    RenderedItem::Layer(&*entry.layer),
+                    entry.opacity,
+                    entry.blend_mode,
+                );
+            }
+        }
+    }
+}
+
+/// Layer or a group of layers drawn by a renderer. See [`LayerCollection::for_each_rendered`].
+pub(crate) enum RenderedItem<'a> {
+    /// A single layer.
+    Layer(&'a dyn Layer),
+    /// Child layers of a [`LayerGroup`].
+    Group(&'a LayerCollection),
 }
 
 impl Index<usize> for LayerCollection {
@@ -581,8 +903,12 @@ impl<L: Into<LayerEntry>, T: IntoIterator<Item = L>> From<T> for LayerCollection
 impl<T: Layer + 'static> From<T> for LayerEntry {
     fn from(value: T) -> Self {
         Self {
+            id: LayerId::next(),
             layer: Box::new(value),
             is_hidden: false,
+            opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+            source: None,
         }
     }
 }
@@ -590,8 +916,12 @@ impl<T: Layer + 'static> From<T> for LayerEntry {
 impl From<Box<dyn Layer>> for LayerEntry {
     fn from(value: Box<dyn Layer>) -> Self {
         Self {
+            id: LayerId::next(),
             layer: value,
             is_hidden: false,
+            opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+            source: None,
         }
     }
 }
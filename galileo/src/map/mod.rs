@@ -1,12 +1,24 @@
+use crate::animation::{Animation, AnimationId};
 use crate::layer::Layer;
 use crate::messenger::Messenger;
-use crate::view::MapView;
+use crate::overlay::Overlay;
+use crate::view::{MapView, ViewConstraints};
 use galileo_types::cartesian::Size;
+use std::sync::Arc;
 use std::time::Duration;
 use web_time::SystemTime;
 
 mod layer_collection;
-pub use layer_collection::LayerCollection;
+mod overview;
+mod state;
+mod swipe;
+pub(crate) mod time;
+pub(crate) use layer_collection::RenderedItem;
+pub use layer_collection::{LayerCollection, LayerId};
+pub use overview::OverviewMap;
+pub use state::{LayerSource, LayerState, MapState, ViewState};
+pub use swipe::{Swipe, SwipeOrientation, SwipeSide, ViewLink};
+pub use time::TimeDimension;
 
 const FRAME_DURATION: Duration = Duration::from_millis(16);
 
@@ -14,8 +26,22 @@ const FRAME_DURATION: Duration = Duration::from_millis(16);
 pub struct Map {
     view: MapView,
     layers: LayerCollection,
-    messenger: Option<Box<dyn Messenger>>,
+    overlays: Vec<Box<dyn Overlay>>,
+    swipe: Option<Swipe>,
+    messenger: Option<Arc<dyn Messenger>>,
     animation: Option<AnimationParameters>,
+    constraints: ViewConstraints,
+    time_dimension: Option<TimeDimension>,
+    time_playback: Option<TimePlayback>,
+    animations: Vec<(AnimationId, Box<dyn Animation>)>,
+    next_animation_id: u64,
+}
+
+struct TimePlayback {
+    start_time: SystemTime,
+    start_real_time: SystemTime,
+    speed: f64,
+    looped: bool,
 }
 
 struct AnimationParameters {
@@ -23,6 +49,42 @@ struct AnimationParameters {
     end_view: MapView,
     start_time: SystemTime,
     duration: Duration,
+    easing: Easing,
+    is_flight: bool,
+}
+
+/// Easing curve of an animation, that specifies how fast the animated value changes during the animation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    /// The value changes with constant speed.
+    #[default]
+    Linear,
+    /// The change starts slowly and accelerates to the end.
+    EaseIn,
+    /// The change starts fast and slows down to the end.
+    EaseOut,
+    /// The change starts slowly, accelerates in the middle and slows down to the end.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Returns the share of the change done by the time `t`, where `t` is the share of the animation duration that
+    /// passed. Both values are in the range `[0.0, 1.0]`.
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
 }
 
 impl Map {
@@ -32,16 +94,23 @@ impl Map {
         layers: Vec<Box<dyn Layer>>,
         messenger: Option<impl Messenger + 'static>,
     ) -> Self {
-        let messenger: Option<Box<dyn Messenger>> = if let Some(m) = messenger {
-            Some(Box::new(m))
+        let messenger: Option<Arc<dyn Messenger>> = if let Some(m) = messenger {
+            Some(Arc::new(m))
         } else {
             None
         };
         Self {
             view,
             layers: layers.into(),
+            overlays: vec![],
+            swipe: None,
             messenger,
             animation: None,
+            constraints: ViewConstraints::default(),
+            time_dimension: None,
+            time_playback: None,
+            animations: vec![],
+            next_animation_id: 0,
         }
     }
 
@@ -56,22 +125,337 @@ impl Map {
     }
 
     /// Returns a mutable reference to the list of map's layers.
+    ///
+    /// Since the layers can be modified through the returned reference, redraw of the map is requested.
     pub fn layers_mut(&mut self) -> &mut LayerCollection {
+        self.redraw();
         &mut self.layers
     }
 
-    pub(crate) fn set_view(&mut self, view: MapView) {
-        self.view = view;
+    /// Adds the layer on top of all other layers of the map, requests redraw and returns the id of the added layer.
+    ///
+    /// If the map has a messenger, it is also set for the layer.
+    pub fn add_layer(&mut self, layer: impl Layer + 'static) -> LayerId {
+        self.insert_layer(self.layers.len(), layer)
+    }
+
+    /// Inserts the layer at the given `index`, shifting all layers after it up, requests redraw and returns the id
+    /// of the inserted layer.
+    ///
+    /// If the map has a messenger, it is also set for the layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of layers.
+    pub fn insert_layer(&mut self, index: usize, mut layer: impl Layer + 'static) -> LayerId {
         if let Some(messenger) = &self.messenger {
-            messenger.request_redraw();
+            layer.set_messenger(Box::new(messenger.clone()));
+        }
+
+        self.layers.insert(index, layer);
+        self.redraw();
+        self.layers.id(index)
+    }
+
+    /// Removes the layer at the given `index` from the map, returns it and requests redraw.
+    ///
+    /// Tiles that are still being loaded by the removed layer are discarded when they are loaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_layer(&mut self, index: usize) -> Box<dyn Layer> {
+        let layer = self.layers.remove(index);
+        self.redraw();
+        layer
+    }
+
+    /// Moves the layer from the index `from` to the index `to` and requests redraw. Layers are drawn in the order
+    /// of their indices, so the layer with the largest index is drawn on top. See [`LayerCollection::move_layer`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` are out of bounds.
+    pub fn move_layer(&mut self, from: usize, to: usize) {
+        self.layers.move_layer(from, to);
+        self.redraw();
+    }
+
+    /// Removes the layer with the given id from the map and requests redraw. Returns `None` if the map does not
+    /// contain the layer.
+    pub fn remove_layer_by_id(&mut self, id: LayerId) -> Option<Box<dyn Layer>> {
+        let index = self.layers.index_of(id)?;
+        Some(self.remove_layer(index))
+    }
+
+    /// Moves the layer with the given id to the index `to` and requests redraw. Returns `false` if the map does not
+    /// contain the layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `to` is out of bounds.
+    pub fn move_layer_by_id(&mut self, id: LayerId, to: usize) -> bool {
+        let Some(from) = self.layers.index_of(id) else {
+            return false;
+        };
+
+        self.move_layer(from, to);
+        true
+    }
+
+    /// Shows or hides the layer with the given id and requests redraw. Returns `false` if the map does not contain
+    /// the layer. See [`LayerCollection::set_visible`].
+    pub fn set_layer_visible(&mut self, id: LayerId, is_visible: bool) -> bool {
+        let Some(index) = self.layers.index_of(id) else {
+            return false;
+        };
+
+        self.layers.set_visible(index, is_visible);
+        self.redraw();
+        true
+    }
+
+    /// Creates a layer from a built-in source with [`LayerSource::build_layer`], adds it on top of all other layers
+    /// and records the source in the layer collection, so that it is saved in the [state](Map::state) of the map.
+    ///
+    /// Returns `None` if the source is not a built-in one.
+    pub fn add_layer_from_source(&mut self, source: LayerSource) -> Option<LayerId> {
+        let layer = source.build_layer()?;
+        let id = self.add_boxed_layer(layer);
+        self.layers.set_source(self.layers.len() - 1, Some(source));
+        Some(id)
+    }
+
+    /// Returns the state of the map, that can be saved and restored with [`Map::restore_state`]. Returns `None` if
+    /// the center of the view cannot be projected into geographic coordinates.
+    pub fn state(&self) -> Option<MapState> {
+        let layers = (0..self.layers.len())
+            .map(|index| LayerState {
+                source: self.layers.source(index).cloned(),
+                is_visible: self.layers.is_visible(index),
+                opacity: self.layers.opacity(index),
+                blend_mode: self.layers.blend_mode(index),
+            })
+            .collect();
+
+        Some(MapState {
+            view: ViewState::from_view(&self.view)?,
+            layers,
+        })
+    }
+
+    /// Replaces the layers of the map with the layers of the `state` and moves the view to the view of the state.
+    ///
+    /// The layers are created by the `build_layer` function from their sources. Use [`LayerSource::build_layer`] to
+    /// create the layers from the built-in sources. Layers that have no source or cannot be created are skipped.
+    ///
+    /// ```no_run
+    /// use galileo::{Map, MapState};
+    /// # fn load(map: &mut Map, json: &str) {
+    /// let state: MapState = serde_json::from_str(json).expect("invalid state");
+    /// map.restore_state(&state, |source| source.build_layer());
+    /// # }
+    /// ```
+    pub fn restore_state(
+        &mut self,
+        state: &MapState,
+        mut build_layer: impl FnMut(&LayerSource) -> Option<Box<dyn Layer>>,
+    ) {
+        self.layers.clear();
+        for layer_state in &state.layers {
+            let Some(source) = &layer_state.source else {
+                continue;
+            };
+            let Some(layer) = build_layer(source) else {
+                log::warn!("Failed to restore a layer from source {source:?}");
+                continue;
+            };
+
+            self.add_boxed_layer(layer);
+            let index = self.layers.len() - 1;
+            self.layers.set_source(index, Some(source.clone()));
+            self.layers.set_visible(index, layer_state.is_visible);
+            self.layers.set_opacity(index, layer_state.opacity);
+            self.layers.set_blend_mode(index, layer_state.blend_mode);
+        }
+
+        self.set_view(state.view.apply(&self.view));
+    }
+
+    fn add_boxed_layer(&mut self, mut layer: Box<dyn Layer>) -> LayerId {
+        if let Some(messenger) = &self.messenger {
+            layer.set_messenger(Box::new(messenger.clone()));
+        }
+
+        self.layers.push_boxed(layer);
+        self.redraw();
+        self.layers.id(self.layers.len() - 1)
+    }
+
+    /// Overlays of the map in the order they are drawn over the layers.
+    pub fn overlays(&self) -> &[Box<dyn Overlay>] {
+        &self.overlays
+    }
+
+    /// Adds the overlay on top of all other overlays of the map and requests redraw.
+    pub fn add_overlay(&mut self, overlay: impl Overlay + 'static) {
+        self.overlays.push(Box::new(overlay));
+        self.redraw();
+    }
+
+    /// Removes the overlay at the given `index`, requests redraw and returns the removed overlay.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_overlay(&mut self, index: usize) -> Box<dyn Overlay> {
+        let overlay = self.overlays.remove(index);
+        self.redraw();
+        overlay
+    }
+
+    /// Swipe comparison mode of the map, if it is enabled.
+    pub fn swipe(&self) -> Option<&Swipe> {
+        self.swipe.as_ref()
+    }
+
+    /// Mutable reference to the swipe comparison mode of the map, e.g. to move the divider. Requests redraw.
+    pub fn swipe_mut(&mut self) -> Option<&mut Swipe> {
+        self.redraw();
+        self.swipe.as_mut()
+    }
+
+    /// Enables the swipe comparison mode with the given settings, or disables it if `None` is given, and requests
+    /// redraw.
+    pub fn set_swipe(&mut self, swipe: Option<Swipe>) {
+        self.swipe = swipe;
+        self.redraw();
+    }
+
+    /// Sets the view of the map and requests redraw. The view is adjusted to satisfy the
+    /// [view constraints](Map::set_view_constraints) of the map.
+    ///
+    /// Any running animation is stopped. The time of the map is not changed by the view, use [`Map::set_time`] for
+    /// that.
+    pub fn set_view(&mut self, view: MapView) {
+        self.animation = None;
+        self.view = self.constraints.apply(&view).with_time(self.view.time());
+        self.redraw();
+    }
+
+    /// Current time of the map, see [`MapView::time`].
+    pub fn time(&self) -> Option<SystemTime> {
+        self.view.time()
+    }
+
+    /// Sets the time displayed by the time-enabled layers of the map and requests redraw. If the map has a
+    /// [time dimension](Map::set_time_dimension), the time is clamped to it.
+    ///
+    /// If the time playback is running, it continues from the given time.
+    pub fn set_time(&mut self, time: SystemTime) {
+        let time = match &self.time_dimension {
+            Some(dimension) => dimension.clamp(time),
+            None => time,
+        };
+
+        if let Some(playback) = &mut self.time_playback {
+            playback.start_time = time;
+            playback.start_real_time = SystemTime::now();
+        }
+
+        self.view = self.view.with_time(Some(time));
+        self.redraw();
+    }
+
+    /// Removes the time of the map, so that the time-enabled layers display all their data. Stops the time playback.
+    pub fn clear_time(&mut self) {
+        self.time_playback = None;
+        self.view = self.view.with_time(None);
+        self.redraw();
+    }
+
+    /// Range of the values of the map time.
+    pub fn time_dimension(&self) -> Option<&TimeDimension> {
+        self.time_dimension.as_ref()
+    }
+
+    /// Sets the range of the values of the map time. The current time of the map, if set, is clamped to the new
+    /// range.
+    pub fn set_time_dimension(&mut self, dimension: Option<TimeDimension>) {
+        self.time_dimension = dimension;
+        if let Some(time) = self.time() {
+            self.set_time(time);
+        }
+    }
+
+    /// Starts the playback of the map time. The time is advanced by [`Map::animate`] by `speed` seconds every second,
+    /// starting from the current time of the map (or the start of the time dimension if the map time is not set).
+    /// Negative `speed` plays the time backwards.
+    ///
+    /// When the time reaches the end of the [time dimension](Map::set_time_dimension), the playback either stops, or
+    /// if `looped` is true, continues from the other end of the dimension.
+    pub fn play_time(&mut self, speed: f64, looped: bool) {
+        let start_time = self
+            .time()
+            .or(self.time_dimension.map(|dimension| dimension.start()))
+            .unwrap_or_else(SystemTime::now);
+
+        self.time_playback = Some(TimePlayback {
+            start_time,
+            start_real_time: SystemTime::now(),
+            speed,
+            looped,
+        });
+        self.view = self.view.with_time(Some(start_time));
+        self.redraw();
+    }
+
+    /// Stops the playback of the map time started by [`Map::play_time`]. The map keeps its current time.
+    pub fn stop_time(&mut self) {
+        self.time_playback = None;
+    }
+
+    /// Returns true if the playback of the map time is running.
+    pub fn is_playing_time(&self) -> bool {
+        self.time_playback.is_some()
+    }
+
+    /// Limits of the map view.
+    pub fn view_constraints(&self) -> &ViewConstraints {
+        &self.constraints
+    }
+
+    /// Sets the limits of the map view. The current view is adjusted to satisfy them.
+    pub fn set_view_constraints(&mut self, constraints: ViewConstraints) {
+        self.constraints = constraints;
+        self.view = self.constraints.apply(&self.view);
+        if let Some(animation) = &self.animation {
+            let end_view = self.animation_target(&animation.end_view);
+            if let Some(animation) = &mut self.animation {
+                animation.end_view = end_view;
+            }
         }
+        self.redraw();
     }
 
     /// Calls [`Layer::prepare`] method on all the layers with the current map view. Used to preload layer data before
     /// the map is rendered.
     pub fn load_layers(&self) {
+        let views = self.world_views(&self.view);
         for layer in self.layers.iter_visible() {
-            layer.prepare(&self.view);
+            for view in &views {
+                layer.prepare(view);
+            }
+        }
+    }
+
+    /// Views to draw the map with for the given `view`: the copies of the world visible in the view if the world is
+    /// [repeated horizontally](ViewConstraints::with_world_wrap), or only the view itself otherwise.
+    pub(crate) fn world_views(&self, view: &MapView) -> Vec<MapView> {
+        if self.constraints.world_wrap() {
+            view.world_copies()
+        } else {
+            vec![view.clone()]
         }
     }
 
@@ -82,32 +466,80 @@ impl Map {
         }
     }
 
-    /// Update the view of the map before the rendering in case [`Map::animate_to`] was called.
+    /// Update the view of the map before the rendering in case [`Map::animate_to`] or [`Map::play_time`] was called,
+    /// and the [animations](Map::add_animation) of the map content.
     pub fn animate(&mut self) {
+        if self.animate_time() {
+            self.redraw();
+        }
+
+        if !self.animations.is_empty() {
+            let now = SystemTime::now();
+            self.animations
+                .retain_mut(|(_, animation)| animation.update(now));
+            self.redraw();
+        }
+
         let Some(animation) = &self.animation else {
             return;
         };
 
         let now = SystemTime::now();
-        let k = now
-            .duration_since(animation.start_time)
-            .unwrap_or_default()
-            .as_millis() as f64
-            / animation.duration.as_millis() as f64;
+        let k = if animation.duration.is_zero() {
+            1.0
+        } else {
+            now.duration_since(animation.start_time)
+                .unwrap_or_default()
+                .as_millis() as f64
+                / animation.duration.as_millis() as f64
+        };
 
         if k >= 1.0 {
             let animation = self
                 .animation
                 .take()
                 .expect("the value was removed unexpectedly");
-            self.view = animation.end_view;
+            self.view = self
+                .constraints
+                .apply(&animation.end_view)
+                .with_time(self.view.time());
         } else {
-            self.view = animation.start_view.interpolate(&animation.end_view, k);
+            let k = animation.easing.apply(k);
+            let view = if animation.is_flight {
+                animation.start_view.interpolate_fly(&animation.end_view, k)
+            } else {
+                animation.start_view.interpolate(&animation.end_view, k)
+            };
+            self.view = self.constraints.apply(&view).with_time(self.view.time());
         }
 
         self.redraw();
     }
 
+    /// Advances the time of the map if the playback is running. Returns false if there is no playback.
+    fn animate_time(&mut self) -> bool {
+        let Some(playback) = &self.time_playback else {
+            return false;
+        };
+
+        let passed = time::seconds_between(playback.start_real_time, SystemTime::now());
+        let mut time = time::shift_time(playback.start_time, passed * playback.speed);
+        if let Some(dimension) = &self.time_dimension {
+            let length = dimension.length().as_secs_f64();
+            let offset = time::seconds_between(dimension.start(), time);
+            if playback.looped && length > 0.0 {
+                time = time::shift_time(dimension.start(), offset.rem_euclid(length));
+            } else if offset < 0.0 || offset > length {
+                self.time_playback = None;
+            }
+
+            time = dimension.clamp(time);
+        }
+
+        self.view = self.view.with_time(Some(time));
+        true
+    }
+
     /// Target view of the current animation.
     pub fn target_view(&self) -> &MapView {
         self.animation
@@ -117,17 +549,329 @@ impl Map {
     }
 
     /// Request a gradual change of the map view to the specified view.
-    pub fn animate_to(&mut self, target: MapView, duration: Duration) {
+    ///
+    /// All parameters of the view (position, resolution and rotation) are interpolated between the current and the
+    /// `target` views with the given `easing` curve. The view is updated by [`Map::animate`], which should be called
+    /// before every frame is rendered. Calling this method again replaces the current animation, and the new one starts
+    /// from the current view.
+    pub fn animate_to(&mut self, target: MapView, duration: Duration, easing: Easing) {
+        self.start_animation(target, duration, easing, false);
+    }
+
+    /// Request a gradual change of the map view to the specified view, that zooms out in the beginning and zooms in
+    /// in the end, as if the camera flew from the current position to the target one.
+    ///
+    /// This is a better way to move between places that are far from each other than [`Map::animate_to`], since with
+    /// linear interpolation of the position most of the animation would show the map scrolling too fast to see
+    /// anything. If the current and the target positions are close, the animation is the same as with
+    /// [`Easing::EaseInOut`].
+    pub fn fly_to(&mut self, target: MapView, duration: Duration) {
+        self.start_animation(target, duration, Easing::EaseInOut, true);
+    }
+
+    fn start_animation(
+        &mut self,
+        target: MapView,
+        duration: Duration,
+        easing: Easing,
+        is_flight: bool,
+    ) {
         self.animation = Some(AnimationParameters {
             start_view: self.view.clone(),
-            end_view: target,
+            end_view: self.animation_target(&target),
             start_time: SystemTime::now() - FRAME_DURATION,
             duration,
+            easing,
+            is_flight,
         });
+        self.redraw();
+    }
+
+    /// Applies the constraints to the target view of an animation. If the world is repeated, the target is moved to
+    /// the world copy closest to the current view, so the animation takes the short way across the antimeridian.
+    fn animation_target(&self, target: &MapView) -> MapView {
+        let target = self.constraints.apply(target);
+        if self.constraints.world_wrap() {
+            target.closest_world_copy(&self.view)
+        } else {
+            target
+        }
+    }
+
+    /// Adds an animation of the map content, e.g. a [`FeatureTween`](crate::animation::FeatureTween), requests
+    /// redraw and returns the id of the added animation.
+    ///
+    /// The animation is updated by [`Map::animate`] before every frame until it finishes or is removed with
+    /// [`Map::remove_animation`].
+    pub fn add_animation(&mut self, animation: impl Animation + 'static) -> AnimationId {
+        let id = AnimationId(self.next_animation_id);
+        self.next_animation_id += 1;
+        self.animations.push((id, Box::new(animation)));
+        self.redraw();
+
+        id
+    }
+
+    /// Removes the animation with the given id. Returns `false` if the animation has already finished or been
+    /// removed.
+    pub fn remove_animation(&mut self, id: AnimationId) -> bool {
+        let count = self.animations.len();
+        self.animations
+            .retain(|(animation_id, _)| *animation_id != id);
+        self.animations.len() != count
     }
 
     /// Set the size of the map.
     pub fn set_size(&mut self, new_size: Size) {
-        self.view = self.view.with_size(new_size);
+        self.view = self.constraints.apply(&self.view.with_size(new_size));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{BlendMode, Canvas};
+    use std::any::Any;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, PartialEq)]
+    struct NamedLayer(&'static str);
+
+    impl Layer for NamedLayer {
+        fn render(&self, _view: &MapView, _canvas: &mut dyn Canvas) {}
+
+        fn prepare(&self, _view: &MapView) {}
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct CountingMessenger(Arc<AtomicUsize>);
+
+    impl Messenger for CountingMessenger {
+        fn request_redraw(&self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn names(map: &Map) -> Vec<&'static str> {
+        map.layers()
+            .iter()
+            .map(|layer| layer.as_any().downcast_ref::<NamedLayer>().unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn edit_layers() {
+        let messenger = CountingMessenger::default();
+        let mut map = Map::new(
+            MapView::new_projected(&galileo_types::cartesian::Point2d::new(0.0, 0.0), 1.0),
+            vec![Box::new(NamedLayer("A"))],
+            Some(messenger.clone()),
+        );
+
+        map.add_layer(NamedLayer("B"));
+        map.add_layer(NamedLayer("C"));
+        assert_eq!(names(&map), vec!["A", "B", "C"]);
+
+        map.move_layer(2, 0);
+        assert_eq!(names(&map), vec!["C", "A", "B"]);
+
+        let removed = map.remove_layer(1);
+        assert_eq!(removed.as_any().downcast_ref(), Some(&NamedLayer("A")));
+        assert_eq!(names(&map), vec!["C", "B"]);
+
+        assert_eq!(messenger.0.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn edit_layers_by_id() {
+        let messenger = CountingMessenger::default();
+        let mut map = Map::new(
+            MapView::new_projected(&galileo_types::cartesian::Point2d::new(0.0, 0.0), 1.0),
+            vec![Box::new(NamedLayer("A"))],
+            Some(messenger.clone()),
+        );
+
+        let c = map.add_layer(NamedLayer("C"));
+        let b = map.insert_layer(1, NamedLayer("B"));
+        assert_eq!(names(&map), vec!["A", "B", "C"]);
+
+        assert!(map.move_layer_by_id(c, 0));
+        assert_eq!(names(&map), vec!["C", "A", "B"]);
+        assert_eq!(map.layers().index_of(b), Some(2));
+
+        assert!(map.set_layer_visible(b, false));
+        assert!(!map.layers().is_visible(2));
+
+        let removed = map.remove_layer_by_id(c).unwrap();
+        assert_eq!(removed.as_any().downcast_ref(), Some(&NamedLayer("C")));
+        assert_eq!(names(&map), vec!["A", "B"]);
+        assert!(map.remove_layer_by_id(c).is_none());
+        assert!(!map.move_layer_by_id(c, 0));
+
+        assert_eq!(messenger.0.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn hidden_and_transparent_layers_are_not_rendered() {
+        let mut map = Map::new(
+            MapView::new_projected(&galileo_types::cartesian::Point2d::new(0.0, 0.0), 1.0),
+            vec![
+                Box::new(NamedLayer("A")),
+                Box::new(NamedLayer("B")),
+                Box::new(NamedLayer("C")),
+            ],
+            None::<CountingMessenger>,
+        );
+
+        map.layers_mut().set_visible(0, false);
+        map.layers_mut().set_opacity(1, 0.0);
+        map.layers_mut().set_opacity(2, 0.25);
+        map.layers_mut().set_blend_mode(2, BlendMode::Screen);
+
+        let mut rendered = vec![];
+        map.layers()
+            .for_each_rendered(|_, item, opacity, blend_mode| {
+                if let RenderedItem::Layer(layer) = item {
+                    rendered.push((
+                        layer.as_any().downcast_ref::<NamedLayer>().unwrap().0,
+                        opacity,
+                        blend_mode,
+                    ));
+                }
+            });
+        assert_eq!(rendered, vec![("C", 0.25, BlendMode::Screen)]);
+    }
+
+    #[test]
+    fn map_time_is_clamped_and_played() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let mut map = Map::new(
+            MapView::new_projected(&galileo_types::cartesian::Point2d::new(0.0, 0.0), 1.0),
+            vec![],
+            None::<CountingMessenger>,
+        );
+        assert_eq!(map.time(), None);
+
+        map.set_time_dimension(Some(
+            TimeDimension::new(start, start + Duration::from_secs(100))
+                .with_step(Duration::from_secs(10)),
+        ));
+        map.set_time(start + Duration::from_secs(35));
+        assert_eq!(map.time(), Some(start + Duration::from_secs(30)));
+
+        map.set_view(map.view().with_resolution(2.0).with_time(None));
+        assert_eq!(map.view().time(), Some(start + Duration::from_secs(30)));
+
+        map.play_time(1e9, false);
+        assert!(map.is_playing_time());
+        std::thread::sleep(Duration::from_millis(1));
+        map.animate();
+        assert_eq!(map.time(), Some(start + Duration::from_secs(100)));
+        assert!(!map.is_playing_time());
+
+        map.clear_time();
+        assert_eq!(map.time(), None);
+    }
+
+    #[test]
+    fn easing_curves() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert!(Easing::EaseIn.apply(0.25) < 0.25);
+        assert!(Easing::EaseOut.apply(0.25) > 0.25);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::EaseInOut.apply(0.25) < 0.25);
+    }
+
+    #[test]
+    fn animation_with_zero_duration_ends_immediately() {
+        let messenger = CountingMessenger::default();
+        let mut map = Map::new(
+            MapView::new_projected(&galileo_types::cartesian::Point2d::new(0.0, 0.0), 1.0),
+            vec![],
+            Some(messenger.clone()),
+        );
+        let target =
+            MapView::new_projected(&galileo_types::cartesian::Point2d::new(100.0, 0.0), 2.0);
+
+        map.fly_to(target.clone(), Duration::ZERO);
+        assert_eq!(map.target_view().resolution(), 2.0);
+        assert_eq!(messenger.0.load(Ordering::Relaxed), 1);
+
+        map.animate();
+        assert_eq!(map.view().resolution(), 2.0);
+        assert_eq!(map.view().get_bbox(), target.get_bbox());
+        assert_eq!(map.target_view().resolution(), 2.0);
+    }
+
+    #[test]
+    fn content_animations_run_until_finished() {
+        let messenger = CountingMessenger::default();
+        let mut map = Map::new(
+            MapView::new_projected(&galileo_types::cartesian::Point2d::new(0.0, 0.0), 1.0),
+            vec![],
+            Some(messenger.clone()),
+        );
+
+        let frames = Arc::new(AtomicUsize::new(0));
+        let counter = frames.clone();
+        map.add_animation(move |_| counter.fetch_add(1, Ordering::Relaxed) < 1);
+        let endless = map.add_animation(|_| true);
+        assert_eq!(messenger.0.load(Ordering::Relaxed), 2);
+
+        map.animate();
+        map.animate();
+        map.animate();
+        assert_eq!(frames.load(Ordering::Relaxed), 2);
+        assert_eq!(messenger.0.load(Ordering::Relaxed), 5);
+
+        assert!(map.remove_animation(endless));
+        assert!(!map.remove_animation(endless));
+        map.animate();
+        assert_eq!(messenger.0.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn view_changes_are_constrained() {
+        let mut map = Map::new(
+            MapView::new_projected(&galileo_types::cartesian::Point2d::new(0.0, 0.0), 1.0),
+            vec![],
+            None::<CountingMessenger>,
+        );
+
+        map.set_view_constraints(ViewConstraints::new().with_resolution_range(2.0, 8.0));
+        assert_eq!(map.view().resolution(), 2.0);
+
+        map.set_view(map.view().with_resolution(100.0));
+        assert_eq!(map.view().resolution(), 8.0);
+
+        map.animate_to(
+            map.view().with_resolution(0.1),
+            Duration::from_secs(10),
+            Easing::Linear,
+        );
+        assert_eq!(map.target_view().resolution(), 2.0);
+        map.animate();
+        assert!(map.view().resolution() <= 8.0 && map.view().resolution() >= 2.0);
     }
 }
@@ -0,0 +1,163 @@
+use crate::layer::ExtentIndicatorLayer;
+use crate::map::Map;
+use crate::symbol::SimplePolygonSymbol;
+use crate::view::MapView;
+use std::sync::{Arc, RwLock};
+
+/// Overview map that follows the view of a main map at a smaller scale and shows the footprint of the main view.
+///
+/// The overview is a separate [`Map`] with its own layers, usually a single light basemap, and is rendered by its own
+/// renderer, e.g. into a small widget in a corner of the application window. An [`ExtentIndicatorLayer`] is added on
+/// top of the layers of the overview map to show the area visible in the main map.
+///
+/// Call [`OverviewMap::sync`] every time the main map is drawn. The overview is then centered at the center of the
+/// main view with the resolution [`zoom_offset`](OverviewMap::zoom_offset) zoom levels lower (every level makes the
+/// resolution two times larger). The overview is always north-up and not tilted, the footprint shows the rotation of
+/// the main view instead. For the resolutions to match, both maps should use the same CRS.
+///
+/// ```
+/// use galileo::{Color, Map, MapView, OverviewMap};
+/// use galileo::symbol::SimplePolygonSymbol;
+/// use galileo_types::cartesian::Size;
+/// use galileo_types::latlon;
+///
+/// let view = MapView::new(&latlon!(52.0, 13.0), 10.0).with_size(Size::new(800.0, 600.0));
+/// let main_map = Map::new(view.clone(), vec![], None::<galileo::DummyMessenger>);
+///
+/// let overview_view = view.with_size(Size::new(200.0, 150.0));
+/// let mut overview = OverviewMap::new(
+///     Map::new(overview_view, vec![], None::<galileo::DummyMessenger>),
+///     4.0,
+///     SimplePolygonSymbol::new(Color::rgba(255, 0, 0, 50))
+///         .with_stroke_color(Color::RED)
+///         .with_stroke_width(1.0),
+/// );
+///
+/// overview.sync(&main_map);
+/// assert_eq!(overview.map().view().resolution(), 160.0);
+/// ```
+pub struct OverviewMap {
+    map: Map,
+    zoom_offset: f64,
+    indicator: Arc<RwLock<ExtentIndicatorLayer>>,
+}
+
+impl OverviewMap {
+    /// Creates a new overview from the `map`, adding the extent indicator layer drawn with the `indicator_symbol` on
+    /// top of its layers.
+    pub fn new(mut map: Map, zoom_offset: f64, indicator_symbol: SimplePolygonSymbol) -> Self {
+        let indicator = Arc::new(RwLock::new(ExtentIndicatorLayer::new(indicator_symbol)));
+        map.add_layer(indicator.clone());
+
+        Self {
+            map,
+            zoom_offset,
+            indicator,
+        }
+    }
+
+    /// The overview map.
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    /// Mutable reference to the overview map.
+    pub fn map_mut(&mut self) -> &mut Map {
+        &mut self.map
+    }
+
+    /// Number of zoom levels the overview is zoomed out relative to the main map.
+    pub fn zoom_offset(&self) -> f64 {
+        self.zoom_offset
+    }
+
+    /// Changes the zoom offset of the overview. The change is applied on the next [`OverviewMap::sync`].
+    pub fn set_zoom_offset(&mut self, zoom_offset: f64) {
+        self.zoom_offset = zoom_offset;
+    }
+
+    /// Layer showing the footprint of the main view.
+    pub fn indicator(&self) -> &Arc<RwLock<ExtentIndicatorLayer>> {
+        &self.indicator
+    }
+
+    /// Returns the view of the overview map for the `main_view`.
+    ///
+    /// Returns `None` if the center of the main view cannot be converted into geographic coordinates.
+    pub fn overview_view(&self, main_view: &MapView) -> Option<MapView> {
+        let current = self.map.view();
+        let position = main_view.position()?;
+        let resolution = main_view.resolution() * 2f64.powf(self.zoom_offset);
+
+        Some(
+            MapView::new_with_crs(&position, resolution, current.crs().clone())
+                .with_size(current.size())
+                .with_time(current.time()),
+        )
+    }
+
+    /// Updates the view of the overview map and the footprint indicator to follow the view of the `main` map. Layers
+    /// of the overview are loaded for the new view if it has changed.
+    pub fn sync(&mut self, main: &Map) {
+        self.indicator
+            .write()
+            .expect("lock is poisoned")
+            .set_view(main.view());
+
+        let Some(view) = self.overview_view(main.view()) else {
+            return;
+        };
+        let view = self.map.view_constraints().apply(&view);
+        if &view != self.map.view() {
+            self.map.set_view(view);
+            self.map.load_layers();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messenger::DummyMessenger;
+    use galileo_types::cartesian::{Point2d, Size};
+    use galileo_types::geo::GeoPoint;
+    use galileo_types::latlon;
+
+    #[test]
+    fn overview_follows_main_map() {
+        let view = MapView::new(&latlon!(10.0, 20.0), 100.0).with_size(Size::new(400.0, 300.0));
+        let mut main = Map::new(view.clone(), vec![], None::<DummyMessenger>);
+        let mut overview = OverviewMap::new(
+            Map::new(
+                view.with_size(Size::new(100.0, 100.0)),
+                vec![],
+                None::<DummyMessenger>,
+            ),
+            3.0,
+            SimplePolygonSymbol::new(crate::Color::RED),
+        );
+        assert_eq!(overview.map().layers().len(), 1);
+
+        main.set_view(view.translate_pixels(100.0, 0.0).with_rotation_z(0.5));
+        overview.sync(&main);
+
+        let overview_view = overview.map().view();
+        assert_eq!(overview_view.resolution(), 800.0);
+        assert_eq!(overview_view.size(), Size::new(100.0, 100.0));
+        assert_eq!(overview_view.rotation_z(), 0.0);
+        let center = overview_view.position().unwrap();
+        let main_center = main.view().position().unwrap();
+        assert!((center.lat() - main_center.lat()).abs() < 1e-9);
+        assert!((center.lon() - main_center.lon()).abs() < 1e-9);
+
+        let indicator = overview.indicator().read().unwrap();
+        let top_left = main
+            .view()
+            .screen_to_map_geo(Point2d::new(0.0, 0.0))
+            .unwrap();
+        let footprint = indicator.footprint();
+        assert_eq!(footprint.len(), 32);
+        assert!((footprint[0].lat() - top_left.lat()).abs() < 1e-9);
+        assert!((footprint[0].lon() - top_left.lon()).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,342 @@
+use crate::error::GalileoError;
+use crate::layer::vector_tile_layer::style::VectorTileStyle;
+use crate::layer::Layer;
+use crate::render::BlendMode;
+use crate::tile_scheme::TileIndex;
+use crate::view::MapView;
+use crate::{MapBuilder, TileSchema};
+use galileo_types::geo::impls::GeoPoint2d;
+use galileo_types::geo::{GeoPoint, NewGeoPoint};
+use std::collections::BTreeMap;
+
+/// State of a [`Map`](crate::Map) that can be saved and restored later: the view and the list of layers with their
+/// sources and display parameters. Obtained with [`Map::state`](crate::Map::state) and applied with
+/// [`Map::restore_state`](crate::Map::restore_state).
+///
+/// With `serde` feature the state can be (de)serialized, for example to save workspaces of an application:
+///
+/// ```json
+/// {
+///   "view": { "center": { "lat": 52.5, "lon": 13.4 }, "resolution": 19.1, "rotation": 0.0, "tilt": 0.0 },
+///   "layers": [
+///     {
+///       "source": { "type": "raster_tiles", "url": "https://tile.openstreetmap.org/{z}/{x}/{y}.png", "tile_schema": { ... } },
+///       "is_visible": true,
+///       "opacity": 1.0,
+///       "blend_mode": "Normal"
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapState {
+    /// View of the map.
+    pub view: ViewState,
+    /// Layers of the map in the order they are drawn.
+    pub layers: Vec<LayerState>,
+}
+
+/// Position of the view of a map, independent of the CRS and the size of the map.
+///
+/// For deep links the state can be written into a short string with [`ViewState::to_url_fragment`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViewState {
+    /// Geographic position of the center of the view.
+    pub center: GeoPoint2d,
+    /// Resolution at the center of the view, see [`MapView::resolution`].
+    pub resolution: f64,
+    /// Rotation of the map in radians, see [`MapView::rotation_z`].
+    pub rotation: f64,
+    /// Tilt of the map in radians, see [`MapView::rotation_x`].
+    pub tilt: f64,
+}
+
+impl ViewState {
+    /// State of the given view. Returns `None` if the center of the view cannot be projected into geographic
+    /// coordinates.
+    pub fn from_view(view: &MapView) -> Option<Self> {
+        Some(Self {
+            center: view.position()?,
+            resolution: view.resolution(),
+            rotation: view.rotation_z(),
+            tilt: view.rotation_x(),
+        })
+    }
+
+    /// Returns a copy of the `view` moved to this state. CRS, size and time of the view are not changed.
+    pub fn apply(&self, view: &MapView) -> MapView {
+        MapView::new_with_crs(&self.center, self.resolution, view.crs().clone())
+            .with_rotation(self.tilt, self.rotation)
+            .with_size(view.size())
+            .with_time(view.time())
+    }
+
+    /// Writes the state as `resolution/lat/lon`, followed by `/rotation/tilt` in degrees if the map is rotated or
+    /// tilted. The result can be used as the fragment of a URL to link to the view.
+    ///
+    /// ```
+    /// use galileo::ViewState;
+    /// use galileo_types::latlon;
+    ///
+    /// let state = ViewState { center: latlon!(52.5, 13.4), resolution: 19.1, rotation: 0.0, tilt: 0.0 };
+    /// let fragment = state.to_url_fragment();
+    /// assert_eq!(fragment, "19.1/52.5000000/13.4000000");
+    /// assert_eq!(ViewState::from_url_fragment(&format!("#{fragment}")).unwrap(), state);
+    /// ```
+    pub fn to_url_fragment(&self) -> String {
+        let mut fragment = format!(
+            "{}/{:.7}/{:.7}",
+            self.resolution,
+            self.center.lat(),
+            self.center.lon()
+        );
+        if self.rotation != 0.0 || self.tilt != 0.0 {
+            fragment.push_str(&format!(
+                "/{:.2}/{:.2}",
+                self.rotation.to_degrees(),
+                self.tilt.to_degrees()
+            ));
+        }
+
+        fragment
+    }
+
+    /// Reads the state written by [`ViewState::to_url_fragment`]. The leading `#` is ignored.
+    pub fn from_url_fragment(fragment: &str) -> Result<Self, GalileoError> {
+        let invalid = || GalileoError::Generic(format!("invalid view url fragment: {fragment}"));
+
+        let values = fragment
+            .trim_start_matches('#')
+            .split('/')
+            .map(|value| value.parse::<f64>().ok().filter(|value| value.is_finite()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+
+        let (resolution, lat, lon, rotation, tilt) = match values[..] {
+            [resolution, lat, lon] => (resolution, lat, lon, 0.0, 0.0),
+            [resolution, lat, lon, rotation, tilt] => (resolution, lat, lon, rotation, tilt),
+            _ => return Err(invalid()),
+        };
+        if resolution <= 0.0 || !(-90.0..=90.0).contains(&lat) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            center: GeoPoint2d::latlon(lat, lon),
+            resolution,
+            rotation: rotation.to_radians(),
+            tilt: tilt.to_radians(),
+        })
+    }
+}
+
+/// State of a layer of a map.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerState {
+    /// Source the layer was created from, see [`LayerCollection::set_source`](crate::LayerCollection::set_source).
+    /// Layers without a source cannot be restored.
+    pub source: Option<LayerSource>,
+    /// Whether the layer is visible.
+    pub is_visible: bool,
+    /// Opacity of the layer.
+    pub opacity: f32,
+    /// Blend mode of the layer.
+    pub blend_mode: BlendMode,
+}
+
+/// Configuration a layer is created from.
+///
+/// A layer can be created from the built-in sources with [`LayerSource::build_layer`]. Other layers of the
+/// application can be described by a [`LayerSource::Custom`] source with the parameters the application needs to
+/// create them again.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum LayerSource {
+    /// Raster tiles loaded from a URL template with `{z}`, `{x}` and `{y}` placeholders.
+    RasterTiles {
+        /// URL template of the tiles.
+        url: String,
+        /// Tile schema of the tiles.
+        tile_schema: TileSchema,
+    },
+    /// Vector tiles loaded from a URL template with `{z}`, `{x}` and `{y}` placeholders.
+    VectorTiles {
+        /// URL template of the tiles.
+        url: String,
+        /// Tile schema of the tiles.
+        tile_schema: TileSchema,
+        /// Style the tiles are drawn with.
+        style: VectorTileStyle,
+    },
+    /// Layer created by the application.
+    Custom {
+        /// Name identifying the kind of the layer.
+        name: String,
+        /// Parameters of the layer.
+        #[cfg_attr(feature = "serde", serde(default))]
+        params: BTreeMap<String, String>,
+    },
+}
+
+impl LayerSource {
+    /// Creates a layer from a built-in source. Returns `None` for [`LayerSource::Custom`] sources.
+    pub fn build_layer(&self) -> Option<Box<dyn Layer>> {
+        match self {
+            Self::RasterTiles { url, tile_schema } => {
+                let url = url.clone();
+                Some(Box::new(MapBuilder::create_raster_tile_layer(
+                    move |index: &TileIndex| tile_url(&url, index),
+                    tile_schema.clone(),
+                )))
+            }
+            Self::VectorTiles {
+                url,
+                tile_schema,
+                style,
+            } => {
+                let url = url.clone();
+                Some(Box::new(MapBuilder::create_vector_tile_layer(
+                    move |index: &TileIndex| tile_url(&url, index),
+                    tile_schema.clone(),
+                    style.clone(),
+                )))
+            }
+            Self::Custom { .. } => None,
+        }
+    }
+}
+
+fn tile_url(template: &str, index: &TileIndex) -> String {
+    template
+        .replace("{z}", &index.z.to_string())
+        .replace("{x}", &index.x.to_string())
+        .replace("{y}", &index.y.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::TestLayer;
+    use crate::map::Map;
+    use crate::DummyMessenger;
+    use galileo_types::latlon;
+
+    #[test]
+    fn url_fragment_round_trip() {
+        let state = ViewState {
+            center: latlon!(-33.8688, 151.2093),
+            resolution: 2.5,
+            rotation: 30f64.to_radians(),
+            tilt: 45f64.to_radians(),
+        };
+
+        let fragment = state.to_url_fragment();
+        assert_eq!(fragment, "2.5/-33.8688000/151.2093000/30.00/45.00");
+
+        let restored = ViewState::from_url_fragment(&fragment).unwrap();
+        assert_eq!(restored.center, state.center);
+        assert_eq!(restored.resolution, state.resolution);
+        assert!((restored.rotation - state.rotation).abs() < 1e-9);
+        assert!((restored.tilt - state.tilt).abs() < 1e-9);
+
+        for invalid in [
+            "", "1/2", "1/2/3/4", "0/10/10", "1/91/10", "1/a/10", "1/NaN/10",
+        ] {
+            assert!(ViewState::from_url_fragment(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn view_state_keeps_crs_and_size() {
+        let view = MapView::new(&latlon!(10.0, 20.0), 100.0)
+            .with_rotation(0.5, 1.0)
+            .with_size(galileo_types::cartesian::Size::new(300.0, 200.0));
+        let state = ViewState::from_view(&view).unwrap();
+        assert!((state.center.lat() - 10.0).abs() < 1e-9);
+        assert!((state.center.lon() - 20.0).abs() < 1e-9);
+
+        let other = MapView::new(&latlon!(0.0, 0.0), 1.0)
+            .with_size(galileo_types::cartesian::Size::new(300.0, 200.0));
+        let restored = state.apply(&other);
+        assert_eq!(restored.resolution(), 100.0);
+        assert_eq!(restored.rotation_x(), 0.5);
+        assert_eq!(restored.rotation_z(), 1.0);
+        assert_eq!(restored.size(), other.size());
+        assert_eq!(restored.crs(), other.crs());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn map_state_restores_layers() {
+        let mut map = Map::new(
+            MapView::new(&latlon!(10.0, 20.0), 100.0),
+            vec![],
+            None::<DummyMessenger>,
+        );
+        map.add_layer(TestLayer("Basemap"));
+        map.layers_mut().set_source(
+            0,
+            Some(LayerSource::Custom {
+                name: "basemap".into(),
+                params: BTreeMap::new(),
+            }),
+        );
+        map.add_layer(TestLayer("Overlay"));
+        map.layers_mut().set_source(
+            1,
+            Some(LayerSource::Custom {
+                name: "overlay".into(),
+                params: [("year".to_string(), "2020".to_string())].into(),
+            }),
+        );
+        map.layers_mut().set_opacity(1, 0.5);
+        map.layers_mut().set_blend_mode(1, BlendMode::Multiply);
+        map.layers_mut().hide(1);
+        map.add_layer(TestLayer("Not persisted"));
+
+        let json = serde_json::to_string(&map.state().unwrap()).unwrap();
+        let state: MapState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.layers.len(), 3);
+
+        let mut restored = Map::new(
+            MapView::new(&latlon!(0.0, 0.0), 1.0),
+            vec![Box::new(TestLayer("Old"))],
+            None::<DummyMessenger>,
+        );
+        restored.restore_state(&state, |source| match source {
+            LayerSource::Custom { name, params } if name == "basemap" => {
+                assert!(params.is_empty());
+                Some(Box::new(TestLayer("Basemap")))
+            }
+            LayerSource::Custom { name, params } if name == "overlay" => {
+                assert_eq!(params["year"], "2020");
+                Some(Box::new(TestLayer("Overlay")))
+            }
+            _ => None,
+        });
+
+        let layers = restored.layers();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(
+            layers[0].as_any().downcast_ref(),
+            Some(&TestLayer("Basemap"))
+        );
+        assert_eq!(
+            layers[1].as_any().downcast_ref(),
+            Some(&TestLayer("Overlay"))
+        );
+        assert!(layers.is_visible(0));
+        assert!(!layers.is_visible(1));
+        assert_eq!(layers.opacity(1), 0.5);
+        assert_eq!(layers.blend_mode(1), BlendMode::Multiply);
+        assert!(
+            matches!(layers.source(1), Some(LayerSource::Custom { name, .. }) if name == "overlay")
+        );
+        assert_eq!(restored.view().resolution(), 100.0);
+        assert!((restored.view().position().unwrap().lon() - 20.0).abs() < 1e-9);
+    }
+}
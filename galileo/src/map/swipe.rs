@@ -0,0 +1,303 @@
+use crate::map::{LayerId, Map};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::Rect;
+
+/// Swipe comparison mode of a map, set with [`Map::set_swipe`].
+///
+/// In the swipe mode the screen is split by a divider line into two parts. The *leading* layers are drawn only
+/// before the divider (to the left of a vertical divider or above a horizontal one), and the *trailing* layers only
+/// after it. All other layers are drawn over the whole screen as usual, so for example two imagery layers can be
+/// compared over a common basemap. The layers are still drawn in the order of the layer collection.
+///
+/// The divider is drawn by the renderer as a line of the [divider color](Swipe::with_divider). It can be moved by
+/// the user with the [`SwipeController`](crate::control::SwipeController).
+///
+/// ```
+/// use galileo::{Map, MapView, Swipe};
+/// use galileo::layer::TestLayer;
+/// use galileo_types::latlon;
+///
+/// let mut map = Map::new(MapView::new(&latlon!(0.0, 0.0), 1.0), vec![], None::<galileo::DummyMessenger>);
+/// let before = map.add_layer(TestLayer("Imagery 2010"));
+/// let after = map.add_layer(TestLayer("Imagery 2020"));
+///
+/// map.set_swipe(Some(Swipe::new(vec![before], vec![after]).with_position(0.3)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swipe {
+    leading: Vec<LayerId>,
+    trailing: Vec<LayerId>,
+    orientation: SwipeOrientation,
+    position: f64,
+    divider_color: Color,
+    divider_width: f64,
+}
+
+/// Direction of the divider of a [`Swipe`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SwipeOrientation {
+    /// The divider is a vertical line, leading layers are drawn to the left of it.
+    #[default]
+    Vertical,
+    /// The divider is a horizontal line, leading layers are drawn above it.
+    Horizontal,
+}
+
+/// Part of the screen a layer is drawn to in the swipe mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwipeSide {
+    /// Before the divider.
+    Leading,
+    /// After the divider.
+    Trailing,
+}
+
+impl Swipe {
+    /// Creates a new swipe with the divider in the middle of the screen.
+    pub fn new(leading: Vec<LayerId>, trailing: Vec<LayerId>) -> Self {
+        Self {
+            leading,
+            trailing,
+            orientation: SwipeOrientation::Vertical,
+            position: 0.5,
+            divider_color: Color::WHITE,
+            divider_width: 2.0,
+        }
+    }
+
+    /// Sets the orientation of the divider.
+    pub fn with_orientation(mut self, orientation: SwipeOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Sets the position of the divider. See [`Swipe::set_position`].
+    pub fn with_position(mut self, position: f64) -> Self {
+        self.set_position(position);
+        self
+    }
+
+    /// Sets the color and the width in pixels of the divider line. Default is a white line 2 pixels wide, zero width
+    /// hides the line.
+    pub fn with_divider(mut self, color: Color, width: f64) -> Self {
+        self.divider_color = color;
+        self.divider_width = width;
+        self
+    }
+
+    /// Layers drawn before the divider.
+    pub fn leading(&self) -> &[LayerId] {
+        &self.leading
+    }
+
+    /// Layers drawn after the divider.
+    pub fn trailing(&self) -> &[LayerId] {
+        &self.trailing
+    }
+
+    /// Orientation of the divider.
+    pub fn orientation(&self) -> SwipeOrientation {
+        self.orientation
+    }
+
+    /// Position of the divider as a share of the screen width (or height for a horizontal divider), from `0.0` to
+    /// `1.0`.
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    /// Moves the divider. The `position` is a share of the screen width (or height for a horizontal divider) and is
+    /// clamped into the `[0.0, 1.0]` range.
+    pub fn set_position(&mut self, position: f64) {
+        self.position = position.clamp(0.0, 1.0);
+    }
+
+    /// Color of the divider line.
+    pub fn divider_color(&self) -> Color {
+        self.divider_color
+    }
+
+    /// Width of the divider line in pixels.
+    pub fn divider_width(&self) -> f64 {
+        self.divider_width
+    }
+
+    /// Returns the side of the screen the layer is drawn to, or `None` if it is drawn over the whole screen.
+    pub fn side(&self, layer: LayerId) -> Option<SwipeSide> {
+        if self.leading.contains(&layer) {
+            Some(SwipeSide::Leading)
+        } else if self.trailing.contains(&layer) {
+            Some(SwipeSide::Trailing)
+        } else {
+            None
+        }
+    }
+
+    /// Screen coordinate of the divider in pixels for the `view`: `x` for a vertical divider, `y` for a horizontal
+    /// one.
+    pub fn divider_offset(&self, view: &MapView) -> f64 {
+        let size = view.size();
+        match self.orientation {
+            SwipeOrientation::Vertical => size.width() * self.position,
+            SwipeOrientation::Horizontal => size.height() * self.position,
+        }
+    }
+
+    /// Pixel rectangle of the screen of the `view` that the layers of the `side` are drawn to.
+    pub fn side_rect(&self, view: &MapView, side: SwipeSide) -> Rect {
+        let size = view.size();
+        let offset = self.divider_offset(view);
+        match (self.orientation, side) {
+            (SwipeOrientation::Vertical, SwipeSide::Leading) => {
+                Rect::new(0.0, 0.0, offset, size.height())
+            }
+            (SwipeOrientation::Vertical, SwipeSide::Trailing) => {
+                Rect::new(offset, 0.0, size.width(), size.height())
+            }
+            (SwipeOrientation::Horizontal, SwipeSide::Leading) => {
+                Rect::new(0.0, 0.0, size.width(), offset)
+            }
+            (SwipeOrientation::Horizontal, SwipeSide::Trailing) => {
+                Rect::new(0.0, offset, size.width(), size.height())
+            }
+        }
+    }
+
+    /// Pixel rectangle of the divider line on the screen of the `view`.
+    pub fn divider_rect(&self, view: &MapView) -> Rect {
+        let size = view.size();
+        let offset = self.divider_offset(view);
+        let half_width = self.divider_width / 2.0;
+        match self.orientation {
+            SwipeOrientation::Vertical => {
+                Rect::new(offset - half_width, 0.0, offset + half_width, size.height())
+            }
+            SwipeOrientation::Horizontal => {
+                Rect::new(0.0, offset - half_width, size.width(), offset + half_width)
+            }
+        }
+    }
+}
+
+/// Link between the views of two maps, e.g. for a side-by-side comparison of two maps.
+///
+/// The link does not own the maps. Call [`ViewLink::sync`] with both maps every frame (or after every change of
+/// the views), and the view of the map that changed since the last call is copied to the other map. Each map keeps
+/// its own size, so the maps can be of different sizes, and its own [view constraints](Map::set_view_constraints).
+///
+/// ```
+/// use galileo::{Map, MapView, ViewLink};
+/// use galileo_types::latlon;
+///
+/// let view = MapView::new(&latlon!(0.0, 0.0), 100.0);
+/// let mut left = Map::new(view.clone(), vec![], None::<galileo::DummyMessenger>);
+/// let mut right = Map::new(view.clone(), vec![], None::<galileo::DummyMessenger>);
+/// let mut link = ViewLink::default();
+/// link.sync(&mut left, &mut right);
+///
+/// right.set_view(view.with_resolution(10.0));
+/// link.sync(&mut left, &mut right);
+/// assert_eq!(left.view().resolution(), 10.0);
+/// ```
+#[derive(Debug, Default)]
+pub struct ViewLink {
+    last_views: Option<(MapView, MapView)>,
+}
+
+impl ViewLink {
+    /// Creates a new link.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copies the view of the map that changed since the last call to the other map. If both maps changed, or the
+    /// method is called for the first time, the view of the `first` map is used.
+    ///
+    /// Returns `true` if the view of one of the maps was changed.
+    pub fn sync(&mut self, first: &mut Map, second: &mut Map) -> bool {
+        let is_second_changed = match &self.last_views {
+            Some((first_view, second_view)) => {
+                first_view == first.view() && second_view != second.view()
+            }
+            None => false,
+        };
+        let (source, target) = if is_second_changed {
+            (&*second, &mut *first)
+        } else {
+            (&*first, &mut *second)
+        };
+
+        let view = source.view().with_size(target.view().size());
+        let is_changed = &view != target.view();
+        if is_changed {
+            target.set_view(view);
+        }
+
+        self.last_views = Some((first.view().clone(), second.view().clone()));
+        is_changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messenger::DummyMessenger;
+    use galileo_types::cartesian::{Point2d, Size};
+
+    fn map(size: Size) -> Map {
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0).with_size(size);
+        Map::new(view, vec![], None::<DummyMessenger>)
+    }
+
+    #[test]
+    fn view_link_copies_changed_view() {
+        let mut first = map(Size::new(100.0, 100.0));
+        let mut second = map(Size::new(50.0, 80.0));
+        let mut link = ViewLink::new();
+        assert!(!link.sync(&mut first, &mut second));
+
+        first.set_view(first.view().with_resolution(2.0));
+        assert!(link.sync(&mut first, &mut second));
+        assert_eq!(second.view().resolution(), 2.0);
+        assert_eq!(second.view().size(), Size::new(50.0, 80.0));
+
+        second.set_view(second.view().with_rotation_z(0.5));
+        assert!(link.sync(&mut first, &mut second));
+        assert_eq!(first.view().rotation_z(), 0.5);
+        assert_eq!(first.view().size(), Size::new(100.0, 100.0));
+        assert!(!link.sync(&mut first, &mut second));
+    }
+
+    #[test]
+    fn swipe_splits_screen() {
+        let view = map(Size::new(200.0, 100.0)).view().clone();
+        let mut map = map(Size::new(200.0, 100.0));
+        let leading = map.add_layer(crate::layer::TestLayer("A"));
+        let trailing = map.add_layer(crate::layer::TestLayer("B"));
+        let other = map.add_layer(crate::layer::TestLayer("C"));
+
+        let mut swipe = Swipe::new(vec![leading], vec![trailing]).with_position(0.25);
+        assert_eq!(swipe.side(leading), Some(SwipeSide::Leading));
+        assert_eq!(swipe.side(trailing), Some(SwipeSide::Trailing));
+        assert_eq!(swipe.side(other), None);
+
+        assert_eq!(
+            swipe.side_rect(&view, SwipeSide::Leading),
+            Rect::new(0.0, 0.0, 50.0, 100.0)
+        );
+        assert_eq!(
+            swipe.side_rect(&view, SwipeSide::Trailing),
+            Rect::new(50.0, 0.0, 200.0, 100.0)
+        );
+        assert_eq!(swipe.divider_rect(&view), Rect::new(49.0, 0.0, 51.0, 100.0));
+
+        swipe.set_position(2.0);
+        assert_eq!(swipe.position(), 1.0);
+        let swipe = swipe.with_orientation(SwipeOrientation::Horizontal);
+        assert_eq!(
+            swipe.side_rect(&view, SwipeSide::Leading),
+            Rect::new(0.0, 0.0, 200.0, 100.0)
+        );
+    }
+}
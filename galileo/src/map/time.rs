@@ -0,0 +1,168 @@
+use web_time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time range that the time of a [`Map`](super::Map) can take, e.g. the period covered by a weather forecast.
+///
+/// If the dimension has a step, the time of the map is snapped to the steps counted from the start of the range.
+/// This is useful for the data sources that only have data for discrete moments of time.
+///
+/// ```
+/// use galileo::TimeDimension;
+/// use web_time::{Duration, SystemTime};
+///
+/// let start = SystemTime::UNIX_EPOCH;
+/// let dimension = TimeDimension::new(start, start + Duration::from_secs(3600))
+///     .with_step(Duration::from_secs(600));
+///
+/// assert_eq!(dimension.clamp(start + Duration::from_secs(1000)), start + Duration::from_secs(600));
+/// assert_eq!(dimension.clamp(start + Duration::from_secs(7200)), start + Duration::from_secs(3600));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeDimension {
+    start: SystemTime,
+    end: SystemTime,
+    step: Option<Duration>,
+}
+
+impl TimeDimension {
+    /// Creates a new continuous dimension between `start` and `end` (inclusive).
+    pub fn new(start: SystemTime, end: SystemTime) -> Self {
+        let (start, end) = if end < start {
+            (end, start)
+        } else {
+            (start, end)
+        };
+
+        Self {
+            start,
+            end,
+            step: None,
+        }
+    }
+
+    /// Sets the step of the dimension. Zero step makes the dimension continuous.
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = (!step.is_zero()).then_some(step);
+        self
+    }
+
+    /// Start of the range.
+    pub fn start(&self) -> SystemTime {
+        self.start
+    }
+
+    /// End of the range.
+    pub fn end(&self) -> SystemTime {
+        self.end
+    }
+
+    /// Step of the dimension, or `None` if the dimension is continuous.
+    pub fn step(&self) -> Option<Duration> {
+        self.step
+    }
+
+    /// Length of the range.
+    pub fn length(&self) -> Duration {
+        self.end.duration_since(self.start).unwrap_or_default()
+    }
+
+    /// Returns the closest value of the dimension not later than `time`. Values outside the range are clamped to its
+    /// start or end.
+    pub fn clamp(&self, time: SystemTime) -> SystemTime {
+        if time <= self.start {
+            return self.start;
+        }
+        if time >= self.end {
+            return self.end;
+        }
+
+        let passed = time.duration_since(self.start).unwrap_or_default();
+        match self.step {
+            Some(step) => {
+                let steps = passed.as_nanos() / step.as_nanos();
+                self.start + Duration::from_nanos((step.as_nanos() * steps) as u64)
+            }
+            None => time,
+        }
+    }
+}
+
+/// Shifts the `time` by the given number of seconds, that can be negative.
+pub(crate) fn shift_time(time: SystemTime, seconds: f64) -> SystemTime {
+    if seconds >= 0.0 {
+        time + Duration::from_secs_f64(seconds)
+    } else {
+        time - Duration::from_secs_f64(-seconds)
+    }
+}
+
+/// Number of seconds from `from` to `to`, negative if `to` is earlier.
+pub(crate) fn seconds_between(from: SystemTime, to: SystemTime) -> f64 {
+    match to.duration_since(from) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(err) => -err.duration().as_secs_f64(),
+    }
+}
+
+/// Formats the time in ISO 8601 format in UTC, e.g. `2024-05-17T12:30:00Z`. Milliseconds are added only if they are
+/// not zero.
+pub(crate) fn format_iso8601(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (days, seconds_of_day) = (seconds / 86400, seconds % 86400);
+
+    // Conversion of days since epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let (hour, minute, second) = (
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60,
+    );
+    let millis = since_epoch.subsec_millis();
+    if millis == 0 {
+        format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+    } else {
+        format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.{millis:03}Z")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso8601_formatting() {
+        assert_eq!(format_iso8601(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_iso8601(UNIX_EPOCH + Duration::from_secs(1_715_949_000)),
+            "2024-05-17T12:30:00Z"
+        );
+        assert_eq!(
+            format_iso8601(UNIX_EPOCH + Duration::from_millis(951_782_400_250)),
+            "2000-02-29T00:00:00.250Z"
+        );
+    }
+
+    #[test]
+    fn continuous_dimension_clamps_to_range() {
+        let start = UNIX_EPOCH + Duration::from_secs(100);
+        let dimension = TimeDimension::new(start + Duration::from_secs(50), start);
+        assert_eq!(dimension.start(), start);
+        assert_eq!(dimension.length(), Duration::from_secs(50));
+        assert_eq!(dimension.clamp(UNIX_EPOCH), start);
+        assert_eq!(
+            dimension.clamp(start + Duration::from_secs(7)),
+            start + Duration::from_secs(7)
+        );
+        assert_eq!(seconds_between(start, shift_time(start, -2.5)), -2.5);
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 /// Messenger used to notifiy application when the map requires update.
 pub trait Messenger: Send + Sync {
     /// Notifies the application that the map requires an update.
@@ -11,3 +13,9 @@ impl Messenger for DummyMessenger {
         // do nothing
     }
 }
+
+impl<T: Messenger + ?Sized> Messenger for Arc<T> {
+    fn request_redraw(&self) {
+        (**self).request_redraw()
+    }
+}
@@ -0,0 +1,598 @@
+//! Overlays are widgets drawn by the renderers over all the layers of the map, positioned in screen space.
+//!
+//! Add overlays to a map with [`Map::add_overlay`]. Two overlays are provided:
+//! * [`ScaleBar`] - a metric or imperial scale bar calculated from the resolution of the view at its position.
+//! * [`AttributionOverlay`] - a line with the attributions of the visible layers of the map.
+
+use crate::map::Map;
+use crate::render::point_paint::PointPaint;
+use crate::render::render_bundle::{RenderBundle, RenderPrimitive};
+use crate::render::text::{TextAnchor, TextStyle};
+use crate::render::{Canvas, LineCap, LineJoin, LinePaint, RenderOptions};
+use crate::view::MapView;
+use galileo_types::cartesian::{Point2d, Point3d, Size};
+use galileo_types::geo::{haversine_distance, EARTH_MEAN_RADIUS};
+use galileo_types::impls::{Contour, Polygon};
+use maybe_sync::{MaybeSend, MaybeSync};
+use std::fmt::{Debug, Formatter};
+use std::sync::Mutex;
+
+const METERS_IN_FOOT: f64 = 0.3048;
+const FEET_IN_MILE: f64 = 5280.0;
+
+/// Widget drawn over the map layers.
+///
+/// The canvas an overlay is drawn to uses screen coordinates: the point `(x, -y)` is drawn at `x` pixels from the left
+/// edge and `y` pixels from the top edge of the screen, with the resolution of `1`. The canvas is neither tilted nor
+/// rotated, so the overlays look the same for any view and can be drawn over the parts of the screen that do not show
+/// the map surface (e.g. the sky of a strongly tilted view).
+pub trait Overlay: MaybeSend + MaybeSync {
+    /// Renders the overlay for the `view` of the `map` to the canvas.
+    fn render(&self, map: &Map, view: &MapView, canvas: &mut dyn Canvas);
+}
+
+/// View of the same size as the `view`, with which the renderers draw the overlays. See [`Overlay`] for the
+/// coordinates of the view.
+pub(crate) fn screen_view(view: &MapView) -> MapView {
+    let size = view.size();
+    MapView::new_projected(&Point2d::new(size.half_width(), -size.half_height()), 1.0)
+        .with_size(size)
+}
+
+/// Corner of the screen an overlay is placed at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverlayPosition {
+    /// Top left corner.
+    TopLeft,
+    /// Top right corner.
+    TopRight,
+    /// Bottom left corner.
+    BottomLeft,
+    /// Bottom right corner.
+    BottomRight,
+}
+
+impl OverlayPosition {
+    fn is_left(&self) -> bool {
+        matches!(self, Self::TopLeft | Self::BottomLeft)
+    }
+
+    fn is_top(&self) -> bool {
+        matches!(self, Self::TopLeft | Self::TopRight)
+    }
+
+    fn text_anchor(&self) -> TextAnchor {
+        match self {
+            Self::TopLeft => TextAnchor::TopLeft,
+            Self::TopRight => TextAnchor::TopRight,
+            Self::BottomLeft => TextAnchor::BottomLeft,
+            Self::BottomRight => TextAnchor::BottomRight,
+        }
+    }
+}
+
+/// Units of a [`ScaleBar`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ScaleBarUnits {
+    /// Meters and kilometers.
+    #[default]
+    Metric,
+    /// Feet and miles.
+    Imperial,
+}
+
+/// Length of a [`ScaleBar`] measured for a view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleBarLength {
+    /// Width of the bar in pixels.
+    pub width: f64,
+    /// Label of the bar, e.g. `200 m` or `5 mi`.
+    pub label: String,
+}
+
+/// Overlay showing the length of a round distance on the map.
+///
+/// The distance is measured along the bar at its position on the screen, so it takes into account both the resolution
+/// of the view and the latitude (e.g. the scale distortion of Web Mercator). The bar is as long as the largest round
+/// distance (`1`, `2` or `5` multiplied by a power of ten) that fits into [`ScaleBar::with_max_width`] pixels.
+///
+/// ```no_run
+/// use galileo::overlay::{OverlayPosition, ScaleBar, ScaleBarUnits};
+/// use galileo::render::text::{Font, TextStyle};
+/// # let mut map = galileo::Map::new(galileo::MapView::new(&galileo_types::latlon!(0.0, 0.0), 1.0), vec![], None::<galileo::DummyMessenger>);
+///
+/// let font = Font::from_path("examples/data/fonts/Hack-Regular.ttf").expect("failed to load font");
+/// map.add_overlay(
+///     ScaleBar::new(TextStyle::new(font, 12.0))
+///         .with_units(ScaleBarUnits::Imperial)
+///         .with_position(OverlayPosition::TopLeft),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleBar {
+    style: TextStyle,
+    units: ScaleBarUnits,
+    position: OverlayPosition,
+    margin: f64,
+    max_width: f64,
+    line_width: f64,
+    bundle: CachedBundle<(ScaleBarLength, Size)>,
+}
+
+impl ScaleBar {
+    /// Creates a new metric scale bar at the bottom left corner of the screen with the label drawn with the given
+    /// style. The bar is drawn with the color of the text.
+    pub fn new(style: TextStyle) -> Self {
+        Self {
+            style,
+            units: ScaleBarUnits::Metric,
+            position: OverlayPosition::BottomLeft,
+            margin: 10.0,
+            max_width: 100.0,
+            line_width: 2.0,
+            bundle: CachedBundle::default(),
+        }
+    }
+
+    /// Sets the units of the bar.
+    pub fn with_units(mut self, units: ScaleBarUnits) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Sets the corner of the screen the bar is placed at.
+    pub fn with_position(mut self, position: OverlayPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets the distance between the bar and the edges of the screen in pixels. Default is `10`.
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets the maximum width of the bar in pixels. Default is `100`.
+    pub fn with_max_width(mut self, max_width: f64) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Sets the width of the bar line in pixels. Default is `2`.
+    pub fn with_line_width(mut self, line_width: f64) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Measures the bar for the `view`.
+    ///
+    /// If the position of the bar is not on the map surface (e.g. it is over the sky of a tilted view), the distance is
+    /// measured at the center of the screen instead. Returns `None` if neither position can be converted into
+    /// geographic coordinates.
+    pub fn measure(&self, view: &MapView) -> Option<ScaleBarLength> {
+        let (left, y) = self.origin(view);
+        let size = view.size();
+        let meters_per_pixel = self.meters_per_pixel(view, left, y).or_else(|| {
+            self.meters_per_pixel(
+                view,
+                size.half_width() - self.max_width / 2.0,
+                size.half_height(),
+            )
+        })?;
+
+        let (unit_length, unit) = match self.units {
+            ScaleBarUnits::Metric if self.max_width * meters_per_pixel >= 1000.0 => (1000.0, "km"),
+            ScaleBarUnits::Metric => (1.0, "m"),
+            ScaleBarUnits::Imperial
+                if self.max_width * meters_per_pixel >= FEET_IN_MILE * METERS_IN_FOOT =>
+            {
+                (FEET_IN_MILE * METERS_IN_FOOT, "mi")
+            }
+            ScaleBarUnits::Imperial => (METERS_IN_FOOT, "ft"),
+        };
+
+        let units_per_pixel = meters_per_pixel / unit_length;
+        let length = round_down(self.max_width * units_per_pixel);
+        Some(ScaleBarLength {
+            width: length / units_per_pixel,
+            label: format!("{length} {unit}"),
+        })
+    }
+
+    /// Ground distance covered by a pixel of the bar of the maximum width starting at the screen point `(left, y)`.
+    fn meters_per_pixel(&self, view: &MapView, left: f64, y: f64) -> Option<f64> {
+        let from = view.screen_to_map_geo(Point2d::new(left, y))?;
+        let to = view.screen_to_map_geo(Point2d::new(left + self.max_width, y))?;
+        let meters_per_pixel = haversine_distance(&from, &to, EARTH_MEAN_RADIUS) / self.max_width;
+        meters_per_pixel.is_normal().then_some(meters_per_pixel)
+    }
+
+    /// Screen position of the left end of the bar line when the bar has the maximum width.
+    fn origin(&self, view: &MapView) -> (f64, f64) {
+        let size = view.size();
+        let x = if self.position.is_left() {
+            self.margin
+        } else {
+            size.width() - self.margin - self.max_width
+        };
+        let y = if self.position.is_top() {
+            self.margin + self.style.font_size as f64 + self.line_width * 2.0
+        } else {
+            size.height() - self.margin - self.line_width / 2.0
+        };
+
+        (x, y)
+    }
+}
+
+impl Overlay for ScaleBar {
+    fn render(&self, _map: &Map, view: &MapView, canvas: &mut dyn Canvas) {
+        let Some(length) = self.measure(view) else {
+            return;
+        };
+
+        let (origin_x, y) = self.origin(view);
+        let left = if self.position.is_left() {
+            origin_x
+        } else {
+            origin_x + self.max_width - length.width
+        };
+        let right = left + length.width;
+        let tick = self.style.font_size as f64 / 2.0;
+
+        self.bundle
+            .draw((length.clone(), view.size()), canvas, |bundle| {
+                let points = [(left, y - tick), (left, y), (right, y), (right, y - tick)]
+                    .into_iter()
+                    .map(|(x, y)| screen_position(x, y))
+                    .collect();
+                add_primitive(
+                    bundle,
+                    RenderPrimitive::new_contour(
+                        Contour::open(points),
+                        LinePaint {
+                            color: self.style.color,
+                            width: self.line_width,
+                            offset: 0.0,
+                            line_cap: LineCap::Butt,
+                            line_join: LineJoin::Miter,
+                            dash: None,
+                            arrows: None,
+                        },
+                    ),
+                );
+                let style = overlay_text_style(&self.style, TextAnchor::Bottom);
+                add_primitive(
+                    bundle,
+                    RenderPrimitive::new_point(
+                        screen_position((left + right) / 2.0, y - self.line_width),
+                        PointPaint::label(&length.label, &style),
+                    ),
+                );
+            });
+    }
+}
+
+/// Overlay showing the [attributions](crate::LayerCollection::attributions) of the visible layers of the map in one
+/// line.
+///
+/// ```no_run
+/// use galileo::overlay::AttributionOverlay;
+/// use galileo::render::text::{Font, TextStyle};
+/// use galileo::Color;
+/// # let mut map = galileo::Map::new(galileo::MapView::new(&galileo_types::latlon!(0.0, 0.0), 1.0), vec![], None::<galileo::DummyMessenger>);
+///
+/// let font = Font::from_path("examples/data/fonts/Hack-Regular.ttf").expect("failed to load font");
+/// map.add_overlay(AttributionOverlay::new(
+///     TextStyle::new(font, 11.0).with_halo(Color::WHITE, 2.0),
+/// ));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributionOverlay {
+    style: TextStyle,
+    position: OverlayPosition,
+    margin: f64,
+    separator: String,
+    bundle: CachedBundle<(String, Size)>,
+}
+
+impl AttributionOverlay {
+    /// Creates a new overlay at the bottom right corner of the screen with the text drawn with the given style.
+    pub fn new(style: TextStyle) -> Self {
+        Self {
+            style,
+            position: OverlayPosition::BottomRight,
+            margin: 4.0,
+            separator: " | ".to_string(),
+            bundle: CachedBundle::default(),
+        }
+    }
+
+    /// Sets the corner of the screen the text is placed at.
+    pub fn with_position(mut self, position: OverlayPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Sets the distance between the text and the edges of the screen in pixels. Default is `4`.
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets the text put between the attributions of different layers. Default is `" | "`.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Text of the overlay for the `map`. Returns `None` if no visible layer has attributions.
+    pub fn text(&self, map: &Map) -> Option<String> {
+        let attributions = map.layers().attributions();
+        if attributions.is_empty() {
+            return None;
+        }
+
+        Some(
+            attributions
+                .iter()
+                .map(|attribution| attribution.text.as_str())
+                .collect::<Vec<_>>()
+                .join(&self.separator),
+        )
+    }
+}
+
+impl Overlay for AttributionOverlay {
+    fn render(&self, map: &Map, view: &MapView, canvas: &mut dyn Canvas) {
+        let Some(text) = self.text(map) else {
+            return;
+        };
+
+        let size = view.size();
+        let x = if self.position.is_left() {
+            self.margin
+        } else {
+            size.width() - self.margin
+        };
+        let y = if self.position.is_top() {
+            self.margin
+        } else {
+            size.height() - self.margin
+        };
+
+        self.bundle.draw((text.clone(), size), canvas, |bundle| {
+            let style = overlay_text_style(&self.style, self.position.text_anchor());
+            add_primitive(
+                bundle,
+                RenderPrimitive::new_point(screen_position(x, y), PointPaint::label(&text, &style)),
+            );
+        });
+    }
+}
+
+/// Render bundle of an overlay, kept between the frames while the content of the overlay (identified by the key)
+/// does not change.
+struct CachedBundle<K> {
+    cache: Mutex<Option<(K, RenderBundle)>>,
+}
+
+impl<K: PartialEq> CachedBundle<K> {
+    /// Draws the cached bundle to the `canvas`, building it again with the `build` function if the `key` has changed.
+    fn draw(&self, key: K, canvas: &mut dyn Canvas, build: impl FnOnce(&mut RenderBundle)) {
+        let mut cache = self.cache.lock().expect("mutex is poisoned");
+        if cache.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let mut bundle = canvas.create_bundle();
+            build(&mut bundle);
+            *cache = Some((key, bundle));
+        }
+
+        if let Some((_, bundle)) = &*cache {
+            let packed = canvas.pack_bundle(bundle);
+            canvas.draw_bundles(&[&*packed], RenderOptions::default());
+        }
+    }
+}
+
+impl<K> Default for CachedBundle<K> {
+    fn default() -> Self {
+        Self {
+            cache: Mutex::new(None),
+        }
+    }
+}
+
+/// Cloned overlays build their own bundles.
+impl<K> Clone for CachedBundle<K> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// The cache does not change the way the overlay looks, so it is ignored when the overlays are compared.
+impl<K> PartialEq for CachedBundle<K> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<K> Debug for CachedBundle<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedBundle").finish_non_exhaustive()
+    }
+}
+
+/// Overlay labels are always shown over the labels of the layers.
+fn overlay_text_style(style: &TextStyle, anchor: TextAnchor) -> TextStyle {
+    style
+        .clone()
+        .with_anchor(anchor)
+        .with_allow_overlap(true)
+        .with_priority(f32::MAX)
+}
+
+/// Position on the canvas of the [`screen_view`] of the pixel `(x, y)` of the screen.
+fn screen_position(x: f64, y: f64) -> Point3d {
+    Point3d::new(x, -y, 0.0)
+}
+
+fn add_primitive(
+    bundle: &mut RenderBundle,
+    primitive: RenderPrimitive<f64, Point3d, Contour<Point3d>, Polygon<Point3d>>,
+) {
+    bundle.add(primitive, 1.0);
+}
+
+/// The largest number not greater than `value` that is `1`, `2` or `5` multiplied by a power of ten.
+fn round_down(value: f64) -> f64 {
+    let magnitude = 10f64.powf(value.log10().floor());
+    let leading = value / magnitude;
+    let leading = if leading >= 5.0 {
+        5.0
+    } else if leading >= 2.0 {
+        2.0
+    } else {
+        1.0
+    };
+
+    // Multiplying by a negative power of ten gives values like 0.30000000000000004, so the small values are divided.
+    if magnitude < 1.0 {
+        leading / (1.0 / magnitude).round()
+    } else {
+        leading * magnitude
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::{Attribution, Layer};
+    use crate::messenger::{DummyMessenger, Messenger};
+    use crate::render::text::tests::test_font;
+    use crate::render::SvgRenderer;
+    use galileo_types::cartesian::Size;
+    use galileo_types::latlon;
+    use std::any::Any;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn view(lat: f64, resolution: f64) -> MapView {
+        MapView::new(&latlon!(lat, 0.0), resolution).with_size(Size::new(800.0, 600.0))
+    }
+
+    #[test]
+    fn round_down_values() {
+        assert_eq!(round_down(7.3), 5.0);
+        assert_eq!(round_down(380.0), 200.0);
+        assert_eq!(round_down(1000.0), 1000.0);
+        assert_eq!(round_down(0.35), 0.2);
+    }
+
+    #[test]
+    fn scale_bar_depends_on_resolution_and_latitude() {
+        let bar = ScaleBar::new(TextStyle::new(test_font(), 12.0));
+
+        let length = bar.measure(&view(0.0, 12.0)).unwrap();
+        assert_eq!(length.label, "1 km");
+        assert!((length.width - 83.5).abs() < 0.5);
+
+        // Web Mercator resolution at 60 degrees is two times smaller on the ground.
+        let length = bar.measure(&view(60.0, 12.0)).unwrap();
+        assert_eq!(length.label, "500 m");
+        assert!((length.width - 83.5).abs() < 0.5);
+
+        let bar = bar.with_units(ScaleBarUnits::Imperial);
+        assert_eq!(bar.measure(&view(0.0, 12.0)).unwrap().label, "2000 ft");
+        assert_eq!(bar.measure(&view(0.0, 100.0)).unwrap().label, "5 mi");
+    }
+
+    struct AttributedLayer(&'static str);
+
+    impl Layer for AttributedLayer {
+        fn render(&self, _view: &MapView, _canvas: &mut dyn Canvas) {}
+
+        fn prepare(&self, _view: &MapView) {}
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn attributions(&self) -> Vec<Attribution> {
+            vec![Attribution::new(self.0, None)]
+        }
+    }
+
+    #[test]
+    fn attribution_text_joins_layer_attributions() {
+        let mut map = Map::new(view(0.0, 10.0), vec![], None::<DummyMessenger>);
+        let overlay = AttributionOverlay::new(TextStyle::new(test_font(), 12.0));
+        assert_eq!(overlay.text(&map), None);
+
+        map.add_layer(AttributedLayer("© OpenStreetMap"));
+        map.add_layer(AttributedLayer("© Esri"));
+        map.add_layer(AttributedLayer("© Esri"));
+        assert_eq!(
+            overlay.text(&map).as_deref(),
+            Some("© OpenStreetMap | © Esri")
+        );
+    }
+
+    #[test]
+    fn overlays_are_drawn_over_the_sky() {
+        let view = view(0.0, 10.0).with_rotation_x(1.4);
+        assert!(view.screen_to_map(Point2d::new(10.0, 10.0)).is_none());
+
+        let style = TextStyle::new(test_font(), 12.0);
+        let mut map = Map::new(view, vec![], None::<DummyMessenger>);
+        map.add_layer(AttributedLayer("© OpenStreetMap"));
+        map.add_overlay(
+            AttributionOverlay::new(style.clone()).with_position(OverlayPosition::TopRight),
+        );
+        map.add_overlay(ScaleBar::new(style).with_position(OverlayPosition::TopLeft));
+
+        let svg = SvgRenderer::new().render(&map);
+        assert_eq!(svg.matches("<use ").count(), 2);
+    }
+
+    struct CountingOverlay {
+        key: Arc<AtomicUsize>,
+        builds: Arc<AtomicUsize>,
+        bundle: CachedBundle<usize>,
+    }
+
+    impl Overlay for CountingOverlay {
+        fn render(&self, _map: &Map, _view: &MapView, canvas: &mut dyn Canvas) {
+            self.bundle
+                .draw(self.key.load(Ordering::Relaxed), canvas, |_| {
+                    self.builds.fetch_add(1, Ordering::Relaxed);
+                });
+        }
+    }
+
+    #[test]
+    fn bundle_is_built_again_only_when_key_changes() {
+        let key = Arc::new(AtomicUsize::new(0));
+        let builds = Arc::new(AtomicUsize::new(0));
+        let mut map = Map::new(view(0.0, 10.0), vec![], None::<DummyMessenger>);
+        map.add_overlay(CountingOverlay {
+            key: key.clone(),
+            builds: builds.clone(),
+            bundle: CachedBundle::default(),
+        });
+
+        let renderer = SvgRenderer::new();
+        renderer.render(&map);
+        renderer.render(&map);
+        assert_eq!(builds.load(Ordering::Relaxed), 1);
+
+        key.store(1, Ordering::Relaxed);
+        renderer.render(&map);
+        assert_eq!(builds.load(Ordering::Relaxed), 2);
+    }
+}
@@ -26,5 +26,7 @@ pub type PlatformServiceImpl = native::NativePlatformService;
 #[cfg(target_arch = "wasm32")]
 mod web;
 #[cfg(target_arch = "wasm32")]
+pub use web::worker;
+#[cfg(target_arch = "wasm32")]
 /// Default implementation of the [`PlatformService`] for the current platform.
 pub type PlatformServiceImpl = web::WebPlatformService;
@@ -1,5 +1,7 @@
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
+use crate::layer::data_provider::CacheMetadata;
+use crate::layer::data_provider::{parse_retry_after, HttpResponse};
 use crate::platform::PlatformService;
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -34,17 +36,147 @@ impl PlatformService for NativePlatformService {
 }
 
 impl NativePlatformService {
+    /// Creates a new service that makes all requests with the given client.
+    pub(crate) fn with_http_client(http_client: reqwest::Client) -> Self {
+        Self { http_client }
+    }
+
+    /// Loads `length` bytes starting at `offset` from the resource at `url` using HTTP range request.
+    pub(crate) async fn load_range_from_url(
+        &self,
+        url: &str,
+        offset: u64,
+        length: u64,
+    ) -> Result<Bytes, GalileoError> {
+        if length == 0 {
+            return Ok(Bytes::new());
+        }
+
+        let range = format!("bytes={offset}-{}", offset + length - 1);
+        let response = self
+            .http_client
+            .get(url)
+            .header(reqwest::header::RANGE, range)
+            .send()
+            .await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            info!("Failed to load {url}: {status}");
+            return Err(GalileoError::NotFound);
+        }
+
+        if !status.is_success() {
+            info!("Failed to load range {offset}+{length} from {url}: {status}");
+            return Err(status_error(&response));
+        }
+
+        let bytes = response.bytes().await?;
+        match status {
+            reqwest::StatusCode::PARTIAL_CONTENT if bytes.len() as u64 == length => Ok(bytes),
+            // Server ignored the range header and returned the whole resource.
+            reqwest::StatusCode::OK if bytes.len() as u64 >= offset + length => {
+                Ok(bytes.slice(offset as usize..(offset + length) as usize))
+            }
+            _ => {
+                info!("Failed to load range {offset}+{length} from {url}: {status}");
+                Err(GalileoError::IO)
+            }
+        }
+    }
+
+    /// Loads the resource at `url` with the given additional `headers`. If the metadata of the `cached` data is
+    /// given, the request is conditional, and the server can reply that the cached data is still valid.
+    pub(crate) async fn load_with_validation(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        cached: Option<&CacheMetadata>,
+    ) -> Result<HttpResponse, GalileoError> {
+        let mut request = self.http_client.get(url);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(etag) = cached.and_then(|cached| cached.etag.as_ref()) {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = cached.and_then(|cached| cached.last_modified.as_ref()) {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let metadata = CacheMetadata::from_headers(
+            header(reqwest::header::CACHE_CONTROL),
+            header(reqwest::header::EXPIRES),
+            header(reqwest::header::ETAG),
+            header(reqwest::header::LAST_MODIFIED),
+            std::time::SystemTime::now(),
+        );
+
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            let cached = cached.cloned().unwrap_or_default();
+            // The server may omit the validators in the reply, then the previous ones are still valid.
+            let metadata = metadata.map(|metadata| CacheMetadata {
+                etag: metadata.etag.or(cached.etag.clone()),
+                last_modified: metadata.last_modified.or(cached.last_modified.clone()),
+                expires: metadata.expires,
+            });
+            return Ok(HttpResponse::NotModified(metadata.unwrap_or(cached)));
+        }
+
+        if status == reqwest::StatusCode::NOT_FOUND {
+            info!("Failed to load {url}: {status}");
+            return Err(GalileoError::NotFound);
+        }
+
+        if !status.is_success() {
+            let error = status_error(&response);
+            info!(
+                "Failed to load {url}: {status}, {:?}",
+                response.text().await
+            );
+            return Err(error);
+        }
+
+        Ok(HttpResponse::Data(response.bytes().await?, metadata))
+    }
+
     async fn load_from_web(&self, url: &str) -> Result<Bytes, GalileoError> {
         let response = self.http_client.get(url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            info!("Failed to load {url}: {}", response.status());
+            return Err(GalileoError::NotFound);
+        }
+
         if !response.status().is_success() {
+            let error = status_error(&response);
             info!(
                 "Failed to load {url}: {}, {:?}",
                 response.status(),
                 response.text().await
             );
-            return Err(GalileoError::IO);
+            return Err(error);
         }
 
         Ok(response.bytes().await?)
     }
 }
+
+/// Error for an unsuccessful reply of the server.
+fn status_error(response: &reqwest::Response) -> GalileoError {
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_retry_after(value, std::time::SystemTime::now()));
+    GalileoError::Http {
+        status: response.status().as_u16(),
+        retry_after,
+    }
+}
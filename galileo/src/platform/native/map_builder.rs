@@ -1,3 +1,7 @@
+#[cfg(feature = "mbtiles")]
+use crate::error::GalileoError;
+#[cfg(feature = "mbtiles")]
+use crate::layer::data_provider::MbTilesSource;
 use crate::layer::data_provider::{
     FileCacheController, UrlDataProvider, UrlImageProvider, UrlSource,
 };
@@ -19,8 +23,10 @@ impl MapBuilder {
             view: None,
             layers: vec![],
             event_handlers: vec![],
+            map_event_handlers: vec![],
             window: None,
             event_loop: None,
+            renderer_options: Default::default(),
         }
     }
 
@@ -82,4 +88,37 @@ impl MapBuilder {
 
         VectorTileLayer::from_url(tile_provider, style, tile_scheme)
     }
+
+    /// Create a new raster tile layer that loads tiles from an MBTiles archive.
+    ///
+    /// The tile schema of the layer contains the z-levels stored in the archive.
+    #[cfg(feature = "mbtiles")]
+    pub fn create_mbtiles_raster_layer(
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<RasterTileLayer<MbTilesSource>, GalileoError> {
+        let source = MbTilesSource::open(path)?;
+        Ok(RasterTileLayer::new(source.tile_schema(), source, None))
+    }
+
+    /// Create a new vector tile layer that loads tiles from an MBTiles archive.
+    ///
+    /// The tile schema of the layer contains the z-levels stored in the archive.
+    #[cfg(feature = "mbtiles")]
+    pub fn create_mbtiles_vector_layer(
+        path: impl AsRef<std::path::Path>,
+        style: VectorTileStyle,
+    ) -> Result<VectorTileLayer<ThreadedProvider<MbTilesSource>>, GalileoError> {
+        let source = MbTilesSource::open(path)?;
+        let tile_scheme = source.tile_schema();
+        let tile_provider = ThreadedProvider::new(
+            None,
+            tile_scheme.clone(),
+            source,
+            RenderBundle(RenderBundleType::Tessellating(
+                TessellatingRenderBundle::new(),
+            )),
+        );
+
+        Ok(VectorTileLayer::from_url(tile_provider, style, tile_scheme))
+    }
 }
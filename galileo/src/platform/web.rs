@@ -1,5 +1,6 @@
 use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
+use crate::layer::data_provider::parse_retry_after;
 use crate::platform::PlatformService;
 use async_trait::async_trait;
 use js_sys::Uint8Array;
@@ -12,11 +13,13 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{
-    CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, Request, RequestInit,
-    RequestMode, Response, WorkerGlobalScope,
+    Blob, CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, ImageBitmap,
+    OffscreenCanvas, OffscreenCanvasRenderingContext2d, Request, RequestInit, RequestMode,
+    Response, WorkerGlobalScope,
 };
 
 pub mod map_builder;
+pub mod worker;
 
 pub struct WebPlatformService {}
 
@@ -27,12 +30,14 @@ impl PlatformService for WebPlatformService {
     }
 
     async fn load_image_url(&self, url: &str) -> Result<DecodedImage, GalileoError> {
+        let Some(window) = web_sys::window() else {
+            // Image elements are not available in web workers.
+            return self.load_image_in_worker(url).await;
+        };
+
         let image = ImageFuture::new(url).await?;
 
-        let canvas: HtmlCanvasElement = web_sys::window()
-            .ok_or(GalileoError::Wasm(Some(
-                "Window element is not available".into(),
-            )))?
+        let canvas: HtmlCanvasElement = window
             .document()
             .ok_or(GalileoError::Wasm(Some(
                 "Document element is not available".into(),
@@ -67,14 +72,69 @@ impl PlatformService for WebPlatformService {
     }
 
     async fn load_bytes_from_url(&self, url: &str) -> Result<bytes::Bytes, GalileoError> {
+        self.load_bytes_with_headers(url, &[]).await
+    }
+}
+
+impl WebPlatformService {
+    /// Loads a byte array from the given url, adding the `headers` to the request.
+    pub(crate) async fn load_bytes_with_headers(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> Result<bytes::Bytes, GalileoError> {
+        let array = self
+            .fetch(url, Some("application/vnd.mapbox-vector-tile"), headers)
+            .await?;
+        Ok(array.to_vec().into())
+    }
+
+    /// Loads and decodes an image in a web worker, using `createImageBitmap()` and an `OffscreenCanvas` instead of
+    /// the DOM elements.
+    async fn load_image_in_worker(&self, url: &str) -> Result<DecodedImage, GalileoError> {
+        let global = js_sys::global().dyn_into::<WorkerGlobalScope>()?;
+        let array = self.fetch(url, None, &[]).await?;
+        let blob = Blob::new_with_u8_array_sequence(&js_sys::Array::of1(&array))?;
+        let bitmap: ImageBitmap = JsFuture::from(global.create_image_bitmap_with_blob(&blob)?)
+            .await?
+            .dyn_into()?;
+
+        let (width, height) = (bitmap.width(), bitmap.height());
+        let canvas = OffscreenCanvas::new(width, height)?;
+        let context = canvas
+            .get_context("2d")?
+            .ok_or(GalileoError::Wasm(Some(
+                "Cannot get 2d canvas context".into(),
+            )))?
+            .dyn_into::<OffscreenCanvasRenderingContext2d>()?;
+
+        context.draw_image_with_image_bitmap(&bitmap, 0.0, 0.0)?;
+        bitmap.close();
+        let image_data = context.get_image_data(0.0, 0.0, width as f64, height as f64)?;
+
+        Ok(DecodedImage {
+            bytes: image_data.data().to_vec(),
+            dimensions: (image_data.width(), image_data.height()),
+        })
+    }
+
+    async fn fetch(
+        &self,
+        url: &str,
+        accept: Option<&str>,
+        headers: &[(String, String)],
+    ) -> Result<Uint8Array, GalileoError> {
         let mut opts = RequestInit::new();
         opts.method("GET");
         opts.mode(RequestMode::Cors);
 
-        let request = Request::new_with_str_and_init(url, &opts).unwrap();
-        request
-            .headers()
-            .set("Accept", "application/vnd.mapbox-vector-tile")?;
+        let request = Request::new_with_str_and_init(url, &opts)?;
+        if let Some(accept) = accept {
+            request.headers().set("Accept", accept)?;
+        }
+        for (name, value) in headers {
+            request.headers().set(name, value)?;
+        }
 
         use wasm_bindgen::JsCast;
         let resp_value = {
@@ -91,10 +151,26 @@ impl PlatformService for WebPlatformService {
 
         assert!(resp_value.is_instance_of::<Response>());
         let resp: Response = resp_value.dyn_into()?;
+        if resp.status() == 404 {
+            log::info!("Failed to load {url}: {}", resp.status());
+            return Err(GalileoError::NotFound);
+        }
+        if !resp.ok() {
+            log::info!("Failed to load {url}: {}", resp.status());
+            let retry_after = resp
+                .headers()
+                .get("Retry-After")
+                .ok()
+                .flatten()
+                .and_then(|value| parse_retry_after(&value, web_time::SystemTime::now()));
+            return Err(GalileoError::Http {
+                status: resp.status(),
+                retry_after,
+            });
+        }
 
         let bytes_val = JsFuture::from(resp.array_buffer()?).await?;
-        let array = Uint8Array::new(&bytes_val);
-        Ok(array.to_vec().into())
+        Ok(Uint8Array::new(&bytes_val))
     }
 }
 
@@ -47,8 +47,10 @@ impl MapBuilder {
             view: None,
             layers: vec![],
             event_handlers: vec![],
+            map_event_handlers: vec![],
             window: None,
             event_loop: None,
+            renderer_options: Default::default(),
         }
     }
 
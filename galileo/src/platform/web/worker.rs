@@ -0,0 +1,285 @@
+//! Helpers for rendering a map in a web worker.
+//!
+//! Rendering a map and decoding its tiles can take considerable time, during which the page does not respond to the
+//! user. To keep the main thread free, the map can be moved into a dedicated worker:
+//!
+//! 1. The page creates a worker and a [`MapWorker`] for it, which transfers control over the canvas to the worker.
+//! 2. The page forwards DOM input events to the worker through the methods of the [`MapWorker`].
+//! 3. The worker decodes the posted messages with [`WorkerMessage::from_js`]. The canvas of the
+//!    [`WorkerMessage::Init`] message is given to
+//!    [`WgpuRenderer::new_with_offscreen_canvas`](crate::render::WgpuRenderer::new_with_offscreen_canvas), and the
+//!    [`WorkerMessage::Input`] events to the [`EventProcessor`](crate::control::EventProcessor).
+//! 4. Layers of the map are given a [`WorkerMessenger`], and the worker renders the map when
+//!    [`WorkerMessenger::take_redraw_request`] returns `true`.
+//!
+//! Messages are queued by the browser until the script of the worker is evaluated, so the worker must set its
+//! `onmessage` handler before awaiting initialization of the wasm module, or it will miss the `Init` message.
+
+use crate::control::{Key, MouseButton, RawUserEvent, TouchEvent};
+use crate::error::GalileoError;
+use crate::messenger::Messenger;
+use galileo_types::cartesian::{Point2d, Size};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlCanvasElement, OffscreenCanvas, Worker};
+
+const CANVAS_FIELD: &str = "canvas";
+const WIDTH_FIELD: &str = "width";
+const HEIGHT_FIELD: &str = "height";
+
+/// Handle of a worker rendering the map, used on the main thread of the page to post user input to the worker.
+#[wasm_bindgen]
+pub struct MapWorker {
+    worker: Worker,
+}
+
+#[wasm_bindgen]
+impl MapWorker {
+    /// Transfers control over the `canvas` to the `worker`, which receives it as [`WorkerMessage::Init`]. After
+    /// this the canvas cannot be drawn to from the main thread.
+    #[wasm_bindgen(constructor)]
+    pub fn new(worker: Worker, canvas: HtmlCanvasElement) -> Result<MapWorker, JsValue> {
+        let width = canvas.width();
+        let height = canvas.height();
+        let offscreen = canvas.transfer_control_to_offscreen()?;
+
+        let message = js_sys::Object::new();
+        js_sys::Reflect::set(&message, &CANVAS_FIELD.into(), &offscreen)?;
+        js_sys::Reflect::set(&message, &WIDTH_FIELD.into(), &width.into())?;
+        js_sys::Reflect::set(&message, &HEIGHT_FIELD.into(), &height.into())?;
+        worker.post_message_with_transfer(&message, &js_sys::Array::of1(&offscreen))?;
+
+        Ok(Self { worker })
+    }
+
+    /// Notifies the worker that the canvas was resized to the given size in pixels.
+    pub fn resize(&self, width: u32, height: u32) -> Result<(), JsValue> {
+        self.post(PostedMessage::Resize { width, height })
+    }
+
+    /// Mouse pointer was moved to the given position in pixels from the top-left corner of the canvas.
+    pub fn pointer_moved(&self, x: f64, y: f64) -> Result<(), JsValue> {
+        self.post(PostedMessage::PointerMoved { x, y })
+    }
+
+    /// A mouse button was pressed. The button is given as the `MouseEvent.button` value of the DOM event.
+    pub fn button_pressed(&self, button: i16) -> Result<(), JsValue> {
+        self.post(PostedMessage::ButtonPressed { button })
+    }
+
+    /// A mouse button was released. The button is given as the `MouseEvent.button` value of the DOM event.
+    pub fn button_released(&self, button: i16) -> Result<(), JsValue> {
+        self.post(PostedMessage::ButtonReleased { button })
+    }
+
+    /// Scroll was done by the given number of lines. Positive values scroll up.
+    pub fn scroll(&self, delta: f64) -> Result<(), JsValue> {
+        self.post(PostedMessage::Scroll { delta })
+    }
+
+    /// New touch started at the given position of the canvas.
+    pub fn touch_start(&self, touch_id: u32, x: f64, y: f64) -> Result<(), JsValue> {
+        self.post(PostedMessage::TouchStart { touch_id, x, y })
+    }
+
+    /// Existing touch moved to the given position of the canvas.
+    pub fn touch_move(&self, touch_id: u32, x: f64, y: f64) -> Result<(), JsValue> {
+        self.post(PostedMessage::TouchMove { touch_id, x, y })
+    }
+
+    /// Existing touch was released at the given position of the canvas.
+    pub fn touch_end(&self, touch_id: u32, x: f64, y: f64) -> Result<(), JsValue> {
+        self.post(PostedMessage::TouchEnd { touch_id, x, y })
+    }
+
+    /// A keyboard key was pressed. The key is given as the `KeyboardEvent.key` value of the DOM event.
+    pub fn key_pressed(&self, key: String) -> Result<(), JsValue> {
+        self.post(PostedMessage::KeyPressed { key })
+    }
+
+    /// A keyboard key was released. The key is given as the `KeyboardEvent.key` value of the DOM event.
+    pub fn key_released(&self, key: String) -> Result<(), JsValue> {
+        self.post(PostedMessage::KeyReleased { key })
+    }
+}
+
+impl MapWorker {
+    fn post(&self, message: PostedMessage) -> Result<(), JsValue> {
+        let value = serde_wasm_bindgen::to_value(&message)?;
+        self.worker.post_message(&value)
+    }
+}
+
+/// Message received by the worker from a [`MapWorker`].
+pub enum WorkerMessage {
+    /// The canvas to render the map to, and its size in pixels. This is the first message the worker receives.
+    Init {
+        /// Canvas transferred from the page.
+        canvas: OffscreenCanvas,
+        /// Size of the canvas.
+        size: Size<u32>,
+    },
+    /// The canvas was resized to the given size in pixels.
+    Resize(Size<u32>),
+    /// User input event.
+    Input(RawUserEvent),
+}
+
+impl WorkerMessage {
+    /// Decodes the data of a message event received by the worker.
+    pub fn from_js(data: JsValue) -> Result<Self, GalileoError> {
+        let canvas = js_sys::Reflect::get(&data, &CANVAS_FIELD.into())?;
+        if let Ok(canvas) = canvas.dyn_into::<OffscreenCanvas>() {
+            let dimension = |field: &str| -> Result<u32, GalileoError> {
+                js_sys::Reflect::get(&data, &field.into())?
+                    .as_f64()
+                    .map(|value| value as u32)
+                    .ok_or_else(|| GalileoError::Wasm(Some(format!("invalid canvas {field}"))))
+            };
+            let size = Size::new(dimension(WIDTH_FIELD)?, dimension(HEIGHT_FIELD)?);
+            return Ok(Self::Init { canvas, size });
+        }
+
+        let message: PostedMessage = serde_wasm_bindgen::from_value(data)
+            .map_err(|err| GalileoError::Wasm(Some(format!("invalid worker message: {err}"))))?;
+        Ok(message.into())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum PostedMessage {
+    Resize { width: u32, height: u32 },
+    PointerMoved { x: f64, y: f64 },
+    ButtonPressed { button: i16 },
+    ButtonReleased { button: i16 },
+    Scroll { delta: f64 },
+    TouchStart { touch_id: u32, x: f64, y: f64 },
+    TouchMove { touch_id: u32, x: f64, y: f64 },
+    TouchEnd { touch_id: u32, x: f64, y: f64 },
+    KeyPressed { key: String },
+    KeyReleased { key: String },
+}
+
+impl From<PostedMessage> for WorkerMessage {
+    fn from(value: PostedMessage) -> Self {
+        let touch = |touch_id: u32, x, y| TouchEvent {
+            touch_id: touch_id as u64,
+            position: Point2d::new(x, y),
+        };
+
+        let event = match value {
+            PostedMessage::Resize { width, height } => {
+                return WorkerMessage::Resize(Size::new(width, height))
+            }
+            PostedMessage::PointerMoved { x, y } => RawUserEvent::PointerMoved(Point2d::new(x, y)),
+            PostedMessage::ButtonPressed { button } => {
+                RawUserEvent::ButtonPressed(dom_mouse_button(button))
+            }
+            PostedMessage::ButtonReleased { button } => {
+                RawUserEvent::ButtonReleased(dom_mouse_button(button))
+            }
+            PostedMessage::Scroll { delta } => RawUserEvent::Scroll(delta),
+            PostedMessage::TouchStart { touch_id, x, y } => {
+                RawUserEvent::TouchStart(touch(touch_id, x, y))
+            }
+            PostedMessage::TouchMove { touch_id, x, y } => {
+                RawUserEvent::TouchMove(touch(touch_id, x, y))
+            }
+            PostedMessage::TouchEnd { touch_id, x, y } => {
+                RawUserEvent::TouchEnd(touch(touch_id, x, y))
+            }
+            PostedMessage::KeyPressed { key } => RawUserEvent::KeyPressed(dom_key(&key)),
+            PostedMessage::KeyReleased { key } => RawUserEvent::KeyReleased(dom_key(&key)),
+        };
+
+        WorkerMessage::Input(event)
+    }
+}
+
+fn dom_mouse_button(button: i16) -> MouseButton {
+    match button {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        2 => MouseButton::Right,
+        _ => MouseButton::Other,
+    }
+}
+
+fn dom_key(key: &str) -> Key {
+    match key {
+        "ArrowLeft" => Key::ArrowLeft,
+        "ArrowRight" => Key::ArrowRight,
+        "ArrowUp" => Key::ArrowUp,
+        "ArrowDown" => Key::ArrowDown,
+        "Shift" => Key::Shift,
+        "Control" => Key::Control,
+        "Alt" => Key::Alt,
+        _ => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Character(c),
+                _ => Key::Other,
+            }
+        }
+    }
+}
+
+/// Messenger for a map rendered in a web worker.
+///
+/// Workers have no window to request a redraw from, so the messenger only records the request. The worker checks it
+/// with [`WorkerMessenger::take_redraw_request`] on each animation frame.
+#[derive(Debug, Clone, Default)]
+pub struct WorkerMessenger {
+    redraw_requested: Arc<AtomicBool>,
+}
+
+impl WorkerMessenger {
+    /// Creates a new messenger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if a redraw was requested since the last call, and resets the request.
+    pub fn take_redraw_request(&self) -> bool {
+        self.redraw_requested.swap(false, Ordering::AcqRel)
+    }
+}
+
+impl Messenger for WorkerMessenger {
+    fn request_redraw(&self) {
+        self.redraw_requested.store(true, Ordering::Release);
+    }
+}
+
+/// Returns the list of array buffers of all typed arrays contained in the `value`, to be given as the transfer list
+/// of `postMessage()`.
+///
+/// Transferring the buffers moves them to the receiving thread instead of copying them. The buffers cannot be used by
+/// the sending thread afterwards.
+#[wasm_bindgen]
+pub fn transfer_list(value: &JsValue) -> js_sys::Array {
+    let list = js_sys::Array::new();
+    collect_buffers(value, &list);
+    list
+}
+
+fn collect_buffers(value: &JsValue, list: &js_sys::Array) {
+    if let Some(array) = value.dyn_ref::<js_sys::Uint8Array>() {
+        let buffer = array.buffer();
+        // Listing a buffer more than once makes `postMessage()` fail.
+        if !list.includes(&buffer, 0) {
+            list.push(&buffer);
+        }
+    } else if let Some(array) = value.dyn_ref::<js_sys::Array>() {
+        for item in array.iter() {
+            collect_buffers(&item, list);
+        }
+    } else if let Some(object) = value.dyn_ref::<js_sys::Object>() {
+        for item in js_sys::Object::values(object).iter() {
+            collect_buffers(&item, list);
+        }
+    }
+}
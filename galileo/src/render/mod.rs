@@ -2,24 +2,36 @@
 //!
 //! The backends use [`Canvas`] instances to render map layers to the render target (screen, image, etc.).
 //!
-//! At this point only [`WgpuRenderer`] is implemented.
+//! [`WgpuRenderer`] draws the map to the screen or to an image, and [`SvgRenderer`] exports it as a vector SVG
+//! document.
 
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::view::MapView;
 use crate::Color;
-use galileo_types::cartesian::Size;
+use galileo_types::cartesian::{Point2d, Size};
 use maybe_sync::{MaybeSend, MaybeSync};
+use nalgebra::{Matrix4, Vector3};
 use render_bundle::RenderBundle;
 use std::any::Any;
+use std::sync::Arc;
 
 #[cfg(feature = "wgpu")]
 mod wgpu;
 #[cfg(feature = "wgpu")]
-pub use wgpu::WgpuRenderer;
+pub use wgpu::{LineAntialiasing, WgpuFrame, WgpuRenderer, WgpuRendererOptions};
 
 pub mod point_paint;
 pub mod render_bundle;
+#[cfg(not(target_arch = "wasm32"))]
+mod svg;
+pub mod text;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use svg::SvgRenderer;
 
 /// Id of a rendering primitive
-#[derive(Debug, Copy, Clone, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct PrimitiveId(usize);
 
 /// Canvas that a layer can be rendered to.
@@ -33,7 +45,7 @@ pub struct PrimitiveId(usize);
 /// 3. [`PackedBundle`]s can then be rendered by calling [`Canvas::draw_bundles`] method.
 ///
 /// A layer may choose to store `RenderBundles` and `PackedBundles` between redraws to skip the expensive preparation
-/// process.
+/// process. Such layers must not store the bundles packed by a [transient](Canvas::is_transient) canvas.
 pub trait Canvas {
     /// Size of the drawing area.
     fn size(&self) -> Size;
@@ -43,6 +55,25 @@ pub trait Canvas {
     fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle>;
     /// Render the bundles.
     fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions);
+    /// Packs weighted points to be drawn as a heatmap with [`Canvas::draw_heatmap`]. Positions of the points are set
+    /// in the map CRS.
+    fn pack_heatmap(&self, points: &[(Point2d, f32)]) -> Box<dyn PackedBundle>;
+    /// Draws the density surface of the points packed with [`Canvas::pack_heatmap`].
+    fn draw_heatmap(&mut self, points: &dyn PackedBundle, paint: HeatmapPaint);
+    /// Returns `true` if the canvas is used for a one-off rendering, like an export with [`SvgRenderer`].
+    ///
+    /// Bundles packed by a transient canvas cannot be drawn by other canvases, so layers pack the bundles for it on
+    /// every draw instead of replacing the bundles they store between redraws.
+    fn is_transient(&self) -> bool {
+        false
+    }
+
+    /// Returns the wgpu resources of the frame, if the canvas is drawn by [`WgpuRenderer`]. This allows layers to draw
+    /// with their own wgpu pipelines. Returns `None` by default.
+    #[cfg(feature = "wgpu")]
+    fn wgpu_frame(&mut self) -> Option<WgpuFrame<'_>> {
+        None
+    }
 }
 
 /// Packed render bundle ready to be drawn.
@@ -51,24 +82,257 @@ pub trait PackedBundle: MaybeSend + MaybeSync {
     fn as_any(&self) -> &dyn Any;
 }
 
+/// The way colors of a layer are combined with the colors of the layers drawn below it. See
+/// [`LayerCollection::set_blend_mode`](crate::LayerCollection::set_blend_mode).
+///
+/// The layer is first drawn on its own, and then its image is combined with the layers below it, so the features of
+/// the layer are not blended with each other. In all modes the alpha of the layer image (including the layer opacity)
+/// controls how strong the effect is: fully transparent pixels leave the underlying colors unchanged.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    /// The layer is drawn over the underlying layers.
+    #[default]
+    Normal,
+    /// Colors of the layer are multiplied with the underlying colors. The result is never lighter than either of
+    /// them, white pixels of the layer don't change the underlying colors.
+    Multiply,
+    /// Inverted colors of the layer are multiplied with the inverted underlying colors. The result is never darker
+    /// than either of them, black pixels of the layer don't change the underlying colors.
+    Screen,
+    /// Colors of the layer are added to the underlying colors.
+    Additive,
+}
+
 /// Rendering options.
 #[derive(Debug, Copy, Clone)]
 pub struct RenderOptions {
     /// If set to true, the primitives will be drawn using antialiasing (multisampling).
     pub antialias: bool,
+    /// Color adjustments applied to the images of the bundles.
+    pub color_adjustment: ColorAdjustment,
 }
 
 impl Default for RenderOptions {
     fn default() -> Self {
-        Self { antialias: true }
+        Self {
+            antialias: true,
+            color_adjustment: ColorAdjustment::default(),
+        }
     }
 }
 
-/// Parameters to draw a polygon primitive with.
+/// Adjustments of the colors of the images drawn by a layer, e.g. the raster tiles of
+/// [`RasterTileLayer`](crate::layer::RasterTileLayer).
+///
+/// The adjustments work the same way as the corresponding CSS filters and are applied in the order of the fields:
+/// inversion, hue rotation, saturation, grayscale, brightness and contrast. Alpha of the images is not changed.
+/// The default value does not change the colors.
+///
+/// ```
+/// use galileo::render::ColorAdjustment;
+///
+/// // Dark basemap from the standard light tiles.
+/// let dark = ColorAdjustment::dark_mode().with_brightness(0.9);
+/// assert!(!dark.is_identity());
+/// assert!(ColorAdjustment::default().is_identity());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorAdjustment {
+    /// Amount of color inversion from `0.0` (unchanged) to `1.0` (fully inverted).
+    pub invert: f32,
+    /// Rotation of the hue in radians.
+    pub hue_rotate: f32,
+    /// Saturation multiplier. `0.0` removes all colors, values larger than `1.0` make the colors more saturated.
+    pub saturation: f32,
+    /// Amount of conversion to grayscale from `0.0` (unchanged) to `1.0` (fully gray).
+    pub grayscale: f32,
+    /// Brightness multiplier. `0.0` makes the images black.
+    pub brightness: f32,
+    /// Contrast multiplier. `0.0` makes the images uniformly gray.
+    pub contrast: f32,
+}
+
+impl Default for ColorAdjustment {
+    fn default() -> Self {
+        Self {
+            invert: 0.0,
+            hue_rotate: 0.0,
+            saturation: 1.0,
+            grayscale: 0.0,
+            brightness: 1.0,
+            contrast: 1.0,
+        }
+    }
+}
+
+impl ColorAdjustment {
+    /// Inverts the lightness of the images while keeping their hues, turning a light basemap into a dark one.
+    pub fn dark_mode() -> Self {
+        Self {
+            invert: 1.0,
+            hue_rotate: std::f32::consts::PI,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the amount of color inversion.
+    pub fn with_invert(&self, invert: f32) -> Self {
+        Self { invert, ..*self }
+    }
+
+    /// Sets the rotation of the hue in radians.
+    pub fn with_hue_rotate(&self, hue_rotate: f32) -> Self {
+        Self {
+            hue_rotate,
+            ..*self
+        }
+    }
+
+    /// Sets the saturation multiplier.
+    pub fn with_saturation(&self, saturation: f32) -> Self {
+        Self {
+            saturation,
+            ..*self
+        }
+    }
+
+    /// Sets the amount of conversion to grayscale.
+    pub fn with_grayscale(&self, grayscale: f32) -> Self {
+        Self { grayscale, ..*self }
+    }
+
+    /// Sets the brightness multiplier.
+    pub fn with_brightness(&self, brightness: f32) -> Self {
+        Self {
+            brightness,
+            ..*self
+        }
+    }
+
+    /// Sets the contrast multiplier.
+    pub fn with_contrast(&self, contrast: f32) -> Self {
+        Self { contrast, ..*self }
+    }
+
+    /// Returns `true` if the adjustment does not change the colors.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Affine transformation of the RGB components (with values from `0.0` to `1.0`) as a homogeneous matrix.
+    pub(crate) fn color_matrix(&self) -> Matrix4<f32> {
+        let invert = Matrix4::new_nonuniform_scaling(&Vector3::repeat(1.0 - 2.0 * self.invert))
+            .append_translation(&Vector3::repeat(self.invert));
+        let (sin, cos) = self.hue_rotate.sin_cos();
+        #[rustfmt::skip]
+        let hue_rotate = Matrix4::new(
+            0.213 + cos * 0.787 - sin * 0.213, 0.715 - cos * 0.715 - sin * 0.715, 0.072 - cos * 0.072 + sin * 0.928, 0.0,
+            0.213 - cos * 0.213 + sin * 0.143, 0.715 + cos * 0.285 + sin * 0.140, 0.072 - cos * 0.072 - sin * 0.283, 0.0,
+            0.213 - cos * 0.213 - sin * 0.787, 0.715 - cos * 0.715 + sin * 0.715, 0.072 + cos * 0.928 + sin * 0.072, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+        let saturation = Self::saturation_matrix(self.saturation);
+        let grayscale = Self::saturation_matrix(1.0 - self.grayscale.clamp(0.0, 1.0));
+        let brightness = Matrix4::new_nonuniform_scaling(&Vector3::repeat(self.brightness));
+        let contrast = Matrix4::new_nonuniform_scaling(&Vector3::repeat(self.contrast))
+            .append_translation(&Vector3::repeat(0.5 - 0.5 * self.contrast));
+
+        contrast * brightness * grayscale * saturation * hue_rotate * invert
+    }
+
+    fn saturation_matrix(s: f32) -> Matrix4<f32> {
+        #[rustfmt::skip]
+        let matrix = Matrix4::new(
+            0.213 + 0.787 * s, 0.715 - 0.715 * s, 0.072 - 0.072 * s, 0.0,
+            0.213 - 0.213 * s, 0.715 + 0.285 * s, 0.072 - 0.072 * s, 0.0,
+            0.213 - 0.213 * s, 0.715 - 0.715 * s, 0.072 + 0.928 * s, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+        matrix
+    }
+}
+
+/// Parameters to draw a heatmap with.
 #[derive(Debug, Clone, Copy)]
+pub struct HeatmapPaint<'a> {
+    /// Radius of influence of every point in pixels.
+    pub radius: f32,
+    /// Multiplier for the density of the points. Density of a single point with weight `1.0` at its center is equal
+    /// to the intensity.
+    pub intensity: f32,
+    /// Colors for the density values evenly distributed between `0.0` and `1.0`. Density values larger than `1.0` are
+    /// drawn with the last color.
+    pub colors: &'a [Color],
+}
+
+/// Parameters to draw a polygon primitive with.
+#[derive(Debug, Clone)]
 pub struct PolygonPaint {
-    /// Fill color of the polygon.
+    /// Fill color of the polygon. For hatch fills this is the color of the hatch lines, and pattern images are
+    /// multiplied by it.
     pub color: Color,
+    /// Style of the polygon fill.
+    pub fill: PolygonFill,
+}
+
+/// Style of a polygon fill.
+///
+/// Hatches and patterns are drawn in screen pixels, so they keep their size when the map is zoomed. They are aligned
+/// to the map and move and rotate together with it. Hatched and patterned polygons of a bundle are drawn above its
+/// solid polygons and lines.
+///
+/// Polygons that don't lie in the ground plane (e.g. walls of extruded buildings) are always filled solid.
+#[derive(Debug, Clone, Default)]
+pub enum PolygonFill {
+    /// The polygon is filled with its color.
+    #[default]
+    Solid,
+    /// The polygon is filled with parallel lines of its color.
+    Hatch(HatchFill),
+    /// The polygon is filled with the image repeated in both directions, one image pixel per screen pixel.
+    Pattern(Arc<DecodedImage>),
+}
+
+/// Parameters of a hatch fill of a polygon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HatchFill {
+    /// Angle of the lines in radians, counterclockwise from the horizontal direction of the map.
+    pub angle: f64,
+    /// Distance between the centers of neighbouring lines in pixels.
+    pub spacing: f64,
+    /// Width of the lines in pixels.
+    pub line_width: f64,
+}
+
+impl HatchFill {
+    /// Creates a new hatch.
+    pub fn new(angle: f64, spacing: f64, line_width: f64) -> Self {
+        Self {
+            angle,
+            spacing,
+            line_width,
+        }
+    }
+}
+
+/// Distance in pixels between the possible positions of the pattern origin.
+const PATTERN_ORIGIN_STEP: f64 = 65536.0;
+
+/// Point of the map that hatches and pattern images are aligned to.
+///
+/// Pattern coordinates are calculated relative to this point, so that they don't lose precision far from the CRS
+/// origin. The point is snapped to a coarse grid, so it does not move when the map is panned, otherwise the patterns
+/// would stay in place on the screen.
+pub(crate) fn pattern_origin(view: &MapView) -> Option<Point2d> {
+    let step = view.resolution() * PATTERN_ORIGIN_STEP;
+    let position = view.projected_position()?;
+    Some(Point2d::new(
+        (position.x / step).round() * step,
+        (position.y / step).round() * step,
+    ))
 }
 
 /// Parameter to draw a line primitive with.
@@ -81,16 +345,25 @@ pub struct LinePaint {
     /// Offset of the line in pixels. The line is offset to the right side if the positive value is given, and to the
     /// left otherwise.
     pub offset: f64,
-    /// Type of the cap of the line.
+    /// Type of the cap of the line. With a dash pattern, every dash gets the caps.
     pub line_cap: LineCap,
+    /// Type of the joins between the segments of the line.
+    pub line_join: LineJoin,
+    /// Dash pattern of the line. If not set, a solid line is drawn.
+    pub dash: Option<DashPattern>,
+    /// Arrowheads drawn on the line.
+    pub arrows: Option<LineArrows>,
 }
 
 /// Cap (end point) style of the line.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum LineCap {
     /// Half-circle cap.
     Round,
     /// Strait rectangular cap.
+    #[default]
     Butt,
 }
 
@@ -103,6 +376,193 @@ impl From<LineCap> for lyon::path::LineCap {
     }
 }
 
+/// Style of the joins between the segments of the line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LineJoin {
+    /// Circular arc join.
+    #[default]
+    Round,
+    /// Join cut straight across the corner.
+    Bevel,
+    /// Sharp join, extending the outer edges of the segments until they meet. Joins of very sharp corners are
+    /// beveled to limit their length to 4 widths of the line.
+    Miter,
+}
+
+impl From<LineJoin> for lyon::path::LineJoin {
+    fn from(val: LineJoin) -> Self {
+        match val {
+            LineJoin::Round => lyon::path::LineJoin::Round,
+            LineJoin::Bevel => lyon::path::LineJoin::Bevel,
+            LineJoin::Miter => lyon::path::LineJoin::Miter,
+        }
+    }
+}
+
+/// Lengths of the dashes and gaps of a dashed line in pixels.
+///
+/// The pattern starts with a dash, followed by a gap, and so on. If the number of lengths is odd, the lengths are
+/// repeated to make it even (as in SVG), so `[5.0]` gives 5 pixel dashes with 5 pixel gaps.
+///
+/// Lines are drawn with constant width in pixels, but dashes are cut from the line geometry, so they are scaled with
+/// the map between levels of detail. The lengths correspond to pixels at the resolution the line is tessellated for
+/// (e.g. the resolution of a [`FeatureLayer`](crate::layer::FeatureLayer) LOD).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "DashPatternConfig", into = "DashPatternConfig")
+)]
+pub struct DashPattern {
+    lengths: [f64; DashPattern::MAX_LEN * 2],
+    len: usize,
+    offset: f64,
+}
+
+impl DashPattern {
+    /// Maximum number of lengths in a pattern.
+    pub const MAX_LEN: usize = 8;
+
+    /// Creates a new pattern with the given lengths of the dashes and gaps in pixels.
+    ///
+    /// Returns an error if the list is empty or longer than [`DashPattern::MAX_LEN`], if any of the lengths is
+    /// negative or not finite, or if all of them are zero.
+    pub fn new(lengths: &[f64]) -> Result<Self, GalileoError> {
+        if lengths.is_empty() || lengths.len() > Self::MAX_LEN {
+            return Err(GalileoError::Generic(format!(
+                "dash pattern must have from 1 to {} lengths",
+                Self::MAX_LEN
+            )));
+        }
+
+        if lengths.iter().any(|v| !v.is_finite() || *v < 0.0) || lengths.iter().all(|v| *v == 0.0) {
+            return Err(GalileoError::Generic(
+                "dash pattern lengths must be non-negative and not all zero".into(),
+            ));
+        }
+
+        let mut pattern = Self {
+            lengths: [0.0; Self::MAX_LEN * 2],
+            len: lengths.len(),
+            offset: 0.0,
+        };
+        pattern.lengths[..lengths.len()].copy_from_slice(lengths);
+        if lengths.len() % 2 == 1 {
+            pattern.lengths[lengths.len()..lengths.len() * 2].copy_from_slice(lengths);
+        }
+
+        Ok(pattern)
+    }
+
+    /// Creates a new pattern from a copy of the current, but starting at the given distance in pixels into the
+    /// pattern.
+    pub fn with_offset(&self, offset: f64) -> Self {
+        Self { offset, ..*self }
+    }
+
+    /// Lengths of the dashes and gaps as they were given to [`DashPattern::new`].
+    pub fn lengths(&self) -> &[f64] {
+        &self.lengths[..self.len]
+    }
+
+    /// Distance into the pattern at which the line starts.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Dash and gap lengths of one repetition of the pattern. The number of the lengths is always even.
+    pub(crate) fn period(&self) -> &[f64] {
+        if self.len % 2 == 1 {
+            &self.lengths[..self.len * 2]
+        } else {
+            &self.lengths[..self.len]
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DashPatternConfig {
+    lengths: Vec<f64>,
+    #[serde(default)]
+    offset: f64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DashPatternConfig> for DashPattern {
+    type Error = GalileoError;
+
+    fn try_from(value: DashPatternConfig) -> Result<Self, Self::Error> {
+        Ok(Self::new(&value.lengths)?.with_offset(value.offset))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<DashPattern> for DashPatternConfig {
+    fn from(value: DashPattern) -> Self {
+        Self {
+            lengths: value.lengths().to_vec(),
+            offset: value.offset,
+        }
+    }
+}
+
+/// Arrowheads drawn on a line, pointing in the direction of the line.
+///
+/// Arrowheads are triangles of constant size in pixels, and they have the color of the line. Every arrowhead is
+/// centered at its point on the line, so the arrowheads at the ends extend beyond the end points. Closed contours
+/// have no ends, so only the arrowheads at intervals are drawn for them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineArrows {
+    /// Length of the arrowhead along the line in pixels.
+    pub length: f64,
+    /// Width of the base of the arrowhead in pixels.
+    pub width: f64,
+    /// Draw an arrowhead at the first point of the line, pointing backward.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub start: bool,
+    /// Draw an arrowhead at the last point of the line.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub end: bool,
+    /// Distance between the arrowheads along the line in pixels. The first arrowhead is placed at half the interval
+    /// from the start of the line. Like [`DashPattern`], the interval corresponds to pixels at the resolution the
+    /// line is tessellated for.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub interval: Option<f64>,
+}
+
+impl LineArrows {
+    /// Creates an arrowhead at the end of the line with the given size in pixels.
+    pub fn new(length: f64, width: f64) -> Self {
+        Self {
+            length,
+            width,
+            start: false,
+            end: true,
+            interval: None,
+        }
+    }
+
+    /// Creates a new instance from a copy of the current, but with arrowhead at the start of the line set or unset.
+    pub fn with_start(&self, start: bool) -> Self {
+        Self { start, ..*self }
+    }
+
+    /// Creates a new instance from a copy of the current, but with arrowhead at the end of the line set or unset.
+    pub fn with_end(&self, end: bool) -> Self {
+        Self { end, ..*self }
+    }
+
+    /// Creates a new instance from a copy of the current, but with arrowheads repeated along the line with the given
+    /// interval in pixels.
+    pub fn with_interval(&self, interval: Option<f64>) -> Self {
+        Self { interval, ..*self }
+    }
+}
+
 /// Parameter to render an image with.
 pub struct ImagePaint {
     /// Opacity of the image. The value of 255 means fully opaque image.
@@ -111,3 +571,53 @@ pub struct ImagePaint {
     /// opacity and this value represented in percents.
     pub opacity: u8,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector4;
+
+    fn adjust(adjustment: ColorAdjustment, rgb: [f32; 3]) -> [f32; 3] {
+        let color = adjustment.color_matrix() * Vector4::new(rgb[0], rgb[1], rgb[2], 1.0);
+        [color.x, color.y, color.z]
+    }
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-3, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn color_adjustments() {
+        let color = [0.8, 0.4, 0.1];
+        assert_close(adjust(ColorAdjustment::default(), color), color);
+        assert_close(
+            adjust(ColorAdjustment::default().with_invert(1.0), color),
+            [0.2, 0.6, 0.9],
+        );
+        assert_close(
+            adjust(ColorAdjustment::default().with_contrast(0.0), color),
+            [0.5; 3],
+        );
+        assert_close(
+            adjust(ColorAdjustment::default().with_brightness(0.5), color),
+            [0.4, 0.2, 0.05],
+        );
+
+        let gray = 0.213 * 0.8 + 0.715 * 0.4 + 0.072 * 0.1;
+        assert_close(
+            adjust(ColorAdjustment::default().with_grayscale(1.0), color),
+            [gray; 3],
+        );
+        assert_close(
+            adjust(ColorAdjustment::default().with_saturation(0.0), color),
+            [gray; 3],
+        );
+
+        // Hue rotation by the full circle does not change the colors, and gray colors have no hue.
+        let full_turn = ColorAdjustment::default().with_hue_rotate(std::f32::consts::TAU);
+        assert_close(adjust(full_turn, color), color);
+        assert_close(adjust(ColorAdjustment::dark_mode(), [1.0; 3]), [0.0; 3]);
+    }
+}
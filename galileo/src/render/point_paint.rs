@@ -1,10 +1,12 @@
 //! [`PointPaint`] specifies the way a point should be drawn to the map.
 
 use crate::decoded_image::DecodedImage;
-use crate::render::{LineCap, LinePaint};
+use crate::render::text::{LabelGlyph, TextStyle};
+use crate::render::{LineCap, LineJoin, LinePaint};
 use crate::Color;
 use galileo_types::impls::ClosedContour;
 use nalgebra::{Point2, Vector2};
+use std::borrow::Cow;
 use std::sync::Arc;
 
 /// Specifies the way a point should be drawn to the map.
@@ -12,6 +14,17 @@ use std::sync::Arc;
 pub struct PointPaint<'a> {
     pub(crate) shape: PointShape<'a>,
     pub(crate) offset: Vector2<f32>,
+    pub(crate) placement: Option<LabelPlacement>,
+}
+
+/// Parameters of screen collision detection of a label.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LabelPlacement {
+    pub priority: f32,
+    pub allow_overlap: bool,
+    /// The point is a part of the label of the previous point primitive of the feature, e.g. a glyph of a label
+    /// placed along a line. All parts of a label are shown or hidden together.
+    pub continues_previous: bool,
 }
 
 impl<'a> PointPaint<'a> {
@@ -19,6 +32,7 @@ impl<'a> PointPaint<'a> {
     pub fn circle(color: Color, diameter: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            placement: None,
             shape: PointShape::Circle {
                 fill: color.into(),
                 radius: diameter / 2.0,
@@ -31,6 +45,7 @@ impl<'a> PointPaint<'a> {
     pub fn sector(color: Color, diameter: f32, start_angle: f32, end_angle: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            placement: None,
             shape: PointShape::Sector(SectorParameters {
                 fill: color.into(),
                 radius: diameter / 2.0,
@@ -45,10 +60,12 @@ impl<'a> PointPaint<'a> {
     pub fn square(color: Color, size: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            placement: None,
             shape: PointShape::Square {
                 fill: color,
                 size,
                 outline: None,
+                rotation: 0.0,
             },
         }
     }
@@ -57,6 +74,7 @@ impl<'a> PointPaint<'a> {
     pub fn dot(color: Color) -> Self {
         Self {
             offset: Vector2::default(),
+            placement: None,
             shape: PointShape::Dot { color },
         }
     }
@@ -65,11 +83,12 @@ impl<'a> PointPaint<'a> {
     pub fn shape(color: Color, contour: &'a ClosedContour<Point2<f32>>, scale: f32) -> Self {
         Self {
             offset: Vector2::default(),
+            placement: None,
             shape: PointShape::FreeShape {
                 fill: color,
                 scale,
                 outline: None,
-                shape: contour,
+                shape: Cow::Borrowed(contour),
             },
         }
     }
@@ -81,15 +100,52 @@ impl<'a> PointPaint<'a> {
         let height = image.dimensions.1 as f32 * scale;
         Self {
             offset,
+            placement: None,
             shape: PointShape::Image {
                 image,
                 opacity: 255,
                 width,
                 height,
+                map_rotation: None,
             },
         }
     }
 
+    /// Creates a paint that draws a text label with the given style. The size of the label is fixed on the screen and
+    /// does not depend on map resolution.
+    pub fn label(text: &str, style: &TextStyle) -> Self {
+        let (image, offset) = style.rasterize(text);
+        Self {
+            placement: Some(LabelPlacement {
+                priority: style.priority,
+                allow_overlap: style.allow_overlap,
+                continues_previous: false,
+            }),
+            ..Self::image(image, offset, 1.0)
+        }
+    }
+
+    /// Creates a paint that draws a glyph of a label placed along a line. The glyph is rotated by the `rotation`
+    /// (radians, counterclockwise) relative to the map and turns together with the map.
+    pub(crate) fn label_glyph(
+        glyph: &LabelGlyph,
+        rotation: f32,
+        style: &TextStyle,
+        continues_previous: bool,
+    ) -> Self {
+        let mut paint = Self::image(glyph.image.clone(), glyph.offset, 1.0);
+        if let PointShape::Image { map_rotation, .. } = &mut paint.shape {
+            *map_rotation = Some(rotation);
+        }
+        paint.placement = Some(LabelPlacement {
+            priority: style.priority,
+            allow_overlap: style.allow_overlap,
+            continues_previous,
+        });
+
+        paint
+    }
+
     /// Sets an outline for the symbol (if applicable).
     pub fn with_outline(mut self, color: Color, width: f32) -> Self {
         match &mut self.shape {
@@ -101,6 +157,9 @@ impl<'a> PointPaint<'a> {
                     width: width as f64,
                     offset: 0.0,
                     line_cap: LineCap::Round,
+                    line_join: LineJoin::Round,
+                    dash: None,
+                    arrows: None,
                 })
             }
             _ => {}
@@ -108,6 +167,74 @@ impl<'a> PointPaint<'a> {
 
         self
     }
+
+    /// Rotates the shape by the `angle` (radians, counterclockwise) on the screen (if applicable). Only squares can be
+    /// rotated.
+    pub fn with_rotation(mut self, angle: f32) -> Self {
+        if let PointShape::Square { rotation, .. } = &mut self.shape {
+            *rotation = angle;
+        }
+
+        self
+    }
+
+    /// Converts the paint into one that does not borrow the shape contour, so that it can be sent to another thread.
+    pub(crate) fn into_owned(self) -> PointPaint<'static> {
+        let shape = match self.shape {
+            PointShape::Dot { color } => PointShape::Dot { color },
+            PointShape::Circle {
+                fill,
+                radius,
+                outline,
+            } => PointShape::Circle {
+                fill,
+                radius,
+                outline,
+            },
+            PointShape::Sector(parameters) => PointShape::Sector(parameters),
+            PointShape::Square {
+                fill,
+                size,
+                outline,
+                rotation,
+            } => PointShape::Square {
+                fill,
+                size,
+                outline,
+                rotation,
+            },
+            PointShape::FreeShape {
+                fill,
+                scale,
+                outline,
+                shape,
+            } => PointShape::FreeShape {
+                fill,
+                scale,
+                outline,
+                shape: Cow::Owned(shape.into_owned()),
+            },
+            PointShape::Image {
+                image,
+                opacity,
+                width,
+                height,
+                map_rotation,
+            } => PointShape::Image {
+                image,
+                opacity,
+                width,
+                height,
+                map_rotation,
+            },
+        };
+
+        PointPaint {
+            shape,
+            offset: self.offset,
+            placement: self.placement,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -125,18 +252,23 @@ pub(crate) enum PointShape<'a> {
         fill: Color,
         size: f32,
         outline: Option<LinePaint>,
+        /// Rotation of the square on the screen (radians, counterclockwise).
+        rotation: f32,
     },
     FreeShape {
         fill: Color,
         scale: f32,
         outline: Option<LinePaint>,
-        shape: &'a ClosedContour<Point2<f32>>,
+        shape: Cow<'a, ClosedContour<Point2<f32>>>,
     },
     Image {
         image: Arc<DecodedImage>,
         opacity: u8,
         width: f32,
         height: f32,
+        /// If set, the image is rotated by this angle (radians, counterclockwise) relative to the map and turns
+        /// together with the map. Otherwise, the image is aligned with the screen.
+        map_rotation: Option<f32>,
     },
 }
 
@@ -11,6 +11,7 @@ use galileo_types::contour::Contour;
 use galileo_types::Polygon;
 use num_traits::AsPrimitive;
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 pub(crate) mod tessellating;
 
@@ -75,6 +76,21 @@ impl RenderBundle {
         }
     }
 
+    /// Moves all primitives of the `other` bundle into this one, drawing them as if they were added to this bundle
+    /// after its own primitives. Returns the new ids of the primitives by their ids in the `other` bundle.
+    pub(crate) fn append(&mut self, other: RenderBundle) -> HashMap<PrimitiveId, PrimitiveId> {
+        let new_ids = match (&mut self.0, other.0) {
+            (RenderBundleType::Tessellating(inner), RenderBundleType::Tessellating(other)) => {
+                inner.append(other)
+            }
+        };
+        new_ids
+            .into_iter()
+            .enumerate()
+            .filter_map(|(id, new_id)| Some((PrimitiveId(id), new_id?)))
+            .collect()
+    }
+
     /// Removes the primitive from the bundle.
     pub fn remove(&mut self, primitive_id: PrimitiveId) -> Result<(), GalileoError> {
         match &mut self.0 {
@@ -184,4 +200,24 @@ where
     pub fn new_polygon_ref(polygon: &'a Poly, paint: PolygonPaint) -> Self {
         Self::Polygon(Cow::Borrowed(polygon), paint)
     }
+
+    /// Converts the primitive into one that does not borrow any data, so that it can be sent to another thread.
+    pub(crate) fn into_owned(self) -> RenderPrimitive<'static, N, P, C, Poly>
+    where
+        P: 'static,
+        C: 'static,
+        Poly: 'static,
+    {
+        match self {
+            Self::Point(point, paint) => {
+                RenderPrimitive::Point(Cow::Owned(point.into_owned()), paint.into_owned())
+            }
+            Self::Contour(contour, paint) => {
+                RenderPrimitive::Contour(Cow::Owned(contour.into_owned()), paint)
+            }
+            Self::Polygon(polygon, paint) => {
+                RenderPrimitive::Polygon(Cow::Owned(polygon.into_owned()), paint)
+            }
+        }
+    }
 }
@@ -2,7 +2,10 @@ use crate::decoded_image::DecodedImage;
 use crate::error::GalileoError;
 use crate::render::point_paint::{CircleFill, PointPaint, PointShape, SectorParameters};
 use crate::render::render_bundle::RenderPrimitive;
-use crate::render::{ImagePaint, LinePaint, PolygonPaint, PrimitiveId};
+use crate::render::{
+    DashPattern, HatchFill, ImagePaint, LineArrows, LineJoin, LinePaint, PolygonFill, PolygonPaint,
+    PrimitiveId,
+};
 use crate::view::MapView;
 use crate::Color;
 use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2d, Point3d};
@@ -10,15 +13,15 @@ use galileo_types::contour::Contour;
 use galileo_types::impls::ClosedContour;
 use galileo_types::Polygon;
 use lyon::lyon_tessellation::{
-    BuffersBuilder, FillOptions, FillTessellator, FillVertex, FillVertexConstructor, LineJoin,
-    Side, StrokeOptions, StrokeTessellator, StrokeVertex, StrokeVertexConstructor, VertexBuffers,
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex, FillVertexConstructor, Side,
+    StrokeOptions, StrokeTessellator, StrokeVertex, StrokeVertexConstructor, VertexBuffers,
 };
 use lyon::math::point;
 use lyon::path::builder::PathBuilder;
 use lyon::path::path::BuilderWithAttributes;
 use lyon::path::{EndpointId, Path};
 use lyon::tessellation::VertexSource;
-use nalgebra::{Point2, Vector2};
+use nalgebra::{Point2, Rotation2, Vector2};
 use num_traits::AsPrimitive;
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
@@ -29,7 +32,16 @@ use std::sync::Arc;
 #[derive(Debug, Clone)]
 pub(crate) struct TessellatingRenderBundle {
     pub poly_tessellation: VertexBuffers<PolyVertex, u32>,
+    /// Polygons that do not lie in the ground plane. They are drawn with depth test, so they correctly cover
+    /// each other.
+    pub extrusion_tessellation: VertexBuffers<PolyVertex, u32>,
+    /// Polygons filled with hatches.
+    pub hatch_tessellation: VertexBuffers<PatternVertex, u32>,
+    /// Polygons filled with pattern images. Every polygon is drawn separately with the texture of its image.
+    pub pattern_fills: Vec<PatternFillInfo>,
     pub points: Vec<PointInstance>,
+    /// Circles and squares drawn with one instanced quad each instead of their geometry.
+    pub markers: Vec<MarkerInstance>,
     pub screen_ref: ScreenRefTessellation,
     pub images: Vec<ImageInfo>,
     pub clip_area: Option<VertexBuffers<PolyVertex, u32>>,
@@ -38,6 +50,8 @@ pub(crate) struct TessellatingRenderBundle {
     vacant_ids: Vec<usize>,
     vacant_image_ids: Vec<usize>,
     vacant_image_store_ids: Vec<usize>,
+    vacant_pattern_ids: Vec<usize>,
+    vacant_marker_ids: Vec<usize>,
     buffer_size: usize,
 }
 
@@ -53,22 +67,35 @@ pub(crate) enum ImageInfo {
     Image((usize, [ImageVertex; 4])),
 }
 
+#[derive(Debug, Clone)]
+pub(crate) enum PatternFillInfo {
+    Vacant,
+    Fill {
+        image_store_index: usize,
+        tessellation: VertexBuffers<PatternVertex, u32>,
+    },
+}
+
 pub(crate) type ScreenRefTessellation = VertexBuffers<ScreenRefVertex, u32>;
 
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub(crate) struct ScreenRefVertex {
-    position: [f32; 3],
-    normal: [f32; 2],
-    color: [u8; 4],
+    pub position: [f32; 3],
+    pub normal: [f32; 2],
+    pub color: [u8; 4],
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) enum PrimitiveInfo {
     Vacant,
     MapRef { vertex_range: Range<usize> },
+    Extrusion { vertex_range: Range<usize> },
     ScreenRef { vertex_range: Range<usize> },
+    Hatch { vertex_range: Range<usize> },
+    Pattern { pattern_index: usize },
     Dot { point_index: usize },
+    Marker { marker_index: usize },
     Image { image_index: usize },
 }
 
@@ -82,7 +109,11 @@ impl TessellatingRenderBundle {
     pub fn new() -> Self {
         Self {
             poly_tessellation: VertexBuffers::new(),
+            extrusion_tessellation: VertexBuffers::new(),
+            hatch_tessellation: VertexBuffers::new(),
+            pattern_fills: Vec::new(),
             points: Vec::new(),
+            markers: Vec::new(),
             screen_ref: VertexBuffers::new(),
             images: Vec::new(),
             primitives: Vec::new(),
@@ -91,6 +122,8 @@ impl TessellatingRenderBundle {
             vacant_ids: vec![],
             vacant_image_ids: vec![],
             vacant_image_store_ids: vec![],
+            vacant_pattern_ids: vec![],
+            vacant_marker_ids: vec![],
             buffer_size: 0,
         }
     }
@@ -111,6 +144,7 @@ impl TessellatingRenderBundle {
             polygon,
             PolygonPaint {
                 color: Color::BLACK,
+                fill: PolygonFill::Solid,
             },
             &mut tessellation,
         );
@@ -138,24 +172,28 @@ impl TessellatingRenderBundle {
                 opacity,
                 tex_coords: [0.0, 1.0],
                 offset: [0.0, 0.0],
+                map_aligned: 0.0,
             },
             ImageVertex {
                 position: [vertices[1].x() as f32, vertices[1].y() as f32],
                 opacity,
                 tex_coords: [0.0, 0.0],
                 offset: [0.0, 0.0],
+                map_aligned: 0.0,
             },
             ImageVertex {
                 position: [vertices[3].x() as f32, vertices[3].y() as f32],
                 opacity,
                 tex_coords: [1.0, 1.0],
                 offset: [0.0, 0.0],
+                map_aligned: 0.0,
             },
             ImageVertex {
                 position: [vertices[2].x() as f32, vertices[2].y() as f32],
                 opacity,
                 tex_coords: [1.0, 0.0],
                 offset: [0.0, 0.0],
+                map_aligned: 0.0,
             },
         ];
 
@@ -172,9 +210,9 @@ impl TessellatingRenderBundle {
         position: &P,
         image: Arc<DecodedImage>,
         opacity: u8,
-        width: f32,
-        height: f32,
+        [width, height]: [f32; 2],
         offset: Vector2<f32>,
+        map_rotation: Option<f32>,
     ) -> PrimitiveInfo
     where
         N: AsPrimitive<f32>,
@@ -187,33 +225,22 @@ impl TessellatingRenderBundle {
         let position = [position.x().as_(), position.y().as_()];
         let offset_x = -offset[0] * width;
         let offset_y = offset[1] * height;
+        let (sin, cos) = map_rotation.unwrap_or(0.0).sin_cos();
+        let map_aligned = if map_rotation.is_some() { 1.0 } else { 0.0 };
+        let vertex = |tex_coords: [f32; 2], [x, y]: [f32; 2]| ImageVertex {
+            position,
+            opacity,
+            tex_coords,
+            offset: [x * cos - y * sin, x * sin + y * cos],
+            map_aligned,
+        };
 
         let index = self.add_image_to_store(image);
         let vertices = [
-            ImageVertex {
-                position,
-                opacity,
-                tex_coords: [0.0, 1.0],
-                offset: [offset_x, offset_y - height],
-            },
-            ImageVertex {
-                position,
-                opacity,
-                tex_coords: [0.0, 0.0],
-                offset: [offset_x, offset_y],
-            },
-            ImageVertex {
-                position,
-                opacity,
-                tex_coords: [1.0, 1.0],
-                offset: [offset_x + width, offset_y - height],
-            },
-            ImageVertex {
-                position,
-                opacity,
-                tex_coords: [1.0, 0.0],
-                offset: [offset_x + width, offset_y],
-            },
+            vertex([0.0, 1.0], [offset_x, offset_y - height]),
+            vertex([0.0, 0.0], [offset_x, offset_y]),
+            vertex([1.0, 1.0], [offset_x + width, offset_y - height]),
+            vertex([1.0, 0.0], [offset_x + width, offset_y]),
         ];
 
         let image_index = self.add_image_info(index, vertices);
@@ -266,6 +293,133 @@ impl TessellatingRenderBundle {
         }
     }
 
+    /// Moves all primitives of the `other` bundle into this one. Returns the new ids of the primitives, indexed by
+    /// their ids in the `other` bundle. Removed primitives of the `other` bundle have no new ids.
+    pub fn append(&mut self, other: Self) -> Vec<Option<PrimitiveId>> {
+        let poly_offset =
+            Self::append_tessellation(&mut self.poly_tessellation, other.poly_tessellation);
+        let extrusion_offset = Self::append_tessellation(
+            &mut self.extrusion_tessellation,
+            other.extrusion_tessellation,
+        );
+        let hatch_offset =
+            Self::append_tessellation(&mut self.hatch_tessellation, other.hatch_tessellation);
+        let screen_ref_offset = Self::append_tessellation(&mut self.screen_ref, other.screen_ref);
+
+        let point_offset = self.points.len();
+        self.points.extend(other.points);
+
+        let image_store_indices: Vec<_> = other
+            .image_store
+            .into_iter()
+            .map(|info| match info {
+                ImageStoreInfo::Vacant => None,
+                ImageStoreInfo::Image(image) => Some(self.add_image_to_store(image)),
+            })
+            .collect();
+        let image_indices: Vec<_> = other
+            .images
+            .into_iter()
+            .map(|info| match info {
+                ImageInfo::Vacant => None,
+                ImageInfo::Image((store_index, vertices)) => {
+                    let store_index = image_store_indices[store_index]?;
+                    Some(self.add_image_info(store_index, vertices))
+                }
+            })
+            .collect();
+        let pattern_indices: Vec<_> = other
+            .pattern_fills
+            .into_iter()
+            .map(|info| match info {
+                PatternFillInfo::Vacant => None,
+                PatternFillInfo::Fill {
+                    image_store_index,
+                    tessellation,
+                } => {
+                    let info = PatternFillInfo::Fill {
+                        image_store_index: image_store_indices[image_store_index]?,
+                        tessellation,
+                    };
+                    Some(if let Some(index) = self.vacant_pattern_ids.pop() {
+                        self.pattern_fills[index] = info;
+                        index
+                    } else {
+                        self.pattern_fills.push(info);
+                        self.pattern_fills.len() - 1
+                    })
+                }
+            })
+            .collect();
+        let marker_indices: Vec<_> = other
+            .markers
+            .into_iter()
+            .map(|marker| {
+                if marker.is_vacant() {
+                    None
+                } else if let Some(index) = self.vacant_marker_ids.pop() {
+                    self.markers[index] = marker;
+                    Some(index)
+                } else {
+                    self.markers.push(marker);
+                    Some(self.markers.len() - 1)
+                }
+            })
+            .collect();
+
+        let shift = |range: Range<usize>, offset: usize| range.start + offset..range.end + offset;
+        let ids = other
+            .primitives
+            .into_iter()
+            .map(|info| {
+                let info = match info {
+                    PrimitiveInfo::Vacant => return None,
+                    PrimitiveInfo::MapRef { vertex_range } => PrimitiveInfo::MapRef {
+                        vertex_range: shift(vertex_range, poly_offset),
+                    },
+                    PrimitiveInfo::Extrusion { vertex_range } => PrimitiveInfo::Extrusion {
+                        vertex_range: shift(vertex_range, extrusion_offset),
+                    },
+                    PrimitiveInfo::ScreenRef { vertex_range } => PrimitiveInfo::ScreenRef {
+                        vertex_range: shift(vertex_range, screen_ref_offset),
+                    },
+                    PrimitiveInfo::Hatch { vertex_range } => PrimitiveInfo::Hatch {
+                        vertex_range: shift(vertex_range, hatch_offset),
+                    },
+                    PrimitiveInfo::Pattern { pattern_index } => PrimitiveInfo::Pattern {
+                        pattern_index: pattern_indices[pattern_index]?,
+                    },
+                    PrimitiveInfo::Dot { point_index } => PrimitiveInfo::Dot {
+                        point_index: point_index + point_offset,
+                    },
+                    PrimitiveInfo::Marker { marker_index } => PrimitiveInfo::Marker {
+                        marker_index: marker_indices[marker_index]?,
+                    },
+                    PrimitiveInfo::Image { image_index } => PrimitiveInfo::Image {
+                        image_index: image_indices[image_index]?,
+                    },
+                };
+                Some(self.add_primitive_info(info))
+            })
+            .collect();
+
+        self.buffer_size += other.buffer_size;
+        ids
+    }
+
+    /// Appends the vertices and indices of `other` to `tessellation`. Returns the offset of the appended vertices.
+    fn append_tessellation<T>(
+        tessellation: &mut VertexBuffers<T, u32>,
+        other: VertexBuffers<T, u32>,
+    ) -> usize {
+        let offset = tessellation.vertices.len();
+        tessellation.vertices.extend(other.vertices);
+        tessellation
+            .indices
+            .extend(other.indices.into_iter().map(|index| index + offset as u32));
+        offset
+    }
+
     pub fn add<N, P, C, Poly>(
         &mut self,
         primitive: RenderPrimitive<N, P, C, Poly>,
@@ -311,13 +465,115 @@ impl TessellatingRenderBundle {
 
         match info {
             PrimitiveInfo::MapRef { vertex_range } => {
-                self.update_map_ref(vertex_range.clone(), primitive)
+                Self::update_map_ref(&mut self.poly_tessellation, vertex_range.clone(), primitive)
+            }
+            PrimitiveInfo::Extrusion { vertex_range } => Self::update_map_ref(
+                &mut self.extrusion_tessellation,
+                vertex_range.clone(),
+                primitive,
+            ),
+            PrimitiveInfo::Hatch { vertex_range } => {
+                let RenderPrimitive::Polygon(
+                    _,
+                    PolygonPaint {
+                        color,
+                        fill: PolygonFill::Hatch(hatch),
+                    },
+                ) = primitive
+                else {
+                    return Err(GalileoError::Generic(
+                        "hatched polygon can only be updated with a hatch fill".into(),
+                    ));
+                };
+
+                let pattern = hatch_parameters(&hatch);
+                for vertex in &mut self.hatch_tessellation.vertices[vertex_range.clone()] {
+                    vertex.color = color.to_f32_array();
+                    vertex.pattern = pattern;
+                }
+
+                Ok(())
+            }
+            PrimitiveInfo::Pattern { pattern_index } => {
+                let RenderPrimitive::Polygon(
+                    _,
+                    PolygonPaint {
+                        color,
+                        fill: PolygonFill::Pattern(_),
+                    },
+                ) = primitive
+                else {
+                    return Err(GalileoError::Generic(
+                        "patterned polygon can only be updated with a pattern fill".into(),
+                    ));
+                };
+
+                if let Some(PatternFillInfo::Fill { tessellation, .. }) =
+                    self.pattern_fills.get_mut(*pattern_index)
+                {
+                    for vertex in &mut tessellation.vertices {
+                        vertex.color = color.to_f32_array();
+                    }
+                }
+
+                Ok(())
+            }
+            PrimitiveInfo::Marker { marker_index } => {
+                let RenderPrimitive::Point(_, paint) = &primitive else {
+                    return Err(GalileoError::Generic(
+                        "point marker can only be updated with a point".into(),
+                    ));
+                };
+                let Some(update) = MarkerInstance::from_paint(paint) else {
+                    return self.replace_point(primitive_id, primitive);
+                };
+
+                let marker = &mut self.markers[*marker_index];
+                *marker = MarkerInstance {
+                    position: marker.position,
+                    ..update
+                };
+
+                Ok(())
             }
+            PrimitiveInfo::ScreenRef { .. }
+            | PrimitiveInfo::Dot { .. }
+            | PrimitiveInfo::Image { .. } => self.replace_point(primitive_id, primitive),
             PrimitiveInfo::Vacant => Ok(()),
-            _ => todo!(),
         }
     }
 
+    /// Replaces the point primitive with a new one keeping its id. Used for the points that are tessellated with their
+    /// paint, so their style cannot be updated in place. The new point is drawn on top of the other points of the
+    /// bundle.
+    fn replace_point<N, P, C, Poly>(
+        &mut self,
+        primitive_id: PrimitiveId,
+        primitive: RenderPrimitive<N, P, C, Poly>,
+    ) -> Result<(), GalileoError>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N> + Clone,
+        C: Contour<Point = P> + Clone,
+        Poly: Polygon + Clone,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let RenderPrimitive::Point(point, paint) = primitive else {
+            return Err(GalileoError::Generic(
+                "point can only be updated with a point".into(),
+            ));
+        };
+
+        self.remove(primitive_id)?;
+        let PrimitiveId(new_id) = self.add_point::<N, P>(point.borrow(), paint);
+        if new_id != primitive_id.0 {
+            self.primitives.swap(primitive_id.0, new_id);
+            self.vacant_ids.push(new_id);
+        }
+
+        Ok(())
+    }
+
     pub fn remove(&mut self, primitive_id: PrimitiveId) -> Result<(), GalileoError> {
         if primitive_id.0 >= self.primitives.len() {
             return Err(GalileoError::Generic(
@@ -329,8 +585,12 @@ impl TessellatingRenderBundle {
 
         match info {
             PrimitiveInfo::MapRef { vertex_range } => self.remove_map_ref(vertex_range),
+            PrimitiveInfo::Extrusion { vertex_range } => self.remove_extrusion(vertex_range),
             PrimitiveInfo::ScreenRef { vertex_range } => self.remove_screen_ref(vertex_range),
+            PrimitiveInfo::Hatch { vertex_range } => self.remove_hatch(vertex_range),
+            PrimitiveInfo::Pattern { pattern_index } => self.remove_pattern(pattern_index),
             PrimitiveInfo::Dot { point_index } => self.remove_dot(point_index),
+            PrimitiveInfo::Marker { marker_index } => self.remove_marker(marker_index),
             PrimitiveInfo::Image { image_index } => self.remove_image(image_index),
             PrimitiveInfo::Vacant => Ok(()),
         }
@@ -353,12 +613,7 @@ impl TessellatingRenderBundle {
                 }
             };
 
-            let stored_image_unused = self.images.iter().all(|info| match info {
-                ImageInfo::Vacant => false,
-                ImageInfo::Image((i, _)) => *i != image_id,
-            });
-
-            if stored_image_unused {
+            if !self.is_stored_image_used(image_id) {
                 match std::mem::replace(&mut self.image_store[image_id], ImageStoreInfo::Vacant) {
                     ImageStoreInfo::Vacant => {
                         // this should not happen
@@ -386,6 +641,68 @@ impl TessellatingRenderBundle {
         }
     }
 
+    fn remove_pattern(&mut self, index: usize) -> Result<(), GalileoError> {
+        let Some(info) = self.pattern_fills.get_mut(index) else {
+            return Err(GalileoError::Generic("index out of bounds".into()));
+        };
+
+        let PatternFillInfo::Fill {
+            image_store_index,
+            tessellation,
+        } = std::mem::replace(info, PatternFillInfo::Vacant)
+        else {
+            return Err(GalileoError::Generic(
+                "tried to replace vacant pattern with vacant slot".into(),
+            ));
+        };
+
+        self.vacant_pattern_ids.push(index);
+        self.buffer_size -= size_of::<PatternVertex>() * tessellation.vertices.len()
+            + size_of::<u32>() * tessellation.indices.len();
+
+        if !self.is_stored_image_used(image_store_index) {
+            if let ImageStoreInfo::Image(image) = std::mem::replace(
+                &mut self.image_store[image_store_index],
+                ImageStoreInfo::Vacant,
+            ) {
+                self.vacant_image_store_ids.push(image_store_index);
+                self.buffer_size -= image.bytes.len();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the image in the store is drawn by any image or pattern primitive.
+    fn is_stored_image_used(&self, image_store_index: usize) -> bool {
+        let used_by_image = self.images.iter().any(|info| match info {
+            ImageInfo::Vacant => false,
+            ImageInfo::Image((i, _)) => *i == image_store_index,
+        });
+        let used_by_pattern = self.pattern_fills.iter().any(|info| match info {
+            PatternFillInfo::Vacant => false,
+            PatternFillInfo::Fill {
+                image_store_index: i,
+                ..
+            } => *i == image_store_index,
+        });
+
+        used_by_image || used_by_pattern
+    }
+
+    /// Marker slots are not removed, so that the indices of the other markers stay the same. Vacant slots are not
+    /// drawn and are reused by the next added markers.
+    fn remove_marker(&mut self, index: usize) -> Result<(), GalileoError> {
+        let Some(marker) = self.markers.get_mut(index) else {
+            return Err(GalileoError::Generic("index out of bounds".into()));
+        };
+
+        *marker = MarkerInstance::VACANT;
+        self.vacant_marker_ids.push(index);
+
+        Ok(())
+    }
+
     fn remove_dot(&mut self, index: usize) -> Result<(), GalileoError> {
         if index >= self.points.len() {
             Err(GalileoError::Generic("index out of bounds".into()))
@@ -452,6 +769,49 @@ impl TessellatingRenderBundle {
         Ok(())
     }
 
+    fn remove_hatch(&mut self, range: Range<usize>) -> Result<(), GalileoError> {
+        let removed_index_count =
+            Self::remove_from_tessellation(&mut self.hatch_tessellation, range.clone())?;
+        let len = range.len();
+        self.buffer_size -=
+            size_of::<PatternVertex>() * len + size_of::<u32>() * removed_index_count;
+
+        for info in &mut self.primitives {
+            match info {
+                PrimitiveInfo::Hatch {
+                    ref mut vertex_range,
+                } if vertex_range.start >= range.end => {
+                    vertex_range.start -= len;
+                    vertex_range.end -= len;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remove_extrusion(&mut self, range: Range<usize>) -> Result<(), GalileoError> {
+        let removed_index_count =
+            Self::remove_from_tessellation(&mut self.extrusion_tessellation, range.clone())?;
+        let len = range.len();
+        self.buffer_size -= size_of::<PolyVertex>() * len + size_of::<u32>() * removed_index_count;
+
+        for info in &mut self.primitives {
+            match info {
+                PrimitiveInfo::Extrusion {
+                    ref mut vertex_range,
+                } if vertex_range.start >= range.end => {
+                    vertex_range.start -= len;
+                    vertex_range.end -= len;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
     fn remove_from_tessellation<T>(
         tessellation: &mut VertexBuffers<T, u32>,
         range: Range<usize>,
@@ -489,6 +849,14 @@ impl TessellatingRenderBundle {
         N: AsPrimitive<f32>,
         P: CartesianPoint3d<Num = N>,
     {
+        if let Some(marker) = MarkerInstance::from_paint(&paint) {
+            let marker_index = self.add_marker(MarkerInstance {
+                position: [point.x().as_(), point.y().as_(), point.z().as_()],
+                ..marker
+            });
+            return self.add_primitive_info(PrimitiveInfo::Marker { marker_index });
+        }
+
         let start_index = self.screen_ref.vertices.len();
         let info = match &paint.shape {
             PointShape::Dot { color } => {
@@ -502,13 +870,14 @@ impl TessellatingRenderBundle {
                 opacity,
                 width,
                 height,
+                map_rotation,
             } => self.add_image_point(
                 point,
                 image.clone(),
                 *opacity,
-                *width,
-                *height,
+                [*width, *height],
                 paint.offset,
+                *map_rotation,
             ),
             PointShape::Circle {
                 fill,
@@ -530,8 +899,16 @@ impl TessellatingRenderBundle {
                 fill,
                 size,
                 outline,
+                rotation,
             } => {
-                self.add_shape(point, *fill, *size, *outline, &square_shape(), paint.offset);
+                let shape = ClosedContour::new(
+                    square_shape()
+                        .points
+                        .iter()
+                        .map(|point| Rotation2::new(*rotation) * point)
+                        .collect(),
+                );
+                self.add_shape(point, *fill, *size, *outline, &shape, paint.offset);
                 PrimitiveInfo::ScreenRef {
                     vertex_range: start_index..self.screen_ref.vertices.len(),
                 }
@@ -581,59 +958,91 @@ impl TessellatingRenderBundle {
         P: CartesianPoint3d<Num = N>,
         C: Contour<Point = P>,
     {
-        let tessellation = &mut self.poly_tessellation;
-        let mut path_builder = BuilderWithAttributes::new(1);
-        let mut iterator = line.iter_points();
-
-        let Some(first_point) = iterator.next() else {
+        let resolution = min_resolution as f32;
+        let mut points: Vec<LinePoint> = line
+            .iter_points()
+            .map(|p| LinePoint {
+                position: Vector2::new(p.x().as_() / resolution, p.y().as_() / resolution),
+                z: p.z().as_(),
+            })
+            .collect();
+        if points.is_empty() {
             return 0..0;
-        };
-
-        let _ = path_builder.begin(
-            point(
-                first_point.x().as_() / min_resolution as f32,
-                first_point.y().as_() / min_resolution as f32,
-            ),
-            &[first_point.z().as_()],
-        );
-
-        for p in iterator {
-            let _ = path_builder.line_to(
-                point(
-                    p.x().as_() / min_resolution as f32,
-                    p.y().as_() / min_resolution as f32,
-                ),
-                &[p.z().as_()],
-            );
         }
 
-        path_builder.end(line.is_closed());
-        let path = path_builder.build();
-
-        let vertex_constructor = LineVertexConstructor {
-            width: paint.width as f32,
-            offset: paint.offset as f32,
-            color: paint.color.to_f32_array(),
-            resolution: min_resolution as f32,
-            path: &path,
+        let is_closed = line.is_closed();
+        let parts = match paint.dash {
+            Some(dash) => {
+                if is_closed {
+                    points.push(points[0]);
+                }
+                dash_line(&points, &dash)
+            }
+            None => vec![points.clone()],
         };
 
-        let mut tesselator = StrokeTessellator::new();
+        let tessellation = &mut self.poly_tessellation;
         let start_index = tessellation.vertices.len();
         let start_index_count = tessellation.indices.len();
 
-        if let Err(err) = tesselator.tessellate_path(
-            &path,
-            &StrokeOptions::DEFAULT
-                .with_line_cap(paint.line_cap.into())
-                .with_line_width(paint.width as f32)
-                .with_miter_limit(1.0)
-                .with_tolerance(0.1)
-                .with_line_join(LineJoin::Round),
-            &mut BuffersBuilder::new(tessellation, vertex_constructor),
-        ) {
-            log::error!("Tessellation failed: {err}");
-            return 0..0;
+        if paint.width > 0.0 {
+            let mut path_builder = BuilderWithAttributes::new(1);
+            for part in &parts {
+                let Some((first_point, rest)) = part.split_first() else {
+                    continue;
+                };
+
+                let _ = path_builder.begin(
+                    point(first_point.position.x, first_point.position.y),
+                    &[first_point.z],
+                );
+                for p in rest {
+                    let _ = path_builder.line_to(point(p.position.x, p.position.y), &[p.z]);
+                }
+                path_builder.end(is_closed && paint.dash.is_none());
+            }
+            let path = path_builder.build();
+
+            let vertex_constructor = LineVertexConstructor {
+                width: paint.width as f32,
+                offset: paint.offset as f32,
+                color: paint.color.to_f32_array(),
+                resolution,
+                path: &path,
+            };
+
+            let miter_limit = match paint.line_join {
+                LineJoin::Miter => 4.0,
+                _ => 1.0,
+            };
+
+            let mut tesselator = StrokeTessellator::new();
+            if let Err(err) = tesselator.tessellate_path(
+                &path,
+                &StrokeOptions::DEFAULT
+                    .with_line_cap(paint.line_cap.into())
+                    .with_line_width(paint.width as f32)
+                    .with_miter_limit(miter_limit)
+                    .with_tolerance(0.1)
+                    .with_line_join(paint.line_join.into()),
+                &mut BuffersBuilder::new(tessellation, vertex_constructor),
+            ) {
+                log::error!("Tessellation failed: {err}");
+                tessellation.vertices.truncate(start_index);
+                tessellation.indices.truncate(start_index_count);
+                return 0..0;
+            }
+        }
+
+        if let Some(arrows) = paint.arrows {
+            if is_closed && paint.dash.is_none() {
+                points.push(points[0]);
+            }
+
+            let color = paint.color.to_f32_array();
+            for (anchor, direction) in arrow_anchors(&points, &arrows, is_closed) {
+                add_arrow(tessellation, anchor, direction, &arrows, color, resolution);
+            }
         }
 
         let end_index = tessellation.vertices.len();
@@ -656,8 +1065,94 @@ impl TessellatingRenderBundle {
         Poly: Polygon,
         Poly::Contour: Contour<Point = P>,
     {
-        let vertex_range = self.add_polygon_lod(polygon, paint, min_resolution as f32);
-        self.add_primitive_info(PrimitiveInfo::MapRef { vertex_range })
+        let is_flat = polygon
+            .iter_contours()
+            .all(|contour| contour.iter_points().all(|p| p.z().as_() == 0.0));
+        if !is_flat {
+            let vertex_range = self.add_extrusion(polygon, paint);
+            return self.add_primitive_info(PrimitiveInfo::Extrusion { vertex_range });
+        }
+
+        match &paint.fill {
+            PolygonFill::Solid => {
+                let vertex_range = self.add_polygon_lod(polygon, paint, min_resolution as f32);
+                self.add_primitive_info(PrimitiveInfo::MapRef { vertex_range })
+            }
+            PolygonFill::Hatch(hatch) => {
+                let vertex_range = self.add_hatch(polygon, paint.color, hatch);
+                self.add_primitive_info(PrimitiveInfo::Hatch { vertex_range })
+            }
+            PolygonFill::Pattern(image) => {
+                let pattern_index = self.add_pattern(polygon, paint.color, image.clone());
+                self.add_primitive_info(PrimitiveInfo::Pattern { pattern_index })
+            }
+        }
+    }
+
+    fn add_hatch<N, P, Poly>(
+        &mut self,
+        polygon: &Poly,
+        color: Color,
+        hatch: &HatchFill,
+    ) -> Range<usize>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+        Poly: Polygon,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let tessellation = &mut self.hatch_tessellation;
+        let start_index = tessellation.vertices.len();
+        let start_index_count = tessellation.indices.len();
+
+        Self::tessellate_pattern_polygon(polygon, color, hatch_parameters(hatch), tessellation);
+
+        let end_index = tessellation.vertices.len();
+
+        self.buffer_size += (end_index - start_index) * size_of::<PatternVertex>();
+        self.buffer_size += (tessellation.indices.len() - start_index_count) * size_of::<u32>();
+
+        start_index..end_index
+    }
+
+    fn add_pattern<N, P, Poly>(
+        &mut self,
+        polygon: &Poly,
+        color: Color,
+        image: Arc<DecodedImage>,
+    ) -> usize
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+        Poly: Polygon,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let mut tessellation = VertexBuffers::new();
+        Self::tessellate_pattern_polygon(polygon, color, [0.0; 4], &mut tessellation);
+
+        self.buffer_size += tessellation.vertices.len() * size_of::<PatternVertex>()
+            + tessellation.indices.len() * size_of::<u32>();
+
+        // The size of the image is counted once while it is in the store, since it is released with the last
+        // pattern that uses it.
+        let is_stored = self.image_store.iter().any(
+            |stored| matches!(stored, ImageStoreInfo::Image(stored) if Arc::ptr_eq(stored, &image)),
+        );
+        if !is_stored {
+            self.buffer_size += image.bytes.len();
+        }
+
+        let info = PatternFillInfo::Fill {
+            image_store_index: self.add_image_to_store(image),
+            tessellation,
+        };
+        if let Some(index) = self.vacant_pattern_ids.pop() {
+            self.pattern_fills[index] = info;
+            index
+        } else {
+            self.pattern_fills.push(info);
+            self.pattern_fills.len() - 1
+        }
     }
 
     pub fn modify_image(&mut self, id: PrimitiveId, paint: ImagePaint) -> Result<(), GalileoError> {
@@ -689,7 +1184,7 @@ impl TessellatingRenderBundle {
     }
 
     fn update_map_ref<N, P, C, Poly>(
-        &mut self,
+        tessellation: &mut VertexBuffers<PolyVertex, u32>,
         range: Range<usize>,
         primitive: RenderPrimitive<N, P, C, Poly>,
     ) -> Result<(), GalileoError>
@@ -702,7 +1197,7 @@ impl TessellatingRenderBundle {
     {
         let color = match primitive {
             RenderPrimitive::Contour(_, LinePaint { color, .. })
-            | RenderPrimitive::Polygon(_, PolygonPaint { color }) => color,
+            | RenderPrimitive::Polygon(_, PolygonPaint { color, .. }) => color,
             _ => {
                 return Err(GalileoError::Generic(
                     "expected line or polygon primitive, but got a point".into(),
@@ -710,7 +1205,7 @@ impl TessellatingRenderBundle {
             }
         };
 
-        for vertex in &mut self.poly_tessellation.vertices[range] {
+        for vertex in &mut tessellation.vertices[range] {
             vertex.color = color.to_f32_array();
         }
 
@@ -743,13 +1238,34 @@ impl TessellatingRenderBundle {
         start_index..end_index
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.primitives.is_empty()
-    }
-
-    fn tessellate_polygon<N, P, Poly>(
-        polygon: &Poly,
-        paint: PolygonPaint,
+    fn add_extrusion<N, P, Poly>(&mut self, polygon: &Poly, paint: PolygonPaint) -> Range<usize>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+        Poly: Polygon,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let tessellation = &mut self.extrusion_tessellation;
+        let start_index = tessellation.vertices.len();
+        let start_index_count = tessellation.indices.len();
+
+        Self::tessellate_polygon_3d(polygon, paint, tessellation);
+
+        let end_index = tessellation.vertices.len();
+
+        self.buffer_size += (end_index - start_index) * size_of::<PolyVertex>();
+        self.buffer_size += (tessellation.indices.len() - start_index_count) * size_of::<u32>();
+
+        start_index..end_index
+    }
+
+    /// Tessellates a flat polygon with arbitrary orientation in space, for example a wall of a building.
+    ///
+    /// The polygon is projected onto the coordinate plane it is the most parallel to, tessellated there, and the
+    /// dropped coordinate is restored from the vertex attributes.
+    fn tessellate_polygon_3d<N, P, Poly>(
+        polygon: &Poly,
+        paint: PolygonPaint,
         tessellation: &mut VertexBuffers<PolyVertex, u32>,
     ) where
         N: AsPrimitive<f32>,
@@ -757,21 +1273,48 @@ impl TessellatingRenderBundle {
         Poly: Polygon,
         Poly::Contour: Contour<Point = P>,
     {
+        let to_array = |p: &P| [p.x().as_(), p.y().as_(), p.z().as_()];
+
+        // Newell's method gives the normal of the polygon plane without assuming that the polygon is convex.
+        let mut normal = [0.0f32; 3];
+        let outer: Vec<_> = polygon
+            .outer_contour()
+            .iter_points()
+            .map(to_array)
+            .collect();
+        for (i, a) in outer.iter().enumerate() {
+            let b = outer[(i + 1) % outer.len()];
+            normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+            normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+            normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+        }
+        let dropped_axis = (0..3)
+            .max_by(|a, b| normal[*a].abs().total_cmp(&normal[*b].abs()))
+            .unwrap_or(2);
+        let plane_axes = match dropped_axis {
+            0 => [1, 2],
+            1 => [0, 2],
+            _ => [0, 1],
+        };
+
         let mut path_builder = BuilderWithAttributes::new(1);
         for contour in polygon.iter_contours() {
-            let mut iterator = contour.iter_points();
+            let mut iterator = contour.iter_points().map(to_array);
 
             if let Some(first_point) = iterator.next() {
                 let _ = path_builder.begin(
-                    point(first_point.x().as_(), first_point.y().as_()),
-                    &[first_point.z().as_()],
+                    point(first_point[plane_axes[0]], first_point[plane_axes[1]]),
+                    &[first_point[dropped_axis]],
                 );
             } else {
                 return;
             }
 
             for p in iterator {
-                let _ = path_builder.line_to(point(p.x().as_(), p.y().as_()), &[p.z().as_()]);
+                let _ = path_builder.line_to(
+                    point(p[plane_axes[0]], p[plane_axes[1]]),
+                    &[p[dropped_axis]],
+                );
             }
 
             path_builder.end(true);
@@ -779,6 +1322,40 @@ impl TessellatingRenderBundle {
 
         let path = path_builder.build();
 
+        let vertex_constructor = SpatialPolygonVertexConstructor {
+            color: paint.color.to_f32_array(),
+            dropped_axis,
+            plane_axes,
+        };
+        let mut tesselator = FillTessellator::new();
+
+        if let Err(err) = tesselator.tessellate_path(
+            &path,
+            &FillOptions::DEFAULT,
+            &mut BuffersBuilder::new(tessellation, vertex_constructor),
+        ) {
+            log::error!("Tessellation failed: {err:?}");
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.primitives.is_empty()
+    }
+
+    fn tessellate_polygon<N, P, Poly>(
+        polygon: &Poly,
+        paint: PolygonPaint,
+        tessellation: &mut VertexBuffers<PolyVertex, u32>,
+    ) where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+        Poly: Polygon,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let Some(path) = Self::polygon_path(polygon) else {
+            return;
+        };
+
         let vertex_constructor = PolygonVertexConstructor {
             color: paint.color.to_f32_array(),
         };
@@ -793,6 +1370,66 @@ impl TessellatingRenderBundle {
         }
     }
 
+    fn tessellate_pattern_polygon<N, P, Poly>(
+        polygon: &Poly,
+        color: Color,
+        pattern: [f32; 4],
+        tessellation: &mut VertexBuffers<PatternVertex, u32>,
+    ) where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+        Poly: Polygon,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let Some(path) = Self::polygon_path(polygon) else {
+            return;
+        };
+
+        let vertex_constructor = PatternVertexConstructor {
+            color: color.to_f32_array(),
+            pattern,
+        };
+        let mut tesselator = FillTessellator::new();
+
+        if let Err(err) = tesselator.tessellate(
+            &path,
+            &FillOptions::DEFAULT,
+            &mut BuffersBuilder::new(tessellation, vertex_constructor),
+        ) {
+            log::error!("Tessellation failed: {err:?}");
+        }
+    }
+
+    fn polygon_path<N, P, Poly>(polygon: &Poly) -> Option<Path>
+    where
+        N: AsPrimitive<f32>,
+        P: CartesianPoint3d<Num = N>,
+        Poly: Polygon,
+        Poly::Contour: Contour<Point = P>,
+    {
+        let mut path_builder = BuilderWithAttributes::new(1);
+        for contour in polygon.iter_contours() {
+            let mut iterator = contour.iter_points();
+
+            if let Some(first_point) = iterator.next() {
+                let _ = path_builder.begin(
+                    point(first_point.x().as_(), first_point.y().as_()),
+                    &[first_point.z().as_()],
+                );
+            } else {
+                return None;
+            }
+
+            for p in iterator {
+                let _ = path_builder.line_to(point(p.x().as_(), p.y().as_()), &[p.z().as_()]);
+            }
+
+            path_builder.end(true);
+        }
+
+        Some(path_builder.build())
+    }
+
     pub fn add_shape<N, P>(
         &mut self,
         position: &P,
@@ -955,6 +1592,17 @@ impl TessellatingRenderBundle {
             (self.screen_ref.indices.len() - start_index_count) * std::mem::size_of::<u32>();
     }
 
+    fn add_marker(&mut self, marker: MarkerInstance) -> usize {
+        if let Some(index) = self.vacant_marker_ids.pop() {
+            self.markers[index] = marker;
+            return index;
+        }
+
+        self.markers.push(marker);
+        self.buffer_size += size_of::<MarkerInstance>();
+        self.markers.len() - 1
+    }
+
     fn add_dot<P, N>(&mut self, point: &P, color: Color, offset: Vector2<f32>)
     where
         N: AsPrimitive<f32>,
@@ -1070,6 +1718,174 @@ fn build_contour_path(
     Some(())
 }
 
+/// Point of a line in the tessellation coordinates (map coordinates divided by the resolution).
+#[derive(Debug, Clone, Copy)]
+struct LinePoint {
+    position: Vector2<f32>,
+    z: f32,
+}
+
+impl LinePoint {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position + (other.position - self.position) * t,
+            z: self.z + (other.z - self.z) * t,
+        }
+    }
+}
+
+/// Cuts the line into dashes of the pattern. Since the line is in the tessellation coordinates, the pattern lengths
+/// are in pixels at the tessellation resolution.
+fn dash_line(points: &[LinePoint], dash: &DashPattern) -> Vec<Vec<LinePoint>> {
+    let period = dash.period();
+    let period_length: f64 = period.iter().sum();
+
+    // Position in the pattern: index of the current dash or gap and the distance left until its end.
+    let mut index = 0;
+    let mut left = period[0];
+    let mut offset = dash.offset().rem_euclid(period_length);
+    while offset > 0.0 {
+        if offset < left {
+            left -= offset;
+            break;
+        }
+
+        offset -= left;
+        index = (index + 1) % period.len();
+        left = period[index];
+    }
+
+    let mut parts = vec![];
+    let mut current = vec![];
+    if index % 2 == 0 {
+        current.push(points[0]);
+    }
+
+    for segment in points.windows(2) {
+        let (from, to) = (segment[0], segment[1]);
+        let length = (to.position - from.position).norm() as f64;
+        let mut passed = 0.0;
+
+        while length - passed > left {
+            passed += left;
+            let split = from.lerp(&to, (passed / length) as f32);
+            if index % 2 == 0 {
+                current.push(split);
+                parts.push(std::mem::take(&mut current));
+            } else {
+                current.push(split);
+            }
+
+            index = (index + 1) % period.len();
+            left = period[index];
+        }
+
+        left -= length - passed;
+        if index % 2 == 0 {
+            current.push(to);
+        }
+    }
+
+    if current.len() > 1 {
+        parts.push(current);
+    }
+
+    parts.retain(|part| part.len() > 1);
+    parts
+}
+
+/// Points and directions of the arrowheads on the line.
+fn arrow_anchors(
+    points: &[LinePoint],
+    arrows: &LineArrows,
+    is_closed: bool,
+) -> Vec<(LinePoint, Vector2<f32>)> {
+    let mut anchors = vec![];
+    let segments: Vec<_> = points
+        .windows(2)
+        .filter_map(|segment| {
+            let vector = segment[1].position - segment[0].position;
+            let length = vector.norm();
+            (length > 0.0).then(|| (segment[0], segment[1], vector / length, length))
+        })
+        .collect();
+
+    let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        return anchors;
+    };
+
+    if arrows.start && !is_closed {
+        anchors.push((first.0, -first.2));
+    }
+
+    if let Some(interval) = arrows.interval.filter(|v| *v > 0.0) {
+        let mut next = interval / 2.0;
+        let mut passed = 0.0;
+        for (from, to, direction, length) in &segments {
+            let length = *length as f64;
+            while next <= passed + length {
+                let t = ((next - passed) / length) as f32;
+                anchors.push((from.lerp(to, t), *direction));
+                next += interval;
+            }
+            passed += length;
+        }
+    }
+
+    if arrows.end && !is_closed {
+        anchors.push((last.1, last.2));
+    }
+
+    anchors
+}
+
+/// Adds an arrowhead at the `anchor` point. The vertices of the arrowhead are offset from the anchor by their normals
+/// in pixels, so the arrowhead keeps its size on the screen and is rotated together with the map.
+///
+/// The triangle is centered at the center of its circumscribed circle, so that all its vertices have the same
+/// offset length. This way the feathered line antialiasing, which fades the pixels by the offset length, does not
+/// fade the inner area of the triangle.
+fn add_arrow(
+    tessellation: &mut VertexBuffers<PolyVertex, u32>,
+    anchor: LinePoint,
+    direction: Vector2<f32>,
+    arrows: &LineArrows,
+    color: [f32; 4],
+    resolution: f32,
+) {
+    let length = arrows.length as f32;
+    let half_width = arrows.width as f32 / 2.0;
+    if length <= 0.0 || half_width <= 0.0 {
+        return;
+    }
+
+    let radius = (length * length + half_width * half_width) / (2.0 * length);
+    let normal = Vector2::new(-direction.y, direction.x);
+    let base = direction * (radius - length);
+    let offsets = [
+        direction * radius,
+        base + normal * half_width,
+        base - normal * half_width,
+    ];
+
+    let first_index = tessellation.vertices.len() as u32;
+    for offset in offsets {
+        tessellation.vertices.push(PolyVertex {
+            position: [
+                anchor.position.x * resolution,
+                anchor.position.y * resolution,
+                anchor.z,
+            ],
+            color,
+            normal: [offset.x, offset.y],
+            norm_limit: f32::MAX,
+        });
+    }
+    tessellation
+        .indices
+        .extend([first_index, first_index + 1, first_index + 2]);
+}
+
 #[allow(dead_code)]
 struct LineVertexConstructor<'a> {
     width: f32,
@@ -1140,6 +1956,43 @@ impl FillVertexConstructor<PolyVertex> for PolygonVertexConstructor {
     }
 }
 
+struct PatternVertexConstructor {
+    color: [f32; 4],
+    pattern: [f32; 4],
+}
+
+impl FillVertexConstructor<PatternVertex> for PatternVertexConstructor {
+    fn new_vertex(&mut self, vertex: FillVertex) -> PatternVertex {
+        PatternVertex {
+            position: [vertex.position().x, vertex.position().y, 0.0],
+            color: self.color,
+            pattern: self.pattern,
+        }
+    }
+}
+
+struct SpatialPolygonVertexConstructor {
+    color: [f32; 4],
+    dropped_axis: usize,
+    plane_axes: [usize; 2],
+}
+
+impl FillVertexConstructor<PolyVertex> for SpatialPolygonVertexConstructor {
+    fn new_vertex(&mut self, mut vertex: FillVertex) -> PolyVertex {
+        let mut position = [0.0; 3];
+        position[self.plane_axes[0]] = vertex.position().x;
+        position[self.plane_axes[1]] = vertex.position().y;
+        position[self.dropped_axis] = vertex.interpolated_attributes()[0];
+
+        PolyVertex {
+            position,
+            color: self.color,
+            normal: Default::default(),
+            norm_limit: 1.0,
+        }
+    }
+}
+
 struct ScreenRefVertexConstructor {
     color: [u8; 4],
     position: [f32; 3],
@@ -1177,6 +2030,26 @@ pub(crate) struct PolyVertex {
     pub norm_limit: f32,
 }
 
+/// Vertex of a polygon filled with a hatch or a pattern image.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct PatternVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+    /// Parameters of the hatch: direction of the lines, spacing and line width. Not used by pattern images.
+    pub pattern: [f32; 4],
+}
+
+/// Parameters of the hatch as they are stored in the [`PatternVertex::pattern`].
+fn hatch_parameters(hatch: &HatchFill) -> [f32; 4] {
+    [
+        hatch.angle.cos() as f32,
+        hatch.angle.sin() as f32,
+        hatch.spacing as f32,
+        hatch.line_width as f32,
+    ]
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct PointInstance {
@@ -1184,6 +2057,100 @@ pub(crate) struct PointInstance {
     pub color: [u8; 4],
 }
 
+/// A circle or a square of fixed pixel size, drawn by the wgpu renderer as an instance of a quad. The shape and its
+/// outline are cut out of the quad in the fragment shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct MarkerInstance {
+    pub position: [f32; 3],
+    /// Offset of the center of the marker in pixels, with Y axis going up.
+    pub offset: [f32; 2],
+    /// Radius of a circle or half of the side of a square in pixels. Negative for vacant slots, which are not drawn.
+    pub size: f32,
+    /// Rotation of the marker on the screen (radians, counterclockwise).
+    pub rotation: f32,
+    /// Distances from the edge of the shape (positive outside) in pixels between which the outline is drawn.
+    pub outline_range: [f32; 2],
+    pub fill: [u8; 4],
+    pub outline: [u8; 4],
+    /// [`MarkerInstance::CIRCLE`] or [`MarkerInstance::SQUARE`].
+    pub shape: u32,
+}
+
+impl MarkerInstance {
+    pub const CIRCLE: u32 = 0;
+    pub const SQUARE: u32 = 1;
+
+    const VACANT: Self = Self {
+        position: [0.0; 3],
+        offset: [0.0; 2],
+        size: -1.0,
+        rotation: 0.0,
+        outline_range: [0.0; 2],
+        fill: [0; 4],
+        outline: [0; 4],
+        shape: Self::CIRCLE,
+    };
+
+    /// Creates a marker at the origin for the paint, or returns `None` if the paint cannot be drawn as a marker.
+    fn from_paint(paint: &PointPaint) -> Option<Self> {
+        let (shape, fill, size, rotation, outline, outline_range) = match &paint.shape {
+            // Gradient circles are drawn with their geometry.
+            PointShape::Circle {
+                fill,
+                radius,
+                outline,
+            } if fill.center_color == fill.side_color => {
+                // Circle outlines are centered on the edge of the circle.
+                let width = outline.map(|outline| outline.width as f32).unwrap_or(0.0);
+                (
+                    Self::CIRCLE,
+                    fill.center_color,
+                    *radius,
+                    0.0,
+                    outline,
+                    [-width, width],
+                )
+            }
+            PointShape::Square {
+                fill,
+                size,
+                outline,
+                rotation,
+            } => {
+                // Square outlines are drawn outside of the square.
+                let width = outline.map(|outline| outline.width as f32).unwrap_or(0.0);
+                (
+                    Self::SQUARE,
+                    *fill,
+                    *size / 2.0,
+                    *rotation,
+                    outline,
+                    [0.0, width],
+                )
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            position: [0.0; 3],
+            offset: [paint.offset.x, paint.offset.y],
+            size,
+            rotation,
+            outline_range,
+            fill: fill.to_u8_array(),
+            outline: outline
+                .map(|outline| outline.color.to_u8_array())
+                .unwrap_or_default(),
+            shape,
+        })
+    }
+
+    pub fn is_vacant(&self) -> bool {
+        self.size < 0.0
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct ImageVertex {
@@ -1191,6 +2158,9 @@ pub(crate) struct ImageVertex {
     pub opacity: f32,
     pub tex_coords: [f32; 2],
     pub offset: [f32; 2],
+    /// `1.0` if the offset is set in the map orientation and must be rotated together with the map, `0.0` if it is
+    /// set in screen pixels.
+    pub map_aligned: f32,
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -1199,9 +2169,211 @@ pub(crate) mod serialization;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::render::LineCap;
 
     type C = galileo_types::impls::Contour<Point3d>;
 
+    fn line_points(points: &[(f32, f32)]) -> Vec<LinePoint> {
+        points
+            .iter()
+            .map(|(x, y)| LinePoint {
+                position: Vector2::new(*x, *y),
+                z: 0.0,
+            })
+            .collect()
+    }
+
+    fn part_ends(parts: &[Vec<LinePoint>]) -> Vec<(f32, f32)> {
+        parts
+            .iter()
+            .map(|part| (part[0].position.x, part[part.len() - 1].position.x))
+            .collect()
+    }
+
+    #[test]
+    fn dash_line_cuts_dashes() {
+        let points = line_points(&[(0.0, 0.0), (5.0, 0.0), (12.0, 0.0)]);
+        let dash = DashPattern::new(&[3.0, 2.0]).unwrap();
+        let parts = dash_line(&points, &dash);
+        assert_eq!(
+            part_ends(&parts),
+            vec![(0.0, 3.0), (5.0, 8.0), (10.0, 12.0)]
+        );
+
+        // The vertex between the segments stays in the dash.
+        assert_eq!(parts[1].len(), 2);
+        let parts = dash_line(&line_points(&[(0.0, 0.0), (6.0, 0.0), (6.0, 2.0)]), &dash);
+        assert_eq!(parts[1].len(), 3);
+    }
+
+    #[test]
+    fn dash_line_applies_offset_and_odd_patterns() {
+        let points = line_points(&[(0.0, 0.0), (10.0, 0.0)]);
+
+        let dash = DashPattern::new(&[3.0, 2.0]).unwrap().with_offset(4.0);
+        assert_eq!(
+            part_ends(&dash_line(&points, &dash)),
+            vec![(1.0, 4.0), (6.0, 9.0)]
+        );
+
+        let dash = DashPattern::new(&[2.0]).unwrap();
+        assert_eq!(
+            part_ends(&dash_line(&points, &dash)),
+            vec![(0.0, 2.0), (4.0, 6.0), (8.0, 10.0)]
+        );
+    }
+
+    #[test]
+    fn arrows_are_placed_along_line() {
+        let points = line_points(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+        let arrows = LineArrows::new(4.0, 4.0)
+            .with_start(true)
+            .with_interval(Some(10.0));
+        let anchors: Vec<_> = arrow_anchors(&points, &arrows, false)
+            .into_iter()
+            .map(|(p, d)| (p.position.x, p.position.y, d.x, d.y))
+            .collect();
+        assert_eq!(
+            anchors,
+            vec![
+                (0.0, 0.0, -1.0, 0.0),
+                (5.0, 0.0, 1.0, 0.0),
+                (10.0, 5.0, 0.0, 1.0),
+                (10.0, 10.0, 0.0, 1.0),
+            ]
+        );
+
+        assert!(arrow_anchors(&points, &LineArrows::new(4.0, 4.0), true).is_empty());
+    }
+
+    #[test]
+    fn dashed_line_with_arrows_is_tessellated() {
+        let line = galileo_types::impls::Contour::open(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(100.0, 0.0, 0.0),
+        ]);
+        let paint = LinePaint {
+            color: Color::BLACK,
+            width: 2.0,
+            offset: 0.0,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Round,
+            dash: None,
+            arrows: None,
+        };
+
+        let mut solid = TessellatingRenderBundle::new();
+        solid.add_line(&line, paint, 1.0);
+        let solid_vertices = solid.poly_tessellation.vertices.len();
+
+        let mut dashed = TessellatingRenderBundle::new();
+        dashed.add_line(
+            &line,
+            LinePaint {
+                dash: Some(DashPattern::new(&[10.0, 5.0]).unwrap()),
+                ..paint
+            },
+            1.0,
+        );
+        assert!(dashed.poly_tessellation.vertices.len() > solid_vertices);
+        assert!(dashed
+            .poly_tessellation
+            .vertices
+            .iter()
+            .all(|v| !(10.5..14.5).contains(&v.position[0])));
+
+        let mut with_arrows = TessellatingRenderBundle::new();
+        with_arrows.add_line(
+            &line,
+            LinePaint {
+                arrows: Some(LineArrows::new(8.0, 6.0)),
+                ..paint
+            },
+            1.0,
+        );
+        let arrow = &with_arrows.poly_tessellation.vertices[solid_vertices..];
+        assert_eq!(arrow.len(), 3);
+        assert!(arrow.iter().all(|v| v.position[0] == 100.0));
+        assert_eq!(arrow[0].normal, [4.5625, 0.0]);
+        assert_eq!(with_arrows.poly_tessellation.indices.len() % 3, 0);
+    }
+
+    #[test]
+    fn hatch_and_pattern_fills() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let polygon = galileo_types::impls::Polygon::from(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+        ]);
+        let image = Arc::new(DecodedImage::from_raw(vec![255; 4], 1, 1).unwrap());
+        let add = |bundle: &mut TessellatingRenderBundle, fill| {
+            bundle.add(
+                RenderPrimitive::<_, _, C, _>::new_polygon_ref(
+                    &polygon,
+                    PolygonPaint {
+                        color: Color::RED,
+                        fill,
+                    },
+                ),
+                1.0,
+            )
+        };
+
+        let hatch = add(
+            &mut bundle,
+            PolygonFill::Hatch(HatchFill::new(0.0, 8.0, 2.0)),
+        );
+        let pattern = add(&mut bundle, PolygonFill::Pattern(image.clone()));
+        let second_pattern = add(&mut bundle, PolygonFill::Pattern(image));
+        assert!(bundle.poly_tessellation.vertices.is_empty());
+        assert_eq!(bundle.hatch_tessellation.vertices.len(), 3);
+        assert_eq!(
+            bundle.hatch_tessellation.vertices[0].pattern,
+            [1.0, 0.0, 8.0, 2.0]
+        );
+        assert_eq!(bundle.pattern_fills.len(), 2);
+        assert_eq!(bundle.image_store.len(), 1);
+
+        bundle
+            .update(
+                hatch,
+                RenderPrimitive::<_, _, C, _>::new_polygon_ref(
+                    &polygon,
+                    PolygonPaint {
+                        color: Color::BLUE,
+                        fill: PolygonFill::Hatch(HatchFill::new(0.0, 4.0, 1.0)),
+                    },
+                ),
+            )
+            .unwrap();
+        assert_eq!(
+            bundle.hatch_tessellation.vertices[0].color,
+            Color::BLUE.to_f32_array()
+        );
+        assert!(bundle
+            .update(
+                hatch,
+                RenderPrimitive::<_, _, C, _>::new_polygon_ref(
+                    &polygon,
+                    PolygonPaint {
+                        color: Color::BLUE,
+                        fill: PolygonFill::Solid,
+                    },
+                ),
+            )
+            .is_err());
+
+        bundle.remove(hatch).unwrap();
+        assert!(bundle.hatch_tessellation.vertices.is_empty());
+
+        bundle.remove(pattern).unwrap();
+        assert!(matches!(bundle.image_store[0], ImageStoreInfo::Image(_)));
+        bundle.remove(second_pattern).unwrap();
+        assert!(matches!(bundle.image_store[0], ImageStoreInfo::Vacant));
+        assert_eq!(bundle.approx_buffer_size(), 0);
+    }
+
     #[test]
     fn remove_map_ref() {
         let mut bundle = TessellatingRenderBundle::new();
@@ -1213,11 +2385,15 @@ mod tests {
         ]);
         let paint1 = PolygonPaint {
             color: Color::BLACK,
+            fill: PolygonFill::Solid,
+        };
+        let paint2 = PolygonPaint {
+            color: Color::RED,
+            fill: PolygonFill::Solid,
         };
-        let paint2 = PolygonPaint { color: Color::RED };
 
         let _id0 = bundle.add(
-            RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint1),
+            RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint1.clone()),
             1.0,
         );
         let id1 = bundle.add(
@@ -1251,4 +2427,179 @@ mod tests {
 
         assert_eq!(vertex_range.end, vertex_count);
     }
+
+    #[test]
+    fn append_moves_primitives() {
+        type Poly = galileo_types::impls::Polygon<Point3d>;
+        let polygon = Poly::from(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 1.0, 0.0),
+        ]);
+        let paint = |color| PolygonPaint {
+            color,
+            fill: PolygonFill::Solid,
+        };
+        let point = Point3d::new(1.0, 2.0, 0.0);
+
+        let mut bundle = TessellatingRenderBundle::new();
+        bundle.add(
+            RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint(Color::BLACK)),
+            1.0,
+        );
+        bundle.add(
+            RenderPrimitive::<_, _, C, Poly>::new_point_ref(&point, PointPaint::dot(Color::BLACK)),
+            1.0,
+        );
+        let vertex_count = bundle.poly_tessellation.vertices.len();
+        let index_count = bundle.poly_tessellation.indices.len();
+
+        let mut other = TessellatingRenderBundle::new();
+        let removed = other.add(
+            RenderPrimitive::<_, _, C, Poly>::new_point_ref(
+                &point,
+                PointPaint::circle(Color::RED, 4.0),
+            ),
+            1.0,
+        );
+        let polygon_id = other.add(
+            RenderPrimitive::<_, _, C, _>::new_polygon_ref(&polygon, paint(Color::RED)),
+            1.0,
+        );
+        let dot_id = other.add(
+            RenderPrimitive::<_, _, C, Poly>::new_point_ref(&point, PointPaint::dot(Color::RED)),
+            1.0,
+        );
+        other.remove(removed).unwrap();
+
+        let new_ids = bundle.append(other);
+        assert!(new_ids[removed.0].is_none());
+        assert_eq!(bundle.poly_tessellation.vertices.len(), vertex_count * 2);
+        assert!(bundle.poly_tessellation.indices[index_count..]
+            .iter()
+            .all(|index| *index as usize >= vertex_count));
+        assert_eq!(bundle.points.len(), 2);
+        assert!(bundle.markers.iter().all(|marker| marker.is_vacant()));
+
+        bundle.remove(new_ids[dot_id.0].unwrap()).unwrap();
+        assert_eq!(bundle.points[0].color, Color::BLACK.to_u8_array());
+        bundle.remove(new_ids[polygon_id.0].unwrap()).unwrap();
+        assert_eq!(bundle.poly_tessellation.vertices.len(), vertex_count);
+        assert!(bundle
+            .poly_tessellation
+            .vertices
+            .iter()
+            .all(|v| v.color == Color::BLACK.to_f32_array()));
+    }
+
+    #[test]
+    fn spatial_polygons_are_tessellated_with_depth() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let wall = galileo_types::impls::Polygon::from(vec![
+            Point3d::new(0.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 0.0),
+            Point3d::new(1.0, 0.0, 2.0),
+            Point3d::new(0.0, 0.0, 2.0),
+        ]);
+        let paint = PolygonPaint {
+            color: Color::RED,
+            fill: PolygonFill::Solid,
+        };
+
+        let id = bundle.add(
+            RenderPrimitive::<_, _, C, _>::new_polygon_ref(&wall, paint),
+            1.0,
+        );
+
+        assert!(bundle.poly_tessellation.vertices.is_empty());
+        assert_eq!(bundle.extrusion_tessellation.indices.len(), 6);
+        assert!(bundle
+            .extrusion_tessellation
+            .vertices
+            .iter()
+            .all(|v| v.position[1] == 0.0 && (v.position[2] == 0.0 || v.position[2] == 2.0)));
+
+        bundle.remove(id).unwrap();
+        assert!(bundle.extrusion_tessellation.vertices.is_empty());
+        assert!(bundle.extrusion_tessellation.indices.is_empty());
+    }
+
+    #[test]
+    fn points_are_drawn_as_markers() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let point = Point3d::new(1.0, 2.0, 0.0);
+        let circle = PointPaint::circle(Color::RED, 10.0).with_outline(Color::BLACK, 2.0);
+        let square = PointPaint::square(Color::BLUE, 6.0).with_rotation(0.5);
+
+        let circle_id = bundle.add(
+            RenderPrimitive::<_, _, C, galileo_types::impls::Polygon<_>>::new_point_ref(
+                &point, circle,
+            ),
+            1.0,
+        );
+        let square_id = bundle.add(
+            RenderPrimitive::<_, _, C, galileo_types::impls::Polygon<_>>::new_point_ref(
+                &point, square,
+            ),
+            1.0,
+        );
+
+        assert!(bundle.screen_ref.vertices.is_empty());
+        assert_eq!(bundle.markers.len(), 2);
+        assert_eq!(bundle.markers[0].position, [1.0, 2.0, 0.0]);
+        assert_eq!(bundle.markers[0].size, 5.0);
+        assert_eq!(bundle.markers[0].outline_range, [-2.0, 2.0]);
+        assert_eq!(bundle.markers[0].outline, Color::BLACK.to_u8_array());
+        assert_eq!(bundle.markers[1].shape, MarkerInstance::SQUARE);
+        assert_eq!(bundle.markers[1].size, 3.0);
+        assert_eq!(bundle.markers[1].rotation, 0.5);
+
+        bundle
+            .update(
+                square_id,
+                RenderPrimitive::<_, _, C, galileo_types::impls::Polygon<_>>::new_point_ref(
+                    &Point3d::new(5.0, 5.0, 0.0),
+                    PointPaint::square(Color::GREEN, 8.0),
+                ),
+            )
+            .unwrap();
+        assert_eq!(bundle.markers[1].fill, Color::GREEN.to_u8_array());
+        assert_eq!(bundle.markers[1].size, 4.0);
+        assert_eq!(bundle.markers[1].position, [1.0, 2.0, 0.0]);
+
+        bundle.remove(circle_id).unwrap();
+        assert!(bundle.markers[0].is_vacant());
+        assert_eq!(bundle.markers[1].fill, Color::GREEN.to_u8_array());
+
+        let buffer_size = bundle.approx_buffer_size();
+        bundle.add(
+            RenderPrimitive::<_, _, C, galileo_types::impls::Polygon<_>>::new_point_ref(
+                &point,
+                PointPaint::circle(Color::RED, 4.0),
+            ),
+            1.0,
+        );
+        assert_eq!(bundle.markers.len(), 2);
+        assert!(!bundle.markers[0].is_vacant());
+        assert_eq!(bundle.approx_buffer_size(), buffer_size);
+    }
+
+    #[test]
+    fn gradient_circles_are_tessellated() {
+        let mut bundle = TessellatingRenderBundle::new();
+        let mut paint = PointPaint::circle(Color::RED, 10.0);
+        if let PointShape::Circle { fill, .. } = &mut paint.shape {
+            fill.side_color = Color::BLUE;
+        }
+
+        bundle.add(
+            RenderPrimitive::<_, _, C, galileo_types::impls::Polygon<_>>::new_point(
+                Point3d::new(0.0, 0.0, 0.0),
+                paint,
+            ),
+            1.0,
+        );
+        assert!(bundle.markers.is_empty());
+        assert!(!bundle.screen_ref.vertices.is_empty());
+    }
 }
@@ -1,6 +1,7 @@
 use crate::decoded_image::DecodedImage;
 use crate::render::render_bundle::tessellating::{
-    ImageInfo, ImageStoreInfo, PolyVertex, PrimitiveInfo, ScreenRefVertex, TessellatingRenderBundle,
+    ImageInfo, ImageStoreInfo, PatternFillInfo, PatternVertex, PolyVertex, PrimitiveInfo,
+    ScreenRefVertex, TessellatingRenderBundle,
 };
 use lyon::lyon_tessellation::VertexBuffers;
 use serde::{Deserialize, Serialize};
@@ -10,13 +11,19 @@ use std::sync::Arc;
 #[derive(Debug, Deserialize, Serialize)]
 pub(crate) struct TessellatingRenderBundleBytes {
     pub poly_tessellation: PolyVertexBuffersBytes,
+    pub extrusion_tessellation: PolyVertexBuffersBytes,
+    pub hatch_tessellation: PatternVertexBuffersBytes,
+    pub pattern_fills: Vec<Option<(usize, PatternVertexBuffersBytes)>>,
     pub points: Vec<u32>,
+    pub markers: Vec<u32>,
     pub screen_ref: ScreenRefVertexBuffersBytes,
     pub images: Vec<Option<ImageBytes>>,
     pub primitives: Vec<PrimitiveInfo>,
     pub image_store: Vec<Option<(u32, u32, Vec<u8>)>>,
     pub vacant_image_ids: Vec<usize>,
     pub vacant_image_store_ids: Vec<usize>,
+    pub vacant_pattern_ids: Vec<usize>,
+    pub vacant_marker_ids: Vec<usize>,
     pub clip_area: Option<PolyVertexBuffersBytes>,
     pub bundle_size: usize,
 }
@@ -82,11 +89,54 @@ impl ScreenRefVertexBuffersBytes {
     }
 }
 
+const PATTERN_VERTEX_BLOCKS: usize = size_of::<PatternVertex>() / size_of::<u32>();
+type PatternVertexShim = [u32; PATTERN_VERTEX_BLOCKS];
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct PatternVertexBuffersBytes {
+    vertices: Vec<PatternVertexShim>,
+    indices: Vec<u32>,
+}
+
+impl From<VertexBuffers<PatternVertex, u32>> for PatternVertexBuffersBytes {
+    fn from(value: VertexBuffers<PatternVertex, u32>) -> Self {
+        Self {
+            vertices: bytemuck::cast_vec(value.vertices),
+            indices: value.indices,
+        }
+    }
+}
+
+impl PatternVertexBuffersBytes {
+    fn into_typed_unchecked(self) -> VertexBuffers<PatternVertex, u32> {
+        let vertices = bytemuck::cast_vec(self.vertices);
+
+        VertexBuffers {
+            vertices,
+            indices: self.indices,
+        }
+    }
+}
+
 impl TessellatingRenderBundle {
     pub(crate) fn into_bytes(self) -> TessellatingRenderBundleBytes {
         let converted = TessellatingRenderBundleBytes {
             poly_tessellation: self.poly_tessellation.into(),
+            extrusion_tessellation: self.extrusion_tessellation.into(),
+            hatch_tessellation: self.hatch_tessellation.into(),
+            pattern_fills: self
+                .pattern_fills
+                .into_iter()
+                .map(|info| match info {
+                    PatternFillInfo::Vacant => None,
+                    PatternFillInfo::Fill {
+                        image_store_index,
+                        tessellation,
+                    } => Some((image_store_index, tessellation.into())),
+                })
+                .collect(),
             points: bytemuck::cast_vec(self.points),
+            markers: bytemuck::cast_vec(self.markers),
             screen_ref: self.screen_ref.into(),
             images: self
                 .images
@@ -112,6 +162,8 @@ impl TessellatingRenderBundle {
                 .collect(),
             vacant_image_ids: self.vacant_image_ids,
             vacant_image_store_ids: self.vacant_image_store_ids,
+            vacant_pattern_ids: self.vacant_pattern_ids,
+            vacant_marker_ids: self.vacant_marker_ids,
             clip_area: self.clip_area.map(|v| v.into()),
             bundle_size: self.buffer_size,
         };
@@ -122,7 +174,21 @@ impl TessellatingRenderBundle {
     pub(crate) fn from_bytes_unchecked(bundle: TessellatingRenderBundleBytes) -> Self {
         Self {
             poly_tessellation: bundle.poly_tessellation.into_typed_unchecked(),
+            extrusion_tessellation: bundle.extrusion_tessellation.into_typed_unchecked(),
+            hatch_tessellation: bundle.hatch_tessellation.into_typed_unchecked(),
+            pattern_fills: bundle
+                .pattern_fills
+                .into_iter()
+                .map(|item| match item {
+                    Some((image_store_index, tessellation)) => PatternFillInfo::Fill {
+                        image_store_index,
+                        tessellation: tessellation.into_typed_unchecked(),
+                    },
+                    None => PatternFillInfo::Vacant,
+                })
+                .collect(),
             points: bytemuck::cast_vec(bundle.points),
+            markers: bytemuck::cast_vec(bundle.markers),
             screen_ref: bundle.screen_ref.into_typed_unchecked(),
             images: bundle
                 .images
@@ -155,6 +221,8 @@ impl TessellatingRenderBundle {
                 .collect(),
             vacant_image_ids: bundle.vacant_image_ids,
             vacant_image_store_ids: bundle.vacant_image_store_ids,
+            vacant_pattern_ids: bundle.vacant_pattern_ids,
+            vacant_marker_ids: bundle.vacant_marker_ids,
             clip_area: bundle.clip_area.map(|v| v.into_typed_unchecked()),
             buffer_size: bundle.bundle_size,
             vacant_ids: vec![],
@@ -0,0 +1,1222 @@
+use crate::decoded_image::DecodedImage;
+use crate::map::{Map, RenderedItem, SwipeSide};
+use crate::overlay;
+use crate::render::render_bundle::tessellating::{
+    ImageInfo, ImageStoreInfo, ImageVertex, MarkerInstance, PatternFillInfo, PatternVertex,
+    PointInstance, PolyVertex, ScreenRefTessellation, TessellatingRenderBundle,
+};
+use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+use crate::render::{pattern_origin, BlendMode, Canvas, HeatmapPaint, PackedBundle, RenderOptions};
+use crate::view::MapView;
+use crate::Color;
+use galileo_types::cartesian::{Point2d, Size};
+use lyon::tessellation::VertexBuffers;
+use nalgebra::{Matrix4, Rotation3, Vector3, Vector4};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Renders a [`Map`] into an SVG document.
+///
+/// Unlike reading back the image rendered by [`WgpuRenderer`](super::WgpuRenderer), the output is resolution
+/// independent, so it can be used for printing and report generation. The layers are drawn with the same
+/// [`Map`] and [`MapView`] as on the screen: features are written as vector paths, and images (e.g. raster tiles
+/// and point markers) are embedded as PNG images. Opacity and blend modes of the layers are preserved.
+///
+/// Layers draw only the data they have already loaded, so [`Map::load_layers`] should be called and the loading
+/// finished before rendering. Raster and vector tiles are prepared separately for the document, so
+/// exporting does not change what is drawn on the screen. Heatmaps are not drawn.
+///
+/// Only SVG is produced. Other vector formats like PDF are out of scope of this renderer, the document can be
+/// converted into them with external tools.
+///
+/// ```no_run
+/// # use galileo::Map;
+/// use galileo::render::SvgRenderer;
+///
+/// # fn export(map: &Map) -> std::io::Result<()> {
+/// let svg = SvgRenderer::new().render(map);
+/// std::fs::write("map.svg", svg)
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SvgRenderer {
+    background: Color,
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SvgRenderer {
+    /// Creates a new renderer with opaque white background.
+    pub fn new() -> Self {
+        Self {
+            background: Color::WHITE,
+        }
+    }
+
+    /// Sets the background color of the document. Transparent background is not written to the document at all.
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Sets the background color of the document. See [`SvgRenderer::with_background`].
+    pub fn set_background(&mut self, background: Color) {
+        self.background = background;
+    }
+
+    /// Background color of the document.
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// Renders the map with its current view.
+    pub fn render(&self, map: &Map) -> String {
+        self.render_view(map, map.view())
+    }
+
+    /// Renders the map with the given view instead of the view of the map. This can be used to export the map with
+    /// a size different from the size of the screen.
+    pub fn render_view(&self, map: &Map, view: &MapView) -> String {
+        let size = view.size();
+        let mut writer = SvgWriter::default();
+        let _ = write!(
+            writer.out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = number(size.width()),
+            h = number(size.height()),
+        );
+
+        if !self.background.is_transparent() {
+            let _ = write!(
+                writer.out,
+                r#"<rect width="100%" height="100%"{}/>"#,
+                paint_attributes("fill", self.background.to_u8_array())
+            );
+        }
+
+        let swipe = map.swipe();
+        let swipe_clips = swipe.map(|swipe| {
+            writer.out.push_str("<defs>");
+            let clips = [SwipeSide::Leading, SwipeSide::Trailing].map(|side| {
+                let rect = swipe.side_rect(view, side);
+                let id = writer.next_id("clip");
+                let _ = write!(
+                    writer.out,
+                    r#"<clipPath id="{id}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+                    number(rect.x_min()),
+                    number(rect.y_min()),
+                    number(rect.width()),
+                    number(rect.height()),
+                );
+                (side, id)
+            });
+            writer.out.push_str("</defs>");
+            clips
+        });
+
+        let world_views = map.world_views(view);
+        map.layers()
+            .for_each_rendered(|id, item, opacity, blend_mode| {
+                let side = swipe.and_then(|swipe| swipe.side(id));
+                let clip_id = swipe_clips
+                    .iter()
+                    .flatten()
+                    .find(|(clip_side, _)| Some(*clip_side) == side)
+                    .map(|(_, clip_id)| clip_id.as_str());
+                write_rendered(
+                    &mut writer,
+                    item,
+                    opacity,
+                    blend_mode,
+                    clip_id,
+                    &world_views,
+                    size,
+                );
+            });
+
+        if let Some(swipe) = swipe.filter(|swipe| swipe.divider_width() > 0.0) {
+            let rect = swipe.divider_rect(view);
+            let _ = write!(
+                writer.out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+                number(rect.x_min()),
+                number(rect.y_min()),
+                number(rect.width()),
+                number(rect.height()),
+                paint_attributes("fill", swipe.divider_color().to_u8_array())
+            );
+        }
+
+        if let Some(projector) = Projector::new(&overlay::screen_view(view)) {
+            let mut canvas = SvgCanvas {
+                size,
+                projector,
+                writer: &mut writer,
+            };
+            for overlay in map.overlays() {
+                overlay.render(map, view, &mut canvas);
+            }
+        }
+
+        writer.out.push_str("</svg>");
+        writer.out
+    }
+}
+
+/// Writes a layer or a group of layers as an SVG group with the opacity and blend mode.
+fn write_rendered(
+    writer: &mut SvgWriter,
+    item: RenderedItem,
+    opacity: f32,
+    blend_mode: BlendMode,
+    clip_id: Option<&str>,
+    world_views: &[MapView],
+    size: Size,
+) {
+    writer.out.push_str("<g");
+    if let Some(clip_id) = clip_id {
+        let _ = write!(writer.out, r#" clip-path="url(#{clip_id})""#);
+    }
+    if opacity < 1.0 {
+        let _ = write!(writer.out, r#" opacity="{}""#, number(opacity as f64));
+    }
+
+    // Blend modes of the layers of a group apply only to the other layers of the group.
+    let isolation = matches!(item, RenderedItem::Group(_)).then_some("isolation:isolate");
+    let style: Vec<_> = css_blend_mode(blend_mode)
+        .map(|mode| format!("mix-blend-mode:{mode}"))
+        .into_iter()
+        .chain(isolation.map(str::to_string))
+        .collect();
+    if !style.is_empty() {
+        let _ = write!(writer.out, r#" style="{}""#, style.join(";"));
+    }
+    writer.out.push('>');
+
+    match item {
+        RenderedItem::Layer(layer) => {
+            for world_view in world_views {
+                let Some(projector) = Projector::new(world_view) else {
+                    log::warn!("Layer cannot be rendered to the map view.");
+                    continue;
+                };
+
+                let mut canvas = SvgCanvas {
+                    size,
+                    projector,
+                    writer: &mut *writer,
+                };
+                layer.render(world_view, &mut canvas);
+            }
+        }
+        RenderedItem::Group(layers) => {
+            layers.for_each_rendered(|_, item, opacity, blend_mode| {
+                write_rendered(writer, item, opacity, blend_mode, None, world_views, size);
+            });
+        }
+    }
+
+    writer.out.push_str("</g>");
+}
+
+fn css_blend_mode(blend_mode: BlendMode) -> Option<&'static str> {
+    match blend_mode {
+        BlendMode::Normal => None,
+        BlendMode::Multiply => Some("multiply"),
+        BlendMode::Screen => Some("screen"),
+        BlendMode::Additive => Some("plus-lighter"),
+    }
+}
+
+#[derive(Default)]
+struct SvgWriter {
+    out: String,
+    next_id: usize,
+    /// Ids of the images written to the document by the address of the image. The images are kept alive, so that the
+    /// addresses are not reused by other images.
+    images: HashMap<usize, (Arc<DecodedImage>, Option<String>)>,
+    /// Ids of the pattern definitions by their parameters.
+    patterns: HashMap<String, String>,
+}
+
+impl SvgWriter {
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}{}", self.next_id)
+    }
+
+    /// Returns the id of the image definition, writing the definition if the image has not been written yet.
+    fn image_id(&mut self, image: &Arc<DecodedImage>) -> Option<String> {
+        let key = Arc::as_ptr(image) as usize;
+        if let Some((_, id)) = self.images.get(&key) {
+            return id.clone();
+        }
+
+        let id = encode_png(image).map(|png| {
+            let id = self.next_id("image");
+            let _ = write!(
+                self.out,
+                r#"<defs><image id="{id}" width="{}" height="{}" preserveAspectRatio="none" xlink:href="data:image/png;base64,{}"/></defs>"#,
+                image.dimensions.0,
+                image.dimensions.1,
+                base64(&png),
+            );
+            id
+        });
+        self.images.insert(key, (image.clone(), id.clone()));
+        id
+    }
+
+    /// Returns the fill attributes of a hatch with the parameters of the [`PatternVertex`].
+    fn hatch_fill(&mut self, transform: &str, color: [u8; 4], pattern: [f32; 4]) -> String {
+        let [cos, sin, spacing, line_width] = pattern.map(|v| v as f64);
+        let spacing = number(spacing.max(1.0));
+        let half_width = number(line_width / 2.0);
+        let key = format!(
+            "hatch {transform} {} {spacing} {half_width} {color:?}",
+            precise_number(sin.atan2(cos).to_degrees()),
+        );
+        let id = match self.patterns.get(&key) {
+            Some(id) => id.clone(),
+            None => {
+                let id = self.next_id("pattern");
+                // The pattern is drawn in pixels of the map with Y axis going down, so the angle is reversed.
+                let _ = write!(
+                    self.out,
+                    r#"<defs><pattern id="{id}" patternUnits="userSpaceOnUse" y="-{half_width}" width="{spacing}" height="{spacing}" patternTransform="{transform} rotate({})"><rect y="-{half_width}" width="{spacing}" height="{}"{}/></pattern></defs>"#,
+                    precise_number(-sin.atan2(cos).to_degrees()),
+                    number(line_width),
+                    paint_attributes("fill", color),
+                );
+                self.patterns.insert(key, id.clone());
+                id
+            }
+        };
+
+        format!(r#" fill="url(#{id})""#)
+    }
+
+    /// Returns the fill attributes of an image pattern, or `None` if the image cannot be encoded.
+    fn image_pattern_fill(
+        &mut self,
+        transform: &str,
+        image: &Arc<DecodedImage>,
+        color: [u8; 4],
+    ) -> Option<String> {
+        let image_id = self.image_id(image)?;
+        let key = format!("image {transform} {image_id}");
+        let id = match self.patterns.get(&key) {
+            Some(id) => id.clone(),
+            None => {
+                let id = self.next_id("pattern");
+                let _ = write!(
+                    self.out,
+                    r##"<defs><pattern id="{id}" patternUnits="userSpaceOnUse" width="{}" height="{}" patternTransform="{transform}"><use xlink:href="#{image_id}"/></pattern></defs>"##,
+                    image.dimensions.0, image.dimensions.1,
+                );
+                self.patterns.insert(key, id.clone());
+                id
+            }
+        };
+
+        let mut fill = format!(r#" fill="url(#{id})""#);
+        if color[3] < 255 {
+            let _ = write!(
+                fill,
+                r#" fill-opacity="{}""#,
+                number(color[3] as f64 / 255.0)
+            );
+        }
+        Some(fill)
+    }
+}
+
+/// Converts map coordinates of the vertices into the pixel coordinates of the document, the same way the shaders of
+/// the wgpu renderer do.
+struct Projector {
+    transform: Matrix4<f64>,
+    rotation: Matrix4<f64>,
+    half_width: f64,
+    half_height: f64,
+    resolution: f64,
+    /// Transformation from the pixels of the map with Y axis going down, starting at the pattern origin, to the
+    /// document. For tilted views the transformation is taken at the center of the view.
+    pattern_transform: Option<String>,
+}
+
+impl Projector {
+    fn new(view: &MapView) -> Option<Self> {
+        let rotation = Rotation3::new(Vector3::new(view.rotation_x(), 0.0, -view.rotation_z()))
+            .to_homogeneous()
+            .transpose();
+        let mut projector = Self {
+            transform: view.map_to_scene_transform()?,
+            rotation,
+            half_width: view.size().half_width(),
+            half_height: view.size().half_height(),
+            resolution: view.resolution(),
+            pattern_transform: None,
+        };
+        projector.pattern_transform = projector.pattern_transform(view);
+
+        Some(projector)
+    }
+
+    fn pattern_transform(&self, view: &MapView) -> Option<String> {
+        let origin = pattern_origin(view)?;
+        let center = view.projected_position()?;
+        let resolution = self.resolution;
+        let [x, y] = self.project_f64(center.x, center.y, 0.0)?;
+        let [x_axis_x, x_axis_y] = self.project_f64(center.x + resolution, center.y, 0.0)?;
+        let [y_axis_x, y_axis_y] = self.project_f64(center.x, center.y - resolution, 0.0)?;
+        let (a, b, c, d) = (x_axis_x - x, x_axis_y - y, y_axis_x - x, y_axis_y - y);
+
+        // Position of the origin, extrapolated from the center with the same axes.
+        let dx = (origin.x - center.x) / resolution;
+        let dy = (center.y - origin.y) / resolution;
+        let e = x + a * dx + c * dy;
+        let f = y + b * dx + d * dy;
+
+        Some(format!(
+            "matrix({})",
+            [a, b, c, d, e, f].map(precise_number).join(" ")
+        ))
+    }
+
+    fn project(&self, position: [f32; 3]) -> Option<[f64; 2]> {
+        self.project_f64(position[0] as f64, position[1] as f64, position[2] as f64)
+    }
+
+    fn project_f64(&self, x: f64, y: f64, z: f64) -> Option<[f64; 2]> {
+        let scene = self.transform * Vector4::new(x, y, z, 1.0);
+        if scene.w <= 0.0 {
+            return None;
+        }
+
+        Some([
+            (scene.x / scene.w + 1.0) * self.half_width,
+            (1.0 - scene.y / scene.w) * self.half_height,
+        ])
+    }
+
+    /// Position of a vertex with the given offset in pixels, with Y axis of the offset going up.
+    fn project_with_offset(&self, position: [f32; 3], offset: [f32; 2]) -> Option<[f64; 2]> {
+        let [x, y] = self.project(position)?;
+        Some([x + offset[0] as f64, y - offset[1] as f64])
+    }
+
+    /// Position of an image corner. Offsets of map aligned images are rotated together with the map.
+    fn project_image_vertex(&self, vertex: &ImageVertex) -> Option<[f64; 2]> {
+        let position = [vertex.position[0], vertex.position[1], 0.0];
+        if vertex.map_aligned < 0.5 {
+            return self.project_with_offset(position, vertex.offset);
+        }
+
+        let offset = Vector4::new(vertex.offset[0] as f64, vertex.offset[1] as f64, 0.0, 0.0);
+        let offset = self.rotation * offset;
+        self.project_with_offset(position, [offset.x as f32, offset.y as f32])
+    }
+
+    /// Position of a vertex of a map referenced primitive. Line vertices are offset by their normal in pixels,
+    /// limited by the `norm_limit` in map units, and rotated together with the map.
+    fn poly_vertex(
+        &self,
+        tessellation: &VertexBuffers<PolyVertex, u32>,
+        index: u32,
+    ) -> Option<([f64; 2], [u8; 4])> {
+        let vertex = tessellation.vertices.get(index as usize)?;
+        Some((self.project_poly_vertex(vertex)?, color_bytes(vertex.color)))
+    }
+
+    fn pattern_triangle(
+        &self,
+        tessellation: &VertexBuffers<PatternVertex, u32>,
+        triangle: &[u32],
+    ) -> Option<Vec<[f64; 2]>> {
+        triangle
+            .iter()
+            .map(|index| self.project(tessellation.vertices.get(*index as usize)?.position))
+            .collect()
+    }
+
+    fn project_poly_vertex(&self, vertex: &PolyVertex) -> Option<[f64; 2]> {
+        let normal = Vector4::new(vertex.normal[0] as f64, vertex.normal[1] as f64, 0.0, 0.0);
+        let norm_length = normal.norm() * self.resolution;
+        let limit = if norm_length > vertex.norm_limit as f64 {
+            vertex.norm_limit as f64 / norm_length
+        } else {
+            1.0
+        };
+        let offset = self.rotation * normal * limit;
+
+        self.project_with_offset(vertex.position, [offset.x as f32, offset.y as f32])
+    }
+}
+
+struct SvgCanvas<'a> {
+    size: Size,
+    projector: Projector,
+    writer: &'a mut SvgWriter,
+}
+
+impl<'a> SvgCanvas<'a> {
+    /// Draws the bundle. The `filter` is the id of the SVG filter of the color adjustment applied to the images.
+    fn draw_bundle(&mut self, bundle: &SvgPackedBundle, filter: Option<&str>) {
+        let clipped = match &bundle.clip_area {
+            Some(clip) => {
+                let mut path = TrianglePath::default();
+                for triangle in clip.indices.chunks_exact(3) {
+                    let vertices: Option<Vec<_>> = triangle
+                        .iter()
+                        .map(|index| {
+                            self.projector
+                                .poly_vertex(clip, *index)
+                                .map(|(point, _)| point)
+                        })
+                        .collect();
+                    if let Some(vertices) = vertices {
+                        path.add(&vertices);
+                    }
+                }
+
+                let id = self.writer.next_id("clip");
+                let _ = write!(
+                    self.writer.out,
+                    r#"<clipPath id="{id}"><path d="{}"/></clipPath><g clip-path="url(#{id})">"#,
+                    path.data
+                );
+                true
+            }
+            None => false,
+        };
+
+        let filter = filter.filter(|_| !bundle.images.is_empty());
+        if let Some(id) = filter {
+            let _ = write!(self.writer.out, r#"<g filter="url(#{id})">"#);
+        }
+        for (image, vertices) in &bundle.images {
+            self.draw_image(image, vertices);
+        }
+        if filter.is_some() {
+            self.writer.out.push_str("</g>");
+        }
+
+        let projector = &self.projector;
+
+        let mut triangles = TriangleWriter::default();
+        Self::write_poly_triangles(projector, &bundle.map_ref, self.writer, &mut triangles);
+
+        if let Some(transform) = &projector.pattern_transform {
+            for triangle in bundle.hatch.indices.chunks_exact(3) {
+                let Some(vertices) = projector.pattern_triangle(&bundle.hatch, triangle) else {
+                    continue;
+                };
+                let vertex = &bundle.hatch.vertices[triangle[0] as usize];
+                let color = color_bytes(vertex.color);
+                if color[3] == 0 {
+                    continue;
+                }
+
+                let fill = self.writer.hatch_fill(transform, color, vertex.pattern);
+                triangles.add_filled(&mut self.writer.out, &vertices, fill);
+            }
+
+            for (image, tessellation) in &bundle.patterns {
+                let Some(vertex) = tessellation.vertices.first() else {
+                    continue;
+                };
+                let color = color_bytes(vertex.color);
+                let Some(fill) = self.writer.image_pattern_fill(transform, image, color) else {
+                    log::warn!("Failed to encode pattern image for the SVG document");
+                    continue;
+                };
+
+                for triangle in tessellation.indices.chunks_exact(3) {
+                    if let Some(vertices) = projector.pattern_triangle(tessellation, triangle) {
+                        triangles.add_filled(&mut self.writer.out, &vertices, fill.clone());
+                    }
+                }
+            }
+        }
+
+        Self::write_poly_triangles(projector, &bundle.extrusion, self.writer, &mut triangles);
+
+        let screen_ref = &bundle.screen_ref;
+        for triangle in screen_ref.indices.chunks_exact(3) {
+            let vertices: Option<Vec<_>> = triangle
+                .iter()
+                .map(|index| {
+                    let vertex = screen_ref.vertices.get(*index as usize)?;
+                    Some((
+                        projector.project_with_offset(vertex.position, vertex.normal)?,
+                        vertex.color,
+                    ))
+                })
+                .collect();
+            if let Some(vertices) = vertices {
+                triangles.add(&mut self.writer.out, &vertices);
+            }
+        }
+        triangles.flush(&mut self.writer.out);
+
+        for marker in &bundle.markers {
+            if let Some(center) = projector.project_with_offset(marker.position, marker.offset) {
+                write_marker(&mut self.writer.out, marker, center);
+            }
+        }
+
+        for point in &bundle.points {
+            if let Some([x, y]) = projector.project(point.position) {
+                let _ = write!(
+                    self.writer.out,
+                    r#"<rect x="{}" y="{}" width="1" height="1"{}/>"#,
+                    number(x - 0.5),
+                    number(y - 0.5),
+                    paint_attributes("fill", point.color)
+                );
+            }
+        }
+
+        if clipped {
+            self.writer.out.push_str("</g>");
+        }
+    }
+
+    fn write_poly_triangles(
+        projector: &Projector,
+        tessellation: &VertexBuffers<PolyVertex, u32>,
+        writer: &mut SvgWriter,
+        triangles: &mut TriangleWriter,
+    ) {
+        for triangle in tessellation.indices.chunks_exact(3) {
+            let vertices: Option<Vec<_>> = triangle
+                .iter()
+                .map(|index| projector.poly_vertex(tessellation, *index))
+                .collect();
+            if let Some(vertices) = vertices {
+                triangles.add(&mut writer.out, &vertices);
+            }
+        }
+    }
+
+    /// Draws the image with an affine transformation mapping its corners to the projected vertices.
+    fn draw_image(&mut self, image: &Arc<DecodedImage>, vertices: &[ImageVertex; 4]) {
+        let (width, height) = image.dimensions;
+        let opacity = vertices[0].opacity;
+        if width == 0 || height == 0 || opacity <= 0.0 {
+            return;
+        }
+
+        let corner = |tex_coords: [f32; 2]| {
+            let vertex = vertices.iter().find(|v| v.tex_coords == tex_coords)?;
+            self.projector.project_image_vertex(vertex)
+        };
+        let (Some(origin), Some(right), Some(bottom)) =
+            (corner([0.0, 0.0]), corner([1.0, 0.0]), corner([0.0, 1.0]))
+        else {
+            return;
+        };
+
+        let Some(id) = self.writer.image_id(image) else {
+            log::warn!("Failed to encode image for the SVG document");
+            return;
+        };
+
+        let matrix = [
+            (right[0] - origin[0]) / width as f64,
+            (right[1] - origin[1]) / width as f64,
+            (bottom[0] - origin[0]) / height as f64,
+            (bottom[1] - origin[1]) / height as f64,
+            origin[0],
+            origin[1],
+        ];
+        let _ = write!(
+            self.writer.out,
+            r##"<use xlink:href="#{id}" transform="matrix({})""##,
+            matrix.map(precise_number).join(" ")
+        );
+        if opacity < 1.0 {
+            let _ = write!(self.writer.out, r#" opacity="{}""#, number(opacity as f64));
+        }
+        self.writer.out.push_str("/>");
+    }
+}
+
+impl<'a> Canvas for SvgCanvas<'a> {
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    fn create_bundle(&self) -> RenderBundle {
+        RenderBundle(RenderBundleType::Tessellating(
+            TessellatingRenderBundle::new(),
+        ))
+    }
+
+    fn pack_bundle(&self, bundle: &RenderBundle) -> Box<dyn PackedBundle> {
+        match bundle {
+            RenderBundle(RenderBundleType::Tessellating(inner)) => {
+                Box::new(SvgPackedBundle::new(inner))
+            }
+        }
+    }
+
+    fn draw_bundles(&mut self, bundles: &[&dyn PackedBundle], options: RenderOptions) {
+        let mut filter = None;
+        if !options.color_adjustment.is_identity() {
+            let matrix = options.color_adjustment.color_matrix();
+            let mut values = String::new();
+            for row in 0..3 {
+                let _ = write!(
+                    values,
+                    "{} {} {} 0 {} ",
+                    matrix[(row, 0)],
+                    matrix[(row, 1)],
+                    matrix[(row, 2)],
+                    matrix[(row, 3)]
+                );
+            }
+            values.push_str("0 0 0 1 0");
+
+            let id = self.writer.next_id("filter");
+            let _ = write!(
+                self.writer.out,
+                r#"<defs><filter id="{id}" color-interpolation-filters="sRGB"><feColorMatrix type="matrix" values="{values}"/></filter></defs>"#
+            );
+            filter = Some(id);
+        }
+
+        for bundle in bundles {
+            if let Some(bundle) = bundle.as_any().downcast_ref::<SvgPackedBundle>() {
+                self.draw_bundle(bundle, filter.as_deref());
+            }
+        }
+    }
+
+    fn pack_heatmap(&self, _points: &[(Point2d, f32)]) -> Box<dyn PackedBundle> {
+        Box::new(SvgPackedBundle::new(&TessellatingRenderBundle::new()))
+    }
+
+    fn draw_heatmap(&mut self, _points: &dyn PackedBundle, _paint: HeatmapPaint) {
+        log::warn!("Heatmaps are not supported by the SVG renderer");
+    }
+
+    fn is_transient(&self) -> bool {
+        true
+    }
+}
+
+struct SvgPackedBundle {
+    clip_area: Option<VertexBuffers<PolyVertex, u32>>,
+    images: Vec<(Arc<DecodedImage>, [ImageVertex; 4])>,
+    map_ref: VertexBuffers<PolyVertex, u32>,
+    extrusion: VertexBuffers<PolyVertex, u32>,
+    hatch: VertexBuffers<PatternVertex, u32>,
+    patterns: Vec<(Arc<DecodedImage>, VertexBuffers<PatternVertex, u32>)>,
+    screen_ref: ScreenRefTessellation,
+    markers: Vec<MarkerInstance>,
+    points: Vec<PointInstance>,
+}
+
+impl SvgPackedBundle {
+    fn new(bundle: &TessellatingRenderBundle) -> Self {
+        let images = bundle
+            .images
+            .iter()
+            .filter_map(|info| match info {
+                ImageInfo::Image((store_index, vertices)) => {
+                    match bundle.image_store.get(*store_index)? {
+                        ImageStoreInfo::Image(image) => Some((image.clone(), *vertices)),
+                        ImageStoreInfo::Vacant => None,
+                    }
+                }
+                ImageInfo::Vacant => None,
+            })
+            .collect();
+
+        let patterns = bundle
+            .pattern_fills
+            .iter()
+            .filter_map(|info| match info {
+                PatternFillInfo::Fill {
+                    image_store_index,
+                    tessellation,
+                } => match bundle.image_store.get(*image_store_index)? {
+                    ImageStoreInfo::Image(image) => Some((image.clone(), tessellation.clone())),
+                    ImageStoreInfo::Vacant => None,
+                },
+                PatternFillInfo::Vacant => None,
+            })
+            .collect();
+
+        Self {
+            clip_area: bundle.clip_area.clone(),
+            images,
+            map_ref: bundle.poly_tessellation.clone(),
+            extrusion: bundle.extrusion_tessellation.clone(),
+            hatch: bundle.hatch_tessellation.clone(),
+            patterns,
+            screen_ref: bundle.screen_ref.clone(),
+            markers: bundle
+                .markers
+                .iter()
+                .filter(|marker| !marker.is_vacant())
+                .copied()
+                .collect(),
+            points: bundle.points.clone(),
+        }
+    }
+}
+
+impl PackedBundle for SvgPackedBundle {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Path data of a set of triangles. All triangles are added with the same orientation, so that the path filled with
+/// the non-zero rule covers their union without seams between adjacent triangles.
+#[derive(Default)]
+struct TrianglePath {
+    data: String,
+}
+
+impl TrianglePath {
+    fn add(&mut self, vertices: &[[f64; 2]]) {
+        let [a, mut b, mut c] = [vertices[0], vertices[1], vertices[2]];
+        let area = (b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1]);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        if area < 0.0 {
+            std::mem::swap(&mut b, &mut c);
+        }
+
+        let _ = write!(
+            self.data,
+            "M{} {}L{} {} {} {}Z",
+            number(a[0]),
+            number(a[1]),
+            number(b[0]),
+            number(b[1]),
+            number(c[0]),
+            number(c[1])
+        );
+    }
+}
+
+/// Writes consecutive triangles of the same fill as a single path.
+#[derive(Default)]
+struct TriangleWriter {
+    fill: Option<String>,
+    path: TrianglePath,
+}
+
+impl TriangleWriter {
+    fn add(&mut self, out: &mut String, vertices: &[([f64; 2], [u8; 4])]) {
+        let color = vertices[0].1;
+        if color[3] == 0 {
+            return;
+        }
+
+        let points: Vec<_> = vertices.iter().map(|(point, _)| *point).collect();
+        self.add_filled(out, &points, paint_attributes("fill", color));
+    }
+
+    /// Adds a triangle with the given fill attributes.
+    fn add_filled(&mut self, out: &mut String, vertices: &[[f64; 2]], fill: String) {
+        if self.fill.as_ref() != Some(&fill) {
+            self.flush(out);
+            self.fill = Some(fill);
+        }
+
+        self.path.add(vertices);
+    }
+
+    fn flush(&mut self, out: &mut String) {
+        let path = std::mem::take(&mut self.path);
+        if let (Some(fill), false) = (&self.fill, path.data.is_empty()) {
+            let _ = write!(out, r#"<path{fill} d="{}"/>"#, path.data);
+        }
+        self.fill = None;
+    }
+}
+
+/// Writes the marker as a filled path with the outline drawn as a stroke over it.
+fn write_marker(out: &mut String, marker: &MarkerInstance, center: [f64; 2]) {
+    let [from, to] = marker.outline_range.map(|distance| distance as f64);
+    let size = marker.size as f64;
+
+    if marker.fill[3] > 0 {
+        let _ = write!(
+            out,
+            r#"<path{} d="{}"/>"#,
+            paint_attributes("fill", marker.fill),
+            marker_path(marker, center, size)
+        );
+    }
+
+    if to > from && marker.outline[3] > 0 {
+        let _ = write!(
+            out,
+            r#"<path fill="none"{} stroke-width="{}" d="{}"/>"#,
+            paint_attributes("stroke", marker.outline),
+            number(to - from),
+            marker_path(marker, center, size + (from + to) / 2.0)
+        );
+    }
+}
+
+/// Path data of the marker shape with the given radius or half of the side.
+fn marker_path(marker: &MarkerInstance, [x, y]: [f64; 2], size: f64) -> String {
+    if marker.shape == MarkerInstance::SQUARE {
+        let (sin, cos) = (marker.rotation as f64).sin_cos();
+        // Rotation is counterclockwise with Y axis going up, while the Y axis of the document goes down.
+        let corners = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]].map(|[dx, dy]| {
+            let (dx, dy) = (dx * size, dy * size);
+            [x + dx * cos - dy * sin, y - (dx * sin + dy * cos)]
+        });
+        let [a, b, c, d] = corners.map(|[x, y]| format!("{} {}", number(x), number(y)));
+        format!("M{a}L{b} {c} {d}Z")
+    } else {
+        let r = number(size);
+        format!(
+            "M{} {}A{r} {r} 0 1 0 {} {}A{r} {r} 0 1 0 {} {}Z",
+            number(x - size),
+            number(y),
+            number(x + size),
+            number(y),
+            number(x - size),
+            number(y)
+        )
+    }
+}
+
+fn color_bytes(color: [f32; 4]) -> [u8; 4] {
+    color.map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8)
+}
+
+fn paint_attributes(attribute: &str, [r, g, b, a]: [u8; 4]) -> String {
+    let mut result = format!(r##" {attribute}="#{r:02x}{g:02x}{b:02x}""##);
+    if a < 255 {
+        let _ = write!(
+            result,
+            r#" {attribute}-opacity="{}""#,
+            number(a as f64 / 255.0)
+        );
+    }
+    result
+}
+
+/// Formats a coordinate with the precision of 0.01 pixel.
+fn number(value: f64) -> String {
+    trim_number(format!("{value:.2}"))
+}
+
+/// Formats a value of a transformation matrix, that requires more precision than coordinates.
+fn precise_number(value: f64) -> String {
+    trim_number(format!("{value:.6}"))
+}
+
+fn trim_number(formatted: String) -> String {
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" | "" => "0".to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+fn encode_png(image: &DecodedImage) -> Option<Vec<u8>> {
+    use image::ImageEncoder;
+
+    let mut png = vec![];
+    image::codecs::png::PngEncoder::new(&mut png)
+        .write_image(
+            &image.bytes,
+            image.dimensions.0,
+            image.dimensions.1,
+            image::ColorType::Rgba8,
+        )
+        .ok()?;
+    Some(png)
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, byte)| acc | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                result.push(ALPHABET[(value >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::symbol::CirclePointSymbol;
+    use crate::layer::feature_layer::FeatureLayer;
+    use crate::layer::Layer;
+    use crate::messenger::{DummyMessenger, Messenger};
+    use crate::render::render_bundle::RenderPrimitive;
+    use crate::render::{PolygonFill, PolygonPaint};
+    use galileo_types::cartesian::Point3d;
+    use galileo_types::geo::Crs;
+    use galileo_types::geometry_type::CartesianSpace2d;
+    use galileo_types::impls::{ClosedContour, Contour, Polygon};
+
+    const WIDTH: f64 = 100.0;
+    const HEIGHT: f64 = 60.0;
+
+    /// Draws a red square with the side of 20 pixels in the center of the view and a 2x1 image below it.
+    struct TestLayer;
+
+    impl Layer for TestLayer {
+        fn render(&self, _view: &MapView, canvas: &mut dyn Canvas) {
+            let mut bundle = canvas.create_bundle();
+            let polygon = Polygon::new(
+                ClosedContour::new(vec![
+                    Point3d::new(-10.0, -10.0, 0.0),
+                    Point3d::new(10.0, -10.0, 0.0),
+                    Point3d::new(10.0, 10.0, 0.0),
+                    Point3d::new(-10.0, 10.0, 0.0),
+                ]),
+                vec![],
+            );
+            bundle.add(
+                RenderPrimitive::<_, _, Contour<_>, _>::new_polygon(
+                    polygon,
+                    PolygonPaint {
+                        color: Color::RED,
+                        fill: PolygonFill::Solid,
+                    },
+                ),
+                1.0,
+            );
+            bundle.add_image(
+                DecodedImage::from_raw(vec![255; 8], 2, 1).unwrap(),
+                [
+                    Point2d::new(-10.0, -30.0),
+                    Point2d::new(-10.0, -20.0),
+                    Point2d::new(10.0, -20.0),
+                    Point2d::new(10.0, -30.0),
+                ],
+                crate::render::ImagePaint { opacity: 255 },
+            );
+
+            let packed = canvas.pack_bundle(&bundle);
+            canvas.draw_bundles(&[&*packed], RenderOptions::default());
+        }
+
+        fn prepare(&self, _view: &MapView) {}
+
+        fn set_messenger(&mut self, _messenger: Box<dyn Messenger>) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    fn test_map(layers: Vec<Box<dyn Layer>>) -> Map {
+        Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+                .with_size(Size::new(WIDTH, HEIGHT)),
+            layers,
+            None::<DummyMessenger>,
+        )
+    }
+
+    #[test]
+    fn renders_primitives_and_images() {
+        let mut map = test_map(vec![Box::new(TestLayer)]);
+        map.layers_mut().set_opacity(0, 0.5);
+        map.layers_mut().set_blend_mode(0, BlendMode::Multiply);
+
+        let svg = SvgRenderer::new()
+            .with_background(Color::TRANSPARENT)
+            .render(&map);
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"width="100" height="60" viewBox="0 0 100 60""#));
+        assert!(!svg.contains("<rect"));
+        assert!(svg.contains(r#"<g opacity="0.5" style="mix-blend-mode:multiply">"#));
+        assert!(svg.contains(r##"<path fill="#ff0000" d="M"##));
+        assert!(svg.contains("40 20"));
+        assert!(svg.contains("60 40"));
+        assert!(svg.contains(r#"width="2" height="1" preserveAspectRatio="none" xlink:href="data:image/png;base64,iVBORw0KGgo"#));
+        assert!(svg.contains(r#"transform="matrix(10 0 0 10 40 50)""#));
+        assert!(svg.ends_with("</g></svg>"));
+    }
+
+    #[test]
+    fn clips_swiped_layers() {
+        let mut map = test_map(vec![Box::new(TestLayer), Box::new(TestLayer)]);
+        let leading = map.layers().id(0);
+        let trailing = map.layers().id(1);
+        map.set_swipe(Some(crate::map::Swipe::new(vec![leading], vec![trailing])));
+
+        let svg = SvgRenderer::new().render(&map);
+
+        assert!(svg.contains(concat!(
+            r#"<defs><clipPath id="clip1"><rect x="0" y="0" width="50" height="60"/></clipPath>"#,
+            r#"<clipPath id="clip2"><rect x="50" y="0" width="50" height="60"/></clipPath></defs>"#
+        )));
+        assert!(svg.contains(r#"<g clip-path="url(#clip1)">"#));
+        assert!(svg.contains(r#"<g clip-path="url(#clip2)">"#));
+        assert!(svg.contains(r##"<rect x="49" y="0" width="2" height="60" fill="#ffffff"/>"##));
+    }
+
+    fn circle_map() -> Map {
+        let layer = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![Point2d::new(0.0, 0.0)],
+            CirclePointSymbol::new(Color::BLUE, 8.0),
+            Crs::EPSG3857,
+        );
+        test_map(vec![Box::new(layer)])
+    }
+
+    #[test]
+    fn feature_layer_keeps_screen_bundles() {
+        let map = circle_map();
+
+        let svg = SvgRenderer::new().render(&map);
+        assert!(svg.contains(r##"<rect width="100%" height="100%" fill="#ffffff"/>"##));
+        assert!(svg.contains(r##"<path fill="#0000ff""##));
+        assert_eq!(svg, SvgRenderer::new().render(&map));
+    }
+
+    #[test]
+    #[cfg(feature = "wgpu")]
+    #[ignore = "requires a graphics adapter"]
+    fn feature_layer_keeps_wgpu_bundles() {
+        use crate::render::WgpuRenderer;
+
+        let map = circle_map();
+        let renderer = tokio_test::block_on(WgpuRenderer::new_with_texture_rt(
+            galileo_types::cartesian::Size::new(WIDTH as u32, HEIGHT as u32),
+        ))
+        .expect("no graphics adapter is available");
+        renderer.render(&map).unwrap();
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+        let center = (30 * WIDTH as usize + 50) * 4;
+        assert_eq!(&image[center..center + 4], Color::BLUE.to_u8_array());
+    }
+
+    #[test]
+    fn hatch_and_pattern_fills_use_svg_patterns() {
+        use crate::layer::feature_layer::symbol::{PatternPolygonSymbol, SimplePolygonSymbol};
+        use crate::render::HatchFill;
+
+        let square = || {
+            Polygon::from(vec![
+                Point2d::new(-10.0, -10.0),
+                Point2d::new(10.0, -10.0),
+                Point2d::new(10.0, 10.0),
+                Point2d::new(-10.0, 10.0),
+            ])
+        };
+        let hatch = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![square()],
+            SimplePolygonSymbol::new(Color::BLUE).with_fill_hatch(HatchFill::new(
+                std::f64::consts::FRAC_PI_2,
+                6.0,
+                2.0,
+            )),
+            Crs::EPSG3857,
+        );
+        let pattern = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![square()],
+            PatternPolygonSymbol::new(Arc::new(
+                DecodedImage::from_raw(vec![255; 16], 2, 2).unwrap(),
+            )),
+            Crs::EPSG3857,
+        );
+        let map = test_map(vec![Box::new(hatch), Box::new(pattern)]);
+
+        let svg = SvgRenderer::new().render(&map);
+        assert!(svg.contains(r##"<pattern id="pattern1" patternUnits="userSpaceOnUse" y="-1" width="6" height="6" patternTransform="matrix(1 0 0 1 50 30) rotate(-90)"><rect y="-1" width="6" height="2" fill="#0000ff"/></pattern>"##));
+        assert!(svg.contains(r#"<path fill="url(#pattern1)" d="M"#));
+        assert!(svg.contains(r##"<pattern id="pattern3" patternUnits="userSpaceOnUse" width="2" height="2" patternTransform="matrix(1 0 0 1 50 30)"><use xlink:href="#image2"/></pattern>"##));
+        assert!(svg.contains(r#"<path fill="url(#pattern3)" d="M"#));
+    }
+
+    #[test]
+    fn background_tessellation_renders_same_features() {
+        use crate::layer::feature_layer::symbol::SimplePolygonSymbol;
+        use crate::layer::feature_layer::FeatureLayerOptions;
+
+        let layer = |background_tessellation| {
+            let polygons = (0..3)
+                .map(|i| {
+                    let x = i as f64 * 15.0 - 25.0;
+                    Polygon::from(vec![
+                        Point2d::new(x, -5.0),
+                        Point2d::new(x + 10.0, -5.0),
+                        Point2d::new(x + 10.0, 5.0),
+                        Point2d::new(x, 5.0),
+                    ])
+                })
+                .collect();
+            FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+                polygons,
+                SimplePolygonSymbol::new(Color::BLUE),
+                Crs::EPSG3857,
+            )
+            .with_options(FeatureLayerOptions {
+                background_tessellation,
+                ..Default::default()
+            })
+        };
+        let expected = SvgRenderer::new().render(&test_map(vec![Box::new(layer(false))]));
+        assert!(expected.contains(r##"<path fill="#0000ff""##));
+
+        let map = test_map(vec![Box::new(layer(true))]);
+        let is_tessellating = || {
+            map.layers()[0]
+                .as_any()
+                .downcast_ref::<FeatureLayer<Point2d, Polygon<Point2d>, SimplePolygonSymbol, CartesianSpace2d>>()
+                .unwrap()
+                .is_tessellating()
+        };
+        let mut svg = SvgRenderer::new().render(&map);
+        for _ in 0..500 {
+            if !is_tessellating() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            svg = SvgRenderer::new().render(&map);
+        }
+
+        assert!(!is_tessellating());
+        assert_eq!(svg, expected);
+    }
+
+    #[test]
+    fn base64_encoding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Many"), "TWFueQ==");
+    }
+}
@@ -0,0 +1,494 @@
+//! Text styles and rasterization of text labels.
+//!
+//! Labels are rasterized into images on CPU and rendered as screen-referenced images, so their size does not depend on
+//! the map resolution. Rasterized labels are cached by the [`Font`], so the same text with the same style is
+//! rasterized only once and is stored in GPU memory once per render bundle.
+
+use crate::decoded_image::DecodedImage;
+use crate::error::GalileoError;
+use crate::Color;
+use ab_glyph::{point, Font as _, FontArc, Glyph, PxScale, ScaleFont};
+use nalgebra::Vector2;
+use quick_cache::sync::Cache;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+const LABEL_CACHE_SIZE: usize = 1024;
+
+/// Font used to render text labels.
+///
+/// Fonts are cheap to clone: clones share the font data and the cache of rasterized labels.
+#[derive(Clone)]
+pub struct Font {
+    font: FontArc,
+    labels: Arc<Cache<LabelKey, Arc<DecodedImage>>>,
+}
+
+impl Font {
+    /// Creates a font from the contents of a TrueType or OpenType font file.
+    pub fn try_from_bytes(bytes: Vec<u8>) -> Result<Self, GalileoError> {
+        let font = FontArc::try_from_vec(bytes)
+            .map_err(|err| GalileoError::Generic(format!("invalid font: {err}")))?;
+        Ok(Self {
+            font,
+            labels: Arc::new(Cache::new(LABEL_CACHE_SIZE)),
+        })
+    }
+
+    /// Loads a TrueType or OpenType font from the file system path.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, GalileoError> {
+        Self::try_from_bytes(std::fs::read(path)?)
+    }
+}
+
+impl Debug for Font {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Font").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Font {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.labels, &other.labels)
+    }
+}
+
+/// Outline drawn around the text to make it readable over any background.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextHalo {
+    /// Color of the halo.
+    pub color: Color,
+    /// Width of the halo in pixels.
+    pub width: f32,
+}
+
+/// Point of the text box that is placed at the label position.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextAnchor {
+    /// Center of the text.
+    #[default]
+    Center,
+    /// Middle of the left side.
+    Left,
+    /// Middle of the right side.
+    Right,
+    /// Middle of the top side.
+    Top,
+    /// Middle of the bottom side.
+    Bottom,
+    /// Top left corner.
+    TopLeft,
+    /// Top right corner.
+    TopRight,
+    /// Bottom left corner.
+    BottomLeft,
+    /// Bottom right corner.
+    BottomRight,
+}
+
+impl TextAnchor {
+    /// Position of the anchor as a portion of the text box size, starting from the top left corner.
+    fn position(&self) -> Vector2<f32> {
+        let (x, y) = match self {
+            Self::Center => (0.5, 0.5),
+            Self::Left => (0.0, 0.5),
+            Self::Right => (1.0, 0.5),
+            Self::Top => (0.5, 0.0),
+            Self::Bottom => (0.5, 1.0),
+            Self::TopLeft => (0.0, 0.0),
+            Self::TopRight => (1.0, 0.0),
+            Self::BottomLeft => (0.0, 1.0),
+            Self::BottomRight => (1.0, 1.0),
+        };
+        Vector2::new(x, y)
+    }
+}
+
+/// Style of a text label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStyle {
+    /// Font of the text.
+    pub font: Font,
+    /// Size of the font in pixels.
+    pub font_size: f32,
+    /// Color of the text.
+    pub color: Color,
+    /// Halo around the text. If `None`, no halo is drawn.
+    pub halo: Option<TextHalo>,
+    /// Offset of the label from its position in pixels. Positive values move it to the right and to the bottom.
+    pub offset: Vector2<f32>,
+    /// Point of the text box that is placed at the label position.
+    pub anchor: TextAnchor,
+    /// Priority of the labels when they collide on the screen. Labels with higher priority are placed first, and
+    /// the labels overlapping them are hidden.
+    pub priority: f32,
+    /// If set to true, the labels are always shown, even if they overlap other labels.
+    pub allow_overlap: bool,
+}
+
+impl TextStyle {
+    /// Creates a new style with black text centered at the label position without a halo.
+    pub fn new(font: Font, font_size: f32) -> Self {
+        Self {
+            font,
+            font_size,
+            color: Color::BLACK,
+            halo: None,
+            offset: Vector2::default(),
+            anchor: TextAnchor::Center,
+            priority: 0.0,
+            allow_overlap: false,
+        }
+    }
+
+    /// Sets the color of the text.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the halo around the text.
+    pub fn with_halo(mut self, color: Color, width: f32) -> Self {
+        self.halo = Some(TextHalo { color, width });
+        self
+    }
+
+    /// Sets the offset of the label in pixels.
+    pub fn with_offset(mut self, offset: Vector2<f32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the anchor of the label.
+    pub fn with_anchor(mut self, anchor: TextAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Sets the placement priority of the labels.
+    pub fn with_priority(mut self, priority: f32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets whether the labels are shown even if they overlap other labels.
+    pub fn with_allow_overlap(mut self, allow_overlap: bool) -> Self {
+        self.allow_overlap = allow_overlap;
+        self
+    }
+
+    /// Returns the rasterized label and its anchor offset as a portion of the image size, as expected by
+    /// [`PointPaint::image`](super::point_paint::PointPaint::image).
+    pub(crate) fn rasterize(&self, text: &str) -> (Arc<DecodedImage>, Vector2<f32>) {
+        let key = LabelKey::new(text, self);
+        let image = match self.font.labels.get(&key) {
+            Some(image) => image,
+            None => {
+                let image = Arc::new(rasterize(text, self));
+                self.font.labels.insert(key, image.clone());
+                image
+            }
+        };
+
+        let (width, height) = (image.dimensions.0 as f32, image.dimensions.1 as f32);
+        let anchor = self.anchor.position();
+        let offset = Vector2::new(
+            anchor.x - self.offset.x / width,
+            anchor.y - self.offset.y / height,
+        );
+
+        (image, offset)
+    }
+
+    /// Rasterizes every glyph of a single-line text separately, so that the glyphs can be placed along a line.
+    /// Returns the glyphs and the width of the text in pixels. Line breaks are replaced with spaces, and whitespace
+    /// has no glyphs.
+    pub(crate) fn rasterize_glyphs(&self, text: &str) -> (Vec<LabelGlyph>, f32) {
+        let font = self
+            .font
+            .font
+            .as_scaled(PxScale::from(self.font_size.max(0.0)));
+        let padding = padding(self);
+
+        let mut glyphs = vec![];
+        let mut caret = 0.0;
+        let mut prev = None;
+        for c in text.chars().map(|c| if c == '\n' { ' ' } else { c }) {
+            let id = font.glyph_id(c);
+            if let Some(prev) = prev {
+                caret += font.kern(prev, id);
+            }
+            let advance = font.h_advance(id);
+            prev = Some(id);
+
+            if !c.is_whitespace() {
+                let (image, _) = self.rasterize(c.encode_utf8(&mut [0; 4]));
+                let offset =
+                    Vector2::new((padding + advance / 2.0) / image.dimensions.0 as f32, 0.5);
+                glyphs.push(LabelGlyph {
+                    image,
+                    offset,
+                    position: caret + advance / 2.0,
+                });
+            }
+
+            caret += advance;
+        }
+
+        (glyphs, caret)
+    }
+}
+
+/// Glyph of a text rasterized separately from other glyphs.
+#[derive(Debug, Clone)]
+pub(crate) struct LabelGlyph {
+    pub image: Arc<DecodedImage>,
+    /// Offset of the glyph center as a portion of the image size, starting from the top left corner.
+    pub offset: Vector2<f32>,
+    /// Distance from the start of the text to the center of the glyph in pixels.
+    pub position: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LabelKey {
+    text: String,
+    font_size: u32,
+    color: [u8; 4],
+    halo: Option<([u8; 4], u32)>,
+}
+
+impl LabelKey {
+    fn new(text: &str, style: &TextStyle) -> Self {
+        Self {
+            text: text.to_string(),
+            font_size: style.font_size.to_bits(),
+            color: style.color.to_u8_array(),
+            halo: style
+                .halo
+                .map(|halo| (halo.color.to_u8_array(), halo.width.to_bits())),
+        }
+    }
+}
+
+/// Rasterizes the text into an RGBA image. Lines of the text are separated by `\n` and aligned by the center.
+fn rasterize(text: &str, style: &TextStyle) -> DecodedImage {
+    let font = style
+        .font
+        .font
+        .as_scaled(PxScale::from(style.font_size.max(0.0)));
+    let padding = padding(style);
+    let line_height = font.height() + font.line_gap();
+
+    let lines: Vec<(Vec<Glyph>, f32)> = text
+        .lines()
+        .enumerate()
+        .map(|(line_index, line)| {
+            let baseline = padding + font.ascent() + line_index as f32 * line_height;
+            let mut caret = 0.0;
+            let mut prev = None;
+            let mut glyphs = vec![];
+            for c in line.chars() {
+                let id = font.glyph_id(c);
+                if let Some(prev) = prev {
+                    caret += font.kern(prev, id);
+                }
+                glyphs.push(id.with_scale_and_position(font.scale(), point(caret, baseline)));
+                caret += font.h_advance(id);
+                prev = Some(id);
+            }
+            (glyphs, caret)
+        })
+        .collect();
+
+    let text_width = lines.iter().map(|(_, width)| *width).fold(0.0, f32::max);
+    let width = (text_width + padding * 2.0).ceil().max(1.0) as usize;
+    let height = (lines.len().max(1) as f32 * line_height + padding * 2.0)
+        .ceil()
+        .max(1.0) as usize;
+
+    let mut coverage = vec![0.0f32; width * height];
+    for (glyphs, line_width) in lines {
+        let shift = padding + (text_width - line_width) / 2.0;
+        for mut glyph in glyphs {
+            glyph.position.x += shift;
+            let Some(outlined) = font.outline_glyph(glyph) else {
+                continue;
+            };
+
+            let bounds = outlined.px_bounds();
+            outlined.draw(|x, y, value| {
+                let x = x as i64 + bounds.min.x as i64;
+                let y = y as i64 + bounds.min.y as i64;
+                if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                    let pixel = &mut coverage[y as usize * width + x as usize];
+                    *pixel = pixel.max(value.min(1.0));
+                }
+            });
+        }
+    }
+
+    let halo = style
+        .halo
+        .filter(|halo| halo.width > 0.0)
+        .map(|halo| (halo.color, dilate(&coverage, width, height, halo.width)));
+
+    let text_color = style.color.to_f32_array();
+    let mut bytes = Vec::with_capacity(width * height * 4);
+    for (i, text_coverage) in coverage.iter().enumerate() {
+        let text_alpha = text_coverage * text_color[3];
+        let (halo_color, halo_alpha) = match &halo {
+            Some((color, halo_coverage)) => {
+                let color = color.to_f32_array();
+                (color, halo_coverage[i] * color[3] * (1.0 - text_alpha))
+            }
+            None => ([0.0; 4], 0.0),
+        };
+
+        let alpha = text_alpha + halo_alpha;
+        if alpha <= 0.0 {
+            bytes.extend_from_slice(&[0, 0, 0, 0]);
+            continue;
+        }
+
+        for channel in 0..3 {
+            let value =
+                (text_color[channel] * text_alpha + halo_color[channel] * halo_alpha) / alpha;
+            bytes.push((value * 255.0).round() as u8);
+        }
+        bytes.push((alpha * 255.0).round() as u8);
+    }
+
+    DecodedImage {
+        bytes,
+        dimensions: (width as u32, height as u32),
+    }
+}
+
+/// Empty space around the text in the rasterized image, leaving room for the halo.
+fn padding(style: &TextStyle) -> f32 {
+    let halo_width = style.halo.map(|halo| halo.width.max(0.0)).unwrap_or(0.0);
+    halo_width.ceil() + 1.0
+}
+
+/// Expands the coverage by `radius` pixels with antialiased edge. Pixels covered by the glyphs at least by half are
+/// treated as fully covered, so the halo is opaque along the whole glyph outline.
+fn dilate(coverage: &[f32], width: usize, height: usize, radius: f32) -> Vec<f32> {
+    let extent = radius.ceil() as i64;
+    let kernel: Vec<(i64, i64, f32)> = (-extent..=extent)
+        .flat_map(|dy| (-extent..=extent).map(move |dx| (dx, dy)))
+        .filter_map(|(dx, dy)| {
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+            let weight = (radius + 0.5 - distance).clamp(0.0, 1.0);
+            (weight > 0.0).then_some((dx, dy, weight))
+        })
+        .collect();
+
+    let mut result = vec![0.0f32; coverage.len()];
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            let value = (coverage[y as usize * width + x as usize] * 2.0).min(1.0);
+            if value <= 0.0 {
+                continue;
+            }
+
+            for (dx, dy, weight) in &kernel {
+                let (nx, ny) = (x + dx, y + dy);
+                if (0..width as i64).contains(&nx) && (0..height as i64).contains(&ny) {
+                    let pixel = &mut result[ny as usize * width + nx as usize];
+                    *pixel = pixel.max(value * weight);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn test_font() -> Font {
+        Font::from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/examples/data/fonts/Hack-Regular.ttf"
+        ))
+        .unwrap()
+    }
+
+    fn pixel(image: &DecodedImage, x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * image.dimensions.0 + x) * 4) as usize;
+        image.bytes[index..index + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn invalid_font() {
+        assert!(Font::try_from_bytes(vec![1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn rasterize_text() {
+        let style = TextStyle::new(test_font(), 20.0).with_color(Color::RED);
+        let (short, _) = style.rasterize("a");
+        let (long, _) = style.rasterize("abc");
+        assert!(long.dimensions.0 > short.dimensions.0);
+        assert_eq!(long.dimensions.1, short.dimensions.1);
+        assert_eq!(
+            long.bytes.len() as u32,
+            long.dimensions.0 * long.dimensions.1 * 4
+        );
+
+        assert!(long.bytes.chunks(4).any(|pixel| pixel == [255, 0, 0, 255]));
+        assert!(long
+            .bytes
+            .chunks(4)
+            .all(|pixel| pixel[3] == 0 || pixel[..3] == [255, 0, 0]));
+
+        let (two_lines, _) = style.rasterize("abc\nabc");
+        assert_eq!(two_lines.dimensions.0, long.dimensions.0);
+        assert!(two_lines.dimensions.1 > long.dimensions.1);
+    }
+
+    #[test]
+    fn rasterize_halo() {
+        let plain = TextStyle::new(test_font(), 20.0);
+        let with_halo = plain.clone().with_halo(Color::WHITE, 2.0);
+
+        let (plain_image, _) = plain.rasterize("l");
+        let (halo_image, _) = with_halo.rasterize("l");
+        assert_eq!(halo_image.dimensions.0, plain_image.dimensions.0 + 4);
+
+        assert!(halo_image
+            .bytes
+            .chunks(4)
+            .any(|pixel| pixel == [255, 255, 255, 255]));
+        let opaque = |image: &DecodedImage| image.bytes.chunks(4).filter(|p| p[3] > 0).count();
+        assert!(opaque(&halo_image) > opaque(&plain_image));
+        assert_eq!(pixel(&halo_image, 0, 0), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn labels_are_cached() {
+        let style = TextStyle::new(test_font(), 14.0);
+        let (first, _) = style.rasterize("label");
+        let (second, _) = style.clone().rasterize("label");
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let (other_color, _) = style.clone().with_color(Color::BLUE).rasterize("label");
+        assert!(!Arc::ptr_eq(&first, &other_color));
+    }
+
+    #[test]
+    fn anchor_and_offset() {
+        let style = TextStyle::new(test_font(), 14.0);
+        let (image, offset) = style.rasterize("label");
+        assert_eq!(offset, Vector2::new(0.5, 0.5));
+
+        let (_, offset) = style
+            .with_anchor(TextAnchor::BottomLeft)
+            .with_offset(Vector2::new(image.dimensions.0 as f32, 0.0))
+            .rasterize("label");
+        assert_eq!(offset, Vector2::new(-1.0, 1.0));
+    }
+}
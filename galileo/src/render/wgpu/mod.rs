@@ -1,14 +1,14 @@
 use cfg_if::cfg_if;
-use galileo_types::cartesian::Size;
+use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect, Size};
 use lyon::tessellation::VertexBuffers;
-use nalgebra::{Rotation3, Vector3};
+use nalgebra::{Matrix4, Rotation3, Vector3};
 use std::any::Any;
 use std::mem::size_of;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use wgpu::util::DeviceExt;
 use wgpu::{
-    Adapter, Buffer, BufferAddress, BufferDescriptor, BufferUsages, Device, Extent3d,
-    ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, Queue,
+    Adapter, BindGroup, BindGroupLayout, Buffer, BufferAddress, BufferDescriptor, BufferUsages,
+    Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Origin3d, Queue,
     RenderPassDepthStencilAttachment, StoreOp, Surface, SurfaceConfiguration, SurfaceError,
     SurfaceTexture, Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat,
     TextureUsages, TextureView, TextureViewDescriptor, WasmNotSendSync,
@@ -16,24 +16,63 @@ use wgpu::{
 
 use crate::error::GalileoError;
 use crate::layer::Layer;
-use crate::map::Map;
+use crate::map::{Map, RenderedItem};
+use crate::overlay;
 use crate::render::render_bundle::tessellating::{
     PointInstance, PolyVertex, TessellatingRenderBundle,
 };
 use crate::render::render_bundle::{RenderBundle, RenderBundleType};
+use crate::render::wgpu::pipelines::heatmap::{HeatmapInstance, WgpuHeatmapPoints};
 use crate::render::wgpu::pipelines::image::WgpuImage;
+use crate::render::wgpu::pipelines::pattern::WgpuPatternFill;
 use crate::render::wgpu::pipelines::Pipelines;
 use crate::view::MapView;
 use crate::Color;
 
-use super::render_bundle::tessellating::{ImageInfo, ImageStoreInfo};
-use super::{Canvas, PackedBundle, RenderOptions};
+use super::render_bundle::tessellating::{ImageInfo, ImageStoreInfo, PatternFillInfo};
+use super::{pattern_origin, BlendMode, Canvas, HeatmapPaint, PackedBundle, RenderOptions};
 
 mod pipelines;
 
 const DEFAULT_BACKGROUND: Color = Color::WHITE;
 const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth24PlusStencil8;
 const TARGET_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+const DEFAULT_SAMPLE_COUNT: u32 = 4;
+const VALID_SAMPLE_COUNTS: [u32; 4] = [1, 2, 4, 8];
+/// Maximum size of the tiles used by [`WgpuRenderer::render_large`]. Larger tiles make the intermediate textures
+/// take too much memory with multisampling.
+const MAX_EXPORT_TILE_SIZE: u32 = 2048;
+
+/// Technique used by [`WgpuRenderer`] to smooth the edges of lines.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LineAntialiasing {
+    /// Edges of lines are smoothed by multisample antialiasing only. With the sample count of 1 lines are drawn with
+    /// jagged edges.
+    #[default]
+    Multisampling,
+    /// Edges of lines are additionally faded out by the shader over the width of one pixel. This gives smooth lines
+    /// regardless of the sample count, which is most noticeable on thin lines.
+    Feathering,
+}
+
+/// Options of a [`WgpuRenderer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WgpuRendererOptions {
+    /// Number of samples per pixel used for multisample antialiasing. Must be one of 1, 2, 4 or 8. The value of 1
+    /// disables multisampling. Default value is 4.
+    pub sample_count: u32,
+    /// Technique used to smooth the edges of lines.
+    pub line_antialiasing: LineAntialiasing,
+}
+
+impl Default for WgpuRendererOptions {
+    fn default() -> Self {
+        Self {
+            sample_count: DEFAULT_SAMPLE_COUNT,
+            line_antialiasing: LineAntialiasing::default(),
+        }
+    }
+}
 
 /// Render backend that uses `wgpu` crate to render the map.
 pub struct WgpuRenderer {
@@ -41,6 +80,8 @@ pub struct WgpuRenderer {
     queue: Arc<Queue>,
     render_set: Option<RenderSet>,
     background: Color,
+    sample_count: u32,
+    line_antialiasing: LineAntialiasing,
 }
 
 struct RenderSet {
@@ -49,6 +90,36 @@ struct RenderSet {
     multisampling_view: TextureView,
     stencil_view_multisample: TextureView,
     stencil_view: TextureView,
+    /// Copy of the last frame presented to the surface, used to capture the frame after it was presented.
+    frame_copy: Option<Texture>,
+    /// Offscreen targets of the layers drawn with opacity or blend mode, indexed by the nesting depth of the layer in
+    /// the layer groups. Created when first needed.
+    layer_targets: Mutex<Vec<Arc<LayerTarget>>>,
+}
+
+/// Offscreen texture a layer is drawn into before it is composited onto its parent target with the opacity and
+/// blend mode of the layer.
+struct LayerTarget {
+    view: TextureView,
+    multisampling_view: TextureView,
+    composite_binding: BindGroup,
+}
+
+impl LayerTarget {
+    fn draw_target(&self) -> DrawTarget<'_> {
+        DrawTarget {
+            view: &self.view,
+            multisampling_view: &self.multisampling_view,
+        }
+    }
+}
+
+/// Color attachments the layers are drawn to.
+#[derive(Clone, Copy)]
+struct DrawTarget<'a> {
+    view: &'a TextureView,
+    /// Multisampled texture resolved into the `view` by the antialiased draws.
+    multisampling_view: &'a TextureView,
 }
 
 enum RenderTarget {
@@ -81,7 +152,7 @@ impl<'a> RenderTargetTexture<'a> {
 }
 
 impl RenderTarget {
-    fn texture(&self) -> Result<RenderTargetTexture, SurfaceError> {
+    fn texture(&self) -> Result<RenderTargetTexture<'_>, SurfaceError> {
         match &self {
             RenderTarget::Surface { surface, .. } => {
                 Ok(RenderTargetTexture::Surface(surface.get_current_texture()?))
@@ -110,25 +181,29 @@ impl WgpuRenderer {
     ///
     /// Returns `None` if a device adapter cannot be acquired.
     pub async fn new() -> Option<Self> {
-        let instance = Self::create_instance();
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: None,
-                force_fallback_adapter: false,
-            })
-            .await?;
-
-        let (device, queue) = Self::create_device(&adapter).await;
+        let adapter = Self::request_adapter().await?;
+        let (device, queue) = Self::create_device(&adapter, wgpu::Features::empty()).await;
 
         Some(Self {
             device: Arc::new(device),
             queue: Arc::new(queue),
             render_set: None,
             background: DEFAULT_BACKGROUND,
+            sample_count: DEFAULT_SAMPLE_COUNT,
+            line_antialiasing: LineAntialiasing::default(),
         })
     }
 
+    async fn request_adapter() -> Option<Adapter> {
+        Self::create_instance()
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await
+    }
+
     /// Creates a new wgpu renderer that renders the map to an image buffer of the given size.
     ///
     /// Returns `None` if a device adapter cannot be acquired.
@@ -139,6 +214,167 @@ impl WgpuRenderer {
         Some(renderer)
     }
 
+    /// Blocking version of [`WgpuRenderer::new_with_texture_rt`]. Can be called outside of any async runtime.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn new_with_texture_rt_blocking(size: Size<u32>) -> Option<Self> {
+        futures::executor::block_on(Self::new_with_texture_rt(size))
+    }
+
+    /// Creates a new wgpu renderer that renders the map to an image buffer of the given size, using multisample
+    /// antialiasing with the given number of samples per pixel.
+    ///
+    /// The map is drawn into an intermediate multisampled texture, that is resolved into the image buffer of the
+    /// given `size`, so [`WgpuRenderer::get_image`] returns an image of the requested size regardless of the sample
+    /// count. Setting `sample_count` to 1 disables antialiasing. [`WgpuRenderer::new_with_texture_rt`] uses 4
+    /// samples.
+    ///
+    /// The sample count must be one of 1, 2, 4 or 8, otherwise an error is returned. If the adapter does not support
+    /// the given sample count, the largest supported smaller count is used instead and a warning is logged. The
+    /// actually used value can be checked with [`WgpuRenderer::sample_count`].
+    pub async fn new_with_texture_rt_msaa(
+        size: Size<u32>,
+        sample_count: u32,
+    ) -> Result<Self, GalileoError> {
+        Self::new_with_texture_rt_and_options(
+            size,
+            WgpuRendererOptions {
+                sample_count,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Creates a new wgpu renderer that renders the map to an image buffer of the given size with the given options.
+    ///
+    /// See [`WgpuRenderer::new_with_texture_rt_msaa`] for how the sample count is validated and adjusted to the
+    /// capabilities of the adapter.
+    pub async fn new_with_texture_rt_and_options(
+        size: Size<u32>,
+        options: WgpuRendererOptions,
+    ) -> Result<Self, GalileoError> {
+        Self::validate_sample_count(options.sample_count)?;
+
+        let adapter = Self::request_adapter()
+            .await
+            .ok_or_else(|| GalileoError::Generic("failed to acquire a graphics adapter".into()))?;
+        let sample_count =
+            Self::supported_sample_count(&adapter, TARGET_TEXTURE_FORMAT, options.sample_count);
+        let (device, queue) = Self::create_msaa_device(&adapter).await;
+
+        let mut renderer = Self {
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            render_set: None,
+            background: DEFAULT_BACKGROUND,
+            sample_count,
+            line_antialiasing: options.line_antialiasing,
+        };
+        renderer.init_target_texture(size);
+
+        Ok(renderer)
+    }
+
+    /// Blocking version of [`WgpuRenderer::new_with_texture_rt_and_options`]. Can be called outside of any async
+    /// runtime.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn new_with_texture_rt_and_options_blocking(
+        size: Size<u32>,
+        options: WgpuRendererOptions,
+    ) -> Result<Self, GalileoError> {
+        futures::executor::block_on(Self::new_with_texture_rt_and_options(size, options))
+    }
+
+    fn validate_sample_count(sample_count: u32) -> Result<(), GalileoError> {
+        if !VALID_SAMPLE_COUNTS.contains(&sample_count) {
+            return Err(GalileoError::Generic(format!(
+                "invalid MSAA sample count {sample_count}, expected one of {VALID_SAMPLE_COUNTS:?}"
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn create_msaa_device(adapter: &Adapter) -> (Device, Queue) {
+        // Sample counts other than 1 and 4 are only available with adapter specific format features.
+        let features =
+            adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+        Self::create_device(adapter, features).await
+    }
+
+    /// Blocking version of [`WgpuRenderer::new_with_texture_rt_msaa`]. Can be called outside of any async runtime.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn new_with_texture_rt_msaa_blocking(
+        size: Size<u32>,
+        sample_count: u32,
+    ) -> Result<Self, GalileoError> {
+        futures::executor::block_on(Self::new_with_texture_rt_msaa(size, sample_count))
+    }
+
+    /// Number of samples per pixel used for antialiasing. The value of 1 means antialiasing is disabled.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Technique used to smooth the edges of lines.
+    pub fn line_antialiasing(&self) -> LineAntialiasing {
+        self.line_antialiasing
+    }
+
+    /// Sets the technique used to smooth the edges of lines.
+    pub fn set_line_antialiasing(&mut self, line_antialiasing: LineAntialiasing) {
+        if self.line_antialiasing == line_antialiasing {
+            return;
+        }
+
+        self.line_antialiasing = line_antialiasing;
+        if let Some(render_set) = &mut self.render_set {
+            render_set.pipelines = Pipelines::create(
+                &self.device,
+                render_set.render_target.format(),
+                self.sample_count,
+                line_antialiasing,
+            );
+        }
+    }
+
+    /// Returns the largest sample count not greater than `requested` that both the target and depth textures
+    /// support on the adapter. If it is different from the requested count, a warning is logged.
+    fn supported_sample_count(adapter: &Adapter, format: TextureFormat, requested: u32) -> u32 {
+        let has_specific_features = adapter
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+
+        let supported = VALID_SAMPLE_COUNTS
+            .into_iter()
+            .rev()
+            .filter(|&count| count <= requested)
+            .find(|&count| {
+                if count == 1 {
+                    return true;
+                }
+                if count != 4 && !has_specific_features {
+                    return false;
+                }
+
+                [format, DEPTH_FORMAT].into_iter().all(|format| {
+                    adapter
+                        .get_texture_format_features(format)
+                        .flags
+                        .sample_count_supported(count)
+                })
+            })
+            .unwrap_or(1);
+
+        if supported != requested {
+            log::warn!(
+                "MSAA sample count {requested} is not supported by the adapter, using {supported} instead"
+            );
+        }
+
+        supported
+    }
+
     fn init_target_texture(&mut self, size: Size<u32>) {
         let target_texture = Self::create_target_texture(&self.device, size);
         let render_target = RenderTarget::Texture(target_texture, size);
@@ -171,19 +407,28 @@ impl WgpuRenderer {
                 multisampling_view,
                 stencil_view_multisample,
                 stencil_view,
+                ..
             }) if new_target.size() == render_target.size() => {
                 let pipelines = if new_target.format() == render_target.format() {
                     pipelines
                 } else {
-                    Pipelines::create(&self.device, new_target.format())
+                    Pipelines::create(
+                        &self.device,
+                        new_target.format(),
+                        self.sample_count,
+                        self.line_antialiasing,
+                    )
                 };
 
+                let frame_copy = Self::create_frame_copy(&self.device, &new_target);
                 self.render_set = Some(RenderSet {
                     render_target: new_target,
                     pipelines,
                     multisampling_view,
                     stencil_view_multisample,
                     stencil_view,
+                    frame_copy,
+                    layer_targets: Default::default(),
                 })
             }
             _ => self.render_set = Some(self.create_render_set(new_target)),
@@ -194,11 +439,20 @@ impl WgpuRenderer {
         let size = render_target.size();
         let format = render_target.format();
 
-        let multisampling_view = Self::create_multisample_texture(&self.device, size, format);
-        let stencil_view_multisample = Self::create_stencil_texture(&self.device, size, 4);
+        let multisampling_view =
+            Self::create_multisample_texture(&self.device, size, format, self.sample_count);
+        let stencil_view_multisample =
+            Self::create_stencil_texture(&self.device, size, self.sample_count);
         let stencil_view = Self::create_stencil_texture(&self.device, size, 1);
 
-        let pipelines = Pipelines::create(&self.device, format);
+        let pipelines = Pipelines::create(
+            &self.device,
+            format,
+            self.sample_count,
+            self.line_antialiasing,
+        );
+
+        let frame_copy = Self::create_frame_copy(&self.device, &render_target);
 
         RenderSet {
             render_target,
@@ -206,7 +460,50 @@ impl WgpuRenderer {
             multisampling_view,
             stencil_view_multisample,
             stencil_view,
+            frame_copy,
+            layer_targets: Default::default(),
+        }
+    }
+
+    /// Creates a texture to keep the copy of the frames presented to the surface target. The copy is only possible if
+    /// the surface is configured with `COPY_SRC` usage and has a 8-bit RGBA or BGRA format.
+    fn create_frame_copy(device: &Device, render_target: &RenderTarget) -> Option<Texture> {
+        let RenderTarget::Surface { config, .. } = render_target else {
+            return None;
+        };
+
+        if !config.usage.contains(TextureUsages::COPY_SRC) {
+            return None;
+        }
+
+        if !matches!(
+            config.format,
+            TextureFormat::Rgba8Unorm
+                | TextureFormat::Rgba8UnormSrgb
+                | TextureFormat::Bgra8Unorm
+                | TextureFormat::Bgra8UnormSrgb
+        ) {
+            log::warn!(
+                "Frames of surface with format {:?} cannot be captured",
+                config.format
+            );
+            return None;
         }
+
+        Some(device.create_texture(&TextureDescriptor {
+            label: Some("Frame copy texture"),
+            size: Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: config.format,
+            usage: TextureUsages::COPY_DST | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        }))
     }
 
     /// Creates a new wgpu renderer that renders the map to the given window. The given size must be equal to the
@@ -220,19 +517,94 @@ impl WgpuRenderer {
             + WasmNotSendSync
             + 'static,
     {
-        let (surface, adapter) = Self::get_window_surface(window).await?;
-        let (device, queue) = Self::create_device(&adapter).await;
+        Self::new_with_window_and_options(window, size, WgpuRendererOptions::default())
+            .await
+            .ok()
+    }
+
+    /// Creates a new wgpu renderer that renders the map to the given window with the given options. The given size
+    /// must be equal to the window size.
+    ///
+    /// See [`WgpuRenderer::new_with_texture_rt_msaa`] for how the sample count is validated and adjusted to the
+    /// capabilities of the adapter.
+    pub async fn new_with_window_and_options<W>(
+        window: Arc<W>,
+        size: Size<u32>,
+        options: WgpuRendererOptions,
+    ) -> Result<Self, GalileoError>
+    where
+        W: raw_window_handle::HasWindowHandle
+            + raw_window_handle::HasDisplayHandle
+            + WasmNotSendSync
+            + 'static,
+    {
+        Self::validate_sample_count(options.sample_count)?;
+
+        let (surface, adapter) = Self::get_window_surface(window)
+            .await
+            .ok_or_else(|| GalileoError::Generic("failed to acquire a graphics adapter".into()))?;
+        Ok(Self::with_surface(surface, adapter, size, options).await)
+    }
+
+    /// Creates a new wgpu renderer that renders the map to the given `OffscreenCanvas` with the given options. The
+    /// given size must be equal to the canvas size.
+    ///
+    /// Unlike a window, an offscreen canvas can be transferred to a web worker with
+    /// `HTMLCanvasElement.transferControlToOffscreen()`, so that the map is rendered without blocking the main thread
+    /// of the page. See [`worker`](crate::platform::worker) module for the helpers to send user input to the worker.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn new_with_offscreen_canvas(
+        canvas: web_sys::OffscreenCanvas,
+        size: Size<u32>,
+        options: WgpuRendererOptions,
+    ) -> Result<Self, GalileoError> {
+        Self::validate_sample_count(options.sample_count)?;
+
+        let instance = Self::create_instance();
+        log::info!("Creating new surface for an offscreen canvas");
+        let surface = instance
+            .create_surface(wgpu::SurfaceTarget::OffscreenCanvas(canvas))
+            .map_err(|err| {
+                GalileoError::Generic(format!("failed to create a surface from canvas: {err}"))
+            })?;
+        let adapter = Self::request_surface_adapter(&instance, &surface)
+            .await
+            .ok_or_else(|| GalileoError::Generic("failed to acquire a graphics adapter".into()))?;
+
+        Ok(Self::with_surface(surface, adapter, size, options).await)
+    }
+
+    async fn with_surface(
+        surface: Surface<'static>,
+        adapter: Adapter,
+        size: Size<u32>,
+        options: WgpuRendererOptions,
+    ) -> Self {
+        let (device, queue) = Self::create_msaa_device(&adapter).await;
 
         let config = Self::get_surface_configuration(&surface, &adapter, size);
         log::info!("Configuring surface with size {size:?}");
         surface.configure(&device, &config);
 
-        Some(Self::new_with_device_and_surface(
-            Arc::new(device),
-            Arc::new(surface),
-            Arc::new(queue),
+        let render_target = RenderTarget::Surface {
+            surface: Arc::new(surface),
             config,
-        ))
+        };
+        let mut renderer = Self {
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            render_set: None,
+            background: DEFAULT_BACKGROUND,
+            sample_count: Self::supported_sample_count(
+                &adapter,
+                render_target.format(),
+                options.sample_count,
+            ),
+            line_antialiasing: options.line_antialiasing,
+        };
+        renderer.init_render_set(render_target);
+
+        renderer
     }
 
     /// Creates a wgpu surface for the given window.
@@ -256,14 +628,21 @@ impl WgpuRenderer {
             }
         };
 
-        let adapter = instance
+        let adapter = Self::request_surface_adapter(&instance, &surface).await?;
+        Some((surface, adapter))
+    }
+
+    async fn request_surface_adapter(
+        instance: &wgpu::Instance,
+        surface: &Surface<'_>,
+    ) -> Option<Adapter> {
+        instance
             .request_adapter(&wgpu::RequestAdapterOptions {
                 power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
+                compatible_surface: Some(surface),
                 force_fallback_adapter: false,
             })
-            .await?;
-        Some((surface, adapter))
+            .await
     }
 
     fn get_surface_configuration(
@@ -279,8 +658,12 @@ impl WgpuRenderer {
             .find(|f| f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
 
+        // Copying from the surface texture allows capturing the presented frames.
+        let usage =
+            TextureUsages::RENDER_ATTACHMENT | (surface_caps.usages & TextureUsages::COPY_SRC);
+
         SurfaceConfiguration {
-            usage: TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format: surface_format,
             width: size.width(),
             height: size.height(),
@@ -304,6 +687,8 @@ impl WgpuRenderer {
             queue,
             render_set: None,
             background: DEFAULT_BACKGROUND,
+            sample_count: DEFAULT_SAMPLE_COUNT,
+            line_antialiasing: LineAntialiasing::default(),
         };
         renderer.init_render_set(render_target);
 
@@ -311,10 +696,20 @@ impl WgpuRenderer {
     }
 
     /// Set the background color for the map.
+    ///
+    /// The render target is cleared with this color before the layers are drawn, so it is visible in all the areas
+    /// not covered by any layer (e.g. not yet loaded tiles). The default background is opaque white, which gives
+    /// images with alpha 255 everywhere. Set it to [`Color::TRANSPARENT`] to render images with transparent
+    /// background.
     pub fn set_background(&mut self, color: Color) {
         self.background = color;
     }
 
+    /// Background color of the map. See [`WgpuRenderer::set_background`].
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
     /// Returns `true` if the renderer can be used to draw to.
     pub fn initialized(&self) -> bool {
         self.render_set.is_some()
@@ -337,11 +732,11 @@ impl WgpuRenderer {
         })
     }
 
-    async fn create_device(adapter: &Adapter) -> (Device, Queue) {
+    async fn create_device(adapter: &Adapter, features: wgpu::Features) -> (Device, Queue) {
         adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
+                    required_features: features,
                     required_limits: if cfg!(any(target_arch = "wasm32", target_os = "android")) {
                         wgpu::Limits {
                             max_texture_dimension_2d: 4096,
@@ -362,6 +757,7 @@ impl WgpuRenderer {
         device: &Device,
         size: Size<u32>,
         format: TextureFormat,
+        sample_count: u32,
     ) -> TextureView {
         let multisampling_texture = device.create_texture(&TextureDescriptor {
             label: Some("Multisampling texture"),
@@ -371,7 +767,7 @@ impl WgpuRenderer {
                 depth_or_array_layers: 1,
             },
             mip_level_count: 1,
-            sample_count: 4,
+            sample_count,
             dimension: TextureDimension::D2,
             format,
             usage: TextureUsages::RENDER_ATTACHMENT,
@@ -465,16 +861,40 @@ impl WgpuRenderer {
                     surface.configure(&self.device, config);
                 }
                 RenderTarget::Texture(texture, size) => {
-                    *texture = Self::create_target_texture(&self.device, *size);
+                    *texture = Self::create_target_texture(&self.device, new_size);
                     *size = new_size
                 }
             }
 
             render_set.multisampling_view =
-                Self::create_multisample_texture(&self.device, new_size, format);
+                Self::create_multisample_texture(&self.device, new_size, format, self.sample_count);
             render_set.stencil_view_multisample =
-                Self::create_stencil_texture(&self.device, new_size, 4);
+                Self::create_stencil_texture(&self.device, new_size, self.sample_count);
             render_set.stencil_view = Self::create_stencil_texture(&self.device, new_size, 1);
+            render_set.frame_copy =
+                Self::create_frame_copy(&self.device, &render_set.render_target);
+            render_set.layer_targets = Default::default();
+        }
+    }
+
+    /// Changes the size of the image buffer the map is rendered to.
+    ///
+    /// Only the target and intermediate textures are reallocated, while the device, queue and render pipelines are
+    /// reused. This allows one renderer to produce images of different sizes without the initialization cost. If the
+    /// renderer does not render to an image buffer yet, a new buffer of the given size is created instead.
+    ///
+    /// Zero sizes are ignored.
+    pub fn resize_target(&mut self, size: Size<u32>) {
+        if size.width() == 0 || size.height() == 0 {
+            return;
+        }
+
+        match &self.render_set {
+            Some(RenderSet {
+                render_target: RenderTarget::Texture(..),
+                ..
+            }) => self.resize(size),
+            _ => self.init_target_texture(size),
         }
     }
 
@@ -489,13 +909,62 @@ impl WgpuRenderer {
     }
 
     /// Returns the image of the last render operation.
+    ///
+    /// For window surface targets, see the requirements in [`WgpuRenderer::capture_frame`].
     pub async fn get_image(&self) -> Result<Vec<u8>, SurfaceError> {
         let Some(render_set) = &self.render_set else {
             return Err(SurfaceError::Lost);
         };
 
         let size = render_set.render_target.size();
-        let buffer_size = (size.width() * size.height() * size_of::<u32>() as u32) as BufferAddress;
+        self.read_target(Rect::new(0, 0, size.width(), size.height()))
+            .await
+    }
+
+    /// Returns the given pixel `region` of the image of the last render operation.
+    ///
+    /// The region is clamped to the bounds of the render target the same way as in
+    /// [`WgpuRenderer::render_region`]. The returned buffer contains the rows of the clamped region one after
+    /// another without padding. If the region has zero area, an empty buffer is returned.
+    pub async fn get_image_region(&self, region: Rect<u32>) -> Result<Vec<u8>, SurfaceError> {
+        if self.render_set.is_none() {
+            return Err(SurfaceError::Lost);
+        }
+
+        match self.clamp_region(region) {
+            Some(region) => self.read_target(region).await,
+            None => Ok(vec![]),
+        }
+    }
+
+    async fn read_target(&self, region: Rect<u32>) -> Result<Vec<u8>, SurfaceError> {
+        let Some(render_set) = &self.render_set else {
+            return Err(SurfaceError::Lost);
+        };
+
+        let (texture, is_bgra) = match &render_set.render_target {
+            RenderTarget::Texture(texture, _) => (texture, false),
+            RenderTarget::Surface { config, .. } => {
+                let Some(texture) = &render_set.frame_copy else {
+                    log::error!("Frames of the surface cannot be captured. The surface must be configured with COPY_SRC usage.");
+                    return Err(SurfaceError::Lost);
+                };
+
+                let is_bgra = matches!(
+                    config.format,
+                    TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+                );
+                (texture, is_bgra)
+            }
+        };
+
+        let pixel_size = size_of::<u32>() as u32;
+        let row_size = region.width() * pixel_size;
+        // Rows in the copy buffer must be aligned, so the padding is removed after the copy.
+        let padded_row_size = row_size.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer_size = (padded_row_size * region.height()) as BufferAddress;
         let buffer_desc = BufferDescriptor {
             size: buffer_size,
             usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
@@ -504,29 +973,29 @@ impl WgpuRenderer {
         };
         let buffer = self.device.create_buffer(&buffer_desc);
 
-        let RenderTarget::Texture(texture, _) = &render_set.render_target else {
-            todo!()
-        };
-
         let mut encoder = self.device.create_command_encoder(&Default::default());
         encoder.copy_texture_to_buffer(
             ImageCopyTexture {
                 aspect: TextureAspect::All,
                 texture,
                 mip_level: 0,
-                origin: Origin3d::ZERO,
+                origin: Origin3d {
+                    x: region.x_min(),
+                    y: region.y_min(),
+                    z: 0,
+                },
             },
             ImageCopyBuffer {
                 buffer: &buffer,
                 layout: ImageDataLayout {
                     offset: 0,
-                    bytes_per_row: Some(size_of::<u32>() as u32 * size.width()),
-                    rows_per_image: Some(size.height()),
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: Some(region.height()),
                 },
             },
             Extent3d {
-                width: size.width(),
-                height: size.height(),
+                width: region.width(),
+                height: region.height(),
                 depth_or_array_layers: 1,
             },
         );
@@ -556,47 +1025,80 @@ impl WgpuRenderer {
         }
 
         let data = buffer_slice.get_mapped_range();
-        Ok(data.to_vec())
+        let mut image: Vec<u8> = if padded_row_size == row_size {
+            data.to_vec()
+        } else {
+            data.chunks(padded_row_size as usize)
+                .flat_map(|row| &row[..row_size as usize])
+                .copied()
+                .collect()
+        };
+
+        if is_bgra {
+            for pixel in image.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Ok(image)
     }
 
-    /// Renders the map to the given texture.
-    pub fn render_to_texture_view(&self, map: &Map, view: &TextureView) {
-        if let Some(render_set) = &self.render_set {
-            let mut encoder = self
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Render Encoder"),
-                });
+    /// Returns the image of the last frame rendered by the renderer in RGBA format.
+    ///
+    /// For texture targets this is the same as [`WgpuRenderer::get_image`], but it also works for window surface
+    /// targets, so it can be used to implement "save the current view as image" functionality in desktop
+    /// applications. The frames presented to a window surface are copied to an intermediate texture, from which the
+    /// image is read asynchronously, so the frame can be captured at any moment after it was presented.
+    ///
+    /// The buffer has the size of the render target (see [`WgpuRenderer::size`]) with the rows going one after
+    /// another, so it can be directly converted into e.g. `image::RgbaImage` and saved as PNG.
+    ///
+    /// Capturing a window frame requires the surface to support `COPY_SRC` usage. Surfaces created by the
+    /// renderer are configured with it if the adapter supports it, but the surfaces given to
+    /// [`WgpuRenderer::new_with_device_and_surface`] must be configured by the caller. If frames cannot be captured,
+    /// or nothing was rendered yet, an error is returned.
+    pub async fn capture_frame(&self) -> Result<Vec<u8>, SurfaceError> {
+        self.get_image().await
+    }
 
-            {
-                let background = self.background.to_f32_array();
-                let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("Render Pass"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &render_set.multisampling_view,
-                        resolve_target: Some(view),
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                r: background[0] as f64,
-                                g: background[1] as f64,
-                                b: background[2] as f64,
-                                a: background[3] as f64,
-                            }),
-                            store: StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
-            }
+    /// Blocking version of [`WgpuRenderer::capture_frame`]. Can be called outside of any async runtime.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn capture_frame_blocking(&self) -> Result<Vec<u8>, SurfaceError> {
+        futures::executor::block_on(self.capture_frame())
+    }
 
-            self.queue.submit(std::iter::once(encoder.finish()));
-        } else {
+    /// Keeps the copy of the frame before it is presented to the surface, so that it can be captured later.
+    fn copy_frame(&self, render_set: &RenderSet, texture: &RenderTargetTexture) {
+        let (RenderTargetTexture::Surface(surface_texture), Some(frame_copy)) =
+            (texture, &render_set.frame_copy)
+        else {
             return;
-        }
+        };
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_texture_to_texture(
+            surface_texture.texture.as_image_copy(),
+            frame_copy.as_image_copy(),
+            frame_copy.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Blocking version of [`WgpuRenderer::get_image`]. Can be called outside of any async runtime.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn get_image_blocking(&self) -> Result<Vec<u8>, SurfaceError> {
+        futures::executor::block_on(self.get_image())
+    }
+
+    /// Blocking version of [`WgpuRenderer::get_image_region`]. Can be called outside of any async runtime.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn get_image_region_blocking(&self, region: Rect<u32>) -> Result<Vec<u8>, SurfaceError> {
+        futures::executor::block_on(self.get_image_region(region))
+    }
 
-        self.render_map(map, view);
+    /// Renders the map to the given texture.
+    pub fn render_to_texture_view(&self, map: &Map, view: &TextureView) {
+        self.render_to_texture_view_region(map, &TargetView::new(map.view()), view, None);
     }
 
     /// Renders the map.
@@ -610,39 +1112,540 @@ impl WgpuRenderer {
 
         self.render_to_texture_view(map, &view);
 
+        self.copy_frame(render_set, &texture);
         texture.present();
 
         Ok(())
     }
 
-    fn render_map(&self, map: &Map, texture_view: &TextureView) {
-        let view = map.view();
-        for layer in map.layers().iter_visible() {
-            self.render_layer(layer, view, texture_view);
-        }
-    }
-
-    fn render_layer(&self, layer: &dyn Layer, view: &MapView, texture_view: &TextureView) {
+    /// Renders only the given pixel `region` of the map, leaving the rest of the render target untouched.
+    ///
+    /// The region is set in pixels with the origin at the top left corner of the target, `x_max` and `y_max` being
+    /// exclusive. It is clamped to the bounds of the render target, and if it has zero area after that, nothing is
+    /// rendered.
+    ///
+    /// All the layers are drawn in full with the rendering limited by the region, so the features crossing the
+    /// region boundaries (e.g. wide lines) are drawn exactly as with [`WgpuRenderer::render`]. This allows rendering
+    /// adjacent regions separately without seams between them.
+    ///
+    /// This is intended for renderers created with a texture target (see [`WgpuRenderer::new_with_texture_rt`]).
+    /// The region can be read with [`WgpuRenderer::get_image_region`].
+    pub fn render_region(&self, map: &Map, region: Rect<u32>) -> Result<(), SurfaceError> {
         let Some(render_set) = &self.render_set else {
-            return;
-        };
-        let Some(mut canvas) = WgpuCanvas::new(self, render_set, texture_view, view.clone()) else {
-            log::warn!("Layer cannot be rendered to the map view.");
-            return;
+            return Ok(());
         };
 
-        layer.render(view, &mut canvas);
-    }
-
-    /// Returns the size of the rendering area.
-    pub fn size(&self) -> Size {
-        let size = match &self.render_set {
-            Some(set) => set.render_target.size(),
-            None => Size::default(),
+        let Some(region) = self.clamp_region(region) else {
+            return Ok(());
         };
 
-        Size::new(size.width() as f64, size.height() as f64)
-    }
+        let texture = render_set.render_target.texture()?;
+        let view = texture.view();
+
+        self.render_to_texture_view_region(map, &TargetView::new(map.view()), &view, Some(region));
+
+        self.copy_frame(render_set, &texture);
+        texture.present();
+
+        Ok(())
+    }
+
+    fn clamp_region(&self, region: Rect<u32>) -> Option<Rect<u32>> {
+        let size = self.render_set.as_ref()?.render_target.size();
+        let x_max = region.x_max().min(size.width());
+        let y_max = region.y_max().min(size.height());
+
+        if region.x_min() >= x_max || region.y_min() >= y_max {
+            return None;
+        }
+
+        Some(Rect::new(region.x_min(), region.y_min(), x_max, y_max))
+    }
+
+    /// Renders the map with the given `view` into an image of the given pixel size and returns the image in RGBA
+    /// format.
+    ///
+    /// The image can be larger than the maximum texture size supported by the GPU: it is rendered in tiles that are
+    /// stitched together, so this can be used to export maps for printing in high resolution. All the tiles are
+    /// rendered with the same full-size view, only the drawn part of the scene differs. This means that the layers
+    /// select the data, decide which labels are shown and size the symbols exactly as they would for one image of
+    /// that size, and the features crossing the tile boundaries are drawn without seams. To make symbols and labels
+    /// larger for a dense print, set the pixel size and the resolution of the `view` accordingly.
+    ///
+    /// Layers draw only the data they have already loaded, so the layers should be loaded for the `view` with the
+    /// `pixel_size` before calling this (e.g. with [`Map::load_layers`] on a map with this view).
+    ///
+    /// The renderer uses its own intermediate texture for the tiles and restores its render target afterward.
+    pub async fn render_large(
+        &mut self,
+        map: &Map,
+        view: &MapView,
+        pixel_size: Size<u32>,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        let tile_size = self
+            .device
+            .limits()
+            .max_texture_dimension_2d
+            .min(MAX_EXPORT_TILE_SIZE);
+        self.render_tiled(map, view, pixel_size, tile_size).await
+    }
+
+    /// Blocking version of [`WgpuRenderer::render_large`]. Can be called outside of any async runtime.
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn render_large_blocking(
+        &mut self,
+        map: &Map,
+        view: &MapView,
+        pixel_size: Size<u32>,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        futures::executor::block_on(self.render_large(map, view, pixel_size))
+    }
+
+    async fn render_tiled(
+        &mut self,
+        map: &Map,
+        view: &MapView,
+        pixel_size: Size<u32>,
+        tile_size: u32,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        if pixel_size.width() == 0 || pixel_size.height() == 0 {
+            return Ok(vec![]);
+        }
+
+        let view = view.with_size(Size::new(
+            pixel_size.width() as f64,
+            pixel_size.height() as f64,
+        ));
+        let tile_size = Size::new(
+            tile_size.min(pixel_size.width()),
+            tile_size.min(pixel_size.height()),
+        );
+
+        let previous_set = self.render_set.take();
+        self.init_target_texture(tile_size);
+        let result = self.render_tiles(map, &view, pixel_size, tile_size).await;
+        self.render_set = previous_set;
+
+        result
+    }
+
+    async fn render_tiles(
+        &self,
+        map: &Map,
+        view: &MapView,
+        pixel_size: Size<u32>,
+        tile_size: Size<u32>,
+    ) -> Result<Vec<u8>, SurfaceError> {
+        let Some(render_set) = &self.render_set else {
+            return Err(SurfaceError::Lost);
+        };
+
+        let row_size = pixel_size.width() as usize * 4;
+        let mut image = vec![0; row_size * pixel_size.height() as usize];
+
+        for y in (0..pixel_size.height()).step_by(tile_size.height() as usize) {
+            for x in (0..pixel_size.width()).step_by(tile_size.width() as usize) {
+                // The last tiles in a row or column are not cut, only the part of them inside the image is read.
+                let tile = Rect::new(x, y, x + tile_size.width(), y + tile_size.height());
+                let texture = render_set.render_target.texture()?;
+                self.render_to_texture_view_region(
+                    map,
+                    &TargetView {
+                        map_view: view,
+                        tile: Some(tile),
+                    },
+                    &texture.view(),
+                    None,
+                );
+
+                let width = tile_size.width().min(pixel_size.width() - x);
+                let height = tile_size.height().min(pixel_size.height() - y);
+                let data = self.read_target(Rect::new(0, 0, width, height)).await?;
+
+                let tile_row_size = width as usize * 4;
+                for (index, row) in data.chunks_exact(tile_row_size).enumerate() {
+                    let offset = (y as usize + index) * row_size + x as usize * 4;
+                    image[offset..offset + tile_row_size].copy_from_slice(row);
+                }
+            }
+        }
+
+        Ok(image)
+    }
+
+    fn render_to_texture_view_region(
+        &self,
+        map: &Map,
+        target_view: &TargetView,
+        view: &TextureView,
+        region: Option<Rect<u32>>,
+    ) {
+        self.fill_target(view, region, self.background);
+        self.render_map(map, target_view, view, region);
+    }
+
+    /// Fills the pixel `region` of the target (or the whole target if `region` is `None`) with the color, replacing
+    /// its content.
+    fn fill_target(&self, view: &TextureView, region: Option<Rect<u32>>, color: Color) {
+        let Some(render_set) = &self.render_set else {
+            return;
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            let color = color.to_f32_array();
+            let load = match region {
+                Some(_) => wgpu::LoadOp::Load,
+                None => wgpu::LoadOp::Clear(wgpu::Color {
+                    r: color[0] as f64,
+                    g: color[1] as f64,
+                    b: color[2] as f64,
+                    a: color[3] as f64,
+                }),
+            };
+
+            // Without multisampling there is no intermediate texture to resolve, so the target is drawn to directly.
+            let (target_view, resolve_target) = if self.sample_count > 1 {
+                (&render_set.multisampling_view, Some(view))
+            } else {
+                (view, None)
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target_view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load,
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            if let Some(region) = region {
+                // Load operation cannot clear only a part of the target, so the region is filled with a draw call.
+                set_scissor_rect(&mut render_pass, region);
+                render_set
+                    .pipelines
+                    .clear_pipeline()
+                    .render(&self.queue, &mut render_pass, color);
+            }
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    fn render_map(
+        &self,
+        map: &Map,
+        view: &TargetView,
+        texture_view: &TextureView,
+        region: Option<Rect<u32>>,
+    ) {
+        let Some(render_set) = &self.render_set else {
+            return;
+        };
+        let target = DrawTarget {
+            view: texture_view,
+            multisampling_view: &render_set.multisampling_view,
+        };
+
+        let swipe = map.swipe();
+        let world_views = map.world_views(view.map_view);
+        map.layers()
+            .for_each_rendered(|id, item, opacity, blend_mode| {
+                let layer_region = match swipe.and_then(|swipe| Some((swipe, swipe.side(id)?))) {
+                    Some((swipe, side)) => {
+                        let side_rect = swipe.side_rect(view.map_view, side);
+                        let Some(side_region) = self.target_region(view, side_rect, region) else {
+                            return;
+                        };
+                        Some(side_region)
+                    }
+                    None => region,
+                };
+
+                let layer = LayerDraw {
+                    world_views: &world_views,
+                    view,
+                    region: layer_region,
+                };
+                self.render_item(item, &layer, target, opacity, blend_mode, 0);
+            });
+
+        if let Some(swipe) = swipe.filter(|swipe| swipe.divider_width() > 0.0) {
+            let divider_rect = swipe.divider_rect(view.map_view);
+            if let Some(divider) = self.target_region(view, divider_rect, region) {
+                self.fill_target(texture_view, Some(divider), swipe.divider_color());
+            }
+        }
+
+        let screen_view = overlay::screen_view(view.map_view);
+        let screen_target = TargetView {
+            map_view: &screen_view,
+            tile: view.tile,
+        };
+        for overlay in map.overlays() {
+            let Some(mut canvas) =
+                WgpuCanvas::new(self, render_set, target, &screen_target, region)
+            else {
+                return;
+            };
+
+            overlay.render(map, view.map_view, &mut canvas);
+        }
+    }
+
+    /// Draws the layer or layer group to the `target`. If the item has opacity or blend mode, it is drawn into the
+    /// offscreen target of the `depth` first, and then composited onto the `target` at once, so that the overlapping
+    /// primitives of the item do not show through each other.
+    fn render_item(
+        &self,
+        item: RenderedItem,
+        layer: &LayerDraw,
+        target: DrawTarget,
+        opacity: f32,
+        blend_mode: BlendMode,
+        depth: usize,
+    ) {
+        if opacity >= 1.0 && blend_mode == BlendMode::Normal {
+            self.draw_item(item, layer, target, depth);
+            return;
+        }
+
+        let Some(layer_target) = self.layer_target(depth) else {
+            return;
+        };
+
+        self.clear_layer_target(&layer_target);
+        self.draw_item(item, layer, layer_target.draw_target(), depth + 1);
+        self.composite(&layer_target, target, opacity, blend_mode, layer.region);
+    }
+
+    fn draw_item(&self, item: RenderedItem, layer: &LayerDraw, target: DrawTarget, depth: usize) {
+        match item {
+            RenderedItem::Layer(rendered) => {
+                for world_view in layer.world_views {
+                    let world_target = TargetView {
+                        map_view: world_view,
+                        tile: layer.view.tile,
+                    };
+                    self.render_layer(rendered, &world_target, target, layer.region);
+                }
+            }
+            RenderedItem::Group(layers) => {
+                layers.for_each_rendered(|_, item, opacity, blend_mode| {
+                    self.render_item(item, layer, target, opacity, blend_mode, depth)
+                });
+            }
+        }
+    }
+
+    /// Returns the offscreen target for the layers of the nesting `depth`, creating it if necessary.
+    fn layer_target(&self, depth: usize) -> Option<Arc<LayerTarget>> {
+        let render_set = self.render_set.as_ref()?;
+        let mut layer_targets = render_set.layer_targets.lock().expect("mutex is poisoned");
+
+        while layer_targets.len() <= depth {
+            let size = render_set.render_target.size();
+            let format = render_set.pipelines.format();
+            let texture = self.device.create_texture(&TextureDescriptor {
+                label: Some("Layer texture"),
+                size: Extent3d {
+                    width: size.width(),
+                    height: size.height(),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&TextureViewDescriptor::default());
+            let composite_binding = render_set
+                .pipelines
+                .composite_pipeline()
+                .create_binding(&self.device, &view);
+
+            layer_targets.push(Arc::new(LayerTarget {
+                view,
+                multisampling_view: Self::create_multisample_texture(
+                    &self.device,
+                    size,
+                    format,
+                    self.sample_count,
+                ),
+                composite_binding,
+            }));
+        }
+
+        Some(layer_targets[depth].clone())
+    }
+
+    fn clear_layer_target(&self, layer_target: &LayerTarget) {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Layer Clear Encoder"),
+            });
+
+        {
+            let (view, resolve_target) = if self.sample_count > 1 {
+                (&layer_target.multisampling_view, Some(&layer_target.view))
+            } else {
+                (&layer_target.view, None)
+            };
+
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Layer Clear Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Draws the image of the layer target over the `target` with the opacity and blend mode.
+    fn composite(
+        &self,
+        layer_target: &LayerTarget,
+        target: DrawTarget,
+        opacity: f32,
+        blend_mode: BlendMode,
+        region: Option<Rect<u32>>,
+    ) {
+        let Some(render_set) = &self.render_set else {
+            return;
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Composite Encoder"),
+            });
+
+        {
+            // The multisampled texture of the target is kept in sync with the target, so that the next antialiased
+            // draws resolve to the composited image.
+            let (view, resolve_target) = if self.sample_count > 1 {
+                (target.multisampling_view, Some(target.view))
+            } else {
+                (target.view, None)
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Composite Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            if let Some(region) = region {
+                set_scissor_rect(&mut render_pass, region);
+            }
+
+            render_set.pipelines.composite_pipeline().render(
+                &self.queue,
+                &mut render_pass,
+                &layer_target.composite_binding,
+                opacity,
+                blend_mode,
+            );
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Converts the pixel rectangle of the map view into the pixels of the render target, taking into account the
+    /// tile of the view being drawn, and limits it by the `region`. Returns `None` if nothing is left of the rectangle.
+    fn target_region(
+        &self,
+        view: &TargetView,
+        rect: Rect,
+        region: Option<Rect<u32>>,
+    ) -> Option<Rect<u32>> {
+        let size = self.render_set.as_ref()?.render_target.size();
+        let (offset_x, offset_y) = view.tile.map_or((0.0, 0.0), |tile| {
+            (tile.x_min() as f64, tile.y_min() as f64)
+        });
+        let to_target = |value: f64, offset: f64, max: u32| {
+            (value - offset).round().clamp(0.0, max as f64) as u32
+        };
+
+        let mut x_min = to_target(rect.x_min(), offset_x, size.width());
+        let mut y_min = to_target(rect.y_min(), offset_y, size.height());
+        let mut x_max = to_target(rect.x_max(), offset_x, size.width());
+        let mut y_max = to_target(rect.y_max(), offset_y, size.height());
+        if let Some(region) = region {
+            x_min = x_min.max(region.x_min());
+            y_min = y_min.max(region.y_min());
+            x_max = x_max.min(region.x_max());
+            y_max = y_max.min(region.y_max());
+        }
+
+        if x_min >= x_max || y_min >= y_max {
+            return None;
+        }
+
+        Some(Rect::new(x_min, y_min, x_max, y_max))
+    }
+
+    fn render_layer(
+        &self,
+        layer: &dyn Layer,
+        view: &TargetView,
+        target: DrawTarget,
+        region: Option<Rect<u32>>,
+    ) {
+        let Some(render_set) = &self.render_set else {
+            return;
+        };
+        let Some(mut canvas) = WgpuCanvas::new(self, render_set, target, view, region) else {
+            log::warn!("Layer cannot be rendered to the map view.");
+            return;
+        };
+
+        layer.render(view.map_view, &mut canvas);
+    }
+
+    /// Returns the size of the rendering area.
+    pub fn size(&self) -> Size {
+        let size = match &self.render_set {
+            Some(set) => set.render_target.size(),
+            None => Size::default(),
+        };
+
+        Size::new(size.width() as f64, size.height() as f64)
+    }
 
     fn create_bundle(&self) -> RenderBundle {
         RenderBundle(RenderBundleType::Tessellating(
@@ -651,20 +1654,167 @@ impl WgpuRenderer {
     }
 }
 
+/// Access to the wgpu resources of the frame being rendered, given to the layers that draw with their own wgpu
+/// pipelines (see [`CustomRenderLayer`](crate::layer::CustomRenderLayer)).
+///
+/// The layer can record and submit its own command buffers to the [`WgpuFrame::queue`]:
+/// * the layer is drawn into [`WgpuFrame::target_view`] with the normal blending. If the layer has opacity or a blend
+///   mode, the target is an offscreen texture that the renderer then composites onto the map with them, so the layer
+///   does not apply them itself. For multisampled rendering, draw to [`WgpuFrame::multisample_view`] resolving into the target view, like the
+///   antialiased layers of the map do;
+/// * the pixel region being redrawn ([`WgpuFrame::region`]) must be set as the scissor rectangle of the
+///   render passes, if it is present;
+/// * the view transform of the map is available as the bind group [`WgpuFrame::view_bind_group`] with the layout
+///   [`WgpuFrame::view_bind_group_layout`]. It contains one uniform buffer with the following structure:
+///
+/// ```wgsl
+/// struct ViewTransform {
+///     // Transforms map coordinates into the clip space of the target
+///     view_proj: mat4x4<f32>,
+///     // Rotation of the map, used to rotate the screen-referenced offsets
+///     view_rotation: mat4x4<f32>,
+///     // `1 / width` and `1 / height` of the target in pixels
+///     inv_screen_size: vec2<f32>,
+///     // Map units in one pixel
+///     resolution: f32,
+///     // Always 1.0, the opacity of the layer is applied by the renderer
+///     opacity: f32,
+///     // Point of the map that hatch and pattern fills are aligned to
+///     pattern_origin: vec2<f32>,
+/// };
+///
+/// @group(0) @binding(0)
+/// var<uniform> transform: ViewTransform;
+/// ```
+pub struct WgpuFrame<'a> {
+    device: &'a Device,
+    queue: &'a Queue,
+    render_set: &'a RenderSet,
+    target_view: &'a TextureView,
+    sample_count: u32,
+    multisample_view: &'a TextureView,
+    region: Option<Rect<u32>>,
+}
+
+impl<'a> WgpuFrame<'a> {
+    /// The device used by the renderer.
+    pub fn device(&self) -> &'a Device {
+        self.device
+    }
+
+    /// The queue of the device used by the renderer.
+    pub fn queue(&self) -> &'a Queue {
+        self.queue
+    }
+
+    /// The texture view of the render target.
+    pub fn target_view(&self) -> &'a TextureView {
+        self.target_view
+    }
+
+    /// Intermediate multisampled texture view with [`WgpuFrame::sample_count`] samples. Its contents are undefined
+    /// before the layer draws to it.
+    pub fn multisample_view(&self) -> &'a TextureView {
+        self.multisample_view
+    }
+
+    /// Number of samples of [`WgpuFrame::multisample_view`]. If it's 1, multisampling is not available.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Format of the render target and the multisampled texture.
+    pub fn target_format(&self) -> TextureFormat {
+        self.render_set.pipelines.format()
+    }
+
+    /// Size of the render target in pixels.
+    pub fn target_size(&self) -> Size<u32> {
+        self.render_set.render_target.size()
+    }
+
+    /// Region of the target being rendered, or `None` if the whole target is rendered. See
+    /// [`WgpuRenderer::render_region`].
+    pub fn region(&self) -> Option<Rect<u32>> {
+        self.region
+    }
+
+    /// Bind group with the view transform uniform of the map.
+    pub fn view_bind_group(&self) -> &'a BindGroup {
+        self.render_set.pipelines.map_view_binding()
+    }
+
+    /// Layout of the [`WgpuFrame::view_bind_group`], to be used in the pipeline layouts of the layer.
+    pub fn view_bind_group_layout(&self) -> &'a BindGroupLayout {
+        self.render_set.pipelines.map_view_layout()
+    }
+}
+
+/// World views and the pixel region a layer or layer group is drawn with.
+struct LayerDraw<'a> {
+    world_views: &'a [MapView],
+    view: &'a TargetView<'a>,
+    region: Option<Rect<u32>>,
+}
+
+/// Map view drawn to the render target.
+struct TargetView<'a> {
+    map_view: &'a MapView,
+    /// Pixel rectangle of the view to be drawn, stretched to the whole target. If not set, the whole view is drawn.
+    tile: Option<Rect<u32>>,
+}
+
+impl<'a> TargetView<'a> {
+    fn new(map_view: &'a MapView) -> Self {
+        Self {
+            map_view,
+            tile: None,
+        }
+    }
+
+    fn scene_transform(&self) -> Option<Matrix4<f64>> {
+        let transform = self.map_view.map_to_scene_transform()?;
+        let Some(tile) = self.tile else {
+            return Some(transform);
+        };
+
+        // Scales and moves the scene coordinates in homogeneous space, so that the tile covers the `[-1.0, 1.0]`
+        // range. This keeps the perspective of the full view for tilted maps.
+        let size = self.map_view.size();
+        let scale_x = size.width() / tile.width() as f64;
+        let scale_y = size.height() / tile.height() as f64;
+        let center_x = (tile.x_min() as f64 + tile.width() as f64 / 2.0) / size.width() * 2.0 - 1.0;
+        let center_y =
+            1.0 - (tile.y_min() as f64 + tile.height() as f64 / 2.0) / size.height() * 2.0;
+        #[rustfmt::skip]
+        let crop = Matrix4::new(
+            scale_x, 0.0, 0.0, -scale_x * center_x,
+            0.0, scale_y, 0.0, -scale_y * center_y,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+
+        Some(crop * transform)
+    }
+}
+
 #[allow(dead_code)]
 struct WgpuCanvas<'a> {
     renderer: &'a WgpuRenderer,
     render_set: &'a RenderSet,
-    view: &'a TextureView,
+    target: DrawTarget<'a>,
+    region: Option<Rect<u32>>,
 }
 
 impl<'a> WgpuCanvas<'a> {
     fn new(
         renderer: &'a WgpuRenderer,
         render_set: &'a RenderSet,
-        view: &'a TextureView,
-        map_view: MapView,
+        target: DrawTarget<'a>,
+        target_view: &TargetView,
+        region: Option<Rect<u32>>,
     ) -> Option<Self> {
+        let map_view = target_view.map_view;
         let rotation_mtx = Rotation3::new(Vector3::new(
             map_view.rotation_x(),
             0.0,
@@ -675,21 +1825,26 @@ impl<'a> WgpuCanvas<'a> {
             render_set.pipelines.map_view_buffer(),
             0,
             bytemuck::cast_slice(&[ViewUniform {
-                view_proj: map_view.map_to_scene_mtx()?,
+                view_proj: target_view.scene_transform()?.cast::<f32>().data.0,
                 view_rotation: rotation_mtx.cast::<f32>().data.0,
                 inv_screen_size: [
                     1.0 / renderer.size().width() as f32,
                     1.0 / renderer.size().height() as f32,
                 ],
                 resolution: map_view.resolution() as f32,
-                _padding: [0.0; 1],
+                opacity: 1.0,
+                pattern_origin: pattern_origin(map_view)
+                    .map_or([0.0; 2], |p| [p.x() as f32, p.y() as f32]),
+                _padding: [0.0; 2],
+                color_matrix: Matrix4::identity().data.0,
             }]),
         );
 
         Some(Self {
             renderer,
             render_set,
-            view,
+            target,
+            region,
         })
     }
 }
@@ -719,15 +1874,29 @@ impl<'a> Canvas for WgpuCanvas<'a> {
                     label: Some("Render Encoder"),
                 });
 
+        // Without multisampling the antialiased pipelines are single sampled, so they must draw to the target directly.
+        let options = RenderOptions {
+            antialias: options.antialias && self.renderer.sample_count > 1,
+            ..options
+        };
+
+        // The queued write is applied before the commands of this encoder are submitted, so every call draws with its
+        // own adjustment even though the uniform buffer is shared.
+        self.renderer.queue.write_buffer(
+            self.render_set.pipelines.map_view_buffer(),
+            std::mem::offset_of!(ViewUniform, color_matrix) as wgpu::BufferAddress,
+            bytemuck::cast_slice(&options.color_adjustment.color_matrix().data.0),
+        );
+
         {
             let (view, resolve_target, depth_view) = if options.antialias {
                 (
-                    &self.render_set.multisampling_view,
-                    Some(self.view),
+                    self.target.multisampling_view,
+                    Some(self.target.view),
                     &self.render_set.stencil_view_multisample,
                 )
             } else {
-                (self.view, None, &self.render_set.stencil_view)
+                (self.target.view, None, &self.render_set.stencil_view)
             };
 
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -755,6 +1924,10 @@ impl<'a> Canvas for WgpuCanvas<'a> {
                 occlusion_query_set: None,
             });
 
+            if let Some(region) = self.region {
+                set_scissor_rect(&mut render_pass, region);
+            }
+
             for bundle in bundles {
                 if let Some(cast) = bundle.as_any().downcast_ref::<WgpuPackedBundle>() {
                     self.render_set
@@ -768,14 +1941,101 @@ impl<'a> Canvas for WgpuCanvas<'a> {
             .queue
             .submit(std::iter::once(encoder.finish()));
     }
+
+    fn pack_heatmap(&self, points: &[(Point2d, f32)]) -> Box<dyn PackedBundle> {
+        let instances: Vec<_> = points
+            .iter()
+            .map(|(position, weight)| HeatmapInstance {
+                position: [position.x() as f32, position.y() as f32],
+                weight: *weight,
+            })
+            .collect();
+
+        Box::new(
+            self.render_set
+                .pipelines
+                .heatmap_pipeline()
+                .create_points(&self.renderer.device, &instances),
+        )
+    }
+
+    fn draw_heatmap(&mut self, points: &dyn PackedBundle, paint: HeatmapPaint) {
+        let Some(points) = points.as_any().downcast_ref::<WgpuHeatmapPoints>() else {
+            return;
+        };
+
+        let mut encoder =
+            self.renderer
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Heatmap Encoder"),
+                });
+
+        let pipelines = &self.render_set.pipelines;
+        pipelines.heatmap_pipeline().render(
+            &self.renderer.device,
+            &self.renderer.queue,
+            &mut encoder,
+            pipelines.map_view_binding(),
+            self.target.view,
+            self.render_set.render_target.size(),
+            self.region.map(|region| {
+                (
+                    region.x_min(),
+                    region.y_min(),
+                    region.width(),
+                    region.height(),
+                )
+            }),
+            points,
+            paint,
+            // The opacity of the layer is applied when it is composited onto the target.
+            1.0,
+        );
+
+        self.renderer
+            .queue
+            .submit(std::iter::once(encoder.finish()));
+    }
+
+    fn wgpu_frame(&mut self) -> Option<WgpuFrame<'_>> {
+        Some(WgpuFrame {
+            device: &self.renderer.device,
+            queue: &self.renderer.queue,
+            render_set: self.render_set,
+            target_view: self.target.view,
+            sample_count: self.renderer.sample_count,
+            multisample_view: self.target.multisampling_view,
+            region: self.region,
+        })
+    }
+}
+
+impl PackedBundle for WgpuHeatmapPoints {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn set_scissor_rect(render_pass: &mut wgpu::RenderPass, region: Rect<u32>) {
+    render_pass.set_scissor_rect(
+        region.x_min(),
+        region.y_min(),
+        region.width(),
+        region.height(),
+    );
 }
 
 struct WgpuPackedBundle {
     clip_area_buffers: Option<WgpuPolygonBuffers>,
     map_ref_buffers: WgpuPolygonBuffers,
+    extrusion_buffers: Option<WgpuPolygonBuffers>,
     screen_ref_buffers: Option<ScreenRefBuffers>,
     dot_buffers: Option<WgpuDotBuffers>,
+    marker_buffers: Option<WgpuMarkerBuffers>,
     image_buffers: Vec<WgpuImage>,
+    hatch_buffers: Option<WgpuPolygonBuffers>,
+    pattern_buffers: Vec<WgpuPatternFill>,
 }
 
 struct WgpuPolygonBuffers {
@@ -795,6 +2055,11 @@ struct WgpuDotBuffers {
     point_count: u32,
 }
 
+struct WgpuMarkerBuffers {
+    buffer: Buffer,
+    marker_count: u32,
+}
+
 impl WgpuPackedBundle {
     fn new(
         bundle: &TessellatingRenderBundle,
@@ -803,7 +2068,11 @@ impl WgpuPackedBundle {
     ) -> Self {
         let TessellatingRenderBundle {
             poly_tessellation,
+            extrusion_tessellation,
+            hatch_tessellation,
+            pattern_fills,
             points,
+            markers,
             screen_ref,
             images,
             clip_area,
@@ -816,6 +2085,10 @@ impl WgpuPackedBundle {
             .map(|v| Self::write_poly_buffers(v, renderer));
 
         let poly_buffers = Self::write_poly_buffers(poly_tessellation, renderer);
+        let extrusion_buffers = (!extrusion_tessellation.indices.is_empty())
+            .then(|| Self::write_poly_buffers(extrusion_tessellation, renderer));
+        let hatch_buffers = (!hatch_tessellation.indices.is_empty())
+            .then(|| Self::write_poly_buffers(hatch_tessellation, renderer));
 
         let screen_ref_buffers = if !screen_ref.vertices.is_empty() {
             let index = renderer
@@ -861,6 +2134,17 @@ impl WgpuPackedBundle {
             })
         };
 
+        let marker_buffers = (!markers.is_empty()).then(|| WgpuMarkerBuffers {
+            buffer: renderer
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    usage: wgpu::BufferUsages::VERTEX,
+                    contents: bytemuck::cast_slice(markers),
+                }),
+            marker_count: markers.len() as u32,
+        });
+
         let textures: Vec<_> = image_store
             .iter()
             .map(|stored| match stored {
@@ -875,17 +2159,34 @@ impl WgpuPackedBundle {
             })
             .collect();
 
+        let texture = |index: usize| {
+            textures
+                .get(index)
+                .expect("texture at index must exist")
+                .clone()
+                .expect("image texture must not be None")
+        };
+
+        let pattern_buffers = pattern_fills
+            .iter()
+            .filter_map(|info| match info {
+                PatternFillInfo::Fill {
+                    image_store_index,
+                    tessellation,
+                } if !tessellation.indices.is_empty() => Some(WgpuPatternFill {
+                    texture_bind_group: texture(*image_store_index),
+                    buffers: Self::write_poly_buffers(tessellation, renderer),
+                }),
+                _ => None,
+            })
+            .collect();
+
         let mut image_buffers = vec![];
         for image_info in images {
             if let ImageInfo::Image((image_index, vertices)) = image_info {
                 let image = render_set.pipelines.image_pipeline().create_image(
                     &renderer.device,
-                    textures
-                        .get(*image_index)
-                        .expect("texture at index must exist")
-                        .clone()
-                        .expect("image texture must not be None")
-                        .clone(),
+                    texture(*image_index),
                     vertices,
                 );
                 image_buffers.push(image);
@@ -897,14 +2198,18 @@ impl WgpuPackedBundle {
         Self {
             clip_area_buffers,
             map_ref_buffers: poly_buffers,
+            extrusion_buffers,
             image_buffers,
             screen_ref_buffers,
             dot_buffers,
+            marker_buffers,
+            hatch_buffers,
+            pattern_buffers,
         }
     }
 
-    fn write_poly_buffers(
-        tessellation: &VertexBuffers<PolyVertex, u32>,
+    fn write_poly_buffers<T: bytemuck::Pod>(
+        tessellation: &VertexBuffers<T, u32>,
         renderer: &WgpuRenderer,
     ) -> WgpuPolygonBuffers {
         let index_bytes = bytemuck::cast_slice(&tessellation.indices);
@@ -947,7 +2252,10 @@ struct ViewUniform {
     view_rotation: [[f32; 4]; 4],
     inv_screen_size: [f32; 2],
     resolution: f32,
-    _padding: [f32; 1],
+    opacity: f32,
+    pattern_origin: [f32; 2],
+    _padding: [f32; 2],
+    color_matrix: [[f32; 4]; 4],
 }
 
 impl PointInstance {
@@ -1002,3 +2310,570 @@ impl PolyVertex {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::feature_layer::symbol::{CirclePointSymbol, SimpleContourSymbol};
+    use crate::layer::FeatureLayer;
+    use galileo_types::cartesian::Point2d;
+    use galileo_types::geo::Crs;
+    use galileo_types::geometry_type::CartesianSpace2d;
+
+    const WIDTH: u32 = 100;
+    const HEIGHT: u32 = 60;
+
+    /// Message of the tests that need a graphics adapter. They are ignored by default, run them with
+    /// `cargo test -- --ignored` on a machine with a GPU or a software renderer.
+    const NO_ADAPTER: &str = "no graphics adapter is available";
+
+    fn test_renderer() -> WgpuRenderer {
+        tokio_test::block_on(WgpuRenderer::new_with_texture_rt(Size::new(WIDTH, HEIGHT)))
+            .expect(NO_ADAPTER)
+    }
+
+    fn test_map() -> Map {
+        Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+                .with_size(Size::new(WIDTH as f64, HEIGHT as f64)),
+            vec![],
+            None::<crate::messenger::DummyMessenger>,
+        )
+    }
+
+    fn pixel(image: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+        let offset = ((y * width + x) * 4) as usize;
+        image[offset..offset + 4]
+            .try_into()
+            .expect("slice has 4 elements")
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn render_region_leaves_rest_untouched() {
+        let mut renderer = test_renderer();
+        let map = test_map();
+
+        renderer.set_background(Color::WHITE);
+        renderer.render(&map).unwrap();
+        renderer.set_background(Color::RED);
+        renderer
+            .render_region(&map, Rect::new(10, 20, 30, 25))
+            .unwrap();
+
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+        assert_eq!(image.len(), (WIDTH * HEIGHT * 4) as usize);
+        for (x, y) in [(10, 20), (29, 24), (20, 22)] {
+            assert_eq!(pixel(&image, WIDTH, x, y), Color::RED.to_u8_array());
+        }
+        for (x, y) in [(9, 20), (30, 24), (20, 19), (20, 25), (0, 0)] {
+            assert_eq!(pixel(&image, WIDTH, x, y), Color::WHITE.to_u8_array());
+        }
+
+        let region =
+            tokio_test::block_on(renderer.get_image_region(Rect::new(10, 20, 30, 25))).unwrap();
+        assert_eq!(region.len(), 20 * 5 * 4);
+        assert!(region
+            .chunks(4)
+            .all(|pixel| pixel == Color::RED.to_u8_array()));
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    #[cfg(feature = "blocking")]
+    fn blocking_render_to_image() {
+        let mut renderer =
+            WgpuRenderer::new_with_texture_rt_blocking(Size::new(WIDTH, HEIGHT)).expect(NO_ADAPTER);
+
+        renderer.set_background(Color::RED);
+        renderer.render(&test_map()).unwrap();
+
+        let image = renderer.get_image_blocking().unwrap();
+        assert_eq!(image.len(), (WIDTH * HEIGHT * 4) as usize);
+        assert!(image
+            .chunks(4)
+            .all(|pixel| pixel == Color::RED.to_u8_array()));
+    }
+
+    fn render_points(renderer: &mut WgpuRenderer, size: Size<u32>) -> Vec<u8> {
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+            .with_size(Size::new(size.width() as f64, size.height() as f64));
+        let layer = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(-20.0, 10.0),
+                Point2d::new(30.0, -15.0),
+            ],
+            CirclePointSymbol::new(Color::BLUE, 8.0),
+            Crs::EPSG3857,
+        );
+        let map = Map::new(
+            view,
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        );
+
+        renderer.render(&map).unwrap();
+        tokio_test::block_on(renderer.get_image()).unwrap()
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn resized_target_renders_same_as_new_renderer() {
+        let size_a = Size::new(WIDTH, HEIGHT);
+        let size_b = Size::new(73, 128);
+
+        let render_fresh = |size| {
+            let mut renderer =
+                tokio_test::block_on(WgpuRenderer::new_with_texture_rt(size)).unwrap();
+            render_points(&mut renderer, size)
+        };
+
+        let mut renderer = test_renderer();
+        let image_a = render_points(&mut renderer, size_a);
+        renderer.resize_target(size_b);
+        assert_eq!(renderer.size(), Size::new(73.0, 128.0));
+        let image_b = render_points(&mut renderer, size_b);
+        renderer.resize_target(size_a);
+        let image_a_again = render_points(&mut renderer, size_a);
+        drop(renderer);
+
+        let fresh_a = render_fresh(size_a);
+        assert!(fresh_a.chunks(4).any(|p| p != Color::WHITE.to_u8_array()));
+        assert_eq!(image_a, fresh_a);
+        assert_eq!(image_a_again, fresh_a);
+
+        let fresh_b = render_fresh(size_b);
+        assert_eq!(fresh_b.len(), 73 * 128 * 4);
+        assert_eq!(image_b, fresh_b);
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn background_fills_empty_areas() {
+        let mut renderer = test_renderer();
+        let map = test_map();
+        assert_eq!(renderer.background(), Color::WHITE);
+
+        renderer.render(&map).unwrap();
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+        assert!(image.chunks(4).all(|p| p == Color::WHITE.to_u8_array()));
+
+        let background = Color::rgba(255, 0, 255, 255);
+        renderer.set_background(background);
+        renderer.render(&map).unwrap();
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+        assert!(image.chunks(4).all(|p| p == background.to_u8_array()));
+
+        renderer.set_background(Color::TRANSPARENT);
+        let image = render_points(&mut renderer, Size::new(WIDTH, HEIGHT));
+        assert_eq!(pixel(&image, WIDTH, 0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&image, WIDTH, WIDTH / 2, HEIGHT / 2)[3], 255);
+    }
+
+    fn is_blue_or_white(pixel: &[u8]) -> bool {
+        pixel == Color::BLUE.to_u8_array() || pixel == Color::WHITE.to_u8_array()
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn msaa_sample_count() {
+        let size = Size::new(73, 41);
+
+        let mut renderer = tokio_test::block_on(WgpuRenderer::new_with_texture_rt_msaa(size, 1))
+            .expect(NO_ADAPTER);
+        assert_eq!(renderer.sample_count(), 1);
+        let image = render_points(&mut renderer, size);
+        drop(renderer);
+        assert_eq!(image.len(), 73 * 41 * 4);
+        assert!(image.chunks(4).all(is_blue_or_white));
+        assert!(image.chunks(4).any(|p| p == Color::BLUE.to_u8_array()));
+
+        for sample_count in [2, 4, 8] {
+            let mut renderer =
+                tokio_test::block_on(WgpuRenderer::new_with_texture_rt_msaa(size, sample_count))
+                    .unwrap();
+            assert!(renderer.sample_count() <= sample_count);
+            let is_multisampled = renderer.sample_count() > 1;
+            let image = render_points(&mut renderer, size);
+            drop(renderer);
+
+            assert_eq!(image.len(), 73 * 41 * 4);
+            if is_multisampled {
+                // Edges of the circles are blended with the background.
+                assert!(!image.chunks(4).all(is_blue_or_white));
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn circle_markers_have_outline() {
+        let size = Size::new(WIDTH, HEIGHT);
+        let renderer = tokio_test::block_on(WgpuRenderer::new_with_texture_rt_msaa(size, 1))
+            .expect(NO_ADAPTER);
+
+        let layer = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![Point2d::new(0.0, 0.0)],
+            CirclePointSymbol::new(Color::BLUE, 20.0)
+                .with_outline_color(Color::RED)
+                .with_outline_width(2.0),
+            Crs::EPSG3857,
+        );
+        let map = Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+                .with_size(Size::new(WIDTH as f64, HEIGHT as f64)),
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        );
+        renderer.render(&map).unwrap();
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+
+        let (x, y) = (WIDTH / 2, HEIGHT / 2);
+        assert_eq!(pixel(&image, WIDTH, x, y), Color::BLUE.to_u8_array());
+        assert_eq!(pixel(&image, WIDTH, x + 5, y), Color::BLUE.to_u8_array());
+        assert_eq!(pixel(&image, WIDTH, x + 10, y), Color::RED.to_u8_array());
+        assert_eq!(pixel(&image, WIDTH, x, y - 10), Color::RED.to_u8_array());
+        assert_eq!(pixel(&image, WIDTH, x + 14, y), Color::WHITE.to_u8_array());
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn feathered_lines() {
+        let size = Size::new(WIDTH, HEIGHT);
+        let options = WgpuRendererOptions {
+            sample_count: 1,
+            line_antialiasing: LineAntialiasing::Feathering,
+        };
+        let mut renderer =
+            tokio_test::block_on(WgpuRenderer::new_with_texture_rt_and_options(size, options))
+                .expect(NO_ADAPTER);
+        assert_eq!(renderer.line_antialiasing(), LineAntialiasing::Feathering);
+
+        let render_line = |renderer: &mut WgpuRenderer| {
+            let view = MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+                .with_size(Size::new(WIDTH as f64, HEIGHT as f64));
+            let layer = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+                vec![galileo_types::impls::Contour::open(vec![
+                    Point2d::new(-30.0, 0.3),
+                    Point2d::new(30.0, 10.7),
+                ])],
+                SimpleContourSymbol::new(Color::BLUE, 1.5),
+                Crs::EPSG3857,
+            );
+            let map = Map::new(
+                view,
+                vec![Box::new(layer)],
+                None::<crate::messenger::DummyMessenger>,
+            );
+
+            renderer.render(&map).unwrap();
+            tokio_test::block_on(renderer.get_image()).unwrap()
+        };
+
+        let feathered = render_line(&mut renderer);
+        assert!(!feathered.chunks(4).all(is_blue_or_white));
+
+        renderer.set_line_antialiasing(LineAntialiasing::Multisampling);
+        let aliased = render_line(&mut renderer);
+        assert!(aliased.chunks(4).all(is_blue_or_white));
+        assert!(aliased.chunks(4).any(|p| p == Color::BLUE.to_u8_array()));
+    }
+
+    #[test]
+    fn invalid_msaa_sample_count() {
+        for sample_count in [0, 3, 16] {
+            let Err(GalileoError::Generic(message)) = tokio_test::block_on(
+                WgpuRenderer::new_with_texture_rt_msaa(Size::new(10, 10), sample_count),
+            ) else {
+                panic!("sample count {sample_count} must be rejected");
+            };
+            assert!(message.contains("invalid MSAA sample count"));
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn region_is_clamped_to_target() {
+        let renderer = test_renderer();
+
+        let region =
+            tokio_test::block_on(renderer.get_image_region(Rect::new(90, 50, 200, 200))).unwrap();
+        assert_eq!(region.len(), 10 * 10 * 4);
+
+        let region =
+            tokio_test::block_on(renderer.get_image_region(Rect::new(100, 0, 200, 10))).unwrap();
+        assert!(region.is_empty());
+
+        assert!(renderer
+            .render_region(&test_map(), Rect::new(100, 60, 100, 60))
+            .is_ok());
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn tiled_render_matches_single_render() {
+        let mut renderer = test_renderer();
+
+        let layer = FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![
+                Point2d::new(0.0, 0.0),
+                // Crosses the corner of four tiles
+                Point2d::new(-18.0, -2.0),
+                Point2d::new(45.0, 25.0),
+            ],
+            CirclePointSymbol::new(Color::BLUE, 8.0),
+            Crs::EPSG3857,
+        );
+        let mut map = Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
+                .with_size(Size::new(WIDTH as f64, HEIGHT as f64)),
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        );
+
+        let views = [map.view().clone(), map.view().with_rotation(0.5, 0.3)];
+        for view in views {
+            map.set_view(view);
+            renderer.render(&map).unwrap();
+            let expected = tokio_test::block_on(renderer.get_image()).unwrap();
+            assert!(expected.chunks(4).any(|p| p == Color::BLUE.to_u8_array()));
+
+            let image = tokio_test::block_on(renderer.render_tiled(
+                &map,
+                map.view(),
+                Size::new(WIDTH, HEIGHT),
+                32,
+            ))
+            .unwrap();
+            assert_eq!(image, expected);
+
+            // Previous render target is restored
+            assert_eq!(renderer.size(), Size::new(WIDTH as f64, HEIGHT as f64));
+            assert_eq!(
+                tokio_test::block_on(renderer.get_image()).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn render_large_stitches_tiles() {
+        let mut renderer = test_renderer();
+
+        let map = test_map();
+        let pixel_size = Size::new(MAX_EXPORT_TILE_SIZE + 10, 20);
+        let image =
+            tokio_test::block_on(renderer.render_large(&map, map.view(), pixel_size)).unwrap();
+        assert_eq!(
+            image.len(),
+            (pixel_size.width() * pixel_size.height() * 4) as usize
+        );
+        assert!(image.chunks(4).all(|p| p == Color::WHITE.to_u8_array()));
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn capture_frame_of_texture_target() {
+        let mut renderer = test_renderer();
+
+        let image = render_points(&mut renderer, Size::new(WIDTH, HEIGHT));
+        let frame = tokio_test::block_on(renderer.capture_frame()).unwrap();
+        assert_eq!(frame.len(), (WIDTH * HEIGHT * 4) as usize);
+        assert_eq!(frame, image);
+        assert_eq!(
+            pixel(&frame, WIDTH, WIDTH / 2, HEIGHT / 2),
+            Color::BLUE.to_u8_array()
+        );
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn heatmap_draws_density_of_points() {
+        use crate::layer::{ColorRamp, HeatmapLayer, HeatmapOptions};
+        use galileo_types::geo::impls::GeoPoint2d;
+        use galileo_types::geo::NewGeoPoint;
+
+        let renderer = test_renderer();
+        let layer = HeatmapLayer::new(vec![(GeoPoint2d::latlon(0.0, 0.0), 1.0)]).with_options(
+            HeatmapOptions {
+                radius: 20.0,
+                intensity: 2.0,
+                color_ramp: ColorRamp::new([(0.0, Color::TRANSPARENT), (1.0, Color::RED)]),
+            },
+        );
+        let mut map = test_map();
+        map.layers_mut().push(layer);
+
+        renderer.render(&map).unwrap();
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+
+        let (center_x, center_y) = (WIDTH / 2, HEIGHT / 2);
+        assert_eq!(
+            pixel(&image, WIDTH, center_x, center_y),
+            Color::RED.to_u8_array()
+        );
+        let near_edge = pixel(&image, WIDTH, center_x + 15, center_y);
+        assert_ne!(near_edge, Color::RED.to_u8_array());
+        assert_ne!(near_edge, Color::WHITE.to_u8_array());
+        assert_eq!(
+            pixel(&image, WIDTH, center_x + 25, center_y),
+            Color::WHITE.to_u8_array()
+        );
+        assert_eq!(pixel(&image, WIDTH, 0, 0), Color::WHITE.to_u8_array());
+    }
+
+    fn covering_polygon_map(
+        resolution: f64,
+        symbol: impl crate::layer::feature_layer::symbol::Symbol<galileo_types::impls::Polygon<Point2d>>
+            + maybe_sync::MaybeSend
+            + maybe_sync::MaybeSync
+            + 'static,
+    ) -> Map {
+        let polygon = galileo_types::impls::Polygon::from(vec![
+            Point2d::new(-1000.0, -1000.0),
+            Point2d::new(1000.0, -1000.0),
+            Point2d::new(1000.0, 1000.0),
+            Point2d::new(-1000.0, 1000.0),
+        ]);
+        let layer =
+            FeatureLayer::<_, _, _, CartesianSpace2d>::new(vec![polygon], symbol, Crs::EPSG3857);
+        Map::new(
+            MapView::new_projected(&Point2d::new(0.0, 0.0), resolution)
+                .with_size(Size::new(WIDTH as f64, HEIGHT as f64)),
+            vec![Box::new(layer)],
+            None::<crate::messenger::DummyMessenger>,
+        )
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn hatch_keeps_spacing_in_pixels() {
+        use crate::layer::feature_layer::symbol::SimplePolygonSymbol;
+        use crate::render::HatchFill;
+
+        let renderer = test_renderer();
+
+        let symbol =
+            SimplePolygonSymbol::new(Color::BLUE).with_fill_hatch(HatchFill::new(0.0, 10.0, 4.0));
+        for resolution in [1.0, 2.0] {
+            let map = covering_polygon_map(resolution, symbol);
+            renderer.render(&map).unwrap();
+            let image = tokio_test::block_on(renderer.get_image()).unwrap();
+
+            let center_x = WIDTH / 2;
+            for (y, color) in [(30, Color::BLUE), (25, Color::WHITE), (20, Color::BLUE)] {
+                assert_eq!(
+                    pixel(&image, WIDTH, center_x, y),
+                    color.to_u8_array(),
+                    "row {y} at resolution {resolution}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn pattern_repeats_image() {
+        use crate::decoded_image::DecodedImage;
+        use crate::layer::feature_layer::symbol::PatternPolygonSymbol;
+
+        let renderer = test_renderer();
+
+        let image = DecodedImage::from_raw([255, 0, 0, 255, 0, 0, 255, 255], 2, 1).unwrap();
+        let map = covering_polygon_map(1.0, PatternPolygonSymbol::new(Arc::new(image)));
+        renderer.render(&map).unwrap();
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+
+        for x in [40, 50, 52] {
+            assert_eq!(pixel(&image, WIDTH, x, 10), Color::RED.to_u8_array());
+            assert_eq!(pixel(&image, WIDTH, x + 1, 30), Color::BLUE.to_u8_array());
+        }
+    }
+
+    /// Layer with two overlapping vertical bands, covering the pixel columns `10..60` and `40..90` of the test map.
+    fn overlapping_bands_layer(color: Color) -> impl Layer {
+        use crate::layer::feature_layer::symbol::SimplePolygonSymbol;
+
+        let band = |x_min: f64, x_max: f64| {
+            galileo_types::impls::Polygon::from(vec![
+                Point2d::new(x_min, -1000.0),
+                Point2d::new(x_max, -1000.0),
+                Point2d::new(x_max, 1000.0),
+                Point2d::new(x_min, 1000.0),
+            ])
+        };
+        FeatureLayer::<_, _, _, CartesianSpace2d>::new(
+            vec![band(-40.0, 10.0), band(-10.0, 40.0)],
+            SimplePolygonSymbol::new(color),
+            Crs::EPSG3857,
+        )
+    }
+
+    /// Colors of the test map in the first band, the overlap of the bands and the second band.
+    fn band_colors(renderer: &WgpuRenderer, map: &Map) -> [[u8; 4]; 3] {
+        renderer.render(map).unwrap();
+        let image = tokio_test::block_on(renderer.get_image()).unwrap();
+        [20, 50, 80].map(|x| pixel(&image, WIDTH, x, HEIGHT / 2))
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn opacity_is_applied_to_whole_layer() {
+        let renderer = test_renderer();
+        let mut map = test_map();
+        map.layers_mut().push(overlapping_bands_layer(Color::BLUE));
+        map.layers_mut().set_opacity(0, 0.5);
+
+        let [first, overlap, second] = band_colors(&renderer, &map);
+        assert_ne!(first, Color::BLUE.to_u8_array());
+        assert_ne!(first, Color::WHITE.to_u8_array());
+        assert_eq!(overlap, first);
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn blend_mode_is_applied_to_whole_layer() {
+        let renderer = test_renderer();
+        let color = Color::rgba(128, 128, 255, 255);
+        let mut map = test_map();
+        map.layers_mut().push(overlapping_bands_layer(color));
+        let normal = band_colors(&renderer, &map);
+
+        // Multiplying with the white background keeps the color, and the overlap is not multiplied twice.
+        map.layers_mut().set_blend_mode(0, BlendMode::Multiply);
+        let colors = band_colors(&renderer, &map);
+        assert_eq!(colors, [normal[0]; 3]);
+    }
+
+    #[test]
+    #[ignore = "requires a graphics adapter"]
+    fn group_is_composited_once() {
+        use crate::layer::LayerGroup;
+        use crate::map::LayerCollection;
+        use std::sync::RwLock;
+
+        let renderer = test_renderer();
+        let group = || {
+            let mut group = LayerGroup::new(LayerCollection::default());
+            group.add_layer(overlapping_bands_layer(Color::BLUE));
+            group.add_layer(overlapping_bands_layer(Color::BLUE));
+            group
+        };
+
+        let mut map = test_map();
+        map.layers_mut().push(group());
+        map.layers_mut().set_opacity(0, 0.5);
+        let colors = band_colors(&renderer, &map);
+        assert_ne!(colors[0], Color::BLUE.to_u8_array());
+        assert_ne!(colors[0], Color::WHITE.to_u8_array());
+        assert_eq!(colors, [colors[0]; 3]);
+
+        let mut shared_map = test_map();
+        shared_map.layers_mut().push(Arc::new(RwLock::new(group())));
+        shared_map.layers_mut().set_opacity(0, 0.5);
+        assert_eq!(band_colors(&renderer, &shared_map), colors);
+    }
+}
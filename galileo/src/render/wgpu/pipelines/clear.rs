@@ -0,0 +1,97 @@
+use std::mem::size_of;
+use wgpu::{BindGroup, Buffer, Device, Queue, RenderPass, RenderPipeline, TextureFormat};
+
+/// Fills the render target with a solid color. Unlike clearing on a render pass load, it respects the scissor
+/// rectangle, so it can be used to clear only a part of the target.
+pub struct ClearPipeline {
+    wgpu_pipeline: RenderPipeline,
+    color_buffer: Buffer,
+    color_binding: BindGroup,
+}
+
+impl ClearPipeline {
+    pub fn create(device: &Device, format: TextureFormat, sample_count: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/clear.wgsl"));
+
+        let color_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Clear color buffer"),
+            size: size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let color_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("clear_color_bind_group_layout"),
+        });
+
+        let color_binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &color_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: color_buffer.as_entire_binding(),
+            }],
+            label: Some("clear_color_bind_group"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&color_layout],
+            push_constant_ranges: &[],
+        });
+
+        let wgpu_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Clear pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            wgpu_pipeline,
+            color_buffer,
+            color_binding,
+        }
+    }
+
+    /// Fills the color target of the `render_pass` with the `color`.
+    ///
+    /// The color is written into the uniform buffer through the `queue`, so it is applied when the render pass is
+    /// submitted.
+    pub fn render<'a>(&'a self, queue: &Queue, render_pass: &mut RenderPass<'a>, color: [f32; 4]) {
+        queue.write_buffer(&self.color_buffer, 0, bytemuck::cast_slice(&color));
+
+        render_pass.set_pipeline(&self.wgpu_pipeline);
+        render_pass.set_bind_group(0, &self.color_binding, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
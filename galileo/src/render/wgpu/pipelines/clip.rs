@@ -1,7 +1,7 @@
 use crate::render::render_bundle::tessellating::PolyVertex;
-use crate::render::wgpu::pipelines::{default_pipeline_descriptor, default_targets};
+use crate::render::wgpu::pipelines::{blend_state, default_pipeline_descriptor, default_targets};
 use crate::render::wgpu::{WgpuPolygonBuffers, DEPTH_FORMAT};
-use crate::render::RenderOptions;
+use crate::render::{BlendMode, RenderOptions};
 use wgpu::{
     BindGroupLayout, CompareFunction, DepthStencilState, Device, RenderPass, RenderPipeline,
     RenderPipelineDescriptor, StencilFaceState, StencilOperation, StencilState, TextureFormat,
@@ -20,6 +20,7 @@ impl ClipPipeline {
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
     ) -> Self {
         let buffers = [PolyVertex::wgpu_desc()];
         let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/map_ref.wgsl"));
@@ -30,7 +31,7 @@ impl ClipPipeline {
             depth_fail_op: StencilOperation::Keep,
             pass_op: StencilOperation::Keep,
         };
-        let targets = default_targets(format);
+        let targets = default_targets(format, blend_state(BlendMode::Normal));
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
             bind_group_layouts: &[map_view_layout],
@@ -52,11 +53,11 @@ impl ClipPipeline {
 
         let wgpu_pipeline_antialias = device.create_render_pipeline(&RenderPipelineDescriptor {
             depth_stencil: depth_stencil.clone(),
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, true)
+            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, sample_count)
         });
         let wgpu_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             depth_stencil,
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
+            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1)
         });
 
         Self {
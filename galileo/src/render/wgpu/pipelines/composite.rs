@@ -0,0 +1,143 @@
+use crate::render::wgpu::pipelines::blend_state;
+use crate::render::BlendMode;
+use std::mem::size_of;
+use wgpu::{
+    BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPass, RenderPipeline, TextureFormat,
+    TextureView,
+};
+
+/// Draws the image of a layer, rendered into a separate texture of the size of the target, over the target with the
+/// opacity and blend mode of the layer.
+pub struct CompositePipeline {
+    /// Pipelines by the blend mode.
+    pipelines: [RenderPipeline; 4],
+    texture_layout: BindGroupLayout,
+    opacity_buffer: Buffer,
+}
+
+impl CompositePipeline {
+    pub fn create(device: &Device, format: TextureFormat, sample_count: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/composite.wgsl"));
+
+        let opacity_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Composite opacity buffer"),
+            size: size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("composite_bind_group_layout"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&texture_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipelines = [
+            BlendMode::Normal,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+            BlendMode::Additive,
+        ]
+        .map(|blend_mode| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Composite pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(blend_state(blend_mode)),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: Default::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        });
+
+        Self {
+            pipelines,
+            texture_layout,
+            opacity_buffer,
+        }
+    }
+
+    /// Creates the bind group to draw the layer texture with.
+    pub fn create_binding(&self, device: &Device, texture_view: &TextureView) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.opacity_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("composite_bind_group"),
+        })
+    }
+
+    /// Draws the layer texture of the `binding` over the color target of the `render_pass`.
+    ///
+    /// The opacity is written into the uniform buffer through the `queue`, so it is applied when the render pass is
+    /// submitted.
+    pub fn render<'a>(
+        &'a self,
+        queue: &Queue,
+        render_pass: &mut RenderPass<'a>,
+        binding: &'a BindGroup,
+        opacity: f32,
+        blend_mode: BlendMode,
+    ) {
+        queue.write_buffer(
+            &self.opacity_buffer,
+            0,
+            bytemuck::cast_slice(&[opacity, 0.0, 0.0, 0.0]),
+        );
+
+        render_pass.set_pipeline(&self.pipelines[blend_mode as usize]);
+        render_pass.set_bind_group(0, binding, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -3,9 +3,9 @@ use crate::render::wgpu::pipelines::{default_pipeline_descriptor, default_target
 use crate::render::wgpu::{WgpuDotBuffers, DEPTH_FORMAT};
 use crate::render::RenderOptions;
 use wgpu::{
-    BindGroupLayout, CompareFunction, DepthStencilState, Device, RenderPass, RenderPipeline,
-    RenderPipelineDescriptor, StencilFaceState, StencilOperation, StencilState, TextureFormat,
-    VertexStepMode,
+    BindGroupLayout, BlendState, CompareFunction, DepthStencilState, Device, RenderPass,
+    RenderPipeline, RenderPipelineDescriptor, StencilFaceState, StencilOperation, StencilState,
+    TextureFormat, VertexStepMode,
 };
 
 pub struct DotPipeline {
@@ -18,6 +18,8 @@ impl DotPipeline {
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
+        blend: BlendState,
     ) -> Self {
         let mut desc = PointInstance::wgpu_desc();
         desc.step_mode = VertexStepMode::Vertex;
@@ -25,7 +27,7 @@ impl DotPipeline {
         let buffers = [desc];
         let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/dot.wgsl"));
 
-        let targets = default_targets(format);
+        let targets = default_targets(format, blend);
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
             bind_group_layouts: &[map_view_layout],
@@ -62,12 +64,12 @@ impl DotPipeline {
         let wgpu_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
             primitive,
             depth_stencil: depth_stencil.clone(),
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
+            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1)
         });
         let wgpu_pipeline_antialias = device.create_render_pipeline(&RenderPipelineDescriptor {
             primitive,
             depth_stencil,
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, true)
+            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, sample_count)
         });
         Self {
             wgpu_pipeline,
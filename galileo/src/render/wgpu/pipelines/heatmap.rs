@@ -0,0 +1,484 @@
+use crate::render::HeatmapPaint;
+use crate::Color;
+use galileo_types::cartesian::Size;
+use std::mem::size_of;
+use std::sync::Mutex;
+use wgpu::util::DeviceExt;
+use wgpu::{
+    BindGroup, BindGroupLayout, Buffer, CommandEncoder, Device, Queue, RenderPipeline, Sampler,
+    Texture, TextureFormat, TextureView,
+};
+
+/// Format of the texture the density of the points is accumulated in.
+const DENSITY_FORMAT: TextureFormat = TextureFormat::R16Float;
+/// Number of colors in the color ramp texture.
+const RAMP_SIZE: u32 = 256;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct HeatmapInstance {
+    pub position: [f32; 2],
+    pub weight: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct HeatmapUniform {
+    radius: f32,
+    intensity: f32,
+    opacity: f32,
+    padding: f32,
+}
+
+pub struct WgpuHeatmapPoints {
+    buffer: Buffer,
+    count: u32,
+}
+
+/// Draws heatmaps in two passes: first the density of the points is accumulated in an offscreen float texture with
+/// additive blending, and then the density is converted into colors with the color ramp and drawn to the target.
+pub struct HeatmapPipeline {
+    accumulate_pipeline: RenderPipeline,
+    colorize_pipeline: RenderPipeline,
+    uniform_buffer: Buffer,
+    uniform_binding: BindGroup,
+    colorize_layout: BindGroupLayout,
+    ramp_texture: Texture,
+    ramp_view: TextureView,
+    ramp_sampler: Sampler,
+    density_target: Mutex<Option<DensityTarget>>,
+}
+
+struct DensityTarget {
+    size: Size<u32>,
+    view: TextureView,
+    colorize_binding: BindGroup,
+}
+
+impl HeatmapPipeline {
+    pub fn create(
+        device: &Device,
+        format: TextureFormat,
+        map_view_layout: &BindGroupLayout,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Heatmap uniform buffer"),
+            size: size_of::<HeatmapUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[uniform_layout_entry()],
+            label: Some("heatmap_uniform_bind_group_layout"),
+        });
+
+        let uniform_binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &uniform_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("heatmap_uniform_bind_group"),
+        });
+
+        let colorize_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                uniform_layout_entry(),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("heatmap_colorize_bind_group_layout"),
+        });
+
+        let accumulate_pipeline =
+            Self::create_accumulate_pipeline(device, map_view_layout, &uniform_layout);
+        let colorize_pipeline = Self::create_colorize_pipeline(device, format, &colorize_layout);
+
+        let ramp_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Heatmap color ramp texture"),
+            size: wgpu::Extent3d {
+                width: RAMP_SIZE,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let ramp_view = ramp_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let ramp_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            accumulate_pipeline,
+            colorize_pipeline,
+            uniform_buffer,
+            uniform_binding,
+            colorize_layout,
+            ramp_texture,
+            ramp_view,
+            ramp_sampler,
+            density_target: Mutex::new(None),
+        }
+    }
+
+    fn create_accumulate_pipeline(
+        device: &Device,
+        map_view_layout: &BindGroupLayout,
+        uniform_layout: &BindGroupLayout,
+    ) -> RenderPipeline {
+        let shader =
+            device.create_shader_module(wgpu::include_wgsl!("./shaders/heatmap_accumulate.wgsl"));
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[map_view_layout, uniform_layout],
+            push_constant_ranges: &[],
+        });
+
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Heatmap accumulate pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[HeatmapInstance::wgpu_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: DENSITY_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: additive,
+                        alpha: additive,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+        })
+    }
+
+    fn create_colorize_pipeline(
+        device: &Device,
+        format: TextureFormat,
+        colorize_layout: &BindGroupLayout,
+    ) -> RenderPipeline {
+        let shader =
+            device.create_shader_module(wgpu::include_wgsl!("./shaders/heatmap_colorize.wgsl"));
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[colorize_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Heatmap colorize pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+        })
+    }
+
+    pub fn create_points(&self, device: &Device, points: &[HeatmapInstance]) -> WgpuHeatmapPoints {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Heatmap points buffer"),
+            contents: bytemuck::cast_slice(points),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        WgpuHeatmapPoints {
+            buffer,
+            count: points.len() as u32,
+        }
+    }
+
+    /// Records both heatmap passes into the `encoder`. The uniforms are written through the `queue`, so they are
+    /// applied when the encoder is submitted.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        map_view_binding: &BindGroup,
+        target: &TextureView,
+        target_size: Size<u32>,
+        scissor: Option<(u32, u32, u32, u32)>,
+        points: &WgpuHeatmapPoints,
+        paint: HeatmapPaint,
+        opacity: f32,
+    ) {
+        if points.count == 0 || target_size.width() == 0 || target_size.height() == 0 {
+            return;
+        }
+
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[HeatmapUniform {
+                radius: paint.radius.max(0.0),
+                intensity: paint.intensity,
+                opacity,
+                padding: 0.0,
+            }]),
+        );
+        self.write_ramp(queue, paint.colors);
+
+        let mut density_target = self.density_target.lock().expect("mutex is poisoned");
+        let density_target = match &mut *density_target {
+            Some(density_target) if density_target.size == target_size => density_target,
+            density_target => {
+                density_target.insert(self.create_density_target(device, target_size))
+            }
+        };
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Heatmap accumulate pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &density_target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&self.accumulate_pipeline);
+            render_pass.set_bind_group(0, map_view_binding, &[]);
+            render_pass.set_bind_group(1, &self.uniform_binding, &[]);
+            render_pass.set_vertex_buffer(0, points.buffer.slice(..));
+            render_pass.draw(0..6, 0..points.count);
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Heatmap colorize pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        if let Some((x, y, width, height)) = scissor {
+            render_pass.set_scissor_rect(x, y, width, height);
+        }
+
+        render_pass.set_pipeline(&self.colorize_pipeline);
+        render_pass.set_bind_group(0, &density_target.colorize_binding, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn write_ramp(&self, queue: &Queue, colors: &[Color]) {
+        let ramp: Vec<[u8; 4]> = (0..RAMP_SIZE)
+            .map(|index| ramp_color(colors, index as f32 / (RAMP_SIZE - 1) as f32))
+            .collect();
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.ramp_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytemuck::cast_slice(&ramp),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(RAMP_SIZE * 4),
+                rows_per_image: Some(1),
+            },
+            wgpu::Extent3d {
+                width: RAMP_SIZE,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    fn create_density_target(&self, device: &Device, size: Size<u32>) -> DensityTarget {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Heatmap density texture"),
+            size: wgpu::Extent3d {
+                width: size.width(),
+                height: size.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DENSITY_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let colorize_binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.colorize_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&self.ramp_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&self.ramp_sampler),
+                },
+            ],
+            label: Some("heatmap_colorize_bind_group"),
+        });
+
+        DensityTarget {
+            size,
+            view,
+            colorize_binding,
+        }
+    }
+}
+
+fn uniform_layout_entry() -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+/// Color of the ramp at `position` in `[0.0, 1.0]`, linearly interpolated between the evenly distributed `colors`.
+fn ramp_color(colors: &[Color], position: f32) -> [u8; 4] {
+    match colors {
+        [] => [0, 0, 0, 0],
+        [color] => color.to_u8_array(),
+        _ => {
+            let scaled = position.clamp(0.0, 1.0) * (colors.len() - 1) as f32;
+            let index = (scaled.floor() as usize).min(colors.len() - 2);
+            let ratio = scaled - index as f32;
+            let from = colors[index].to_u8_array();
+            let to = colors[index + 1].to_u8_array();
+            std::array::from_fn(|channel| {
+                (from[channel] as f32 + (to[channel] as f32 - from[channel] as f32) * ratio).round()
+                    as u8
+            })
+        }
+    }
+}
+
+impl HeatmapInstance {
+    fn wgpu_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<HeatmapInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32,
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramp_interpolates_colors() {
+        let colors = [Color::rgba(0, 0, 0, 0), Color::rgba(200, 100, 0, 255)];
+        assert_eq!(ramp_color(&colors, 0.0), [0, 0, 0, 0]);
+        assert_eq!(ramp_color(&colors, 0.5), [100, 50, 0, 128]);
+        assert_eq!(ramp_color(&colors, 1.0), [200, 100, 0, 255]);
+        assert_eq!(ramp_color(&colors, 2.0), [200, 100, 0, 255]);
+        assert_eq!(ramp_color(&[Color::RED], 0.3), Color::RED.to_u8_array());
+        assert_eq!(ramp_color(&[], 0.3), [0, 0, 0, 0]);
+    }
+}
@@ -6,7 +6,7 @@ use crate::render::RenderOptions;
 use std::sync::Arc;
 use wgpu::util::{DeviceExt, TextureDataOrder};
 use wgpu::{
-    BindGroup, BindGroupLayout, Device, Queue, RenderPass, RenderPipeline,
+    BindGroup, BindGroupLayout, BlendState, Device, Queue, RenderPass, RenderPipeline,
     RenderPipelineDescriptor, TextureFormat,
 };
 
@@ -20,19 +20,14 @@ pub struct WgpuImage {
 pub struct ImagePipeline {
     wgpu_pipeline: RenderPipeline,
     index_buffer: wgpu::Buffer,
-    texture_bind_group_layout: BindGroupLayout,
+    texture_bind_group_layout: Arc<BindGroupLayout>,
     pub wgpu_pipeline_antialias: RenderPipeline,
 }
 
 impl ImagePipeline {
-    pub fn create(
-        device: &Device,
-        format: TextureFormat,
-        map_view_layout: &BindGroupLayout,
-    ) -> Self {
-        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/image.wgsl"));
-
-        let texture_bind_group_layout =
+    /// Creates the layout of the image textures bind group, shared by the image pipelines of all blend modes.
+    pub fn create_texture_layout(device: &Device) -> Arc<BindGroupLayout> {
+        Arc::new(
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
@@ -53,7 +48,20 @@ impl ImagePipeline {
                     },
                 ],
                 label: Some("texture_bind_group_label"),
-            });
+            }),
+        )
+    }
+
+    pub fn create(
+        device: &Device,
+        format: TextureFormat,
+        map_view_layout: &BindGroupLayout,
+        texture_bind_group_layout: Arc<BindGroupLayout>,
+        sample_count: u32,
+        blend: BlendState,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/image.wgsl"));
+
         let buffers = [ImageVertex::wgpu_desc()];
 
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -62,14 +70,14 @@ impl ImagePipeline {
             push_constant_ranges: &[],
         });
 
-        let targets = default_targets(format);
+        let targets = default_targets(format, blend);
 
         let mut desc = RenderPipelineDescriptor {
-            ..pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
+            ..pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1)
         };
 
         let wgpu_pipeline = device.create_render_pipeline(&desc);
-        desc.multisample.count = 4;
+        desc.multisample.count = sample_count;
         let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
 
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -211,6 +219,12 @@ impl ImageVertex {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Float32x2,
                 },
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 2]>() * 3 + std::mem::size_of::<f32>())
+                        as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
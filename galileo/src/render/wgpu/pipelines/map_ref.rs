@@ -2,7 +2,9 @@ use crate::render::render_bundle::tessellating::PolyVertex;
 use crate::render::wgpu::pipelines::default_targets;
 use crate::render::wgpu::{pipelines, WgpuPolygonBuffers};
 use crate::render::RenderOptions;
-use wgpu::{BindGroupLayout, Device, RenderPass, RenderPipeline, TextureFormat};
+use wgpu::{
+    BindGroupLayout, BlendState, CompareFunction, Device, RenderPass, RenderPipeline, TextureFormat,
+};
 
 pub struct MapRefPipeline {
     wgpu_pipeline: RenderPipeline,
@@ -14,21 +16,37 @@ impl MapRefPipeline {
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
+        depth_test: bool,
+        feathering: bool,
+        blend: BlendState,
     ) -> Self {
         let buffers = [PolyVertex::wgpu_desc()];
         let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/map_ref.wgsl"));
 
-        let targets = default_targets(format);
+        let targets = default_targets(format, blend);
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
             bind_group_layouts: &[map_view_layout],
             push_constant_ranges: &[],
         });
         let mut desc =
-            pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false);
+            pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1);
+        if feathering {
+            desc.vertex.entry_point = "vs_feathered";
+            if let Some(fragment) = &mut desc.fragment {
+                fragment.entry_point = "fs_feathered";
+            }
+        }
+        if depth_test {
+            if let Some(depth_stencil) = &mut desc.depth_stencil {
+                depth_stencil.depth_write_enabled = true;
+                depth_stencil.depth_compare = CompareFunction::LessEqual;
+            }
+        }
         let wgpu_pipeline = device.create_render_pipeline(&desc);
 
-        desc.multisample.count = 4;
+        desc.multisample.count = sample_count;
         let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
 
         Self {
@@ -0,0 +1,91 @@
+use crate::render::render_bundle::tessellating::MarkerInstance;
+use crate::render::wgpu::pipelines::{default_pipeline_descriptor, default_targets};
+use crate::render::wgpu::WgpuMarkerBuffers;
+use crate::render::RenderOptions;
+use std::mem::size_of;
+use wgpu::{BindGroupLayout, BlendState, Device, RenderPass, RenderPipeline, TextureFormat};
+
+/// Number of vertices of the quad drawn for every marker.
+const QUAD_VERTEX_COUNT: u32 = 6;
+
+pub struct MarkerPipeline {
+    wgpu_pipeline: RenderPipeline,
+    pub wgpu_pipeline_antialias: RenderPipeline,
+}
+
+impl MarkerPipeline {
+    pub fn create(
+        device: &Device,
+        format: TextureFormat,
+        map_view_layout: &BindGroupLayout,
+        sample_count: u32,
+        blend: BlendState,
+    ) -> Self {
+        let buffers = [MarkerInstance::wgpu_desc()];
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/marker.wgsl"));
+
+        let targets = default_targets(format, blend);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[map_view_layout],
+            push_constant_ranges: &[],
+        });
+        let mut desc = default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1);
+        let set_fragment_entry = |desc: &mut wgpu::RenderPipelineDescriptor, entry_point| {
+            if let Some(fragment) = &mut desc.fragment {
+                fragment.entry_point = entry_point;
+            }
+        };
+
+        set_fragment_entry(&mut desc, "fs_aliased");
+        let wgpu_pipeline = device.create_render_pipeline(&desc);
+
+        desc.multisample.count = sample_count;
+        if sample_count > 1 {
+            set_fragment_entry(&mut desc, "fs_main");
+        }
+        let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
+
+        Self {
+            wgpu_pipeline,
+            wgpu_pipeline_antialias,
+        }
+    }
+
+    pub fn render<'a>(
+        &'a self,
+        buffers: &'a WgpuMarkerBuffers,
+        render_pass: &mut RenderPass<'a>,
+        render_options: RenderOptions,
+    ) {
+        if render_options.antialias {
+            render_pass.set_pipeline(&self.wgpu_pipeline_antialias);
+        } else {
+            render_pass.set_pipeline(&self.wgpu_pipeline);
+        }
+
+        render_pass.set_vertex_buffer(0, buffers.buffer.slice(..));
+        render_pass.draw(0..QUAD_VERTEX_COUNT, 0..buffers.marker_count);
+    }
+}
+
+impl MarkerInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 8] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x2,
+        2 => Float32,
+        3 => Float32,
+        4 => Float32x2,
+        5 => Uint8x4,
+        6 => Uint8x4,
+        7 => Uint32,
+    ];
+
+    fn wgpu_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<MarkerInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
@@ -1,36 +1,66 @@
+use crate::render::wgpu::pipelines::clear::ClearPipeline;
 use crate::render::wgpu::pipelines::clip::ClipPipeline;
+use crate::render::wgpu::pipelines::composite::CompositePipeline;
 use crate::render::wgpu::pipelines::dot::DotPipeline;
+use crate::render::wgpu::pipelines::heatmap::HeatmapPipeline;
 use crate::render::wgpu::pipelines::image::ImagePipeline;
 use crate::render::wgpu::pipelines::map_ref::MapRefPipeline;
+use crate::render::wgpu::pipelines::marker::MarkerPipeline;
+use crate::render::wgpu::pipelines::pattern::PatternPipeline;
 use crate::render::wgpu::pipelines::screen_ref::ScreenRefPipeline;
-use crate::render::wgpu::{ViewUniform, WgpuPackedBundle, DEPTH_FORMAT};
-use crate::render::RenderOptions;
+use crate::render::wgpu::{LineAntialiasing, ViewUniform, WgpuPackedBundle, DEPTH_FORMAT};
+use crate::render::{BlendMode, RenderOptions};
 use std::mem::size_of;
 use wgpu::{
-    BindGroup, Buffer, CompareFunction, DepthStencilState, Device, PipelineLayout, RenderPass,
+    BindGroup, BindGroupLayout, BlendComponent, BlendFactor, BlendOperation, BlendState, Buffer,
+    CompareFunction, DepthStencilState, Device, PipelineLayout, RenderPass,
     RenderPipelineDescriptor, ShaderModule, StencilFaceState, StencilOperation, StencilState,
     TextureFormat, VertexBufferLayout,
 };
 
+mod clear;
 mod clip;
+mod composite;
 mod dot;
+pub mod heatmap;
 pub mod image;
 mod map_ref;
+mod marker;
+pub mod pattern;
 mod screen_ref;
 
 pub struct Pipelines {
     map_view_binding: BindGroup,
     map_view_buffer: Buffer,
+    map_view_layout: BindGroupLayout,
+    format: TextureFormat,
 
+    primitives: PrimitivePipelines,
+    clip: ClipPipeline,
+    clear: ClearPipeline,
+    composite: CompositePipeline,
+    heatmap: HeatmapPipeline,
+}
+
+/// Pipelines drawing the primitives of the bundles. Primitives are always drawn with the normal blend mode, the
+/// opacity and blend mode of a layer are applied when its image is composited onto the target.
+struct PrimitivePipelines {
     image: ImagePipeline,
     screen_ref: ScreenRefPipeline,
     map_ref: MapRefPipeline,
-    clip: ClipPipeline,
+    extrusion: MapRefPipeline,
+    pattern: PatternPipeline,
     dot: DotPipeline,
+    marker: MarkerPipeline,
 }
 
 impl Pipelines {
-    pub fn create(device: &Device, format: TextureFormat) -> Self {
+    pub fn create(
+        device: &Device,
+        format: TextureFormat,
+        sample_count: u32,
+        line_antialiasing: LineAntialiasing,
+    ) -> Self {
         let map_view_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Map view buffer"),
             size: size_of::<ViewUniform>() as wgpu::BufferAddress,
@@ -38,23 +68,22 @@ impl Pipelines {
             mapped_at_creation: false,
         });
 
-        let map_view_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-                label: None,
-            });
+        let map_view_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: None,
+        });
 
         let map_view_binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &map_view_bind_group_layout,
+            layout: &map_view_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: map_view_buffer.as_entire_binding(),
@@ -62,14 +91,59 @@ impl Pipelines {
             label: Some("view_bind_group"),
         });
 
+        let image_texture_layout = ImagePipeline::create_texture_layout(device);
+        let blend = blend_state(BlendMode::Normal);
+        let layout = &map_view_layout;
+        let primitives = PrimitivePipelines {
+            image: ImagePipeline::create(
+                device,
+                format,
+                layout,
+                image_texture_layout.clone(),
+                sample_count,
+                blend,
+            ),
+            map_ref: MapRefPipeline::create(
+                device,
+                format,
+                layout,
+                sample_count,
+                false,
+                line_antialiasing == LineAntialiasing::Feathering,
+                blend,
+            ),
+            extrusion: MapRefPipeline::create(
+                device,
+                format,
+                layout,
+                sample_count,
+                true,
+                false,
+                blend,
+            ),
+            pattern: PatternPipeline::create(
+                device,
+                format,
+                layout,
+                &image_texture_layout,
+                sample_count,
+                blend,
+            ),
+            screen_ref: ScreenRefPipeline::create(device, format, layout, sample_count, blend),
+            dot: DotPipeline::create(device, format, layout, sample_count, blend),
+            marker: MarkerPipeline::create(device, format, layout, sample_count, blend),
+        };
+
         Self {
             map_view_binding,
             map_view_buffer,
-            image: ImagePipeline::create(device, format, &map_view_bind_group_layout),
-            map_ref: MapRefPipeline::create(device, format, &map_view_bind_group_layout),
-            screen_ref: ScreenRefPipeline::create(device, format, &map_view_bind_group_layout),
-            clip: ClipPipeline::create(device, format, &map_view_bind_group_layout),
-            dot: DotPipeline::create(device, format, &map_view_bind_group_layout),
+            primitives,
+            clip: ClipPipeline::create(device, format, &map_view_layout, sample_count),
+            clear: ClearPipeline::create(device, format, sample_count),
+            composite: CompositePipeline::create(device, format, sample_count),
+            heatmap: HeatmapPipeline::create(device, format, &map_view_layout),
+            map_view_layout,
+            format,
         }
     }
 
@@ -80,27 +154,56 @@ impl Pipelines {
         render_options: RenderOptions,
     ) {
         self.set_bindings(render_pass);
+        let primitives = &self.primitives;
 
         if let Some(clip) = &bundle.clip_area_buffers {
             self.clip.clip(clip, render_pass, render_options);
         }
 
         for image in &bundle.image_buffers {
-            self.image.render(image, render_pass, render_options);
+            primitives.image.render(image, render_pass, render_options);
         }
 
         if bundle.map_ref_buffers.index_count > 0 {
-            self.map_ref
+            primitives
+                .map_ref
                 .render(&bundle.map_ref_buffers, render_pass, render_options);
         }
 
+        if let Some(hatch_buffers) = &bundle.hatch_buffers {
+            primitives
+                .pattern
+                .render_hatch(hatch_buffers, render_pass, render_options);
+        }
+
+        for pattern in &bundle.pattern_buffers {
+            primitives
+                .pattern
+                .render_pattern(pattern, render_pass, render_options);
+        }
+
+        if let Some(extrusion_buffers) = &bundle.extrusion_buffers {
+            primitives
+                .extrusion
+                .render(extrusion_buffers, render_pass, render_options);
+        }
+
         if let Some(screen_ref_buffers) = &bundle.screen_ref_buffers {
-            self.screen_ref
+            primitives
+                .screen_ref
                 .render(screen_ref_buffers, render_pass, render_options);
         }
 
+        if let Some(marker_buffers) = &bundle.marker_buffers {
+            primitives
+                .marker
+                .render(marker_buffers, render_pass, render_options);
+        }
+
         if let Some(dot_buffers) = &bundle.dot_buffers {
-            self.dot.render(dot_buffers, render_pass, render_options);
+            primitives
+                .dot
+                .render(dot_buffers, render_pass, render_options);
         }
 
         if let Some(clip) = &bundle.clip_area_buffers {
@@ -113,7 +216,31 @@ impl Pipelines {
     }
 
     pub fn image_pipeline(&self) -> &ImagePipeline {
-        &self.image
+        &self.primitives.image
+    }
+
+    pub fn clear_pipeline(&self) -> &ClearPipeline {
+        &self.clear
+    }
+
+    pub fn composite_pipeline(&self) -> &CompositePipeline {
+        &self.composite
+    }
+
+    pub fn heatmap_pipeline(&self) -> &HeatmapPipeline {
+        &self.heatmap
+    }
+
+    pub fn map_view_binding(&self) -> &BindGroup {
+        &self.map_view_binding
+    }
+
+    pub fn map_view_layout(&self) -> &BindGroupLayout {
+        &self.map_view_layout
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
     }
 
     fn set_bindings<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
@@ -121,20 +248,44 @@ impl Pipelines {
     }
 }
 
-fn default_targets(format: TextureFormat) -> [Option<wgpu::ColorTargetState>; 1] {
+fn default_targets(
+    format: TextureFormat,
+    blend: BlendState,
+) -> [Option<wgpu::ColorTargetState>; 1] {
     [Some(wgpu::ColorTargetState {
         format,
-        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+        blend: Some(blend),
         write_mask: wgpu::ColorWrites::ALL,
     })]
 }
 
+/// Blend state of the blend mode. The shaders of the primitives output colors with premultiplied alpha.
+fn blend_state(blend_mode: BlendMode) -> BlendState {
+    let component = |src_factor, dst_factor| BlendComponent {
+        src_factor,
+        dst_factor,
+        operation: BlendOperation::Add,
+    };
+
+    let color = match blend_mode {
+        BlendMode::Normal => component(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+        BlendMode::Multiply => component(BlendFactor::Dst, BlendFactor::OneMinusSrcAlpha),
+        BlendMode::Screen => component(BlendFactor::One, BlendFactor::OneMinusSrc),
+        BlendMode::Additive => component(BlendFactor::One, BlendFactor::One),
+    };
+
+    BlendState {
+        color,
+        alpha: component(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+    }
+}
+
 fn default_pipeline_descriptor<'a>(
     pipeline_layout: &'a PipelineLayout,
     shader: &'a ShaderModule,
     targets: &'a [Option<wgpu::ColorTargetState>],
     buffers: &'a [VertexBufferLayout<'a>],
-    antialias: bool,
+    sample_count: u32,
 ) -> RenderPipelineDescriptor<'a> {
     let stencil_state = StencilFaceState {
         compare: CompareFunction::Equal,
@@ -178,7 +329,7 @@ fn default_pipeline_descriptor<'a>(
             bias: Default::default(),
         }),
         multisample: wgpu::MultisampleState {
-            count: if antialias { 4 } else { 1 },
+            count: sample_count,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
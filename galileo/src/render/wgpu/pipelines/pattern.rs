@@ -0,0 +1,130 @@
+use crate::render::render_bundle::tessellating::PatternVertex;
+use crate::render::wgpu::pipelines::default_targets;
+use crate::render::wgpu::{pipelines, WgpuPolygonBuffers};
+use crate::render::RenderOptions;
+use std::mem::size_of;
+use std::sync::Arc;
+use wgpu::{
+    BindGroup, BindGroupLayout, BlendState, Device, RenderPass, RenderPipeline, TextureFormat,
+};
+
+pub struct WgpuPatternFill {
+    pub texture_bind_group: Arc<BindGroup>,
+    pub buffers: WgpuPolygonBuffers,
+}
+
+/// Draws polygons filled with hatches and pattern images.
+pub struct PatternPipeline {
+    hatch: RenderPipeline,
+    hatch_antialias: RenderPipeline,
+    image: RenderPipeline,
+    image_antialias: RenderPipeline,
+}
+
+impl PatternPipeline {
+    pub fn create(
+        device: &Device,
+        format: TextureFormat,
+        map_view_layout: &BindGroupLayout,
+        texture_bind_group_layout: &BindGroupLayout,
+        sample_count: u32,
+        blend: BlendState,
+    ) -> Self {
+        let buffers = [PatternVertex::wgpu_desc()];
+        let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/pattern.wgsl"));
+        let targets = default_targets(format, blend);
+
+        let create = |bind_group_layouts: &[&BindGroupLayout], entry_point| {
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+            let mut desc =
+                pipelines::default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1);
+            if let Some(fragment) = &mut desc.fragment {
+                fragment.entry_point = entry_point;
+            }
+
+            let pipeline = device.create_render_pipeline(&desc);
+            desc.multisample.count = sample_count;
+            let pipeline_antialias = device.create_render_pipeline(&desc);
+
+            (pipeline, pipeline_antialias)
+        };
+
+        let (hatch, hatch_antialias) = create(&[map_view_layout], "fs_hatch");
+        let (image, image_antialias) =
+            create(&[map_view_layout, texture_bind_group_layout], "fs_image");
+
+        Self {
+            hatch,
+            hatch_antialias,
+            image,
+            image_antialias,
+        }
+    }
+
+    pub fn render_hatch<'a>(
+        &'a self,
+        buffers: &'a WgpuPolygonBuffers,
+        render_pass: &mut RenderPass<'a>,
+        render_options: RenderOptions,
+    ) {
+        if render_options.antialias {
+            render_pass.set_pipeline(&self.hatch_antialias);
+        } else {
+            render_pass.set_pipeline(&self.hatch);
+        }
+
+        Self::draw(buffers, render_pass);
+    }
+
+    pub fn render_pattern<'a>(
+        &'a self,
+        fill: &'a WgpuPatternFill,
+        render_pass: &mut RenderPass<'a>,
+        render_options: RenderOptions,
+    ) {
+        if render_options.antialias {
+            render_pass.set_pipeline(&self.image_antialias);
+        } else {
+            render_pass.set_pipeline(&self.image);
+        }
+
+        render_pass.set_bind_group(1, &fill.texture_bind_group, &[]);
+        Self::draw(&fill.buffers, render_pass);
+    }
+
+    fn draw<'a>(buffers: &'a WgpuPolygonBuffers, render_pass: &mut RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, buffers.vertex.slice(..));
+        render_pass.set_index_buffer(buffers.index.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..buffers.index_count, 0, 0..1);
+    }
+}
+
+impl PatternVertex {
+    fn wgpu_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: size_of::<PatternVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: (size_of::<[f32; 3]>() + size_of::<[f32; 4]>()) as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
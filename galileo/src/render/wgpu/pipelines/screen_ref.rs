@@ -4,8 +4,9 @@ use crate::render::wgpu::{ScreenRefBuffers, DEPTH_FORMAT};
 use crate::render::RenderOptions;
 use std::mem::size_of;
 use wgpu::{
-    BindGroupLayout, CompareFunction, DepthStencilState, Device, RenderPass, RenderPipeline,
-    RenderPipelineDescriptor, StencilFaceState, StencilOperation, StencilState, TextureFormat,
+    BindGroupLayout, BlendState, CompareFunction, DepthStencilState, Device, RenderPass,
+    RenderPipeline, RenderPipelineDescriptor, StencilFaceState, StencilOperation, StencilState,
+    TextureFormat,
 };
 
 pub struct ScreenRefPipeline {
@@ -18,11 +19,13 @@ impl ScreenRefPipeline {
         device: &Device,
         format: TextureFormat,
         map_view_layout: &BindGroupLayout,
+        sample_count: u32,
+        blend: BlendState,
     ) -> Self {
         let buffers = [ScreenRefVertex::wgpu_desc()];
         let shader = device.create_shader_module(wgpu::include_wgsl!("./shaders/screen_ref.wgsl"));
 
-        let targets = default_targets(format);
+        let targets = default_targets(format, blend);
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: None,
             bind_group_layouts: &[map_view_layout],
@@ -47,12 +50,12 @@ impl ScreenRefPipeline {
                 },
                 bias: Default::default(),
             }),
-            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, false)
+            ..default_pipeline_descriptor(&layout, &shader, &targets, &buffers, 1)
         };
 
         let wgpu_pipeline = device.create_render_pipeline(&desc);
 
-        desc.multisample.count = 4;
+        desc.multisample.count = sample_count;
         let wgpu_pipeline_antialias = device.create_render_pipeline(&desc);
 
         Self {
@@ -0,0 +1,21 @@
+// Fills the whole render target (limited by the scissor rectangle) with a single color.
+
+struct ClearUniform {
+    color: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> clear: ClearUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // A single triangle covering the whole clip space.
+    let x = f32(i32(index) / 2) * 4.0 - 1.0;
+    let y = f32(i32(index) % 2) * 4.0 - 1.0;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return clear.color;
+}
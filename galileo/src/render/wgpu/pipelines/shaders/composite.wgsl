@@ -0,0 +1,24 @@
+// Draws the image of a layer, rendered into a separate texture of the same size as the target, over the target.
+
+struct CompositeUniform {
+    opacity: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var layer: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> composite: CompositeUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // A single triangle covering the whole clip space.
+    let x = f32(i32(index) / 2) * 4.0 - 1.0;
+    let y = f32(i32(index) % 2) * 4.0 - 1.0;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // Colors of the layer texture are premultiplied by alpha, so the opacity is applied to all the components.
+    return textureLoad(layer, vec2<i32>(position.xy), 0) * composite.opacity.x;
+}
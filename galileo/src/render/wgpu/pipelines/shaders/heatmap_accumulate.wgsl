@@ -0,0 +1,72 @@
+// Accumulates density of the heatmap points into a single channel float texture.
+
+struct ViewUniform {
+    view_proj: mat4x4<f32>,
+    view_rotation: mat4x4<f32>,
+    inv_screen_size: vec2<f32>,
+    resolution: f32,
+    opacity: f32,
+}
+
+struct HeatmapUniform {
+    radius: f32,
+    intensity: f32,
+    opacity: f32,
+    padding: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> transform: ViewUniform;
+
+@group(1) @binding(0)
+var<uniform> heatmap: HeatmapUniform;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) weight: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) offset: vec2<f32>,
+    @location(1) weight: f32,
+};
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) index: u32,
+    model: VertexInput,
+) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[index];
+
+    var out: VertexOutput;
+    let point_position = transform.view_proj * vec4<f32>(model.position, 0.0, 1.0);
+    let vertex_delta = corner * heatmap.radius * transform.inv_screen_size * point_position[3] * 2.0;
+    out.clip_position = point_position + vec4<f32>(vertex_delta, 0.0, 0.0);
+    out.offset = corner;
+    out.weight = model.weight;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance_sq = dot(in.offset, in.offset);
+    if distance_sq >= 1.0 {
+        discard;
+    }
+
+    // Smooth kernel that is equal to 1 at the point and falls to 0 at the radius.
+    let falloff = 1.0 - distance_sq;
+    let density = falloff * falloff * falloff * in.weight * heatmap.intensity;
+
+    return vec4<f32>(density, 0.0, 0.0, 1.0);
+}
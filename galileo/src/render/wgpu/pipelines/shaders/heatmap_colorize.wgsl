@@ -0,0 +1,41 @@
+// Colors the accumulated heatmap density with the color ramp.
+
+struct HeatmapUniform {
+    radius: f32,
+    intensity: f32,
+    opacity: f32,
+    padding: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> heatmap: HeatmapUniform;
+@group(0) @binding(1)
+var density_texture: texture_2d<f32>;
+@group(0) @binding(2)
+var ramp_texture: texture_2d<f32>;
+@group(0) @binding(3)
+var ramp_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    // A single triangle covering the whole clip space.
+    let x = f32(i32(index) / 2) * 4.0 - 1.0;
+    let y = f32(i32(index) % 2) * 4.0 - 1.0;
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let density = textureLoad(density_texture, vec2<i32>(position.xy), 0)[0];
+    // Map the density to the centers of the first and the last texels of the ramp.
+    let ramp_size = f32(textureDimensions(ramp_texture)[0]);
+    let ramp_position = (clamp(density, 0.0, 1.0) * (ramp_size - 1.0) + 0.5) / ramp_size;
+    var color = textureSampleLevel(ramp_texture, ramp_sampler, vec2<f32>(ramp_position, 0.5), 0.0);
+    color[3] = color[3] * heatmap.opacity;
+
+    if density <= 0.0 || color[3] == 0.0 {
+        discard;
+    }
+
+    return color;
+}
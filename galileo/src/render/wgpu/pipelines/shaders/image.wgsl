@@ -5,6 +5,9 @@ struct ViewUniform {
     view_rotation: mat4x4<f32>,
     inv_screen_size: vec2<f32>,
     resolution: f32,
+    opacity: f32,
+    pattern_origin: vec2<f32>,
+    color_matrix: mat4x4<f32>,
 }
 
 @group(0) @binding(0)
@@ -15,6 +18,7 @@ struct VertexInput {
     @location(1) opacity: f32,
     @location(2) tex_coord: vec2<f32>,
     @location(3) offset: vec2<f32>,
+    @location(4) map_aligned: f32,
 }
 
 struct VertexOutput {
@@ -32,9 +36,12 @@ fn vs_main(
 
     var point_position = transform.view_proj * vec4<f32>(model.position, 0.0, 1.0);
     var vertex_delta = vec4<f32>(model.offset * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+    if (model.map_aligned > 0.5) {
+        vertex_delta = vertex_delta * transform.view_rotation;
+    }
 
     out.clip_position = point_position + vertex_delta;
-    out.opacity = model.opacity;
+    out.opacity = model.opacity * transform.opacity;
 
     return out;
 }
@@ -51,10 +58,12 @@ var s_diffuse: sampler;
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     var color = textureSample(t_diffuse, s_diffuse, in.tex_coord);
     color[3] = color[3] * in.opacity;
+    let rgb = clamp((transform.color_matrix * vec4<f32>(color.rgb, 1.0)).rgb, vec3<f32>(0.0), vec3<f32>(1.0));
 
     if color[3] == 0.0 {
         discard;
     }
 
-    return color;
+    // Blend states expect colors with premultiplied alpha.
+    return vec4<f32>(rgb * color[3], color[3]);
 }
@@ -5,6 +5,7 @@ struct ViewUniform {
     view_rotation: mat4x4<f32>,
     inv_screen_size: vec2<f32>,
     resolution: f32,
+    opacity: f32,
 }
 
 @group(0) @binding(0)
@@ -27,7 +28,7 @@ fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.color = model.color;
+    out.color = vec4<f32>(model.color.rgb, model.color.a * transform.opacity);
 
     var vertex_position = transform.view_proj * vec4<f32>(model.position, 1.0);
     var norm_length = sqrt(model.norm[0] * model.norm[0] + model.norm[1] * model.norm[1]) * transform.resolution;
@@ -49,5 +50,61 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    // Blend states expect colors with premultiplied alpha.
+    return vec4<f32>(in.color.rgb * in.color.a, in.color.a);
+}
+
+
+// Feathered lines
+
+struct FeatheredVertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    // Offset of the vertex from the center of the line in pixels. Zero for polygons.
+    @location(2) offset: vec2<f32>,
+    // Half of the line width in pixels, including the feathered edge.
+    @location(3) half_width: f32,
+};
+
+@vertex
+fn vs_feathered(
+    model: VertexInput,
+) -> FeatheredVertexOutput {
+    var out: FeatheredVertexOutput;
+    out.color = vec4<f32>(model.color.rgb, model.color.a * transform.opacity);
+
+    var vertex_position = transform.view_proj * vec4<f32>(model.position, 1.0);
+    var norm_length = sqrt(model.norm[0] * model.norm[0] + model.norm[1] * model.norm[1]) * transform.resolution;
+
+    var norm_limit = 1.0;
+    if (norm_length > model.norm_limit) {
+        norm_limit = model.norm_limit / norm_length;
+    }
+
+    // The line is widened by half a pixel on each side, and this band is faded out in the fragment shader.
+    var offset = model.norm * norm_limit;
+    var half_width = length(offset);
+    if (half_width > 0.0) {
+        offset = offset * (half_width + 0.5) / half_width;
+        half_width = half_width + 0.5;
+    }
+
+    out.offset = offset;
+    out.half_width = half_width;
+
+    var norm_scale = vec2<f32>(offset[0] * transform.inv_screen_size[0], offset[1] * transform.inv_screen_size[1]);
+    var norm = vec4<f32>(norm_scale * vertex_position[3] * 2.0, 0.0, 0.0) * transform.view_rotation;
+    out.clip_position = vertex_position + norm;
+
+    return out;
+}
+
+@fragment
+fn fs_feathered(in: FeatheredVertexOutput) -> @location(0) vec4<f32> {
+    var alpha = in.color.a;
+    if (in.half_width > 0.0) {
+        alpha = alpha * clamp(in.half_width - length(in.offset), 0.0, 1.0);
+    }
+
+    return vec4<f32>(in.color.rgb * alpha, alpha);
 }
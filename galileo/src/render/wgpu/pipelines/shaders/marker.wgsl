@@ -0,0 +1,130 @@
+// Vertex shader
+
+struct ViewUniform {
+    view_proj: mat4x4<f32>,
+    view_rotation: mat4x4<f32>,
+    inv_screen_size: vec2<f32>,
+    resolution: f32,
+    opacity: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> transform: ViewUniform;
+
+struct InstanceInput {
+    @location(0) position: vec3<f32>,
+    @location(1) offset: vec2<f32>,
+    @location(2) size: f32,
+    @location(3) rotation: f32,
+    @location(4) outline_range: vec2<f32>,
+    @location(5) fill: vec4<u32>,
+    @location(6) outline: vec4<u32>,
+    @location(7) shape: u32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Position of the fragment relative to the center of the marker in pixels, before rotation.
+    @location(0) local: vec2<f32>,
+    @location(1) @interpolate(flat) size: f32,
+    @location(2) @interpolate(flat) outline_range: vec2<f32>,
+    @location(3) @interpolate(flat) fill: vec4<f32>,
+    @location(4) @interpolate(flat) outline: vec4<f32>,
+    @location(5) @interpolate(flat) shape: u32,
+};
+
+const SQUARE: u32 = 1u;
+
+var<private> CORNERS: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, -1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, 1.0),
+);
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Vacant marker slots collapse into a point and produce no fragments.
+    if (instance.size < 0.0) {
+        out.clip_position = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        return out;
+    }
+
+    // One additional pixel around the shape leaves space for antialiasing of the edge.
+    let extent = instance.size + max(instance.outline_range.y, 0.0) + 1.0;
+    let local = CORNERS[vertex_index] * extent;
+    let c = cos(instance.rotation);
+    let s = sin(instance.rotation);
+    let screen_offset = instance.offset + vec2<f32>(local.x * c - local.y * s, local.x * s + local.y * c);
+
+    let point_position = transform.view_proj * vec4<f32>(instance.position, 1.0);
+    let vertex_delta = vec4<f32>(screen_offset * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
+    out.clip_position = point_position + vertex_delta;
+
+    out.local = local;
+    out.size = instance.size;
+    out.outline_range = instance.outline_range;
+    out.fill = vec4<f32>(instance.fill) / 255.0;
+    out.outline = vec4<f32>(instance.outline) / 255.0;
+    out.shape = instance.shape;
+
+    return out;
+}
+
+// Fragment shader
+
+// Antialiased markers are used with multisampling, otherwise the edges are left sharp like the edges of the
+// tessellated primitives.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return marker_color(in, true);
+}
+
+@fragment
+fn fs_aliased(in: VertexOutput) -> @location(0) vec4<f32> {
+    return marker_color(in, false);
+}
+
+// Part of the pixel covered by the area with negative distance.
+fn coverage(distance: f32, antialias: bool) -> f32 {
+    if (antialias) {
+        return clamp(0.5 - distance, 0.0, 1.0);
+    }
+
+    return select(0.0, 1.0, distance <= 0.0);
+}
+
+fn marker_color(in: VertexOutput, antialias: bool) -> vec4<f32> {
+    // Signed distance from the edge of the shape in pixels, positive outside.
+    var distance: f32;
+    if (in.shape == SQUARE) {
+        distance = max(abs(in.local.x), abs(in.local.y)) - in.size;
+    } else {
+        distance = length(in.local) - in.size;
+    }
+
+    let fill_coverage = coverage(distance, antialias);
+    var outline_coverage = 0.0;
+    if (in.outline_range.y > in.outline_range.x) {
+        outline_coverage = coverage(distance - in.outline_range.y, antialias)
+            * coverage(in.outline_range.x - distance, antialias);
+    }
+
+    // Blend states expect colors with premultiplied alpha. The outline is drawn over the fill.
+    let fill_alpha = in.fill.a * fill_coverage;
+    let outline_alpha = in.outline.a * outline_coverage;
+    let alpha = outline_alpha + fill_alpha * (1.0 - outline_alpha);
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    let color = in.outline.rgb * outline_alpha + in.fill.rgb * fill_alpha * (1.0 - outline_alpha);
+    return vec4<f32>(color, alpha) * transform.opacity;
+}
@@ -0,0 +1,80 @@
+// Vertex shader
+
+struct ViewUniform {
+    view_proj: mat4x4<f32>,
+    view_rotation: mat4x4<f32>,
+    inv_screen_size: vec2<f32>,
+    resolution: f32,
+    opacity: f32,
+    pattern_origin: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> transform: ViewUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) pattern: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    // Position of the vertex in pixels of the map, relative to the pattern origin.
+    @location(2) pattern_position: vec2<f32>,
+    @location(3) pattern: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = vec4<f32>(model.color.rgb, model.color.a * transform.opacity);
+    out.clip_position = transform.view_proj * vec4<f32>(model.position, 1.0);
+    out.pattern_position = (model.position.xy - transform.pattern_origin) / transform.resolution;
+    out.pattern = model.pattern;
+
+    return out;
+}
+
+
+// Hatch fragment shader
+
+@fragment
+fn fs_hatch(in: VertexOutput) -> @location(0) vec4<f32> {
+    let direction = in.pattern.xy;
+    let spacing = max(in.pattern.z, 1.0);
+    let half_width = in.pattern.w / 2.0;
+
+    // Distance from the hatch line going through the pattern origin.
+    let distance = dot(in.pattern_position, vec2<f32>(-direction.y, direction.x));
+    let offset = abs(distance - spacing * round(distance / spacing));
+
+    // The edges of the lines are smoothed over one pixel of the screen.
+    let pixel = max(fwidth(distance), 0.0001);
+    let alpha = in.color.a * clamp((half_width - offset) / pixel + 0.5, 0.0, 1.0);
+
+    // Blend states expect colors with premultiplied alpha.
+    return vec4<f32>(in.color.rgb * alpha, alpha);
+}
+
+
+// Image pattern fragment shader
+
+@group(1) @binding(0)
+var t_pattern: texture_2d<f32>;
+
+@fragment
+fn fs_image(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t_pattern));
+
+    // Rows of the image go down, while the y axis of the map goes up.
+    let position = vec2<f32>(in.pattern_position.x, -in.pattern_position.y);
+    let texel = vec2<i32>(floor(position - size * floor(position / size)));
+    let color = textureLoad(t_pattern, clamp(texel, vec2<i32>(0), vec2<i32>(size) - 1), 0) * in.color;
+
+    // Blend states expect colors with premultiplied alpha.
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
@@ -5,6 +5,7 @@ struct ViewUniform {
     view_rotation: mat4x4<f32>,
     inv_screen_size: vec2<f32>,
     resolution: f32,
+    opacity: f32,
 }
 
 @group(0) @binding(0)
@@ -27,6 +28,7 @@ fn vs_main(
 ) -> VertexOutput {
     var out: VertexOutput;
     out.color = vec4<f32>(model.color) / 255.0;
+    out.color[3] = out.color[3] * transform.opacity;
     var point_position = transform.view_proj * vec4<f32>(model.position, 1.0);
     var vertex_delta = vec4<f32>(model.normal * transform.inv_screen_size * point_position[3] * 2.0, 0.0, 0.0);
 
@@ -40,5 +42,6 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    // Blend states expect colors with premultiplied alpha.
+    return vec4<f32>(in.color.rgb * in.color.a, in.color.a);
 }
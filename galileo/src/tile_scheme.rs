@@ -1,9 +1,9 @@
 //! [`TileSchema`] is used by tile layers to calculate [tile indices](TileIndex) needed for a given ['MapView'].
 
-use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect};
+use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint2dFloat, Point2d, Point3d, Rect};
 use galileo_types::geo::Crs;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 
 #[cfg(target_arch = "wasm32")]
 use js_sys::wasm_bindgen::prelude::wasm_bindgen;
@@ -13,6 +13,9 @@ use crate::view::MapView;
 
 const RESOLUTION_TOLERANCE: f64 = 0.01;
 
+/// Number of points on each side of a rectangle used to find its bounding box in another CRS.
+pub(crate) const REPROJECTION_SAMPLES: usize = 16;
+
 /// Direction of the Y index of tiles.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum VerticalDirection {
@@ -35,7 +38,114 @@ pub struct TileIndex {
     pub(crate) display_x: i32,
 }
 
+impl TileIndex {
+    /// Creates a new tile index.
+    pub fn new(x: i32, y: i32, z: u32) -> Self {
+        Self {
+            z,
+            x,
+            y,
+            display_x: x,
+        }
+    }
+}
+
+/// Specifies which tiles outside of the visible area a tile layer loads in advance, so that panning and zooming the
+/// map shows fewer blank areas.
+///
+/// Prefetched tiles are requested after the visible ones, in the following order: tiles of the lower levels of
+/// detail covering the view, tiles of the current level of detail around the view, then tiles of the higher levels
+/// of detail covering the view. No more than `max_tiles` tiles are requested for one view.
+///
+/// The default policy does not prefetch any tiles.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PrefetchPolicy {
+    /// Width of the ring of tiles around the visible area, in tiles.
+    pub ring: u32,
+    /// Number of the lower levels of detail to load.
+    pub parent_levels: u32,
+    /// Number of the higher levels of detail to load.
+    pub child_levels: u32,
+    /// Maximum number of tiles to prefetch for one view.
+    pub max_tiles: usize,
+}
+
+impl PrefetchPolicy {
+    /// Creates a policy that loads a ring of `ring` tiles around the view and `parent_levels` of the lower levels of
+    /// detail, with up to `max_tiles` tiles.
+    pub fn new(ring: u32, parent_levels: u32, max_tiles: usize) -> Self {
+        Self {
+            ring,
+            parent_levels,
+            child_levels: 0,
+            max_tiles,
+        }
+    }
+
+    /// Sets the number of the higher levels of detail to load. Every next level has 4 times more tiles than the
+    /// previous one, so this should be used with a reasonable `max_tiles` budget.
+    pub fn with_child_levels(mut self, child_levels: u32) -> Self {
+        self.child_levels = child_levels;
+        self
+    }
+}
+
+/// Parameters of a single z-level of a [`TileSchema`], corresponding to a `TileMatrix` of a WMTS `TileMatrixSet`.
+///
+/// Tile matrices allow z-levels of a schema to have different origins and tile sizes, and to be limited to a given
+/// number of tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TileMatrix {
+    /// Z-level of the matrix.
+    pub z: u32,
+    /// Resolution of the z-level in units of the schema CRS per pixel.
+    pub resolution: f64,
+    /// Position of the corner of the tile with `X == 0, Y == 0` indices. For schemas with
+    /// [`VerticalDirection::TopToBottom`] it is the top left corner (the `TopLeftCorner` of WMTS matrices), otherwise
+    /// the bottom left one.
+    pub origin: Point2d,
+    /// Width of a tile in pixels.
+    pub tile_width: u32,
+    /// Height of a tile in pixels.
+    pub tile_height: u32,
+    /// Number of tile columns in the matrix.
+    pub matrix_width: u32,
+    /// Number of tile rows in the matrix.
+    pub matrix_height: u32,
+}
+
+impl TileMatrix {
+    /// Size of a pixel in meters used by WMTS to define the scale denominators of tile matrices.
+    pub const WMTS_PIXEL_SIZE: f64 = 0.00028;
+
+    /// Converts the `ScaleDenominator` of a WMTS tile matrix into the resolution. `meters_per_unit` is the size of
+    /// a unit of the matrix CRS in meters, e.g. `1.0` for Web Mercator and `111319.49079327358` for degrees of
+    /// WGS84.
+    pub fn resolution_from_scale_denominator(scale_denominator: f64, meters_per_unit: f64) -> f64 {
+        scale_denominator * Self::WMTS_PIXEL_SIZE / meters_per_unit
+    }
+
+    fn bbox(&self, y_direction: VerticalDirection) -> Rect {
+        let width = self.matrix_width as f64 * self.tile_width as f64 * self.resolution;
+        let height = self.matrix_height as f64 * self.tile_height as f64 * self.resolution;
+        let y_min = match y_direction {
+            VerticalDirection::TopToBottom => self.origin.y() - height,
+            VerticalDirection::BottomToTop => self.origin.y(),
+        };
+
+        Rect::new(
+            self.origin.x(),
+            y_min,
+            self.origin.x() + width,
+            y_min + height,
+        )
+    }
+}
+
 /// Tile schema specifies how tile indices are calculated based on the map position and resolution.
+///
+/// All z-levels of a schema share the same origin and tile size, unless the schema has a [`TileMatrix`] for the
+/// level. Schemas of WMTS tile matrix sets can be created with [`TileSchema::from_tile_matrices`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TileSchema {
     /// Position where all tiles have `X == 0, Y == 0` indices.
@@ -52,6 +162,9 @@ pub struct TileSchema {
     pub y_direction: VerticalDirection,
     /// Crs of the scheme.
     pub crs: Crs,
+    /// Z-levels with their own origin, tile size and number of tiles. Other levels use the parameters of the schema.
+    #[serde(default)]
+    pub matrices: Vec<TileMatrix>,
 }
 
 impl TileSchema {
@@ -66,16 +179,26 @@ impl TileSchema {
         None
     }
 
-    /// Width of a single tile.
+    /// Width of a single tile. Z-levels with a [`TileMatrix`] can have a different tile size, see
+    /// [`TileSchema::tile_size`].
     pub fn tile_width(&self) -> u32 {
         self.tile_width
     }
 
-    /// Height of a single tile.
+    /// Height of a single tile. Z-levels with a [`TileMatrix`] can have a different tile size, see
+    /// [`TileSchema::tile_size`].
     pub fn tile_height(&self) -> u32 {
         self.tile_height
     }
 
+    /// Width and height of the tiles of the z-level in pixels.
+    pub fn tile_size(&self, z: u32) -> (u32, u32) {
+        match self.tile_matrix(z) {
+            Some(matrix) => (matrix.tile_width, matrix.tile_height),
+            None => (self.tile_width, self.tile_height),
+        }
+    }
+
     /// Select a level of detail for the given resolution.
     pub fn select_lod(&self, resolution: f64) -> Option<Lod> {
         if !resolution.is_finite() {
@@ -95,7 +218,41 @@ impl TileSchema {
         Some(*prev_lod)
     }
 
+    /// Returns the z-index of the level of detail with the resolution closest to the given one.
+    ///
+    /// Resolutions are compared in logarithmic scale, so for the levels with resolutions `4.0` and `2.0` the boundary
+    /// between them is `2.83` rather than `3.0`. A resolution exactly on the boundary selects the finer level.
+    /// Resolutions outside the schema range are clamped to the first or the last level.
+    ///
+    /// Returns `None` if the schema has no levels of detail or the resolution is not a finite positive number.
+    pub fn lod_for_resolution(&self, resolution: f64) -> Option<u32> {
+        if !resolution.is_finite() || resolution <= 0.0 {
+            return None;
+        }
+
+        let target = resolution.ln();
+        let mut closest = self.lods.iter().next()?;
+        for lod in self.lods.iter().skip(1) {
+            if (lod.resolution().ln() - target).abs() <= (closest.resolution().ln() - target).abs()
+            {
+                closest = lod;
+            }
+        }
+
+        Some(closest.z_index())
+    }
+
+    /// Snaps the resolution to the closest level of detail of the schema. See [`TileSchema::lod_for_resolution`].
+    pub fn clamp_resolution(&self, resolution: f64) -> Option<f64> {
+        self.lod_resolution(self.lod_for_resolution(resolution)?)
+    }
+
     /// Iterate over tile indices that should be displayed for the given map view.
+    ///
+    /// If the view is not tilted, all tiles are of the level of detail selected for the view resolution. For a tilted
+    /// view, the parts of the map farther from the camera are drawn smaller, so they are covered by tiles of lower
+    /// levels of detail, chosen to be drawn at the screen approximately in their pixel size. In this case tiles are
+    /// returned sorted by their z-level from the lowest to the highest, so the tiles can be drawn in the given order.
     pub fn iter_tiles(&self, view: &MapView) -> Option<impl Iterator<Item = TileIndex>> {
         if *view.crs() != self.crs {
             return None;
@@ -103,42 +260,209 @@ impl TileSchema {
 
         let resolution = view.resolution();
         let bounding_box = view.get_bbox()?;
-        self.iter_tiles_over_bbox(resolution, bounding_box)
+        let tiles: Box<dyn Iterator<Item = TileIndex>> = if view.rotation_x() == 0.0 {
+            Box::new(self.iter_tiles_over_bbox(resolution, bounding_box)?)
+        } else {
+            Box::new(self.select_tilted_tiles(view, bounding_box)?.into_iter())
+        };
+
+        Some(tiles)
     }
 
-    fn iter_tiles_over_bbox(
+    /// Iterate over tile indices that cover the given map view, if the CRS of the view is different from the CRS of
+    /// the schema.
+    ///
+    /// The visible area of the view is projected into the CRS of the schema, and the level of detail is selected
+    /// by the resolution of the view at its center, converted into the schema CRS. If the view has the same CRS as the
+    /// schema, this method returns the same tiles as [`TileSchema::iter_tiles`].
+    ///
+    /// Returns `None` if the view cannot be projected into the schema CRS.
+    pub fn iter_tiles_reprojected(&self, view: &MapView) -> Option<Vec<TileIndex>> {
+        if *view.crs() == self.crs {
+            return Some(self.iter_tiles(view)?.collect());
+        }
+
+        let projection = view.crs().projection_to(&self.crs)?;
+        let Some(view_bbox) = clamp_latitude(view.get_bbox()?, view.crs()) else {
+            return Some(vec![]);
+        };
+        let bounding_box = Rect::from_points(
+            rect_outline(&view_bbox, REPROJECTION_SAMPLES)
+                .iter()
+                .filter_map(|point| projection.project_point(point))
+                .collect::<Vec<_>>()
+                .iter(),
+        )?
+        .intersection(&self.bounds)?;
+
+        let center = view_bbox.center();
+        let step = view.resolution();
+        let projected_center = projection.project_point(&center)?;
+        let resolution = [
+            Point2d::new(center.x() + step, center.y()),
+            Point2d::new(center.x(), center.y() + step),
+        ]
+        .iter()
+        .filter_map(|point| projection.project_point(point))
+        .map(|point| point.distance(&projected_center))
+        .fold(f64::INFINITY, f64::min);
+
+        Some(
+            self.iter_tiles_over_bbox(resolution, bounding_box)?
+                .collect(),
+        )
+    }
+
+    /// Iterate over indices of the tiles of the z-level `z` that intersect the `bounding_box`.
+    ///
+    /// Returns `None` if the schema does not have the given z-level.
+    pub fn iter_tiles_in_bbox(
         &self,
-        resolution: f64,
+        z: u32,
         bounding_box: Rect,
     ) -> Option<impl Iterator<Item = TileIndex>> {
-        let lod = self.select_lod(resolution)?;
+        self.iter_tiles_over_bbox(self.lod_resolution(z)?, bounding_box)
+    }
 
-        let tile_w = lod.resolution() * self.tile_width as f64;
-        let tile_h = lod.resolution() * self.tile_height as f64;
+    /// Returns indices of the tiles that should be loaded in advance for the given view according to the `policy`.
+    ///
+    /// The returned tiles do not include the tiles returned by [`TileSchema::iter_tiles`] for the view. Tiles are
+    /// returned in the order of priority. If the view CRS is different from the schema CRS, no tiles are returned.
+    pub fn prefetch_tiles(&self, view: &MapView, policy: &PrefetchPolicy) -> Vec<TileIndex> {
+        if policy.max_tiles == 0 || *view.crs() != self.crs {
+            return vec![];
+        }
 
-        let x_min = (self.x_adj(bounding_box.x_min()) / tile_w) as i32;
-        let x_min = x_min.max(self.min_x_index(lod.resolution()));
+        let (Some(bounding_box), Some(lod)) = (view.get_bbox(), self.select_lod(view.resolution()))
+        else {
+            return vec![];
+        };
 
-        let x_max_adj = self.x_adj(bounding_box.x_max());
-        let x_add_one = if (x_max_adj % tile_w) < 0.001 { -1 } else { 0 };
+        let mut seen: HashSet<TileIndex> = self.iter_tiles(view).into_iter().flatten().collect();
+        let mut tiles = vec![];
+        let mut add_tiles = |resolution: f64, bbox: Rect, tiles: &mut Vec<TileIndex>| {
+            for index in self
+                .iter_tiles_over_bbox(resolution, bbox)
+                .into_iter()
+                .flatten()
+            {
+                if tiles.len() >= policy.max_tiles {
+                    return;
+                }
+                if seen.insert(index) {
+                    tiles.push(index);
+                }
+            }
+        };
 
-        let x_max = (x_max_adj / tile_w) as i32 + x_add_one;
-        let x_max = x_max.min(self.max_x_index(lod.resolution()));
+        let mut parent = lod;
+        for _ in 0..policy.parent_levels {
+            let Some(next) = self.lod_over(parent.z_index()) else {
+                break;
+            };
+            parent = *next;
+            add_tiles(parent.resolution(), bounding_box, &mut tiles);
+        }
 
-        let (top, bottom) = if self.y_direction == VerticalDirection::TopToBottom {
-            (bounding_box.y_min(), bounding_box.y_max())
-        } else {
-            (bounding_box.y_max(), bounding_box.y_min())
-        };
+        if policy.ring > 0 {
+            let grid = self.grid(lod);
+            let dx = policy.ring as f64 * grid.tile_width;
+            let dy = policy.ring as f64 * grid.tile_height;
+            let ring_bbox = Rect::new(
+                bounding_box.x_min() - dx,
+                bounding_box.y_min() - dy,
+                bounding_box.x_max() + dx,
+                bounding_box.y_max() + dy,
+            );
+            add_tiles(lod.resolution(), ring_bbox, &mut tiles);
+        }
+
+        let mut child = lod;
+        for _ in 0..policy.child_levels {
+            let Some(next) = self.lod_under(child.z_index()) else {
+                break;
+            };
+            child = *next;
+            add_tiles(child.resolution(), bounding_box, &mut tiles);
+        }
 
-        let y_min = (self.y_adj(bottom) / tile_h) as i32;
-        let y_min = y_min.max(self.min_y_index(lod.resolution()));
+        tiles
+    }
+
+    /// Selects tiles for a tilted view by splitting tiles, starting from the lowest level of detail, while they are
+    /// drawn at the screen larger than their size in pixels.
+    fn select_tilted_tiles(&self, view: &MapView, bounding_box: Rect) -> Option<Vec<TileIndex>> {
+        let max_lod = self.select_lod(view.resolution())?;
+        let min_lod = self.lods.last()?;
+        let screen = Rect::new(0.0, 0.0, view.size().width(), view.size().height());
+
+        let mut tiles = vec![];
+        let mut to_check: Vec<_> = self
+            .iter_tiles_over_bbox(min_lod.resolution(), bounding_box)?
+            .collect();
+        while let Some(index) = to_check.pop() {
+            let Some(tile_bbox) = self.tile_bbox(index) else {
+                continue;
+            };
+
+            let corners: Vec<_> = tile_bbox
+                .into_quadrangle()
+                .iter()
+                .map(|corner| view.map_to_screen(&Point3d::new(corner.x(), corner.y(), 0.0)))
+                .collect();
+            let is_too_large = match corners.iter().copied().collect::<Option<Vec<_>>>() {
+                Some(corners) => {
+                    if !Rect::from_points(corners.iter())
+                        .is_some_and(|rect| rect.intersects(&screen))
+                    {
+                        continue;
+                    }
 
-        let y_max_adj = self.y_adj(top);
-        let y_add_one = if (y_max_adj % tile_h) < 0.001 { -1 } else { 0 };
+                    let (tile_width, tile_height) = self.tile_size(index.z);
+                    (0..4).any(|i| {
+                        let edge_length = corners[i].distance(&corners[(i + 1) % 4]);
+                        let tile_size = if i % 2 == 0 { tile_height } else { tile_width };
+                        edge_length > tile_size as f64 * (1.0 + RESOLUTION_TOLERANCE)
+                    })
+                }
+                // The whole tile is behind the camera.
+                None if corners.iter().all(Option::is_none) => continue,
+                // Part of the tile is behind the camera, so the tile is too close to it to be drawn as a whole.
+                None => true,
+            };
 
-        let y_max = (y_max_adj / tile_h) as i32 + y_add_one;
-        let y_max = y_max.min(self.max_y_index(lod.resolution()));
+            let next_lod = self
+                .lod_under(index.z)
+                .filter(|lod| lod.resolution() >= max_lod.resolution());
+            match next_lod {
+                Some(lod) if is_too_large && index.z != max_lod.z_index() => {
+                    if let Some(children_bbox) = tile_bbox
+                        .shrink(lod.resolution())
+                        .intersection(&bounding_box)
+                    {
+                        to_check
+                            .extend(self.iter_tiles_over_bbox(lod.resolution(), children_bbox)?);
+                    }
+                }
+                _ => tiles.push(index),
+            }
+        }
+
+        tiles.sort_by_key(|index| index.z);
+        Some(tiles)
+    }
+
+    fn iter_tiles_over_bbox(
+        &self,
+        resolution: f64,
+        bounding_box: Rect,
+    ) -> Option<impl Iterator<Item = TileIndex>> {
+        let lod = self.select_lod(resolution)?;
+        let grid = self.grid(lod);
+        let (x_range, y_range) = grid.index_ranges(&bounding_box);
+        let (x_limits, y_limits) = grid.limits(&self.bounds);
+        let IndexRange(x_min, x_max) = x_range.intersect(x_limits);
+        let IndexRange(y_min, y_max) = y_range.intersect(y_limits);
 
         Some((x_min..=x_max).flat_map(move |x| {
             (y_min..=y_max).map(move |y| TileIndex {
@@ -175,23 +499,135 @@ impl TileSchema {
         lod_iter.next()
     }
 
-    fn x_adj(&self, x: f64) -> f64 {
-        x - self.origin.x()
+    /// Returns lod one z-level under the given.
+    fn lod_under(&self, z: u32) -> Option<&Lod> {
+        let mut lod_iter = self.lods.iter().rev();
+        for lod in lod_iter.by_ref() {
+            if lod.z_index() == z {
+                break;
+            }
+        }
+
+        lod_iter.next()
     }
 
-    fn y_adj(&self, y: f64) -> f64 {
-        match self.y_direction {
-            VerticalDirection::TopToBottom => self.origin.y() - y,
-            VerticalDirection::BottomToTop => y - self.origin.y(),
+    /// Returns the tile matrix of the z-level, if the schema has one for it.
+    pub fn tile_matrix(&self, z: u32) -> Option<&TileMatrix> {
+        self.matrices.iter().find(|matrix| matrix.z == z)
+    }
+
+    fn grid(&self, lod: Lod) -> TileGrid {
+        let matrix = self.tile_matrix(lod.z_index());
+        let (tile_width, tile_height) = self.tile_size(lod.z_index());
+        TileGrid {
+            origin: matrix.map_or(self.origin, |m| m.origin),
+            tile_width: tile_width as f64 * lod.resolution(),
+            tile_height: tile_height as f64 * lod.resolution(),
+            y_direction: self.y_direction,
+            x_count: matrix.map(|m| m.matrix_width as i32),
+            y_count: matrix.map(|m| m.matrix_height as i32),
         }
     }
 
     /// Standard Web Mercator based tile scheme (used, for example, by OSM and Google maps).
     pub fn web(lods_count: u32) -> Self {
+        Self::web_with_tile_size(lods_count, 256)
+    }
+
+    /// Web Mercator based tile scheme with the Y index of the tiles growing from the bottom of the map, as defined by
+    /// the Tile Map Service (TMS) specification.
+    ///
+    /// A tile `(x, y, z)` of this schema is the tile `(x, 2^z - 1 - y, z)` of the [standard scheme](TileSchema::web).
+    pub fn web_tms(lods_count: u32) -> Self {
+        let schema = Self::web(lods_count);
+        Self {
+            origin: Point2d::new(schema.bounds.x_min(), schema.bounds.y_min()),
+            y_direction: VerticalDirection::BottomToTop,
+            ..schema
+        }
+    }
+
+    /// Creates a schema from the tile matrices of a WMTS `TileMatrixSet`, or any other set of z-levels with
+    /// arbitrary resolutions, origins and tile sizes.
+    ///
+    /// The bounds of the schema cover all the matrices, and the origin and tile size of the schema are taken from the
+    /// matrix with the lowest z-level.
+    ///
+    /// Returns `None` if there are no matrices, or some of them have invalid resolution or zero tile size.
+    ///
+    /// ```
+    /// use galileo::tile_scheme::{TileMatrix, TileSchema, VerticalDirection};
+    /// use galileo_types::cartesian::Point2d;
+    /// use galileo_types::geo::Crs;
+    ///
+    /// // WGS84 matrix set with two tiles at the top level.
+    /// let matrices = (0..5).map(|z| TileMatrix {
+    ///     z,
+    ///     resolution: TileMatrix::resolution_from_scale_denominator(
+    ///         279541132.0143589 / 2f64.powi(z as i32),
+    ///         111319.49079327358,
+    ///     ),
+    ///     origin: Point2d::new(-180.0, 90.0),
+    ///     tile_width: 256,
+    ///     tile_height: 256,
+    ///     matrix_width: 2 << z,
+    ///     matrix_height: 1 << z,
+    /// });
+    /// let schema = TileSchema::from_tile_matrices(Crs::WGS84, VerticalDirection::TopToBottom, matrices).unwrap();
+    /// assert_eq!(schema.lods.len(), 5);
+    /// ```
+    pub fn from_tile_matrices(
+        crs: Crs,
+        y_direction: VerticalDirection,
+        matrices: impl IntoIterator<Item = TileMatrix>,
+    ) -> Option<Self> {
+        let mut matrices: Vec<_> = matrices.into_iter().collect();
+        matrices.sort_by_key(|matrix| matrix.z);
+        if matrices
+            .iter()
+            .any(|matrix| matrix.tile_width == 0 || matrix.tile_height == 0)
+        {
+            return None;
+        }
+
+        let lods = matrices
+            .iter()
+            .map(|matrix| Lod::new(matrix.resolution, matrix.z))
+            .collect::<Option<BTreeSet<_>>>()?;
+        let bounds = matrices
+            .iter()
+            .map(|matrix| matrix.bbox(y_direction))
+            .reduce(|a, b| a.merge(b))?;
+        let first = matrices[0];
+
+        Some(Self {
+            origin: first.origin,
+            bounds,
+            lods,
+            tile_width: first.tile_width,
+            tile_height: first.tile_height,
+            y_direction,
+            crs,
+            matrices,
+        })
+    }
+
+    /// Web Mercator based tile scheme with square tiles of `tile_size` pixels (for example, 512 for high-DPI tiles).
+    ///
+    /// Each tile of a z-level covers the same area as in the [standard scheme](TileSchema::web), so the resolution of
+    /// the levels is scaled by `256 / tile_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tile_size` is zero.
+    pub fn web_with_tile_size(lods_count: u32, tile_size: u32) -> Self {
         const ORIGIN: Point2d = Point2d::new(-20037508.342787, 20037508.342787);
         const TOP_RESOLUTION: f64 = 156543.03392800014;
 
-        let mut lods = vec![Lod::new(TOP_RESOLUTION, 0).expect("invalid const parameters")];
+        assert!(tile_size > 0, "tile size must be positive");
+        let top_resolution = TOP_RESOLUTION * 256.0 / tile_size as f64;
+
+        let mut lods = vec![Lod::new(top_resolution, 0).expect("invalid const parameters")];
         for i in 1..lods_count {
             lods.push(
                 Lod::new(lods[(i - 1) as usize].resolution() / 2.0, i)
@@ -208,77 +644,161 @@ impl TileSchema {
                 20037508.342787,
             ),
             lods: lods.into_iter().collect(),
-            tile_width: 256,
-            tile_height: 256,
+            tile_width: tile_size,
+            tile_height: tile_size,
             y_direction: VerticalDirection::TopToBottom,
             crs: Crs::EPSG3857,
+            matrices: vec![],
         }
     }
 
+    /// Sorts the tiles by the distance of their centers from the center of the `view`, nearest first, so that the
+    /// tiles the user is looking at are loaded before the tiles at the edges of the view. Tiles that cannot be placed
+    /// go last.
+    pub(crate) fn sort_by_distance(&self, tiles: &mut [TileIndex], view: &MapView) {
+        let Some(mut center) = view.get_bbox().map(|bbox| bbox.center()) else {
+            return;
+        };
+        if *view.crs() != self.crs {
+            let Some(projected) = view
+                .crs()
+                .projection_to(&self.crs)
+                .and_then(|projection| projection.project_point(&center))
+            else {
+                return;
+            };
+            center = projected;
+        }
+
+        let distance = |index: &TileIndex| {
+            self.tile_bbox(*index)
+                .map_or(f64::INFINITY, |bbox| bbox.center().distance_sq(&center))
+        };
+        tiles.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+    }
+
     pub(crate) fn tile_bbox(&self, index: TileIndex) -> Option<Rect> {
-        let resolution = self
-            .lods
-            .iter()
-            .find(|lod| lod.z_index() == index.z)?
-            .resolution();
-        let x_min = self.origin.x() + (index.x as f64) * self.tile_width as f64 * resolution;
+        let lod = self.lods.iter().find(|lod| lod.z_index() == index.z)?;
+        Some(self.grid(*lod).tile_bbox(index.x, index.y))
+    }
+}
+
+/// Placement of the tiles of a single z-level in the schema CRS.
+struct TileGrid {
+    origin: Point2d,
+    /// Width of a tile in map units.
+    tile_width: f64,
+    /// Height of a tile in map units.
+    tile_height: f64,
+    y_direction: VerticalDirection,
+    x_count: Option<i32>,
+    y_count: Option<i32>,
+}
+
+/// Inclusive range of tile indices.
+#[derive(Debug, Clone, Copy)]
+struct IndexRange(i32, i32);
+
+impl IndexRange {
+    fn intersect(self, other: IndexRange) -> IndexRange {
+        IndexRange(self.0.max(other.0), self.1.min(other.1))
+    }
+}
+
+impl TileGrid {
+    /// Distance from the origin in tile units along the X and Y index directions.
+    fn tile_position(&self, x: f64, y: f64) -> (f64, f64) {
+        let dy = match self.y_direction {
+            VerticalDirection::TopToBottom => self.origin.y() - y,
+            VerticalDirection::BottomToTop => y - self.origin.y(),
+        };
+        (
+            (x - self.origin.x()) / self.tile_width,
+            dy / self.tile_height,
+        )
+    }
+
+    /// Ranges of the X and Y indices of the tiles intersecting the `rect`. Tiles that only touch the border of the
+    /// rect are not included.
+    fn index_ranges(&self, rect: &Rect) -> (IndexRange, IndexRange) {
+        const TOLERANCE: f64 = 1e-9;
+
+        let (x_1, y_1) = self.tile_position(rect.x_min(), rect.y_min());
+        let (x_2, y_2) = self.tile_position(rect.x_max(), rect.y_max());
+        let range = |a: f64, b: f64| {
+            IndexRange(
+                (a.min(b) + TOLERANCE).floor() as i32,
+                (a.max(b) - TOLERANCE).ceil() as i32 - 1,
+            )
+        };
+
+        (range(x_1, x_2), range(y_1, y_2))
+    }
+
+    /// Ranges of the indices of the tiles that exist in the grid: the tiles inside the schema `bounds` and, if the
+    /// size of the matrix is specified, inside the matrix.
+    fn limits(&self, bounds: &Rect) -> (IndexRange, IndexRange) {
+        let (x_range, y_range) = self.index_ranges(bounds);
+        let matrix_range = |count: Option<i32>| {
+            count.map_or(IndexRange(i32::MIN, i32::MAX), |count| {
+                IndexRange(0, count - 1)
+            })
+        };
+        (
+            x_range.intersect(matrix_range(self.x_count)),
+            y_range.intersect(matrix_range(self.y_count)),
+        )
+    }
+
+    fn tile_bbox(&self, x: i32, y: i32) -> Rect {
+        let x_min = self.origin.x() + x as f64 * self.tile_width;
         let y_min = match self.y_direction {
-            VerticalDirection::TopToBottom => {
-                self.origin.y() - (index.y + 1) as f64 * self.tile_height as f64 * resolution
-            }
-            VerticalDirection::BottomToTop => {
-                self.origin.y() + (index.y as f64) * self.tile_height as f64 * resolution
-            }
+            VerticalDirection::TopToBottom => self.origin.y() - (y + 1) as f64 * self.tile_height,
+            VerticalDirection::BottomToTop => self.origin.y() + y as f64 * self.tile_height,
         };
 
-        Some(Rect::new(
+        Rect::new(
             x_min,
             y_min,
-            x_min + self.tile_width as f64 * resolution,
-            y_min + self.tile_height as f64 * resolution,
-        ))
+            x_min + self.tile_width,
+            y_min + self.tile_height,
+        )
     }
+}
 
-    fn min_x_index(&self, resolution: f64) -> i32 {
-        ((self.bounds.x_min() - self.origin.x()) / resolution / self.tile_width as f64).floor()
-            as i32
-    }
+/// Latitude limit of the Web Mercator projection. Geographic areas are clamped to it before they are projected, as
+/// points closer to the poles are projected far outside of the map.
+pub(crate) const MAX_MERCATOR_LATITUDE: f64 = 85.0511;
 
-    fn max_x_index(&self, resolution: f64) -> i32 {
-        let pix_bound = (self.bounds.x_max() - self.origin.x()) / resolution;
-        let floored = pix_bound.floor();
-        if (pix_bound - floored).abs() < 0.1 {
-            (floored / self.tile_width as f64) as i32 - 1
-        } else {
-            (floored / self.tile_width as f64) as i32
-        }
+/// Clamps the latitude of the `rect` to [`MAX_MERCATOR_LATITUDE`] if the `crs` is geographic. Rects in other CRSs
+/// are returned as is.
+///
+/// Returns `None` if the whole rect lies closer to a pole than the limit.
+pub(crate) fn clamp_latitude(rect: Rect, crs: &Crs) -> Option<Rect> {
+    if !crs.is_geographic() {
+        return Some(rect);
     }
 
-    fn min_y_index(&self, resolution: f64) -> i32 {
-        match self.y_direction {
-            VerticalDirection::TopToBottom => {
-                ((self.bounds.y_min() + self.origin.y()) / resolution / self.tile_height as f64)
-                    .floor() as i32
-            }
-            VerticalDirection::BottomToTop => {
-                ((self.bounds.y_min() - self.origin.y()) / resolution / self.tile_height as f64)
-                    .floor() as i32
-            }
-        }
-    }
+    let y_min = rect.y_min().max(-MAX_MERCATOR_LATITUDE);
+    let y_max = rect.y_max().min(MAX_MERCATOR_LATITUDE);
+    (y_min < y_max).then(|| Rect::new(rect.x_min(), y_min, rect.x_max(), y_max))
+}
 
-    fn max_y_index(&self, resolution: f64) -> i32 {
-        let pix_bound = match self.y_direction {
-            VerticalDirection::TopToBottom => (self.bounds.y_max() + self.origin.y()) / resolution,
-            VerticalDirection::BottomToTop => (self.bounds.y_max() - self.origin.y()) / resolution,
-        };
-        let floored = pix_bound.floor();
-        if (pix_bound - floored).abs() < 0.1 {
-            (floored / self.tile_height as f64) as i32 - 1
-        } else {
-            (floored / self.tile_height as f64) as i32
-        }
+/// Returns points along the edges of the `rect`, `samples` for each edge.
+pub(crate) fn rect_outline(rect: &Rect, samples: usize) -> Vec<Point2d> {
+    let samples = samples.max(1);
+    let mut points = Vec::with_capacity(samples * 4);
+    for i in 0..samples {
+        let t = i as f64 / samples as f64;
+        let x = rect.x_min() + rect.width() * t;
+        let y = rect.y_min() + rect.height() * t;
+        points.push(Point2d::new(x, rect.y_min()));
+        points.push(Point2d::new(rect.x_max(), y));
+        points.push(Point2d::new(rect.x_max() - rect.width() * t, rect.y_max()));
+        points.push(Point2d::new(rect.x_min(), rect.y_max() - rect.height() * t));
     }
+
+    points
 }
 
 #[cfg(test)]
@@ -300,6 +820,7 @@ mod tests {
             tile_height: 256,
             y_direction: VerticalDirection::BottomToTop,
             crs: Crs::EPSG3857,
+            matrices: vec![],
         }
     }
 
@@ -310,6 +831,137 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn iter_tiles_tilted_view_uses_lower_lods_far_from_camera() {
+        let schema = TileSchema::web(18);
+        let resolution = schema.lod_resolution(10).unwrap();
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), resolution)
+            .with_size(Size::new(512.0, 512.0))
+            .with_rotation_x(1.2);
+
+        let tiles: Vec<_> = schema.iter_tiles(&view).unwrap().collect();
+        assert!(tiles.windows(2).all(|pair| pair[0].z <= pair[1].z));
+        assert!(tiles.iter().all(|index| index.z <= 10));
+
+        // Tiles near the bottom of the screen are closest to the camera and have the highest level of detail.
+        let bottom = view.screen_to_map(Point2d::new(256.0, 500.0)).unwrap();
+        let nearest = tiles
+            .iter()
+            .find(|index| schema.tile_bbox(**index).unwrap().contains(&bottom))
+            .unwrap();
+        assert_eq!(nearest.z, 10);
+
+        let top = view.screen_to_map(Point2d::new(256.0, 185.0)).unwrap();
+        let farthest = tiles
+            .iter()
+            .find(|index| schema.tile_bbox(**index).unwrap().contains(&top))
+            .unwrap();
+        assert!(farthest.z < 10);
+    }
+
+    #[test]
+    fn iter_tiles_not_tilted_view_uses_single_lod() {
+        let schema = simple_schema();
+        let view = MapView::new_projected(&Point2d::new(1024.0, 1024.0), 2.0)
+            .with_size(Size::new(512.0, 512.0));
+
+        let tiles: Vec<_> = schema.iter_tiles(&view).unwrap().collect();
+        assert_eq!(tiles.len(), 4);
+        assert!(tiles.iter().all(|index| index.z == 2));
+    }
+
+    #[test]
+    fn iter_tiles_reprojected_selects_lod_in_schema_crs() {
+        let schema = TileSchema {
+            origin: Point2d::new(-180.0, 90.0),
+            bounds: Rect::new(-180.0, -90.0, 180.0, 90.0),
+            lods: (0..10)
+                .map(|z| Lod::new(360.0 / 512.0 / 2f64.powi(z as i32), z).unwrap())
+                .collect(),
+            tile_width: 256,
+            tile_height: 256,
+            y_direction: VerticalDirection::TopToBottom,
+            crs: Crs::WGS84,
+            matrices: vec![],
+        };
+        let web = TileSchema::web(18);
+        let view = MapView::new_projected(&Point2d::new(0.0, 0.0), web.lod_resolution(3).unwrap())
+            .with_size(Size::new(512.0, 512.0));
+
+        assert!(schema.iter_tiles(&view).is_none());
+        let tiles = schema.iter_tiles_reprojected(&view).unwrap();
+        assert!(tiles.iter().all(|index| index.z == 2));
+
+        // The view covers longitudes from -45 to 45 degrees.
+        for corner in [Point2d::new(-44.0, 40.0), Point2d::new(44.0, -40.0)] {
+            assert!(tiles
+                .iter()
+                .any(|index| schema.tile_bbox(*index).unwrap().contains(&corner)));
+        }
+        assert!(!tiles
+            .iter()
+            .any(|index| schema.tile_bbox(*index).unwrap().x_min() >= 45.0));
+
+        assert_eq!(
+            web.iter_tiles_reprojected(&view).unwrap(),
+            web.iter_tiles(&view).unwrap().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn iter_tiles_reprojected_near_pole() {
+        let web = TileSchema::web(18);
+        // The view covers latitudes from 54 to 106 degrees.
+        let view = MapView::new_projected_with_crs(&Point2d::new(0.0, 80.0), 0.1, Crs::WGS84)
+            .with_size(Size::new(512.0, 512.0));
+
+        let tiles = web.iter_tiles_reprojected(&view).unwrap();
+        assert!(tiles.iter().all(|index| index.z == 4));
+        assert!(tiles.iter().any(|index| index.y == 0));
+
+        // The view is entirely beyond the latitude limit of Web Mercator.
+        let view = MapView::new_projected_with_crs(&Point2d::new(0.0, 88.0), 0.001, Crs::WGS84)
+            .with_size(Size::new(512.0, 512.0));
+        assert_eq!(web.iter_tiles_reprojected(&view), Some(vec![]));
+    }
+
+    #[test]
+    fn prefetch_tiles_by_priority() {
+        let schema = simple_schema();
+        let view = MapView::new_projected(&Point2d::new(1024.0, 1024.0), 2.0)
+            .with_size(Size::new(256.0, 256.0));
+        let visible: Vec<_> = schema.iter_tiles(&view).unwrap().collect();
+        assert_eq!(visible.len(), 4);
+
+        assert!(schema
+            .prefetch_tiles(&view, &PrefetchPolicy::default())
+            .is_empty());
+
+        let policy = PrefetchPolicy::new(1, 2, 100).with_child_levels(1);
+        let tiles = schema.prefetch_tiles(&view, &policy);
+        let levels: Vec<_> = tiles.iter().map(|index| index.z).collect();
+        assert_eq!(levels, [[1; 4].as_slice(), &[0], &[2; 12]].concat());
+        assert!(tiles.iter().all(|index| !visible.contains(index)));
+
+        let limited = schema.prefetch_tiles(&view, &PrefetchPolicy::new(1, 2, 3));
+        assert_eq!(limited, tiles[..3]);
+    }
+
+    #[test]
+    fn iter_tiles_in_bbox() {
+        let schema = simple_schema();
+        let tiles: Vec<_> = schema
+            .iter_tiles_in_bbox(1, Rect::new(100.0, 100.0, 1500.0, 900.0))
+            .unwrap()
+            .collect();
+        assert_eq!(tiles.len(), 2);
+        assert!(tiles.iter().all(|index| index.z == 1 && index.y == 0));
+
+        assert!(schema
+            .iter_tiles_in_bbox(5, Rect::new(100.0, 100.0, 1500.0, 900.0))
+            .is_none());
+    }
+
     #[test]
     fn select_lod() {
         let schema = simple_schema();
@@ -324,6 +976,41 @@ mod tests {
         assert_eq!(schema.select_lod(1.0).unwrap().z_index(), 2);
     }
 
+    #[test]
+    fn lod_for_resolution() {
+        let schema = simple_schema();
+        assert_eq!(schema.lod_for_resolution(8.0), Some(0));
+        assert_eq!(schema.lod_for_resolution(6.0), Some(0));
+        assert_eq!(schema.lod_for_resolution(5.0), Some(1));
+        assert_eq!(schema.lod_for_resolution(3.0), Some(1));
+        assert_eq!(schema.lod_for_resolution(2.7), Some(2));
+        assert_eq!(schema.lod_for_resolution(100.0), Some(0));
+        assert_eq!(schema.lod_for_resolution(0.01), Some(2));
+        assert_eq!(schema.lod_for_resolution(0.0), None);
+        assert_eq!(schema.lod_for_resolution(f64::NAN), None);
+    }
+
+    #[test]
+    fn clamp_resolution_web_schema() {
+        let schema = TileSchema::web(18);
+        let top = schema.lod_resolution(0).unwrap();
+        let bottom = schema.lod_resolution(17).unwrap();
+
+        for z in 0..18 {
+            let resolution = schema.lod_resolution(z).unwrap();
+            assert_eq!(schema.lod_for_resolution(resolution), Some(z));
+            assert_eq!(schema.lod_for_resolution(resolution * 1.2), Some(z));
+            assert_eq!(schema.lod_for_resolution(resolution / 1.2), Some(z));
+            assert_eq!(schema.clamp_resolution(resolution * 1.05), Some(resolution));
+        }
+
+        assert_eq!(schema.lod_for_resolution(top * 10.0), Some(0));
+        assert_eq!(schema.clamp_resolution(top * 10.0), Some(top));
+        assert_eq!(schema.lod_for_resolution(bottom / 10.0), Some(17));
+        assert_eq!(schema.clamp_resolution(bottom / 10.0), Some(bottom));
+        assert_eq!(schema.clamp_resolution(-1.0), None);
+    }
+
     #[test]
     fn iter_indices_full_bbox() {
         let schema = simple_schema();
@@ -428,4 +1115,129 @@ mod tests {
         assert_eq!(schema.lod_over(2).unwrap().z_index(), 1);
         assert_eq!(schema.lod_over(3), None);
     }
+
+    #[test]
+    fn web_tms_flips_y_index() {
+        let xyz = TileSchema::web(18);
+        let tms = TileSchema::web_tms(18);
+        let view = MapView::new_projected(
+            &Point2d::new(1_000_000.0, 5_000_000.0),
+            xyz.lod_resolution(6).unwrap(),
+        )
+        .with_size(Size::new(600.0, 400.0));
+
+        let mut xyz_tiles: Vec<_> = xyz.iter_tiles(&view).unwrap().collect();
+        let mut tms_tiles: Vec<_> = tms
+            .iter_tiles(&view)
+            .unwrap()
+            .map(|index| TileIndex::new(index.x, (1 << index.z) - 1 - index.y, index.z))
+            .collect();
+        xyz_tiles.sort_by_key(|index| (index.x, index.y));
+        tms_tiles.sort_by_key(|index| (index.x, index.y));
+        assert_eq!(xyz_tiles, tms_tiles);
+
+        for index in &tms_tiles {
+            let tms_index = TileIndex::new(index.x, (1 << index.z) - 1 - index.y, index.z);
+            let (a, b) = (
+                xyz.tile_bbox(*index).unwrap(),
+                tms.tile_bbox(tms_index).unwrap(),
+            );
+            assert!((a.y_min() - b.y_min()).abs() < 1e-3 && (a.x_min() - b.x_min()).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn tile_matrices() {
+        // Level 1 has non-square tiles and the origin shifted by a tile, level 2 has only 3x2 tiles.
+        let matrix =
+            |z, resolution, origin, tile_width, tile_height, matrix_width, matrix_height| {
+                TileMatrix {
+                    z,
+                    resolution,
+                    origin,
+                    tile_width,
+                    tile_height,
+                    matrix_width,
+                    matrix_height,
+                }
+            };
+        let schema = TileSchema::from_tile_matrices(
+            Crs::EPSG3857,
+            VerticalDirection::TopToBottom,
+            [
+                matrix(2, 2.0, Point2d::new(0.0, 1000.0), 100, 100, 3, 2),
+                matrix(0, 10.0, Point2d::new(0.0, 1000.0), 100, 100, 1, 1),
+                matrix(1, 5.0, Point2d::new(-500.0, 1000.0), 200, 100, 2, 2),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(schema.lods.len(), 3);
+        assert_eq!(schema.bounds, Rect::new(-500.0, 0.0, 1500.0, 1000.0));
+        assert_eq!(schema.tile_size(1), (200, 100));
+        assert_eq!(schema.tile_size(0), (100, 100));
+        assert_eq!(
+            schema.tile_bbox(TileIndex::new(1, 1, 1)),
+            Some(Rect::new(500.0, 0.0, 1500.0, 500.0))
+        );
+
+        let tiles = |z: u32| {
+            let mut tiles: Vec<_> = schema
+                .iter_tiles_in_bbox(z, Rect::new(-1000.0, -1000.0, 2000.0, 2000.0))
+                .unwrap()
+                .map(|index| (index.x, index.y))
+                .collect();
+            tiles.sort();
+            tiles
+        };
+        assert_eq!(tiles(0), [(0, 0)]);
+        assert_eq!(tiles(1), [(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(tiles(2), [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]);
+
+        assert!(
+            TileSchema::from_tile_matrices(Crs::EPSG3857, VerticalDirection::TopToBottom, [])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn top_to_bottom_schema_with_asymmetric_bounds() {
+        let schema = TileSchema {
+            origin: Point2d::new(0.0, 1000.0),
+            bounds: Rect::new(0.0, 500.0, 300.0, 1000.0),
+            lods: [Lod::new(1.0, 0).unwrap()].into(),
+            tile_width: 100,
+            tile_height: 100,
+            y_direction: VerticalDirection::TopToBottom,
+            crs: Crs::EPSG3857,
+            matrices: vec![],
+        };
+
+        let tiles: Vec<_> = schema
+            .iter_tiles_in_bbox(0, Rect::new(-100.0, 0.0, 1000.0, 2000.0))
+            .unwrap()
+            .collect();
+        assert_eq!(tiles.len(), 15);
+        assert!(tiles
+            .iter()
+            .all(|index| (0..3).contains(&index.x) && (0..5).contains(&index.y)));
+    }
+
+    #[test]
+    fn web_with_tile_size() {
+        let schema_256 = TileSchema::web(18);
+        let schema_512 = TileSchema::web_with_tile_size(18, 512);
+
+        assert_eq!(schema_512.tile_width(), 512);
+        assert_eq!(schema_512.tile_height(), 512);
+        assert_eq!(schema_512.lods.len(), 18);
+
+        for z in [0, 1, 5, 10, 17] {
+            let resolution_256 = schema_256.lod_resolution(z).unwrap();
+            let resolution_512 = schema_512.lod_resolution(z).unwrap();
+            assert!((resolution_256 / 2.0 - resolution_512).abs() < resolution_512 * 1e-10);
+        }
+
+        assert_eq!(schema_512.lod_resolution(18), None);
+    }
 }
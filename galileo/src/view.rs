@@ -1,10 +1,11 @@
-use galileo_types::cartesian::{CartesianPoint2d, Point2d, Rect, Size};
+use galileo_types::cartesian::{CartesianPoint2d, CartesianPoint3d, Point2d, Rect, Size};
 use galileo_types::geo::impls::GeoPoint2d;
-use galileo_types::geo::{Crs, GeoPoint};
+use galileo_types::geo::{Crs, GeoExtent, GeoPoint, NewGeoPoint};
 use nalgebra::{
     Matrix4, OMatrix, Perspective3, Point2, Point3, Rotation3, Scale3, Translation3, Vector2,
-    Vector3, U4,
+    Vector3, Vector4, U4,
 };
+use web_time::SystemTime;
 
 /// Map view specifies the area of the map that should be drawn. In other words, it sets the position of "camera" that
 /// looks at the map.
@@ -17,8 +18,9 @@ use nalgebra::{
 ///   displayed in. Note, that currently geographic CRSs are not supported, and a map with such a view will not be
 ///   drawn.
 ///
-/// The view can also specify rotation along *x* (tilt) and *z* (rotation) axis.
-#[derive(Debug, Clone)]
+/// The view can also specify rotation along *x* (tilt) and *z* (rotation) axis, and the time that the time-enabled
+/// layers should display (see [`MapView::time`]).
+#[derive(Debug, Clone, PartialEq)]
 pub struct MapView {
     projected_position: Option<Point3<f64>>,
     resolution: f64,
@@ -26,6 +28,7 @@ pub struct MapView {
     rotation_z: f64,
     size: Size,
     crs: Crs,
+    time: Option<SystemTime>,
 }
 
 impl MapView {
@@ -47,6 +50,7 @@ impl MapView {
             rotation_x: 0.0,
             size: Default::default(),
             crs,
+            time: None,
         }
     }
 
@@ -68,6 +72,7 @@ impl MapView {
             rotation_x: 0.0,
             size: Default::default(),
             crs,
+            time: None,
         }
     }
 
@@ -87,6 +92,11 @@ impl MapView {
         })
     }
 
+    /// Position of the center point of the map in projected coordinates.
+    pub(crate) fn projected_position(&self) -> Option<Point3<f64>> {
+        self.projected_position
+    }
+
     /// Resolution at the center of the map.
     pub fn resolution(&self) -> f64 {
         self.resolution
@@ -101,6 +111,21 @@ impl MapView {
         }
     }
 
+    /// Time displayed by the time-enabled layers of the map, e.g. the time of the weather forecast. Layers that do
+    /// not depend on time ignore it. When the time is not set, time-enabled layers display all their data.
+    pub fn time(&self) -> Option<SystemTime> {
+        self.time
+    }
+
+    /// Creates a new view, same as the current one, but with the given time.
+    pub fn with_time(&self, time: Option<SystemTime>) -> Self {
+        Self {
+            time,
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
     /// Size of the view in pixels.
     pub fn size(&self) -> Size {
         self.size
@@ -115,6 +140,70 @@ impl MapView {
         }
     }
 
+    /// Creates a new view, same as the current one, but centered at the `extent` and with resolution set so that the
+    /// whole `extent` fits into the view. The `extent` is expected to be in the CRS of the view.
+    ///
+    /// If the view has zero size, only the position is changed.
+    pub fn fit_extent(&self, extent: &Rect) -> Self {
+        let center = extent.center();
+        let resolution = if self.size.width() > 0.0 && self.size.height() > 0.0 {
+            (extent.width() / self.size.width()).max(extent.height() / self.size.height())
+        } else {
+            self.resolution
+        };
+
+        Self {
+            projected_position: Some(Point3::new(center.x, center.y, 0.0)),
+            resolution: if resolution > 0.0 {
+                resolution
+            } else {
+                self.resolution
+            },
+            crs: self.crs.clone(),
+            ..*self
+        }
+    }
+
+    /// Creates a new view, same as the current one, but fitting the given geographic `extent` (see
+    /// [`MapView::fit_extent`]).
+    ///
+    /// Extents crossing the antimeridian are shown as a continuous area around it, instead of the area spanning the
+    /// other way around the globe.
+    ///
+    /// If the corners of the extent cannot be projected into the CRS of the view, the view is returned unchanged.
+    pub fn fit_geo_extent(&self, extent: &GeoExtent) -> Self {
+        let Some(projection) = self.crs.get_projection::<GeoPoint2d, Point2d>() else {
+            return self.clone();
+        };
+
+        // Longitudes of the unwrapped rectangle can exceed 180°. The projected extent is then continuous, but might be
+        // outside of the projection bounds, so the position is normalized through the geographic center point.
+        let unwrapped = extent.unwrapped_rect();
+        let (Some(min), Some(max)) = (
+            projection.project(&GeoPoint2d::latlon(unwrapped.y_min(), unwrapped.x_min())),
+            projection.project(&GeoPoint2d::latlon(unwrapped.y_max(), unwrapped.x_max())),
+        ) else {
+            return self.clone();
+        };
+        let projected = Rect::new(min.x, min.y, max.x, max.y);
+
+        let fitted = self.fit_extent(&projected);
+        let Some(center) = projection
+            .unproject(&projected.center())
+            .and_then(|center| {
+                projection.project(&GeoPoint2d::latlon(center.lat(), extent.center().lon()))
+            })
+        else {
+            return fitted;
+        };
+
+        Self {
+            projected_position: Some(Point3::new(center.x, center.y, 0.0)),
+            crs: self.crs.clone(),
+            ..fitted
+        }
+    }
+
     /// Returns bounding rectangle of the view (in projected coordinates).
     pub fn get_bbox(&self) -> Option<Rect> {
         let points = [
@@ -145,6 +234,82 @@ impl MapView {
         }
     }
 
+    /// Width of the whole world in the projected coordinates of the view CRS, if the map in this CRS can be
+    /// repeated horizontally (see [`ViewConstraints::with_world_wrap`]).
+    ///
+    /// Only CRSs with cylindrical projections (like web-mercator), in which the antimeridian is a straight vertical
+    /// line, have a world width.
+    pub fn world_width(&self) -> Option<f64> {
+        world_width(&self.crs)
+    }
+
+    /// Returns the views that draw the copies of the world visible in this view when the world is repeated
+    /// horizontally: the view itself for the main copy, and the view moved by whole world widths for the copies on the
+    /// left and on the right of it. Drawing the map with each of the views gives a seamless map across the
+    /// antimeridian.
+    ///
+    /// If the CRS of the view cannot be repeated ([`MapView::world_width`] is `None`), only the view itself is
+    /// returned. The number of copies is limited, so a view zoomed out very far is not filled completely.
+    pub fn world_copies(&self) -> Vec<MapView> {
+        let (Some(width), Some(bbox)) = (self.world_width(), self.get_bbox()) else {
+            return vec![self.clone()];
+        };
+
+        let copy_index = |x: f64| ((x + width / 2.0) / width).floor() as i64;
+        let first = copy_index(bbox.x_min()).max(-MAX_WORLD_COPIES);
+        let last = copy_index(bbox.x_max()).min(MAX_WORLD_COPIES);
+        if first > last {
+            return vec![self.clone()];
+        }
+
+        (first..=last)
+            .map(|copy| self.translate(Vector2::new(copy as f64 * width, 0.0)))
+            .collect()
+    }
+
+    /// Returns `true` if the view is a copy of the `other` view in another copy of the world, i.e. the views differ
+    /// only in their position, which is shifted by whole world widths. See [`MapView::world_copies`].
+    pub(crate) fn is_world_copy_of(&self, other: &MapView) -> bool {
+        let (Some(width), Some(position), Some(other_position)) = (
+            self.world_width(),
+            self.projected_position,
+            other.projected_position,
+        ) else {
+            return false;
+        };
+
+        let copies = ((other_position.x - position.x) / width).round();
+        let tolerance = self.resolution * 1e-6;
+        copies != 0.0
+            && (position.x + copies * width - other_position.x).abs() <= tolerance
+            && position.y == other_position.y
+            && MapView {
+                projected_position: other.projected_position,
+                crs: self.crs.clone(),
+                ..*self
+            } == *other
+    }
+
+    /// Returns the view moved by whole world widths to be the closest to the `other` view, e.g. to animate the view
+    /// to the `other` one the short way across the antimeridian. The view is returned unchanged if the CRS cannot be
+    /// repeated.
+    pub(crate) fn closest_world_copy(&self, other: &MapView) -> MapView {
+        let (Some(width), Some(position), Some(other_position)) = (
+            self.world_width(),
+            self.projected_position,
+            other.projected_position,
+        ) else {
+            return self.clone();
+        };
+
+        let copies = ((other_position.x - position.x) / width).round();
+        if copies == 0.0 {
+            return self.clone();
+        }
+
+        self.translate(Vector2::new(-copies * width, 0.0))
+    }
+
     fn map_to_screen_center_transform(&self) -> Option<OMatrix<f64, U4, U4>> {
         if self.size.is_zero() {
             return None;
@@ -198,6 +363,9 @@ impl MapView {
     }
 
     /// Rotation angle around *Z* axis in radians.
+    ///
+    /// Positive values rotate the map counterclockwise. So, to show the map heading-up, set the rotation to the
+    /// heading (clockwise angle from the north) of the vehicle.
     pub fn rotation_z(&self) -> f64 {
         self.rotation_z
     }
@@ -266,6 +434,23 @@ impl MapView {
         Some(Point2::new(transformed.x, transformed.y))
     }
 
+    /// Projects the given point in map coordinates into the screen pixel position. This is the inverse of
+    /// [`MapView::screen_to_map`].
+    ///
+    /// Returns `None` if the view has zero size, or if the point is behind the camera.
+    pub fn map_to_screen(&self, point: &impl CartesianPoint3d<Num = f64>) -> Option<Point2d> {
+        let transform = self.map_to_scene_transform()?;
+        let scene = transform * Vector4::new(point.x(), point.y(), point.z(), 1.0);
+        if scene.w <= 0.0 {
+            return None;
+        }
+
+        Some(Point2d::new(
+            (scene.x / scene.w + 1.0) * self.size.half_width(),
+            (1.0 - scene.y / scene.w) * self.size.half_height(),
+        ))
+    }
+
     /// Projects the given screen point into map coordinates at the 0 elevation, and then projects them into
     /// geographic coordinates.
     ///
@@ -320,17 +505,42 @@ impl MapView {
         }
     }
 
-    pub(crate) fn zoom(&self, zoom: f64, base_point: Point2d) -> Self {
-        let base_point = self.screen_to_map(base_point);
-        let resolution = self.resolution * zoom;
+    /// Creates a new view, same as the current one, but moved by the given number of pixels on the screen. Positive
+    /// `dx` moves the map content to the right, and positive `dy` moves it down, as if the map was dragged with the
+    /// mouse.
+    ///
+    /// Unlike [`MapView::translate_by_pixels`], the movement is not limited. If the view has no position or the
+    /// center of the screen cannot be projected to the map, the view is returned unchanged.
+    pub fn translate_pixels(&self, dx: f64, dy: f64) -> Self {
+        let center = Point2d::new(self.size.half_width(), self.size.half_height());
+        let target = Point2d::new(center.x + dx, center.y + dy);
+        match (self.screen_to_map(center), self.screen_to_map(target)) {
+            (Some(from), Some(to)) => self.translate(to - from),
+            _ => self.clone(),
+        }
+    }
+
+    /// Creates a new view with the resolution multiplied by `zoom`, keeping the map point under the `base_point` in
+    /// place on the screen. Zoom values less than 1 zoom the map in, and values greater than 1 zoom it out.
+    ///
+    /// The `base_point` is given in screen pixels relative to the top left corner of the view, so the view size must
+    /// be set with [`MapView::with_size`] for the anchor to work. If the `base_point` cannot be projected to the map,
+    /// the view is zoomed around its center. Non-finite or non-positive `zoom` values leave the view unchanged.
+    pub fn zoom(&self, zoom: f64, base_point: Point2d) -> Self {
+        if !zoom.is_finite() || zoom <= 0.0 {
+            return self.clone();
+        }
 
-        let new_position = base_point.and_then(|base_point| {
-            self.projected_position.map(|position| {
+        let resolution = self.resolution * zoom;
+        let new_position = match (self.screen_to_map(base_point), self.projected_position) {
+            (Some(base_point), Some(position)) => {
                 let position2d = Point2::new(position.x, position.y);
                 let result = base_point.add((position2d - base_point) * zoom);
-                Point3::new(result.x, result.y, position.z)
-            })
-        });
+                Some(Point3::new(result.x, result.y, position.z))
+            }
+            (None, position) => position,
+            (Some(_), None) => None,
+        };
 
         Self {
             projected_position: new_position,
@@ -340,6 +550,22 @@ impl MapView {
         }
     }
 
+    /// Creates a new view rotated around *Z* axis by the `angle` in radians (counterclockwise), keeping the map point
+    /// under the `base_point` in place on the screen.
+    ///
+    /// The `base_point` is given in screen pixels relative to the top left corner of the view. If it cannot be
+    /// projected to the map, the view is rotated around its center.
+    pub fn rotate(&self, angle: f64, base_point: Point2d) -> Self {
+        let rotated = self.with_rotation_z(self.rotation_z + angle);
+        match (
+            self.screen_to_map(base_point),
+            rotated.screen_to_map(base_point),
+        ) {
+            (Some(anchor), Some(moved)) => rotated.translate(moved - anchor),
+            _ => rotated,
+        }
+    }
+
     pub(crate) fn interpolate(&self, target: &MapView, k: f64) -> Self {
         let Some(source_position) = self.projected_position else {
             return self.clone();
@@ -349,19 +575,249 @@ impl MapView {
         };
 
         let projected_position = source_position + (target_position - source_position) * k;
+
+        // Rotate the shorter way around.
+        let mut rotation_z_delta = (target.rotation_z - self.rotation_z) % std::f64::consts::TAU;
+        if rotation_z_delta > std::f64::consts::PI {
+            rotation_z_delta -= std::f64::consts::TAU;
+        } else if rotation_z_delta < -std::f64::consts::PI {
+            rotation_z_delta += std::f64::consts::TAU;
+        }
+
         Self {
             projected_position: Some(projected_position),
             resolution: self.resolution + (target.resolution - self.resolution) * k,
+            rotation_x: self.rotation_x + (target.rotation_x - self.rotation_x) * k,
+            rotation_z: self.rotation_z + rotation_z_delta * k,
             crs: self.crs.clone(),
             ..*self
         }
     }
+
+    /// Interpolates the view along a path that zooms out in the beginning and zooms in in the end, so that moving
+    /// between two far away places looks like a flight.
+    ///
+    /// The path is the optimal zoom-and-pan path from "Smooth and efficient zooming and panning" by J. van Wijk and
+    /// W. Nuij, with the parameter `k` being the share of the path length already passed.
+    pub(crate) fn interpolate_fly(&self, target: &MapView, k: f64) -> Self {
+        let linear = self.interpolate(target, k);
+        let (Some(source_position), Some(target_position)) =
+            (self.projected_position, target.projected_position)
+        else {
+            return linear;
+        };
+
+        // Relation between zooming and panning speed. Value recommended by the paper authors.
+        const RHO: f64 = 1.42;
+        let rho2 = RHO * RHO;
+
+        let screen_size = self.size.width().max(self.size.height()).max(1.0);
+        let w0 = screen_size * self.resolution;
+        let w1 = screen_size * target.resolution;
+        let delta = Vector2::new(
+            target_position.x - source_position.x,
+            target_position.y - source_position.y,
+        );
+        let u1 = delta.norm();
+
+        let b = |w: f64, sign: f64| {
+            (w1 * w1 - w0 * w0 + sign * rho2 * rho2 * u1 * u1) / (2.0 * w * rho2 * u1)
+        };
+        let r = |b: f64| ((b * b + 1.0).sqrt() - b).ln();
+        let r0 = r(b(w0, 1.0));
+        let path_length = (r(b(w1, -1.0)) - r0) / RHO;
+
+        let (scale, passed) = if u1 < 1e-6 * w0.max(w1) || !path_length.is_finite() {
+            // The centers are the same, so the path is just zooming.
+            ((w1 / w0).powf(k), 0.0)
+        } else {
+            let s = path_length * k;
+            let scale = r0.cosh() / (r0 + RHO * s).cosh();
+            let passed = w0 * (r0.cosh() * (r0 + RHO * s).tanh() - r0.sinh()) / rho2 / u1;
+            (scale, passed)
+        };
+
+        let z = linear.projected_position.map_or(source_position.z, |p| p.z);
+        Self {
+            projected_position: Some(Point3::new(
+                source_position.x + delta.x * passed,
+                source_position.y + delta.y * passed,
+                z,
+            )),
+            resolution: self.resolution * scale,
+            ..linear
+        }
+    }
+}
+
+/// The maximum number of world copies drawn on each side of the main one.
+const MAX_WORLD_COPIES: i64 = 8;
+
+/// Width of the world in the projected coordinates of the `crs`, if its projection is cylindrical.
+pub(crate) fn world_width(crs: &Crs) -> Option<f64> {
+    let projection = crs.get_projection::<GeoPoint2d, Point2d>()?;
+    let project = |lat: f64, lon: f64| projection.project(&GeoPoint2d::latlon(lat, lon));
+    let west = project(0.0, -180.0)?;
+    let east = project(0.0, 180.0)?;
+    let north_east = project(60.0, 180.0)?;
+
+    let width = east.x - west.x;
+    let tolerance = width.abs() * 1e-9;
+    // In a cylindrical projection the antimeridian is a vertical line, and both its sides have the same y.
+    let is_cylindrical =
+        (north_east.x - east.x).abs() <= tolerance && (east.y - west.y).abs() <= tolerance;
+
+    (width.is_finite() && width > 0.0 && is_cylindrical).then_some(width)
+}
+
+/// Limits for the [`MapView`] of a [`Map`](crate::Map): the range of resolutions the map can be zoomed to and the
+/// extent of the map that can be shown.
+///
+/// Constraints set with [`Map::set_view_constraints`](crate::Map::set_view_constraints) are applied to every view
+/// change of the map: by user input, by animations and by [`Map::set_view`](crate::Map::set_view).
+///
+/// ```
+/// use galileo::ViewConstraints;
+/// use galileo_types::cartesian::Rect;
+///
+/// let constraints = ViewConstraints::new()
+///     .with_resolution_range(0.5, 1000.0)
+///     .with_max_extent(Rect::new(1_400_000.0, 6_800_000.0, 1_600_000.0, 7_000_000.0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViewConstraints {
+    min_resolution: Option<f64>,
+    max_resolution: Option<f64>,
+    max_extent: Option<Rect>,
+    world_wrap: bool,
+}
+
+impl ViewConstraints {
+    /// Creates constraints that do not limit the view.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum (most detailed) and the maximum resolution of the view.
+    pub fn with_resolution_range(mut self, min_resolution: f64, max_resolution: f64) -> Self {
+        self.min_resolution = Some(min_resolution);
+        self.max_resolution = Some(max_resolution);
+        self
+    }
+
+    /// Sets the minimum (most detailed) resolution of the view.
+    pub fn with_min_resolution(mut self, min_resolution: f64) -> Self {
+        self.min_resolution = Some(min_resolution);
+        self
+    }
+
+    /// Sets the maximum resolution of the view.
+    pub fn with_max_resolution(mut self, max_resolution: f64) -> Self {
+        self.max_resolution = Some(max_resolution);
+        self
+    }
+
+    /// Sets the extent in the view CRS that the view cannot leave when panning.
+    ///
+    /// If the view is larger than the extent, it is centered on the extent. Tilt of the view is not taken into
+    /// account, so a tilted view can show the area beyond the extent near the horizon.
+    pub fn with_max_extent(mut self, max_extent: Rect) -> Self {
+        self.max_extent = Some(max_extent);
+        self
+    }
+
+    /// Enables repeating of the world horizontally, so the map can be panned across the antimeridian without end.
+    ///
+    /// The center of the view is kept inside the main copy of the world, and the renderers draw the copies of the
+    /// world visible on the left and on the right of it (see [`MapView::world_copies`]). The option has no effect for
+    /// the CRSs that cannot be repeated (see [`MapView::world_width`]).
+    pub fn with_world_wrap(mut self, world_wrap: bool) -> Self {
+        self.world_wrap = world_wrap;
+        self
+    }
+
+    /// The minimum resolution of the view, if set.
+    pub fn min_resolution(&self) -> Option<f64> {
+        self.min_resolution
+    }
+
+    /// The maximum resolution of the view, if set.
+    pub fn max_resolution(&self) -> Option<f64> {
+        self.max_resolution
+    }
+
+    /// The extent the view cannot leave, if set.
+    pub fn max_extent(&self) -> Option<Rect> {
+        self.max_extent
+    }
+
+    /// Returns `true` if the world is repeated horizontally.
+    pub fn world_wrap(&self) -> bool {
+        self.world_wrap
+    }
+
+    /// Returns the closest resolution to the given one in the allowed range.
+    pub fn clamp_resolution(&self, resolution: f64) -> f64 {
+        let mut resolution = resolution;
+        if let Some(max_resolution) = self.max_resolution {
+            resolution = resolution.min(max_resolution);
+        }
+        if let Some(min_resolution) = self.min_resolution {
+            resolution = resolution.max(min_resolution);
+        }
+
+        resolution
+    }
+
+    /// Returns the closest view to the given one that satisfies the constraints.
+    pub fn apply(&self, view: &MapView) -> MapView {
+        let resolution = self.clamp_resolution(view.resolution);
+        let mut constrained = view.with_resolution(resolution);
+        if self.world_wrap {
+            if let (Some(width), Some(mut position)) = (view.world_width(), view.projected_position)
+            {
+                let half_width = width / 2.0;
+                if position.x < -half_width || position.x >= half_width {
+                    position.x = (position.x + half_width).rem_euclid(width) - half_width;
+                    constrained.projected_position = Some(position);
+                }
+            }
+        }
+
+        let (Some(extent), Some(position)) = (self.max_extent, constrained.projected_position)
+        else {
+            return constrained;
+        };
+
+        // Half size of the axis-aligned bounding box of the rotated screen in map units.
+        let (sin, cos) = view.rotation_z.sin_cos();
+        let half_width = view.size.half_width() * resolution;
+        let half_height = view.size.half_height() * resolution;
+        let half_extent_x = cos.abs() * half_width + sin.abs() * half_height;
+        let half_extent_y = sin.abs() * half_width + cos.abs() * half_height;
+
+        let clamp = |value: f64, min: f64, max: f64, half_size: f64| {
+            if max - min <= 2.0 * half_size {
+                (min + max) / 2.0
+            } else {
+                value.clamp(min + half_size, max - half_size)
+            }
+        };
+
+        constrained.projected_position = Some(Point3::new(
+            clamp(position.x, extent.x_min(), extent.x_max(), half_extent_x),
+            clamp(position.y, extent.y_min(), extent.y_max(), half_extent_y),
+            position.z,
+        ));
+        constrained
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use approx::assert_abs_diff_eq;
+    use galileo_types::cartesian::Point3d;
 
     fn test_view() -> MapView {
         MapView::new_projected(&Point2d::new(0.0, 0.0), 1.0)
@@ -396,6 +852,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fit_extent() {
+        let view = test_view()
+            .with_size(Size::new(100.0, 50.0))
+            .fit_extent(&Rect::new(100.0, 100.0, 300.0, 400.0));
+
+        assert_eq!(view.resolution(), 6.0);
+        let bbox = view.get_bbox().unwrap();
+        assert_abs_diff_eq!(bbox.center(), Point2d::new(200.0, 250.0), epsilon = 0.0001);
+        assert!(bbox.x_min() <= 100.0 && bbox.x_max() >= 300.0);
+        assert_abs_diff_eq!(bbox.height(), 300.0, epsilon = 0.0001);
+    }
+
     #[test]
     fn screen_to_map_zero_size() {
         let view = test_view().with_size(Size::new(0.0, 0.0));
@@ -465,6 +934,255 @@ mod tests {
         );
     }
 
+    #[test]
+    fn map_to_screen_is_inverse_of_screen_to_map() {
+        let views = [
+            MapView::new_projected(&Point2d::new(-100.0, 30.0), 2.0)
+                .with_size(Size::new(100.0, 80.0)),
+            test_view()
+                .with_rotation(std::f64::consts::PI / 5.0, 1.0)
+                .with_size(Size::new(100.0, 100.0)),
+        ];
+
+        for view in views {
+            for px in [
+                Point2d::new(0.0, 99.0),
+                Point2d::new(50.0, 50.0),
+                Point2d::new(90.0, 70.0),
+            ] {
+                let map = view.screen_to_map(px).unwrap();
+                let screen = view
+                    .map_to_screen(&Point3d::new(map.x(), map.y(), 0.0))
+                    .unwrap();
+                assert_abs_diff_eq!(screen, px, epsilon = 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn map_to_screen_zero_size() {
+        let view = test_view().with_size(Size::new(0.0, 0.0));
+        assert!(view.map_to_screen(&Point3d::new(0.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn rotation_z_turns_heading_up() {
+        // Heading to the east.
+        let view = test_view()
+            .with_size(Size::new(100.0, 100.0))
+            .with_rotation_z(std::f64::consts::FRAC_PI_2);
+        let east = view.map_to_screen(&Point3d::new(10.0, 0.0, 0.0)).unwrap();
+        assert_abs_diff_eq!(east, Point2d::new(50.0, 40.0), epsilon = 0.0001);
+    }
+
+    #[test]
+    fn rotate_keeps_anchor_in_place() {
+        let view = test_view().with_size(Size::new(100.0, 100.0));
+        let anchor = Point2d::new(80.0, 30.0);
+        let map_point = view.screen_to_map(anchor).unwrap();
+
+        let rotated = view.rotate(0.7, anchor);
+        assert_abs_diff_eq!(rotated.rotation_z(), 0.7);
+        assert_abs_diff_eq!(
+            rotated.screen_to_map(anchor).unwrap(),
+            map_point,
+            epsilon = 0.0001
+        );
+    }
+
+    #[test]
+    fn fly_zooms_out_between_far_points() {
+        let from = test_view().with_size(Size::new(100.0, 100.0));
+        let to = MapView::new_projected(&Point2d::new(10000.0, 0.0), 1.0)
+            .with_size(Size::new(100.0, 100.0));
+
+        let middle = from.interpolate_fly(&to, 0.5);
+        assert!(middle.resolution() > 10.0);
+        assert_abs_diff_eq!(middle.projected_position.unwrap().x, 5000.0, epsilon = 1e-6);
+
+        let end = from.interpolate_fly(&to, 1.0);
+        assert_abs_diff_eq!(end.resolution(), 1.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(end.projected_position.unwrap().x, 10000.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn fly_in_place_only_zooms() {
+        let from = test_view().with_size(Size::new(100.0, 100.0));
+        let to = test_view()
+            .with_size(Size::new(100.0, 100.0))
+            .with_resolution(4.0);
+
+        let middle = from.interpolate_fly(&to, 0.5);
+        assert_abs_diff_eq!(middle.resolution(), 2.0, epsilon = 1e-9);
+        assert_eq!(middle.projected_position, from.projected_position);
+    }
+
+    #[test]
+    fn constraints_clamp_resolution() {
+        let constraints = ViewConstraints::new().with_resolution_range(1.0, 10.0);
+        assert_eq!(
+            constraints
+                .apply(&test_view().with_resolution(0.5))
+                .resolution(),
+            1.0
+        );
+        assert_eq!(
+            constraints
+                .apply(&test_view().with_resolution(5.0))
+                .resolution(),
+            5.0
+        );
+        assert_eq!(
+            constraints
+                .apply(&test_view().with_resolution(20.0))
+                .resolution(),
+            10.0
+        );
+    }
+
+    #[test]
+    fn constraints_keep_view_inside_extent() {
+        let constraints =
+            ViewConstraints::new().with_max_extent(Rect::new(0.0, 0.0, 1000.0, 500.0));
+        let view = |x: f64, y: f64| {
+            MapView::new_projected(&Point2d::new(x, y), 1.0).with_size(Size::new(200.0, 100.0))
+        };
+
+        let inside = constraints.apply(&view(500.0, 250.0));
+        assert_eq!(
+            inside.projected_position,
+            view(500.0, 250.0).projected_position
+        );
+
+        let outside = constraints.apply(&view(-300.0, 1000.0));
+        assert_eq!(
+            outside.projected_position,
+            Some(Point3::new(100.0, 450.0, 0.0))
+        );
+
+        // The view is wider than the extent.
+        let zoomed_out = constraints.apply(&view(-300.0, 300.0).with_resolution(10.0));
+        assert_eq!(
+            zoomed_out.projected_position,
+            Some(Point3::new(500.0, 250.0, 0.0))
+        );
+
+        // Rotated by 90 degrees, the view is 100 units wide and 200 units high.
+        let rotated =
+            constraints.apply(&view(0.0, 0.0).with_rotation_z(std::f64::consts::FRAC_PI_2));
+        let position = rotated.projected_position.unwrap();
+        assert_abs_diff_eq!(position.x, 50.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(position.y, 100.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn world_wrap_repeats_mercator_world() {
+        let width = world_width(&Crs::EPSG3857).unwrap();
+        assert_abs_diff_eq!(width, 40_075_016.68, epsilon = 0.01);
+        assert_eq!(world_width(&Crs::WGS84), None);
+
+        // The view shows 1.5 worlds around the antimeridian.
+        let view = MapView::new_projected(&Point2d::new(width / 2.0, 0.0), width / 100.0)
+            .with_size(Size::new(150.0, 100.0));
+        let copies: Vec<f64> = view
+            .world_copies()
+            .iter()
+            .map(|copy| copy.projected_position.unwrap().x)
+            .collect();
+        assert_eq!(copies, vec![width / 2.0, -width / 2.0]);
+
+        let constraints = ViewConstraints::new().with_world_wrap(true);
+        let wrapped = constraints.apply(&view);
+        assert_abs_diff_eq!(
+            wrapped.projected_position.unwrap().x,
+            -width / 2.0,
+            epsilon = 1e-6
+        );
+        assert_eq!(constraints.apply(&wrapped), wrapped);
+
+        let far = view.translate(Vector2::new(width * 3.0 + 10.0, 0.0));
+        let closest = far.closest_world_copy(&view);
+        assert_abs_diff_eq!(
+            closest.projected_position.unwrap().x,
+            width / 2.0 - 10.0,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn interpolate_rotates_shorter_way() {
+        let from = test_view().with_rotation_z(0.1);
+        let to = test_view().with_rotation_z(std::f64::consts::TAU - 0.1);
+        assert_abs_diff_eq!(from.interpolate(&to, 0.5).rotation_z(), 0.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn zoom_keeps_anchor_in_place() {
+        let view =
+            MapView::new(&GeoPoint2d::latlon(52.0, 13.0), 100.0).with_size(Size::new(800.0, 300.0));
+
+        for anchor in [
+            Point2d::new(0.0, 0.0),
+            Point2d::new(700.0, 40.0),
+            Point2d::new(400.0, 150.0),
+            Point2d::new(123.0, 299.0),
+        ] {
+            let before = view.screen_to_map_geo(anchor).unwrap();
+            for zoom in [0.5, 2.0, 0.01] {
+                let zoomed = view.zoom(zoom, anchor);
+                assert_eq!(zoomed.resolution(), 100.0 * zoom);
+
+                let after = zoomed.screen_to_map_geo(anchor).unwrap();
+                assert_abs_diff_eq!(after.lat(), before.lat(), epsilon = 1e-9);
+                assert_abs_diff_eq!(after.lon(), before.lon(), epsilon = 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_around_center() {
+        let view = MapView::new_projected(&Point2d::new(10.0, 20.0), 2.0)
+            .with_size(Size::new(100.0, 50.0));
+        let zoomed = view.zoom(0.5, Point2d::new(50.0, 25.0));
+        assert_abs_diff_eq!(
+            zoomed.screen_to_map(Point2d::new(50.0, 25.0)).unwrap(),
+            Point2d::new(10.0, 20.0),
+            epsilon = 1e-9
+        );
+        assert_eq!(zoomed.resolution(), 1.0);
+
+        assert_eq!(view.zoom(-1.0, Point2d::new(0.0, 0.0)).resolution(), 2.0);
+        assert_eq!(
+            view.zoom(f64::NAN, Point2d::new(0.0, 0.0)).resolution(),
+            2.0
+        );
+    }
+
+    #[test]
+    fn translate_pixels() {
+        let view = MapView::new_projected(&Point2d::new(10.0, 20.0), 2.0)
+            .with_size(Size::new(100.0, 50.0));
+        let anchor = Point2d::new(30.0, 10.0);
+        let map_point = view.screen_to_map(anchor).unwrap();
+
+        let moved = view.translate_pixels(150.0, -40.0);
+        assert_abs_diff_eq!(
+            moved
+                .screen_to_map(Point2d::new(anchor.x + 150.0, anchor.y - 40.0))
+                .unwrap(),
+            map_point,
+            epsilon = 1e-9
+        );
+        assert_eq!(moved.resolution(), view.resolution());
+
+        let back = moved.translate_pixels(-150.0, 40.0);
+        assert_abs_diff_eq!(
+            back.screen_to_map(anchor).unwrap(),
+            map_point,
+            epsilon = 1e-9
+        );
+    }
+
     #[test]
     fn map_to_scene() {
         let view = test_view().with_size(Size::new(100.0, 100.0));
@@ -1,10 +1,11 @@
 //! Types that help using `Galileo` with `winit`.
 
-use crate::control::{MouseButton, RawUserEvent, TouchEvent};
+use crate::control::{Key, MouseButton, RawUserEvent, TouchEvent};
 use crate::messenger::Messenger;
 use galileo_types::cartesian::Point2d;
 use std::sync::Arc;
 use winit::event::{ElementState, MouseScrollDelta, Touch, TouchPhase, WindowEvent};
+use winit::keyboard::NamedKey;
 use winit::window::Window;
 
 /// Converts `winit` events into `Galileo` [`RawUserEvent`]s.
@@ -38,6 +39,13 @@ impl WinitInputHandler {
 
                 Some(RawUserEvent::Scroll(zoom))
             }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let key = (&event.logical_key).into();
+                match event.state {
+                    ElementState::Pressed => Some(RawUserEvent::KeyPressed(key)),
+                    ElementState::Released => Some(RawUserEvent::KeyReleased(key)),
+                }
+            }
             WindowEvent::Touch(touch) => match touch.phase {
                 TouchPhase::Started => {
                     Some(RawUserEvent::TouchStart(self.get_touch_event(touch, scale)))
@@ -74,6 +82,28 @@ impl From<&winit::event::MouseButton> for MouseButton {
     }
 }
 
+impl From<&winit::keyboard::Key> for Key {
+    fn from(value: &winit::keyboard::Key) -> Self {
+        match value {
+            winit::keyboard::Key::Named(NamedKey::ArrowLeft) => Key::ArrowLeft,
+            winit::keyboard::Key::Named(NamedKey::ArrowRight) => Key::ArrowRight,
+            winit::keyboard::Key::Named(NamedKey::ArrowUp) => Key::ArrowUp,
+            winit::keyboard::Key::Named(NamedKey::ArrowDown) => Key::ArrowDown,
+            winit::keyboard::Key::Named(NamedKey::Shift) => Key::Shift,
+            winit::keyboard::Key::Named(NamedKey::Control) => Key::Control,
+            winit::keyboard::Key::Named(NamedKey::Alt) => Key::Alt,
+            winit::keyboard::Key::Character(text) => {
+                let mut chars = text.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Key::Character(c),
+                    _ => Key::Other,
+                }
+            }
+            _ => Key::Other,
+        }
+    }
+}
+
 /// Messenger for a `winit` window.
 #[derive(Debug, Clone)]
 pub struct WinitMessenger {
@@ -1,6 +1,6 @@
 importScripts("./pkg/vector_tiles_example.js");
 
-const {load_tile, init_vt_worker} = wasm_bindgen;
+const {load_tile, init_vt_worker, transfer_list} = wasm_bindgen;
 
 async function init_worker() {
     await wasm_bindgen("./pkg/vector_tiles_example_bg.wasm");
@@ -9,7 +9,7 @@ async function init_worker() {
 
     self.onmessage = async event => {
         let result = await load_tile(event.data);
-        self.postMessage(result, null, [result]);
+        self.postMessage(result, transfer_list(result));
     }
 }
 